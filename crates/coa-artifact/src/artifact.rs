@@ -1,16 +1,22 @@
 //! Artifact type trait and implementations
 //!
 //! Defines the [`ArtifactType`] trait for content-addressed typed artifacts.
-//! This is a sealed trait - only crate-internal types can implement it.
+//! This is a sealed trait - external crates add types via
+//! [`define_artifact_type!`](crate::define_artifact_type).
 
+use crate::delta::StructuralDelta;
 use crate::hash::ContentHash;
+use crate::provenance::ArtifactProvenance;
 use std::fmt::Debug;
 use std::marker::PhantomData;
+use std::sync::Arc;
 
 /// Trait for artifact types
 ///
 /// Implement this for each type of work product (Code, Config, Spec, Binary).
-/// This trait is **sealed** - only types defined within this crate can implement it.
+/// This trait is **sealed** - downstream crates implement it through
+/// [`define_artifact_type!`](crate::define_artifact_type), which is the
+/// stable extension point.
 ///
 /// # Type Safety
 /// - `Content` must be Send + Sync for multi-threaded access
@@ -61,6 +67,16 @@ pub trait ArtifactType: Send + Sync + 'static + Debug + private::Sealed {
     fn validate_content(_content: &Self::Content) -> Result<(), ArtifactError> {
         Ok(())
     }
+
+    /// Approximate in-memory size of content in bytes
+    ///
+    /// Used for memory projections during composition, not for exact
+    /// accounting. Default counts only the inline size; override for content
+    /// that owns heap data (source text, byte buffers).
+    #[inline]
+    fn estimated_size(content: &Self::Content) -> usize {
+        std::mem::size_of_val(content)
+    }
 }
 
 /// Sealed trait - prevents external implementations
@@ -101,10 +117,12 @@ pub enum ArtifactError {
 /// - `hash` is always `T::hash(&content)`
 /// - Immutable after construction
 /// - Cheap to clone (Arc content if needed for large data)
-#[derive(Debug, PartialEq, Eq)]
+/// - Provenance is metadata: it is excluded from the hash and from equality
+#[derive(Debug)]
 pub struct Artifact<T: ArtifactType> {
     hash: ContentHash,
     content: T::Content,
+    provenance: Option<Arc<ArtifactProvenance>>,
     _phantom: PhantomData<T>,
 }
 
@@ -113,11 +131,20 @@ impl<T: ArtifactType> Clone for Artifact<T> {
         Self {
             hash: self.hash,
             content: self.content.clone(),
+            provenance: self.provenance.clone(),
             _phantom: PhantomData,
         }
     }
 }
 
+impl<T: ArtifactType> PartialEq for Artifact<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash && self.content == other.content
+    }
+}
+
+impl<T: ArtifactType> Eq for Artifact<T> where T::Content: Eq {}
+
 impl<T: ArtifactType> Artifact<T> {
     /// Create new artifact (computes hash and validates)
     ///
@@ -132,6 +159,7 @@ impl<T: ArtifactType> Artifact<T> {
         Ok(Self {
             hash,
             content,
+            provenance: None,
             _phantom: PhantomData,
         })
     }
@@ -148,6 +176,7 @@ impl<T: ArtifactType> Artifact<T> {
         Self {
             hash,
             content,
+            provenance: None,
             _phantom: PhantomData,
         }
     }
@@ -166,6 +195,13 @@ impl<T: ArtifactType> Artifact<T> {
         &self.content
     }
 
+    /// Approximate in-memory size in bytes (see [`ArtifactType::estimated_size`])
+    #[inline]
+    #[must_use]
+    pub fn estimated_size(&self) -> usize {
+        T::estimated_size(&self.content)
+    }
+
     /// Clone content out of artifact
     #[inline]
     #[must_use]
@@ -173,6 +209,82 @@ impl<T: ArtifactType> Artifact<T> {
         self.content
     }
 
+    /// Attach provenance record
+    #[inline]
+    #[must_use]
+    pub fn with_provenance(mut self, provenance: ArtifactProvenance) -> Self {
+        self.provenance = Some(Arc::new(provenance));
+        self
+    }
+
+    /// Provenance record, if tracked
+    #[inline]
+    #[must_use]
+    pub fn provenance(&self) -> Option<&ArtifactProvenance> {
+        self.provenance.as_deref()
+    }
+
+    /// Create the artifact that results from applying `delta` to this one
+    ///
+    /// Provenance is taken from the delta (falling back to this artifact's)
+    /// and linked to this artifact's hash as parent. Untracked inputs
+    /// produce an untracked result.
+    ///
+    /// # Errors
+    /// Returns error if content validation fails
+    pub fn successor(
+        &self,
+        content: T::Content,
+        delta: &StructuralDelta<T>,
+    ) -> Result<Self, ArtifactError> {
+        let provenance = delta
+            .provenance()
+            .or_else(|| self.provenance())
+            .map(|p| p.derive(self.hash));
+        Ok(Self {
+            provenance: provenance.map(Arc::new),
+            ..Self::new(content)?
+        })
+    }
+
+    /// Create the artifact that results from composing `deltas` onto this one
+    ///
+    /// Records every delta author as a contributor (see
+    /// [`ArtifactProvenance::compose`]).
+    ///
+    /// # Errors
+    /// Returns error if content validation fails
+    pub fn composed(
+        &self,
+        content: T::Content,
+        deltas: &[StructuralDelta<T>],
+    ) -> Result<Self, ArtifactError> {
+        Ok(Self::new(content)?.composed_from(self, deltas))
+    }
+
+    /// Link an artifact a strategy composed from `base` and `deltas` into
+    /// their provenance chain, as [`composed`](Self::composed) does
+    ///
+    /// Returns `self` unchanged if it is `base` itself (nothing was
+    /// applied) or already records `base` as a parent.
+    #[must_use]
+    pub fn composed_from(mut self, base: &Self, deltas: &[StructuralDelta<T>]) -> Self {
+        let linked = self.hash == base.hash
+            || self
+                .provenance()
+                .is_some_and(|p| p.parents.contains(&base.hash));
+        let tracked =
+            base.provenance.is_some() || deltas.iter().any(|d| d.provenance().is_some());
+        if !linked && tracked {
+            self.provenance = Some(Arc::new(ArtifactProvenance::compose(
+                base.provenance(),
+                base.hash,
+                deltas.iter().filter_map(StructuralDelta::provenance),
+            )));
+        }
+        self
+    }
+
     /// Verify integrity (useful after deserialization)
     ///
     /// Returns true if hash matches content recomputation
@@ -197,8 +309,13 @@ impl<T: ArtifactType> Artifact<T> {
         U: ArtifactType,
         F: FnOnce(T::Content) -> U::Content,
     {
+        let provenance = self.provenance.as_deref().map(|p| p.derive(self.hash));
         let new_content = f(self.content);
-        Artifact::<U>::new(new_content)
+        let artifact = Artifact::<U>::new(new_content)?;
+        Ok(match provenance {
+            Some(p) => artifact.with_provenance(p),
+            None => artifact,
+        })
     }
 
     /// Get type identifier
@@ -318,6 +435,32 @@ mod tests {
         assert_eq!(artifact.hash(), cloned.hash());
     }
 
+    #[test]
+    fn artifact_provenance_follows_deltas() {
+        use crate::delta::DeltaOperation;
+        use crate::path::SymbolPath;
+
+        let content = |data: &str| TestContent {
+            data: data.to_string(),
+        };
+        let base = Artifact::<TestArtifact>::new(content("v1"))
+            .unwrap()
+            .with_provenance(ArtifactProvenance::new().with_task("t1"));
+        assert_eq!(base, Artifact::<TestArtifact>::new(content("v1")).unwrap());
+
+        let delta = StructuralDelta::new(SymbolPath::root(), DeltaOperation::Remove, *base.hash())
+            .with_provenance(ArtifactProvenance::new().with_agent("a1").with_task("t1"));
+        let next = base.successor(content("v2"), &delta).unwrap();
+        let record = next.provenance().unwrap();
+        assert_eq!(record.agent_id.as_deref(), Some("a1"));
+        assert_eq!(record.parents, vec![*base.hash()]);
+
+        let composed = base.composed(content("v3"), &[delta]).unwrap();
+        let record = composed.provenance().unwrap();
+        assert_eq!(record.task_id.as_deref(), Some("t1"));
+        assert!(record.contributors.contains("a1"));
+    }
+
     #[test]
     fn artifact_type_id_static() {
         assert_eq!(Artifact::<TestArtifact>::type_id(), "test");
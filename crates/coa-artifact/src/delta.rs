@@ -6,6 +6,10 @@
 use crate::artifact::{Artifact, ArtifactError, ArtifactType};
 use crate::hash::ContentHash;
 use crate::path::SymbolPath;
+use crate::pin::SymbolPin;
+use crate::provenance::ArtifactProvenance;
+use crate::resolve::{nearest_matches, AddressableContent};
+use crate::span::{NodeSpan, SpannedContent};
 use std::fmt::Debug;
 
 /// Semantic transformation on an artifact
@@ -19,7 +23,7 @@ use std::fmt::Debug;
 /// # Invariants
 /// - `base_hash` must match the artifact being transformed
 /// - `target` must be a valid path within the artifact's content
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, PartialEq)]
 pub struct StructuralDelta<T: ArtifactType> {
     /// Target symbol path within artifact tree
     target: SymbolPath,
@@ -35,6 +39,32 @@ pub struct StructuralDelta<T: ArtifactType> {
 
     /// Optional ordering hint for composition strategies
     order: Option<u32>,
+
+    /// Author of the delta (agent, task, graph node)
+    provenance: Option<ArtifactProvenance>,
+
+    /// Symbol-level base binding (tolerates unrelated artifact edits)
+    pin: Option<SymbolPin>,
+
+    /// Source region of the base the delta rewrites (sub-symbol granularity)
+    span: Option<NodeSpan>,
+}
+
+impl<T: ArtifactType> Clone for StructuralDelta<T> {
+    /// # Panics
+    /// If the operation is a [`DeltaOperation::Transform`]
+    fn clone(&self) -> Self {
+        Self {
+            target: self.target.clone(),
+            operation: self.operation.clone(),
+            base_hash: self.base_hash,
+            description: self.description.clone(),
+            order: self.order,
+            provenance: self.provenance.clone(),
+            pin: self.pin.clone(),
+            span: self.span,
+        }
+    }
 }
 
 impl<T: ArtifactType> StructuralDelta<T> {
@@ -53,6 +83,9 @@ impl<T: ArtifactType> StructuralDelta<T> {
             base_hash,
             description,
             order: None,
+            provenance: None,
+            pin: None,
+            span: None,
         }
     }
 
@@ -72,9 +105,104 @@ impl<T: ArtifactType> StructuralDelta<T> {
             base_hash,
             description,
             order: Some(order),
+            provenance: None,
+            pin: None,
+            span: None,
         }
     }
 
+    /// Reassemble delta from its parts (used when decoding wire formats)
+    #[inline]
+    #[must_use]
+    pub(crate) fn from_parts(
+        target: SymbolPath,
+        operation: DeltaOperation<T>,
+        base_hash: ContentHash,
+        description: String,
+        order: Option<u32>,
+        provenance: Option<ArtifactProvenance>,
+        pin: Option<SymbolPin>,
+    ) -> Self {
+        Self {
+            target,
+            operation,
+            base_hash,
+            description,
+            order,
+            provenance,
+            pin,
+            span: None,
+        }
+    }
+
+    /// Replace the ordering hint, keeping everything else
+    #[inline]
+    #[must_use]
+    pub fn reordered(mut self, order: u32) -> Self {
+        self.order = Some(order);
+        self
+    }
+
+    /// Move the delta onto a new base artifact, keeping everything else
+    ///
+    /// The pin, if any, is re-anchored to the new artifact as well. Callers
+    /// must first check the target is unchanged in the new base, e.g. with
+    /// [`validate_base_pinned`](Self::validate_base_pinned).
+    #[inline]
+    #[must_use]
+    pub fn rebased(mut self, base_hash: ContentHash) -> Self {
+        self.base_hash = base_hash;
+        if let Some(pin) = &mut self.pin {
+            pin.parent_hash = base_hash;
+        }
+        self
+    }
+
+    /// Attach authoring provenance
+    #[inline]
+    #[must_use]
+    pub fn with_provenance(mut self, provenance: ArtifactProvenance) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
+
+    /// Authoring provenance, if tracked
+    #[inline]
+    #[must_use]
+    pub fn provenance(&self) -> Option<&ArtifactProvenance> {
+        self.provenance.as_ref()
+    }
+
+    /// Pin the delta to the target symbol's content
+    #[inline]
+    #[must_use]
+    pub fn with_pin(mut self, pin: SymbolPin) -> Self {
+        self.pin = Some(pin);
+        self
+    }
+
+    /// Symbol-level base binding, if pinned
+    #[inline]
+    #[must_use]
+    pub fn pin(&self) -> Option<&SymbolPin> {
+        self.pin.as_ref()
+    }
+
+    /// Narrow the delta to a source region of its base
+    #[inline]
+    #[must_use]
+    pub fn with_span(mut self, span: NodeSpan) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    /// Source region of the base the delta rewrites, if narrowed
+    #[inline]
+    #[must_use]
+    pub fn span(&self) -> Option<NodeSpan> {
+        self.span
+    }
+
     /// Target path
     #[inline]
     #[must_use]
@@ -110,6 +238,19 @@ impl<T: ArtifactType> StructuralDelta<T> {
         self.order
     }
 
+    /// Approximate in-memory size in bytes, including carried content
+    #[must_use]
+    pub fn estimated_size(&self) -> usize {
+        let content = match &self.operation {
+            DeltaOperation::Add(content) | DeltaOperation::Replace(content) => {
+                T::estimated_size(content)
+            }
+            DeltaOperation::BinaryPatch(patch) => patch.len(),
+            DeltaOperation::Remove | DeltaOperation::Transform(_) => 0,
+        };
+        std::mem::size_of::<Self>() + self.description.len() + content
+    }
+
     /// Verify delta can apply to artifact
     ///
     /// # Errors
@@ -125,6 +266,102 @@ impl<T: ArtifactType> StructuralDelta<T> {
         Ok(())
     }
 
+    /// Verify delta can apply, tolerating unrelated edits for pinned deltas
+    ///
+    /// `current_symbol_hash` is the present hash of the target symbol (as
+    /// recorded by the symbol index). A delta whose base hash matches the
+    /// artifact always passes; otherwise a pinned delta passes if the
+    /// target symbol itself is unchanged.
+    ///
+    /// # Errors
+    /// Returns [`DeltaError::StaleSymbol`] if the pinned symbol changed or
+    /// is unknown, [`DeltaError::BaseMismatch`] for unpinned deltas
+    pub fn validate_base_pinned(
+        &self,
+        artifact: &Artifact<T>,
+        current_symbol_hash: Option<ContentHash>,
+    ) -> Result<(), DeltaError> {
+        let Some(pin) = &self.pin else {
+            return self.validate_base(artifact);
+        };
+        if self.base_hash == *artifact.hash() || current_symbol_hash == Some(pin.symbol_hash) {
+            return Ok(());
+        }
+        Err(DeltaError::StaleSymbol {
+            target: self.target.clone(),
+            expected: pin.symbol_hash,
+            actual: current_symbol_hash,
+        })
+    }
+
+    /// Verify delta target against the artifact's structure
+    ///
+    /// - `Add`: target must not exist; its parent must exist
+    /// - `Remove`/`Replace`/`Transform`: target must exist
+    ///
+    /// # Errors
+    /// Returns [`DeltaError::TargetNotFound`] (with nearest-match
+    /// suggestions) or [`DeltaError::TargetAlreadyExists`]
+    pub fn validate_target(&self, artifact: &Artifact<T>) -> Result<(), DeltaError>
+    where
+        T::Content: AddressableContent,
+    {
+        let content = artifact.content();
+        let missing = |path: &SymbolPath| DeltaError::TargetNotFound {
+            target: path.clone(),
+            suggestions: nearest_matches(path, &content.symbol_paths()),
+        };
+
+        match self.operation {
+            DeltaOperation::Add(_) => {
+                if content.resolves(&self.target) {
+                    return Err(DeltaError::TargetAlreadyExists(self.target.clone()));
+                }
+                if let Some(parent) = self.target.parent() {
+                    if !content.resolves(&parent) {
+                        return Err(missing(&parent));
+                    }
+                }
+                Ok(())
+            }
+            DeltaOperation::Remove
+            | DeltaOperation::Replace(_)
+            | DeltaOperation::Transform(_)
+            | DeltaOperation::BinaryPatch(_) => {
+                if content.resolves(&self.target) {
+                    Ok(())
+                } else {
+                    Err(missing(&self.target))
+                }
+            }
+        }
+    }
+
+    /// Verify the delta's span lies within its target's span in the base
+    ///
+    /// Deltas without a span rewrite the whole target and always pass.
+    ///
+    /// # Errors
+    /// Returns [`DeltaError::SpanOutOfTarget`] if the span escapes the
+    /// target or the target has no known span
+    pub fn validate_span(&self, artifact: &Artifact<T>) -> Result<(), DeltaError>
+    where
+        T::Content: SpannedContent,
+    {
+        let Some(span) = self.span else {
+            return Ok(());
+        };
+        let target_span = artifact.content().span_of(&self.target);
+        if target_span.is_some_and(|outer| outer.contains(&span)) {
+            Ok(())
+        } else {
+            Err(DeltaError::SpanOutOfTarget {
+                target: self.target.clone(),
+                span,
+            })
+        }
+    }
+
     /// Map to different artifact type
     ///
     /// # Type Parameters
@@ -143,6 +380,9 @@ impl<T: ArtifactType> StructuralDelta<T> {
             base_hash: self.base_hash,
             description: self.description,
             order: self.order,
+            provenance: self.provenance,
+            pin: self.pin,
+            span: self.span,
         }
     }
 }
@@ -169,6 +409,12 @@ pub enum DeltaOperation<T: ArtifactType> {
     ///
     /// Uses a transformation trait object for custom logic.
     Transform(Box<dyn Transformation<T>>),
+
+    /// Apply encoded binary patch to element at target path
+    ///
+    /// Produced by [`BinaryPatch::encode`](crate::types::binary::BinaryPatch::encode);
+    /// only binary artifacts can apply it.
+    BinaryPatch(Vec<u8>),
 }
 
 impl<T: ArtifactType> Clone for DeltaOperation<T> {
@@ -178,6 +424,7 @@ impl<T: ArtifactType> Clone for DeltaOperation<T> {
             Self::Remove => Self::Remove,
             Self::Replace(content) => Self::Replace(content.clone()),
             Self::Transform(_) => panic!("Cannot clone Transform operation"),
+            Self::BinaryPatch(patch) => Self::BinaryPatch(patch.clone()),
         }
     }
 }
@@ -188,6 +435,7 @@ impl<T: ArtifactType> PartialEq for DeltaOperation<T> {
             (Self::Add(a), Self::Add(b)) => a == b,
             (Self::Remove, Self::Remove) => true,
             (Self::Replace(a), Self::Replace(b)) => a == b,
+            (Self::BinaryPatch(a), Self::BinaryPatch(b)) => a == b,
             _ => false,
         }
     }
@@ -197,18 +445,22 @@ impl<T: ArtifactType> DeltaOperation<T> {
     /// Check if operation is commutative
     ///
     /// Add/Remove are generally commutative when targeting different paths.
-    /// Transform may or may not be commutative depending on implementation.
+    /// Transform defers to [`Transformation::is_commutative`].
     #[inline]
     #[must_use]
     pub fn is_commutative(&self) -> bool {
-        matches!(self, Self::Add(_) | Self::Remove)
+        match self {
+            Self::Add(_) | Self::Remove => true,
+            Self::Replace(_) | Self::BinaryPatch(_) => false,
+            Self::Transform(t) => t.is_commutative(),
+        }
     }
 
     /// Check if operation reads existing state
     #[inline]
     #[must_use]
     pub fn reads_state(&self) -> bool {
-        matches!(self, Self::Replace(_) | Self::Transform(_))
+        matches!(self, Self::Replace(_) | Self::Transform(_) | Self::BinaryPatch(_))
     }
 
     /// Check if operation writes state
@@ -232,6 +484,26 @@ pub trait Transformation<T: ArtifactType>: Send + Sync + Debug {
     /// Describe the transformation
     fn describe(&self) -> String;
 
+    /// Check if transformation commutes with other commutative operations
+    /// on different targets (e.g. adding a dependency)
+    ///
+    /// Default is `false`; semantic operations opt in.
+    #[inline]
+    #[must_use]
+    fn is_commutative(&self) -> bool {
+        false
+    }
+
+    /// Check if the symbol at the delta target no longer exists afterwards
+    /// (e.g. a rename of the definition)
+    ///
+    /// Such transforms are treated like `Remove` by reference checks.
+    #[inline]
+    #[must_use]
+    fn removes_target(&self) -> bool {
+        false
+    }
+
     /// Check if transformation is reversible
     #[inline]
     #[must_use]
@@ -257,9 +529,24 @@ pub enum DeltaError {
         actual: ContentHash,
     },
 
+    /// Pinned target symbol changed since the delta was authored
+    #[error("pinned symbol {target} is stale: expected {expected}, got {}", describe_hash(actual.as_ref()))]
+    StaleSymbol {
+        target: SymbolPath,
+        expected: ContentHash,
+        actual: Option<ContentHash>,
+    },
+
+    /// Delta span is not inside its target's span
+    #[error("span {span} lies outside target {target}")]
+    SpanOutOfTarget { target: SymbolPath, span: NodeSpan },
+
     /// Target not found
-    #[error("target not found: {0}")]
-    TargetNotFound(SymbolPath),
+    #[error("target not found: {target}{}", did_you_mean(suggestions))]
+    TargetNotFound {
+        target: SymbolPath,
+        suggestions: Vec<SymbolPath>,
+    },
 
     /// Target already exists
     #[error("target already exists: {0}")]
@@ -281,6 +568,20 @@ pub enum DeltaError {
     Artifact(#[from] ArtifactError),
 }
 
+/// Render a possibly unknown hash
+fn describe_hash(hash: Option<&ContentHash>) -> String {
+    hash.map_or_else(|| "unknown".to_string(), ToString::to_string)
+}
+
+/// Render " (did you mean: a, b?)" for non-empty suggestions
+fn did_you_mean(suggestions: &[SymbolPath]) -> String {
+    if suggestions.is_empty() {
+        return String::new();
+    }
+    let list: Vec<String> = suggestions.iter().map(ToString::to_string).collect();
+    format!(" (did you mean: {}?)", list.join(", "))
+}
+
 /// Errors during transformation
 #[derive(Debug, thiserror::Error)]
 pub enum TransformError {
@@ -308,6 +609,9 @@ pub struct DeltaBuilder<T: ArtifactType> {
     operation: Option<DeltaOperation<T>>,
     base_hash: Option<ContentHash>,
     order: Option<u32>,
+    provenance: Option<ArtifactProvenance>,
+    pin: Option<SymbolPin>,
+    span: Option<NodeSpan>,
 }
 
 impl<T: ArtifactType> DeltaBuilder<T> {
@@ -320,6 +624,9 @@ impl<T: ArtifactType> DeltaBuilder<T> {
             operation: None,
             base_hash: None,
             order: None,
+            provenance: None,
+            pin: None,
+            span: None,
         }
     }
 
@@ -362,6 +669,30 @@ impl<T: ArtifactType> DeltaBuilder<T> {
         self
     }
 
+    /// Set authoring provenance
+    #[inline]
+    #[must_use]
+    pub fn provenance(mut self, provenance: ArtifactProvenance) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
+
+    /// Set symbol-level pin
+    #[inline]
+    #[must_use]
+    pub fn pin(mut self, pin: SymbolPin) -> Self {
+        self.pin = Some(pin);
+        self
+    }
+
+    /// Set rewritten source span
+    #[inline]
+    #[must_use]
+    pub fn span(mut self, span: NodeSpan) -> Self {
+        self.span = Some(span);
+        self
+    }
+
     /// Build delta
     ///
     /// # Errors
@@ -390,6 +721,9 @@ impl<T: ArtifactType> DeltaBuilder<T> {
 
         let mut delta = StructuralDelta::new(target, operation, base_hash);
         delta.order = self.order;
+        delta.provenance = self.provenance;
+        delta.pin = self.pin;
+        delta.span = self.span;
         Ok(delta)
     }
 }
@@ -487,6 +821,85 @@ mod tests {
         assert!(matches!(result, Err(DeltaError::BaseMismatch { .. })));
     }
 
+    #[test]
+    fn delta_rebased_moves_base_and_pin() {
+        let artifact = Artifact::<TestArtifact>::new(TestContent {
+            data: "test".to_string(),
+        })
+        .unwrap();
+        let old = ContentHash::compute(b"old");
+        let delta = StructuralDelta::<TestArtifact>::new(
+            SymbolPath::from_str("test").unwrap(),
+            DeltaOperation::Remove,
+            old,
+        )
+        .with_pin(SymbolPin::new(old, ContentHash::compute(b"symbol")));
+
+        let rebased = delta.rebased(*artifact.hash());
+        assert!(rebased.validate_base(&artifact).is_ok());
+        assert_eq!(rebased.pin().unwrap().parent_hash, *artifact.hash());
+    }
+
+    impl AddressableContent for TestContent {
+        fn symbol_paths(&self) -> Vec<SymbolPath> {
+            self.data
+                .split(',')
+                .filter_map(|p| SymbolPath::from_str(p).ok())
+                .collect()
+        }
+    }
+
+    fn target_artifact() -> Artifact<TestArtifact> {
+        Artifact::new(TestContent {
+            data: "server,server.port,server.host".to_string(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn delta_validate_target_replace_missing_suggests() {
+        let artifact = target_artifact();
+        let delta = StructuralDelta::<TestArtifact>::new(
+            SymbolPath::from_str("server.prot").unwrap(),
+            DeltaOperation::Remove,
+            *artifact.hash(),
+        );
+
+        let err = delta.validate_target(&artifact).unwrap_err();
+        match &err {
+            DeltaError::TargetNotFound { target, suggestions } => {
+                assert_eq!(target.to_string(), "server.prot");
+                assert_eq!(suggestions[0].to_string(), "server.port");
+            }
+            other => panic!("unexpected error: {other}"),
+        }
+        assert!(err.to_string().contains("did you mean: server.port"));
+    }
+
+    #[test]
+    fn delta_validate_target_add() {
+        let artifact = target_artifact();
+        let add = |path: &str| {
+            StructuralDelta::<TestArtifact>::new(
+                SymbolPath::from_str(path).unwrap(),
+                DeltaOperation::Add(TestContent {
+                    data: String::new(),
+                }),
+                *artifact.hash(),
+            )
+        };
+
+        assert!(add("server.timeout").validate_target(&artifact).is_ok());
+        assert!(matches!(
+            add("server.port").validate_target(&artifact),
+            Err(DeltaError::TargetAlreadyExists(_))
+        ));
+        assert!(matches!(
+            add("client.port").validate_target(&artifact),
+            Err(DeltaError::TargetNotFound { .. })
+        ));
+    }
+
     #[test]
     fn delta_operation_is_commutative() {
         let content = TestContent {
@@ -0,0 +1,660 @@
+//! Type-erased wire format for structural deltas
+//!
+//! Provides [`DeltaEnvelope`] so that [`StructuralDelta`]s can cross process
+//! boundaries (agent workers, composition journal) without the receiver
+//! knowing the concrete artifact type up front.
+//!
+//! Content is encoded as JSON bytes; the artifact `TYPE_ID` travels alongside
+//! and is checked again when the envelope is opened.
+//!
+//! A producing agent may [`sign`](DeltaEnvelope::sign) its envelopes with its
+//! session key. A [`DeltaVerifier`] holding the agents' public keys opens
+//! them, checks the signature and stamps the delta's provenance with the
+//! verified [`DeltaSigner`].
+
+use crate::artifact::ArtifactType;
+use crate::delta::{DeltaOperation, StructuralDelta};
+use crate::hash::ContentHash;
+use crate::path::SymbolPath;
+use crate::pin::SymbolPin;
+use crate::provenance::{ArtifactProvenance, DeltaSigner};
+use crate::span::NodeSpan;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Operation kind carried by an envelope
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnvelopeOperation {
+    /// [`DeltaOperation::Add`]
+    Add,
+    /// [`DeltaOperation::Remove`]
+    Remove,
+    /// [`DeltaOperation::Replace`]
+    Replace,
+    /// [`DeltaOperation::BinaryPatch`]
+    BinaryPatch,
+}
+
+/// Type-erased, serializable form of a [`StructuralDelta`]
+///
+/// # Invariants
+/// - `content` is `Some` for `Add`/`Replace`/`BinaryPatch`, `None` for `Remove`
+/// - `BinaryPatch` content is the encoded patch itself, not JSON
+/// - `type_id` matches the `TYPE_ID` of the originating artifact type
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeltaEnvelope {
+    type_id: String,
+    target: SymbolPath,
+    operation: EnvelopeOperation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content: Option<Vec<u8>>,
+    base_hash: ContentHash,
+    description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    order: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    provenance: Option<ArtifactProvenance>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pin: Option<SymbolPin>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    span: Option<NodeSpan>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    signature: Option<DeltaSignature>,
+}
+
+/// Producing agent's signature over a [`DeltaEnvelope`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeltaSignature {
+    /// Signing agent
+    pub signer: String,
+    /// Key the agent signed with
+    pub public_key: VerifyingKey,
+    /// Signature over the envelope's [`signed_bytes`](DeltaEnvelope::signed_bytes)
+    pub signature: Signature,
+}
+
+impl DeltaEnvelope {
+    /// Seal a typed delta into an envelope
+    ///
+    /// # Errors
+    /// Returns error if the operation is a `Transform` (not serializable)
+    /// or if content encoding fails
+    pub fn seal<T>(delta: &StructuralDelta<T>) -> Result<Self, EnvelopeError>
+    where
+        T: ArtifactType,
+        T::Content: Serialize,
+    {
+        let (operation, content) = match delta.operation() {
+            DeltaOperation::Add(content) => {
+                (EnvelopeOperation::Add, Some(serde_json::to_vec(content)?))
+            }
+            DeltaOperation::Remove => (EnvelopeOperation::Remove, None),
+            DeltaOperation::Replace(content) => (
+                EnvelopeOperation::Replace,
+                Some(serde_json::to_vec(content)?),
+            ),
+            DeltaOperation::BinaryPatch(patch) => {
+                (EnvelopeOperation::BinaryPatch, Some(patch.clone()))
+            }
+            DeltaOperation::Transform(t) => {
+                return Err(EnvelopeError::UnsupportedOperation(t.describe()));
+            }
+        };
+
+        Ok(Self {
+            type_id: T::TYPE_ID.to_string(),
+            target: delta.target().clone(),
+            operation,
+            content,
+            base_hash: *delta.base_hash(),
+            description: delta.description().to_string(),
+            order: delta.order(),
+            provenance: delta.provenance().cloned(),
+            pin: delta.pin().cloned(),
+            span: delta.span(),
+            signature: None,
+        })
+    }
+
+    /// Sign the envelope as `signer`, replacing any earlier signature
+    #[must_use]
+    pub fn sign(mut self, signer: impl Into<String>, key: &SigningKey) -> Self {
+        self.signature = None;
+        let signature = key.sign(&self.signed_bytes());
+        self.signature = Some(DeltaSignature {
+            signer: signer.into(),
+            public_key: key.verifying_key(),
+            signature,
+        });
+        self
+    }
+
+    /// Bytes a signature covers: the envelope's JSON without its signature
+    #[must_use]
+    pub fn signed_bytes(&self) -> Vec<u8> {
+        let unsigned = Self {
+            signature: None,
+            ..self.clone()
+        };
+        // Plain fields only; serialization cannot fail
+        serde_json::to_vec(&unsigned).unwrap_or_default()
+    }
+
+    /// Digest of the signed bytes, identifying the delta in audit records
+    #[must_use]
+    pub fn digest(&self) -> ContentHash {
+        ContentHash::compute(&self.signed_bytes())
+    }
+
+    /// Producing agent's signature, if signed
+    #[inline]
+    #[must_use]
+    pub fn signature(&self) -> Option<&DeltaSignature> {
+        self.signature.as_ref()
+    }
+
+    /// Open envelope as a typed delta
+    ///
+    /// # Errors
+    /// Returns error if `T::TYPE_ID` does not match, content is missing,
+    /// or content decoding fails
+    pub fn open<T>(&self) -> Result<StructuralDelta<T>, EnvelopeError>
+    where
+        T: ArtifactType,
+        T::Content: DeserializeOwned,
+    {
+        if self.type_id != T::TYPE_ID {
+            return Err(EnvelopeError::TypeMismatch {
+                expected: T::TYPE_ID.to_string(),
+                actual: self.type_id.clone(),
+            });
+        }
+
+        let operation = match self.operation {
+            EnvelopeOperation::Add => DeltaOperation::Add(self.decode_content::<T>()?),
+            EnvelopeOperation::Remove => DeltaOperation::Remove,
+            EnvelopeOperation::Replace => DeltaOperation::Replace(self.decode_content::<T>()?),
+            EnvelopeOperation::BinaryPatch => DeltaOperation::BinaryPatch(
+                self.content
+                    .clone()
+                    .ok_or(EnvelopeError::MissingContent(self.operation))?,
+            ),
+        };
+
+        // Signers are only ever stamped by a verifier, never taken on trust
+        let provenance = self.provenance.clone().map(|mut provenance| {
+            provenance.signers.clear();
+            provenance
+        });
+        let delta = StructuralDelta::from_parts(
+            self.target.clone(),
+            operation,
+            self.base_hash,
+            self.description.clone(),
+            self.order,
+            provenance,
+            self.pin.clone(),
+        );
+        Ok(match self.span {
+            Some(span) => delta.with_span(span),
+            None => delta,
+        })
+    }
+
+    fn decode_content<T: ArtifactType>(&self) -> Result<T::Content, EnvelopeError>
+    where
+        T::Content: DeserializeOwned,
+    {
+        let bytes = self
+            .content
+            .as_deref()
+            .ok_or(EnvelopeError::MissingContent(self.operation))?;
+        Ok(serde_json::from_slice(bytes)?)
+    }
+
+    /// Detach the encoded content (for out-of-line storage)
+    pub(crate) fn take_content(&mut self) -> Option<Vec<u8>> {
+        self.content.take()
+    }
+
+    /// Reattach content detached by [`take_content`](Self::take_content)
+    pub(crate) fn set_content(&mut self, content: Vec<u8>) {
+        self.content = Some(content);
+    }
+
+    /// Artifact type identifier of the enclosed delta
+    #[inline]
+    #[must_use]
+    pub fn type_id(&self) -> &str {
+        &self.type_id
+    }
+
+    /// Target path
+    #[inline]
+    #[must_use]
+    pub fn target(&self) -> &SymbolPath {
+        &self.target
+    }
+
+    /// Operation kind
+    #[inline]
+    #[must_use]
+    pub fn operation(&self) -> EnvelopeOperation {
+        self.operation
+    }
+
+    /// Encoded content bytes (if any)
+    #[inline]
+    #[must_use]
+    pub fn content_bytes(&self) -> Option<&[u8]> {
+        self.content.as_deref()
+    }
+
+    /// Base hash (for optimistic concurrency)
+    #[inline]
+    #[must_use]
+    pub fn base_hash(&self) -> &ContentHash {
+        &self.base_hash
+    }
+
+    /// Description
+    #[inline]
+    #[must_use]
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Ordering hint
+    #[inline]
+    #[must_use]
+    pub fn order(&self) -> Option<u32> {
+        self.order
+    }
+
+    /// Authoring provenance
+    #[inline]
+    #[must_use]
+    pub fn provenance(&self) -> Option<&ArtifactProvenance> {
+        self.provenance.as_ref()
+    }
+
+    /// Symbol-level pin
+    #[inline]
+    #[must_use]
+    pub fn pin(&self) -> Option<&SymbolPin> {
+        self.pin.as_ref()
+    }
+
+    /// Rewritten source span
+    #[inline]
+    #[must_use]
+    pub fn span(&self) -> Option<NodeSpan> {
+        self.span
+    }
+}
+
+/// Opens envelopes, checking producer signatures against known agent keys
+#[derive(Debug, Clone, Default)]
+pub struct DeltaVerifier {
+    keys: HashMap<String, VerifyingKey>,
+    require_signatures: bool,
+}
+
+impl DeltaVerifier {
+    /// Verifier that knows no agents and accepts unsigned envelopes
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Trust `key` for envelopes signed by `agent_id`
+    #[must_use]
+    pub fn with_key(mut self, agent_id: impl Into<String>, key: VerifyingKey) -> Self {
+        self.register(agent_id, key);
+        self
+    }
+
+    /// Reject unsigned envelopes
+    #[inline]
+    #[must_use]
+    pub fn requiring_signatures(mut self) -> Self {
+        self.require_signatures = true;
+        self
+    }
+
+    /// Trust `key` for envelopes signed by `agent_id`, replacing its
+    /// previous key
+    pub fn register(&mut self, agent_id: impl Into<String>, key: VerifyingKey) {
+        self.keys.insert(agent_id.into(), key);
+    }
+
+    /// Key trusted for `agent_id`
+    #[inline]
+    #[must_use]
+    pub fn key(&self, agent_id: &str) -> Option<&VerifyingKey> {
+        self.keys.get(agent_id)
+    }
+
+    /// Check an envelope's signature, returning the verified signer
+    ///
+    /// Unsigned envelopes yield `None` unless signatures are required.
+    ///
+    /// # Errors
+    /// - `EnvelopeError::Unsigned` if signatures are required and missing
+    /// - `EnvelopeError::UnknownSigner` if the signer has no trusted key
+    /// - `EnvelopeError::SignerMismatch` if the provenance names another
+    ///   agent than the signer
+    /// - `EnvelopeError::BadSignature` if the signature does not verify
+    ///   against the trusted key
+    pub fn verify(&self, envelope: &DeltaEnvelope) -> Result<Option<DeltaSigner>, EnvelopeError> {
+        let Some(signature) = envelope.signature() else {
+            return if self.require_signatures {
+                Err(EnvelopeError::Unsigned)
+            } else {
+                Ok(None)
+            };
+        };
+        let key = self
+            .keys
+            .get(&signature.signer)
+            .ok_or_else(|| EnvelopeError::UnknownSigner(signature.signer.clone()))?;
+        if let Some(agent) = envelope.provenance().and_then(|p| p.agent_id.as_ref()) {
+            if *agent != signature.signer {
+                return Err(EnvelopeError::SignerMismatch {
+                    signer: signature.signer.clone(),
+                    agent: agent.clone(),
+                });
+            }
+        }
+        if *key != signature.public_key || key.verify(&envelope.signed_bytes(), &signature.signature).is_err() {
+            return Err(EnvelopeError::BadSignature(signature.signer.clone()));
+        }
+        Ok(Some(DeltaSigner {
+            agent_id: signature.signer.clone(),
+            digest: envelope.digest(),
+        }))
+    }
+
+    /// Verify and open an envelope, recording the signer in the delta's
+    /// provenance
+    ///
+    /// # Errors
+    /// Any error from [`verify`](Self::verify) or
+    /// [`DeltaEnvelope::open`]
+    pub fn open<T>(&self, envelope: &DeltaEnvelope) -> Result<StructuralDelta<T>, EnvelopeError>
+    where
+        T: ArtifactType,
+        T::Content: DeserializeOwned,
+    {
+        let signer = self.verify(envelope)?;
+        let delta = envelope.open::<T>()?;
+        Ok(match signer {
+            Some(signer) => {
+                let mut provenance = delta.provenance().cloned().unwrap_or_else(ArtifactProvenance::new);
+                provenance.signers = vec![signer];
+                delta.with_provenance(provenance)
+            }
+            None => delta,
+        })
+    }
+}
+
+/// Errors when sealing or opening a [`DeltaEnvelope`]
+#[derive(Debug, thiserror::Error)]
+pub enum EnvelopeError {
+    /// Envelope holds a different artifact type
+    #[error("artifact type mismatch: expected {expected}, got {actual}")]
+    TypeMismatch { expected: String, actual: String },
+
+    /// Operation cannot be represented on the wire
+    #[error("operation cannot be serialized: {0}")]
+    UnsupportedOperation(String),
+
+    /// Operation requires content but none was present
+    #[error("missing content for {0:?} operation")]
+    MissingContent(EnvelopeOperation),
+
+    /// Content encoding/decoding failed
+    #[error("content codec error: {0}")]
+    Codec(#[from] serde_json::Error),
+
+    /// Verifier requires signatures and the envelope has none
+    #[error("envelope is not signed")]
+    Unsigned,
+
+    /// Signer has no trusted key
+    #[error("unknown signer: {0}")]
+    UnknownSigner(String),
+
+    /// Provenance names another agent than the signer
+    #[error("envelope signed by {signer} but authored by {agent}")]
+    SignerMismatch { signer: String, agent: String },
+
+    /// Signature does not verify against the signer's trusted key
+    #[error("invalid signature from {0}")]
+    BadSignature(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::artifact::private;
+    use crate::provenance::ArtifactProvenance;
+    use crate::delta::{TransformError, Transformation};
+    use std::str::FromStr;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct NoteArtifact;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct NoteContent {
+        text: String,
+    }
+
+    impl private::Sealed for NoteArtifact {}
+
+    impl ArtifactType for NoteArtifact {
+        type Content = NoteContent;
+
+        fn hash(content: &Self::Content) -> ContentHash {
+            ContentHash::compute(content.text.as_bytes())
+        }
+
+        const TYPE_ID: &'static str = "note";
+    }
+
+    #[derive(Debug, Clone)]
+    struct OtherArtifact;
+
+    impl private::Sealed for OtherArtifact {}
+
+    impl ArtifactType for OtherArtifact {
+        type Content = NoteContent;
+
+        fn hash(content: &Self::Content) -> ContentHash {
+            ContentHash::compute(content.text.as_bytes())
+        }
+
+        const TYPE_ID: &'static str = "other";
+    }
+
+    #[derive(Debug)]
+    struct Upper;
+
+    impl Transformation<NoteArtifact> for Upper {
+        fn apply(&self, content: &NoteContent) -> Result<NoteContent, TransformError> {
+            Ok(NoteContent {
+                text: content.text.to_uppercase(),
+            })
+        }
+
+        fn describe(&self) -> String {
+            "uppercase".to_string()
+        }
+    }
+
+    fn note(text: &str) -> NoteContent {
+        NoteContent {
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn envelope_roundtrip_through_json() {
+        let delta = StructuralDelta::<NoteArtifact>::with_order(
+            SymbolPath::from_str("notes.first").unwrap(),
+            DeltaOperation::Replace(note("hello")),
+            ContentHash::compute(b"base"),
+            7,
+        )
+        .with_provenance(ArtifactProvenance::new().with_agent("writer"))
+        .with_pin(SymbolPin::new(
+            ContentHash::compute(b"base"),
+            ContentHash::compute(b"hello"),
+        ))
+        .with_span(NodeSpan::new(4, 9));
+
+        let envelope = DeltaEnvelope::seal(&delta).unwrap();
+        let wire = serde_json::to_string(&envelope).unwrap();
+        let received: DeltaEnvelope = serde_json::from_str(&wire).unwrap();
+
+        assert_eq!(received.type_id(), "note");
+        assert_eq!(received.open::<NoteArtifact>().unwrap(), delta);
+    }
+
+    #[test]
+    fn envelope_remove_has_no_content() {
+        let delta = StructuralDelta::<NoteArtifact>::new(
+            SymbolPath::single("gone"),
+            DeltaOperation::Remove,
+            ContentHash::compute(b"base"),
+        );
+
+        let envelope = DeltaEnvelope::seal(&delta).unwrap();
+        assert_eq!(envelope.operation(), EnvelopeOperation::Remove);
+        assert!(envelope.content_bytes().is_none());
+        assert_eq!(envelope.open::<NoteArtifact>().unwrap(), delta);
+    }
+
+    #[test]
+    fn envelope_rejects_wrong_type() {
+        let delta = StructuralDelta::<NoteArtifact>::new(
+            SymbolPath::single("x"),
+            DeltaOperation::Add(note("x")),
+            ContentHash::compute(b"base"),
+        );
+
+        let envelope = DeltaEnvelope::seal(&delta).unwrap();
+        let result = envelope.open::<OtherArtifact>();
+        assert!(matches!(result, Err(EnvelopeError::TypeMismatch { .. })));
+    }
+
+    #[test]
+    fn envelope_rejects_transform() {
+        let delta = StructuralDelta::<NoteArtifact>::new(
+            SymbolPath::single("x"),
+            DeltaOperation::Transform(Box::new(Upper)),
+            ContentHash::compute(b"base"),
+        );
+
+        let result = DeltaEnvelope::seal(&delta);
+        assert!(matches!(
+            result,
+            Err(EnvelopeError::UnsupportedOperation(_))
+        ));
+    }
+
+    fn signed_note(agent: &str, key: &SigningKey) -> DeltaEnvelope {
+        let delta = StructuralDelta::<NoteArtifact>::new(
+            SymbolPath::single("x"),
+            DeltaOperation::Replace(note("signed")),
+            ContentHash::compute(b"base"),
+        )
+        .with_provenance(ArtifactProvenance::new().with_agent(agent));
+        DeltaEnvelope::seal(&delta).unwrap().sign(agent, key)
+    }
+
+    #[test]
+    fn verifier_stamps_signer_into_provenance() {
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let envelope = signed_note("writer", &key);
+        let wire = serde_json::to_string(&envelope).unwrap();
+        let received: DeltaEnvelope = serde_json::from_str(&wire).unwrap();
+
+        let verifier = DeltaVerifier::new().with_key("writer", key.verifying_key());
+        let delta = verifier.open::<NoteArtifact>(&received).unwrap();
+        let signers = &delta.provenance().unwrap().signers;
+        assert_eq!(
+            signers,
+            &[DeltaSigner {
+                agent_id: "writer".to_string(),
+                digest: received.digest(),
+            }]
+        );
+        // Plain opening never trusts a claimed signer
+        let claimed = DeltaEnvelope::seal(&delta).unwrap();
+        assert!(claimed.open::<NoteArtifact>().unwrap().provenance().unwrap().signers.is_empty());
+    }
+
+    #[test]
+    fn verifier_rejects_forged_and_unknown_signatures() {
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let other = SigningKey::from_bytes(&[2u8; 32]);
+        let verifier = DeltaVerifier::new().with_key("writer", key.verifying_key());
+
+        let mut tampered = serde_json::to_value(signed_note("writer", &key)).unwrap();
+        tampered["description"] = "edited in transit".into();
+        let tampered: DeltaEnvelope = serde_json::from_value(tampered).unwrap();
+        assert!(matches!(verifier.verify(&tampered), Err(EnvelopeError::BadSignature(_))));
+        assert!(matches!(
+            verifier.verify(&signed_note("writer", &other)),
+            Err(EnvelopeError::BadSignature(_))
+        ));
+        assert!(matches!(
+            verifier.verify(&signed_note("intruder", &other)),
+            Err(EnvelopeError::UnknownSigner(_))
+        ));
+
+        let impersonated = DeltaEnvelope::seal(&StructuralDelta::<NoteArtifact>::new(
+            SymbolPath::single("x"),
+            DeltaOperation::Remove,
+            ContentHash::compute(b"base"),
+        )
+        .with_provenance(ArtifactProvenance::new().with_agent("reviewer")))
+        .unwrap()
+        .sign("writer", &key);
+        assert!(matches!(
+            verifier.verify(&impersonated),
+            Err(EnvelopeError::SignerMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn unsigned_envelopes_pass_unless_required() {
+        let delta = StructuralDelta::<NoteArtifact>::new(
+            SymbolPath::single("x"),
+            DeltaOperation::Remove,
+            ContentHash::compute(b"base"),
+        );
+        let envelope = DeltaEnvelope::seal(&delta).unwrap();
+
+        assert_eq!(DeltaVerifier::new().open::<NoteArtifact>(&envelope).unwrap(), delta);
+        assert!(matches!(
+            DeltaVerifier::new().requiring_signatures().verify(&envelope),
+            Err(EnvelopeError::Unsigned)
+        ));
+    }
+
+    #[test]
+    fn symbol_path_serde_roundtrip() {
+        let path = SymbolPath::from_str("crate.module.function").unwrap();
+        let json = serde_json::to_string(&path).unwrap();
+        assert_eq!(json, r#"["crate","module","function"]"#);
+        let back: SymbolPath = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, path);
+    }
+}
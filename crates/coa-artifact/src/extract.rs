@@ -0,0 +1,141 @@
+//! Pluggable per-language symbol extraction
+//!
+//! Built-in code parsing has dedicated symbol-table builders for a few
+//! languages and a weak identifier-based fallback for the rest. Embedders
+//! can cover other languages (Kotlin, Swift, Terraform HCL, ...) by
+//! implementing [`SymbolExtractor`] and registering it with
+//! [`register_symbol_extractor`]; code parsing consults the registry before
+//! falling back.
+
+use crate::span::NodeSpan;
+use std::sync::{Arc, OnceLock, PoisonError, RwLock};
+
+/// Symbol found by a [`SymbolExtractor`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractedSymbol {
+    /// Symbol name
+    pub name: String,
+    /// Language-specific kind (e.g. `fun`, `class`, `resource`)
+    pub kind: String,
+    /// Byte range of the definition in the source
+    pub span: NodeSpan,
+    /// Enclosing symbol, if nested
+    pub parent: Option<String>,
+}
+
+impl ExtractedSymbol {
+    /// Create top-level symbol
+    #[inline]
+    #[must_use]
+    pub fn new(name: impl Into<String>, kind: impl Into<String>, span: NodeSpan) -> Self {
+        Self {
+            name: name.into(),
+            kind: kind.into(),
+            span,
+            parent: None,
+        }
+    }
+
+    /// Nest under `parent`
+    #[inline]
+    #[must_use]
+    pub fn with_parent(mut self, parent: impl Into<String>) -> Self {
+        self.parent = Some(parent.into());
+        self
+    }
+}
+
+/// Symbol-table builder for one language
+pub trait SymbolExtractor: Send + Sync + 'static {
+    /// Language name, matched case-insensitively (e.g. `kotlin`)
+    fn language(&self) -> &str;
+
+    /// File extensions handled (without dot)
+    fn extensions(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Extract symbols from source text, in source order
+    fn extract(&self, source: &str) -> Vec<ExtractedSymbol>;
+}
+
+fn registry() -> &'static RwLock<Vec<Arc<dyn SymbolExtractor>>> {
+    static REGISTRY: OnceLock<RwLock<Vec<Arc<dyn SymbolExtractor>>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(Vec::new()))
+}
+
+/// Register an extractor for all code parsing in this process
+///
+/// A later registration for the same language or extension takes
+/// precedence over earlier ones.
+pub fn register_symbol_extractor(extractor: Arc<dyn SymbolExtractor>) {
+    registry()
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push(extractor);
+}
+
+/// Extractor registered for a language name or file extension
+#[must_use]
+pub fn symbol_extractor(language_or_extension: &str) -> Option<Arc<dyn SymbolExtractor>> {
+    let key = language_or_extension.trim_start_matches('.');
+    registry()
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .rev()
+        .find(|e| e.language().eq_ignore_ascii_case(key) || e.extensions().contains(&key))
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Line-based Kotlin `fun`/`class` extractor
+    struct Kotlin;
+
+    impl SymbolExtractor for Kotlin {
+        fn language(&self) -> &'static str {
+            "kotlin"
+        }
+
+        fn extensions(&self) -> &[&str] {
+            &["kt", "kts"]
+        }
+
+        fn extract(&self, source: &str) -> Vec<ExtractedSymbol> {
+            let mut offset = 0;
+            let mut symbols = Vec::new();
+            for line in source.split_inclusive('\n') {
+                let start = offset;
+                offset += line.len();
+                for kind in ["fun", "class"] {
+                    if let Some(rest) = line.trim().strip_prefix(kind).and_then(|r| r.strip_prefix(' ')) {
+                        let name: String = rest.chars().take_while(|c| c.is_alphanumeric()).collect();
+                        symbols.push(ExtractedSymbol::new(name, kind, NodeSpan::new(start, offset)));
+                    }
+                }
+            }
+            symbols
+        }
+    }
+
+    #[test]
+    fn registered_extractor_is_found_by_language_or_extension() {
+        register_symbol_extractor(Arc::new(Kotlin));
+
+        let by_name = symbol_extractor("Kotlin").unwrap();
+        assert!(symbol_extractor(".kts").is_some());
+        assert!(symbol_extractor("swift-test-unregistered").is_none());
+
+        let symbols = by_name.extract("class Greeter\nfun main() {}\n");
+        assert_eq!(
+            symbols,
+            [
+                ExtractedSymbol::new("Greeter", "class", NodeSpan::new(0, 14)),
+                ExtractedSymbol::new("main", "fun", NodeSpan::new(14, 28)),
+            ]
+        );
+    }
+}
@@ -1,44 +1,282 @@
 //! Content-addressed hashing primitives
 //!
 //! Provides [`ContentHash`], a strongly-typed 32-byte hash used for
-//! content addressing throughout the COA system.
+//! content addressing throughout the COA system, and the [`Hasher`]
+//! abstraction over the algorithms producing it.
+//!
+//! # Algorithms
+//! Blake3 is the default. Deployments restricted to FIPS-approved
+//! primitives install SHA-256 or SHA3-256 once at startup with
+//! [`HashAlgorithm::install`]; [`ContentHash::compute`] then uses it, as do
+//! the kernel's event log and directive hashing.
+//!
+//! Every hash records the algorithm that produced it, and hashes of
+//! different algorithms never compare equal. Displayed and serialized
+//! forms carry the algorithm as a prefix (`sha256:<hex>`), except Blake3,
+//! which keeps the bare hex form so existing stores stay readable.
 
+use std::cell::Cell;
 use std::fmt::{self, Display, Formatter};
 use std::str::FromStr;
+use std::sync::OnceLock;
+
+/// Hash algorithm backing a [`ContentHash`]
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
+pub enum HashAlgorithm {
+    /// Blake3 (default)
+    #[default]
+    #[serde(rename = "blake3")]
+    Blake3,
+    /// SHA-256 (FIPS 180-4)
+    #[serde(rename = "sha256")]
+    Sha256,
+    /// SHA3-256 (FIPS 202)
+    #[serde(rename = "sha3-256")]
+    Sha3_256,
+}
+
+/// Algorithm installed for the process
+static INSTALLED: OnceLock<HashAlgorithm> = OnceLock::new();
+
+thread_local! {
+    /// Override set by [`HashAlgorithm::scope`]
+    static SCOPED: Cell<Option<HashAlgorithm>> = const { Cell::new(None) };
+}
+
+impl HashAlgorithm {
+    /// All supported algorithms
+    pub const ALL: [Self; 3] = [Self::Blake3, Self::Sha256, Self::Sha3_256];
+
+    /// Identifier used in serialized hashes
+    #[inline]
+    #[must_use]
+    pub const fn id(self) -> &'static str {
+        match self {
+            Self::Blake3 => "blake3",
+            Self::Sha256 => "sha256",
+            Self::Sha3_256 => "sha3-256",
+        }
+    }
+
+    /// Tag byte of the binary encoding
+    const fn tag(self) -> u8 {
+        match self {
+            Self::Blake3 => 0,
+            Self::Sha256 => 1,
+            Self::Sha3_256 => 2,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| a.tag() == tag)
+    }
+
+    /// Whether the algorithm is FIPS-approved
+    #[inline]
+    #[must_use]
+    pub const fn is_fips_approved(self) -> bool {
+        !matches!(self, Self::Blake3)
+    }
+
+    /// Fresh incremental hasher
+    #[must_use]
+    pub fn hasher(self) -> Box<dyn Hasher> {
+        match self {
+            Self::Blake3 => Box::new(blake3::Hasher::new()),
+            Self::Sha256 => Box::new(<sha2::Sha256 as sha2::Digest>::new()),
+            Self::Sha3_256 => Box::new(<sha3::Sha3_256 as sha3::Digest>::new()),
+        }
+    }
+
+    /// Hash `data` in one shot
+    #[must_use]
+    pub fn digest(self, data: &[u8]) -> ContentHash {
+        let bytes = match self {
+            Self::Blake3 => *blake3::hash(data).as_bytes(),
+            Self::Sha256 => <sha2::Sha256 as sha2::Digest>::digest(data).into(),
+            Self::Sha3_256 => <sha3::Sha3_256 as sha3::Digest>::digest(data).into(),
+        };
+        ContentHash::with_algorithm(self, bytes)
+    }
+
+    /// Install the process-wide algorithm
+    ///
+    /// Call once at startup, before anything is hashed: hashes computed
+    /// earlier keep their algorithm and will not match recomputed ones.
+    ///
+    /// # Errors
+    /// Returns the installed algorithm if a different one was installed first
+    pub fn install(self) -> Result<(), Self> {
+        match *INSTALLED.get_or_init(|| self) {
+            installed if installed == self => Ok(()),
+            installed => Err(installed),
+        }
+    }
+
+    /// Algorithm installed with [`install`](Self::install), if any
+    #[inline]
+    #[must_use]
+    pub fn installed() -> Option<Self> {
+        INSTALLED.get().copied()
+    }
+
+    /// Algorithm [`ContentHash::compute`] uses on this thread
+    ///
+    /// The innermost [`scope`](Self::scope), else the installed algorithm,
+    /// else Blake3.
+    #[must_use]
+    pub fn current() -> Self {
+        SCOPED
+            .with(Cell::get)
+            .or_else(Self::installed)
+            .unwrap_or_default()
+    }
+
+    /// Run `f` with [`current`](Self::current) set to `self` on this thread
+    ///
+    /// Used to recompute hashes under another algorithm (e.g. migration)
+    /// without installing it.
+    pub fn scope<R>(self, f: impl FnOnce() -> R) -> R {
+        struct Restore(Option<HashAlgorithm>);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                SCOPED.with(|scoped| scoped.set(self.0));
+            }
+        }
 
-/// A 32-byte content hash (Blake3)
+        let _restore = Restore(SCOPED.with(|scoped| scoped.replace(Some(self))));
+        f()
+    }
+}
+
+impl Display for HashAlgorithm {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = HashError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|a| a.id() == s)
+            .ok_or_else(|| HashError::UnknownAlgorithm(s.to_string()))
+    }
+}
+
+/// Incremental hash computation
+///
+/// Implemented for the built-in backends returned by
+/// [`HashAlgorithm::hasher`]; other implementations (e.g. a validated
+/// cryptographic module) can stand in wherever a `Box<dyn Hasher>` is taken.
+pub trait Hasher: Send {
+    /// Algorithm the produced hash is tagged with
+    fn algorithm(&self) -> HashAlgorithm;
+
+    /// Feed more data
+    fn update(&mut self, data: &[u8]);
+
+    /// Finish and produce the hash
+    fn finalize(self: Box<Self>) -> ContentHash;
+}
+
+impl Hasher for blake3::Hasher {
+    fn algorithm(&self) -> HashAlgorithm {
+        HashAlgorithm::Blake3
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        blake3::Hasher::update(self, data);
+    }
+
+    fn finalize(self: Box<Self>) -> ContentHash {
+        ContentHash::with_algorithm(HashAlgorithm::Blake3, *blake3::Hasher::finalize(&self).as_bytes())
+    }
+}
+
+impl Hasher for sha2::Sha256 {
+    fn algorithm(&self) -> HashAlgorithm {
+        HashAlgorithm::Sha256
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        sha2::Digest::update(self, data);
+    }
+
+    fn finalize(self: Box<Self>) -> ContentHash {
+        ContentHash::with_algorithm(HashAlgorithm::Sha256, sha2::Digest::finalize(*self).into())
+    }
+}
+
+impl Hasher for sha3::Sha3_256 {
+    fn algorithm(&self) -> HashAlgorithm {
+        HashAlgorithm::Sha3_256
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        sha3::Digest::update(self, data);
+    }
+
+    fn finalize(self: Box<Self>) -> ContentHash {
+        ContentHash::with_algorithm(HashAlgorithm::Sha3_256, sha3::Digest::finalize(*self).into())
+    }
+}
+
+/// A 32-byte content hash tagged with its [`HashAlgorithm`]
 ///
 /// Used for content-addressed storage and artifact identification.
 /// Immutable and cheap to clone (Copy).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct ContentHash([u8; 32]);
+pub struct ContentHash {
+    bytes: [u8; 32],
+    algorithm: HashAlgorithm,
+}
 
 impl ContentHash {
     /// Zero hash (all zeros)
-    pub const ZERO: Self = Self([0u8; 32]);
+    pub const ZERO: Self = Self::new([0u8; 32]);
 
-    /// Create a new ContentHash from raw bytes
+    /// Create a new `ContentHash` from raw Blake3 bytes
     #[inline]
     #[must_use]
     pub const fn new(bytes: [u8; 32]) -> Self {
-        Self(bytes)
+        Self::with_algorithm(HashAlgorithm::Blake3, bytes)
+    }
+
+    /// Create a `ContentHash` from raw bytes produced by `algorithm`
+    #[inline]
+    #[must_use]
+    pub const fn with_algorithm(algorithm: HashAlgorithm, bytes: [u8; 32]) -> Self {
+        Self { bytes, algorithm }
+    }
+
+    /// Algorithm that produced the hash
+    #[inline]
+    #[must_use]
+    pub const fn algorithm(&self) -> HashAlgorithm {
+        self.algorithm
     }
 
     /// Get reference to the underlying bytes
     #[inline]
     #[must_use]
     pub const fn as_bytes(&self) -> &[u8; 32] {
-        &self.0
+        &self.bytes
     }
 
     /// Convert to byte array (consumes self)
     #[inline]
     #[must_use]
     pub const fn into_bytes(self) -> [u8; 32] {
-        self.0
+        self.bytes
     }
 
-    /// Create hash from byte slice
+    /// Create Blake3 hash from byte slice
     ///
     /// # Errors
     /// Returns error if slice length is not exactly 32 bytes
@@ -52,15 +290,21 @@ impl ContentHash {
         }
         let mut arr = [0u8; 32];
         arr.copy_from_slice(bytes);
-        Ok(Self(arr))
+        Ok(Self::new(arr))
     }
 
-    /// Compute Blake3 hash of arbitrary data
+    /// Compute hash of arbitrary data with [`HashAlgorithm::current`]
     #[inline]
     #[must_use]
     pub fn compute(data: &[u8]) -> Self {
-        let hash = blake3::hash(data);
-        Self::new(*hash.as_bytes())
+        HashAlgorithm::current().digest(data)
+    }
+
+    /// Compute hash of arbitrary data with a specific algorithm
+    #[inline]
+    #[must_use]
+    pub fn compute_with(algorithm: HashAlgorithm, data: &[u8]) -> Self {
+        algorithm.digest(data)
     }
 
     /// Compute hash from serializable value (JSON encoding)
@@ -80,7 +324,7 @@ impl ContentHash {
     #[inline]
     #[must_use]
     pub fn short(&self) -> String {
-        hex::encode(&self.0[..8])
+        hex::encode(&self.bytes[..8])
     }
 
     /// Check if hash is all zeros (placeholder/uninitialized)
@@ -89,7 +333,7 @@ impl ContentHash {
     pub const fn is_zero(&self) -> bool {
         let mut i = 0;
         while i < 32 {
-            if self.0[i] != 0 {
+            if self.bytes[i] != 0 {
                 return false;
             }
             i += 1;
@@ -100,7 +344,10 @@ impl ContentHash {
 
 impl Display for ContentHash {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", hex::encode(self.0))
+        match self.algorithm {
+            HashAlgorithm::Blake3 => write!(f, "{}", hex::encode(self.bytes)),
+            algorithm => write!(f, "{algorithm}:{}", hex::encode(self.bytes)),
+        }
     }
 }
 
@@ -108,24 +355,31 @@ impl FromStr for ContentHash {
     type Err = HashError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let bytes = hex::decode(s)?;
-        Self::from_slice(&bytes)
+        let (algorithm, digest) = match s.split_once(':') {
+            Some((id, digest)) => (id.parse()?, digest),
+            None => (HashAlgorithm::Blake3, s),
+        };
+        let bytes = hex::decode(digest)?;
+        Ok(Self::with_algorithm(algorithm, *Self::from_slice(&bytes)?.as_bytes()))
     }
 }
 
 impl AsRef<[u8; 32]> for ContentHash {
     fn as_ref(&self) -> &[u8; 32] {
-        &self.0
+        &self.bytes
     }
 }
 
 impl Default for ContentHash {
     fn default() -> Self {
-        Self([0; 32])
+        Self::ZERO
     }
 }
 
 // Serde implementations for compact serialization
+//
+// Binary formats store Blake3 hashes as 32 bytes and others as a tag byte
+// followed by the 32 hash bytes.
 impl serde::Serialize for ContentHash {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -133,8 +387,13 @@ impl serde::Serialize for ContentHash {
     {
         if serializer.is_human_readable() {
             serializer.serialize_str(&self.to_string())
+        } else if self.algorithm == HashAlgorithm::Blake3 {
+            serializer.serialize_bytes(&self.bytes)
         } else {
-            serializer.serialize_bytes(&self.0)
+            let mut tagged = [0u8; 33];
+            tagged[0] = self.algorithm.tag();
+            tagged[1..].copy_from_slice(&self.bytes);
+            serializer.serialize_bytes(&tagged)
         }
     }
 }
@@ -164,6 +423,15 @@ impl<'de> serde::Deserialize<'de> for ContentHash {
             where
                 E: serde::de::Error,
             {
+                if let [tag, bytes @ ..] = value {
+                    if bytes.len() == 32 {
+                        let algorithm = HashAlgorithm::from_tag(*tag).ok_or_else(|| {
+                            serde::de::Error::custom(HashError::UnknownAlgorithm(format!("tag {tag}")))
+                        })?;
+                        let hash = ContentHash::from_slice(bytes).map_err(serde::de::Error::custom)?;
+                        return Ok(ContentHash::with_algorithm(algorithm, hash.into_bytes()));
+                    }
+                }
                 ContentHash::from_slice(value).map_err(serde::de::Error::custom)
             }
 
@@ -203,6 +471,10 @@ pub enum HashError {
     /// Serialization error
     #[error("serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
+
+    /// Unrecognised algorithm identifier
+    #[error("unknown hash algorithm: {0}")]
+    UnknownAlgorithm(String),
 }
 
 #[cfg(test)]
@@ -294,4 +566,35 @@ mod tests {
         assert!(json.contains('"'));
         assert!(json.len() > 64); // " + 64 hex chars + "
     }
+
+    #[test]
+    fn content_hash_algorithms_are_tagged() {
+        let data = b"fips";
+        let blake3 = ContentHash::compute_with(HashAlgorithm::Blake3, data);
+        let sha256 = ContentHash::compute_with(HashAlgorithm::Sha256, data);
+        let sha3 = ContentHash::compute_with(HashAlgorithm::Sha3_256, data);
+        assert_eq!(blake3, ContentHash::compute(data));
+        assert_ne!(sha256, sha3);
+        assert_ne!(ContentHash::with_algorithm(HashAlgorithm::Sha256, sha3.into_bytes()), sha3);
+
+        let mut hasher = HashAlgorithm::Sha3_256.hasher();
+        hasher.update(b"fi");
+        hasher.update(b"ps");
+        assert_eq!(hasher.finalize(), sha3);
+        assert_eq!(HashAlgorithm::Sha256.scope(|| ContentHash::compute(data)), sha256);
+        assert_eq!(HashAlgorithm::current(), HashAlgorithm::Blake3);
+
+        // Blake3 keeps the legacy form; others carry their identifier
+        assert!(!blake3.to_string().contains(':'));
+        assert!(sha3.to_string().starts_with("sha3-256:"));
+        for hash in [blake3, sha256, sha3] {
+            assert_eq!(hash.to_string().parse::<ContentHash>().unwrap(), hash);
+            let json = serde_json::to_string(&hash).unwrap();
+            assert_eq!(serde_json::from_str::<ContentHash>(&json).unwrap(), hash);
+        }
+        assert!(matches!(
+            "md5:00".parse::<ContentHash>(),
+            Err(HashError::UnknownAlgorithm(_))
+        ));
+    }
 }
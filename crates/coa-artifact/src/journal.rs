@@ -0,0 +1,326 @@
+//! Compressed, deduplicated delta journal
+//!
+//! [`DeltaJournal`] stores [`DeltaEnvelope`]s for long runs. Envelope
+//! payloads are split off into content-addressed blobs: identical payloads
+//! across deltas share one blob, and each blob is zstd-compressed unless
+//! that would make it larger. Read APIs hand back complete envelopes, so
+//! callers never see the storage format.
+
+use crate::envelope::DeltaEnvelope;
+use crate::hash::ContentHash;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Default zstd compression level
+pub const DEFAULT_COMPRESSION_LEVEL: i32 = 3;
+
+/// Payload compression applied to new blobs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Compression {
+    /// Store payloads as is
+    None,
+    /// zstd at the given level
+    Zstd { level: i32 },
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Self::Zstd {
+            level: DEFAULT_COMPRESSION_LEVEL,
+        }
+    }
+}
+
+/// Encoding of one stored blob
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Codec {
+    Raw,
+    Zstd,
+}
+
+/// Shared payload, keyed by the hash of its uncompressed bytes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Blob {
+    codec: Codec,
+    raw_len: usize,
+    #[serde(with = "hex_bytes")]
+    bytes: Vec<u8>,
+}
+
+/// Envelope with its payload replaced by a blob reference
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredEnvelope {
+    envelope: DeltaEnvelope,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    payload: Option<ContentHash>,
+}
+
+/// Storage statistics of a [`DeltaJournal`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalStats {
+    pub entries: usize,
+    /// Distinct payload blobs
+    pub blobs: usize,
+    /// Entries whose payload reused an existing blob
+    pub deduplicated: usize,
+    /// Payload bytes as appended, before dedup and compression
+    pub payload_bytes: usize,
+    /// Uncompressed bytes of the distinct blobs
+    pub unique_bytes: usize,
+    /// Bytes actually stored for the blobs
+    pub stored_bytes: usize,
+}
+
+impl JournalStats {
+    /// Payload bytes appended per byte stored (1.0 when empty)
+    #[must_use]
+    pub fn ratio(&self) -> f64 {
+        if self.stored_bytes == 0 {
+            return 1.0;
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let ratio = self.payload_bytes as f64 / self.stored_bytes as f64;
+        ratio
+    }
+}
+
+/// Append-only journal of delta envelopes with shared, compressed payloads
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeltaJournal {
+    #[serde(default)]
+    compression: Compression,
+    entries: Vec<StoredEnvelope>,
+    blobs: BTreeMap<ContentHash, Blob>,
+}
+
+impl DeltaJournal {
+    /// Create empty journal with default (zstd) compression
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set compression for blobs added from now on
+    #[inline]
+    #[must_use]
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Append an envelope, returning its index
+    ///
+    /// # Errors
+    /// Returns error if compression fails
+    pub fn append(&mut self, mut envelope: DeltaEnvelope) -> Result<usize, JournalError> {
+        let payload = match envelope.take_content() {
+            Some(bytes) => {
+                let hash = ContentHash::compute(&bytes);
+                if !self.blobs.contains_key(&hash) {
+                    let blob = self.encode(bytes)?;
+                    self.blobs.insert(hash, blob);
+                }
+                Some(hash)
+            }
+            None => None,
+        };
+        self.entries.push(StoredEnvelope { envelope, payload });
+        Ok(self.entries.len() - 1)
+    }
+
+    /// Envelope at `index`, with its payload restored
+    ///
+    /// # Errors
+    /// Returns error if the payload blob is missing or corrupt
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<Result<DeltaEnvelope, JournalError>> {
+        self.entries.get(index).map(|stored| self.restore(stored))
+    }
+
+    /// All envelopes in append order
+    pub fn iter(&self) -> impl Iterator<Item = Result<DeltaEnvelope, JournalError>> + '_ {
+        self.entries.iter().map(|stored| self.restore(stored))
+    }
+
+    /// Number of entries
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if empty
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Storage statistics
+    #[must_use]
+    pub fn stats(&self) -> JournalStats {
+        let referencing = self.entries.iter().filter_map(|e| e.payload.as_ref());
+        let (references, payload_bytes) = referencing.fold((0, 0), |(n, bytes), hash| {
+            (n + 1, bytes + self.blobs.get(hash).map_or(0, |b| b.raw_len))
+        });
+        JournalStats {
+            entries: self.entries.len(),
+            blobs: self.blobs.len(),
+            deduplicated: references - self.blobs.len().min(references),
+            payload_bytes,
+            unique_bytes: self.blobs.values().map(|b| b.raw_len).sum(),
+            stored_bytes: self.blobs.values().map(|b| b.bytes.len()).sum(),
+        }
+    }
+
+    fn encode(&self, raw: Vec<u8>) -> Result<Blob, JournalError> {
+        let raw_len = raw.len();
+        if let Compression::Zstd { level } = self.compression {
+            let compressed = zstd::bulk::compress(&raw, level)
+                .map_err(|e| JournalError::Compression(e.to_string()))?;
+            if compressed.len() < raw_len {
+                return Ok(Blob {
+                    codec: Codec::Zstd,
+                    raw_len,
+                    bytes: compressed,
+                });
+            }
+        }
+        Ok(Blob {
+            codec: Codec::Raw,
+            raw_len,
+            bytes: raw,
+        })
+    }
+
+    fn restore(&self, stored: &StoredEnvelope) -> Result<DeltaEnvelope, JournalError> {
+        let mut envelope = stored.envelope.clone();
+        if let Some(hash) = stored.payload {
+            let blob = self.blobs.get(&hash).ok_or(JournalError::MissingBlob(hash))?;
+            let raw = match blob.codec {
+                Codec::Raw => blob.bytes.clone(),
+                Codec::Zstd => zstd::bulk::decompress(&blob.bytes, blob.raw_len)
+                    .map_err(|e| JournalError::Compression(e.to_string()))?,
+            };
+            if ContentHash::compute(&raw) != hash {
+                return Err(JournalError::Corrupt(hash));
+            }
+            envelope.set_content(raw);
+        }
+        Ok(envelope)
+    }
+}
+
+/// Errors reading or writing a [`DeltaJournal`]
+#[derive(Debug, thiserror::Error)]
+pub enum JournalError {
+    /// Entry references a blob that is not stored
+    #[error("missing payload blob {0}")]
+    MissingBlob(ContentHash),
+
+    /// Blob does not decode to the bytes its hash names
+    #[error("corrupt payload blob {0}")]
+    Corrupt(ContentHash),
+
+    /// zstd failed
+    #[error("compression error: {0}")]
+    Compression(String),
+}
+
+/// Hex encoding for blob bytes in serialized journals
+mod hex_bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(bytes))
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        hex::decode(text).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::artifact::{private, ArtifactType};
+    use crate::delta::{DeltaOperation, StructuralDelta};
+    use crate::path::SymbolPath;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct NoteArtifact;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct NoteContent {
+        text: String,
+    }
+
+    impl private::Sealed for NoteArtifact {}
+
+    impl ArtifactType for NoteArtifact {
+        type Content = NoteContent;
+
+        fn hash(content: &Self::Content) -> ContentHash {
+            ContentHash::compute(content.text.as_bytes())
+        }
+
+        const TYPE_ID: &'static str = "note";
+    }
+
+    fn envelope(target: &str, op: DeltaOperation<NoteArtifact>) -> DeltaEnvelope {
+        let delta = StructuralDelta::new(SymbolPath::single(target), op, ContentHash::compute(b"base"));
+        DeltaEnvelope::seal(&delta).unwrap()
+    }
+
+    fn body(text: &str) -> DeltaOperation<NoteArtifact> {
+        DeltaOperation::Replace(NoteContent {
+            text: text.repeat(200),
+        })
+    }
+
+    #[test]
+    fn identical_payloads_share_a_compressed_blob() {
+        let mut journal = DeltaJournal::new();
+        let sealed = [
+            envelope("a", body("shared ")),
+            envelope("b", body("shared ")),
+            envelope("c", body("other ")),
+            envelope("d", DeltaOperation::Remove),
+        ];
+        for e in &sealed {
+            journal.append(e.clone()).unwrap();
+        }
+
+        let stats = journal.stats();
+        assert_eq!((stats.entries, stats.blobs, stats.deduplicated), (4, 2, 1));
+        assert!(stats.stored_bytes < stats.unique_bytes);
+        assert!(stats.unique_bytes < stats.payload_bytes);
+        assert!(stats.ratio() > 10.0);
+
+        let restored: Vec<_> = journal.iter().map(Result::unwrap).collect();
+        assert_eq!(restored, sealed);
+        assert_eq!(
+            journal.get(1).unwrap().unwrap().open::<NoteArtifact>().unwrap(),
+            sealed[1].open::<NoteArtifact>().unwrap()
+        );
+    }
+
+    #[test]
+    fn survives_serialization_and_detects_corruption() {
+        let mut journal = DeltaJournal::new().with_compression(Compression::None);
+        journal.append(envelope("a", body("x"))).unwrap();
+        assert_eq!(journal.stats().stored_bytes, journal.stats().unique_bytes);
+
+        let wire = serde_json::to_string(&journal).unwrap();
+        let back: DeltaJournal = serde_json::from_str(&wire).unwrap();
+        assert_eq!(back.get(0).unwrap().unwrap(), envelope("a", body("x")));
+
+        let mut tampered = back;
+        tampered.blobs.values_mut().for_each(|blob| blob.bytes[0] ^= 1);
+        assert!(matches!(tampered.get(0), Some(Err(JournalError::Corrupt(_)))));
+    }
+}
@@ -6,9 +6,16 @@
 //!
 //! - [`Artifact<T>`]: Content-addressed container for typed content
 //! - [`ArtifactType`]: Trait for defining artifact types (Code, Config, Spec, etc.)
-//! - [`ContentHash`]: 32-byte Blake3 hash for content addressing
+//! - [`ContentHash`]: 32-byte hash for content addressing (Blake3 by default,
+//!   see [`HashAlgorithm`])
 //! - [`StructuralDelta<T>`]: Semantic transformation operations
 //! - [`SymbolPath`]: Hierarchical addressing within artifacts
+//! - [`DeltaEnvelope`]: Type-erased wire format for deltas, optionally signed
+//!   by the producing agent ([`DeltaVerifier`])
+//! - [`DeltaJournal`]: Compressed, deduplicated storage for envelopes
+//! - [`SymbolExtractor`]: Plug-in symbol tables for additional languages
+//! - [`ArtifactProvenance`]: Origin metadata (agent, task, parents) for tracing
+//! - [`define_artifact_type!`]: Supported extension point for custom types
 //!
 //! # Example
 //!
@@ -27,16 +34,26 @@
 #![allow(missing_docs)]
 
 // Core modules
+#[macro_use]
+mod macros;
 mod artifact;
 mod delta;
+mod envelope;
+mod extract;
 mod hash;
+mod journal;
 mod path;
+mod pin;
+mod provenance;
+mod resolve;
+mod span;
 
 // Re-exports
 pub use artifact::{Artifact, ArtifactError, ArtifactType, DynArtifactRef};
 
-/// Sealed trait support - for implementing custom artifact types.
-/// **Note:** This is only for internal/testing use and may change.
+/// Sealed trait support - target of [`define_artifact_type!`].
+/// **Note:** Implement custom artifact types through the macro; naming this
+/// module directly is not covered by semver.
 #[doc(hidden)]
 pub mod __private {
     pub use super::artifact::private::Sealed;
@@ -44,19 +61,34 @@ pub mod __private {
 pub use delta::{
     DeltaBuilder, DeltaError, DeltaOperation, StructuralDelta, TransformError, Transformation,
 };
-pub use hash::{ContentHash, HashError};
+pub use envelope::{DeltaEnvelope, DeltaSignature, DeltaVerifier, EnvelopeError, EnvelopeOperation};
+pub use extract::{
+    register_symbol_extractor, symbol_extractor, ExtractedSymbol, SymbolExtractor,
+};
+pub use hash::{ContentHash, HashAlgorithm, HashError, Hasher};
+pub use journal::{
+    Compression, DeltaJournal, JournalError, JournalStats, DEFAULT_COMPRESSION_LEVEL,
+};
 pub use path::{PathError, SymbolPath};
+pub use pin::{PinRevision, SymbolPin};
+pub use provenance::{ArtifactProvenance, CorrelationId, DeltaSigner, ProvenanceIndex, TenantId};
+pub use resolve::{nearest_matches, AddressableContent, MAX_SUGGESTIONS};
+pub use span::{NodeSpan, SpannedContent};
 
 /// Artifact type implementations
 pub mod types {
     //! Concrete artifact types
     //!
-    //! - Binary: Raw byte content
+    //! - Binary: Raw byte content, in memory or file-backed
     //! - Code: Parsed AST with symbol table
     //! - Config: Schema-validated configuration
     //! - Spec: Structured specification documents
     //!
-    // Will be implemented next
+    // Code, Config and Spec will be implemented next
+
+    pub mod binary;
+
+    pub use binary::{BinaryArtifact, BinaryContent, BinaryPatch, BinarySplice, PatchOp};
 }
 
 /// Merkle tree support
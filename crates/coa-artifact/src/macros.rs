@@ -0,0 +1,166 @@
+//! Extension macro for downstream artifact types
+//!
+//! [`ArtifactType`](crate::ArtifactType) stays sealed so the crate controls
+//! which invariants an implementation must uphold. [`define_artifact_type!`]
+//! is the supported way for other crates to add their own types (SQL schemas,
+//! protobuf IDL, `OpenAPI` documents, ...).
+//!
+//! # Stability
+//! The macro input grammar is part of the public API and follows semver.
+//! The expansion (and the `__private` module it targets) is not.
+
+/// Define a custom artifact type outside this crate
+///
+/// Declares a unit marker struct and implements the sealed
+/// [`ArtifactType`](crate::ArtifactType) for it. The marker derives
+/// `Debug, Clone, Copy, Default, PartialEq, Eq, Hash`.
+///
+/// `hash` and the optional `validate` and `size` accept any expression
+/// coercible to a plain function pointer (non-capturing closures or function
+/// paths). `size` overrides [`ArtifactType::estimated_size`](crate::ArtifactType::estimated_size).
+///
+/// # Example
+/// ```rust
+/// use coa_artifact::{define_artifact_type, ArtifactError, ContentHash};
+///
+/// #[derive(Debug, Clone, PartialEq)]
+/// pub struct ProtoContent {
+///     pub source: String,
+/// }
+///
+/// define_artifact_type! {
+///     /// Protobuf IDL files
+///     pub ProtoArtifact {
+///         content: ProtoContent,
+///         type_id: "protobuf",
+///         hash: |c| ContentHash::compute(c.source.as_bytes()),
+///         validate: |c| if c.source.contains("syntax") {
+///             Ok(())
+///         } else {
+///             Err(ArtifactError::InvariantViolation("missing syntax".into()))
+///         },
+///     }
+/// }
+///
+/// let content = ProtoContent { source: "syntax = \"proto3\";".into() };
+/// let artifact = coa_artifact::Artifact::<ProtoArtifact>::new(content).unwrap();
+/// assert_eq!(coa_artifact::Artifact::<ProtoArtifact>::type_id(), "protobuf");
+/// assert!(artifact.verify());
+/// ```
+#[macro_export]
+macro_rules! define_artifact_type {
+    (
+        $(#[$meta:meta])*
+        $vis:vis $name:ident {
+            content: $content:ty,
+            type_id: $type_id:literal,
+            hash: $hash:expr
+            $(, validate: $validate:expr)?
+            $(, size: $size:expr)?
+            $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+        $vis struct $name;
+
+        impl $crate::__private::Sealed for $name {}
+
+        impl $crate::ArtifactType for $name {
+            type Content = $content;
+
+            fn hash(content: &Self::Content) -> $crate::ContentHash {
+                let f: fn(&$content) -> $crate::ContentHash = $hash;
+                f(content)
+            }
+
+            const TYPE_ID: &'static str = $type_id;
+
+            $(
+                fn validate_content(
+                    content: &Self::Content,
+                ) -> ::std::result::Result<(), $crate::ArtifactError> {
+                    let f: fn(&$content) -> ::std::result::Result<(), $crate::ArtifactError> =
+                        $validate;
+                    f(content)
+                }
+            )?
+
+            $(
+                fn estimated_size(content: &Self::Content) -> usize {
+                    let f: fn(&$content) -> usize = $size;
+                    f(content)
+                }
+            )?
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Artifact, ArtifactError, ContentHash};
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Counter {
+        value: u32,
+    }
+
+    fn hash_counter(c: &Counter) -> ContentHash {
+        ContentHash::compute(&c.value.to_le_bytes())
+    }
+
+    define_artifact_type! {
+        /// Plain artifact without validation
+        CounterArtifact {
+            content: Counter,
+            type_id: "counter",
+            hash: hash_counter,
+        }
+    }
+
+    define_artifact_type! {
+        BoundedCounterArtifact {
+            content: Counter,
+            type_id: "bounded_counter",
+            hash: hash_counter,
+            validate: |c| {
+                if c.value <= 10 {
+                    Ok(())
+                } else {
+                    Err(ArtifactError::InvariantViolation("value > 10".into()))
+                }
+            },
+        }
+    }
+
+    define_artifact_type! {
+        SizedCounterArtifact {
+            content: Counter,
+            type_id: "sized_counter",
+            hash: hash_counter,
+            size: |c| c.value as usize,
+        }
+    }
+
+    #[test]
+    fn macro_defined_type_creates_artifacts() {
+        let artifact = Artifact::<CounterArtifact>::new(Counter { value: 99 }).unwrap();
+        assert_eq!(Artifact::<CounterArtifact>::type_id(), "counter");
+        assert_eq!(*artifact.hash(), hash_counter(&Counter { value: 99 }));
+    }
+
+    #[test]
+    fn macro_defined_validation_is_enforced() {
+        assert!(Artifact::<BoundedCounterArtifact>::new(Counter { value: 5 }).is_ok());
+        let result = Artifact::<BoundedCounterArtifact>::new(Counter { value: 11 });
+        assert!(matches!(result, Err(ArtifactError::InvariantViolation(_))));
+    }
+
+    #[test]
+    fn macro_defined_size_overrides_default() {
+        let plain = Artifact::<CounterArtifact>::new(Counter { value: 99 }).unwrap();
+        let sized = Artifact::<SizedCounterArtifact>::new(Counter { value: 99 }).unwrap();
+        assert_eq!(plain.estimated_size(), std::mem::size_of::<Counter>());
+        assert_eq!(sized.estimated_size(), 99);
+    }
+}
@@ -2,6 +2,7 @@
 //!
 //! Provides [`SymbolPath`] for hierarchical addressing of elements within artifacts.
 
+use serde::{Deserialize, Serialize};
 use std::fmt::{self, Display, Formatter};
 use std::str::FromStr;
 
@@ -13,7 +14,12 @@ use std::str::FromStr;
 /// # Examples
 /// - `["crate", "module", "function"]` → `crate.module.function`
 /// - `["config", "database", "host"]` → `config.database.host`
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+///
+/// # Wire Format
+/// Serializes as a sequence of segments (not the dotted form), so segments
+/// containing `.` survive a round-trip.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct SymbolPath(Vec<String>);
 
 impl SymbolPath {
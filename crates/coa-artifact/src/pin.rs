@@ -0,0 +1,68 @@
+//! Symbol-level revision pinning for deltas
+//!
+//! A [`StructuralDelta`](crate::StructuralDelta) normally binds to the hash of
+//! the whole artifact, so any edit elsewhere in the file makes it stale. A
+//! [`SymbolPin`] additionally records the hash of the *target symbol* the
+//! agent read, letting validation accept the delta as long as that symbol is
+//! unchanged even if unrelated parts of the artifact moved on.
+//!
+//! The pin mirrors a symbol reference (the delta target is the path, plus
+//! parent hash and optional revision); the symbol index supplies the current
+//! symbol hash at validation time.
+
+use crate::hash::ContentHash;
+use serde::{Deserialize, Serialize};
+
+/// Branch and commit a pinned symbol was read at
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PinRevision {
+    /// Branch name
+    pub branch: String,
+    /// Commit hash
+    pub commit: ContentHash,
+}
+
+impl PinRevision {
+    /// Create revision
+    #[inline]
+    #[must_use]
+    pub fn new(branch: impl Into<String>, commit: ContentHash) -> Self {
+        Self {
+            branch: branch.into(),
+            commit,
+        }
+    }
+}
+
+/// Symbol-level base binding carried by a delta
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SymbolPin {
+    /// Hash of the artifact the symbol was read from
+    pub parent_hash: ContentHash,
+    /// Hash of the target symbol's own content when it was read
+    pub symbol_hash: ContentHash,
+    /// Revision the symbol was read at
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<PinRevision>,
+}
+
+impl SymbolPin {
+    /// Pin to a symbol read from `parent_hash`
+    #[inline]
+    #[must_use]
+    pub fn new(parent_hash: ContentHash, symbol_hash: ContentHash) -> Self {
+        Self {
+            parent_hash,
+            symbol_hash,
+            revision: None,
+        }
+    }
+
+    /// Set revision
+    #[inline]
+    #[must_use]
+    pub fn with_revision(mut self, revision: PinRevision) -> Self {
+        self.revision = Some(revision);
+        self
+    }
+}
@@ -0,0 +1,546 @@
+//! Provenance metadata for artifacts
+//!
+//! [`ArtifactProvenance`] records who produced an artifact and from what:
+//! creating agent, task, graph node, originating intent, parent hashes and
+//! tool versions. It travels with [`Artifact`](crate::Artifact) and
+//! [`StructuralDelta`](crate::StructuralDelta) but is **not** part of the
+//! content hash - identical content produced by two agents is still the same
+//! artifact.
+//!
+//! Deltas opened through a [`DeltaVerifier`](crate::DeltaVerifier) carry the
+//! verified [`DeltaSigner`]; composition gathers every delta's signer, so a
+//! composed artifact names the agents that signed for each change.
+//!
+//! [`ProvenanceIndex`] answers "where did this come from?" by walking parent
+//! hashes back to the original inputs.
+//!
+//! [`CorrelationId`] ties one intent (and each of its tasks) together across
+//! the stack: orchestrator tasks, artifact provenance, composition and kernel
+//! events all carry it, so one agent action can be followed end to end.
+//!
+//! [`TenantId`] names the namespace a deployment isolates graphs, artifacts,
+//! symbols and events under; nothing is shared between two tenants.
+
+use crate::hash::ContentHash;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Identifier shared by everything done for one intent, optionally narrowed
+/// to one of its tasks
+///
+/// Renders as `intent` or `intent/task`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub struct CorrelationId {
+    intent: String,
+    task: Option<String>,
+}
+
+impl CorrelationId {
+    /// Identifier for an intent
+    #[must_use]
+    pub fn new(intent: impl Into<String>) -> Self {
+        Self {
+            intent: intent.into(),
+            task: None,
+        }
+    }
+
+    /// Identifier for a task of the same intent
+    #[must_use]
+    pub fn for_task(&self, task: impl Display) -> Self {
+        Self {
+            intent: self.intent.clone(),
+            task: Some(task.to_string()),
+        }
+    }
+
+    /// Intent part
+    #[inline]
+    #[must_use]
+    pub fn intent(&self) -> &str {
+        &self.intent
+    }
+
+    /// Task part, if narrowed to a task
+    #[inline]
+    #[must_use]
+    pub fn task(&self) -> Option<&str> {
+        self.task.as_deref()
+    }
+
+    /// Whether `other` is this identifier or, for an intent-wide
+    /// identifier, one of its tasks
+    #[must_use]
+    pub fn covers(&self, other: &Self) -> bool {
+        self.intent == other.intent && (self.task.is_none() || self.task == other.task)
+    }
+}
+
+impl Display for CorrelationId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.task {
+            Some(task) => write!(f, "{}/{task}", self.intent),
+            None => f.write_str(&self.intent),
+        }
+    }
+}
+
+impl From<String> for CorrelationId {
+    fn from(s: String) -> Self {
+        match s.split_once('/') {
+            Some((intent, task)) => Self::new(intent).for_task(task),
+            None => Self::new(s),
+        }
+    }
+}
+
+impl From<CorrelationId> for String {
+    fn from(id: CorrelationId) -> Self {
+        id.to_string()
+    }
+}
+
+impl FromStr for CorrelationId {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(s.to_string()))
+    }
+}
+
+/// Namespace that isolates one tenant's graphs, artifacts, symbols and
+/// events from every other tenant's
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TenantId(pub String);
+
+impl TenantId {
+    /// Identifier for a tenant
+    #[inline]
+    #[must_use]
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
+
+    /// Identifier as a string
+    #[inline]
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for TenantId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Agent whose verified signature covers a delta
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeltaSigner {
+    /// Signing agent
+    pub agent_id: String,
+    /// Digest of the signed envelope
+    pub digest: ContentHash,
+}
+
+/// Origin record for an artifact
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactProvenance {
+    /// Agent that produced the artifact
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
+    /// Task the agent was executing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<String>,
+    /// Execution graph node
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node_id: Option<String>,
+    /// Intent (user request / directive) that led to the artifact
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub intent: Option<String>,
+    /// Correlation of the intent or task that led to the artifact
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<CorrelationId>,
+    /// Agents whose deltas were composed into the artifact
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub contributors: BTreeSet<String>,
+    /// Verified delta signatures: the delta's own for a delta, every
+    /// composed delta's for a composition
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub signers: Vec<DeltaSigner>,
+    /// Hashes of the artifacts this one was derived from
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parents: Vec<ContentHash>,
+    /// Tool name → version used to produce the artifact
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tool_versions: BTreeMap<String, String>,
+    /// Creation time (milliseconds since Unix epoch)
+    pub created_at: u64,
+}
+
+impl ArtifactProvenance {
+    /// Create empty record stamped with the current time
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            created_at: now_millis(),
+            ..Self::default()
+        }
+    }
+
+    /// Set creating agent
+    #[must_use]
+    pub fn with_agent(mut self, agent_id: impl Into<String>) -> Self {
+        self.agent_id = Some(agent_id.into());
+        self
+    }
+
+    /// Set task id
+    #[must_use]
+    pub fn with_task(mut self, task_id: impl Into<String>) -> Self {
+        self.task_id = Some(task_id.into());
+        self
+    }
+
+    /// Set graph node id
+    #[must_use]
+    pub fn with_node(mut self, node_id: impl Into<String>) -> Self {
+        self.node_id = Some(node_id.into());
+        self
+    }
+
+    /// Set originating intent
+    #[must_use]
+    pub fn with_intent(mut self, intent: impl Into<String>) -> Self {
+        self.intent = Some(intent.into());
+        self
+    }
+
+    /// Set correlation
+    #[must_use]
+    pub fn with_correlation(mut self, id: CorrelationId) -> Self {
+        self.correlation_id = Some(id);
+        self
+    }
+
+    /// Add parent artifact hash
+    #[must_use]
+    pub fn with_parent(mut self, parent: ContentHash) -> Self {
+        self.parents.push(parent);
+        self
+    }
+
+    /// Record tool version
+    #[must_use]
+    pub fn with_tool(mut self, tool: impl Into<String>, version: impl Into<String>) -> Self {
+        self.tool_versions.insert(tool.into(), version.into());
+        self
+    }
+
+    /// Record for an artifact derived from `parent`
+    ///
+    /// Keeps agent, task, node, intent, correlation and tool versions; replaces parents
+    /// with `parent` and restamps the creation time.
+    #[must_use]
+    pub fn derive(&self, parent: ContentHash) -> Self {
+        Self {
+            contributors: BTreeSet::new(),
+            signers: Vec::new(),
+            parents: vec![parent],
+            created_at: now_millis(),
+            ..self.clone()
+        }
+    }
+
+    /// Record for the result of composing deltas onto `base`
+    ///
+    /// Context (task, node, intent, correlation, tools) comes from the base
+    /// record; every delta author is listed in `contributors`. If exactly one
+    /// agent contributed, it also becomes the creating agent. Delta signers
+    /// are kept in delta order. Without a base
+    /// correlation, the first correlated delta supplies it.
+    #[must_use]
+    pub fn compose<'a>(
+        base: Option<&ArtifactProvenance>,
+        base_hash: ContentHash,
+        deltas: impl IntoIterator<Item = &'a ArtifactProvenance>,
+    ) -> Self {
+        let mut record = base.map_or_else(Self::new, |b| b.derive(base_hash));
+        record.parents = vec![base_hash];
+
+        for delta in deltas {
+            if let Some(agent) = &delta.agent_id {
+                record.contributors.insert(agent.clone());
+            }
+            record.signers.extend(delta.signers.iter().cloned());
+            if record.correlation_id.is_none() {
+                record.correlation_id.clone_from(&delta.correlation_id);
+            }
+            for (tool, version) in &delta.tool_versions {
+                record
+                    .tool_versions
+                    .entry(tool.clone())
+                    .or_insert_with(|| version.clone());
+            }
+        }
+
+        if record.contributors.len() == 1 {
+            record.agent_id = record.contributors.iter().next().cloned();
+        }
+        record
+    }
+}
+
+/// Queryable store of provenance records keyed by content hash
+#[derive(Debug, Clone, Default)]
+pub struct ProvenanceIndex {
+    records: HashMap<ContentHash, ArtifactProvenance>,
+}
+
+impl ProvenanceIndex {
+    /// Create empty index
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record provenance for a hash (replaces existing)
+    pub fn insert(&mut self, hash: ContentHash, provenance: ArtifactProvenance) {
+        self.records.insert(hash, provenance);
+    }
+
+    /// Record an artifact's provenance, if it carries one
+    ///
+    /// Returns true if a record was stored.
+    pub fn record<T: crate::ArtifactType>(&mut self, artifact: &crate::Artifact<T>) -> bool {
+        match artifact.provenance() {
+            Some(provenance) => {
+                self.insert(*artifact.hash(), provenance.clone());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Provenance for a hash
+    #[inline]
+    #[must_use]
+    pub fn get(&self, hash: &ContentHash) -> Option<&ArtifactProvenance> {
+        self.records.get(hash)
+    }
+
+    /// Number of records
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Check if index is empty
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Lineage of `hash`: the artifact itself, then its ancestors
+    ///
+    /// Breadth-first over parent hashes; each ancestor appears once.
+    /// Ancestors without a record end the walk on that branch.
+    #[must_use]
+    pub fn lineage(&self, hash: &ContentHash) -> Vec<(ContentHash, &ArtifactProvenance)> {
+        let mut out = Vec::new();
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::from([*hash]);
+
+        while let Some(current) = queue.pop_front() {
+            if !seen.insert(current) {
+                continue;
+            }
+            if let Some(record) = self.records.get(&current) {
+                out.push((current, record));
+                queue.extend(record.parents.iter().copied());
+            }
+        }
+        out
+    }
+
+    /// Original inputs of `hash` (ancestors with no recorded parents)
+    #[must_use]
+    pub fn origins(&self, hash: &ContentHash) -> Vec<ContentHash> {
+        let mut out = Vec::new();
+        let mut seen = HashSet::new();
+        let mut stack = vec![*hash];
+
+        while let Some(current) = stack.pop() {
+            if !seen.insert(current) {
+                continue;
+            }
+            match self.records.get(&current) {
+                Some(record) if !record.parents.is_empty() => {
+                    stack.extend(record.parents.iter().copied());
+                }
+                _ => out.push(current),
+            }
+        }
+        out.sort();
+        out
+    }
+
+    /// Hashes produced or contributed to by an agent
+    #[must_use]
+    pub fn by_agent(&self, agent_id: &str) -> Vec<ContentHash> {
+        self.filter(|p| {
+            p.agent_id.as_deref() == Some(agent_id) || p.contributors.contains(agent_id)
+        })
+    }
+
+    /// Hashes produced for a task
+    #[must_use]
+    pub fn by_task(&self, task_id: &str) -> Vec<ContentHash> {
+        self.filter(|p| p.task_id.as_deref() == Some(task_id))
+    }
+
+    /// Hashes produced under a correlation (an intent-wide one matches all
+    /// of its tasks)
+    #[must_use]
+    pub fn by_correlation(&self, id: &CorrelationId) -> Vec<ContentHash> {
+        self.filter(|p| p.correlation_id.as_ref().is_some_and(|c| id.covers(c)))
+    }
+
+    /// Hashes produced by a graph node
+    #[must_use]
+    pub fn by_node(&self, node_id: &str) -> Vec<ContentHash> {
+        self.filter(|p| p.node_id.as_deref() == Some(node_id))
+    }
+
+    fn filter(&self, pred: impl Fn(&ArtifactProvenance) -> bool) -> Vec<ContentHash> {
+        let mut out: Vec<ContentHash> = self
+            .records
+            .iter()
+            .filter(|(_, p)| pred(p))
+            .map(|(h, _)| *h)
+            .collect();
+        out.sort();
+        out
+    }
+}
+
+/// Current time in milliseconds since Unix epoch
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn h(s: &str) -> ContentHash {
+        ContentHash::compute(s.as_bytes())
+    }
+
+    #[test]
+    fn derive_keeps_context_and_sets_parent() {
+        let root = ArtifactProvenance::new()
+            .with_agent("agent-1")
+            .with_task("task-7")
+            .with_tool("rustfmt", "1.8.0")
+            .with_parent(h("x"));
+
+        let child = root.derive(h("a"));
+        assert_eq!(child.agent_id.as_deref(), Some("agent-1"));
+        assert_eq!(child.task_id.as_deref(), Some("task-7"));
+        assert_eq!(child.parents, vec![h("a")]);
+        assert_eq!(child.tool_versions["rustfmt"], "1.8.0");
+    }
+
+    #[test]
+    fn compose_collects_contributors() {
+        let base = ArtifactProvenance::new().with_node("node-1");
+        let d1 = ArtifactProvenance::new().with_agent("a1");
+        let mut d2 = ArtifactProvenance::new().with_agent("a2");
+        d2.signers.push(DeltaSigner {
+            agent_id: "a2".to_string(),
+            digest: h("d2"),
+        });
+
+        let composed = ArtifactProvenance::compose(Some(&base), h("base"), [&d1, &d2]);
+        assert_eq!(composed.node_id.as_deref(), Some("node-1"));
+        assert_eq!(composed.parents, vec![h("base")]);
+        assert_eq!(composed.contributors.len(), 2);
+        assert!(composed.agent_id.is_none());
+        assert_eq!(composed.signers, d2.signers);
+        assert!(composed.derive(h("next")).signers.is_empty());
+
+        let single = ArtifactProvenance::compose(None, h("base"), [&d1]);
+        assert_eq!(single.agent_id.as_deref(), Some("a1"));
+    }
+
+    #[test]
+    fn correlation_round_trips_and_follows_composition() {
+        let intent = CorrelationId::new("run-1");
+        let task = intent.for_task("task-7");
+        assert_eq!(task.to_string(), "run-1/task-7");
+        assert_eq!(CorrelationId::from_str("run-1/task-7").unwrap(), task);
+        assert_eq!(serde_json::to_string(&task).unwrap(), "\"run-1/task-7\"");
+        assert!(intent.covers(&task));
+        assert!(!task.covers(&intent));
+
+        let delta = ArtifactProvenance::new().with_agent("a1").with_correlation(task.clone());
+        let composed = ArtifactProvenance::compose(None, h("base"), [&delta]);
+        assert_eq!(composed.correlation_id, Some(task));
+
+        let mut index = ProvenanceIndex::new();
+        index.insert(h("v1"), composed);
+        index.insert(h("v2"), ArtifactProvenance::new().with_correlation(CorrelationId::new("run-2")));
+        assert_eq!(index.by_correlation(&intent), vec![h("v1")]);
+    }
+
+    #[test]
+    fn index_traces_lineage_to_origins() {
+        let mut index = ProvenanceIndex::new();
+        index.insert(h("src"), ArtifactProvenance::new().with_intent("add login"));
+        index.insert(
+            h("v1"),
+            ArtifactProvenance::new()
+                .with_agent("a1")
+                .with_parent(h("src")),
+        );
+        index.insert(
+            h("v2"),
+            ArtifactProvenance::new()
+                .with_agent("a2")
+                .with_task("t1")
+                .with_parent(h("v1")),
+        );
+
+        let lineage: Vec<ContentHash> = index
+            .lineage(&h("v2"))
+            .into_iter()
+            .map(|(h, _)| h)
+            .collect();
+        assert_eq!(lineage, vec![h("v2"), h("v1"), h("src")]);
+        assert_eq!(index.origins(&h("v2")), vec![h("src")]);
+        assert_eq!(index.by_agent("a1"), vec![h("v1")]);
+        assert_eq!(index.by_task("t1"), vec![h("v2")]);
+        assert!(index.by_node("n").is_empty());
+    }
+
+    #[test]
+    fn serde_omits_empty_fields() {
+        let record = ArtifactProvenance::new().with_agent("a1");
+        let json = serde_json::to_string(&record).unwrap();
+        assert!(!json.contains("parents"));
+        let back: ArtifactProvenance = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, record);
+    }
+}
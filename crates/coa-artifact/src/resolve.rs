@@ -0,0 +1,108 @@
+//! Symbol resolution against artifact content
+//!
+//! Provides [`AddressableContent`] so deltas can check their target against
+//! the artifact's own structure (symbol table, JSON keys, document sections)
+//! before any transformation runs.
+
+use crate::path::SymbolPath;
+
+/// Maximum number of suggestions reported for a missing target
+pub const MAX_SUGGESTIONS: usize = 3;
+
+/// Content whose elements are addressable by [`SymbolPath`]
+///
+/// Implemented by content types that expose a navigable structure.
+pub trait AddressableContent {
+    /// Every path currently addressable in this content
+    ///
+    /// Ancestors must be listed alongside descendants (e.g. both
+    /// `server` and `server.port`).
+    fn symbol_paths(&self) -> Vec<SymbolPath>;
+
+    /// Check if path resolves to an element
+    ///
+    /// Default implementation scans [`symbol_paths`](Self::symbol_paths);
+    /// override when a direct lookup is cheaper.
+    fn resolves(&self, path: &SymbolPath) -> bool {
+        path.is_empty() || self.symbol_paths().iter().any(|p| p == path)
+    }
+
+    /// Paths declared as append-only regions (e.g. a generated tests module)
+    ///
+    /// Concurrent `Add` deltas targeting a region do not conflict; they are
+    /// merged in content-hash order. Defaults to none.
+    fn append_regions(&self) -> Vec<SymbolPath> {
+        Vec::new()
+    }
+}
+
+/// Find known paths closest to `target` (for "did you mean" hints)
+///
+/// Uses edit distance over the dotted form; candidates further than a third
+/// of the target length (minimum 2) are discarded.
+#[must_use]
+pub fn nearest_matches(target: &SymbolPath, known: &[SymbolPath]) -> Vec<SymbolPath> {
+    let target_str = target.to_string();
+    let threshold = (target_str.chars().count() / 3).max(2);
+
+    let mut scored: Vec<(usize, &SymbolPath)> = known
+        .iter()
+        .filter(|p| *p != target)
+        .map(|p| (edit_distance(&target_str, &p.to_string()), p))
+        .filter(|(d, _)| *d <= threshold)
+        .collect();
+    scored.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(b.1)));
+    scored
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, p)| p.clone())
+        .collect()
+}
+
+/// Levenshtein distance over chars
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        curr[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn p(s: &str) -> SymbolPath {
+        SymbolPath::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn edit_distance_basic() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("same", "same"), 0);
+    }
+
+    #[test]
+    fn nearest_matches_ranks_by_distance() {
+        let known = vec![p("server.port"), p("server.host"), p("database.url")];
+        let matches = nearest_matches(&p("server.prot"), &known);
+        assert_eq!(matches.first(), Some(&p("server.port")));
+        assert!(!matches.contains(&p("database.url")));
+    }
+
+    #[test]
+    fn nearest_matches_empty_when_far() {
+        let known = vec![p("alpha"), p("beta")];
+        assert!(nearest_matches(&p("completely_different"), &known).is_empty());
+    }
+}
@@ -0,0 +1,103 @@
+//! Source spans for sub-symbol conflict detection
+//!
+//! Path-prefix overlap treats two edits to the same function as conflicting
+//! even when they touch different statements. A [`NodeSpan`] records the byte
+//! range of the AST node a delta rewrites, so composition can compare the
+//! actual regions instead of the enclosing symbol.
+
+use crate::path::SymbolPath;
+use crate::resolve::AddressableContent;
+use serde::{Deserialize, Serialize};
+
+/// Half-open byte range `[start, end)` in an artifact's source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct NodeSpan {
+    /// First byte covered
+    pub start: usize,
+    /// One past the last byte covered
+    pub end: usize,
+}
+
+impl NodeSpan {
+    /// Create span, normalising reversed bounds
+    #[inline]
+    #[must_use]
+    pub fn new(start: usize, end: usize) -> Self {
+        Self {
+            start: start.min(end),
+            end: start.max(end),
+        }
+    }
+
+    /// Length in bytes
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Check if span covers no bytes
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Check if two spans share at least one byte
+    ///
+    /// Empty spans (insertion points) overlap a span strictly containing
+    /// them, or another insertion at the same point.
+    #[inline]
+    #[must_use]
+    pub fn overlaps(&self, other: &Self) -> bool {
+        match (self.is_empty(), other.is_empty()) {
+            (true, true) => self.start == other.start,
+            (true, false) => other.start < self.start && self.start < other.end,
+            (false, true) => self.start < other.start && other.start < self.end,
+            (false, false) => self.start < other.end && other.start < self.end,
+        }
+    }
+
+    /// Check if `other` lies entirely within this span
+    #[inline]
+    #[must_use]
+    pub fn contains(&self, other: &Self) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+}
+
+impl std::fmt::Display for NodeSpan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+/// Content whose addressable elements map to source spans
+pub trait SpannedContent: AddressableContent {
+    /// Byte range of the element at `path`, if it resolves
+    fn span_of(&self, path: &SymbolPath) -> Option<NodeSpan>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlap_is_half_open() {
+        let a = NodeSpan::new(0, 10);
+        assert!(a.overlaps(&NodeSpan::new(5, 15)));
+        assert!(!a.overlaps(&NodeSpan::new(10, 20)));
+        assert!(a.contains(&NodeSpan::new(2, 8)));
+        assert!(!a.contains(&NodeSpan::new(8, 12)));
+        assert_eq!(NodeSpan::new(7, 3), NodeSpan::new(3, 7));
+    }
+
+    #[test]
+    fn insertion_point_overlaps_enclosing_span() {
+        let body = NodeSpan::new(0, 10);
+        assert!(body.overlaps(&NodeSpan::new(4, 4)));
+        assert!(NodeSpan::new(4, 4).overlaps(&body));
+        assert!(!body.overlaps(&NodeSpan::new(10, 10)));
+        assert!(!body.overlaps(&NodeSpan::new(0, 0)));
+    }
+}
@@ -1,101 +1,492 @@
 //! Binary Artifact Type
 //!
-//! Simplest artifact type - raw byte content.
-//! Used for files that don't need structured parsing.
+//! Raw byte content for files that don't need structured parsing: models,
+//! images, archives.
+//!
+//! Content need not fit in memory. A [`BinaryContent`] is a sequence of
+//! segments, each either an in-memory buffer or a byte range of a file read
+//! on demand. Hashing, editing and egress all work one chunk at a time:
+//!
+//! - the content hash is a hash over per-chunk hashes, each computed lazily
+//!   on first use and then cached
+//! - [`BinaryContent::splice`] builds new content that shares every
+//!   untouched segment with the old one
+//! - [`BinaryContent::write_to`] streams the content out chunk by chunk
+//! - a [`BinaryPatch`] rebuilds new content from copied ranges of the old
+//!   one plus inserted bytes, so updates ship as compact
+//!   [`DeltaOperation::BinaryPatch`] deltas
+//!
+//! File-backed content assumes the file does not change while referenced;
+//! cached chunk hashes are not re-checked.
 
-use crate::artifact_type::{ArtifactContent, ArtifactType};
+use crate::artifact::{private, Artifact, ArtifactError, ArtifactType};
+use crate::delta::{DeltaError, DeltaOperation, StructuralDelta, TransformError, Transformation};
 use crate::hash::ContentHash;
+use crate::path::SymbolPath;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+/// Default chunk size for hashing and streaming (1 MiB)
+pub const DEFAULT_CHUNK_SIZE: usize = 1 << 20;
 
 /// Binary artifact marker type
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct BinaryArtifact;
 
+impl private::Sealed for BinaryArtifact {}
+
 impl ArtifactType for BinaryArtifact {
     type Content = BinaryContent;
 
+    /// Hash over the chunk hashes
+    ///
+    /// Content whose chunks cannot be read hashes to [`ContentHash::ZERO`];
+    /// [`Artifact::new`] rejects such content before hashing it.
     #[inline]
     fn hash(content: &Self::Content) -> ContentHash {
-        ContentHash::compute(&content.0)
+        content.root_hash().unwrap_or(ContentHash::ZERO)
     }
 
     const TYPE_ID: &'static str = "binary";
+
+    fn validate_content(content: &Self::Content) -> Result<(), ArtifactError> {
+        content
+            .root_hash()
+            .map(|_| ())
+            .map_err(|e| ArtifactError::InvariantViolation(format!("unreadable binary content: {e}")))
+    }
+
+    #[inline]
+    fn estimated_size(content: &Self::Content) -> usize {
+        content.memory_size()
+    }
 }
 
-/// Binary content - raw bytes
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct BinaryContent(Vec<u8>);
+impl BinaryArtifact {
+    /// Apply a delta to binary content
+    ///
+    /// `Add` and `Replace` swap in new content, `Remove` empties it,
+    /// `Transform` (e.g. [`BinarySplice`]) edits it chunk-wise and
+    /// `BinaryPatch` rebuilds it from an encoded [`BinaryPatch`]. Only the
+    /// segments a transform or patch touches are copied.
+    ///
+    /// # Errors
+    /// - `DeltaError::BaseMismatch` if the delta was authored against other content
+    /// - `DeltaError::TransformationFailed` if the transform fails, or the
+    ///   patch is malformed or fails hash verification
+    /// - `DeltaError::Artifact` if the result is unreadable
+    ///
+    /// The result's provenance is derived from the delta's (see
+    /// [`Artifact::successor`]).
+    pub fn apply_delta(
+        base: &Artifact<Self>,
+        delta: &StructuralDelta<Self>,
+    ) -> Result<Artifact<Self>, DeltaError> {
+        delta.validate_base(base)?;
+        let content = match delta.operation() {
+            DeltaOperation::Add(content) | DeltaOperation::Replace(content) => content.clone(),
+            DeltaOperation::Remove => BinaryContent::default(),
+            DeltaOperation::Transform(transform) => transform.apply(base.content())?,
+            DeltaOperation::BinaryPatch(encoded) => {
+                BinaryPatch::decode(encoded)?.apply(base.content())?
+            }
+        };
+        Ok(base.successor(content, delta)?)
+    }
+}
+
+/// Run of bytes backing part of a [`BinaryContent`]
+#[derive(Debug, Clone)]
+enum Segment {
+    /// `len` bytes of a shared buffer, starting at `start`
+    Memory {
+        bytes: Arc<[u8]>,
+        start: usize,
+        len: usize,
+    },
+    /// `len` bytes of a file, starting at `offset`
+    File {
+        path: Arc<Path>,
+        offset: u64,
+        len: u64,
+    },
+}
+
+impl Segment {
+    fn len(&self) -> u64 {
+        match self {
+            Self::Memory { len, .. } => *len as u64,
+            Self::File { len, .. } => *len,
+        }
+    }
+
+    /// Sub-range `[from, from + len)` of this segment
+    fn slice(&self, from: u64, len: u64) -> Self {
+        match self {
+            Self::Memory { bytes, start, .. } => Self::Memory {
+                bytes: Arc::clone(bytes),
+                start: start + to_usize(from),
+                len: to_usize(len),
+            },
+            Self::File { path, offset, .. } => Self::File {
+                path: Arc::clone(path),
+                offset: offset + from,
+                len,
+            },
+        }
+    }
+
+    /// Append bytes `[from, from + len)` of this segment to `out`
+    fn read_into(&self, from: u64, len: u64, out: &mut Vec<u8>) -> io::Result<()> {
+        match self {
+            Self::Memory { bytes, start, .. } => {
+                let begin = start + to_usize(from);
+                out.extend_from_slice(&bytes[begin..begin + to_usize(len)]);
+                Ok(())
+            }
+            Self::File { path, offset, .. } => {
+                let mut file = File::open(path)?;
+                file.seek(SeekFrom::Start(offset + from))?;
+                let before = out.len();
+                out.resize(before + to_usize(len), 0);
+                file.read_exact(&mut out[before..])
+            }
+        }
+    }
+}
+
+/// Binary content - raw bytes, in memory or file-backed
+#[derive(Debug, Clone)]
+pub struct BinaryContent {
+    segments: Arc<[Segment]>,
+    len: u64,
+    chunk_size: usize,
+    /// Lazily computed hash of each chunk
+    chunk_hashes: Arc<[OnceLock<ContentHash>]>,
+}
 
 impl BinaryContent {
     /// Create from byte vector
     #[inline]
     #[must_use]
     pub fn new(data: Vec<u8>) -> Self {
-        Self(data)
+        Self::from_segments(vec![memory_segment(data)], DEFAULT_CHUNK_SIZE)
     }
 
     /// Create from string
     #[inline]
     #[must_use]
     pub fn from_string(s: impl Into<String>) -> Self {
-        Self(s.into().into_bytes())
+        Self::new(s.into().into_bytes())
     }
 
-    /// Get reference to bytes
-    #[inline]
+    /// Create content backed by the file at `path`
+    ///
+    /// Nothing is read until the content is hashed, read or written out.
+    ///
+    /// # Errors
+    /// Returns error if the file's metadata cannot be read
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path: Arc<Path> = Arc::from(path.as_ref());
+        let len = std::fs::metadata(&path)?.len();
+        Ok(Self::from_segments(
+            vec![Segment::File { path, offset: 0, len }],
+            DEFAULT_CHUNK_SIZE,
+        ))
+    }
+
+    /// Copy a stream into the file at `spill` and back the content by it
+    ///
+    /// The stream is copied one chunk at a time, so it may be larger than
+    /// memory.
+    ///
+    /// # Errors
+    /// Returns error if reading the stream or writing the file fails
+    pub fn from_reader(mut reader: impl Read, spill: impl Into<PathBuf>) -> io::Result<Self> {
+        let spill = spill.into();
+        let mut file = File::create(&spill)?;
+        let mut buf = vec![0u8; DEFAULT_CHUNK_SIZE];
+        loop {
+            let read = reader.read(&mut buf)?;
+            if read == 0 {
+                break;
+            }
+            file.write_all(&buf[..read])?;
+        }
+        file.sync_all()?;
+        Self::from_file(spill)
+    }
+
+    /// Use `chunk_size` bytes per chunk (hashes depend on it)
+    ///
+    /// # Panics
+    /// Panics if `chunk_size` is zero
     #[must_use]
-    pub fn data(&self) -> &[u8] {
-        &self.0
+    pub fn with_chunk_size(self, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "chunk size must be positive");
+        Self::from_segments(self.segments.to_vec(), chunk_size)
     }
 
-    /// Get mutable reference to bytes
+    fn from_segments(segments: Vec<Segment>, chunk_size: usize) -> Self {
+        let segments: Vec<Segment> = segments.into_iter().filter(|s| s.len() > 0).collect();
+        let len: u64 = segments.iter().map(Segment::len).sum();
+        let chunks = to_usize(len.div_ceil(chunk_size as u64));
+        Self {
+            segments: segments.into(),
+            len,
+            chunk_size,
+            chunk_hashes: (0..chunks).map(|_| OnceLock::new()).collect(),
+        }
+    }
+
+    /// Get content length in bytes
     #[inline]
     #[must_use]
-    pub fn data_mut(&mut self) -> &mut Vec<u8> {
-        &mut self.0
+    pub fn len(&self) -> u64 {
+        self.len
     }
 
-    /// Convert to bytes (consumes self)
+    /// Check if empty
     #[inline]
     #[must_use]
-    pub fn into_bytes(self) -> Vec<u8> {
-        self.0
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
     }
 
-    /// Try convert to string
-    ///
-    /// # Errors
-    /// Returns error if bytes are not valid UTF-8
+    /// Bytes per chunk
     #[inline]
-    pub fn to_string(&self) -> Result<String, std::string::FromUtf8Error> {
-        String::from_utf8(self.0.clone())
+    #[must_use]
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
     }
 
-    /// Check if content is valid UTF-8
+    /// Number of chunks
     #[inline]
     #[must_use]
-    pub fn is_utf8(&self) -> bool {
-        std::str::from_utf8(&self.0).is_ok()
+    pub fn chunk_count(&self) -> usize {
+        self.chunk_hashes.len()
     }
 
-    /// Get content length
-    #[inline]
+    /// Check if any bytes are read from files
     #[must_use]
-    pub fn len(&self) -> usize {
-        self.0.len()
+    pub fn is_file_backed(&self) -> bool {
+        self.segments.iter().any(|s| matches!(s, Segment::File { .. }))
     }
 
-    /// Check if empty
-    #[inline]
+    /// Borrow the bytes, if they are one contiguous in-memory run
     #[must_use]
-    pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+    pub fn as_slice(&self) -> Option<&[u8]> {
+        match &*self.segments {
+            [] => Some(&[]),
+            [Segment::Memory { bytes, start, len }] => Some(&bytes[*start..start + len]),
+            _ => None,
+        }
+    }
+
+    /// Approximate bytes held in memory (file-backed bytes excluded)
+    #[must_use]
+    pub fn memory_size(&self) -> usize {
+        let buffered: usize = self
+            .segments
+            .iter()
+            .map(|s| match s {
+                Segment::Memory { len, .. } => *len,
+                Segment::File { .. } => 0,
+            })
+            .sum();
+        std::mem::size_of::<Self>()
+            + buffered
+            + self.chunk_hashes.len() * std::mem::size_of::<OnceLock<ContentHash>>()
+    }
+
+    /// Read `len` bytes starting at `offset`
+    ///
+    /// # Errors
+    /// - `InvalidInput` if the range extends past the end
+    /// - Any error reading a backing file
+    pub fn read_range(&self, offset: u64, len: u64) -> io::Result<Vec<u8>> {
+        let end = offset
+            .checked_add(len)
+            .filter(|end| *end <= self.len)
+            .ok_or_else(|| out_of_bounds(offset..offset.saturating_add(len), self.len))?;
+        let mut out = Vec::with_capacity(to_usize(len));
+        let mut position = 0;
+        for segment in self.segments.iter() {
+            let segment_end = position + segment.len();
+            if segment_end > offset && position < end {
+                let from = offset.max(position) - position;
+                let to = end.min(segment_end) - position;
+                segment.read_into(from, to - from, &mut out)?;
+            }
+            position = segment_end;
+        }
+        Ok(out)
+    }
+
+    /// Bytes of chunk `index`
+    ///
+    /// # Errors
+    /// - `InvalidInput` if there is no such chunk
+    /// - Any error reading a backing file
+    pub fn chunk(&self, index: usize) -> io::Result<Vec<u8>> {
+        let offset = index as u64 * self.chunk_size as u64;
+        if index >= self.chunk_count() {
+            return Err(out_of_bounds(offset..offset, self.len));
+        }
+        self.read_range(offset, (self.len - offset).min(self.chunk_size as u64))
+    }
+
+    /// Chunks in order
+    pub fn chunks(&self) -> impl Iterator<Item = io::Result<Vec<u8>>> + '_ {
+        (0..self.chunk_count()).map(|i| self.chunk(i))
+    }
+
+    /// Hash of chunk `index`, computed on first use
+    ///
+    /// # Errors
+    /// Same as [`chunk`](Self::chunk)
+    pub fn chunk_hash(&self, index: usize) -> io::Result<ContentHash> {
+        if let Some(hash) = self.chunk_hashes.get(index).and_then(OnceLock::get) {
+            return Ok(*hash);
+        }
+        let hash = ContentHash::compute(&self.chunk(index)?);
+        Ok(*self.chunk_hashes[index].get_or_init(|| hash))
+    }
+
+    /// Hash over all chunk hashes
+    ///
+    /// # Errors
+    /// Same as [`chunk`](Self::chunk)
+    pub fn root_hash(&self) -> io::Result<ContentHash> {
+        let mut hashes = Vec::with_capacity(self.chunk_count() * 32);
+        for index in 0..self.chunk_count() {
+            hashes.extend_from_slice(self.chunk_hash(index)?.as_bytes());
+        }
+        Ok(ContentHash::compute(&hashes))
+    }
+
+    /// Content with bytes `range` replaced by `replacement`
+    ///
+    /// Segments outside `range` are shared with `self`; chunk hashes of the
+    /// result are computed afresh on demand.
+    ///
+    /// # Errors
+    /// Returns `TransformError::InvalidInput` if `range` is reversed or
+    /// extends past the end
+    pub fn splice(&self, range: Range<u64>, replacement: Vec<u8>) -> Result<Self, TransformError> {
+        if range.start > range.end || range.end > self.len {
+            return Err(TransformError::InvalidInput(
+                out_of_bounds(range, self.len).to_string(),
+            ));
+        }
+        let replacement = memory_segment(replacement);
+        let mut segments = Vec::with_capacity(self.segments.len() + 2);
+        let mut position = 0;
+        let mut inserted = false;
+        for segment in self.segments.iter() {
+            let segment_end = position + segment.len();
+            if position < range.start {
+                let keep = range.start.min(segment_end) - position;
+                segments.push(segment.slice(0, keep));
+            }
+            if !inserted && segment_end >= range.start {
+                segments.push(replacement.clone());
+                inserted = true;
+            }
+            if segment_end > range.end {
+                let from = range.end.max(position) - position;
+                segments.push(segment.slice(from, segment_end - position - from));
+            }
+            position = segment_end;
+        }
+        if !inserted {
+            segments.push(replacement);
+        }
+        Ok(Self::from_segments(segments, self.chunk_size))
+    }
+
+    /// Stream the content to `writer` chunk by chunk
+    ///
+    /// Returns the number of bytes written.
+    ///
+    /// # Errors
+    /// Returns error if reading a backing file or writing fails
+    pub fn write_to(&self, writer: &mut impl Write) -> io::Result<u64> {
+        for chunk in self.chunks() {
+            writer.write_all(&chunk?)?;
+        }
+        writer.flush()?;
+        Ok(self.len)
+    }
+
+    /// Write the content to a file at `path`
+    ///
+    /// The bytes go to a sibling temporary file that then replaces `path`,
+    /// so `path` may back this content itself.
+    ///
+    /// # Errors
+    /// Returns error if reading or writing fails
+    pub fn write_to_path(&self, path: impl AsRef<Path>) -> io::Result<u64> {
+        let path = path.as_ref();
+        let mut staging = path.as_os_str().to_owned();
+        staging.push(".partial");
+        let staging = PathBuf::from(staging);
+        let written = self.write_to(&mut io::BufWriter::new(File::create(&staging)?))?;
+        std::fs::rename(&staging, path)?;
+        Ok(written)
+    }
+
+    /// Segments covering bytes `range` (must lie within the content)
+    fn segments_in(&self, range: Range<u64>, out: &mut Vec<Segment>) {
+        let mut position = 0;
+        for segment in self.segments.iter() {
+            let segment_end = position + segment.len();
+            if segment_end > range.start && position < range.end {
+                let from = range.start.max(position) - position;
+                let to = range.end.min(segment_end) - position;
+                out.push(segment.slice(from, to - from));
+            }
+            position = segment_end;
+        }
+    }
+
+    /// Read the whole content into memory
+    ///
+    /// # Errors
+    /// Returns error if reading a backing file fails
+    pub fn to_vec(&self) -> io::Result<Vec<u8>> {
+        self.read_range(0, self.len)
     }
 }
 
-impl ArtifactContent for BinaryContent {
-    #[inline]
-    fn approximate_size(&self) -> usize {
-        std::mem::size_of::<Self>() + self.0.capacity()
+impl PartialEq for BinaryContent {
+    /// Equal if lengths and, chunk by chunk, hashes match
+    ///
+    /// Unreadable content equals nothing.
+    fn eq(&self, other: &Self) -> bool {
+        if let (Some(a), Some(b)) = (self.as_slice(), other.as_slice()) {
+            return a == b;
+        }
+        self.len == other.len
+            && match (self.root_hash(), other.with_same_chunks(self).root_hash()) {
+                (Ok(a), Ok(b)) => a == b,
+                _ => false,
+            }
+    }
+}
+
+impl BinaryContent {
+    /// `self`, chunked like `other`
+    fn with_same_chunks(&self, other: &Self) -> Self {
+        if self.chunk_size == other.chunk_size {
+            self.clone()
+        } else {
+            self.clone().with_chunk_size(other.chunk_size)
+        }
     }
 }
 
@@ -129,27 +520,524 @@ impl From<&str> for BinaryContent {
     }
 }
 
+/// Replace a byte range of binary content
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinarySplice {
+    /// Bytes to replace
+    pub range: Range<u64>,
+    /// Bytes to put in their place
+    pub replacement: Vec<u8>,
+}
+
+impl BinarySplice {
+    /// Create splice replacing `range` with `replacement`
+    #[inline]
+    #[must_use]
+    pub fn new(range: Range<u64>, replacement: impl Into<Vec<u8>>) -> Self {
+        Self {
+            range,
+            replacement: replacement.into(),
+        }
+    }
+}
+
+impl Transformation<BinaryArtifact> for BinarySplice {
+    fn apply(&self, content: &BinaryContent) -> Result<BinaryContent, TransformError> {
+        content.splice(self.range.clone(), self.replacement.clone())
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "splice bytes {}..{} with {} bytes",
+            self.range.start,
+            self.range.end,
+            self.replacement.len()
+        )
+    }
+}
+
+/// Block size the diff matches on
+const PATCH_BLOCK: usize = WINDOW as usize;
+
+/// [`PATCH_BLOCK`] as rolling hash weight
+const WINDOW: u32 = 64;
+
+/// Candidate old offsets remembered per block hash
+const PATCH_CANDIDATES: usize = 8;
+
+/// Leading bytes of an encoded patch
+const PATCH_MAGIC: &[u8; 4] = b"COAP";
+
+/// Encoded patch format version
+const PATCH_VERSION: u8 = 1;
+
+/// Step of a [`BinaryPatch`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchOp {
+    /// Copy `len` bytes of the source starting at `offset`
+    Copy {
+        /// Source offset
+        offset: u64,
+        /// Bytes copied
+        len: u64,
+    },
+    /// Insert literal bytes
+    Insert(Vec<u8>),
+}
+
+impl PatchOp {
+    /// Bytes this step contributes to the target
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        match self {
+            Self::Copy { len, .. } => *len,
+            Self::Insert(bytes) => bytes.len() as u64,
+        }
+    }
+
+    /// Check if the step contributes nothing
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Binary patch turning one [`BinaryContent`] into another
+///
+/// The target is described as copies of source ranges interleaved with
+/// inserted bytes, found by matching fixed-size blocks of the source with a
+/// rolling hash and extending each match in both directions. Source and
+/// target root hashes (at the target's chunk size) are carried along and
+/// checked on application, so a patch never silently applies to the wrong
+/// base or yields corrupted content.
+///
+/// Diffing reads both contents into memory; applying shares the copied
+/// ranges with the source without reading them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinaryPatch {
+    /// Root hash of the source
+    pub source: ContentHash,
+    /// Root hash of the target
+    pub target: ContentHash,
+    /// Length of the target in bytes
+    pub target_len: u64,
+    /// Chunk size both hashes are computed with
+    pub chunk_size: usize,
+    /// Steps producing the target
+    pub ops: Vec<PatchOp>,
+}
+
+impl BinaryPatch {
+    /// Compute patch from `old` to `new`
+    ///
+    /// # Errors
+    /// Returns error if reading either content fails
+    pub fn diff(old: &BinaryContent, new: &BinaryContent) -> io::Result<Self> {
+        let chunk_size = new.chunk_size;
+        let source = old.with_same_chunks(new).root_hash()?;
+        let target = new.root_hash()?;
+        let old_bytes = old.to_vec()?;
+        let new_bytes = new.to_vec()?;
+        Ok(Self {
+            source,
+            target,
+            target_len: new.len,
+            chunk_size,
+            ops: diff_ops(&old_bytes, &new_bytes),
+        })
+    }
+
+    /// Compute patch and wrap it in a whole-artifact delta against `base`
+    ///
+    /// # Errors
+    /// Returns error if reading either content fails
+    pub fn delta(
+        base: &Artifact<BinaryArtifact>,
+        new: &BinaryContent,
+    ) -> io::Result<StructuralDelta<BinaryArtifact>> {
+        let patch = Self::diff(base.content(), new)?;
+        Ok(StructuralDelta::new(
+            SymbolPath::root(),
+            DeltaOperation::BinaryPatch(patch.encode()),
+            *base.hash(),
+        ))
+    }
+
+    /// Bytes inserted literally (the rest is copied)
+    #[must_use]
+    pub fn inserted_bytes(&self) -> u64 {
+        self.ops
+            .iter()
+            .filter(|op| matches!(op, PatchOp::Insert(_)))
+            .map(PatchOp::len)
+            .sum()
+    }
+
+    /// Apply patch to `base`
+    ///
+    /// # Errors
+    /// - `TransformError::StateConflict` if `base` is not the patch source
+    ///   or the result does not hash to the patch target
+    /// - `TransformError::InvalidInput` if a copy reaches past the end of
+    ///   `base`, or the steps do not add up to the target length
+    pub fn apply(&self, base: &BinaryContent) -> Result<BinaryContent, TransformError> {
+        let base = if base.chunk_size == self.chunk_size {
+            base.clone()
+        } else {
+            base.clone().with_chunk_size(self.chunk_size)
+        };
+        let source = base
+            .root_hash()
+            .map_err(|e| TransformError::Failed(e.to_string()))?;
+        if source != self.source {
+            return Err(TransformError::StateConflict(format!(
+                "patch source {} does not match base {}",
+                self.source.short(),
+                source.short()
+            )));
+        }
+
+        let mut segments = Vec::with_capacity(self.ops.len());
+        let mut len = 0u64;
+        for op in &self.ops {
+            match op {
+                PatchOp::Copy { offset, len: copied } => {
+                    let end = offset
+                        .checked_add(*copied)
+                        .filter(|end| *end <= base.len)
+                        .ok_or_else(|| {
+                            TransformError::InvalidInput(
+                                out_of_bounds(*offset..offset.saturating_add(*copied), base.len)
+                                    .to_string(),
+                            )
+                        })?;
+                    base.segments_in(*offset..end, &mut segments);
+                }
+                PatchOp::Insert(bytes) => segments.push(memory_segment(bytes.clone())),
+            }
+            len += op.len();
+        }
+        if len != self.target_len {
+            return Err(TransformError::InvalidInput(format!(
+                "patch produces {len} bytes, expected {}",
+                self.target_len
+            )));
+        }
+
+        let result = BinaryContent::from_segments(segments, self.chunk_size);
+        let target = result
+            .root_hash()
+            .map_err(|e| TransformError::Failed(e.to_string()))?;
+        if target != self.target {
+            return Err(TransformError::StateConflict(format!(
+                "patched content hashes to {}, expected {}",
+                target.short(),
+                self.target.short()
+            )));
+        }
+        Ok(result)
+    }
+
+    /// Encode as zstd-compressed bytes for [`DeltaOperation::BinaryPatch`]
+    ///
+    /// # Panics
+    /// Panics if zstd fails to compress an in-memory buffer
+    #[must_use]
+    pub fn encode(&self) -> Vec<u8> {
+        let mut raw = Vec::new();
+        raw.extend_from_slice(self.source.as_bytes());
+        raw.extend_from_slice(self.target.as_bytes());
+        put_varint(&mut raw, self.target_len);
+        put_varint(&mut raw, self.chunk_size as u64);
+        put_varint(&mut raw, self.ops.len() as u64);
+        for op in &self.ops {
+            match op {
+                PatchOp::Copy { offset, len } => {
+                    raw.push(0);
+                    put_varint(&mut raw, *offset);
+                    put_varint(&mut raw, *len);
+                }
+                PatchOp::Insert(bytes) => {
+                    raw.push(1);
+                    put_varint(&mut raw, bytes.len() as u64);
+                    raw.extend_from_slice(bytes);
+                }
+            }
+        }
+        let mut out = Vec::with_capacity(raw.len() / 2 + 8);
+        out.extend_from_slice(PATCH_MAGIC);
+        out.push(PATCH_VERSION);
+        out.extend(
+            zstd::stream::encode_all(raw.as_slice(), zstd::DEFAULT_COMPRESSION_LEVEL)
+                .expect("zstd compression of an in-memory buffer"),
+        );
+        out
+    }
+
+    /// Decode bytes produced by [`encode`](Self::encode)
+    ///
+    /// # Errors
+    /// Returns `TransformError::InvalidInput` if the bytes are not a patch
+    pub fn decode(encoded: &[u8]) -> Result<Self, TransformError> {
+        let malformed = |what: &str| TransformError::InvalidInput(format!("malformed binary patch: {what}"));
+        let body = encoded
+            .strip_prefix(PATCH_MAGIC.as_slice())
+            .ok_or_else(|| malformed("bad magic"))?;
+        let (&version, body) = body.split_first().ok_or_else(|| malformed("truncated"))?;
+        if version != PATCH_VERSION {
+            return Err(malformed(&format!("unsupported version {version}")));
+        }
+        let raw = zstd::stream::decode_all(body).map_err(|e| malformed(&e.to_string()))?;
+
+        let mut reader = PatchReader { bytes: &raw };
+        let source = reader.hash().ok_or_else(|| malformed("truncated"))?;
+        let target = reader.hash().ok_or_else(|| malformed("truncated"))?;
+        let target_len = reader.varint().ok_or_else(|| malformed("truncated"))?;
+        let chunk_size = reader
+            .varint()
+            .map(to_usize)
+            .filter(|size| *size > 0)
+            .ok_or_else(|| malformed("bad chunk size"))?;
+        let count = reader.varint().ok_or_else(|| malformed("truncated"))?;
+        let mut ops = Vec::new();
+        for _ in 0..count {
+            let op = match reader.take(1).ok_or_else(|| malformed("truncated"))? {
+                [0] => {
+                    let offset = reader.varint();
+                    let len = reader.varint();
+                    offset
+                        .zip(len)
+                        .map(|(offset, len)| PatchOp::Copy { offset, len })
+                }
+                [1] => reader
+                    .varint()
+                    .and_then(|len| reader.take(to_usize(len)))
+                    .map(|bytes| PatchOp::Insert(bytes.to_vec())),
+                _ => return Err(malformed("unknown step")),
+            };
+            ops.push(op.ok_or_else(|| malformed("truncated"))?);
+        }
+        if !reader.bytes.is_empty() {
+            return Err(malformed("trailing bytes"));
+        }
+        Ok(Self {
+            source,
+            target,
+            target_len,
+            chunk_size,
+            ops,
+        })
+    }
+}
+
+impl Transformation<BinaryArtifact> for BinaryPatch {
+    fn apply(&self, content: &BinaryContent) -> Result<BinaryContent, TransformError> {
+        BinaryPatch::apply(self, content)
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "binary patch to {} ({} steps, {} bytes inserted)",
+            self.target.short(),
+            self.ops.len(),
+            self.inserted_bytes()
+        )
+    }
+}
+
+/// Copy/insert steps rebuilding `new` from `old`
+fn diff_ops(old: &[u8], new: &[u8]) -> Vec<PatchOp> {
+    let mut ops = Vec::new();
+    if old.len() < PATCH_BLOCK || new.len() < PATCH_BLOCK {
+        if !new.is_empty() {
+            ops.push(PatchOp::Insert(new.to_vec()));
+        }
+        return ops;
+    }
+
+    let mut blocks: HashMap<u32, Vec<usize>> = HashMap::new();
+    for offset in (0..=old.len() - PATCH_BLOCK).step_by(PATCH_BLOCK) {
+        let candidates = blocks.entry(RollingHash::new(&old[offset..offset + PATCH_BLOCK]).value()).or_default();
+        if candidates.len() < PATCH_CANDIDATES {
+            candidates.push(offset);
+        }
+    }
+
+    let mut literal_start = 0;
+    let mut position = 0;
+    let mut rolling = RollingHash::new(&new[..PATCH_BLOCK]);
+    while position + PATCH_BLOCK <= new.len() {
+        let window = &new[position..position + PATCH_BLOCK];
+        let found = blocks
+            .get(&rolling.value())
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|offset| &old[*offset..offset + PATCH_BLOCK] == window)
+            .map(|offset| {
+                let forward = old[offset..]
+                    .iter()
+                    .zip(&new[position..])
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                (offset, forward)
+            })
+            .max_by_key(|(_, forward)| *forward);
+
+        let Some((mut offset, forward)) = found else {
+            if position + PATCH_BLOCK < new.len() {
+                rolling.roll(new[position], new[position + PATCH_BLOCK]);
+            }
+            position += 1;
+            continue;
+        };
+        let mut start = position;
+        while start > literal_start && offset > 0 && old[offset - 1] == new[start - 1] {
+            start -= 1;
+            offset -= 1;
+        }
+        if start > literal_start {
+            ops.push(PatchOp::Insert(new[literal_start..start].to_vec()));
+        }
+        let end = position + forward;
+        push_copy(&mut ops, offset as u64, (end - start) as u64);
+        literal_start = end;
+        position = end;
+        if position + PATCH_BLOCK <= new.len() {
+            rolling = RollingHash::new(&new[position..position + PATCH_BLOCK]);
+        }
+    }
+    if literal_start < new.len() {
+        ops.push(PatchOp::Insert(new[literal_start..].to_vec()));
+    }
+    ops
+}
+
+/// Push a copy step, merging it into a directly preceding one
+fn push_copy(ops: &mut Vec<PatchOp>, offset: u64, len: u64) {
+    if let Some(PatchOp::Copy { offset: previous, len: previous_len }) = ops.last_mut() {
+        if *previous + *previous_len == offset {
+            *previous_len += len;
+            return;
+        }
+    }
+    ops.push(PatchOp::Copy { offset, len });
+}
+
+/// Adler-style rolling checksum over a [`PATCH_BLOCK`]-byte window
+struct RollingHash {
+    a: u32,
+    b: u32,
+}
+
+impl RollingHash {
+    fn new(window: &[u8]) -> Self {
+        let mut hash = Self { a: 0, b: 0 };
+        for (byte, weight) in window.iter().zip((1..=WINDOW).rev()) {
+            hash.a = hash.a.wrapping_add(u32::from(*byte));
+            hash.b = hash.b.wrapping_add(u32::from(*byte).wrapping_mul(weight));
+        }
+        hash
+    }
+
+    /// Slide the window one byte: drop `out`, append `into`
+    fn roll(&mut self, out: u8, into: u8) {
+        self.a = self.a.wrapping_sub(u32::from(out)).wrapping_add(u32::from(into));
+        self.b = self
+            .b
+            .wrapping_sub(u32::from(out).wrapping_mul(WINDOW))
+            .wrapping_add(self.a);
+    }
+
+    fn value(&self) -> u32 {
+        (self.a & 0xffff) | (self.b << 16)
+    }
+}
+
+fn put_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value.to_le_bytes()[0] | 0x80);
+        value >>= 7;
+    }
+    out.push(value.to_le_bytes()[0]);
+}
+
+/// Cursor over a decompressed patch
+struct PatchReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> PatchReader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if len > self.bytes.len() {
+            return None;
+        }
+        let (head, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Some(head)
+    }
+
+    fn hash(&mut self) -> Option<ContentHash> {
+        self.take(32).and_then(|bytes| ContentHash::from_slice(bytes).ok())
+    }
+
+    fn varint(&mut self) -> Option<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+        None
+    }
+}
+
+fn memory_segment(bytes: Vec<u8>) -> Segment {
+    let len = bytes.len();
+    Segment::Memory {
+        bytes: Arc::from(bytes),
+        start: 0,
+        len,
+    }
+}
+
+fn out_of_bounds(range: Range<u64>, len: u64) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("byte range {}..{} outside content of {len} bytes", range.start, range.end),
+    )
+}
+
+/// Convert an in-range offset or length to `usize`
+///
+/// Callers only pass values bounded by an in-memory buffer or by one chunk.
+fn to_usize(value: u64) -> usize {
+    usize::try_from(value).unwrap_or(usize::MAX)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::provenance::ArtifactProvenance;
 
     #[test]
     fn binary_content_new() {
         let content = BinaryContent::new(vec![1, 2, 3]);
-        assert_eq!(content.data(), &[1, 2, 3]);
+        assert_eq!(content.as_slice(), Some(&[1u8, 2, 3][..]));
     }
 
     #[test]
     fn binary_content_from_string() {
         let content = BinaryContent::from_string("hello");
-        assert_eq!(content.data(), b"hello");
-        assert!(content.is_utf8());
+        assert_eq!(content.as_slice(), Some(&b"hello"[..]));
     }
 
     #[test]
     fn binary_content_size() {
         let content = BinaryContent::new(vec![0u8; 100]);
-        assert!(content.approximate_size() >= 100);
+        assert!(BinaryArtifact::estimated_size(&content) >= 100);
     }
 
     #[test]
@@ -163,7 +1051,7 @@ mod tests {
     fn binary_artifact_hash() {
         let content = BinaryContent::new(b"test".to_vec());
         let hash = BinaryArtifact::hash(&content);
-        assert!(!hash.is_null());
+        assert!(!hash.is_zero());
 
         // Same content -> same hash
         let content2 = BinaryContent::new(b"test".to_vec());
@@ -175,4 +1063,145 @@ mod tests {
         let hash3 = BinaryArtifact::hash(&content3);
         assert_ne!(hash, hash3);
     }
+
+    #[test]
+    fn file_backed_content_is_read_chunk_wise() {
+        let dir = tempfile::tempdir().unwrap();
+        let bytes: Vec<u8> = (0..=255u8).cycle().take(10_000).collect();
+        let content = BinaryContent::from_reader(&bytes[..], dir.path().join("model.bin"))
+            .unwrap()
+            .with_chunk_size(4096);
+
+        assert!(content.is_file_backed());
+        assert!(content.as_slice().is_none());
+        assert!(content.memory_size() < 1024);
+        assert_eq!(content.chunk_count(), 3);
+        assert_eq!(content.chunk(2).unwrap(), bytes[8192..]);
+        assert_eq!(content.read_range(4000, 200).unwrap(), bytes[4000..4200]);
+        assert!(content.read_range(9_990, 20).is_err());
+
+        // Same bytes hash the same regardless of backing
+        let in_memory = BinaryContent::new(bytes.clone()).with_chunk_size(4096);
+        assert_eq!(BinaryArtifact::hash(&content), BinaryArtifact::hash(&in_memory));
+        assert_eq!(content, in_memory);
+
+        let out = dir.path().join("copy.bin");
+        assert_eq!(content.write_to_path(&out).unwrap(), 10_000);
+        assert_eq!(std::fs::read(out).unwrap(), bytes);
+    }
+
+    #[test]
+    fn splice_shares_untouched_segments() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("image.bin");
+        std::fs::write(&path, b"0123456789").unwrap();
+        let content = BinaryContent::from_file(&path).unwrap();
+
+        let edited = content.splice(2..5, b"ab".to_vec()).unwrap();
+        assert_eq!(edited.to_vec().unwrap(), b"01ab56789");
+        assert!(edited.is_file_backed());
+        let appended = edited.splice(9..9, b"!".to_vec()).unwrap();
+        assert_eq!(appended.to_vec().unwrap(), b"01ab56789!");
+        let prefixed = appended.splice(0..0, b">".to_vec()).unwrap();
+        assert_eq!(prefixed.to_vec().unwrap(), b">01ab56789!");
+        assert!(content.splice(8..12, Vec::new()).is_err());
+
+        // Egress may overwrite the file backing the content
+        prefixed.write_to_path(&path).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b">01ab56789!");
+    }
+
+    #[test]
+    fn apply_delta_splices_and_verifies_base() {
+        let base = Artifact::<BinaryArtifact>::new(BinaryContent::from("hello world")).unwrap();
+        let delta = StructuralDelta::new(
+            SymbolPath::root(),
+            DeltaOperation::Transform(Box::new(BinarySplice::new(0..5, "HELLO"))),
+            *base.hash(),
+        );
+        let result = BinaryArtifact::apply_delta(&base, &delta).unwrap();
+        assert_eq!(result.content().to_vec().unwrap(), b"HELLO world");
+        assert!(result.verify());
+
+        assert!(matches!(
+            BinaryArtifact::apply_delta(&result, &delta),
+            Err(DeltaError::BaseMismatch { .. })
+        ));
+        let bad = StructuralDelta::new(
+            SymbolPath::root(),
+            DeltaOperation::Transform(Box::new(BinarySplice::new(20..30, ""))),
+            *base.hash(),
+        );
+        assert!(matches!(
+            BinaryArtifact::apply_delta(&base, &bad),
+            Err(DeltaError::TransformationFailed(_))
+        ));
+    }
+
+    /// Deterministic pseudo-random bytes
+    fn noise(len: usize, seed: u64) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+                (state >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn binary_patch_is_compact_and_verified() {
+        let old = noise(64 * 1024, 1);
+        let mut new = old.clone();
+        new.splice(10_000..10_000, b"inserted".iter().copied());
+        new[40_000..40_100].copy_from_slice(&noise(100, 2));
+        new.truncate(60_000);
+        let old = BinaryContent::new(old).with_chunk_size(4096);
+        let new = BinaryContent::new(new).with_chunk_size(4096);
+
+        let patch = BinaryPatch::diff(&old, &new).unwrap();
+        assert!(patch.inserted_bytes() < 512);
+        let encoded = patch.encode();
+        assert!(encoded.len() < 1024);
+        assert_eq!(BinaryPatch::decode(&encoded).unwrap(), patch);
+        assert_eq!(patch.apply(&old).unwrap(), new);
+
+        assert!(matches!(patch.apply(&new), Err(TransformError::StateConflict(_))));
+        assert!(matches!(
+            BinaryPatch::decode(&encoded[..encoded.len() / 2]),
+            Err(TransformError::InvalidInput(_))
+        ));
+        let mut forged = patch.clone();
+        forged.ops.push(PatchOp::Copy { offset: 0, len: 0 });
+        forged.ops[0] = PatchOp::Insert(vec![0; to_usize(forged.ops[0].len())]);
+        assert!(matches!(forged.apply(&old), Err(TransformError::StateConflict(_))));
+    }
+
+    #[test]
+    fn apply_delta_applies_binary_patch_over_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("model.bin");
+        let bytes = noise(8 * 1024, 3);
+        std::fs::write(&path, &bytes).unwrap();
+        let base = Artifact::<BinaryArtifact>::new(BinaryContent::from_file(&path).unwrap()).unwrap();
+
+        let mut updated = bytes;
+        updated[100..108].copy_from_slice(b"weights!");
+        let updated = BinaryContent::new(updated);
+        let delta = BinaryPatch::delta(&base, &updated)
+            .unwrap()
+            .with_provenance(ArtifactProvenance::new().with_agent("trainer"));
+        assert!(matches!(delta.operation(), DeltaOperation::BinaryPatch(p) if p.len() < 256));
+
+        let result = BinaryArtifact::apply_delta(&base, &delta).unwrap();
+        assert_eq!(result.content(), &updated);
+        let provenance = result.provenance().unwrap();
+        assert_eq!(provenance.agent_id.as_deref(), Some("trainer"));
+        assert_eq!(provenance.parents, vec![*base.hash()]);
+        assert!(result.content().is_file_backed());
+        assert!(matches!(
+            BinaryArtifact::apply_delta(&result, &delta),
+            Err(DeltaError::BaseMismatch { .. })
+        ));
+    }
 }
@@ -6,6 +6,7 @@
 use std::collections::HashMap;
 
 use crate::artifact_type::ArtifactContent;
+use crate::extract::{symbol_extractor, SymbolExtractor};
 use crate::hash::ContentHash;
 use crate::merkle::ArtifactMerkleTree;
 
@@ -372,15 +373,53 @@ fn build_symbol_table(
     match language {
         Language::Rust => build_rust_symbols(&root, source, &mut table, None),
         Language::Python => build_python_symbols(&root, source, &mut table, None),
-        _ => {
+        _ => match registered_extractor(language) {
+            Some(extractor) => build_extracted_symbols(extractor.as_ref(), source, &mut table),
             // Generic symbol extraction for other languages
-            build_generic_symbols(&root, source, &mut table, None);
-        }
+            None => build_generic_symbols(&root, source, &mut table, None),
+        },
     }
 
     table
 }
 
+/// Extractor registered for the language's name or one of its extensions
+fn registered_extractor(language: Language) -> Option<std::sync::Arc<dyn SymbolExtractor>> {
+    symbol_extractor(&language.to_string())
+        .or_else(|| language.extensions().iter().find_map(|ext| symbol_extractor(ext)))
+}
+
+/// Build symbols with a registered [`SymbolExtractor`]
+fn build_extracted_symbols(extractor: &dyn SymbolExtractor, source: &str, table: &mut SymbolTable) {
+    for symbol in extractor.extract(source) {
+        table.add(SymbolInfo {
+            kind: extracted_kind(&symbol.kind),
+            span: symbol.span.start..symbol.span.end,
+            parent: symbol.parent,
+            visibility: Visibility::Public,
+            name: symbol.name,
+        });
+    }
+}
+
+/// Map a language-specific kind onto the closest [`SymbolKind`]
+fn extracted_kind(kind: &str) -> SymbolKind {
+    match kind.to_ascii_lowercase().as_str() {
+        "fn" | "fun" | "func" | "function" => SymbolKind::Function,
+        "method" => SymbolKind::Method,
+        "struct" => SymbolKind::Struct,
+        "enum" => SymbolKind::Enum,
+        "trait" | "interface" | "protocol" => SymbolKind::Trait,
+        "class" | "type" | "typealias" => SymbolKind::Type,
+        "const" | "constant" | "let" => SymbolKind::Constant,
+        "module" | "mod" | "package" | "namespace" => SymbolKind::Module,
+        "import" | "use" => SymbolKind::Import,
+        "field" | "property" => SymbolKind::Field,
+        "variant" | "case" => SymbolKind::Variant,
+        _ => SymbolKind::Variable,
+    }
+}
+
 /// Build Merkle tree from AST nodes
 fn build_ast_merkle_tree(tree: &tree_sitter::Tree, source: &str) -> ArtifactMerkleTree {
     let mut leaves = Vec::new();
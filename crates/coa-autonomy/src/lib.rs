@@ -0,0 +1,197 @@
+//! COA Autonomy Policy
+//!
+//! Autonomy levels and their policy semantics, shared by the kernel (which
+//! enforces ceilings and isolation) and the orchestrator (which assigns
+//! levels to tasks), so both layers read a level the same way.
+//!
+//! - [`AutonomyLevel`]: L0 (human in the loop) to L5 (fully autonomous)
+//! - [`AutonomyCeiling`]: Highest level a deployment or graph admits
+
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// Autonomy levels (embedded in node types)
+///
+/// Levels are ordered: a higher level grants every capability of the
+/// levels below it.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+pub enum AutonomyLevel {
+    /// Level 0: Full human-in-the-loop
+    L0,
+    /// Level 1: Approval before merge
+    L1,
+    /// Level 2: Auto code, human merge
+    L2,
+    /// Level 3: Auto merge in sandbox
+    #[default]
+    L3,
+    /// Level 4: Auto merge + test deploy
+    L4,
+    /// Level 5: Full autonomous within boundary
+    L5,
+}
+
+impl AutonomyLevel {
+    /// All levels, lowest first
+    pub const ALL: [Self; 6] = [Self::L0, Self::L1, Self::L2, Self::L3, Self::L4, Self::L5];
+
+    /// Get numeric value
+    #[inline]
+    #[must_use]
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Self::L0 => 0,
+            Self::L1 => 1,
+            Self::L2 => 2,
+            Self::L3 => 3,
+            Self::L4 => 4,
+            Self::L5 => 5,
+        }
+    }
+
+    /// Check if this level can auto-merge
+    #[inline]
+    #[must_use]
+    pub fn can_auto_merge(self) -> bool {
+        self >= Self::L3
+    }
+
+    /// Check if this level requires human approval
+    #[inline]
+    #[must_use]
+    pub fn requires_human_approval(self) -> bool {
+        self < Self::L2
+    }
+
+    /// Check if work at this level must run in a separate process
+    ///
+    /// Levels that merge without a human are isolated from the kernel.
+    #[inline]
+    #[must_use]
+    pub fn requires_process_isolation(self) -> bool {
+        self.can_auto_merge()
+    }
+}
+
+impl From<AutonomyLevel> for u8 {
+    fn from(level: AutonomyLevel) -> Self {
+        level.as_u8()
+    }
+}
+
+impl TryFrom<u8> for AutonomyLevel {
+    type Error = AutonomyParseError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Self::ALL
+            .get(usize::from(value))
+            .copied()
+            .ok_or(AutonomyParseError::OutOfRange(value))
+    }
+}
+
+impl Display for AutonomyLevel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "L{}", self.as_u8())
+    }
+}
+
+/// Parses `L3`, `l3` or `3`
+impl FromStr for AutonomyLevel {
+    type Err = AutonomyParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.strip_prefix(['L', 'l']).unwrap_or(s);
+        digits
+            .parse::<u8>()
+            .map_err(|_| AutonomyParseError::Invalid(s.to_string()))
+            .and_then(Self::try_from)
+    }
+}
+
+/// Maximum autonomy ceiling - can be configured per deployment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AutonomyCeiling {
+    /// Highest admitted level
+    pub max_level: AutonomyLevel,
+}
+
+impl Default for AutonomyCeiling {
+    fn default() -> Self {
+        Self {
+            max_level: AutonomyLevel::L5,
+        }
+    }
+}
+
+impl AutonomyCeiling {
+    /// Ceiling admitting levels up to `max_level`
+    #[inline]
+    #[must_use]
+    pub fn new(max_level: AutonomyLevel) -> Self {
+        Self { max_level }
+    }
+
+    /// Check if `level` is within the ceiling
+    #[inline]
+    #[must_use]
+    pub fn check(&self, level: AutonomyLevel) -> bool {
+        level <= self.max_level
+    }
+
+    /// Lower `level` to the ceiling if it exceeds it
+    #[inline]
+    #[must_use]
+    pub fn clamp(&self, level: AutonomyLevel) -> AutonomyLevel {
+        level.min(self.max_level)
+    }
+}
+
+/// Errors converting into an [`AutonomyLevel`]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AutonomyParseError {
+    /// Numeric level above L5
+    #[error("autonomy level out of range: {0} (expected 0-5)")]
+    OutOfRange(u8),
+
+    /// Text is not a level
+    #[error("invalid autonomy level: {0:?}")]
+    Invalid(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_round_trip_through_numbers_text_and_serde() {
+        for level in AutonomyLevel::ALL {
+            assert_eq!(AutonomyLevel::try_from(level.as_u8()), Ok(level));
+            assert_eq!(level.to_string().parse(), Ok(level));
+            let json = serde_json::to_string(&level).unwrap();
+            assert_eq!(json, format!("\"{level}\""));
+            assert_eq!(serde_json::from_str::<AutonomyLevel>(&json).unwrap(), level);
+        }
+        assert_eq!("4".parse(), Ok(AutonomyLevel::L4));
+        assert_eq!(AutonomyLevel::try_from(6), Err(AutonomyParseError::OutOfRange(6)));
+        assert_eq!("high".parse::<AutonomyLevel>(), Err(AutonomyParseError::Invalid("high".into())));
+    }
+
+    #[test]
+    fn capabilities_follow_level_order() {
+        assert!(AutonomyLevel::L1.requires_human_approval());
+        assert!(!AutonomyLevel::L2.requires_human_approval());
+        assert!(!AutonomyLevel::L2.can_auto_merge());
+        assert!(AutonomyLevel::L3.can_auto_merge());
+        assert!(AutonomyLevel::L5.requires_process_isolation());
+
+        let ceiling = AutonomyCeiling::new(AutonomyLevel::L3);
+        assert!(ceiling.check(AutonomyLevel::L3));
+        assert!(!ceiling.check(AutonomyLevel::L4));
+        assert_eq!(ceiling.clamp(AutonomyLevel::L5), AutonomyLevel::L3);
+        assert!(AutonomyCeiling::default().check(AutonomyLevel::L5));
+    }
+}
@@ -0,0 +1,423 @@
+//! Composition result memoization
+//!
+//! Retry loops and simulator replays often compose the same delta batch onto
+//! the same base again. [`CompositionCache`] keys each run by
+//! `(strategy config, base hash, delta batch hash)` and returns the stored
+//! result artifact instead of recomputing.
+//!
+//! Validation is never cached: it depends on the [`SymbolRefIndex`], which
+//! changes between runs, so every call re-validates before a cached artifact
+//! is served. Only successful compositions are cached. Batches containing
+//! [`DeltaOperation::Transform`] have no stable content identity and always
+//! bypass the cache.
+
+use crate::strategy::{CompositionError, CompositionStrategy, Validation};
+use coa_artifact::{Artifact, ArtifactType, ContentHash, DeltaOperation, StructuralDelta};
+use coa_symbol::SymbolRefIndex;
+use moka::sync::Cache;
+use std::any::Any;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Cache key for one composition run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CompositionKey {
+    /// Hash of the strategy's [`CompositionStrategy::config_id`]
+    pub strategy: ContentHash,
+    /// Hash of the base artifact
+    pub base: ContentHash,
+    /// Hash of the delta batch (see [`CompositionCache::batch_hash`])
+    pub batch: ContentHash,
+}
+
+/// Result of a (possibly cached) composition
+#[derive(Debug, Clone)]
+pub struct CachedComposition<T: ArtifactType> {
+    /// Composed artifact
+    pub artifact: Artifact<T>,
+    /// Validation report from the strategy (always freshly computed)
+    pub validation: Validation,
+    /// Whether the result was served from the cache
+    pub hit: bool,
+}
+
+impl<T: ArtifactType> CachedComposition<T> {
+    /// Hash of the composed artifact
+    #[inline]
+    #[must_use]
+    pub fn result_hash(&self) -> &ContentHash {
+        self.artifact.hash()
+    }
+}
+
+/// Cache hit/miss statistics
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompositionCacheStats {
+    /// Compositions served from the cache
+    pub hits: u64,
+    /// Compositions computed and stored
+    pub misses: u64,
+    /// Compositions that could not be cached (e.g. `Transform` deltas)
+    pub uncacheable: u64,
+    /// Entries currently cached
+    pub entries: u64,
+}
+
+impl CompositionCacheStats {
+    /// Fraction of cacheable lookups served from the cache
+    #[inline]
+    #[must_use]
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            return 0.0;
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let rate = self.hits as f64 / lookups as f64;
+        rate
+    }
+}
+
+/// Memoization layer for [`CompositionStrategy`] runs
+///
+/// Cheap to clone; clones share entries and statistics.
+#[derive(Debug, Clone)]
+pub struct CompositionCache {
+    inner: Cache<CompositionKey, Arc<dyn Any + Send + Sync>>,
+    hits: Arc<AtomicU64>,
+    misses: Arc<AtomicU64>,
+    uncacheable: Arc<AtomicU64>,
+}
+
+impl CompositionCache {
+    /// Create cache holding at most `max_entries` results
+    #[inline]
+    #[must_use]
+    pub fn new(max_entries: u64) -> Self {
+        Self {
+            inner: Cache::new(max_entries),
+            hits: Arc::new(AtomicU64::new(0)),
+            misses: Arc::new(AtomicU64::new(0)),
+            uncacheable: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Content hash identifying a delta batch
+    ///
+    /// Covers artifact type, and for each delta in order: target, operation,
+    /// content hash, base hash, ordering hint and provenance. Descriptions
+    /// are ignored. Returns `None` if any delta is a `Transform`.
+    #[must_use]
+    pub fn batch_hash<T: ArtifactType>(deltas: &[StructuralDelta<T>]) -> Option<ContentHash> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(T::TYPE_ID.as_bytes());
+        bytes.push(0);
+
+        for delta in deltas {
+            bytes.extend_from_slice(delta.target().to_string().as_bytes());
+            bytes.push(0);
+            match delta.operation() {
+                DeltaOperation::Add(content) => {
+                    bytes.push(b'a');
+                    bytes.extend_from_slice(T::hash(content).as_bytes());
+                }
+                DeltaOperation::Remove => bytes.push(b'r'),
+                DeltaOperation::Replace(content) => {
+                    bytes.push(b'p');
+                    bytes.extend_from_slice(T::hash(content).as_bytes());
+                }
+                DeltaOperation::BinaryPatch(patch) => {
+                    bytes.push(b'b');
+                    bytes.extend_from_slice(ContentHash::compute(patch).as_bytes());
+                }
+                DeltaOperation::Transform(_) => return None,
+            }
+            bytes.extend_from_slice(delta.base_hash().as_bytes());
+            match delta.order() {
+                Some(order) => {
+                    bytes.push(1);
+                    bytes.extend_from_slice(&order.to_le_bytes());
+                }
+                None => bytes.push(0),
+            }
+            if let Some(provenance) = delta.provenance() {
+                bytes.extend_from_slice(&serde_json::to_vec(provenance).ok()?);
+            }
+            bytes.push(0);
+        }
+
+        Some(ContentHash::compute(&bytes))
+    }
+
+    /// Validate and compose, reusing a previous result when possible
+    ///
+    /// # Errors
+    /// Returns the strategy's validation error, or its compose error on a
+    /// miss; errors are not cached
+    pub fn compose<T, S>(
+        &self,
+        strategy: &S,
+        base: &Artifact<T>,
+        deltas: &[StructuralDelta<T>],
+        index: &SymbolRefIndex,
+    ) -> Result<CachedComposition<T>, CompositionError>
+    where
+        T: ArtifactType,
+        S: CompositionStrategy,
+    {
+        let validation = strategy.validate(deltas, index)?;
+        let Some(key) = Self::key(strategy, base, deltas) else {
+            self.uncacheable.fetch_add(1, Ordering::Relaxed);
+            let artifact = strategy.compose(base, deltas)?;
+            return Ok(CachedComposition {
+                artifact,
+                validation,
+                hit: false,
+            });
+        };
+
+        if let Some(artifact) = self.get::<T>(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(CachedComposition {
+                artifact,
+                validation,
+                hit: true,
+            });
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let artifact = strategy.compose(base, deltas)?;
+        self.inner.insert(key, Arc::new(artifact.clone()));
+        Ok(CachedComposition {
+            artifact,
+            validation,
+            hit: false,
+        })
+    }
+
+    /// Cache key for composing `deltas` onto `base` with `strategy`
+    ///
+    /// Returns `None` if the batch is uncacheable.
+    #[must_use]
+    pub fn key<T, S>(
+        strategy: &S,
+        base: &Artifact<T>,
+        deltas: &[StructuralDelta<T>],
+    ) -> Option<CompositionKey>
+    where
+        T: ArtifactType,
+        S: CompositionStrategy,
+    {
+        Some(CompositionKey {
+            strategy: ContentHash::compute(strategy.config_id().as_bytes()),
+            base: *base.hash(),
+            batch: Self::batch_hash(deltas)?,
+        })
+    }
+
+    /// Look up a cached artifact
+    ///
+    /// The caller is responsible for validating the batch first.
+    #[must_use]
+    pub fn get<T: ArtifactType>(&self, key: &CompositionKey) -> Option<Artifact<T>> {
+        let entry = self.inner.get(key)?;
+        entry.downcast_ref::<Artifact<T>>().cloned()
+    }
+
+    /// Drop every cached result
+    #[inline]
+    pub fn clear(&self) {
+        self.inner.invalidate_all();
+    }
+
+    /// Current statistics
+    #[must_use]
+    pub fn stats(&self) -> CompositionCacheStats {
+        self.inner.run_pending_tasks();
+        CompositionCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            uncacheable: self.uncacheable.load(Ordering::Relaxed),
+            entries: self.inner.entry_count(),
+        }
+    }
+}
+
+impl Default for CompositionCache {
+    fn default() -> Self {
+        Self::new(1_000)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::{ConflictKind, Granularity, Parallelism};
+    use coa_artifact::SymbolPath;
+    use coa_symbol::SymbolRef;
+    use std::str::FromStr;
+    use std::sync::atomic::AtomicUsize;
+
+    #[derive(Debug, Clone)]
+    struct TestArtifact;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct TestContent(String);
+
+    impl coa_artifact::__private::Sealed for TestArtifact {}
+
+    impl ArtifactType for TestArtifact {
+        type Content = TestContent;
+
+        fn hash(content: &Self::Content) -> ContentHash {
+            ContentHash::compute(content.0.as_bytes())
+        }
+
+        const TYPE_ID: &'static str = "test";
+    }
+
+    /// Returns the base unchanged and counts compose calls
+    ///
+    /// Rejects deltas whose target is referenced in the index.
+    #[derive(Debug, Default)]
+    struct CountingStrategy {
+        calls: AtomicUsize,
+        mode: u8,
+    }
+
+    impl CompositionStrategy for CountingStrategy {
+        fn validate<T: ArtifactType>(
+            &self,
+            deltas: &[StructuralDelta<T>],
+            index: &SymbolRefIndex,
+        ) -> Result<Validation, CompositionError> {
+            if deltas
+                .iter()
+                .any(|d| !index.references_to(d.target().segments()).is_empty())
+            {
+                return Err(CompositionError::validation_failed_simple(
+                    ConflictKind::InvalidDependencies,
+                    "target is referenced",
+                ));
+            }
+            Ok(Validation::minimal())
+        }
+
+        fn compose<T: ArtifactType>(
+            &self,
+            base: &Artifact<T>,
+            _deltas: &[StructuralDelta<T>],
+        ) -> Result<Artifact<T>, CompositionError> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            Ok(base.clone())
+        }
+
+        fn parallelism(&self) -> Parallelism {
+            Parallelism::None
+        }
+
+        fn granularity(&self) -> Granularity {
+            Granularity::Node
+        }
+
+        fn name(&self) -> &'static str {
+            "Counting"
+        }
+
+        fn config_id(&self) -> String {
+            format!("Counting:{}", self.mode)
+        }
+    }
+
+    fn base() -> Artifact<TestArtifact> {
+        Artifact::new(TestContent("base".into())).unwrap()
+    }
+
+    fn add(target: &str, value: &str) -> StructuralDelta<TestArtifact> {
+        StructuralDelta::new(
+            SymbolPath::from_str(target).unwrap(),
+            DeltaOperation::Add(TestContent(value.into())),
+            *base().hash(),
+        )
+    }
+
+    #[test]
+    fn repeated_batch_is_served_from_cache() {
+        let cache = CompositionCache::new(16);
+        let strategy = CountingStrategy::default();
+        let index = SymbolRefIndex::new();
+        let deltas = vec![add("a", "1"), add("b", "2")];
+
+        let first = cache.compose(&strategy, &base(), &deltas, &index).unwrap();
+        let second = cache.compose(&strategy, &base(), &deltas.clone(), &index).unwrap();
+
+        assert!(!first.hit);
+        assert!(second.hit);
+        assert_eq!(first.result_hash(), second.result_hash());
+        assert_eq!(strategy.calls.load(Ordering::Relaxed), 1);
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+        assert!((stats.hit_rate() - 0.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn different_batch_misses() {
+        let cache = CompositionCache::new(16);
+        let strategy = CountingStrategy::default();
+        let index = SymbolRefIndex::new();
+
+        cache.compose(&strategy, &base(), &[add("a", "1")], &index).unwrap();
+        cache.compose(&strategy, &base(), &[add("a", "2")], &index).unwrap();
+
+        assert_eq!(strategy.calls.load(Ordering::Relaxed), 2);
+        assert_eq!(cache.stats().hits, 0);
+    }
+
+    #[test]
+    fn batch_hash_depends_on_order_but_not_description() {
+        let a = add("a", "1");
+        let b = add("b", "2");
+        let ab = CompositionCache::batch_hash(&[a.clone(), b.clone()]);
+        let ba = CompositionCache::batch_hash(&[b, a]);
+        assert!(ab.is_some());
+        assert_ne!(ab, ba);
+    }
+
+    #[test]
+    fn index_change_is_revalidated_on_hit() {
+        let cache = CompositionCache::new(16);
+        let strategy = CountingStrategy::default();
+        let index = SymbolRefIndex::new();
+        let deltas = vec![add("a", "1")];
+
+        cache.compose(&strategy, &base(), &deltas, &index).unwrap();
+        index.add_reference(
+            &["a".to_string()],
+            SymbolRef::new(vec!["b".to_string()], *base().hash()),
+        );
+
+        let err = cache.compose(&strategy, &base(), &deltas, &index).unwrap_err();
+        assert!(matches!(err, CompositionError::ValidationFailed { .. }));
+        assert_eq!(cache.stats().hits, 0);
+    }
+
+    #[test]
+    fn strategy_config_is_part_of_key() {
+        let cache = CompositionCache::new(16);
+        let first = CountingStrategy::default();
+        let second = CountingStrategy {
+            mode: 1,
+            ..CountingStrategy::default()
+        };
+        let index = SymbolRefIndex::new();
+        let deltas = vec![add("a", "1")];
+
+        cache.compose(&first, &base(), &deltas, &index).unwrap();
+        let other = cache.compose(&second, &base(), &deltas, &index).unwrap();
+
+        assert!(!other.hit);
+        assert_eq!(second.calls.load(Ordering::Relaxed), 1);
+        assert_eq!(cache.stats().entries, 2);
+    }
+}
@@ -0,0 +1,718 @@
+//! Strategy combinators
+//!
+//! A [`StrategyPolicy`] composes one batch with several built-in strategies:
+//!
+//! - a strategy name: `"ordered"`
+//! - a fallback chain, trying each member until one validates:
+//!   `{"fallback": ["commutative", "ordered", "single_writer"]}`
+//! - a partition, routing each delta by the longest route prefix of its
+//!   target: `{"partitioned": {"routes": {"ui": "commutative"}, "default": "single_writer"}}`
+//!
+//! Policies nest and deserialize from JSON or TOML policy files.
+//! [`StrategyRegistry::build`](crate::StrategyRegistry::build) resolves a
+//! policy into a [`ComposedStrategy`], whose validations record the members
+//! used under [`SELECTED_STRATEGY_KEY`] in [`ValidationMetadata::custom`].
+
+use crate::cache::CompositionCache;
+use crate::strategy::{
+    CompositionCost, CompositionError, CompositionStrategy, ConflictKind, Granularity,
+    OrderingConstraint, Parallelism, Validation, ValidationDiagnostic, ValidationMetadata,
+};
+use crate::{
+    CommutativeBatchStrategy, HybridCompositionStrategy, OrderedCompositionStrategy,
+    SingleWriterStrategy,
+};
+use coa_artifact::{
+    Artifact, ArtifactType, ContentHash, DeltaOperation, StructuralDelta, SymbolPath,
+};
+use coa_symbol::SymbolRefIndex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, PoisonError};
+
+/// [`ValidationMetadata::custom`] key describing the members a
+/// [`ComposedStrategy`] used
+///
+/// A strategy name for a plain member, `{"fallback": <member>, "rejected":
+/// [..]}` for a fallback chain and `{"partitioned": {<prefix>: <member>}}`
+/// for a partition, listing only partitions that received deltas. The
+/// default partition is keyed by `""`.
+pub const SELECTED_STRATEGY_KEY: &str = "selected_strategy";
+
+/// Composition policy over registered strategies
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum StrategyPolicy {
+    /// Built-in strategy by registry name
+    Named(String),
+
+    /// Members in preference order
+    Fallback {
+        /// Tried in order until one validates
+        fallback: Vec<StrategyPolicy>,
+    },
+
+    /// Strategies per subtree
+    Partitioned {
+        /// Routes and default
+        partitioned: PartitionPolicy,
+    },
+}
+
+/// Routes of a [`StrategyPolicy::Partitioned`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PartitionPolicy {
+    /// Policy per symbol path prefix, e.g. `"ui.widgets"`
+    pub routes: BTreeMap<String, StrategyPolicy>,
+
+    /// Policy for deltas matching no route
+    pub default: Box<StrategyPolicy>,
+}
+
+impl StrategyPolicy {
+    /// Built-in strategy by name
+    #[must_use]
+    pub fn named(name: impl Into<String>) -> Self {
+        Self::Named(name.into())
+    }
+
+    /// Fallback chain over `members`
+    #[must_use]
+    pub fn fallback<P: Into<Self>>(members: impl IntoIterator<Item = P>) -> Self {
+        Self::Fallback {
+            fallback: members.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Partition by path prefix
+    #[must_use]
+    pub fn partitioned<K: Into<String>, P: Into<Self>>(
+        routes: impl IntoIterator<Item = (K, P)>,
+        default: impl Into<Self>,
+    ) -> Self {
+        Self::Partitioned {
+            partitioned: PartitionPolicy {
+                routes: routes
+                    .into_iter()
+                    .map(|(prefix, policy)| (prefix.into(), policy.into()))
+                    .collect(),
+                default: Box::new(default.into()),
+            },
+        }
+    }
+}
+
+impl From<&str> for StrategyPolicy {
+    fn from(name: &str) -> Self {
+        Self::named(name)
+    }
+}
+
+impl From<String> for StrategyPolicy {
+    fn from(name: String) -> Self {
+        Self::Named(name)
+    }
+}
+
+/// Strategy built from a [`StrategyPolicy`]
+#[derive(Debug)]
+pub struct ComposedStrategy {
+    policy: StrategyPolicy,
+    root: Node,
+}
+
+impl ComposedStrategy {
+    /// Resolve `policy`, accepting names for which `known` holds
+    pub(crate) fn resolve(
+        policy: &StrategyPolicy,
+        known: impl Fn(&str) -> bool + Copy,
+    ) -> Result<Self, CompositionError> {
+        Ok(Self {
+            policy: policy.clone(),
+            root: Node::resolve(policy, known)?,
+        })
+    }
+
+    /// Policy this strategy was built from
+    #[must_use]
+    pub fn policy(&self) -> &StrategyPolicy {
+        &self.policy
+    }
+}
+
+impl CompositionStrategy for ComposedStrategy {
+    fn validate<T: ArtifactType>(
+        &self,
+        deltas: &[StructuralDelta<T>],
+        index: &SymbolRefIndex,
+    ) -> Result<Validation, CompositionError> {
+        let (mut validation, selected) = self.root.validate(deltas, index)?;
+        validation
+            .metadata
+            .custom
+            .insert(SELECTED_STRATEGY_KEY.to_string(), selected);
+        Ok(validation)
+    }
+
+    fn compose<T: ArtifactType>(
+        &self,
+        base: &Artifact<T>,
+        deltas: &[StructuralDelta<T>],
+    ) -> Result<Artifact<T>, CompositionError> {
+        self.root.compose(base, deltas)
+    }
+
+    fn parallelism(&self) -> Parallelism {
+        self.root.parallelism()
+    }
+
+    fn granularity(&self) -> Granularity {
+        self.root.granularity()
+    }
+
+    fn name(&self) -> &'static str {
+        "Composed"
+    }
+
+    fn config_id(&self) -> String {
+        format!("{}:{:?}", self.name(), self.policy)
+    }
+}
+
+#[derive(Debug)]
+enum Node {
+    SingleWriter(SingleWriterStrategy),
+    Ordered(OrderedCompositionStrategy),
+    Commutative(CommutativeBatchStrategy),
+    Hybrid(HybridCompositionStrategy),
+    Fallback(Fallback),
+    Partitioned(Partitioned),
+}
+
+/// Dispatch `$call` to the built-in strategy of a leaf node
+macro_rules! leaf {
+    ($node:expr, $strategy:ident => $call:expr, $($other:pat => $otherwise:expr),+) => {
+        match $node {
+            Node::SingleWriter($strategy) => $call,
+            Node::Ordered($strategy) => $call,
+            Node::Commutative($strategy) => $call,
+            Node::Hybrid($strategy) => $call,
+            $($other => $otherwise,)+
+        }
+    };
+}
+
+impl Node {
+    fn resolve(
+        policy: &StrategyPolicy,
+        known: impl Fn(&str) -> bool + Copy,
+    ) -> Result<Self, CompositionError> {
+        match policy {
+            StrategyPolicy::Named(name) => {
+                if !known(name) {
+                    return Err(CompositionError::InvalidPolicy(format!(
+                        "strategy '{name}' is not registered"
+                    )));
+                }
+                match name.as_str() {
+                    "single_writer" => Ok(Self::SingleWriter(SingleWriterStrategy::new())),
+                    "ordered" => Ok(Self::Ordered(OrderedCompositionStrategy::new())),
+                    "commutative" => Ok(Self::Commutative(CommutativeBatchStrategy::new())),
+                    "hybrid" => Ok(Self::Hybrid(HybridCompositionStrategy::new())),
+                    _ => Err(CompositionError::InvalidPolicy(format!(
+                        "strategy '{name}' has no built-in implementation"
+                    ))),
+                }
+            }
+            StrategyPolicy::Fallback { fallback } => {
+                if fallback.is_empty() {
+                    return Err(CompositionError::InvalidPolicy(
+                        "fallback chain is empty".to_string(),
+                    ));
+                }
+                Ok(Self::Fallback(Fallback {
+                    members: fallback
+                        .iter()
+                        .map(|member| Self::resolve(member, known))
+                        .collect::<Result<_, _>>()?,
+                    chosen: Mutex::new(HashMap::new()),
+                }))
+            }
+            StrategyPolicy::Partitioned { partitioned } => {
+                let mut routes = partitioned
+                    .routes
+                    .iter()
+                    .map(|(prefix, policy)| {
+                        let path = prefix.parse::<SymbolPath>().map_err(|e| {
+                            CompositionError::InvalidPolicy(format!(
+                                "invalid route prefix '{prefix}': {e}"
+                            ))
+                        })?;
+                        Ok((path, Self::resolve(policy, known)?))
+                    })
+                    .collect::<Result<Vec<_>, CompositionError>>()?;
+                // Longest prefix wins
+                routes.sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+                Ok(Self::Partitioned(Partitioned {
+                    routes,
+                    default: Box::new(Self::resolve(&partitioned.default, known)?),
+                }))
+            }
+        }
+    }
+
+    fn validate<T: ArtifactType>(
+        &self,
+        deltas: &[StructuralDelta<T>],
+        index: &SymbolRefIndex,
+    ) -> Result<(Validation, Value), CompositionError> {
+        leaf!(self, strategy => Ok((strategy.validate(deltas, index)?, json!(self.label()))),
+            Self::Fallback(fallback) => fallback.validate(deltas, index),
+            Self::Partitioned(partitioned) => partitioned.validate(deltas, index))
+    }
+
+    fn compose<T: ArtifactType>(
+        &self,
+        base: &Artifact<T>,
+        deltas: &[StructuralDelta<T>],
+    ) -> Result<Artifact<T>, CompositionError> {
+        leaf!(self, strategy => strategy.compose(base, deltas),
+            Self::Fallback(fallback) => fallback.compose(base, deltas),
+            Self::Partitioned(partitioned) => partitioned.compose(base, deltas))
+    }
+
+    /// Weakest parallelism of any member
+    fn parallelism(&self) -> Parallelism {
+        leaf!(self, strategy => strategy.parallelism(),
+            _ => self
+                .children()
+                .map(Self::parallelism)
+                .min_by_key(|parallelism| match parallelism {
+                    Parallelism::None => 0,
+                    Parallelism::Partial => 1,
+                    Parallelism::Full => 2,
+                })
+                .unwrap_or(Parallelism::None))
+    }
+
+    /// Granularity of the first member
+    fn granularity(&self) -> Granularity {
+        leaf!(self, strategy => strategy.granularity(),
+            _ => self
+                .children()
+                .next()
+                .map_or(Granularity::Node, Self::granularity))
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::SingleWriter(_) => "single_writer",
+            Self::Ordered(_) => "ordered",
+            Self::Commutative(_) => "commutative",
+            Self::Hybrid(_) => "hybrid",
+            Self::Fallback(_) => "fallback",
+            Self::Partitioned(_) => "partitioned",
+        }
+    }
+
+    fn children(&self) -> Box<dyn Iterator<Item = &Self> + '_> {
+        match self {
+            Self::Fallback(fallback) => Box::new(fallback.members.iter()),
+            Self::Partitioned(partitioned) => Box::new(
+                partitioned
+                    .routes
+                    .iter()
+                    .map(|(_, node)| node)
+                    .chain(std::iter::once(partitioned.default.as_ref())),
+            ),
+            _ => Box::new(std::iter::empty()),
+        }
+    }
+}
+
+/// Fallback chain
+///
+/// `compose` has no symbol index, so `validate` remembers the member it
+/// picked per batch hash. Batches composed without a prior validation, or
+/// without a stable hash, pick the first member that validates against an
+/// empty index.
+#[derive(Debug)]
+struct Fallback {
+    members: Vec<Node>,
+    chosen: Mutex<HashMap<ContentHash, usize>>,
+}
+
+impl Fallback {
+    fn validate<T: ArtifactType>(
+        &self,
+        deltas: &[StructuralDelta<T>],
+        index: &SymbolRefIndex,
+    ) -> Result<(Validation, Value), CompositionError> {
+        let mut rejected = Vec::new();
+        let mut last_error = None;
+        for (i, member) in self.members.iter().enumerate() {
+            match member.validate(deltas, index) {
+                Ok((validation, selected)) => {
+                    if let Some(hash) = CompositionCache::batch_hash(deltas) {
+                        self.chosen
+                            .lock()
+                            .unwrap_or_else(PoisonError::into_inner)
+                            .insert(hash, i);
+                    }
+                    return Ok((
+                        validation,
+                        json!({ "fallback": selected, "rejected": rejected }),
+                    ));
+                }
+                Err(error) => {
+                    rejected.push(member.label());
+                    last_error = Some(error);
+                }
+            }
+        }
+        // Chains are never empty, so some member failed
+        Err(last_error.unwrap_or(CompositionError::NotValidated))
+    }
+
+    fn compose<T: ArtifactType>(
+        &self,
+        base: &Artifact<T>,
+        deltas: &[StructuralDelta<T>],
+    ) -> Result<Artifact<T>, CompositionError> {
+        let remembered = CompositionCache::batch_hash(deltas).and_then(|hash| {
+            self.chosen
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .remove(&hash)
+        });
+        let chosen = if let Some(i) = remembered {
+            i
+        } else {
+            let index = SymbolRefIndex::new();
+            self.members
+                .iter()
+                .position(|member| member.validate(deltas, &index).is_ok())
+                .ok_or(CompositionError::NotValidated)?
+        };
+        self.members[chosen].compose(base, deltas)
+    }
+}
+
+/// Strategies per subtree
+#[derive(Debug)]
+struct Partitioned {
+    /// Longest prefix first
+    routes: Vec<(SymbolPath, Node)>,
+    default: Box<Node>,
+}
+
+impl Partitioned {
+    /// Delta indices per partition; `routes.len()` is the default partition
+    fn split<T: ArtifactType>(&self, deltas: &[StructuralDelta<T>]) -> Vec<Vec<usize>> {
+        let mut partitions = vec![Vec::new(); self.routes.len() + 1];
+        for (i, delta) in deltas.iter().enumerate() {
+            let route = self
+                .routes
+                .iter()
+                .position(|(prefix, _)| prefix.is_prefix_of(delta.target()))
+                .unwrap_or(self.routes.len());
+            partitions[route].push(i);
+        }
+        partitions
+    }
+
+    fn node(&self, partition: usize) -> &Node {
+        self.routes
+            .get(partition)
+            .map_or(self.default.as_ref(), |(_, node)| node)
+    }
+
+    fn prefix(&self, partition: usize) -> String {
+        self.routes
+            .get(partition)
+            .map_or_else(String::new, |(prefix, _)| prefix.to_string())
+    }
+
+    fn validate<T: ArtifactType>(
+        &self,
+        deltas: &[StructuralDelta<T>],
+        index: &SymbolRefIndex,
+    ) -> Result<(Validation, Value), CompositionError> {
+        let partitions = self.split(deltas);
+        self.check_disjoint(deltas, &partitions)?;
+
+        let mut metadata = ValidationMetadata::default();
+        let mut cost = (0, CompositionCost::default());
+        let mut selected = serde_json::Map::new();
+        for (partition, members) in partitions.iter().enumerate() {
+            if members.is_empty() {
+                continue;
+            }
+            let batch = sub_batch(deltas, members)?;
+            let (validation, member) = self
+                .node(partition)
+                .validate(&batch, index)
+                .map_err(|error| remap_error(error, members))?;
+
+            let sub = validation.metadata;
+            if let Some(count) = sub.batch_count {
+                metadata.set_batch_count(metadata.batch_count.unwrap_or(0) + count);
+            }
+            for constraint in sub.ordering {
+                metadata.add_ordering(OrderingConstraint::new(
+                    members[constraint.delta_index],
+                    constraint.must_follow.iter().map(|&j| members[j]).collect(),
+                ));
+            }
+            metadata.add_conflicts_resolved(sub.conflicts_resolved);
+            metadata.custom.extend(sub.custom);
+            // Report the cost of the largest partition
+            if members.len() > cost.0 {
+                cost = (members.len(), validation.cost_estimate);
+            }
+            selected.insert(self.prefix(partition), member);
+        }
+
+        Ok((
+            Validation::with_metadata(metadata).with_cost(cost.1),
+            json!({ "partitioned": selected }),
+        ))
+    }
+
+    /// Reject deltas whose target contains another partition's subtree
+    fn check_disjoint<T: ArtifactType>(
+        &self,
+        deltas: &[StructuralDelta<T>],
+        partitions: &[Vec<usize>],
+    ) -> Result<(), CompositionError> {
+        for (route, (prefix, _)) in self.routes.iter().enumerate() {
+            let Some(&inside) = partitions[route].first() else {
+                continue;
+            };
+            let outside = partitions
+                .iter()
+                .enumerate()
+                .filter(|&(other, _)| other != route)
+                .flat_map(|(_, members)| members)
+                .find(|&&i| deltas[i].target().is_ancestor_of(prefix));
+            if let Some(&outside) = outside {
+                return Err(CompositionError::validation_failed(
+                    ValidationDiagnostic::new(
+                        ConflictKind::OverlappingTargets,
+                        format!(
+                            "delta {outside} targets '{}', which contains partition '{prefix}'",
+                            deltas[outside].target()
+                        ),
+                    )
+                    .involving(deltas, [outside, inside]),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn compose<T: ArtifactType>(
+        &self,
+        base: &Artifact<T>,
+        deltas: &[StructuralDelta<T>],
+    ) -> Result<Artifact<T>, CompositionError> {
+        let mut artifact = base.clone();
+        for (partition, members) in self.split(deltas).iter().enumerate() {
+            if members.is_empty() {
+                continue;
+            }
+            let batch = sub_batch(deltas, members)?;
+            artifact = self.node(partition).compose(&artifact, &batch)?;
+        }
+        Ok(artifact)
+    }
+}
+
+/// Deltas of one partition
+///
+/// `Transform` operations cannot be copied out of the batch, so they are
+/// rejected.
+fn sub_batch<T: ArtifactType>(
+    deltas: &[StructuralDelta<T>],
+    members: &[usize],
+) -> Result<Vec<StructuralDelta<T>>, CompositionError> {
+    members
+        .iter()
+        .map(|&i| match deltas[i].operation() {
+            DeltaOperation::Transform(_) => Err(CompositionError::InvalidDelta(format!(
+                "delta {i} is a transform, which partitioned policies cannot route"
+            ))),
+            _ => Ok(deltas[i].clone()),
+        })
+        .collect()
+}
+
+/// Map delta indices in a partition's diagnostic back to the whole batch
+fn remap_error(error: CompositionError, members: &[usize]) -> CompositionError {
+    match error {
+        CompositionError::ValidationFailed { mut diagnostic } => {
+            for i in &mut diagnostic.involved_deltas {
+                *i = members.get(*i).copied().unwrap_or(*i);
+            }
+            CompositionError::ValidationFailed { diagnostic }
+        }
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StrategyRegistry;
+    use std::str::FromStr;
+
+    #[derive(Debug, Clone)]
+    struct TestArtifact;
+
+    impl coa_artifact::__private::Sealed for TestArtifact {}
+
+    impl ArtifactType for TestArtifact {
+        type Content = String;
+
+        fn hash(content: &Self::Content) -> ContentHash {
+            ContentHash::compute(content.as_bytes())
+        }
+
+        const TYPE_ID: &'static str = "test";
+    }
+
+    fn delta(target: &str) -> StructuralDelta<TestArtifact> {
+        StructuralDelta::new(
+            SymbolPath::from_str(target).unwrap(),
+            DeltaOperation::Add("x".to_string()),
+            ContentHash::compute(b"base"),
+        )
+    }
+
+    fn ordered(target: &str, order: u32) -> StructuralDelta<TestArtifact> {
+        StructuralDelta::with_order(
+            SymbolPath::from_str(target).unwrap(),
+            DeltaOperation::Add("x".to_string()),
+            ContentHash::compute(b"base"),
+            order,
+        )
+    }
+
+    fn selected(validation: &Validation) -> &Value {
+        &validation.metadata.custom[SELECTED_STRATEGY_KEY]
+    }
+
+    #[test]
+    fn fallback_uses_first_member_that_validates() {
+        let registry = StrategyRegistry::with_defaults();
+        let strategy = registry.fallback(["ordered", "single_writer"]).unwrap();
+        let index = SymbolRefIndex::new();
+
+        // Unordered deltas fail `ordered` but have disjoint targets
+        let validation = strategy
+            .validate(&[delta("a.b"), delta("a.c")], &index)
+            .unwrap();
+        assert_eq!(
+            selected(&validation),
+            &json!({ "fallback": "single_writer", "rejected": ["ordered"] })
+        );
+
+        let validation = strategy
+            .validate(&[ordered("a.b", 1), ordered("a.b", 0)], &index)
+            .unwrap();
+        assert_eq!(
+            selected(&validation),
+            &json!({ "fallback": "ordered", "rejected": [] })
+        );
+
+        // Overlapping and unordered: every member rejects
+        assert!(strategy
+            .validate(&[delta("a.b"), delta("a.b")], &index)
+            .is_err());
+    }
+
+    #[test]
+    fn partitioned_routes_by_longest_prefix() {
+        let registry = StrategyRegistry::with_defaults();
+        let strategy = registry
+            .partitioned(
+                [("ui", "ordered"), ("ui.theme", "single_writer")],
+                "single_writer",
+            )
+            .unwrap();
+        let index = SymbolRefIndex::new();
+
+        let deltas = [
+            ordered("ui.layout", 1),
+            delta("core.a"),
+            ordered("ui.layout", 0),
+            delta("ui.theme.color"),
+        ];
+        let validation = strategy.validate(&deltas, &index).unwrap();
+        assert_eq!(
+            selected(&validation),
+            &json!({ "partitioned": {
+                "": "single_writer",
+                "ui": "ordered",
+                "ui.theme": "single_writer",
+            } })
+        );
+
+        // Diagnostics point at the whole batch, not the partition
+        let error = strategy
+            .validate(&[delta("core.a"), delta("ui.x"), delta("ui.y")], &index)
+            .unwrap_err();
+        let CompositionError::ValidationFailed { diagnostic } = error else {
+            panic!("expected a validation failure");
+        };
+        assert_eq!(diagnostic.involved_deltas, vec![1, 2]);
+
+        // A default-partition delta covering a routed subtree overlaps it
+        assert!(strategy
+            .validate(&[delta("ui"), ordered("ui.layout", 0)], &index)
+            .is_err());
+    }
+
+    #[test]
+    fn policies_load_from_files() {
+        let policy: StrategyPolicy = serde_json::from_str(
+            r#"{"partitioned": {
+                "routes": {"ui": {"fallback": ["commutative", "ordered"]}},
+                "default": "single_writer"
+            }}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            policy,
+            StrategyPolicy::partitioned(
+                [("ui", StrategyPolicy::fallback(["commutative", "ordered"]))],
+                "single_writer",
+            )
+        );
+
+        let registry = StrategyRegistry::with_defaults();
+        let strategy = registry.build(&policy).unwrap();
+        assert_eq!(strategy.policy(), &policy);
+        assert_eq!(strategy.parallelism(), Parallelism::None);
+
+        let mut unregistered = StrategyRegistry::with_defaults();
+        unregistered.remove("ordered");
+        assert!(matches!(
+            unregistered.build(&policy),
+            Err(CompositionError::InvalidPolicy(_))
+        ));
+        registry_rejects(&registry, &StrategyPolicy::fallback(Vec::<&str>::new()));
+        registry_rejects(
+            &registry,
+            &StrategyPolicy::partitioned([("a..b", "ordered")], "ordered"),
+        );
+    }
+
+    fn registry_rejects(registry: &StrategyRegistry, policy: &StrategyPolicy) {
+        assert!(matches!(
+            registry.build(policy),
+            Err(CompositionError::InvalidPolicy(_))
+        ));
+    }
+}
@@ -1,15 +1,27 @@
 //! Commutative batch composition strategy
 //!
 //! CRDT-style commutative operations for maximum parallelism.
+//!
+//! Targets must be unique, except for append-only regions (symbols indexed
+//! with [`APPEND_ONLY`](coa_symbol::APPEND_ONLY), e.g. a generated tests
+//! module or a registry list): any number of `Add` deltas may target one,
+//! and they are merged in content-hash order so every replica produces the
+//! same result regardless of arrival order.
 
+use crate::explain::CONFLICT_RESOLUTION_KEY;
 use crate::strategy::{
-    CompositionCost, CompositionError, CompositionStrategy, ConflictKind, DeltaClass,
-    Granularity, Parallelism, ResolutionSuggestion, TimeComplexity, Validation,
-    ValidationDiagnostic, ValidationMetadata,
+    CompositionCost, CompositionError, CompositionStrategy, ConflictKind, DeltaClass, DeltaEdit,
+    Granularity, MeasuredParallelism, OrderingConstraint, Parallelism, ResolutionSuggestion,
+    TimeComplexity, Validation, ValidationDiagnostic, ValidationMetadata,
 };
-use coa_artifact::{Artifact, ArtifactType, StructuralDelta};
+use coa_artifact::{Artifact, ArtifactType, ContentHash, DeltaOperation, StructuralDelta};
 use coa_symbol::SymbolRefIndex;
-use std::collections::HashSet;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// Default batch size at which validation switches to the parallel path
+pub const DEFAULT_PARALLEL_THRESHOLD: usize = 1024;
 
 /// Commutative batch composition strategy
 ///
@@ -17,75 +29,207 @@ use std::collections::HashSet;
 /// - Maximum parallelism (order-independent)
 /// - Requires operations to be naturally commutative
 /// - Good for set-like operations (add/remove layers, tags)
-#[derive(Debug, Clone, Copy, Default)]
-pub struct CommutativeBatchStrategy;
+///
+/// Batches of at least `parallel_threshold` deltas are validated on the
+/// rayon thread pool. Both paths report the same first offending delta.
+#[derive(Debug, Clone, Copy)]
+pub struct CommutativeBatchStrategy {
+    parallel_threshold: usize,
+}
 
 impl CommutativeBatchStrategy {
     /// Create new commutative batch strategy
     #[inline]
     #[must_use]
     pub fn new() -> Self {
-        Self
+        Self {
+            parallel_threshold: DEFAULT_PARALLEL_THRESHOLD,
+        }
+    }
+
+    /// Set batch size at which validation runs in parallel
+    #[inline]
+    #[must_use]
+    pub fn with_parallel_threshold(mut self, threshold: usize) -> Self {
+        self.parallel_threshold = threshold;
+        self
+    }
+
+    /// Batch size at which validation runs in parallel
+    #[inline]
+    #[must_use]
+    pub fn parallel_threshold(&self) -> usize {
+        self.parallel_threshold
     }
 
     /// Validate that all operations are commutative
     fn validate_commutative<T: ArtifactType>(
         &self,
         deltas: &[StructuralDelta<T>],
+        parallel: bool,
     ) -> Result<(), CompositionError> {
-        for (i, delta) in deltas.iter().enumerate() {
-            if !Self::is_operation_commutative(delta) {
-                return Err(CompositionError::validation_failed(
-                    ValidationDiagnostic {
-                        kind: ConflictKind::NonCommutativeOperations,
-                        involved_deltas: vec![i],
-                        description: format!(
-                            "Delta {} contains non-commutative operation: {:?}",
-                            i,
-                            delta.operation()
-                        ),
-                        suggestions: vec![
-                            ResolutionSuggestion::UseOrdered,
-                            ResolutionSuggestion::UseSingleWriter,
-                        ],
-                    },
-                ));
-            }
-        }
+        let offender = if parallel {
+            deltas
+                .par_iter()
+                .position_first(|delta| !Self::is_operation_commutative(delta))
+        } else {
+            deltas
+                .iter()
+                .position(|delta| !Self::is_operation_commutative(delta))
+        };
 
-        Ok(())
+        match offender {
+            Some(i) => Err(CompositionError::validation_failed(
+                ValidationDiagnostic::new(
+                    ConflictKind::NonCommutativeOperations,
+                    format!(
+                        "Delta {} contains non-commutative operation: {:?}",
+                        i,
+                        deltas[i].operation()
+                    ),
+                )
+                .involving(deltas, [i])
+                .with_suggestion(ResolutionSuggestion::UseOrdered)
+                .with_suggestion(ResolutionSuggestion::UseSingleWriter),
+            )),
+            None => Ok(()),
+        }
     }
 
     /// Check if delta operation is commutative
+    ///
+    /// Add/Remove, plus transforms that declare themselves commutative
+    /// (semantic operations such as manifest dependency edits).
     fn is_operation_commutative<T: ArtifactType>(delta: &StructuralDelta<T>) -> bool {
-        matches!(
-            delta.operation(),
-            coa_artifact::DeltaOperation::Add(_) | coa_artifact::DeltaOperation::Remove
-        )
+        delta.operation().is_commutative()
     }
 
     /// Check for duplicate targets (still not allowed in commutative mode)
+    ///
+    /// A repeat is allowed when it and the target's first delta are both
+    /// `Add`s into an append-only region.
     fn validate_unique_targets<T: ArtifactType>(
         &self,
         deltas: &[StructuralDelta<T>],
+        index: &SymbolRefIndex,
+        parallel: bool,
     ) -> Result<(), CompositionError> {
-        let mut seen = HashSet::new();
-
-        for (i, delta) in deltas.iter().enumerate() {
-            let key = delta.target().to_string();
-            if !seen.insert(key.clone()) {
-                return Err(CompositionError::validation_failed(
-                    ValidationDiagnostic {
-                        kind: ConflictKind::OverlappingTargets,
-                        involved_deltas: vec![i],
-                        description: format!("Duplicate target in commutative batch: {}", key),
-                        suggestions: vec![ResolutionSuggestion::UseSingleWriter],
-                    },
-                ));
+        let appends = Self::region_appends(deltas, index);
+        let duplicate = if parallel {
+            Self::first_duplicate_parallel(deltas, &appends)
+        } else {
+            let mut seen = HashMap::new();
+            deltas.iter().enumerate().find_map(|(i, delta)| {
+                let first = *seen.entry(delta.target().to_string()).or_insert(i);
+                (first != i && !(appends[first] && appends[i])).then_some((first, i))
+            })
+        };
+
+        match duplicate {
+            Some((first, i)) => Err(CompositionError::validation_failed(
+                ValidationDiagnostic::new(
+                    ConflictKind::OverlappingTargets,
+                    format!("Duplicate target in commutative batch: {}", deltas[i].target()),
+                )
+                .involving(deltas, [first, i])
+                .with_suggestion(ResolutionSuggestion::ApplyEdits {
+                    edits: vec![DeltaEdit::Drop { delta_index: i }],
+                })
+                .with_suggestion(ResolutionSuggestion::UseSingleWriter),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// First disallowed repeat as `(first occurrence, repeat)`
+    ///
+    /// Matches the sequential scan: sorting by `(target, index)` groups each
+    /// target with its first occurrence at the front.
+    fn first_duplicate_parallel<T: ArtifactType>(
+        deltas: &[StructuralDelta<T>],
+        appends: &[bool],
+    ) -> Option<(usize, usize)> {
+        let mut keyed: Vec<(String, usize)> = deltas
+            .par_iter()
+            .enumerate()
+            .map(|(i, delta)| (delta.target().to_string(), i))
+            .collect();
+        keyed.par_sort_unstable();
+
+        keyed
+            .par_chunk_by(|a, b| a.0 == b.0)
+            .filter_map(|group| {
+                let first = group[0].1;
+                group[1..]
+                    .iter()
+                    .find(|(_, i)| !(appends[first] && appends[*i]))
+                    .map(|&(_, repeat)| (first, repeat))
+            })
+            .min_by_key(|&(_, repeat)| repeat)
+    }
+
+    /// Per delta: is it an `Add` into an append-only region?
+    fn region_appends<T: ArtifactType>(
+        deltas: &[StructuralDelta<T>],
+        index: &SymbolRefIndex,
+    ) -> Vec<bool> {
+        let mut regions: HashMap<String, bool> = HashMap::new();
+        deltas
+            .iter()
+            .map(|delta| {
+                matches!(delta.operation(), DeltaOperation::Add(_))
+                    && *regions
+                        .entry(delta.target().to_string())
+                        .or_insert_with(|| index.is_append_region(delta.target().segments()))
+            })
+            .collect()
+    }
+
+    /// Order in which to apply `deltas`
+    ///
+    /// Appends sharing a region are moved into content-hash order (ties keep
+    /// batch order) within the slots they occupy; every other delta keeps
+    /// its position.
+    #[must_use]
+    pub fn append_order<T: ArtifactType>(
+        deltas: &[StructuralDelta<T>],
+        index: &SymbolRefIndex,
+    ) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..deltas.len()).collect();
+        for chain in Self::append_chains(deltas, index) {
+            let mut slots = chain.clone();
+            slots.sort_unstable();
+            for (slot, i) in slots.into_iter().zip(chain) {
+                order[slot] = i;
             }
         }
+        order
+    }
+
+    /// Appends per shared region, in content-hash order (regions with one
+    /// append are omitted)
+    fn append_chains<T: ArtifactType>(
+        deltas: &[StructuralDelta<T>],
+        index: &SymbolRefIndex,
+    ) -> Vec<Vec<usize>> {
+        let appends = Self::region_appends(deltas, index);
+        let mut groups: HashMap<String, Vec<usize>> = HashMap::new();
+        for (i, delta) in deltas.iter().enumerate().filter(|(i, _)| appends[*i]) {
+            groups.entry(delta.target().to_string()).or_default().push(i);
+        }
 
-        Ok(())
+        let mut chains: Vec<Vec<usize>> = groups
+            .into_values()
+            .filter(|group| group.len() > 1)
+            .collect();
+        for chain in &mut chains {
+            chain.sort_by_key(|&i| match deltas[i].operation() {
+                DeltaOperation::Add(content) => (T::hash(content), i),
+                _ => (ContentHash::default(), i),
+            });
+        }
+        chains.sort_unstable_by_key(|chain| chain.iter().min().copied());
+        chains
     }
 
     /// Apply deltas in parallel (order doesn't matter)
@@ -109,26 +253,51 @@ impl CompositionStrategy for CommutativeBatchStrategy {
     fn validate<T: ArtifactType>(
         &self,
         deltas: &[StructuralDelta<T>],
-        _index: &SymbolRefIndex,
+        index: &SymbolRefIndex,
     ) -> Result<Validation, CompositionError> {
         if deltas.len() <= 1 {
             return Ok(Validation::minimal());
         }
 
+        let parallel = deltas.len() >= self.parallel_threshold;
+        let started = Instant::now();
+
         // All operations must be commutative
-        self.validate_commutative(deltas)?;
+        self.validate_commutative(deltas, parallel)?;
+
+        // Targets must still be unique, except for append-only regions
+        self.validate_unique_targets(deltas, index, parallel)?;
 
-        // Targets must still be unique
-        self.validate_unique_targets(deltas)?;
+        let measured = MeasuredParallelism {
+            mode: if parallel { Parallelism::Full } else { Parallelism::None },
+            workers: if parallel { rayon::current_num_threads() } else { 1 },
+            elapsed: started.elapsed(),
+        };
 
         let mut metadata = ValidationMetadata::default();
         metadata.set_batch_count(1);
 
+        // Merged appends are chained in content-hash order
+        for chain in Self::append_chains(deltas, index) {
+            metadata.add_conflicts_resolved(chain.len() - 1);
+            for pair in chain.windows(2) {
+                metadata.add_ordering(OrderingConstraint::new(pair[1], vec![pair[0]]));
+            }
+        }
+        if metadata.conflicts_resolved > 0 {
+            metadata.custom.insert(
+                CONFLICT_RESOLUTION_KEY.to_string(),
+                "merged appends in content-hash order".into(),
+            );
+        }
+
         let cost = CompositionCost {
             time: TimeComplexity::ON,
             space: crate::strategy::SpaceComplexity::ON,
             parallelism_factor: 1.0,
-        };
+            measured: None,
+        }
+        .with_measured(measured);
 
         Ok(Validation::with_metadata(metadata).with_cost(cost))
     }
@@ -158,6 +327,12 @@ impl CompositionStrategy for CommutativeBatchStrategy {
     }
 }
 
+impl Default for CommutativeBatchStrategy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Classifier for commutative strategy
 pub struct CommutativeClassifier;
 
@@ -166,10 +341,7 @@ impl CommutativeClassifier {
     #[inline]
     #[must_use]
     pub fn is_commutative<T: ArtifactType>(delta: &StructuralDelta<T>) -> bool {
-        matches!(
-            delta.operation(),
-            coa_artifact::DeltaOperation::Add(_) | coa_artifact::DeltaOperation::Remove
-        )
+        delta.operation().is_commutative()
     }
 
     /// Classify delta for hybrid strategy
@@ -282,7 +454,162 @@ mod tests {
         ];
 
         let result = strategy.validate(&deltas, &index);
-        assert!(result.is_err());
+        let Err(CompositionError::ValidationFailed { diagnostic }) = result else {
+            panic!("expected validation failure");
+        };
+        assert_eq!(diagnostic.involved_deltas, vec![0, 1]);
+        assert_eq!(diagnostic.paths[1].to_string(), "layer1");
+        assert_eq!(diagnostic.edits(), vec![DeltaEdit::Drop { delta_index: 1 }]);
+        assert_eq!(DeltaEdit::apply_all(deltas, &diagnostic.edits()).len(), 1);
+    }
+
+    fn large_batch(n: usize) -> Vec<StructuralDelta<TestArtifact>> {
+        (0..n)
+            .map(|i| make_add_delta(&format!("layer{i}"), test_hash()))
+            .collect()
+    }
+
+    #[test]
+    fn commutative_parallel_path_reports_measured_parallelism() {
+        let index = SymbolRefIndex::new();
+        let deltas = large_batch(2_000);
+
+        let parallel = CommutativeBatchStrategy::new().with_parallel_threshold(16);
+        let measured = parallel
+            .validate(&deltas, &index)
+            .unwrap()
+            .cost_estimate
+            .measured
+            .unwrap();
+        assert_eq!(measured.mode, Parallelism::Full);
+        assert!(measured.workers >= 1);
+
+        let sequential = CommutativeBatchStrategy::new().with_parallel_threshold(usize::MAX);
+        let measured = sequential
+            .validate(&deltas, &index)
+            .unwrap()
+            .cost_estimate
+            .measured
+            .unwrap();
+        assert_eq!(measured.mode, Parallelism::None);
+        assert_eq!(measured.workers, 1);
+    }
+
+    #[test]
+    fn commutative_parallel_matches_sequential_diagnostics() {
+        let index = SymbolRefIndex::new();
+        let parallel = CommutativeBatchStrategy::new().with_parallel_threshold(2);
+        let sequential = CommutativeBatchStrategy::new().with_parallel_threshold(usize::MAX);
+
+        let mut duplicates = large_batch(3_000);
+        duplicates[2_500] = make_add_delta("layer7", test_hash());
+        duplicates[1_200] = make_add_delta("layer900", test_hash());
+
+        let mut replaced = large_batch(3_000);
+        for i in [2_900, 1_700, 1_701] {
+            replaced[i] = StructuralDelta::new(
+                SymbolPath::from_str(&format!("layer{i}")).unwrap(),
+                DeltaOperation::Replace(TestContent),
+                test_hash(),
+            );
+        }
+
+        for deltas in [duplicates, replaced] {
+            let involved = |strategy: &CommutativeBatchStrategy| match strategy
+                .validate(&deltas, &index)
+                .unwrap_err()
+            {
+                CompositionError::ValidationFailed { diagnostic } => {
+                    (diagnostic.kind, diagnostic.involved_deltas)
+                }
+                other => panic!("unexpected error: {other}"),
+            };
+            assert_eq!(involved(&parallel), involved(&sequential));
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct Registry;
+
+    impl coa_artifact::__private::Sealed for Registry {}
+
+    impl ArtifactType for Registry {
+        type Content = String;
+
+        fn hash(content: &Self::Content) -> ContentHash {
+            ContentHash::compute(content.as_bytes())
+        }
+
+        const TYPE_ID: &'static str = "registry";
+    }
+
+    fn append(target: &str, entry: &str) -> StructuralDelta<Registry> {
+        StructuralDelta::new(
+            SymbolPath::from_str(target).unwrap(),
+            DeltaOperation::Add(entry.to_string()),
+            test_hash(),
+        )
+    }
+
+    #[test]
+    fn commutative_merges_appends_into_regions_in_hash_order() {
+        use coa_symbol::{SymbolMetadata, SymbolRef};
+
+        let index = SymbolRefIndex::new();
+        index
+            .insert(
+                SymbolRef::new(vec!["plugins".to_string()], test_hash()),
+                SymbolMetadata::default().append_only(),
+            )
+            .unwrap();
+
+        let deltas = vec![
+            append("plugins", "zeta"),
+            append("other", "x"),
+            append("plugins", "alpha"),
+            append("plugins", "mid"),
+        ];
+        let mut by_hash = vec![0, 2, 3];
+        by_hash.sort_by_key(|&i| match deltas[i].operation() {
+            DeltaOperation::Add(entry) => Registry::hash(entry),
+            _ => unreachable!(),
+        });
+
+        for threshold in [2, usize::MAX] {
+            let strategy = CommutativeBatchStrategy::new().with_parallel_threshold(threshold);
+            let validation = strategy.validate(&deltas, &index).unwrap();
+            let chain: Vec<_> = validation
+                .metadata
+                .ordering
+                .iter()
+                .map(|c| (c.must_follow[0], c.delta_index))
+                .collect();
+            assert_eq!(chain, [(by_hash[0], by_hash[1]), (by_hash[1], by_hash[2])]);
+            assert_eq!(validation.metadata.conflicts_resolved, 2);
+        }
+
+        let order = CommutativeBatchStrategy::append_order(&deltas, &index);
+        assert_eq!(order[1], 1);
+        let merged: Vec<_> = order.into_iter().filter(|&i| i != 1).collect();
+        assert_eq!(merged, by_hash);
+
+        // Non-Add edits to a region, and repeats elsewhere, still conflict
+        let mut conflicting = deltas.clone();
+        conflicting.push(StructuralDelta::new(
+            SymbolPath::from_str("plugins").unwrap(),
+            DeltaOperation::Remove,
+            test_hash(),
+        ));
+        conflicting.push(append("other", "y"));
+        for threshold in [2, usize::MAX] {
+            let strategy = CommutativeBatchStrategy::new().with_parallel_threshold(threshold);
+            let Err(CompositionError::ValidationFailed { diagnostic }) =
+                strategy.validate(&conflicting, &index)
+            else {
+                panic!("expected validation failure");
+            };
+            assert_eq!(diagnostic.involved_deltas, vec![0, 4]);
+        }
     }
 
     #[test]
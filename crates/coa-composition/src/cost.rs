@@ -0,0 +1,536 @@
+//! Cost models for composition
+//!
+//! [`CompositionCost`](crate::CompositionCost) classifies a strategy with
+//! fixed complexity classes. A [`CostModel`] instead predicts time and space
+//! for a concrete batch, described by a [`BatchProfile`]: delta count,
+//! artifact and delta sizes, and the depth of the symbols the batch touches.
+//!
+//! [`LinearCostModel`] predicts time as a weighted sum of batch features.
+//! Its defaults follow each built-in strategy's complexity class; a
+//! [`Calibrator`] fits the weights to timed runs on real batches, so
+//! [`StrategySelector::select_cheapest`](crate::StrategySelector::select_cheapest)
+//! can compare strategies by predicted cost.
+
+use crate::strategy::{CompositionError, CompositionStrategy, SpaceComplexity, TimeComplexity};
+use coa_artifact::{Artifact, ArtifactType, StructuralDelta};
+use coa_symbol::SymbolRefIndex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// Number of features a [`LinearCostModel`] weighs
+pub const COST_FEATURES: usize = 7;
+
+/// Nanoseconds per unit of work in the default coefficients
+const DEFAULT_UNIT_NS: f64 = 1_000.0;
+
+/// Shape of one delta batch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BatchProfile {
+    /// Number of deltas
+    pub delta_count: usize,
+
+    /// Estimated size of the base artifact
+    pub artifact_bytes: usize,
+
+    /// Estimated size of all deltas
+    pub delta_bytes: usize,
+
+    /// Deepest symbol path targeted by the batch
+    pub max_depth: usize,
+}
+
+impl BatchProfile {
+    /// Profile composing `deltas` onto `base`
+    #[must_use]
+    pub fn of<T: ArtifactType>(base: &Artifact<T>, deltas: &[StructuralDelta<T>]) -> Self {
+        Self {
+            delta_count: deltas.len(),
+            artifact_bytes: base.estimated_size(),
+            delta_bytes: deltas
+                .iter()
+                .map(StructuralDelta::estimated_size)
+                .fold(0usize, usize::saturating_add),
+            max_depth: deltas.iter().map(|d| d.target().len()).max().unwrap_or(0),
+        }
+    }
+
+    /// Features weighed by [`CostCoefficients`], in field order
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn features(&self) -> [f64; COST_FEATURES] {
+        let n = self.delta_count as f64;
+        let log_n = if self.delta_count > 1 { n.log2() } else { 0.0 };
+        [
+            1.0,
+            log_n,
+            n,
+            n * log_n,
+            n * n,
+            (self.artifact_bytes as f64) + (self.delta_bytes as f64),
+            n * self.max_depth as f64,
+        ]
+    }
+}
+
+/// Predicted cost of composing one batch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CostEstimate {
+    /// Predicted validation and composition time
+    pub time: Duration,
+
+    /// Projected peak memory
+    pub space_bytes: usize,
+}
+
+/// Estimates composition cost from batch characteristics
+pub trait CostModel: Send + Sync + std::fmt::Debug {
+    /// Predict the cost of `strategy` (a registry name) on a batch
+    ///
+    /// Returns `None` for strategies the model knows nothing about.
+    fn estimate(&self, strategy: &str, profile: &BatchProfile) -> Option<CostEstimate>;
+}
+
+/// Weights of one strategy in a [`LinearCostModel`]
+///
+/// Time in nanoseconds is the dot product with
+/// [`BatchProfile::features`]. Space follows the strategy's space class
+/// like [`MemoryEstimate`](crate::MemoryEstimate).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CostCoefficients {
+    /// Per batch
+    pub fixed_ns: f64,
+
+    /// Per `log2 n`
+    pub log_ns: f64,
+
+    /// Per delta
+    pub per_delta_ns: f64,
+
+    /// Per `n log2 n`
+    pub per_delta_log_ns: f64,
+
+    /// Per pair of deltas
+    pub per_pair_ns: f64,
+
+    /// Per byte of artifact and deltas
+    pub per_byte_ns: f64,
+
+    /// Per delta and level of symbol depth
+    pub per_depth_ns: f64,
+
+    /// Working-set class
+    pub space: SpaceComplexity,
+}
+
+impl CostCoefficients {
+    /// Coefficients charging one unit of work per step of `time`
+    #[must_use]
+    pub fn from_class(time: TimeComplexity, space: SpaceComplexity) -> Self {
+        let mut weights = [0.0; COST_FEATURES];
+        weights[0] = DEFAULT_UNIT_NS;
+        let term = match time {
+            TimeComplexity::O1 => 0,
+            TimeComplexity::OLogN => 1,
+            TimeComplexity::ON => 2,
+            TimeComplexity::ONLogN => 3,
+            TimeComplexity::ON2 => 4,
+        };
+        weights[term] += DEFAULT_UNIT_NS;
+        Self::from_weights(weights, space)
+    }
+
+    fn from_weights(weights: [f64; COST_FEATURES], space: SpaceComplexity) -> Self {
+        let [fixed_ns, log_ns, per_delta_ns, per_delta_log_ns, per_pair_ns, per_byte_ns, per_depth_ns] =
+            weights;
+        Self {
+            fixed_ns,
+            log_ns,
+            per_delta_ns,
+            per_delta_log_ns,
+            per_pair_ns,
+            per_byte_ns,
+            per_depth_ns,
+            space,
+        }
+    }
+
+    /// Weights in [`BatchProfile::features`] order
+    #[must_use]
+    pub fn weights(&self) -> [f64; COST_FEATURES] {
+        [
+            self.fixed_ns,
+            self.log_ns,
+            self.per_delta_ns,
+            self.per_delta_log_ns,
+            self.per_pair_ns,
+            self.per_byte_ns,
+            self.per_depth_ns,
+        ]
+    }
+
+    /// Predict the cost of a batch
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn estimate(&self, profile: &BatchProfile) -> CostEstimate {
+        let nanos: f64 = self
+            .weights()
+            .iter()
+            .zip(profile.features())
+            .map(|(weight, feature)| weight * feature)
+            .sum();
+        let working = self
+            .space
+            .working_bytes(profile.delta_bytes, profile.delta_count);
+        CostEstimate {
+            // Fitted weights may go negative on tiny batches
+            time: Duration::from_nanos(nanos.max(0.0) as u64),
+            space_bytes: profile
+                .artifact_bytes
+                .saturating_mul(2)
+                .saturating_add(profile.delta_bytes)
+                .saturating_add(working),
+        }
+    }
+}
+
+/// Cost model with linear weights per strategy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinearCostModel {
+    strategies: BTreeMap<String, CostCoefficients>,
+}
+
+impl Default for LinearCostModel {
+    /// Built-in strategies weighted by their complexity classes
+    fn default() -> Self {
+        let mut model = Self::empty();
+        for (name, time, space) in [
+            ("single_writer", TimeComplexity::ONLogN, SpaceComplexity::ON),
+            ("ordered", TimeComplexity::ON, SpaceComplexity::O1),
+            ("commutative", TimeComplexity::ON, SpaceComplexity::ON),
+            ("hybrid", TimeComplexity::ON, SpaceComplexity::ON),
+        ] {
+            model.set(name, CostCoefficients::from_class(time, space));
+        }
+        model
+    }
+}
+
+impl LinearCostModel {
+    /// Model without strategies
+    #[must_use]
+    pub fn empty() -> Self {
+        Self {
+            strategies: BTreeMap::new(),
+        }
+    }
+
+    /// Set the coefficients of `strategy`
+    pub fn set(&mut self, strategy: &str, coefficients: CostCoefficients) {
+        self.strategies.insert(strategy.to_string(), coefficients);
+    }
+
+    /// Coefficients of `strategy`
+    #[must_use]
+    pub fn get(&self, strategy: &str) -> Option<&CostCoefficients> {
+        self.strategies.get(strategy)
+    }
+}
+
+impl CostModel for LinearCostModel {
+    fn estimate(&self, strategy: &str, profile: &BatchProfile) -> Option<CostEstimate> {
+        self.get(strategy)
+            .map(|coefficients| coefficients.estimate(profile))
+    }
+}
+
+/// Calibration failure
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CalibrationError {
+    /// Not enough distinct batches to fit every weight
+    #[error("strategy '{strategy}' has {samples} distinct sample(s), {required} needed")]
+    TooFewSamples {
+        /// Strategy name
+        strategy: String,
+        /// Distinct batch profiles recorded
+        samples: usize,
+        /// Profiles needed for a fit
+        required: usize,
+    },
+
+    /// Samples do not determine the weights, e.g. every batch has one size
+    #[error("samples for strategy '{0}' are degenerate")]
+    Degenerate(String),
+}
+
+/// One timed composition
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CostSample {
+    pub profile: BatchProfile,
+    pub elapsed: Duration,
+}
+
+/// Fits [`LinearCostModel`] weights to timed runs
+///
+/// Record runs with [`measure`](Self::measure), or import timings from a
+/// benchmark with [`record`](Self::record), then [`fit`](Self::fit).
+#[derive(Debug, Clone, Default)]
+pub struct Calibrator {
+    samples: BTreeMap<String, Vec<CostSample>>,
+}
+
+impl Calibrator {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a run of `strategy` on a batch
+    pub fn record(&mut self, strategy: &str, profile: BatchProfile, elapsed: Duration) {
+        self.samples
+            .entry(strategy.to_string())
+            .or_default()
+            .push(CostSample { profile, elapsed });
+    }
+
+    /// Time validating and composing `deltas` with `strategy`, recording
+    /// the run under `name`
+    ///
+    /// # Errors
+    /// Returns the strategy's validation or composition error; failed runs
+    /// are not recorded
+    pub fn measure<T, S>(
+        &mut self,
+        name: &str,
+        strategy: &S,
+        base: &Artifact<T>,
+        deltas: &[StructuralDelta<T>],
+        index: &SymbolRefIndex,
+    ) -> Result<Duration, CompositionError>
+    where
+        T: ArtifactType,
+        S: CompositionStrategy,
+    {
+        let started = Instant::now();
+        strategy.validate(deltas, index)?;
+        strategy.compose(base, deltas)?;
+        let elapsed = started.elapsed();
+        self.record(name, BatchProfile::of(base, deltas), elapsed);
+        Ok(elapsed)
+    }
+
+    /// Samples recorded for `strategy`
+    #[must_use]
+    pub fn samples(&self, strategy: &str) -> &[CostSample] {
+        self.samples.get(strategy).map_or(&[], Vec::as_slice)
+    }
+
+    /// Fit every sampled strategy, starting from the default model
+    ///
+    /// # Errors
+    /// See [`fit_onto`](Self::fit_onto)
+    pub fn fit(&self) -> Result<LinearCostModel, CalibrationError> {
+        self.fit_onto(LinearCostModel::default())
+    }
+
+    /// Replace the time weights of every sampled strategy in `model` with a
+    /// least-squares fit; space classes are kept
+    ///
+    /// # Errors
+    /// - `TooFewSamples` if a strategy has fewer distinct batches than
+    ///   [`COST_FEATURES`]
+    /// - `Degenerate` if its batches do not determine the weights
+    pub fn fit_onto(
+        &self,
+        mut model: LinearCostModel,
+    ) -> Result<LinearCostModel, CalibrationError> {
+        for (strategy, samples) in &self.samples {
+            let weights = fit_weights(strategy, samples)?;
+            let space = model
+                .get(strategy)
+                .map_or(SpaceComplexity::ON, |coefficients| coefficients.space);
+            model.set(strategy, CostCoefficients::from_weights(weights, space));
+        }
+        Ok(model)
+    }
+}
+
+/// Least-squares weights for `samples`
+///
+/// Features span many orders of magnitude, so columns are scaled to a
+/// maximum of 1 before solving the normal equations.
+#[allow(clippy::cast_precision_loss)]
+fn fit_weights(
+    strategy: &str,
+    samples: &[CostSample],
+) -> Result<[f64; COST_FEATURES], CalibrationError> {
+    let distinct = samples
+        .iter()
+        .map(|sample| sample.profile)
+        .collect::<HashSet<_>>()
+        .len();
+    if distinct < COST_FEATURES {
+        return Err(CalibrationError::TooFewSamples {
+            strategy: strategy.to_string(),
+            samples: distinct,
+            required: COST_FEATURES,
+        });
+    }
+
+    let rows: Vec<([f64; COST_FEATURES], f64)> = samples
+        .iter()
+        .map(|sample| (sample.profile.features(), sample.elapsed.as_nanos() as f64))
+        .collect();
+    let mut scale = [0.0f64; COST_FEATURES];
+    for (features, _) in &rows {
+        for (max, feature) in scale.iter_mut().zip(features) {
+            *max = max.max(feature.abs());
+        }
+    }
+    for max in &mut scale {
+        if *max == 0.0 {
+            *max = 1.0;
+        }
+    }
+
+    // Normal equations (XᵀX) w = Xᵀy on scaled columns
+    let mut system = [[0.0f64; COST_FEATURES + 1]; COST_FEATURES];
+    for (features, target) in &rows {
+        let scaled: Vec<f64> = features.iter().zip(&scale).map(|(f, s)| f / s).collect();
+        for i in 0..COST_FEATURES {
+            for j in 0..COST_FEATURES {
+                system[i][j] += scaled[i] * scaled[j];
+            }
+            system[i][COST_FEATURES] += scaled[i] * target;
+        }
+    }
+    let solution =
+        solve(system).ok_or_else(|| CalibrationError::Degenerate(strategy.to_string()))?;
+
+    let mut weights = [0.0; COST_FEATURES];
+    for ((weight, value), s) in weights.iter_mut().zip(solution).zip(scale) {
+        *weight = value / s;
+    }
+    Ok(weights)
+}
+
+/// Solve an augmented linear system by Gaussian elimination with partial
+/// pivoting; `None` if it is singular
+fn solve(mut system: [[f64; COST_FEATURES + 1]; COST_FEATURES]) -> Option<[f64; COST_FEATURES]> {
+    const EPSILON: f64 = 1e-12;
+
+    for column in 0..COST_FEATURES {
+        let pivot = (column..COST_FEATURES)
+            .max_by(|&a, &b| system[a][column].abs().total_cmp(&system[b][column].abs()))?;
+        if system[pivot][column].abs() < EPSILON {
+            return None;
+        }
+        system.swap(column, pivot);
+        let (upper, lower) = system.split_at_mut(column + 1);
+        let pivot_row = &upper[column];
+        for row in lower {
+            let factor = row[column] / pivot_row[column];
+            for (value, pivot_value) in row.iter_mut().zip(pivot_row).skip(column) {
+                *value -= factor * pivot_value;
+            }
+        }
+    }
+
+    let mut solution = [0.0; COST_FEATURES];
+    for row in (0..COST_FEATURES).rev() {
+        let known: f64 = (row + 1..COST_FEATURES)
+            .map(|k| system[row][k] * solution[k])
+            .sum();
+        solution[row] = (system[row][COST_FEATURES] - known) / system[row][row];
+    }
+    Some(solution)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(n: usize, bytes: usize, depth: usize) -> BatchProfile {
+        BatchProfile {
+            delta_count: n,
+            artifact_bytes: bytes,
+            delta_bytes: n * 16,
+            max_depth: depth,
+        }
+    }
+
+    #[test]
+    fn default_model_follows_complexity_classes() {
+        let model = LinearCostModel::default();
+        let small = profile(4, 100, 1);
+        let large = profile(4_000, 100, 1);
+
+        let ordered = model.estimate("ordered", &large).unwrap();
+        let single_writer = model.estimate("single_writer", &large).unwrap();
+        assert!(ordered.time < single_writer.time);
+        assert!(ordered.space_bytes < single_writer.space_bytes);
+        assert!(model.estimate("ordered", &small).unwrap().time < ordered.time);
+        assert_eq!(model.estimate("custom", &small), None);
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn calibration_recovers_known_weights() {
+        let truth = CostCoefficients {
+            fixed_ns: 5_000.0,
+            log_ns: 300.0,
+            per_delta_ns: 800.0,
+            per_delta_log_ns: 120.0,
+            per_pair_ns: 2.0,
+            per_byte_ns: 0.5,
+            per_depth_ns: 40.0,
+            space: SpaceComplexity::ON,
+        };
+        let mut calibrator = Calibrator::new();
+        for n in [1, 2, 5, 10, 40, 100, 300] {
+            for bytes in [0, 1_000, 50_000] {
+                for depth in [1, 3, 6] {
+                    let batch = profile(n, bytes, depth);
+                    calibrator.record("custom", batch, truth.estimate(&batch).time);
+                }
+            }
+        }
+
+        let model = calibrator.fit_onto(LinearCostModel::empty()).unwrap();
+        let fitted = model.get("custom").unwrap();
+        for batch in [profile(7, 2_000, 2), profile(250, 40_000, 5)] {
+            let expected = truth.estimate(&batch).time.as_nanos() as f64;
+            let actual = fitted.estimate(&batch).time.as_nanos() as f64;
+            assert!(
+                (expected - actual).abs() / expected < 0.01,
+                "{expected} vs {actual}"
+            );
+        }
+    }
+
+    #[test]
+    fn calibration_needs_distinct_batches() {
+        let mut calibrator = Calibrator::new();
+        for _ in 0..10 {
+            calibrator.record("ordered", profile(3, 10, 1), Duration::from_micros(5));
+        }
+        assert_eq!(
+            calibrator.fit(),
+            Err(CalibrationError::TooFewSamples {
+                strategy: "ordered".to_string(),
+                samples: 1,
+                required: COST_FEATURES,
+            })
+        );
+
+        // Distinct in count only: byte and depth columns stay collinear
+        let mut calibrator = Calibrator::new();
+        for n in 1..=10 {
+            calibrator.record("ordered", profile(n, 0, 0), Duration::from_micros(n as u64));
+        }
+        assert!(matches!(
+            calibrator.fit(),
+            Err(CalibrationError::Degenerate(_))
+        ));
+    }
+}
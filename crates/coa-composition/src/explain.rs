@@ -0,0 +1,314 @@
+//! Human-readable composition reports
+//!
+//! [`explain`] turns a [`Validation`] and the composed artifact into a short
+//! Markdown narrative, e.g. for pull request descriptions:
+//!
+//! ```text
+//! **Composed 15 deltas with CommutativeBatch → `3fa4c2e19b0d7a61`**
+//!
+//! - 12 deltas applied commutatively to `auth.*`
+//! - 2 deltas ordered due to dependency on new symbol `auth.login_v2`
+//! - 1 conflict auto-resolved: merged appends in content-hash order
+//! ```
+
+use crate::combinator::SELECTED_STRATEGY_KEY;
+use crate::strategy::{CompositionStrategy, Parallelism, Validation};
+use coa_artifact::{Artifact, ArtifactType, DeltaOperation, StructuralDelta};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Write as _};
+
+/// [`ValidationMetadata::custom`](crate::ValidationMetadata::custom) key
+/// describing how a strategy resolved conflicts, for [`explain`]
+pub const CONFLICT_RESOLUTION_KEY: &str = "conflict_resolution";
+
+/// Narrative report of one composition
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    /// Headline: delta count, strategy and result hash
+    pub summary: String,
+
+    /// One sentence per group of deltas or notable event
+    pub items: Vec<String>,
+}
+
+impl Explanation {
+    /// Render as Markdown: bold headline followed by a bullet list
+    #[must_use]
+    pub fn to_markdown(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "**{}**", self.summary)?;
+        if !self.items.is_empty() {
+            writeln!(f)?;
+        }
+        for item in &self.items {
+            writeln!(f, "- {item}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Explain how `strategy` composed `deltas`
+///
+/// `validation` is the strategy's report for `deltas`; `result` is the
+/// composed artifact, if composition has run.
+#[must_use]
+pub fn explain<T, S>(
+    strategy: &S,
+    deltas: &[StructuralDelta<T>],
+    validation: &Validation,
+    result: Option<&Artifact<T>>,
+) -> Explanation
+where
+    T: ArtifactType,
+    S: CompositionStrategy,
+{
+    let metadata = &validation.metadata;
+    let mut summary = format!(
+        "Composed {} with {}",
+        count(deltas.len(), "delta"),
+        strategy.name()
+    );
+    if let Some(result) = result {
+        let _ = write!(summary, " → `{}`", result.hash().short());
+    }
+
+    let mut items = Vec::new();
+
+    // Deltas free of ordering constraints, grouped by top-level symbol
+    let constrained: BTreeSet<usize> = metadata.ordering.iter().map(|c| c.delta_index).collect();
+    let mut free: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (i, delta) in deltas.iter().enumerate() {
+        if !constrained.contains(&i) {
+            free.entry(delta.target().first().unwrap_or(""))
+                .or_default()
+                .push(i);
+        }
+    }
+    let how = manner(strategy);
+    for (root, members) in &free {
+        let scope = match members.as_slice() {
+            [only] => format!("`{}`", deltas[*only].target()),
+            _ if root.is_empty() => "the root".to_string(),
+            _ => format!("`{root}.*`"),
+        };
+        items.push(format!(
+            "{} applied {how} to {scope}",
+            count(members.len(), "delta")
+        ));
+    }
+
+    // Constrained deltas, grouped by what they wait for
+    let mut waits: BTreeMap<Vec<String>, usize> = BTreeMap::new();
+    for constraint in &metadata.ordering {
+        let mut reasons: Vec<String> = constraint
+            .must_follow
+            .iter()
+            .filter_map(|&j| deltas.get(j))
+            .map(|dependency| match dependency.operation() {
+                DeltaOperation::Add(_) => format!("new symbol `{}`", dependency.target()),
+                _ => format!("`{}`", dependency.target()),
+            })
+            .collect();
+        reasons.sort();
+        reasons.dedup();
+        *waits.entry(reasons).or_default() += 1;
+    }
+    for (reasons, ordered) in waits {
+        let mut item = format!("{} ordered", count(ordered, "delta"));
+        if !reasons.is_empty() {
+            item.push_str(" due to dependency on ");
+            item.push_str(&reasons.join(", "));
+        }
+        items.push(item);
+    }
+
+    if metadata.conflicts_resolved > 0 {
+        let mut item = format!(
+            "{} auto-resolved",
+            count(metadata.conflicts_resolved, "conflict")
+        );
+        if let Some(Value::String(resolution)) = metadata.custom.get(CONFLICT_RESOLUTION_KEY) {
+            item.push_str(": ");
+            item.push_str(resolution);
+        }
+        items.push(item);
+    }
+
+    if let Some(selected) = metadata.custom.get(SELECTED_STRATEGY_KEY) {
+        items.push(format!(
+            "Strategy selection: {}",
+            describe_selection(selected)
+        ));
+    }
+
+    if let Some(batches) = metadata.batch_count.filter(|&batches| batches > 1) {
+        items.push(format!("Validated in {batches} batches"));
+    }
+
+    Explanation { summary, items }
+}
+
+/// Adverb for how a strategy applies unconstrained deltas
+fn manner<S: CompositionStrategy>(strategy: &S) -> String {
+    match strategy.name() {
+        "CommutativeBatch" => "commutatively".to_string(),
+        "SingleWriter" => "as disjoint single-writer edits".to_string(),
+        "OrderedComposition" => "in explicit order".to_string(),
+        _ => match strategy.parallelism() {
+            Parallelism::Full => "in parallel".to_string(),
+            Parallelism::Partial => "partly in parallel".to_string(),
+            Parallelism::None => "sequentially".to_string(),
+        },
+    }
+}
+
+/// Render a [`SELECTED_STRATEGY_KEY`] value
+fn describe_selection(selected: &Value) -> String {
+    match selected {
+        Value::String(name) => format!("`{name}`"),
+        Value::Object(object) => {
+            if let Some(member) = object.get("fallback") {
+                let mut text = describe_selection(member);
+                if let Some(Value::Array(rejected)) = object.get("rejected") {
+                    let rejected: Vec<String> = rejected.iter().map(describe_selection).collect();
+                    if !rejected.is_empty() {
+                        let _ = write!(text, " after {} rejected the batch", rejected.join(", "));
+                    }
+                }
+                text
+            } else if let Some(Value::Object(partitions)) = object.get("partitioned") {
+                partitions
+                    .iter()
+                    .map(|(prefix, member)| {
+                        let scope = if prefix.is_empty() {
+                            "other paths".to_string()
+                        } else {
+                            format!("`{prefix}.*`")
+                        };
+                        format!("{scope} → {}", describe_selection(member))
+                    })
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            } else {
+                selected.to_string()
+            }
+        }
+        other => other.to_string(),
+    }
+}
+
+fn count(n: usize, noun: &str) -> String {
+    if n == 1 {
+        format!("1 {noun}")
+    } else {
+        format!("{n} {noun}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::{OrderingConstraint, ValidationMetadata};
+    use crate::{CommutativeBatchStrategy, StrategyPolicy, StrategyRegistry};
+    use coa_artifact::{ContentHash, SymbolPath};
+    use coa_symbol::SymbolRefIndex;
+    use std::str::FromStr;
+
+    #[derive(Debug, Clone)]
+    struct TestArtifact;
+
+    impl coa_artifact::__private::Sealed for TestArtifact {}
+
+    impl ArtifactType for TestArtifact {
+        type Content = String;
+
+        fn hash(content: &Self::Content) -> ContentHash {
+            ContentHash::compute(content.as_bytes())
+        }
+
+        const TYPE_ID: &'static str = "test";
+    }
+
+    fn delta(
+        target: &str,
+        operation: DeltaOperation<TestArtifact>,
+    ) -> StructuralDelta<TestArtifact> {
+        StructuralDelta::new(
+            SymbolPath::from_str(target).unwrap(),
+            operation,
+            ContentHash::compute(b"base"),
+        )
+    }
+
+    #[test]
+    fn narrates_groups_orderings_and_conflicts() {
+        let deltas = vec![
+            delta("auth.login", DeltaOperation::Replace("a".to_string())),
+            delta("auth.logout", DeltaOperation::Replace("b".to_string())),
+            delta("auth.login_v2", DeltaOperation::Add("c".to_string())),
+            delta("auth.session", DeltaOperation::Replace("d".to_string())),
+            delta("api.routes", DeltaOperation::Replace("e".to_string())),
+            delta("api.handlers", DeltaOperation::Remove),
+        ];
+        let mut metadata = ValidationMetadata::default();
+        metadata.add_ordering(OrderingConstraint::new(4, vec![2]));
+        metadata.add_ordering(OrderingConstraint::new(5, vec![2]));
+        metadata.add_conflicts_resolved(1);
+        metadata.custom.insert(
+            CONFLICT_RESOLUTION_KEY.to_string(),
+            Value::from("merged appends in content-hash order"),
+        );
+        let validation = Validation::with_metadata(metadata);
+        let result = Artifact::<TestArtifact>::new("done".to_string()).unwrap();
+
+        let explanation = explain(
+            &CommutativeBatchStrategy::new(),
+            &deltas,
+            &validation,
+            Some(&result),
+        );
+        assert_eq!(
+            explanation.to_markdown(),
+            format!(
+                "**Composed 6 deltas with CommutativeBatch → `{}`**\n\n\
+                 - 4 deltas applied commutatively to `auth.*`\n\
+                 - 2 deltas ordered due to dependency on new symbol `auth.login_v2`\n\
+                 - 1 conflict auto-resolved: merged appends in content-hash order\n",
+                result.hash().short()
+            )
+        );
+    }
+
+    #[test]
+    fn describes_composed_strategy_selection() {
+        let registry = StrategyRegistry::with_defaults();
+        let strategy = registry
+            .partitioned(
+                [("ui", "ordered")],
+                StrategyPolicy::fallback(["ordered", "single_writer"]),
+            )
+            .unwrap();
+        let deltas = vec![
+            delta("core.a", DeltaOperation::Remove),
+            delta("core.b", DeltaOperation::Remove),
+        ];
+        let validation = strategy.validate(&deltas, &SymbolRefIndex::new()).unwrap();
+
+        let explanation = explain(&strategy, &deltas, &validation, None);
+        assert_eq!(explanation.summary, "Composed 2 deltas with Composed");
+        assert_eq!(
+            explanation.items,
+            vec![
+                "2 deltas applied sequentially to `core.*`".to_string(),
+                "Strategy selection: other paths → `single_writer` after `ordered` rejected the batch"
+                    .to_string(),
+            ]
+        );
+    }
+}
@@ -5,13 +5,13 @@
 use crate::commutative::CommutativeClassifier;
 use crate::ordered::OrderedClassifier;
 use crate::strategy::{
-    CompositionCost, CompositionError, CompositionStrategy, ConflictKind, DeltaClass,
+    CompositionCost, CompositionError, CompositionStrategy, ConflictKind, DeltaClass, DeltaEdit,
     Granularity, Parallelism, ResolutionSuggestion, TimeComplexity, Validation,
     ValidationDiagnostic, ValidationMetadata,
 };
 use coa_artifact::{Artifact, ArtifactType, StructuralDelta};
 use coa_symbol::SymbolRefIndex;
-use std::collections::HashSet;
+use std::collections::HashMap;
 
 /// Combines commutative batch with ordered refinement
 ///
@@ -40,6 +40,16 @@ impl<C: Classifier> std::fmt::Debug for HybridCompositionStrategy<C> {
 pub trait Classifier: Send + Sync {
     /// Classify a delta
     fn classify<T: ArtifactType>(&self, delta: &StructuralDelta<T>) -> DeltaClass;
+
+    /// Check if a delta classified [`DeltaClass::Commutative`] may really
+    /// join the commutative batch
+    ///
+    /// Defaults to the operation's own commutativity. Classifiers that know
+    /// more about the artifact (e.g. that distinct resources never interact)
+    /// override it.
+    fn commutes<T: ArtifactType>(&self, delta: &StructuralDelta<T>) -> bool {
+        CommutativeClassifier::is_commutative(delta)
+    }
 }
 
 impl Classifier for () {
@@ -78,18 +88,18 @@ impl<C: Classifier> HybridCompositionStrategy<C> {
         }
     }
 
-    /// Partition deltas into commutative and ordered batches
-    fn partition_deltas<'a, T: ArtifactType>(
+    /// Partition delta indices into commutative and ordered batches
+    fn partition_deltas<T: ArtifactType>(
         &self,
-        deltas: &'a [StructuralDelta<T>],
-    ) -> (Vec<&'a StructuralDelta<T>>, Vec<(u32, &'a StructuralDelta<T>)>) {
+        deltas: &[StructuralDelta<T>],
+    ) -> (Vec<usize>, Vec<(u32, usize)>) {
         let mut commutative = Vec::new();
         let mut ordered = Vec::new();
 
-        for delta in deltas {
+        for (i, delta) in deltas.iter().enumerate() {
             match self.classifier.classify(delta) {
-                DeltaClass::Commutative => commutative.push(delta),
-                DeltaClass::Ordered(order) => ordered.push((order, delta)),
+                DeltaClass::Commutative => commutative.push(i),
+                DeltaClass::Ordered(order) => ordered.push((order, i)),
             }
         }
 
@@ -102,37 +112,39 @@ impl<C: Classifier> HybridCompositionStrategy<C> {
     /// Validate commutative batch
     fn validate_commutative_batch<T: ArtifactType>(
         &self,
-        batch: &[&StructuralDelta<T>],
+        deltas: &[StructuralDelta<T>],
+        batch: &[usize],
     ) -> Result<(), CompositionError> {
         // Check for duplicates
-        let mut seen = HashSet::new();
-        for delta in batch {
-            let key = delta.target().to_string();
-            if !seen.insert(key.clone()) {
+        let mut seen = HashMap::new();
+        for &i in batch {
+            let key = deltas[i].target().to_string();
+            if let Some(&first) = seen.get(&key) {
                 return Err(CompositionError::validation_failed(
-                    ValidationDiagnostic {
-                        kind: ConflictKind::OverlappingTargets,
-                        involved_deltas: vec![],
-                        description: format!(
-                            "Duplicate target in commutative batch: {}",
-                            key
-                        ),
-                        suggestions: vec![ResolutionSuggestion::UseSingleWriter],
-                    },
+                    ValidationDiagnostic::new(
+                        ConflictKind::OverlappingTargets,
+                        format!("Duplicate target in commutative batch: {key}"),
+                    )
+                    .involving(deltas, [first, i])
+                    .with_suggestion(ResolutionSuggestion::ApplyEdits {
+                        edits: vec![DeltaEdit::Drop { delta_index: i }],
+                    })
+                    .with_suggestion(ResolutionSuggestion::UseSingleWriter),
                 ));
             }
+            seen.insert(key, i);
         }
 
         // All must actually be commutative operations
-        for delta in batch {
-            if !CommutativeClassifier::is_commutative(delta) {
+        for &i in batch {
+            if !self.classifier.commutes(&deltas[i]) {
                 return Err(CompositionError::validation_failed(
-                    ValidationDiagnostic {
-                        kind: ConflictKind::NonCommutativeOperations,
-                        involved_deltas: vec![],
-                        description: "Non-commutative operation in commutative batch".to_string(),
-                        suggestions: vec![ResolutionSuggestion::UseOrdered],
-                    },
+                    ValidationDiagnostic::new(
+                        ConflictKind::NonCommutativeOperations,
+                        "Non-commutative operation in commutative batch",
+                    )
+                    .involving(deltas, [i])
+                    .with_suggestion(ResolutionSuggestion::UseOrdered),
                 ));
             }
         }
@@ -143,22 +155,31 @@ impl<C: Classifier> HybridCompositionStrategy<C> {
     /// Validate ordered sequence
     fn validate_ordered_sequence<T: ArtifactType>(
         &self,
-        sequence: &[(u32, &StructuralDelta<T>)],
+        deltas: &[StructuralDelta<T>],
+        sequence: &[(u32, usize)],
     ) -> Result<(), CompositionError> {
         // Check all have explicit ordering
-        for (order, delta) in sequence {
-            if delta.order() != Some(*order) {
-                return Err(CompositionError::validation_failed(
-                    ValidationDiagnostic {
-                        kind: ConflictKind::MissingOrdering,
-                        involved_deltas: vec![],
-                        description: format!("Delta has mismatched order: expected {:?}, got {:?}", delta.order(), order),
-                        suggestions: vec![ResolutionSuggestion::AddOrdering {
-                            suggested_order: vec![],
-                        }],
-                    },
-                ));
-            }
+        let mismatched: Vec<(usize, u32)> = sequence
+            .iter()
+            .filter(|&&(order, i)| deltas[i].order() != Some(order))
+            .map(|&(order, i)| (i, order))
+            .collect();
+
+        if let Some(&(i, order)) = mismatched.first() {
+            return Err(CompositionError::validation_failed(
+                ValidationDiagnostic::new(
+                    ConflictKind::MissingOrdering,
+                    format!(
+                        "Delta has mismatched order: expected {:?}, got {:?}",
+                        deltas[i].order(),
+                        order
+                    ),
+                )
+                .involving(deltas, mismatched.iter().map(|&(i, _)| i))
+                .with_suggestion(ResolutionSuggestion::AddOrdering {
+                    suggested_order: mismatched,
+                }),
+            ));
         }
 
         Ok(())
@@ -192,10 +213,10 @@ impl<C: Classifier> CompositionStrategy for HybridCompositionStrategy<C> {
         let (commutative, ordered) = self.partition_deltas(deltas);
 
         // Validate commutative batch
-        self.validate_commutative_batch(&commutative)?;
+        self.validate_commutative_batch(deltas, &commutative)?;
 
         // Validate ordered sequence
-        self.validate_ordered_sequence(&ordered)?;
+        self.validate_ordered_sequence(deltas, &ordered)?;
 
         let mut metadata = ValidationMetadata::default();
         metadata.set_batch_count(2); // Commutative + Ordered
@@ -204,6 +225,7 @@ impl<C: Classifier> CompositionStrategy for HybridCompositionStrategy<C> {
             time: TimeComplexity::ON,
             space: crate::strategy::SpaceComplexity::ON,
             parallelism_factor: self.compute_parallelism_factor(commutative.len(), deltas.len()),
+            measured: None,
         };
 
         Ok(Validation::with_metadata(metadata).with_cost(cost))
@@ -211,11 +233,11 @@ impl<C: Classifier> CompositionStrategy for HybridCompositionStrategy<C> {
 
     fn compose<T: ArtifactType>(
         &self,
-        _base: &Artifact<T>,
+        base: &Artifact<T>,
         deltas: &[StructuralDelta<T>],
     ) -> Result<Artifact<T>, CompositionError> {
         if deltas.is_empty() {
-            return Err(CompositionError::NotValidated);
+            return Ok(base.clone());
         }
 
         let (commutative, ordered) = self.partition_deltas(deltas);
@@ -242,6 +264,10 @@ impl<C: Classifier> CompositionStrategy for HybridCompositionStrategy<C> {
     fn name(&self) -> &'static str {
         "HybridComposition"
     }
+
+    fn config_id(&self) -> String {
+        format!("{}:{}", self.name(), std::any::type_name::<C>())
+    }
 }
 
 #[cfg(test)]
@@ -372,6 +398,36 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn hybrid_unordered_transform_suggests_order_edit() {
+        let strategy = HybridCompositionStrategy::new();
+        let index = SymbolRefIndex::new();
+
+        let deltas = vec![
+            make_add_delta("layer1", test_hash()),
+            StructuralDelta::new(
+                SymbolPath::from_str("layer2").unwrap(),
+                DeltaOperation::Replace(TestContent),
+                test_hash(),
+            ),
+        ];
+
+        let Err(CompositionError::ValidationFailed { diagnostic }) =
+            strategy.validate(&deltas, &index)
+        else {
+            panic!("expected validation failure");
+        };
+        assert_eq!(diagnostic.kind, ConflictKind::MissingOrdering);
+        assert_eq!(diagnostic.paths[0].to_string(), "layer2");
+        assert_eq!(
+            diagnostic.edits(),
+            vec![DeltaEdit::SetOrder { delta_index: 1, order: 1 }]
+        );
+
+        let repaired = DeltaEdit::apply_all(deltas, &diagnostic.edits());
+        assert!(strategy.validate(&repaired, &index).is_ok());
+    }
+
     #[test]
     fn hybrid_parallelism_partial() {
         let strategy = HybridCompositionStrategy::new();
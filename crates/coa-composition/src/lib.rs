@@ -10,6 +10,12 @@
 //! - [`CommutativeBatchStrategy`]: Order-independent operations (maximum parallelism)
 //! - [`HybridCompositionStrategy`]: Best of both worlds
 //! - [`StrategyRegistry`]: Registry for strategy selection
+//! - [`StrategyPolicy`]: Fallback chains and per-subtree routing over registered strategies
+//! - [`CompositionCache`]: Memoized results keyed by base and delta batch hash
+//! - [`CostModel`]: Predicts strategy cost from batch size, calibrated by [`Calibrator`]
+//! - [`explain`]: Markdown narrative of a composition, e.g. for pull request descriptions
+//! - [`MemoryBudget`]: Rejects compositions whose projected memory exceeds a cap
+//! - [`CompositionTransaction`]: Atomic composition across multiple artifacts
 //!
 //! # Example
 //!
@@ -32,23 +38,44 @@
 #![allow(missing_docs)]
 
 // Strategy implementations
+mod cache;
+mod combinator;
 mod commutative;
+mod cost;
+mod explain;
 mod hybrid;
+mod memory;
 mod ordered;
 mod registry;
 mod single_writer;
 mod strategy;
+mod transaction;
 
 // Re-exports
-pub use commutative::{CommutativeBatchStrategy, CommutativeClassifier};
-pub use hybrid::HybridCompositionStrategy;
+pub use cache::{CachedComposition, CompositionCache, CompositionCacheStats, CompositionKey};
+pub use combinator::{
+    ComposedStrategy, PartitionPolicy, StrategyPolicy, SELECTED_STRATEGY_KEY,
+};
+pub use commutative::{
+    CommutativeBatchStrategy, CommutativeClassifier, DEFAULT_PARALLEL_THRESHOLD,
+};
+pub use cost::{
+    BatchProfile, CalibrationError, Calibrator, CostCoefficients, CostEstimate, CostModel,
+    CostSample, LinearCostModel, COST_FEATURES,
+};
+pub use explain::{explain, Explanation, CONFLICT_RESOLUTION_KEY};
+pub use hybrid::{Classifier, HybridCompositionStrategy};
+pub use memory::{MemoryBudget, MemoryEstimate};
 pub use ordered::{OrderedClassifier, OrderedCompositionStrategy};
-pub use registry::{StrategyHint, StrategyRegistry, StrategySelector};
+pub use registry::{ComposableArtifact, StrategyHint, StrategyRegistry, StrategySelector};
 pub use single_writer::{SingleWriterClassifier, SingleWriterStrategy};
 pub use strategy::{
-    CompositionCost, CompositionError, CompositionStrategy, ConflictKind, DeltaClass,
-    Granularity, OrderingConstraint, Parallelism, ResolutionSuggestion, SpaceComplexity,
-    TimeComplexity, Validation, ValidationDiagnostic, ValidationMetadata,
+    CompositionCost, CompositionError, CompositionStrategy, ConflictKind, DeltaClass, DeltaEdit,
+    Granularity, MeasuredParallelism, OrderingConstraint, Parallelism, ResolutionSuggestion,
+    SpaceComplexity, TimeComplexity, Validation, ValidationDiagnostic, ValidationMetadata,
+};
+pub use transaction::{
+    CommittedTransaction, CompositionTransaction, TransactionError, TransactionSlot,
 };
 
 /// Version of this crate
@@ -0,0 +1,239 @@
+//! Memory guardrails for composition
+//!
+//! [`CompositionCost`] only classifies space symbolically. [`MemoryBudget`]
+//! turns that class into a byte projection using
+//! [`ArtifactType::estimated_size`] and rejects a composition whose projected
+//! peak exceeds the configured cap before any content is cloned.
+//!
+//! Projections are approximate: they protect the orchestrator from
+//! pathologically large artifacts, not from small overshoots.
+
+use crate::strategy::{CompositionCost, CompositionError, CompositionStrategy, SpaceComplexity};
+use coa_artifact::{Artifact, ArtifactType, StructuralDelta};
+use coa_symbol::SymbolRefIndex;
+
+/// Projected memory use of one composition
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryEstimate {
+    /// Estimated size of the base artifact
+    pub base_bytes: usize,
+    /// Estimated size of all deltas
+    pub delta_bytes: usize,
+    /// Strategy working set derived from its space complexity
+    pub working_bytes: usize,
+    /// Projected peak: base, result (base + deltas) and working set
+    pub projected_bytes: usize,
+}
+
+impl MemoryEstimate {
+    /// Project peak memory for composing `deltas` onto `base`
+    #[must_use]
+    pub fn project<T: ArtifactType>(
+        space: SpaceComplexity,
+        base: &Artifact<T>,
+        deltas: &[StructuralDelta<T>],
+    ) -> Self {
+        let base_bytes = base.estimated_size();
+        let delta_bytes = deltas
+            .iter()
+            .map(StructuralDelta::estimated_size)
+            .fold(0usize, usize::saturating_add);
+        let working_bytes = space.working_bytes(delta_bytes, deltas.len());
+        let projected_bytes = base_bytes
+            .saturating_mul(2)
+            .saturating_add(delta_bytes)
+            .saturating_add(working_bytes);
+
+        Self {
+            base_bytes,
+            delta_bytes,
+            working_bytes,
+            projected_bytes,
+        }
+    }
+}
+
+/// Upper bound on projected composition memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryBudget {
+    cap_bytes: usize,
+}
+
+impl MemoryBudget {
+    /// Create budget capping projected memory at `cap_bytes`
+    #[inline]
+    #[must_use]
+    pub fn new(cap_bytes: usize) -> Self {
+        Self { cap_bytes }
+    }
+
+    /// Configured cap in bytes
+    #[inline]
+    #[must_use]
+    pub fn cap_bytes(&self) -> usize {
+        self.cap_bytes
+    }
+
+    /// Check a composition against the budget
+    ///
+    /// # Errors
+    /// Returns `CompositionError::MemoryBudgetExceeded` if the projection
+    /// exceeds the cap
+    pub fn check<T: ArtifactType>(
+        &self,
+        cost: &CompositionCost,
+        base: &Artifact<T>,
+        deltas: &[StructuralDelta<T>],
+    ) -> Result<MemoryEstimate, CompositionError> {
+        let estimate = MemoryEstimate::project(cost.space, base, deltas);
+        if estimate.projected_bytes > self.cap_bytes {
+            return Err(CompositionError::MemoryBudgetExceeded {
+                projected: estimate.projected_bytes,
+                cap: self.cap_bytes,
+            });
+        }
+        Ok(estimate)
+    }
+
+    /// Validate, check the budget, then compose
+    ///
+    /// # Errors
+    /// Returns the strategy's validation or composition error, or
+    /// `CompositionError::MemoryBudgetExceeded` before composing
+    pub fn compose<T, S>(
+        &self,
+        strategy: &S,
+        base: &Artifact<T>,
+        deltas: &[StructuralDelta<T>],
+        index: &SymbolRefIndex,
+    ) -> Result<Artifact<T>, CompositionError>
+    where
+        T: ArtifactType,
+        S: CompositionStrategy,
+    {
+        let validation = strategy.validate(deltas, index)?;
+        self.check(&validation.cost_estimate, base, deltas)?;
+        strategy.compose(base, deltas)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::{Granularity, Parallelism, TimeComplexity, Validation};
+    use coa_artifact::{ContentHash, DeltaOperation, SymbolPath};
+    use std::str::FromStr;
+
+    #[derive(Debug, Clone)]
+    struct TestArtifact;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct TestContent(String);
+
+    impl coa_artifact::__private::Sealed for TestArtifact {}
+
+    impl ArtifactType for TestArtifact {
+        type Content = TestContent;
+
+        fn hash(content: &Self::Content) -> ContentHash {
+            ContentHash::compute(content.0.as_bytes())
+        }
+
+        const TYPE_ID: &'static str = "test";
+
+        fn estimated_size(content: &Self::Content) -> usize {
+            content.0.len()
+        }
+    }
+
+    /// Declares the given space class and returns the base unchanged
+    #[derive(Debug)]
+    struct SpaceStrategy(SpaceComplexity);
+
+    impl CompositionStrategy for SpaceStrategy {
+        fn validate<T: ArtifactType>(
+            &self,
+            _deltas: &[StructuralDelta<T>],
+            _index: &SymbolRefIndex,
+        ) -> Result<Validation, CompositionError> {
+            Ok(Validation::minimal().with_cost(CompositionCost {
+                time: TimeComplexity::ON,
+                space: self.0,
+                parallelism_factor: 0.0,
+                measured: None,
+            }))
+        }
+
+        fn compose<T: ArtifactType>(
+            &self,
+            base: &Artifact<T>,
+            _deltas: &[StructuralDelta<T>],
+        ) -> Result<Artifact<T>, CompositionError> {
+            Ok(base.clone())
+        }
+
+        fn parallelism(&self) -> Parallelism {
+            Parallelism::None
+        }
+
+        fn granularity(&self) -> Granularity {
+            Granularity::Node
+        }
+
+        fn name(&self) -> &'static str {
+            "Space"
+        }
+    }
+
+    fn base(size: usize) -> Artifact<TestArtifact> {
+        Artifact::new(TestContent("x".repeat(size))).unwrap()
+    }
+
+    fn adds(n: usize, size: usize) -> Vec<StructuralDelta<TestArtifact>> {
+        (0..n)
+            .map(|i| {
+                StructuralDelta::new(
+                    SymbolPath::from_str(&format!("item{i}")).unwrap(),
+                    DeltaOperation::Add(TestContent("y".repeat(size))),
+                    ContentHash::compute(b"base"),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn projection_scales_with_space_class() {
+        let base = base(1_000);
+        let deltas = adds(16, 100);
+
+        let constant = MemoryEstimate::project(SpaceComplexity::O1, &base, &deltas);
+        let log = MemoryEstimate::project(SpaceComplexity::OLogN, &base, &deltas);
+        let linear = MemoryEstimate::project(SpaceComplexity::ON, &base, &deltas);
+
+        assert_eq!(constant.base_bytes, 1_000);
+        assert!(constant.delta_bytes > 16 * 100);
+        assert_eq!(linear.working_bytes, linear.delta_bytes);
+        assert_eq!(log.working_bytes, constant.working_bytes * 5);
+        assert!(constant.projected_bytes < log.projected_bytes);
+        assert!(log.projected_bytes < linear.projected_bytes);
+    }
+
+    #[test]
+    fn budget_rejects_before_composing() {
+        let strategy = SpaceStrategy(SpaceComplexity::ON);
+        let index = SymbolRefIndex::new();
+        let base = base(10_000);
+        let deltas = adds(4, 1_000);
+
+        let err = MemoryBudget::new(16 * 1024)
+            .compose(&strategy, &base, &deltas, &index)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            CompositionError::MemoryBudgetExceeded { cap: 16_384, projected } if projected > 20_000
+        ));
+
+        let composed = MemoryBudget::new(64 * 1024).compose(&strategy, &base, &deltas, &index);
+        assert_eq!(composed.unwrap().hash(), base.hash());
+    }
+}
@@ -35,19 +35,20 @@ impl OrderedCompositionStrategy {
         let orders: Vec<_> = deltas.iter().map(|d| d.order()).collect();
 
         // Verify all deltas have ordering
-        for (i, order) in orders.iter().enumerate() {
-            if order.is_none() {
-                return Err(CompositionError::validation_failed(
-                    ValidationDiagnostic {
-                        kind: ConflictKind::MissingOrdering,
-                        involved_deltas: vec![i],
-                        description: format!("Delta {} missing required order", i),
-                        suggestions: vec![ResolutionSuggestion::AddOrdering {
-                            suggested_order: vec![(i, i as u32)],
-                        }],
-                    },
-                ));
-            }
+        let missing: Vec<usize> = (0..orders.len()).filter(|&i| orders[i].is_none()).collect();
+        if let Some(&first) = missing.first() {
+            // Suggest appending unordered deltas after the highest explicit order
+            let next = orders.iter().flatten().max().map_or(0, |max| max + 1);
+            let suggested_order = missing.iter().zip(next..).map(|(&i, o)| (i, o)).collect();
+
+            return Err(CompositionError::validation_failed(
+                ValidationDiagnostic::new(
+                    ConflictKind::MissingOrdering,
+                    format!("Delta {first} missing required order"),
+                )
+                .involving(deltas, missing)
+                .with_suggestion(ResolutionSuggestion::AddOrdering { suggested_order }),
+            ));
         }
 
         Ok(orders)
@@ -142,6 +143,7 @@ impl CompositionStrategy for OrderedCompositionStrategy {
             time: TimeComplexity::ON,
             space: crate::strategy::SpaceComplexity::O1,
             parallelism_factor: 0.0, // Sequential
+            measured: None,
         };
 
         Ok(Validation::with_metadata(metadata).with_cost(cost))
@@ -181,10 +183,11 @@ impl OrderedClassifier {
     #[inline]
     #[must_use]
     pub fn needs_order<T: ArtifactType>(delta: &StructuralDelta<T>) -> bool {
-        // Transform operations typically need ordering
+        // Transform operations typically need ordering; binary patches are
+        // computed against one exact base
         matches!(
             delta.operation(),
-            coa_artifact::DeltaOperation::Transform(_)
+            coa_artifact::DeltaOperation::Transform(_) | coa_artifact::DeltaOperation::BinaryPatch(_)
         )
     }
 
@@ -292,6 +295,43 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn ordered_missing_order_edits_repair_batch() {
+        let strategy = OrderedCompositionStrategy::new();
+        let index = SymbolRefIndex::new();
+
+        let deltas: Vec<StructuralDelta<TestArtifact>> = vec![
+            StructuralDelta::new(
+                SymbolPath::from_str("step1").unwrap(),
+                DeltaOperation::Remove,
+                test_hash(),
+            ),
+            make_delta_with_order("step2", 7, test_hash()),
+            StructuralDelta::new(
+                SymbolPath::from_str("step3").unwrap(),
+                DeltaOperation::Remove,
+                test_hash(),
+            ),
+        ];
+
+        let Err(CompositionError::ValidationFailed { diagnostic }) =
+            strategy.validate(&deltas, &index)
+        else {
+            panic!("expected validation failure");
+        };
+        assert_eq!(diagnostic.involved_deltas, vec![0, 2]);
+        assert_eq!(
+            diagnostic.edits(),
+            vec![
+                crate::DeltaEdit::SetOrder { delta_index: 0, order: 8 },
+                crate::DeltaEdit::SetOrder { delta_index: 2, order: 9 },
+            ]
+        );
+
+        let repaired = crate::DeltaEdit::apply_all(deltas, &diagnostic.edits());
+        assert!(strategy.validate(&repaired, &index).is_ok());
+    }
+
     #[test]
     fn ordered_single_delta_ok() {
         let strategy = OrderedCompositionStrategy::new();
@@ -2,7 +2,31 @@
 //!
 //! Provides [`StrategyRegistry`] for managing and selecting composition strategies.
 
-use std::collections::HashSet;
+use crate::combinator::{ComposedStrategy, StrategyPolicy};
+use crate::cost::{BatchProfile, CostEstimate, CostModel};
+use crate::strategy::CompositionError;
+use coa_artifact::ArtifactType;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Extension trait for artifact types that declare their composition class
+///
+/// Downstream artifact types (see [`coa_artifact::define_artifact_type!`])
+/// implement this so [`StrategyRegistry::select_name`] routes them without
+/// editing the built-in table.
+///
+/// # Example
+/// ```rust,ignore
+/// impl ComposableArtifact for SqlArtifact {
+///     const DEFAULT_STRATEGY: &'static str = "ordered";
+/// }
+///
+/// registry.register_artifact_type::<SqlArtifact>();
+/// ```
+pub trait ComposableArtifact: ArtifactType {
+    /// Strategy name used for every operation on this artifact type
+    const DEFAULT_STRATEGY: &'static str;
+}
 
 /// Registry of available composition strategy names
 ///
@@ -11,6 +35,7 @@ use std::collections::HashSet;
 #[derive(Debug, Default, Clone)]
 pub struct StrategyRegistry {
     strategies: HashSet<String>,
+    artifact_strategies: HashMap<String, &'static str>,
 }
 
 impl StrategyRegistry {
@@ -20,6 +45,7 @@ impl StrategyRegistry {
     pub fn new() -> Self {
         Self {
             strategies: HashSet::new(),
+            artifact_strategies: HashMap::new(),
         }
     }
 
@@ -39,6 +65,16 @@ impl StrategyRegistry {
         self.strategies.insert(name.to_string());
     }
 
+    /// Route an artifact type to a strategy, overriding the built-in table
+    pub fn classify_artifact(&mut self, type_id: &str, strategy: &'static str) {
+        self.artifact_strategies.insert(type_id.to_string(), strategy);
+    }
+
+    /// Register a [`ComposableArtifact`] with its default strategy
+    pub fn register_artifact_type<T: ComposableArtifact>(&mut self) {
+        self.classify_artifact(T::TYPE_ID, T::DEFAULT_STRATEGY);
+    }
+
     /// Check if strategy exists
     #[inline]
     #[must_use]
@@ -76,6 +112,7 @@ impl StrategyRegistry {
     /// Auto-select strategy name based on context
     ///
     /// # Selection Logic
+    /// - registered artifact types → their classified strategy
     /// - `code` → `single_writer` (safety-critical)
     /// - `svg`/`image` with `add_layer` → `commutative`
     /// - `audio`/`video` with `add_track` → `commutative`
@@ -83,6 +120,10 @@ impl StrategyRegistry {
     /// - default → `single_writer`
     #[must_use]
     pub fn select_name(&self, artifact_type: &str, operation: &str) -> &'static str {
+        if let Some(strategy) = self.artifact_strategies.get(artifact_type) {
+            return strategy;
+        }
+
         match (artifact_type, operation) {
             ("code", _) => "single_writer",
             ("svg" | "image", "add_layer" | "remove_layer") => "commutative",
@@ -97,6 +138,40 @@ impl StrategyRegistry {
     pub fn iter(&self) -> impl Iterator<Item = &String> {
         self.strategies.iter()
     }
+
+    /// Build the strategy described by `policy`
+    ///
+    /// # Errors
+    /// `CompositionError::InvalidPolicy` if the policy names an unregistered
+    /// strategy or one without a built-in implementation, has an empty
+    /// fallback chain or an invalid route prefix
+    pub fn build(&self, policy: &StrategyPolicy) -> Result<ComposedStrategy, CompositionError> {
+        ComposedStrategy::resolve(policy, |name| self.contains(name))
+    }
+
+    /// Strategy trying each of `members` in order until one validates
+    ///
+    /// # Errors
+    /// See [`build`](Self::build)
+    pub fn fallback<P: Into<StrategyPolicy>>(
+        &self,
+        members: impl IntoIterator<Item = P>,
+    ) -> Result<ComposedStrategy, CompositionError> {
+        self.build(&StrategyPolicy::fallback(members))
+    }
+
+    /// Strategy routing deltas by the longest matching path prefix in
+    /// `routes`, and the rest to `default`
+    ///
+    /// # Errors
+    /// See [`build`](Self::build)
+    pub fn partitioned<K: Into<String>, P: Into<StrategyPolicy>>(
+        &self,
+        routes: impl IntoIterator<Item = (K, P)>,
+        default: impl Into<StrategyPolicy>,
+    ) -> Result<ComposedStrategy, CompositionError> {
+        self.build(&StrategyPolicy::partitioned(routes, default))
+    }
 }
 
 /// Strategy selection hint
@@ -120,6 +195,7 @@ pub enum StrategyHint {
 #[derive(Debug, Default)]
 pub struct StrategySelector {
     hint: StrategyHint,
+    cost_model: Option<Arc<dyn CostModel>>,
 }
 
 impl StrategySelector {
@@ -128,6 +204,7 @@ impl StrategySelector {
     pub fn new() -> Self {
         Self {
             hint: StrategyHint::Balanced,
+            cost_model: None,
         }
     }
 
@@ -149,6 +226,45 @@ impl StrategySelector {
             StrategyHint::Balanced => "hybrid",
         }
     }
+
+    /// Rank strategies with `model` in [`select_cheapest`](Self::select_cheapest)
+    #[must_use]
+    pub fn with_cost_model(mut self, model: Arc<dyn CostModel>) -> Self {
+        self.cost_model = Some(model);
+        self
+    }
+
+    /// Predicted cost of `strategy` on a batch, if a model is set and knows it
+    #[must_use]
+    pub fn estimate(&self, strategy: &str, profile: &BatchProfile) -> Option<CostEstimate> {
+        self.cost_model.as_ref()?.estimate(strategy, profile)
+    }
+
+    /// Candidate with the lowest predicted time, then space
+    ///
+    /// Candidates the model cannot estimate are passed over. Without a cost
+    /// model, or if no candidate can be estimated, falls back to the hint's
+    /// strategy when it is a candidate and to the first candidate otherwise.
+    #[must_use]
+    pub fn select_cheapest<'a>(
+        &self,
+        candidates: &[&'a str],
+        profile: &BatchProfile,
+    ) -> Option<&'a str> {
+        candidates
+            .iter()
+            .filter_map(|&name| Some((self.estimate(name, profile)?, name)))
+            .min()
+            .map(|(_, name)| name)
+            .or_else(|| {
+                let hinted = self.select_name("", "");
+                candidates
+                    .iter()
+                    .find(|&&name| name == hinted)
+                    .or(candidates.first())
+                    .copied()
+            })
+    }
 }
 
 #[cfg(test)]
@@ -217,6 +333,30 @@ mod tests {
         assert_eq!(name, "ordered");
     }
 
+    #[test]
+    fn registry_select_name_registered_artifact() {
+        coa_artifact::define_artifact_type! {
+            SchemaArtifact {
+                content: String,
+                type_id: "schema",
+                hash: |s| coa_artifact::ContentHash::compute(s.as_bytes()),
+            }
+        }
+
+        impl ComposableArtifact for SchemaArtifact {
+            const DEFAULT_STRATEGY: &'static str = "ordered";
+        }
+
+        let mut registry = StrategyRegistry::with_defaults();
+        assert_eq!(registry.select_name("schema", "add"), "single_writer");
+
+        registry.register_artifact_type::<SchemaArtifact>();
+        assert_eq!(registry.select_name("schema", "add"), "ordered");
+
+        registry.classify_artifact("code", "hybrid");
+        assert_eq!(registry.select_name("code", "modify"), "hybrid");
+    }
+
     #[test]
     fn registry_select_name_default() {
         let registry = StrategyRegistry::with_defaults();
@@ -245,6 +385,36 @@ mod tests {
         assert_eq!(name, "commutative");
     }
 
+    #[test]
+    fn selector_picks_cheapest_estimate() {
+        use crate::cost::{CostCoefficients, LinearCostModel};
+        use crate::{SpaceComplexity, TimeComplexity};
+
+        let candidates = ["single_writer", "ordered", "custom"];
+        let batch = BatchProfile {
+            delta_count: 1_000,
+            artifact_bytes: 4_096,
+            delta_bytes: 64_000,
+            max_depth: 3,
+        };
+
+        let selector = StrategySelector::new().with_hint(StrategyHint::Safety);
+        assert_eq!(selector.select_cheapest(&candidates, &batch), Some("single_writer"));
+
+        let mut model = LinearCostModel::default();
+        let selector = selector.with_cost_model(Arc::new(model.clone()));
+        assert_eq!(selector.select_cheapest(&candidates, &batch), Some("ordered"));
+        assert_eq!(selector.estimate("custom", &batch), None);
+
+        model.set(
+            "custom",
+            CostCoefficients::from_class(TimeComplexity::O1, SpaceComplexity::O1),
+        );
+        let selector = StrategySelector::new().with_cost_model(Arc::new(model));
+        assert_eq!(selector.select_cheapest(&candidates, &batch), Some("custom"));
+        assert_eq!(selector.select_cheapest(&[], &batch), None);
+    }
+
     #[test]
     fn strategy_hint_variants() {
         assert!(StrategyHint::Safety != StrategyHint::Parallelism);
@@ -4,12 +4,12 @@
 //! Maximum safety, universal applicability.
 
 use crate::strategy::{
-    CompositionCost, CompositionError, CompositionStrategy, ConflictKind, DeltaClass,
+    CompositionCost, CompositionError, CompositionStrategy, ConflictKind, DeltaClass, DeltaEdit,
     Granularity, Parallelism, ResolutionSuggestion, TimeComplexity, Validation,
     ValidationDiagnostic, ValidationMetadata,
 };
 use coa_artifact::{Artifact, ArtifactType, StructuralDelta};
-use coa_symbol::{SingleWriterValidator, SymbolRefIndex};
+use coa_symbol::{SingleWriterValidator, SymbolRefIndex, ValidationError};
 
 /// Single writer strategy: disjoint subtree claims
 ///
@@ -18,15 +18,32 @@ use coa_symbol::{SingleWriterValidator, SymbolRefIndex};
 /// - Universal applicability
 /// - Requires fine-grained COA decomposition
 /// - Fully parallel composition
-#[derive(Debug, Clone, Copy, Default)]
-pub struct SingleWriterStrategy;
+///
+/// Claims are disjoint at [`Granularity::Subtree`] by default. Code
+/// artifacts can opt into [`Granularity::Span`], where edits to the same
+/// symbol are accepted as long as the AST-node spans they rewrite are
+/// disjoint.
+#[derive(Debug, Clone, Copy)]
+pub struct SingleWriterStrategy {
+    granularity: Granularity,
+}
 
 impl SingleWriterStrategy {
     /// Create new single writer strategy
     #[inline]
     #[must_use]
     pub fn new() -> Self {
-        Self
+        Self {
+            granularity: Granularity::Subtree,
+        }
+    }
+
+    /// Set conflict detection granularity
+    #[inline]
+    #[must_use]
+    pub fn with_granularity(mut self, granularity: Granularity) -> Self {
+        self.granularity = granularity;
+        self
     }
 
     /// Validate deltas have disjoint targets
@@ -37,21 +54,67 @@ impl SingleWriterStrategy {
     ) -> Result<(), CompositionError> {
         let validator = SingleWriterValidator::new();
 
-        validator
-            .validate_deltas(deltas, index)
-            .map_err(|e| CompositionError::validation_failed_simple(
-                ConflictKind::OverlappingTargets,
-                e.to_string(),
-            ))?;
-
-        validator
-            .validate_against_index(deltas, index)
-            .map_err(|e| CompositionError::validation_failed_simple(
-                ConflictKind::OverlappingTargets,
-                e.to_string(),
-            ))?;
-
-        Ok(())
+        let pairwise = match self.granularity {
+            Granularity::Subtree => validator.validate_deltas(deltas, index),
+            granularity => Self::validate_granular(granularity, deltas),
+        };
+        pairwise
+            .and_then(|()| validator.validate_against_index(deltas, index))
+            .map_err(|e| Self::diagnose(deltas, &e))
+    }
+
+    /// Pairwise check at a finer granularity than path prefixes
+    fn validate_granular<T: ArtifactType>(
+        granularity: Granularity,
+        deltas: &[StructuralDelta<T>],
+    ) -> Result<(), ValidationError> {
+        let Some((i, j)) = granularity.first_conflict(deltas) else {
+            return Ok(());
+        };
+        let (a, b) = (deltas[i].target(), deltas[j].target());
+        Err(ValidationError::OverlappingClaims {
+            claim1: a.to_string(),
+            claim2: b.to_string(),
+            delta1_index: i,
+            delta2_index: j,
+            suggestion: coa_symbol::ResolutionSuggestion::DecomposeTargets {
+                common_prefix: SingleWriterValidator::find_common_prefix(a, b),
+            },
+        })
+    }
+
+    /// Convert a symbol validation error into a structured diagnostic
+    fn diagnose<T: ArtifactType>(
+        deltas: &[StructuralDelta<T>],
+        error: &ValidationError,
+    ) -> CompositionError {
+        let diagnostic = ValidationDiagnostic::new(ConflictKind::OverlappingTargets, error.to_string());
+        let diagnostic = match error {
+            ValidationError::OverlappingClaims {
+                delta1_index,
+                delta2_index,
+                suggestion: coa_symbol::ResolutionSuggestion::DecomposeTargets { common_prefix },
+                ..
+            } => diagnostic
+                .involving(deltas, [*delta1_index, *delta2_index])
+                .with_suggestion(ResolutionSuggestion::DecomposeTargets {
+                    common_prefix: common_prefix.clone(),
+                }),
+            ValidationError::OverlappingClaims {
+                delta1_index,
+                delta2_index,
+                ..
+            } => diagnostic.involving(deltas, [*delta1_index, *delta2_index]),
+            ValidationError::ClaimOverlapsExisting { delta_index, .. } => diagnostic
+                .involving(deltas, [*delta_index])
+                .with_suggestion(ResolutionSuggestion::ApplyEdits {
+                    edits: vec![DeltaEdit::Drop {
+                        delta_index: *delta_index,
+                    }],
+                }),
+            ValidationError::InvalidPath { .. } | ValidationError::Internal(_) => diagnostic,
+        };
+        CompositionError::validation_failed(diagnostic)
     }
 
     /// Apply deltas in any order (they're independent)
@@ -105,6 +168,7 @@ impl CompositionStrategy for SingleWriterStrategy {
             time: TimeComplexity::ONLogN,
             space: crate::strategy::SpaceComplexity::ON,
             parallelism_factor: 1.0, // Fully parallel
+            measured: None,
         };
 
         Ok(Validation::with_metadata(metadata).with_cost(cost))
@@ -127,12 +191,22 @@ impl CompositionStrategy for SingleWriterStrategy {
     }
 
     fn granularity(&self) -> Granularity {
-        Granularity::Subtree
+        self.granularity
     }
 
     fn name(&self) -> &'static str {
         "SingleWriter"
     }
+
+    fn config_id(&self) -> String {
+        format!("{}:{:?}", self.name(), self.granularity)
+    }
+}
+
+impl Default for SingleWriterStrategy {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 /// Classifier for single writer compatibility
@@ -161,7 +235,7 @@ impl SingleWriterClassifier {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use coa_artifact::{ArtifactType, ContentHash, DeltaOperation, SymbolPath};
+    use coa_artifact::{ArtifactType, ContentHash, DeltaOperation, NodeSpan, SymbolPath};
     use std::str::FromStr;
 
     // Test artifact type
@@ -231,11 +305,38 @@ mod tests {
         ];
 
         let result = strategy.validate(&deltas, &index);
-        assert!(result.is_err());
-        assert!(matches!(
-            result.unwrap_err(),
-            CompositionError::ValidationFailed { .. }
-        ));
+        let Err(CompositionError::ValidationFailed { diagnostic }) = result else {
+            panic!("expected validation failure");
+        };
+        assert_eq!(diagnostic.involved_deltas, vec![0, 1]);
+        assert_eq!(diagnostic.paths[0].to_string(), "auth");
+        assert_eq!(
+            diagnostic.suggestions,
+            vec![ResolutionSuggestion::DecomposeTargets {
+                common_prefix: "auth".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn single_writer_span_granularity_allows_disjoint_edits_in_one_symbol() {
+        let index = SymbolRefIndex::new();
+        let edit = |start, end| make_delta("auth.login", test_hash()).with_span(NodeSpan::new(start, end));
+
+        let disjoint = vec![edit(0, 10), edit(20, 30)];
+        assert!(SingleWriterStrategy::new().validate(&disjoint, &index).is_err());
+
+        let strategy = SingleWriterStrategy::new().with_granularity(Granularity::Span);
+        assert_eq!(strategy.granularity(), Granularity::Span);
+        assert!(strategy.validate(&disjoint, &index).is_ok());
+
+        let overlapping = vec![edit(0, 10), edit(5, 30)];
+        let Err(CompositionError::ValidationFailed { diagnostic }) =
+            strategy.validate(&overlapping, &index)
+        else {
+            panic!("expected validation failure");
+        };
+        assert_eq!(diagnostic.involved_deltas, vec![0, 1]);
     }
 
     #[test]
@@ -3,9 +3,11 @@
 //! Provides the [`CompositionStrategy`] trait for pluggable conflict resolution
 //! in multi-agent delta composition.
 
-use coa_artifact::{Artifact, ArtifactType, ContentHash, StructuralDelta};
+use coa_artifact::{Artifact, ArtifactType, ContentHash, StructuralDelta, SymbolPath};
 use coa_symbol::SymbolRefIndex;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::time::Duration;
 
 /// Composition strategy for multi-agent delta coordination
 ///
@@ -48,6 +50,15 @@ pub trait CompositionStrategy: Send + Sync + std::fmt::Debug {
 
     /// Strategy name (for debugging/serialization)
     fn name(&self) -> &'static str;
+
+    /// Identity of this strategy's configuration (for result caching)
+    ///
+    /// Two strategies with equal identities must compose identically.
+    /// Defaults to [`name`](Self::name); configurable strategies must
+    /// include their configuration.
+    fn config_id(&self) -> String {
+        self.name().to_string()
+    }
 }
 
 /// Validation result with metadata
@@ -99,6 +110,9 @@ pub struct ValidationMetadata {
     /// Ordering constraints (for sequential strategies)
     pub ordering: Vec<OrderingConstraint>,
 
+    /// Overlapping edits the strategy reconciled instead of rejecting
+    pub conflicts_resolved: usize,
+
     /// Custom strategy data
     pub custom: HashMap<String, serde_json::Value>,
 }
@@ -115,6 +129,12 @@ impl ValidationMetadata {
     pub fn set_batch_count(&mut self, count: usize) {
         self.batch_count = Some(count);
     }
+
+    /// Record reconciled conflicts
+    #[inline]
+    pub fn add_conflicts_resolved(&mut self, count: usize) {
+        self.conflicts_resolved += count;
+    }
 }
 
 /// Ordering constraint between deltas
@@ -150,6 +170,19 @@ pub struct CompositionCost {
 
     /// Parallelism factor (1.0 = fully parallel)
     pub parallelism_factor: f64,
+
+    /// Parallelism actually used by validation, if the strategy measured it
+    pub measured: Option<MeasuredParallelism>,
+}
+
+impl CompositionCost {
+    /// Attach measured parallelism
+    #[inline]
+    #[must_use]
+    pub fn with_measured(mut self, measured: MeasuredParallelism) -> Self {
+        self.measured = Some(measured);
+        self
+    }
 }
 
 impl Default for CompositionCost {
@@ -158,12 +191,26 @@ impl Default for CompositionCost {
             time: TimeComplexity::ON,
             space: SpaceComplexity::ON,
             parallelism_factor: 0.0,
+            measured: None,
         }
     }
 }
 
-/// Time complexity classes
+/// Parallelism observed while validating a batch
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MeasuredParallelism {
+    /// Execution mode that was used
+    pub mode: Parallelism,
+
+    /// Worker threads available to the run (1 when sequential)
+    pub workers: usize,
+
+    /// Wall-clock validation time
+    pub elapsed: Duration,
+}
+
+/// Time complexity classes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TimeComplexity {
     /// O(1) - constant time
     O1,
@@ -182,7 +229,7 @@ pub enum TimeComplexity {
 }
 
 /// Space complexity classes
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SpaceComplexity {
     /// O(1) - constant space
     O1,
@@ -194,6 +241,25 @@ pub enum SpaceComplexity {
     ON,
 }
 
+impl SpaceComplexity {
+    /// Approximate working-set bytes for `n` deltas totalling `delta_bytes`
+    ///
+    /// O(1) keeps about one delta live, O(log n) about `log2 n` of them,
+    /// O(n) all of them.
+    #[must_use]
+    pub fn working_bytes(self, delta_bytes: usize, n: usize) -> usize {
+        if n == 0 {
+            return 0;
+        }
+        let average = delta_bytes / n;
+        match self {
+            Self::O1 => average,
+            Self::OLogN => average.saturating_mul(n.ilog2() as usize + 1),
+            Self::ON => delta_bytes,
+        }
+    }
+}
+
 /// Parallelism characteristics
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Parallelism {
@@ -227,6 +293,47 @@ pub enum Granularity {
 
     /// Attribute-level within node
     Attribute,
+
+    /// Source-span level: deltas on the same symbol conflict only if the
+    /// AST-node spans they rewrite overlap
+    Span,
+}
+
+impl Granularity {
+    /// Check if two deltas conflict at this granularity
+    ///
+    /// - `Subtree`: targets overlap (one is a prefix of the other)
+    /// - `Node`/`Attribute`: targets are identical
+    /// - `Span`: targets overlap and, when both deltas carry a span over
+    ///   the same base, those spans overlap; otherwise as `Subtree`
+    #[must_use]
+    pub fn conflicts<T: ArtifactType>(self, a: &StructuralDelta<T>, b: &StructuralDelta<T>) -> bool {
+        match self {
+            Self::Subtree => a.target().overlaps(b.target()),
+            Self::Node | Self::Attribute => a.target() == b.target(),
+            Self::Span => {
+                if !a.target().overlaps(b.target()) {
+                    return false;
+                }
+                match (a.span(), b.span()) {
+                    (Some(x), Some(y)) if a.base_hash() == b.base_hash() => x.overlaps(&y),
+                    _ => true,
+                }
+            }
+        }
+    }
+
+    /// First conflicting pair of deltas, by index
+    ///
+    /// O(n²) pairwise scan, reporting pairs in `(i, j)` order with `i < j`.
+    #[must_use]
+    pub fn first_conflict<T: ArtifactType>(self, deltas: &[StructuralDelta<T>]) -> Option<(usize, usize)> {
+        (0..deltas.len()).find_map(|i| {
+            ((i + 1)..deltas.len())
+                .find(|&j| self.conflicts(&deltas[i], &deltas[j]))
+                .map(|j| (i, j))
+        })
+    }
 }
 
 /// Composition error with diagnostics
@@ -254,6 +361,19 @@ pub enum CompositionError {
     /// Strategy-specific error
     #[error("{0}")]
     Strategy(String),
+
+    /// Strategy policy names an unknown strategy or is malformed
+    #[error("invalid strategy policy: {0}")]
+    InvalidPolicy(String),
+
+    /// Projected memory exceeds the configured budget
+    #[error("projected memory {projected} bytes exceeds budget of {cap} bytes")]
+    MemoryBudgetExceeded {
+        /// Projected peak bytes
+        projected: usize,
+        /// Configured cap in bytes
+        cap: usize,
+    },
 }
 
 impl CompositionError {
@@ -269,18 +389,16 @@ impl CompositionError {
     #[must_use]
     pub fn validation_failed_simple(kind: ConflictKind, message: impl Into<String>) -> Self {
         Self::ValidationFailed {
-            diagnostic: ValidationDiagnostic {
-                kind,
-                involved_deltas: vec![],
-                description: message.into(),
-                suggestions: vec![],
-            },
+            diagnostic: ValidationDiagnostic::new(kind, message),
         }
     }
 }
 
 /// Detailed validation failure diagnostic
-#[derive(Debug, Clone)]
+///
+/// Serializes to JSON so repair loops and UIs can act on the conflicting
+/// deltas and suggested edits without parsing `description`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ValidationDiagnostic {
     /// Kind of conflict
     pub kind: ConflictKind,
@@ -288,13 +406,66 @@ pub struct ValidationDiagnostic {
     /// Involved delta indices
     pub involved_deltas: Vec<usize>,
 
+    /// Targets of the involved deltas, in the same order
+    #[serde(default)]
+    pub paths: Vec<SymbolPath>,
+
     /// Human-readable description
     pub description: String,
 
     /// Suggested resolutions
+    #[serde(default)]
     pub suggestions: Vec<ResolutionSuggestion>,
 }
 
+impl ValidationDiagnostic {
+    /// Create diagnostic without involved deltas or suggestions
+    #[inline]
+    #[must_use]
+    pub fn new(kind: ConflictKind, description: impl Into<String>) -> Self {
+        Self {
+            kind,
+            involved_deltas: Vec::new(),
+            paths: Vec::new(),
+            description: description.into(),
+            suggestions: Vec::new(),
+        }
+    }
+
+    /// Record involved deltas by index, along with their targets
+    #[must_use]
+    pub fn involving<T: ArtifactType>(
+        mut self,
+        deltas: &[StructuralDelta<T>],
+        indices: impl IntoIterator<Item = usize>,
+    ) -> Self {
+        for i in indices {
+            self.involved_deltas.push(i);
+            if let Some(delta) = deltas.get(i) {
+                self.paths.push(delta.target().clone());
+            }
+        }
+        self
+    }
+
+    /// Add suggested resolution
+    #[inline]
+    #[must_use]
+    pub fn with_suggestion(mut self, suggestion: ResolutionSuggestion) -> Self {
+        self.suggestions.push(suggestion);
+        self
+    }
+
+    /// Concrete edits across all suggestions
+    #[must_use]
+    pub fn edits(&self) -> Vec<DeltaEdit> {
+        self.suggestions
+            .iter()
+            .flat_map(ResolutionSuggestion::edits)
+            .collect()
+    }
+}
+
 impl std::fmt::Display for ValidationDiagnostic {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:?}: {}", self.kind, self.description)
@@ -302,7 +473,8 @@ impl std::fmt::Display for ValidationDiagnostic {
 }
 
 /// Types of conflicts
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ConflictKind {
     /// Overlapping target paths
     OverlappingTargets,
@@ -321,7 +493,8 @@ pub enum ConflictKind {
 }
 
 /// Resolution suggestions
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum ResolutionSuggestion {
     /// Use single writer strategy (disjoint paths)
     UseSingleWriter,
@@ -349,6 +522,93 @@ pub enum ResolutionSuggestion {
 
     /// Use hybrid strategy
     UseHybrid,
+
+    /// Apply concrete edits to the delta batch
+    ApplyEdits {
+        /// Edits, addressed by delta index
+        edits: Vec<DeltaEdit>,
+    },
+}
+
+impl ResolutionSuggestion {
+    /// Concrete edits this suggestion implies
+    ///
+    /// `AddOrdering` maps to [`DeltaEdit::SetOrder`]; advisory suggestions
+    /// such as `UseOrdered` yield none.
+    #[must_use]
+    pub fn edits(&self) -> Vec<DeltaEdit> {
+        match self {
+            Self::AddOrdering { suggested_order } => suggested_order
+                .iter()
+                .map(|&(delta_index, order)| DeltaEdit::SetOrder { delta_index, order })
+                .collect(),
+            Self::ApplyEdits { edits } => edits.clone(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Machine-applicable edit to one delta of a batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "edit", rename_all = "snake_case")]
+pub enum DeltaEdit {
+    /// Set the delta's ordering hint
+    SetOrder {
+        /// Index into the validated batch
+        delta_index: usize,
+        /// New order value
+        order: u32,
+    },
+
+    /// Remove the delta from the batch
+    Drop {
+        /// Index into the validated batch
+        delta_index: usize,
+    },
+}
+
+impl DeltaEdit {
+    /// Index of the edited delta
+    #[inline]
+    #[must_use]
+    pub fn delta_index(&self) -> usize {
+        match self {
+            Self::SetOrder { delta_index, .. } | Self::Drop { delta_index } => *delta_index,
+        }
+    }
+
+    /// Apply edits to the batch they were computed for
+    ///
+    /// Indices refer to the original batch; drops are applied last so
+    /// they do not shift other edits. Out-of-range indices are ignored.
+    #[must_use]
+    pub fn apply_all<T: ArtifactType>(
+        deltas: Vec<StructuralDelta<T>>,
+        edits: &[DeltaEdit],
+    ) -> Vec<StructuralDelta<T>> {
+        let mut orders = HashMap::new();
+        let mut dropped = BTreeSet::new();
+        for edit in edits {
+            match *edit {
+                Self::SetOrder { delta_index, order } => {
+                    orders.insert(delta_index, order);
+                }
+                Self::Drop { delta_index } => {
+                    dropped.insert(delta_index);
+                }
+            }
+        }
+
+        deltas
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| !dropped.contains(i))
+            .map(|(i, delta)| match orders.get(&i) {
+                Some(&order) => delta.reordered(order),
+                None => delta,
+            })
+            .collect()
+    }
 }
 
 /// Delta classification for hybrid strategies
@@ -387,6 +647,7 @@ mod tests {
             time: TimeComplexity::ONLogN,
             space: SpaceComplexity::ON,
             parallelism_factor: 0.5,
+            measured: None,
         };
 
         let v = Validation::minimal().with_cost(cost);
@@ -412,6 +673,7 @@ mod tests {
         let diag = ValidationDiagnostic {
             kind: ConflictKind::OverlappingTargets,
             involved_deltas: vec![0, 1],
+            paths: vec![],
             description: "test".to_string(),
             suggestions: vec![],
         };
@@ -420,6 +682,77 @@ mod tests {
         assert!(matches!(err, CompositionError::ValidationFailed { .. }));
     }
 
+    #[test]
+    fn diagnostic_serde_roundtrip() {
+        let diag = ValidationDiagnostic::new(ConflictKind::MissingOrdering, "missing order")
+            .with_suggestion(ResolutionSuggestion::AddOrdering {
+                suggested_order: vec![(1, 3)],
+            })
+            .with_suggestion(ResolutionSuggestion::ApplyEdits {
+                edits: vec![DeltaEdit::Drop { delta_index: 2 }],
+            });
+
+        let json = serde_json::to_value(&diag).unwrap();
+        assert_eq!(json["kind"], "missing_ordering");
+        assert_eq!(json["suggestions"][0]["type"], "add_ordering");
+        assert_eq!(json["suggestions"][1]["edits"][0]["edit"], "drop");
+
+        let back: ValidationDiagnostic = serde_json::from_value(json).unwrap();
+        assert_eq!(back, diag);
+        assert_eq!(
+            back.edits(),
+            vec![
+                DeltaEdit::SetOrder { delta_index: 1, order: 3 },
+                DeltaEdit::Drop { delta_index: 2 },
+            ]
+        );
+    }
+
+    fn spanned(target: &str, span: Option<(usize, usize)>) -> StructuralDelta<SpanArtifact> {
+        let delta = StructuralDelta::new(
+            target.parse().unwrap(),
+            coa_artifact::DeltaOperation::Remove,
+            ContentHash::compute(b"base"),
+        );
+        match span {
+            Some((start, end)) => delta.with_span(coa_artifact::NodeSpan::new(start, end)),
+            None => delta,
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct SpanArtifact;
+
+    impl coa_artifact::__private::Sealed for SpanArtifact {}
+
+    impl ArtifactType for SpanArtifact {
+        type Content = ();
+
+        fn hash(_content: &Self::Content) -> ContentHash {
+            ContentHash::compute(b"span")
+        }
+
+        const TYPE_ID: &'static str = "span";
+    }
+
+    #[test]
+    fn span_granularity_compares_regions_within_symbol() {
+        let head = spanned("main", Some((10, 20)));
+        let tail = spanned("main", Some((30, 40)));
+        let middle = spanned("main", Some((15, 35)));
+        let whole = spanned("main", None);
+
+        assert!(!Granularity::Span.conflicts(&head, &tail));
+        assert!(Granularity::Span.conflicts(&head, &middle));
+        assert!(Granularity::Span.conflicts(&head, &whole));
+        assert!(Granularity::Subtree.conflicts(&head, &tail));
+        assert!(!Granularity::Span.conflicts(&head, &spanned("helper", Some((10, 20)))));
+
+        let batch = [head, tail, middle];
+        assert_eq!(Granularity::Span.first_conflict(&batch), Some((0, 2)));
+        assert_eq!(Granularity::Span.first_conflict(&batch[..2]), None);
+    }
+
     #[test]
     fn composition_error_simple() {
         let err = CompositionError::validation_failed_simple(
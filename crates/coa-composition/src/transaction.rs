@@ -0,0 +1,546 @@
+//! Multi-artifact composition transactions
+//!
+//! Agent changes often span several artifacts: renaming a function touches
+//! its definition and every caller. [`CompositionTransaction`] groups one
+//! delta batch per artifact, validates all of them, checks that no removed
+//! symbol is still referenced from outside the transaction (using the
+//! reference data in [`SymbolRefIndex`]), and only then composes.
+//!
+//! Either every artifact is composed or none is returned.
+
+use crate::strategy::{CompositionError, CompositionStrategy, Validation};
+use coa_artifact::{
+    Artifact, ArtifactType, ContentHash, DeltaOperation, StructuralDelta, SymbolPath,
+};
+use coa_symbol::{SymbolRefIndex, SymbolReference};
+use std::any::Any;
+use std::marker::PhantomData;
+
+/// Transaction failure
+#[derive(Debug, thiserror::Error)]
+pub enum TransactionError {
+    /// One artifact's batch failed validation or composition
+    #[error("artifact {artifact} failed: {source}")]
+    Artifact {
+        /// Position of the artifact in the transaction
+        artifact: usize,
+        /// Strategy error
+        #[source]
+        source: CompositionError,
+    },
+
+    /// A removal leaves references that no delta in the transaction updates
+    #[error("removing '{removed}' leaves {} dangling reference(s)", dangling.len())]
+    DanglingReferences {
+        /// Position of the artifact containing the removal
+        artifact: usize,
+        /// Index of the removing delta within that artifact's batch
+        delta: usize,
+        /// Removed path
+        removed: SymbolPath,
+        /// References left without a covering delta
+        dangling: Vec<SymbolReference>,
+    },
+
+    /// The same base artifact was added twice
+    #[error("artifact {0} added to transaction twice")]
+    DuplicateArtifact(ContentHash),
+
+    /// The transaction has no artifacts
+    #[error("empty transaction")]
+    Empty,
+}
+
+/// Typed handle to an artifact added to a transaction
+#[derive(Debug)]
+pub struct TransactionSlot<T: ArtifactType> {
+    position: usize,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T: ArtifactType> Clone for TransactionSlot<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ArtifactType> Copy for TransactionSlot<T> {}
+
+impl<T: ArtifactType> TransactionSlot<T> {
+    /// Position of the artifact in the transaction
+    #[inline]
+    #[must_use]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+/// Delta target summary used for cross-artifact checks
+struct Target {
+    path: SymbolPath,
+    removes: bool,
+}
+
+/// Type-erased batch for one artifact
+trait Part: Send + Sync {
+    fn base_hash(&self) -> ContentHash;
+    fn targets(&self) -> Vec<Target>;
+    fn validate(&self, index: &SymbolRefIndex) -> Result<Validation, CompositionError>;
+    fn compose(&self) -> Result<Box<dyn Any + Send + Sync>, CompositionError>;
+}
+
+struct TypedPart<'a, T: ArtifactType, S> {
+    strategy: &'a S,
+    base: &'a Artifact<T>,
+    deltas: Vec<StructuralDelta<T>>,
+}
+
+impl<T, S> Part for TypedPart<'_, T, S>
+where
+    T: ArtifactType,
+    S: CompositionStrategy,
+{
+    fn base_hash(&self) -> ContentHash {
+        *self.base.hash()
+    }
+
+    fn targets(&self) -> Vec<Target> {
+        self.deltas
+            .iter()
+            .map(|delta| Target {
+                path: delta.target().clone(),
+                removes: match delta.operation() {
+                    DeltaOperation::Remove => true,
+                    DeltaOperation::Transform(transform) => transform.removes_target(),
+                    DeltaOperation::Add(_)
+                    | DeltaOperation::Replace(_)
+                    | DeltaOperation::BinaryPatch(_) => false,
+                },
+            })
+            .collect()
+    }
+
+    fn validate(&self, index: &SymbolRefIndex) -> Result<Validation, CompositionError> {
+        self.strategy.validate(&self.deltas, index)
+    }
+
+    fn compose(&self) -> Result<Box<dyn Any + Send + Sync>, CompositionError> {
+        let artifact = self.strategy.compose(self.base, &self.deltas)?;
+        Ok(Box::new(artifact))
+    }
+}
+
+/// Coordinated delta batches across multiple artifacts
+///
+/// # Example
+/// ```rust,ignore
+/// let mut tx = CompositionTransaction::new(&index);
+/// let lib = tx.add(&strategy, &lib_rs, rename_definition)?;
+/// let main = tx.add(&strategy, &main_rs, update_callers)?;
+///
+/// let mut committed = tx.commit()?;
+/// let lib_rs = committed.take(lib).unwrap();
+/// let main_rs = committed.take(main).unwrap();
+/// ```
+pub struct CompositionTransaction<'a> {
+    index: &'a SymbolRefIndex,
+    parts: Vec<Box<dyn Part + 'a>>,
+}
+
+impl std::fmt::Debug for CompositionTransaction<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompositionTransaction")
+            .field("artifacts", &self.parts.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a> CompositionTransaction<'a> {
+    /// Create transaction validated against `index`
+    #[inline]
+    #[must_use]
+    pub fn new(index: &'a SymbolRefIndex) -> Self {
+        Self {
+            index,
+            parts: Vec::new(),
+        }
+    }
+
+    /// Add a delta batch for one artifact
+    ///
+    /// # Errors
+    /// Returns `TransactionError::DuplicateArtifact` if `base` is already
+    /// part of the transaction
+    pub fn add<T, S>(
+        &mut self,
+        strategy: &'a S,
+        base: &'a Artifact<T>,
+        deltas: Vec<StructuralDelta<T>>,
+    ) -> Result<TransactionSlot<T>, TransactionError>
+    where
+        T: ArtifactType,
+        S: CompositionStrategy,
+    {
+        if self
+            .parts
+            .iter()
+            .any(|part| part.base_hash() == *base.hash())
+        {
+            return Err(TransactionError::DuplicateArtifact(*base.hash()));
+        }
+
+        self.parts.push(Box::new(TypedPart {
+            strategy,
+            base,
+            deltas,
+        }));
+        Ok(TransactionSlot {
+            position: self.parts.len() - 1,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Number of artifacts in the transaction
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.parts.len()
+    }
+
+    /// Check if transaction has no artifacts
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.parts.is_empty()
+    }
+
+    /// Validate every batch and the cross-artifact references
+    ///
+    /// # Errors
+    /// Returns the first failing artifact or dangling reference
+    pub fn validate(&self) -> Result<Vec<Validation>, TransactionError> {
+        if self.parts.is_empty() {
+            return Err(TransactionError::Empty);
+        }
+
+        let validations = self
+            .parts
+            .iter()
+            .enumerate()
+            .map(|(artifact, part)| {
+                part.validate(self.index)
+                    .map_err(|source| TransactionError::Artifact { artifact, source })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.check_references()?;
+        Ok(validations)
+    }
+
+    /// Validate, then compose every artifact
+    ///
+    /// # Errors
+    /// Returns the first validation, reference or composition failure; no
+    /// composed artifact is returned unless all succeed
+    pub fn commit(self) -> Result<CommittedTransaction, TransactionError> {
+        let validations = self.validate()?;
+
+        let artifacts = self
+            .parts
+            .iter()
+            .enumerate()
+            .map(|(artifact, part)| {
+                part.compose()
+                    .map(Some)
+                    .map_err(|source| TransactionError::Artifact { artifact, source })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(CommittedTransaction {
+            artifacts,
+            validations,
+        })
+    }
+
+    /// Every reference to a removed symbol must be touched by the transaction
+    ///
+    /// A reference is covered when some delta applies to the artifact that
+    /// contains it (matching base hash) and targets an overlapping path.
+    fn check_references(&self) -> Result<(), TransactionError> {
+        let touched: Vec<(ContentHash, Vec<Target>)> = self
+            .parts
+            .iter()
+            .map(|part| (part.base_hash(), part.targets()))
+            .collect();
+
+        for (artifact, (_, targets)) in touched.iter().enumerate() {
+            for (delta, target) in targets.iter().enumerate().filter(|(_, t)| t.removes) {
+                let dangling: Vec<SymbolReference> = self
+                    .index
+                    .references_to(target.path.segments())
+                    .into_iter()
+                    .filter(|reference| !Self::covered(&touched, reference))
+                    .collect();
+
+                if !dangling.is_empty() {
+                    return Err(TransactionError::DanglingReferences {
+                        artifact,
+                        delta,
+                        removed: target.path.clone(),
+                        dangling,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn covered(touched: &[(ContentHash, Vec<Target>)], reference: &SymbolReference) -> bool {
+        let site = SymbolPath::new(reference.site.path().to_vec());
+        touched
+            .iter()
+            .filter(|(hash, _)| hash == reference.site.parent_hash())
+            .flat_map(|(_, targets)| targets)
+            .any(|target| target.path.overlaps(&site))
+    }
+}
+
+/// Results of a committed transaction
+#[derive(Debug)]
+pub struct CommittedTransaction {
+    artifacts: Vec<Option<Box<dyn Any + Send + Sync>>>,
+    validations: Vec<Validation>,
+}
+
+impl CommittedTransaction {
+    /// Take the composed artifact for `slot`
+    ///
+    /// Returns `None` if already taken.
+    pub fn take<T: ArtifactType>(&mut self, slot: TransactionSlot<T>) -> Option<Artifact<T>> {
+        let artifact = self.artifacts.get_mut(slot.position)?.take()?;
+        artifact.downcast::<Artifact<T>>().ok().map(|boxed| *boxed)
+    }
+
+    /// Validation report for `slot`
+    #[inline]
+    #[must_use]
+    pub fn validation<T: ArtifactType>(&self, slot: TransactionSlot<T>) -> Option<&Validation> {
+        self.validations.get(slot.position)
+    }
+
+    /// Number of artifacts composed
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.validations.len()
+    }
+
+    /// Check if no artifacts were composed
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.validations.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::{Granularity, Parallelism};
+    use coa_symbol::SymbolRef;
+    use std::str::FromStr;
+
+    #[derive(Debug, Clone)]
+    struct TestArtifact;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct TestContent(String);
+
+    impl coa_artifact::__private::Sealed for TestArtifact {}
+
+    impl ArtifactType for TestArtifact {
+        type Content = TestContent;
+
+        fn hash(content: &Self::Content) -> ContentHash {
+            ContentHash::compute(content.0.as_bytes())
+        }
+
+        const TYPE_ID: &'static str = "test";
+    }
+
+    /// Returns the base unchanged; fails on deltas targeting `fail`
+    #[derive(Debug)]
+    struct PassStrategy;
+
+    impl CompositionStrategy for PassStrategy {
+        fn validate<T: ArtifactType>(
+            &self,
+            _deltas: &[StructuralDelta<T>],
+            _index: &SymbolRefIndex,
+        ) -> Result<Validation, CompositionError> {
+            Ok(Validation::minimal())
+        }
+
+        fn compose<T: ArtifactType>(
+            &self,
+            base: &Artifact<T>,
+            deltas: &[StructuralDelta<T>],
+        ) -> Result<Artifact<T>, CompositionError> {
+            if deltas.iter().any(|d| d.target().to_string() == "fail") {
+                return Err(CompositionError::CompositionFailed("fail".to_string()));
+            }
+            Ok(base.clone())
+        }
+
+        fn parallelism(&self) -> Parallelism {
+            Parallelism::None
+        }
+
+        fn granularity(&self) -> Granularity {
+            Granularity::Node
+        }
+
+        fn name(&self) -> &'static str {
+            "Pass"
+        }
+    }
+
+    fn artifact(source: &str) -> Artifact<TestArtifact> {
+        Artifact::new(TestContent(source.to_string())).unwrap()
+    }
+
+    fn delta(
+        base: &Artifact<TestArtifact>,
+        target: &str,
+        operation: DeltaOperation<TestArtifact>,
+    ) -> StructuralDelta<TestArtifact> {
+        StructuralDelta::new(
+            SymbolPath::from_str(target).unwrap(),
+            operation,
+            *base.hash(),
+        )
+    }
+
+    /// `main.handler` in `main` calls `auth.login`
+    fn caller_index(main: &Artifact<TestArtifact>) -> SymbolRefIndex {
+        let index = SymbolRefIndex::new();
+        let caller = SymbolRef::new(vec!["main".into(), "handler".into()], *main.hash());
+        index.add_reference(&["auth".to_string(), "login".to_string()], caller);
+        index
+    }
+
+    #[test]
+    fn rename_without_updating_callers_is_rejected() {
+        let lib = artifact("fn login()");
+        let main = artifact("login()");
+        let index = caller_index(&main);
+
+        let mut tx = CompositionTransaction::new(&index);
+        tx.add(
+            &PassStrategy,
+            &lib,
+            vec![
+                delta(&lib, "auth.login", DeltaOperation::Remove),
+                delta(
+                    &lib,
+                    "auth.sign_in",
+                    DeltaOperation::Add(TestContent("fn sign_in()".into())),
+                ),
+            ],
+        )
+        .unwrap();
+
+        let err = tx.commit().unwrap_err();
+        let TransactionError::DanglingReferences {
+            artifact,
+            delta,
+            removed,
+            dangling,
+        } = err
+        else {
+            panic!("expected dangling references, got {err}");
+        };
+        assert_eq!((artifact, delta), (0, 0));
+        assert_eq!(removed.to_string(), "auth.login");
+        assert_eq!(dangling[0].site.path(), ["main", "handler"]);
+    }
+
+    #[test]
+    fn rename_with_callers_commits_all_artifacts() {
+        let lib = artifact("fn login()");
+        let main = artifact("login()");
+        let index = caller_index(&main);
+
+        let mut tx = CompositionTransaction::new(&index);
+        let lib_slot = tx
+            .add(
+                &PassStrategy,
+                &lib,
+                vec![delta(&lib, "auth.login", DeltaOperation::Remove)],
+            )
+            .unwrap();
+        let main_slot = tx
+            .add(
+                &PassStrategy,
+                &main,
+                vec![delta(
+                    &main,
+                    "main.handler",
+                    DeltaOperation::Replace(TestContent("sign_in()".into())),
+                )],
+            )
+            .unwrap();
+
+        let mut committed = tx.commit().unwrap();
+        assert_eq!(committed.len(), 2);
+        assert!(committed.validation(main_slot).is_some());
+        assert_eq!(committed.take(lib_slot).unwrap().hash(), lib.hash());
+        assert_eq!(committed.take(main_slot).unwrap().hash(), main.hash());
+        assert!(committed.take(main_slot).is_none());
+    }
+
+    #[test]
+    fn failing_artifact_aborts_whole_transaction() {
+        let lib = artifact("lib");
+        let main = artifact("main");
+        let index = SymbolRefIndex::new();
+
+        let mut tx = CompositionTransaction::new(&index);
+        tx.add(
+            &PassStrategy,
+            &lib,
+            vec![delta(&lib, "ok", DeltaOperation::Remove)],
+        )
+        .unwrap();
+        tx.add(
+            &PassStrategy,
+            &main,
+            vec![delta(&main, "fail", DeltaOperation::Remove)],
+        )
+        .unwrap();
+
+        assert!(matches!(
+            tx.commit(),
+            Err(TransactionError::Artifact { artifact: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn duplicate_and_empty_transactions_are_rejected() {
+        let lib = artifact("lib");
+        let index = SymbolRefIndex::new();
+
+        assert!(matches!(
+            CompositionTransaction::new(&index).commit(),
+            Err(TransactionError::Empty)
+        ));
+
+        let mut tx = CompositionTransaction::new(&index);
+        tx.add(&PassStrategy, &lib, vec![]).unwrap();
+        assert!(matches!(
+            tx.add(&PassStrategy, &lib, vec![]),
+            Err(TransactionError::DuplicateArtifact(_))
+        ));
+    }
+}
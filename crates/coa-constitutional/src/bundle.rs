@@ -0,0 +1,66 @@
+//! Signed egress bundles of serialized artifacts
+//!
+//! Adds artifacts to a kernel [`BundleBuilder`]: each is rendered by its
+//! [`ArtifactSerializer`] and bundled with a provenance record of its
+//! content hash, type and [`ArtifactProvenance`](coa_artifact::ArtifactProvenance).
+
+use crate::error::SerializeError;
+use crate::serializers::ArtifactSerializer;
+use coa_artifact::{Artifact, ArtifactType};
+
+pub use coa_kernel::bundle::{BundleBuilder, BundleEntry, BundleError, BundleManifest, EgressBundle};
+
+/// Serialize `artifact` into the bundle at `path`
+///
+/// # Errors
+/// - Any error from the serializer
+pub fn with_artifact<S: ArtifactSerializer>(
+    builder: BundleBuilder,
+    serializer: &S,
+    artifact: &Artifact<S::Input>,
+    path: &str,
+) -> Result<BundleBuilder, SerializeError> {
+    let content = serializer.serialize(artifact)?;
+    let record = serde_json::json!({
+        "artifact_hash": artifact.hash().to_string(),
+        "artifact_type": <S::Input as ArtifactType>::TYPE_ID,
+        "provenance": artifact.provenance(),
+    });
+    Ok(builder
+        .with_file(path, content)
+        .with_provenance(path, record))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::{ArtifactParser, CodeParser, Language};
+    use crate::serializers::CodeSerializer;
+    use coa_artifact::ArtifactProvenance;
+    use ed25519_dalek::SigningKey;
+
+    #[test]
+    fn bundled_artifact_carries_provenance() {
+        let key = SigningKey::from_bytes(&[3u8; 32]);
+        let artifact = CodeParser::new(Language::Rust)
+            .parse("fn main() {}\n")
+            .unwrap()
+            .with_provenance(ArtifactProvenance::new().with_agent("coder"));
+
+        let builder = with_artifact(
+            EgressBundle::builder(),
+            &CodeSerializer::new(Language::Rust),
+            &artifact,
+            "src/main.rs",
+        )
+        .unwrap();
+        let bundle = builder.sign(&key).unwrap();
+        let bundle = EgressBundle::from_bytes(&bundle.to_bytes().unwrap()).unwrap();
+
+        bundle.verify(&key.verifying_key()).unwrap();
+        assert_eq!(bundle.file("src/main.rs"), Some(&b"fn main() {}\n"[..]));
+        let record = &bundle.manifest().provenance["src/main.rs"];
+        assert_eq!(record["artifact_hash"], artifact.hash().to_string());
+        assert_eq!(record["provenance"]["agent_id"], "coder");
+    }
+}
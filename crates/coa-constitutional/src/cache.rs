@@ -1,21 +1,520 @@
 //! Content-addressed artifact cache using moka
 //!
 //! Provides high-performance, concurrent caching of artifacts by their content hash.
+//!
+//! Caches are bounded by entry count or estimated bytes
+//! ([`CacheCapacity`]), evict by a chosen [`EvictionPolicy`], and can give
+//! individual artifact types their own quota so, say, large binaries cannot
+//! push parsed code out. An [`EvictionHook`] is told about every removal so
+//! dependent indexes (e.g. the symbol index) can drop stale entries.
+//!
+//! Multi-tenant deployments give each tenant its own cache
+//! ([`CacheConfig::with_tenant`]): deduplication never crosses tenants, so
+//! one tenant cannot learn whether another holds some content.
 
-use coa_artifact::{Artifact, ArtifactType, ContentHash};
+use crate::parsers::ErasedArtifact;
+use coa_artifact::{Artifact, ArtifactType, ContentHash, HashAlgorithm, TenantId};
 use moka::future::Cache;
+use moka::notification::RemovalCause;
 use std::any::{Any, TypeId};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::Debug;
 use std::future::Future;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock};
+use std::time::{Duration, Instant};
 
 /// Statistics for cache performance monitoring
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct CacheStats {
     /// Number of entries in cache
     pub entry_count: u64,
+
+    /// Estimated bytes held by cached artifacts
+    pub bytes: u64,
+
+    /// Lookups that found an artifact
+    pub hits: u64,
+
+    /// Lookups that found nothing
+    pub misses: u64,
+
+    /// Entries removed by capacity pressure or expiry
+    pub evictions: u64,
+}
+
+impl CacheStats {
+    /// Fraction of lookups served from the cache
+    #[inline]
+    #[must_use]
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            return 0.0;
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let rate = self.hits as f64 / lookups as f64;
+        rate
+    }
+}
+
+/// Which entries to drop when a cache segment is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EvictionPolicy {
+    /// Least recently used
+    Lru,
+
+    /// Least frequently used (TinyLFU admission and eviction)
+    #[default]
+    Lfu,
+
+    /// Adaptive replacement: balances recency and frequency, resists scans
+    Arc,
+}
+
+impl EvictionPolicy {
+    /// Moka's equivalent, or `None` when the cache applies the policy itself
+    fn to_moka(self) -> Option<moka::policy::EvictionPolicy> {
+        match self {
+            Self::Lru => Some(moka::policy::EvictionPolicy::lru()),
+            Self::Lfu => Some(moka::policy::EvictionPolicy::tiny_lfu()),
+            Self::Arc => None,
+        }
+    }
+}
+
+/// Size bound of a cache segment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheCapacity {
+    /// Maximum number of entries
+    Entries(u64),
+
+    /// Maximum total of [`Artifact::estimated_size`] in bytes
+    Bytes(u64),
+}
+
+impl Default for CacheCapacity {
+    fn default() -> Self {
+        Self::Entries(10_000)
+    }
+}
+
+/// Why an entry left the cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EvictionCause {
+    /// Segment capacity exceeded
+    Size,
+
+    /// Time-to-live elapsed
+    Expired,
+
+    /// Removed by [`ArtifactCache::invalidate`] or `invalidate_all`
+    Explicit,
+}
+
+/// Entry removal reported to the [`EvictionHook`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvictionEvent {
+    /// Content hash of the removed artifact
+    pub hash: ContentHash,
+
+    /// [`ArtifactType::TYPE_ID`] of the removed artifact
+    pub type_id: &'static str,
+
+    /// Estimated size of the removed artifact
+    pub bytes: usize,
+
+    /// Reason for removal
+    pub cause: EvictionCause,
+}
+
+/// Callback invoked for every entry removal
+///
+/// Runs on the cache's maintenance path; it must be cheap and must not panic
+/// (moka stops calling a listener after it panics).
+pub type EvictionHook = Arc<dyn Fn(&EvictionEvent) + Send + Sync>;
+
+/// Bounds, policy and hooks for an [`ArtifactCache`]
+#[derive(Clone, Default)]
+pub struct CacheConfig {
+    capacity: CacheCapacity,
+    policy: EvictionPolicy,
+    ttl: Option<Duration>,
+    quotas: HashMap<&'static str, CacheCapacity>,
+    hook: Option<EvictionHook>,
+    tenant: Option<TenantId>,
+}
+
+impl CacheConfig {
+    /// Create config with the default bound (10,000 entries, LFU)
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bound the shared segment by entry count
+    #[inline]
+    #[must_use]
+    pub fn with_max_entries(mut self, max_entries: u64) -> Self {
+        self.capacity = CacheCapacity::Entries(max_entries);
+        self
+    }
+
+    /// Bound the shared segment by estimated bytes
+    #[inline]
+    #[must_use]
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.capacity = CacheCapacity::Bytes(max_bytes);
+        self
+    }
+
+    /// Set eviction policy (applies to every segment)
+    #[inline]
+    #[must_use]
+    pub fn with_policy(mut self, policy: EvictionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Expire entries after `ttl` (applies to every segment)
+    #[inline]
+    #[must_use]
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Give artifacts with `type_id` a dedicated segment of `capacity`
+    ///
+    /// Artifacts of that type no longer count against the shared bound.
+    #[inline]
+    #[must_use]
+    pub fn with_quota(mut self, type_id: &'static str, capacity: CacheCapacity) -> Self {
+        self.quotas.insert(type_id, capacity);
+        self
+    }
+
+    /// Observe entry removals
+    #[inline]
+    #[must_use]
+    pub fn with_eviction_hook(mut self, hook: impl Fn(&EvictionEvent) + Send + Sync + 'static) -> Self {
+        self.hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// Hold only `tenant`'s artifacts
+    #[inline]
+    #[must_use]
+    pub fn with_tenant(mut self, tenant: TenantId) -> Self {
+        self.tenant = Some(tenant);
+        self
+    }
+}
+
+impl Debug for CacheConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CacheConfig")
+            .field("capacity", &self.capacity)
+            .field("policy", &self.policy)
+            .field("ttl", &self.ttl)
+            .field("quotas", &self.quotas)
+            .field("hook", &self.hook.is_some())
+            .field("tenant", &self.tenant)
+            .finish()
+    }
+}
+
+/// Cached artifact with the metadata eviction accounting needs
+#[derive(Debug, Clone)]
+struct CachedArtifact {
+    artifact: Arc<dyn Any + Send + Sync>,
+    /// [`Artifact::hash`], which the symbol index binds symbols to
+    artifact_hash: ContentHash,
+    type_id: &'static str,
+    bytes: usize,
+    inserted: Instant,
+}
+
+/// Cached entry as seen by the garbage collector
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct EntryInfo {
+    /// Cache key (content checksum)
+    pub(crate) key: ContentHash,
+    pub(crate) artifact_hash: ContentHash,
+    pub(crate) bytes: usize,
+    pub(crate) inserted: Instant,
+}
+
+/// One bounded partition of the cache
+#[derive(Debug, Clone)]
+struct Segment {
+    entries: Cache<ContentHash, CachedArtifact>,
+    /// Replacement directory under [`EvictionPolicy::Arc`]; moka bounds
+    /// the segment otherwise
+    arc: Option<Arc<Mutex<AdaptiveReplacement>>>,
+    /// Whether an entry weighs its bytes (rather than 1) in the directory
+    weigh_bytes: bool,
+}
+
+impl Segment {
+    fn directory(&self) -> Option<MutexGuard<'_, AdaptiveReplacement>> {
+        self.arc
+            .as_ref()
+            .map(|arc| arc.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    fn contains_key(&self, key: &ContentHash) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    async fn get(&self, key: &ContentHash) -> Option<CachedArtifact> {
+        let entry = self.entries.get(key).await?;
+        if let Some(mut arc) = self.directory() {
+            arc.touch(key);
+        }
+        Some(entry)
+    }
+
+    async fn insert(&self, key: ContentHash, entry: CachedArtifact) {
+        let weight = if self.weigh_bytes { entry.bytes as u64 } else { 1 };
+        self.entries.insert(key, entry).await;
+        let victims = match self.directory() {
+            Some(mut arc) => arc.admit(key, weight),
+            None => return,
+        };
+        for victim in victims {
+            // Already gone (e.g. expired): no listener call will claim it
+            if self.entries.remove(&victim).await.is_none() {
+                if let Some(mut arc) = self.directory() {
+                    arc.take_victim(&victim);
+                }
+            }
+        }
+    }
+
+    async fn remove(&self, key: &ContentHash) -> Option<CachedArtifact> {
+        if let Some(mut arc) = self.directory() {
+            arc.forget(key);
+        }
+        self.entries.remove(key).await
+    }
+
+    fn invalidate_all(&self) {
+        if let Some(mut arc) = self.directory() {
+            arc.clear();
+        }
+        self.entries.invalidate_all();
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (Arc<ContentHash>, CachedArtifact)> + '_ {
+        self.entries.iter()
+    }
+
+    async fn run_pending_tasks(&self) {
+        self.entries.run_pending_tasks().await;
+    }
+}
+
+/// List of the ARC directory a key is on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArcList {
+    /// Cached, seen once (T1)
+    Recent,
+    /// Cached, seen more than once (T2)
+    Frequent,
+    /// Recently evicted from `Recent` (B1)
+    RecentGhost,
+    /// Recently evicted from `Frequent` (B2)
+    FrequentGhost,
+}
+
+impl ArcList {
+    const ALL: usize = 4;
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ArcSlot {
+    list: ArcList,
+    tick: u64,
+    weight: u64,
+}
+
+/// Keys of one list, least recently used first
+#[derive(Debug, Default)]
+struct ArcQueue {
+    order: BTreeMap<u64, ContentHash>,
+    weight: u64,
+}
+
+/// Adaptive replacement (ARC) directory of one segment
+///
+/// Weights are capacity units (entries or bytes). A ghost that comes back
+/// moves the target size of the recency list toward the list it was
+/// evicted from, so the split between recency and frequency follows the
+/// workload.
+#[derive(Debug)]
+struct AdaptiveReplacement {
+    capacity: u64,
+    /// Target weight of [`ArcList::Recent`]
+    target: u64,
+    queues: [ArcQueue; ArcList::ALL],
+    slots: HashMap<ContentHash, ArcSlot>,
+    tick: u64,
+    /// Evicted keys whose removal from storage is still to be reported
+    victims: HashSet<ContentHash>,
+}
+
+impl AdaptiveReplacement {
+    fn new(capacity: u64) -> Self {
+        Self {
+            capacity,
+            target: 0,
+            queues: Default::default(),
+            slots: HashMap::new(),
+            tick: 0,
+            victims: HashSet::new(),
+        }
+    }
+
+    fn weight(&self, list: ArcList) -> u64 {
+        self.queues[list as usize].weight
+    }
+
+    fn resident(&self) -> u64 {
+        self.weight(ArcList::Recent) + self.weight(ArcList::Frequent)
+    }
+
+    fn push(&mut self, key: ContentHash, list: ArcList, weight: u64) {
+        self.tick += 1;
+        let queue = &mut self.queues[list as usize];
+        queue.order.insert(self.tick, key);
+        queue.weight += weight;
+        self.slots.insert(
+            key,
+            ArcSlot {
+                list,
+                tick: self.tick,
+                weight,
+            },
+        );
+    }
+
+    fn unlink(&mut self, key: &ContentHash) -> Option<ArcSlot> {
+        let slot = self.slots.remove(key)?;
+        let queue = &mut self.queues[slot.list as usize];
+        queue.order.remove(&slot.tick);
+        queue.weight = queue.weight.saturating_sub(slot.weight);
+        Some(slot)
+    }
+
+    fn pop_oldest(&mut self, list: ArcList) -> Option<(ContentHash, ArcSlot)> {
+        let (_, &key) = self.queues[list as usize].order.first_key_value()?;
+        self.unlink(&key).map(|slot| (key, slot))
+    }
+
+    /// Record a hit on a cached key
+    fn touch(&mut self, key: &ContentHash) {
+        if let Some(slot) = self.slots.get(key).copied() {
+            if matches!(slot.list, ArcList::Recent | ArcList::Frequent) {
+                self.unlink(key);
+                self.push(*key, ArcList::Frequent, slot.weight);
+            }
+        }
+    }
+
+    /// Record an insertion and return the keys to evict
+    fn admit(&mut self, key: ContentHash, weight: u64) -> Vec<ContentHash> {
+        let previous = self.slots.get(&key).map(|slot| slot.list);
+        let recent_ghosts = self.weight(ArcList::RecentGhost).max(1);
+        let frequent_ghosts = self.weight(ArcList::FrequentGhost).max(1);
+        let list = match previous {
+            None => ArcList::Recent,
+            Some(ArcList::Recent | ArcList::Frequent) => ArcList::Frequent,
+            Some(ArcList::RecentGhost) => {
+                let step = (frequent_ghosts / recent_ghosts).max(1);
+                self.target = (self.target + weight.saturating_mul(step)).min(self.capacity);
+                ArcList::Frequent
+            }
+            Some(ArcList::FrequentGhost) => {
+                let step = (recent_ghosts / frequent_ghosts).max(1);
+                self.target = self.target.saturating_sub(weight.saturating_mul(step));
+                ArcList::Frequent
+            }
+        };
+        self.unlink(&key);
+        self.push(key, list, weight);
+
+        let mut victims = Vec::new();
+        while self.resident() > self.capacity {
+            let recent = self.weight(ArcList::Recent);
+            let shrink_recent = recent > 0
+                && (recent > self.target
+                    || (recent == self.target && previous == Some(ArcList::FrequentGhost))
+                    || self.weight(ArcList::Frequent) == 0);
+            let (from, ghost) = if shrink_recent {
+                (ArcList::Recent, ArcList::RecentGhost)
+            } else {
+                (ArcList::Frequent, ArcList::FrequentGhost)
+            };
+            let Some((victim, slot)) = self.pop_oldest(from) else {
+                break;
+            };
+            self.push(victim, ghost, slot.weight);
+            victims.push(victim);
+        }
+
+        // Remember at most one capacity of each kind of history
+        while self.weight(ArcList::Recent) + self.weight(ArcList::RecentGhost) > self.capacity
+            && self.pop_oldest(ArcList::RecentGhost).is_some()
+        {}
+        while self.resident() + self.weight(ArcList::RecentGhost) + self.weight(ArcList::FrequentGhost)
+            > self.capacity.saturating_mul(2)
+            && self.pop_oldest(ArcList::FrequentGhost).is_some()
+        {}
+
+        self.victims.extend(victims.iter().copied());
+        victims
+    }
+
+    /// Drop `key` after an explicit removal or expiry
+    fn forget(&mut self, key: &ContentHash) {
+        self.unlink(key);
+    }
+
+    /// Check whether `key` was removed by [`admit`](Self::admit)
+    fn take_victim(&mut self, key: &ContentHash) -> bool {
+        self.victims.remove(key)
+    }
+
+    fn clear(&mut self) {
+        *self = Self::new(self.capacity);
+    }
+}
+
+/// Generations per (artifact type, logical path), sorted by revision
+type Lineages = HashMap<(TypeId, Arc<str>), Vec<TypedCacheKey>>;
+
+#[derive(Debug, Default)]
+struct Counters {
+    entries: AtomicU64,
+    bytes: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl Counters {
+    fn remove(&self, bytes: usize) {
+        saturating_sub(&self.entries, 1);
+        saturating_sub(&self.bytes, bytes as u64);
+    }
+}
+
+fn saturating_sub(counter: &AtomicU64, amount: u64) {
+    let _ = counter.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |v| {
+        Some(v.saturating_sub(amount))
+    });
 }
 
 /// Content-addressed artifact cache
@@ -23,12 +522,19 @@ pub struct CacheStats {
 /// Stores artifacts by their content hash, enabling:
 /// - Deduplication (same content = same hash = single entry)
 /// - Fast lookup by hash
-/// - Automatic eviction based on LRU
+/// - Bounded size with LRU, LFU or ARC eviction ([`CacheConfig`])
+/// - Per-type quotas and eviction notifications
 /// - Time-based expiration (TTL)
+///
+/// Capacity eviction runs during moka's maintenance; call
+/// [`run_pending_tasks`](Self::run_pending_tasks) to apply it eagerly.
 #[derive(Debug, Clone)]
 pub struct ArtifactCache {
-    inner: Cache<ContentHash, Arc<dyn Any + Send + Sync>>,
-    entry_count: Arc<AtomicU64>,
+    shared: Segment,
+    quotas: Arc<HashMap<&'static str, Segment>>,
+    counters: Arc<Counters>,
+    lineages: Arc<RwLock<Lineages>>,
+    tenant: Option<TenantId>,
 }
 
 impl ArtifactCache {
@@ -36,42 +542,176 @@ impl ArtifactCache {
     #[inline]
     #[must_use]
     pub fn new(max_capacity: u64) -> Self {
-        Self {
-            inner: Cache::new(max_capacity),
-            entry_count: Arc::new(AtomicU64::new(0)),
-        }
+        Self::with_config(CacheConfig::new().with_max_entries(max_capacity))
     }
 
     /// Create cache with time-based expiration
     #[inline]
     #[must_use]
     pub fn with_ttl(max_capacity: u64, ttl: Duration) -> Self {
+        Self::with_config(CacheConfig::new().with_max_entries(max_capacity).with_ttl(ttl))
+    }
+
+    /// Create cache from explicit bounds, policy and hooks
+    #[must_use]
+    pub fn with_config(config: CacheConfig) -> Self {
+        let counters = Arc::new(Counters::default());
+        let quotas = config
+            .quotas
+            .iter()
+            .map(|(&type_id, &capacity)| (type_id, Self::segment(&config, capacity, &counters)))
+            .collect();
         Self {
-            inner: Cache::builder()
-                .max_capacity(max_capacity)
-                .time_to_live(ttl)
-                .build(),
-            entry_count: Arc::new(AtomicU64::new(0)),
+            shared: Self::segment(&config, config.capacity, &counters),
+            quotas: Arc::new(quotas),
+            counters,
+            lineages: Arc::default(),
+            tenant: config.tenant.clone(),
+        }
+    }
+
+    fn segment(config: &CacheConfig, capacity: CacheCapacity, counters: &Arc<Counters>) -> Segment {
+        let mut builder = Cache::builder();
+        let arc = match config.policy.to_moka() {
+            Some(policy) => {
+                builder = builder.eviction_policy(policy);
+                builder = match capacity {
+                    CacheCapacity::Entries(max) => builder.max_capacity(max),
+                    CacheCapacity::Bytes(max) => builder.max_capacity(max).weigher(
+                        |_, entry: &CachedArtifact| u32::try_from(entry.bytes).unwrap_or(u32::MAX),
+                    ),
+                };
+                None
+            }
+            None => {
+                let (CacheCapacity::Entries(max) | CacheCapacity::Bytes(max)) = capacity;
+                Some(Arc::new(Mutex::new(AdaptiveReplacement::new(max))))
+            }
+        };
+        if let Some(ttl) = config.ttl {
+            builder = builder.time_to_live(ttl);
+        }
+
+        let counters = Arc::clone(counters);
+        let hook = config.hook.clone();
+        let directory = arc.clone();
+        let entries = builder
+            .eviction_listener(move |hash: Arc<ContentHash>, entry: CachedArtifact, cause| {
+                let mut directory = directory
+                    .as_ref()
+                    .map(|arc| arc.lock().unwrap_or_else(PoisonError::into_inner));
+                let cause = match cause {
+                    // Replacing keeps the entry; only its size changes
+                    RemovalCause::Replaced => {
+                        saturating_sub(&counters.bytes, entry.bytes as u64);
+                        return;
+                    }
+                    // ARC evicts through explicit removal
+                    RemovalCause::Explicit
+                        if directory.as_mut().is_some_and(|arc| arc.take_victim(&hash)) =>
+                    {
+                        EvictionCause::Size
+                    }
+                    // Counters were already adjusted by the caller
+                    RemovalCause::Explicit => EvictionCause::Explicit,
+                    RemovalCause::Size => EvictionCause::Size,
+                    RemovalCause::Expired => {
+                        if let Some(arc) = directory.as_mut() {
+                            arc.forget(&hash);
+                        }
+                        EvictionCause::Expired
+                    }
+                };
+                drop(directory);
+                if cause != EvictionCause::Explicit {
+                    counters.remove(entry.bytes);
+                    counters.evictions.fetch_add(1, Ordering::Relaxed);
+                }
+                if let Some(hook) = &hook {
+                    hook(&EvictionEvent {
+                        hash: *hash,
+                        type_id: entry.type_id,
+                        bytes: entry.bytes,
+                        cause,
+                    });
+                }
+            })
+            .build();
+        Segment {
+            entries,
+            arc,
+            weigh_bytes: matches!(capacity, CacheCapacity::Bytes(_)),
         }
     }
 
+    /// Segment holding artifacts of `type_id`
+    fn segment_for(&self, type_id: &str) -> &Segment {
+        self.quotas.get(type_id).unwrap_or(&self.shared)
+    }
+
+    fn segments(&self) -> impl Iterator<Item = &Segment> {
+        std::iter::once(&self.shared).chain(self.quotas.values())
+    }
+
     /// Insert artifact into cache
     #[inline]
     pub async fn insert<T: ArtifactType>(&self, hash: ContentHash, artifact: Artifact<T>) {
-        if !self.contains(&hash).await {
-            self.entry_count.fetch_add(1, Ordering::Relaxed);
+        let bytes = artifact.estimated_size();
+        self.insert_entry(
+            hash,
+            CachedArtifact {
+                artifact_hash: *artifact.hash(),
+                artifact: Arc::new(artifact),
+                type_id: T::TYPE_ID,
+                bytes,
+                inserted: Instant::now(),
+            },
+        )
+        .await;
+    }
+
+    /// Insert an artifact whose type is only known at runtime
+    pub(crate) async fn insert_erased(&self, hash: ContentHash, artifact: &ErasedArtifact) {
+        self.insert_entry(
+            hash,
+            CachedArtifact {
+                artifact: artifact.shared(),
+                artifact_hash: artifact.hash,
+                type_id: artifact.type_id,
+                bytes: artifact.bytes,
+                inserted: Instant::now(),
+            },
+        )
+        .await;
+    }
+
+    async fn insert_entry(&self, hash: ContentHash, entry: CachedArtifact) {
+        let segment = self.segment_for(entry.type_id);
+        if !segment.contains_key(&hash) {
+            self.counters.entries.fetch_add(1, Ordering::Relaxed);
         }
-        self.inner.insert(hash, Arc::new(artifact)).await;
+        self.counters.bytes.fetch_add(entry.bytes as u64, Ordering::Relaxed);
+        segment.insert(hash, entry).await;
     }
 
     /// Get artifact from cache
     #[inline]
     #[must_use]
     pub async fn get<T: ArtifactType>(&self, hash: &ContentHash) -> Option<Artifact<T>> {
-        self.inner
+        let found = self
+            .segment_for(T::TYPE_ID)
             .get(hash)
             .await
-            .and_then(|arc| arc.downcast_ref::<Artifact<T>>().cloned())
+            .and_then(|entry| entry.artifact.downcast_ref::<Artifact<T>>().cloned());
+        let counter = if found.is_some() {
+            &self.counters.hits
+        } else {
+            &self.counters.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        #[cfg(feature = "metrics")]
+        coa_kernel::metrics::global().artifact_cache.record(found.is_some());
+        found
     }
 
     /// Get or compute artifact
@@ -127,24 +767,317 @@ impl ArtifactCache {
     /// Invalidate cache entry
     #[inline]
     pub async fn invalidate(&self, hash: &ContentHash) {
-        if self.contains(hash).await {
-            self.entry_count.fetch_sub(1, Ordering::Relaxed);
+        for segment in self.segments() {
+            if let Some(entry) = segment.remove(hash).await {
+                self.counters.remove(entry.bytes);
+            }
         }
-        self.inner.invalidate(hash).await;
     }
 
     /// Invalidate all entries
     #[inline]
     pub fn invalidate_all(&self) {
-        self.entry_count.store(0, Ordering::Relaxed);
-        self.inner.invalidate_all();
+        self.counters.entries.store(0, Ordering::Relaxed);
+        self.counters.bytes.store(0, Ordering::Relaxed);
+        for segment in self.segments() {
+            segment.invalidate_all();
+        }
+        self.lineages
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clear();
     }
 
     /// Check if cache contains hash
     #[inline]
     #[must_use]
     pub async fn contains(&self, hash: &ContentHash) -> bool {
-        self.inner.get(hash).await.is_some()
+        self.segments().any(|segment| segment.contains_key(hash))
+    }
+
+    /// Insert one generation of a logical artifact
+    ///
+    /// `key` must carry a [`Generation`]; the artifact is cached by hash as
+    /// with [`insert`](Self::insert) and appended to its path's lineage.
+    /// Re-inserting an existing revision replaces it. Keys without a
+    /// generation are cached but not tracked.
+    pub async fn insert_generation<T: ArtifactType>(&self, key: TypedCacheKey, artifact: Artifact<T>) {
+        self.insert(key.hash, artifact).await;
+        adopt(
+            &mut self
+                .lineages
+                .write()
+                .unwrap_or_else(std::sync::PoisonError::into_inner),
+            std::slice::from_ref(&key),
+        );
+    }
+
+    /// Known generations of `path`, oldest first
+    ///
+    /// Includes generations whose artifact has since been evicted.
+    #[must_use]
+    pub fn generations<T: ArtifactType>(&self, path: &str) -> Vec<TypedCacheKey> {
+        self.lineages
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(&(TypeId::of::<T>(), Arc::from(path)))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Newest still-cached generation of `path`
+    pub async fn latest<T: ArtifactType>(&self, path: &str) -> Option<(TypedCacheKey, Artifact<T>)> {
+        for key in self.generations::<T>(path).into_iter().rev() {
+            if let Some(artifact) = self.get::<T>(&key.hash).await {
+                return Some((key, artifact));
+            }
+        }
+        None
+    }
+
+    /// Cached artifact for a specific revision of `path`
+    pub async fn get_generation<T: ArtifactType>(&self, path: &str, revision: u64) -> Option<Artifact<T>> {
+        let key = self
+            .generations::<T>(path)
+            .into_iter()
+            .find(|k| k.revision() == Some(revision))?;
+        self.get::<T>(&key.hash).await
+    }
+
+    /// Drop all but the newest `keep` generations of `path`
+    ///
+    /// Content still referenced by a retained generation (of any path) stays
+    /// cached. Returns the number of generations dropped.
+    pub async fn prune_superseded<T: ArtifactType>(&self, path: &str, keep: usize) -> usize {
+        let (dropped, orphaned) = {
+            let mut lineages = self
+                .lineages
+                .write()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            let Some(chain) = lineages.get_mut(&(TypeId::of::<T>(), Arc::from(path))) else {
+                return 0;
+            };
+            let cut = chain.len().saturating_sub(keep);
+            let superseded: Vec<TypedCacheKey> = chain.drain(..cut).collect();
+            if chain.is_empty() {
+                lineages.remove(&(TypeId::of::<T>(), Arc::from(path)));
+            }
+            let orphaned: Vec<TypedCacheKey> = superseded
+                .iter()
+                .filter(|old| {
+                    !lineages
+                        .values()
+                        .flatten()
+                        .any(|live| live.hash == old.hash && live.type_id == old.type_id)
+                })
+                .cloned()
+                .collect();
+            (superseded.len(), orphaned)
+        };
+
+        for key in orphaned {
+            self.invalidate(&key.hash).await;
+        }
+        dropped
+    }
+
+    /// Recompute the hashes of cached `T` artifacts with `algorithm`
+    ///
+    /// Entries keyed by their own artifact hash move to the new hash, as do
+    /// generations naming them; other keys (source checksums) are kept.
+    /// Returns the `(old, new)` artifact hashes.
+    pub(crate) async fn rehash<T: ArtifactType>(
+        &self,
+        algorithm: HashAlgorithm,
+    ) -> Vec<(ContentHash, ContentHash)> {
+        let stale: Vec<(ContentHash, CachedArtifact)> = self
+            .segment_for(T::TYPE_ID)
+            .iter()
+            .filter(|(_, entry)| {
+                entry.type_id == T::TYPE_ID && entry.artifact_hash.algorithm() != algorithm
+            })
+            .map(|(key, entry)| (*key, entry))
+            .collect();
+
+        let mut rehashed = Vec::new();
+        let mut moved = HashMap::new();
+        for (key, entry) in stale {
+            let Some(artifact) = entry.artifact.downcast_ref::<Artifact<T>>() else {
+                continue;
+            };
+            let Ok(migrated) = algorithm.scope(|| Artifact::<T>::new(artifact.content().clone()))
+            else {
+                continue;
+            };
+            let new_hash = *migrated.hash();
+            if new_hash == entry.artifact_hash {
+                continue;
+            }
+            let new_key = if key == entry.artifact_hash {
+                self.invalidate(&key).await;
+                moved.insert(key, new_hash);
+                new_hash
+            } else {
+                key
+            };
+            self.insert(new_key, migrated).await;
+            rehashed.push((entry.artifact_hash, new_hash));
+        }
+
+        if !moved.is_empty() {
+            let mut lineages = self
+                .lineages
+                .write()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            for key in lineages.values_mut().flatten() {
+                if let Some(new_hash) = moved.get(&key.hash) {
+                    key.hash = *new_hash;
+                }
+            }
+        }
+        rehashed
+    }
+
+    /// Every cached entry, for reachability analysis
+    pub(crate) fn entry_infos(&self) -> Vec<EntryInfo> {
+        self.segments()
+            .flat_map(|segment| segment.iter())
+            .map(|(key, entry)| EntryInfo {
+                key: *key,
+                artifact_hash: entry.artifact_hash,
+                bytes: entry.bytes,
+                inserted: entry.inserted,
+            })
+            .collect()
+    }
+
+    /// Hashes of the newest `keep` generations of every path
+    pub(crate) fn newest_generations(&self, keep: usize) -> Vec<ContentHash> {
+        self.lineages
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .values()
+            .flat_map(|chain| chain.iter().rev().take(keep).map(|key| key.hash))
+            .collect()
+    }
+
+    /// Drop generations whose content is in `hashes`
+    pub(crate) fn forget_generations(&self, hashes: &HashSet<ContentHash>) {
+        let mut lineages = self
+            .lineages
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        for chain in lineages.values_mut() {
+            chain.retain(|key| !hashes.contains(&key.hash));
+        }
+        lineages.retain(|_, chain| !chain.is_empty());
+    }
+
+    /// Capture the current generation of every logical artifact
+    ///
+    /// Cached content is immutable, so the snapshot only copies lineages;
+    /// [`restore`](Self::restore) rolls them back to this point.
+    #[must_use]
+    pub fn snapshot(&self) -> CacheSnapshot {
+        CacheSnapshot {
+            lineages: self
+                .lineages
+                .read()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .clone(),
+        }
+    }
+
+    /// Roll generations back to `snapshot`
+    ///
+    /// Generations recorded since are forgotten; their content stays
+    /// cached until evicted or collected.
+    pub fn restore(&self, snapshot: &CacheSnapshot) {
+        *self
+            .lineages
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = snapshot.lineages.clone();
+    }
+
+    /// Copy-on-write view for speculative work
+    ///
+    /// The fork shares cached content (and capacity, quotas and statistics)
+    /// with this cache but starts from a private copy of its generations:
+    /// generations inserted into either side are invisible to the other.
+    #[must_use]
+    pub fn fork(&self) -> Self {
+        Self {
+            shared: self.shared.clone(),
+            quotas: Arc::clone(&self.quotas),
+            counters: Arc::clone(&self.counters),
+            lineages: Arc::new(RwLock::new(self.snapshot().lineages)),
+            tenant: self.tenant.clone(),
+        }
+    }
+
+    /// Tenant owning the cache, if any
+    #[inline]
+    #[must_use]
+    pub fn tenant(&self) -> Option<&TenantId> {
+        self.tenant.as_ref()
+    }
+
+    /// Check if `other` is this cache or a fork sharing its content
+    #[inline]
+    #[must_use]
+    pub fn shares_content(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.counters, &other.counters)
+    }
+
+    /// Generations present here but not in `base`, oldest first per path
+    pub(crate) fn generations_since(&self, base: &CacheSnapshot) -> Vec<TypedCacheKey> {
+        let lineages = self
+            .lineages
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let mut added: Vec<TypedCacheKey> = lineages
+            .iter()
+            .flat_map(|(lineage, chain)| {
+                let known = base.lineages.get(lineage);
+                chain
+                    .iter()
+                    .filter(move |key| !known.is_some_and(|known| known.contains(key)))
+                    .cloned()
+            })
+            .collect();
+        added.sort_by(|a, b| (a.path(), a.revision()).cmp(&(b.path(), b.revision())));
+        added
+    }
+
+    /// Append `keys` unless their lineages moved on since `base`
+    ///
+    /// All-or-nothing: on conflict nothing is appended and the first key
+    /// whose lineage advanced is returned with its current head revision.
+    pub(crate) fn adopt_unchanged(
+        &self,
+        base: &CacheSnapshot,
+        keys: &[TypedCacheKey],
+    ) -> Result<(), (TypedCacheKey, Option<u64>)> {
+        let mut lineages = self
+            .lineages
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        for key in keys {
+            let current = head_revision(&lineages, key);
+            if current != base.head_revision(key) {
+                return Err((key.clone(), current));
+            }
+        }
+        adopt(&mut lineages, keys);
+        Ok(())
+    }
+
+
+    /// Apply pending evictions and expirations now
+    pub async fn run_pending_tasks(&self) {
+        for segment in self.segments() {
+            segment.run_pending_tasks().await;
+        }
     }
 
     /// Get cache statistics
@@ -152,7 +1085,11 @@ impl ArtifactCache {
     #[must_use]
     pub fn stats(&self) -> CacheStats {
         CacheStats {
-            entry_count: self.entry_count.load(Ordering::Relaxed),
+            entry_count: self.counters.entries.load(Ordering::Relaxed),
+            bytes: self.counters.bytes.load(Ordering::Relaxed),
+            hits: self.counters.hits.load(Ordering::Relaxed),
+            misses: self.counters.misses.load(Ordering::Relaxed),
+            evictions: self.counters.evictions.load(Ordering::Relaxed),
         }
     }
 
@@ -160,7 +1097,7 @@ impl ArtifactCache {
     #[inline]
     #[must_use]
     pub fn entry_count(&self) -> u64 {
-        self.entry_count.load(Ordering::Relaxed)
+        self.counters.entries.load(Ordering::Relaxed)
     }
 }
 
@@ -171,11 +1108,71 @@ impl Default for ArtifactCache {
     }
 }
 
+/// Position of an artifact in the history of one logical file
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Generation {
+    /// Logical path (e.g. workspace-relative file path)
+    pub path: Arc<str>,
+
+    /// Revision number; higher is newer
+    pub revision: u64,
+}
+
+/// Generations of a cache at one point in time
+///
+/// Taken by [`ArtifactCache::snapshot`]; see also [`ArtifactCache::fork`].
+#[derive(Debug, Clone, Default)]
+pub struct CacheSnapshot {
+    lineages: Lineages,
+}
+
+impl CacheSnapshot {
+    /// Newest revision of the lineage `key` belongs to, as of the snapshot
+    #[must_use]
+    pub fn head_revision(&self, key: &TypedCacheKey) -> Option<u64> {
+        head_revision(&self.lineages, key)
+    }
+
+    /// Every generation in the snapshot
+    pub(crate) fn keys(&self) -> impl Iterator<Item = &TypedCacheKey> {
+        self.lineages.values().flatten()
+    }
+}
+
+/// Insert generations into their lineages, replacing equal revisions
+fn adopt(lineages: &mut Lineages, keys: &[TypedCacheKey]) {
+    for key in keys {
+        let Some(generation) = &key.generation else {
+            continue;
+        };
+        let chain = lineages
+            .entry((key.type_id, Arc::clone(&generation.path)))
+            .or_default();
+        match chain.binary_search_by_key(&generation.revision, |k| k.revision().unwrap_or(0)) {
+            Ok(i) => chain[i] = key.clone(),
+            Err(i) => chain.insert(i, key.clone()),
+        }
+    }
+}
+
+fn head_revision(lineages: &Lineages, key: &TypedCacheKey) -> Option<u64> {
+    let generation = key.generation.as_ref()?;
+    lineages
+        .get(&(key.type_id, Arc::clone(&generation.path)))?
+        .last()?
+        .revision()
+}
+
 /// Type-aware cache key for type-safe caching
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+///
+/// A key may also name a [`Generation`], letting the cache hold several
+/// versions of one logical artifact (e.g. a file as ingested and as
+/// composed) and resolve the latest one by path.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TypedCacheKey {
     hash: ContentHash,
     type_id: TypeId,
+    generation: Option<Generation>,
 }
 
 impl TypedCacheKey {
@@ -186,6 +1183,20 @@ impl TypedCacheKey {
         Self {
             hash,
             type_id: TypeId::of::<T>(),
+            generation: None,
+        }
+    }
+
+    /// Create key for revision `revision` of the artifact at `path`
+    #[inline]
+    #[must_use]
+    pub fn for_generation<T: ArtifactType>(hash: ContentHash, path: &str, revision: u64) -> Self {
+        Self {
+            generation: Some(Generation {
+                path: Arc::from(path),
+                revision,
+            }),
+            ..Self::new::<T>(hash)
         }
     }
 
@@ -202,6 +1213,27 @@ impl TypedCacheKey {
     pub fn type_id(&self) -> TypeId {
         self.type_id
     }
+
+    /// Logical path and revision, if generational
+    #[inline]
+    #[must_use]
+    pub fn generation(&self) -> Option<&Generation> {
+        self.generation.as_ref()
+    }
+
+    /// Logical path, if generational
+    #[inline]
+    #[must_use]
+    pub fn path(&self) -> Option<&str> {
+        self.generation.as_ref().map(|g| &*g.path)
+    }
+
+    /// Revision, if generational
+    #[inline]
+    #[must_use]
+    pub fn revision(&self) -> Option<u64> {
+        self.generation.as_ref().map(|g| g.revision)
+    }
 }
 
 #[cfg(test)]
@@ -332,6 +1364,164 @@ mod tests {
         assert!(cache.get::<TestArtifact>(&hash).await.is_some());
     }
 
+    #[derive(Debug, Clone)]
+    struct BlobArtifact;
+
+    impl coa_artifact::__private::Sealed for BlobArtifact {}
+
+    impl ArtifactType for BlobArtifact {
+        type Content = Vec<u8>;
+
+        fn hash(content: &Self::Content) -> ContentHash {
+            ContentHash::compute(content)
+        }
+
+        const TYPE_ID: &'static str = "blob";
+
+        fn estimated_size(content: &Self::Content) -> usize {
+            content.len()
+        }
+    }
+
+    fn text(data: &str) -> Artifact<TestArtifact> {
+        Artifact::new(TestContent {
+            data: data.to_string(),
+        })
+        .unwrap()
+    }
+
+    fn blob(fill: u8, len: usize) -> Artifact<BlobArtifact> {
+        Artifact::new(vec![fill; len]).unwrap()
+    }
+
+    #[tokio::test]
+    async fn cache_tracks_hits_and_misses() {
+        let cache = ArtifactCache::new(100);
+        let artifact = text("hit me");
+        cache.insert(*artifact.hash(), artifact.clone()).await;
+
+        assert!(cache.get::<TestArtifact>(artifact.hash()).await.is_some());
+        assert!(cache.get::<TestArtifact>(&ContentHash::compute(b"nope")).await.is_none());
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (1, 1));
+        assert!((stats.hit_rate() - 0.5).abs() < f64::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn lru_cache_evicts_least_recent_and_notifies() {
+        let evicted = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = Arc::clone(&evicted);
+        let cache = ArtifactCache::with_config(
+            CacheConfig::new()
+                .with_max_entries(2)
+                .with_policy(EvictionPolicy::Lru)
+                .with_eviction_hook(move |event| sink.lock().unwrap().push(event.clone())),
+        );
+
+        let (a, b, c) = (text("a"), text("b"), text("c"));
+        cache.insert(*a.hash(), a.clone()).await;
+        cache.insert(*b.hash(), b.clone()).await;
+        cache.run_pending_tasks().await;
+        assert!(cache.get::<TestArtifact>(a.hash()).await.is_some());
+        cache.insert(*c.hash(), c.clone()).await;
+        cache.run_pending_tasks().await;
+
+        assert!(cache.contains(a.hash()).await);
+        assert!(!cache.contains(b.hash()).await);
+        assert!(cache.contains(c.hash()).await);
+        assert_eq!(cache.stats().evictions, 1);
+        assert_eq!(cache.entry_count(), 2);
+
+        let events = evicted.lock().unwrap().clone();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].hash, *b.hash());
+        assert_eq!(events[0].type_id, "test");
+        assert_eq!(events[0].cause, EvictionCause::Size);
+    }
+
+    #[tokio::test]
+    async fn arc_cache_keeps_reused_entries_through_a_scan() {
+        let causes = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = Arc::clone(&causes);
+        let cache = ArtifactCache::with_config(
+            CacheConfig::new()
+                .with_max_entries(4)
+                .with_policy(EvictionPolicy::Arc)
+                .with_eviction_hook(move |event| sink.lock().unwrap().push(event.cause)),
+        );
+
+        let (a, b) = (text("a"), text("b"));
+        for hot in [&a, &b] {
+            cache.insert(*hot.hash(), hot.clone()).await;
+            assert!(cache.get::<TestArtifact>(hot.hash()).await.is_some());
+        }
+        let scan: Vec<_> = (0..10).map(|i| text(&format!("scan {i}"))).collect();
+        for cold in &scan {
+            cache.insert(*cold.hash(), cold.clone()).await;
+        }
+        cache.run_pending_tasks().await;
+
+        assert!(cache.contains(a.hash()).await);
+        assert!(cache.contains(b.hash()).await);
+        assert!(!cache.contains(scan[0].hash()).await);
+        assert!(cache.contains(scan[9].hash()).await);
+        assert_eq!(cache.entry_count(), 4);
+        assert_eq!(cache.stats().evictions, 8);
+        assert_eq!(*causes.lock().unwrap(), vec![EvictionCause::Size; 8]);
+
+        // A recent victim that comes back joins the reused entries
+        cache.insert(*scan[7].hash(), scan[7].clone()).await;
+        for cold in [text("late 1"), text("late 2")] {
+            cache.insert(*cold.hash(), cold).await;
+        }
+        cache.run_pending_tasks().await;
+        assert!(cache.contains(a.hash()).await);
+        assert!(cache.contains(b.hash()).await);
+        assert!(cache.contains(scan[7].hash()).await);
+        assert!(!cache.contains(scan[9].hash()).await);
+        assert_eq!(cache.entry_count(), 4);
+    }
+
+    #[tokio::test]
+    async fn per_type_quota_isolates_large_artifacts() {
+        let cache = ArtifactCache::with_config(
+            CacheConfig::new()
+                .with_max_entries(10)
+                .with_quota("blob", CacheCapacity::Bytes(1_000)),
+        );
+        let code = text("fn main() {}");
+        cache.insert(*code.hash(), code.clone()).await;
+
+        for fill in 0..4 {
+            let big = blob(fill, 400);
+            cache.insert(*big.hash(), big).await;
+            cache.run_pending_tasks().await;
+        }
+
+        assert!(cache.contains(code.hash()).await);
+        let stats = cache.stats();
+        assert!(stats.evictions >= 2);
+        assert!(stats.bytes <= 1_000 + code.estimated_size() as u64);
+    }
+
+    #[tokio::test]
+    async fn invalidate_reports_explicit_removal() {
+        let causes = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = Arc::clone(&causes);
+        let cache = ArtifactCache::with_config(
+            CacheConfig::new().with_eviction_hook(move |event| sink.lock().unwrap().push(event.cause)),
+        );
+        let big = blob(7, 64);
+        cache.insert(*big.hash(), big.clone()).await;
+        assert_eq!(cache.stats().bytes, 64);
+
+        cache.invalidate(big.hash()).await;
+
+        assert_eq!(cache.stats(), CacheStats::default());
+        assert_eq!(*causes.lock().unwrap(), vec![EvictionCause::Explicit]);
+    }
+
     #[test]
     fn typed_cache_key_creation() {
         let hash = ContentHash::compute(b"test");
@@ -339,5 +1529,78 @@ mod tests {
 
         assert_eq!(key.hash(), &hash);
         assert_eq!(key.type_id(), TypeId::of::<TestArtifact>());
+        assert!(key.generation().is_none());
+    }
+
+    #[tokio::test]
+    async fn generations_resolve_latest_and_prune_superseded() {
+        let cache = ArtifactCache::new(100);
+        let path = "src/main.rs";
+        let (original, composed, reformatted) = (text("v1"), text("v2"), text("v3"));
+
+        for (revision, artifact) in [(2, composed.clone()), (1, original.clone()), (3, reformatted.clone())] {
+            let key = TypedCacheKey::for_generation::<TestArtifact>(*artifact.hash(), path, revision);
+            cache.insert_generation(key, artifact).await;
+        }
+
+        let revisions: Vec<_> = cache
+            .generations::<TestArtifact>(path)
+            .iter()
+            .map(|k| k.revision().unwrap())
+            .collect();
+        assert_eq!(revisions, [1, 2, 3]);
+
+        let (key, latest) = cache.latest::<TestArtifact>(path).await.unwrap();
+        assert_eq!(key.path(), Some(path));
+        assert_eq!(latest.hash(), reformatted.hash());
+        assert_eq!(
+            cache.get_generation::<TestArtifact>(path, 1).await.unwrap().hash(),
+            original.hash()
+        );
+        assert!(cache.generations::<BlobArtifact>(path).is_empty());
+
+        // Another file still references v2's content, so it stays cached
+        let alias = TypedCacheKey::for_generation::<TestArtifact>(*composed.hash(), "src/copy.rs", 1);
+        cache.insert_generation(alias, composed.clone()).await;
+
+        assert_eq!(cache.prune_superseded::<TestArtifact>(path, 1).await, 2);
+        assert_eq!(cache.generations::<TestArtifact>(path).len(), 1);
+        assert!(!cache.contains(original.hash()).await);
+        assert!(cache.contains(composed.hash()).await);
+
+        // Generations whose content was evicted are skipped
+        cache.invalidate(reformatted.hash()).await;
+        assert!(cache.latest::<TestArtifact>(path).await.is_none());
+        assert!(cache.latest::<TestArtifact>("src/copy.rs").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn forks_share_content_but_not_generations() {
+        let cache = ArtifactCache::new(100);
+        let path = "src/main.rs";
+        let (original, speculative) = (text("v1"), text("v2"));
+        let v1 = TypedCacheKey::for_generation::<TestArtifact>(*original.hash(), path, 1);
+        cache.insert_generation(v1.clone(), original).await;
+
+        let base = cache.snapshot();
+        let fork = cache.fork();
+        assert!(fork.shares_content(&cache));
+        assert!(!ArtifactCache::new(100).shares_content(&cache));
+
+        let v2 = TypedCacheKey::for_generation::<TestArtifact>(*speculative.hash(), path, 2);
+        fork.insert_generation(v2.clone(), speculative.clone()).await;
+        assert_eq!(fork.generations::<TestArtifact>(path).len(), 2);
+        assert_eq!(cache.generations::<TestArtifact>(path), std::slice::from_ref(&v1));
+        assert!(cache.contains(speculative.hash()).await);
+
+        assert_eq!(fork.generations_since(&base), std::slice::from_ref(&v2));
+        assert_eq!(base.head_revision(&v2), Some(1));
+        cache.adopt_unchanged(&base, std::slice::from_ref(&v2)).unwrap();
+        assert_eq!(cache.snapshot().head_revision(&v2), Some(2));
+        let conflict = cache.adopt_unchanged(&base, std::slice::from_ref(&v2));
+        assert_eq!(conflict, Err((v2, Some(2))));
+
+        cache.restore(&base);
+        assert_eq!(cache.generations::<TestArtifact>(path), [v1]);
     }
 }
@@ -0,0 +1,392 @@
+//! Artifact diffing into structural deltas
+//!
+//! Agents that emit whole new file contents can be brought into the delta
+//! world with [`diff`]: it compares two versions of an artifact and returns
+//! the [`StructuralDelta`]s that turn the base into the new version.
+//!
+//! Granularity depends on the artifact type:
+//! - Code: one delta per changed top-level symbol (Merkle root fast path)
+//! - JSON/YAML config: one delta per changed key
+//! - Markdown: one delta per changed section
+//! - Anything else (SQL, manifests, binaries): a whole-content `Replace`
+//!
+//! Whenever the structural view cannot express a change (e.g. code edited
+//! between symbols), the diff falls back to a whole-content `Replace` at the
+//! root path.
+
+use crate::parsers::{
+    CodeArtifact, CodeContent, JsonArtifact, JsonContent, ManifestArtifact, MarkdownArtifact,
+    MarkdownContent, Section, SqlArtifact, YamlArtifact, YamlContent,
+};
+use coa_artifact::{
+    Artifact, ArtifactMerkleTree, ArtifactType, ContentHash, DeltaOperation, StructuralDelta,
+    SymbolPath,
+};
+
+/// Artifact types that can be diffed into structural deltas
+pub trait ArtifactDiff: ArtifactType + Sized {
+    /// Structural operations turning `base` into `new`, as (target, operation)
+    ///
+    /// Return an empty list when the change cannot be expressed structurally;
+    /// [`diff`] then emits a whole-content replacement. The default has no
+    /// structure, which suits opaque (e.g. binary) content.
+    fn structural_diff(
+        _base: &Self::Content,
+        _new: &Self::Content,
+    ) -> Vec<(SymbolPath, DeltaOperation<Self>)> {
+        Vec::new()
+    }
+}
+
+/// Deltas that turn `base` into `new`
+///
+/// Every delta is based on `base`'s hash and carries its position as
+/// `order`. Identical artifacts produce no deltas.
+#[must_use]
+pub fn diff<T: ArtifactDiff>(base: &Artifact<T>, new: &Artifact<T>) -> Vec<StructuralDelta<T>> {
+    if base.hash() == new.hash() {
+        return Vec::new();
+    }
+    let mut ops = T::structural_diff(base.content(), new.content());
+    if ops.is_empty() {
+        ops.push((SymbolPath::root(), DeltaOperation::Replace(new.content().clone())));
+    }
+    ops.into_iter()
+        .enumerate()
+        .map(|(order, (target, op))| {
+            let order = u32::try_from(order).unwrap_or(u32::MAX);
+            StructuralDelta::with_order(target, op, *base.hash(), order)
+        })
+        .collect()
+}
+
+impl ArtifactDiff for CodeArtifact {
+    /// Symbol-level diff
+    ///
+    /// Each top-level symbol's source is hashed into a Merkle leaf (plus one
+    /// leaf for the text between symbols); equal roots mean no symbol
+    /// changed. Text between symbols is compared ignoring whitespace, and
+    /// any other change there yields no structural delta.
+    fn structural_diff(
+        base: &CodeContent,
+        new: &CodeContent,
+    ) -> Vec<(SymbolPath, DeltaOperation<Self>)> {
+        if base.language != new.language {
+            return Vec::new();
+        }
+        let base_leaves = code_leaves(base);
+        let new_leaves = code_leaves(new);
+        let root = |leaves: &CodeLeaves| {
+            let mut hashes: Vec<ContentHash> = leaves.symbols.iter().map(|s| s.1).collect();
+            hashes.push(leaves.skeleton);
+            ArtifactMerkleTree::from_leaves(&hashes).root()
+        };
+        if base_leaves.skeleton != new_leaves.skeleton || root(&base_leaves) == root(&new_leaves) {
+            return Vec::new();
+        }
+
+        let find = |leaves: &CodeLeaves, name: &str| {
+            leaves.symbols.iter().find(|(n, ..)| n == name).map(|s| s.1)
+        };
+        let fragment = |name: &str, text: String| CodeContent {
+            language: new.language,
+            source: text,
+            symbols: vec![name.to_string()],
+        };
+
+        let mut ops = Vec::new();
+        for (name, _, _) in &base_leaves.symbols {
+            if find(&new_leaves, name).is_none() {
+                ops.push((SymbolPath::single(name.as_str()), DeltaOperation::Remove));
+            }
+        }
+        for (name, hash, text) in new_leaves.symbols {
+            let op = match find(&base_leaves, &name) {
+                None => DeltaOperation::Add(fragment(&name, text)),
+                Some(old) if old != hash => DeltaOperation::Replace(fragment(&name, text)),
+                Some(_) => continue,
+            };
+            ops.push((SymbolPath::single(name), op));
+        }
+        ops
+    }
+}
+
+/// Per-symbol hashes of a code artifact
+struct CodeLeaves {
+    /// (name, hash of source, source) in definition order
+    symbols: Vec<(String, ContentHash, String)>,
+    /// Hash of the whitespace-normalised text outside all symbols
+    skeleton: ContentHash,
+}
+
+fn code_leaves(content: &CodeContent) -> CodeLeaves {
+    let mut symbols: Vec<(String, ContentHash, String)> = Vec::new();
+    let mut spans = Vec::new();
+    for name in &content.symbols {
+        if symbols.iter().any(|(n, ..)| n == name) {
+            continue;
+        }
+        if let Some(span) = content.symbol_span(name) {
+            let text = content.source[span.start..span.end].to_string();
+            symbols.push((name.clone(), ContentHash::compute(text.as_bytes()), text));
+            spans.push(span);
+        }
+    }
+
+    spans.sort();
+    let mut outside = String::new();
+    let mut cursor = 0;
+    for span in spans {
+        if span.start >= cursor {
+            outside.push_str(&content.source[cursor..span.start]);
+            outside.push(' ');
+        }
+        cursor = cursor.max(span.end);
+    }
+    outside.push_str(&content.source[cursor..]);
+    let normalised = outside.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    CodeLeaves {
+        symbols,
+        skeleton: ContentHash::compute(normalised.as_bytes()),
+    }
+}
+
+impl ArtifactDiff for JsonArtifact {
+    /// Key-level diff; arrays and scalars are replaced whole
+    fn structural_diff(
+        base: &JsonContent,
+        new: &JsonContent,
+    ) -> Vec<(SymbolPath, DeltaOperation<Self>)> {
+        let (serde_json::Value::Object(old), serde_json::Value::Object(updated)) =
+            (&base.root, &new.root)
+        else {
+            return Vec::new();
+        };
+        let mut ops = Vec::new();
+        diff_json(old, updated, &SymbolPath::root(), &mut ops);
+        ops
+    }
+}
+
+fn diff_json(
+    old: &serde_json::Map<String, serde_json::Value>,
+    new: &serde_json::Map<String, serde_json::Value>,
+    prefix: &SymbolPath,
+    ops: &mut Vec<(SymbolPath, DeltaOperation<JsonArtifact>)>,
+) {
+    use serde_json::Value;
+
+    for key in old.keys().filter(|k| !new.contains_key(*k)) {
+        ops.push((prefix.child(key.as_str()), DeltaOperation::Remove));
+    }
+    for (key, value) in new {
+        let path = prefix.child(key.as_str());
+        match (old.get(key), value) {
+            (None, _) => ops.push((path, DeltaOperation::Add(JsonContent::new(value.clone())))),
+            (Some(Value::Object(a)), Value::Object(b)) => diff_json(a, b, &path, ops),
+            (Some(previous), _) if previous != value => {
+                ops.push((path, DeltaOperation::Replace(JsonContent::new(value.clone()))));
+            }
+            _ => {}
+        }
+    }
+}
+
+impl ArtifactDiff for YamlArtifact {
+    /// Key-level diff of single-document files; sequences and scalars are
+    /// replaced whole
+    fn structural_diff(
+        base: &YamlContent,
+        new: &YamlContent,
+    ) -> Vec<(SymbolPath, DeltaOperation<Self>)> {
+        let ([serde_yaml::Value::Mapping(old)], [serde_yaml::Value::Mapping(updated)]) =
+            (base.documents.as_slice(), new.documents.as_slice())
+        else {
+            return Vec::new();
+        };
+        let mut ops = Vec::new();
+        diff_yaml(old, updated, &SymbolPath::root(), &mut ops);
+        ops
+    }
+}
+
+fn diff_yaml(
+    old: &serde_yaml::Mapping,
+    new: &serde_yaml::Mapping,
+    prefix: &SymbolPath,
+    ops: &mut Vec<(SymbolPath, DeltaOperation<YamlArtifact>)>,
+) {
+    use serde_yaml::Value;
+
+    for key in old.keys().filter(|k| !new.contains_key(*k)) {
+        if let Some(key) = key.as_str() {
+            ops.push((prefix.child(key), DeltaOperation::Remove));
+        }
+    }
+    for (key, value) in new {
+        let Some(name) = key.as_str() else { continue };
+        let path = prefix.child(name);
+        match (old.get(key), value) {
+            (None, _) => ops.push((path, DeltaOperation::Add(YamlContent::new(value.clone())))),
+            (Some(Value::Mapping(a)), Value::Mapping(b)) => diff_yaml(a, b, &path, ops),
+            (Some(previous), _) if previous != value => {
+                ops.push((path, DeltaOperation::Replace(YamlContent::new(value.clone()))));
+            }
+            _ => {}
+        }
+    }
+}
+
+impl ArtifactDiff for MarkdownArtifact {
+    /// Section-level diff keyed by section slug
+    ///
+    /// A section whose heading or own text changed is replaced together with
+    /// its subsections; otherwise its subsections are compared.
+    fn structural_diff(
+        base: &MarkdownContent,
+        new: &MarkdownContent,
+    ) -> Vec<(SymbolPath, DeltaOperation<Self>)> {
+        if base.metadata != new.metadata {
+            return Vec::new();
+        }
+        let mut ops = Vec::new();
+        diff_sections(&base.sections, &new.sections, &SymbolPath::root(), &mut ops);
+        ops
+    }
+}
+
+fn diff_sections(
+    old: &[Section],
+    new: &[Section],
+    prefix: &SymbolPath,
+    ops: &mut Vec<(SymbolPath, DeltaOperation<MarkdownArtifact>)>,
+) {
+    let find = |sections: &'_ [Section], slug: &str| {
+        sections.iter().find(|s| s.slug() == slug).cloned()
+    };
+    let fragment = |section: &Section| MarkdownContent {
+        source: String::new(),
+        title: None,
+        sections: vec![section.clone()],
+        code_blocks: Vec::new(),
+        metadata: None,
+        links: Vec::new(),
+    };
+
+    for section in old {
+        if find(new, &section.slug()).is_none() {
+            ops.push((prefix.child(section.slug()), DeltaOperation::Remove));
+        }
+    }
+    for section in new {
+        let path = prefix.child(section.slug());
+        match find(old, &section.slug()) {
+            None => ops.push((path, DeltaOperation::Add(fragment(section)))),
+            Some(previous)
+                if previous.level != section.level
+                    || previous.title != section.title
+                    || previous.content != section.content =>
+            {
+                ops.push((path, DeltaOperation::Replace(fragment(section))));
+            }
+            Some(previous) => diff_sections(&previous.children, &section.children, &path, ops),
+        }
+    }
+}
+
+impl ArtifactDiff for SqlArtifact {}
+
+impl ArtifactDiff for ManifestArtifact {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::{
+        ArtifactParser, CodeParser, JsonParser, Language, MarkdownParser, YamlParser,
+    };
+    use crate::serializers::CodeSerializer;
+
+    fn targets<T: ArtifactType>(deltas: &[StructuralDelta<T>]) -> Vec<String> {
+        deltas
+            .iter()
+            .map(|d| {
+                let op = match d.operation() {
+                    DeltaOperation::Add(_) => "add",
+                    DeltaOperation::Remove => "remove",
+                    DeltaOperation::Replace(_) => "replace",
+                    DeltaOperation::Transform(_) => "transform",
+                    DeltaOperation::BinaryPatch(_) => "binary_patch",
+                };
+                format!("{op} {}", d.target())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn code_diff_is_symbol_level_and_applies() {
+        let parser = CodeParser::new(Language::Rust);
+        let base = parser
+            .parse("fn keep() {}\n\nfn edit() {\n    a();\n}\n\nfn drop() {}\n")
+            .unwrap();
+        let new = parser
+            .parse("fn keep() {}\n\nfn edit() {\n    b();\n}\n\nfn added() {}\n")
+            .unwrap();
+
+        let deltas = diff(&base, &new);
+        assert_eq!(targets(&deltas), ["remove drop", "replace edit", "add added"]);
+        assert!(deltas.iter().all(|d| d.base_hash() == base.hash()));
+
+        let applied = CodeSerializer::new(Language::Rust).apply(&base, &deltas).unwrap();
+        assert_eq!(applied.content().symbols, ["keep", "edit", "added"]);
+        assert!(applied.content().source.contains("b();"));
+        assert!(diff(&base, &base).is_empty());
+    }
+
+    #[test]
+    fn code_edit_outside_symbols_replaces_whole_content() {
+        let parser = CodeParser::new(Language::Rust);
+        let base = parser.parse("use a;\n\nfn run() {}\n").unwrap();
+        let new = parser.parse("use b;\n\nfn run() {}\n").unwrap();
+
+        let deltas = diff(&base, &new);
+        assert_eq!(targets(&deltas), ["replace "]);
+        assert!(deltas[0].target().is_empty());
+
+        let applied = CodeSerializer::new(Language::Rust).apply(&base, &deltas).unwrap();
+        assert_eq!(applied.hash(), new.hash());
+    }
+
+    #[test]
+    fn config_diff_is_key_level() {
+        let base = JsonParser
+            .parse(r#"{"server": {"port": 80, "host": "a"}, "debug": true, "tags": [1]}"#)
+            .unwrap();
+        let new = JsonParser
+            .parse(r#"{"server": {"port": 8080, "host": "a", "tls": true}, "tags": [1, 2]}"#)
+            .unwrap();
+        assert_eq!(
+            targets(&diff(&base, &new)),
+            ["remove debug", "replace server.port", "add server.tls", "replace tags"]
+        );
+
+        let base = YamlParser.parse("server:\n  port: 80\nname: x\n").unwrap();
+        let new = YamlParser.parse("server:\n  port: 81\nname: x\n").unwrap();
+        assert_eq!(targets(&diff(&base, &new)), ["replace server.port"]);
+    }
+
+    #[test]
+    fn markdown_diff_is_section_level() {
+        let base = MarkdownParser
+            .parse("# Spec\n\nIntro\n\n## Auth\n\nTokens\n\n## Legacy\n\nOld\n")
+            .unwrap();
+        let new = MarkdownParser
+            .parse("# Spec\n\nIntro\n\n## Auth\n\nSessions\n\n## Billing\n\nPlans\n")
+            .unwrap();
+        assert_eq!(
+            targets(&diff(&base, &new)),
+            ["remove spec.legacy", "replace spec.auth", "add spec.billing"]
+        );
+    }
+}
@@ -4,9 +4,18 @@
 //! - Parse operations (file → Artifact)
 //! - Apply operations (delta transformation)
 //! - Serialize operations (Artifact → file)
-
-use coa_artifact::{ArtifactError, DeltaError, SymbolPath};
-use coa_composition::CompositionError;
+//! - Egress operations (patches and pull requests)
+//! - Speculative execution (commit/abort of forked work)
+//! - Pipeline operations (post-apply middlewares)
+//! - Refactoring operations (cross-artifact rewrites)
+
+use coa_artifact::{ArtifactError, ContentHash, DeltaError, SymbolPath};
+use coa_composition::{CompositionError, TransactionError};
+use coa_kernel::error::LogError;
+use coa_kernel::types::AutonomyLevel;
+use coa_kernel::isolation::ScopeViolation;
+use crate::secrets::SecretFinding;
+use crate::xref::BrokenReference;
 use std::path::PathBuf;
 
 /// Errors during file parsing (ingress)
@@ -39,6 +48,17 @@ pub enum ParseError {
     /// Content validation failed
     #[error("content validation failed: {0}")]
     ValidationError(String),
+
+    /// Path outside the agent's filesystem scope
+    #[error("scope violation: {0}")]
+    ScopeViolation(#[from] ScopeViolation),
+
+    /// Secret scanner blocked the content
+    #[error("secrets detected in {}: {} finding(s)", path.display(), findings.len())]
+    SecretsDetected {
+        path: PathBuf,
+        findings: Vec<SecretFinding>,
+    },
 }
 
 impl ParseError {
@@ -100,6 +120,18 @@ pub enum ApplyError {
     /// Artifact system error
     #[error("artifact error: {0}")]
     ArtifactError(#[from] ArtifactError),
+
+    /// Applied composition could not be recorded in the kernel event log
+    #[error("audit log error: {0}")]
+    Audit(#[from] LogError),
+
+    /// Composition would leave spec cross-references dangling
+    #[error("{} broken reference(s): {}", .0.len(), list_broken(.0))]
+    BrokenReferences(Vec<BrokenReference>),
+}
+
+fn list_broken(broken: &[BrokenReference]) -> String {
+    broken.iter().map(ToString::to_string).collect::<Vec<_>>().join("; ")
 }
 
 impl ApplyError {
@@ -134,6 +166,10 @@ pub enum SerializeError {
     /// Format-specific error
     #[error("format error: {0}")]
     FormatError(String),
+
+    /// Path outside the agent's filesystem scope
+    #[error("scope violation: {0}")]
+    ScopeViolation(#[from] ScopeViolation),
 }
 
 impl SerializeError {
@@ -146,6 +182,118 @@ impl SerializeError {
     }
 }
 
+/// Errors publishing changes as patches or pull requests
+#[derive(Debug, thiserror::Error)]
+pub enum EgressError {
+    /// Rendering an artifact failed
+    #[error("serialize error: {0}")]
+    Serialize(#[from] SerializeError),
+
+    /// IO error writing a patch
+    #[error("io error writing {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// Change set changes no file
+    #[error("change set is empty")]
+    EmptyChangeSet,
+
+    /// Forge rejected a request
+    #[error("forge returned {status}: {message}")]
+    Forge { status: u16, message: String },
+
+    /// Request to the forge failed or returned an unexpected body
+    #[error("forge request failed: {0}")]
+    Http(String),
+}
+
+impl EgressError {
+    /// Create IO error for path
+    pub fn io_error(path: impl Into<PathBuf>, source: std::io::Error) -> Self {
+        Self::Io {
+            path: path.into(),
+            source,
+        }
+    }
+}
+
+/// Errors committing speculative work
+#[derive(Debug, thiserror::Error)]
+pub enum SpeculationError {
+    /// Node may not merge unattended, so cannot run speculatively
+    #[error("speculative execution requires autonomy L3 or higher, got {0}")]
+    AutonomyTooLow(AutonomyLevel),
+
+    /// Speculation was forked from another layer
+    #[error("speculation was not forked from this layer")]
+    ForeignLayer,
+
+    /// The workspace moved on under a staged generation
+    #[error("{path} changed since the speculation started (revision {base:?}, now {current:?})")]
+    Conflict {
+        path: String,
+        base: Option<u64>,
+        current: Option<u64>,
+    },
+}
+
+/// Errors from the transformation pipeline
+#[derive(Debug, thiserror::Error)]
+pub enum PipelineError {
+    /// Middleware rejected the artifact by policy
+    #[error("vetoed by {middleware}: {reason}")]
+    Vetoed { middleware: String, reason: String },
+
+    /// Middleware name not found in registry
+    #[error("unknown middleware: {0}")]
+    UnknownMiddleware(String),
+
+    /// Middleware failed to run
+    #[error("middleware {middleware} failed: {message}")]
+    Failed { middleware: String, message: String },
+
+    /// Rewritten artifact violated its invariants
+    #[error("artifact error: {0}")]
+    Artifact(#[from] ArtifactError),
+}
+
+impl PipelineError {
+    /// Create veto error for middleware
+    pub fn veto(middleware: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self::Vetoed {
+            middleware: middleware.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+/// Errors while planning or staging a refactoring
+#[derive(Debug, thiserror::Error)]
+pub enum RefactorError {
+    /// Symbol is not in the index
+    #[error("symbol not found: {0}")]
+    SymbolNotFound(SymbolPath),
+
+    /// Replacement name is not a valid identifier
+    #[error("invalid identifier: '{0}'")]
+    InvalidName(String),
+
+    /// Renamed path is already taken by another symbol
+    #[error("target name already in use: {0}")]
+    NameTaken(SymbolPath),
+
+    /// Artifact touched by the plan was not supplied
+    #[error("artifact {0} required by refactoring was not provided")]
+    MissingArtifact(ContentHash),
+
+    /// Staging into the composition transaction failed
+    #[error("transaction error: {0}")]
+    Transaction(#[from] TransactionError),
+}
+
 /// Errors during cache operations
 #[derive(Debug, thiserror::Error)]
 pub enum CacheError {
@@ -176,6 +324,18 @@ pub enum ConstitutionalError {
 
     #[error("cache error: {0}")]
     Cache(#[from] CacheError),
+
+    #[error("pipeline error: {0}")]
+    Pipeline(#[from] PipelineError),
+
+    #[error("refactor error: {0}")]
+    Refactor(#[from] RefactorError),
+
+    #[error("egress error: {0}")]
+    Egress(#[from] EgressError),
+
+    #[error("speculation error: {0}")]
+    Speculation(#[from] SpeculationError),
 }
 
 /// Result type alias for constitutional operations
@@ -0,0 +1,234 @@
+//! Pull requests on a forge (feature `forge`)
+//!
+//! [`ForgeClient`] turns a [`ChangeSet`] into a pull request through the
+//! GitHub REST API (or a compatible GitHub Enterprise endpoint): it commits
+//! the changed files onto the tip of the base branch, points a new branch at
+//! the commit and opens a pull request whose body is the change set's
+//! description, explanation and audit metadata included.
+//!
+//! Nothing is merged: at L1/L2 autonomy a human reviews and merges.
+
+use crate::error::EgressError;
+use crate::patch::ChangeSet;
+use serde_json::{json, Value};
+
+/// Public GitHub API
+pub const GITHUB_API: &str = "https://api.github.com";
+
+/// Opened pull request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PullRequest {
+    /// Pull request number
+    pub number: u64,
+
+    /// Web URL for reviewers
+    pub url: String,
+
+    /// Commit holding the changes
+    pub commit: String,
+}
+
+/// Client opening pull requests on one repository
+#[derive(Debug, Clone)]
+pub struct ForgeClient {
+    api: String,
+    repo: String,
+    token: String,
+    http: reqwest::Client,
+}
+
+impl ForgeClient {
+    /// Client for `repo` (`owner/name`) on GitHub, authenticated by `token`
+    #[must_use]
+    pub fn github(repo: impl Into<String>, token: impl Into<String>) -> Self {
+        Self {
+            api: GITHUB_API.to_string(),
+            repo: repo.into(),
+            token: token.into(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Use another API base, e.g. GitHub Enterprise's `https://host/api/v3`
+    #[must_use]
+    pub fn with_api_base(mut self, api: impl Into<String>) -> Self {
+        self.api = api.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Commit `set` on top of `base` as branch `branch` and open a pull
+    /// request into `base`
+    ///
+    /// # Errors
+    /// - `EgressError::EmptyChangeSet` if no file changed
+    /// - `EgressError::Forge` if the forge rejects a request, e.g. because
+    ///   `branch` exists
+    /// - `EgressError::Http` on transport failures
+    pub async fn open_pull_request(
+        &self,
+        set: &ChangeSet,
+        base: &str,
+        branch: &str,
+    ) -> Result<PullRequest, EgressError> {
+        if set.is_empty() {
+            return Err(EgressError::EmptyChangeSet);
+        }
+
+        let head = self.get(&format!("git/ref/heads/{base}")).await?;
+        let parent = field(&head, &["object", "sha"])?;
+        let commit = self.get(&format!("git/commits/{parent}")).await?;
+        let base_tree = field(&commit, &["tree", "sha"])?;
+
+        let tree = self
+            .post(
+                "git/trees",
+                &json!({ "base_tree": base_tree, "tree": tree_entries(set) }),
+            )
+            .await?;
+        let commit = self
+            .post(
+                "git/commits",
+                &json!({
+                    "message": commit_message(set),
+                    "tree": field(&tree, &["sha"])?,
+                    "parents": [parent],
+                }),
+            )
+            .await?;
+        let commit = field(&commit, &["sha"])?;
+        self.post(
+            "git/refs",
+            &json!({ "ref": format!("refs/heads/{branch}"), "sha": commit }),
+        )
+        .await?;
+
+        let pull = self
+            .post(
+                "pulls",
+                &json!({
+                    "title": set.title,
+                    "head": branch,
+                    "base": base,
+                    "body": set.description(),
+                }),
+            )
+            .await?;
+        Ok(PullRequest {
+            number: pull["number"].as_u64().ok_or_else(|| missing("number"))?,
+            url: field(&pull, &["html_url"])?,
+            commit,
+        })
+    }
+
+    async fn get(&self, endpoint: &str) -> Result<Value, EgressError> {
+        self.send(self.http.get(self.url(endpoint))).await
+    }
+
+    async fn post(&self, endpoint: &str, body: &Value) -> Result<Value, EgressError> {
+        self.send(self.http.post(self.url(endpoint)).json(body))
+            .await
+    }
+
+    fn url(&self, endpoint: &str) -> String {
+        format!("{}/repos/{}/{endpoint}", self.api, self.repo)
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<Value, EgressError> {
+        let response = request
+            .bearer_auth(&self.token)
+            .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+            .header(reqwest::header::USER_AGENT, "coa-constitutional")
+            .send()
+            .await
+            .map_err(|e| EgressError::Http(e.to_string()))?;
+        let status = response.status();
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| EgressError::Http(e.to_string()))?;
+        if !status.is_success() {
+            return Err(EgressError::Forge {
+                status: status.as_u16(),
+                message: body["message"].as_str().unwrap_or_default().to_string(),
+            });
+        }
+        Ok(body)
+    }
+}
+
+/// Tree entries writing each changed file; deletions have a null blob
+fn tree_entries(set: &ChangeSet) -> Vec<Value> {
+    set.changes
+        .iter()
+        .map(|change| match &change.after {
+            Some(content) => json!({
+                "path": change.path,
+                "mode": "100644",
+                "type": "blob",
+                "content": content,
+            }),
+            None => json!({
+                "path": change.path,
+                "mode": "100644",
+                "type": "blob",
+                "sha": null,
+            }),
+        })
+        .collect()
+}
+
+fn commit_message(set: &ChangeSet) -> String {
+    let description = set.description();
+    if description.is_empty() {
+        set.title.clone()
+    } else {
+        format!("{}\n\n{description}", set.title)
+    }
+}
+
+fn field(value: &Value, path: &[&str]) -> Result<String, EgressError> {
+    path.iter()
+        .try_fold(value, |value, key| value.get(key))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| missing(&path.join(".")))
+}
+
+fn missing(field: &str) -> EgressError {
+    EgressError::Http(format!("forge response lacks '{field}'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patch::FileChange;
+
+    #[test]
+    fn tree_entries_write_and_delete_files() {
+        let set = ChangeSet::new("Update")
+            .with_change(FileChange::new("a.rs", None, Some("fn a() {}\n".into())))
+            .with_change(FileChange::new("b.rs", Some("fn b() {}\n".into()), None));
+
+        let entries = tree_entries(&set);
+        assert_eq!(entries[0]["content"], "fn a() {}\n");
+        assert_eq!(entries[1]["path"], "b.rs");
+        assert!(entries[1]["sha"].is_null());
+        assert_eq!(commit_message(&ChangeSet::new("Update")), "Update");
+    }
+
+    #[tokio::test]
+    async fn empty_change_sets_are_not_proposed() {
+        let client =
+            ForgeClient::github("owner/repo", "token").with_api_base("http://127.0.0.1:9/");
+        assert_eq!(
+            client.url("pulls"),
+            "http://127.0.0.1:9/repos/owner/repo/pulls"
+        );
+        assert!(matches!(
+            client
+                .open_pull_request(&ChangeSet::new("Nothing"), "main", "coa/empty")
+                .await,
+            Err(EgressError::EmptyChangeSet)
+        ));
+    }
+}
@@ -0,0 +1,348 @@
+//! Artifact garbage collection
+//!
+//! Long sessions keep caching parsed and composed artifacts and indexing
+//! their symbols. [`ArtifactGc`] marks what is still reachable and sweeps
+//! the rest out of an [`ArtifactCache`] and its [`SymbolRefIndex`].
+//!
+//! Roots are:
+//! - current workspace state: explicit roots and the newest generations of
+//!   every cached path
+//! - in-flight compositions: explicit roots, plus every entry younger than
+//!   the grace period
+//! - checkpoints: content hashes mentioned in recorded node outputs
+//! - recent journal entries: base and result hashes within the retention
+//!   window
+//!
+//! A cache entry is reachable if its checksum or its artifact hash is a
+//! root. Symbols survive while their parent artifact is still cached.
+
+use crate::cache::ArtifactCache;
+use coa_artifact::ContentHash;
+use coa_kernel::executor::CheckpointStore;
+use coa_kernel::replay::CompositionJournal;
+use coa_kernel::types::Timestamp;
+use coa_symbol::SymbolRefIndex;
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Retention windows and mode of a collection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GcConfig {
+    /// Report what would be pruned without pruning it
+    pub dry_run: bool,
+    /// Entries cached more recently than this are kept
+    pub grace_period: Duration,
+    /// Journal entries younger than this are roots
+    pub journal_retention: Duration,
+    /// Newest generations of each cached path that are roots
+    pub keep_generations: usize,
+}
+
+impl Default for GcConfig {
+    fn default() -> Self {
+        Self {
+            dry_run: false,
+            grace_period: Duration::from_secs(60),
+            journal_retention: Duration::from_secs(3600),
+            keep_generations: 1,
+        }
+    }
+}
+
+impl GcConfig {
+    /// Create config with default windows
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only report what would be pruned
+    #[inline]
+    #[must_use]
+    pub fn dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+
+    /// Set grace period for recently cached entries
+    #[inline]
+    #[must_use]
+    pub fn with_grace_period(mut self, grace_period: Duration) -> Self {
+        self.grace_period = grace_period;
+        self
+    }
+
+    /// Set retention window for journal entries
+    #[inline]
+    #[must_use]
+    pub fn with_journal_retention(mut self, retention: Duration) -> Self {
+        self.journal_retention = retention;
+        self
+    }
+
+    /// Set number of generations kept per path
+    #[inline]
+    #[must_use]
+    pub fn with_keep_generations(mut self, keep: usize) -> Self {
+        self.keep_generations = keep;
+        self
+    }
+}
+
+/// Outcome of a collection
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GcReport {
+    /// Whether anything was actually removed
+    pub dry_run: bool,
+    /// Cache entries still reachable (or within the grace period)
+    pub retained: usize,
+    /// Checksums of unreachable cache entries
+    pub pruned_artifacts: Vec<ContentHash>,
+    /// Estimated bytes held by the unreachable entries
+    pub pruned_bytes: u64,
+    /// Unreachable symbol index entries
+    pub pruned_symbols: usize,
+}
+
+/// Reachability-based collector
+#[derive(Debug, Clone, Default)]
+pub struct ArtifactGc {
+    config: GcConfig,
+    roots: HashSet<ContentHash>,
+}
+
+impl ArtifactGc {
+    /// Create collector with `config` and no roots
+    #[inline]
+    #[must_use]
+    pub fn new(config: GcConfig) -> Self {
+        Self {
+            config,
+            roots: HashSet::new(),
+        }
+    }
+
+    /// Add root (e.g. current workspace file, in-flight composition base)
+    #[inline]
+    #[must_use]
+    pub fn with_root(mut self, hash: ContentHash) -> Self {
+        self.roots.insert(hash);
+        self
+    }
+
+    /// Add roots
+    #[must_use]
+    pub fn with_roots(mut self, hashes: impl IntoIterator<Item = ContentHash>) -> Self {
+        self.roots.extend(hashes);
+        self
+    }
+
+    /// Add base and result hashes of journal entries recorded within the
+    /// retention window before `now`
+    #[must_use]
+    pub fn with_journal(mut self, journal: &CompositionJournal, now: Timestamp) -> Self {
+        let cutoff = now.saturating_sub(self.config.journal_retention.as_secs());
+        for entry in journal.entries().iter().filter(|e| e.timestamp >= cutoff) {
+            self.roots.extend(
+                [&entry.base_hash, &entry.result_hash]
+                    .into_iter()
+                    .filter_map(|hash| ContentHash::from_str(hash).ok()),
+            );
+        }
+        self
+    }
+
+    /// Add content hashes mentioned in recorded node outputs
+    #[must_use]
+    pub fn with_checkpoints(mut self, store: &CheckpointStore) -> Self {
+        for checkpoint in store.checkpoints() {
+            if let Some(output) = &checkpoint.output {
+                collect_hashes(&output.value, &mut self.roots);
+            }
+        }
+        self
+    }
+
+    /// Configuration in use
+    #[inline]
+    #[must_use]
+    pub fn config(&self) -> &GcConfig {
+        &self.config
+    }
+
+    /// Mark from the roots and sweep `cache` and, if given, `index`
+    pub async fn collect(&self, cache: &ArtifactCache, index: Option<&SymbolRefIndex>) -> GcReport {
+        let mut roots = self.roots.clone();
+        roots.extend(cache.newest_generations(self.config.keep_generations));
+
+        let mut report = GcReport {
+            dry_run: self.config.dry_run,
+            ..GcReport::default()
+        };
+        let mut live_artifacts = HashSet::new();
+        let mut pruned = HashSet::new();
+        for entry in cache.entry_infos() {
+            let reachable = roots.contains(&entry.key)
+                || roots.contains(&entry.artifact_hash)
+                || entry.inserted.elapsed() < self.config.grace_period;
+            if reachable {
+                report.retained += 1;
+                live_artifacts.insert(entry.artifact_hash);
+            } else if pruned.insert(entry.key) {
+                report.pruned_artifacts.push(entry.key);
+                report.pruned_bytes += entry.bytes as u64;
+            }
+        }
+
+        if let Some(index) = index {
+            let dead_parents: HashSet<ContentHash> = index
+                .entries()
+                .iter()
+                .map(|entry| *entry.symbol.parent_hash())
+                .filter(|parent| !live_artifacts.contains(parent) && !roots.contains(parent))
+                .collect();
+            for parent in &dead_parents {
+                report.pruned_symbols += if self.config.dry_run {
+                    index.get_by_parent(parent).len()
+                } else {
+                    index.remove_by_parent(parent)
+                };
+            }
+        }
+
+        if !self.config.dry_run {
+            for hash in &report.pruned_artifacts {
+                cache.invalidate(hash).await;
+            }
+            cache.forget_generations(&pruned);
+        }
+        tracing::debug!(
+            dry_run = self.config.dry_run,
+            retained = report.retained,
+            pruned = report.pruned_artifacts.len(),
+            pruned_symbols = report.pruned_symbols,
+            "artifact gc"
+        );
+        report
+    }
+}
+
+/// Add every string in `value` that parses as a content hash
+fn collect_hashes(value: &serde_json::Value, out: &mut HashSet<ContentHash>) {
+    match value {
+        serde_json::Value::String(s) => {
+            if let Ok(hash) = ContentHash::from_str(s) {
+                out.insert(hash);
+            }
+        }
+        serde_json::Value::Array(items) => items.iter().for_each(|v| collect_hashes(v, out)),
+        serde_json::Value::Object(map) => map.values().for_each(|v| collect_hashes(v, out)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::TypedCacheKey;
+    use crate::parsers::{ArtifactParser, JsonArtifact, JsonParser};
+    use coa_kernel::executor::{CacheKey, Checkpoint, NodePayload};
+    use coa_kernel::replay::JournalEntry;
+    use coa_kernel::types::ResourceCaps;
+    use coa_symbol::{SymbolMetadata, SymbolRef};
+
+    async fn cached(cache: &ArtifactCache, json: &str) -> ContentHash {
+        let key = ContentHash::compute(json.as_bytes());
+        cache.insert(key, JsonParser.parse(json).unwrap()).await;
+        key
+    }
+
+    #[tokio::test]
+    async fn unreachable_artifacts_and_symbols_are_pruned() {
+        let cache = ArtifactCache::default();
+        let index = SymbolRefIndex::new();
+        let root = cached(&cache, r#"{"root": 1}"#).await;
+        let journaled = cached(&cache, r#"{"journaled": 1}"#).await;
+        let checkpointed = cached(&cache, r#"{"checkpointed": 1}"#).await;
+        let stale = cached(&cache, r#"{"stale": 1}"#).await;
+        let stale_artifact = *JsonParser.parse(r#"{"stale": 1}"#).unwrap().hash();
+        index
+            .insert(SymbolRef::new(vec!["stale".to_string()], stale_artifact), SymbolMetadata::default())
+            .unwrap();
+
+        let journal = CompositionJournal::new();
+        journal.record(JournalEntry {
+            timestamp: 1_000,
+            artifact: "config".to_string(),
+            node_id: None,
+            base_hash: journaled.to_string(),
+            result_hash: journaled.to_string(),
+            delta_count: 1,
+        });
+        let store = CheckpointStore::new();
+        store.record(
+            CacheKey([1; 32]),
+            Checkpoint {
+                output: Some(NodePayload::new("artifact", serde_json::json!({"hash": checkpointed.to_string()}))),
+                resource_consumed: ResourceCaps {
+                    cpu_time_ms: 0,
+                    memory_bytes: 0,
+                    token_limit: 0,
+                    iteration_cap: 0,
+                },
+                execution_time_ms: 0,
+            },
+        );
+
+        let gc = ArtifactGc::new(GcConfig::new().with_grace_period(Duration::ZERO))
+            .with_root(root)
+            .with_journal(&journal, 1_500)
+            .with_checkpoints(&store);
+
+        let dry = ArtifactGc::new(gc.config().dry_run()).with_roots(gc.roots.iter().copied());
+        let report = dry.collect(&cache, Some(&index)).await;
+        assert!(report.dry_run);
+        assert_eq!(report.pruned_artifacts, vec![stale]);
+        assert_eq!(report.pruned_symbols, 1);
+        assert!(cache.contains(&stale).await);
+        assert_eq!(index.len(), 1);
+
+        let report = gc.collect(&cache, Some(&index)).await;
+        assert_eq!(report.retained, 3);
+        assert_eq!(report.pruned_artifacts, vec![stale]);
+        assert!(!cache.contains(&stale).await);
+        assert!(index.is_empty());
+
+        // Outside the retention window the journal no longer roots anything
+        let expired = ArtifactGc::new(GcConfig::new().with_grace_period(Duration::ZERO))
+            .with_journal(&journal, 1_000 + 7_200)
+            .collect(&cache, None)
+            .await;
+        assert!(expired.pruned_artifacts.contains(&journaled));
+    }
+
+    #[tokio::test]
+    async fn newest_generations_and_recent_entries_are_kept() {
+        let cache = ArtifactCache::default();
+        let mut keys = Vec::new();
+        for revision in 0..3 {
+            let json = format!(r#"{{"revision": {revision}}}"#);
+            let key = ContentHash::compute(json.as_bytes());
+            let artifact = JsonParser.parse(&json).unwrap();
+            cache
+                .insert_generation(TypedCacheKey::for_generation::<JsonArtifact>(key, "config.json", revision), artifact)
+                .await;
+            keys.push(key);
+        }
+
+        let recent = ArtifactGc::default().collect(&cache, None).await;
+        assert!(recent.pruned_artifacts.is_empty());
+
+        let config = GcConfig::new().with_grace_period(Duration::ZERO).with_keep_generations(2);
+        let report = ArtifactGc::new(config).collect(&cache, None).await;
+        assert_eq!(report.pruned_artifacts, vec![keys[0]]);
+        assert_eq!(cache.generations::<JsonArtifact>("config.json").len(), 2);
+    }
+}
@@ -0,0 +1,343 @@
+//! Golden-file snapshots of parser output
+//!
+//! A [`GoldenSuite`] parses every file of a corpus directory and compares a
+//! rendered [`GoldenSnapshot`] — content hash, symbol table, Merkle root of
+//! the symbol hashes and the hash after a serialize/reparse round trip —
+//! against a checked-in `.golden` file. Parser changes that would alter
+//! hashing (and so invalidate cached artifacts) show up as mismatches.
+//!
+//! Snapshots are always taken with [`HashAlgorithm::Blake3`] so they do not
+//! depend on the installed algorithm. After an intended change, rewrite the
+//! goldens with [`GoldenSuite::bless`]; the workspace test does so when run
+//! with `BLESS_GOLDENS=1`:
+//!
+//! ```text
+//! BLESS_GOLDENS=1 cargo test -p coa-constitutional --test golden
+//! ```
+
+use crate::error::ParseError;
+use crate::parsers::{
+    default_parsers, CodeArtifact, ErasedArtifact, JsonArtifact, MarkdownArtifact, ParserRegistry,
+    SqlArtifact, YamlArtifact,
+};
+use crate::serializers::{ArtifactSerializer, CodeSerializer};
+use coa_artifact::{ArtifactMerkleTree, ContentHash, HashAlgorithm};
+use std::fmt::{self, Write as _};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Extension of snapshot files
+pub const GOLDEN_EXTENSION: &str = "golden";
+
+/// Hash-relevant parser output for one file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoldenSnapshot {
+    /// Artifact type identifier
+    pub type_id: &'static str,
+    /// Artifact content hash
+    pub hash: ContentHash,
+    /// Symbols with their kind and content hash, in parser order
+    pub symbols: Vec<String>,
+    /// Merkle root over the symbol lines
+    pub merkle_root: ContentHash,
+    /// Hash after serializing and reparsing (`None` if no serializer)
+    pub round_trip: Option<ContentHash>,
+}
+
+impl GoldenSnapshot {
+    /// Parse `content` as the file at `path` and snapshot the result
+    ///
+    /// # Errors
+    /// Fails if no parser handles `path` or parsing fails.
+    pub fn capture(
+        registry: &ParserRegistry,
+        path: &Path,
+        content: &str,
+    ) -> Result<Self, ParseError> {
+        HashAlgorithm::Blake3.scope(|| {
+            let parser = registry.find_for_path(path).ok_or_else(|| {
+                ParseError::NoParserForExtension(path.display().to_string())
+            })?;
+            let erased = parser.parse_erased(content)?;
+
+            let symbols: Vec<String> = erased
+                .symbols
+                .iter()
+                .zip(&erased.symbol_metadata)
+                .map(|(symbol, metadata)| {
+                    let hash = metadata
+                        .content_hash
+                        .map_or_else(|| "-".to_string(), |h| h.to_string());
+                    format!("{symbol} {:?} {hash}", metadata.kind)
+                })
+                .collect();
+            let leaves: Vec<ContentHash> = symbols
+                .iter()
+                .map(|line| ContentHash::compute(line.as_bytes()))
+                .collect();
+
+            let round_trip = match serialize(&erased) {
+                Some(text) => Some(parser.parse_erased(&text)?.hash),
+                None => None,
+            };
+
+            Ok(Self {
+                type_id: erased.type_id,
+                hash: erased.hash,
+                merkle_root: ArtifactMerkleTree::from_leaves(&leaves).root(),
+                symbols,
+                round_trip,
+            })
+        })
+    }
+
+    /// Check if serializing and reparsing preserves the hash
+    #[inline]
+    #[must_use]
+    pub fn round_trip_stable(&self) -> bool {
+        self.round_trip.iter().all(|hash| *hash == self.hash)
+    }
+}
+
+impl fmt::Display for GoldenSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "type: {}", self.type_id)?;
+        writeln!(f, "hash: {}", self.hash)?;
+        writeln!(f, "merkle_root: {}", self.merkle_root)?;
+        match self.round_trip {
+            Some(hash) => writeln!(f, "round_trip: {hash}")?,
+            None => writeln!(f, "round_trip: -")?,
+        }
+        writeln!(f, "symbols: {}", self.symbols.len())?;
+        for symbol in &self.symbols {
+            writeln!(f, "  {symbol}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Render a parsed artifact back to file content
+fn serialize(erased: &ErasedArtifact) -> Option<String> {
+    if let Some(code) = erased.downcast::<CodeArtifact>() {
+        return CodeSerializer::new(code.content().language)
+            .serialize(code)
+            .ok();
+    }
+    if let Some(json) = erased.downcast::<JsonArtifact>() {
+        return serde_json::to_string_pretty(&json.content().root).ok();
+    }
+    if let Some(yaml) = erased.downcast::<YamlArtifact>() {
+        let documents = &yaml.content().documents;
+        let mut text = String::new();
+        for (i, document) in documents.iter().enumerate() {
+            if i > 0 {
+                text.push_str("---\n");
+            }
+            text.push_str(&serde_yaml::to_string(document).ok()?);
+        }
+        return Some(text);
+    }
+    if let Some(markdown) = erased.downcast::<MarkdownArtifact>() {
+        return Some(markdown.content().source.clone());
+    }
+    if let Some(sql) = erased.downcast::<SqlArtifact>() {
+        return Some(sql.content().to_sql());
+    }
+    None
+}
+
+/// Corpus file whose snapshot differs from its golden
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoldenMismatch {
+    /// Corpus file, relative to the corpus directory
+    pub file: PathBuf,
+    /// Checked-in snapshot (`None` if missing)
+    pub expected: Option<String>,
+    /// Snapshot of the current parser output
+    pub actual: String,
+}
+
+impl fmt::Display for GoldenMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(expected) = &self.expected else {
+            return write!(f, "{}: no golden file", self.file.display());
+        };
+        writeln!(f, "{}:", self.file.display())?;
+        let expected: Vec<&str> = expected.lines().collect();
+        let actual: Vec<&str> = self.actual.lines().collect();
+        for i in 0..expected.len().max(actual.len()) {
+            match (expected.get(i), actual.get(i)) {
+                (Some(e), Some(a)) if e == a => {}
+                (e, a) => {
+                    if let Some(e) = e {
+                        writeln!(f, "  - {e}")?;
+                    }
+                    if let Some(a) = a {
+                        writeln!(f, "  + {a}")?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Corpus of files with their golden snapshots
+///
+/// Expects `root/corpus/` holding the files and `root/snapshots/` holding
+/// one `<file>.golden` per corpus file, mirroring the directory layout.
+#[derive(Debug)]
+pub struct GoldenSuite {
+    root: PathBuf,
+    registry: ParserRegistry,
+}
+
+impl GoldenSuite {
+    /// Suite rooted at `root`, parsed with the default parsers
+    #[inline]
+    #[must_use]
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            registry: default_parsers(),
+        }
+    }
+
+    /// Parse with `registry` instead of the default parsers
+    #[inline]
+    #[must_use]
+    pub fn with_registry(mut self, registry: ParserRegistry) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// Snapshot of every corpus file, in path order
+    ///
+    /// # Errors
+    /// Fails on I/O errors, unparseable files, or an unstable round trip.
+    pub fn snapshots(&self) -> io::Result<Vec<(PathBuf, String)>> {
+        let corpus = self.root.join("corpus");
+        let mut files = Vec::new();
+        collect_files(&corpus, &mut files)?;
+        files.sort();
+
+        let mut snapshots = Vec::with_capacity(files.len());
+        for path in files {
+            let content = std::fs::read_to_string(&path)?;
+            let snapshot = GoldenSnapshot::capture(&self.registry, &path, &content)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            let relative = path.strip_prefix(&corpus).unwrap_or(&path).to_path_buf();
+            if !snapshot.round_trip_stable() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: round trip changes the hash", relative.display()),
+                ));
+            }
+            snapshots.push((relative, snapshot.to_string()));
+        }
+        Ok(snapshots)
+    }
+
+    /// Corpus files whose snapshot differs from its golden
+    ///
+    /// # Errors
+    /// See [`snapshots`](Self::snapshots).
+    pub fn check(&self) -> io::Result<Vec<GoldenMismatch>> {
+        let mut mismatches = Vec::new();
+        for (file, actual) in self.snapshots()? {
+            let expected = std::fs::read_to_string(self.golden_path(&file)).ok();
+            if expected.as_deref() != Some(actual.as_str()) {
+                mismatches.push(GoldenMismatch {
+                    file,
+                    expected,
+                    actual,
+                });
+            }
+        }
+        Ok(mismatches)
+    }
+
+    /// Rewrite every golden from the current parser output
+    ///
+    /// Returns the number of goldens written.
+    ///
+    /// # Errors
+    /// See [`snapshots`](Self::snapshots); also fails if a golden cannot be
+    /// written.
+    pub fn bless(&self) -> io::Result<usize> {
+        let snapshots = self.snapshots()?;
+        for (file, snapshot) in &snapshots {
+            let golden = self.golden_path(file);
+            if let Some(parent) = golden.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(golden, snapshot)?;
+        }
+        Ok(snapshots.len())
+    }
+
+    fn golden_path(&self, file: &Path) -> PathBuf {
+        let mut name = file.as_os_str().to_owned();
+        name.push(".");
+        name.push(GOLDEN_EXTENSION);
+        self.root.join("snapshots").join(name)
+    }
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Summarize mismatches for a test failure message
+#[must_use]
+pub fn describe_mismatches(mismatches: &[GoldenMismatch]) -> String {
+    let mut out = String::new();
+    for mismatch in mismatches {
+        let _ = writeln!(out, "{mismatch}");
+    }
+    let _ = writeln!(
+        out,
+        "{} golden file(s) out of date; rerun with BLESS_GOLDENS=1 if the change is intended",
+        mismatches.len()
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshots_are_pinned_to_blake3_and_bless_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("corpus/src")).unwrap();
+        std::fs::write(dir.path().join("corpus/src/lib.rs"), "pub fn run() {}\n").unwrap();
+        std::fs::write(dir.path().join("corpus/config.json"), "{\"a\": [1, 2]}").unwrap();
+        let suite = GoldenSuite::new(dir.path());
+
+        let missing = suite.check().unwrap();
+        assert_eq!(missing.len(), 2);
+        assert!(missing.iter().all(|m| m.expected.is_none()));
+
+        assert_eq!(suite.bless().unwrap(), 2);
+        assert!(dir.path().join("snapshots/src/lib.rs.golden").exists());
+        assert!(suite.check().unwrap().is_empty());
+
+        // Snapshots ignore the algorithm in scope
+        let pinned = HashAlgorithm::Sha256.scope(|| suite.snapshots().unwrap());
+        assert_eq!(pinned, suite.snapshots().unwrap());
+
+        std::fs::write(dir.path().join("corpus/src/lib.rs"), "pub fn run2() {}\n").unwrap();
+        let changed = suite.check().unwrap();
+        assert_eq!(changed.len(), 1);
+        assert!(changed[0].to_string().contains("+   run2"));
+    }
+}
@@ -0,0 +1,567 @@
+//! Workspace-wide ingress
+//!
+//! [`ConstitutionalLayer::ingress_workspace`](crate::ConstitutionalLayer::ingress_workspace)
+//! walks a directory tree, parses every matching file in parallel and
+//! summarises the run in an [`IngressReport`]. Parsing is CPU-bound, so the
+//! walk and parse run on the rayon pool inside a blocking task; only cache
+//! and index population happen back on the async side.
+//!
+//! An [`IngressFilter`] keeps vendored and build output out of the cache and
+//! symbol index: `.gitignore`/`.coaignore` files found during the walk,
+//! extra ignore patterns, a size cap and binary detection. Every exclusion
+//! is attributed to the rule that caused it.
+
+use crate::error::ParseError;
+use crate::parsers::{ErasedArtifact, ParserRegistry};
+use crate::secrets::{SecretFinding, SecretScanner};
+use coa_artifact::{ContentHash, SymbolPath};
+use rayon::prelude::*;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+/// Bytes inspected when sniffing for binary content
+const BINARY_SNIFF_LEN: usize = 8000;
+
+/// Exclusion label for files above the size cap
+pub const SIZE_RULE: &str = "max-file-size";
+
+/// Exclusion label for binary files
+pub const BINARY_RULE: &str = "binary";
+
+/// Shell-style file pattern relative to the workspace root
+///
+/// - `*` matches within one path segment, `?` matches one character
+/// - `**` matches any number of segments (including none)
+/// - Patterns without `/` match the file name at any depth
+#[derive(Debug, Clone)]
+pub struct Glob {
+    pattern: String,
+    regex: Regex,
+}
+
+impl Glob {
+    /// Compile pattern
+    ///
+    /// # Errors
+    /// Returns `ParseError::ValidationError` if the pattern is empty
+    pub fn new(pattern: &str) -> Result<Self, ParseError> {
+        let trimmed = pattern.trim().trim_start_matches("./");
+        if trimmed.is_empty() {
+            return Err(ParseError::ValidationError("empty glob pattern".to_string()));
+        }
+
+        let anchored = if trimmed.contains('/') {
+            trimmed.trim_start_matches('/').to_string()
+        } else {
+            format!("**/{trimmed}")
+        };
+
+        let mut regex = String::from("^");
+        let mut chars = anchored.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '*' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    if chars.peek() == Some(&'/') {
+                        chars.next();
+                        regex.push_str("(?:.*/)?");
+                    } else {
+                        regex.push_str(".*");
+                    }
+                }
+                '*' => regex.push_str("[^/]*"),
+                '?' => regex.push_str("[^/]"),
+                c => regex.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        regex.push('$');
+
+        let regex = Regex::new(&regex)
+            .map_err(|e| ParseError::ValidationError(format!("invalid glob '{pattern}': {e}")))?;
+        Ok(Self {
+            pattern: pattern.to_string(),
+            regex,
+        })
+    }
+
+    /// Original pattern text
+    #[inline]
+    #[must_use]
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Check a `/`-separated path relative to the workspace root
+    #[inline]
+    #[must_use]
+    pub fn matches(&self, relative: &str) -> bool {
+        self.regex.is_match(relative)
+    }
+}
+
+/// One line of an ignore file, in `.gitignore` syntax
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    /// Directory the rule is relative to (`/`-separated, empty for root)
+    base: String,
+    glob: Glob,
+    negated: bool,
+    dir_only: bool,
+    /// Statistics key (`<source>:<pattern>`)
+    label: String,
+}
+
+impl IgnoreRule {
+    /// Parse one ignore line; `None` for blanks and comments
+    fn parse(line: &str, base: &str, source: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, body) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let dir_only = body.ends_with('/');
+        let glob = Glob::new(body.trim_end_matches('/')).ok()?;
+        Some(Self {
+            base: base.to_string(),
+            glob,
+            negated,
+            dir_only,
+            label: format!("{source}:{line}"),
+        })
+    }
+
+    /// Check a root-relative path against this rule
+    fn matches(&self, relative: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        let local = if self.base.is_empty() {
+            relative
+        } else {
+            match relative
+                .strip_prefix(self.base.as_str())
+                .and_then(|rest| rest.strip_prefix('/'))
+            {
+                Some(local) => local,
+                None => return false,
+            }
+        };
+        self.glob.matches(local)
+    }
+}
+
+/// What workspace ingress leaves out
+///
+/// Later rules win, as in `.gitignore`: patterns from a nested ignore file
+/// can re-include (`!pattern`) what a parent excluded, except inside a
+/// directory that is already excluded (it is never entered).
+#[derive(Debug, Clone)]
+pub struct IngressFilter {
+    ignore_files: Vec<String>,
+    rules: Vec<IgnoreRule>,
+    max_file_size: Option<u64>,
+    skip_binary: bool,
+}
+
+impl Default for IngressFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IngressFilter {
+    /// Honour `.gitignore` and `.coaignore`, skip `.git/` and binary files
+    #[must_use]
+    pub fn new() -> Self {
+        Self::none()
+            .with_ignore_file(".gitignore")
+            .with_ignore_file(".coaignore")
+            .with_ignore(".git/")
+            .with_binary_detection(true)
+    }
+
+    /// Filter that excludes nothing
+    #[inline]
+    #[must_use]
+    pub fn none() -> Self {
+        Self {
+            ignore_files: Vec::new(),
+            rules: Vec::new(),
+            max_file_size: None,
+            skip_binary: false,
+        }
+    }
+
+    /// Read ignore rules from files with this name in every directory
+    #[must_use]
+    pub fn with_ignore_file(mut self, name: impl Into<String>) -> Self {
+        self.ignore_files.push(name.into());
+        self
+    }
+
+    /// Add a root-level ignore pattern in `.gitignore` syntax
+    ///
+    /// Blank or comment lines are ignored.
+    #[must_use]
+    pub fn with_ignore(mut self, pattern: &str) -> Self {
+        self.rules.extend(IgnoreRule::parse(pattern, "", "filter"));
+        self
+    }
+
+    /// Exclude files larger than `bytes` (defaults to the layer's parse limit)
+    #[inline]
+    #[must_use]
+    pub fn with_max_file_size(mut self, bytes: u64) -> Self {
+        self.max_file_size = Some(bytes);
+        self
+    }
+
+    /// Exclude files whose first bytes contain a NUL
+    #[inline]
+    #[must_use]
+    pub fn with_binary_detection(mut self, enabled: bool) -> Self {
+        self.skip_binary = enabled;
+        self
+    }
+
+    /// Configured size cap, if any
+    #[inline]
+    #[must_use]
+    pub fn max_file_size(&self) -> Option<u64> {
+        self.max_file_size
+    }
+
+    /// Rules from ignore files in `dir`
+    fn load_rules(&self, root: &Path, dir: &Path) -> Vec<IgnoreRule> {
+        let base = slash_path(dir.strip_prefix(root).unwrap_or(dir));
+        let mut rules = Vec::new();
+        for name in &self.ignore_files {
+            let Ok(text) = std::fs::read_to_string(dir.join(name)) else {
+                continue;
+            };
+            let source = if base.is_empty() {
+                name.clone()
+            } else {
+                format!("{base}/{name}")
+            };
+            rules.extend(text.lines().filter_map(|line| IgnoreRule::parse(line, &base, &source)));
+        }
+        rules
+    }
+}
+
+/// Label of the last rule deciding `relative`, if it ends up excluded
+fn excluded_by<'r>(rules: &'r [IgnoreRule], relative: &str, is_dir: bool) -> Option<&'r str> {
+    rules
+        .iter()
+        .rev()
+        .find(|rule| rule.matches(relative, is_dir))
+        .filter(|rule| !rule.negated)
+        .map(|rule| rule.label.as_str())
+}
+
+/// File that could not be ingested
+#[derive(Debug)]
+pub struct IngressFailure {
+    /// Path relative to the workspace root
+    pub path: PathBuf,
+    /// Why the file was rejected
+    pub error: ParseError,
+}
+
+/// Summary of a workspace ingress run
+#[derive(Debug, Default)]
+pub struct IngressReport {
+    /// Files parsed and cached (relative to the root)
+    pub parsed: Vec<PathBuf>,
+    /// Matching files with no parser, or above the size limit
+    pub skipped: Vec<PathBuf>,
+    /// Files rejected by a parser or the secret scanner
+    pub failed: Vec<IngressFailure>,
+    /// Parsed files with secrets the scanner flagged but did not block
+    pub flagged: BTreeMap<PathBuf, Vec<SecretFinding>>,
+    /// Entries left out per filter rule (a pruned directory counts once)
+    pub excluded: BTreeMap<String, usize>,
+    /// Symbols extracted across all parsed files
+    pub symbols: usize,
+    /// Wall-clock time for the whole run
+    pub duration: Duration,
+}
+
+impl IngressReport {
+    /// Number of files considered (parsed, skipped or failed)
+    #[inline]
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.parsed.len() + self.skipped.len() + self.failed.len()
+    }
+
+    /// Number of files and directories excluded by filters
+    #[inline]
+    #[must_use]
+    pub fn excluded_total(&self) -> usize {
+        self.excluded.values().sum()
+    }
+
+    /// Check if no file failed
+    #[inline]
+    #[must_use]
+    pub fn is_clean(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Result of ingesting one file
+#[derive(Debug)]
+pub(crate) enum FileOutcome {
+    Parsed {
+        path: PathBuf,
+        checksum: ContentHash,
+        artifact: ErasedArtifact,
+        secrets: Vec<SecretFinding>,
+    },
+    Skipped(PathBuf),
+    Excluded(&'static str),
+    Failed(IngressFailure),
+}
+
+/// Parse-side settings shared with the worker pool
+#[derive(Debug)]
+pub(crate) struct IngressJob {
+    pub(crate) parsers: std::sync::Arc<ParserRegistry>,
+    pub(crate) secret_scanner: Option<SecretScanner>,
+    pub(crate) filter: IngressFilter,
+    pub(crate) max_file_size: u64,
+}
+
+impl IngressJob {
+    /// Walk `root` and parse matching files in parallel
+    ///
+    /// Also returns the entries ignore rules excluded during the walk.
+    pub(crate) fn run(
+        &self,
+        root: &Path,
+        globs: &[Glob],
+    ) -> Result<(Vec<FileOutcome>, BTreeMap<String, usize>), ParseError> {
+        let mut walk = Walk {
+            root,
+            globs,
+            filter: &self.filter,
+            files: Vec::new(),
+            excluded: BTreeMap::new(),
+        };
+        walk.dir(root, &[])?;
+        let outcomes = walk
+            .files
+            .par_iter()
+            .map(|relative| self.ingest(root, relative))
+            .collect();
+        Ok((outcomes, walk.excluded))
+    }
+
+    fn ingest(&self, root: &Path, relative: &Path) -> FileOutcome {
+        let path = root.join(relative);
+        let Some(parser) = self.parsers.find_for_path(&path) else {
+            return FileOutcome::Skipped(relative.to_path_buf());
+        };
+        let fail = |error| {
+            FileOutcome::Failed(IngressFailure {
+                path: relative.to_path_buf(),
+                error,
+            })
+        };
+
+        match std::fs::metadata(&path) {
+            Ok(meta) if meta.len() > self.max_file_size => return FileOutcome::Excluded(SIZE_RULE),
+            Ok(_) => {}
+            Err(e) => return fail(ParseError::io_error(&path, e)),
+        }
+        let bytes = match std::fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) => return fail(ParseError::io_error(&path, e)),
+        };
+        if self.filter.skip_binary && bytes[..bytes.len().min(BINARY_SNIFF_LEN)].contains(&0) {
+            return FileOutcome::Excluded(BINARY_RULE);
+        }
+        let content = match String::from_utf8(bytes) {
+            Ok(content) => content,
+            Err(_) => {
+                return fail(ParseError::ValidationError(format!(
+                    "{} is not valid UTF-8",
+                    path.display()
+                )))
+            }
+        };
+        let secrets = match &self.secret_scanner {
+            Some(scanner) => match check_secrets(scanner, &path, &content) {
+                Ok(findings) => findings,
+                Err(e) => return fail(e),
+            },
+            None => Vec::new(),
+        };
+
+        match parser.parse_erased(&content) {
+            Ok(artifact) => FileOutcome::Parsed {
+                path: relative.to_path_buf(),
+                checksum: ContentHash::compute(content.as_bytes()),
+                artifact,
+                secrets,
+            },
+            Err(e) => fail(e),
+        }
+    }
+}
+
+/// Reject content the scanner blocks, else return what it flagged
+pub(crate) fn check_secrets(
+    scanner: &SecretScanner,
+    path: &Path,
+    content: &str,
+) -> Result<Vec<SecretFinding>, ParseError> {
+    let report = scanner.scan_text(content);
+    if scanner.blocks(&report) {
+        return Err(ParseError::SecretsDetected {
+            path: path.to_path_buf(),
+            findings: report.findings,
+        });
+    }
+    Ok(report.findings)
+}
+
+/// `/`-separated form of a relative path, as globs see it
+pub(crate) fn slash_path(relative: &Path) -> String {
+    relative
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(s) => s.to_str(),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Index key for `symbol` inside the file at `relative`
+///
+/// File path segments come first, so symbols from different files never
+/// collide (`src/lib.rs` + `run` → `["src", "lib.rs", "run"]`).
+pub(crate) fn workspace_symbol(relative: &Path, symbol: &SymbolPath) -> Vec<String> {
+    file_prefix(relative)
+        .into_iter()
+        .chain(symbol.segments().iter().cloned())
+        .collect()
+}
+
+/// Index prefix owned by the file at `relative`
+pub(crate) fn file_prefix(relative: &Path) -> Vec<String> {
+    slash_path(relative).split('/').map(str::to_string).collect()
+}
+
+/// Directory walk state
+struct Walk<'a> {
+    root: &'a Path,
+    globs: &'a [Glob],
+    filter: &'a IngressFilter,
+    files: Vec<PathBuf>,
+    excluded: BTreeMap<String, usize>,
+}
+
+impl Walk<'_> {
+    /// Collect files under `dir` matching any glob (all files if none given)
+    ///
+    /// `inherited` holds rules from ancestor directories. Symlinks are not
+    /// followed, so the walk cannot escape the root or loop.
+    fn dir(&mut self, dir: &Path, inherited: &[IgnoreRule]) -> Result<(), ParseError> {
+        let mut rules = inherited.to_vec();
+        if dir == self.root {
+            rules.extend(self.filter.rules.iter().cloned());
+        }
+        rules.extend(self.filter.load_rules(self.root, dir));
+
+        let mut entries = std::fs::read_dir(dir)
+            .map_err(|e| ParseError::io_error(dir, e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| ParseError::io_error(dir, e))?;
+        entries.sort_by_key(std::fs::DirEntry::file_name);
+
+        for entry in entries {
+            let path = entry.path();
+            let file_type = entry.file_type().map_err(|e| ParseError::io_error(&path, e))?;
+            if !file_type.is_dir() && !file_type.is_file() {
+                continue;
+            }
+            let relative = path.strip_prefix(self.root).unwrap_or(&path).to_path_buf();
+            let slashed = slash_path(&relative);
+            if file_type.is_file()
+                && !self.globs.is_empty()
+                && !self.globs.iter().any(|g| g.matches(&slashed))
+            {
+                continue;
+            }
+
+            if let Some(label) = excluded_by(&rules, &slashed, file_type.is_dir()) {
+                *self.excluded.entry(label.to_string()).or_default() += 1;
+            } else if file_type.is_dir() {
+                self.dir(&path, &rules)?;
+            } else {
+                self.files.push(relative);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_segments_and_recursion() {
+        let rust = Glob::new("src/**/*.rs").unwrap();
+        assert!(rust.matches("src/lib.rs"));
+        assert!(rust.matches("src/a/b/mod.rs"));
+        assert!(!rust.matches("tests/lib.rs"));
+        assert!(!rust.matches("src/lib.rsx"));
+
+        let name = Glob::new("*.json").unwrap();
+        assert!(name.matches("package.json"));
+        assert!(name.matches("config/app.json"));
+
+        let single = Glob::new("src/?.rs").unwrap();
+        assert!(single.matches("src/a.rs"));
+        assert!(!single.matches("src/ab.rs"));
+
+        assert!(Glob::new("  ").is_err());
+    }
+
+    #[test]
+    fn ignore_rules_follow_gitignore_semantics() {
+        let rules: Vec<_> = ["target/", "*.log", "!keep.log", "/docs/*.md", "# comment", ""]
+            .iter()
+            .filter_map(|line| IgnoreRule::parse(line, "", ".gitignore"))
+            .collect();
+        assert_eq!(rules.len(), 4);
+
+        assert_eq!(excluded_by(&rules, "target", true), Some(".gitignore:target/"));
+        assert_eq!(excluded_by(&rules, "crates/a/target", true), Some(".gitignore:target/"));
+        assert_eq!(excluded_by(&rules, "target", false), None);
+        assert_eq!(excluded_by(&rules, "out/debug.log", false), Some(".gitignore:*.log"));
+        assert_eq!(excluded_by(&rules, "out/keep.log", false), None);
+        assert!(excluded_by(&rules, "docs/intro.md", false).is_some());
+        assert!(excluded_by(&rules, "web/docs/intro.md", false).is_none());
+
+        let nested = IgnoreRule::parse("dist", "web", "web/.coaignore").unwrap();
+        assert!(nested.matches("web/dist", true));
+        assert!(!nested.matches("dist", true));
+    }
+
+    #[test]
+    fn workspace_symbols_are_prefixed_by_file() {
+        let key = workspace_symbol(Path::new("src/lib.rs"), &SymbolPath::single("run"));
+        assert_eq!(key, ["src", "lib.rs", "run"]);
+    }
+}
@@ -7,12 +7,17 @@
 //!
 //! - **Ingress**: Parse external files into typed `Artifact<T>`
 //! - **Transform**: Apply `StructuralDelta<T>` to produce new artifacts
-//! - **Egress**: Serialize artifacts back to external format
+//! - **Egress**: Serialize artifacts back to external format, or propose
+//!   them for review as patches or pull requests ([`patch`])
+//! - **Reconcile**: Catch up with files edited outside the COA mid-run,
+//!   rebasing or pausing the deltas they invalidate ([`reconcile`])
+//! - **Speculate**: Run L3+ subgraphs against a forked cache and commit
+//!   their results once gates pass ([`speculate`])
 //!
 //! # Architecture
 //!
 //! ```text
-//! File System → Parser → Artifact<T> → Transformer → Artifact<T>' → Serializer → File System
+//! File System → Parser → Artifact<T> → Transformer → Pipeline → Artifact<T>' → Serializer → File System
 //!                  ↑___________↓
 //!                    ArtifactCache (content-addressed)
 //! ```
@@ -44,14 +49,60 @@
 #![allow(missing_docs)]
 
 // Core modules
+pub mod bundle;
 pub mod cache;
+pub mod diff;
 pub mod error;
+#[cfg(feature = "forge")]
+pub mod forge;
+pub mod gc;
+pub mod golden;
+pub mod ingress;
 pub mod layer;
+pub mod migrate;
 pub mod parsers;
+pub mod patch;
+pub mod pipeline;
+pub mod reconcile;
+pub mod refactor;
+pub mod secrets;
+pub mod serializers;
+pub mod speculate;
+pub mod xref;
 
 // Re-exports for convenience
-pub use cache::{ArtifactCache, CacheStats, TypedCacheKey};
-pub use error::{ApplyError, CacheError, ConstitutionalError, ParseError, SerializeError};
+pub use bundle::EgressBundle;
+pub use cache::{
+    ArtifactCache, CacheCapacity, CacheConfig, CacheSnapshot, CacheStats, EvictionCause, EvictionEvent,
+    EvictionHook, EvictionPolicy, Generation, TypedCacheKey,
+};
+pub use coa_kernel::isolation::{FsAccess, FsScope, ScopeGuard, ScopeViolation};
+pub use coa_kernel::redaction::Redaction;
+pub use coa_kernel::types::DirectiveSet as KernelDirectiveSet;
+#[cfg(feature = "metrics")]
+pub use coa_kernel::metrics;
+pub use diff::{diff, ArtifactDiff};
+pub use gc::{ArtifactGc, GcConfig, GcReport};
+pub use golden::{GoldenMismatch, GoldenSnapshot, GoldenSuite};
+pub use error::{
+    ApplyError, CacheError, ConstitutionalError, EgressError, ParseError, PipelineError,
+    RefactorError, SerializeError, SpeculationError,
+};
+#[cfg(feature = "forge")]
+pub use forge::{ForgeClient, PullRequest};
+pub use ingress::{Glob, IngressFailure, IngressFilter, IngressReport};
+pub use layer::{CompositionRecord, ConstitutionalLayer, ScopedLayer};
+pub use migrate::{migrate_hashes, MigrationReport};
+pub use patch::{unified_diff, write_patch_series, ChangeSet, FileChange};
+pub use pipeline::{FnMiddleware, MiddlewareRegistry, TransformMiddleware, TransformPipeline};
+pub use reconcile::{
+    FileDrift, PausedDelta, Reconciliation, StaleReason, TrackedFile, Triage,
+};
+pub use refactor::{ArtifactRename, RefactorEngine, RenamePlan, RenameSymbol};
+pub use secrets::{ScanMode, ScanReport, SecretFinding, SecretScanner, SecretSeverity};
+pub use serializers::{ArtifactSerializer, CodeSerializer};
+pub use speculate::Speculation;
+pub use xref::BrokenReference;
 
 /// Version of this crate
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -60,8 +111,9 @@ pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 pub mod prelude {
     //! Common imports for working with the Constitutional Layer
     pub use crate::cache::{ArtifactCache, CacheStats};
-    pub use crate::error::{ApplyError, ConstitutionalError, ParseError, SerializeError};
-    pub use crate::parsers::{ArtifactParser, CodeParser, JsonParser, Language, MarkdownParser, YamlParser};
+    pub use crate::error::{ApplyError, ConstitutionalError, ParseError, PipelineError, SerializeError};
+    pub use crate::pipeline::{TransformMiddleware, TransformPipeline};
+    pub use crate::parsers::{ArtifactParser, CodeParser, JsonParser, Language, ManifestOp, ManifestParser, MarkdownParser, SqlParser, YamlParser};
     pub use coa_artifact::{Artifact, ArtifactType, ContentHash, StructuralDelta};
     pub use coa_composition::CompositionStrategy;
 }
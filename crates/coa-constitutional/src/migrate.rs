@@ -0,0 +1,121 @@
+//! Hash algorithm migration
+//!
+//! Switching algorithms ([`HashAlgorithm::install`]) leaves cached
+//! artifacts, and the symbols bound to them, under hashes of the old one,
+//! so deltas based on freshly computed hashes would never match them.
+//! [`migrate_hashes`] re-hashes the cached artifacts of one type and
+//! rebinds their symbols; run it once per cached artifact type.
+
+use crate::cache::ArtifactCache;
+use coa_artifact::{ArtifactType, ContentHash, HashAlgorithm};
+use coa_symbol::SymbolRefIndex;
+
+/// Outcome of a migration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// Algorithm migrated to
+    pub algorithm: HashAlgorithm,
+    /// `(old, new)` artifact hashes
+    pub rehashed: Vec<(ContentHash, ContentHash)>,
+    /// Symbol index entries rebound to new hashes
+    pub rebound_symbols: usize,
+}
+
+impl MigrationReport {
+    /// New hash of the artifact formerly hashed `old`
+    #[must_use]
+    pub fn new_hash(&self, old: &ContentHash) -> Option<ContentHash> {
+        self.rehashed
+            .iter()
+            .find(|(from, _)| from == old)
+            .map(|(_, to)| *to)
+    }
+}
+
+/// Re-hash cached `T` artifacts with `algorithm` and rebind their symbols
+///
+/// Artifacts already hashed with `algorithm` are left alone, so repeated
+/// runs are cheap.
+pub async fn migrate_hashes<T: ArtifactType>(
+    cache: &ArtifactCache,
+    index: Option<&SymbolRefIndex>,
+    algorithm: HashAlgorithm,
+) -> MigrationReport {
+    let rehashed = cache.rehash::<T>(algorithm).await;
+    let rebound_symbols = index.map_or(0, |index| {
+        rehashed
+            .iter()
+            .map(|(old, new)| index.rebind_parent(old, *new))
+            .sum()
+    });
+    tracing::debug!(
+        type_id = T::TYPE_ID,
+        %algorithm,
+        rehashed = rehashed.len(),
+        rebound_symbols,
+        "hash migration"
+    );
+    MigrationReport {
+        algorithm,
+        rehashed,
+        rebound_symbols,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::TypedCacheKey;
+    use crate::parsers::{ArtifactParser, JsonArtifact, JsonParser};
+    use coa_symbol::{SymbolMetadata, SymbolRef};
+
+    #[tokio::test]
+    async fn cached_artifacts_and_symbols_move_to_new_algorithm() {
+        let cache = ArtifactCache::new(100);
+        let index = SymbolRefIndex::new();
+        let artifact = JsonParser.parse(r#"{"a": 1}"#).unwrap();
+        let old = *artifact.hash();
+        cache
+            .insert_generation(
+                TypedCacheKey::for_generation::<JsonArtifact>(old, "a.json", 1),
+                artifact,
+            )
+            .await;
+        let metadata = SymbolMetadata {
+            content_hash: Some(ContentHash::compute(b"1")),
+            ..SymbolMetadata::default()
+        };
+        index
+            .insert(
+                SymbolRef::new(vec!["a.json".into(), "a".into()], old),
+                metadata,
+            )
+            .unwrap();
+
+        let report =
+            migrate_hashes::<JsonArtifact>(&cache, Some(&index), HashAlgorithm::Sha3_256).await;
+        let new = report.new_hash(&old).unwrap();
+        assert_eq!(new.algorithm(), HashAlgorithm::Sha3_256);
+        assert_eq!(report.rebound_symbols, 1);
+
+        let migrated = cache.get::<JsonArtifact>(&new).await.unwrap();
+        assert_eq!(*migrated.hash(), new);
+        assert!(!cache.contains(&old).await);
+        assert_eq!(
+            *cache
+                .latest::<JsonArtifact>("a.json")
+                .await
+                .unwrap()
+                .0
+                .hash(),
+            new
+        );
+        let entry = index.get_by_path(&["a.json".into(), "a".into()]).unwrap();
+        assert_eq!(*entry.symbol.parent_hash(), new);
+        assert_eq!(entry.metadata.content_hash, None);
+
+        let again =
+            migrate_hashes::<JsonArtifact>(&cache, Some(&index), HashAlgorithm::Sha3_256).await;
+        assert!(again.rehashed.is_empty());
+    }
+}
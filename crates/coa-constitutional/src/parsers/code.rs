@@ -4,7 +4,11 @@
 
 use crate::error::ParseError;
 use crate::parsers::ArtifactParser;
-use coa_artifact::{Artifact, ArtifactType, ContentHash};
+use coa_artifact::{
+    AddressableContent, Artifact, ArtifactType, ContentHash, HashAlgorithm, NodeSpan, SpannedContent,
+    SymbolPath,
+};
+use coa_symbol::{SourceLocation, SymbolKind, SymbolMetadata, Visibility, APPEND_ONLY};
 
 /// Supported programming languages
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -43,6 +47,83 @@ impl Language {
             Language::Python => "python",
         }
     }
+
+    /// Tree-sitter grammar used for syntax checking
+    ///
+    /// JavaScript is checked with the TSX grammar, a superset covering JSX.
+    #[must_use]
+    pub fn grammar(&self) -> tree_sitter::Language {
+        match self {
+            Language::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Language::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            Language::JavaScript => tree_sitter_typescript::LANGUAGE_TSX.into(),
+            Language::Python => tree_sitter_python::LANGUAGE.into(),
+        }
+    }
+}
+
+/// Region of source tree-sitter could not parse
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    /// Byte range of the `ERROR` node (empty for a missing token)
+    pub span: NodeSpan,
+    /// 1-based line where the region starts
+    pub line: usize,
+    /// Short description
+    pub message: String,
+}
+
+impl std::fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Code artifact parsed despite syntax errors
+///
+/// Symbols whose span does not touch an error region are safe targets for
+/// composition; deltas on affected symbols should wait for a fix.
+#[derive(Debug, Clone)]
+pub struct PartialCode {
+    /// Artifact built from the whole source
+    pub artifact: Artifact<CodeArtifact>,
+    /// Unparseable regions, in source order
+    pub errors: Vec<SyntaxError>,
+}
+
+impl PartialCode {
+    /// Check if the source parsed without errors
+    #[inline]
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Check if a symbol overlaps an error region
+    ///
+    /// Symbols whose extent cannot be determined count as affected.
+    #[must_use]
+    pub fn is_affected(&self, symbol: &str) -> bool {
+        match self.artifact.content().symbol_span(symbol) {
+            // A missing token at the very end (e.g. `}`) still belongs to the symbol
+            Some(span) => self.errors.iter().any(|e| {
+                span.overlaps(&e.span) || (e.span.is_empty() && e.span.start == span.end)
+            }),
+            None => true,
+        }
+    }
+
+    /// Symbols clear of every error region
+    #[must_use]
+    pub fn unaffected_symbols(&self) -> Vec<&str> {
+        self.artifact
+            .content()
+            .symbols
+            .iter()
+            .map(String::as_str)
+            .filter(|symbol| !self.is_affected(symbol))
+            .collect()
+    }
 }
 
 /// Parsed code content (simplified)
@@ -56,6 +137,301 @@ pub struct CodeContent {
     pub symbols: Vec<String>,
 }
 
+/// Comment marking the next definition as an append-only region
+///
+/// Written as `// coa:append` (or `# coa:append` in Python) on the line
+/// directly above e.g. a generated tests module or registry list.
+pub const APPEND_MARKER: &str = "coa:append";
+
+impl CodeContent {
+    /// Byte span of a top-level symbol's definition
+    ///
+    /// Runs from the defining line to the end of the definition's syntax
+    /// node, so braces or semicolons inside strings and comments do not cut
+    /// it short.
+    #[must_use]
+    pub fn symbol_span(&self, name: &str) -> Option<NodeSpan> {
+        let tree = syntax_tree(self.language, &self.source).ok()?;
+        let (start, node) = self.definition_node(&tree, name)?;
+        Some(NodeSpan::new(start, node.end_byte()))
+    }
+
+    /// Spans of the statements inside a symbol's body
+    ///
+    /// One span per syntax node in the body (statements, fields, members),
+    /// comments excluded. Deltas narrowed to these spans only conflict when
+    /// they touch the same statement.
+    #[must_use]
+    pub fn statement_spans(&self, name: &str) -> Vec<NodeSpan> {
+        let Ok(tree) = syntax_tree(self.language, &self.source) else {
+            return Vec::new();
+        };
+        let Some(body) = self
+            .definition_node(&tree, name)
+            .and_then(|(_, node)| node.child_by_field_name("body"))
+        else {
+            return Vec::new();
+        };
+        let mut cursor = body.walk();
+        body.named_children(&mut cursor)
+            .filter(|node| !node.is_extra())
+            .map(|node| NodeSpan::new(node.start_byte(), node.end_byte()))
+            .collect()
+    }
+
+    /// Start of the defining line and syntax node of a symbol's definition
+    ///
+    /// Lines whose name is not an identifier in the tree (e.g. `fn x` inside
+    /// a string or comment) are skipped.
+    fn definition_node<'t>(
+        &self,
+        tree: &'t tree_sitter::Tree,
+        name: &str,
+    ) -> Option<(usize, tree_sitter::Node<'t>)> {
+        let mut offset = 0;
+        for line in self.source.split_inclusive('\n') {
+            let start = offset;
+            offset += line.len();
+            let Some(definition) = definition(line.trim()).filter(|d| d.name == name) else {
+                continue;
+            };
+            let name_start = start + (definition.name.as_ptr() as usize - line.as_ptr() as usize);
+            let node = tree
+                .root_node()
+                .descendant_for_byte_range(name_start, name_start + name.len())
+                .filter(|node| node.kind().ends_with("identifier"))
+                .and_then(|identifier| identifier.parent());
+            if let Some(node) = node {
+                return Some((start, node));
+            }
+        }
+        None
+    }
+
+    /// Index metadata of a top-level symbol, from its definition line
+    ///
+    /// Kind and visibility come from the keyword and modifiers (`pub`,
+    /// `export`, a leading `_` in Python); the lines directly above supply
+    /// attributes: annotation and decorator names, `doc` for a doc comment,
+    /// and [`APPEND_ONLY`] for an [`APPEND_MARKER`]. Locations are 1-based.
+    #[must_use]
+    pub fn symbol_metadata(&self, name: &str) -> SymbolMetadata {
+        let mut attributes = Vec::new();
+        for (index, line) in self.source.lines().enumerate() {
+            let trimmed = line.trim();
+            let Some(definition) = definition(trimmed) else {
+                match leading_attribute(trimmed) {
+                    Some(attribute) if !attributes.contains(&attribute) => attributes.push(attribute),
+                    Some(_) => {}
+                    None => attributes.clear(),
+                }
+                continue;
+            };
+            if definition.name != name {
+                attributes.clear();
+                continue;
+            }
+            return SymbolMetadata {
+                kind: definition.kind(),
+                visibility: definition.visibility(self.language),
+                source_location: Some(SourceLocation {
+                    line: index + 1,
+                    column: line.len() - line.trim_start().len() + 1,
+                    file: String::new(),
+                }),
+                attributes,
+                content_hash: self
+                    .symbol_span(name)
+                    .map(|span| ContentHash::compute(&self.source.as_bytes()[span.start..span.end])),
+                ..SymbolMetadata::default()
+            };
+        }
+        SymbolMetadata::default()
+    }
+}
+
+impl AddressableContent for CodeContent {
+    /// Top-level symbols as single-segment paths
+    fn symbol_paths(&self) -> Vec<SymbolPath> {
+        self.symbols.iter().map(SymbolPath::single).collect()
+    }
+
+    /// Symbols whose definition directly follows an [`APPEND_MARKER`] comment
+    fn append_regions(&self) -> Vec<SymbolPath> {
+        let mut marked = false;
+        let mut regions = Vec::new();
+        for line in self.source.lines().map(str::trim) {
+            // Attributes may sit between the marker and the definition
+            if line.starts_with("#[") {
+                continue;
+            }
+            let comment = line.strip_prefix("//").or_else(|| line.strip_prefix('#'));
+            if let Some(comment) = comment {
+                marked = comment.trim() == APPEND_MARKER;
+                continue;
+            }
+            if let (true, Some(name)) = (marked, defined_symbol(line)) {
+                regions.push(SymbolPath::single(name));
+            }
+            marked = false;
+        }
+        regions
+    }
+}
+
+impl SpannedContent for CodeContent {
+    fn span_of(&self, path: &SymbolPath) -> Option<NodeSpan> {
+        match path.segments() {
+            [name] => self.symbol_span(name),
+            _ => None,
+        }
+    }
+}
+
+/// Tree-sitter parse of `source`
+fn syntax_tree(language: Language, source: &str) -> Result<tree_sitter::Tree, ParseError> {
+    let mut parser = tree_sitter::Parser::new();
+    parser
+        .set_language(&language.grammar())
+        .map_err(|e| ParseError::ParserError(e.to_string()))?;
+    parser
+        .parse(source, None)
+        .ok_or_else(|| ParseError::ParserError("tree-sitter parse cancelled".to_string()))
+}
+
+/// Depth-first scan for error nodes, skipping error-free subtrees
+fn collect_errors(node: tree_sitter::Node<'_>, source: &str, errors: &mut Vec<SyntaxError>) {
+    if node.is_error() {
+        let text = source.get(node.start_byte()..node.end_byte()).unwrap_or_default();
+        let snippet: String = text.trim().chars().take(32).collect();
+        errors.push(SyntaxError {
+            span: NodeSpan::new(node.start_byte(), node.end_byte()),
+            line: node.start_position().row + 1,
+            message: format!("unexpected `{snippet}`"),
+        });
+        return;
+    }
+    if node.is_missing() {
+        errors.push(SyntaxError {
+            span: NodeSpan::new(node.start_byte(), node.start_byte()),
+            line: node.start_position().row + 1,
+            message: format!("missing `{}`", node.kind()),
+        });
+        return;
+    }
+    if !node.has_error() {
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_errors(child, source, errors);
+    }
+}
+
+/// Definition introduced by a source line
+struct Definition<'a> {
+    /// Modifiers before the keyword (`pub(crate) async`, `export`, ...)
+    modifiers: &'a str,
+    keyword: &'a str,
+    name: &'a str,
+}
+
+impl Definition<'_> {
+    fn kind(&self) -> SymbolKind {
+        match self.keyword {
+            "fn" | "def" | "function" => SymbolKind::Function,
+            _ => SymbolKind::Type,
+        }
+    }
+
+    fn visibility(&self, language: Language) -> Visibility {
+        match language {
+            Language::Rust if self.modifiers.contains("pub(") => Visibility::Restricted,
+            Language::Rust if self.modifiers.split_whitespace().any(|m| m == "pub") => {
+                Visibility::Public
+            }
+            Language::TypeScript | Language::JavaScript if self.modifiers.contains("export") => {
+                Visibility::Public
+            }
+            Language::Python if !self.name.starts_with('_') => Visibility::Public,
+            _ => Visibility::Internal,
+        }
+    }
+}
+
+/// Keywords introducing a symbol definition
+const DEFINITION_KEYWORDS: &[&str] = &[
+    "fn", "struct", "enum", "trait", "class", "def", "function", "interface",
+];
+
+/// Modifiers that may precede a definition keyword
+const DEFINITION_MODIFIERS: &[&str] = &["pub", "export", "default", "async", "unsafe", "abstract"];
+
+/// Definition on a (trimmed) source line, if any
+fn definition(line: &str) -> Option<Definition<'_>> {
+    let mut rest = line;
+    loop {
+        if let Some(scoped) = rest.strip_prefix("pub(") {
+            rest = scoped.split_once(')')?.1.trim_start();
+            continue;
+        }
+        let word = rest.split_whitespace().next()?;
+        if !DEFINITION_MODIFIERS.contains(&word) {
+            break;
+        }
+        rest = rest[word.len()..].trim_start();
+    }
+    let modifiers = line[..line.len() - rest.len()].trim_end();
+    let keyword = DEFINITION_KEYWORDS
+        .iter()
+        .find(|keyword| rest.strip_prefix(**keyword).is_some_and(|r| r.starts_with(' ')))?;
+    let after = rest[keyword.len()..].trim_start();
+    let end = after
+        .find(|c: char| !c.is_alphanumeric() && c != '_')
+        .unwrap_or(after.len());
+    (end > 0).then(|| Definition {
+        modifiers,
+        keyword,
+        name: &after[..end],
+    })
+}
+
+/// Name defined by a (trimmed) source line, if any
+fn defined_symbol(line: &str) -> Option<&str> {
+    definition(line).map(|d| d.name)
+}
+
+/// Attribute contributed by a (trimmed) line above a definition
+///
+/// `None` for lines that detach preceding attributes from the definition.
+fn leading_attribute(line: &str) -> Option<String> {
+    let name = |text: &str| {
+        text.split(|c: char| !c.is_alphanumeric() && c != '_' && c != ':' && c != '.')
+            .next()
+            .unwrap_or_default()
+            .to_string()
+    };
+    if let Some(attribute) = line.strip_prefix("#[") {
+        return Some(name(attribute));
+    }
+    if let Some(decorator) = line.strip_prefix('@') {
+        return Some(name(decorator));
+    }
+    let comment = line
+        .strip_prefix("//")
+        .or_else(|| line.strip_prefix('#'))
+        .map(str::trim);
+    if comment == Some(APPEND_MARKER) {
+        return Some(APPEND_ONLY.to_string());
+    }
+    let doc = line.starts_with("///")
+        || line.starts_with("/**")
+        || line.starts_with("* ")
+        || line == "*"
+        || line == "*/";
+    doc.then(|| "doc".to_string())
+}
+
 /// Code artifact type
 #[derive(Debug, Clone)]
 pub struct CodeArtifact {
@@ -77,19 +453,30 @@ impl ArtifactType for CodeArtifact {
     type Content = CodeContent;
 
     fn hash(content: &Self::Content) -> ContentHash {
-        let mut hasher = blake3::Hasher::new();
+        let mut hasher = HashAlgorithm::current().hasher();
         hasher.update(content.language.name().as_bytes());
         hasher.update(content.source.as_bytes());
-        ContentHash::from_slice(hasher.finalize().as_bytes()).unwrap_or_else(|_| ContentHash::ZERO)
+        hasher.finalize()
     }
 
     const TYPE_ID: &'static str = "code";
+
+    fn estimated_size(content: &Self::Content) -> usize {
+        std::mem::size_of::<Self::Content>()
+            + content.source.len()
+            + content.symbols.iter().map(String::len).sum::<usize>()
+    }
 }
 
 /// Code parser (simplified - full tree-sitter integration pending)
+///
+/// Lenient by default: [`parse`](ArtifactParser::parse) accepts broken
+/// code. Use [`parse_partial`](Self::parse_partial) to also get the syntax
+/// error regions, or [`strict`](Self::strict) to reject them.
 #[derive(Debug, Clone)]
 pub struct CodeParser {
     language: Language,
+    strict: bool,
 }
 
 impl CodeParser {
@@ -97,7 +484,51 @@ impl CodeParser {
     #[inline]
     #[must_use]
     pub fn new(language: Language) -> Self {
-        Self { language }
+        Self {
+            language,
+            strict: false,
+        }
+    }
+
+    /// Reject sources containing syntax errors
+    #[inline]
+    #[must_use]
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    /// Parse, keeping the artifact usable alongside its syntax errors
+    ///
+    /// # Errors
+    /// Returns `ParseError::ParserError` if the grammar fails to load
+    pub fn parse_partial(&self, source: &str) -> Result<PartialCode, ParseError> {
+        let errors = self.syntax_errors(source)?;
+        let artifact = self.build(source)?;
+        Ok(PartialCode { artifact, errors })
+    }
+
+    /// Locate `ERROR` and missing-token nodes with tree-sitter
+    ///
+    /// # Errors
+    /// Returns `ParseError::ParserError` if the grammar fails to load
+    pub fn syntax_errors(&self, source: &str) -> Result<Vec<SyntaxError>, ParseError> {
+        let tree = syntax_tree(self.language, source)?;
+        let mut errors = Vec::new();
+        collect_errors(tree.root_node(), source, &mut errors);
+        Ok(errors)
+    }
+
+    fn build(&self, source: &str) -> Result<Artifact<CodeArtifact>, ParseError> {
+        let code_content = CodeContent {
+            language: self.language,
+            source: source.to_string(),
+            symbols: self.extract_symbols(source),
+        };
+
+        Artifact::new(code_content).map_err(|e| {
+            ParseError::ValidationError(format!("failed to create artifact: {}", e))
+        })
     }
 
     /// Get parser language
@@ -111,32 +542,11 @@ impl CodeParser {
     fn extract_symbols(&self, source: &str) -> Vec<String> {
         // Simple extraction of fn, struct, class definitions
         // Full tree-sitter implementation will replace this
-        let mut symbols = Vec::new();
-        
-        for line in source.lines() {
-            let trimmed = line.trim();
-            if let Some(name) = trimmed.strip_prefix("fn ") {
-                if let Some(end) = name.find(|c: char| !c.is_alphanumeric() && c != '_') {
-                    symbols.push(name[..end].to_string());
-                } else {
-                    symbols.push(name.to_string());
-                }
-            } else if let Some(name) = trimmed.strip_prefix("struct ") {
-                if let Some(end) = name.find(|c: char| !c.is_alphanumeric() && c != '_') {
-                    symbols.push(name[..end].to_string());
-                } else {
-                    symbols.push(name.to_string());
-                }
-            } else if let Some(name) = trimmed.strip_prefix("class ") {
-                if let Some(end) = name.find(|c: char| !c.is_alphanumeric() && c != '_') {
-                    symbols.push(name[..end].to_string());
-                } else {
-                    symbols.push(name.to_string());
-                }
-            }
-        }
-        
-        symbols
+        source
+            .lines()
+            .filter_map(|line| defined_symbol(line.trim()))
+            .map(str::to_string)
+            .collect()
     }
 }
 
@@ -145,23 +555,32 @@ impl ArtifactParser for CodeParser {
 
     fn parse(&self, content: &str) -> Result<Artifact<Self::Output>, ParseError> {
         // Simplified parsing - full AST construction pending tree-sitter integration
-        let symbols = self.extract_symbols(content);
-
-        let code_content = CodeContent {
-            language: self.language,
-            source: content.to_string(),
-            symbols,
-        };
-
-        Artifact::new(code_content).map_err(|e| {
-            ParseError::ValidationError(format!("failed to create artifact: {}", e))
-        })
+        if self.strict {
+            if let Some(error) = self.syntax_errors(content)?.first() {
+                return Err(ParseError::SyntaxError {
+                    path: std::path::PathBuf::from(format!("input.{}", self.extensions()[0])),
+                    message: error.to_string(),
+                });
+            }
+        }
+        self.build(content)
     }
 
     fn extensions(&self) -> &[&str] {
         self.language.extensions()
     }
 
+    fn symbols(&self, artifact: &Artifact<Self::Output>) -> Vec<SymbolPath> {
+        artifact.content().symbol_paths()
+    }
+
+    fn symbol_metadata(&self, artifact: &Artifact<Self::Output>, symbol: &SymbolPath) -> SymbolMetadata {
+        match symbol.segments() {
+            [name] => artifact.content().symbol_metadata(name),
+            _ => SymbolMetadata::default(),
+        }
+    }
+
     fn priority(&self) -> i32 {
         10 // Higher priority than generic parsers
     }
@@ -211,6 +630,124 @@ fn main() {
         assert!(artifact.content().symbols.contains(&"main".to_string()));
     }
 
+    #[test]
+    fn symbol_metadata_from_definitions() {
+        let source = "/// Entry point\n#[inline]\npub fn run() {}\n\n// coa:append\npub(crate) struct Registry;\n\nasync fn helper() {}\n";
+        let artifact = CodeParser::new(Language::Rust).parse(source).unwrap();
+        let content = artifact.content();
+        assert_eq!(content.symbols, ["run", "Registry", "helper"]);
+
+        let run = content.symbol_metadata("run");
+        assert_eq!((run.kind, run.visibility), (SymbolKind::Function, Visibility::Public));
+        assert_eq!(run.attributes, ["doc", "inline"]);
+        let location = run.source_location.unwrap();
+        assert_eq!((location.line, location.column), (3, 1));
+        assert_eq!(run.content_hash, Some(ContentHash::compute(b"pub fn run() {}")));
+
+        let registry = content.symbol_metadata("Registry");
+        assert_eq!((registry.kind, registry.visibility), (SymbolKind::Type, Visibility::Restricted));
+        assert!(registry.is_append_only());
+
+        let helper = content.symbol_metadata("helper");
+        assert_eq!(helper.visibility, Visibility::Internal);
+        assert!(helper.attributes.is_empty());
+
+        let ts = CodeParser::new(Language::TypeScript)
+            .parse("@Component\nexport class App {}\nfunction local() {}\n")
+            .unwrap();
+        let app = ts.content().symbol_metadata("App");
+        assert_eq!((app.visibility, app.attributes.as_slice()), (Visibility::Public, &["Component".to_string()][..]));
+        assert_eq!(ts.content().symbol_metadata("local").visibility, Visibility::Internal);
+
+        let py = CodeParser::new(Language::Python)
+            .parse("def _private():\n    pass\n\ndef public():\n    pass\n")
+            .unwrap();
+        assert_eq!(py.content().symbol_metadata("_private").visibility, Visibility::Internal);
+        assert_eq!(py.content().symbol_metadata("public").kind, SymbolKind::Function);
+    }
+
+    #[test]
+    fn symbol_and_statement_spans() {
+        let source = "fn main() {\n    let a = 1;\n    let b = 2;\n}\n\nstruct Unit;\n";
+        let artifact = CodeParser::new(Language::Rust).parse(source).unwrap();
+        let content = artifact.content();
+
+        let main = content.span_of(&SymbolPath::single("main")).unwrap();
+        assert_eq!(&source[main.start..main.end], "fn main() {\n    let a = 1;\n    let b = 2;\n}");
+        let unit = content.symbol_span("Unit").unwrap();
+        assert_eq!(&source[unit.start..unit.end], "struct Unit;");
+
+        let statements = content.statement_spans("main");
+        let text: Vec<&str> = statements.iter().map(|s| &source[s.start..s.end]).collect();
+        assert_eq!(text, ["let a = 1;", "let b = 2;"]);
+        assert!(!statements[0].overlaps(&statements[1]));
+        assert!(statements.iter().all(|s| main.contains(s)));
+    }
+
+    #[test]
+    fn spans_ignore_delimiters_in_strings_and_comments() {
+        let source = "fn open() {\n    let s = \"}\";\n    // fn open() { ;\n    let t = ';';\n}\n\nfn next() {}\n";
+        let artifact = CodeParser::new(Language::Rust).parse(source).unwrap();
+        let content = artifact.content();
+
+        let open = content.symbol_span("open").unwrap();
+        assert_eq!(&source[open.start..open.end], &source[..source.find("\n\n").unwrap()]);
+        assert!(!open.overlaps(&content.symbol_span("next").unwrap()));
+
+        let statements = content.statement_spans("open");
+        let text: Vec<&str> = statements.iter().map(|s| &source[s.start..s.end]).collect();
+        assert_eq!(text, ["let s = \"}\";", "let t = ';';"]);
+    }
+
+    #[test]
+    fn python_block_span_follows_indentation() {
+        let source = "class A:\n    x = 1\n\n    y = 2\nz = 3\n";
+        let artifact = CodeParser::new(Language::Python).parse(source).unwrap();
+
+        let span = artifact.content().symbol_span("A").unwrap();
+        assert_eq!(&source[span.start..span.end], "class A:\n    x = 1\n\n    y = 2");
+    }
+
+    #[test]
+    fn lenient_parse_reports_errors_and_unaffected_symbols() {
+        let source = "fn ok() {\n    1\n}\n\nfn broken() {\n    let x = ;\n}\n\nfn after() {}\n";
+        let parser = CodeParser::new(Language::Rust);
+
+        let partial = parser.parse_partial(source).unwrap();
+        assert!(!partial.is_complete());
+        assert_eq!(partial.errors[0].line, 6);
+        assert_eq!(partial.artifact.content().symbols, ["ok", "broken", "after"]);
+        assert!(partial.is_affected("broken"));
+        assert_eq!(partial.unaffected_symbols(), ["ok", "after"]);
+
+        // Lenient parse still yields the artifact; strict rejects it
+        assert!(parser.parse(source).is_ok());
+        assert!(matches!(
+            parser.clone().strict().parse(source),
+            Err(ParseError::SyntaxError { .. })
+        ));
+    }
+
+    #[test]
+    fn clean_source_is_complete() {
+        let partial = CodeParser::new(Language::Python)
+            .parse_partial("class A:\n    x = 1\n")
+            .unwrap();
+        assert!(partial.is_complete());
+        assert_eq!(partial.unaffected_symbols(), ["A"]);
+    }
+
+    #[test]
+    fn append_marker_declares_regions() {
+        let source = "// coa:append\n#[derive(Default)]\nstruct Registry {}\n\n// other\nfn run() {}\n";
+        let artifact = CodeParser::new(Language::Rust).parse(source).unwrap();
+        assert_eq!(artifact.content().append_regions(), [SymbolPath::single("Registry")]);
+
+        let python = "# coa:append\nclass Tests:\n    pass\n";
+        let artifact = CodeParser::new(Language::Python).parse(python).unwrap();
+        assert_eq!(artifact.content().append_regions(), [SymbolPath::single("Tests")]);
+    }
+
     #[test]
     fn code_artifact_type_id() {
         assert_eq!(CodeArtifact::TYPE_ID, "code");
@@ -4,7 +4,8 @@
 
 use crate::error::ParseError;
 use crate::parsers::ArtifactParser;
-use coa_artifact::{Artifact, ArtifactType, ContentHash};
+use coa_artifact::{AddressableContent, Artifact, ArtifactType, ContentHash, SymbolPath};
+use coa_symbol::{SymbolKind, SymbolMetadata};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
@@ -80,6 +81,57 @@ impl JsonContent {
     }
 }
 
+impl AddressableContent for JsonContent {
+    fn symbol_paths(&self) -> Vec<SymbolPath> {
+        let mut paths = Vec::new();
+        collect_json_paths(&self.root, &SymbolPath::root(), &mut paths);
+        paths
+    }
+
+    fn resolves(&self, path: &SymbolPath) -> bool {
+        let mut current = &self.root;
+        for segment in path.iter() {
+            current = match current {
+                Value::Object(map) => match map.get(segment) {
+                    Some(v) => v,
+                    None => return false,
+                },
+                Value::Array(items) => match segment.parse::<usize>().ok().and_then(|i| items.get(i)) {
+                    Some(v) => v,
+                    None => return false,
+                },
+                _ => return false,
+            };
+        }
+        true
+    }
+}
+
+/// Collect object keys and array indices as paths (depth-first)
+///
+/// Keys are visited in sorted order, so paths do not depend on key order.
+fn collect_json_paths(value: &Value, prefix: &SymbolPath, out: &mut Vec<SymbolPath>) {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            for (key, child) in entries {
+                let path = prefix.child(key.as_str());
+                out.push(path.clone());
+                collect_json_paths(child, &path, out);
+            }
+        }
+        Value::Array(items) => {
+            for (i, child) in items.iter().enumerate() {
+                let path = prefix.child(i.to_string());
+                out.push(path.clone());
+                collect_json_paths(child, &path, out);
+            }
+        }
+        _ => {}
+    }
+}
+
 /// JSON config artifact type
 #[derive(Debug, Clone)]
 pub struct JsonArtifact;
@@ -90,12 +142,16 @@ impl ArtifactType for JsonArtifact {
     type Content = JsonContent;
 
     fn hash(content: &Self::Content) -> ContentHash {
-        let json_str =
-            serde_json::to_string(&content.root).unwrap_or_default();
+        let json_str = super::sorted_keys(&content.root).to_string();
         ContentHash::compute(json_str.as_bytes())
     }
 
     const TYPE_ID: &'static str = "json";
+
+    fn estimated_size(content: &Self::Content) -> usize {
+        std::mem::size_of::<Self::Content>()
+            + serde_json::to_string(&content.root).map_or(0, |s| s.len())
+    }
 }
 
 /// JSON parser
@@ -145,6 +201,17 @@ impl ArtifactParser for JsonParser {
     fn extensions(&self) -> &[&str] {
         &["json"]
     }
+
+    fn symbols(&self, artifact: &Artifact<Self::Output>) -> Vec<SymbolPath> {
+        artifact.content().symbol_paths()
+    }
+
+    fn symbol_metadata(&self, _artifact: &Artifact<Self::Output>, _symbol: &SymbolPath) -> SymbolMetadata {
+        SymbolMetadata {
+            kind: SymbolKind::Config,
+            ..SymbolMetadata::default()
+        }
+    }
 }
 
 #[cfg(test)]
@@ -225,6 +292,20 @@ mod tests {
         assert_eq!(content.get_path("debug"), Some(&Value::Bool(true)));
     }
 
+    #[test]
+    fn json_content_resolves_paths() {
+        let content = JsonContent::new(serde_json::json!({
+            "server": {"port": 8080, "hosts": ["a", "b"]}
+        }));
+
+        let path = |s: &str| s.parse::<SymbolPath>().unwrap();
+        assert!(content.resolves(&path("server.port")));
+        assert!(content.resolves(&path("server.hosts.1")));
+        assert!(!content.resolves(&path("server.hosts.2")));
+        assert!(!content.resolves(&path("server.port.value")));
+        assert!(content.symbol_paths().contains(&path("server.hosts.0")));
+    }
+
     #[test]
     fn json_artifact_type_id() {
         assert_eq!(JsonArtifact::TYPE_ID, "json");
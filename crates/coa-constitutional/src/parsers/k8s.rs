@@ -0,0 +1,705 @@
+//! Kubernetes manifest parser
+//!
+//! Parses (multi-document) YAML manifests with the [`YamlParser`] and
+//! addresses each document as a resource identified by
+//! `(apiVersion, kind, namespace, name)`.
+//!
+//! # Symbol Paths
+//! - Resource: `<apiVersion>.<kind>.<namespace>.<name>`, e.g.
+//!   `["apps/v1", "Deployment", "default", "web"]`; cluster-scoped kinds use
+//!   [`CLUSTER_SCOPE`] as namespace, namespaced kinds without one use
+//!   [`DEFAULT_NAMESPACE`]
+//! - Fields: resource path followed by YAML keys and sequence indices,
+//!   e.g. `[..resource.., "spec", "replicas"]`
+//!
+//! Segments may contain `/`, `.` and `-`; build paths with
+//! [`ResourceId::path`] rather than parsing dotted strings.
+//!
+//! Resources of known kinds are validated against a bundled OpenAPI schema
+//! subset ([`K8sSchemaSet::bundled`]); unknown kinds (e.g. custom
+//! resources) are accepted as long as they are identifiable.
+//!
+//! [`K8sClassifier`] lets the hybrid strategy compose edits to different
+//! resources as one commutative batch.
+
+use crate::error::ParseError;
+use crate::parsers::yaml::collect_yaml_paths;
+use crate::parsers::{ArtifactParser, YamlContent, YamlParser};
+use coa_artifact::{
+    define_artifact_type, AddressableContent, Artifact, ArtifactError, ArtifactType, ContentHash,
+    StructuralDelta, SymbolPath,
+};
+use coa_composition::{Classifier, CommutativeClassifier, ComposableArtifact, DeltaClass};
+use coa_symbol::{SymbolKind, SymbolMetadata};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use serde_yaml::Value;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Component, Path};
+use std::sync::OnceLock;
+
+/// Namespace segment of cluster-scoped resources
+pub const CLUSTER_SCOPE: &str = "_cluster";
+
+/// Namespace assumed for namespaced resources that name none
+pub const DEFAULT_NAMESPACE: &str = "default";
+
+/// Path segments of a resource path
+const RESOURCE_DEPTH: usize = 4;
+
+/// Why a manifest document cannot be identified
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ResourceError {
+    /// Identity field is missing or empty
+    #[error("resource without {0}")]
+    MissingField(&'static str),
+
+    /// Document of a multi-document manifest lacks an identity field
+    #[error("document {index}: resource without {field}")]
+    Unidentified { index: usize, field: &'static str },
+}
+
+/// Identity of a Kubernetes resource
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ResourceId {
+    /// `apiVersion`, e.g. `apps/v1`
+    pub api_version: String,
+    /// `kind`, e.g. `Deployment`
+    pub kind: String,
+    /// Namespace ([`CLUSTER_SCOPE`] for cluster-scoped kinds)
+    pub namespace: String,
+    /// `metadata.name`
+    pub name: String,
+}
+
+impl ResourceId {
+    /// Create resource identity
+    #[must_use]
+    pub fn new(
+        api_version: impl Into<String>,
+        kind: impl Into<String>,
+        namespace: impl Into<String>,
+        name: impl Into<String>,
+    ) -> Self {
+        Self {
+            api_version: api_version.into(),
+            kind: kind.into(),
+            namespace: namespace.into(),
+            name: name.into(),
+        }
+    }
+
+    /// Identity of a manifest document
+    ///
+    /// # Errors
+    /// `ResourceError::MissingField` if `apiVersion`, `kind` or
+    /// `metadata.name` is missing
+    pub fn of(document: &Value) -> Result<Self, ResourceError> {
+        let field = |value: Option<&Value>, name: &'static str| {
+            value
+                .and_then(Value::as_str)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+                .ok_or(ResourceError::MissingField(name))
+        };
+        let metadata = document.get("metadata");
+        let api_version = field(document.get("apiVersion"), "apiVersion")?;
+        let kind = field(document.get("kind"), "kind")?;
+        let name = field(metadata.and_then(|m| m.get("name")), "metadata.name")?;
+        let namespace = match metadata
+            .and_then(|m| m.get("namespace"))
+            .and_then(Value::as_str)
+        {
+            _ if K8sSchemaSet::bundled().is_namespaced(&api_version, &kind) == Some(false) => {
+                CLUSTER_SCOPE.to_string()
+            }
+            Some(namespace) => namespace.to_string(),
+            None => DEFAULT_NAMESPACE.to_string(),
+        };
+        Ok(Self {
+            api_version,
+            kind,
+            namespace,
+            name,
+        })
+    }
+
+    /// Symbol path of the resource
+    #[must_use]
+    pub fn path(&self) -> SymbolPath {
+        SymbolPath::new(vec![
+            self.api_version.clone(),
+            self.kind.clone(),
+            self.namespace.clone(),
+            self.name.clone(),
+        ])
+    }
+
+    /// Resource a symbol path lies in, if it is at least resource-deep
+    #[must_use]
+    pub fn from_path(path: &SymbolPath) -> Option<Self> {
+        match path.segments() {
+            [api_version, kind, namespace, name, ..] => {
+                Some(Self::new(api_version, kind, namespace, name))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ResourceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{} {}/{}",
+            self.api_version, self.kind, self.namespace, self.name
+        )
+    }
+}
+
+/// One manifest document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct K8sResource {
+    /// Resource identity
+    pub id: ResourceId,
+    /// Full document
+    pub document: Value,
+}
+
+/// Kubernetes manifest content
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct K8sManifestContent {
+    /// Resources in document order
+    pub resources: Vec<K8sResource>,
+}
+
+impl K8sManifestContent {
+    /// Identify every document of parsed YAML (empty documents are skipped)
+    ///
+    /// # Errors
+    /// `ResourceError::Unidentified` naming the first unidentifiable document
+    pub fn from_yaml(yaml: &YamlContent) -> Result<Self, ResourceError> {
+        let resources = yaml
+            .documents
+            .iter()
+            .enumerate()
+            .filter(|(_, document)| !document.is_null())
+            .map(|(i, document)| {
+                ResourceId::of(document)
+                    .map(|id| K8sResource {
+                        id,
+                        document: document.clone(),
+                    })
+                    .map_err(|e| match e {
+                        ResourceError::MissingField(field) => {
+                            ResourceError::Unidentified { index: i, field }
+                        }
+                        unidentified @ ResourceError::Unidentified { .. } => unidentified,
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { resources })
+    }
+
+    /// Resource with identity `id`
+    #[must_use]
+    pub fn resource(&self, id: &ResourceId) -> Option<&K8sResource> {
+        self.resources.iter().find(|r| r.id == *id)
+    }
+
+    /// Resource identities in document order
+    #[must_use]
+    pub fn ids(&self) -> Vec<&ResourceId> {
+        self.resources.iter().map(|r| &r.id).collect()
+    }
+
+    /// Documents as YAML content
+    #[must_use]
+    pub fn to_yaml(&self) -> YamlContent {
+        YamlContent::new_multi(self.resources.iter().map(|r| r.document.clone()).collect())
+    }
+}
+
+impl AddressableContent for K8sManifestContent {
+    /// Resource paths, each followed by its field paths
+    fn symbol_paths(&self) -> Vec<SymbolPath> {
+        let mut paths = Vec::new();
+        for resource in &self.resources {
+            let path = resource.id.path();
+            paths.push(path.clone());
+            collect_yaml_paths(&resource.document, &path, &mut paths);
+        }
+        paths
+    }
+}
+
+define_artifact_type! {
+    /// Kubernetes manifest artifact type
+    pub K8sManifestArtifact {
+        content: K8sManifestContent,
+        type_id: "k8s_manifest",
+        hash: hash_k8s,
+        validate: validate_k8s,
+        size: |content| {
+            std::mem::size_of::<K8sManifestContent>()
+                + content
+                    .resources
+                    .iter()
+                    .map(|r| serde_yaml::to_string(&r.document).map_or(0, |s| s.len()))
+                    .sum::<usize>()
+        },
+    }
+}
+
+fn hash_k8s(content: &K8sManifestContent) -> ContentHash {
+    let documents: Vec<&Value> = content.resources.iter().map(|r| &r.document).collect();
+    ContentHash::compute(
+        serde_yaml::to_string(&documents)
+            .unwrap_or_default()
+            .as_bytes(),
+    )
+}
+
+/// Identities are unique and known kinds match the bundled schemas
+fn validate_k8s(content: &K8sManifestContent) -> Result<(), ArtifactError> {
+    let mut seen = HashSet::new();
+    let mut problems = Vec::new();
+    for resource in &content.resources {
+        if !seen.insert(&resource.id) {
+            problems.push(format!("duplicate resource {}", resource.id));
+        }
+        problems.extend(
+            K8sSchemaSet::bundled()
+                .validate(resource)
+                .into_iter()
+                .map(|problem| format!("{}: {problem}", resource.id)),
+        );
+    }
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(ArtifactError::InvariantViolation(problems.join("; ")))
+    }
+}
+
+impl ComposableArtifact for K8sManifestArtifact {
+    const DEFAULT_STRATEGY: &'static str = "hybrid";
+}
+
+/// Classifier treating edits confined to one resource as commutative
+///
+/// Resources are applied independently, so deltas at or beneath different
+/// resource paths never interact, whatever their operation. Deltas above
+/// resource level (a whole kind or namespace) keep the operation's own
+/// classification.
+///
+/// Use with [`HybridCompositionStrategy::with_classifier`](coa_composition::HybridCompositionStrategy::with_classifier).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct K8sClassifier;
+
+impl K8sClassifier {
+    fn resource_scoped<T: ArtifactType>(delta: &StructuralDelta<T>) -> bool {
+        delta.target().len() >= RESOURCE_DEPTH
+    }
+}
+
+impl Classifier for K8sClassifier {
+    fn classify<T: ArtifactType>(&self, delta: &StructuralDelta<T>) -> DeltaClass {
+        if Self::resource_scoped(delta) {
+            DeltaClass::Commutative
+        } else {
+            CommutativeClassifier::classify(delta)
+        }
+    }
+
+    fn commutes<T: ArtifactType>(&self, delta: &StructuralDelta<T>) -> bool {
+        Self::resource_scoped(delta) || CommutativeClassifier::is_commutative(delta)
+    }
+}
+
+/// Schema of one `(apiVersion, kind)`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KindSchema {
+    api_version: String,
+    kind: String,
+    namespaced: bool,
+    schema: JsonValue,
+}
+
+/// Bundled schema file layout
+#[derive(Debug, Deserialize)]
+struct SchemaFile {
+    definitions: serde_json::Map<String, JsonValue>,
+    kinds: Vec<KindSchema>,
+}
+
+/// OpenAPI schemas of Kubernetes kinds
+///
+/// Supports the subset of OpenAPI v3 manifests need: `type`, `required`,
+/// `properties`, `additionalProperties`, `items`, `enum`, local `$ref`s and
+/// `x-kubernetes-int-or-string`.
+#[derive(Debug, Clone)]
+pub struct K8sSchemaSet {
+    definitions: serde_json::Map<String, JsonValue>,
+    kinds: HashMap<(String, String), KindSchema>,
+}
+
+impl K8sSchemaSet {
+    /// Schemas of the core workload, config, networking and RBAC kinds
+    ///
+    /// # Panics
+    /// Panics if the bundled schema file is malformed (a build defect)
+    #[must_use]
+    pub fn bundled() -> &'static Self {
+        static BUNDLED: OnceLock<K8sSchemaSet> = OnceLock::new();
+        BUNDLED.get_or_init(|| {
+            Self::from_json(include_str!("k8s_schemas.json"))
+                .expect("bundled k8s schemas are valid")
+        })
+    }
+
+    /// Load schemas from JSON of the bundled layout
+    /// (`{"definitions": {..}, "kinds": [{apiVersion, kind, namespaced, schema}]}`)
+    ///
+    /// # Errors
+    /// Returns error if the JSON does not match the layout
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        let file: SchemaFile = serde_json::from_str(json)?;
+        Ok(Self {
+            definitions: file.definitions,
+            kinds: file
+                .kinds
+                .into_iter()
+                .map(|k| ((k.api_version.clone(), k.kind.clone()), k))
+                .collect(),
+        })
+    }
+
+    /// Whether a kind is namespaced (`None` if unknown)
+    #[must_use]
+    pub fn is_namespaced(&self, api_version: &str, kind: &str) -> Option<bool> {
+        self.kinds
+            .get(&(api_version.to_string(), kind.to_string()))
+            .map(|k| k.namespaced)
+    }
+
+    /// Schema violations of `resource` (none for unknown kinds)
+    #[must_use]
+    pub fn validate(&self, resource: &K8sResource) -> Vec<String> {
+        let key = (resource.id.api_version.clone(), resource.id.kind.clone());
+        let Some(kind) = self.kinds.get(&key) else {
+            return Vec::new();
+        };
+        let document = match serde_json::to_value(&resource.document) {
+            Ok(document) => document,
+            Err(e) => return vec![format!("not representable as JSON: {e}")],
+        };
+        let mut problems = Vec::new();
+        if let Some(metadata) = self.definitions.get("ObjectMeta") {
+            self.check(
+                metadata,
+                document.get("metadata").unwrap_or(&JsonValue::Null),
+                "metadata",
+                &mut problems,
+            );
+        }
+        self.check(&kind.schema, &document, "", &mut problems);
+        problems
+    }
+
+    fn check(&self, schema: &JsonValue, value: &JsonValue, at: &str, problems: &mut Vec<String>) {
+        let at_or_root = if at.is_empty() { "<root>" } else { at };
+        if let Some(reference) = schema.get("$ref").and_then(JsonValue::as_str) {
+            match reference
+                .strip_prefix("#/definitions/")
+                .and_then(|name| self.definitions.get(name))
+            {
+                Some(resolved) => self.check(resolved, value, at, problems),
+                None => problems.push(format!("{at_or_root}: unresolved schema {reference}")),
+            }
+            return;
+        }
+        if schema
+            .get("x-kubernetes-int-or-string")
+            .and_then(JsonValue::as_bool)
+            == Some(true)
+        {
+            if !(value.is_i64() || value.is_u64() || value.is_string()) {
+                problems.push(format!("{at_or_root}: expected integer or string"));
+            }
+            return;
+        }
+        if let Some(expected) = schema.get("type").and_then(JsonValue::as_str) {
+            let matches = match expected {
+                "object" => value.is_object(),
+                "array" => value.is_array(),
+                "string" => value.is_string(),
+                "integer" => value.is_i64() || value.is_u64(),
+                "number" => value.is_number(),
+                "boolean" => value.is_boolean(),
+                _ => true,
+            };
+            if !matches {
+                problems.push(format!("{at_or_root}: expected {expected}"));
+                return;
+            }
+        }
+        if let Some(allowed) = schema.get("enum").and_then(JsonValue::as_array) {
+            if !allowed.contains(value) {
+                problems.push(format!(
+                    "{at_or_root}: {value} is not one of {}",
+                    JsonValue::Array(allowed.clone())
+                ));
+            }
+        }
+
+        let child = |key: &str| {
+            if at.is_empty() {
+                key.to_string()
+            } else {
+                format!("{at}.{key}")
+            }
+        };
+        if let Some(object) = value.as_object() {
+            for required in schema
+                .get("required")
+                .and_then(JsonValue::as_array)
+                .into_iter()
+                .flatten()
+            {
+                if let Some(key) = required.as_str().filter(|key| !object.contains_key(*key)) {
+                    problems.push(format!("{}: required field missing", child(key)));
+                }
+            }
+            let properties = schema.get("properties").and_then(JsonValue::as_object);
+            for (key, field) in object {
+                match properties
+                    .and_then(|p| p.get(key))
+                    .or_else(|| schema.get("additionalProperties"))
+                {
+                    Some(field_schema) if field_schema.is_object() => {
+                        self.check(field_schema, field, &child(key), problems);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+            for (i, item) in array.iter().enumerate() {
+                self.check(items, item, &child(&i.to_string()), problems);
+            }
+        }
+    }
+}
+
+/// Kubernetes manifest parser
+///
+/// Claims YAML files named `*.k8s.yaml`/`*.k8s.yml` or kept under a
+/// `k8s`, `kubernetes` or `manifests` directory, ahead of the generic
+/// YAML parser.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct K8sManifestParser;
+
+impl K8sManifestParser {
+    /// Create new Kubernetes manifest parser
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl ArtifactParser for K8sManifestParser {
+    type Output = K8sManifestArtifact;
+
+    fn parse(&self, content: &str) -> Result<Artifact<Self::Output>, ParseError> {
+        let yaml = YamlParser.parse(content)?;
+        let manifest = K8sManifestContent::from_yaml(yaml.content()).map_err(|e| {
+            ParseError::SyntaxError {
+                path: std::path::PathBuf::from("manifest.yaml"),
+                message: e.to_string(),
+            }
+        })?;
+        Artifact::new(manifest)
+            .map_err(|e| ParseError::ValidationError(format!("artifact creation failed: {e}")))
+    }
+
+    fn can_parse(&self, path: &Path) -> bool {
+        let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+            return false;
+        };
+        if file_name.ends_with(".k8s.yaml") || file_name.ends_with(".k8s.yml") {
+            return true;
+        }
+        let yaml = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| self.extensions().contains(&e));
+        yaml && path.parent().is_some_and(|dir| {
+            dir.components().any(|c| {
+                matches!(c, Component::Normal(name) if matches!(name.to_str(), Some("k8s" | "kubernetes" | "manifests")))
+            })
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["yaml", "yml"]
+    }
+
+    fn priority(&self) -> i32 {
+        10
+    }
+
+    fn symbols(&self, artifact: &Artifact<Self::Output>) -> Vec<SymbolPath> {
+        artifact.content().symbol_paths()
+    }
+
+    fn symbol_metadata(
+        &self,
+        _artifact: &Artifact<Self::Output>,
+        _symbol: &SymbolPath,
+    ) -> SymbolMetadata {
+        SymbolMetadata {
+            kind: SymbolKind::Config,
+            ..SymbolMetadata::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coa_artifact::DeltaOperation;
+    use coa_composition::{CompositionStrategy, HybridCompositionStrategy};
+    use coa_symbol::SymbolRefIndex;
+
+    const MANIFEST: &str = r"
+apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: web
+spec:
+  replicas: 2
+  selector:
+    matchLabels: {app: web}
+  template:
+    spec:
+      containers:
+        - name: web
+          image: nginx
+---
+apiVersion: v1
+kind: Service
+metadata:
+  name: web
+  namespace: prod
+spec:
+  ports:
+    - port: 80
+      targetPort: http
+---
+apiVersion: v1
+kind: Namespace
+metadata:
+  name: prod
+";
+
+    #[test]
+    fn k8s_resources_are_addressed_by_identity() {
+        let artifact = K8sManifestParser.parse(MANIFEST).unwrap();
+        let ids: Vec<String> = artifact
+            .content()
+            .ids()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                "apps/v1/Deployment default/web",
+                "v1/Service prod/web",
+                "v1/Namespace _cluster/prod"
+            ]
+        );
+
+        let deployment = ResourceId::new("apps/v1", "Deployment", "default", "web");
+        let replicas = deployment.path().child("spec").child("replicas");
+        assert!(artifact.content().resolves(&replicas));
+        assert_eq!(ResourceId::from_path(&replicas), Some(deployment));
+        assert!(K8sManifestParser.can_parse(Path::new("deploy/k8s/web.yaml")));
+        assert!(K8sManifestParser.can_parse(Path::new("web.k8s.yml")));
+        assert!(!K8sManifestParser.can_parse(Path::new(".github/workflows/ci.yaml")));
+
+        let unnamed = YamlParser.parse("kind: Pod\n---\napiVersion: v1\nkind: Pod\n").unwrap();
+        assert_eq!(
+            K8sManifestContent::from_yaml(unnamed.content()),
+            Err(ResourceError::Unidentified {
+                index: 0,
+                field: "apiVersion"
+            })
+        );
+        assert_eq!(
+            ResourceId::of(&unnamed.content().documents[1]),
+            Err(ResourceError::MissingField("metadata.name"))
+        );
+    }
+
+    #[test]
+    fn k8s_schema_violations_are_rejected() {
+        let invalid = MANIFEST
+            .replace("replicas: 2", "replicas: two")
+            .replace("- port: 80", "- name: http");
+        let Err(ParseError::ValidationError(message)) = K8sManifestParser.parse(&invalid) else {
+            panic!("expected schema violations");
+        };
+        assert!(
+            message.contains("spec.replicas: expected integer"),
+            "{message}"
+        );
+        assert!(
+            message.contains("spec.ports.0.port: required field missing"),
+            "{message}"
+        );
+
+        let duplicate = format!(
+            "{MANIFEST}---{}",
+            &MANIFEST[..MANIFEST.find("---").unwrap()]
+        );
+        assert!(K8sManifestParser.parse(&duplicate).is_err());
+        assert!(K8sManifestParser
+            .parse("apiVersion: v1\nkind: ConfigMap\n")
+            .is_err());
+
+        // Custom resources are not schema-checked
+        let custom = "apiVersion: example.com/v1\nkind: Widget\nmetadata: {name: w}\nspec: 3\n";
+        assert!(K8sManifestParser.parse(custom).is_ok());
+    }
+
+    #[test]
+    fn k8s_edits_to_different_resources_commute() {
+        let artifact = K8sManifestParser.parse(MANIFEST).unwrap();
+        let hash = *artifact.hash();
+        let deployment = ResourceId::new("apps/v1", "Deployment", "default", "web").path();
+        let service = ResourceId::new("v1", "Service", "prod", "web").path();
+        let replace = |path: SymbolPath| {
+            StructuralDelta::<K8sManifestArtifact>::new(
+                path,
+                DeltaOperation::Replace(artifact.content().clone()),
+                hash,
+            )
+        };
+        let deltas = vec![replace(deployment.child("spec")), replace(service.clone())];
+
+        let index = SymbolRefIndex::new();
+        assert!(HybridCompositionStrategy::new()
+            .validate(&deltas, &index)
+            .is_err());
+        let strategy = HybridCompositionStrategy::with_classifier(K8sClassifier);
+        assert!(strategy.validate(&deltas, &index).is_ok());
+
+        let same = vec![replace(service.clone()), replace(service)];
+        assert!(strategy.validate(&same, &index).is_err());
+    }
+}
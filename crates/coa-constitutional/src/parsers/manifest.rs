@@ -0,0 +1,1077 @@
+//! Dependency manifest parser
+//!
+//! Parses `Cargo.toml`, `package.json` and `pyproject.toml` into a
+//! [`ManifestArtifact`]. Edits are expressed as semantic [`ManifestOp`]s
+//! (add a dependency, bump the version, remove a feature) rather than text
+//! patches, so agents adding *different* dependencies compose under
+//! `CommutativeBatchStrategy` without conflicting on raw text.
+//!
+//! # Symbol Paths
+//! - Dependencies: `dependencies.<name>`, `dev_dependencies.<name>`,
+//!   `build_dependencies.<name>`
+//! - Package version: `version`
+//! - Features: `features.<name>` (Cargo features / pyproject extras)
+//!
+//! # Rendering
+//! Key order is preserved. TOML manifests keep the text they were parsed
+//! from and are rendered by editing it in place with `toml_edit`, so
+//! comments, section order and formatting of untouched entries survive.
+
+use crate::error::{ParseError, SerializeError};
+use crate::parsers::{sorted_keys, ArtifactParser};
+use coa_artifact::{
+    define_artifact_type, Artifact, ArtifactError, ContentHash, DeltaOperation, HashAlgorithm,
+    StructuralDelta, SymbolPath, TransformError, Transformation,
+};
+use coa_composition::ComposableArtifact;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+use toml_edit::{DocumentMut, Item, TableLike};
+
+/// Key under which `toml` represents a datetime in a serde document
+const TOML_DATETIME_KEY: &str = "$__toml_private_datetime";
+
+/// Manifest file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ManifestFormat {
+    /// Rust `Cargo.toml`
+    Cargo,
+    /// Node `package.json`
+    Npm,
+    /// Python `pyproject.toml` (PEP 621)
+    Pyproject,
+}
+
+impl ManifestFormat {
+    /// Get human-readable name
+    #[inline]
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            ManifestFormat::Cargo => "cargo",
+            ManifestFormat::Npm => "npm",
+            ManifestFormat::Pyproject => "pyproject",
+        }
+    }
+
+    /// Conventional file name
+    #[inline]
+    #[must_use]
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            ManifestFormat::Cargo => "Cargo.toml",
+            ManifestFormat::Npm => "package.json",
+            ManifestFormat::Pyproject => "pyproject.toml",
+        }
+    }
+
+    /// Detect format from file name
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.file_name()?.to_str()? {
+            "Cargo.toml" => Some(ManifestFormat::Cargo),
+            "package.json" => Some(ManifestFormat::Npm),
+            "pyproject.toml" => Some(ManifestFormat::Pyproject),
+            _ => None,
+        }
+    }
+
+    /// Path to the package version field
+    fn version_path(self) -> &'static [&'static str] {
+        match self {
+            ManifestFormat::Cargo => &["package", "version"],
+            ManifestFormat::Npm => &["version"],
+            ManifestFormat::Pyproject => &["project", "version"],
+        }
+    }
+
+    /// Path to the dependency table/list for a kind
+    fn dependency_path(self, kind: DependencyKind) -> Option<&'static [&'static str]> {
+        match (self, kind) {
+            (ManifestFormat::Cargo, DependencyKind::Normal) => Some(&["dependencies"]),
+            (ManifestFormat::Cargo, DependencyKind::Dev) => Some(&["dev-dependencies"]),
+            (ManifestFormat::Cargo, DependencyKind::Build) => Some(&["build-dependencies"]),
+            (ManifestFormat::Npm, DependencyKind::Normal) => Some(&["dependencies"]),
+            (ManifestFormat::Npm, DependencyKind::Dev) => Some(&["devDependencies"]),
+            (ManifestFormat::Npm, DependencyKind::Build) => None,
+            (ManifestFormat::Pyproject, DependencyKind::Normal) => {
+                Some(&["project", "dependencies"])
+            }
+            (ManifestFormat::Pyproject, DependencyKind::Dev) => {
+                Some(&["project", "optional-dependencies", "dev"])
+            }
+            (ManifestFormat::Pyproject, DependencyKind::Build) => {
+                Some(&["build-system", "requires"])
+            }
+        }
+    }
+
+    /// Path to the feature table
+    fn features_path(self) -> Option<&'static [&'static str]> {
+        match self {
+            ManifestFormat::Cargo => Some(&["features"]),
+            ManifestFormat::Npm => None,
+            ManifestFormat::Pyproject => Some(&["project", "optional-dependencies"]),
+        }
+    }
+}
+
+/// Dependency section
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum DependencyKind {
+    /// Runtime dependency
+    #[default]
+    Normal,
+    /// Development / test dependency
+    Dev,
+    /// Build-time dependency
+    Build,
+}
+
+impl DependencyKind {
+    /// Symbol path segment for this section
+    #[inline]
+    #[must_use]
+    pub fn section(&self) -> &'static str {
+        match self {
+            DependencyKind::Normal => "dependencies",
+            DependencyKind::Dev => "dev_dependencies",
+            DependencyKind::Build => "build_dependencies",
+        }
+    }
+}
+
+/// Version bump requested by [`ManifestOp::BumpVersion`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VersionBump {
+    /// `x.y.z` → `(x+1).0.0`
+    Major,
+    /// `x.y.z` → `x.(y+1).0`
+    Minor,
+    /// `x.y.z` → `x.y.(z+1)`
+    Patch,
+    /// Set explicit version
+    To(String),
+}
+
+/// Parsed dependency manifest
+///
+/// The full document is kept (as a JSON value) so fields the semantic API
+/// does not model survive a round trip.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestContent {
+    /// Manifest format
+    pub format: ManifestFormat,
+    /// Full document
+    pub document: Value,
+    /// TOML text the document was parsed from; rendering edits it in place
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+/// Equal when format and document are; the source text is layout only
+impl PartialEq for ManifestContent {
+    fn eq(&self, other: &Self) -> bool {
+        self.format == other.format && self.document == other.document
+    }
+}
+
+impl ManifestContent {
+    /// Create from format and document
+    #[inline]
+    #[must_use]
+    pub fn new(format: ManifestFormat, document: Value) -> Self {
+        Self {
+            format,
+            document,
+            source: None,
+        }
+    }
+
+    /// With the TOML text the document was parsed from
+    #[inline]
+    #[must_use]
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Package version
+    #[must_use]
+    pub fn version(&self) -> Option<&str> {
+        lookup(&self.document, self.format.version_path())?.as_str()
+    }
+
+    /// Dependencies of a kind as name → version requirement
+    ///
+    /// Entries without a version (path/git dependencies) map to `""`.
+    #[must_use]
+    pub fn dependencies(&self, kind: DependencyKind) -> BTreeMap<String, String> {
+        let Some(section) = self
+            .format
+            .dependency_path(kind)
+            .and_then(|path| lookup(&self.document, path))
+        else {
+            return BTreeMap::new();
+        };
+
+        match section {
+            Value::Object(map) => map
+                .iter()
+                .map(|(name, spec)| (name.clone(), dependency_version(spec)))
+                .collect(),
+            Value::Array(items) => items
+                .iter()
+                .filter_map(Value::as_str)
+                .map(split_requirement)
+                .map(|(name, spec)| (name.to_string(), spec.to_string()))
+                .collect(),
+            _ => BTreeMap::new(),
+        }
+    }
+
+    /// Version requirement of a single dependency
+    #[must_use]
+    pub fn dependency(&self, kind: DependencyKind, name: &str) -> Option<String> {
+        self.dependencies(kind)
+            .into_iter()
+            .find(|(dep, _)| same_package(self.format, dep, name))
+            .map(|(_, spec)| spec)
+    }
+
+    /// Feature (Cargo) or extra (pyproject) names
+    #[must_use]
+    pub fn features(&self) -> Vec<String> {
+        self.format
+            .features_path()
+            .and_then(|path| lookup(&self.document, path))
+            .and_then(Value::as_object)
+            .map(|map| map.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Apply a semantic operation, producing new content
+    ///
+    /// # Errors
+    /// - `TransformError::StateConflict` when adding an existing dependency
+    ///   or removing a missing one
+    /// - `TransformError::InvalidInput` for operations the format does not
+    ///   support, relative bumps without a literal version, or versions that
+    ///   are not `major.minor.patch`
+    pub fn apply(&self, op: &ManifestOp) -> Result<Self, TransformError> {
+        let mut next = self.clone();
+        match op {
+            ManifestOp::AddDependency {
+                name,
+                version,
+                kind,
+            } => next.add_dependency(name, version, *kind)?,
+            ManifestOp::RemoveDependency { name, kind } => next.remove_dependency(name, *kind)?,
+            ManifestOp::BumpVersion(bump) => next.bump_version(bump)?,
+            ManifestOp::RemoveFeature(name) => next.remove_feature(name)?,
+        }
+        Ok(next)
+    }
+
+    /// Render manifest in its native format
+    ///
+    /// TOML is rendered by updating the source text, if any, so only the
+    /// entries that changed are rewritten.
+    ///
+    /// # Errors
+    /// Returns `SerializeError::FormatError` if the source text no longer
+    /// parses or the document has no TOML/JSON encoding
+    pub fn to_manifest_string(&self) -> Result<String, SerializeError> {
+        let format_error = |e: &dyn std::fmt::Display| SerializeError::FormatError(e.to_string());
+        match self.format {
+            ManifestFormat::Npm => serde_json::to_string_pretty(&self.document)
+                .map(|s| s + "\n")
+                .map_err(|e| format_error(&e)),
+            ManifestFormat::Cargo | ManifestFormat::Pyproject => {
+                let mut document = match &self.source {
+                    Some(source) => source.parse::<DocumentMut>().map_err(|e| format_error(&e))?,
+                    None => DocumentMut::new(),
+                };
+                let Value::Object(root) = &self.document else {
+                    return Err(format_error(&"manifest root must be a table"));
+                };
+                sync_table(document.as_table_mut(), root, false)?;
+                Ok(document.to_string())
+            }
+        }
+    }
+
+    fn dependency_section(
+        &self,
+        kind: DependencyKind,
+    ) -> Result<&'static [&'static str], TransformError> {
+        self.format.dependency_path(kind).ok_or_else(|| {
+            TransformError::InvalidInput(format!(
+                "{} manifests have no {} section",
+                self.format.name(),
+                kind.section()
+            ))
+        })
+    }
+
+    fn add_dependency(
+        &mut self,
+        name: &str,
+        version: &str,
+        kind: DependencyKind,
+    ) -> Result<(), TransformError> {
+        if self.dependency(kind, name).is_some() {
+            return Err(TransformError::StateConflict(format!(
+                "dependency already present: {}.{name}",
+                kind.section()
+            )));
+        }
+
+        let path = self.dependency_section(kind)?;
+        match self.format {
+            ManifestFormat::Cargo | ManifestFormat::Npm => {
+                let section =
+                    lookup_or_insert(&mut self.document, path, || Value::Object(Map::new()));
+                if let Value::Object(map) = section {
+                    map.insert(name.to_string(), Value::String(version.to_string()));
+                }
+            }
+            ManifestFormat::Pyproject => {
+                let section =
+                    lookup_or_insert(&mut self.document, path, || Value::Array(Vec::new()));
+                if let Value::Array(items) = section {
+                    items.push(Value::String(format!("{name}{version}")));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn remove_dependency(
+        &mut self,
+        name: &str,
+        kind: DependencyKind,
+    ) -> Result<(), TransformError> {
+        let path = self.dependency_section(kind)?;
+        let format = self.format;
+        let removed = match lookup_mut(&mut self.document, path) {
+            Some(Value::Object(map)) => {
+                let key = map.keys().find(|k| same_package(format, k, name)).cloned();
+                key.and_then(|k| map.shift_remove(&k)).is_some()
+            }
+            Some(Value::Array(items)) => {
+                let before = items.len();
+                items.retain(|item| {
+                    !item
+                        .as_str()
+                        .is_some_and(|req| same_package(format, split_requirement(req).0, name))
+                });
+                items.len() != before
+            }
+            _ => false,
+        };
+
+        if removed {
+            Ok(())
+        } else {
+            Err(TransformError::StateConflict(format!(
+                "dependency not found: {}.{name}",
+                kind.section()
+            )))
+        }
+    }
+
+    fn bump_version(&mut self, bump: &VersionBump) -> Result<(), TransformError> {
+        let next = match bump {
+            VersionBump::To(version) => version.clone(),
+            _ => {
+                let current = self.version().ok_or_else(|| {
+                    TransformError::InvalidInput("no literal package version to bump".to_string())
+                })?;
+                bump_semver(current, bump)?
+            }
+        };
+        let path = self.format.version_path();
+        *lookup_or_insert(&mut self.document, path, || Value::Null) = Value::String(next);
+        Ok(())
+    }
+
+    fn remove_feature(&mut self, name: &str) -> Result<(), TransformError> {
+        let path = self.format.features_path().ok_or_else(|| {
+            TransformError::InvalidInput(format!(
+                "{} manifests have no features",
+                self.format.name()
+            ))
+        })?;
+        let removed = match lookup_mut(&mut self.document, path) {
+            Some(Value::Object(map)) => map.shift_remove(name).is_some(),
+            _ => false,
+        };
+        if removed {
+            Ok(())
+        } else {
+            Err(TransformError::StateConflict(format!(
+                "feature not found: {name}"
+            )))
+        }
+    }
+}
+
+/// Semantic manifest edit
+///
+/// Carried in a [`StructuralDelta`] as a `Transform` operation; see
+/// [`ManifestOp::into_delta`]. Dependency and feature edits are commutative,
+/// version bumps are not.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ManifestOp {
+    /// Add a dependency with a version requirement
+    AddDependency {
+        /// Package name
+        name: String,
+        /// Version requirement (`1.0`, `^2.3`, `>=1.4` for pyproject)
+        version: String,
+        /// Section
+        kind: DependencyKind,
+    },
+    /// Remove a dependency
+    RemoveDependency {
+        /// Package name
+        name: String,
+        /// Section
+        kind: DependencyKind,
+    },
+    /// Bump the package version
+    BumpVersion(VersionBump),
+    /// Remove a feature / extra
+    RemoveFeature(String),
+}
+
+impl ManifestOp {
+    /// Add runtime dependency
+    #[must_use]
+    pub fn add_dependency(name: impl Into<String>, version: impl Into<String>) -> Self {
+        ManifestOp::AddDependency {
+            name: name.into(),
+            version: version.into(),
+            kind: DependencyKind::Normal,
+        }
+    }
+
+    /// Remove runtime dependency
+    #[must_use]
+    pub fn remove_dependency(name: impl Into<String>) -> Self {
+        ManifestOp::RemoveDependency {
+            name: name.into(),
+            kind: DependencyKind::Normal,
+        }
+    }
+
+    /// Bump package version
+    #[inline]
+    #[must_use]
+    pub fn bump_version(bump: VersionBump) -> Self {
+        ManifestOp::BumpVersion(bump)
+    }
+
+    /// Remove feature
+    #[must_use]
+    pub fn remove_feature(name: impl Into<String>) -> Self {
+        ManifestOp::RemoveFeature(name.into())
+    }
+
+    /// Symbol path this operation targets
+    #[must_use]
+    pub fn target(&self) -> SymbolPath {
+        match self {
+            ManifestOp::AddDependency { name, kind, .. }
+            | ManifestOp::RemoveDependency { name, kind } => {
+                SymbolPath::new(vec![kind.section().to_string(), name.clone()])
+            }
+            ManifestOp::BumpVersion(_) => SymbolPath::single("version"),
+            ManifestOp::RemoveFeature(name) => {
+                SymbolPath::new(vec!["features".to_string(), name.clone()])
+            }
+        }
+    }
+
+    /// Wrap as a delta against `base`
+    #[must_use]
+    pub fn into_delta(
+        self,
+        base: &Artifact<ManifestArtifact>,
+    ) -> StructuralDelta<ManifestArtifact> {
+        StructuralDelta::new(
+            self.target(),
+            DeltaOperation::Transform(Box::new(self)),
+            *base.hash(),
+        )
+    }
+}
+
+impl Transformation<ManifestArtifact> for ManifestOp {
+    fn apply(&self, content: &ManifestContent) -> Result<ManifestContent, TransformError> {
+        content.apply(self)
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            ManifestOp::AddDependency {
+                name,
+                version,
+                kind,
+            } => {
+                format!("add_dependency {}.{name} {version}", kind.section())
+            }
+            ManifestOp::RemoveDependency { name, kind } => {
+                format!("remove_dependency {}.{name}", kind.section())
+            }
+            ManifestOp::BumpVersion(bump) => format!("bump_version {bump:?}"),
+            ManifestOp::RemoveFeature(name) => format!("remove_feature {name}"),
+        }
+    }
+
+    fn is_commutative(&self) -> bool {
+        !matches!(self, ManifestOp::BumpVersion(_))
+    }
+}
+
+define_artifact_type! {
+    /// Dependency manifest artifact type
+    pub ManifestArtifact {
+        content: ManifestContent,
+        type_id: "manifest",
+        hash: hash_manifest,
+        validate: validate_manifest,
+        size: |content| {
+            std::mem::size_of::<ManifestContent>()
+                + content.document.to_string().len()
+                + content.source.as_ref().map_or(0, String::len)
+        },
+    }
+}
+
+/// Hash of the document with keys sorted, so key order does not matter
+fn hash_manifest(content: &ManifestContent) -> ContentHash {
+    let mut hasher = HashAlgorithm::current().hasher();
+    hasher.update(content.format.name().as_bytes());
+    hasher.update(sorted_keys(&content.document).to_string().as_bytes());
+    hasher.finalize()
+}
+
+fn validate_manifest(content: &ManifestContent) -> Result<(), ArtifactError> {
+    if content.document.is_object() {
+        Ok(())
+    } else {
+        Err(ArtifactError::InvariantViolation(
+            "manifest root must be a table/object".to_string(),
+        ))
+    }
+}
+
+impl ComposableArtifact for ManifestArtifact {
+    const DEFAULT_STRATEGY: &'static str = "commutative";
+}
+
+/// Manifest parser
+///
+/// Matches by file name (`Cargo.toml`, `package.json`, `pyproject.toml`)
+/// and takes priority over the generic JSON parser.
+#[derive(Debug, Clone, Copy)]
+pub struct ManifestParser {
+    format: ManifestFormat,
+}
+
+impl ManifestParser {
+    /// Create new parser for format
+    #[inline]
+    #[must_use]
+    pub fn new(format: ManifestFormat) -> Self {
+        Self { format }
+    }
+
+    /// Get parser format
+    #[inline]
+    #[must_use]
+    pub fn format(&self) -> ManifestFormat {
+        self.format
+    }
+}
+
+impl ArtifactParser for ManifestParser {
+    type Output = ManifestArtifact;
+
+    fn parse(&self, content: &str) -> Result<Artifact<Self::Output>, ParseError> {
+        let syntax_error = |message: String| ParseError::SyntaxError {
+            path: std::path::PathBuf::from(self.format.file_name()),
+            message,
+        };
+
+        let document: Value = match self.format {
+            ManifestFormat::Npm => serde_json::from_str(content)
+                .map_err(|e| syntax_error(format!("JSON parse error: {e}")))?,
+            ManifestFormat::Cargo | ManifestFormat::Pyproject => toml::from_str(content)
+                .map_err(|e| syntax_error(format!("TOML parse error: {e}")))?,
+        };
+
+        let content = match self.format {
+            ManifestFormat::Npm => ManifestContent::new(self.format, document),
+            ManifestFormat::Cargo | ManifestFormat::Pyproject => {
+                ManifestContent::new(self.format, document).with_source(content)
+            }
+        };
+        Artifact::new(content)
+            .map_err(|e| ParseError::ValidationError(format!("artifact creation failed: {e}")))
+    }
+
+    fn can_parse(&self, path: &Path) -> bool {
+        ManifestFormat::from_path(path) == Some(self.format)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        match self.format {
+            ManifestFormat::Npm => &["json"],
+            ManifestFormat::Cargo | ManifestFormat::Pyproject => &["toml"],
+        }
+    }
+
+    fn priority(&self) -> i32 {
+        10
+    }
+}
+
+/// Navigate to value at path
+fn lookup<'a>(value: &'a Value, path: &[&str]) -> Option<&'a Value> {
+    path.iter()
+        .try_fold(value, |current, key| current.get(*key))
+}
+
+/// Navigate to value at path (mutable)
+fn lookup_mut<'a>(value: &'a mut Value, path: &[&str]) -> Option<&'a mut Value> {
+    path.iter()
+        .try_fold(value, |current, key| current.get_mut(*key))
+}
+
+/// Navigate to value at path, creating tables and the leaf as needed
+fn lookup_or_insert<'a>(
+    value: &'a mut Value,
+    path: &[&str],
+    leaf: impl FnOnce() -> Value,
+) -> &'a mut Value {
+    let (last, parents) = path.split_last().expect("manifest paths are non-empty");
+    let mut current = value;
+    for key in parents {
+        if !current.is_object() {
+            *current = Value::Object(Map::new());
+        }
+        current = current
+            .as_object_mut()
+            .expect("just ensured object")
+            .entry(*key)
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    if !current.is_object() {
+        *current = Value::Object(Map::new());
+    }
+    current
+        .as_object_mut()
+        .expect("just ensured object")
+        .entry(*last)
+        .or_insert_with(leaf)
+}
+
+/// Version requirement from a Cargo/npm dependency entry
+fn dependency_version(spec: &Value) -> String {
+    match spec {
+        Value::String(version) => version.clone(),
+        Value::Object(table) => table
+            .get("version")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        _ => String::new(),
+    }
+}
+
+/// Split a PEP 508 requirement into (name, rest)
+fn split_requirement(requirement: &str) -> (&str, &str) {
+    let end = requirement
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .unwrap_or(requirement.len());
+    (&requirement[..end], requirement[end..].trim())
+}
+
+/// Compare package names (pyproject names are normalized per PEP 503)
+fn same_package(format: ManifestFormat, a: &str, b: &str) -> bool {
+    match format {
+        ManifestFormat::Pyproject => {
+            let normalize = |s: &str| s.to_ascii_lowercase().replace(['_', '.'], "-");
+            normalize(a) == normalize(b)
+        }
+        ManifestFormat::Cargo | ManifestFormat::Npm => a == b,
+    }
+}
+
+/// Datetime text of a `toml` datetime in a serde document
+fn toml_datetime(map: &Map<String, Value>) -> Option<&str> {
+    match map.len() {
+        1 => map.get(TOML_DATETIME_KEY)?.as_str(),
+        _ => None,
+    }
+}
+
+/// Update `table` to hold `entries`, leaving unchanged items untouched
+///
+/// Stale keys are removed, changed tables and arrays are updated in place
+/// and new keys are appended; `inline` tells whether new nested tables
+/// must be inline.
+fn sync_table(
+    table: &mut dyn TableLike,
+    entries: &Map<String, Value>,
+    inline: bool,
+) -> Result<(), SerializeError> {
+    let stale: Vec<String> = table
+        .iter()
+        .map(|(key, _)| key.to_string())
+        .filter(|key| !entries.contains_key(key))
+        .collect();
+    for key in stale {
+        table.remove(&key);
+    }
+    for (key, value) in entries {
+        let synced = match (table.get_mut(key), value) {
+            (Some(item), value) if same_item(item, value) => true,
+            (Some(Item::Table(nested)), Value::Object(map)) if toml_datetime(map).is_none() => {
+                sync_table(nested, map, false)?;
+                true
+            }
+            (Some(Item::Value(toml_edit::Value::InlineTable(nested))), Value::Object(map))
+                if toml_datetime(map).is_none() =>
+            {
+                sync_table(nested, map, true)?;
+                true
+            }
+            (Some(Item::Value(toml_edit::Value::Array(array))), Value::Array(items)) => {
+                sync_array(array, items)?
+            }
+            _ => false,
+        };
+        if !synced {
+            table.insert(key, to_item(value, inline)?);
+        }
+    }
+    Ok(())
+}
+
+/// Update `array` to hold `items` by dropping and appending elements
+///
+/// Returns `false` if the result is out of order and must be replaced.
+fn sync_array(array: &mut toml_edit::Array, items: &[Value]) -> Result<bool, SerializeError> {
+    array.retain(|element| items.iter().any(|item| same_value(element, item)));
+    for item in items {
+        if !array.iter().any(|element| same_value(element, item)) {
+            let mut element = to_value(item)?;
+            if let Some(last) = array.iter().last() {
+                *element.decor_mut() = last.decor().clone();
+            }
+            array.push_formatted(element);
+        }
+    }
+    Ok(array.len() == items.len() && array.iter().zip(items).all(|(element, item)| same_value(element, item)))
+}
+
+/// Whether a TOML item holds `value`
+fn same_item(item: &Item, value: &Value) -> bool {
+    match (item, value) {
+        (Item::Value(element), value) => same_value(element, value),
+        (Item::Table(table), Value::Object(map)) => same_table(table, map),
+        (Item::ArrayOfTables(tables), Value::Array(items)) => {
+            tables.len() == items.len()
+                && tables.iter().zip(items).all(|(table, item)| {
+                    item.as_object().is_some_and(|map| same_table(table, map))
+                })
+        }
+        _ => false,
+    }
+}
+
+/// Whether a TOML table holds exactly `map`
+fn same_table(table: &toml_edit::Table, map: &Map<String, Value>) -> bool {
+    table.len() == map.len()
+        && map
+            .iter()
+            .all(|(key, value)| table.get(key).is_some_and(|item| same_item(item, value)))
+}
+
+/// Whether a TOML value holds `value`
+fn same_value(element: &toml_edit::Value, value: &Value) -> bool {
+    match (element, value) {
+        (toml_edit::Value::String(s), Value::String(v)) => s.value() == v,
+        (toml_edit::Value::Integer(i), Value::Number(n)) => n.as_i64() == Some(*i.value()),
+        (toml_edit::Value::Float(f), Value::Number(n)) => n.as_f64() == Some(*f.value()),
+        (toml_edit::Value::Boolean(b), Value::Bool(v)) => b.value() == v,
+        (toml_edit::Value::Datetime(d), Value::Object(map)) => {
+            toml_datetime(map) == Some(d.value().to_string().as_str())
+        }
+        (toml_edit::Value::Array(array), Value::Array(items)) => {
+            array.len() == items.len() && array.iter().zip(items).all(|(e, v)| same_value(e, v))
+        }
+        (toml_edit::Value::InlineTable(table), Value::Object(map)) => {
+            table.len() == map.len()
+                && map.iter().all(|(key, value)| table.get(key).is_some_and(|e| same_value(e, value)))
+        }
+        _ => false,
+    }
+}
+
+/// TOML item for a new entry; objects become tables unless `inline`
+fn to_item(value: &Value, inline: bool) -> Result<Item, SerializeError> {
+    match value {
+        Value::Object(map) if !inline && toml_datetime(map).is_none() => {
+            let mut table = toml_edit::Table::new();
+            sync_table(&mut table, map, false)?;
+            Ok(Item::Table(table))
+        }
+        value => Ok(Item::Value(to_value(value)?)),
+    }
+}
+
+/// TOML value for a JSON value
+fn to_value(value: &Value) -> Result<toml_edit::Value, SerializeError> {
+    Ok(match value {
+        Value::String(s) => s.as_str().into(),
+        Value::Bool(b) => (*b).into(),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => i.into(),
+            None => n.as_f64().unwrap_or_default().into(),
+        },
+        Value::Array(items) => items
+            .iter()
+            .map(to_value)
+            .collect::<Result<toml_edit::Array, _>>()?
+            .into(),
+        Value::Object(map) => match toml_datetime(map) {
+            Some(datetime) => datetime
+                .parse::<toml_edit::Datetime>()
+                .map_err(|e| SerializeError::FormatError(e.to_string()))?
+                .into(),
+            None => {
+                let mut table = toml_edit::InlineTable::new();
+                sync_table(&mut table, map, true)?;
+                table.into()
+            }
+        },
+        Value::Null => return Err(SerializeError::FormatError("TOML has no null".to_string())),
+    })
+}
+
+/// Apply a major/minor/patch bump to `major.minor.patch[-pre]`
+fn bump_semver(current: &str, bump: &VersionBump) -> Result<String, TransformError> {
+    let core = current.split(['-', '+']).next().unwrap_or(current);
+    let parts: Vec<u64> = core
+        .split('.')
+        .map(str::parse)
+        .collect::<Result<_, _>>()
+        .map_err(|_| TransformError::InvalidInput(format!("not a semantic version: {current}")))?;
+    let [major, minor, patch] = parts[..] else {
+        return Err(TransformError::InvalidInput(format!(
+            "not a semantic version: {current}"
+        )));
+    };
+
+    Ok(match bump {
+        VersionBump::Major => format!("{}.0.0", major + 1),
+        VersionBump::Minor => format!("{major}.{}.0", minor + 1),
+        VersionBump::Patch => format!("{major}.{minor}.{}", patch + 1),
+        VersionBump::To(version) => version.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coa_composition::{CommutativeBatchStrategy, CompositionStrategy};
+    use coa_symbol::SymbolRefIndex;
+
+    const CARGO: &str = r#"
+[package]
+name = "demo"
+version = "0.3.1"
+
+[dependencies]
+serde = { version = "1", features = ["derive"] }
+
+[features]
+default = []
+fast = []
+"#;
+
+    const PACKAGE_JSON: &str = r#"{
+  "name": "demo",
+  "version": "1.2.3",
+  "dependencies": { "react": "^18.0.0" }
+}"#;
+
+    const PYPROJECT: &str = r#"
+[project]
+name = "demo"
+version = "2.0.0"
+dependencies = ["requests>=2.31", "Pydantic_Core==2.0"]
+
+[project.optional-dependencies]
+cli = ["click"]
+"#;
+
+    fn parse(format: ManifestFormat, source: &str) -> Artifact<ManifestArtifact> {
+        ManifestParser::new(format).parse(source).unwrap()
+    }
+
+    #[test]
+    fn manifest_reads_cargo() {
+        let artifact = parse(ManifestFormat::Cargo, CARGO);
+        let content = artifact.content();
+        assert_eq!(content.version(), Some("0.3.1"));
+        assert_eq!(
+            content
+                .dependency(DependencyKind::Normal, "serde")
+                .as_deref(),
+            Some("1")
+        );
+        assert_eq!(content.features(), vec!["default", "fast"]);
+    }
+
+    #[test]
+    fn manifest_cargo_operations() {
+        let content = parse(ManifestFormat::Cargo, CARGO).content().clone();
+
+        let next = content
+            .apply(&ManifestOp::add_dependency("tokio", "1.35"))
+            .and_then(|c| c.apply(&ManifestOp::bump_version(VersionBump::Minor)))
+            .and_then(|c| c.apply(&ManifestOp::remove_feature("fast")))
+            .unwrap();
+
+        assert_eq!(
+            next.dependency(DependencyKind::Normal, "tokio").as_deref(),
+            Some("1.35")
+        );
+        assert_eq!(next.version(), Some("0.4.0"));
+        assert_eq!(next.features(), vec!["default"]);
+
+        let reparsed = parse(ManifestFormat::Cargo, &next.to_manifest_string().unwrap());
+        assert_eq!(reparsed.content(), &next);
+
+        let duplicate = next.apply(&ManifestOp::add_dependency("serde", "1"));
+        assert!(matches!(duplicate, Err(TransformError::StateConflict(_))));
+    }
+
+    #[test]
+    fn manifest_npm_operations() {
+        let content = parse(ManifestFormat::Npm, PACKAGE_JSON).content().clone();
+        let next = content
+            .apply(&ManifestOp::AddDependency {
+                name: "vitest".to_string(),
+                version: "^1.0.0".to_string(),
+                kind: DependencyKind::Dev,
+            })
+            .and_then(|c| c.apply(&ManifestOp::bump_version(VersionBump::Patch)))
+            .unwrap();
+
+        assert_eq!(
+            next.dependency(DependencyKind::Dev, "vitest").as_deref(),
+            Some("^1.0.0")
+        );
+        assert_eq!(next.version(), Some("1.2.4"));
+        assert!(next
+            .to_manifest_string()
+            .unwrap()
+            .contains("\"devDependencies\""));
+
+        let unsupported = next.apply(&ManifestOp::remove_feature("x"));
+        assert!(matches!(unsupported, Err(TransformError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn manifest_rendering_keeps_layout() {
+        let source = "# Demo crate\n[package]\nname = \"demo\"\nversion = \"0.3.1\" # bumped on release\n\n[dependencies]\nserde = { version = \"1\", features = [\"derive\"] }\n";
+        let next = parse(ManifestFormat::Cargo, source)
+            .content()
+            .apply(&ManifestOp::add_dependency("tokio", "1.35"))
+            .unwrap();
+        assert_eq!(
+            next.to_manifest_string().unwrap(),
+            format!("{source}tokio = \"1.35\"\n")
+        );
+
+        let next = parse(ManifestFormat::Npm, PACKAGE_JSON)
+            .content()
+            .apply(&ManifestOp::bump_version(VersionBump::Patch))
+            .unwrap();
+        let rendered = next.to_manifest_string().unwrap();
+        let position = |key: &str| rendered.find(key).unwrap();
+        assert!(position("\"name\"") < position("\"version\""));
+        assert!(position("\"version\"") < position("\"dependencies\""));
+    }
+
+    #[test]
+    fn manifest_bump_requires_literal_version() {
+        let source = "[package]\nname = \"demo\"\nversion.workspace = true\n";
+        let content = parse(ManifestFormat::Cargo, source).content().clone();
+        let bumped = content.apply(&ManifestOp::bump_version(VersionBump::Patch));
+        assert!(matches!(bumped, Err(TransformError::InvalidInput(_))));
+        assert!(content
+            .apply(&ManifestOp::bump_version(VersionBump::To("1.0.0".to_string())))
+            .is_ok());
+    }
+
+    #[test]
+    fn manifest_pyproject_operations() {
+        let content = parse(ManifestFormat::Pyproject, PYPROJECT)
+            .content()
+            .clone();
+        assert_eq!(
+            content
+                .dependency(DependencyKind::Normal, "pydantic-core")
+                .as_deref(),
+            Some("==2.0")
+        );
+
+        let next = content
+            .apply(&ManifestOp::add_dependency("httpx", ">=0.27"))
+            .and_then(|c| c.apply(&ManifestOp::remove_dependency("requests")))
+            .unwrap();
+        let deps = next.dependencies(DependencyKind::Normal);
+        assert_eq!(deps.get("httpx").map(String::as_str), Some(">=0.27"));
+        assert!(!deps.contains_key("requests"));
+        assert_eq!(next.features(), vec!["cli"]);
+    }
+
+    #[test]
+    fn manifest_parser_matches_file_names() {
+        let cargo = ManifestParser::new(ManifestFormat::Cargo);
+        assert!(cargo.can_parse(Path::new("crates/x/Cargo.toml")));
+        assert!(!cargo.can_parse(Path::new("pyproject.toml")));
+        assert!(!ManifestParser::new(ManifestFormat::Npm).can_parse(Path::new("tsconfig.json")));
+    }
+
+    #[test]
+    fn manifest_dependency_adds_commute() {
+        let base = parse(ManifestFormat::Cargo, CARGO);
+        let deltas = vec![
+            ManifestOp::add_dependency("tokio", "1").into_delta(&base),
+            ManifestOp::add_dependency("anyhow", "1").into_delta(&base),
+        ];
+        assert_eq!(deltas[0].target().to_string(), "dependencies.tokio");
+
+        let strategy = CommutativeBatchStrategy::new();
+        assert!(strategy.validate(&deltas, &SymbolRefIndex::new()).is_ok());
+
+        let with_bump = vec![
+            ManifestOp::add_dependency("tokio", "1").into_delta(&base),
+            ManifestOp::bump_version(VersionBump::Patch).into_delta(&base),
+        ];
+        assert!(strategy
+            .validate(&with_bump, &SymbolRefIndex::new())
+            .is_err());
+    }
+}
@@ -4,7 +4,8 @@
 
 use crate::error::ParseError;
 use crate::parsers::ArtifactParser;
-use coa_artifact::{Artifact, ArtifactType, ContentHash};
+use coa_artifact::{AddressableContent, Artifact, ArtifactType, ContentHash, SymbolPath};
+use coa_symbol::{SymbolKind, SymbolMetadata};
 use pulldown_cmark::{Event, Parser as MdParser, Tag, TagEnd};
 use serde::{Deserialize, Serialize};
 
@@ -21,6 +22,9 @@ pub struct MarkdownContent {
     pub code_blocks: Vec<CodeBlock>,
     /// Frontmatter metadata (if any)
     pub metadata: Option<serde_yaml::Value>,
+    /// Links and symbol references, in document order
+    #[serde(default)]
+    pub links: Vec<SpecLink>,
 }
 
 /// Document section
@@ -47,6 +51,138 @@ pub struct CodeBlock {
     pub context: Option<String>,
 }
 
+/// Reference from a spec to a heading, file or workspace symbol
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SpecLink {
+    /// What the link points at
+    pub kind: LinkKind,
+    /// Relative file path (`File`), workspace symbol (`Symbol`) or empty (`Anchor`)
+    pub target: String,
+    /// Heading anchor, without `#`
+    pub anchor: Option<String>,
+    /// Section containing the link (root if before the first heading)
+    pub section: SymbolPath,
+}
+
+/// Kind of [`SpecLink`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkKind {
+    /// Heading in the same document: `[text](#anchor)`
+    Anchor,
+    /// File relative to the document: `[text](api.md#auth)`
+    File,
+    /// Workspace symbol named in a code fence: `@see src/auth.rs::login`
+    Symbol,
+}
+
+/// Marker introducing a symbol reference inside a code fence
+pub const SEE_MARKER: &str = "@see ";
+
+impl std::fmt::Display for SpecLink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.target)?;
+        match &self.anchor {
+            Some(anchor) => write!(f, "#{anchor}"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl SpecLink {
+    /// Classify a link destination; external URLs yield `None`
+    fn from_destination(destination: &str, section: SymbolPath) -> Option<Self> {
+        if destination.is_empty() || destination.contains("://") || destination.starts_with("mailto:") {
+            return None;
+        }
+        let (target, anchor) = match destination.split_once('#') {
+            Some((target, anchor)) => (target, Some(anchor.to_string())),
+            None => (destination, None),
+        };
+        let kind = if target.is_empty() { LinkKind::Anchor } else { LinkKind::File };
+        Some(Self {
+            kind,
+            target: target.to_string(),
+            anchor,
+            section,
+        })
+    }
+}
+
+impl Section {
+    /// Link anchor for this section: lowercase title, spaces → `-`,
+    /// punctuation other than `-`/`_` dropped
+    #[must_use]
+    pub fn anchor(&self) -> String {
+        self.title
+            .trim()
+            .chars()
+            .filter_map(|c| match c {
+                c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c.to_ascii_lowercase()),
+                c if c.is_whitespace() => Some('-'),
+                _ => None,
+            })
+            .collect()
+    }
+
+
+    /// Path segment for this section: lowercase title, non-alphanumerics → `_`
+    #[must_use]
+    pub fn slug(&self) -> String {
+        let slug: String = self
+            .title
+            .trim()
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+            .collect();
+        slug.trim_matches('_').to_string()
+    }
+}
+
+impl AddressableContent for MarkdownContent {
+    /// Nested section slugs, e.g. `api.authentication`
+    fn symbol_paths(&self) -> Vec<SymbolPath> {
+        fn walk(sections: &[Section], prefix: &SymbolPath, out: &mut Vec<SymbolPath>) {
+            for section in sections {
+                let path = prefix.child(section.slug());
+                out.push(path.clone());
+                walk(&section.children, &path, out);
+            }
+        }
+
+        let mut paths = Vec::new();
+        walk(&self.sections, &SymbolPath::root(), &mut paths);
+        paths
+    }
+}
+
+impl MarkdownContent {
+    /// Section anchors with their symbol paths, in document order
+    #[must_use]
+    pub fn anchors(&self) -> Vec<(String, SymbolPath)> {
+        fn walk(sections: &[Section], prefix: &SymbolPath, out: &mut Vec<(String, SymbolPath)>) {
+            for section in sections {
+                let path = prefix.child(section.slug());
+                out.push((section.anchor(), path.clone()));
+                walk(&section.children, &path, out);
+            }
+        }
+
+        let mut anchors = Vec::new();
+        walk(&self.sections, &SymbolPath::root(), &mut anchors);
+        anchors
+    }
+
+    /// Path of the section with link anchor `anchor`
+    #[must_use]
+    pub fn resolve_anchor(&self, anchor: &str) -> Option<SymbolPath> {
+        self.anchors()
+            .into_iter()
+            .find(|(candidate, _)| candidate.eq_ignore_ascii_case(anchor))
+            .map(|(_, path)| path)
+    }
+}
+
 /// Markdown spec artifact type
 #[derive(Debug, Clone)]
 pub struct MarkdownArtifact;
@@ -61,8 +197,16 @@ impl ArtifactType for MarkdownArtifact {
     }
 
     const TYPE_ID: &'static str = "markdown";
+
+    fn estimated_size(content: &Self::Content) -> usize {
+        // Sections and code blocks are slices of the source, roughly doubling it
+        std::mem::size_of::<Self::Content>() + 2 * content.source.len()
+    }
 }
 
+/// Symbol attribute prefix naming the link anchor of a spec section
+pub const ANCHOR_ATTRIBUTE: &str = "anchor:";
+
 /// Markdown parser
 #[derive(Debug, Clone, Copy, Default)]
 pub struct MarkdownParser;
@@ -100,6 +244,9 @@ impl MarkdownParser {
         let mut in_code_block = false;
         let mut current_code: Option<(Option<String>, String)> = None;
         let mut current_heading: Option<String> = None;
+        // Links found so far, with the index of the heading they follow
+        let mut links: Vec<(Option<usize>, SpecLink)> = Vec::new();
+        let mut headings = 0usize;
 
         for event in parser {
             match event {
@@ -121,18 +268,26 @@ impl MarkdownParser {
                     if let Some(ref mut section) = current_section {
                         current_heading = Some(section.title.clone());
                     }
+                    headings += 1;
+                }
+                Event::Start(Tag::Link { dest_url, .. }) => {
+                    let heading = headings.checked_sub(1);
+                    if let Some(link) = SpecLink::from_destination(&dest_url, SymbolPath::root()) {
+                        links.push((heading, link));
+                    }
                 }
                 Event::Text(text) => {
+                    if in_code_block {
+                        if let Some((_, ref mut code)) = current_code {
+                            code.push_str(&text);
+                        }
+                    }
                     if let Some(ref mut section) = current_section {
                         if section.title.is_empty() {
                             section.title = text.to_string();
                         } else {
                             section.content.push_str(&text);
                         }
-                    } else if in_code_block {
-                        if let Some((_, ref mut code)) = current_code {
-                            code.push_str(&text);
-                        }
                     }
                 }
                 Event::Code(code) => {
@@ -159,6 +314,8 @@ impl MarkdownParser {
                 Event::End(TagEnd::CodeBlock) => {
                     in_code_block = false;
                     if let Some((lang, code)) = current_code.take() {
+                        let heading = headings.checked_sub(1);
+                        links.extend(Self::symbol_references(&code).map(|link| (heading, link)));
                         code_blocks.push(CodeBlock {
                             language: lang,
                             code,
@@ -181,13 +338,44 @@ impl MarkdownParser {
             .find(|s| s.level == 1)
             .map(|s| s.title.clone());
 
-        MarkdownContent {
+        let mut content = MarkdownContent {
             source: content.to_string(),
             title,
             sections,
             code_blocks,
             metadata: None, // Set by caller after frontmatter extraction
-        }
+            links: Vec::new(),
+        };
+
+        // Sections are walked in document order, so heading `i` is path `i`
+        let paths = content.symbol_paths();
+        content.links = links
+            .into_iter()
+            .map(|(heading, link)| SpecLink {
+                section: heading
+                    .and_then(|i| paths.get(i).cloned())
+                    .unwrap_or_else(SymbolPath::root),
+                ..link
+            })
+            .collect();
+        content
+    }
+
+    /// `@see <symbol>` references in a code fence
+    fn symbol_references(code: &str) -> impl Iterator<Item = SpecLink> + '_ {
+        code.match_indices(SEE_MARKER).filter_map(|(at, marker)| {
+            let target: String = code[at + marker.len()..]
+                .chars()
+                .take_while(|c| !c.is_whitespace() && !matches!(c, ',' | ';' | ')' | '`'))
+                .collect();
+            let target = target.trim_end_matches('.');
+            (!target.is_empty()).then(|| SpecLink {
+                kind: LinkKind::Symbol,
+                target: target.to_string(),
+                anchor: None,
+                section: SymbolPath::root(),
+            })
+        })
     }
 
     /// Push section to appropriate parent
@@ -221,6 +409,31 @@ impl ArtifactParser for MarkdownParser {
     fn extensions(&self) -> &[&str] {
         &["md", "markdown"]
     }
+
+    fn symbols(&self, artifact: &Artifact<Self::Output>) -> Vec<SymbolPath> {
+        artifact.content().symbol_paths()
+    }
+
+    /// Spec kind, with the link anchors of the section and its ancestors
+    /// (outermost first) as [`ANCHOR_ATTRIBUTE`] attributes
+    ///
+    /// Only leaf sections get indexed, so this keeps every heading of a file
+    /// resolvable from the index.
+    fn symbol_metadata(&self, artifact: &Artifact<Self::Output>, symbol: &SymbolPath) -> SymbolMetadata {
+        let anchors = artifact.content().anchors();
+        let attributes = (1..=symbol.len())
+            .filter_map(|depth| {
+                let prefix = SymbolPath::new(symbol.segments()[..depth].to_vec());
+                anchors.iter().find(|(_, path)| *path == prefix)
+            })
+            .map(|(anchor, _)| format!("{ANCHOR_ATTRIBUTE}{anchor}"))
+            .collect();
+        SymbolMetadata {
+            kind: SymbolKind::Spec,
+            attributes,
+            ..SymbolMetadata::default()
+        }
+    }
 }
 
 #[cfg(test)]
@@ -247,6 +460,23 @@ More content.
         assert!(!artifact.content().sections.is_empty());
     }
 
+    #[test]
+    fn markdown_sections_are_addressable() {
+        let content = "# API Spec\n\n## Authentication\n\ntext\n\n## Rate Limits\n";
+        let artifact = MarkdownParser.parse(content).unwrap();
+
+        let paths: Vec<String> = artifact
+            .content()
+            .symbol_paths()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            paths,
+            vec!["api_spec", "api_spec.authentication", "api_spec.rate_limits"]
+        );
+    }
+
     #[test]
     fn markdown_parser_with_code() {
         let parser = MarkdownParser;
@@ -305,6 +535,28 @@ Content.
         assert!(artifact.content().title.is_none());
     }
 
+    #[test]
+    fn markdown_links_and_symbol_references_are_extracted() {
+        let content = "# API Spec\n\nSee [limits](#rate-limits) and [guide](../guide.md#setup).\n\n\
+                       ## Rate Limits\n\n[site](https://example.com)\n\n```rust\n// @see src/auth.rs::login\n```\n";
+        let artifact = MarkdownParser.parse(content).unwrap();
+        let links = &artifact.content().links;
+
+        assert_eq!(links.len(), 3);
+        assert_eq!(links[0].kind, LinkKind::Anchor);
+        assert_eq!(links[0].section.to_string(), "api_spec");
+        assert_eq!(links[1].kind, LinkKind::File);
+        assert_eq!(links[1].to_string(), "../guide.md#setup");
+        assert_eq!(links[2].kind, LinkKind::Symbol);
+        assert_eq!(links[2].target, "src/auth.rs::login");
+        assert_eq!(links[2].section.to_string(), "api_spec.rate_limits");
+
+        let resolved = artifact.content().resolve_anchor("rate-limits").unwrap();
+        assert_eq!(resolved.to_string(), "api_spec.rate_limits");
+        let metadata = MarkdownParser.symbol_metadata(&artifact, &resolved);
+        assert_eq!(metadata.attributes, vec!["anchor:api-spec", "anchor:rate-limits"]);
+    }
+
     #[test]
     fn markdown_artifact_type_id() {
         assert_eq!(MarkdownArtifact::TYPE_ID, "markdown");
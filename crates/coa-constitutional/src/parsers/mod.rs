@@ -4,19 +4,41 @@
 //! - Code files (Rust, TypeScript, Python)
 //! - Config files (JSON, YAML) via serde
 //! - Spec files (Markdown) via pulldown-cmark
+//! - SQL schemas via sqlparser
+//! - Dependency manifests (Cargo.toml, package.json, pyproject.toml)
 
 use crate::error::ParseError;
-use coa_artifact::{Artifact, ArtifactType};
+use coa_artifact::{Artifact, ArtifactType, ContentHash, SymbolPath};
+use coa_symbol::SymbolMetadata;
+use std::any::Any;
 use std::path::Path;
+use std::sync::Arc;
 
 mod code;
 mod json;
+mod k8s;
+mod manifest;
 mod markdown;
+mod sql;
 mod yaml;
 
-pub use code::{CodeParser, CodeArtifact, CodeContent, Language};
+pub use code::{
+    CodeArtifact, CodeContent, CodeParser, Language, PartialCode, SyntaxError, APPEND_MARKER,
+};
 pub use json::{JsonParser, JsonArtifact, JsonContent};
-pub use markdown::{MarkdownParser, MarkdownArtifact, MarkdownContent};
+pub use k8s::{
+    K8sClassifier, K8sManifestArtifact, K8sManifestContent, K8sManifestParser, K8sResource, K8sSchemaSet,
+    ResourceError, ResourceId, CLUSTER_SCOPE, DEFAULT_NAMESPACE,
+};
+pub use manifest::{
+    DependencyKind, ManifestArtifact, ManifestContent, ManifestFormat, ManifestOp, ManifestParser,
+    VersionBump,
+};
+pub use markdown::{
+    LinkKind, MarkdownArtifact, MarkdownContent, MarkdownParser, Section, SpecLink, ANCHOR_ATTRIBUTE,
+    SEE_MARKER,
+};
+pub use sql::{SqlArtifact, SqlContent, SqlDialect, SqlObject, SqlObjectKind, SqlParser, DEFAULT_SCHEMA};
 pub use yaml::{YamlParser, YamlArtifact, YamlContent};
 
 /// Parser trait for converting file content into typed artifacts
@@ -44,6 +66,67 @@ pub trait ArtifactParser: Send + Sync + 'static {
     fn priority(&self) -> i32 {
         0
     }
+
+    /// Addressable symbols of a parsed artifact (for indexing on ingress)
+    ///
+    /// Defaults to none; parsers whose content is addressable should
+    /// return its [`symbol_paths`](coa_artifact::AddressableContent::symbol_paths).
+    fn symbols(&self, _artifact: &Artifact<Self::Output>) -> Vec<SymbolPath> {
+        Vec::new()
+    }
+
+    /// Index metadata for one of the artifact's [`symbols`](Self::symbols)
+    ///
+    /// Source locations carry line and column only; ingress fills in the
+    /// file. Defaults to [`SymbolMetadata::default`].
+    fn symbol_metadata(&self, _artifact: &Artifact<Self::Output>, _symbol: &SymbolPath) -> SymbolMetadata {
+        SymbolMetadata::default()
+    }
+}
+
+/// Parsed artifact with its concrete type erased
+///
+/// Produced by [`DynArtifactParser::parse_erased`] so files can be parsed
+/// without knowing their artifact type up front.
+#[derive(Clone)]
+pub struct ErasedArtifact {
+    /// Artifact content hash
+    pub hash: ContentHash,
+    /// Artifact type identifier
+    pub type_id: &'static str,
+    /// Estimated in-memory size
+    pub bytes: usize,
+    /// Addressable symbols
+    pub symbols: Vec<SymbolPath>,
+    /// Index metadata of each symbol, in the same order as `symbols`
+    pub symbol_metadata: Vec<SymbolMetadata>,
+    artifact: Arc<dyn Any + Send + Sync>,
+}
+
+impl ErasedArtifact {
+    /// Recover the typed artifact, if it is a `T`
+    #[inline]
+    #[must_use]
+    pub fn downcast<T: ArtifactType>(&self) -> Option<&Artifact<T>> {
+        self.artifact.downcast_ref()
+    }
+
+    /// Shared handle to the typed artifact
+    #[inline]
+    pub(crate) fn shared(&self) -> Arc<dyn Any + Send + Sync> {
+        Arc::clone(&self.artifact)
+    }
+}
+
+impl std::fmt::Debug for ErasedArtifact {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ErasedArtifact")
+            .field("hash", &self.hash)
+            .field("type_id", &self.type_id)
+            .field("bytes", &self.bytes)
+            .field("symbols", &self.symbols.len())
+            .finish()
+    }
 }
 
 /// Parser registration for dynamic parser management
@@ -78,6 +161,7 @@ pub trait DynArtifactParser: Send + Sync {
     fn can_parse(&self, path: &Path) -> bool;
     fn priority(&self) -> i32;
     fn extensions(&self) -> &[&str];
+    fn parse_erased(&self, content: &str) -> Result<ErasedArtifact, ParseError>;
 }
 
 impl<P> DynArtifactParser for P
@@ -95,6 +179,22 @@ where
     fn extensions(&self) -> &[&str] {
         ArtifactParser::extensions(self)
     }
+
+    fn parse_erased(&self, content: &str) -> Result<ErasedArtifact, ParseError> {
+        let artifact = self.parse(content)?;
+        let symbols = self.symbols(&artifact);
+        Ok(ErasedArtifact {
+            hash: *artifact.hash(),
+            type_id: P::Output::TYPE_ID,
+            bytes: artifact.estimated_size(),
+            symbol_metadata: symbols
+                .iter()
+                .map(|symbol| self.symbol_metadata(&artifact, symbol))
+                .collect(),
+            symbols,
+            artifact: Arc::new(artifact),
+        })
+    }
 }
 
 impl ParserRegistry {
@@ -146,13 +246,39 @@ pub fn default_parsers() -> ParserRegistry {
     // Config parsers
     registry.register(JsonParser);
     registry.register(YamlParser);
+    registry.register(K8sManifestParser);
 
     // Spec parsers
     registry.register(MarkdownParser);
 
+    // Schema parsers
+    registry.register(SqlParser::default());
+
+    // Dependency manifests
+    registry.register(ManifestParser::new(ManifestFormat::Cargo));
+    registry.register(ManifestParser::new(ManifestFormat::Npm));
+    registry.register(ManifestParser::new(ManifestFormat::Pyproject));
+
     registry
 }
 
+/// Copy of `value` with object keys in sorted order
+///
+/// JSON maps keep document order; hashes go through this so they do not
+/// depend on it.
+pub(crate) fn sorted_keys(value: &serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            Value::Object(entries.into_iter().map(|(k, v)| (k.clone(), sorted_keys(v))).collect())
+        }
+        Value::Array(items) => Value::Array(items.iter().map(sorted_keys).collect()),
+        other => other.clone(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
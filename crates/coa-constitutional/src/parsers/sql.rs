@@ -0,0 +1,529 @@
+//! SQL schema parser
+//!
+//! Uses sqlparser-rs to split DDL into statement-level objects. Every
+//! schema, table, view, index, function, sequence and type becomes an
+//! addressable symbol, so migration-writing agents can target e.g.
+//! `public.users.index_email` and have overlaps caught by `SymbolRefIndex`.
+//!
+//! # Symbol Paths
+//! - `CREATE SCHEMA s` → `s`
+//! - `CREATE TABLE s.t` → `s.t` (unqualified names use `public`)
+//! - `CREATE INDEX i ON s.t` → `s.t.i`
+//!
+//! Unquoted identifiers are lowercased; quoted identifiers are kept verbatim.
+
+use crate::error::ParseError;
+use crate::parsers::ArtifactParser;
+use coa_artifact::{
+    define_artifact_type, AddressableContent, Artifact, ArtifactError, ContentHash, HashAlgorithm,
+    SymbolPath,
+};
+use coa_composition::ComposableArtifact;
+use coa_symbol::{SymbolKind, SymbolMetadata, SymbolRef};
+use serde::{Deserialize, Serialize};
+use sqlparser::ast::{Ident, ObjectName, SchemaName, Statement};
+use sqlparser::dialect::{Dialect, GenericDialect, MySqlDialect, PostgreSqlDialect, SQLiteDialect};
+use std::collections::HashSet;
+
+/// Schema assumed for unqualified object names
+pub const DEFAULT_SCHEMA: &str = "public";
+
+/// SQL dialect used for parsing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum SqlDialect {
+    /// ANSI-ish generic dialect
+    #[default]
+    Generic,
+    /// PostgreSQL
+    PostgreSql,
+    /// MySQL
+    MySql,
+    /// SQLite
+    Sqlite,
+}
+
+impl SqlDialect {
+    /// Get human-readable name
+    #[inline]
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            SqlDialect::Generic => "generic",
+            SqlDialect::PostgreSql => "postgresql",
+            SqlDialect::MySql => "mysql",
+            SqlDialect::Sqlite => "sqlite",
+        }
+    }
+
+    fn parser_dialect(self) -> Box<dyn Dialect> {
+        match self {
+            SqlDialect::Generic => Box::new(GenericDialect {}),
+            SqlDialect::PostgreSql => Box::new(PostgreSqlDialect {}),
+            SqlDialect::MySql => Box::new(MySqlDialect {}),
+            SqlDialect::Sqlite => Box::new(SQLiteDialect {}),
+        }
+    }
+}
+
+/// Kind of addressable SQL object
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SqlObjectKind {
+    /// `CREATE SCHEMA`
+    Schema,
+    /// `CREATE TABLE`
+    Table,
+    /// `CREATE [MATERIALIZED] VIEW`
+    View,
+    /// `CREATE INDEX`
+    Index,
+    /// `CREATE FUNCTION`
+    Function,
+    /// `CREATE SEQUENCE`
+    Sequence,
+    /// `CREATE TYPE`
+    Type,
+}
+
+/// A single addressable SQL object and the statement defining it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SqlObject {
+    /// Symbol path of the object
+    pub path: SymbolPath,
+    /// Object kind
+    pub kind: SqlObjectKind,
+    /// Normalized defining statement (without trailing `;`)
+    pub statement: String,
+}
+
+/// Parsed SQL content
+///
+/// Objects are kept in source order since DDL is order-sensitive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SqlContent {
+    /// Dialect the source was parsed with
+    pub dialect: SqlDialect,
+    /// Addressable objects in source order
+    pub objects: Vec<SqlObject>,
+    /// Non-DDL statements (DML, grants, ...) in source order
+    pub other_statements: Vec<String>,
+}
+
+impl SqlContent {
+    /// Create empty content for dialect
+    #[inline]
+    #[must_use]
+    pub fn new(dialect: SqlDialect) -> Self {
+        Self {
+            dialect,
+            objects: Vec::new(),
+            other_statements: Vec::new(),
+        }
+    }
+
+    /// Get object at path
+    #[must_use]
+    pub fn get(&self, path: &SymbolPath) -> Option<&SqlObject> {
+        self.objects.iter().find(|o| &o.path == path)
+    }
+
+    /// Check if an object exists at path
+    #[inline]
+    #[must_use]
+    pub fn contains(&self, path: &SymbolPath) -> bool {
+        self.get(path).is_some()
+    }
+
+    /// Iterate over objects of a given kind
+    pub fn objects_of_kind(&self, kind: SqlObjectKind) -> impl Iterator<Item = &SqlObject> {
+        self.objects.iter().filter(move |o| o.kind == kind)
+    }
+
+    /// Paths of declared objects in source order
+    #[must_use]
+    pub fn object_paths(&self) -> Vec<SymbolPath> {
+        self.objects.iter().map(|o| o.path.clone()).collect()
+    }
+
+    /// Symbol references bound to the given artifact hash
+    #[must_use]
+    pub fn symbol_refs(&self, parent_hash: ContentHash) -> Vec<SymbolRef> {
+        self.objects
+            .iter()
+            .map(|o| SymbolRef::new(o.path.segments().to_vec(), parent_hash))
+            .collect()
+    }
+
+    /// Render back to SQL text (objects first, then other statements)
+    #[must_use]
+    pub fn to_sql(&self) -> String {
+        self.objects
+            .iter()
+            .map(|o| o.statement.as_str())
+            .chain(self.other_statements.iter().map(String::as_str))
+            .map(|s| format!("{s};\n"))
+            .collect()
+    }
+}
+
+impl AddressableContent for SqlContent {
+    /// Object paths plus implied schemas (e.g. `public`)
+    fn symbol_paths(&self) -> Vec<SymbolPath> {
+        let mut paths: Vec<SymbolPath> = Vec::new();
+        for object in &self.objects {
+            if let Some(schema) = object.path.first() {
+                let schema = SymbolPath::single(schema);
+                if !paths.contains(&schema) {
+                    paths.push(schema);
+                }
+            }
+            if !paths.contains(&object.path) {
+                paths.push(object.path.clone());
+            }
+        }
+        paths
+    }
+
+    fn resolves(&self, path: &SymbolPath) -> bool {
+        match path.len() {
+            0 => true,
+            1 => self.objects.iter().any(|o| o.path.first() == path.first()),
+            _ => self.contains(path),
+        }
+    }
+}
+
+define_artifact_type! {
+    /// SQL artifact type
+    pub SqlArtifact {
+        content: SqlContent,
+        type_id: "sql",
+        hash: hash_sql,
+        validate: validate_sql,
+        size: |content| std::mem::size_of::<SqlContent>() + content.to_sql().len(),
+    }
+}
+
+fn hash_sql(content: &SqlContent) -> ContentHash {
+    let mut hasher = HashAlgorithm::current().hasher();
+    hasher.update(content.dialect.name().as_bytes());
+    hasher.update(content.to_sql().as_bytes());
+    hasher.finalize()
+}
+
+fn validate_sql(content: &SqlContent) -> Result<(), ArtifactError> {
+    let mut seen = HashSet::new();
+    for object in &content.objects {
+        if !seen.insert(&object.path) {
+            return Err(ArtifactError::InvariantViolation(format!(
+                "duplicate SQL object: {}",
+                object.path
+            )));
+        }
+    }
+    Ok(())
+}
+
+impl ComposableArtifact for SqlArtifact {
+    const DEFAULT_STRATEGY: &'static str = "single_writer";
+}
+
+/// SQL parser
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SqlParser {
+    dialect: SqlDialect,
+}
+
+impl SqlParser {
+    /// Create new parser for dialect
+    #[inline]
+    #[must_use]
+    pub fn new(dialect: SqlDialect) -> Self {
+        Self { dialect }
+    }
+
+    /// Get parser dialect
+    #[inline]
+    #[must_use]
+    pub fn dialect(&self) -> SqlDialect {
+        self.dialect
+    }
+
+    /// Classify a statement into an addressable object (if it is one)
+    fn classify(statement: &Statement) -> Option<(SymbolPath, SqlObjectKind)> {
+        match statement {
+            Statement::CreateSchema { schema_name, .. } => Some((
+                SymbolPath::single(schema_segment(schema_name)),
+                SqlObjectKind::Schema,
+            )),
+            Statement::CreateTable(table) => Some((object_path(&table.name), SqlObjectKind::Table)),
+            Statement::CreateView { name, .. } => Some((object_path(name), SqlObjectKind::View)),
+            Statement::CreateIndex(index) => {
+                let table = object_path(&index.table_name);
+                let name = match &index.name {
+                    Some(name) => name.0.last().map_or_else(String::new, ident_segment),
+                    None => {
+                        let columns: Vec<_> = index
+                            .columns
+                            .iter()
+                            .map(|c| sanitize(&c.to_string()))
+                            .collect();
+                        format!(
+                            "{}_{}_idx",
+                            table.last().unwrap_or_default(),
+                            columns.join("_")
+                        )
+                    }
+                };
+                Some((table.child(name), SqlObjectKind::Index))
+            }
+            Statement::CreateFunction(function) => {
+                Some((object_path(&function.name), SqlObjectKind::Function))
+            }
+            Statement::CreateSequence { name, .. } => {
+                Some((object_path(name), SqlObjectKind::Sequence))
+            }
+            Statement::CreateType { name, .. } => Some((object_path(name), SqlObjectKind::Type)),
+            _ => None,
+        }
+    }
+}
+
+/// Convert a (possibly qualified) object name to `schema.name`
+fn object_path(name: &ObjectName) -> SymbolPath {
+    let mut segments: Vec<String> = name.0.iter().map(ident_segment).collect();
+    if segments.len() == 1 {
+        segments.insert(0, DEFAULT_SCHEMA.to_string());
+    }
+    SymbolPath::new(segments)
+}
+
+/// Schema name segment; `AUTHORIZATION`-only schemas are named after the user
+fn schema_segment(name: &SchemaName) -> String {
+    match name {
+        SchemaName::Simple(name) | SchemaName::NamedAuthorization(name, _) => {
+            name.0.last().map_or_else(String::new, ident_segment)
+        }
+        SchemaName::UnnamedAuthorization(user) => ident_segment(user),
+    }
+}
+
+/// Normalize identifier: unquoted → lowercase, quoted → verbatim
+fn ident_segment(ident: &Ident) -> String {
+    if ident.quote_style.is_some() {
+        ident.value.clone()
+    } else {
+        ident.value.to_lowercase()
+    }
+}
+
+/// Replace non-identifier characters for generated names
+fn sanitize(raw: &str) -> String {
+    raw.chars()
+        .map(|c| {
+            if c.is_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+impl ArtifactParser for SqlParser {
+    type Output = SqlArtifact;
+
+    fn parse(&self, content: &str) -> Result<Artifact<Self::Output>, ParseError> {
+        let dialect = self.dialect.parser_dialect();
+        let statements =
+            sqlparser::parser::Parser::parse_sql(dialect.as_ref(), content).map_err(|e| {
+                ParseError::SyntaxError {
+                    path: std::path::PathBuf::from("input.sql"),
+                    message: format!("SQL parse error: {e}"),
+                }
+            })?;
+
+        let mut sql_content = SqlContent::new(self.dialect);
+        for statement in &statements {
+            match Self::classify(statement) {
+                Some((path, kind)) => sql_content.objects.push(SqlObject {
+                    path,
+                    kind,
+                    statement: statement.to_string(),
+                }),
+                None => sql_content.other_statements.push(statement.to_string()),
+            }
+        }
+
+        Artifact::new(sql_content)
+            .map_err(|e| ParseError::ValidationError(format!("artifact creation failed: {e}")))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["sql"]
+    }
+
+    fn symbols(&self, artifact: &Artifact<Self::Output>) -> Vec<SymbolPath> {
+        artifact.content().symbol_paths()
+    }
+
+    fn symbol_metadata(
+        &self,
+        artifact: &Artifact<Self::Output>,
+        symbol: &SymbolPath,
+    ) -> SymbolMetadata {
+        let object = artifact
+            .content()
+            .objects
+            .iter()
+            .find(|o| o.path == *symbol);
+        let kind = match object.map(|o| o.kind) {
+            // Bare schema paths are implied by their objects
+            None | Some(SqlObjectKind::Schema) => SymbolKind::Module,
+            Some(SqlObjectKind::Table | SqlObjectKind::View | SqlObjectKind::Type) => {
+                SymbolKind::Type
+            }
+            Some(SqlObjectKind::Function) => SymbolKind::Function,
+            Some(SqlObjectKind::Sequence) => SymbolKind::Variable,
+            Some(SqlObjectKind::Index) => SymbolKind::Unknown,
+        };
+        SymbolMetadata {
+            kind,
+            content_hash: object.map(|o| ContentHash::compute(o.statement.as_bytes())),
+            ..SymbolMetadata::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coa_artifact::ArtifactType;
+    use coa_symbol::{SymbolMetadata, SymbolRefError, SymbolRefIndex};
+    use std::str::FromStr;
+
+    const SCHEMA: &str = r#"
+        CREATE SCHEMA billing;
+        CREATE TABLE users (id INT PRIMARY KEY, email TEXT NOT NULL);
+        CREATE INDEX index_email ON users (email);
+        CREATE TABLE billing."Invoices" (id INT, user_id INT);
+        CREATE VIEW active_users AS SELECT id FROM users;
+        INSERT INTO users VALUES (1, 'a@example.com');
+    "#;
+
+    fn path(s: &str) -> SymbolPath {
+        SymbolPath::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn sql_parser_extracts_objects() {
+        let artifact = SqlParser::new(SqlDialect::PostgreSql)
+            .parse(SCHEMA)
+            .unwrap();
+        let content = artifact.content();
+
+        assert_eq!(
+            content.object_paths(),
+            vec![
+                path("billing"),
+                path("public.users"),
+                path("public.users.index_email"),
+                path("billing.Invoices"),
+                path("public.active_users"),
+            ]
+        );
+        assert_eq!(
+            content.get(&path("public.users.index_email")).unwrap().kind,
+            SqlObjectKind::Index
+        );
+        assert_eq!(content.objects_of_kind(SqlObjectKind::Table).count(), 2);
+        assert_eq!(content.other_statements.len(), 1);
+    }
+
+    #[test]
+    fn sql_parser_names_unnamed_index() {
+        let sql = "CREATE TABLE t (a INT, b INT); CREATE INDEX ON t (a, b);";
+        let artifact = SqlParser::new(SqlDialect::PostgreSql).parse(sql).unwrap();
+        assert!(artifact.content().contains(&path("public.t.t_a_b_idx")));
+    }
+
+    #[test]
+    fn sql_schema_names_match_table_qualifiers() {
+        let sql =
+            r#"CREATE SCHEMA Billing; CREATE TABLE Billing.t (a INT); CREATE SCHEMA "Audit";"#;
+        let artifact = SqlParser::new(SqlDialect::PostgreSql).parse(sql).unwrap();
+        assert_eq!(
+            artifact.content().object_paths(),
+            vec![path("billing"), path("billing.t"), path("Audit")]
+        );
+    }
+
+    #[test]
+    fn sql_parser_invalid() {
+        let result = SqlParser::default().parse("CREATE TABLE (");
+        assert!(matches!(result, Err(ParseError::SyntaxError { .. })));
+    }
+
+    #[test]
+    fn sql_parser_rejects_duplicates() {
+        let sql = "CREATE TABLE t (a INT); CREATE TABLE T (b INT);";
+        let result = SqlParser::default().parse(sql);
+        assert!(matches!(result, Err(ParseError::ValidationError(_))));
+    }
+
+    #[test]
+    fn sql_artifact_hash_deterministic() {
+        let a = SqlParser::default()
+            .parse("CREATE TABLE t (a INT)")
+            .unwrap();
+        let b = SqlParser::default()
+            .parse("CREATE   TABLE t\n  (a INT);")
+            .unwrap();
+        assert_eq!(a.hash(), b.hash());
+    }
+
+    #[test]
+    fn sql_symbols_detect_overlap() {
+        let artifact = SqlParser::new(SqlDialect::PostgreSql)
+            .parse(SCHEMA)
+            .unwrap();
+        let index = SymbolRefIndex::new();
+
+        let refs = artifact.content().symbol_refs(*artifact.hash());
+        let table = refs
+            .iter()
+            .find(|r| r.path() == ["public", "users"])
+            .unwrap();
+        let email = refs
+            .iter()
+            .find(|r| r.name() == Some("index_email"))
+            .unwrap();
+
+        index
+            .insert(table.clone(), SymbolMetadata::default())
+            .unwrap();
+        let result = index.insert(email.clone(), SymbolMetadata::default());
+        assert!(matches!(
+            result,
+            Err(SymbolRefError::OverlappingClaims { .. })
+        ));
+    }
+
+    #[test]
+    fn sql_content_resolves_implied_schema() {
+        let artifact = SqlParser::new(SqlDialect::PostgreSql)
+            .parse(SCHEMA)
+            .unwrap();
+        let content = artifact.content();
+
+        assert!(content.resolves(&path("public")));
+        assert!(content.resolves(&path("public.users.index_email")));
+        assert!(!content.resolves(&path("public.orders")));
+        assert!(content.symbol_paths().contains(&path("public")));
+    }
+
+    #[test]
+    fn sql_parser_extensions() {
+        assert_eq!(SqlParser::default().extensions(), &["sql"]);
+        assert_eq!(SqlArtifact::TYPE_ID, "sql");
+    }
+}
@@ -7,7 +7,8 @@
 
 use crate::error::ParseError;
 use crate::parsers::ArtifactParser;
-use coa_artifact::{Artifact, ArtifactType, ContentHash};
+use coa_artifact::{AddressableContent, Artifact, ArtifactType, ContentHash, SymbolPath};
+use coa_symbol::{SymbolKind, SymbolMetadata};
 use serde::{Deserialize, Serialize};
 use serde_yaml::Value;
 use std::collections::HashMap;
@@ -143,6 +144,39 @@ fn merge_values(base: Value, override_val: Value) -> Value {
     }
 }
 
+impl AddressableContent for YamlContent {
+    /// Paths within the first document (string keys and sequence indices)
+    fn symbol_paths(&self) -> Vec<SymbolPath> {
+        let mut paths = Vec::new();
+        if let Some(doc) = self.first() {
+            collect_yaml_paths(doc, &SymbolPath::root(), &mut paths);
+        }
+        paths
+    }
+}
+
+/// Collect mapping keys and sequence indices as paths (depth-first)
+pub(super) fn collect_yaml_paths(value: &Value, prefix: &SymbolPath, out: &mut Vec<SymbolPath>) {
+    match value {
+        Value::Mapping(map) => {
+            for (key, child) in map {
+                let Some(key) = key.as_str() else { continue };
+                let path = prefix.child(key);
+                out.push(path.clone());
+                collect_yaml_paths(child, &path, out);
+            }
+        }
+        Value::Sequence(items) => {
+            for (i, child) in items.iter().enumerate() {
+                let path = prefix.child(i.to_string());
+                out.push(path.clone());
+                collect_yaml_paths(child, &path, out);
+            }
+        }
+        _ => {}
+    }
+}
+
 /// YAML config artifact type
 #[derive(Debug, Clone)]
 pub struct YamlArtifact;
@@ -159,6 +193,11 @@ impl ArtifactType for YamlArtifact {
     }
 
     const TYPE_ID: &'static str = "yaml";
+
+    fn estimated_size(content: &Self::Content) -> usize {
+        std::mem::size_of::<Self::Content>()
+            + serde_yaml::to_string(&content.documents).map_or(0, |s| s.len())
+    }
 }
 
 /// YAML parser
@@ -222,6 +261,17 @@ impl ArtifactParser for YamlParser {
     fn extensions(&self) -> &[&str] {
         &["yaml", "yml"]
     }
+
+    fn symbols(&self, artifact: &Artifact<Self::Output>) -> Vec<SymbolPath> {
+        artifact.content().symbol_paths()
+    }
+
+    fn symbol_metadata(&self, _artifact: &Artifact<Self::Output>, _symbol: &SymbolPath) -> SymbolMetadata {
+        SymbolMetadata {
+            kind: SymbolKind::Config,
+            ..SymbolMetadata::default()
+        }
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,532 @@
+//! Patch egress: composed changes as unified diffs
+//!
+//! Instead of overwriting files, a [`ChangeSet`] records each file's content
+//! before and after composition and renders it for review:
+//!
+//! - [`ChangeSet::diff`]: a git-style unified diff
+//! - [`ChangeSet::format_patch`] / [`write_patch_series`]: mailbox patches
+//!   that `git am` applies as commits
+//! - [`ForgeClient`](crate::forge::ForgeClient) (feature `forge`): a pull
+//!   request on a forge
+//!
+//! The commit message and pull request body carry the composition
+//! [`Explanation`] and audit metadata, so a human reviewer sees what the
+//! agents changed, how it was composed and under whose authority before
+//! merging.
+
+use crate::error::{EgressError, SerializeError};
+use crate::serializers::ArtifactSerializer;
+use coa_artifact::{Artifact, ArtifactType};
+use coa_composition::Explanation;
+use serde_json::{Map, Value};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// Lines of context around each hunk
+pub const DIFF_CONTEXT: usize = 3;
+
+/// Largest line-pair table diffed exactly; bigger changes become one hunk
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// One file's content before and after composition
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    /// Repository-relative path, `/`-separated
+    pub path: String,
+
+    /// Content before, `None` for a new file
+    pub before: Option<String>,
+
+    /// Content after, `None` for a deleted file
+    pub after: Option<String>,
+}
+
+impl FileChange {
+    #[must_use]
+    pub fn new(path: impl Into<String>, before: Option<String>, after: Option<String>) -> Self {
+        Self {
+            path: path.into(),
+            before,
+            after,
+        }
+    }
+
+    /// Whether the content is unchanged
+    #[must_use]
+    pub fn is_unchanged(&self) -> bool {
+        self.before == self.after
+    }
+
+    /// Git-style unified diff of this file
+    #[must_use]
+    pub fn diff(&self) -> String {
+        unified_diff(&self.path, self.before.as_deref(), self.after.as_deref())
+    }
+}
+
+/// Changes forming one reviewable commit
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChangeSet {
+    /// Commit subject and pull request title
+    pub title: String,
+
+    /// Markdown description
+    pub body: String,
+
+    /// Changed files, in order added
+    pub changes: Vec<FileChange>,
+
+    /// Audit metadata, e.g. graph and node ids, autonomy level, artifact
+    /// provenance
+    pub audit: Map<String, Value>,
+}
+
+impl ChangeSet {
+    #[must_use]
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Append a Markdown paragraph to the body
+    #[must_use]
+    pub fn with_body(mut self, text: &str) -> Self {
+        if !self.body.is_empty() {
+            self.body.push_str("\n\n");
+        }
+        self.body.push_str(text.trim_end());
+        self
+    }
+
+    /// Append a composition explanation to the body
+    #[must_use]
+    pub fn with_explanation(self, explanation: &Explanation) -> Self {
+        self.with_body(&explanation.to_markdown())
+    }
+
+    /// Record an audit entry
+    #[must_use]
+    pub fn with_audit(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.audit.insert(key.into(), value.into());
+        self
+    }
+
+    /// Add a file change; unchanged files are skipped
+    #[must_use]
+    pub fn with_change(mut self, change: FileChange) -> Self {
+        if !change.is_unchanged() {
+            self.changes.push(change);
+        }
+        self
+    }
+
+    /// Add the change from `base` to `result` at `path`, rendered by
+    /// `serializer`
+    ///
+    /// `None` stands for a missing file. The result's hash, type and
+    /// provenance are recorded in the audit metadata under `artifacts`.
+    ///
+    /// # Errors
+    /// - Any error from the serializer
+    pub fn with_artifact<S: ArtifactSerializer>(
+        mut self,
+        serializer: &S,
+        base: Option<&Artifact<S::Input>>,
+        result: Option<&Artifact<S::Input>>,
+        path: &str,
+    ) -> Result<Self, SerializeError> {
+        let before = base.map(|a| serializer.serialize(a)).transpose()?;
+        let after = result.map(|a| serializer.serialize(a)).transpose()?;
+        if let Some(result) = result {
+            let record = serde_json::json!({
+                "artifact_hash": result.hash().to_string(),
+                "artifact_type": <S::Input as ArtifactType>::TYPE_ID,
+                "provenance": result.provenance(),
+            });
+            if let Value::Object(artifacts) = self
+                .audit
+                .entry("artifacts")
+                .or_insert_with(|| Value::Object(Map::new()))
+            {
+                artifacts.insert(path.to_string(), record);
+            }
+        }
+        Ok(self.with_change(FileChange::new(path, before, after)))
+    }
+
+    /// Whether no file changed
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Body followed by the audit metadata as a JSON block
+    #[must_use]
+    pub fn description(&self) -> String {
+        let mut description = self.body.clone();
+        if !self.audit.is_empty() {
+            if !description.is_empty() {
+                description.push_str("\n\n");
+            }
+            let audit = serde_json::to_string_pretty(&self.audit).unwrap_or_default();
+            let _ = write!(description, "### Audit\n\n```json\n{audit}\n```");
+        }
+        description
+    }
+
+    /// Unified diff of every file
+    #[must_use]
+    pub fn diff(&self) -> String {
+        self.changes.iter().map(FileChange::diff).collect()
+    }
+
+    /// Mailbox patch applying this change set as one commit
+    ///
+    /// `number` and `total` number the patch within a series, as in
+    /// `git format-patch`; `author` is `Name <email>`.
+    #[must_use]
+    pub fn format_patch(&self, number: usize, total: usize, author: &str) -> String {
+        let subject = if total > 1 {
+            format!("[PATCH {number}/{total}] {}", self.title)
+        } else {
+            format!("[PATCH] {}", self.title)
+        };
+        let mut patch = format!("From: {author}\nSubject: {subject}\n\n");
+        let description = self.description();
+        if !description.is_empty() {
+            patch.push_str(&description);
+            patch.push('\n');
+        }
+        patch.push_str("---\n");
+        patch.push_str(&self.diff());
+        patch.push_str("-- \ncoa\n");
+        patch
+    }
+
+    /// File name `git format-patch` would give this patch
+    #[must_use]
+    pub fn patch_file_name(&self, number: usize) -> String {
+        let mut slug = String::new();
+        for c in self.title.chars() {
+            if c.is_ascii_alphanumeric() {
+                slug.push(c);
+            } else if !slug.ends_with('-') {
+                slug.push('-');
+            }
+        }
+        let slug: String = slug.trim_matches('-').chars().take(52).collect();
+        format!("{number:04}-{}.patch", slug.trim_end_matches('-'))
+    }
+}
+
+/// Write `series` to `dir` as numbered patch files, returning their paths
+///
+/// Empty change sets are skipped.
+///
+/// # Errors
+/// - `EgressError::Io` if the directory or a file cannot be written
+pub async fn write_patch_series(
+    dir: impl AsRef<Path>,
+    series: &[ChangeSet],
+    author: &str,
+) -> Result<Vec<PathBuf>, EgressError> {
+    let dir = dir.as_ref();
+    tokio::fs::create_dir_all(dir)
+        .await
+        .map_err(|source| EgressError::io_error(dir, source))?;
+
+    let series: Vec<_> = series.iter().filter(|set| !set.is_empty()).collect();
+    let mut written = Vec::with_capacity(series.len());
+    for (i, set) in series.iter().enumerate() {
+        let path = dir.join(set.patch_file_name(i + 1));
+        tokio::fs::write(&path, set.format_patch(i + 1, series.len(), author))
+            .await
+            .map_err(|source| EgressError::io_error(&path, source))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Git-style unified diff of one file
+///
+/// `None` stands for a missing file. Identical content yields an empty
+/// string.
+#[must_use]
+pub fn unified_diff(path: &str, before: Option<&str>, after: Option<&str>) -> String {
+    if before == after {
+        return String::new();
+    }
+    let old: Vec<&str> = before.map_or_else(Vec::new, |text| text.split_inclusive('\n').collect());
+    let new: Vec<&str> = after.map_or_else(Vec::new, |text| text.split_inclusive('\n').collect());
+
+    let mut out = format!("diff --git a/{path} b/{path}\n");
+    match (before, after) {
+        (None, _) => out.push_str("new file mode 100644\n"),
+        (_, None) => out.push_str("deleted file mode 100644\n"),
+        _ => {}
+    }
+    let from = if before.is_some() {
+        format!("a/{path}")
+    } else {
+        "/dev/null".to_string()
+    };
+    let to = if after.is_some() {
+        format!("b/{path}")
+    } else {
+        "/dev/null".to_string()
+    };
+    let _ = write!(out, "--- {from}\n+++ {to}\n");
+
+    let edits = edit_script(&old, &new);
+    for hunk in hunks(&edits) {
+        let (old_start, old_len, new_start, new_len) = hunk_range(&edits[hunk.clone()]);
+        let _ = writeln!(
+            out,
+            "@@ -{} +{} @@",
+            range(old_start, old_len),
+            range(new_start, new_len)
+        );
+        for edit in &edits[hunk] {
+            let (marker, line) = match *edit {
+                Edit::Keep(i, _) => (' ', old[i]),
+                Edit::Delete(i) => ('-', old[i]),
+                Edit::Insert(j) => ('+', new[j]),
+            };
+            out.push(marker);
+            out.push_str(line);
+            if !line.ends_with('\n') {
+                out.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+    out
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Edit {
+    /// Line in both, by old and new index
+    Keep(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Shortest edit script by longest common subsequence
+fn edit_script(old: &[&str], new: &[&str]) -> Vec<Edit> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (a, b) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+
+    let mut edits: Vec<Edit> = (0..prefix).map(|i| Edit::Keep(i, i)).collect();
+    if a.len().saturating_mul(b.len()) > MAX_DIFF_CELLS {
+        edits.extend((0..a.len()).map(|i| Edit::Delete(prefix + i)));
+        edits.extend((0..b.len()).map(|j| Edit::Insert(prefix + j)));
+    } else {
+        // lcs[i][j]: common subsequence length of a[i..] and b[j..]
+        let width = b.len() + 1;
+        let mut lcs = vec![0u32; (a.len() + 1) * width];
+        for i in (0..a.len()).rev() {
+            for j in (0..b.len()).rev() {
+                lcs[i * width + j] = if a[i] == b[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() || j < b.len() {
+            if i < a.len() && j < b.len() && a[i] == b[j] {
+                edits.push(Edit::Keep(prefix + i, prefix + j));
+                i += 1;
+                j += 1;
+            } else if j == b.len()
+                || (i < a.len() && lcs[(i + 1) * width + j] >= lcs[i * width + j + 1])
+            {
+                edits.push(Edit::Delete(prefix + i));
+                i += 1;
+            } else {
+                edits.push(Edit::Insert(prefix + j));
+                j += 1;
+            }
+        }
+    }
+    let (old_end, new_end) = (old.len() - suffix, new.len() - suffix);
+    edits.extend((0..suffix).map(|k| Edit::Keep(old_end + k, new_end + k)));
+    edits
+}
+
+/// Ranges of `edits` forming hunks: changes plus surrounding context, with
+/// hunks closer than twice the context merged
+fn hunks(edits: &[Edit]) -> Vec<std::ops::Range<usize>> {
+    let mut hunks: Vec<std::ops::Range<usize>> = Vec::new();
+    for (k, edit) in edits.iter().enumerate() {
+        if matches!(edit, Edit::Keep(..)) {
+            continue;
+        }
+        let start = k.saturating_sub(DIFF_CONTEXT);
+        let end = (k + 1 + DIFF_CONTEXT).min(edits.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.end => last.end = end,
+            _ => hunks.push(start..end),
+        }
+    }
+    hunks
+}
+
+/// Zero-based start and length of a hunk on each side
+fn hunk_range(edits: &[Edit]) -> (usize, usize, usize, usize) {
+    let (mut old_start, mut new_start) = (None, None);
+    let (mut old_len, mut new_len) = (0, 0);
+    // Position of the first line on each side, even if that side is empty
+    let (mut old_next, mut new_next) = (0, 0);
+    for edit in edits {
+        match *edit {
+            Edit::Keep(i, j) => {
+                old_start.get_or_insert(i);
+                new_start.get_or_insert(j);
+                old_len += 1;
+                new_len += 1;
+                (old_next, new_next) = (i + 1, j + 1);
+            }
+            Edit::Delete(i) => {
+                old_start.get_or_insert(i);
+                old_len += 1;
+                old_next = i + 1;
+            }
+            Edit::Insert(j) => {
+                new_start.get_or_insert(j);
+                new_len += 1;
+                new_next = j + 1;
+            }
+        }
+    }
+    (
+        old_start.unwrap_or(old_next),
+        old_len,
+        new_start.unwrap_or(new_next),
+        new_len,
+    )
+}
+
+/// `start,len` in one-based unified-diff notation
+fn range(start: usize, len: usize) -> String {
+    match len {
+        // An empty side names the line before it
+        0 => format!("{start},0"),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{len}", start + 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::{ArtifactParser, CodeParser, Language};
+    use crate::serializers::CodeSerializer;
+    use coa_artifact::ArtifactProvenance;
+
+    #[test]
+    fn diffs_modified_new_and_deleted_files() {
+        let before = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+        let after = "a\nb\nc\nD\ne\nf\ng\nh\ni\nj\nk\n";
+        assert_eq!(
+            unified_diff("src/x.txt", Some(before), Some(after)),
+            "diff --git a/src/x.txt b/src/x.txt\n\
+             --- a/src/x.txt\n\
+             +++ b/src/x.txt\n\
+             @@ -1,10 +1,11 @@\n a\n b\n c\n-d\n+D\n e\n f\n g\n h\n i\n j\n+k\n"
+        );
+
+        let far = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\n";
+        let edited = "A\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nL";
+        assert_eq!(
+            unified_diff("x", Some(far), Some(edited)),
+            "diff --git a/x b/x\n--- a/x\n+++ b/x\n\
+             @@ -1,4 +1,4 @@\n-a\n+A\n b\n c\n d\n\
+             @@ -9,4 +9,4 @@\n i\n j\n k\n-l\n+L\n\\ No newline at end of file\n"
+        );
+
+        assert_eq!(
+            unified_diff("new.rs", None, Some("fn main() {}\n")),
+            "diff --git a/new.rs b/new.rs\nnew file mode 100644\n\
+             --- /dev/null\n+++ b/new.rs\n@@ -0,0 +1 @@\n+fn main() {}\n"
+        );
+        assert_eq!(
+            unified_diff("old.rs", Some("x\ny\n"), None),
+            "diff --git a/old.rs b/old.rs\ndeleted file mode 100644\n\
+             --- a/old.rs\n+++ /dev/null\n@@ -1,2 +0,0 @@\n-x\n-y\n"
+        );
+        assert_eq!(unified_diff("same", Some("x\n"), Some("x\n")), "");
+    }
+
+    #[test]
+    fn change_sets_render_patches_with_audit() {
+        let parser = CodeParser::new(Language::Rust);
+        let base = parser.parse("fn main() {}\n").unwrap();
+        let result = parser
+            .parse("fn main() {\n    run();\n}\n")
+            .unwrap()
+            .with_provenance(ArtifactProvenance::new().with_agent("coder"));
+
+        let set = ChangeSet::new("Call run from main")
+            .with_body("Generated by the coder agent.")
+            .with_audit("autonomy_level", "L2")
+            .with_artifact(
+                &CodeSerializer::new(Language::Rust),
+                Some(&base),
+                Some(&result),
+                "src/main.rs",
+            )
+            .unwrap()
+            .with_change(FileChange::new(
+                "README",
+                Some("x".into()),
+                Some("x".into()),
+            ));
+        assert_eq!(set.changes.len(), 1);
+        assert_eq!(
+            set.audit["artifacts"]["src/main.rs"]["provenance"]["agent_id"],
+            "coder"
+        );
+
+        let patch = set.format_patch(1, 2, "COA <coa@example.com>");
+        assert!(patch.starts_with(
+            "From: COA <coa@example.com>\nSubject: [PATCH 1/2] Call run from main\n\n\
+             Generated by the coder agent.\n\n### Audit\n\n```json\n"
+        ));
+        assert!(patch.contains("\n---\ndiff --git a/src/main.rs b/src/main.rs\n"));
+        assert!(patch.contains("-fn main() {}\n+fn main() {\n+    run();\n+}\n"));
+        assert_eq!(set.patch_file_name(1), "0001-Call-run-from-main.patch");
+    }
+
+    #[tokio::test]
+    async fn writes_numbered_patch_series() {
+        let dir = tempfile::tempdir().unwrap();
+        let series = [
+            ChangeSet::new("First").with_change(FileChange::new("a", None, Some("1\n".into()))),
+            ChangeSet::new("Nothing"),
+            ChangeSet::new("Second").with_change(FileChange::new("a", Some("1\n".into()), None)),
+        ];
+
+        let written = write_patch_series(dir.path(), &series, "COA <coa@example.com>")
+            .await
+            .unwrap();
+        let names: Vec<_> = written
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, ["0001-First.patch", "0002-Second.patch"]);
+        let second = std::fs::read_to_string(&written[1]).unwrap();
+        assert!(second.contains("Subject: [PATCH 2/2] Second\n"));
+    }
+}
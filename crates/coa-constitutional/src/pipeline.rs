@@ -0,0 +1,353 @@
+//! Transformation pipeline with middlewares
+//!
+//! Middlewares run on an artifact after `apply_delta` and before egress.
+//! Each one may rewrite the artifact (license headers, formatting) or veto it
+//! (lint failures, leaked secrets) with a typed [`PipelineError`].
+//!
+//! # Architecture
+//!
+//! ```text
+//! apply_delta → [lint] → [license_header] → [secret_scan] → serialize_egress
+//!                 └──────── TransformPipeline<T> ────────┘
+//! ```
+//!
+//! Middlewares are registered by name in a [`MiddlewareRegistry`]; a
+//! deployment composes its policy by listing names, e.g. from configuration.
+
+use crate::error::PipelineError;
+use crate::parsers::CodeArtifact;
+use coa_artifact::{Artifact, ArtifactType};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// A single pipeline step
+///
+/// Return the (possibly rewritten) artifact to continue, or an error to stop
+/// the pipeline. Use [`PipelineError::veto`] for policy rejections.
+pub trait TransformMiddleware<T: ArtifactType>: Send + Sync {
+    /// Stable middleware name (used in registries and error reports)
+    fn name(&self) -> &str;
+
+    /// Process artifact
+    ///
+    /// # Errors
+    /// Returns error to veto the artifact or report a failure
+    fn process(&self, artifact: Artifact<T>) -> Result<Artifact<T>, PipelineError>;
+}
+
+/// Boxed middleware function
+type MiddlewareFn<T> = dyn Fn(Artifact<T>) -> Result<Artifact<T>, PipelineError> + Send + Sync;
+
+/// Middleware built from a closure
+pub struct FnMiddleware<T: ArtifactType> {
+    name: String,
+    f: Box<MiddlewareFn<T>>,
+}
+
+impl<T: ArtifactType> FnMiddleware<T> {
+    /// Create middleware from name and function
+    #[must_use]
+    pub fn new<F>(name: impl Into<String>, f: F) -> Self
+    where
+        F: Fn(Artifact<T>) -> Result<Artifact<T>, PipelineError> + Send + Sync + 'static,
+    {
+        Self {
+            name: name.into(),
+            f: Box::new(f),
+        }
+    }
+}
+
+impl<T: ArtifactType> fmt::Debug for FnMiddleware<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FnMiddleware")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+impl<T: ArtifactType> TransformMiddleware<T> for FnMiddleware<T> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn process(&self, artifact: Artifact<T>) -> Result<Artifact<T>, PipelineError> {
+        (self.f)(artifact)
+    }
+}
+
+/// Ordered chain of middlewares for one artifact type
+pub struct TransformPipeline<T: ArtifactType> {
+    middlewares: Vec<Arc<dyn TransformMiddleware<T>>>,
+}
+
+impl<T: ArtifactType> TransformPipeline<T> {
+    /// Create empty pipeline (passes artifacts through unchanged)
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            middlewares: Vec::new(),
+        }
+    }
+
+    /// Append middleware
+    #[must_use]
+    pub fn with<M: TransformMiddleware<T> + 'static>(mut self, middleware: M) -> Self {
+        self.push(Arc::new(middleware));
+        self
+    }
+
+    /// Append shared middleware
+    pub fn push(&mut self, middleware: Arc<dyn TransformMiddleware<T>>) {
+        self.middlewares.push(middleware);
+    }
+
+    /// Middleware names in execution order
+    #[must_use]
+    pub fn names(&self) -> Vec<&str> {
+        self.middlewares.iter().map(|m| m.name()).collect()
+    }
+
+    /// Number of middlewares
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.middlewares.len()
+    }
+
+    /// Check if pipeline is empty
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.middlewares.is_empty()
+    }
+
+    /// Run artifact through every middleware in order
+    ///
+    /// Stops at the first error; later middlewares do not run.
+    ///
+    /// # Errors
+    /// Returns the first middleware error (veto or failure)
+    pub fn run(&self, artifact: Artifact<T>) -> Result<Artifact<T>, PipelineError> {
+        self.middlewares
+            .iter()
+            .try_fold(artifact, |artifact, middleware| {
+                middleware.process(artifact)
+            })
+    }
+}
+
+impl<T: ArtifactType> Default for TransformPipeline<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: ArtifactType> Clone for TransformPipeline<T> {
+    fn clone(&self) -> Self {
+        Self {
+            middlewares: self.middlewares.clone(),
+        }
+    }
+}
+
+impl<T: ArtifactType> fmt::Debug for TransformPipeline<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TransformPipeline")
+            .field("artifact_type", &T::TYPE_ID)
+            .field("middlewares", &self.names())
+            .finish()
+    }
+}
+
+/// Named middlewares available to build pipelines from
+pub struct MiddlewareRegistry<T: ArtifactType> {
+    middlewares: HashMap<String, Arc<dyn TransformMiddleware<T>>>,
+}
+
+impl<T: ArtifactType> MiddlewareRegistry<T> {
+    /// Create empty registry
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            middlewares: HashMap::new(),
+        }
+    }
+
+    /// Register middleware under its own name (replaces existing)
+    pub fn register<M: TransformMiddleware<T> + 'static>(&mut self, middleware: M) {
+        self.middlewares
+            .insert(middleware.name().to_string(), Arc::new(middleware));
+    }
+
+    /// Check if middleware is registered
+    #[inline]
+    #[must_use]
+    pub fn contains(&self, name: &str) -> bool {
+        self.middlewares.contains_key(name)
+    }
+
+    /// Registered middleware names (unordered)
+    #[must_use]
+    pub fn names(&self) -> Vec<&str> {
+        self.middlewares.keys().map(String::as_str).collect()
+    }
+
+    /// Build pipeline from an ordered list of middleware names
+    ///
+    /// # Errors
+    /// Returns `PipelineError::UnknownMiddleware` for unregistered names
+    pub fn build<S: AsRef<str>>(&self, names: &[S]) -> Result<TransformPipeline<T>, PipelineError> {
+        let mut pipeline = TransformPipeline::new();
+        for name in names {
+            let name = name.as_ref();
+            let middleware = self
+                .middlewares
+                .get(name)
+                .ok_or_else(|| PipelineError::UnknownMiddleware(name.to_string()))?;
+            pipeline.push(Arc::clone(middleware));
+        }
+        Ok(pipeline)
+    }
+}
+
+impl<T: ArtifactType> Default for MiddlewareRegistry<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: ArtifactType> fmt::Debug for MiddlewareRegistry<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MiddlewareRegistry")
+            .field("artifact_type", &T::TYPE_ID)
+            .field("middlewares", &self.names())
+            .finish()
+    }
+}
+
+/// Prepends a license header to code artifacts that lack one
+#[derive(Debug, Clone)]
+pub struct LicenseHeaderMiddleware {
+    header: String,
+}
+
+impl LicenseHeaderMiddleware {
+    /// Create middleware with header text (without comment markers)
+    #[inline]
+    #[must_use]
+    pub fn new(header: impl Into<String>) -> Self {
+        Self {
+            header: header.into(),
+        }
+    }
+
+    /// Render header as line comments for the artifact language
+    fn render(&self, language: crate::parsers::Language) -> String {
+        let marker = match language {
+            crate::parsers::Language::Python => "#",
+            _ => "//",
+        };
+        self.header
+            .lines()
+            .map(|line| format!("{marker} {line}\n"))
+            .collect()
+    }
+}
+
+impl TransformMiddleware<CodeArtifact> for LicenseHeaderMiddleware {
+    fn name(&self) -> &str {
+        "license_header"
+    }
+
+    fn process(
+        &self,
+        artifact: Artifact<CodeArtifact>,
+    ) -> Result<Artifact<CodeArtifact>, PipelineError> {
+        let header = self.render(artifact.content().language);
+        if artifact.content().source.starts_with(&header) {
+            return Ok(artifact);
+        }
+
+        let provenance = artifact.provenance().map(|p| p.derive(*artifact.hash()));
+        let mut content = artifact.into_content();
+        content.source.insert_str(0, &header);
+        let rewritten = Artifact::new(content)?;
+        Ok(match provenance {
+            Some(p) => rewritten.with_provenance(p),
+            None => rewritten,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::{ArtifactParser, CodeParser, Language};
+
+    fn rust_artifact(source: &str) -> Artifact<CodeArtifact> {
+        CodeParser::new(Language::Rust).parse(source).unwrap()
+    }
+
+    fn deny_todo() -> FnMiddleware<CodeArtifact> {
+        FnMiddleware::new("deny_todo", |artifact: Artifact<CodeArtifact>| {
+            if artifact.content().source.contains("todo!()") {
+                Err(PipelineError::veto("deny_todo", "unfinished code"))
+            } else {
+                Ok(artifact)
+            }
+        })
+    }
+
+    #[test]
+    fn empty_pipeline_passes_through() {
+        let artifact = rust_artifact("fn main() {}");
+        let out = TransformPipeline::new().run(artifact.clone()).unwrap();
+        assert_eq!(out.hash(), artifact.hash());
+    }
+
+    #[test]
+    fn license_header_is_prepended_once() {
+        let pipeline = TransformPipeline::new()
+            .with(LicenseHeaderMiddleware::new("SPDX-License-Identifier: MIT"));
+
+        let out = pipeline.run(rust_artifact("fn main() {}")).unwrap();
+        assert!(out
+            .content()
+            .source
+            .starts_with("// SPDX-License-Identifier: MIT\n"));
+        assert!(out.verify());
+
+        let again = pipeline.run(out.clone()).unwrap();
+        assert_eq!(again.hash(), out.hash());
+    }
+
+    #[test]
+    fn veto_stops_pipeline() {
+        let pipeline = TransformPipeline::new()
+            .with(deny_todo())
+            .with(LicenseHeaderMiddleware::new("MIT"));
+
+        let result = pipeline.run(rust_artifact("fn main() { todo!() }"));
+        assert!(matches!(
+            result,
+            Err(PipelineError::Vetoed { ref middleware, .. }) if middleware == "deny_todo"
+        ));
+    }
+
+    #[test]
+    fn registry_builds_pipeline_in_order() {
+        let mut registry = MiddlewareRegistry::new();
+        registry.register(deny_todo());
+        registry.register(LicenseHeaderMiddleware::new("MIT"));
+
+        let pipeline = registry.build(&["license_header", "deny_todo"]).unwrap();
+        assert_eq!(pipeline.names(), vec!["license_header", "deny_todo"]);
+
+        let result = registry.build(&["license_header", "format"]);
+        assert!(matches!(result, Err(PipelineError::UnknownMiddleware(ref n)) if n == "format"));
+    }
+}
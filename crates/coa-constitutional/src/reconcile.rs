@@ -0,0 +1,331 @@
+//! Reconciliation with edits made outside the COA
+//!
+//! Humans keep editing the workspace while a run is in flight. Every file
+//! ingested by [`ConstitutionalLayer::ingress_workspace`] is tracked with
+//! the checksum it had on disk; [`ConstitutionalLayer::reconcile`] compares
+//! those against the files as they are now, re-ingests the ones that
+//! changed (refreshing the cache and symbol index) and reports the drift.
+//!
+//! Pending deltas built against a drifted file are then sorted with
+//! [`Reconciliation::triage`]: pinned deltas whose target symbol survived
+//! the edit are [rebased](StructuralDelta::rebased) onto the new artifact,
+//! the rest are paused so their tasks can be escalated instead of composing
+//! against a stale base.
+//!
+//! [`ConstitutionalLayer::ingress_workspace`]: crate::ConstitutionalLayer::ingress_workspace
+//! [`ConstitutionalLayer::reconcile`]: crate::ConstitutionalLayer::reconcile
+
+use crate::ingress::{self, IngressFailure};
+use coa_artifact::{ArtifactType, ContentHash, DeltaError, StructuralDelta};
+use coa_symbol::SymbolRefIndex;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// Ingested file as last seen on disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrackedFile {
+    /// Checksum of the file's bytes
+    pub checksum: ContentHash,
+    /// Hash of the artifact parsed from it
+    pub artifact: ContentHash,
+}
+
+/// Tracked files by path relative to the workspace root
+#[derive(Debug, Default)]
+pub(crate) struct Ledger {
+    files: RwLock<BTreeMap<PathBuf, TrackedFile>>,
+}
+
+impl Ledger {
+    pub(crate) fn record(&self, path: PathBuf, file: TrackedFile) {
+        if let Ok(mut files) = self.files.write() {
+            files.insert(path, file);
+        }
+    }
+
+    pub(crate) fn forget(&self, path: &Path) -> Option<TrackedFile> {
+        self.files.write().ok()?.remove(path)
+    }
+
+    pub(crate) fn get(&self, path: &Path) -> Option<TrackedFile> {
+        self.files.read().ok()?.get(path).copied()
+    }
+
+    pub(crate) fn snapshot(&self) -> Vec<(PathBuf, TrackedFile)> {
+        self.files
+            .read()
+            .map(|files| files.iter().map(|(p, f)| (p.clone(), *f)).collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Tracked file that changed on disk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDrift {
+    /// Path relative to the workspace root
+    pub path: PathBuf,
+    /// Artifact hash before the edit
+    pub previous: ContentHash,
+    /// Artifact hash after the edit; `None` if the file was removed or no
+    /// longer parses
+    pub current: Option<ContentHash>,
+}
+
+/// Outcome of a reconciliation pass
+#[derive(Debug, Default)]
+pub struct Reconciliation {
+    /// Files edited, removed or broken since they were ingested
+    pub drifted: Vec<FileDrift>,
+    /// Changed files that could not be re-ingested
+    pub failed: Vec<IngressFailure>,
+    /// Wall-clock time of the pass
+    pub duration: Duration,
+}
+
+impl Reconciliation {
+    /// Check if the workspace still matches what was ingested
+    #[inline]
+    #[must_use]
+    pub fn is_clean(&self) -> bool {
+        self.drifted.is_empty()
+    }
+
+    /// Drift of the file whose previous artifact is `base`
+    #[must_use]
+    pub fn drift_of(&self, base: &ContentHash) -> Option<&FileDrift> {
+        self.drifted.iter().find(|drift| drift.previous == *base)
+    }
+
+    /// Sort pending deltas, keyed by their task, against the drift
+    ///
+    /// Deltas based on an untouched artifact are ready as they are. A delta
+    /// based on a drifted file is rebased onto the new artifact if it is
+    /// pinned and `index` (refreshed by the pass) still records the pinned
+    /// symbol hash; otherwise it is paused with the reason.
+    pub fn triage<K, T, I>(&self, index: &SymbolRefIndex, pending: I) -> Triage<K, T>
+    where
+        T: ArtifactType,
+        I: IntoIterator<Item = (K, StructuralDelta<T>)>,
+    {
+        let mut triage = Triage {
+            ready: Vec::new(),
+            rebased: 0,
+            paused: Vec::new(),
+        };
+        for (key, delta) in pending {
+            let Some(drift) = self.drift_of(delta.base_hash()) else {
+                triage.ready.push((key, delta));
+                continue;
+            };
+            match stale_reason(index, drift, &delta) {
+                Ok(current) => {
+                    triage.rebased += 1;
+                    triage.ready.push((key, delta.rebased(current)));
+                }
+                Err(reason) => triage.paused.push(PausedDelta {
+                    key,
+                    delta,
+                    path: drift.path.clone(),
+                    reason,
+                }),
+            }
+        }
+        triage
+    }
+}
+
+/// New base for a delta on a drifted file, or why it cannot move there
+fn stale_reason<T: ArtifactType>(
+    index: &SymbolRefIndex,
+    drift: &FileDrift,
+    delta: &StructuralDelta<T>,
+) -> Result<ContentHash, StaleReason> {
+    let current = drift.current.ok_or(StaleReason::Unavailable)?;
+    let Some(pin) = delta.pin() else {
+        return Err(StaleReason::Conflict(DeltaError::BaseMismatch {
+            expected: *delta.base_hash(),
+            actual: current,
+        }));
+    };
+    let symbol = index.symbol_hash(&ingress::workspace_symbol(&drift.path, delta.target()));
+    if symbol == Some(pin.symbol_hash) {
+        Ok(current)
+    } else {
+        Err(StaleReason::Conflict(DeltaError::StaleSymbol {
+            target: delta.target().clone(),
+            expected: pin.symbol_hash,
+            actual: symbol,
+        }))
+    }
+}
+
+/// Pending deltas sorted by [`Reconciliation::triage`]
+#[derive(Debug)]
+pub struct Triage<K, T: ArtifactType> {
+    /// Deltas safe to compose, rebased ones included
+    pub ready: Vec<(K, StructuralDelta<T>)>,
+    /// Number of ready deltas moved onto a new base
+    pub rebased: usize,
+    /// Deltas whose tasks must pause until a human or agent redoes them
+    pub paused: Vec<PausedDelta<K, T>>,
+}
+
+impl<K, T: ArtifactType> Triage<K, T> {
+    /// Check if any task must be paused and escalated
+    #[inline]
+    #[must_use]
+    pub fn needs_escalation(&self) -> bool {
+        !self.paused.is_empty()
+    }
+}
+
+/// Delta invalidated by an external edit
+#[derive(Debug)]
+pub struct PausedDelta<K, T: ArtifactType> {
+    /// Caller's key, e.g. the owning task
+    pub key: K,
+    /// The stale delta
+    pub delta: StructuralDelta<T>,
+    /// File that changed under it
+    pub path: PathBuf,
+    /// Why it cannot be rebased
+    pub reason: StaleReason,
+}
+
+impl<K, T: ArtifactType> fmt::Display for PausedDelta<K, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{}` in {} changed outside the run: {}",
+            self.delta.target(),
+            self.path.display(),
+            self.reason
+        )
+    }
+}
+
+/// Why a delta on a drifted file was paused
+#[derive(Debug, thiserror::Error)]
+pub enum StaleReason {
+    /// The file was removed or no longer parses
+    #[error("file removed or no longer parses")]
+    Unavailable,
+
+    /// The edit touched the delta's target (or the delta is unpinned)
+    #[error(transparent)]
+    Conflict(DeltaError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::CodeArtifact;
+    use crate::ConstitutionalLayer;
+    use coa_artifact::{DeltaOperation, SymbolPath, SymbolPin};
+    use std::str::FromStr;
+
+    fn delta(target: &str, base: ContentHash) -> StructuralDelta<CodeArtifact> {
+        StructuralDelta::new(
+            SymbolPath::from_str(target).unwrap(),
+            DeltaOperation::Remove,
+            base,
+        )
+    }
+
+    #[tokio::test]
+    async fn reconcile_reingests_edits_and_triages_deltas() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("lib.rs"), "fn run() {}\n\nfn stop() {}\n").unwrap();
+        std::fs::write(root.join("util.rs"), "fn helper() {}\n").unwrap();
+        std::fs::write(root.join("gone.rs"), "fn old() {}\n").unwrap();
+
+        let layer = ConstitutionalLayer::new();
+        layer.ingress_workspace(root, &["*.rs"]).await.unwrap();
+        assert!(layer.reconcile(root).await.unwrap().is_clean());
+
+        let lib = layer.tracked_file("lib.rs").unwrap().artifact;
+        let util = layer.tracked_file("util.rs").unwrap().artifact;
+        let gone = layer.tracked_file("gone.rs").unwrap().artifact;
+        let index = layer.symbol_index();
+        let run_hash = index
+            .symbol_hash(&["lib.rs".to_string(), "run".to_string()])
+            .unwrap();
+        let stop_hash = index
+            .symbol_hash(&["lib.rs".to_string(), "stop".to_string()])
+            .unwrap();
+
+        // A human edits `stop` and deletes a file mid-run
+        std::fs::write(
+            root.join("lib.rs"),
+            "fn run() {}\n\nfn stop() { exit(); }\n",
+        )
+        .unwrap();
+        std::fs::remove_file(root.join("gone.rs")).unwrap();
+
+        let reconciliation = layer.reconcile(root).await.unwrap();
+        assert_eq!(reconciliation.drifted.len(), 2);
+        let edited = reconciliation.drift_of(&lib).unwrap();
+        let current = edited.current.unwrap();
+        assert_ne!(current, lib);
+        assert_eq!(layer.tracked_file("lib.rs").unwrap().artifact, current);
+        assert_eq!(reconciliation.drift_of(&gone).unwrap().current, None);
+        assert!(layer.tracked_file("gone.rs").is_none());
+        assert!(index
+            .get_by_path(&["gone.rs".to_string(), "old".to_string()])
+            .is_none());
+
+        let triage = reconciliation.triage(
+            index,
+            vec![
+                ("untouched", delta("helper", util)),
+                (
+                    "pinned",
+                    delta("run", lib).with_pin(SymbolPin::new(lib, run_hash)),
+                ),
+                (
+                    "edited",
+                    delta("stop", lib).with_pin(SymbolPin::new(lib, stop_hash)),
+                ),
+                ("unpinned", delta("run", lib)),
+                ("removed", delta("old", gone)),
+            ],
+        );
+        assert_eq!(triage.rebased, 1);
+        let ready: Vec<_> = triage.ready.iter().map(|(k, _)| *k).collect();
+        assert_eq!(ready, ["untouched", "pinned"]);
+        assert_eq!(*triage.ready[1].1.base_hash(), current);
+
+        assert!(triage.needs_escalation());
+        let paused: Vec<_> = triage.paused.iter().map(|p| p.key).collect();
+        assert_eq!(paused, ["edited", "unpinned", "removed"]);
+        assert!(matches!(
+            triage.paused[0].reason,
+            StaleReason::Conflict(DeltaError::StaleSymbol { .. })
+        ));
+        assert!(matches!(triage.paused[2].reason, StaleReason::Unavailable));
+        assert!(triage.paused[0]
+            .to_string()
+            .starts_with("`stop` in lib.rs changed outside the run"));
+    }
+
+    #[tokio::test]
+    async fn reconcile_removal_keeps_identical_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("a.rs"), "fn run() {}\n").unwrap();
+        std::fs::write(root.join("b.rs"), "fn run() {}\n").unwrap();
+        let layer = ConstitutionalLayer::new();
+        layer.ingress_workspace(root, &["*.rs"]).await.unwrap();
+
+        std::fs::remove_file(root.join("a.rs")).unwrap();
+        let reconciliation = layer.reconcile(root).await.unwrap();
+        assert_eq!(reconciliation.drifted.len(), 1);
+        let index = layer.symbol_index();
+        assert!(index.get_by_path(&["a.rs".to_string(), "run".to_string()]).is_none());
+        assert!(index.get_by_path(&["b.rs".to_string(), "run".to_string()]).is_some());
+    }
+}
@@ -0,0 +1,546 @@
+//! Cross-artifact refactorings
+//!
+//! [`RefactorEngine`] turns a refactoring request into the complete set of
+//! deltas it implies, using the definition and reference data in
+//! [`SymbolRefIndex`]. A rename produces one delta for the definition and
+//! one for every referencing symbol, grouped per artifact so the batches can
+//! be staged into a [`CompositionTransaction`], whose reference check then
+//! rejects the commit if any caller was missed.
+//!
+//! Rewriting is textual: every whole-identifier occurrence of the old name in
+//! an affected file is replaced.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let engine = RefactorEngine::new(&index);
+//! let plan = engine.rename(&"auth.login".parse()?, "sign_in")?;
+//!
+//! let mut tx = CompositionTransaction::new(&index);
+//! let slots = plan.stage(&mut tx, &strategy, &artifacts)?;
+//! let mut committed = tx.commit()?;
+//! ```
+
+use crate::error::RefactorError;
+use crate::parsers::{CodeArtifact, CodeContent};
+use coa_artifact::{
+    Artifact, ContentHash, DeltaOperation, StructuralDelta, SymbolPath, TransformError,
+    Transformation,
+};
+use coa_composition::{CompositionStrategy, CompositionTransaction, TransactionSlot};
+use coa_symbol::SymbolRefIndex;
+
+/// Rename an identifier inside a code artifact
+///
+/// Carried as a `Transform` delta. The definition delta reports
+/// [`Transformation::removes_target`] so transactions verify that every
+/// reference to the old path is updated alongside it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenameSymbol {
+    from: String,
+    to: String,
+    definition: bool,
+}
+
+impl RenameSymbol {
+    /// Rename applied at the symbol's definition
+    #[must_use]
+    pub fn definition(from: impl Into<String>, to: impl Into<String>) -> Self {
+        Self {
+            from: from.into(),
+            to: to.into(),
+            definition: true,
+        }
+    }
+
+    /// Rename applied at a referencing site
+    #[must_use]
+    pub fn reference(from: impl Into<String>, to: impl Into<String>) -> Self {
+        Self {
+            from: from.into(),
+            to: to.into(),
+            definition: false,
+        }
+    }
+
+    /// Old identifier
+    #[inline]
+    #[must_use]
+    pub fn from(&self) -> &str {
+        &self.from
+    }
+
+    /// New identifier
+    #[inline]
+    #[must_use]
+    pub fn to(&self) -> &str {
+        &self.to
+    }
+
+    /// Whether this rename is applied at the definition
+    #[inline]
+    #[must_use]
+    pub fn is_definition(&self) -> bool {
+        self.definition
+    }
+}
+
+impl Transformation<CodeArtifact> for RenameSymbol {
+    fn apply(&self, content: &CodeContent) -> Result<CodeContent, TransformError> {
+        if self.definition && !content.symbols.contains(&self.from) {
+            return Err(TransformError::StateConflict(format!(
+                "'{}' is not defined in this artifact",
+                self.from
+            )));
+        }
+
+        // Several deltas may target one file; later ones find nothing left
+        // to rewrite and leave the content unchanged.
+        Ok(CodeContent {
+            language: content.language,
+            source: rename_identifiers(&content.source, &self.from, &self.to),
+            symbols: content
+                .symbols
+                .iter()
+                .map(|symbol| {
+                    if *symbol == self.from {
+                        self.to.clone()
+                    } else {
+                        symbol.clone()
+                    }
+                })
+                .collect(),
+        })
+    }
+
+    fn describe(&self) -> String {
+        format!("rename {} -> {}", self.from, self.to)
+    }
+
+    fn removes_target(&self) -> bool {
+        self.definition
+    }
+
+    fn is_reversible(&self) -> bool {
+        true
+    }
+
+    fn inverse(&self) -> Option<Box<dyn Transformation<CodeArtifact>>> {
+        Some(Box::new(Self {
+            from: self.to.clone(),
+            to: self.from.clone(),
+            definition: self.definition,
+        }))
+    }
+}
+
+/// Deltas a rename needs in one artifact
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactRename {
+    /// Hash of the artifact the deltas apply to
+    pub artifact: ContentHash,
+    /// Whether the artifact contains the definition
+    pub definition: bool,
+    /// Referencing symbols inside the artifact
+    pub sites: Vec<SymbolPath>,
+}
+
+/// Complete delta set for renaming one symbol
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenamePlan {
+    old_path: SymbolPath,
+    new_path: SymbolPath,
+    artifacts: Vec<ArtifactRename>,
+}
+
+impl RenamePlan {
+    /// Path being renamed
+    #[inline]
+    #[must_use]
+    pub fn old_path(&self) -> &SymbolPath {
+        &self.old_path
+    }
+
+    /// Path after the rename
+    #[inline]
+    #[must_use]
+    pub fn new_path(&self) -> &SymbolPath {
+        &self.new_path
+    }
+
+    /// Affected artifacts, definition first
+    #[inline]
+    #[must_use]
+    pub fn artifacts(&self) -> &[ArtifactRename] {
+        &self.artifacts
+    }
+
+    /// Total number of deltas in the plan
+    #[must_use]
+    pub fn delta_count(&self) -> usize {
+        self.artifacts
+            .iter()
+            .map(|artifact| usize::from(artifact.definition) + artifact.sites.len())
+            .sum()
+    }
+
+    /// Deltas the plan applies to `base`
+    ///
+    /// Empty if `base` is not affected by the rename.
+    #[must_use]
+    pub fn deltas_for(&self, base: &Artifact<CodeArtifact>) -> Vec<StructuralDelta<CodeArtifact>> {
+        let Some(rename) = self.artifacts.iter().find(|a| a.artifact == *base.hash()) else {
+            return Vec::new();
+        };
+        let from = self.old_name();
+        let to = self.new_name();
+
+        let definition = rename.definition.then(|| {
+            StructuralDelta::new(
+                self.old_path.clone(),
+                DeltaOperation::Transform(Box::new(RenameSymbol::definition(from, to))),
+                *base.hash(),
+            )
+        });
+
+        let references = rename.sites.iter().map(|site| {
+            StructuralDelta::new(
+                site.clone(),
+                DeltaOperation::Transform(Box::new(RenameSymbol::reference(from, to))),
+                *base.hash(),
+            )
+        });
+
+        definition.into_iter().chain(references).collect()
+    }
+
+    /// Add one batch per affected artifact to `tx`
+    ///
+    /// `artifacts` must contain every artifact listed in the plan; others are
+    /// ignored. Slots are returned in plan order.
+    ///
+    /// # Errors
+    /// Returns `RefactorError::MissingArtifact` if an affected artifact is
+    /// not supplied, or the transaction's error if staging fails
+    pub fn stage<'a, S: CompositionStrategy>(
+        &self,
+        tx: &mut CompositionTransaction<'a>,
+        strategy: &'a S,
+        artifacts: &'a [Artifact<CodeArtifact>],
+    ) -> Result<Vec<TransactionSlot<CodeArtifact>>, RefactorError> {
+        self.artifacts
+            .iter()
+            .map(|rename| {
+                let base = artifacts
+                    .iter()
+                    .find(|artifact| *artifact.hash() == rename.artifact)
+                    .ok_or(RefactorError::MissingArtifact(rename.artifact))?;
+                Ok(tx.add(strategy, base, self.deltas_for(base))?)
+            })
+            .collect()
+    }
+
+    fn old_name(&self) -> &str {
+        self.old_path.last().unwrap_or_default()
+    }
+
+    fn new_name(&self) -> &str {
+        self.new_path.last().unwrap_or_default()
+    }
+}
+
+/// Plans refactorings from symbol definition and reference data
+#[derive(Debug, Clone, Copy)]
+pub struct RefactorEngine<'a> {
+    index: &'a SymbolRefIndex,
+}
+
+impl<'a> RefactorEngine<'a> {
+    /// Create engine over `index`
+    #[inline]
+    #[must_use]
+    pub fn new(index: &'a SymbolRefIndex) -> Self {
+        Self { index }
+    }
+
+    /// Plan renaming the last segment of `symbol_path` to `new_name`
+    ///
+    /// Covers the definition and every recorded reference to the symbol or
+    /// anything beneath it, across all artifacts.
+    ///
+    /// # Errors
+    /// Returns error if `new_name` is not an identifier, the symbol is not
+    /// indexed, or the new path is already taken
+    pub fn rename(
+        &self,
+        symbol_path: &SymbolPath,
+        new_name: &str,
+    ) -> Result<RenamePlan, RefactorError> {
+        if !is_identifier(new_name) {
+            return Err(RefactorError::InvalidName(new_name.to_string()));
+        }
+
+        let entry = self
+            .index
+            .get_by_path(symbol_path.segments())
+            .ok_or_else(|| RefactorError::SymbolNotFound(symbol_path.clone()))?;
+
+        let new_path = match symbol_path.parent() {
+            Some(parent) => parent.child(new_name),
+            None => SymbolPath::single(new_name),
+        };
+        if new_path != *symbol_path && self.index.get_by_path(new_path.segments()).is_some() {
+            return Err(RefactorError::NameTaken(new_path));
+        }
+
+        let mut artifacts = vec![ArtifactRename {
+            artifact: *entry.symbol.parent_hash(),
+            definition: true,
+            sites: Vec::new(),
+        }];
+
+        for reference in self.index.references_to(symbol_path.segments()) {
+            let site = SymbolPath::new(reference.site.path().to_vec());
+            let hash = *reference.site.parent_hash();
+
+            let rename = match artifacts.iter_mut().position(|a| a.artifact == hash) {
+                Some(position) => &mut artifacts[position],
+                None => {
+                    artifacts.push(ArtifactRename {
+                        artifact: hash,
+                        definition: false,
+                        sites: Vec::new(),
+                    });
+                    artifacts.last_mut().expect("just pushed")
+                }
+            };
+
+            // Sites inside the definition are already covered by its delta
+            let inside_definition = rename.definition && site.overlaps(symbol_path);
+            if !inside_definition && !rename.sites.contains(&site) {
+                rename.sites.push(site);
+            }
+        }
+
+        Ok(RenamePlan {
+            old_path: symbol_path.clone(),
+            new_path,
+            artifacts,
+        })
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_alphabetic() || first == '_')
+        && chars.all(is_identifier_char)
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Replace whole-identifier occurrences of `from` with `to`
+fn rename_identifiers(source: &str, from: &str, to: &str) -> String {
+    let mut out = String::with_capacity(source.len());
+    let mut rest = source;
+
+    while let Some(start) = rest.find(|c: char| is_identifier_char(c)) {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest
+            .find(|c: char| !is_identifier_char(c))
+            .unwrap_or(rest.len());
+        let word = &rest[..end];
+        out.push_str(if word == from { to } else { word });
+        rest = &rest[end..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::{ArtifactParser, CodeParser, Language};
+    use coa_artifact::ArtifactType;
+    use coa_composition::{
+        CompositionError, Granularity, Parallelism, TransactionError, Validation,
+    };
+    use coa_symbol::{SymbolMetadata, SymbolRef};
+    use std::str::FromStr;
+
+    /// Applies transforms in order
+    #[derive(Debug)]
+    struct ApplyStrategy;
+
+    impl CompositionStrategy for ApplyStrategy {
+        fn validate<T: ArtifactType>(
+            &self,
+            _deltas: &[StructuralDelta<T>],
+            _index: &SymbolRefIndex,
+        ) -> Result<Validation, CompositionError> {
+            Ok(Validation::minimal())
+        }
+
+        fn compose<T: ArtifactType>(
+            &self,
+            base: &Artifact<T>,
+            deltas: &[StructuralDelta<T>],
+        ) -> Result<Artifact<T>, CompositionError> {
+            deltas.iter().try_fold(base.clone(), |acc, delta| {
+                let DeltaOperation::Transform(transform) = delta.operation() else {
+                    return Err(CompositionError::CompositionFailed("unsupported".into()));
+                };
+                let content = transform
+                    .apply(acc.content())
+                    .map_err(|e| CompositionError::CompositionFailed(e.to_string()))?;
+                Artifact::new(content)
+                    .map_err(|e| CompositionError::CompositionFailed(e.to_string()))
+            })
+        }
+
+        fn parallelism(&self) -> Parallelism {
+            Parallelism::None
+        }
+
+        fn granularity(&self) -> Granularity {
+            Granularity::Node
+        }
+
+        fn name(&self) -> &'static str {
+            "Apply"
+        }
+    }
+
+    fn parse(source: &str) -> Artifact<CodeArtifact> {
+        CodeParser::new(Language::Rust).parse(source).unwrap()
+    }
+
+    fn path(s: &str) -> SymbolPath {
+        SymbolPath::from_str(s).unwrap()
+    }
+
+    fn segments(s: &str) -> Vec<String> {
+        path(s).segments().to_vec()
+    }
+
+    /// `auth.login` defined in `lib`, called from `auth.logout` and `main.run`
+    fn fixture() -> (SymbolRefIndex, Vec<Artifact<CodeArtifact>>) {
+        let lib = parse("fn login() {}\nfn logout() { login(); }\n");
+        let main = parse("fn run() { login(); let login_count = 1; }\n");
+
+        let index = SymbolRefIndex::new();
+        for (name, artifact) in [
+            ("auth.login", &lib),
+            ("auth.logout", &lib),
+            ("main.run", &main),
+        ] {
+            index
+                .insert(
+                    SymbolRef::new(segments(name), *artifact.hash()),
+                    SymbolMetadata::default(),
+                )
+                .unwrap();
+        }
+        index.add_reference(
+            &segments("auth.login"),
+            SymbolRef::new(segments("auth.logout"), *lib.hash()),
+        );
+        index.add_reference(
+            &segments("auth.login"),
+            SymbolRef::new(segments("main.run"), *main.hash()),
+        );
+
+        (index, vec![lib, main])
+    }
+
+    #[test]
+    fn rename_covers_definition_and_all_references() {
+        let (index, artifacts) = fixture();
+        let plan = RefactorEngine::new(&index)
+            .rename(&path("auth.login"), "sign_in")
+            .unwrap();
+
+        assert_eq!(plan.new_path().to_string(), "auth.sign_in");
+        assert_eq!(plan.delta_count(), 3);
+        assert_eq!(plan.artifacts()[0].artifact, *artifacts[0].hash());
+        assert!(plan.artifacts()[0].definition);
+        assert_eq!(plan.artifacts()[1].sites, vec![path("main.run")]);
+
+        let mut tx = CompositionTransaction::new(&index);
+        let slots = plan.stage(&mut tx, &ApplyStrategy, &artifacts).unwrap();
+        let mut committed = tx.commit().unwrap();
+
+        let lib = committed.take(slots[0]).unwrap();
+        let main = committed.take(slots[1]).unwrap();
+        assert_eq!(
+            lib.content().source,
+            "fn sign_in() {}\nfn logout() { sign_in(); }\n"
+        );
+        assert_eq!(lib.content().symbols, vec!["sign_in", "logout"]);
+        assert_eq!(
+            main.content().source,
+            "fn run() { sign_in(); let login_count = 1; }\n"
+        );
+    }
+
+    #[test]
+    fn dropping_a_reference_is_caught_by_transaction() {
+        let (index, artifacts) = fixture();
+        let plan = RefactorEngine::new(&index)
+            .rename(&path("auth.login"), "sign_in")
+            .unwrap();
+
+        let mut tx = CompositionTransaction::new(&index);
+        tx.add(
+            &ApplyStrategy,
+            &artifacts[0],
+            plan.deltas_for(&artifacts[0]),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            tx.commit(),
+            Err(TransactionError::DanglingReferences { .. })
+        ));
+    }
+
+    #[test]
+    fn rename_rejects_bad_requests() {
+        let (index, artifacts) = fixture();
+        let engine = RefactorEngine::new(&index);
+
+        assert!(matches!(
+            engine.rename(&path("auth.missing"), "x"),
+            Err(RefactorError::SymbolNotFound(_))
+        ));
+        assert!(matches!(
+            engine.rename(&path("auth.login"), "1st"),
+            Err(RefactorError::InvalidName(_))
+        ));
+        assert!(matches!(
+            engine.rename(&path("auth.login"), "logout"),
+            Err(RefactorError::NameTaken(_))
+        ));
+
+        let plan = engine.rename(&path("auth.login"), "sign_in").unwrap();
+        let mut tx = CompositionTransaction::new(&index);
+        assert!(matches!(
+            plan.stage(&mut tx, &ApplyStrategy, &artifacts[..1]),
+            Err(RefactorError::MissingArtifact(_))
+        ));
+    }
+
+    #[test]
+    fn rename_symbol_inverse_restores_source() {
+        let artifact = parse("fn login() {}\n");
+        let rename = RenameSymbol::definition("login", "sign_in");
+        let renamed = rename.apply(artifact.content()).unwrap();
+        let restored = rename.inverse().unwrap().apply(&renamed).unwrap();
+        assert_eq!(restored, *artifact.content());
+    }
+}
@@ -0,0 +1,446 @@
+//! Formatting-preserving code serializer
+//!
+//! Egress counterpart to [`CodeParser`]. Instead of regenerating source from
+//! an AST, [`CodeSerializer::apply`] splices each delta's new text into the
+//! original source at the target's recorded span, so every byte outside the
+//! edited spans (formatting, comments, blank lines) is carried over as is.
+//!
+//! `Add` deltas into an append region (a symbol marked with
+//! [`APPEND_MARKER`](crate::parsers::APPEND_MARKER)) are appended to the end
+//! of its body; several appends into one region land in content-hash order,
+//! so concurrent agents produce the same merged source in any batch order.
+
+use crate::error::{ApplyError, SerializeError};
+use crate::parsers::{ArtifactParser, CodeArtifact, CodeContent, CodeParser, Language};
+use crate::serializers::ArtifactSerializer;
+use coa_artifact::{Artifact, DeltaOperation, NodeSpan, StructuralDelta, SymbolPath};
+use coa_composition::CommutativeBatchStrategy;
+use coa_symbol::SymbolRefIndex;
+
+/// Replacement of one source span
+#[derive(Debug)]
+struct Splice {
+    span: NodeSpan,
+    text: String,
+    delta_index: usize,
+    /// Position in application order; orders splices at the same point
+    rank: usize,
+}
+
+/// Code serializer that splices deltas into the original source
+#[derive(Debug, Clone)]
+pub struct CodeSerializer {
+    language: Language,
+}
+
+impl CodeSerializer {
+    /// Create new serializer for language
+    #[inline]
+    #[must_use]
+    pub fn new(language: Language) -> Self {
+        Self { language }
+    }
+
+    /// Get serializer language
+    #[inline]
+    #[must_use]
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    /// Apply deltas to `base` by splicing source text
+    ///
+    /// - `Replace`: the target's span (or the delta's own span, if narrowed)
+    ///   is replaced by the new content's source; the root path replaces
+    ///   the whole file
+    /// - `Remove`: the target's span and its line break are dropped
+    /// - `Add`: the new source is inserted at the delta's (empty) span, or
+    ///   appended after a blank line; into an append region, it is appended
+    ///   to the region's body, in [`CommutativeBatchStrategy::append_order`]
+    /// - `Transform`: run on the spliced content, in delta order
+    ///
+    /// Spans refer to the base source, so all splices are computed against
+    /// it and must not overlap. The result is re-parsed to refresh symbols.
+    ///
+    /// # Errors
+    /// - `ApplyError::DeltaError` for a base mismatch or a span outside its target
+    /// - `ApplyError::TargetNotFound` / `TargetAlreadyExists` for bad targets
+    /// - `ApplyError::ValidationFailed` if two splices overlap, a span
+    ///   does not fall on character boundaries or an insertion point is
+    ///   past the end of the source
+    /// - `ApplyError::TransformFailed` if a transformation fails
+    pub fn apply(
+        &self,
+        base: &Artifact<CodeArtifact>,
+        deltas: &[StructuralDelta<CodeArtifact>],
+    ) -> Result<Artifact<CodeArtifact>, ApplyError> {
+        let content = base.content();
+        let regions = SymbolRefIndex::new();
+        regions
+            .declare_append_regions(base)
+            .map_err(|e| ApplyError::ValidationFailed(e.to_string()))?;
+        let mut splices = Vec::new();
+        let mut transforms = Vec::new();
+
+        let order = CommutativeBatchStrategy::append_order(deltas, &regions);
+        for (rank, delta_index) in order.into_iter().enumerate() {
+            let delta = &deltas[delta_index];
+            delta.validate_base(base)?;
+            // An `Add` target is not in the base; its span is an insertion point
+            if !matches!(delta.operation(), DeltaOperation::Add(_)) {
+                delta.validate_span(base)?;
+            }
+            let target_span = || {
+                symbol_span(content, delta.target())
+                    .ok_or_else(|| ApplyError::TargetNotFound(delta.target().clone()))
+            };
+
+            let splice = match delta.operation() {
+                DeltaOperation::Replace(new) if delta.target().is_empty() => Splice {
+                    span: NodeSpan::new(0, content.source.len()),
+                    text: new.source.clone(),
+                    delta_index,
+                    rank,
+                },
+                DeltaOperation::Replace(new) => Splice {
+                    span: delta.span().map_or_else(target_span, Ok)?,
+                    text: new.source.trim_end_matches('\n').to_string(),
+                    delta_index,
+                    rank,
+                },
+                DeltaOperation::Remove => {
+                    let span = target_span()?;
+                    let end = if content.source[span.end..].starts_with('\n') {
+                        span.end + 1
+                    } else {
+                        span.end
+                    };
+                    Splice {
+                        span: NodeSpan::new(span.start, end),
+                        text: String::new(),
+                        delta_index,
+                        rank,
+                    }
+                }
+                DeltaOperation::Add(new) => match symbol_span(content, delta.target()) {
+                    Some(region) if regions.is_append_region(delta.target().segments()) => {
+                        let (at, text) = self
+                            .region_append(&content.source, region, &new.source)
+                            .ok_or_else(|| {
+                                ApplyError::ValidationFailed(format!(
+                                    "append region {} has no body to append to",
+                                    delta.target()
+                                ))
+                            })?;
+                        Splice {
+                            span: NodeSpan::new(at, at),
+                            text,
+                            delta_index,
+                            rank,
+                        }
+                    }
+                    Some(_) => return Err(ApplyError::TargetAlreadyExists(delta.target().clone())),
+                    None => match delta.span() {
+                        Some(at) if at.start > content.source.len() => {
+                            return Err(ApplyError::ValidationFailed(format!(
+                                "delta {delta_index} inserts at {at}, past the end of the source"
+                            )));
+                        }
+                        Some(at) => Splice {
+                            span: NodeSpan::new(at.start, at.start),
+                            text: new.source.clone(),
+                            delta_index,
+                            rank,
+                        },
+                        None => Self::append(&content.source, &new.source, delta_index, rank),
+                    },
+                },
+                DeltaOperation::Transform(transform) => {
+                    transforms.push(transform);
+                    continue;
+                }
+                DeltaOperation::BinaryPatch(_) => {
+                    return Err(ApplyError::TransformFailed(
+                        "binary patch cannot apply to source code".to_string(),
+                    ));
+                }
+            };
+            splices.push(splice);
+        }
+
+        let mut source = Self::splice(&content.source, splices)?;
+        for transform in transforms {
+            let current = self.reparse(&source)?;
+            source = transform
+                .apply(current.content())
+                .map_err(|e| ApplyError::TransformFailed(e.to_string()))?
+                .source;
+        }
+        self.reparse(&source)
+    }
+
+    /// Append a new item after a blank line
+    fn append(source: &str, text: &str, delta_index: usize, rank: usize) -> Splice {
+        let separator = if source.is_empty() || source.ends_with("\n\n") {
+            ""
+        } else if source.ends_with('\n') {
+            "\n"
+        } else {
+            "\n\n"
+        };
+        let body = text.trim_end_matches('\n');
+        Splice {
+            span: NodeSpan::new(source.len(), source.len()),
+            text: format!("{separator}{body}\n"),
+            delta_index,
+            rank,
+        }
+    }
+
+    /// Insertion point and text appending `text` to the body of the region
+    /// at `span`, indented like the body
+    ///
+    /// Returns `None` if a brace-delimited region has no closing brace.
+    fn region_append(&self, source: &str, span: NodeSpan, text: &str) -> Option<(usize, String)> {
+        let line_start = |at: usize| source[..at].rfind('\n').map_or(0, |i| i + 1);
+        let header = &source[line_start(span.start)..span.start];
+        let outer = &header[..header.len() - header.trim_start().len()];
+        let body_indent = source[span.start..span.end]
+            .lines()
+            .skip(1)
+            .find(|line| !line.trim().is_empty() && line.trim() != "}")
+            .map_or_else(
+                || format!("{outer}    "),
+                |line| line[..line.len() - line.trim_start().len()].to_string(),
+            );
+        let body = text
+            .trim_end_matches('\n')
+            .lines()
+            .map(|line| if line.is_empty() { String::new() } else { format!("{body_indent}{line}") })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        if self.language == Language::Python {
+            return Some((span.end, format!("\n{body}")));
+        }
+        let close = span.start + source[span.start..span.end].rfind('}')?;
+        let close_line = line_start(close);
+        if close_line > span.start && source[close_line..close].trim().is_empty() {
+            Some((close_line, format!("{body}\n")))
+        } else {
+            Some((close, format!("\n{body}\n{outer}")))
+        }
+    }
+
+    /// Rebuild source from non-overlapping splices
+    fn splice(source: &str, mut splices: Vec<Splice>) -> Result<String, ApplyError> {
+        if let Some(bad) = splices
+            .iter()
+            .find(|s| !source.is_char_boundary(s.span.start) || !source.is_char_boundary(s.span.end))
+        {
+            return Err(ApplyError::ValidationFailed(format!(
+                "delta {} span {} is not on character boundaries of the source",
+                bad.delta_index, bad.span
+            )));
+        }
+        splices.sort_by_key(|s| (s.span.start, s.span.end, s.rank));
+        if let Some(pair) = splices.windows(2).find(|w| w[0].span.end > w[1].span.start) {
+            return Err(ApplyError::ValidationFailed(format!(
+                "deltas {} and {} edit overlapping spans {} and {}",
+                pair[0].delta_index, pair[1].delta_index, pair[0].span, pair[1].span
+            )));
+        }
+
+        let mut out = String::with_capacity(source.len());
+        let mut cursor = 0;
+        for splice in &splices {
+            out.push_str(&source[cursor..splice.span.start]);
+            out.push_str(&splice.text);
+            cursor = splice.span.end;
+        }
+        out.push_str(&source[cursor..]);
+        Ok(out)
+    }
+
+    fn reparse(&self, source: &str) -> Result<Artifact<CodeArtifact>, ApplyError> {
+        CodeParser::new(self.language)
+            .parse(source)
+            .map_err(|e| ApplyError::TransformFailed(e.to_string()))
+    }
+}
+
+/// Span of a single-segment symbol path
+fn symbol_span(content: &CodeContent, path: &SymbolPath) -> Option<NodeSpan> {
+    match path.segments() {
+        [name] => content.symbol_span(name),
+        _ => None,
+    }
+}
+
+impl ArtifactSerializer for CodeSerializer {
+    type Input = CodeArtifact;
+
+    /// Source text as stored; splicing already preserved formatting
+    fn serialize(&self, artifact: &Artifact<Self::Input>) -> Result<String, SerializeError> {
+        Ok(artifact.content().source.clone())
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.language.extensions()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::refactor::RenameSymbol;
+
+    const SOURCE: &str = "// entry point\nfn main() {\n    run();   // keep spacing\n}\n\n/// Helper\nfn run() {\n    println!(\"hi\");\n}\n";
+
+    fn base() -> Artifact<CodeArtifact> {
+        CodeParser::new(Language::Rust).parse(SOURCE).unwrap()
+    }
+
+    fn code(source: &str) -> CodeContent {
+        CodeParser::new(Language::Rust)
+            .parse(source)
+            .unwrap()
+            .content()
+            .clone()
+    }
+
+    fn delta(target: &str, op: DeltaOperation<CodeArtifact>) -> StructuralDelta<CodeArtifact> {
+        StructuralDelta::new(SymbolPath::single(target), op, *base().hash())
+    }
+
+    #[test]
+    fn replace_preserves_untouched_bytes() {
+        let serializer = CodeSerializer::new(Language::Rust);
+        let replaced = delta(
+            "run",
+            DeltaOperation::Replace(code("fn run() {\n    println!(\"bye\");\n}\n")),
+        );
+
+        let result = serializer.apply(&base(), &[replaced]).unwrap();
+        let out = serializer.serialize(&result).unwrap();
+        assert_eq!(out, SOURCE.replace("\"hi\"", "\"bye\""));
+    }
+
+    #[test]
+    fn narrowed_replace_and_remove_and_add() {
+        let serializer = CodeSerializer::new(Language::Rust);
+        let base = base();
+        let call = SOURCE.find("run();").unwrap();
+        let edits = [
+            delta("main", DeltaOperation::Replace(code("start()")))
+                .with_span(NodeSpan::new(call, call + "run()".len())),
+            delta("run", DeltaOperation::Remove),
+            delta("start", DeltaOperation::Add(code("fn start() {}"))),
+        ];
+
+        let result = serializer.apply(&base, &edits).unwrap();
+        assert_eq!(
+            result.content().source,
+            "// entry point\nfn main() {\n    start();   // keep spacing\n}\n\n/// Helper\n\nfn start() {}\n"
+        );
+        assert_eq!(result.content().symbols, ["main", "start"]);
+    }
+
+    #[test]
+    fn add_inserts_at_its_span() {
+        let serializer = CodeSerializer::new(Language::Rust);
+        let at = SOURCE.find("/// Helper").unwrap();
+        let added = delta("start", DeltaOperation::Add(code("fn start() {}\n\n")))
+            .with_span(NodeSpan::new(at, at));
+
+        let result = serializer.apply(&base(), &[added]).unwrap();
+        assert_eq!(
+            result.content().source,
+            SOURCE.replace("/// Helper", "fn start() {}\n\n/// Helper")
+        );
+
+        let past_end = delta("start", DeltaOperation::Add(code("fn start() {}")))
+            .with_span(NodeSpan::new(SOURCE.len() + 1, SOURCE.len() + 1));
+        assert!(matches!(
+            serializer.apply(&base(), &[past_end]),
+            Err(ApplyError::ValidationFailed(_))
+        ));
+    }
+
+    #[test]
+    fn overlapping_splices_are_rejected() {
+        let serializer = CodeSerializer::new(Language::Rust);
+        let edits = [
+            delta("run", DeltaOperation::Remove),
+            delta("run", DeltaOperation::Replace(code("fn run() {}"))),
+        ];
+        assert!(matches!(
+            serializer.apply(&base(), &edits),
+            Err(ApplyError::ValidationFailed(_))
+        ));
+    }
+
+    #[test]
+    fn region_appends_merge_in_hash_order() {
+        use coa_composition::CompositionStrategy;
+
+        let source = "// coa:append\nenum Plugin {\n    Core,\n}\n\n// coa:append\nstruct Registry {}\n";
+        let artifact = CodeParser::new(Language::Rust).parse(source).unwrap();
+        let append = |target: &str, text: &str| {
+            StructuralDelta::new(SymbolPath::single(target), DeltaOperation::Add(code(text)), *artifact.hash())
+        };
+        let edits = vec![
+            append("Plugin", "Lint,"),
+            append("Plugin", "Format,"),
+            append("Registry", "plugin: Plugin,"),
+        ];
+
+        let index = SymbolRefIndex::new();
+        index.declare_append_regions(&artifact).unwrap();
+        CommutativeBatchStrategy::new().validate(&edits, &index).unwrap();
+
+        let serializer = CodeSerializer::new(Language::Rust);
+        let merged = serializer.apply(&artifact, &edits).unwrap().content().source.clone();
+        let mut ordered = [&edits[0], &edits[1]];
+        ordered.sort_by_key(|delta| match delta.operation() {
+            DeltaOperation::Add(content) => <CodeArtifact as coa_artifact::ArtifactType>::hash(content),
+            _ => unreachable!(),
+        });
+        let body = |delta: &StructuralDelta<CodeArtifact>| match delta.operation() {
+            DeltaOperation::Add(content) => format!("    {}\n", content.source),
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            merged,
+            format!(
+                "// coa:append\nenum Plugin {{\n    Core,\n{}{}}}\n\n// coa:append\nstruct Registry {{\n    plugin: Plugin,\n}}\n",
+                body(ordered[0]),
+                body(ordered[1])
+            )
+        );
+
+        // Batch order does not change the result
+        let reversed: Vec<_> = edits.iter().rev().cloned().collect();
+        assert_eq!(serializer.apply(&artifact, &reversed).unwrap().content().source, merged);
+
+        // Adding an existing symbol outside a region still fails
+        let duplicate = delta("run", DeltaOperation::Add(code("fn run() {}")));
+        assert!(matches!(
+            serializer.apply(&base(), &[duplicate]),
+            Err(ApplyError::TargetAlreadyExists(_))
+        ));
+    }
+
+    #[test]
+    fn transforms_run_after_splicing() {
+        let serializer = CodeSerializer::new(Language::Rust);
+        let edits = [delta(
+            "run",
+            DeltaOperation::Transform(Box::new(RenameSymbol::definition("run", "execute"))),
+        )];
+
+        let result = serializer.apply(&base(), &edits).unwrap();
+        assert_eq!(result.content().source, SOURCE.replace("run", "execute"));
+        assert_eq!(result.content().symbols, ["main", "execute"]);
+    }
+}
@@ -0,0 +1,24 @@
+//! Artifact serializers (egress)
+//!
+//! Counterparts to [`parsers`](crate::parsers): turn typed artifacts back
+//! into file content.
+//! - Code files: formatting-preserving splicing of structural deltas
+
+use crate::error::SerializeError;
+use coa_artifact::{Artifact, ArtifactType};
+
+mod code;
+
+pub use code::CodeSerializer;
+
+/// Serializer trait for converting typed artifacts into file content
+pub trait ArtifactSerializer: Send + Sync + 'static {
+    /// The artifact type this serializer consumes
+    type Input: ArtifactType;
+
+    /// Render artifact as file content
+    fn serialize(&self, artifact: &Artifact<Self::Input>) -> Result<String, SerializeError>;
+
+    /// Supported file extensions (without dot)
+    fn extensions(&self) -> &[&str];
+}
@@ -0,0 +1,135 @@
+//! Speculative execution against artifact snapshots
+//!
+//! L3+ subgraphs may run ahead of their gates: a [`Speculation`] is a fork
+//! of the layer whose cache shares content with the real one but records
+//! generations privately. Work staged in it stays invisible to the
+//! workspace until the subgraph's gate nodes pass and the caller
+//! [commits](crate::ConstitutionalLayer::commit) it; a failed gate
+//! [aborts](crate::ConstitutionalLayer::abort) it instead.
+//!
+//! Only generations are isolated: the fork shares the layer's symbol index,
+//! so speculative work should compose rather than re-ingest.
+//!
+//! ```rust,ignore
+//! let speculation = layer.scoped(guard).speculate()?;
+//! let composed = speculation.layer().apply_deltas(&base, &deltas, &strategy, &index)?;
+//! speculation.stage("src/auth.rs", composed).await;
+//! if gate_passed {
+//!     layer.commit(speculation)?;
+//! } else {
+//!     layer.abort(speculation).await;
+//! }
+//! ```
+
+use crate::cache::{CacheSnapshot, TypedCacheKey};
+use crate::layer::ConstitutionalLayer;
+use coa_artifact::{Artifact, ArtifactType};
+
+/// Forked layer holding uncommitted generations
+#[derive(Debug)]
+pub struct Speculation {
+    layer: ConstitutionalLayer,
+    base: CacheSnapshot,
+}
+
+impl Speculation {
+    pub(crate) fn new(layer: ConstitutionalLayer, base: CacheSnapshot) -> Self {
+        Self { layer, base }
+    }
+
+    /// Forked layer to run the speculative work through
+    #[inline]
+    #[must_use]
+    pub fn layer(&self) -> &ConstitutionalLayer {
+        &self.layer
+    }
+
+    /// Generations of the real cache when the speculation started
+    #[inline]
+    #[must_use]
+    pub fn base(&self) -> &CacheSnapshot {
+        &self.base
+    }
+
+    /// Record `artifact` as the next generation of `path` in the fork
+    pub async fn stage<T: ArtifactType>(&self, path: &str, artifact: Artifact<T>) -> TypedCacheKey {
+        let revision = self
+            .layer
+            .cache()
+            .generations::<T>(path)
+            .last()
+            .and_then(TypedCacheKey::revision)
+            .map_or(1, |revision| revision + 1);
+        let key = TypedCacheKey::for_generation::<T>(*artifact.hash(), path, revision);
+        self.layer
+            .cache()
+            .insert_generation(key.clone(), artifact)
+            .await;
+        key
+    }
+
+    /// Generations staged so far, oldest first per path
+    #[must_use]
+    pub fn staged(&self) -> Vec<TypedCacheKey> {
+        self.layer.cache().generations_since(&self.base)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::SpeculationError;
+    use crate::parsers::{ArtifactParser, JsonArtifact, JsonParser};
+
+    fn json(source: &str) -> Artifact<JsonArtifact> {
+        JsonParser.parse(source).unwrap()
+    }
+
+    #[tokio::test]
+    async fn gated_work_is_committed_or_aborted() {
+        let layer = ConstitutionalLayer::new();
+        let path = "config/app.json";
+        let v1 =
+            TypedCacheKey::for_generation::<JsonArtifact>(*json(r#"{"port": 80}"#).hash(), path, 1);
+        layer
+            .cache()
+            .insert_generation(v1, json(r#"{"port": 80}"#))
+            .await;
+
+        // Gate passed: staged generations become the workspace's latest
+        let speculation = layer.speculate();
+        let staged = speculation.stage(path, json(r#"{"port": 8080}"#)).await;
+        assert_eq!(staged.revision(), Some(2));
+        assert_eq!(layer.cache().generations::<JsonArtifact>(path).len(), 1);
+        assert_eq!(layer.commit(speculation).unwrap(), std::slice::from_ref(&staged));
+        let (latest, _) = layer.cache().latest::<JsonArtifact>(path).await.unwrap();
+        assert_eq!(latest, staged);
+
+        // Gate failed: nothing reaches the workspace
+        let speculation = layer.speculate();
+        let rejected = json(r#"{"port": 0}"#);
+        speculation.stage(path, rejected.clone()).await;
+        assert_eq!(layer.abort(speculation).await, 1);
+        assert!(!layer.cache().contains(rejected.hash()).await);
+        assert_eq!(layer.cache().generations::<JsonArtifact>(path).len(), 2);
+
+        // The workspace moved on while speculating
+        let speculation = layer.speculate();
+        speculation.stage(path, json(r#"{"port": 1}"#)).await;
+        let concurrent = layer.speculate();
+        concurrent.stage(path, json(r#"{"port": 2}"#)).await;
+        layer.commit(concurrent).unwrap();
+        assert!(matches!(
+            layer.commit(speculation),
+            Err(SpeculationError::Conflict {
+                base: Some(2),
+                current: Some(3),
+                ..
+            })
+        ));
+        assert!(matches!(
+            ConstitutionalLayer::new().commit(layer.speculate()),
+            Err(SpeculationError::ForeignLayer)
+        ));
+    }
+}
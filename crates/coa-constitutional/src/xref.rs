@@ -0,0 +1,312 @@
+//! Spec cross-references
+//!
+//! Markdown specs link to their own headings, to other files and, from code
+//! fences, to workspace symbols (see [`SpecLink`]). Workspace ingress records
+//! every link that resolves in the [`SymbolRefIndex`], as a reference from
+//! the linking section to its target. A composition of a spec then fails
+//! (see [`ConstitutionalLayer::check_spec_references`]) if it leaves:
+//!
+//! - a link in the new document dangling that resolved before
+//! - a reference recorded from another file pointing at a removed section
+//!
+//! Links into files no parser handles (images, PDFs) cannot be checked and
+//! are ignored; other files count as missing until they have indexed
+//! symbols.
+//!
+//! [`ConstitutionalLayer::check_spec_references`]: crate::ConstitutionalLayer::check_spec_references
+
+use crate::ingress;
+use crate::parsers::{LinkKind, MarkdownArtifact, MarkdownContent, SpecLink, ANCHOR_ATTRIBUTE};
+use coa_artifact::{AddressableContent, Artifact, SymbolPath};
+use coa_symbol::{IndexEntry, SymbolRef, SymbolRefIndex};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+
+/// Link a composition would leave dangling
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrokenReference {
+    /// Index key of the linking section
+    pub site: Vec<String>,
+    /// Link as written (or, for incoming references, the removed target)
+    pub link: String,
+    /// Why it no longer resolves
+    pub reason: String,
+}
+
+impl fmt::Display for BrokenReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} -> {}: {}",
+            self.site.join("/"),
+            self.link,
+            self.reason
+        )
+    }
+}
+
+/// Resolves spec links against the workspace index
+pub(crate) struct SpecReferences<'a> {
+    index: &'a SymbolRefIndex,
+    /// Extensions of files with a parser (links to others are unchecked)
+    extensions: Vec<&'a str>,
+}
+
+impl<'a> SpecReferences<'a> {
+    pub(crate) fn new(index: &'a SymbolRefIndex, extensions: Vec<&'a str>) -> Self {
+        Self { index, extensions }
+    }
+
+    /// Record resolvable links of the spec at `doc` as index references
+    ///
+    /// Returns the number recorded.
+    pub(crate) fn record(&self, doc: &Path, spec: &Artifact<MarkdownArtifact>) -> usize {
+        let mut recorded = 0;
+        for link in &spec.content().links {
+            match self.resolve(doc, spec.content(), link) {
+                Ok(Some(target)) => {
+                    let site = ingress::workspace_symbol(doc, &link.section);
+                    self.index
+                        .add_reference(&target, SymbolRef::new(site, *spec.hash()));
+                    recorded += 1;
+                }
+                Ok(None) => {}
+                Err(reason) => tracing::debug!(
+                    file = %doc.display(),
+                    link = %link,
+                    reason = %reason,
+                    "dangling spec link"
+                ),
+            }
+        }
+        recorded
+    }
+
+    /// References `result` breaks that `base` did not
+    pub(crate) fn check(
+        &self,
+        doc: &Path,
+        base: &Artifact<MarkdownArtifact>,
+        result: &Artifact<MarkdownArtifact>,
+    ) -> Vec<BrokenReference> {
+        let already_broken: HashSet<(LinkKind, &str, Option<&str>)> = base
+            .content()
+            .links
+            .iter()
+            .filter(|link| self.resolve(doc, base.content(), link).is_err())
+            .map(link_key)
+            .collect();
+
+        let mut broken: Vec<BrokenReference> = result
+            .content()
+            .links
+            .iter()
+            .filter(|link| !already_broken.contains(&link_key(link)))
+            .filter_map(|link| {
+                let reason = self.resolve(doc, result.content(), link).err()?;
+                Some(BrokenReference {
+                    site: ingress::workspace_symbol(doc, &link.section),
+                    link: link.to_string(),
+                    reason,
+                })
+            })
+            .collect();
+
+        // The document's own links were checked above
+        let prefix = ingress::file_prefix(doc);
+        for reference in self.index.references_to(&prefix) {
+            if reference.site.parent_hash() == base.hash() {
+                continue;
+            }
+            let section = SymbolPath::new(reference.target[prefix.len()..].to_vec());
+            if !result.content().resolves(&section) {
+                broken.push(BrokenReference {
+                    site: reference.site.path().to_vec(),
+                    link: format!("{}#{section}", ingress::slash_path(doc)),
+                    reason: "section removed".to_string(),
+                });
+            }
+        }
+        broken
+    }
+
+    /// Index key `link` points at
+    ///
+    /// `Ok(None)` if the target cannot be checked (file without a parser).
+    fn resolve(
+        &self,
+        doc: &Path,
+        content: &MarkdownContent,
+        link: &SpecLink,
+    ) -> Result<Option<Vec<String>>, String> {
+        match link.kind {
+            LinkKind::Anchor => {
+                let anchor = link.anchor.as_deref().unwrap_or_default();
+                content
+                    .resolve_anchor(anchor)
+                    .map(|section| Some(ingress::workspace_symbol(doc, &section)))
+                    .ok_or_else(|| format!("no heading #{anchor}"))
+            }
+            LinkKind::File => {
+                let file = normalize(&doc.parent().unwrap_or(Path::new("")).join(&link.target))
+                    .ok_or_else(|| "path escapes the workspace".to_string())?;
+                let prefix = ingress::file_prefix(&file);
+                let entries = self.entries_under(&prefix);
+                if entries.is_empty() {
+                    let checked = file
+                        .extension()
+                        .and_then(|e| e.to_str())
+                        .is_some_and(|e| self.extensions.contains(&e));
+                    return if checked {
+                        Err(format!("no file {}", ingress::slash_path(&file)))
+                    } else {
+                        Ok(None)
+                    };
+                }
+                let Some(anchor) = &link.anchor else {
+                    return Ok(Some(prefix));
+                };
+                entries
+                    .iter()
+                    .find_map(|entry| {
+                        let depth = entry
+                            .metadata
+                            .attributes
+                            .iter()
+                            .filter_map(|a| a.strip_prefix(ANCHOR_ATTRIBUTE))
+                            .position(|a| a.eq_ignore_ascii_case(anchor))?;
+                        Some(entry.symbol.path()[..prefix.len() + depth + 1].to_vec())
+                    })
+                    .map(Some)
+                    .ok_or_else(|| {
+                        format!("no heading #{anchor} in {}", ingress::slash_path(&file))
+                    })
+            }
+            LinkKind::Symbol => {
+                let (file, symbol) = link.target.split_once("::").unwrap_or((&link.target, ""));
+                let mut key = ingress::file_prefix(Path::new(file));
+                key.extend(
+                    symbol
+                        .split("::")
+                        .filter(|s| !s.is_empty())
+                        .map(str::to_string),
+                );
+                if self.entries_under(&key).is_empty() {
+                    Err("no such symbol".to_string())
+                } else {
+                    Ok(Some(key))
+                }
+            }
+        }
+    }
+
+    /// Indexed symbols at or beneath `key`
+    fn entries_under(&self, key: &[String]) -> Vec<IndexEntry> {
+        self.index
+            .get_descendants(key)
+            .into_iter()
+            .filter(|e| e.symbol.path().starts_with(key))
+            .collect()
+    }
+}
+
+/// Identity of a link regardless of the section it sits in
+fn link_key(link: &SpecLink) -> (LinkKind, &str, Option<&str>) {
+    (link.kind, link.target.as_str(), link.anchor.as_deref())
+}
+
+/// Resolve `.` and `..` lexically; `None` if the path leaves the root
+fn normalize(path: &Path) -> Option<PathBuf> {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => out.push(part),
+            Component::ParentDir => {
+                if !out.pop() {
+                    return None;
+                }
+            }
+            Component::CurDir => {}
+            // Absolute links are relative to the workspace root
+            Component::RootDir | Component::Prefix(_) => out = PathBuf::new(),
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::{ArtifactParser, MarkdownParser};
+    use crate::{ApplyError, ConstitutionalLayer};
+
+    #[tokio::test]
+    async fn ingress_records_links_and_compositions_reject_breaking_them() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("docs")).unwrap();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        let api = "# API\n\n## Auth\n\nSee [limits](#limits).\n\n## Limits\n\n100/s\n";
+        std::fs::write(root.join("docs/api.md"), api).unwrap();
+        std::fs::write(
+            root.join("docs/guide.md"),
+            "# Guide\n\nRead [auth](api.md#auth) and [logo](logo.png).\n\n```\n@see src/auth.rs::login\n```\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("src/auth.rs"), "pub fn login() {}\n").unwrap();
+
+        let layer = ConstitutionalLayer::new();
+        let report = layer.ingress_workspace(root, &[]).await.unwrap();
+        assert!(report.is_clean());
+        let index = layer.symbol_index();
+        let into_auth =
+            index.references_to(&["docs".into(), "api.md".into(), "api".into(), "auth".into()]);
+        assert_eq!(into_auth.len(), 1);
+        assert_eq!(into_auth[0].site.path(), ["docs", "guide.md", "guide"]);
+        assert_eq!(
+            index
+                .references_to(&["src".into(), "auth.rs".into(), "login".into()])
+                .len(),
+            1
+        );
+
+        let base = MarkdownParser.parse(api).unwrap();
+        let reworded = MarkdownParser
+            .parse("# API\n\n## Auth\n\nTokens. See [limits](#limits).\n\n## Limits\n\n50/s\n")
+            .unwrap();
+        layer
+            .check_spec_references("docs/api.md", &base, &reworded)
+            .unwrap();
+
+        // Dropping `Auth` breaks guide.md; renaming `Limits` breaks the anchor
+        let broken = MarkdownParser
+            .parse("# API\n\nSee [limits](#limits).\n\n## Quotas\n\n100/s\n")
+            .unwrap();
+        let Err(ApplyError::BrokenReferences(broken)) =
+            layer.check_spec_references("docs/api.md", &base, &broken)
+        else {
+            panic!("expected broken references");
+        };
+        let links: Vec<&str> = broken.iter().map(|b| b.link.as_str()).collect();
+        assert_eq!(links, vec!["#limits", "docs/api.md#api.auth"]);
+
+        // Links that were already dangling are not blamed on the composition
+        let dangling = MarkdownParser.parse("# API\n\n[gone](#gone)\n").unwrap();
+        let still = MarkdownParser
+            .parse("# API\n\n[gone](#gone) again\n")
+            .unwrap();
+        let refs = SpecReferences::new(index, vec!["md"]);
+        assert!(refs
+            .check(Path::new("docs/other.md"), &dangling, &still)
+            .is_empty());
+        let new_link = MarkdownParser
+            .parse("# API\n\n[missing](missing.md)\n")
+            .unwrap();
+        assert_eq!(
+            refs.check(Path::new("docs/other.md"), &dangling, &new_link)
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,27 @@
+//! Golden-file regression suite for parsers and serializers
+//!
+//! Rewrite the snapshots after an intended parser change with:
+//!
+//! ```text
+//! BLESS_GOLDENS=1 cargo test -p coa-constitutional --test golden
+//! ```
+
+use coa_constitutional::golden::{describe_mismatches, GoldenSuite};
+use std::path::Path;
+
+fn suite() -> GoldenSuite {
+    GoldenSuite::new(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden"))
+}
+
+#[test]
+fn corpus_matches_goldens() {
+    let suite = suite();
+    if std::env::var_os("BLESS_GOLDENS").is_some() {
+        let blessed = suite.bless().expect("bless goldens");
+        eprintln!("blessed {blessed} golden file(s)");
+        return;
+    }
+
+    let mismatches = suite.check().expect("snapshot corpus");
+    assert!(mismatches.is_empty(), "{}", describe_mismatches(&mismatches));
+}
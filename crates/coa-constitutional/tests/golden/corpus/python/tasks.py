@@ -0,0 +1,18 @@
+import json
+from dataclasses import dataclass
+
+
+@dataclass
+class Task:
+    name: str
+    priority: int = 0
+
+    def to_json(self) -> str:
+        return json.dumps({"name": self.name, "priority": self.priority})
+
+
+def sort_tasks(tasks):
+    return sorted(tasks, key=lambda t: t.priority)
+
+
+MAX_TASKS = 100
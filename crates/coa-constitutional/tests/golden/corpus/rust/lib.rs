@@ -0,0 +1,34 @@
+//! Sample library
+use std::collections::HashMap;
+
+/// Registry of named values
+pub struct Registry {
+    values: HashMap<String, u64>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self {
+            values: HashMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, name: &str, value: u64) {
+        self.values.insert(name.to_string(), value);
+    }
+}
+
+pub enum Mode {
+    Fast,
+    Safe,
+}
+
+pub trait Named {
+    fn name(&self) -> &str;
+}
+
+pub const LIMIT: usize = 16;
+
+pub fn total(registry: &Registry) -> u64 {
+    registry.values.values().sum()
+}
@@ -0,0 +1,20 @@
+import { Request } from "./request";
+
+export interface Options {
+  retries: number;
+  timeout: number;
+}
+
+export class Client {
+  constructor(private readonly options: Options) {}
+
+  send(request: Request): Promise<string> {
+    return Promise.resolve(request.url);
+  }
+}
+
+export function defaultOptions(): Options {
+  return { retries: 3, timeout: 1000 };
+}
+
+export type Handler = (body: string) => void;
@@ -0,0 +1,368 @@
+//! Backpressure-aware admission control
+//!
+//! [`AdmissionController`] decides whether newly decomposed tasks may start
+//! before any agent is acquired for them. It looks at:
+//! - agent pool load (active agents over pool capacity)
+//! - kernel queue depth (nodes waiting for execution, via a probe)
+//! - memory pressure (a probe, else memory claimed by active agents)
+//! - tasks already admitted and still running
+//!
+//! Overloaded systems turn work away with [`Busy`] and a retry delay that
+//! grows with the overload, instead of queuing it unboundedly. Admitted work
+//! holds an [`AdmissionPermit`] until it finishes.
+
+use crate::agent_pool::AgentPool;
+use crate::error::Busy;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Signal that turned work away
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Overload {
+    /// Too many of the pool's agents are active
+    PoolLoad,
+    /// Too many nodes wait in the kernel's execution queue
+    KernelQueue,
+    /// Too much memory is in use
+    MemoryPressure,
+    /// Too many admitted tasks are still running
+    InFlight,
+}
+
+impl Overload {
+    /// Stable name
+    #[inline]
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::PoolLoad => "pool_load",
+            Self::KernelQueue => "kernel_queue",
+            Self::MemoryPressure => "memory_pressure",
+            Self::InFlight => "in_flight",
+        }
+    }
+}
+
+impl fmt::Display for Overload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Ceilings above which new work is refused
+///
+/// Fractions are of the respective capacity; `None` disables a check.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AdmissionPolicy {
+    /// Pool load at which work is refused
+    pub max_pool_load: f64,
+    /// Kernel queue depth (nodes) at which work is refused
+    pub max_queue_depth: Option<usize>,
+    /// Memory pressure at which work is refused
+    pub max_memory_pressure: f64,
+    /// Admitted-but-unfinished tasks beyond which work is refused
+    pub max_in_flight_tasks: Option<usize>,
+    /// Retry delay suggested at the ceiling; scaled by the overload
+    pub retry_after: Duration,
+}
+
+impl Default for AdmissionPolicy {
+    fn default() -> Self {
+        Self {
+            max_pool_load: 1.0,
+            max_queue_depth: None,
+            max_memory_pressure: 0.95,
+            max_in_flight_tasks: None,
+            retry_after: Duration::from_secs(1),
+        }
+    }
+}
+
+impl AdmissionPolicy {
+    /// Default ceilings
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Refuse work once this fraction of the pool is active
+    #[inline]
+    #[must_use]
+    pub fn with_max_pool_load(mut self, fraction: f64) -> Self {
+        self.max_pool_load = fraction;
+        self
+    }
+
+    /// Refuse work once the kernel queue holds `depth` nodes
+    #[inline]
+    #[must_use]
+    pub fn with_max_queue_depth(mut self, depth: usize) -> Self {
+        self.max_queue_depth = Some(depth);
+        self
+    }
+
+    /// Refuse work once this fraction of memory is in use
+    #[inline]
+    #[must_use]
+    pub fn with_max_memory_pressure(mut self, fraction: f64) -> Self {
+        self.max_memory_pressure = fraction;
+        self
+    }
+
+    /// Refuse work that would take running tasks past `tasks`, unless none are
+    #[inline]
+    #[must_use]
+    pub fn with_max_in_flight_tasks(mut self, tasks: usize) -> Self {
+        self.max_in_flight_tasks = Some(tasks);
+        self
+    }
+
+    /// Suggest retrying after `delay` when just at a ceiling
+    #[inline]
+    #[must_use]
+    pub fn with_retry_after(mut self, delay: Duration) -> Self {
+        self.retry_after = delay;
+        self
+    }
+}
+
+type Probe<T> = Arc<dyn Fn() -> T + Send + Sync>;
+
+/// Gate in front of task execution
+#[derive(Clone, Default)]
+pub struct AdmissionController {
+    policy: AdmissionPolicy,
+    queue_depth: Option<Probe<usize>>,
+    memory_pressure: Option<Probe<f64>>,
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl fmt::Debug for AdmissionController {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AdmissionController")
+            .field("policy", &self.policy)
+            .field("queue_depth", &self.queue_depth.is_some())
+            .field("memory_pressure", &self.memory_pressure.is_some())
+            .field("in_flight", &self.in_flight())
+            .finish()
+    }
+}
+
+impl AdmissionController {
+    /// Controller enforcing `policy`
+    #[inline]
+    #[must_use]
+    pub fn new(policy: AdmissionPolicy) -> Self {
+        Self {
+            policy,
+            ..Self::default()
+        }
+    }
+
+    /// Read the kernel's execution queue depth from `probe`
+    #[must_use]
+    pub fn with_queue_depth(mut self, probe: impl Fn() -> usize + Send + Sync + 'static) -> Self {
+        self.queue_depth = Some(Arc::new(probe));
+        self
+    }
+
+    /// Read memory pressure (fraction of memory in use) from `probe`
+    ///
+    /// Without one, the memory claimed by the pool's active agents is used.
+    #[must_use]
+    pub fn with_memory_pressure(mut self, probe: impl Fn() -> f64 + Send + Sync + 'static) -> Self {
+        self.memory_pressure = Some(Arc::new(probe));
+        self
+    }
+
+    /// Enforced ceilings
+    #[inline]
+    #[must_use]
+    pub fn policy(&self) -> &AdmissionPolicy {
+        &self.policy
+    }
+
+    /// Admitted tasks not yet finished
+    #[inline]
+    #[must_use]
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Acquire)
+    }
+
+    /// Admit `tasks` new tasks to run on `pool`
+    ///
+    /// A batch larger than the in-flight ceiling is admitted on its own once
+    /// nothing else is in flight, rather than being turned away forever.
+    ///
+    /// # Errors
+    /// Returns [`Busy`] naming the first overloaded signal and when to retry
+    pub fn admit(&self, tasks: usize, pool: &AgentPool) -> Result<AdmissionPermit, Busy> {
+        let policy = &self.policy;
+        self.check(Overload::PoolLoad, pool.load(), policy.max_pool_load)?;
+        if let (Some(probe), Some(max)) = (&self.queue_depth, policy.max_queue_depth) {
+            self.check_count(Overload::KernelQueue, probe(), max)?;
+        }
+        let memory = match &self.memory_pressure {
+            Some(probe) => Some(probe()),
+            None => pool.memory_pressure(),
+        };
+        if let Some(memory) = memory {
+            self.check(Overload::MemoryPressure, memory, policy.max_memory_pressure)?;
+        }
+
+        let admitted = match policy.max_in_flight_tasks {
+            Some(max) => self
+                .in_flight
+                .fetch_update(Ordering::AcqRel, Ordering::Acquire, |current| {
+                    (current == 0 || current + tasks <= max).then_some(current + tasks)
+                })
+                .map_err(|current| self.busy(Overload::InFlight, ratio(current + tasks, max))),
+            None => Ok(self.in_flight.fetch_add(tasks, Ordering::AcqRel)),
+        };
+        admitted.map(|_| AdmissionPermit {
+            tasks,
+            in_flight: Arc::clone(&self.in_flight),
+        })
+    }
+
+    fn check(&self, reason: Overload, load: f64, max: f64) -> Result<(), Busy> {
+        if load >= max {
+            Err(self.busy(reason, if max > 0.0 { load / max } else { 1.0 }))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn check_count(&self, reason: Overload, count: usize, max: usize) -> Result<(), Busy> {
+        if count >= max {
+            Err(self.busy(reason, ratio(count, max)))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Rejection with the retry delay scaled by `overload` (1.0 at the ceiling)
+    fn busy(&self, reason: Overload, overload: f64) -> Busy {
+        Busy {
+            reason,
+            retry_after: self.policy.retry_after.mul_f64(overload.clamp(1.0, 60.0)),
+        }
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn ratio(count: usize, max: usize) -> f64 {
+    if max == 0 {
+        1.0
+    } else {
+        count as f64 / max as f64
+    }
+}
+
+/// Admission of a batch of tasks, released on drop
+#[derive(Debug)]
+pub struct AdmissionPermit {
+    tasks: usize,
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl AdmissionPermit {
+    /// Number of tasks admitted
+    #[inline]
+    #[must_use]
+    pub fn tasks(&self) -> usize {
+        self.tasks
+    }
+}
+
+impl Drop for AdmissionPermit {
+    fn drop(&mut self) {
+        self.in_flight.fetch_sub(self.tasks, Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{AgentSpec, SystemLimits};
+
+    #[test]
+    fn admits_until_in_flight_ceiling() {
+        let pool = AgentPool::new(4);
+        let controller = AdmissionController::new(AdmissionPolicy::new().with_max_in_flight_tasks(3));
+
+        let permit = controller.admit(2, &pool).unwrap();
+        assert_eq!(controller.in_flight(), 2);
+        let busy = controller.admit(2, &pool).unwrap_err();
+        assert_eq!(busy.reason, Overload::InFlight);
+        assert!(busy.retry_after >= controller.policy().retry_after);
+
+        drop(permit);
+        assert_eq!(controller.in_flight(), 0);
+        assert!(controller.admit(3, &pool).is_ok());
+    }
+
+    #[test]
+    fn oversized_batch_is_admitted_alone() {
+        let pool = AgentPool::new(4);
+        let controller = AdmissionController::new(AdmissionPolicy::new().with_max_in_flight_tasks(3));
+
+        let small = controller.admit(1, &pool).unwrap();
+        assert_eq!(controller.admit(5, &pool).unwrap_err().reason, Overload::InFlight);
+
+        drop(small);
+        let batch = controller.admit(5, &pool).unwrap();
+        assert_eq!(controller.in_flight(), 5);
+        assert!(controller.admit(1, &pool).is_err());
+
+        drop(batch);
+        assert_eq!(controller.in_flight(), 0);
+    }
+
+    #[test]
+    fn kernel_queue_depth_scales_retry_delay() {
+        let depth = Arc::new(AtomicUsize::new(0));
+        let probe = Arc::clone(&depth);
+        let controller = AdmissionController::new(
+            AdmissionPolicy::new()
+                .with_max_queue_depth(10)
+                .with_retry_after(Duration::from_millis(100)),
+        )
+        .with_queue_depth(move || probe.load(Ordering::Relaxed));
+        let pool = AgentPool::new(4);
+
+        assert!(controller.admit(1, &pool).is_ok());
+        depth.store(30, Ordering::Relaxed);
+        let busy = controller.admit(1, &pool).unwrap_err();
+        assert_eq!(busy.reason, Overload::KernelQueue);
+        assert_eq!(busy.retry_after, Duration::from_millis(300));
+    }
+
+    #[test]
+    fn memory_pressure_from_probe() {
+        let controller = AdmissionController::new(AdmissionPolicy::new().with_max_memory_pressure(0.8))
+            .with_memory_pressure(|| 0.9);
+        let busy = controller.admit(1, &AgentPool::new(4)).unwrap_err();
+        assert_eq!(busy.reason, Overload::MemoryPressure);
+    }
+
+    #[tokio::test]
+    async fn pool_load_and_claimed_memory_refuse_work() {
+        let limits = SystemLimits {
+            max_memory_gb: 1,
+            ..SystemLimits::default()
+        };
+        let pool = AgentPool::new(2).with_limits(limits);
+        let controller = AdmissionController::new(AdmissionPolicy::new().with_max_pool_load(0.5));
+        assert!(controller.admit(1, &pool).is_ok());
+
+        let _agent = pool.acquire(AgentSpec::new("coder")).await.unwrap();
+        assert_eq!(controller.admit(1, &pool).unwrap_err().reason, Overload::PoolLoad);
+        assert!(pool.memory_pressure().is_some_and(|p| p > 0.0));
+    }
+}
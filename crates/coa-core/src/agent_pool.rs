@@ -2,14 +2,69 @@
 //!
 //! Provides efficient agent reuse and lifecycle management:
 //! - Agent acquisition (create or reuse)
+//! - Capability-matched task assignment ([`AgentCapabilities`])
 //! - Message passing to agents
+//! - Supervised delivery with agent restarts
 //! - Pool statistics and monitoring
+//! - Priority preemption under [`SystemLimits`] pressure
+//! - Seeded fault injection ([`ChaosConfig`]) for supervision tests
 
+use crate::capability::{AgentCapabilities, CapabilityMismatch, RejectedAgent};
+use crate::chaos::{Chaos, ChaosConfig, ChaosEvent, ChaosFault};
 use crate::error::PoolError;
-use crate::types::{AgentId, AgentSpec, Task};
+use crate::remote::{RemoteWorker, WorkerFrame};
+use crate::types::{AgentId, AgentSpec, ResourceCaps, SystemLimits, Task, TaskPriority};
+use coa_symbol::ClaimService;
 use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use tokio::sync::{mpsc, Mutex};
 
+/// Default number of restarts per supervised send
+pub const DEFAULT_MAX_RESTARTS: usize = 3;
+
+/// Callback run when an agent is preempted
+pub type PreemptHook = Arc<dyn Fn(&Preemption) + Send + Sync>;
+
+/// Agent preempted to admit higher-priority work
+#[derive(Debug, Clone)]
+pub struct Preemption {
+    /// Preempted agent
+    pub agent: AgentId,
+    /// Its role
+    pub role: String,
+    /// Priority of the work it was running
+    pub priority: TaskPriority,
+    /// Priority of the work admitted in its place
+    pub preempted_by: TaskPriority,
+    /// Task in flight, to requeue
+    pub checkpoint: Option<Task>,
+    /// Symbol claims the agent held in the pool's [`ClaimService`], now released
+    pub released_claims: usize,
+}
+
+/// Per-run state shared by clones of a handle
+#[derive(Default)]
+struct RunState {
+    current: Option<Task>,
+    hooks: Vec<PreemptHook>,
+    /// Position in the pool's acquisition order
+    acquired: u64,
+}
+
+impl fmt::Debug for RunState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RunState")
+            .field("current", &self.current.as_ref().map(|task| task.id))
+            .field("hooks", &self.hooks.len())
+            .finish()
+    }
+}
+
 /// Agent handle for communication
 #[derive(Debug, Clone)]
 pub struct AgentHandle {
@@ -19,17 +74,90 @@ pub struct AgentHandle {
     pub spec: AgentSpec,
     /// Communication channel
     sender: mpsc::Sender<AgentMessage>,
+    /// Fault injector of the owning pool (chaos mode)
+    chaos: Option<Arc<Chaos>>,
+    /// Task in flight and preemption hooks
+    run: Arc<std::sync::Mutex<RunState>>,
 }
 
 impl AgentHandle {
+    fn new(id: AgentId, spec: AgentSpec, sender: mpsc::Sender<AgentMessage>, chaos: Option<Arc<Chaos>>) -> Self {
+        Self {
+            id,
+            spec,
+            sender,
+            chaos,
+            run: Arc::default(),
+        }
+    }
+
     /// Send message to agent
+    ///
+    /// In chaos mode the message may be delayed, delivered twice, or lost
+    /// together with the agent.
     pub async fn send(&self, message: AgentMessage) -> Result<(), PoolError> {
+        if let Some(chaos) = &self.chaos {
+            if chaos.is_killed(self.id) {
+                return Err(self.killed());
+            }
+            match chaos.roll(self.id) {
+                Some(ChaosFault::Kill) => {
+                    let _ = self.sender.send(AgentMessage::Shutdown).await;
+                    return Err(self.killed());
+                }
+                Some(ChaosFault::Duplicate) => self.deliver(message.clone()).await?,
+                Some(ChaosFault::Delay(delay)) => tokio::time::sleep(delay).await,
+                None => {}
+            }
+        }
+        self.deliver(message).await
+    }
+
+    async fn deliver(&self, message: AgentMessage) -> Result<(), PoolError> {
+        if let AgentMessage::Execute(task) = &message {
+            self.run_state().current = Some(task.clone());
+        }
         self.sender
             .send(message)
             .await
             .map_err(|_| PoolError::CommunicationFailed("channel closed".to_string()))
     }
 
+    fn killed(&self) -> PoolError {
+        PoolError::CommunicationFailed(format!("agent {} killed by chaos", self.id.0))
+    }
+
+    fn run_state(&self) -> std::sync::MutexGuard<'_, RunState> {
+        self.run.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Run `hook` if the pool preempts this agent
+    ///
+    /// Hooks run before the agent is stopped, with the task it was running
+    /// as checkpoint. They are dropped when the agent is released.
+    pub fn on_preempt(&self, hook: impl Fn(&Preemption) + Send + Sync + 'static) {
+        self.run_state().hooks.push(Arc::new(hook));
+    }
+
+    /// Task most recently sent to the agent in this run
+    #[must_use]
+    pub fn current_task(&self) -> Option<Task> {
+        self.run_state().current.clone()
+    }
+
+    /// Priority of the work the agent runs
+    #[inline]
+    #[must_use]
+    pub fn priority(&self) -> TaskPriority {
+        self.spec.priority
+    }
+
+    /// Check if the agent can still receive messages
+    #[must_use]
+    pub fn is_alive(&self) -> bool {
+        !self.sender.is_closed() && !self.chaos.as_ref().is_some_and(|c| c.is_killed(self.id))
+    }
+
     /// Get agent ID
     #[inline]
     #[must_use]
@@ -72,7 +200,7 @@ pub enum AgentResponse {
 }
 
 /// Task execution result
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskResult {
     /// Output delta reference (placeholder)
     pub delta_ref: Option<String>,
@@ -81,7 +209,7 @@ pub struct TaskResult {
 }
 
 /// Execution metrics
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ExecutionMetrics {
     /// Execution time in milliseconds
     pub execution_time_ms: u64,
@@ -104,6 +232,14 @@ pub struct PoolStats {
     pub total_tasks_executed: usize,
     /// Cache hit rate (reused agents)
     pub reuse_rate: f64,
+    /// Agents replaced by supervised sends
+    pub restarts: usize,
+    /// Agents preempted for higher-priority work
+    pub preemptions: usize,
+    /// Preemptions suffered per role; victims are picked to even these out
+    pub preempted_by_role: HashMap<String, usize>,
+    /// Agents handed out per priority
+    pub acquired_by_priority: BTreeMap<TaskPriority, usize>,
 }
 
 /// Agent pool for lifecycle management
@@ -117,6 +253,18 @@ pub struct AgentPool {
     active: DashMap<AgentId, AgentHandle>,
     /// Statistics
     stats: Mutex<PoolStats>,
+    /// Restarts allowed per supervised send
+    max_restarts: usize,
+    /// Fault injector (chaos mode)
+    chaos: Option<Arc<Chaos>>,
+    /// Resource limits triggering preemption (unbounded if `None`)
+    limits: Option<SystemLimits>,
+    /// Preemptions not yet collected by the scheduler
+    preempted: Mutex<Vec<Preemption>>,
+    /// Acquisitions so far, ordering active agents
+    acquisitions: AtomicU64,
+    /// Subtree claims held by agents, keyed by agent id
+    claims: ClaimService,
 }
 
 impl AgentPool {
@@ -129,9 +277,68 @@ impl AgentPool {
             available: Mutex::new(Vec::new()),
             active: DashMap::new(),
             stats: Mutex::new(PoolStats::default()),
+            max_restarts: DEFAULT_MAX_RESTARTS,
+            chaos: None,
+            limits: None,
+            preempted: Mutex::new(Vec::new()),
+            acquisitions: AtomicU64::new(0),
+            claims: ClaimService::new(),
         }
     }
 
+    /// Share a claim service with the agents' writers
+    ///
+    /// Agents claim subtrees under their id (`AgentId`'s `Display`); a
+    /// preempted agent's claims are released so requeued work can take them.
+    #[inline]
+    #[must_use]
+    pub fn with_claims(mut self, claims: ClaimService) -> Self {
+        self.claims = claims;
+        self
+    }
+
+    /// Claim service agents of this pool claim subtrees through
+    #[inline]
+    #[must_use]
+    pub fn claims(&self) -> &ClaimService {
+        &self.claims
+    }
+
+    /// Bound the resources claimed by active agents
+    ///
+    /// Once admitting an agent would take memory, CPU or agent count past
+    /// `limits.preemption_threshold` of its limit, lower-priority agents are
+    /// preempted to make room.
+    #[inline]
+    #[must_use]
+    pub fn with_limits(mut self, limits: SystemLimits) -> Self {
+        self.limits = Some(limits);
+        self
+    }
+
+    /// Set restarts allowed per [`send_supervised`](Self::send_supervised)
+    #[inline]
+    #[must_use]
+    pub fn with_max_restarts(mut self, max_restarts: usize) -> Self {
+        self.max_restarts = max_restarts;
+        self
+    }
+
+    /// Inject seeded faults into messages sent to this pool's agents
+    ///
+    /// Applies to agents created or registered from now on.
+    #[must_use]
+    pub fn with_chaos(mut self, config: ChaosConfig) -> Self {
+        self.chaos = Some(Arc::new(Chaos::new(config)));
+        self
+    }
+
+    /// Faults injected so far (empty outside chaos mode)
+    #[must_use]
+    pub fn chaos_events(&self) -> Vec<ChaosEvent> {
+        self.chaos.as_ref().map(|c| c.events()).unwrap_or_default()
+    }
+
     /// Acquire an agent (reuse or create)
     ///
     /// # Arguments
@@ -140,20 +347,30 @@ impl AgentPool {
     /// # Returns
     /// Agent handle for communication
     ///
+    /// Lower-priority agents are preempted (see [`preempt`](Self::preempt))
+    /// when the pool is full or resources are under pressure.
+    ///
     /// # Errors
-    /// - `PoolError::PoolExhausted` if max agents active
+    /// - `PoolError::PoolExhausted` if max agents active and none can be
+    ///   preempted
+    /// - `PoolError::ResourcesExhausted` if admitting the agent would exceed
+    ///   a system limit and none can be preempted
     pub async fn acquire(&self, spec: AgentSpec) -> Result<AgentHandle, PoolError> {
+        self.relieve_pressure(&spec).await?;
+
         // Try to find matching available agent
         let mut available = self.available.lock().await;
+        available.retain(AgentHandle::is_alive);
 
         if let Some(idx) = available.iter().position(|a| a.spec.role == spec.role) {
             // Reuse agent
             let agent = available.remove(idx);
-            self.active.insert(agent.id, agent.clone());
+            self.activate(&agent);
 
             let mut stats = self.stats.lock().await;
             stats.available_count = available.len();
             stats.active_count = self.active.len();
+            *stats.acquired_by_priority.entry(spec.priority).or_default() += 1;
 
             return Ok(agent);
         }
@@ -161,30 +378,215 @@ impl AgentPool {
         drop(available);
 
         // Check capacity
-        if self.active.len() >= self.max_size {
+        if self.active.len() >= self.max_size && self.preempt_lowest(spec.priority).await.is_none() {
             return Err(PoolError::PoolExhausted(self.max_size));
         }
 
         // Create new agent
+        let priority = spec.priority;
         let agent = self.create_agent(spec).await?;
-        self.active.insert(agent.id, agent.clone());
+        self.activate(&agent);
 
         let mut stats = self.stats.lock().await;
         stats.total_created += 1;
         stats.active_count = self.active.len();
+        *stats.acquired_by_priority.entry(priority).or_default() += 1;
 
         Ok(agent)
     }
 
+    /// Register an idle agent advertising `spec.capabilities`
+    ///
+    /// The agent is available to [`assign`](Self::assign) and
+    /// [`acquire`](Self::acquire) like a released one.
+    pub async fn register(&self, spec: AgentSpec) -> Result<AgentId, PoolError> {
+        let agent = self.create_agent(spec).await?;
+        let id = agent.id;
+
+        let mut available = self.available.lock().await;
+        available.push(agent);
+
+        let mut stats = self.stats.lock().await;
+        stats.total_created += 1;
+        stats.available_count = available.len();
+        Ok(id)
+    }
+
+    /// Assign `task` to an idle agent capable of running it
+    ///
+    /// Only [registered](Self::register) agents of the task's role whose
+    /// capabilities cover [`Task::requirements`] are considered; the agent
+    /// runs at the task's priority. A task without requirements is
+    /// [acquired](Self::acquire) for as usual.
+    ///
+    /// # Errors
+    /// - `PoolError::NoCapableAgent` if no idle agent is capable, listing
+    ///   what each idle agent of the role lacks and how many capable agents
+    ///   are busy
+    /// - `PoolError::ResourcesExhausted` as for [`acquire`](Self::acquire)
+    pub async fn assign(&self, task: &Task) -> Result<AgentHandle, PoolError> {
+        if task.requirements.is_empty() {
+            return self.acquire(AgentSpec::from_task(task)).await;
+        }
+        self.relieve_pressure(&AgentSpec::from_task(task)).await?;
+
+        let mut available = self.available.lock().await;
+        available.retain(AgentHandle::is_alive);
+
+        let capable = |spec: &AgentSpec| {
+            spec.role == task.role && spec.capabilities.satisfies(&task.requirements)
+        };
+        let Some(idx) = available.iter().position(|a| capable(&a.spec)) else {
+            let rejected = available
+                .iter()
+                .filter(|a| a.spec.role == task.role)
+                .map(|a| RejectedAgent {
+                    agent: a.id,
+                    gaps: a.spec.capabilities.gaps(&task.requirements),
+                })
+                .collect();
+            let busy = self.active.iter().filter(|a| capable(&a.spec)).count();
+            return Err(PoolError::NoCapableAgent(CapabilityMismatch {
+                task: task.id,
+                role: task.role.clone(),
+                rejected,
+                busy,
+            }));
+        };
+
+        let mut agent = available.remove(idx);
+        agent.spec.priority = task.priority;
+        self.activate(&agent);
+
+        let mut stats = self.stats.lock().await;
+        stats.available_count = available.len();
+        stats.active_count = self.active.len();
+        *stats.acquired_by_priority.entry(task.priority).or_default() += 1;
+
+        Ok(agent)
+    }
+
+    /// Mark `agent` active, after every agent acquired before it
+    fn activate(&self, agent: &AgentHandle) {
+        agent.run_state().acquired = self.acquisitions.fetch_add(1, Ordering::Relaxed);
+        self.active.insert(agent.id, agent.clone());
+    }
+
+    /// Preempt lower-priority agents until `spec` fits under the limits
+    async fn relieve_pressure(&self, spec: &AgentSpec) -> Result<(), PoolError> {
+        let Some(limits) = self.limits else {
+            return Ok(());
+        };
+        while self.over_limit(&limits, &spec.resources, limits.preemption_threshold).is_some() {
+            if self.preempt_lowest(spec.priority).await.is_none() {
+                break;
+            }
+        }
+        match self.over_limit(&limits, &spec.resources, 1.0) {
+            Some(resource) => Err(PoolError::ResourcesExhausted(resource.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    /// First resource that admitting `extra` would take past `fraction` of
+    /// its limit
+    #[allow(clippy::cast_precision_loss)]
+    fn over_limit(&self, limits: &SystemLimits, extra: &ResourceCaps, fraction: f64) -> Option<&'static str> {
+        let (mut memory_mb, mut millicores) = (extra.memory_mb, extra.cpu_millicores);
+        for agent in self.active.iter() {
+            memory_mb += agent.spec.resources.memory_mb;
+            millicores += agent.spec.resources.cpu_millicores;
+        }
+        let over = |used: usize, limit: usize| used as f64 > limit as f64 * fraction;
+        if over(memory_mb, limits.max_memory_gb * 1024) {
+            Some("memory")
+        } else if over(millicores, limits.max_cpu_cores * 1000) {
+            Some("cpu")
+        } else if over(self.active.len() + 1, limits.max_agents) {
+            Some("agents")
+        } else {
+            None
+        }
+    }
+
+    /// Preempt the lowest-priority active agent below `priority`
+    ///
+    /// Among equals, the role preempted least so far goes first, then the
+    /// agent acquired last.
+    async fn preempt_lowest(&self, priority: TaskPriority) -> Option<Preemption> {
+        let victim = {
+            let stats = self.stats.lock().await;
+            self.active
+                .iter()
+                .filter(|agent| agent.spec.priority < priority)
+                .min_by_key(|agent| {
+                    let suffered = stats.preempted_by_role.get(&agent.spec.role).copied().unwrap_or(0);
+                    // Among equals, the latest acquired has the least work to lose
+                    (agent.spec.priority, suffered, Reverse(agent.run_state().acquired))
+                })
+                .map(|agent| agent.id)
+        }?;
+        self.preempt(victim, priority).await
+    }
+
+    /// Preempt an active agent in favour of work at priority `by`
+    ///
+    /// Stops the agent, releasing its resources and its
+    /// [claims](Self::claims), then runs its
+    /// [preemption hooks](AgentHandle::on_preempt) with its in-flight task as
+    /// checkpoint. The preemption is also kept for
+    /// [`take_preempted`](Self::take_preempted). Returns `None` if the agent
+    /// is not active.
+    pub async fn preempt(&self, agent_id: AgentId, by: TaskPriority) -> Option<Preemption> {
+        let (_, agent) = self.active.remove(&agent_id)?;
+        let (checkpoint, hooks) = {
+            let mut run = agent.run_state();
+            (run.current.take(), std::mem::take(&mut run.hooks))
+        };
+        let _ = agent.deliver(AgentMessage::Shutdown).await;
+        let preemption = Preemption {
+            agent: agent.id,
+            role: agent.spec.role.clone(),
+            priority: agent.spec.priority,
+            preempted_by: by,
+            checkpoint,
+            released_claims: self.claims.release_all(&agent.id.to_string()),
+        };
+        for hook in &hooks {
+            hook(&preemption);
+        }
+        tracing::info!(
+            "Preempted agent '{}' ({:?}) for {:?} work",
+            preemption.role,
+            preemption.priority,
+            by
+        );
+
+        let mut stats = self.stats.lock().await;
+        stats.preemptions += 1;
+        *stats.preempted_by_role.entry(preemption.role.clone()).or_default() += 1;
+        stats.active_count = self.active.len();
+        drop(stats);
+
+        self.preempted.lock().await.push(preemption.clone());
+        Some(preemption)
+    }
+
+    /// Preemptions since the last call, so their checkpoints can be requeued
+    pub async fn take_preempted(&self) -> Vec<Preemption> {
+        std::mem::take(&mut *self.preempted.lock().await)
+    }
+
     /// Release agent back to pool
     ///
     /// # Arguments
     /// * `agent` - Agent to release
     pub async fn release(&self, agent: AgentHandle) {
         self.active.remove(&agent.id);
+        *agent.run_state() = RunState::default();
 
         let mut available = self.available.lock().await;
-        if available.len() < self.max_size {
+        if available.len() < self.max_size && agent.is_alive() {
             available.push(agent);
         }
         // Else: drop agent
@@ -194,6 +596,53 @@ impl AgentPool {
         stats.active_count = self.active.len();
     }
 
+    /// Send a message, replacing the agent if it cannot receive it
+    ///
+    /// A failed send retires the agent and retries on a fresh agent with the
+    /// same spec, up to the pool's restart limit. Returns the handle that
+    /// accepted the message, which the caller should use (and release) from
+    /// then on.
+    ///
+    /// # Errors
+    /// - `PoolError::RestartLimitExceeded` once every restart has failed;
+    ///   the caller should escalate rather than retry
+    pub async fn send_supervised(
+        &self,
+        mut agent: AgentHandle,
+        message: AgentMessage,
+    ) -> Result<AgentHandle, PoolError> {
+        let mut restarts = 0;
+        loop {
+            let error = match agent.send(message.clone()).await {
+                Ok(()) => return Ok(agent),
+                Err(error) => error,
+            };
+            self.active.remove(&agent.id);
+
+            if restarts >= self.max_restarts {
+                tracing::warn!("Agent '{}' failed after {} restarts: {}", agent.spec.role, restarts, error);
+                self.stats.lock().await.active_count = self.active.len();
+                return Err(PoolError::RestartLimitExceeded {
+                    role: agent.spec.role.clone(),
+                    restarts,
+                    last_error: error.to_string(),
+                });
+            }
+
+            tracing::debug!("Restarting agent '{}': {}", agent.spec.role, error);
+            agent = self.create_agent(agent.spec.clone()).await?;
+            self.activate(&agent);
+            restarts += 1;
+            #[cfg(feature = "metrics")]
+            coa_constitutional::metrics::global().agent_restarts.inc();
+
+            let mut stats = self.stats.lock().await;
+            stats.total_created += 1;
+            stats.restarts += 1;
+            stats.active_count = self.active.len();
+        }
+    }
+
     /// Shutdown specific agent
     pub async fn shutdown_agent(&self, agent_id: AgentId) -> Result<(), PoolError> {
         if let Some((_, agent)) = self.active.remove(&agent_id) {
@@ -246,6 +695,70 @@ impl AgentPool {
         self.active.len()
     }
 
+    /// Maximum number of active agents
+    #[inline]
+    #[must_use]
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    /// Fraction of the pool's capacity held by active agents
+    #[allow(clippy::cast_precision_loss)]
+    #[must_use]
+    pub fn load(&self) -> f64 {
+        if self.max_size == 0 {
+            return 1.0;
+        }
+        self.active.len() as f64 / self.max_size as f64
+    }
+
+    /// Fraction of the memory limit claimed by active agents
+    ///
+    /// `None` without [limits](Self::with_limits).
+    #[allow(clippy::cast_precision_loss)]
+    #[must_use]
+    pub fn memory_pressure(&self) -> Option<f64> {
+        let limit_mb = self.limits?.max_memory_gb * 1024;
+        let claimed_mb: usize = self.active.iter().map(|agent| agent.spec.resources.memory_mb).sum();
+        Some(if limit_mb == 0 { 1.0 } else { claimed_mb as f64 / limit_mb as f64 })
+    }
+
+    /// Register a connected remote worker
+    ///
+    /// Adds one available agent per role the worker advertised, so
+    /// [`acquire`](Self::acquire) hands out remote agents like local ones.
+    /// Each advertises the worker's capability tags (see
+    /// [`AgentCapabilities::from_tags`]) to [`assign`](Self::assign).
+    /// `Execute`/`Pause`/`Resume` are forwarded to the worker; `Shutdown`
+    /// retires only the pool handle, not the worker session. Results arrive
+    /// on the worker's [`WorkerEvent`](crate::remote::WorkerEvent) stream.
+    ///
+    /// Returns the IDs of the registered agents.
+    pub async fn register_remote(&self, worker: &RemoteWorker) -> Vec<AgentId> {
+        let mut ids = Vec::with_capacity(worker.hello().roles.len());
+        let capabilities = AgentCapabilities::from_tags(&worker.hello().capabilities);
+        let mut available = self.available.lock().await;
+
+        for role in &worker.hello().roles {
+            let id = AgentId::new();
+            let (tx, rx) = mpsc::channel(100);
+            tokio::spawn(remote_agent_task(rx, worker.command_sender()));
+
+            available.push(AgentHandle::new(
+                id,
+                AgentSpec::new(role.clone()).with_capabilities(capabilities.clone()),
+                tx,
+                self.chaos.clone(),
+            ));
+            ids.push(id);
+        }
+
+        let mut stats = self.stats.lock().await;
+        stats.total_created += ids.len();
+        stats.available_count = available.len();
+        ids
+    }
+
     /// Create new agent
     async fn create_agent(&self, spec: AgentSpec) -> Result<AgentHandle, PoolError> {
         let id = AgentId::new();
@@ -254,11 +767,7 @@ impl AgentPool {
         // Spawn agent task
         tokio::spawn(agent_task(id, spec.clone(), rx));
 
-        Ok(AgentHandle {
-            id,
-            spec,
-            sender: tx,
-        })
+        Ok(AgentHandle::new(id, spec, tx, self.chaos.clone()))
     }
 }
 
@@ -296,9 +805,28 @@ async fn agent_task(
     }
 }
 
+/// Bridge from a pool handle to a remote worker session
+async fn remote_agent_task(
+    mut rx: mpsc::Receiver<AgentMessage>,
+    worker: mpsc::Sender<WorkerFrame>,
+) {
+    while let Some(msg) = rx.recv().await {
+        let frame = match msg {
+            AgentMessage::Execute(task) => WorkerFrame::Dispatch(Box::new(task)),
+            AgentMessage::Pause => WorkerFrame::Pause,
+            AgentMessage::Resume => WorkerFrame::Resume,
+            AgentMessage::Shutdown => break,
+        };
+        if worker.send(frame).await.is_err() {
+            break;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::capability::{CapabilityGap, CapabilityRequirements};
 
     #[tokio::test]
     async fn agent_pool_acquire_and_release() {
@@ -358,6 +886,195 @@ mod tests {
         assert_eq!(stats.available_count, 1);
     }
 
+    #[tokio::test]
+    async fn supervised_send_restarts_killed_agents() {
+        let pool = AgentPool::new(4).with_chaos(ChaosConfig::new(7).with_kills(0.5));
+        let spec = AgentSpec::new("tester");
+
+        let mut agent = pool.acquire(spec).await.unwrap();
+        let mut escalated = false;
+        for _ in 0..20 {
+            match pool.send_supervised(agent.clone(), AgentMessage::Resume).await {
+                Ok(next) => agent = next,
+                Err(PoolError::RestartLimitExceeded { .. }) => {
+                    escalated = true;
+                    break;
+                }
+                Err(e) => panic!("unexpected error: {e}"),
+            }
+            assert!(agent.is_alive());
+        }
+
+        // Every kill is followed by a restart, except one that escalates
+        let kills = pool.chaos_events().len();
+        assert!(kills > 0);
+        assert_eq!(pool.stats().await.restarts, kills - usize::from(escalated));
+    }
+
+    #[tokio::test]
+    async fn supervised_send_escalates_at_restart_limit() {
+        let pool = AgentPool::new(4)
+            .with_max_restarts(2)
+            .with_chaos(ChaosConfig::new(1).with_kills(1.0));
+        let agent = pool.acquire(AgentSpec::new("tester")).await.unwrap();
+
+        let result = pool.send_supervised(agent.clone(), AgentMessage::Resume).await;
+        assert!(matches!(
+            result,
+            Err(PoolError::RestartLimitExceeded { restarts: 2, .. })
+        ));
+        assert_eq!(pool.chaos_events().len(), 3);
+        assert_eq!(pool.active_count(), 0);
+
+        // Killed agents are not handed out again
+        pool.release(agent).await;
+        assert_eq!(pool.stats().await.available_count, 0);
+    }
+
+    #[tokio::test]
+    async fn full_pool_preempts_lower_priority_agents() {
+        let pool = AgentPool::new(2);
+        let low = pool
+            .acquire(AgentSpec::new("docs").with_priority(TaskPriority::Low))
+            .await
+            .unwrap();
+        let task = Task::new("docs", "Write the guide", "docs.guide".parse().unwrap());
+        let low = pool.send_supervised(low, AgentMessage::Execute(task.clone())).await.unwrap();
+        let checkpoints = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = Arc::clone(&checkpoints);
+        low.on_preempt(move |p| seen.lock().unwrap().push(p.checkpoint.clone()));
+        let _normal = pool.acquire(AgentSpec::new("coder")).await.unwrap();
+
+        // Equal priority never preempts
+        assert!(matches!(
+            pool.acquire(AgentSpec::new("docs").with_priority(TaskPriority::Low)).await,
+            Err(PoolError::PoolExhausted(2))
+        ));
+
+        let _high = pool
+            .acquire(AgentSpec::new("fixer").with_priority(TaskPriority::High))
+            .await
+            .unwrap();
+        assert_eq!(pool.active_count(), 2);
+        let checkpoint = checkpoints.lock().unwrap().pop().unwrap().unwrap();
+        assert_eq!(checkpoint.id, task.id);
+
+        let preempted = pool.take_preempted().await;
+        assert_eq!(preempted.len(), 1);
+        assert_eq!(preempted[0].agent, low.id);
+        assert_eq!(preempted[0].preempted_by, TaskPriority::High);
+        assert!(pool.take_preempted().await.is_empty());
+
+        let stats = pool.stats().await;
+        assert_eq!(stats.preemptions, 1);
+        assert_eq!(stats.preempted_by_role["docs"], 1);
+        assert_eq!(stats.acquired_by_priority[&TaskPriority::Normal], 1);
+        assert_eq!(stats.acquired_by_priority[&TaskPriority::High], 1);
+    }
+
+    #[tokio::test]
+    async fn preemption_releases_the_agents_claims() {
+        let claims = ClaimService::new();
+        let pool = AgentPool::new(1).with_claims(claims.clone());
+        let low = pool
+            .acquire(AgentSpec::new("docs").with_priority(TaskPriority::Low))
+            .await
+            .unwrap();
+        let guide: coa_artifact::SymbolPath = "docs.guide".parse().unwrap();
+        let _held = claims.try_acquire(low.id.to_string(), guide.clone()).unwrap();
+        assert!(claims.try_acquire("other", guide.clone()).is_err());
+
+        let _high = pool
+            .acquire(AgentSpec::new("fixer").with_priority(TaskPriority::High))
+            .await
+            .unwrap();
+        let preempted = pool.take_preempted().await;
+        assert_eq!(preempted[0].released_claims, 1);
+        assert!(claims.claims().is_empty());
+        assert!(claims.try_acquire("other", guide).is_ok());
+    }
+
+    #[tokio::test]
+    async fn resource_pressure_preempts_fairly() {
+        let limits = SystemLimits {
+            max_memory_gb: 4,
+            ..SystemLimits::default()
+        };
+        let pool = AgentPool::new(10).with_limits(limits);
+        let gig = ResourceCaps {
+            memory_mb: 1024,
+            ..ResourceCaps::default()
+        };
+        let spec = |role: &str, priority| {
+            AgentSpec::new(role).with_priority(priority).with_resources(gig)
+        };
+        pool.acquire(spec("a", TaskPriority::Background)).await.unwrap();
+        pool.acquire(spec("b", TaskPriority::Background)).await.unwrap();
+        pool.acquire(spec("a", TaskPriority::Background)).await.unwrap();
+
+        // A fourth gigabyte passes the 90% threshold: one background agent
+        // gives way, then the other role is picked next
+        pool.acquire(spec("x", TaskPriority::High)).await.unwrap();
+        pool.acquire(spec("y", TaskPriority::High)).await.unwrap();
+        let roles: Vec<_> = pool.take_preempted().await.into_iter().map(|p| p.role).collect();
+        assert_eq!(roles, ["a", "b"]);
+        assert_eq!(pool.active_count(), 3);
+
+        // Nothing left to preempt below Background and the limit is reached
+        pool.acquire(spec("z", TaskPriority::Background)).await.unwrap();
+        assert!(matches!(
+            pool.acquire(spec("w", TaskPriority::Background)).await,
+            Err(PoolError::ResourcesExhausted(resource)) if resource == "memory"
+        ));
+    }
+
+    #[tokio::test]
+    async fn assign_matches_capabilities_with_diagnostics() {
+        let pool = AgentPool::new(4);
+        let rust = AgentCapabilities::new().with_language("rust").with_tool("cargo");
+        let python = AgentCapabilities::new().with_language("python").with_max_context(8000);
+        let rust_id = pool
+            .register(AgentSpec::new("coder").with_capabilities(rust))
+            .await
+            .unwrap();
+        let python_id = pool
+            .register(AgentSpec::new("coder").with_capabilities(python))
+            .await
+            .unwrap();
+
+        let task = |needs: CapabilityRequirements| {
+            Task::new("coder", "Implement", "src.lib".parse().unwrap())
+                .with_priority(TaskPriority::High)
+                .with_requirements(needs)
+        };
+        let rust_task = task(CapabilityRequirements::new().language("Rust"));
+        let agent = pool.assign(&rust_task).await.unwrap();
+        assert_eq!(agent.id, rust_id);
+        assert_eq!(agent.priority(), TaskPriority::High);
+
+        // The only Rust agent is busy; the idle one is reported with its gaps
+        let Err(PoolError::NoCapableAgent(mismatch)) = pool.assign(&rust_task).await else {
+            panic!("expected a capability mismatch");
+        };
+        assert_eq!(mismatch.busy, 1);
+        assert_eq!(mismatch.rejected.len(), 1);
+        assert_eq!(mismatch.rejected[0].agent, python_id);
+        assert_eq!(mismatch.missing(), [&CapabilityGap::Language("rust".to_string())]);
+
+        let big = task(CapabilityRequirements::new().language("python").context(32000));
+        let error = pool.assign(&big).await.unwrap_err();
+        assert!(error.to_string().contains("context of 32000 tokens (has 8000)"));
+
+        let reviewer = Task::new("reviewer", "Review", "src.lib".parse().unwrap())
+            .with_requirements(CapabilityRequirements::new().tool("clippy"));
+        let error = pool.assign(&reviewer).await.unwrap_err();
+        assert!(error.to_string().ends_with("none registered for the role"));
+
+        // Tasks without requirements fall back to plain acquisition
+        pool.release(agent).await;
+        assert!(pool.assign(&task(CapabilityRequirements::new())).await.is_ok());
+    }
+
     #[tokio::test]
     async fn agent_handle_send() {
         let pool = AgentPool::new(1);
@@ -0,0 +1,97 @@
+use clap::{Arg, ArgAction, Command};
+use coa_core::{ConfigLoader, ConfigProfile};
+use std::path::PathBuf;
+
+fn main() {
+    let cli = Command::new("coa")
+        .version(coa_core::VERSION)
+        .about("Creator Orchestrator Agent")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("config")
+                .about("Inspect layered configuration")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("show")
+                        .about("Show configuration and where each setting came from")
+                        .arg(
+                            Arg::new("resolved")
+                                .long("resolved")
+                                .action(ArgAction::SetTrue)
+                                .help("Show every setting, not only those overriding the profile"),
+                        )
+                        .arg(
+                            Arg::new("profile")
+                                .long("profile")
+                                .value_parser(["ci", "local", "prod"])
+                                .help("Profile to start from (default: $COA_PROFILE or local)"),
+                        )
+                        .arg(
+                            Arg::new("config")
+                                .long("config")
+                                .value_parser(clap::value_parser!(PathBuf))
+                                .help("TOML or JSON config file (default: $COA_CONFIG)"),
+                        )
+                        .arg(
+                            Arg::new("set")
+                                .long("set")
+                                .value_name("KEY=VALUE")
+                                .action(ArgAction::Append)
+                                .help("Override a setting, e.g. system_limits.max_agents=16"),
+                        )
+                        .arg(
+                            Arg::new("json")
+                                .long("json")
+                                .action(ArgAction::SetTrue)
+                                .help("Output as JSON"),
+                        ),
+                ),
+        );
+
+    let matches = cli.get_matches();
+
+    if let Some(("config", args)) = matches.subcommand() {
+        if let Some(("show", args)) = args.subcommand() {
+            let mut loader = ConfigLoader::from_env();
+            if let Some(profile) = args.get_one::<String>("profile") {
+                loader = loader.with_profile(profile.parse::<ConfigProfile>().unwrap());
+            }
+            if let Some(path) = args.get_one::<PathBuf>("config") {
+                loader = loader.with_file(path);
+            }
+            for assignment in args.get_many::<String>("set").into_iter().flatten() {
+                loader = loader.with_override(assignment);
+            }
+
+            let resolved = match loader.load() {
+                Ok(resolved) => resolved,
+                Err(e) => {
+                    eprintln!("Configuration error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            let resolved_only = args.get_flag("resolved");
+            if args.get_flag("json") {
+                let output = if resolved_only {
+                    serde_json::to_value(&resolved.config).unwrap()
+                } else {
+                    resolved
+                        .overrides()
+                        .map(|(key, source)| (key.to_string(), source.to_string().into()))
+                        .collect::<serde_json::Map<_, _>>()
+                        .into()
+                };
+                println!("{}", serde_json::to_string_pretty(&output).unwrap());
+            } else if resolved_only {
+                println!("# profile: {}", resolved.profile);
+                print!("{}", resolved);
+            } else {
+                println!("# profile: {}", resolved.profile);
+                for (key, source) in resolved.overrides() {
+                    println!("{:<40} # {}", key, source);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,333 @@
+//! Agent capability negotiation
+//!
+//! Agents advertise [`AgentCapabilities`] (languages, artifact types,
+//! maximum context and tools) when registered with an
+//! [`AgentPool`](crate::AgentPool), and tasks declare the
+//! [`CapabilityRequirements`] they need. [`AgentPool::assign`] hands a task
+//! only to an agent covering every requirement; when none does, the
+//! resulting [`CapabilityMismatch`] lists what each candidate lacked.
+//!
+//! Remote workers advertise capabilities as tags (see
+//! [`AgentCapabilities::from_tags`]).
+//!
+//! [`AgentPool::assign`]: crate::AgentPool::assign
+
+use crate::types::{AgentId, TaskId};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// What an agent can work with
+///
+/// Names are matched case-insensitively. Anything not advertised is
+/// assumed unsupported, including context size.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentCapabilities {
+    /// Programming languages
+    #[serde(default)]
+    pub languages: BTreeSet<String>,
+    /// Artifact types produced (e.g. `code`, `config`, `docs`)
+    #[serde(default)]
+    pub artifact_types: BTreeSet<String>,
+    /// Largest context the agent handles, in tokens
+    #[serde(default)]
+    pub max_context: Option<usize>,
+    /// Tools available to the agent
+    #[serde(default)]
+    pub tools: BTreeSet<String>,
+}
+
+impl AgentCapabilities {
+    /// No capabilities
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse capability tags advertised by a remote worker
+    ///
+    /// Tags are `lang:<name>`, `artifact:<type>`, `context:<tokens>` or
+    /// `tool:<name>`; an untagged name is a tool. Context tags that are not
+    /// a number are ignored, and the largest context wins.
+    #[must_use]
+    pub fn from_tags<I, S>(tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        tags.into_iter().fold(Self::new(), |caps, tag| {
+            match tag.as_ref().split_once(':') {
+                Some(("lang" | "language", name)) => caps.with_language(name),
+                Some(("artifact", kind)) => caps.with_artifact_type(kind),
+                Some(("context", tokens)) => match tokens.trim().parse() {
+                    Ok(tokens) if caps.max_context < Some(tokens) => caps.with_max_context(tokens),
+                    _ => caps,
+                },
+                Some(("tool", name)) => caps.with_tool(name),
+                _ => caps.with_tool(tag.as_ref()),
+            }
+        })
+    }
+
+    /// With language
+    #[must_use]
+    pub fn with_language(mut self, language: impl AsRef<str>) -> Self {
+        self.languages.insert(normalize(language.as_ref()));
+        self
+    }
+
+    /// With artifact type
+    #[must_use]
+    pub fn with_artifact_type(mut self, kind: impl AsRef<str>) -> Self {
+        self.artifact_types.insert(normalize(kind.as_ref()));
+        self
+    }
+
+    /// With maximum context, in tokens
+    #[inline]
+    #[must_use]
+    pub fn with_max_context(mut self, tokens: usize) -> Self {
+        self.max_context = Some(tokens);
+        self
+    }
+
+    /// With tool
+    #[must_use]
+    pub fn with_tool(mut self, tool: impl AsRef<str>) -> Self {
+        self.tools.insert(normalize(tool.as_ref()));
+        self
+    }
+
+    /// Check if every requirement is covered
+    #[must_use]
+    pub fn satisfies(&self, requirements: &CapabilityRequirements) -> bool {
+        self.gaps(requirements).is_empty()
+    }
+
+    /// Requirements not covered, grouped by kind
+    #[must_use]
+    pub fn gaps(&self, requirements: &CapabilityRequirements) -> Vec<CapabilityGap> {
+        let missing = |have: &BTreeSet<String>, want: &BTreeSet<String>| {
+            want.difference(have).cloned().collect::<Vec<_>>()
+        };
+        let mut gaps: Vec<_> = missing(&self.languages, &requirements.languages)
+            .into_iter()
+            .map(CapabilityGap::Language)
+            .collect();
+        gaps.extend(
+            missing(&self.artifact_types, &requirements.artifact_types)
+                .into_iter()
+                .map(CapabilityGap::ArtifactType),
+        );
+        if let Some(required) = requirements.min_context {
+            if self.max_context < Some(required) {
+                gaps.push(CapabilityGap::Context {
+                    required,
+                    available: self.max_context,
+                });
+            }
+        }
+        gaps.extend(
+            missing(&self.tools, &requirements.tools)
+                .into_iter()
+                .map(CapabilityGap::Tool),
+        );
+        gaps
+    }
+}
+
+/// What a task needs from the agent running it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapabilityRequirements {
+    /// Languages the agent must handle
+    #[serde(default)]
+    pub languages: BTreeSet<String>,
+    /// Artifact types the agent must produce
+    #[serde(default)]
+    pub artifact_types: BTreeSet<String>,
+    /// Context the agent must fit, in tokens
+    #[serde(default)]
+    pub min_context: Option<usize>,
+    /// Tools the agent must have
+    #[serde(default)]
+    pub tools: BTreeSet<String>,
+}
+
+impl CapabilityRequirements {
+    /// No requirements
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requiring language
+    #[must_use]
+    pub fn language(mut self, language: impl AsRef<str>) -> Self {
+        self.languages.insert(normalize(language.as_ref()));
+        self
+    }
+
+    /// Requiring artifact type
+    #[must_use]
+    pub fn artifact_type(mut self, kind: impl AsRef<str>) -> Self {
+        self.artifact_types.insert(normalize(kind.as_ref()));
+        self
+    }
+
+    /// Requiring a context of at least `tokens`
+    #[inline]
+    #[must_use]
+    pub fn context(mut self, tokens: usize) -> Self {
+        self.min_context = Some(tokens);
+        self
+    }
+
+    /// Requiring tool
+    #[must_use]
+    pub fn tool(mut self, tool: impl AsRef<str>) -> Self {
+        self.tools.insert(normalize(tool.as_ref()));
+        self
+    }
+
+    /// Check if nothing is required
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.languages.is_empty()
+            && self.artifact_types.is_empty()
+            && self.min_context.is_none()
+            && self.tools.is_empty()
+    }
+}
+
+fn normalize(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// Requirement an agent does not cover
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum CapabilityGap {
+    /// Language not supported
+    Language(String),
+    /// Artifact type not produced
+    ArtifactType(String),
+    /// Context too small (or not advertised)
+    Context {
+        required: usize,
+        available: Option<usize>,
+    },
+    /// Tool not available
+    Tool(String),
+}
+
+impl fmt::Display for CapabilityGap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Language(name) => write!(f, "language '{name}'"),
+            Self::ArtifactType(kind) => write!(f, "artifact type '{kind}'"),
+            Self::Context {
+                required,
+                available: Some(available),
+            } => write!(f, "context of {required} tokens (has {available})"),
+            Self::Context {
+                required,
+                available: None,
+            } => write!(f, "context of {required} tokens (none advertised)"),
+            Self::Tool(name) => write!(f, "tool '{name}'"),
+        }
+    }
+}
+
+/// Agent considered for a task and the requirements it missed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RejectedAgent {
+    /// Agent
+    pub agent: AgentId,
+    /// Requirements it does not cover
+    pub gaps: Vec<CapabilityGap>,
+}
+
+/// Why no agent could be assigned a task
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapabilityMismatch {
+    /// Task that could not be assigned
+    pub task: TaskId,
+    /// Role the task asked for
+    pub role: String,
+    /// Idle agents of that role, none capable
+    pub rejected: Vec<RejectedAgent>,
+    /// Capable agents of that role currently busy
+    pub busy: usize,
+}
+
+impl CapabilityMismatch {
+    /// Requirements no idle agent covers, each listed once
+    #[must_use]
+    pub fn missing(&self) -> Vec<&CapabilityGap> {
+        let mut missing: Vec<&CapabilityGap> = Vec::new();
+        for gap in self.rejected.iter().flat_map(|agent| &agent.gaps) {
+            if !missing.contains(&gap) {
+                missing.push(gap);
+            }
+        }
+        missing
+    }
+}
+
+impl fmt::Display for CapabilityMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no capable '{}' agent for task {}", self.role, self.task)?;
+        if self.rejected.is_empty() && self.busy == 0 {
+            return f.write_str(": none registered for the role");
+        }
+        for agent in &self.rejected {
+            let gaps: Vec<_> = agent.gaps.iter().map(ToString::to_string).collect();
+            write!(f, "; agent {} lacks {}", agent.agent, gaps.join(", "))?;
+        }
+        if self.busy > 0 {
+            write!(f, "; {} capable agent(s) busy", self.busy)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gaps_cover_every_requirement_kind() {
+        let caps = AgentCapabilities::from_tags([
+            "lang:Rust",
+            "artifact:code",
+            "context:8000",
+            "context:32000",
+            "cargo",
+        ]);
+        assert_eq!(caps.max_context, Some(32000));
+        assert!(caps.tools.contains("cargo"));
+
+        let needs = CapabilityRequirements::new()
+            .language("rust")
+            .artifact_type("code")
+            .context(16000)
+            .tool("cargo");
+        assert!(caps.satisfies(&needs));
+        assert!(AgentCapabilities::new().satisfies(&CapabilityRequirements::new()));
+
+        let needs = needs.language("python").context(64000).tool("docker");
+        assert_eq!(
+            caps.gaps(&needs),
+            [
+                CapabilityGap::Language("python".to_string()),
+                CapabilityGap::Context {
+                    required: 64000,
+                    available: Some(32000),
+                },
+                CapabilityGap::Tool("docker".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,188 @@
+//! Chaos testing for agent supervision
+//!
+//! A [`ChaosConfig`] attached to an [`AgentPool`](crate::AgentPool) injects
+//! faults into agent messaging, driven by a seeded RNG so a failing run can
+//! be reproduced from its seed:
+//! - **Delay**: a message is held back for a random duration
+//! - **Kill**: the receiving agent is shut down and the message is lost
+//! - **Duplicate**: a message is delivered twice
+//!
+//! Every injected fault is recorded as a [`ChaosEvent`].
+
+use crate::types::AgentId;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+/// Fault rates and seed for chaos mode
+///
+/// Rates are per-message probabilities; at most one fault is injected per
+/// message.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChaosConfig {
+    /// RNG seed
+    pub seed: u64,
+    /// Probability a message is delayed
+    pub delay_rate: f64,
+    /// Upper bound for injected delays
+    pub max_delay: Duration,
+    /// Probability the receiving agent is killed
+    pub kill_rate: f64,
+    /// Probability a message is delivered twice
+    pub duplicate_rate: f64,
+}
+
+impl ChaosConfig {
+    /// No faults, seeded with `seed`
+    #[inline]
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            delay_rate: 0.0,
+            max_delay: Duration::ZERO,
+            kill_rate: 0.0,
+            duplicate_rate: 0.0,
+        }
+    }
+
+    /// Delay messages with probability `rate`, by up to `max`
+    #[inline]
+    #[must_use]
+    pub fn with_delays(mut self, rate: f64, max: Duration) -> Self {
+        self.delay_rate = rate;
+        self.max_delay = max;
+        self
+    }
+
+    /// Kill the receiving agent with probability `rate`
+    #[inline]
+    #[must_use]
+    pub fn with_kills(mut self, rate: f64) -> Self {
+        self.kill_rate = rate;
+        self
+    }
+
+    /// Duplicate messages with probability `rate`
+    #[inline]
+    #[must_use]
+    pub fn with_duplicates(mut self, rate: f64) -> Self {
+        self.duplicate_rate = rate;
+        self
+    }
+}
+
+/// Injected fault
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChaosFault {
+    Delay(Duration),
+    Kill,
+    Duplicate,
+}
+
+/// Fault injected into a message to `agent_id`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChaosEvent {
+    pub agent_id: AgentId,
+    pub fault: ChaosFault,
+}
+
+/// Shared fault injector for a pool's agent handles
+#[derive(Debug)]
+pub(crate) struct Chaos {
+    config: ChaosConfig,
+    state: Mutex<ChaosState>,
+}
+
+#[derive(Debug)]
+struct ChaosState {
+    rng: StdRng,
+    killed: HashSet<AgentId>,
+    events: Vec<ChaosEvent>,
+}
+
+impl Chaos {
+    pub(crate) fn new(config: ChaosConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(ChaosState {
+                rng: StdRng::seed_from_u64(config.seed),
+                killed: HashSet::new(),
+                events: Vec::new(),
+            }),
+        }
+    }
+
+    /// Decide the fault (if any) for one message to `agent_id`
+    pub(crate) fn roll(&self, agent_id: AgentId) -> Option<ChaosFault> {
+        let ChaosConfig {
+            kill_rate,
+            duplicate_rate,
+            delay_rate,
+            max_delay,
+            ..
+        } = self.config;
+        let mut state = self.lock();
+        let roll: f64 = state.rng.random();
+
+        let fault = if roll < kill_rate {
+            state.killed.insert(agent_id);
+            ChaosFault::Kill
+        } else if roll < kill_rate + duplicate_rate {
+            ChaosFault::Duplicate
+        } else if roll < kill_rate + duplicate_rate + delay_rate {
+            let max_ms = u64::try_from(max_delay.as_millis()).unwrap_or(u64::MAX);
+            ChaosFault::Delay(Duration::from_millis(state.rng.random_range(0..=max_ms)))
+        } else {
+            return None;
+        };
+        state.events.push(ChaosEvent { agent_id, fault });
+        Some(fault)
+    }
+
+    pub(crate) fn is_killed(&self, agent_id: AgentId) -> bool {
+        self.lock().killed.contains(&agent_id)
+    }
+
+    pub(crate) fn events(&self) -> Vec<ChaosEvent> {
+        self.lock().events.clone()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ChaosState> {
+        self.state.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_faults() {
+        let config = ChaosConfig::new(42)
+            .with_kills(0.2)
+            .with_duplicates(0.2)
+            .with_delays(0.2, Duration::from_millis(5));
+        let agent = AgentId::new();
+
+        let run = || {
+            let chaos = Chaos::new(config);
+            (0..50).map(|_| chaos.roll(agent)).collect::<Vec<_>>()
+        };
+        let faults = run();
+        assert_eq!(faults, run());
+        assert!(faults.iter().any(Option::is_some));
+        assert!(faults.iter().any(Option::is_none));
+    }
+
+    #[test]
+    fn no_rates_no_faults() {
+        let chaos = Chaos::new(ChaosConfig::new(1));
+        let agent = AgentId::new();
+        assert!((0..20).all(|_| chaos.roll(agent).is_none()));
+        assert!(chaos.events().is_empty());
+        assert!(!chaos.is_killed(agent));
+    }
+}
@@ -7,18 +7,32 @@
 //! - Handles construction failures with diagnostics
 //! - Coordinates multi-agent composition
 
-use crate::agent_pool::{AgentPool, AgentHandle};
+use crate::admission::AdmissionController;
+use crate::agent_pool::{AgentHandle, AgentMessage, AgentPool};
+use crate::chaos::ChaosConfig;
 use crate::decomposition::TaskDecomposer;
-use crate::error::{COAError, DecompositionError, Diagnostic, ErrorType, Goal, Location, SuggestedFix};
-use crate::types::{AgentSpec, ArtifactSummary, COAConfig, ExecutionResult, Specification, Task, UserIntent};
+use crate::error::{
+    COAError, DecompositionError, Diagnostic, ErrorType, Goal, Location, PoolError, SuggestedFix,
+    TraceError,
+};
+use crate::intent::{IntentClassifier, IntentRoute};
+use crate::trace::Trace;
+use crate::verification::{CriteriaExecutor, UnmetCriterion, VerificationGate};
+use crate::types::{
+    ArtifactSummary, COAConfig, CorrelationId, ExecutionResult, ExpansionType,
+    Specification, Task, TaskId, UserIntent,
+};
 use coa_artifact::{Artifact, ArtifactType, StructuralDelta};
 use coa_constitutional::parsers::CodeArtifact;
 use coa_composition::CompositionStrategy;
-// Constitutional layer will be integrated when ready
-// use coa_constitutional::ConstitutionalLayer;
+use coa_constitutional::{ApplyError, ScopedLayer};
+use coa_kernel::prelude::RunBudget;
 use coa_symbol::SymbolRefIndex;
+use std::collections::{HashMap, VecDeque};
 use std::str::FromStr;
 use std::sync::Arc;
+use tracing::Instrument;
+use uuid::Uuid;
 
 /// The central orchestrator
 ///
@@ -33,6 +47,14 @@ pub struct CreatorOrchestratorAgent {
     agent_pool: AgentPool,
     /// Task decomposer
     decomposer: TaskDecomposer,
+    /// Intent categorization and routing defaults
+    classifier: IntentClassifier,
+    /// Runs the checks behind acceptance criteria
+    criteria_executor: Option<Arc<dyn CriteriaExecutor>>,
+    /// Source of nondeterministic inputs (live, recording or replaying)
+    trace: Trace,
+    /// Refuses decomposed tasks while the system is overloaded
+    admission: AdmissionController,
 }
 
 impl CreatorOrchestratorAgent {
@@ -43,21 +65,80 @@ impl CreatorOrchestratorAgent {
         Self {
             config: config.clone(),
             symbol_index: Arc::new(SymbolRefIndex::new()),
-            agent_pool: AgentPool::new(config.max_concurrent_agents),
-            decomposer: TaskDecomposer::default(),
+            agent_pool: AgentPool::new(config.max_concurrent_agents)
+                .with_limits(config.system_limits),
+            decomposer: TaskDecomposer::default().with_max_depth(config.max_decomposition_depth),
+            classifier: IntentClassifier::new(),
+            criteria_executor: None,
+            trace: Trace::live(),
+            admission: AdmissionController::default(),
         }
     }
 
+    /// Record or replay nondeterministic inputs through `trace`
+    #[inline]
+    #[must_use]
+    pub fn with_trace(mut self, trace: Trace) -> Self {
+        self.trace = trace;
+        self
+    }
+
+    /// Route intents with `classifier` (e.g. one loaded from a role catalog)
+    #[inline]
+    #[must_use]
+    pub fn with_classifier(mut self, classifier: IntentClassifier) -> Self {
+        self.classifier = classifier;
+        self
+    }
+
+    /// Verify acceptance criteria with `executor`
+    ///
+    /// Without one, every criterion is reported unmet and no run merges.
+    #[inline]
+    #[must_use]
+    pub fn with_criteria_executor(mut self, executor: Arc<dyn CriteriaExecutor>) -> Self {
+        self.criteria_executor = Some(executor);
+        self
+    }
+
+    /// Draw every graph the decomposer constructs from `budget`
+    ///
+    /// Share the budget with [`BestOfN::with_budget`](crate::BestOfN::with_budget)
+    /// to charge debates to the same session.
+    #[must_use]
+    pub fn with_budget(mut self, budget: Arc<RunBudget>) -> Self {
+        self.decomposer = self.decomposer.with_budget(budget);
+        self
+    }
+
+    /// Gate decomposed tasks through `admission`
+    #[inline]
+    #[must_use]
+    pub fn with_admission(mut self, admission: AdmissionController) -> Self {
+        self.admission = admission;
+        self
+    }
+
+    /// Run agents in chaos mode (see [`AgentPool::with_chaos`])
+    #[must_use]
+    pub fn with_chaos(mut self, config: ChaosConfig) -> Self {
+        self.agent_pool = AgentPool::new(self.config.max_concurrent_agents)
+            .with_limits(self.config.system_limits)
+            .with_chaos(config);
+        self
+    }
+
     /// Execute high-level user intent
     ///
     /// This is the main entry point for user interactions.
     ///
     /// # Workflow
-    /// 1. Parse intent into structured specification
-    /// 2. Decompose into tasks
-    /// 3. Create execution graph
-    /// 4. Validate and execute
-    /// 5. Handle failures with diagnostics
+    /// 1. Classify intent and parse it into structured specification
+    /// 2. Decompose into tasks and apply the category's routing defaults
+    /// 3. Admit the tasks, or refuse them with `COAError::Busy` if overloaded
+    /// 4. Create execution graph
+    /// 5. Validate and execute
+    /// 6. Handle failures with diagnostics
     ///
     /// # Arguments
     /// * `intent` - User intent (natural language)
@@ -67,18 +148,45 @@ impl CreatorOrchestratorAgent {
     pub async fn execute_intent(
         &self,
         intent: UserIntent,
+    ) -> Result<ExecutionResult, COAError> {
+        let intent = self.trace.capture_intent(intent);
+        let correlation = CorrelationId::new(
+            self.trace
+                .random_choice("correlation_id", Uuid::new_v4)?
+                .to_string(),
+        );
+        let span = tracing::info_span!("intent", correlation_id = %correlation);
+        self.run_intent(intent, &correlation).instrument(span).await
+    }
+
+    /// Run a captured intent under `correlation`
+    async fn run_intent(
+        &self,
+        intent: UserIntent,
+        correlation: &CorrelationId,
     ) -> Result<ExecutionResult, COAError> {
         tracing::info!("Executing intent: {}", intent.description);
 
-        // 1. Parse intent into structured specification
-        let spec = self.parse_intent(intent).await?;
+        // 1. Classify and parse intent into structured specification
+        let route = self.classifier.route(&intent);
+        tracing::debug!("Routed intent as {}", route.category);
+        let spec = self.parse_intent(intent, &route).await?;
         tracing::debug!("Parsed specification: {:?}", spec.goal);
 
         // 2. Decompose into tasks
-        let tasks = self.decompose(spec).await?;
+        let tasks = self.decompose(spec, &route, correlation).await?;
         tracing::info!("Decomposed into {} tasks", tasks.len());
 
-        // 3. Execute tasks through agent pool
+        // 3. Admit tasks instead of queuing them behind an overloaded system
+        let _permit = match self.admission.admit(tasks.len(), &self.agent_pool) {
+            Ok(permit) => permit,
+            Err(busy) => {
+                tracing::warn!("Refused {} tasks: {}", tasks.len(), busy);
+                return Err(busy.into());
+            }
+        };
+
+        // 4. Execute tasks through agent pool
         match self.execute_tasks(&tasks).await {
             Ok(result) => {
                 tracing::info!("Execution completed: {} nodes executed", result.nodes_executed);
@@ -86,14 +194,33 @@ impl CreatorOrchestratorAgent {
             }
             Err(e) => {
                 tracing::error!("Execution failed: {}", e);
-                // 4. Handle failure with diagnostics
+                // 5. Handle failure with diagnostics
                 self.handle_execution_failure(e, &tasks).await
             }
         }
     }
 
+    /// Re-execute the intent recorded in a replayed trace
+    ///
+    /// # Errors
+    /// Returns `COAError::ConfigError` if no intent was recorded, otherwise
+    /// the result of the replayed run
+    pub async fn execute_replay(&self) -> Result<ExecutionResult, COAError> {
+        let intent = self
+            .trace
+            .intent()
+            .ok_or_else(|| COAError::ConfigError("trace has no recorded intent".to_string()))?;
+        self.execute_intent(intent).await
+    }
+
     /// Parse natural language intent into structured spec
-    async fn parse_intent(&self, intent: UserIntent) -> Result<Specification, COAError> {
+    ///
+    /// A goal named in the intent wins over the route's template.
+    async fn parse_intent(
+        &self,
+        intent: UserIntent,
+        route: &IntentRoute,
+    ) -> Result<Specification, COAError> {
         // In a real implementation, this would:
         // 1. Use LLM to extract structured information
         // 2. Parse response into Specification
@@ -125,7 +252,7 @@ impl CreatorOrchestratorAgent {
         } else if desc.contains("optimize") {
             Goal::Optimize
         } else {
-            Goal::CreateNew // Default
+            route.template
         };
 
         let artifact_type = if intent.description.contains("function")
@@ -157,25 +284,96 @@ impl CreatorOrchestratorAgent {
     }
 
     /// Decompose specification into tasks
-    async fn decompose(&self, spec: Specification) -> Result<Vec<Task>, COAError> {
-        self.decomposer
+    async fn decompose(
+        &self,
+        spec: Specification,
+        route: &IntentRoute,
+        correlation: &CorrelationId,
+    ) -> Result<Vec<Task>, COAError> {
+        let mut tasks = self
+            .decomposer
             .decompose(spec, &self.symbol_index)
-            .await
-            .map_err(COAError::from)
+            .await?;
+        route.apply(&mut tasks);
+        self.pin_task_ids(&mut tasks)?;
+        correlate(&mut tasks, correlation);
+        Ok(tasks)
+    }
+
+    /// Route generated task IDs through the trace so replays reuse them
+    fn pin_task_ids(&self, tasks: &mut [Task]) -> Result<(), TraceError> {
+        let mut remap = HashMap::new();
+        for task in tasks.iter_mut() {
+            let id = TaskId(self.trace.random_choice("task_id", || task.id.0)?);
+            remap.insert(task.id, id);
+            task.id = id;
+        }
+        for task in tasks.iter_mut() {
+            for dependency in &mut task.dependencies {
+                if let Some(id) = remap.get(dependency) {
+                    *dependency = *id;
+                }
+            }
+        }
+        Ok(())
     }
 
     /// Execute tasks through agent pool
+    ///
+    /// Recursive expansion points are decomposed when reached and their
+    /// subtasks run in their place.
     async fn execute_tasks(&self, tasks: &[Task]) -> Result<ExecutionResult, COAError> {
         let mut completed = Vec::new();
         let mut artifacts = Vec::new();
-        let start_time = std::time::Instant::now();
+        let mut unmet_criteria = Vec::new();
+        let mut nodes_executed = 0;
+        let start_ms = self.trace.now_millis("execution_start")?;
+
+        let mut pending: VecDeque<Task> = tasks.iter().cloned().collect();
+        while let Some(task) = pending.pop_front() {
+            let task = &task;
+            nodes_executed += 1;
+
+            if matches!(task.expansion_type, Some(ExpansionType::Recursive { .. })) {
+                let mut subtasks = self.decomposer.expand(task).await?.tasks;
+                self.pin_task_ids(&mut subtasks)?;
+                if let Some(id) = &task.correlation_id {
+                    correlate(&mut subtasks, &CorrelationId::new(id.intent()));
+                }
+                completed.push(task.id);
+                for subtask in subtasks.into_iter().rev() {
+                    pending.push_front(subtask);
+                }
+                continue;
+            }
+
+            // Gate tasks hold the merge until acceptance criteria pass
+            if let Some(gate) = VerificationGate::from_task(task) {
+                let unmet = self.verify_gate(&gate).await;
+                if unmet.is_empty() {
+                    completed.push(task.id);
+                }
+                unmet_criteria.extend(unmet);
+                continue;
+            }
 
-        for task in tasks {
-            // Spawn agent for task
-            let agent = self.spawn_agent(task).await?;
+            let span = tracing::info_span!(
+                "task",
+                task_id = %task.id,
+                role = %task.role,
+                correlation_id = task.correlation_id.as_ref().map(tracing::field::display),
+            );
+
+            // Spawn agent for task and hand it the task
+            let agent = self.spawn_agent(task).instrument(span.clone()).await?;
+            let agent = self
+                .agent_pool
+                .send_supervised(agent, AgentMessage::Execute(task.clone()))
+                .instrument(span.clone())
+                .await?;
 
             // Execute task
-            match self.execute_task(&agent, task).await {
+            match self.execute_task(&agent, task).instrument(span).await {
                 Ok(artifact) => {
                     completed.push(task.id);
                     artifacts.push(ArtifactSummary {
@@ -200,23 +398,42 @@ impl CreatorOrchestratorAgent {
             self.agent_pool.release(agent).await;
         }
 
-        let execution_time_ms = start_time.elapsed().as_millis() as u64;
+        let execution_time_ms = self
+            .trace
+            .now_millis("execution_end")?
+            .saturating_sub(start_ms);
 
         Ok(ExecutionResult {
-            nodes_executed: tasks.len(),
+            nodes_executed,
             execution_time_ms,
             artifacts_produced: artifacts,
             tasks_completed: completed,
+            unmet_criteria,
+            correlation_id: tasks
+                .iter()
+                .find_map(|task| task.correlation_id.as_ref())
+                .map(|id| CorrelationId::new(id.intent())),
         })
     }
 
+    /// Run a gate's checks
+    async fn verify_gate(&self, gate: &VerificationGate) -> Vec<UnmetCriterion> {
+        match &self.criteria_executor {
+            Some(executor) => gate.verify(executor.as_ref()).await,
+            None => gate
+                .checks()
+                .iter()
+                .map(|check| UnmetCriterion {
+                    criterion: check.criterion.clone(),
+                    reason: "no criteria executor configured".to_string(),
+                })
+                .collect(),
+        }
+    }
+
     /// Spawn an agent for a task
     async fn spawn_agent(&self, task: &Task) -> Result<AgentHandle, COAError> {
-        let agent_spec = AgentSpec::from_task(task);
-        self.agent_pool
-            .acquire(agent_spec)
-            .await
-            .map_err(COAError::from)
+        self.agent_pool.assign(task).await.map_err(COAError::from)
     }
 
     /// Execute single task through agent
@@ -279,11 +496,20 @@ impl CreatorOrchestratorAgent {
                 ErrorType::Agent,
                 Location::Agent("unknown".to_string()),
             ),
+            COAError::PoolError(PoolError::RestartLimitExceeded { role, .. }) => {
+                (ErrorType::Agent, Location::Agent(role.clone()))
+            }
+            COAError::PoolError(PoolError::NoCapableAgent(mismatch)) => {
+                (ErrorType::Agent, Location::Task(mismatch.task.to_string()))
+            }
             _ => (ErrorType::Unknown, Location::Unknown),
         };
 
-        Diagnostic::new(error_type, location)
-            .with_context(crate::error::Context::empty().add("task_count", tasks.len().to_string()))
+        let mut context = crate::error::Context::empty().add("task_count", tasks.len().to_string());
+        if let Some(id) = tasks.iter().find_map(|task| task.correlation_id.as_ref()) {
+            context = context.add("correlation_id", id.intent().to_string());
+        }
+        Diagnostic::new(error_type, location).with_context(context)
     }
 
     /// Suggest fixes for failure
@@ -300,6 +526,20 @@ impl CreatorOrchestratorAgent {
                     .auto_applicable(),
                 );
             }
+            COAError::PoolError(crate::error::PoolError::NoCapableAgent(mismatch)) => {
+                let missing: Vec<_> = mismatch.missing().iter().map(ToString::to_string).collect();
+                if missing.is_empty() {
+                    fixes.push(SuggestedFix::new(
+                        format!("Register a '{}' agent", mismatch.role),
+                        0.8,
+                    ));
+                } else {
+                    fixes.push(SuggestedFix::new(
+                        format!("Register a '{}' agent with {}", mismatch.role, missing.join(", ")),
+                        0.8,
+                    ));
+                }
+            }
             COAError::DecompositionFailed(DecompositionError::RecursionDepthExceeded) => {
                 fixes.push(SuggestedFix::new(
                     "Increase max decomposition depth",
@@ -317,6 +557,26 @@ impl CreatorOrchestratorAgent {
         fixes
     }
 
+    /// Apply collected deltas through an agent's scoped layer
+    ///
+    /// Deltas are validated against the COA's symbol namespace; on success
+    /// the layer logs the composition as a kernel event attributed to the
+    /// node holding the layer's capability token.
+    ///
+    /// # Errors
+    /// - `ApplyError::CompositionFailed` if the strategy rejects the deltas
+    /// - Other `ApplyError` variants if applying or logging fails; all
+    ///   convert into `COAError`
+    pub fn apply_composition<T: ArtifactType, S: CompositionStrategy>(
+        &self,
+        layer: &ScopedLayer<'_>,
+        base: &Artifact<T>,
+        deltas: &[StructuralDelta<T>],
+        strategy: &S,
+    ) -> Result<Artifact<T>, ApplyError> {
+        layer.apply_deltas(base, deltas, strategy, &self.symbol_index)
+    }
+
     /// Get configuration
     #[inline]
     #[must_use]
@@ -331,10 +591,29 @@ impl CreatorOrchestratorAgent {
         &self.symbol_index
     }
 
+    /// Get trace of nondeterministic inputs
+    #[inline]
+    #[must_use]
+    pub fn trace(&self) -> &Trace {
+        &self.trace
+    }
+
     /// Get agent pool stats
     pub async fn pool_stats(&self) -> crate::agent_pool::PoolStats {
         self.agent_pool.stats().await
     }
+
+    /// Faults injected into agent messages (chaos mode)
+    pub fn chaos_events(&self) -> Vec<crate::chaos::ChaosEvent> {
+        self.agent_pool.chaos_events()
+    }
+}
+
+/// Correlate each task with its own task of the intent `correlation`
+fn correlate(tasks: &mut [Task], correlation: &CorrelationId) {
+    for task in tasks {
+        task.correlation_id = Some(correlation.for_task(task.id));
+    }
 }
 
 impl Default for CreatorOrchestratorAgent {
@@ -343,25 +622,6 @@ impl Default for CreatorOrchestratorAgent {
     }
 }
 
-/// Apply composition strategy to collected deltas
-#[allow(dead_code)]
-fn compose_deltas<T: ArtifactType, S: CompositionStrategy>(
-    base: &Artifact<T>,
-    deltas: Vec<StructuralDelta<T>>,
-    strategy: &S,
-    index: &SymbolRefIndex,
-) -> Result<Artifact<T>, COAError> {
-    // Validate composition
-    strategy
-        .validate(&deltas, index)
-        .map_err(|e| COAError::CompositionFailed(e))?;
-
-    // Compose
-    strategy
-        .compose(base, &deltas)
-        .map_err(|e| COAError::CompositionFailed(e))
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -379,7 +639,8 @@ mod tests {
         let coa = CreatorOrchestratorAgent::default();
 
         let intent = UserIntent::new("Create a new authentication function");
-        let spec = coa.parse_intent(intent).await.unwrap();
+        let route = coa.classifier.route(&intent);
+        let spec = coa.parse_intent(intent, &route).await.unwrap();
 
         assert!(matches!(spec.goal, Goal::CreateNew));
         assert_eq!(spec.artifact_type, "code");
@@ -390,7 +651,8 @@ mod tests {
         let coa = CreatorOrchestratorAgent::default();
 
         let intent = UserIntent::new("Update the config settings");
-        let spec = coa.parse_intent(intent).await.unwrap();
+        let route = coa.classifier.route(&intent);
+        let spec = coa.parse_intent(intent, &route).await.unwrap();
 
         assert!(matches!(spec.goal, Goal::ModifyExisting));
     }
@@ -400,11 +662,111 @@ mod tests {
         let coa = CreatorOrchestratorAgent::default();
 
         let intent = UserIntent::new("Refactor the utils module");
-        let spec = coa.parse_intent(intent).await.unwrap();
+        let route = coa.classifier.route(&intent);
+        let spec = coa.parse_intent(intent, &route).await.unwrap();
 
         assert!(matches!(spec.goal, Goal::Refactor));
     }
 
+    #[tokio::test]
+    async fn coa_parse_intent_uses_category_template() {
+        let coa = CreatorOrchestratorAgent::default();
+
+        let intent = UserIntent::new("Fix the crash in the parser");
+        let route = coa.classifier.route(&intent);
+        let spec = coa.parse_intent(intent, &route).await.unwrap();
+
+        assert_eq!(route.category, crate::intent::IntentCategory::Bugfix);
+        assert!(matches!(spec.goal, Goal::ModifyExisting));
+    }
+
+    #[tokio::test]
+    async fn coa_tasks_and_results_carry_correlation() {
+        let coa = CreatorOrchestratorAgent::default();
+        let intent = UserIntent::new("Create a simple function");
+        let route = coa.classifier.route(&intent);
+        let spec = coa.parse_intent(intent, &route).await.unwrap();
+        let correlation = CorrelationId::new("run-1");
+
+        let tasks = coa.decompose(spec, &route, &correlation).await.unwrap();
+        assert!(tasks
+            .iter()
+            .all(|task| task.correlation_id == Some(correlation.for_task(task.id))));
+
+        let gate_only: Vec<Task> = tasks
+            .into_iter()
+            .filter(|task| VerificationGate::from_task(task).is_some())
+            .collect();
+        let result = coa.execute_tasks(&gate_only).await.unwrap();
+        assert_eq!(result.correlation_id, Some(correlation));
+    }
+
+    #[tokio::test]
+    async fn coa_gate_blocks_merge_until_criteria_pass() {
+        let criteria = vec!["Workspace compiles".to_string()];
+        let mut tasks = Vec::new();
+        VerificationGate::new(&criteria)
+            .unwrap()
+            .append_to(&mut tasks, coa_artifact::SymbolPath::default());
+
+        let result = CreatorOrchestratorAgent::default().execute_tasks(&tasks).await.unwrap();
+        assert!(!result.is_merged());
+        assert_eq!(result.unmet_criteria[0].criterion, "Workspace compiles");
+        assert!(result.tasks_completed.is_empty());
+
+        let executor = crate::verification::CommandCriteriaExecutor::new(std::env::temp_dir())
+            .with_build_command(vec!["true".to_string()]);
+        let coa = CreatorOrchestratorAgent::default().with_criteria_executor(Arc::new(executor));
+        let result = coa.execute_tasks(&tasks).await.unwrap();
+        assert!(result.is_merged());
+        assert_eq!(result.tasks_completed, vec![tasks[0].id]);
+    }
+
+    #[tokio::test]
+    async fn coa_replay_reproduces_recorded_run() {
+        let recorder = CreatorOrchestratorAgent::default().with_trace(Trace::record());
+        let recorded = recorder
+            .execute_intent(UserIntent::new("Create a simple function"))
+            .await
+            .unwrap_err();
+
+        let replayer =
+            CreatorOrchestratorAgent::default().with_trace(Trace::replay(recorder.trace().snapshot()));
+        let replayed = replayer.execute_replay().await.unwrap_err();
+
+        // Failure messages embed the generated task IDs
+        assert_eq!(replayed.to_string(), recorded.to_string());
+        assert_eq!(replayer.trace().remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn coa_replay_without_intent_fails() {
+        let coa = CreatorOrchestratorAgent::default()
+            .with_trace(Trace::replay(crate::trace::RunTrace::default()));
+        assert!(matches!(
+            coa.execute_replay().await,
+            Err(COAError::ConfigError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn coa_refuses_intents_while_overloaded() {
+        use crate::admission::{AdmissionPolicy, Overload};
+
+        let coa = CreatorOrchestratorAgent::default().with_admission(
+            AdmissionController::new(AdmissionPolicy::new().with_max_memory_pressure(0.5))
+                .with_memory_pressure(|| 0.75),
+        );
+        match coa.execute_intent(UserIntent::new("Create a simple function")).await {
+            Err(COAError::Busy(busy)) => {
+                assert_eq!(busy.reason, Overload::MemoryPressure);
+                assert_eq!(busy.retry_after, std::time::Duration::from_millis(1500));
+            }
+            other => panic!("expected busy, got {other:?}"),
+        }
+        assert_eq!(coa.admission.in_flight(), 0);
+    }
+
     #[tokio::test]
     async fn coa_default_config() {
         let coa = CreatorOrchestratorAgent::default();
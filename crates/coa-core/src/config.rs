@@ -0,0 +1,721 @@
+//! Layered configuration loading
+//!
+//! [`COAConfig`] is resolved from these layers, lowest precedence first:
+//! 1. a named [`ConfigProfile`] (`local` unless `COA_PROFILE` or the caller
+//!    picks another)
+//! 2. a TOML or JSON file (`COA_CONFIG`), whose `[profiles.<name>]` tables
+//!    apply on top of its top-level keys when that profile is active
+//! 3. `COA_*` environment variables, `__` separating nested keys
+//!    (`COA_SYSTEM_LIMITS__MAX_AGENTS=16`)
+//! 4. `key=value` overrides, dots separating nested keys
+//!    (`system_limits.max_agents=16`)
+//!
+//! Every layer is checked against the shape of [`COAConfig`] before it is
+//! merged, so a misspelt key or a value of the wrong type is reported along
+//! with the layer it came from instead of being silently dropped.
+//!
+//! ```rust,ignore
+//! let resolved = ConfigLoader::from_env()
+//!     .with_profile(ConfigProfile::Ci)
+//!     .with_override("task_timeout_secs=900")
+//!     .load()?;
+//! let coa = CreatorOrchestratorAgent::new(resolved.config);
+//! ```
+
+use crate::error::ConfigError;
+use crate::types::{AutonomyLevel, COAConfig, EscalationThreshold, SystemLimits};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Prefix of configuration environment variables
+pub const ENV_PREFIX: &str = "COA_";
+
+/// Environment variable naming the active profile
+pub const PROFILE_ENV: &str = "COA_PROFILE";
+
+/// Environment variable naming the config file
+pub const CONFIG_FILE_ENV: &str = "COA_CONFIG";
+
+/// Reserved file key holding per-profile tables
+const PROFILES_KEY: &str = "profiles";
+
+/// Named set of defaults for an environment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ConfigProfile {
+    /// Continuous integration: few agents, no auto-applied fixes
+    Ci,
+    /// Developer machine: the stock defaults
+    #[default]
+    Local,
+    /// Production: human merges and early escalation
+    Prod,
+}
+
+impl ConfigProfile {
+    /// All profiles
+    pub const ALL: [Self; 3] = [Self::Ci, Self::Local, Self::Prod];
+
+    /// Profile name
+    #[inline]
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ci => "ci",
+            Self::Local => "local",
+            Self::Prod => "prod",
+        }
+    }
+
+    /// Configuration this profile starts from
+    #[must_use]
+    pub fn config(self) -> COAConfig {
+        match self {
+            Self::Local => COAConfig::default(),
+            Self::Ci => COAConfig {
+                max_concurrent_agents: 4,
+                system_limits: SystemLimits {
+                    max_memory_gb: 8,
+                    max_cpu_cores: 4,
+                    max_agents: 16,
+                    ..SystemLimits::default()
+                },
+                task_timeout_secs: 600,
+                ..COAConfig::default()
+            },
+            Self::Prod => COAConfig {
+                max_concurrent_agents: 32,
+                default_autonomy: AutonomyLevel::L2,
+                system_limits: SystemLimits {
+                    max_memory_gb: 64,
+                    max_cpu_cores: 16,
+                    max_agents: 256,
+                    ..SystemLimits::default()
+                },
+                escalation_threshold: EscalationThreshold {
+                    max_test_failures: 1,
+                    ..EscalationThreshold::default()
+                },
+                ..COAConfig::default()
+            },
+        }
+    }
+}
+
+impl fmt::Display for ConfigProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ConfigProfile {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|profile| profile.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| ConfigError::UnknownProfile(s.to_string()))
+    }
+}
+
+/// Layer a configuration value came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// Built-in profile defaults
+    Profile(ConfigProfile),
+    /// Config file
+    File(PathBuf),
+    /// Environment variable
+    Env(String),
+    /// Command-line override
+    Cli,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Profile(profile) => write!(f, "profile `{profile}`"),
+            Self::File(path) => write!(f, "{}", path.display()),
+            Self::Env(var) => write!(f, "${var}"),
+            Self::Cli => f.write_str("command line"),
+        }
+    }
+}
+
+/// Builder for a layered [`COAConfig`]
+#[derive(Debug, Clone, Default)]
+pub struct ConfigLoader {
+    profile: Option<ConfigProfile>,
+    file: Option<PathBuf>,
+    env: Vec<(String, String)>,
+    overrides: Vec<String>,
+}
+
+impl ConfigLoader {
+    /// Create a loader with no file, environment or overrides
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a loader reading the process environment
+    #[must_use]
+    pub fn from_env() -> Self {
+        Self::new().with_env(std::env::vars())
+    }
+
+    /// With the profile to start from, overriding `COA_PROFILE`
+    #[inline]
+    #[must_use]
+    pub fn with_profile(mut self, profile: ConfigProfile) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    /// With the config file, overriding `COA_CONFIG`
+    #[inline]
+    #[must_use]
+    pub fn with_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.file = Some(path.into());
+        self
+    }
+
+    /// With environment variables; only `COA_*` ones are read
+    #[must_use]
+    pub fn with_env<I, K, V>(mut self, vars: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.env.extend(
+            vars.into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .filter(|(k, _)| k.starts_with(ENV_PREFIX)),
+        );
+        self
+    }
+
+    /// With a `key=value` override; later overrides win
+    #[inline]
+    #[must_use]
+    pub fn with_override(mut self, assignment: impl Into<String>) -> Self {
+        self.overrides.push(assignment.into());
+        self
+    }
+
+    fn env_var(&self, name: &str) -> Option<&str> {
+        self.env
+            .iter()
+            .rev()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Resolve the layers into a validated configuration
+    ///
+    /// # Errors
+    /// Returns the first unknown profile, unreadable file, key outside the
+    /// schema, mistyped value or invalid combination of settings.
+    pub fn load(&self) -> Result<ResolvedConfig, ConfigError> {
+        let profile = match (self.profile, self.env_var(PROFILE_ENV)) {
+            (Some(profile), _) => profile,
+            (None, Some(name)) => name.parse()?,
+            (None, None) => ConfigProfile::default(),
+        };
+        let schema = to_value(&COAConfig::default());
+        let mut resolved = Layers::new(profile);
+
+        let file = self
+            .file
+            .clone()
+            .or_else(|| self.env_var(CONFIG_FILE_ENV).map(PathBuf::from));
+        if let Some(path) = file {
+            let mut table = read_file(&path)?;
+            let profiles = table.remove(PROFILES_KEY);
+            let source = ConfigSource::File(path);
+            resolved.merge(&schema, &table, &source)?;
+            if let Some(profiles) = profiles {
+                let Value::Object(profiles) = profiles else {
+                    return Err(ConfigError::InvalidValue {
+                        key: PROFILES_KEY.to_string(),
+                        layer: source,
+                        expected: "a table",
+                        found: profiles.to_string(),
+                    });
+                };
+                for (name, table) in &profiles {
+                    name.parse::<ConfigProfile>()?;
+                    let Value::Object(table) = table else {
+                        return Err(ConfigError::InvalidValue {
+                            key: format!("{PROFILES_KEY}.{name}"),
+                            layer: source,
+                            expected: "a table",
+                            found: table.to_string(),
+                        });
+                    };
+                    if name.eq_ignore_ascii_case(profile.as_str()) {
+                        resolved.merge(&schema, table, &source)?;
+                    }
+                }
+            }
+        }
+
+        let mut vars: Vec<_> = self
+            .env
+            .iter()
+            .filter(|(k, _)| k != PROFILE_ENV && k != CONFIG_FILE_ENV)
+            .collect();
+        vars.sort();
+        for (name, raw) in vars {
+            let key = name[ENV_PREFIX.len()..]
+                .to_ascii_lowercase()
+                .replace("__", ".");
+            let source = ConfigSource::Env(name.clone());
+            resolved.set(&schema, &key, raw, &source)?;
+        }
+
+        for assignment in &self.overrides {
+            let (key, raw) = assignment
+                .split_once('=')
+                .ok_or_else(|| ConfigError::MalformedOverride(assignment.clone()))?;
+            resolved.set(&schema, key.trim(), raw.trim(), &ConfigSource::Cli)?;
+        }
+
+        resolved.finish()
+    }
+}
+
+/// Merged configuration with the layer behind each setting
+#[derive(Debug, Clone)]
+pub struct ResolvedConfig {
+    /// The effective configuration
+    pub config: COAConfig,
+    /// Profile the layers were applied to
+    pub profile: ConfigProfile,
+    sources: BTreeMap<String, ConfigSource>,
+}
+
+impl ResolvedConfig {
+    /// Layer that set `key` (dotted, e.g. `system_limits.max_agents`)
+    #[must_use]
+    pub fn source_of(&self, key: &str) -> Option<&ConfigSource> {
+        self.sources.get(key)
+    }
+
+    /// Settings changed from the profile defaults, by key
+    pub fn overrides(&self) -> impl Iterator<Item = (&str, &ConfigSource)> {
+        self.sources
+            .iter()
+            .filter(|(_, source)| !matches!(source, ConfigSource::Profile(_)))
+            .map(|(key, source)| (key.as_str(), source))
+    }
+
+    /// Every setting as `(key, value, source)`, sorted by key
+    #[must_use]
+    pub fn entries(&self) -> Vec<(String, Value, &ConfigSource)> {
+        let mut leaves = Vec::new();
+        flatten(&to_value(&self.config), "", &mut leaves);
+        leaves
+            .into_iter()
+            .filter_map(|(key, value)| {
+                let source = self.sources.get(&key)?;
+                Some((key, value, source))
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for ResolvedConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines: Vec<_> = self
+            .entries()
+            .into_iter()
+            .map(|(key, value, source)| (format!("{key} = {value}"), source))
+            .collect();
+        let width = lines.iter().map(|(line, _)| line.len()).max().unwrap_or(0);
+        for (line, source) in lines {
+            writeln!(f, "{line:<width$}  # {source}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Configuration being merged, as JSON
+struct Layers {
+    profile: ConfigProfile,
+    value: Value,
+    sources: BTreeMap<String, ConfigSource>,
+}
+
+impl Layers {
+    fn new(profile: ConfigProfile) -> Self {
+        let value = to_value(&profile.config());
+        let mut leaves = Vec::new();
+        flatten(&value, "", &mut leaves);
+        let sources = leaves
+            .into_iter()
+            .map(|(key, _)| (key, ConfigSource::Profile(profile)))
+            .collect();
+        Self {
+            profile,
+            value,
+            sources,
+        }
+    }
+
+    /// Merge a file table after checking it against `schema`
+    fn merge(
+        &mut self,
+        schema: &Value,
+        table: &Map<String, Value>,
+        source: &ConfigSource,
+    ) -> Result<(), ConfigError> {
+        for (name, value) in table {
+            self.merge_field(schema, name, value, source)?;
+        }
+        Ok(())
+    }
+
+    fn merge_field(
+        &mut self,
+        schema: &Value,
+        key: &str,
+        value: &Value,
+        source: &ConfigSource,
+    ) -> Result<(), ConfigError> {
+        let expected = lookup(schema, key, source)?;
+        match (expected, value) {
+            (Value::Object(_), Value::Object(fields)) => {
+                for (name, value) in fields {
+                    self.merge_field(schema, &format!("{key}.{name}"), value, source)?;
+                }
+                Ok(())
+            }
+            _ => {
+                check_type(key, expected, value, source)?;
+                self.assign(key, value.clone(), source);
+                Ok(())
+            }
+        }
+    }
+
+    /// Set one leaf from its textual form
+    fn set(
+        &mut self,
+        schema: &Value,
+        key: &str,
+        raw: &str,
+        source: &ConfigSource,
+    ) -> Result<(), ConfigError> {
+        let expected = lookup(schema, key, source)?;
+        let value = match expected {
+            Value::Bool(_) => raw.parse().map(Value::Bool).ok(),
+            Value::Number(n) if n.is_u64() => raw.parse::<u64>().ok().map(Value::from),
+            Value::Number(_) => raw.parse::<f64>().ok().map(Value::from),
+            Value::String(_) => Some(Value::String(raw.to_string())),
+            _ => None,
+        }
+        .ok_or_else(|| ConfigError::InvalidValue {
+            key: key.to_string(),
+            layer: source.clone(),
+            expected: type_name(expected),
+            found: format!("{raw:?}"),
+        })?;
+        self.assign(key, value, source);
+        Ok(())
+    }
+
+    fn assign(&mut self, key: &str, value: Value, source: &ConfigSource) {
+        let mut slot = &mut self.value;
+        for part in key.split('.') {
+            slot = &mut slot[part];
+        }
+        *slot = value;
+        self.sources.insert(key.to_string(), source.clone());
+    }
+
+    fn finish(self) -> Result<ResolvedConfig, ConfigError> {
+        let config: COAConfig =
+            serde_json::from_value(self.value).map_err(|e| ConfigError::Invalid(e.to_string()))?;
+        validate(&config)?;
+        Ok(ResolvedConfig {
+            config,
+            profile: self.profile,
+            sources: self.sources,
+        })
+    }
+}
+
+/// Check settings that are well-typed but cannot work together
+fn validate(config: &COAConfig) -> Result<(), ConfigError> {
+    let limits = &config.system_limits;
+    let problem = if config.max_concurrent_agents == 0 {
+        "max_concurrent_agents must be at least 1".to_string()
+    } else if config.max_concurrent_agents > limits.max_agents {
+        format!(
+            "max_concurrent_agents ({}) exceeds system_limits.max_agents ({})",
+            config.max_concurrent_agents, limits.max_agents
+        )
+    } else if limits.max_memory_gb == 0 || limits.max_cpu_cores == 0 {
+        "system_limits.max_memory_gb and system_limits.max_cpu_cores must be at least 1".to_string()
+    } else if !(limits.preemption_threshold > 0.0 && limits.preemption_threshold <= 1.0) {
+        format!(
+            "system_limits.preemption_threshold ({}) must be in (0, 1]",
+            limits.preemption_threshold
+        )
+    } else if config.task_timeout_secs == 0 {
+        "task_timeout_secs must be at least 1".to_string()
+    } else if config.max_decomposition_depth == 0 {
+        "max_decomposition_depth must be at least 1".to_string()
+    } else {
+        return Ok(());
+    };
+    Err(ConfigError::Invalid(problem))
+}
+
+fn to_value(config: &COAConfig) -> Value {
+    serde_json::to_value(config).unwrap_or(Value::Null)
+}
+
+fn read_file(path: &Path) -> Result<Map<String, Value>, ConfigError> {
+    let text = std::fs::read_to_string(path).map_err(|e| ConfigError::Io {
+        path: path.to_path_buf(),
+        message: e.to_string(),
+    })?;
+    let parsed = if path.extension().is_some_and(|ext| ext == "json") {
+        serde_json::from_str(&text).map_err(|e| e.to_string())
+    } else {
+        toml::from_str(&text).map_err(|e| e.to_string())
+    };
+    match parsed {
+        Ok(Value::Object(table)) => Ok(table),
+        Ok(_) => Err(ConfigError::Parse {
+            path: path.to_path_buf(),
+            message: "expected a table at the top level".to_string(),
+        }),
+        Err(message) => Err(ConfigError::Parse {
+            path: path.to_path_buf(),
+            message,
+        }),
+    }
+}
+
+/// Schema node for a dotted key, suggesting a sibling if it is unknown
+fn lookup<'a>(
+    schema: &'a Value,
+    key: &str,
+    source: &ConfigSource,
+) -> Result<&'a Value, ConfigError> {
+    let mut node = schema;
+    let mut parent = String::new();
+    for part in key.split('.') {
+        let fields = node.as_object().filter(|_| !part.is_empty());
+        match fields.and_then(|fields| fields.get(part)) {
+            Some(child) => node = child,
+            None => {
+                return Err(ConfigError::UnknownKey {
+                    key: key.to_string(),
+                    layer: source.clone(),
+                    suggestion: fields
+                        .and_then(|fields| closest(part, fields))
+                        .map(|name| format!("{parent}{name}")),
+                });
+            }
+        }
+        parent = format!("{parent}{part}.");
+    }
+    Ok(node)
+}
+
+fn check_type(
+    key: &str,
+    expected: &Value,
+    found: &Value,
+    source: &ConfigSource,
+) -> Result<(), ConfigError> {
+    let matches = match (expected, found) {
+        (Value::Bool(_), Value::Bool(_)) | (Value::String(_), Value::String(_)) => true,
+        (Value::Number(n), Value::Number(m)) => !n.is_u64() || m.is_u64(),
+        _ => false,
+    };
+    if matches {
+        Ok(())
+    } else {
+        Err(ConfigError::InvalidValue {
+            key: key.to_string(),
+            layer: source.clone(),
+            expected: type_name(expected),
+            found: found.to_string(),
+        })
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Bool(_) => "a boolean",
+        Value::Number(n) if n.is_u64() => "a non-negative integer",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Object(_) => "a table",
+        Value::Array(_) => "an array",
+        Value::Null => "nothing",
+    }
+}
+
+fn flatten(value: &Value, prefix: &str, out: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Object(fields) => {
+            for (name, child) in fields {
+                let key = if prefix.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{prefix}.{name}")
+                };
+                flatten(child, &key, out);
+            }
+        }
+        leaf => out.push((prefix.to_string(), leaf.clone())),
+    }
+}
+
+/// Known field closest to a misspelt one, if any is close enough
+fn closest<'a>(name: &str, fields: &'a Map<String, Value>) -> Option<&'a str> {
+    fields
+        .keys()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, candidate)| *distance <= candidate.len().max(3) / 3)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.as_str())
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = (diagonal + usize::from(ca != *cb))
+                .min(above + 1)
+                .min(row[j] + 1);
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layers_apply_in_order_with_sources() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("coa.toml");
+        std::fs::write(
+            &file,
+            "task_timeout_secs = 120\nauto_apply_fixes = true\n\n\
+             [system_limits]\nmax_agents = 8\n\n\
+             [profiles.ci]\ntask_timeout_secs = 60\n",
+        )
+        .unwrap();
+
+        let resolved = ConfigLoader::new()
+            .with_env([
+                ("COA_PROFILE", "ci"),
+                ("COA_CONFIG", file.to_str().unwrap()),
+                ("COA_SYSTEM_LIMITS__PREEMPTION_THRESHOLD", "0.75"),
+                ("COA_DEFAULT_AUTONOMY", "L1"),
+                ("PATH", "/usr/bin"),
+            ])
+            .with_override("max_concurrent_agents=6")
+            .with_override("default_autonomy = L2")
+            .load()
+            .unwrap();
+
+        let config = &resolved.config;
+        assert_eq!(resolved.profile, ConfigProfile::Ci);
+        assert_eq!(config.task_timeout_secs, 60);
+        assert!(config.auto_apply_fixes);
+        assert_eq!(config.system_limits.max_agents, 8);
+        assert_eq!(config.system_limits.max_cpu_cores, 4);
+        assert!((config.system_limits.preemption_threshold - 0.75).abs() < f64::EPSILON);
+        assert_eq!(config.max_concurrent_agents, 6);
+        assert_eq!(config.default_autonomy, AutonomyLevel::L2);
+
+        assert_eq!(
+            resolved.source_of("system_limits.max_cpu_cores"),
+            Some(&ConfigSource::Profile(ConfigProfile::Ci))
+        );
+        assert_eq!(
+            resolved.source_of("task_timeout_secs"),
+            Some(&ConfigSource::File(file))
+        );
+        assert_eq!(
+            resolved.source_of("system_limits.preemption_threshold"),
+            Some(&ConfigSource::Env(
+                "COA_SYSTEM_LIMITS__PREEMPTION_THRESHOLD".to_string()
+            ))
+        );
+        assert_eq!(
+            resolved.source_of("default_autonomy"),
+            Some(&ConfigSource::Cli)
+        );
+        assert_eq!(resolved.overrides().count(), 6);
+        assert!(resolved.to_string().contains("max_concurrent_agents = 6"));
+    }
+
+    #[test]
+    fn bad_layers_are_reported_with_their_source() {
+        let err = ConfigLoader::new()
+            .with_override("system_limits.max_agnets=4")
+            .load()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unknown key `system_limits.max_agnets` in command line; \
+             did you mean `system_limits.max_agents`?"
+        );
+
+        let err = ConfigLoader::new()
+            .with_env([("COA_TASK_TIMEOUT_SECS", "soon")])
+            .load()
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::InvalidValue { ref key, expected: "a non-negative integer", .. }
+                if key == "task_timeout_secs"
+        ));
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("coa.json");
+        std::fs::write(&file, r#"{"system_limits": {"max_agents": -1}}"#).unwrap();
+        let err = ConfigLoader::new().with_file(&file).load().unwrap_err();
+        assert!(err.to_string().contains("coa.json"));
+
+        assert!(matches!(
+            ConfigLoader::new()
+                .with_override("max_concurrent_agents=500")
+                .load(),
+            Err(ConfigError::Invalid(_))
+        ));
+        assert!(matches!(
+            ConfigLoader::new()
+                .with_env([("COA_PROFILE", "staging")])
+                .load(),
+            Err(ConfigError::UnknownProfile(_))
+        ));
+        assert!(matches!(
+            ConfigLoader::new()
+                .with_override("task_timeout_secs")
+                .load(),
+            Err(ConfigError::MalformedOverride(_))
+        ));
+    }
+}
@@ -0,0 +1,565 @@
+//! Prompt context assembly for LLM-backed agents
+//!
+//! A [`ContextAssembler`] builds the context an agent works from:
+//! - the task and its acceptance criteria, always kept
+//! - the source of symbols around the task's target and claims, found with
+//!   [`SymbolRefIndex::neighborhood`] and ranked nearest first
+//! - deltas already applied, ranked by whether they touch the task's paths
+//!
+//! Sections are fitted to a token budget with a [`TruncationStrategy`].
+//! Assembled contexts are cached by a hash of everything that went into
+//! them (including the content hashes of the indexed symbols), so an
+//! unchanged task over an unchanged codebase is assembled once.
+
+use crate::types::Task;
+use coa_artifact::{ArtifactType, ContentHash, StructuralDelta, SymbolPath};
+use coa_symbol::{IndexEntry, SymbolRef, SymbolRefIndex};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Characters per token assumed by [`estimate_tokens`]
+pub const CHARS_PER_TOKEN: usize = 4;
+
+/// Default token budget of an assembled context
+pub const DEFAULT_CONTEXT_BUDGET: usize = 8_000;
+
+/// Default tree distance of symbols pulled into a context
+pub const DEFAULT_NEIGHBORHOOD_RADIUS: usize = 2;
+
+/// Appended to sections cut to fit the budget
+const TRUNCATION_MARKER: &str = "\n[... truncated]";
+
+/// Rough token count of `text`
+#[must_use]
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Source text of indexed symbols
+pub trait ContextSource: Send + Sync {
+    /// Text of `symbol`, if known
+    fn text(&self, symbol: &SymbolRef) -> Option<String>;
+}
+
+impl<F> ContextSource for F
+where
+    F: Fn(&SymbolRef) -> Option<String> + Send + Sync,
+{
+    fn text(&self, symbol: &SymbolRef) -> Option<String> {
+        self(symbol)
+    }
+}
+
+/// How sections are fitted to the budget
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TruncationStrategy {
+    /// Keep sections whole in relevance order, skipping any that no longer
+    /// fit
+    #[default]
+    DropLeastRelevant,
+    /// Keep sections in relevance order, cutting the first that overflows
+    /// and dropping the rest
+    TrimTail,
+    /// Give every section an equal share of the budget, cutting those
+    /// larger than their share
+    Proportional,
+}
+
+/// What a section holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SectionKind {
+    /// Task description
+    Task,
+    /// Acceptance criteria
+    Criteria,
+    /// Source of an indexed symbol
+    Symbol,
+    /// Previously applied delta
+    Delta,
+}
+
+/// Part of an assembled context
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextSection {
+    /// What the section holds
+    pub kind: SectionKind,
+    /// Heading (symbol path for symbols and deltas)
+    pub label: String,
+    /// Content
+    pub text: String,
+    /// Rank; lower is more relevant
+    pub relevance: usize,
+    /// Estimated tokens of `text`
+    pub tokens: usize,
+    /// Whether `text` was cut to fit the budget
+    pub truncated: bool,
+}
+
+impl ContextSection {
+    fn new(kind: SectionKind, label: impl Into<String>, text: String, relevance: usize) -> Self {
+        Self {
+            kind,
+            label: label.into(),
+            tokens: estimate_tokens(&text),
+            text,
+            relevance,
+            truncated: false,
+        }
+    }
+
+    /// Cut to at most `tokens`, or `None` if nothing useful fits
+    fn truncate(mut self, tokens: usize) -> Option<Self> {
+        if self.tokens <= tokens {
+            return Some(self);
+        }
+        let keep = (tokens * CHARS_PER_TOKEN).checked_sub(TRUNCATION_MARKER.len())?;
+        if keep == 0 {
+            return None;
+        }
+        self.text = self.text.chars().take(keep).collect();
+        self.text.push_str(TRUNCATION_MARKER);
+        self.tokens = estimate_tokens(&self.text);
+        self.truncated = true;
+        Some(self)
+    }
+}
+
+/// Delta already applied, shown to agents working nearby
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PriorDelta {
+    /// Symbol the delta changed
+    pub target: SymbolPath,
+    /// What it did
+    pub description: String,
+}
+
+impl PriorDelta {
+    /// Create prior delta
+    #[inline]
+    #[must_use]
+    pub fn new(target: SymbolPath, description: impl Into<String>) -> Self {
+        Self {
+            target,
+            description: description.into(),
+        }
+    }
+}
+
+impl<T: ArtifactType> From<&StructuralDelta<T>> for PriorDelta {
+    fn from(delta: &StructuralDelta<T>) -> Self {
+        Self::new(delta.target().clone(), delta.description())
+    }
+}
+
+/// Context assembled for a task
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssembledContext {
+    /// Hash of the inputs; equal keys mean equal contexts
+    pub key: ContentHash,
+    /// Sections kept, most relevant first
+    pub sections: Vec<ContextSection>,
+    /// Labels of sections dropped to fit the budget
+    pub omitted: Vec<String>,
+    /// Estimated tokens of all sections
+    pub tokens: usize,
+    /// Budget the context was fitted to
+    pub budget: usize,
+}
+
+impl AssembledContext {
+    /// Check if anything was cut or dropped
+    #[must_use]
+    pub fn is_truncated(&self) -> bool {
+        !self.omitted.is_empty() || self.sections.iter().any(|s| s.truncated)
+    }
+
+    /// Sections as prompt text under `## <label>` headings
+    #[must_use]
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for section in &self.sections {
+            let _ = write!(out, "## {}\n{}\n\n", section.label, section.text.trim_end());
+        }
+        out
+    }
+}
+
+/// Builds token-budgeted prompt contexts for tasks
+pub struct ContextAssembler {
+    index: Arc<SymbolRefIndex>,
+    source: Arc<dyn ContextSource>,
+    budget: usize,
+    strategy: TruncationStrategy,
+    radius: usize,
+    cache: Mutex<HashMap<ContentHash, AssembledContext>>,
+    cache_hits: AtomicUsize,
+}
+
+impl fmt::Debug for ContextAssembler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ContextAssembler")
+            .field("budget", &self.budget)
+            .field("strategy", &self.strategy)
+            .field("radius", &self.radius)
+            .field("cached", &self.cached())
+            .finish_non_exhaustive()
+    }
+}
+
+impl ContextAssembler {
+    /// Create assembler reading symbols from `index` and their text from
+    /// `source`
+    #[must_use]
+    pub fn new(index: Arc<SymbolRefIndex>, source: impl ContextSource + 'static) -> Self {
+        Self {
+            index,
+            source: Arc::new(source),
+            budget: DEFAULT_CONTEXT_BUDGET,
+            strategy: TruncationStrategy::default(),
+            radius: DEFAULT_NEIGHBORHOOD_RADIUS,
+            cache: Mutex::new(HashMap::new()),
+            cache_hits: AtomicUsize::new(0),
+        }
+    }
+
+    /// With token budget (e.g. the agent's advertised maximum context)
+    #[inline]
+    #[must_use]
+    pub fn with_budget(mut self, tokens: usize) -> Self {
+        self.budget = tokens;
+        self
+    }
+
+    /// With truncation strategy
+    #[inline]
+    #[must_use]
+    pub fn with_strategy(mut self, strategy: TruncationStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// With neighborhood radius (tree steps from the task's paths)
+    #[inline]
+    #[must_use]
+    pub fn with_radius(mut self, radius: usize) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Assemble the context for `task`
+    ///
+    /// The task and `criteria` are always kept, cut only if they alone
+    /// exceed the budget. Symbols near the task's target and claims, and
+    /// `deltas`, fill the rest by relevance. Symbols the source has no text
+    /// for are left out.
+    #[must_use]
+    pub fn assemble(
+        &self,
+        task: &Task,
+        criteria: &[String],
+        deltas: &[PriorDelta],
+    ) -> AssembledContext {
+        let paths: Vec<&SymbolPath> = std::iter::once(&task.target_artifact)
+            .chain(&task.claims)
+            .collect();
+        let neighbors = self.neighbors(&paths);
+        let key = self.cache_key(task, criteria, deltas, &neighbors);
+
+        if let Some(hit) = self.lock_cache().get(&key) {
+            self.cache_hits.fetch_add(1, Ordering::Relaxed);
+            return hit.clone();
+        }
+
+        let mut pinned = vec![ContextSection::new(
+            SectionKind::Task,
+            "Task",
+            format!(
+                "{}\nrole: {}\ntarget: {}",
+                task.description, task.role, task.target_artifact
+            ),
+            0,
+        )];
+        if !criteria.is_empty() {
+            let list = criteria.iter().map(|c| format!("- {c}\n")).collect();
+            pinned.push(ContextSection::new(
+                SectionKind::Criteria,
+                "Acceptance criteria",
+                list,
+                0,
+            ));
+        }
+
+        let mut ranked: Vec<ContextSection> = neighbors
+            .iter()
+            .filter_map(|(entry, distance)| {
+                let text = self.source.text(&entry.symbol)?;
+                Some(ContextSection::new(
+                    SectionKind::Symbol,
+                    entry.symbol.path().join("."),
+                    text,
+                    distance + 1,
+                ))
+            })
+            .collect();
+        ranked.extend(deltas.iter().map(|delta| {
+            let nearby = paths.iter().any(|path| path.overlaps(&delta.target));
+            ContextSection::new(
+                SectionKind::Delta,
+                format!("Applied delta: {}", delta.target),
+                delta.description.clone(),
+                if nearby { 1 } else { self.radius + 2 },
+            )
+        }));
+        // Stable: symbols stay nearest first, then by path
+        ranked.sort_by_key(|section| section.relevance);
+
+        let (mut sections, mut omitted) = fit(pinned, self.budget, TruncationStrategy::TrimTail);
+        let used = sections.iter().map(|s| s.tokens).sum::<usize>();
+        let (rest, dropped) = fit(ranked, self.budget.saturating_sub(used), self.strategy);
+        sections.extend(rest);
+        omitted.extend(dropped);
+
+        let context = AssembledContext {
+            key,
+            tokens: sections.iter().map(|s| s.tokens).sum(),
+            sections,
+            omitted,
+            budget: self.budget,
+        };
+        self.lock_cache().insert(key, context.clone());
+        context
+    }
+
+    /// Contexts served from the cache so far
+    #[must_use]
+    pub fn cache_hits(&self) -> usize {
+        self.cache_hits.load(Ordering::Relaxed)
+    }
+
+    /// Contexts currently cached
+    #[must_use]
+    pub fn cached(&self) -> usize {
+        self.lock_cache().len()
+    }
+
+    /// Drop every cached context
+    pub fn clear_cache(&self) {
+        self.lock_cache().clear();
+    }
+
+    fn lock_cache(&self) -> std::sync::MutexGuard<'_, HashMap<ContentHash, AssembledContext>> {
+        self.cache
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Symbols near any of `paths`, each at its smallest distance
+    fn neighbors(&self, paths: &[&SymbolPath]) -> Vec<(IndexEntry, usize)> {
+        let mut nearest: Vec<(IndexEntry, usize)> = Vec::new();
+        for path in paths {
+            for near in self.index.neighborhood(path.segments(), self.radius) {
+                match nearest
+                    .iter_mut()
+                    .find(|(entry, _)| entry.symbol.path() == near.entry.symbol.path())
+                {
+                    Some((_, distance)) => *distance = (*distance).min(near.distance),
+                    None => nearest.push((near.entry, near.distance)),
+                }
+            }
+        }
+        nearest.sort_by(|(a, da), (b, db)| (da, a.symbol.path()).cmp(&(db, b.symbol.path())));
+        nearest
+    }
+
+    fn cache_key(
+        &self,
+        task: &Task,
+        criteria: &[String],
+        deltas: &[PriorDelta],
+        neighbors: &[(IndexEntry, usize)],
+    ) -> ContentHash {
+        let mut key = format!(
+            "{}|{:?}|{}\n{}\n{}\n{}\n",
+            self.budget,
+            self.strategy,
+            self.radius,
+            task.role,
+            task.description,
+            task.target_artifact
+        );
+        for claim in &task.claims {
+            let _ = writeln!(key, "claim {claim}");
+        }
+        for criterion in criteria {
+            let _ = writeln!(key, "criterion {criterion}");
+        }
+        for delta in deltas {
+            let _ = writeln!(key, "delta {} {}", delta.target, delta.description);
+        }
+        for (entry, distance) in neighbors {
+            let _ = writeln!(
+                key,
+                "symbol {} {distance} {} {:?}",
+                entry.symbol.path().join("."),
+                entry.symbol.parent_hash(),
+                entry.metadata.content_hash
+            );
+        }
+        ContentHash::compute(key.as_bytes())
+    }
+}
+
+/// Fit `sections` (most relevant first) into `budget` tokens
+///
+/// Returns the sections kept, in their original order, and the labels of
+/// those dropped.
+fn fit(
+    sections: Vec<ContextSection>,
+    budget: usize,
+    strategy: TruncationStrategy,
+) -> (Vec<ContextSection>, Vec<String>) {
+    let mut kept = Vec::new();
+    let mut omitted = Vec::new();
+    let mut remaining = budget;
+
+    match strategy {
+        TruncationStrategy::DropLeastRelevant => {
+            for section in sections {
+                if section.tokens <= remaining {
+                    remaining -= section.tokens;
+                    kept.push(section);
+                } else {
+                    omitted.push(section.label);
+                }
+            }
+        }
+        TruncationStrategy::TrimTail => {
+            for section in sections {
+                let label = section.label.clone();
+                match section.truncate(remaining) {
+                    Some(section) => {
+                        remaining -= section.tokens;
+                        kept.push(section);
+                    }
+                    None => omitted.push(label),
+                }
+            }
+        }
+        TruncationStrategy::Proportional => {
+            // Smallest first, so sections under their share leave the
+            // surplus to larger ones
+            let mut order: Vec<usize> = (0..sections.len()).collect();
+            order.sort_by_key(|&i| sections[i].tokens);
+            let mut shares = vec![0; sections.len()];
+            for (placed, &i) in order.iter().enumerate() {
+                let share = remaining / (sections.len() - placed);
+                shares[i] = sections[i].tokens.min(share);
+                remaining -= shares[i];
+            }
+            for (section, share) in sections.into_iter().zip(shares) {
+                let label = section.label.clone();
+                match section.truncate(share) {
+                    Some(section) => kept.push(section),
+                    None => omitted.push(label),
+                }
+            }
+        }
+    }
+    (kept, omitted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coa_symbol::SymbolMetadata;
+
+    fn index(paths: &[&str]) -> Arc<SymbolRefIndex> {
+        let index = SymbolRefIndex::new();
+        for path in paths {
+            let symbol = SymbolRef::new(
+                path.split('.').map(str::to_string).collect(),
+                ContentHash::compute(b"lib"),
+            );
+            index.insert(symbol, SymbolMetadata::default()).unwrap();
+        }
+        Arc::new(index)
+    }
+
+    fn source(symbol: &SymbolRef) -> Option<String> {
+        let path = symbol.path().join(".");
+        (path != "auth.secret").then(|| format!("fn {path}() {{ {} }}", "x".repeat(100)))
+    }
+
+    #[test]
+    fn assembles_nearest_symbols_within_budget() {
+        let index = index(&[
+            "auth.login",
+            "auth.logout",
+            "auth.secret",
+            "billing.invoice",
+        ]);
+        let assembler = ContextAssembler::new(index, source).with_budget(70);
+        let task = Task::new("coder", "Add rate limiting", "auth.login".parse().unwrap());
+        let criteria = vec!["tests pass".to_string()];
+        let deltas = [
+            PriorDelta::new("auth.login".parse().unwrap(), "Added audit logging"),
+            PriorDelta::new("billing.invoice".parse().unwrap(), "Renamed totals"),
+        ];
+
+        let context = assembler.assemble(&task, &criteria, &deltas);
+        let labels: Vec<_> = context.sections.iter().map(|s| s.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            [
+                "Task",
+                "Acceptance criteria",
+                "auth.login",
+                "Applied delta: auth.login",
+                "Applied delta: billing.invoice",
+            ]
+        );
+        // The sibling no longer fits; the unreadable one is skipped
+        assert_eq!(context.omitted, ["auth.logout"]);
+        assert!(context.tokens <= 70);
+        assert!(context.render().starts_with("## Task\nAdd rate limiting\n"));
+
+        assert_eq!(assembler.assemble(&task, &criteria, &deltas), context);
+        assert_eq!((assembler.cache_hits(), assembler.cached()), (1, 1));
+        let other = Task::new("coder", "Add rate limiting", "auth.logout".parse().unwrap());
+        assert_ne!(
+            assembler.assemble(&other, &criteria, &deltas).key,
+            context.key
+        );
+    }
+
+    #[test]
+    fn strategies_fit_the_budget_differently() {
+        let long = |label: &str, chars: usize, relevance| {
+            ContextSection::new(SectionKind::Symbol, label, "y".repeat(chars), relevance)
+        };
+        let sections = vec![long("a", 400, 1), long("b", 40, 2), long("c", 400, 3)];
+
+        let (kept, omitted) = fit(sections.clone(), 120, TruncationStrategy::DropLeastRelevant);
+        assert_eq!(kept.iter().map(|s| s.tokens).collect::<Vec<_>>(), [100, 10]);
+        assert_eq!(omitted, ["c"]);
+
+        let (kept, omitted) = fit(sections.clone(), 120, TruncationStrategy::TrimTail);
+        assert_eq!(
+            kept.iter().map(|s| s.tokens).collect::<Vec<_>>(),
+            [100, 10, 10]
+        );
+        assert!(kept[2].truncated && kept[2].text.ends_with(TRUNCATION_MARKER));
+        assert!(omitted.is_empty());
+
+        let (kept, _) = fit(sections, 120, TruncationStrategy::Proportional);
+        assert_eq!(
+            kept.iter().map(|s| s.tokens).collect::<Vec<_>>(),
+            [55, 10, 55]
+        );
+    }
+}
@@ -0,0 +1,478 @@
+//! Best-of-N candidate selection
+//!
+//! A task can be run by several [`Contender`]s at once: the same task under
+//! different agent roles or directive overrides. Each contender works in its
+//! own [`Sandbox`]: a kernel `SandboxGraph` holding its task variant, with a
+//! signed capability token, plus private snapshots of the base artifact and
+//! symbol index. Contenders hand back candidate delta batches without
+//! touching the shared namespace. [`BestOfN`] scores the candidates (tests
+//! passing, lint, diff size) and only the winner goes on to composition.
+//! The task's [`MAX_DEBATE_ITERATIONS_DIRECTIVE`] caps how many contenders
+//! run.
+
+use crate::error::{CandidateError, DebateError};
+use crate::types::{kernel_node_spec, DirectiveSet, DirectiveValue, Task, TaskId};
+use coa_artifact::{Artifact, ArtifactType, StructuralDelta};
+use coa_kernel::autonomy::CapabilityToken;
+use coa_kernel::prelude::{GraphBuilder, GraphType, NodeId, RunBudget, ValidatedGraph};
+use coa_symbol::SymbolRefIndex;
+use ed25519_dalek::SigningKey;
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::sync::Arc;
+
+/// Directive capping the number of contenders run for a task
+pub const MAX_DEBATE_ITERATIONS_DIRECTIVE: &str = "max_debate_iterations";
+
+/// One way of running a task: an agent role and directive overrides
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contender {
+    /// Name reported in scores
+    pub label: String,
+    /// Role replacing the task's role
+    pub role: Option<String>,
+    /// Directives overriding the task's
+    pub directives: DirectiveSet,
+}
+
+impl Contender {
+    /// Create contender running the task unchanged
+    #[must_use]
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            role: None,
+            directives: DirectiveSet::new(),
+        }
+    }
+
+    /// With agent role
+    #[inline]
+    #[must_use]
+    pub fn with_role(mut self, role: impl Into<String>) -> Self {
+        self.role = Some(role.into());
+        self
+    }
+
+    /// With directive override
+    #[inline]
+    #[must_use]
+    pub fn with_directive(mut self, key: impl Into<String>, value: DirectiveValue) -> Self {
+        self.directives.insert(key.into(), value);
+        self
+    }
+
+    /// Copy of `task` as this contender runs it, under a fresh ID
+    #[must_use]
+    pub fn instantiate(&self, task: &Task) -> Task {
+        let mut variant = task.clone();
+        variant.id = TaskId::new();
+        if let Some(role) = &self.role {
+            variant.role.clone_from(role);
+        }
+        variant
+            .directives
+            .extend(self.directives.iter().map(|(k, v)| (k.clone(), v.clone())));
+        variant
+    }
+}
+
+/// Isolated workspace one contender runs in
+#[derive(Debug, Clone)]
+pub struct Sandbox<T: ArtifactType> {
+    /// Task as the contender runs it
+    pub task: Task,
+    /// Kernel sandbox graph holding `task` as its only node
+    pub graph: ValidatedGraph,
+    /// Node of `task` in `graph`
+    pub node: NodeId,
+    /// Snapshot of the base artifact
+    pub base: Artifact<T>,
+    /// Private copy of the shared index's symbols
+    pub index: Arc<SymbolRefIndex>,
+}
+
+impl<T: ArtifactType> Sandbox<T> {
+    /// Capability token the kernel signed for the contender
+    #[must_use]
+    pub fn token(&self) -> Option<&CapabilityToken> {
+        self.graph.get_node_token(self.node)
+    }
+}
+
+/// Delta batch a contender proposed
+#[derive(Debug, Clone)]
+pub struct Candidate<T: ArtifactType> {
+    /// Contender that produced it
+    pub contender: String,
+    /// Sandbox it was produced in
+    pub sandbox: Sandbox<T>,
+    /// Proposed deltas
+    pub deltas: Vec<StructuralDelta<T>>,
+}
+
+impl<T: ArtifactType> Candidate<T> {
+    /// Task as the contender ran it
+    #[inline]
+    #[must_use]
+    pub fn task(&self) -> &Task {
+        &self.sandbox.task
+    }
+
+    /// Approximate size of the proposed change
+    #[must_use]
+    pub fn diff_size(&self) -> usize {
+        self.deltas.iter().map(StructuralDelta::estimated_size).sum()
+    }
+}
+
+/// How well a candidate did
+///
+/// Scores order by passing tests, then clean lint, then smaller diff: the
+/// greatest score wins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CandidateScore {
+    /// Tests pass with the candidate applied
+    pub tests_passed: bool,
+    /// Linter reports nothing with the candidate applied
+    pub lint_clean: bool,
+    /// Approximate size of the change
+    pub diff_size: usize,
+}
+
+impl Ord for CandidateScore {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.tests_passed, self.lint_clean, Reverse(self.diff_size)).cmp(&(
+            other.tests_passed,
+            other.lint_clean,
+            Reverse(other.diff_size),
+        ))
+    }
+}
+
+impl PartialOrd for CandidateScore {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Runs a task variant in a sandbox and returns its proposed deltas
+#[async_trait::async_trait]
+pub trait CandidateGenerator<T: ArtifactType>: Send + Sync + std::fmt::Debug {
+    /// Propose deltas for `sandbox.task`, working only in `sandbox`
+    async fn propose(&self, sandbox: &Sandbox<T>) -> Result<Vec<StructuralDelta<T>>, CandidateError>;
+}
+
+/// Scores a candidate, typically by testing and linting it in its sandbox
+#[async_trait::async_trait]
+pub trait CandidateScorer<T: ArtifactType>: Send + Sync + std::fmt::Debug {
+    /// Score `candidate`
+    async fn score(&self, candidate: &Candidate<T>) -> Result<CandidateScore, CandidateError>;
+}
+
+/// Result of a best-of-N run
+#[derive(Debug)]
+pub struct DebateOutcome<T: ArtifactType> {
+    /// Candidate to merge
+    pub winner: Candidate<T>,
+    /// Score of every candidate, by contender
+    pub scores: Vec<(String, CandidateScore)>,
+    /// Contenders that failed to propose or be scored, with the error
+    pub failures: Vec<(String, CandidateError)>,
+}
+
+/// Best-of-N runner
+#[derive(Debug)]
+pub struct BestOfN<T: ArtifactType> {
+    contenders: Vec<Contender>,
+    generator: Arc<dyn CandidateGenerator<T>>,
+    scorer: Arc<dyn CandidateScorer<T>>,
+    signing_key: SigningKey,
+    budget: Option<Arc<RunBudget>>,
+}
+
+impl<T: ArtifactType> BestOfN<T> {
+    /// Create runner with no contenders
+    #[must_use]
+    pub fn new(
+        generator: Arc<dyn CandidateGenerator<T>>,
+        scorer: Arc<dyn CandidateScorer<T>>,
+    ) -> Self {
+        Self {
+            contenders: Vec::new(),
+            generator,
+            scorer,
+            signing_key: SigningKey::from_bytes(&rand::random()),
+            budget: None,
+        }
+    }
+
+    /// With the session budget sandbox graphs are drawn from
+    #[inline]
+    #[must_use]
+    pub fn with_budget(mut self, budget: Arc<RunBudget>) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// With the key sandbox graphs are signed with
+    #[inline]
+    #[must_use]
+    pub fn with_signing_key(mut self, key: SigningKey) -> Self {
+        self.signing_key = key;
+        self
+    }
+
+    /// With contender
+    #[inline]
+    #[must_use]
+    pub fn with_contender(mut self, contender: Contender) -> Self {
+        self.contenders.push(contender);
+        self
+    }
+
+    /// Contenders that run for `task`
+    ///
+    /// A positive [`MAX_DEBATE_ITERATIONS_DIRECTIVE`] keeps only that many,
+    /// in registration order; otherwise all run.
+    #[must_use]
+    pub fn contenders_for(&self, task: &Task) -> &[Contender] {
+        let limit = match task.directives.get(MAX_DEBATE_ITERATIONS_DIRECTIVE) {
+            Some(DirectiveValue::Int(n)) if *n > 0 => usize::try_from(*n).unwrap_or(usize::MAX),
+            _ => self.contenders.len(),
+        };
+        &self.contenders[..limit.min(self.contenders.len())]
+    }
+
+    /// Run the contenders for `task` in parallel and select the winner
+    ///
+    /// Each contender runs in its own [`Sandbox`] over snapshots of `base`
+    /// and `index`. Ties go to the earlier contender.
+    ///
+    /// # Errors
+    /// - `DebateError::NoContenders` if none are registered
+    /// - `DebateError::AllFailed` if no contender produced a scored candidate
+    pub async fn run(
+        &self,
+        task: &Task,
+        base: &Artifact<T>,
+        index: &SymbolRefIndex,
+    ) -> Result<DebateOutcome<T>, DebateError> {
+        let contenders = self.contenders_for(task);
+        if contenders.is_empty() {
+            return Err(DebateError::NoContenders);
+        }
+
+        let proposals = futures::future::join_all(contenders.iter().map(|contender| async move {
+            let candidate = match self.sandbox(contender.instantiate(task), base, index) {
+                Ok(sandbox) => self.generator.propose(&sandbox).await.map(|deltas| Candidate {
+                    contender: contender.label.clone(),
+                    sandbox,
+                    deltas,
+                }),
+                Err(e) => Err(e),
+            };
+            (contender.label.clone(), candidate)
+        }))
+        .await;
+
+        let mut candidates = Vec::new();
+        let mut failures = Vec::new();
+        for (label, candidate) in proposals {
+            match candidate {
+                Ok(candidate) => candidates.push(candidate),
+                Err(e) => failures.push((label, e)),
+            }
+        }
+
+        let scores =
+            futures::future::join_all(candidates.iter().map(|c| self.scorer.score(c))).await;
+        let mut scored = Vec::new();
+        for (candidate, score) in candidates.into_iter().zip(scores) {
+            match score {
+                Ok(score) => scored.push((candidate, score)),
+                Err(e) => failures.push((candidate.contender, e)),
+            }
+        }
+        if scored.is_empty() {
+            return Err(DebateError::AllFailed(failures));
+        }
+
+        let mut best = 0;
+        for (i, (_, score)) in scored.iter().enumerate() {
+            if *score > scored[best].1 {
+                best = i;
+            }
+        }
+        let scores = scored
+            .iter()
+            .map(|(c, score)| (c.contender.clone(), *score))
+            .collect();
+
+        Ok(DebateOutcome {
+            winner: scored.swap_remove(best).0,
+            scores,
+            failures,
+        })
+    }
+
+    /// Sandbox for `task`: a kernel sandbox graph and private snapshots
+    fn sandbox(
+        &self,
+        task: Task,
+        base: &Artifact<T>,
+        index: &SymbolRefIndex,
+    ) -> Result<Sandbox<T>, CandidateError> {
+        let mut builder = GraphBuilder::new(GraphType::SandboxGraph);
+        if let Some(budget) = &self.budget {
+            builder = builder.with_budget(Arc::clone(budget));
+        }
+        let node = builder.add_node(kernel_node_spec(&task));
+        Ok(Sandbox {
+            task,
+            graph: builder.validate(&self.signing_key)?,
+            node,
+            base: base.clone(),
+            index: Arc::new(SymbolRefIndex::from_entries(index.entries())?),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coa_artifact::{ContentHash, DeltaOperation, SymbolPath};
+    use coa_constitutional::parsers::{ArtifactParser, CodeArtifact, CodeParser, Language};
+    use crate::types::AutonomyLevel;
+    use coa_symbol::{SymbolMetadata, SymbolRef};
+    use std::str::FromStr;
+
+    /// Proposes one removal per `size` directive, recording its label in
+    /// the sandbox index; fails for the "broken" role
+    #[derive(Debug)]
+    struct SizedGenerator;
+
+    #[async_trait::async_trait]
+    impl CandidateGenerator<CodeArtifact> for SizedGenerator {
+        async fn propose(
+            &self,
+            sandbox: &Sandbox<CodeArtifact>,
+        ) -> Result<Vec<StructuralDelta<CodeArtifact>>, CandidateError> {
+            let task = &sandbox.task;
+            if task.role == "broken" {
+                return Err(CandidateError::Proposal("agent crashed".to_string()));
+            }
+            let scratch = SymbolRef::new(vec!["scratch".to_string()], *sandbox.base.hash());
+            sandbox
+                .index
+                .insert(scratch, SymbolMetadata::default())
+                .map_err(|e| CandidateError::Proposal(e.to_string()))?;
+            let size = match task.directives.get("size") {
+                Some(DirectiveValue::Int(n)) => *n as usize,
+                _ => 1,
+            };
+            let target = SymbolPath::from_str("src.parser").unwrap();
+            Ok((0..size)
+                .map(|_| StructuralDelta::new(target.clone(), DeltaOperation::Remove, ContentHash::ZERO))
+                .collect())
+        }
+    }
+
+    /// Tests fail for the "sloppy" role and cannot run for "untestable"
+    #[derive(Debug)]
+    struct RoleScorer;
+
+    #[async_trait::async_trait]
+    impl CandidateScorer<CodeArtifact> for RoleScorer {
+        async fn score(
+            &self,
+            candidate: &Candidate<CodeArtifact>,
+        ) -> Result<CandidateScore, CandidateError> {
+            if candidate.task().role == "untestable" {
+                return Err(CandidateError::Scoring("no test runner".to_string()));
+            }
+            Ok(CandidateScore {
+                tests_passed: candidate.task().role != "sloppy",
+                lint_clean: true,
+                diff_size: candidate.diff_size(),
+            })
+        }
+    }
+
+    fn runner() -> BestOfN<CodeArtifact> {
+        BestOfN::new(Arc::new(SizedGenerator), Arc::new(RoleScorer))
+            .with_contender(Contender::new("sloppy").with_role("sloppy"))
+            .with_contender(Contender::new("large").with_directive("size", DirectiveValue::Int(3)))
+            .with_contender(Contender::new("small").with_directive("size", DirectiveValue::Int(2)))
+            .with_contender(Contender::new("broken").with_role("broken"))
+            .with_contender(Contender::new("untestable").with_role("untestable"))
+    }
+
+    fn base() -> Artifact<CodeArtifact> {
+        CodeParser::new(Language::Rust).parse("fn parser() {}").unwrap()
+    }
+
+    #[tokio::test]
+    async fn best_of_n_selects_smallest_passing_candidate() {
+        let task = Task::new("implementer", "Implement parser", SymbolPath::from_str("src").unwrap());
+        let outcome = runner().run(&task, &base(), &SymbolRefIndex::new()).await.unwrap();
+
+        assert_eq!(outcome.winner.contender, "small");
+        assert_eq!(outcome.winner.deltas.len(), 2);
+        assert_ne!(outcome.winner.task().id, task.id);
+        assert_eq!(outcome.scores.len(), 3);
+        let failures: Vec<_> = outcome
+            .failures
+            .iter()
+            .map(|(label, e)| (label.as_str(), e.to_string()))
+            .collect();
+        assert_eq!(
+            failures,
+            [
+                ("broken", "proposal failed: agent crashed".to_string()),
+                ("untestable", "scoring failed: no test runner".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn contenders_run_in_isolated_sandboxes() {
+        let key = SigningKey::from_bytes(&[9u8; 32]);
+        let shared = SymbolRefIndex::new();
+        let task = Task::new("implementer", "Implement parser", SymbolPath::from_str("src").unwrap())
+            .with_autonomy(AutonomyLevel::L2);
+        let outcome = runner()
+            .with_signing_key(key.clone())
+            .run(&task, &base(), &shared)
+            .await
+            .unwrap();
+
+        // Each contender wrote to its own index copy, none to the shared one
+        assert!(shared.is_empty());
+        assert_eq!(outcome.winner.sandbox.index.len(), 1);
+        assert_eq!(outcome.winner.sandbox.base.hash(), base().hash());
+
+        let sandbox = &outcome.winner.sandbox;
+        assert_eq!(sandbox.graph.graph_type(), GraphType::SandboxGraph);
+        let token = sandbox.token().unwrap();
+        assert!(token.verify(&key.verifying_key()));
+        assert_eq!(token.autonomy_level, AutonomyLevel::L2);
+    }
+
+    #[tokio::test]
+    async fn debate_directive_limits_contenders() {
+        let task = Task::new("implementer", "Implement parser", SymbolPath::from_str("src").unwrap())
+            .with_directive(MAX_DEBATE_ITERATIONS_DIRECTIVE, DirectiveValue::Int(2));
+        let index = SymbolRefIndex::new();
+        let outcome = runner().run(&task, &base(), &index).await.unwrap();
+        assert_eq!(outcome.winner.contender, "large");
+        assert_eq!(outcome.scores.len(), 2);
+
+        let broken = BestOfN::new(Arc::new(SizedGenerator), Arc::new(RoleScorer))
+            .with_contender(Contender::new("broken").with_role("broken"));
+        assert!(matches!(
+            broken.run(&task, &base(), &index).await,
+            Err(DebateError::AllFailed(failures)) if failures.len() == 1
+        ));
+    }
+}
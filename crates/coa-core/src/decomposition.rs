@@ -1,22 +1,82 @@
 //! Task decomposition
 //!
 //! Decomposes high-level specifications into executable tasks.
-//! Supports multiple goal types and recursive decomposition.
+//! Supports multiple goal types and recursive decomposition; specifications
+//! matching a registered [`DecompositionTemplate`] expand via the template.
+//!
+//! Tasks whose complexity exceeds the decomposer's threshold are not
+//! decomposed up front: they become recursive expansion points
+//! ([`ExpansionType::Recursive`]) that [`TaskDecomposer::expand`] turns into
+//! subtasks at runtime, splitting the parent's complexity and budget among
+//! them until the maximum depth is reached.
+//!
+//! Each expansion is admitted by the kernel: the parent becomes a kernel
+//! expansion node, the subtasks are provided through [`StagedConstruction`]
+//! (which checks their budget and autonomy against the parent), and the
+//! subtasks are then validated and signed as their own [`ValidatedGraph`].
 
 use crate::error::{DecompositionError, Goal};
+use crate::templates::{DecompositionTemplate, TemplateRegistry};
 use crate::types::{
-    AutonomyLevel, DirectiveSet, DirectiveValue, ExpansionType,
-    Specification, Task,
+    kernel_caps, kernel_node_spec, AutonomyLevel, DirectiveSet, DirectiveValue, ExpansionType,
+    ResourceCaps, Specification, Task,
 };
+use crate::verification::VerificationGate;
 use coa_composition::StrategySelector;
+use coa_kernel::prelude::{
+    ExpansionBuilder, ExpansionSchema, GraphBuilder, GraphType, NodeId, RunBudget,
+    StagedConstruction, SubgraphSpec, ValidatedGraph, ValidationError,
+};
 use coa_symbol::SymbolRefIndex;
+use ed25519_dalek::SigningKey;
 use std::str::FromStr;
+use std::sync::Arc;
+
+/// Directive holding a task's estimated complexity (an integer, default 1)
+pub const COMPLEXITY_DIRECTIVE: &str = "complexity";
+
+/// Directive naming the template an expansion point decomposes with
+pub const TEMPLATE_DIRECTIVE: &str = "template";
+
+/// Complexity above which tasks become expansion points by default
+pub const DEFAULT_COMPLEXITY_THRESHOLD: i64 = 8;
+
+/// Kernel expansion schema of recursive task expansion points
+///
+/// Accepts any non-empty subgraph; budget and autonomy are checked by
+/// [`StagedConstruction`].
+#[derive(Debug)]
+pub struct TaskExpansion;
+
+impl ExpansionSchema for TaskExpansion {
+    fn validate_subgraph(subgraph: &SubgraphSpec<Self>) -> Result<(), ValidationError> {
+        if subgraph.nodes.is_empty() {
+            return Err(ValidationError::InvalidGraphStructure);
+        }
+        Ok(())
+    }
+}
+
+/// Subtasks of an expansion point, as admitted by the kernel
+#[derive(Debug, Clone)]
+pub struct Expansion {
+    /// Subtasks to run in place of the expansion point
+    pub tasks: Vec<Task>,
+    /// Subtasks as a validated graph carrying signed capability tokens
+    pub graph: ValidatedGraph,
+    /// Graph node of each subtask, in `tasks` order
+    pub nodes: Vec<NodeId>,
+}
 
 /// Task decomposer for breaking down specifications
 #[derive(Debug)]
 pub struct TaskDecomposer {
     strategy_selector: StrategySelector,
+    templates: TemplateRegistry,
     max_depth: usize,
+    complexity_threshold: i64,
+    signing_key: SigningKey,
+    budget: Option<Arc<RunBudget>>,
 }
 
 impl TaskDecomposer {
@@ -26,10 +86,30 @@ impl TaskDecomposer {
     pub fn new(strategy_selector: StrategySelector) -> Self {
         Self {
             strategy_selector,
+            templates: TemplateRegistry::builtin(),
             max_depth: 5,
+            complexity_threshold: DEFAULT_COMPLEXITY_THRESHOLD,
+            signing_key: SigningKey::from_bytes(&rand::random()),
+            budget: None,
         }
     }
 
+    /// With the session budget expansion graphs are drawn from
+    #[inline]
+    #[must_use]
+    pub fn with_budget(mut self, budget: Arc<RunBudget>) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// With the key expansion graphs are signed with
+    #[inline]
+    #[must_use]
+    pub fn with_signing_key(mut self, key: SigningKey) -> Self {
+        self.signing_key = key;
+        self
+    }
+
     /// With max decomposition depth
     #[inline]
     #[must_use]
@@ -38,6 +118,37 @@ impl TaskDecomposer {
         self
     }
 
+    /// With complexity threshold above which tasks become expansion points
+    #[inline]
+    #[must_use]
+    pub fn with_complexity_threshold(mut self, threshold: i64) -> Self {
+        self.complexity_threshold = threshold;
+        self
+    }
+
+    /// With template registry (replacing the built-in templates)
+    #[inline]
+    #[must_use]
+    pub fn with_templates(mut self, templates: TemplateRegistry) -> Self {
+        self.templates = templates;
+        self
+    }
+
+    /// With `template` registered alongside the current ones
+    #[inline]
+    #[must_use]
+    pub fn with_template(mut self, template: std::sync::Arc<dyn DecompositionTemplate>) -> Self {
+        self.templates.register(template);
+        self
+    }
+
+    /// Registered templates
+    #[inline]
+    #[must_use]
+    pub fn templates(&self) -> &TemplateRegistry {
+        &self.templates
+    }
+
     /// Decompose specification into tasks
     ///
     /// # Arguments
@@ -45,13 +156,139 @@ impl TaskDecomposer {
     /// * `index` - Symbol index for reference resolution
     ///
     /// # Returns
-    /// List of executable tasks
+    /// List of executable tasks, closed by a verification gate task when
+    /// the specification has acceptance criteria
     pub async fn decompose(
         &self,
         spec: Specification,
         _index: &SymbolRefIndex,
     ) -> Result<Vec<Task>, DecompositionError> {
-        self.decompose_recursive(spec, 0).await
+        let gate = VerificationGate::from_spec(&spec);
+        let target = spec.target_path.clone();
+        let mut tasks = self.decompose_recursive(spec, 0).await?;
+        self.mark_expansion_points(&mut tasks, 0);
+        if let Some(gate) = gate {
+            gate.append_to(&mut tasks, target);
+        }
+        Ok(tasks)
+    }
+
+    /// Decompose a recursive expansion point into its subtasks
+    ///
+    /// The subtasks split the parent's complexity, memory and timeout
+    /// evenly, keep its CPU cap, never exceed its autonomy, and inherit its
+    /// dependencies where they have none of their own. Subtasks still above
+    /// the complexity threshold become expansion points one level deeper.
+    /// The kernel admits the subtasks before they are returned.
+    ///
+    /// # Errors
+    /// Returns `DecompositionError::RecursionDepthExceeded` if the task has
+    /// no expansion depth left, `InvalidSpecification` if it is not a
+    /// recursive expansion point, or an `Expansion*` error if the kernel
+    /// rejects the subtasks
+    pub async fn expand(&self, task: &Task) -> Result<Expansion, DecompositionError> {
+        let Some(ExpansionType::Recursive { max_depth: remaining }) = task.expansion_type else {
+            return Err(DecompositionError::InvalidSpecification(format!(
+                "task {} is not a recursive expansion point",
+                task.id
+            )));
+        };
+        if remaining == 0 {
+            return Err(DecompositionError::RecursionDepthExceeded);
+        }
+        let depth = self.max_depth.saturating_sub(remaining) + 1;
+
+        let mut spec = Specification::new(goal_for_role(&task.role), "code", task.target_artifact.clone())
+            .with_criteria(vec![task.description.clone()]);
+        if let Some(DirectiveValue::String(name)) = task.directives.get(TEMPLATE_DIRECTIVE) {
+            spec = spec.with_template(name.clone());
+        }
+        let mut children = self.decompose_recursive(spec, depth).await?;
+
+        let count = children.len().max(1);
+        let share = (complexity(task) / count as i64).max(1);
+        for child in &mut children {
+            child
+                .directives
+                .entry(COMPLEXITY_DIRECTIVE.to_string())
+                .or_insert(DirectiveValue::Int(share));
+            child.autonomy = child.autonomy.min(task.autonomy);
+            child.resources = ResourceCaps {
+                memory_mb: (task.resources.memory_mb / count).max(1),
+                cpu_millicores: task.resources.cpu_millicores,
+                timeout_secs: (task.resources.timeout_secs / count as u64).max(1),
+            };
+            if child.dependencies.is_empty() {
+                child.dependencies = task.dependencies.clone();
+            }
+        }
+        self.mark_expansion_points(&mut children, depth);
+
+        let (graph, nodes) = self.admit(task, remaining, &children).await?;
+        Ok(Expansion {
+            tasks: children,
+            graph,
+            nodes,
+        })
+    }
+
+    /// Have the kernel admit `children` as the expansion of `task`
+    ///
+    /// `task` becomes an expansion node of a one-node graph, and the
+    /// children are provided through [`StagedConstruction`]. Once accepted,
+    /// the children and the dependencies among them are validated and
+    /// signed as their own graph.
+    async fn admit(
+        &self,
+        task: &Task,
+        remaining: usize,
+        children: &[Task],
+    ) -> Result<(ValidatedGraph, Vec<NodeId>), DecompositionError> {
+        let mut parent = GraphBuilder::new(GraphType::ProductionDAG);
+        parent.add_expansion_node::<TaskExpansion>(
+            kernel_node_spec(task),
+            kernel_caps(&task.resources),
+            u32::try_from(remaining).unwrap_or(u32::MAX),
+        );
+        let mut staged =
+            StagedConstruction::new(parent.validate(&self.signing_key)?, self.signing_key.clone());
+        staged
+            .execute_until_expansion()
+            .await?
+            .ok_or(ValidationError::InvalidGraphStructure)?;
+        staged.provide_expansion(SubgraphSpec::<TaskExpansion>::new(
+            children.iter().map(kernel_node_spec).collect(),
+            Vec::new(),
+        ))?;
+        staged.complete_expansion()?;
+
+        // Only the subtasks run, so only their graph draws on the budget
+        let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
+        if let Some(budget) = &self.budget {
+            builder = builder.with_budget(Arc::clone(budget));
+        }
+        let nodes: Vec<NodeId> = children.iter().map(|child| builder.add_node(kernel_node_spec(child))).collect();
+        for (child, &node) in children.iter().zip(&nodes) {
+            for dependency in &child.dependencies {
+                if let Some(i) = children.iter().position(|c| c.id == *dependency) {
+                    builder.add_edge(nodes[i], node)?;
+                }
+            }
+        }
+        Ok((builder.validate(&self.signing_key)?, nodes))
+    }
+
+    /// Turn tasks above the complexity threshold into expansion points
+    fn mark_expansion_points(&self, tasks: &mut [Task], depth: usize) {
+        let remaining = self.max_depth.saturating_sub(depth);
+        if remaining == 0 {
+            return;
+        }
+        for task in tasks {
+            if task.expansion_type.is_none() && complexity(task) > self.complexity_threshold {
+                task.expansion_type = Some(ExpansionType::Recursive { max_depth: remaining });
+            }
+        }
     }
 
     /// Recursive decomposition
@@ -64,6 +301,10 @@ impl TaskDecomposer {
             return Err(DecompositionError::RecursionDepthExceeded);
         }
 
+        if let Some(template) = self.templates.select(&spec)? {
+            return template.expand(&spec);
+        }
+
         match spec.goal {
             Goal::CreateNew => self.decompose_create(spec, depth).await,
             Goal::ModifyExisting => self.decompose_modify(spec, depth).await,
@@ -347,6 +588,25 @@ impl TaskDecomposer {
     }
 }
 
+/// Estimated complexity of `task`
+fn complexity(task: &Task) -> i64 {
+    match task.directives.get(COMPLEXITY_DIRECTIVE) {
+        Some(DirectiveValue::Int(value)) => *value,
+        _ => 1,
+    }
+}
+
+/// Goal to decompose an expansion point with, by the role that owns it
+fn goal_for_role(role: &str) -> Goal {
+    match role {
+        "analyzer" => Goal::Analyze,
+        "refactorer" => Goal::Refactor,
+        "optimizer" | "benchmarker" => Goal::Optimize,
+        "modifier" | "migrator" => Goal::ModifyExisting,
+        _ => Goal::CreateNew,
+    }
+}
+
 impl Default for TaskDecomposer {
     fn default() -> Self {
         Self::new(StrategySelector::new())
@@ -372,9 +632,12 @@ mod tests {
 
         let tasks = decomposer.decompose(spec, &index).await.unwrap();
 
-        // Should have design + implementations + tests
+        // Should have design + implementations + tests + criteria gate
         assert!(!tasks.is_empty());
         assert_eq!(tasks[0].role, "architect");
+        let gate = tasks.last().unwrap();
+        assert_eq!(gate.role, crate::verification::GATE_ROLE);
+        assert_eq!(gate.dependencies.len(), tasks.len() - 1);
     }
 
     #[tokio::test]
@@ -428,6 +691,131 @@ mod tests {
         let _result = decomposer.decompose(spec, &index).await;
     }
 
+    #[tokio::test]
+    async fn decompose_uses_matching_template() {
+        let decomposer = TaskDecomposer::default();
+        let index = SymbolRefIndex::new();
+
+        let spec = Specification::new(
+            Goal::CreateNew,
+            "code",
+            SymbolPath::from_str("cli.run.verbose").unwrap(),
+        )
+        .with_criteria(vec!["Add a verbose flag".to_string()]);
+
+        let tasks = decomposer.decompose(spec, &index).await.unwrap();
+
+        // declare + wire + document + test + criteria gate
+        assert_eq!(tasks.len(), 5);
+        assert_eq!(tasks[0].description, "Declare --verbose argument");
+
+        let plain = TaskDecomposer::default().with_templates(TemplateRegistry::new());
+        let spec = Specification::new(
+            Goal::ModifyExisting,
+            "code",
+            SymbolPath::from_str("cli.run.verbose").unwrap(),
+        )
+        .with_template("add_cli_flag");
+        assert!(matches!(
+            plain.decompose(spec, &index).await,
+            Err(DecompositionError::UnknownTemplate(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn complex_tasks_expand_recursively_within_depth_and_budget() {
+        let key = SigningKey::from_bytes(&[5u8; 32]);
+        let decomposer = TaskDecomposer::default()
+            .with_max_depth(2)
+            .with_signing_key(key.clone());
+        let parent = Task::new("modifier", "Rework storage", SymbolPath::from_str("db").unwrap())
+            .with_autonomy(AutonomyLevel::L3)
+            .with_resources(ResourceCaps {
+                memory_mb: 1024,
+                cpu_millicores: 1000,
+                timeout_secs: 300,
+            })
+            .with_directive(COMPLEXITY_DIRECTIVE, DirectiveValue::Int(40));
+        let upstream = Task::new("architect", "Design storage", SymbolPath::from_str("db").unwrap());
+        let mut tasks = vec![upstream.clone(), parent.depends_on(upstream.id)];
+        decomposer.mark_expansion_points(&mut tasks, 0);
+        assert!(tasks[0].expansion_type.is_none());
+        assert!(matches!(tasks[1].expansion_type, Some(ExpansionType::Recursive { max_depth: 2 })));
+
+        // analyzer + modifier + verifier, each with a third of the work
+        let expansion = decomposer.expand(&tasks[1]).await.unwrap();
+        let children = expansion.tasks;
+        assert_eq!(children.len(), 3);
+        assert_eq!(children[0].dependencies, vec![upstream.id]);
+        assert_eq!(children[1].autonomy, AutonomyLevel::L3);
+        assert_eq!(children[1].resources.timeout_secs, 100);
+        assert_eq!(children[1].resources.memory_mb, 341);
+        assert_eq!(children[1].directives.get(COMPLEXITY_DIRECTIVE), Some(&DirectiveValue::Int(13)));
+        assert!(matches!(children[1].expansion_type, Some(ExpansionType::Recursive { max_depth: 1 })));
+
+        // The kernel signed the subtasks as their own chained graph
+        assert_eq!(expansion.graph.node_count(), 3);
+        assert_eq!(expansion.graph.edge_count(), 2);
+        let token = expansion.graph.get_node_token(expansion.nodes[1]).unwrap();
+        assert!(token.verify(&key.verifying_key()));
+        assert_eq!(token.autonomy_level, AutonomyLevel::L3);
+
+        // The last level decomposes but marks nothing further
+        let grandchildren = decomposer.expand(&children[1]).await.unwrap().tasks;
+        assert!(grandchildren.iter().all(|t| t.expansion_type.is_none()));
+        assert!(matches!(
+            decomposer.expand(&grandchildren[0]).await,
+            Err(DecompositionError::InvalidSpecification(_))
+        ));
+        let exhausted = grandchildren[0]
+            .clone()
+            .with_expansion(ExpansionType::Recursive { max_depth: 0 });
+        assert!(matches!(
+            decomposer.expand(&exhausted).await,
+            Err(DecompositionError::RecursionDepthExceeded)
+        ));
+    }
+
+    #[tokio::test]
+    async fn kernel_rejects_expansions_over_budget() {
+        let decomposer = TaskDecomposer::default();
+        // Three subtasks need at least a second each
+        let parent = Task::new("modifier", "Rework storage", SymbolPath::from_str("db").unwrap())
+            .with_resources(ResourceCaps {
+                memory_mb: 1024,
+                cpu_millicores: 1000,
+                timeout_secs: 2,
+            })
+            .with_expansion(ExpansionType::Recursive { max_depth: 1 });
+
+        assert!(matches!(
+            decomposer.expand(&parent).await,
+            Err(DecompositionError::ExpansionRejected(
+                ValidationError::ExpansionBudgetExceeded
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn expansions_draw_on_the_session_budget() {
+        let parent = Task::new("modifier", "Rework storage", SymbolPath::from_str("db").unwrap())
+            .with_resources(ResourceCaps {
+                memory_mb: 1024,
+                cpu_millicores: 1000,
+                timeout_secs: 300,
+            })
+            .with_expansion(ExpansionType::Recursive { max_depth: 1 });
+        let budget = Arc::new(RunBudget::new(kernel_caps(&parent.resources)));
+        let decomposer = TaskDecomposer::default().with_budget(budget.clone());
+
+        assert!(decomposer.expand(&parent).await.is_ok());
+        assert_eq!(budget.remaining().cpu_time_ms, 0);
+        assert!(matches!(
+            decomposer.expand(&parent).await,
+            Err(DecompositionError::ExpansionRejected(ValidationError::BudgetExceeded))
+        ));
+    }
+
     #[test]
     fn task_dependencies() {
         let task1 = Task::new("dev", "task 1", SymbolPath::from_str("a").unwrap());
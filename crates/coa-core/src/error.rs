@@ -7,8 +7,16 @@
 //! - Construction/execution failures
 //! - Human escalation requirements
 
+use crate::admission::Overload;
+use crate::capability::CapabilityMismatch;
+use crate::config::ConfigSource;
+use crate::tools::ToolKind;
 use coa_composition::CompositionError;
+use coa_constitutional::ApplyError;
 use coa_symbol::{SymbolRef, SymbolRefError};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
 
 /// Main COA error type
 #[derive(Debug, thiserror::Error)]
@@ -64,6 +72,10 @@ pub enum COAError {
     #[error("configuration error: {0}")]
     ConfigError(String),
 
+    /// Admission control refused new work; retry later
+    #[error(transparent)]
+    Busy(#[from] Busy),
+
     /// Timeout
     #[error("operation timed out after {duration_secs}s")]
     Timeout { duration_secs: u64 },
@@ -71,6 +83,10 @@ pub enum COAError {
     /// Cancelled
     #[error("operation cancelled")]
     Cancelled,
+
+    /// Run trace recording or replay failed
+    #[error("trace error: {0}")]
+    Trace(#[from] TraceError),
 }
 
 impl COAError {
@@ -109,6 +125,21 @@ impl COAError {
     }
 }
 
+impl From<ApplyError> for COAError {
+    fn from(error: ApplyError) -> Self {
+        match error {
+            ApplyError::CompositionFailed(e) => Self::CompositionFailed(e),
+            other => Self::ExecutionFailed(other.to_string()),
+        }
+    }
+}
+
+impl From<ConfigError> for COAError {
+    fn from(error: ConfigError) -> Self {
+        Self::ConfigError(error.to_string())
+    }
+}
+
 /// Task decomposition errors
 #[derive(Debug, thiserror::Error)]
 pub enum DecompositionError {
@@ -131,6 +162,22 @@ pub enum DecompositionError {
     /// Cannot decompose goal type
     #[error("cannot decompose goal: {0:?}")]
     UnsupportedGoal(Goal),
+
+    /// Specification names a template the registry does not know
+    #[error("unknown decomposition template: {0}")]
+    UnknownTemplate(String),
+
+    /// Kernel refused the subtasks of an expansion point
+    #[error("expansion rejected by kernel: {0}")]
+    ExpansionRejected(#[from] coa_kernel::ValidationError),
+
+    /// Kernel could not stage an expansion point
+    #[error("expansion staging failed: {0}")]
+    ExpansionFailed(#[from] coa_kernel::ExecutionError),
+
+    /// Subtask dependencies do not form a valid kernel graph
+    #[error("invalid expansion graph: {0}")]
+    ExpansionGraph(#[from] coa_kernel::construction::GraphBuilderError),
 }
 
 /// Construction errors
@@ -171,6 +218,14 @@ pub enum PoolError {
     #[error("pool exhausted (max: {0})")]
     PoolExhausted(usize),
 
+    /// Admitting an agent would exceed a system limit
+    #[error("system limit exhausted: {0}")]
+    ResourcesExhausted(String),
+
+    /// No registered agent covers a task's capability requirements
+    #[error("{0}")]
+    NoCapableAgent(CapabilityMismatch),
+
     /// Agent creation failed
     #[error("agent creation failed: {0}")]
     CreationFailed(String),
@@ -182,10 +237,270 @@ pub enum PoolError {
     /// Communication failed
     #[error("communication failed: {0}")]
     CommunicationFailed(String),
+
+    /// Supervisor gave up restarting an agent
+    #[error("agent '{role}' failed after {restarts} restarts: {last_error}")]
+    RestartLimitExceeded {
+        role: String,
+        restarts: usize,
+        last_error: String,
+    },
+}
+
+/// Remote worker transport errors
+#[derive(Debug, thiserror::Error)]
+pub enum TransportError {
+    /// Endpoint unreachable
+    #[error("failed to connect to {endpoint}: {message}")]
+    ConnectFailed { endpoint: String, message: String },
+
+    /// Connection closed by peer
+    #[error("connection closed")]
+    Closed,
+
+    /// Handshake failed
+    #[error("handshake failed: {0}")]
+    Handshake(String),
+
+    /// Peer rejected the handshake
+    #[error("rejected by orchestrator: {0}")]
+    Rejected(String),
+
+    /// Incompatible protocol versions
+    #[error("protocol mismatch: expected v{expected}, got v{actual}")]
+    ProtocolMismatch { expected: u32, actual: u32 },
+
+    /// Frame encoding/decoding failed
+    #[error("codec error: {0}")]
+    Codec(#[from] serde_json::Error),
+
+    /// Socket I/O error
+    #[error("I/O error: {0}")]
+    Io(String),
+}
+
+/// Run trace errors
+#[derive(Debug, thiserror::Error)]
+pub enum TraceError {
+    /// Replay asked for more inputs than were recorded
+    #[error("trace exhausted at input {index}: expected {expected}")]
+    Exhausted { index: usize, expected: String },
+
+    /// Replay asked for a different input than was recorded
+    #[error("replay diverged at input {index}: expected {expected}, found {found}")]
+    Diverged {
+        index: usize,
+        expected: String,
+        found: String,
+    },
+
+    /// Recorded value could not be decoded
+    #[error("invalid recorded value at input {index}: {value}")]
+    InvalidValue { index: usize, value: String },
+
+    /// Trace file encoding/decoding failed
+    #[error("codec error: {0}")]
+    Codec(#[from] serde_json::Error),
+
+    /// Trace file I/O error
+    #[error("I/O error: {0}")]
+    Io(String),
+}
+
+/// Configuration loading errors
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    /// Profile name is not one of the built-in profiles
+    #[error("unknown profile `{0}` (expected ci, local or prod)")]
+    UnknownProfile(String),
+
+    /// Config file could not be read
+    #[error("failed to read {}: {message}", path.display())]
+    Io { path: PathBuf, message: String },
+
+    /// Config file is not valid TOML or JSON
+    #[error("failed to parse {}: {message}", path.display())]
+    Parse { path: PathBuf, message: String },
+
+    /// Key is not part of the configuration schema
+    #[error(
+        "unknown key `{key}` in {layer}{}",
+        suggestion.as_ref().map(|s| format!("; did you mean `{s}`?")).unwrap_or_default()
+    )]
+    UnknownKey {
+        key: String,
+        layer: ConfigSource,
+        suggestion: Option<String>,
+    },
+
+    /// Value has the wrong type for its key
+    #[error("invalid value for `{key}` in {layer}: expected {expected}, found {found}")]
+    InvalidValue {
+        key: String,
+        layer: ConfigSource,
+        expected: &'static str,
+        found: String,
+    },
+
+    /// Command-line override is not `key=value`
+    #[error("invalid override `{0}`: expected key=value")]
+    MalformedOverride(String),
+
+    /// Merged settings are well-typed but unusable
+    #[error("invalid configuration: {0}")]
+    Invalid(String),
+}
+
+/// Test runner errors
+#[derive(Debug, thiserror::Error)]
+pub enum TestRunError {
+    /// Test command could not be started
+    #[error("failed to run {command}: {message}")]
+    Spawn { command: String, message: String },
+
+    /// Suite produced no parseable results (e.g. it did not compile)
+    #[error("no test results from {command}: {stderr}")]
+    NoResults { command: String, stderr: String },
+
+    /// Suite was killed after running too long
+    #[error("{command} did not finish within {timeout:?}")]
+    TimedOut {
+        command: String,
+        timeout: std::time::Duration,
+    },
+
+    /// Report I/O error
+    #[error("I/O error: {0}")]
+    Io(String),
+}
+
+/// Role catalog errors
+#[derive(Debug, thiserror::Error)]
+pub enum CatalogError {
+    /// Catalog file encoding/decoding failed
+    #[error("codec error: {0}")]
+    Codec(#[from] serde_json::Error),
+
+    /// Catalog file I/O error
+    #[error("I/O error: {0}")]
+    Io(String),
+}
+
+/// Best-of-N selection errors
+#[derive(Debug, thiserror::Error)]
+pub enum DebateError {
+    /// No contenders are registered
+    #[error("no contenders registered")]
+    NoContenders,
+
+    /// Every contender failed to produce a scored candidate
+    #[error(
+        "all contenders failed: {}",
+        .0.iter().map(|(label, e)| format!("{label}: {e}")).collect::<Vec<_>>().join("; ")
+    )]
+    AllFailed(Vec<(String, CandidateError)>),
+}
+
+/// Failure of one best-of-N contender
+#[derive(Debug, thiserror::Error)]
+pub enum CandidateError {
+    /// Kernel refused the contender's sandbox graph
+    #[error("sandbox rejected by kernel: {0}")]
+    Sandbox(#[from] coa_kernel::ValidationError),
+
+    /// Symbol index could not be copied into the sandbox
+    #[error("index snapshot failed: {0}")]
+    Snapshot(#[from] coa_symbol::SymbolRefError),
+
+    /// Agent did not produce deltas
+    #[error("proposal failed: {0}")]
+    Proposal(String),
+
+    /// Tests or lint could not be run on the candidate
+    #[error("scoring failed: {0}")]
+    Scoring(String),
+}
+
+/// LLM call errors
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum LlmError {
+    /// Provider asked the caller to slow down (HTTP 429)
+    #[error("rate limited{}", .retry_after.map(|d| format!(" (retry after {d:?})")).unwrap_or_default())]
+    RateLimited { retry_after: Option<Duration> },
+
+    /// Provider could not serve the call; another provider may
+    #[error("provider unavailable: {0}")]
+    Unavailable(String),
+
+    /// Request is invalid; no provider will accept it
+    #[error("request rejected: {0}")]
+    Rejected(String),
+
+    /// Spending limit reached
+    #[error("LLM budget exhausted: {0}")]
+    BudgetExhausted(String),
+
+    /// No providers are configured
+    #[error("no LLM providers configured")]
+    NoProviders,
+
+    /// Every provider failed; one entry per provider
+    #[error("all providers failed: {}", .0.join("; "))]
+    AllProvidersFailed(Vec<String>),
+
+    /// Response could not be recorded to or replayed from the run trace
+    #[error("trace error: {0}")]
+    Replay(String),
+}
+
+impl From<TraceError> for LlmError {
+    fn from(error: TraceError) -> Self {
+        Self::Replay(error.to_string())
+    }
+}
+
+/// Agent tool call errors
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ToolError {
+    /// Capability token or filesystem scope does not allow the call
+    #[error("{tool} denied: {reason}")]
+    Denied { tool: ToolKind, reason: String },
+
+    /// Token's resource caps are used up
+    #[error("{tool} over budget: {reason}")]
+    BudgetExhausted { tool: ToolKind, reason: String },
+
+    /// Call was authorized but did not succeed
+    #[error("{tool} failed: {message}")]
+    Failed { tool: ToolKind, message: String },
+
+    /// Call could not be recorded in the kernel event log
+    #[error("{tool} not recorded: {message}")]
+    Audit { tool: ToolKind, message: String },
+}
+
+/// Work refused by admission control because the system is overloaded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("busy ({reason}); retry after {retry_after:?}")]
+pub struct Busy {
+    /// Signal that was over its ceiling
+    pub reason: Overload,
+    /// Suggested delay before retrying
+    pub retry_after: Duration,
+}
+
+/// Directive conversion errors
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DirectiveError {
+    /// JSON value has no `DirectiveValue` form (null, float, object or
+    /// integer beyond `i64`)
+    #[error("directive `{key}` cannot be represented: found {found}")]
+    Unrepresentable { key: String, found: String },
 }
 
 /// Goal types for specification
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Goal {
     /// Create new artifact
     CreateNew,
@@ -279,6 +594,8 @@ pub enum ErrorType {
     Agent,
     /// Resource error
     Resource,
+    /// Test failure
+    Test,
     /// System error
     System,
     /// Unknown
@@ -413,6 +730,17 @@ mod tests {
         assert!(human_err.requires_human());
     }
 
+    #[test]
+    fn apply_errors_map_to_coa_errors() {
+        let err = COAError::from(ApplyError::CompositionFailed(
+            CompositionError::CompositionFailed("boom".to_string()),
+        ));
+        assert!(matches!(err, COAError::CompositionFailed(_)));
+
+        let err = COAError::from(ApplyError::NoTransformer("code".to_string()));
+        assert!(matches!(err, COAError::ExecutionFailed(_)));
+    }
+
     #[test]
     fn coa_error_is_retryable() {
         assert!(COAError::AgentFailed("test".to_string()).is_retryable());
@@ -0,0 +1,435 @@
+//! Intent classification
+//!
+//! Sorts a [`UserIntent`] into an [`IntentCategory`] and routes it with the
+//! category's [`CategoryProfile`]: the decomposition template used when the
+//! intent names no goal of its own, a composition strategy hint, and the
+//! autonomy ceiling every generated task is clamped to.
+//!
+//! Profiles come from the role catalog, a JSON file mapping categories to
+//! profiles. Categories the catalog leaves out keep their built-in profile:
+//!
+//! ```json
+//! {
+//!   "categories": {
+//!     "infra": {
+//!       "keywords": ["deploy", "terraform"],
+//!       "template": "modify_existing",
+//!       "strategy_hint": "single_writer",
+//!       "autonomy_ceiling": "L1"
+//!     }
+//!   },
+//!   "fallback": "feature"
+//! }
+//! ```
+
+use crate::error::{CatalogError, Goal};
+use crate::types::{AutonomyCeiling, AutonomyLevel, DirectiveValue, Task, UserIntent};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+use std::path::Path;
+use std::str::FromStr;
+
+/// Directive recording the category a task was routed under
+pub const INTENT_CATEGORY_DIRECTIVE: &str = "intent_category";
+
+/// Directive carrying the composition strategy for a task
+pub const COMPOSITION_STRATEGY_DIRECTIVE: &str = "composition_strategy";
+
+/// Kind of work an intent asks for
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum IntentCategory {
+    /// Correct wrong behaviour
+    Bugfix,
+    /// Add new behaviour
+    Feature,
+    /// Restructure without changing behaviour
+    Refactor,
+    /// Inspect and report, no changes
+    Analysis,
+    /// Documentation
+    Docs,
+    /// Build, configuration and deployment
+    Infra,
+}
+
+impl IntentCategory {
+    /// All categories, in tie-break order
+    pub const ALL: [Self; 6] = [
+        Self::Bugfix,
+        Self::Feature,
+        Self::Refactor,
+        Self::Analysis,
+        Self::Docs,
+        Self::Infra,
+    ];
+
+    /// Catalog name of the category
+    #[inline]
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Bugfix => "bugfix",
+            Self::Feature => "feature",
+            Self::Refactor => "refactor",
+            Self::Analysis => "analysis",
+            Self::Docs => "docs",
+            Self::Infra => "infra",
+        }
+    }
+}
+
+impl Display for IntentCategory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for IntentCategory {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_ascii_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|category| category.as_str() == s)
+            .ok_or_else(|| format!("unknown intent category: {s}"))
+    }
+}
+
+/// Routing defaults for one category
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CategoryProfile {
+    /// Words that vote for the category (matched against word prefixes)
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    /// Decomposition template when the intent names no goal
+    pub template: Goal,
+    /// Composition strategy for generated tasks, if not left to the selector
+    #[serde(default)]
+    pub strategy_hint: Option<String>,
+    /// Highest autonomy any generated task may run at
+    pub autonomy_ceiling: AutonomyLevel,
+}
+
+impl CategoryProfile {
+    /// Create profile with no keywords or strategy hint
+    #[inline]
+    #[must_use]
+    pub fn new(template: Goal, autonomy_ceiling: AutonomyLevel) -> Self {
+        Self {
+            keywords: Vec::new(),
+            template,
+            strategy_hint: None,
+            autonomy_ceiling,
+        }
+    }
+
+    /// With keywords
+    #[must_use]
+    pub fn with_keywords<I, S>(mut self, keywords: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.keywords = keywords.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// With composition strategy hint
+    #[inline]
+    #[must_use]
+    pub fn with_strategy_hint(mut self, strategy: impl Into<String>) -> Self {
+        self.strategy_hint = Some(strategy.into());
+        self
+    }
+
+    /// Built-in profile for `category`
+    #[must_use]
+    pub fn builtin(category: IntentCategory) -> Self {
+        match category {
+            IntentCategory::Bugfix => Self::new(Goal::ModifyExisting, AutonomyLevel::L3)
+                .with_keywords(["fix", "bug", "crash", "broken", "regression", "error", "fail"])
+                .with_strategy_hint("single_writer"),
+            IntentCategory::Feature => Self::new(Goal::CreateNew, AutonomyLevel::L3)
+                .with_keywords(["create", "new", "add", "implement", "introduce", "support"]),
+            IntentCategory::Refactor => Self::new(Goal::Refactor, AutonomyLevel::L3)
+                .with_keywords(["refactor", "rename", "restructure", "extract", "simplify", "cleanup"])
+                .with_strategy_hint("single_writer"),
+            IntentCategory::Analysis => Self::new(Goal::Analyze, AutonomyLevel::L4)
+                .with_keywords(["analy", "investigate", "audit", "review", "explain", "profile"]),
+            IntentCategory::Docs => Self::new(Goal::CreateNew, AutonomyLevel::L4)
+                .with_keywords(["doc", "readme", "changelog", "comment", "guide", "tutorial"])
+                .with_strategy_hint("hybrid"),
+            IntentCategory::Infra => Self::new(Goal::ModifyExisting, AutonomyLevel::L2)
+                .with_keywords(["config", "setting", "deploy", "pipeline", "docker", "infra", "dependenc"])
+                .with_strategy_hint("hybrid"),
+        }
+    }
+
+    /// Number of keywords matching a word in `words`
+    fn score(&self, words: &[&str]) -> usize {
+        self.keywords
+            .iter()
+            .filter(|keyword| {
+                let keyword = keyword.to_lowercase();
+                words.iter().any(|word| word.starts_with(keyword.as_str()))
+            })
+            .count()
+    }
+}
+
+/// Role catalog: category profiles loaded from file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoleCatalog {
+    /// Profiles overriding the built-in ones
+    #[serde(default)]
+    pub categories: BTreeMap<IntentCategory, CategoryProfile>,
+    /// Category for intents no keyword matches
+    #[serde(default = "default_fallback")]
+    pub fallback: IntentCategory,
+}
+
+fn default_fallback() -> IntentCategory {
+    IntentCategory::Feature
+}
+
+impl Default for RoleCatalog {
+    fn default() -> Self {
+        Self {
+            categories: BTreeMap::new(),
+            fallback: default_fallback(),
+        }
+    }
+}
+
+impl RoleCatalog {
+    /// Write catalog as JSON
+    ///
+    /// # Errors
+    /// Returns `CatalogError::Io` or `CatalogError::Codec` on failure
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CatalogError> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json).map_err(|e| CatalogError::Io(e.to_string()))
+    }
+
+    /// Read catalog file
+    ///
+    /// # Errors
+    /// Returns `CatalogError::Io` or `CatalogError::Codec` on failure
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CatalogError> {
+        let json = std::fs::read_to_string(path).map_err(|e| CatalogError::Io(e.to_string()))?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// With profile for `category`
+    #[inline]
+    #[must_use]
+    pub fn with_profile(mut self, category: IntentCategory, profile: CategoryProfile) -> Self {
+        self.categories.insert(category, profile);
+        self
+    }
+}
+
+/// Routing decision for one intent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntentRoute {
+    /// Category the intent was sorted into
+    pub category: IntentCategory,
+    /// Decomposition template when the intent names no goal
+    pub template: Goal,
+    /// Composition strategy for generated tasks
+    pub strategy_hint: Option<String>,
+    /// Ceiling generated tasks are clamped to
+    pub autonomy_ceiling: AutonomyCeiling,
+}
+
+impl IntentRoute {
+    /// Apply the route to decomposed tasks
+    ///
+    /// Tags each task with its category, clamps its autonomy to the ceiling
+    /// and, if the route carries a strategy hint, sets its composition
+    /// strategy.
+    pub fn apply(&self, tasks: &mut [Task]) {
+        for task in tasks {
+            task.autonomy = self.autonomy_ceiling.clamp(task.autonomy);
+            task.directives.insert(
+                INTENT_CATEGORY_DIRECTIVE.to_string(),
+                DirectiveValue::String(self.category.to_string()),
+            );
+            if let Some(strategy) = &self.strategy_hint {
+                task.directives.insert(
+                    COMPOSITION_STRATEGY_DIRECTIVE.to_string(),
+                    DirectiveValue::String(strategy.clone()),
+                );
+            }
+        }
+    }
+}
+
+/// Sorts intents into categories and routes them
+#[derive(Debug, Clone, Default)]
+pub struct IntentClassifier {
+    catalog: RoleCatalog,
+}
+
+impl IntentClassifier {
+    /// Create classifier with the built-in profiles
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create classifier from a role catalog
+    #[inline]
+    #[must_use]
+    pub fn from_catalog(catalog: RoleCatalog) -> Self {
+        Self { catalog }
+    }
+
+    /// Create classifier from a role catalog file
+    ///
+    /// # Errors
+    /// Returns `CatalogError` if the file cannot be read or parsed
+    pub fn from_catalog_file(path: impl AsRef<Path>) -> Result<Self, CatalogError> {
+        RoleCatalog::load(path).map(Self::from_catalog)
+    }
+
+    /// Catalog in use
+    #[inline]
+    #[must_use]
+    pub fn catalog(&self) -> &RoleCatalog {
+        &self.catalog
+    }
+
+    /// Profile for `category`, from the catalog or built in
+    #[must_use]
+    pub fn profile(&self, category: IntentCategory) -> CategoryProfile {
+        self.catalog
+            .categories
+            .get(&category)
+            .cloned()
+            .unwrap_or_else(|| CategoryProfile::builtin(category))
+    }
+
+    /// Category of `intent`
+    ///
+    /// A context `mode` naming a category wins. Otherwise the category with
+    /// the most keyword matches is chosen, ties going to the earlier entry
+    /// of [`IntentCategory::ALL`]; with no match the catalog fallback is used.
+    #[must_use]
+    pub fn classify(&self, intent: &UserIntent) -> IntentCategory {
+        if let Some(category) = intent
+            .context
+            .as_ref()
+            .and_then(|c| c.mode.as_deref())
+            .and_then(|mode| mode.parse().ok())
+        {
+            return category;
+        }
+
+        let description = intent.description.to_lowercase();
+        let words: Vec<&str> = description
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .collect();
+
+        let mut best = (0, self.catalog.fallback);
+        for category in IntentCategory::ALL {
+            let score = self.profile(category).score(&words);
+            if score > best.0 {
+                best = (score, category);
+            }
+        }
+        best.1
+    }
+
+    /// Classify `intent` and resolve its routing defaults
+    #[must_use]
+    pub fn route(&self, intent: &UserIntent) -> IntentRoute {
+        let category = self.classify(intent);
+        let profile = self.profile(category);
+        IntentRoute {
+            category,
+            template: profile.template,
+            strategy_hint: profile.strategy_hint,
+            autonomy_ceiling: AutonomyCeiling::new(profile.autonomy_ceiling),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::IntentContext;
+    use coa_artifact::SymbolPath;
+
+    #[test]
+    fn builtin_profiles_classify_common_intents() {
+        let classifier = IntentClassifier::new();
+        let cases = [
+            ("Fix the crash when parsing empty input", IntentCategory::Bugfix),
+            ("Create a new authentication function", IntentCategory::Feature),
+            ("Refactor the utils module", IntentCategory::Refactor),
+            ("Investigate why requests are slow", IntentCategory::Analysis),
+            ("Write a README for the parser crate", IntentCategory::Docs),
+            ("Update the config settings", IntentCategory::Infra),
+            ("Make it nicer", IntentCategory::Feature),
+        ];
+        for (description, expected) in cases {
+            assert_eq!(classifier.classify(&UserIntent::new(description)), expected, "{description}");
+        }
+
+        let mut context = IntentContext::new();
+        context.mode = Some("docs".to_string());
+        let intent = UserIntent::new("Fix the broken example").with_context(context);
+        assert_eq!(classifier.classify(&intent), IntentCategory::Docs);
+    }
+
+    #[test]
+    fn catalog_file_overrides_profiles_and_routes_tasks() {
+        let catalog = RoleCatalog {
+            fallback: IntentCategory::Analysis,
+            ..RoleCatalog::default()
+        }
+        .with_profile(
+            IntentCategory::Infra,
+            CategoryProfile::new(Goal::ModifyExisting, AutonomyLevel::L1)
+                .with_keywords(["terraform"])
+                .with_strategy_hint("single_writer"),
+        );
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("roles.json");
+        catalog.save(&path).unwrap();
+
+        let classifier = IntentClassifier::from_catalog_file(&path).unwrap();
+        assert_eq!(classifier.catalog(), &catalog);
+        assert_eq!(classifier.classify(&UserIntent::new("Make it nicer")), IntentCategory::Analysis);
+        // Overridden keywords replace the built-in ones
+        assert_eq!(
+            classifier.classify(&UserIntent::new("Update the config settings")),
+            IntentCategory::Analysis
+        );
+
+        let route = classifier.route(&UserIntent::new("Apply the terraform plan"));
+        assert_eq!(route.category, IntentCategory::Infra);
+        assert_eq!(route.template, Goal::ModifyExisting);
+
+        let mut tasks = vec![Task::new("deployer", "Apply plan", SymbolPath::default())
+            .with_autonomy(AutonomyLevel::L4)];
+        route.apply(&mut tasks);
+        assert_eq!(tasks[0].autonomy, AutonomyLevel::L1);
+        assert_eq!(
+            tasks[0].directives.get(INTENT_CATEGORY_DIRECTIVE),
+            Some(&DirectiveValue::String("infra".to_string()))
+        );
+        assert_eq!(
+            tasks[0].directives.get(COMPOSITION_STRATEGY_DIRECTIVE),
+            Some(&DirectiveValue::String("single_writer".to_string()))
+        );
+    }
+}
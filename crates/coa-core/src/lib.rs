@@ -6,6 +6,9 @@
 //! - Manages the agent lifecycle
 //! - Handles construction failures with diagnostics
 //! - Coordinates multi-agent composition
+//! - Loads layered configuration from profiles, files, env and CLI
+//! - Mediates agent tool calls under their capability tokens
+//! - Refuses new work with a retry delay when overloaded
 //!
 //! # Example
 //!
@@ -28,25 +31,84 @@
 #![allow(missing_docs)]
 
 // Core modules
+pub mod admission;
 pub mod agent_pool;
+pub mod capability;
+pub mod chaos;
 pub mod coa;
+pub mod config;
+pub mod context;
+pub mod debate;
 pub mod decomposition;
 pub mod error;
+pub mod intent;
+pub mod llm;
+pub mod remote;
+pub mod templates;
+pub mod test_runner;
+pub mod tools;
+pub mod trace;
 pub mod types;
+pub mod verification;
+pub mod visualize;
 
 // Re-exports for convenience
-pub use agent_pool::{AgentHandle, AgentMessage, AgentPool, PoolStats};
+pub use admission::{AdmissionController, AdmissionPermit, AdmissionPolicy, Overload};
+pub use agent_pool::{
+    AgentHandle, AgentMessage, AgentPool, PoolStats, PreemptHook, Preemption, DEFAULT_MAX_RESTARTS,
+};
+pub use capability::{
+    AgentCapabilities, CapabilityGap, CapabilityMismatch, CapabilityRequirements, RejectedAgent,
+};
+pub use chaos::{ChaosConfig, ChaosEvent, ChaosFault};
 pub use coa::CreatorOrchestratorAgent;
-pub use decomposition::TaskDecomposer;
+pub use config::{ConfigLoader, ConfigProfile, ConfigSource, ResolvedConfig};
+pub use debate::{
+    BestOfN, Candidate, CandidateGenerator, CandidateScore, CandidateScorer, Contender,
+    DebateOutcome, Sandbox, MAX_DEBATE_ITERATIONS_DIRECTIVE,
+};
+pub use context::{
+    estimate_tokens, AssembledContext, ContextAssembler, ContextSection, ContextSource, PriorDelta,
+    SectionKind, TruncationStrategy, DEFAULT_CONTEXT_BUDGET,
+};
+pub use decomposition::{
+    Expansion, TaskDecomposer, TaskExpansion, COMPLEXITY_DIRECTIVE, DEFAULT_COMPLEXITY_THRESHOLD,
+    TEMPLATE_DIRECTIVE,
+};
 pub use error::{
-    ConstructionError, COAError, DecompositionError, Diagnostic, ErrorType, Goal, Location,
-    PoolError, ResourceAmount, SuggestedFix,
+    Busy, CandidateError, CatalogError, ConfigError, ConstructionError, COAError, DebateError, DecompositionError, DirectiveError, Diagnostic, LlmError,
+    ErrorType, Goal, Location, PoolError, ResourceAmount, SuggestedFix, TestRunError, ToolError,
+    TraceError, TransportError,
 };
+pub use intent::{CategoryProfile, IntentCategory, IntentClassifier, IntentRoute, RoleCatalog};
+pub use llm::{
+    BudgetManager, BudgetUsage, CallRecord, LlmCall, LlmExecutor, LlmProvider, LlmRequest,
+    LlmResponse, Pricing, ProviderSpend, TokenUsage,
+};
+pub use remote::{
+    ReconnectPolicy, RemoteWorker, WebSocketConnector, WebSocketListener, WorkerConnection,
+    WorkerConnector, WorkerEvent, WorkerFrame, WorkerHello, WorkerSession,
+};
+pub use templates::{
+    AddCliFlag, AddEndpoint, DecompositionTemplate, TemplateRegistry, UpgradeDependency,
+    WriteUnitTests,
+};
+pub use test_runner::{TestCase, TestFramework, TestOutcome, TestReport, TestRunner};
+pub use tools::{
+    Proposal, ToolCallRecord, ToolKind, ToolOutcome, ToolUsage, Toolbox, TOOL_CALL_ACTION,
+};
+pub use trace::{RunTrace, Trace, TraceEvent, TraceMode};
 pub use types::{
-    AgentId, AgentSpec, ArtifactSummary, AutonomyLevel, COAConfig, Constraint, ExecutionResult,
-    ExpansionType, IntentContext, OutputSpec, ResourceCaps, SpecFormat, Specification, Task,
-    TaskId, UserIntent,
+    canonical_directive_json, directives_from_kernel, directives_to_kernel, AgentId, AgentSpec,
+    ArtifactSummary, AutonomyLevel, COAConfig, Constraint, DirectiveSet, DirectiveValue,
+    ExecutionResult, ExpansionType, IntentContext, OutputSpec, ResourceCaps, SpecFormat,
+    Specification, Task, TaskId, TaskPriority, UserIntent,
+};
+pub use verification::{
+    CheckKind, CommandCriteriaExecutor, CriteriaExecutor, CriterionCheck, CriterionOutcome,
+    UnmetCriterion, VerificationGate,
 };
+pub use visualize::{GraphFormat, TaskGraphExporter};
 
 /// Prelude module for common imports
 pub mod prelude {
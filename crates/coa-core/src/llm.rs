@@ -0,0 +1,663 @@
+//! LLM providers and the call executor
+//!
+//! An [`LlmProvider`] is one model endpoint. An [`LlmExecutor`] calls its
+//! providers in order of preference:
+//! - a rate-limited call (HTTP 429) is retried on the same provider with
+//!   exponential backoff, honouring the provider's `retry_after`
+//! - once retries run out, or the provider is unavailable, the next
+//!   provider is tried; a rejected request fails at once
+//! - every attempt is logged with its prompt and response passed through a
+//!   [`Redaction`] (hashed by default, so equal prompts still correlate)
+//! - token usage and its cost are charged to a shared [`BudgetManager`],
+//!   which refuses further calls once a limit is reached
+//! - with a [`Trace`], successful responses are recorded, or served back
+//!   on replay without calling the provider
+
+use crate::error::LlmError;
+use crate::trace::Trace;
+use coa_constitutional::Redaction;
+use coa_kernel::clock::{system_clock, SharedClock};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+/// Default retries of a rate-limited call per provider
+pub const DEFAULT_RATE_LIMIT_RETRIES: u32 = 3;
+
+/// Default wait before the first retry of a rate-limited call
+pub const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Default longest wait before a retry; longer waits fail over instead
+pub const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Completion request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LlmRequest {
+    /// Prompt text
+    pub prompt: String,
+    /// Upper bound on completion tokens
+    pub max_tokens: Option<u32>,
+}
+
+impl LlmRequest {
+    /// Create request
+    #[inline]
+    #[must_use]
+    pub fn new(prompt: impl Into<String>) -> Self {
+        Self {
+            prompt: prompt.into(),
+            max_tokens: None,
+        }
+    }
+
+    /// With completion token limit
+    #[inline]
+    #[must_use]
+    pub fn with_max_tokens(mut self, tokens: u32) -> Self {
+        self.max_tokens = Some(tokens);
+        self
+    }
+}
+
+/// Tokens consumed by a call
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    /// Prompt tokens
+    pub prompt_tokens: u64,
+    /// Completion tokens
+    pub completion_tokens: u64,
+}
+
+impl TokenUsage {
+    /// Prompt and completion tokens together
+    #[inline]
+    #[must_use]
+    pub fn total(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+
+    fn add(&mut self, other: &TokenUsage) {
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+    }
+}
+
+/// Completion returned by a provider
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LlmResponse {
+    /// Completion text
+    pub text: String,
+    /// Tokens consumed
+    pub usage: TokenUsage,
+}
+
+/// Price of a provider's tokens, in USD per thousand
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Pricing {
+    /// Per thousand prompt tokens
+    pub prompt_per_1k: f64,
+    /// Per thousand completion tokens
+    pub completion_per_1k: f64,
+}
+
+impl Pricing {
+    /// Cost of `usage`, in USD
+    #[allow(clippy::cast_precision_loss)]
+    #[must_use]
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        (usage.prompt_tokens as f64 * self.prompt_per_1k
+            + usage.completion_tokens as f64 * self.completion_per_1k)
+            / 1000.0
+    }
+}
+
+/// Model endpoint
+#[async_trait::async_trait]
+pub trait LlmProvider: Send + Sync + std::fmt::Debug {
+    /// Name used in logs and accounting
+    fn name(&self) -> &str;
+
+    /// Token prices (free unless overridden)
+    fn pricing(&self) -> Pricing {
+        Pricing::default()
+    }
+
+    /// Complete `request`
+    ///
+    /// Providers map HTTP 429 to [`LlmError::RateLimited`], failures another
+    /// provider could avoid (outages, auth) to [`LlmError::Unavailable`] and
+    /// invalid requests to [`LlmError::Rejected`].
+    async fn complete(&self, request: &LlmRequest) -> Result<LlmResponse, LlmError>;
+}
+
+/// Spend on one provider
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProviderSpend {
+    /// Successful calls
+    pub calls: u64,
+    /// Tokens consumed
+    pub tokens: TokenUsage,
+    /// Cost, in USD
+    pub cost: f64,
+}
+
+/// Spend so far, overall and per provider
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BudgetUsage {
+    /// Across all providers
+    pub total: ProviderSpend,
+    /// By provider name
+    pub by_provider: BTreeMap<String, ProviderSpend>,
+}
+
+/// Token and cost budget shared by LLM calls
+#[derive(Debug, Default)]
+pub struct BudgetManager {
+    max_tokens: Option<u64>,
+    max_cost: Option<f64>,
+    usage: Mutex<BudgetUsage>,
+}
+
+impl BudgetManager {
+    /// Unlimited budget
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Refuse calls once `tokens` have been consumed
+    #[inline]
+    #[must_use]
+    pub fn with_max_tokens(mut self, tokens: u64) -> Self {
+        self.max_tokens = Some(tokens);
+        self
+    }
+
+    /// Refuse calls once `usd` has been spent
+    #[inline]
+    #[must_use]
+    pub fn with_max_cost(mut self, usd: f64) -> Self {
+        self.max_cost = Some(usd);
+        self
+    }
+
+    /// Spend so far
+    #[must_use]
+    pub fn usage(&self) -> BudgetUsage {
+        self.lock().clone()
+    }
+
+    /// Check that another call may be made
+    ///
+    /// # Errors
+    /// - `LlmError::BudgetExhausted` once a limit is reached
+    pub fn check(&self) -> Result<(), LlmError> {
+        let total = self.lock().total;
+        if let Some(max) = self.max_tokens.filter(|max| total.tokens.total() >= *max) {
+            return Err(LlmError::BudgetExhausted(format!(
+                "{} of {max} tokens used",
+                total.tokens.total()
+            )));
+        }
+        if let Some(max) = self.max_cost.filter(|max| total.cost >= *max) {
+            return Err(LlmError::BudgetExhausted(format!(
+                "${:.4} of ${max:.4} spent",
+                total.cost
+            )));
+        }
+        Ok(())
+    }
+
+    /// Charge a successful call to `provider`
+    pub fn record(&self, provider: &str, tokens: &TokenUsage, cost: f64) {
+        let mut usage = self.lock();
+        let BudgetUsage { total, by_provider } = &mut *usage;
+        for spend in [total, by_provider.entry(provider.to_string()).or_default()] {
+            spend.calls += 1;
+            spend.tokens.add(tokens);
+            spend.cost += cost;
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BudgetUsage> {
+        self.usage.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Logged attempt, with prompt and response redacted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CallRecord {
+    /// Provider called
+    pub provider: String,
+    /// Attempt number within the call, from 1
+    pub attempt: u32,
+    /// Redacted prompt
+    pub prompt: String,
+    /// Redacted completion, if the attempt succeeded
+    pub response: Option<String>,
+    /// Failure, if it did not
+    pub error: Option<String>,
+    /// Tokens consumed
+    pub tokens: TokenUsage,
+    /// Cost, in USD
+    pub cost: f64,
+    /// Time the provider took
+    pub latency: Duration,
+}
+
+/// Completed call
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LlmCall {
+    /// Provider that answered
+    pub provider: String,
+    /// Its completion
+    pub response: LlmResponse,
+    /// Cost, in USD
+    pub cost: f64,
+    /// Attempts made across all providers
+    pub attempts: u32,
+}
+
+/// Calls providers with rate-limit backoff, failover, logging and
+/// accounting
+#[derive(Debug)]
+pub struct LlmExecutor {
+    providers: Vec<Arc<dyn LlmProvider>>,
+    budget: Arc<BudgetManager>,
+    max_retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    redaction: Redaction,
+    trace: Option<Trace>,
+    clock: SharedClock,
+    records: Mutex<Vec<CallRecord>>,
+}
+
+impl LlmExecutor {
+    /// Create executor charging calls to `budget`
+    #[must_use]
+    pub fn new(budget: Arc<BudgetManager>) -> Self {
+        Self {
+            providers: Vec::new(),
+            budget,
+            max_retries: DEFAULT_RATE_LIMIT_RETRIES,
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+            redaction: Redaction::Hash,
+            trace: None,
+            clock: system_clock(),
+            records: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Add a provider, tried after those added before it
+    #[must_use]
+    pub fn with_provider(mut self, provider: impl LlmProvider + 'static) -> Self {
+        self.providers.push(Arc::new(provider));
+        self
+    }
+
+    /// Set retries of a rate-limited call per provider
+    #[inline]
+    #[must_use]
+    pub fn with_max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = retries;
+        self
+    }
+
+    /// Set the first backoff and the longest wait before failing over
+    #[inline]
+    #[must_use]
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Set how prompts and responses are redacted in the call log
+    #[inline]
+    #[must_use]
+    pub fn with_redaction(mut self, redaction: Redaction) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    /// Record responses to, or replay them from, `trace`
+    #[inline]
+    #[must_use]
+    pub fn with_trace(mut self, trace: Trace) -> Self {
+        self.trace = Some(trace);
+        self
+    }
+
+    /// Measure call latency against `clock`
+    #[inline]
+    #[must_use]
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Budget calls are charged to
+    #[inline]
+    #[must_use]
+    pub fn budget(&self) -> &Arc<BudgetManager> {
+        &self.budget
+    }
+
+    /// Complete `request` on the first provider that can
+    ///
+    /// # Errors
+    /// - `LlmError::NoProviders` if none are configured
+    /// - `LlmError::Rejected` as soon as a provider rejects the request
+    /// - `LlmError::BudgetExhausted` once the budget refuses another attempt
+    /// - `LlmError::AllProvidersFailed` if every provider was unavailable or
+    ///   stayed rate limited
+    /// - `LlmError::Replay` if a replay does not match the recorded trace
+    pub async fn call(&self, request: &LlmRequest) -> Result<LlmCall, LlmError> {
+        if self.providers.is_empty() {
+            return Err(LlmError::NoProviders);
+        }
+        let mut attempts = 0;
+        let mut failures = Vec::new();
+
+        for provider in &self.providers {
+            let mut backoff = self.initial_backoff;
+            let mut retries = 0;
+            loop {
+                self.budget.check()?;
+                attempts += 1;
+                let started = self.clock.now();
+                let result = self.complete(provider.as_ref(), request).await;
+                let latency = Duration::from_secs(self.clock.now().saturating_sub(started));
+
+                let error = match result {
+                    Ok(response) => {
+                        let cost = provider.pricing().cost(&response.usage);
+                        self.budget.record(provider.name(), &response.usage, cost);
+                        self.log(
+                            provider.name(),
+                            attempts,
+                            request,
+                            Ok(&response),
+                            cost,
+                            latency,
+                        );
+                        return Ok(LlmCall {
+                            provider: provider.name().to_string(),
+                            response,
+                            cost,
+                            attempts,
+                        });
+                    }
+                    Err(error) => error,
+                };
+                self.log(
+                    provider.name(),
+                    attempts,
+                    request,
+                    Err(&error),
+                    0.0,
+                    latency,
+                );
+
+                match error {
+                    LlmError::RateLimited { retry_after } if retries < self.max_retries => {
+                        let wait = retry_after.unwrap_or(backoff);
+                        if wait > self.max_backoff {
+                            failures.push(format!("{}: {error}", provider.name()));
+                            break;
+                        }
+                        tokio::time::sleep(wait).await;
+                        backoff = (backoff * 2).min(self.max_backoff);
+                        retries += 1;
+                    }
+                    LlmError::Rejected(_) | LlmError::BudgetExhausted(_) | LlmError::Replay(_) => {
+                        return Err(error)
+                    }
+                    error => {
+                        failures.push(format!("{}: {error}", provider.name()));
+                        break;
+                    }
+                }
+            }
+        }
+        Err(LlmError::AllProvidersFailed(failures))
+    }
+
+    /// Provider completion, passed through the trace when there is one
+    async fn complete(
+        &self,
+        provider: &dyn LlmProvider,
+        request: &LlmRequest,
+    ) -> Result<LlmResponse, LlmError> {
+        let Some(trace) = &self.trace else {
+            return provider.complete(request).await;
+        };
+        let live = async {
+            let response = provider.complete(request).await?;
+            serde_json::to_string(&response).map_err(|e| LlmError::Replay(e.to_string()))
+        };
+        let recorded = trace.llm_response(&request.prompt, live).await?;
+        serde_json::from_str(&recorded).map_err(|e| LlmError::Replay(e.to_string()))
+    }
+
+    /// Attempts logged since the last call, oldest first
+    pub fn take_records(&self) -> Vec<CallRecord> {
+        std::mem::take(&mut *self.records.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    fn log(
+        &self,
+        provider: &str,
+        attempt: u32,
+        request: &LlmRequest,
+        outcome: Result<&LlmResponse, &LlmError>,
+        cost: f64,
+        latency: Duration,
+    ) {
+        let record = CallRecord {
+            provider: provider.to_string(),
+            attempt,
+            prompt: self.redaction.apply(&request.prompt),
+            response: outcome.ok().map(|r| self.redaction.apply(&r.text)),
+            error: outcome.err().map(ToString::to_string),
+            tokens: outcome.map(|r| r.usage).unwrap_or_default(),
+            cost,
+            latency,
+        };
+        match &record.error {
+            None => tracing::debug!(
+                "LLM call to '{}' (attempt {}): {} -> {} [{} tokens, ${:.4}, {:?}]",
+                record.provider,
+                record.attempt,
+                record.prompt,
+                record.response.as_deref().unwrap_or_default(),
+                record.tokens.total(),
+                record.cost,
+                record.latency
+            ),
+            Some(error) => tracing::warn!(
+                "LLM call to '{}' (attempt {}) failed: {} [prompt {}]",
+                record.provider,
+                record.attempt,
+                error,
+                record.prompt
+            ),
+        }
+        self.records
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(record);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coa_kernel::clock::MonotonicClock;
+    use std::collections::VecDeque;
+
+    #[derive(Debug)]
+    struct Scripted {
+        name: &'static str,
+        replies: Mutex<VecDeque<Result<LlmResponse, LlmError>>>,
+    }
+
+    impl Scripted {
+        fn new(name: &'static str, replies: Vec<Result<LlmResponse, LlmError>>) -> Self {
+            Self {
+                name,
+                replies: Mutex::new(replies.into()),
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl LlmProvider for Scripted {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn pricing(&self) -> Pricing {
+            Pricing {
+                prompt_per_1k: 1.0,
+                completion_per_1k: 2.0,
+            }
+        }
+
+        async fn complete(&self, _request: &LlmRequest) -> Result<LlmResponse, LlmError> {
+            self.replies
+                .lock()
+                .unwrap()
+                .pop_front()
+                .unwrap_or_else(|| Err(LlmError::Unavailable("script exhausted".to_string())))
+        }
+    }
+
+    fn ok(text: &str) -> Result<LlmResponse, LlmError> {
+        Ok(LlmResponse {
+            text: text.to_string(),
+            usage: TokenUsage {
+                prompt_tokens: 500,
+                completion_tokens: 250,
+            },
+        })
+    }
+
+    fn limited() -> Result<LlmResponse, LlmError> {
+        Err(LlmError::RateLimited { retry_after: None })
+    }
+
+    fn executor(budget: BudgetManager) -> LlmExecutor {
+        LlmExecutor::new(Arc::new(budget))
+            .with_backoff(Duration::from_millis(1), Duration::from_millis(10))
+    }
+
+    #[tokio::test]
+    async fn retries_rate_limits_then_fails_over() {
+        let executor = executor(BudgetManager::new())
+            .with_max_retries(2)
+            .with_provider(Scripted::new("primary", vec![limited(), ok("hello")]))
+            .with_provider(Scripted::new("fallback", vec![ok("from fallback")]));
+        let request = LlmRequest::new("api_key=sk-live-123 summarise");
+
+        let call = executor.call(&request).await.unwrap();
+        assert_eq!((call.provider.as_str(), call.attempts), ("primary", 2));
+        assert!((call.cost - 1.0).abs() < 1e-9);
+
+        let records = executor.take_records();
+        assert_eq!(records.len(), 2);
+        assert!(records[0]
+            .error
+            .as_deref()
+            .unwrap()
+            .starts_with("rate limited"));
+        assert!(records.iter().all(|r| r.prompt.starts_with("[sha256:")));
+        assert!(!format!("{records:?}").contains("sk-live"));
+
+        // Primary stays rate limited past its retries, then is out of replies
+        let executor = executor_with(vec![limited(), limited(), limited()]);
+        let call = executor.call(&request).await.unwrap();
+        assert_eq!((call.provider.as_str(), call.attempts), ("fallback", 4));
+        let usage = executor.budget().usage();
+        assert_eq!(usage.total.calls, 1);
+        assert_eq!(usage.by_provider["fallback"].tokens.total(), 750);
+
+        // A wait beyond the longest backoff fails over immediately
+        let long = Err(LlmError::RateLimited {
+            retry_after: Some(Duration::from_secs(60)),
+        });
+        let call = executor_with(vec![long]).call(&request).await.unwrap();
+        assert_eq!(call.attempts, 2);
+    }
+
+    fn executor_with(primary: Vec<Result<LlmResponse, LlmError>>) -> LlmExecutor {
+        executor(BudgetManager::new())
+            .with_max_retries(2)
+            .with_provider(Scripted::new("primary", primary))
+            .with_provider(Scripted::new("fallback", vec![ok("from fallback")]))
+    }
+
+    #[tokio::test]
+    async fn stops_on_rejection_budget_or_total_failure() {
+        let request = LlmRequest::new("prompt");
+
+        let rejected = executor_with(vec![Err(LlmError::Rejected("too long".to_string()))]);
+        assert_eq!(
+            rejected.call(&request).await,
+            Err(LlmError::Rejected("too long".to_string()))
+        );
+
+        let budgeted = executor(BudgetManager::new().with_max_cost(0.5))
+            .with_provider(Scripted::new("primary", vec![ok("one"), ok("two")]));
+        assert!(budgeted.call(&request).await.is_ok());
+        assert!(matches!(
+            budgeted.call(&request).await,
+            Err(LlmError::BudgetExhausted(_))
+        ));
+
+        let down = executor(BudgetManager::new())
+            .with_provider(Scripted::new("a", vec![]))
+            .with_provider(Scripted::new("b", vec![]));
+        let Err(LlmError::AllProvidersFailed(failures)) = down.call(&request).await else {
+            panic!("expected every provider to fail");
+        };
+        assert_eq!(failures.len(), 2);
+        assert!(failures[0].starts_with("a: provider unavailable"));
+
+        assert_eq!(
+            executor(BudgetManager::new()).call(&request).await,
+            Err(LlmError::NoProviders)
+        );
+    }
+
+    #[tokio::test]
+    async fn records_and_replays_responses_through_the_trace() {
+        let request = LlmRequest::new("summarise");
+        let recorder = Trace::record();
+        let live = executor(BudgetManager::new())
+            .with_trace(recorder.clone())
+            .with_clock(Arc::new(MonotonicClock::new(0, 2)))
+            .with_provider(Scripted::new("primary", vec![limited(), ok("hello")]));
+        let recorded = live.call(&request).await.unwrap();
+        assert_eq!(live.take_records()[1].latency, Duration::from_secs(2));
+
+        // Only the successful response is recorded; replay never calls out
+        let replayer = Trace::replay(recorder.snapshot());
+        let replay = executor(BudgetManager::new())
+            .with_trace(replayer.clone())
+            .with_provider(Scripted::new("primary", vec![]));
+        let replayed = replay.call(&request).await.unwrap();
+        assert_eq!(replayed.response, recorded.response);
+        assert_eq!(replay.budget().usage().total.tokens.total(), 750);
+        assert_eq!(replayer.remaining(), 0);
+
+        assert!(matches!(
+            replay.call(&LlmRequest::new("other")).await,
+            Err(LlmError::Replay(_))
+        ));
+    }
+}
@@ -0,0 +1,1181 @@
+//! Remote agent workers
+//!
+//! Lets [`AgentPool`](crate::AgentPool) dispatch tasks to agent workers that
+//! run out of process or on other machines, so agent fleets can scale
+//! horizontally.
+//!
+//! # Protocol
+//!
+//! JSON [`WorkerFrame`]s over a [`WorkerConnection`]:
+//!
+//! ```text
+//! worker                              orchestrator
+//!   ── Hello {roles, capabilities} ──►
+//!   ◄── Welcome {session_id} ─────────   (or Reject)
+//!   ◄── Dispatch (task) ──────────────
+//!   ── Delta {task_id, delta} ───────►   zero or more, streamed
+//!   ── Completed / Failed ───────────►
+//! ```
+//!
+//! A worker that completes the handshake with a session key
+//! ([`WorkerSession::handshake_signed`]) advertises its public half in the
+//! hello and signs every delta it streams; [`RemoteWorker::delta_verifier`]
+//! checks them on the orchestrator side.
+//!
+//! If the connection drops, [`RemoteWorker`] redials with exponential
+//! backoff ([`ReconnectPolicy`]), repeats the handshake and re-dispatches
+//! every task that had not completed.
+//!
+//! # Transports
+//! - WebSocket: [`WebSocketConnector`] (orchestrator) / [`WebSocketListener`] (worker)
+//! - In-memory: [`ChannelConnector`] / [`ChannelListener`] (in-process workers, tests)
+//!
+//! Other transports (e.g. gRPC streams) plug in by implementing
+//! [`WorkerConnector`] and [`WorkerConnection`].
+
+use crate::agent_pool::TaskResult;
+use crate::error::TransportError;
+use crate::types::{Task, TaskId};
+use async_trait::async_trait;
+use coa_artifact::{DeltaEnvelope, DeltaVerifier};
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+/// Wire protocol version; both sides must match
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Frames buffered per direction of an in-memory connection, and commands
+/// queued for a worker
+pub const FRAME_BUFFER: usize = 100;
+
+/// Worker events buffered before the session stops reading from the worker
+pub const EVENT_BUFFER: usize = 100;
+
+/// Capability/role advertisement sent by a worker on connect
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkerHello {
+    /// Stable worker identifier (host/process name)
+    pub worker_id: String,
+    /// Protocol version spoken by the worker
+    pub protocol_version: u32,
+    /// Agent roles the worker can run
+    pub roles: Vec<String>,
+    /// Free-form capabilities (languages, tools, GPU, ...)
+    pub capabilities: Vec<String>,
+    /// Maximum tasks executed concurrently
+    pub max_concurrency: usize,
+    /// Public half of the session key the worker signs deltas with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_key: Option<VerifyingKey>,
+}
+
+impl WorkerHello {
+    /// Create hello for the current protocol version
+    #[must_use]
+    pub fn new<I, S>(worker_id: impl Into<String>, roles: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            worker_id: worker_id.into(),
+            protocol_version: PROTOCOL_VERSION,
+            roles: roles.into_iter().map(Into::into).collect(),
+            capabilities: Vec::new(),
+            max_concurrency: 1,
+            signing_key: None,
+        }
+    }
+
+    /// Add capability
+    #[must_use]
+    pub fn with_capability(mut self, capability: impl Into<String>) -> Self {
+        self.capabilities.push(capability.into());
+        self
+    }
+
+    /// Set maximum concurrency
+    #[inline]
+    #[must_use]
+    pub fn with_max_concurrency(mut self, max: usize) -> Self {
+        self.max_concurrency = max;
+        self
+    }
+
+    /// Check if worker runs the given role
+    #[must_use]
+    pub fn supports_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|r| r == role)
+    }
+}
+
+/// Message exchanged between orchestrator and worker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "payload", rename_all = "snake_case")]
+pub enum WorkerFrame {
+    /// Worker → orchestrator: capability advertisement
+    Hello(Box<WorkerHello>),
+    /// Orchestrator → worker: handshake accepted
+    Welcome { session_id: String },
+    /// Orchestrator → worker: handshake refused
+    Reject { reason: String },
+    /// Orchestrator → worker: execute task
+    Dispatch(Box<Task>),
+    /// Worker → orchestrator: intermediate delta for a running task
+    Delta {
+        task_id: TaskId,
+        delta: Box<DeltaEnvelope>,
+    },
+    /// Worker → orchestrator: task finished
+    Completed { task_id: TaskId, result: TaskResult },
+    /// Worker → orchestrator: task failed
+    Failed { task_id: TaskId, error: String },
+    /// Orchestrator → worker: pause execution
+    Pause,
+    /// Orchestrator → worker: resume execution
+    Resume,
+    /// Either direction: liveness probe (answered with `Heartbeat`)
+    Heartbeat,
+    /// Orchestrator → worker: close session
+    Shutdown,
+}
+
+impl WorkerFrame {
+    /// Encode as JSON text
+    ///
+    /// # Errors
+    /// Returns error if serialization fails
+    pub fn encode(&self) -> Result<String, TransportError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Decode from JSON text
+    ///
+    /// # Errors
+    /// Returns error if the text is not a valid frame
+    pub fn decode(text: &str) -> Result<Self, TransportError> {
+        Ok(serde_json::from_str(text)?)
+    }
+}
+
+/// Bidirectional frame stream to a single peer
+#[async_trait]
+pub trait WorkerConnection: Send {
+    /// Send frame
+    ///
+    /// # Errors
+    /// Returns error if the connection is broken
+    async fn send(&mut self, frame: WorkerFrame) -> Result<(), TransportError>;
+
+    /// Receive next frame; `Ok(None)` when the peer closed the connection
+    ///
+    /// # Errors
+    /// Returns error if the connection is broken or a frame is malformed
+    async fn recv(&mut self) -> Result<Option<WorkerFrame>, TransportError>;
+}
+
+/// Dials a worker endpoint (used for initial connect and reconnection)
+#[async_trait]
+pub trait WorkerConnector: Send + Sync + fmt::Debug {
+    /// Endpoint description for logs and errors
+    fn endpoint(&self) -> String;
+
+    /// Open new connection
+    ///
+    /// # Errors
+    /// Returns `TransportError::ConnectFailed` if the endpoint is unreachable
+    async fn connect(&self) -> Result<Box<dyn WorkerConnection>, TransportError>;
+}
+
+/// In-memory connection (one end of a [`ChannelConnector`] link)
+#[derive(Debug)]
+pub struct ChannelConnection {
+    tx: mpsc::Sender<WorkerFrame>,
+    rx: mpsc::Receiver<WorkerFrame>,
+}
+
+/// Create a connected pair of in-memory connections
+///
+/// Each direction buffers [`FRAME_BUFFER`] frames; `send` waits while the
+/// peer's buffer is full.
+#[must_use]
+pub fn channel_pair() -> (ChannelConnection, ChannelConnection) {
+    let (a_tx, b_rx) = mpsc::channel(FRAME_BUFFER);
+    let (b_tx, a_rx) = mpsc::channel(FRAME_BUFFER);
+    (
+        ChannelConnection { tx: a_tx, rx: a_rx },
+        ChannelConnection { tx: b_tx, rx: b_rx },
+    )
+}
+
+#[async_trait]
+impl WorkerConnection for ChannelConnection {
+    async fn send(&mut self, frame: WorkerFrame) -> Result<(), TransportError> {
+        self.tx
+            .send(frame)
+            .await
+            .map_err(|_| TransportError::Closed)
+    }
+
+    async fn recv(&mut self) -> Result<Option<WorkerFrame>, TransportError> {
+        Ok(self.rx.recv().await)
+    }
+}
+
+/// Connections a [`ChannelConnector`] queues for its listener
+const PENDING_CONNECTIONS: usize = 16;
+
+/// In-memory connector; every `connect` hands a fresh link to the listener
+#[derive(Debug, Clone)]
+pub struct ChannelConnector {
+    incoming: mpsc::Sender<ChannelConnection>,
+}
+
+/// Worker side of a [`ChannelConnector`]
+#[derive(Debug)]
+pub struct ChannelListener {
+    incoming: mpsc::Receiver<ChannelConnection>,
+}
+
+impl ChannelConnector {
+    /// Create connector and the listener its connections arrive on
+    #[must_use]
+    pub fn new() -> (Self, ChannelListener) {
+        let (tx, rx) = mpsc::channel(PENDING_CONNECTIONS);
+        (Self { incoming: tx }, ChannelListener { incoming: rx })
+    }
+}
+
+impl ChannelListener {
+    /// Wait for next connection; `None` once the connector is dropped
+    pub async fn accept(&mut self) -> Option<ChannelConnection> {
+        self.incoming.recv().await
+    }
+}
+
+#[async_trait]
+impl WorkerConnector for ChannelConnector {
+    fn endpoint(&self) -> String {
+        "memory".to_string()
+    }
+
+    async fn connect(&self) -> Result<Box<dyn WorkerConnection>, TransportError> {
+        let (local, remote) = channel_pair();
+        self.incoming
+            .send(remote)
+            .await
+            .map_err(|_| TransportError::ConnectFailed {
+                endpoint: self.endpoint(),
+                message: "listener dropped".to_string(),
+            })?;
+        Ok(Box::new(local))
+    }
+}
+
+/// WebSocket connection carrying JSON text frames
+pub struct WebSocketConnection<S> {
+    stream: WebSocketStream<S>,
+}
+
+impl<S> fmt::Debug for WebSocketConnection<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebSocketConnection")
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl<S> WorkerConnection for WebSocketConnection<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send,
+{
+    async fn send(&mut self, frame: WorkerFrame) -> Result<(), TransportError> {
+        self.stream
+            .send(Message::Text(frame.encode()?))
+            .await
+            .map_err(|e| TransportError::Io(e.to_string()))
+    }
+
+    async fn recv(&mut self) -> Result<Option<WorkerFrame>, TransportError> {
+        loop {
+            let message = match self.stream.next().await {
+                None => return Ok(None),
+                Some(message) => message.map_err(|e| TransportError::Io(e.to_string()))?,
+            };
+            match message {
+                Message::Text(text) => return WorkerFrame::decode(&text).map(Some),
+                Message::Binary(bytes) => {
+                    return Ok(Some(serde_json::from_slice(&bytes)?));
+                }
+                Message::Close(_) => return Ok(None),
+                Message::Ping(_) | Message::Pong(_) | Message::Frame(_) => {}
+            }
+        }
+    }
+}
+
+/// Dials a worker's WebSocket endpoint (`ws://host:port/...`)
+#[derive(Debug, Clone)]
+pub struct WebSocketConnector {
+    url: String,
+}
+
+impl WebSocketConnector {
+    /// Create connector for URL
+    #[inline]
+    #[must_use]
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into() }
+    }
+}
+
+#[async_trait]
+impl WorkerConnector for WebSocketConnector {
+    fn endpoint(&self) -> String {
+        self.url.clone()
+    }
+
+    async fn connect(&self) -> Result<Box<dyn WorkerConnection>, TransportError> {
+        let (stream, _response) = tokio_tungstenite::connect_async(self.url.as_str())
+            .await
+            .map_err(|e| TransportError::ConnectFailed {
+                endpoint: self.url.clone(),
+                message: e.to_string(),
+            })?;
+        Ok(Box::new(WebSocketConnection { stream }))
+    }
+}
+
+/// Accepts orchestrator connections on a worker host
+#[derive(Debug)]
+pub struct WebSocketListener {
+    listener: TcpListener,
+}
+
+impl WebSocketListener {
+    /// Bind listener
+    ///
+    /// # Errors
+    /// Returns error if the address cannot be bound
+    pub async fn bind(addr: impl ToSocketAddrs) -> Result<Self, TransportError> {
+        let listener = TcpListener::bind(addr)
+            .await
+            .map_err(|e| TransportError::Io(e.to_string()))?;
+        Ok(Self { listener })
+    }
+
+    /// Local address (useful with port 0)
+    ///
+    /// # Errors
+    /// Returns error if the socket has no local address
+    pub fn local_addr(&self) -> Result<std::net::SocketAddr, TransportError> {
+        self.listener
+            .local_addr()
+            .map_err(|e| TransportError::Io(e.to_string()))
+    }
+
+    /// Accept next connection and complete the WebSocket upgrade
+    ///
+    /// # Errors
+    /// Returns error if accepting or upgrading fails
+    pub async fn accept(&self) -> Result<WebSocketConnection<TcpStream>, TransportError> {
+        let (tcp, _peer) = self
+            .listener
+            .accept()
+            .await
+            .map_err(|e| TransportError::Io(e.to_string()))?;
+        let stream = tokio_tungstenite::accept_async(tcp)
+            .await
+            .map_err(|e| TransportError::Handshake(e.to_string()))?;
+        Ok(WebSocketConnection { stream })
+    }
+}
+
+/// Worker-side session after a successful handshake
+pub struct WorkerSession {
+    conn: Box<dyn WorkerConnection>,
+    session_id: String,
+    worker_id: String,
+    key: Option<SigningKey>,
+}
+
+impl fmt::Debug for WorkerSession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WorkerSession")
+            .field("session_id", &self.session_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl WorkerSession {
+    /// Advertise capabilities and wait for the orchestrator's answer
+    ///
+    /// # Errors
+    /// - `TransportError::Rejected` if the orchestrator refuses the worker
+    /// - `TransportError::Handshake` on an unexpected reply
+    pub async fn handshake(
+        mut conn: Box<dyn WorkerConnection>,
+        hello: WorkerHello,
+    ) -> Result<Self, TransportError> {
+        let worker_id = hello.worker_id.clone();
+        conn.send(WorkerFrame::Hello(Box::new(hello))).await?;
+        match conn.recv().await? {
+            Some(WorkerFrame::Welcome { session_id }) => Ok(Self {
+                conn,
+                session_id,
+                worker_id,
+                key: None,
+            }),
+            Some(WorkerFrame::Reject { reason }) => Err(TransportError::Rejected(reason)),
+            Some(other) => Err(TransportError::Handshake(format!(
+                "expected welcome, got {other:?}"
+            ))),
+            None => Err(TransportError::Closed),
+        }
+    }
+
+    /// Handshake advertising `key`, then sign every streamed delta with it
+    /// as the worker
+    ///
+    /// # Errors
+    /// Same as [`handshake`](Self::handshake)
+    pub async fn handshake_signed(
+        conn: Box<dyn WorkerConnection>,
+        mut hello: WorkerHello,
+        key: SigningKey,
+    ) -> Result<Self, TransportError> {
+        hello.signing_key = Some(key.verifying_key());
+        let mut session = Self::handshake(conn, hello).await?;
+        session.key = Some(key);
+        Ok(session)
+    }
+
+    /// Session identifier assigned by the orchestrator
+    #[inline]
+    #[must_use]
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// Wait for next task
+    ///
+    /// Answers heartbeats; `Pause`/`Resume` are advisory and skipped.
+    /// Returns `Ok(None)` on `Shutdown` or when the orchestrator disconnects.
+    ///
+    /// # Errors
+    /// Returns error if the connection is broken
+    pub async fn next_task(&mut self) -> Result<Option<Task>, TransportError> {
+        loop {
+            match self.conn.recv().await? {
+                Some(WorkerFrame::Dispatch(task)) => return Ok(Some(*task)),
+                Some(WorkerFrame::Heartbeat) => self.conn.send(WorkerFrame::Heartbeat).await?,
+                Some(WorkerFrame::Shutdown) | None => return Ok(None),
+                Some(_) => {}
+            }
+        }
+    }
+
+    /// Stream an intermediate delta for a running task
+    ///
+    /// Signed with the session key, if the handshake advertised one.
+    ///
+    /// # Errors
+    /// Returns error if the connection is broken
+    pub async fn send_delta(
+        &mut self,
+        task_id: TaskId,
+        delta: DeltaEnvelope,
+    ) -> Result<(), TransportError> {
+        let delta = match &self.key {
+            Some(key) => delta.sign(&self.worker_id, key),
+            None => delta,
+        };
+        self.conn
+            .send(WorkerFrame::Delta {
+                task_id,
+                delta: Box::new(delta),
+            })
+            .await
+    }
+
+    /// Report task completion
+    ///
+    /// # Errors
+    /// Returns error if the connection is broken
+    pub async fn complete(
+        &mut self,
+        task_id: TaskId,
+        result: TaskResult,
+    ) -> Result<(), TransportError> {
+        self.conn
+            .send(WorkerFrame::Completed { task_id, result })
+            .await
+    }
+
+    /// Report task failure
+    ///
+    /// # Errors
+    /// Returns error if the connection is broken
+    pub async fn fail(
+        &mut self,
+        task_id: TaskId,
+        error: impl Into<String>,
+    ) -> Result<(), TransportError> {
+        let error = error.into();
+        self.conn.send(WorkerFrame::Failed { task_id, error }).await
+    }
+}
+
+/// Reconnection behavior for [`RemoteWorker`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    /// Attempts before giving up (0 disables reconnection)
+    pub max_attempts: u32,
+    /// Delay before the first attempt
+    pub initial_backoff: Duration,
+    /// Upper bound for the exponential backoff
+    pub max_backoff: Duration,
+}
+
+impl ReconnectPolicy {
+    /// Never reconnect
+    #[inline]
+    #[must_use]
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 0,
+            ..Self::default()
+        }
+    }
+
+    /// Delay before attempt `n` (0-based): doubles each attempt, capped
+    #[must_use]
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff)
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Worker activity reported to the orchestrator
+#[derive(Debug, Clone)]
+pub enum WorkerEvent {
+    /// Intermediate delta streamed by a running task
+    Delta {
+        task_id: TaskId,
+        delta: Box<DeltaEnvelope>,
+    },
+    /// Task finished
+    Completed { task_id: TaskId, result: TaskResult },
+    /// Task failed (on the worker, or because the worker became unreachable)
+    Failed { task_id: TaskId, error: String },
+    /// Connection lost; reconnection in progress
+    Disconnected { error: String },
+    /// Connection restored; unfinished tasks were re-dispatched
+    Reconnected {
+        session_id: String,
+        redispatched: usize,
+    },
+    /// Session ended (shutdown or reconnection gave up)
+    Closed,
+}
+
+/// Orchestrator-side handle to a connected worker
+#[derive(Debug)]
+pub struct RemoteWorker {
+    hello: WorkerHello,
+    session_id: String,
+    endpoint: String,
+    commands: mpsc::Sender<WorkerFrame>,
+    events: Option<mpsc::Receiver<WorkerEvent>>,
+}
+
+impl RemoteWorker {
+    /// Connect, perform the handshake and start the session driver
+    ///
+    /// # Errors
+    /// Returns error if the initial connection or handshake fails
+    pub async fn connect(
+        connector: Arc<dyn WorkerConnector>,
+        policy: ReconnectPolicy,
+    ) -> Result<Self, TransportError> {
+        let endpoint = connector.endpoint();
+        let mut conn = connector.connect().await?;
+        let session_id = ulid::Ulid::new().to_string();
+        let hello = accept_hello(conn.as_mut(), &session_id).await?;
+
+        let (commands, command_rx) = mpsc::channel(FRAME_BUFFER);
+        let (event_tx, events) = mpsc::channel(EVENT_BUFFER);
+        tokio::spawn(drive_session(conn, connector, policy, command_rx, event_tx));
+
+        tracing::debug!(worker = %hello.worker_id, %endpoint, %session_id, "remote worker connected");
+        Ok(Self {
+            hello,
+            session_id,
+            endpoint,
+            commands,
+            events: Some(events),
+        })
+    }
+
+    /// Worker's capability advertisement
+    #[inline]
+    #[must_use]
+    pub fn hello(&self) -> &WorkerHello {
+        &self.hello
+    }
+
+    /// Session identifier of the initial handshake
+    #[inline]
+    #[must_use]
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// Verifier for the deltas this worker streams
+    ///
+    /// Trusts the session key advertised in the hello; a worker that
+    /// advertised one must sign every delta.
+    #[must_use]
+    pub fn delta_verifier(&self) -> DeltaVerifier {
+        match self.hello.signing_key {
+            Some(key) => DeltaVerifier::new()
+                .with_key(self.hello.worker_id.clone(), key)
+                .requiring_signatures(),
+            None => DeltaVerifier::new(),
+        }
+    }
+
+    /// Endpoint the worker was dialed at
+    #[inline]
+    #[must_use]
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Dispatch task to the worker
+    ///
+    /// # Errors
+    /// Returns `TransportError::Closed` if the session has ended
+    pub async fn dispatch(&self, task: Task) -> Result<(), TransportError> {
+        self.send(WorkerFrame::Dispatch(Box::new(task))).await
+    }
+
+    /// Close the session
+    ///
+    /// # Errors
+    /// Returns `TransportError::Closed` if the session has already ended
+    pub async fn shutdown(&self) -> Result<(), TransportError> {
+        self.send(WorkerFrame::Shutdown).await
+    }
+
+    /// Take the event stream (deltas, completions, connection changes)
+    ///
+    /// At most [`EVENT_BUFFER`] events are held; beyond that the session
+    /// stops reading from the worker, whose sends then wait. Drain the
+    /// stream, or drop it to discard events.
+    ///
+    /// Returns `None` after the first call.
+    pub fn take_events(&mut self) -> Option<mpsc::Receiver<WorkerEvent>> {
+        self.events.take()
+    }
+
+    /// Outbound frame queue (shared with pool agent handles)
+    pub(crate) fn command_sender(&self) -> mpsc::Sender<WorkerFrame> {
+        self.commands.clone()
+    }
+
+    async fn send(&self, frame: WorkerFrame) -> Result<(), TransportError> {
+        self.commands
+            .send(frame)
+            .await
+            .map_err(|_| TransportError::Closed)
+    }
+}
+
+/// Orchestrator side of the handshake
+async fn accept_hello(
+    conn: &mut dyn WorkerConnection,
+    session_id: &str,
+) -> Result<WorkerHello, TransportError> {
+    match conn.recv().await? {
+        Some(WorkerFrame::Hello(hello)) if hello.protocol_version == PROTOCOL_VERSION => {
+            conn.send(WorkerFrame::Welcome {
+                session_id: session_id.to_string(),
+            })
+            .await?;
+            Ok(*hello)
+        }
+        Some(WorkerFrame::Hello(hello)) => {
+            let reason = format!(
+                "protocol version {} not supported (expected {PROTOCOL_VERSION})",
+                hello.protocol_version
+            );
+            let _ = conn.send(WorkerFrame::Reject { reason }).await;
+            Err(TransportError::ProtocolMismatch {
+                expected: PROTOCOL_VERSION,
+                actual: hello.protocol_version,
+            })
+        }
+        Some(other) => Err(TransportError::Handshake(format!(
+            "expected hello, got {other:?}"
+        ))),
+        None => Err(TransportError::Closed),
+    }
+}
+
+/// Redial with backoff until a handshake succeeds or attempts run out
+async fn reconnect(
+    connector: &dyn WorkerConnector,
+    policy: ReconnectPolicy,
+) -> Option<(Box<dyn WorkerConnection>, String)> {
+    for attempt in 0..policy.max_attempts {
+        tokio::time::sleep(policy.backoff(attempt)).await;
+        let Ok(mut conn) = connector.connect().await else {
+            continue;
+        };
+        let session_id = ulid::Ulid::new().to_string();
+        if accept_hello(conn.as_mut(), &session_id).await.is_ok() {
+            return Some((conn, session_id));
+        }
+    }
+    None
+}
+
+/// Session driver: forwards commands, surfaces worker frames as events and
+/// handles reconnection
+async fn drive_session(
+    mut conn: Box<dyn WorkerConnection>,
+    connector: Arc<dyn WorkerConnector>,
+    policy: ReconnectPolicy,
+    mut commands: mpsc::Receiver<WorkerFrame>,
+    events: mpsc::Sender<WorkerEvent>,
+) {
+    let mut in_flight: HashMap<TaskId, Task> = HashMap::new();
+
+    loop {
+        let failure = tokio::select! {
+            command = commands.recv() => {
+                let Some(frame) = command else {
+                    // Every handle dropped
+                    let _ = conn.send(WorkerFrame::Shutdown).await;
+                    break;
+                };
+                if let WorkerFrame::Dispatch(task) = &frame {
+                    in_flight.insert(task.id, (**task).clone());
+                }
+                let shutdown = matches!(frame, WorkerFrame::Shutdown);
+                match conn.send(frame).await {
+                    Ok(()) if shutdown => break,
+                    Ok(()) => continue,
+                    Err(e) => e.to_string(),
+                }
+            }
+            inbound = conn.recv() => match inbound {
+                Ok(Some(frame)) => {
+                    handle_inbound(frame, conn.as_mut(), &mut in_flight, &events).await;
+                    continue;
+                }
+                Ok(None) => "worker closed connection".to_string(),
+                Err(e) => e.to_string(),
+            },
+        };
+
+        tracing::warn!(endpoint = %connector.endpoint(), error = %failure, "remote worker disconnected");
+        let _ = events
+            .send(WorkerEvent::Disconnected { error: failure })
+            .await;
+
+        let Some((new_conn, session_id)) = reconnect(connector.as_ref(), policy).await else {
+            for task_id in in_flight.into_keys() {
+                let _ = events
+                    .send(WorkerEvent::Failed {
+                        task_id,
+                        error: "worker unreachable".to_string(),
+                    })
+                    .await;
+            }
+            break;
+        };
+        conn = new_conn;
+
+        let mut redispatched = 0;
+        for task in in_flight.values() {
+            if conn
+                .send(WorkerFrame::Dispatch(Box::new(task.clone())))
+                .await
+                .is_ok()
+            {
+                redispatched += 1;
+            }
+        }
+        let _ = events
+            .send(WorkerEvent::Reconnected {
+                session_id,
+                redispatched,
+            })
+            .await;
+    }
+
+    let _ = events.send(WorkerEvent::Closed).await;
+}
+
+async fn handle_inbound(
+    frame: WorkerFrame,
+    conn: &mut dyn WorkerConnection,
+    in_flight: &mut HashMap<TaskId, Task>,
+    events: &mpsc::Sender<WorkerEvent>,
+) {
+    let event = match frame {
+        WorkerFrame::Delta { task_id, delta } => WorkerEvent::Delta { task_id, delta },
+        WorkerFrame::Completed { task_id, result } => {
+            in_flight.remove(&task_id);
+            WorkerEvent::Completed { task_id, result }
+        }
+        WorkerFrame::Failed { task_id, error } => {
+            in_flight.remove(&task_id);
+            WorkerEvent::Failed { task_id, error }
+        }
+        WorkerFrame::Heartbeat => {
+            let _ = conn.send(WorkerFrame::Heartbeat).await;
+            return;
+        }
+        other => {
+            tracing::debug!(frame = ?other, "ignoring unexpected frame from worker");
+            return;
+        }
+    };
+    // Waits while the event stream is full, pausing reads from the worker
+    let _ = events.send(event).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent_pool::ExecutionMetrics;
+    use coa_artifact::{DeltaOperation, StructuralDelta, SymbolPath};
+    use coa_constitutional::parsers::{JsonArtifact, JsonContent};
+    use std::str::FromStr;
+
+    fn task(role: &str) -> Task {
+        Task::new(role, "write code", SymbolPath::from_str("src.lib").unwrap())
+    }
+
+    fn result() -> TaskResult {
+        TaskResult {
+            delta_ref: Some("delta-1".to_string()),
+            metrics: ExecutionMetrics::default(),
+        }
+    }
+
+    fn envelope() -> DeltaEnvelope {
+        let delta = StructuralDelta::<JsonArtifact>::new(
+            SymbolPath::from_str("port").unwrap(),
+            DeltaOperation::Replace(JsonContent::new(serde_json::json!(8080))),
+            coa_artifact::ContentHash::compute(b"base"),
+        );
+        DeltaEnvelope::seal(&delta).unwrap()
+    }
+
+    async fn next_event(events: &mut mpsc::Receiver<WorkerEvent>) -> WorkerEvent {
+        tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .expect("event timeout")
+            .expect("event stream open")
+    }
+
+    #[test]
+    fn frame_json_roundtrip() {
+        let frame = WorkerFrame::Dispatch(Box::new(task("coder")));
+        let decoded = WorkerFrame::decode(&frame.encode().unwrap()).unwrap();
+        assert!(matches!(decoded, WorkerFrame::Dispatch(t) if t.role == "coder"));
+    }
+
+    #[test]
+    fn reconnect_backoff_is_capped() {
+        let policy = ReconnectPolicy::default();
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(400));
+        assert_eq!(policy.backoff(20), policy.max_backoff);
+    }
+
+    #[tokio::test]
+    async fn dispatch_streams_deltas_and_completion() {
+        let (connector, mut listener) = ChannelConnector::new();
+        let worker = tokio::spawn(async move {
+            let conn = listener.accept().await.unwrap();
+            let hello = WorkerHello::new("w1", ["coder"]).with_capability("rust");
+            let mut session = WorkerSession::handshake(Box::new(conn), hello)
+                .await
+                .unwrap();
+            let task = session.next_task().await.unwrap().unwrap();
+            session.send_delta(task.id, envelope()).await.unwrap();
+            session.complete(task.id, result()).await.unwrap();
+            assert!(session.next_task().await.unwrap().is_none());
+        });
+
+        let mut remote = RemoteWorker::connect(Arc::new(connector), ReconnectPolicy::none())
+            .await
+            .unwrap();
+        assert!(remote.hello().supports_role("coder"));
+        assert_eq!(remote.hello().capabilities, vec!["rust"]);
+        let mut events = remote.take_events().unwrap();
+
+        let task = task("coder");
+        let task_id = task.id;
+        remote.dispatch(task).await.unwrap();
+
+        assert!(matches!(
+            next_event(&mut events).await,
+            WorkerEvent::Delta { task_id: id, ref delta } if id == task_id && delta.type_id() == "json"
+        ));
+        assert!(matches!(
+            next_event(&mut events).await,
+            WorkerEvent::Completed { task_id: id, .. } if id == task_id
+        ));
+
+        remote.shutdown().await.unwrap();
+        assert!(matches!(next_event(&mut events).await, WorkerEvent::Closed));
+        worker.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn signed_session_signs_streamed_deltas() {
+        let key = SigningKey::from_bytes(&[3u8; 32]);
+        let public = key.verifying_key();
+        let (connector, mut listener) = ChannelConnector::new();
+        let worker = tokio::spawn(async move {
+            let conn = listener.accept().await.unwrap();
+            let hello = WorkerHello::new("w1", ["coder"]);
+            let mut session = WorkerSession::handshake_signed(Box::new(conn), hello, key)
+                .await
+                .unwrap();
+            let task = session.next_task().await.unwrap().unwrap();
+            session.send_delta(task.id, envelope()).await.unwrap();
+            assert!(session.next_task().await.unwrap().is_none());
+        });
+
+        let mut remote = RemoteWorker::connect(Arc::new(connector), ReconnectPolicy::none())
+            .await
+            .unwrap();
+        assert_eq!(remote.hello().signing_key, Some(public));
+        let mut events = remote.take_events().unwrap();
+        remote.dispatch(task("coder")).await.unwrap();
+
+        let WorkerEvent::Delta { delta, .. } = next_event(&mut events).await else {
+            panic!("expected delta");
+        };
+        assert_eq!(delta.signature().unwrap().signer, "w1");
+        let opened = remote.delta_verifier().open::<JsonArtifact>(&delta).unwrap();
+        assert_eq!(opened.provenance().unwrap().signers[0].agent_id, "w1");
+        assert!(matches!(
+            remote.delta_verifier().verify(&envelope()),
+            Err(coa_artifact::EnvelopeError::Unsigned)
+        ));
+
+        remote.shutdown().await.unwrap();
+        worker.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn handshake_rejects_protocol_mismatch() {
+        let (connector, mut listener) = ChannelConnector::new();
+        let worker = tokio::spawn(async move {
+            let conn = listener.accept().await.unwrap();
+            let mut hello = WorkerHello::new("old", ["coder"]);
+            hello.protocol_version = PROTOCOL_VERSION + 1;
+            WorkerSession::handshake(Box::new(conn), hello).await
+        });
+
+        let result = RemoteWorker::connect(Arc::new(connector), ReconnectPolicy::none()).await;
+        assert!(matches!(
+            result,
+            Err(TransportError::ProtocolMismatch { .. })
+        ));
+        assert!(matches!(
+            worker.await.unwrap(),
+            Err(TransportError::Rejected(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn reconnect_redispatches_unfinished_tasks() {
+        let (connector, mut listener) = ChannelConnector::new();
+        let worker = tokio::spawn(async move {
+            // First session: receive the task, then drop the connection
+            let conn = listener.accept().await.unwrap();
+            let mut session =
+                WorkerSession::handshake(Box::new(conn), WorkerHello::new("w1", ["coder"]))
+                    .await
+                    .unwrap();
+            let first = session.next_task().await.unwrap().unwrap();
+            drop(session);
+
+            // Second session: the same task arrives again
+            let conn = listener.accept().await.unwrap();
+            let mut session =
+                WorkerSession::handshake(Box::new(conn), WorkerHello::new("w1", ["coder"]))
+                    .await
+                    .unwrap();
+            let again = session.next_task().await.unwrap().unwrap();
+            assert_eq!(first.id, again.id);
+            session.complete(again.id, result()).await.unwrap();
+        });
+
+        let policy = ReconnectPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(10),
+        };
+        let mut remote = RemoteWorker::connect(Arc::new(connector), policy)
+            .await
+            .unwrap();
+        let mut events = remote.take_events().unwrap();
+        remote.dispatch(task("coder")).await.unwrap();
+
+        assert!(matches!(
+            next_event(&mut events).await,
+            WorkerEvent::Disconnected { .. }
+        ));
+        assert!(matches!(
+            next_event(&mut events).await,
+            WorkerEvent::Reconnected {
+                redispatched: 1,
+                ..
+            }
+        ));
+        assert!(matches!(
+            next_event(&mut events).await,
+            WorkerEvent::Completed { .. }
+        ));
+        worker.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn flooding_worker_waits_for_event_consumer() {
+        const DELTAS: usize = 3 * (EVENT_BUFFER + FRAME_BUFFER);
+
+        let (connector, mut listener) = ChannelConnector::new();
+        let worker = tokio::spawn(async move {
+            let conn = listener.accept().await.unwrap();
+            let mut session =
+                WorkerSession::handshake(Box::new(conn), WorkerHello::new("w1", ["coder"]))
+                    .await
+                    .unwrap();
+            let task = session.next_task().await.unwrap().unwrap();
+            for _ in 0..DELTAS {
+                session.send_delta(task.id, envelope()).await.unwrap();
+            }
+            session.complete(task.id, result()).await.unwrap();
+        });
+
+        let mut remote = RemoteWorker::connect(Arc::new(connector), ReconnectPolicy::none())
+            .await
+            .unwrap();
+        let mut events = remote.take_events().unwrap();
+        remote.dispatch(task("coder")).await.unwrap();
+
+        // Nobody reads events, so the worker is held back instead of the
+        // orchestrator buffering every delta
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!worker.is_finished());
+
+        let mut deltas = 0;
+        loop {
+            match next_event(&mut events).await {
+                WorkerEvent::Delta { .. } => deltas += 1,
+                WorkerEvent::Completed { .. } => break,
+                other => panic!("unexpected event {other:?}"),
+            }
+        }
+        assert_eq!(deltas, DELTAS);
+        worker.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn pool_dispatches_to_remote_agents() {
+        let (connector, mut listener) = ChannelConnector::new();
+        let worker = tokio::spawn(async move {
+            let conn = listener.accept().await.unwrap();
+            let hello = WorkerHello::new("w1", ["coder", "tester"]).with_max_concurrency(2);
+            let mut session = WorkerSession::handshake(Box::new(conn), hello)
+                .await
+                .unwrap();
+            let task = session.next_task().await.unwrap().unwrap();
+            session.complete(task.id, result()).await.unwrap();
+            task.role
+        });
+
+        let mut remote = RemoteWorker::connect(Arc::new(connector), ReconnectPolicy::none())
+            .await
+            .unwrap();
+        let mut events = remote.take_events().unwrap();
+
+        let pool = crate::AgentPool::new(4);
+        assert_eq!(pool.register_remote(&remote).await.len(), 2);
+        assert_eq!(pool.stats().await.available_count, 2);
+
+        let agent = pool.acquire(crate::AgentSpec::new("tester")).await.unwrap();
+        agent
+            .send(crate::AgentMessage::Execute(task("tester")))
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            next_event(&mut events).await,
+            WorkerEvent::Completed { .. }
+        ));
+        assert_eq!(worker.await.unwrap(), "tester");
+    }
+
+    #[tokio::test]
+    async fn websocket_transport_roundtrip() {
+        let listener = WebSocketListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+
+        let worker = tokio::spawn(async move {
+            let conn = listener.accept().await.unwrap();
+            let mut session =
+                WorkerSession::handshake(Box::new(conn), WorkerHello::new("ws", ["reviewer"]))
+                    .await
+                    .unwrap();
+            let task = session.next_task().await.unwrap().unwrap();
+            session.fail(task.id, "no reviewer model").await.unwrap();
+        });
+
+        let mut remote = RemoteWorker::connect(
+            Arc::new(WebSocketConnector::new(url)),
+            ReconnectPolicy::none(),
+        )
+        .await
+        .unwrap();
+        let mut events = remote.take_events().unwrap();
+        remote.dispatch(task("reviewer")).await.unwrap();
+
+        assert!(matches!(
+            next_event(&mut events).await,
+            WorkerEvent::Failed { ref error, .. } if error == "no reviewer model"
+        ));
+        worker.await.unwrap();
+    }
+}
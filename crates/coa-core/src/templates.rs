@@ -0,0 +1,455 @@
+//! Decomposition templates
+//!
+//! A [`DecompositionTemplate`] expands a [`Specification`] for a common,
+//! well-understood piece of work straight into a task DAG, instead of the
+//! generic per-goal decomposition. Templates live in a [`TemplateRegistry`]:
+//! a specification either names its template (`Specification::template`) or
+//! the first registered template whose wording matches its acceptance
+//! criteria is used.
+//!
+//! Built-in templates:
+//! - `add_endpoint`: design, handler, route registration, tests
+//! - `write_unit_tests`: analyze module, write tests, run them
+//! - `upgrade_dependency`: review changelog, bump, fix call sites, verify
+//! - `add_cli_flag`: declare flag, wire handling, document, test
+
+use crate::error::DecompositionError;
+use crate::types::{AutonomyLevel, DirectiveValue, ExpansionType, Specification, Task};
+use std::fmt::Debug;
+use std::sync::Arc;
+
+/// Expands a specification into a task DAG
+pub trait DecompositionTemplate: Send + Sync + Debug {
+    /// Name specifications refer to the template by
+    fn name(&self) -> &str;
+
+    /// Whether the template fits a specification that names no template
+    fn matches(&self, spec: &Specification) -> bool;
+
+    /// Expand `spec` into tasks, dependencies before dependents
+    fn expand(&self, spec: &Specification) -> Result<Vec<Task>, DecompositionError>;
+}
+
+/// Registered decomposition templates, in matching order
+#[derive(Debug, Clone, Default)]
+pub struct TemplateRegistry {
+    templates: Vec<Arc<dyn DecompositionTemplate>>,
+}
+
+impl TemplateRegistry {
+    /// Create empty registry
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry with the built-in templates
+    #[must_use]
+    pub fn builtin() -> Self {
+        Self::new()
+            .with(Arc::new(AddEndpoint))
+            .with(Arc::new(WriteUnitTests))
+            .with(Arc::new(UpgradeDependency))
+            .with(Arc::new(AddCliFlag))
+    }
+
+    /// With `template` registered
+    #[inline]
+    #[must_use]
+    pub fn with(mut self, template: Arc<dyn DecompositionTemplate>) -> Self {
+        self.register(template);
+        self
+    }
+
+    /// Register `template`, replacing any template of the same name
+    pub fn register(&mut self, template: Arc<dyn DecompositionTemplate>) {
+        match self
+            .templates
+            .iter_mut()
+            .find(|t| t.name() == template.name())
+        {
+            Some(existing) => *existing = template,
+            None => self.templates.push(template),
+        }
+    }
+
+    /// Template named `name`
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&Arc<dyn DecompositionTemplate>> {
+        self.templates.iter().find(|t| t.name() == name)
+    }
+
+    /// Names of registered templates, in matching order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.templates.iter().map(|t| t.name())
+    }
+
+    /// Template to expand `spec` with, if any
+    ///
+    /// # Errors
+    /// Returns `DecompositionError::UnknownTemplate` if `spec` names a
+    /// template that is not registered
+    pub fn select(
+        &self,
+        spec: &Specification,
+    ) -> Result<Option<&Arc<dyn DecompositionTemplate>>, DecompositionError> {
+        match &spec.template {
+            Some(name) => self
+                .get(name)
+                .map(Some)
+                .ok_or_else(|| DecompositionError::UnknownTemplate(name.clone())),
+            None => Ok(self.templates.iter().find(|t| t.matches(spec))),
+        }
+    }
+}
+
+/// Whether any acceptance criterion mentions all of `words`
+fn criteria_mention(spec: &Specification, words: &[&str]) -> bool {
+    spec.acceptance_criteria.iter().any(|criterion| {
+        let criterion = criterion.to_lowercase();
+        words.iter().all(|word| criterion.contains(word))
+    })
+}
+
+/// Last segment of the target, naming the endpoint, module, dependency or flag
+fn subject(spec: &Specification) -> Result<String, DecompositionError> {
+    spec.target_path.last().map(str::to_string).ok_or_else(|| {
+        DecompositionError::InvalidSpecification("template needs a target path".to_string())
+    })
+}
+
+/// New HTTP endpoint at the target path
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AddEndpoint;
+
+impl DecompositionTemplate for AddEndpoint {
+    fn name(&self) -> &str {
+        "add_endpoint"
+    }
+
+    fn matches(&self, spec: &Specification) -> bool {
+        criteria_mention(spec, &["endpoint"])
+    }
+
+    fn expand(&self, spec: &Specification) -> Result<Vec<Task>, DecompositionError> {
+        let endpoint = subject(spec)?;
+        let target = &spec.target_path;
+        let resource = target.parent().unwrap_or_default();
+        let router = resource.child("routes");
+        let tests_path = resource.child("tests").child(endpoint.as_str());
+
+        let design = Task::new(
+            "architect",
+            format!("Design {endpoint} endpoint contract"),
+            target.clone(),
+        )
+        .with_autonomy(AutonomyLevel::L3)
+        .with_directive("endpoint", DirectiveValue::String(endpoint.clone()))
+        .with_directive(
+            "output_format",
+            DirectiveValue::String("api_spec".to_string()),
+        );
+        let handler = Task::new(
+            "implementer",
+            format!("Implement {endpoint} handler"),
+            target.clone(),
+        )
+        .with_autonomy(AutonomyLevel::L4)
+        .with_claim(target.clone())
+        .with_directive("endpoint", DirectiveValue::String(endpoint.clone()))
+        .depends_on(design.id);
+        let route = Task::new(
+            "implementer",
+            format!("Register {endpoint} route"),
+            router.clone(),
+        )
+        .with_autonomy(AutonomyLevel::L3)
+        .with_claim(router)
+        .depends_on(handler.id);
+        let tests = Task::new(
+            "tester",
+            format!("Test {endpoint} endpoint"),
+            tests_path.clone(),
+        )
+        .with_autonomy(AutonomyLevel::L3)
+        .with_claim(tests_path)
+        .with_directive("coverage_target", DirectiveValue::Int(90))
+        .depends_on(handler.id)
+        .depends_on(route.id);
+
+        Ok(vec![design, handler, route, tests])
+    }
+}
+
+/// Unit tests for the module at the target path
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WriteUnitTests;
+
+impl DecompositionTemplate for WriteUnitTests {
+    fn name(&self) -> &str {
+        "write_unit_tests"
+    }
+
+    fn matches(&self, spec: &Specification) -> bool {
+        criteria_mention(spec, &["unit test"])
+    }
+
+    fn expand(&self, spec: &Specification) -> Result<Vec<Task>, DecompositionError> {
+        let module = subject(spec)?;
+        let target = &spec.target_path;
+        let tests_path = target.child("tests");
+
+        // Analysis only reads the module, so it claims nothing
+        let analyze = Task::new(
+            "analyzer",
+            format!("List behaviours of {module}"),
+            target.clone(),
+        )
+        .with_autonomy(AutonomyLevel::L3);
+        let write = Task::new(
+            "tester",
+            format!("Write unit tests for {module}"),
+            tests_path.clone(),
+        )
+        .with_autonomy(AutonomyLevel::L4)
+        .with_claim(tests_path.clone())
+        .with_directive("coverage_target", DirectiveValue::Int(80))
+        .depends_on(analyze.id);
+        let run = Task::new("verifier", format!("Run {module} unit tests"), tests_path)
+            .with_autonomy(AutonomyLevel::L3)
+            .with_directive("test_filter", DirectiveValue::String(module))
+            .depends_on(write.id);
+
+        Ok(vec![analyze, write, run])
+    }
+}
+
+/// Upgrade of the dependency named by the target path
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UpgradeDependency;
+
+impl DecompositionTemplate for UpgradeDependency {
+    fn name(&self) -> &str {
+        "upgrade_dependency"
+    }
+
+    fn matches(&self, spec: &Specification) -> bool {
+        criteria_mention(spec, &["upgrade"]) || criteria_mention(spec, &["bump"])
+    }
+
+    fn expand(&self, spec: &Specification) -> Result<Vec<Task>, DecompositionError> {
+        let dependency = subject(spec)?;
+        let target = &spec.target_path;
+
+        let review = Task::new(
+            "analyzer",
+            format!("Review {dependency} changelog for breaking changes"),
+            target.clone(),
+        )
+        .with_autonomy(AutonomyLevel::L3)
+        .with_directive("dependency", DirectiveValue::String(dependency.clone()));
+        let bump = Task::new(
+            "modifier",
+            format!("Bump {dependency} version"),
+            target.clone(),
+        )
+        .with_autonomy(AutonomyLevel::L3)
+        .with_claim(target.clone())
+        .with_directive("dependency", DirectiveValue::String(dependency.clone()))
+        .depends_on(review.id);
+        // Call sites are only known after the bump; expand per affected module
+        let migrate = Task::new(
+            "migrator",
+            format!("Adapt call sites to new {dependency}"),
+            target.clone(),
+        )
+        .with_autonomy(AutonomyLevel::L3)
+        .with_expansion(ExpansionType::Parallel { branches: vec![] })
+        .depends_on(bump.id);
+        let verify = Task::new("verifier", "Build and run the test suite", target.clone())
+            .with_autonomy(AutonomyLevel::L3)
+            .depends_on(migrate.id);
+
+        Ok(vec![review, bump, migrate, verify])
+    }
+}
+
+/// New command-line flag on the command at the target path
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AddCliFlag;
+
+impl DecompositionTemplate for AddCliFlag {
+    fn name(&self) -> &str {
+        "add_cli_flag"
+    }
+
+    fn matches(&self, spec: &Specification) -> bool {
+        criteria_mention(spec, &["flag"]) || criteria_mention(spec, &["cli", "option"])
+    }
+
+    fn expand(&self, spec: &Specification) -> Result<Vec<Task>, DecompositionError> {
+        let flag = subject(spec)?;
+        let target = &spec.target_path;
+        let command = target.parent().unwrap_or_default();
+        let args = command.child("args");
+
+        let declare = Task::new(
+            "implementer",
+            format!("Declare --{flag} argument"),
+            args.clone(),
+        )
+        .with_autonomy(AutonomyLevel::L4)
+        .with_claim(args)
+        .with_directive("flag", DirectiveValue::String(flag.clone()));
+        let wire = Task::new("implementer", format!("Handle --{flag}"), command.clone())
+            .with_autonomy(AutonomyLevel::L4)
+            .with_claim(target.clone())
+            .depends_on(declare.id);
+        let document = Task::new(
+            "documenter",
+            format!("Document --{flag}"),
+            command.child("help"),
+        )
+        .with_autonomy(AutonomyLevel::L3)
+        .with_claim(command.child("help"))
+        .depends_on(declare.id);
+        let tests = Task::new("tester", format!("Test --{flag}"), command.child("tests"))
+            .with_autonomy(AutonomyLevel::L3)
+            .with_claim(command.child("tests"))
+            .depends_on(wire.id);
+
+        Ok(vec![declare, wire, document, tests])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Goal;
+    use coa_artifact::SymbolPath;
+    use std::collections::HashSet;
+    use std::str::FromStr;
+
+    fn spec(target: &str, criterion: &str) -> Specification {
+        Specification::new(
+            Goal::CreateNew,
+            "code",
+            SymbolPath::from_str(target).unwrap(),
+        )
+        .with_criteria(vec![criterion.to_string()])
+    }
+
+    /// Dependencies point at earlier tasks and claims never overlap
+    fn assert_well_formed(tasks: &[Task]) {
+        let mut seen = HashSet::new();
+        for task in tasks {
+            assert!(
+                task.dependencies.iter().all(|d| seen.contains(d)),
+                "{}",
+                task.description
+            );
+            seen.insert(task.id);
+        }
+        let claims: Vec<_> = tasks.iter().flat_map(|t| t.claims.iter()).collect();
+        for (i, a) in claims.iter().enumerate() {
+            for b in &claims[i + 1..] {
+                assert!(!a.overlaps(b), "{a} overlaps {b}");
+            }
+        }
+    }
+
+    #[test]
+    fn builtin_templates_expand_to_well_formed_dags() {
+        let registry = TemplateRegistry::builtin();
+        let cases = [
+            (
+                "api.users.create",
+                "Add a create user endpoint",
+                "add_endpoint",
+                4,
+            ),
+            (
+                "core.parser",
+                "Write unit tests for the parser",
+                "write_unit_tests",
+                3,
+            ),
+            (
+                "deps.serde",
+                "Upgrade serde to 2.0",
+                "upgrade_dependency",
+                4,
+            ),
+            (
+                "cli.run.verbose",
+                "Add a verbose flag to run",
+                "add_cli_flag",
+                4,
+            ),
+        ];
+        for (target, criterion, name, len) in cases {
+            let spec = spec(target, criterion);
+            let template = registry.select(&spec).unwrap().unwrap();
+            assert_eq!(template.name(), name);
+            let tasks = template.expand(&spec).unwrap();
+            assert_eq!(tasks.len(), len, "{name}");
+            assert_well_formed(&tasks);
+        }
+
+        let tasks = AddEndpoint.expand(&spec("api.users.create", "")).unwrap();
+        assert_eq!(tasks[3].dependencies, vec![tasks[1].id, tasks[2].id]);
+        assert_eq!(
+            tasks[2].claims,
+            vec![SymbolPath::from_str("api.users.routes").unwrap()]
+        );
+        assert!(registry
+            .select(&spec("core", "Has login function"))
+            .unwrap()
+            .is_none());
+    }
+
+    #[derive(Debug)]
+    struct AddMigration;
+
+    impl DecompositionTemplate for AddMigration {
+        fn name(&self) -> &str {
+            "add_migration"
+        }
+
+        fn matches(&self, spec: &Specification) -> bool {
+            criteria_mention(spec, &["migration"])
+        }
+
+        fn expand(&self, spec: &Specification) -> Result<Vec<Task>, DecompositionError> {
+            Ok(vec![Task::new(
+                "implementer",
+                "Write migration",
+                spec.target_path.clone(),
+            )])
+        }
+    }
+
+    #[test]
+    fn registry_accepts_custom_templates_and_explicit_names() {
+        let mut registry = TemplateRegistry::builtin();
+        registry.register(Arc::new(AddMigration));
+        assert_eq!(registry.names().count(), 5);
+
+        let spec = spec("db.users", "Add a migration for users");
+        assert_eq!(
+            registry.select(&spec).unwrap().unwrap().name(),
+            "add_migration"
+        );
+
+        let named = spec.clone().with_template("write_unit_tests");
+        assert_eq!(
+            registry.select(&named).unwrap().unwrap().name(),
+            "write_unit_tests"
+        );
+        assert!(matches!(
+            registry.select(&spec.with_template("missing")),
+            Err(DecompositionError::UnknownTemplate(name)) if name == "missing"
+        ));
+    }
+}
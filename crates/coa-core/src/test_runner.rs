@@ -0,0 +1,553 @@
+//! Test execution
+//!
+//! [`TestRunner`] runs a project's test suite through the kernel's
+//! subprocess isolation: with a minimal environment, under resource limits
+//! and a timeout, in the root of its filesystem sandbox ([`FsScope`]) and
+//! confined to it where the platform allows. Results are read from libtest
+//! output for cargo (JSON on nightly toolchains, the human format
+//! otherwise) and JUnit XML for pytest. Failures are attributed to the most recently
+//! changed symbol they mention and turned into [`Diagnostic`]s, so gates and
+//! repair steps see which change broke which test.
+
+use crate::error::{Context, Diagnostic, ErrorType, Location, SuggestedFix, TestRunError};
+use coa_artifact::SymbolPath;
+use coa_constitutional::FsScope;
+use coa_kernel::isolation::{clear_env, run_subprocess, SubprocessLimits};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// How long a suite may run by default
+pub const DEFAULT_TEST_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Variables the suite inherits; everything else is cleared
+const INHERITED_ENV: [&str; 10] = [
+    "PATH",
+    "HOME",
+    "USERPROFILE",
+    "CARGO_HOME",
+    "RUSTUP_HOME",
+    "RUSTUP_TOOLCHAIN",
+    "CARGO_TARGET_DIR",
+    "VIRTUAL_ENV",
+    "PYTHONPATH",
+    "LANG",
+];
+
+/// Test framework of the target project
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TestFramework {
+    /// `cargo test`, read as libtest JSON
+    Cargo,
+    /// `pytest`, read as JUnit XML
+    Pytest,
+}
+
+/// Outcome of one test case
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TestOutcome {
+    Passed,
+    Failed { message: String },
+    Ignored,
+}
+
+/// One test case from a report
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestCase {
+    /// Fully qualified test name (`module::test`)
+    pub name: String,
+    pub outcome: TestOutcome,
+}
+
+impl TestCase {
+    /// Whether the test failed
+    #[inline]
+    #[must_use]
+    pub fn failed(&self) -> bool {
+        matches!(self.outcome, TestOutcome::Failed { .. })
+    }
+}
+
+/// Parsed results of one suite run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestReport {
+    pub framework: TestFramework,
+    pub cases: Vec<TestCase>,
+}
+
+impl TestReport {
+    /// Parse libtest JSON output (`--format json`), one event per line
+    ///
+    /// Lines that are not test events are ignored.
+    #[must_use]
+    pub fn from_cargo_json(output: &str) -> Self {
+        let cases = output
+            .lines()
+            .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+            .filter(|event| event["type"] == "test")
+            .filter_map(|event| {
+                let name = event["name"].as_str()?.to_string();
+                let outcome = match event["event"].as_str()? {
+                    "ok" => TestOutcome::Passed,
+                    "ignored" => TestOutcome::Ignored,
+                    "failed" | "timeout" => TestOutcome::Failed {
+                        message: event["stdout"].as_str().unwrap_or_default().trim().to_string(),
+                    },
+                    _ => return None,
+                };
+                Some(TestCase { name, outcome })
+            })
+            .collect();
+        Self {
+            framework: TestFramework::Cargo,
+            cases,
+        }
+    }
+
+    /// Parse libtest's default human-readable output
+    ///
+    /// Reads the `test <name> ... <result>` lines, taking failure messages
+    /// from the `---- <name> stdout ----` sections.
+    #[must_use]
+    pub fn from_cargo_text(output: &str) -> Self {
+        let cases = output
+            .lines()
+            .filter_map(|line| line.strip_prefix("test ")?.split_once(" ... "))
+            .filter_map(|(name, result)| {
+                let outcome = match result.trim() {
+                    "ok" => TestOutcome::Passed,
+                    "FAILED" => TestOutcome::Failed {
+                        message: failure_section(output, name),
+                    },
+                    ignored if ignored.starts_with("ignored") => TestOutcome::Ignored,
+                    _ => return None,
+                };
+                Some(TestCase {
+                    name: name.to_string(),
+                    outcome,
+                })
+            })
+            .collect();
+        Self {
+            framework: TestFramework::Cargo,
+            cases,
+        }
+    }
+
+    /// Parse a JUnit XML report
+    ///
+    /// Reads `<testcase>` elements with their `<failure>`, `<error>` and
+    /// `<skipped>` children; everything else is ignored.
+    #[must_use]
+    pub fn from_junit_xml(xml: &str) -> Self {
+        let mut cases = Vec::new();
+        let mut rest = xml;
+        while let Some(start) = rest.find("<testcase") {
+            rest = &rest[start + "<testcase".len()..];
+            let Some(tag_end) = rest.find('>') else { break };
+            let tag = &rest[..tag_end];
+            let body = if tag.ends_with('/') {
+                ""
+            } else {
+                let end = rest.find("</testcase>").unwrap_or(rest.len());
+                &rest[tag_end + 1..end]
+            };
+
+            let name = xml_attr(tag, "name").unwrap_or_default();
+            let name = match xml_attr(tag, "classname") {
+                Some(class) if !class.is_empty() => format!("{}::{}", class.replace('.', "::"), name),
+                _ => name,
+            };
+            let failure = ["<failure", "<error"]
+                .iter()
+                .find_map(|element| body.find(element).map(|i| &body[i + element.len()..]));
+            let outcome = if let Some(failure) = failure {
+                let tag = &failure[..failure.find('>').unwrap_or(failure.len())];
+                TestOutcome::Failed {
+                    message: xml_attr(tag, "message").unwrap_or_default(),
+                }
+            } else if body.contains("<skipped") {
+                TestOutcome::Ignored
+            } else {
+                TestOutcome::Passed
+            };
+            cases.push(TestCase { name, outcome });
+        }
+        Self {
+            framework: TestFramework::Pytest,
+            cases,
+        }
+    }
+
+    /// Failed test cases
+    pub fn failures(&self) -> impl Iterator<Item = &TestCase> {
+        self.cases.iter().filter(|case| case.failed())
+    }
+
+    /// Whether no test failed
+    #[inline]
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.failures().next().is_none()
+    }
+
+    /// Diagnostics for each failure, attributed to a changed symbol
+    ///
+    /// `changed` lists the symbols touched by recent deltas, oldest first.
+    /// A failure is attributed to the most recent change whose name appears
+    /// in the test path, falling back to the most recent change overall.
+    #[must_use]
+    pub fn diagnostics(&self, changed: &[SymbolPath]) -> Vec<Diagnostic> {
+        self.failures()
+            .map(|case| {
+                let symbol = attribute(&case.name, changed);
+                let message = match &case.outcome {
+                    TestOutcome::Failed { message } => message.as_str(),
+                    _ => "",
+                };
+                let mut context = Context::empty()
+                    .add("test", case.name.as_str())
+                    .add("message", message);
+                let location = match symbol {
+                    Some(symbol) => {
+                        context = context.add("symbol", symbol.to_string());
+                        Location::Node(symbol.to_string())
+                    }
+                    None => Location::Unknown,
+                };
+                let fix = match symbol {
+                    Some(symbol) => format!("Revise the change to {symbol} so {} passes", case.name),
+                    None => format!("Fix failing test {}", case.name),
+                };
+                Diagnostic::new(ErrorType::Test, location)
+                    .with_context(context)
+                    .with_suggestions(vec![SuggestedFix::new(fix, 0.6)])
+            })
+            .collect()
+    }
+}
+
+/// Most recent change in `changed` a test named `test` exercises
+fn attribute<'a>(test: &str, changed: &'a [SymbolPath]) -> Option<&'a SymbolPath> {
+    let test = test.to_lowercase();
+    let parts: Vec<&str> = test.split("::").collect();
+    changed
+        .iter()
+        .rev()
+        .find(|symbol| {
+            symbol.iter().any(|segment| {
+                let segment = segment.to_lowercase();
+                parts.iter().any(|part| *part == segment || part.contains(&segment))
+            })
+        })
+        .or_else(|| changed.last())
+}
+
+/// Value of attribute `name` in the inside of an XML tag
+fn xml_attr(tag: &str, name: &str) -> Option<String> {
+    let pattern = format!(" {name}=\"");
+    let start = tag.find(&pattern)? + pattern.len();
+    let len = tag[start..].find('"')?;
+    Some(
+        tag[start..start + len]
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&amp;", "&"),
+    )
+}
+
+/// Captured output libtest prints for the failed test `name`
+fn failure_section(output: &str, name: &str) -> String {
+    let header = format!("---- {name} stdout ----");
+    let Some(start) = output.find(&header) else {
+        return String::new();
+    };
+    let section = &output[start + header.len()..];
+    let end = ["\n---- ", "\nfailures:"]
+        .iter()
+        .filter_map(|marker| section.find(marker))
+        .min()
+        .unwrap_or(section.len());
+    section[..end].trim().to_string()
+}
+
+/// Runs a project's test suite inside its sandbox
+#[derive(Debug, Clone)]
+pub struct TestRunner {
+    framework: TestFramework,
+    scope: FsScope,
+    program: Option<Vec<String>>,
+    limits: SubprocessLimits,
+    timeout: Duration,
+}
+
+impl TestRunner {
+    /// Create runner for the project at the root of `scope`
+    #[inline]
+    #[must_use]
+    pub fn new(framework: TestFramework, scope: FsScope) -> Self {
+        Self {
+            framework,
+            scope,
+            program: None,
+            limits: SubprocessLimits {
+                address_space_bytes: None,
+                cpu_time_ms: Some(DEFAULT_TEST_TIMEOUT.as_millis() as u64),
+            },
+            timeout: DEFAULT_TEST_TIMEOUT,
+        }
+    }
+
+    /// With the OS limits each process of the suite runs under
+    ///
+    /// Defaults to a CPU-time cap of [`DEFAULT_TEST_TIMEOUT`].
+    #[inline]
+    #[must_use]
+    pub fn with_limits(mut self, limits: SubprocessLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Kill the suite if it runs longer than `timeout`
+    #[inline]
+    #[must_use]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// With the command that starts the suite (`cargo` / `python -m pytest`
+    /// by default); framework arguments are appended to it
+    #[inline]
+    #[must_use]
+    pub fn with_program(mut self, program: Vec<String>) -> Self {
+        self.program = Some(program);
+        self
+    }
+
+    /// Sandbox the suite runs in
+    #[inline]
+    #[must_use]
+    pub fn scope(&self) -> &FsScope {
+        &self.scope
+    }
+
+    /// Run the suite, optionally only tests matching `filter`
+    ///
+    /// # Errors
+    /// Returns `TestRunError` if the suite cannot start, times out or
+    /// yields no results
+    pub async fn run(&self, filter: Option<&str>) -> Result<TestReport, TestRunError> {
+        match self.framework {
+            TestFramework::Cargo => {
+                let mut args = self.program_or(&["cargo"]);
+                args.push("test".to_string());
+                args.extend(filter.map(str::to_string));
+                if self.nightly_toolchain().await {
+                    args.extend(["--", "-Z", "unstable-options", "--format", "json"].map(String::from));
+                }
+                let (stdout, stderr) = self.execute(&args).await?;
+                let mut report = TestReport::from_cargo_json(&stdout);
+                if report.cases.is_empty() {
+                    report = TestReport::from_cargo_text(&stdout);
+                }
+                Self::non_empty(report, &args, &stderr)
+            }
+            TestFramework::Pytest => {
+                let junit = self.junit_path();
+                let mut args = self.program_or(&["python", "-m", "pytest"]);
+                args.push(format!("--junitxml={}", junit.display()));
+                if let Some(filter) = filter {
+                    args.extend(["-k".to_string(), filter.to_string()]);
+                }
+                let result = self.execute(&args).await;
+                let xml = tokio::fs::read_to_string(&junit).await.unwrap_or_default();
+                let _ = tokio::fs::remove_file(&junit).await;
+                let (_, stderr) = result?;
+                Self::non_empty(TestReport::from_junit_xml(&xml), &args, &stderr)
+            }
+        }
+    }
+
+    fn program_or(&self, default: &[&str]) -> Vec<String> {
+        self.program
+            .clone()
+            .unwrap_or_else(|| default.iter().map(|s| (*s).to_string()).collect())
+    }
+
+    /// Report path inside the scope, where the suite may write
+    fn junit_path(&self) -> PathBuf {
+        self.scope
+            .root()
+            .join(format!(".coa-junit-{}.xml", uuid::Uuid::new_v4()))
+    }
+
+    /// Whether the project's toolchain accepts libtest's unstable JSON format
+    async fn nightly_toolchain(&self) -> bool {
+        let version = ["rustc", "--version"].map(String::from);
+        self.execute(&version)
+            .await
+            .is_ok_and(|(stdout, _)| stdout.contains("-nightly") || stdout.contains("-dev"))
+    }
+
+    /// Run `args` isolated in the scope root, returning stdout and stderr
+    async fn execute(&self, args: &[String]) -> Result<(String, String), TestRunError> {
+        let command = args.join(" ");
+        let (program, rest) = args.split_first().ok_or_else(|| TestRunError::Spawn {
+            command: command.clone(),
+            message: "empty command".to_string(),
+        })?;
+        let mut cmd = std::process::Command::new(program);
+        cmd.args(rest).current_dir(self.scope.root());
+        clear_env(&mut cmd);
+        for name in INHERITED_ENV {
+            if let Some(value) = std::env::var_os(name) {
+                cmd.env(name, value);
+            }
+        }
+        let output = run_subprocess(cmd, &self.limits, Some(self.scope.root()), self.timeout)
+            .await
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::TimedOut => TestRunError::TimedOut {
+                    command: command.clone(),
+                    timeout: self.timeout,
+                },
+                _ => TestRunError::Spawn {
+                    command: command.clone(),
+                    message: e.to_string(),
+                },
+            })?;
+        Ok((
+            String::from_utf8_lossy(&output.stdout).into_owned(),
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        ))
+    }
+
+    fn non_empty(report: TestReport, args: &[String], stderr: &str) -> Result<TestReport, TestRunError> {
+        if report.cases.is_empty() {
+            return Err(TestRunError::NoResults {
+                command: args.join(" "),
+                stderr: stderr.lines().last().unwrap_or_default().to_string(),
+            });
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    const CARGO_JSON: &str = r#"{ "type": "suite", "event": "started", "test_count": 3 }
+{ "type": "test", "event": "started", "name": "parser::tests::parses_empty" }
+{ "type": "test", "name": "parser::tests::parses_empty", "event": "failed", "stdout": "assertion failed: tokens.is_empty()\n" }
+{ "type": "test", "name": "lexer::tests::lexes_ident", "event": "ok" }
+{ "type": "test", "name": "lexer::tests::slow", "event": "ignored" }
+{ "type": "suite", "event": "failed", "passed": 1, "failed": 1, "ignored": 1 }"#;
+
+    const JUNIT_XML: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<testsuites><testsuite name="pytest" tests="3">
+<testcase classname="tests.test_api" name="test_login" time="0.01"/>
+<testcase classname="tests.test_api" name="test_logout" time="0.02"><failure message="assert 401 == 200">trace</failure></testcase>
+<testcase classname="tests.test_db" name="test_migrate"><skipped message="no db"/></testcase>
+</testsuite></testsuites>"#;
+
+    #[test]
+    fn parses_cargo_json_and_junit_xml() {
+        let report = TestReport::from_cargo_json(CARGO_JSON);
+        assert_eq!(report.cases.len(), 3);
+        assert!(!report.passed());
+        assert_eq!(
+            report.failures().next().unwrap().outcome,
+            TestOutcome::Failed {
+                message: "assertion failed: tokens.is_empty()".to_string()
+            }
+        );
+        assert_eq!(report.cases[2].outcome, TestOutcome::Ignored);
+
+        let report = TestReport::from_junit_xml(JUNIT_XML);
+        assert_eq!(report.cases.len(), 3);
+        assert_eq!(report.cases[0].name, "tests::test_api::test_login");
+        assert_eq!(report.cases[0].outcome, TestOutcome::Passed);
+        assert_eq!(
+            report.cases[1].outcome,
+            TestOutcome::Failed {
+                message: "assert 401 == 200".to_string()
+            }
+        );
+        assert_eq!(report.cases[2].outcome, TestOutcome::Ignored);
+    }
+
+    #[test]
+    fn parses_cargo_text_output() {
+        let output = "running 3 tests
+test lexer::tests::lexes_ident ... ok
+test lexer::tests::slow ... ignored, needs network
+test parser::tests::parses_empty ... FAILED
+
+failures:
+
+---- parser::tests::parses_empty stdout ----
+assertion failed: tokens.is_empty()
+
+failures:
+    parser::tests::parses_empty
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored
+";
+        let report = TestReport::from_cargo_text(output);
+        assert_eq!(report.cases.len(), 3);
+        assert_eq!(report.cases[0].outcome, TestOutcome::Passed);
+        assert_eq!(report.cases[1].outcome, TestOutcome::Ignored);
+        assert_eq!(
+            report.cases[2].outcome,
+            TestOutcome::Failed {
+                message: "assertion failed: tokens.is_empty()".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn failures_are_attributed_to_recent_changes() {
+        let changed = vec![
+            SymbolPath::from_str("parser.parse").unwrap(),
+            SymbolPath::from_str("lexer.next_token").unwrap(),
+        ];
+        let diagnostics = TestReport::from_cargo_json(CARGO_JSON).diagnostics(&changed);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].error_type, ErrorType::Test);
+        assert_eq!(diagnostics[0].location, Location::Node("parser.parse".to_string()));
+
+        // Unmatched failures blame the latest change
+        let diagnostics = TestReport::from_junit_xml(JUNIT_XML).diagnostics(&changed);
+        assert_eq!(diagnostics[0].location, Location::Node("lexer.next_token".to_string()));
+        assert_eq!(TestReport::from_junit_xml(JUNIT_XML).diagnostics(&[])[0].location, Location::Unknown);
+    }
+
+    #[tokio::test]
+    async fn runner_executes_in_scope_root() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("events.json"), CARGO_JSON).unwrap();
+        let runner = TestRunner::new(TestFramework::Cargo, FsScope::new(dir.path()))
+            .with_program(vec!["sh".to_string(), "-c".to_string(), "cat events.json".to_string(), "sh".to_string()]);
+        let report = runner.run(None).await.unwrap();
+        assert_eq!(report.cases.len(), 3);
+
+        let empty = TestRunner::new(TestFramework::Cargo, FsScope::new(dir.path()))
+            .with_program(vec!["true".to_string()]);
+        assert!(matches!(empty.run(None).await, Err(TestRunError::NoResults { .. })));
+    }
+
+    #[tokio::test]
+    async fn runner_kills_suites_at_the_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let runner = TestRunner::new(TestFramework::Cargo, FsScope::new(dir.path()))
+            .with_program(vec!["sh".to_string(), "-c".to_string(), "sleep 30".to_string(), "sh".to_string()])
+            .with_timeout(Duration::from_millis(200));
+        assert!(matches!(runner.run(None).await, Err(TestRunError::TimedOut { .. })));
+    }
+}
@@ -0,0 +1,661 @@
+//! Agent tool calls
+//!
+//! A [`Toolbox`] is the only way an agent worker touches the workspace: it
+//! reads artifacts, searches symbols, proposes deltas and runs tests on the
+//! agent's behalf, through the constitutional layer and under the agent's
+//! capability token. Every call is:
+//! - authorized: the token must be unexpired and bound to the tool's
+//!   operation (`read`, `write` or `execute`), and paths must lie inside
+//!   the agent's filesystem scope
+//! - logged as a [`TOOL_CALL_ACTION`] kernel event attributed to the
+//!   token's node, refused calls included
+//! - charged to the token's resource caps: each call counts against
+//!   `iteration_cap`, and test runs against `cpu_time_ms`
+
+use crate::error::ToolError;
+use crate::test_runner::{TestReport, TestRunner};
+use coa_artifact::{AddressableContent, Artifact, ArtifactType, StructuralDelta};
+use coa_constitutional::layer::ParseResult;
+use coa_constitutional::{ConstitutionalLayer, FsAccess, ParseError, ScopeGuard, ScopedLayer};
+use coa_kernel::clock::{system_clock, SharedClock};
+use coa_symbol::IndexEntry;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Instant;
+
+/// Event action recorded for every tool call
+pub const TOOL_CALL_ACTION: &str = "tool_call";
+
+/// Tools an agent can call
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolKind {
+    /// Parse a file into a typed artifact
+    ReadArtifact,
+    /// Query the symbol index
+    SearchSymbols,
+    /// Validate a delta and hand it back for composition
+    ProposeDelta,
+    /// Run the project's test suite
+    RunTests,
+}
+
+impl ToolKind {
+    /// All tools
+    pub const ALL: [Self; 4] = [
+        Self::ReadArtifact,
+        Self::SearchSymbols,
+        Self::ProposeDelta,
+        Self::RunTests,
+    ];
+
+    /// Name used in logs and function-calling schemas
+    #[inline]
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::ReadArtifact => "read_artifact",
+            Self::SearchSymbols => "search_symbols",
+            Self::ProposeDelta => "propose_delta",
+            Self::RunTests => "run_tests",
+        }
+    }
+
+    /// Operation the capability token must be bound to
+    #[inline]
+    #[must_use]
+    pub fn operation(self) -> &'static str {
+        match self {
+            Self::ReadArtifact | Self::SearchSymbols => "read",
+            Self::ProposeDelta => "write",
+            Self::RunTests => "execute",
+        }
+    }
+}
+
+impl fmt::Display for ToolKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// How a tool call ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolOutcome {
+    Completed,
+    /// Refused by the token, the scope or the resource caps
+    Denied,
+    /// Authorized, but the tool itself failed
+    Failed,
+}
+
+/// Result of a [`TOOL_CALL_ACTION`] event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolCallRecord {
+    /// Tool called
+    pub tool: ToolKind,
+    /// Path, query, delta target or test filter the tool was called with
+    pub argument: String,
+    /// How the call ended
+    pub outcome: ToolOutcome,
+    /// Result summary, or why the call was refused or failed
+    pub detail: String,
+}
+
+impl ToolCallRecord {
+    fn to_json(&self) -> String {
+        // Plain fields only; serialization cannot fail
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// Resources charged to a toolbox's token so far
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolUsage {
+    /// Authorized calls, counted against `iteration_cap`
+    pub calls: u64,
+    /// Time spent running tests, counted against `cpu_time_ms`
+    pub test_time_ms: u64,
+}
+
+/// Delta accepted by [`Toolbox::propose_delta`], ready for composition
+#[derive(Debug, PartialEq)]
+pub struct Proposal<T: ArtifactType> {
+    /// Scope-checked absolute path of the artifact's file
+    pub path: PathBuf,
+    /// Validated delta
+    pub delta: StructuralDelta<T>,
+}
+
+/// Tools available to one agent, bound to its scope guard
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let tools = Toolbox::new(&layer, guard).with_test_runner(runner);
+///
+/// let parsed = tools.read_artifact::<CodeArtifact>("src/auth.rs").await?;
+/// let callers = tools.search_symbols("path:auth/* kind:function")?;
+/// let proposal = tools.propose_delta("src/auth.rs", &parsed.artifact, delta)?;
+/// let report = tools.run_tests(Some("auth")).await?;
+/// ```
+#[derive(Debug)]
+pub struct Toolbox<'a> {
+    layer: &'a ConstitutionalLayer,
+    scoped: ScopedLayer<'a>,
+    test_runner: Option<TestRunner>,
+    clock: SharedClock,
+    usage: Mutex<ToolUsage>,
+}
+
+impl<'a> Toolbox<'a> {
+    /// Create toolbox acting on `layer` under `guard`
+    #[must_use]
+    pub fn new(layer: &'a ConstitutionalLayer, guard: ScopeGuard) -> Self {
+        Self {
+            layer,
+            scoped: layer.scoped(guard),
+            test_runner: None,
+            clock: system_clock(),
+            usage: Mutex::new(ToolUsage::default()),
+        }
+    }
+
+    /// With the runner behind `run_tests`
+    #[inline]
+    #[must_use]
+    pub fn with_test_runner(mut self, runner: TestRunner) -> Self {
+        self.test_runner = Some(runner);
+        self
+    }
+
+    /// With the clock token expiry is checked against
+    #[inline]
+    #[must_use]
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Scope guard calls are authorized and logged through
+    #[inline]
+    #[must_use]
+    pub fn guard(&self) -> &ScopeGuard {
+        self.scoped.guard()
+    }
+
+    /// Resources charged so far
+    #[must_use]
+    pub fn usage(&self) -> ToolUsage {
+        *self.lock()
+    }
+
+    /// `read_artifact(path)`: parse a file readable in scope
+    ///
+    /// # Errors
+    /// - `ToolError::Denied` / `ToolError::BudgetExhausted` if the call is
+    ///   refused
+    /// - `ToolError::Failed` if the file cannot be parsed
+    /// - `ToolError::Audit` if the call cannot be logged
+    pub async fn read_artifact<T: ArtifactType>(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<ParseResult<T>, ToolError> {
+        let tool = ToolKind::ReadArtifact;
+        let path = path.as_ref();
+        let argument = path.display().to_string();
+        self.authorize(tool, &argument)?;
+
+        match self.scoped.parse_ingress::<T>(path).await {
+            Ok(parsed) => {
+                self.complete(tool, &argument, parsed.artifact.hash().to_string())?;
+                Ok(parsed)
+            }
+            Err(ParseError::ScopeViolation(violation)) => {
+                Err(self.deny(tool, &argument, violation.to_string()))
+            }
+            Err(e) => Err(self.fail(tool, &argument, e.to_string())),
+        }
+    }
+
+    /// `search_symbols(query)`: run a symbol query
+    ///
+    /// Symbols defined in files outside the read scope are left out.
+    ///
+    /// # Errors
+    /// - `ToolError::Denied` / `ToolError::BudgetExhausted` if the call is
+    ///   refused
+    /// - `ToolError::Failed` if the query does not parse
+    /// - `ToolError::Audit` if the call cannot be logged
+    pub fn search_symbols(&self, query: &str) -> Result<Vec<IndexEntry>, ToolError> {
+        let tool = ToolKind::SearchSymbols;
+        self.authorize(tool, query)?;
+
+        let scope = self.guard().scope();
+        let entries: Vec<IndexEntry> = match self.layer.symbol_index().query(query) {
+            Ok(entries) => entries
+                .into_iter()
+                .filter(|entry| {
+                    entry
+                        .metadata
+                        .source_location
+                        .iter()
+                        .all(|location| scope.permits(&location.file, FsAccess::Read))
+                })
+                .collect(),
+            Err(e) => return Err(self.fail(tool, query, e.to_string())),
+        };
+        self.complete(tool, query, format!("{} match(es)", entries.len()))?;
+        Ok(entries)
+    }
+
+    /// `propose_delta(delta)`: validate a delta to a file writable in scope
+    ///
+    /// Nothing is applied; the returned [`Proposal`] goes to composition
+    /// with the other agents' deltas.
+    ///
+    /// # Errors
+    /// - `ToolError::Denied` / `ToolError::BudgetExhausted` if the call is
+    ///   refused
+    /// - `ToolError::Failed` if the delta does not fit `base`
+    /// - `ToolError::Audit` if the call cannot be logged
+    pub fn propose_delta<T>(
+        &self,
+        path: impl AsRef<Path>,
+        base: &Artifact<T>,
+        delta: StructuralDelta<T>,
+    ) -> Result<Proposal<T>, ToolError>
+    where
+        T: ArtifactType,
+        T::Content: AddressableContent,
+    {
+        let tool = ToolKind::ProposeDelta;
+        let path = path.as_ref();
+        let argument = format!("{}#{}", path.display(), delta.target());
+        self.authorize(tool, &argument)?;
+
+        let path = self
+            .guard()
+            .check(path, FsAccess::Write)
+            .map_err(|violation| self.deny(tool, &argument, violation.to_string()))?;
+        if let Err(e) = self.layer.validate_delta(base, &delta) {
+            return Err(self.fail(tool, &argument, e.to_string()));
+        }
+        self.complete(tool, &argument, delta.description().to_string())?;
+        Ok(Proposal { path, delta })
+    }
+
+    /// `run_tests(scope)`: run the test suite, optionally only tests
+    /// matching `scope`
+    ///
+    /// # Errors
+    /// - `ToolError::Denied` / `ToolError::BudgetExhausted` if the call is
+    ///   refused
+    /// - `ToolError::Failed` if no runner is configured or the suite
+    ///   yields no results
+    /// - `ToolError::Audit` if the call cannot be logged
+    pub async fn run_tests(&self, scope: Option<&str>) -> Result<TestReport, ToolError> {
+        let tool = ToolKind::RunTests;
+        let argument = scope.unwrap_or_default();
+        self.authorize(tool, argument)?;
+
+        let Some(runner) = &self.test_runner else {
+            return Err(self.fail(tool, argument, "no test runner configured".to_string()));
+        };
+        let started = Instant::now();
+        let result = runner.run(scope).await;
+        let elapsed = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+        {
+            let mut usage = self.lock();
+            usage.test_time_ms = usage.test_time_ms.saturating_add(elapsed);
+        }
+
+        match result {
+            Ok(report) => {
+                let failed = report.failures().count();
+                let detail = format!("{} passed, {failed} failed", report.cases.len() - failed);
+                self.complete(tool, argument, detail)?;
+                Ok(report)
+            }
+            Err(e) => Err(self.fail(tool, argument, e.to_string())),
+        }
+    }
+
+    /// Check the token and resource caps, charging the call if allowed
+    fn authorize(&self, tool: ToolKind, argument: &str) -> Result<(), ToolError> {
+        let token = self.guard().token();
+        if token.is_expired(self.clock.as_ref()) {
+            return Err(self.deny(tool, argument, "capability token expired".to_string()));
+        }
+        if !token.is_bound_to(tool.operation()) {
+            return Err(self.deny(
+                tool,
+                argument,
+                format!(
+                    "token is bound to {}, not {}",
+                    token.bound_operation,
+                    tool.operation()
+                ),
+            ));
+        }
+
+        let caps = token.caps;
+        let exhausted = {
+            let mut usage = self.lock();
+            if usage.calls >= caps.iteration_cap {
+                Some(format!("{} of {} calls made", usage.calls, caps.iteration_cap))
+            } else if tool == ToolKind::RunTests && usage.test_time_ms >= caps.cpu_time_ms {
+                Some(format!(
+                    "{}ms of {}ms test time used",
+                    usage.test_time_ms, caps.cpu_time_ms
+                ))
+            } else {
+                usage.calls += 1;
+                None
+            }
+        };
+        match exhausted {
+            Some(reason) => {
+                self.log(tool, argument, ToolOutcome::Denied, &reason);
+                Err(ToolError::BudgetExhausted { tool, reason })
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Log a completed call; an unrecorded call is not returned
+    fn complete(&self, tool: ToolKind, argument: &str, detail: String) -> Result<(), ToolError> {
+        let record = ToolCallRecord {
+            tool,
+            argument: argument.to_string(),
+            outcome: ToolOutcome::Completed,
+            detail,
+        };
+        self.guard()
+            .record(TOOL_CALL_ACTION, record.to_json())
+            .map(|_| ())
+            .map_err(|e| ToolError::Audit {
+                tool,
+                message: e.to_string(),
+            })
+    }
+
+    fn deny(&self, tool: ToolKind, argument: &str, reason: String) -> ToolError {
+        self.log(tool, argument, ToolOutcome::Denied, &reason);
+        ToolError::Denied { tool, reason }
+    }
+
+    fn fail(&self, tool: ToolKind, argument: &str, message: String) -> ToolError {
+        self.log(tool, argument, ToolOutcome::Failed, &message);
+        ToolError::Failed { tool, message }
+    }
+
+    /// Log a refused or failed call; the caller gets an error either way
+    fn log(&self, tool: ToolKind, argument: &str, outcome: ToolOutcome, detail: &str) {
+        let record = ToolCallRecord {
+            tool,
+            argument: argument.to_string(),
+            outcome,
+            detail: detail.to_string(),
+        };
+        let _ = self.guard().record(TOOL_CALL_ACTION, record.to_json());
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ToolUsage> {
+        self.usage.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coa_artifact::{ContentHash, DeltaOperation, SymbolPath};
+    use coa_constitutional::parsers::{ArtifactParser, CodeArtifact, CodeParser, Language};
+    use coa_constitutional::FsScope;
+    use coa_kernel::autonomy::CapabilityToken;
+    use coa_kernel::clock::ManualClock;
+    use coa_kernel::logging::{EventLog, ARTIFACT_READ_ACTION};
+    use coa_kernel::types::{AutonomyLevel, DirectiveProfileHash, NodeId, ResourceCaps};
+    use coa_symbol::{SourceLocation, SymbolMetadata, SymbolRef, SymbolRefIndex};
+    use std::sync::Arc;
+
+    fn token(operation: &str, iteration_cap: u64) -> CapabilityToken {
+        CapabilityToken::sign(
+            NodeId::new(),
+            AutonomyLevel::L3,
+            ResourceCaps {
+                cpu_time_ms: 1000,
+                memory_bytes: 1024,
+                token_limit: 10,
+                iteration_cap,
+            },
+            DirectiveProfileHash([0u8; 32]),
+            &ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]),
+            0,
+            operation,
+        )
+    }
+
+    fn workspace() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src/auth")).unwrap();
+        std::fs::write(dir.path().join("src/auth/login.rs"), "pub fn login() {}\n").unwrap();
+        std::fs::write(dir.path().join("src/main.rs"), "fn main() {}\n").unwrap();
+        dir
+    }
+
+    fn records(log: &EventLog) -> Vec<ToolCallRecord> {
+        log.events()
+            .iter()
+            .filter(|event| event.action == TOOL_CALL_ACTION)
+            .map(|event| serde_json::from_str(&event.result).unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn read_artifact_is_scoped_and_logged() {
+        let dir = workspace();
+        let log = Arc::new(EventLog::default());
+        let scope = FsScope::new(dir.path()).allow_write("src/auth");
+        let guard = ScopeGuard::new(scope, token("", 10), log.clone());
+        let layer = ConstitutionalLayer::new();
+        let tools = Toolbox::new(&layer, guard);
+
+        let parsed = tools
+            .read_artifact::<CodeArtifact>("src/auth/login.rs")
+            .await
+            .unwrap();
+        let err = tools
+            .read_artifact::<CodeArtifact>("src/main.rs")
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ToolError::Denied {
+                tool: ToolKind::ReadArtifact,
+                ..
+            }
+        ));
+
+        let actions: Vec<String> = log
+            .events()
+            .iter()
+            .map(|e| e.action.as_str().to_string())
+            .collect();
+        assert_eq!(actions[..2], [ARTIFACT_READ_ACTION, TOOL_CALL_ACTION]);
+        let records = records(&log);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].outcome, ToolOutcome::Completed);
+        assert_eq!(records[0].detail, parsed.artifact.hash().to_string());
+        assert_eq!(records[1].outcome, ToolOutcome::Denied);
+        assert_eq!(records[1].argument, "src/main.rs");
+        assert_eq!(tools.usage().calls, 2);
+    }
+
+    #[test]
+    fn token_binding_gates_tools() {
+        let log = Arc::new(EventLog::default());
+        let guard = ScopeGuard::new(FsScope::new("/work"), token("read", 10), log.clone());
+        let layer = ConstitutionalLayer::new();
+        let tools = Toolbox::new(&layer, guard);
+        let base = CodeParser::new(Language::Rust).parse("fn a() {}").unwrap();
+
+        assert!(tools.search_symbols("kind:function").is_ok());
+        let delta = StructuralDelta::new(
+            SymbolPath::single("a"),
+            DeltaOperation::Remove,
+            ContentHash::compute(b"x"),
+        );
+        let err = tools.propose_delta("src/a.rs", &base, delta).unwrap_err();
+        assert_eq!(
+            err,
+            ToolError::Denied {
+                tool: ToolKind::ProposeDelta,
+                reason: "token is bound to read, not write".to_string(),
+            }
+        );
+        assert_eq!(tools.usage().calls, 1);
+        assert_eq!(records(&log).len(), 2);
+    }
+
+    #[test]
+    fn token_expiry_follows_toolbox_clock() {
+        let mut expiring = token("", 10);
+        expiring.expires_at = 100;
+        let guard = ScopeGuard::new(FsScope::new("/work"), expiring, Arc::new(EventLog::default()));
+        let layer = ConstitutionalLayer::new();
+        let clock = Arc::new(ManualClock::new(50));
+        let tools = Toolbox::new(&layer, guard).with_clock(clock.clone());
+
+        assert!(tools.search_symbols("kind:function").is_ok());
+        clock.advance(100);
+        let err = tools.search_symbols("kind:function").unwrap_err();
+        assert_eq!(
+            err,
+            ToolError::Denied {
+                tool: ToolKind::SearchSymbols,
+                reason: "capability token expired".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn search_hides_symbols_outside_scope() {
+        let index = SymbolRefIndex::new();
+        let hash = ContentHash::compute(b"workspace");
+        for (path, file) in [("auth.login", "src/auth/login.rs"), ("main", "src/main.rs")] {
+            let metadata = SymbolMetadata {
+                source_location: Some(SourceLocation {
+                    line: 1,
+                    column: 0,
+                    file: file.to_string(),
+                }),
+                ..SymbolMetadata::default()
+            };
+            let path = path.split('.').map(str::to_string).collect();
+            index.insert(SymbolRef::new(path, hash), metadata).unwrap();
+        }
+        let layer = ConstitutionalLayer::new().with_symbol_index(Arc::new(index));
+        let scope = FsScope::new("/work").allow_read("src/auth");
+        let guard = ScopeGuard::new(scope, token("", 10), Arc::new(EventLog::default()));
+        let tools = Toolbox::new(&layer, guard);
+
+        let found = tools.search_symbols("path:**").unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].symbol.path(), ["auth", "login"]);
+        assert!(matches!(
+            tools.search_symbols("bogus:term"),
+            Err(ToolError::Failed {
+                tool: ToolKind::SearchSymbols,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn propose_delta_validates_against_base() {
+        let dir = workspace();
+        let log = Arc::new(EventLog::default());
+        let scope = FsScope::new(dir.path()).allow_write("src/auth");
+        let guard = ScopeGuard::new(scope, token("", 10), log.clone());
+        let layer = ConstitutionalLayer::new();
+        let tools = Toolbox::new(&layer, guard);
+        let base = tools
+            .read_artifact::<CodeArtifact>("src/auth/login.rs")
+            .await
+            .unwrap()
+            .artifact;
+
+        let delta = StructuralDelta::new(SymbolPath::single("login"), DeltaOperation::Remove, *base.hash());
+        let proposal = tools.propose_delta("src/auth/login.rs", &base, delta).unwrap();
+        assert_eq!(proposal.path, dir.path().join("src/auth/login.rs"));
+        assert_eq!(proposal.delta.target(), &SymbolPath::single("login"));
+
+        let stale = StructuralDelta::new(
+            SymbolPath::single("login"),
+            DeltaOperation::Remove,
+            ContentHash::compute(b"old"),
+        );
+        assert!(matches!(
+            tools.propose_delta("src/auth/login.rs", &base, stale),
+            Err(ToolError::Failed {
+                tool: ToolKind::ProposeDelta,
+                ..
+            })
+        ));
+        let delta = StructuralDelta::new(SymbolPath::single("main"), DeltaOperation::Remove, *base.hash());
+        assert!(matches!(
+            tools.propose_delta("src/main.rs", &base, delta),
+            Err(ToolError::Denied {
+                tool: ToolKind::ProposeDelta,
+                ..
+            })
+        ));
+
+        let outcomes: Vec<ToolOutcome> = records(&log).iter().map(|r| r.outcome).collect();
+        assert_eq!(
+            outcomes,
+            [
+                ToolOutcome::Completed,
+                ToolOutcome::Completed,
+                ToolOutcome::Failed,
+                ToolOutcome::Denied
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn calls_are_charged_to_iteration_cap() {
+        let log = Arc::new(EventLog::default());
+        let guard = ScopeGuard::new(FsScope::new("/work"), token("", 2), log.clone());
+        let layer = ConstitutionalLayer::new();
+        let tools = Toolbox::new(&layer, guard);
+
+        assert!(tools.search_symbols("kind:function").is_ok());
+        assert!(matches!(
+            tools.run_tests(None).await,
+            Err(ToolError::Failed {
+                tool: ToolKind::RunTests,
+                ..
+            })
+        ));
+        assert!(matches!(
+            tools.search_symbols("kind:function"),
+            Err(ToolError::BudgetExhausted {
+                tool: ToolKind::SearchSymbols,
+                ..
+            })
+        ));
+        assert_eq!(
+            tools.usage(),
+            ToolUsage {
+                calls: 2,
+                test_time_ms: 0
+            }
+        );
+        assert_eq!(records(&log).last().unwrap().outcome, ToolOutcome::Denied);
+    }
+}
@@ -0,0 +1,418 @@
+//! Deterministic record/replay of orchestrator runs
+//!
+//! Every nondeterministic input the orchestrator consumes — LLM responses,
+//! wall-clock timestamps and random choices such as generated task IDs —
+//! goes through a [`Trace`]. In [`TraceMode::Record`] the inputs are captured
+//! into a [`RunTrace`] that can be saved alongside a bug report; in
+//! [`TraceMode::Replay`] the same inputs are served back in order, so the
+//! orchestration re-executes identically without calling any model or
+//! reading the clock.
+//!
+//! A replay that asks for a different input than was recorded fails with
+//! [`TraceError::Diverged`], pointing at the first point where the code
+//! under test behaves differently from the recorded run.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let coa = CreatorOrchestratorAgent::new(config.clone()).with_trace(Trace::record());
+//! let _ = coa.execute_intent(intent).await;
+//! coa.trace().save("run.trace.json")?;
+//!
+//! let replay = Trace::replay(RunTrace::load("run.trace.json")?);
+//! let coa = CreatorOrchestratorAgent::new(config).with_trace(replay);
+//! let _ = coa.execute_replay().await;
+//! ```
+
+use crate::error::TraceError;
+use crate::types::UserIntent;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Trace file format version
+pub const TRACE_FORMAT_VERSION: u32 = 1;
+
+/// One recorded nondeterministic input
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TraceEvent {
+    /// Model response to a prompt
+    LlmResponse { prompt: String, response: String },
+    /// Wall-clock reading in milliseconds since the Unix epoch
+    Timestamp { label: String, millis: u64 },
+    /// Random or generated value, stored in its string form
+    RandomChoice { label: String, value: String },
+}
+
+impl TraceEvent {
+    /// Short description used in divergence errors
+    #[must_use]
+    pub fn describe(&self) -> String {
+        match self {
+            Self::LlmResponse { prompt, .. } => format!("llm response to {prompt:?}"),
+            Self::Timestamp { label, .. } => format!("timestamp '{label}'"),
+            Self::RandomChoice { label, .. } => format!("random choice '{label}'"),
+        }
+    }
+}
+
+/// Recorded inputs of one orchestrator run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunTrace {
+    /// Format version
+    pub version: u32,
+    /// Intent the run was started with
+    pub intent: Option<UserIntent>,
+    /// Inputs in the order they were consumed
+    pub events: Vec<TraceEvent>,
+}
+
+impl Default for RunTrace {
+    fn default() -> Self {
+        Self {
+            version: TRACE_FORMAT_VERSION,
+            intent: None,
+            events: Vec::new(),
+        }
+    }
+}
+
+impl RunTrace {
+    /// Write trace as JSON
+    ///
+    /// # Errors
+    /// Returns `TraceError::Io` or `TraceError::Codec` on failure
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), TraceError> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json).map_err(|e| TraceError::Io(e.to_string()))
+    }
+
+    /// Read trace written by [`RunTrace::save`]
+    ///
+    /// # Errors
+    /// Returns `TraceError::Io` or `TraceError::Codec` on failure
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TraceError> {
+        let json = std::fs::read_to_string(path).map_err(|e| TraceError::Io(e.to_string()))?;
+        Ok(serde_json::from_str(&json)?)
+    }
+}
+
+/// How a [`Trace`] treats nondeterministic inputs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceMode {
+    /// Use live inputs without recording
+    Live,
+    /// Use live inputs and record them
+    Record,
+    /// Serve recorded inputs; live sources are never consulted
+    Replay,
+}
+
+#[derive(Debug, Default)]
+struct TraceState {
+    trace: RunTrace,
+    cursor: usize,
+}
+
+/// Source of nondeterministic inputs for an orchestrator run
+///
+/// Cloning shares the underlying trace.
+#[derive(Debug, Clone)]
+pub struct Trace {
+    mode: TraceMode,
+    state: Arc<Mutex<TraceState>>,
+}
+
+impl Default for Trace {
+    fn default() -> Self {
+        Self::live()
+    }
+}
+
+impl Trace {
+    /// Pass-through trace (no recording)
+    #[inline]
+    #[must_use]
+    pub fn live() -> Self {
+        Self::with_mode(TraceMode::Live, RunTrace::default())
+    }
+
+    /// Record every input into a new trace
+    #[inline]
+    #[must_use]
+    pub fn record() -> Self {
+        Self::with_mode(TraceMode::Record, RunTrace::default())
+    }
+
+    /// Replay inputs from a recorded trace
+    #[inline]
+    #[must_use]
+    pub fn replay(trace: RunTrace) -> Self {
+        Self::with_mode(TraceMode::Replay, trace)
+    }
+
+    fn with_mode(mode: TraceMode, trace: RunTrace) -> Self {
+        Self {
+            mode,
+            state: Arc::new(Mutex::new(TraceState { trace, cursor: 0 })),
+        }
+    }
+
+    /// Current mode
+    #[inline]
+    #[must_use]
+    pub fn mode(&self) -> TraceMode {
+        self.mode
+    }
+
+    /// Intent to run: recorded when recording, taken from the trace when
+    /// replaying (falling back to `intent` if none was recorded)
+    pub fn capture_intent(&self, intent: UserIntent) -> UserIntent {
+        let mut state = self.lock();
+        match self.mode {
+            TraceMode::Live => intent,
+            TraceMode::Record => {
+                state.trace.intent = Some(intent.clone());
+                intent
+            }
+            TraceMode::Replay => state.trace.intent.clone().unwrap_or(intent),
+        }
+    }
+
+    /// Recorded intent, if any
+    #[must_use]
+    pub fn intent(&self) -> Option<UserIntent> {
+        self.lock().trace.intent.clone()
+    }
+
+    /// Wall-clock time in milliseconds since the Unix epoch
+    ///
+    /// # Errors
+    /// Returns a `TraceError` if a replay does not match the recording
+    pub fn now_millis(&self, label: &str) -> Result<u64, TraceError> {
+        match self.mode {
+            TraceMode::Live => Ok(system_millis()),
+            TraceMode::Record => {
+                let millis = system_millis();
+                self.push(TraceEvent::Timestamp {
+                    label: label.to_string(),
+                    millis,
+                });
+                Ok(millis)
+            }
+            TraceMode::Replay => self
+                .next(&format!("timestamp '{label}'"), |event| match event {
+                    TraceEvent::Timestamp { label: l, millis } if l == label => Some(*millis),
+                    _ => None,
+                })
+                .map(|(_, millis)| millis),
+        }
+    }
+
+    /// Random or generated value (e.g. an ID)
+    ///
+    /// `live` is only called when not replaying.
+    ///
+    /// # Errors
+    /// Returns a `TraceError` if a replay does not match the recording or
+    /// the recorded value does not parse as `T`
+    pub fn random_choice<T>(&self, label: &str, live: impl FnOnce() -> T) -> Result<T, TraceError>
+    where
+        T: ToString + FromStr,
+    {
+        match self.mode {
+            TraceMode::Live => Ok(live()),
+            TraceMode::Record => {
+                let value = live();
+                self.push(TraceEvent::RandomChoice {
+                    label: label.to_string(),
+                    value: value.to_string(),
+                });
+                Ok(value)
+            }
+            TraceMode::Replay => {
+                let (index, value) =
+                    self.next(&format!("random choice '{label}'"), |event| match event {
+                        TraceEvent::RandomChoice { label: l, value } if l == label => {
+                            Some(value.clone())
+                        }
+                        _ => None,
+                    })?;
+                value
+                    .parse()
+                    .map_err(|_| TraceError::InvalidValue { index, value })
+            }
+        }
+    }
+
+    /// Model response for `prompt`
+    ///
+    /// `live` is only awaited when not replaying; failed calls are not
+    /// recorded.
+    ///
+    /// # Errors
+    /// Returns the error of `live`, or a `TraceError` if a replay does not
+    /// match the recording
+    pub async fn llm_response<F, E>(&self, prompt: &str, live: F) -> Result<String, E>
+    where
+        F: Future<Output = Result<String, E>>,
+        E: From<TraceError>,
+    {
+        match self.mode {
+            TraceMode::Live => live.await,
+            TraceMode::Record => {
+                let response = live.await?;
+                self.push(TraceEvent::LlmResponse {
+                    prompt: prompt.to_string(),
+                    response: response.clone(),
+                });
+                Ok(response)
+            }
+            TraceMode::Replay => self
+                .next(&format!("llm response to {prompt:?}"), |event| match event {
+                    TraceEvent::LlmResponse { prompt: p, response } if p == prompt => {
+                        Some(response.clone())
+                    }
+                    _ => None,
+                })
+                .map(|(_, response)| response)
+                .map_err(E::from),
+        }
+    }
+
+    /// Copy of the trace recorded (or being replayed) so far
+    #[must_use]
+    pub fn snapshot(&self) -> RunTrace {
+        self.lock().trace.clone()
+    }
+
+    /// Recorded inputs not yet consumed by a replay
+    #[must_use]
+    pub fn remaining(&self) -> usize {
+        let state = self.lock();
+        match self.mode {
+            TraceMode::Replay => state.trace.events.len() - state.cursor,
+            TraceMode::Live | TraceMode::Record => 0,
+        }
+    }
+
+    /// Write the trace as JSON
+    ///
+    /// # Errors
+    /// Returns `TraceError::Io` or `TraceError::Codec` on failure
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), TraceError> {
+        self.snapshot().save(path)
+    }
+
+    fn push(&self, event: TraceEvent) {
+        self.lock().trace.events.push(event);
+    }
+
+    /// Consume the next recorded input if `extract` accepts it
+    fn next<T>(
+        &self,
+        expected: &str,
+        extract: impl FnOnce(&TraceEvent) -> Option<T>,
+    ) -> Result<(usize, T), TraceError> {
+        let mut state = self.lock();
+        let index = state.cursor;
+        let event = state
+            .trace
+            .events
+            .get(index)
+            .ok_or_else(|| TraceError::Exhausted {
+                index,
+                expected: expected.to_string(),
+            })?;
+        let value = extract(event).ok_or_else(|| TraceError::Diverged {
+            index,
+            expected: expected.to_string(),
+            found: event.describe(),
+        })?;
+        state.cursor += 1;
+        Ok((index, value))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, TraceState> {
+        self.state.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+fn system_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::COAError;
+
+    #[tokio::test]
+    async fn recorded_inputs_replay_in_order() {
+        let recorder = Trace::record();
+        let start = recorder.now_millis("start").unwrap();
+        let id = recorder.random_choice("id", ulid::Ulid::new).unwrap();
+        let response = recorder
+            .llm_response("plan?", async { Ok::<_, COAError>("two tasks".to_string()) })
+            .await
+            .unwrap();
+
+        let replay = Trace::replay(recorder.snapshot());
+        assert_eq!(replay.remaining(), 3);
+        assert_eq!(replay.now_millis("start").unwrap(), start);
+        assert_eq!(
+            replay.random_choice("id", || -> ulid::Ulid { panic!("live source used") }).unwrap(),
+            id
+        );
+        let replayed: Result<String, COAError> = replay
+            .llm_response("plan?", async { panic!("model called during replay") })
+            .await;
+        assert_eq!(replayed.unwrap(), response);
+        assert_eq!(replay.remaining(), 0);
+    }
+
+    #[test]
+    fn replay_reports_divergence_and_exhaustion() {
+        let recorder = Trace::record();
+        recorder.now_millis("start").unwrap();
+
+        let replay = Trace::replay(recorder.snapshot());
+        let err = replay.random_choice("id", || 7u32).unwrap_err();
+        assert!(matches!(err, TraceError::Diverged { index: 0, .. }));
+        assert_eq!(
+            err.to_string(),
+            "replay diverged at input 0: expected random choice 'id', found timestamp 'start'"
+        );
+
+        replay.now_millis("start").unwrap();
+        assert!(matches!(
+            replay.now_millis("end"),
+            Err(TraceError::Exhausted { index: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn trace_file_round_trip() {
+        let recorder = Trace::record();
+        recorder.capture_intent(UserIntent::new("Create a function"));
+        recorder.random_choice("seed", || 42u64).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.trace.json");
+        recorder.save(&path).unwrap();
+
+        let trace = RunTrace::load(&path).unwrap();
+        assert_eq!(trace.version, TRACE_FORMAT_VERSION);
+        assert_eq!(trace.events, recorder.snapshot().events);
+
+        let replay = Trace::replay(trace);
+        let intent = replay.capture_intent(UserIntent::new("ignored"));
+        assert_eq!(intent.description, "Create a function");
+        assert_eq!(replay.random_choice("seed", || 0u64).unwrap(), 42);
+    }
+}
@@ -6,11 +6,14 @@
 //! - Tasks and their properties
 //! - Agent specifications
 
-use crate::error::Goal;
+use crate::capability::{AgentCapabilities, CapabilityRequirements};
+use crate::error::{DirectiveError, Goal};
 use coa_artifact::SymbolPath;
 use coa_composition::StrategyHint;
+use coa_constitutional::{FsScope, KernelDirectiveSet};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::str::FromStr;
 use ulid::Ulid;
 
@@ -131,6 +134,14 @@ pub struct SystemLimits {
     pub max_cpu_cores: usize,
     /// Maximum number of agents
     pub max_agents: usize,
+    /// Fraction of any limit above which the agent pool preempts
+    /// lower-priority agents to admit new ones
+    #[serde(default = "default_preemption_threshold")]
+    pub preemption_threshold: f64,
+}
+
+fn default_preemption_threshold() -> f64 {
+    0.9
 }
 
 impl Default for SystemLimits {
@@ -139,6 +150,7 @@ impl Default for SystemLimits {
             max_memory_gb: 32,
             max_cpu_cores: 8,
             max_agents: 100,
+            preemption_threshold: default_preemption_threshold(),
         }
     }
 }
@@ -164,58 +176,8 @@ impl Default for EscalationThreshold {
     }
 }
 
-/// Autonomy levels (embedded in node types)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
-pub enum AutonomyLevel {
-    /// Level 0: Full human-in-the-loop
-    L0,
-    /// Level 1: Approval before merge
-    L1,
-    /// Level 2: Auto code, human merge
-    L2,
-    /// Level 3: Auto merge in sandbox
-    L3,
-    /// Level 4: Auto merge + test deploy
-    L4,
-    /// Level 5: Full autonomous within boundary
-    L5,
-}
-
-impl AutonomyLevel {
-    /// Get numeric value
-    #[inline]
-    #[must_use]
-    pub fn value(&self) -> u8 {
-        match self {
-            AutonomyLevel::L0 => 0,
-            AutonomyLevel::L1 => 1,
-            AutonomyLevel::L2 => 2,
-            AutonomyLevel::L3 => 3,
-            AutonomyLevel::L4 => 4,
-            AutonomyLevel::L5 => 5,
-        }
-    }
-
-    /// Check if this level can auto-merge
-    #[inline]
-    #[must_use]
-    pub fn can_auto_merge(&self) -> bool {
-        self.value() >= 3
-    }
-
-    /// Check if this level requires human approval
-    #[inline]
-    #[must_use]
-    pub fn requires_human_approval(&self) -> bool {
-        self.value() < 2
-    }
-}
-
-impl Default for AutonomyLevel {
-    fn default() -> Self {
-        AutonomyLevel::L3
-    }
-}
+pub use coa_artifact::CorrelationId;
+pub use coa_autonomy::{AutonomyCeiling, AutonomyLevel, AutonomyParseError};
 
 /// User intent (natural language input)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -294,6 +256,8 @@ pub struct Specification {
     pub constraints: Vec<Constraint>,
     /// Output specification
     pub output_spec: Option<OutputSpec>,
+    /// Decomposition template to expand, overriding template matching
+    pub template: Option<String>,
 }
 
 impl Specification {
@@ -312,6 +276,7 @@ impl Specification {
             acceptance_criteria: Vec::new(),
             constraints: Vec::new(),
             output_spec: None,
+            template: None,
         }
     }
 
@@ -323,6 +288,14 @@ impl Specification {
         self
     }
 
+    /// With decomposition template
+    #[inline]
+    #[must_use]
+    pub fn with_template(mut self, name: impl Into<String>) -> Self {
+        self.template = Some(name.into());
+        self
+    }
+
     /// Get composition strategy hint based on goal
     #[inline]
     #[must_use]
@@ -378,7 +351,7 @@ pub enum SpecFormat {
 }
 
 /// Executable task
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Task {
     /// Task identifier
     pub id: TaskId,
@@ -396,10 +369,23 @@ pub struct Task {
     pub dependencies: Vec<TaskId>,
     /// Target artifact path
     pub target_artifact: SymbolPath,
+    /// Additional subtrees the task declares it will modify
+    #[serde(default)]
+    pub claims: Vec<SymbolPath>,
     /// Expected output type
     pub expected_output: Option<OutputSpec>,
     /// Expansion type for dynamic graphs
     pub expansion_type: Option<ExpansionType>,
+    /// Intent and task this task's events and artifacts are correlated with
+    #[serde(default)]
+    pub correlation_id: Option<CorrelationId>,
+    /// Scheduling priority; higher-priority tasks may preempt lower ones
+    /// under resource pressure
+    #[serde(default)]
+    pub priority: TaskPriority,
+    /// Capabilities the agent running this task must have
+    #[serde(default)]
+    pub requirements: CapabilityRequirements,
 }
 
 impl Task {
@@ -420,8 +406,12 @@ impl Task {
             resources: ResourceCaps::default(),
             dependencies: Vec::new(),
             target_artifact,
+            claims: Vec::new(),
             expected_output: None,
             expansion_type: None,
+            correlation_id: None,
+            priority: TaskPriority::default(),
+            requirements: CapabilityRequirements::default(),
         }
     }
 
@@ -464,6 +454,72 @@ impl Task {
         self.expansion_type = Some(expansion);
         self
     }
+
+    /// With correlation
+    #[inline]
+    #[must_use]
+    pub fn with_correlation(mut self, id: CorrelationId) -> Self {
+        self.correlation_id = Some(id);
+        self
+    }
+
+    /// With scheduling priority
+    #[inline]
+    #[must_use]
+    pub fn with_priority(mut self, priority: TaskPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// With capability requirements
+    #[inline]
+    #[must_use]
+    pub fn with_requirements(mut self, requirements: CapabilityRequirements) -> Self {
+        self.requirements = requirements;
+        self
+    }
+
+    /// With claim on an additional subtree
+    #[inline]
+    #[must_use]
+    pub fn with_claim(mut self, path: SymbolPath) -> Self {
+        self.claims.push(path);
+        self
+    }
+
+    /// Least-privilege filesystem scope for this task
+    ///
+    /// Grants write access beneath `root` to the target artifact and every
+    /// claim, mapping each symbol path segment to a path component
+    /// (`src.auth` covers `src/auth/` and `src/auth.rs`). Nothing else is
+    /// readable or writable.
+    #[must_use]
+    pub fn fs_scope(&self, root: impl Into<PathBuf>) -> FsScope {
+        std::iter::once(&self.target_artifact)
+            .chain(&self.claims)
+            .fold(FsScope::new(root), |scope, path| {
+                scope.allow_write(path.iter().collect::<PathBuf>())
+            })
+    }
+}
+
+/// Scheduling priority of a task
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskPriority {
+    /// Opportunistic work, first to be preempted
+    Background,
+    /// Below normal
+    Low,
+    /// Default priority
+    #[default]
+    Normal,
+    /// Above normal
+    High,
+    /// Never preempted
+    Critical,
 }
 
 /// Resource capacity specification for a task
@@ -503,6 +559,121 @@ pub enum DirectiveValue {
     List(Vec<DirectiveValue>),
 }
 
+impl From<&DirectiveValue> for serde_json::Value {
+    fn from(value: &DirectiveValue) -> Self {
+        match value {
+            DirectiveValue::Bool(b) => Self::Bool(*b),
+            DirectiveValue::Int(n) => Self::from(*n),
+            DirectiveValue::String(s) => Self::String(s.clone()),
+            DirectiveValue::List(values) => Self::Array(values.iter().map(Self::from).collect()),
+        }
+    }
+}
+
+impl From<DirectiveValue> for serde_json::Value {
+    fn from(value: DirectiveValue) -> Self {
+        Self::from(&value)
+    }
+}
+
+impl TryFrom<&serde_json::Value> for DirectiveValue {
+    type Error = DirectiveError;
+
+    fn try_from(value: &serde_json::Value) -> Result<Self, Self::Error> {
+        directive_value(value, "")
+    }
+}
+
+impl TryFrom<serde_json::Value> for DirectiveValue {
+    type Error = DirectiveError;
+
+    fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
+        Self::try_from(&value)
+    }
+}
+
+/// Convert a JSON value found at `key`, naming the offending element on failure
+fn directive_value(value: &serde_json::Value, key: &str) -> Result<DirectiveValue, DirectiveError> {
+    use serde_json::Value;
+
+    let unrepresentable = |found: String| DirectiveError::Unrepresentable {
+        key: key.to_string(),
+        found,
+    };
+    match value {
+        Value::Bool(b) => Ok(DirectiveValue::Bool(*b)),
+        Value::Number(n) => n
+            .as_i64()
+            .map(DirectiveValue::Int)
+            .ok_or_else(|| unrepresentable(format!("number {n}"))),
+        Value::String(s) => Ok(DirectiveValue::String(s.clone())),
+        Value::Array(values) => values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| directive_value(v, &format!("{key}[{i}]")))
+            .collect::<Result<_, _>>()
+            .map(DirectiveValue::List),
+        Value::Null => Err(unrepresentable("null".to_string())),
+        Value::Object(_) => Err(unrepresentable("object".to_string())),
+    }
+}
+
+/// Convert a directive set to the kernel's representation
+///
+/// The kernel set is keyed in sorted order, so equal sets serialize (and
+/// hash) identically regardless of insertion order. The conversion is
+/// lossless: [`directives_from_kernel`] gives back an equal set.
+#[must_use]
+pub fn directives_to_kernel(directives: &DirectiveSet) -> KernelDirectiveSet {
+    KernelDirectiveSet {
+        directives: directives
+            .iter()
+            .map(|(key, value)| (key.clone(), value.into()))
+            .collect(),
+    }
+}
+
+/// Kernel node spec for `task`: its directives, autonomy and budget
+pub(crate) fn kernel_node_spec(task: &Task) -> coa_kernel::prelude::NodeSpecV2 {
+    coa_kernel::prelude::NodeSpecV2::new(
+        directives_to_kernel(&task.directives),
+        task.autonomy,
+        kernel_caps(&task.resources),
+    )
+}
+
+/// Kernel budget of `caps`: timeout as CPU time, memory in bytes
+pub(crate) fn kernel_caps(caps: &ResourceCaps) -> coa_kernel::ResourceCaps {
+    coa_kernel::ResourceCaps {
+        cpu_time_ms: caps.timeout_secs.saturating_mul(1000),
+        memory_bytes: (caps.memory_mb as u64).saturating_mul(1024 * 1024),
+        token_limit: 0,
+        iteration_cap: 0,
+    }
+}
+
+/// Convert a kernel directive set back to coa-core's representation
+///
+/// # Errors
+///
+/// Returns [`DirectiveError::Unrepresentable`] for values with no
+/// [`DirectiveValue`] form (null, floats, objects, integers beyond `i64`).
+pub fn directives_from_kernel(directives: &KernelDirectiveSet) -> Result<DirectiveSet, DirectiveError> {
+    directives
+        .directives
+        .iter()
+        .map(|(key, value)| Ok((key.clone(), directive_value(value, key)?)))
+        .collect()
+}
+
+/// Canonical JSON encoding of a directive set: plain JSON values with keys
+/// in sorted order, suitable for hashing
+#[must_use]
+pub fn canonical_directive_json(directives: &DirectiveSet) -> String {
+    serde_json::Value::Object(directives_to_kernel(directives).directives.into_iter().collect())
+        .to_string()
+}
+
 /// Create new empty directive set
 #[inline]
 #[must_use]
@@ -563,6 +734,12 @@ pub struct AgentSpec {
     pub autonomy: AutonomyLevel,
     /// Resource limits
     pub resources: ResourceCaps,
+    /// Priority of the work the agent runs
+    #[serde(default)]
+    pub priority: TaskPriority,
+    /// Capabilities the agent advertises
+    #[serde(default)]
+    pub capabilities: AgentCapabilities,
 }
 
 impl AgentSpec {
@@ -575,9 +752,35 @@ impl AgentSpec {
             directives: new_directive_set(),
             autonomy: AutonomyLevel::L3,
             resources: ResourceCaps::default(),
+            priority: TaskPriority::default(),
+            capabilities: AgentCapabilities::default(),
         }
     }
 
+    /// With advertised capabilities
+    #[inline]
+    #[must_use]
+    pub fn with_capabilities(mut self, capabilities: AgentCapabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// With priority
+    #[inline]
+    #[must_use]
+    pub fn with_priority(mut self, priority: TaskPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// With resource limits
+    #[inline]
+    #[must_use]
+    pub fn with_resources(mut self, resources: ResourceCaps) -> Self {
+        self.resources = resources;
+        self
+    }
+
     /// From task
     #[inline]
     #[must_use]
@@ -587,6 +790,8 @@ impl AgentSpec {
             directives: task.directives.clone(),
             autonomy: task.autonomy,
             resources: task.resources,
+            priority: task.priority,
+            capabilities: AgentCapabilities::default(),
         }
     }
 }
@@ -602,6 +807,19 @@ pub struct ExecutionResult {
     pub artifacts_produced: Vec<ArtifactSummary>,
     /// Tasks completed
     pub tasks_completed: Vec<TaskId>,
+    /// Acceptance criteria the verification gate found unmet
+    pub unmet_criteria: Vec<crate::verification::UnmetCriterion>,
+    /// Intent the run was correlated with
+    pub correlation_id: Option<CorrelationId>,
+}
+
+impl ExecutionResult {
+    /// Whether the run may merge: every acceptance criterion passed
+    #[inline]
+    #[must_use]
+    pub fn is_merged(&self) -> bool {
+        self.unmet_criteria.is_empty()
+    }
 }
 
 /// Artifact summary in execution result
@@ -619,6 +837,44 @@ pub struct ArtifactSummary {
 mod tests {
     use super::*;
 
+    #[test]
+    fn directives_round_trip_through_kernel_in_stable_order() {
+        let entries = [
+            ("strict", DirectiveValue::Bool(true)),
+            ("coverage_target", DirectiveValue::Int(-90)),
+            ("endpoint", DirectiveValue::String("/users".to_string())),
+            (
+                "criteria",
+                DirectiveValue::List(vec![
+                    DirectiveValue::String("compiles".to_string()),
+                    DirectiveValue::List(vec![DirectiveValue::Int(1)]),
+                ]),
+            ),
+        ];
+        let forward: DirectiveSet = entries.iter().cloned().map(|(k, v)| (k.to_string(), v)).collect();
+        let reverse: DirectiveSet = entries.iter().rev().cloned().map(|(k, v)| (k.to_string(), v)).collect();
+
+        let kernel = directives_to_kernel(&forward);
+        assert_eq!(kernel.directives["criteria"], serde_json::json!(["compiles", [1]]));
+        assert_eq!(directives_from_kernel(&kernel).unwrap(), forward);
+        assert_eq!(canonical_directive_json(&forward), canonical_directive_json(&reverse));
+        assert_eq!(
+            canonical_directive_json(&forward),
+            r#"{"coverage_target":-90,"criteria":["compiles",[1]],"endpoint":"/users","strict":true}"#
+        );
+
+        let mut lossy = kernel;
+        lossy.directives.insert("ratio".to_string(), serde_json::json!([1, 0.5]));
+        assert_eq!(
+            directives_from_kernel(&lossy).unwrap_err(),
+            DirectiveError::Unrepresentable {
+                key: "ratio[1]".to_string(),
+                found: "number 0.5".to_string(),
+            }
+        );
+        assert!(DirectiveValue::try_from(serde_json::json!({ "a": 1 })).is_err());
+    }
+
     #[test]
     fn task_id_generation() {
         let id1 = TaskId::new();
@@ -628,8 +884,8 @@ mod tests {
 
     #[test]
     fn autonomy_level_value() {
-        assert_eq!(AutonomyLevel::L0.value(), 0);
-        assert_eq!(AutonomyLevel::L5.value(), 5);
+        assert_eq!(AutonomyLevel::L0.as_u8(), 0);
+        assert_eq!(AutonomyLevel::L5.as_u8(), 5);
     }
 
     #[test]
@@ -650,6 +906,20 @@ mod tests {
         assert_eq!(task.dependencies.len(), 1);
     }
 
+    #[test]
+    fn task_fs_scope_covers_target_and_claims() {
+        use coa_constitutional::FsAccess;
+
+        let task = Task::new("coder", "auth", SymbolPath::from_str("src.auth").unwrap())
+            .with_claim(SymbolPath::from_str("tests.auth").unwrap());
+        let scope = task.fs_scope("/work");
+
+        assert!(scope.permits("src/auth.rs", FsAccess::Write));
+        assert!(scope.permits("src/auth/login.rs", FsAccess::Write));
+        assert!(scope.permits("tests/auth.rs", FsAccess::Read));
+        assert!(!scope.permits("src/main.rs", FsAccess::Read));
+    }
+
     #[test]
     fn directive_set_operations() {
         let mut directives = new_directive_set();
@@ -663,10 +933,12 @@ mod tests {
     #[test]
     fn agent_spec_from_task() {
         let task = Task::new("dev", "implement feature", SymbolPath::from_str("api.login").unwrap())
-            .with_autonomy(AutonomyLevel::L4);
+            .with_autonomy(AutonomyLevel::L4)
+            .with_priority(TaskPriority::High);
 
         let spec = AgentSpec::from_task(&task);
         assert_eq!(spec.role, "dev");
         assert_eq!(spec.autonomy, AutonomyLevel::L4);
+        assert_eq!(spec.priority, TaskPriority::High);
     }
 }
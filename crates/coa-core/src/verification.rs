@@ -0,0 +1,346 @@
+//! Acceptance-criteria verification
+//!
+//! Each acceptance criterion of a [`Specification`] maps to a
+//! [`CriterionCheck`]: a test run, a static check, or a review nothing can
+//! automate. The decomposer closes every task graph with a gate task that
+//! depends on all other tasks; the orchestrator runs the gate's checks
+//! through a [`CriteriaExecutor`] and only merges the run when all pass.
+//! Unmet criteria are reported in
+//! [`ExecutionResult::unmet_criteria`](crate::types::ExecutionResult::unmet_criteria).
+
+use crate::test_runner::TestRunner;
+use crate::types::{DirectiveValue, Specification, Task};
+use coa_artifact::SymbolPath;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Directive listing the criteria a gate task verifies
+pub const ACCEPTANCE_CRITERIA_DIRECTIVE: &str = "acceptance_criteria";
+
+/// Role of gate tasks
+pub const GATE_ROLE: &str = "verifier";
+
+/// How a criterion is checked
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CheckKind {
+    /// Run tests, optionally only those matching a filter
+    Test { filter: Option<String> },
+    /// Run the linter
+    Lint,
+    /// Build the workspace
+    Build,
+    /// Needs judgement no command provides
+    Review,
+}
+
+impl CheckKind {
+    /// Check implied by the wording of `criterion`
+    ///
+    /// A backquoted name in a test criterion becomes the test filter, so
+    /// "tests in `parser` pass" runs only the parser tests.
+    #[must_use]
+    pub fn for_criterion(criterion: &str) -> Self {
+        let lower = criterion.to_lowercase();
+        if lower.contains("test") {
+            let filter = criterion
+                .split('`')
+                .nth(1)
+                .filter(|name| !name.trim().is_empty())
+                .map(|name| name.trim().to_string());
+            Self::Test { filter }
+        } else if ["lint", "clippy", "warning"].iter().any(|w| lower.contains(w)) {
+            Self::Lint
+        } else if ["build", "compile"].iter().any(|w| lower.contains(w)) {
+            Self::Build
+        } else {
+            Self::Review
+        }
+    }
+}
+
+/// One acceptance criterion and its check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CriterionCheck {
+    /// Criterion as written in the specification
+    pub criterion: String,
+    /// How it is checked
+    pub kind: CheckKind,
+}
+
+impl CriterionCheck {
+    /// Map `criterion` to its check
+    #[must_use]
+    pub fn new(criterion: impl Into<String>) -> Self {
+        let criterion = criterion.into();
+        let kind = CheckKind::for_criterion(&criterion);
+        Self { criterion, kind }
+    }
+}
+
+/// Result of checking one criterion
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CriterionOutcome {
+    /// Criterion as written in the specification
+    pub criterion: String,
+    /// Whether the check passed
+    pub passed: bool,
+    /// Why the check failed, or what it ran
+    pub detail: String,
+}
+
+/// Criterion a gate found unmet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnmetCriterion {
+    /// Criterion as written in the specification
+    pub criterion: String,
+    /// Why it is unmet
+    pub reason: String,
+}
+
+/// Runs the checks behind acceptance criteria
+#[async_trait::async_trait]
+pub trait CriteriaExecutor: Send + Sync + std::fmt::Debug {
+    /// Check a single criterion
+    async fn check(&self, check: &CriterionCheck) -> CriterionOutcome;
+}
+
+/// Runs checks as workspace commands (`cargo test`, `cargo clippy`,
+/// `cargo build` by default)
+///
+/// With a [`TestRunner`], test criteria run through it instead and fail with
+/// the names of the failing tests. Review criteria always fail: no command
+/// can sign them off.
+#[derive(Debug, Clone)]
+pub struct CommandCriteriaExecutor {
+    workdir: PathBuf,
+    test_runner: Option<TestRunner>,
+    test_command: Vec<String>,
+    lint_command: Vec<String>,
+    build_command: Vec<String>,
+}
+
+impl CommandCriteriaExecutor {
+    /// Create executor running cargo in `workdir`
+    #[must_use]
+    pub fn new(workdir: impl Into<PathBuf>) -> Self {
+        let command = |args: &[&str]| args.iter().map(|s| (*s).to_string()).collect();
+        Self {
+            workdir: workdir.into(),
+            test_runner: None,
+            test_command: command(&["cargo", "test"]),
+            lint_command: command(&["cargo", "clippy", "--", "-D", "warnings"]),
+            build_command: command(&["cargo", "build"]),
+        }
+    }
+
+    /// With test command (a test filter is appended as the last argument)
+    #[inline]
+    #[must_use]
+    pub fn with_test_command(mut self, command: Vec<String>) -> Self {
+        self.test_command = command;
+        self
+    }
+
+    /// Run test criteria through `runner`
+    #[inline]
+    #[must_use]
+    pub fn with_test_runner(mut self, runner: TestRunner) -> Self {
+        self.test_runner = Some(runner);
+        self
+    }
+
+    /// With lint command
+    #[inline]
+    #[must_use]
+    pub fn with_lint_command(mut self, command: Vec<String>) -> Self {
+        self.lint_command = command;
+        self
+    }
+
+    /// With build command
+    #[inline]
+    #[must_use]
+    pub fn with_build_command(mut self, command: Vec<String>) -> Self {
+        self.build_command = command;
+        self
+    }
+
+    async fn run(&self, args: &[String]) -> Result<String, String> {
+        let (program, rest) = args.split_first().ok_or("empty command")?;
+        let output = tokio::process::Command::new(program)
+            .args(rest)
+            .current_dir(&self.workdir)
+            .output()
+            .await
+            .map_err(|e| format!("failed to run {program}: {e}"))?;
+        let line = args.join(" ");
+        if output.status.success() {
+            Ok(line)
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let last = stderr.lines().last().unwrap_or_default();
+            Err(format!("`{line}` exited with {}: {last}", output.status))
+        }
+    }
+
+    async fn run_tests(runner: &TestRunner, filter: Option<&str>) -> Result<String, String> {
+        let report = runner.run(filter).await.map_err(|e| e.to_string())?;
+        if report.passed() {
+            return Ok(format!("{} tests passed", report.cases.len()));
+        }
+        let failed: Vec<&str> = report.failures().map(|case| case.name.as_str()).collect();
+        Err(format!("failing tests: {}", failed.join(", ")))
+    }
+}
+
+#[async_trait::async_trait]
+impl CriteriaExecutor for CommandCriteriaExecutor {
+    async fn check(&self, check: &CriterionCheck) -> CriterionOutcome {
+        let result = match &check.kind {
+            CheckKind::Test { filter } => match &self.test_runner {
+                Some(runner) => Self::run_tests(runner, filter.as_deref()).await,
+                None => {
+                    let mut args = self.test_command.clone();
+                    args.extend(filter.clone());
+                    self.run(&args).await
+                }
+            },
+            CheckKind::Lint => self.run(&self.lint_command).await,
+            CheckKind::Build => self.run(&self.build_command).await,
+            CheckKind::Review => Err("requires review sign-off".to_string()),
+        };
+        let (passed, detail) = match result {
+            Ok(detail) => (true, detail),
+            Err(detail) => (false, detail),
+        };
+        CriterionOutcome {
+            criterion: check.criterion.clone(),
+            passed,
+            detail,
+        }
+    }
+}
+
+/// Gate verifying a specification's acceptance criteria
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationGate {
+    checks: Vec<CriterionCheck>,
+}
+
+impl VerificationGate {
+    /// Gate for `criteria`, or `None` if there are none
+    #[must_use]
+    pub fn new(criteria: &[String]) -> Option<Self> {
+        if criteria.is_empty() {
+            return None;
+        }
+        Some(Self {
+            checks: criteria.iter().map(CriterionCheck::new).collect(),
+        })
+    }
+
+    /// Gate for the acceptance criteria of `spec`
+    #[inline]
+    #[must_use]
+    pub fn from_spec(spec: &Specification) -> Option<Self> {
+        Self::new(&spec.acceptance_criteria)
+    }
+
+    /// Gate carried by `task`, if it is a gate task
+    #[must_use]
+    pub fn from_task(task: &Task) -> Option<Self> {
+        let Some(DirectiveValue::List(values)) = task.directives.get(ACCEPTANCE_CRITERIA_DIRECTIVE)
+        else {
+            return None;
+        };
+        let criteria: Vec<String> = values
+            .iter()
+            .filter_map(|value| match value {
+                DirectiveValue::String(s) => Some(s.clone()),
+                _ => None,
+            })
+            .collect();
+        Self::new(&criteria)
+    }
+
+    /// Checks run by the gate
+    #[inline]
+    #[must_use]
+    pub fn checks(&self) -> &[CriterionCheck] {
+        &self.checks
+    }
+
+    /// Append a gate task to `tasks`, depending on all of them
+    pub fn append_to(&self, tasks: &mut Vec<Task>, target: SymbolPath) {
+        let criteria = self
+            .checks
+            .iter()
+            .map(|check| DirectiveValue::String(check.criterion.clone()))
+            .collect();
+        let gate = Task::new(GATE_ROLE, "Verify acceptance criteria", target)
+            .with_directive(ACCEPTANCE_CRITERIA_DIRECTIVE, DirectiveValue::List(criteria));
+        let gate = tasks.iter().fold(gate, |gate, task| gate.depends_on(task.id));
+        tasks.push(gate);
+    }
+
+    /// Run every check and collect the unmet criteria
+    pub async fn verify(&self, executor: &dyn CriteriaExecutor) -> Vec<UnmetCriterion> {
+        let mut unmet = Vec::new();
+        for check in &self.checks {
+            let outcome = executor.check(check).await;
+            if !outcome.passed {
+                unmet.push(UnmetCriterion {
+                    criterion: outcome.criterion,
+                    reason: outcome.detail,
+                });
+            }
+        }
+        unmet
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn criteria_map_to_checks() {
+        assert_eq!(
+            CheckKind::for_criterion("All tests in `parser` pass"),
+            CheckKind::Test {
+                filter: Some("parser".to_string())
+            }
+        );
+        assert_eq!(CheckKind::for_criterion("Unit tests pass"), CheckKind::Test { filter: None });
+        assert_eq!(CheckKind::for_criterion("No clippy warnings"), CheckKind::Lint);
+        assert_eq!(CheckKind::for_criterion("Workspace compiles"), CheckKind::Build);
+        assert_eq!(CheckKind::for_criterion("Handles empty input"), CheckKind::Review);
+    }
+
+    #[tokio::test]
+    async fn gate_task_depends_on_all_tasks_and_reports_unmet_criteria() {
+        let target = SymbolPath::from_str("src.parser").unwrap();
+        let mut tasks = vec![
+            Task::new("implementer", "Implement parser", target.clone()),
+            Task::new("tester", "Generate tests", target.clone()),
+        ];
+        let criteria = vec!["Workspace compiles".to_string(), "Handles empty input".to_string()];
+        VerificationGate::new(&criteria).unwrap().append_to(&mut tasks, target);
+
+        let gate_task = tasks.last().unwrap();
+        assert_eq!(gate_task.role, GATE_ROLE);
+        assert_eq!(gate_task.dependencies, vec![tasks[0].id, tasks[1].id]);
+
+        let gate = VerificationGate::from_task(gate_task).unwrap();
+        let executor = CommandCriteriaExecutor::new(std::env::temp_dir())
+            .with_build_command(vec!["true".to_string()]);
+        let unmet = gate.verify(&executor).await;
+        assert_eq!(unmet.len(), 1);
+        assert_eq!(unmet[0].criterion, "Handles empty input");
+
+        let failing = executor.with_build_command(vec!["false".to_string()]);
+        assert_eq!(gate.verify(&failing).await.len(), 2);
+        assert!(VerificationGate::new(&[]).is_none());
+    }
+}
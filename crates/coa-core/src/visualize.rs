@@ -0,0 +1,248 @@
+//! Task DAG visualization
+//!
+//! Renders the decomposed task list as Graphviz DOT or Mermaid so a human
+//! approving low-autonomy work (L0–L2) can review the plan before it is
+//! compiled into a kernel graph. Edges run from a dependency to the task
+//! that depends on it; dependencies outside the exported set are skipped.
+
+use crate::types::{AutonomyLevel, Task, TaskId};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Output format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Graphviz DOT
+    Dot,
+    /// Mermaid flowchart
+    Mermaid,
+}
+
+/// Exporter for a decomposed Task DAG
+#[derive(Debug, Clone)]
+pub struct TaskGraphExporter<'a> {
+    tasks: &'a [Task],
+    states: HashMap<TaskId, String>,
+}
+
+impl<'a> TaskGraphExporter<'a> {
+    /// Create exporter for tasks (as returned by the decomposer)
+    #[inline]
+    #[must_use]
+    pub fn new(tasks: &'a [Task]) -> Self {
+        Self {
+            tasks,
+            states: HashMap::new(),
+        }
+    }
+
+    /// Annotate a task with its current state (e.g. "running")
+    #[must_use]
+    pub fn with_state(mut self, task_id: TaskId, state: impl Into<String>) -> Self {
+        self.states.insert(task_id, state.into());
+        self
+    }
+
+    /// Render in the given format
+    #[must_use]
+    pub fn render(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Dot => self.to_dot(),
+            GraphFormat::Mermaid => self.to_mermaid(),
+        }
+    }
+
+    /// Render as Graphviz DOT
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph tasks {\n");
+        out.push_str("    rankdir=TB;\n");
+        out.push_str("    node [shape=box, style=\"rounded,filled\"];\n");
+
+        for (index, task) in self.tasks.iter().enumerate() {
+            let label = self
+                .label_lines(task)
+                .iter()
+                .map(|line| escape_dot(line))
+                .collect::<Vec<_>>()
+                .join("\\n");
+            let _ = writeln!(
+                out,
+                "    t{index} [label=\"{label}\", fillcolor=\"{}\"];",
+                autonomy_color(task.autonomy)
+            );
+        }
+
+        for (from, to) in self.edges() {
+            let _ = writeln!(out, "    t{from} -> t{to};");
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    /// Render as a Mermaid flowchart
+    #[must_use]
+    pub fn to_mermaid(&self) -> String {
+        let mut out = String::from("flowchart TD\n");
+
+        for (index, task) in self.tasks.iter().enumerate() {
+            let label = self
+                .label_lines(task)
+                .iter()
+                .map(|line| line.replace('"', "#quot;"))
+                .collect::<Vec<_>>()
+                .join("<br/>");
+            let _ = writeln!(out, "    t{index}[\"{label}\"]");
+        }
+
+        for (from, to) in self.edges() {
+            let _ = writeln!(out, "    t{from} --> t{to}");
+        }
+
+        let mut levels: Vec<AutonomyLevel> = self.tasks.iter().map(|task| task.autonomy).collect();
+        levels.sort_unstable();
+        levels.dedup();
+
+        for level in levels {
+            let members: Vec<String> = self
+                .tasks
+                .iter()
+                .enumerate()
+                .filter(|(_, task)| task.autonomy == level)
+                .map(|(index, _)| format!("t{index}"))
+                .collect();
+            let value = level.as_u8();
+            let _ = writeln!(out, "    classDef l{value} fill:{};", autonomy_color(level));
+            let _ = writeln!(out, "    class {} l{value};", members.join(","));
+        }
+
+        out
+    }
+
+    /// (dependency, dependent) index pairs
+    fn edges(&self) -> Vec<(usize, usize)> {
+        let position: HashMap<TaskId, usize> = self
+            .tasks
+            .iter()
+            .enumerate()
+            .map(|(index, task)| (task.id, index))
+            .collect();
+
+        let mut edges: Vec<(usize, usize)> = self
+            .tasks
+            .iter()
+            .enumerate()
+            .flat_map(|(index, task)| {
+                task.dependencies
+                    .iter()
+                    .filter_map(|dep| position.get(dep).map(|from| (*from, index)))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        edges.sort_unstable();
+        edges.dedup();
+        edges
+    }
+
+    /// Unescaped label lines
+    fn label_lines(&self, task: &Task) -> Vec<String> {
+        let resources = &task.resources;
+        let mut lines = vec![
+            format!("{}: {}", task.role, task.target_artifact),
+            task.description.clone(),
+            format!(
+                "L{} ({})",
+                task.autonomy.as_u8(),
+                approval_note(task.autonomy)
+            ),
+            format!(
+                "mem {}MB · cpu {}m · timeout {}s",
+                resources.memory_mb, resources.cpu_millicores, resources.timeout_secs
+            ),
+        ];
+        if let Some(state) = self.states.get(&task.id) {
+            lines.push(format!("state: {state}"));
+        }
+        lines
+    }
+}
+
+/// Fill color per autonomy level; human-gated levels stand out
+fn autonomy_color(level: AutonomyLevel) -> &'static str {
+    match level {
+        AutonomyLevel::L0 => "#f8d7da",
+        AutonomyLevel::L1 => "#fde2c4",
+        AutonomyLevel::L2 => "#fff3cd",
+        AutonomyLevel::L3 => "#d4edda",
+        AutonomyLevel::L4 => "#d1ecf1",
+        AutonomyLevel::L5 => "#e2e3e5",
+    }
+}
+
+fn approval_note(level: AutonomyLevel) -> &'static str {
+    match level {
+        AutonomyLevel::L0 => "human in the loop",
+        AutonomyLevel::L1 => "approval before merge",
+        AutonomyLevel::L2 => "human merge",
+        AutonomyLevel::L3 => "auto merge in sandbox",
+        AutonomyLevel::L4 => "auto merge + test deploy",
+        AutonomyLevel::L5 => "fully autonomous",
+    }
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coa_artifact::SymbolPath;
+    use std::str::FromStr;
+
+    fn tasks() -> Vec<Task> {
+        let design = Task::new(
+            "architect",
+            "design \"auth\" module",
+            SymbolPath::from_str("docs.auth").unwrap(),
+        )
+        .with_autonomy(AutonomyLevel::L1);
+        let code = Task::new(
+            "coder",
+            "implement login",
+            SymbolPath::from_str("src.auth").unwrap(),
+        )
+        .depends_on(design.id)
+        .depends_on(TaskId::new());
+        vec![design, code]
+    }
+
+    #[test]
+    fn dot_annotates_tasks_and_dependencies() {
+        let tasks = tasks();
+        let dot = TaskGraphExporter::new(&tasks)
+            .with_state(tasks[1].id, "running")
+            .to_dot();
+
+        assert!(dot.contains(
+            "architect: docs.auth\\ndesign \\\"auth\\\" module\\nL1 (approval before merge)"
+        ));
+        assert!(dot.contains("mem 512MB · cpu 500m · timeout 60s"));
+        assert!(dot.contains("state: running"));
+        assert!(dot.contains("t0 -> t1;"));
+        assert_eq!(dot.matches(" -> ").count(), 1);
+    }
+
+    #[test]
+    fn mermaid_classes_by_autonomy() {
+        let tasks = tasks();
+        let mermaid = TaskGraphExporter::new(&tasks).render(GraphFormat::Mermaid);
+
+        assert!(mermaid.starts_with("flowchart TD\n"));
+        assert!(mermaid.contains("design #quot;auth#quot; module"));
+        assert!(mermaid.contains("t0 --> t1"));
+        assert!(mermaid.contains("classDef l1 fill:#fde2c4;"));
+        assert!(mermaid.contains("class t1 l3;"));
+    }
+}
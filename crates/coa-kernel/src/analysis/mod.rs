@@ -0,0 +1,525 @@
+//! Static analysis and coverage gates
+//!
+//! Enforces the `security_scan_depth` and `required_test_coverage_percent`
+//! directives of a node's compiled [`ExecutionProfile`]. Analyzers are
+//! pluggable: a [`Scanner`] reports [`Finding`]s (clippy, semgrep, ...) and
+//! runs when its depth is within the profile's scan depth; a
+//! [`CoverageProbe`] measures line coverage. Scanners and probes run in the
+//! root of the node's filesystem scope.
+//!
+//! [`AnalysisGateExecutor`] runs an [`AnalysisGate`] as a gate node: every
+//! report is appended to the kernel event log under [`ANALYSIS_ACTION`],
+//! and the node fails (holding back its dependents) unless the report passes.
+
+use crate::directives;
+use crate::error::{AnalysisError, ExecutionError};
+use crate::executor::{NodeExecutionResult, NodeExecutor, NodeInputs, NodePayload};
+use crate::isolation::{FsScope, ScopeGuard};
+use crate::logging::EventLog;
+use crate::types::v2::ValidatedGraph;
+use crate::types::{ExecutionProfile, NodeId, ResourceCaps};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::process::Command;
+
+/// Event action recorded for each gate evaluation; `result` holds the
+/// [`AnalysisReport`] as JSON
+pub const ANALYSIS_ACTION: &str = "analysis_gate";
+
+/// Payload type of the report a gate node emits
+pub const ANALYSIS_PAYLOAD: &str = "AnalysisReport";
+
+/// Scan depth of lint-level scanners
+pub const LINT_DEPTH: u8 = 1;
+
+/// Scan depth of pattern-based security scanners
+pub const PATTERN_DEPTH: u8 = 2;
+
+/// Severity of a finding, lowest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Severity {
+    Info,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    /// Whether a finding of this severity fails the gate
+    pub fn is_blocking(self) -> bool {
+        self >= Severity::High
+    }
+}
+
+/// One issue reported by a scanner
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Finding {
+    pub scanner: String,
+    pub severity: Severity,
+    pub message: String,
+    /// File the finding points at, relative to the scope root
+    pub path: Option<String>,
+}
+
+/// Pluggable static analyzer
+#[async_trait::async_trait]
+pub trait Scanner: Send + Sync {
+    /// Name recorded on findings
+    fn name(&self) -> &str;
+
+    /// Scan depth at which this scanner starts running
+    fn depth(&self) -> u8;
+
+    /// Scan the workspace at `root`
+    async fn scan(&self, root: &Path) -> Result<Vec<Finding>, AnalysisError>;
+}
+
+/// Pluggable coverage measurement
+#[async_trait::async_trait]
+pub trait CoverageProbe: Send + Sync {
+    /// Line coverage of the workspace at `root`, in percent
+    async fn measure(&self, root: &Path) -> Result<f64, AnalysisError>;
+}
+
+/// Run `program` in `root` and return its stdout
+///
+/// Scanners exit non-zero when they find something, so the exit status is
+/// not treated as failure.
+async fn run_command(program: &[String], root: &Path) -> Result<String, AnalysisError> {
+    let (command, args) = program
+        .split_first()
+        .ok_or_else(|| AnalysisError::Spawn("empty command".to_string()))?;
+    let output = Command::new(command)
+        .args(args)
+        .current_dir(root)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| AnalysisError::Spawn(format!("{command}: {e}")))?;
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Scanner that runs a command and parses its output
+pub struct CommandScanner {
+    name: String,
+    depth: u8,
+    program: Vec<String>,
+    parse: fn(&str, &str) -> Result<Vec<Finding>, AnalysisError>,
+}
+
+impl CommandScanner {
+    /// `cargo clippy` at [`LINT_DEPTH`]; warnings are medium, errors high
+    pub fn clippy() -> Self {
+        Self {
+            name: "clippy".to_string(),
+            depth: LINT_DEPTH,
+            program: ["cargo", "clippy", "--message-format=json"].map(String::from).to_vec(),
+            parse: parse_clippy_json,
+        }
+    }
+
+    /// `semgrep` at [`PATTERN_DEPTH`], with its own severities
+    pub fn semgrep() -> Self {
+        Self {
+            name: "semgrep".to_string(),
+            depth: PATTERN_DEPTH,
+            program: ["semgrep", "scan", "--json", "--config", "auto"].map(String::from).to_vec(),
+            parse: parse_semgrep_json,
+        }
+    }
+
+    /// Replace the command the scanner runs, keeping its output format
+    pub fn with_program(mut self, program: Vec<String>) -> Self {
+        self.program = program;
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl Scanner for CommandScanner {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    async fn scan(&self, root: &Path) -> Result<Vec<Finding>, AnalysisError> {
+        let output = run_command(&self.program, root).await?;
+        (self.parse)(&self.name, &output)
+    }
+}
+
+/// Parse `cargo clippy --message-format=json` output
+pub fn parse_clippy_json(scanner: &str, output: &str) -> Result<Vec<Finding>, AnalysisError> {
+    Ok(output
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter(|entry| entry["reason"] == "compiler-message")
+        .filter_map(|entry| {
+            let message = &entry["message"];
+            let severity = match message["level"].as_str()? {
+                "error" => Severity::High,
+                "warning" => Severity::Medium,
+                _ => return None,
+            };
+            Some(Finding {
+                scanner: scanner.to_string(),
+                severity,
+                message: message["message"].as_str()?.to_string(),
+                path: message["spans"][0]["file_name"].as_str().map(str::to_string),
+            })
+        })
+        .collect())
+}
+
+/// Parse `semgrep --json` output
+pub fn parse_semgrep_json(scanner: &str, output: &str) -> Result<Vec<Finding>, AnalysisError> {
+    let report: serde_json::Value =
+        serde_json::from_str(output).map_err(|e| AnalysisError::Malformed(e.to_string()))?;
+    let results = report["results"]
+        .as_array()
+        .ok_or_else(|| AnalysisError::Malformed("missing results".to_string()))?;
+    Ok(results
+        .iter()
+        .map(|result| Finding {
+            scanner: scanner.to_string(),
+            severity: match result["extra"]["severity"].as_str() {
+                Some("ERROR") => Severity::High,
+                Some("WARNING") => Severity::Medium,
+                Some("INFO") => Severity::Low,
+                _ => Severity::Info,
+            },
+            message: result["extra"]["message"]
+                .as_str()
+                .or_else(|| result["check_id"].as_str())
+                .unwrap_or_default()
+                .to_string(),
+            path: result["path"].as_str().map(str::to_string),
+        })
+        .collect())
+}
+
+/// Coverage probe that runs a command and parses its output
+pub struct CommandCoverage {
+    program: Vec<String>,
+    parse: fn(&str) -> Result<f64, AnalysisError>,
+}
+
+impl CommandCoverage {
+    /// `cargo llvm-cov` line coverage
+    pub fn llvm_cov() -> Self {
+        Self {
+            program: ["cargo", "llvm-cov", "--json", "--summary-only"].map(String::from).to_vec(),
+            parse: parse_llvm_cov_json,
+        }
+    }
+
+    /// Replace the command the probe runs, keeping its output format
+    pub fn with_program(mut self, program: Vec<String>) -> Self {
+        self.program = program;
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl CoverageProbe for CommandCoverage {
+    async fn measure(&self, root: &Path) -> Result<f64, AnalysisError> {
+        let output = run_command(&self.program, root).await?;
+        (self.parse)(&output)
+    }
+}
+
+/// Parse the line coverage from `cargo llvm-cov --json --summary-only`
+pub fn parse_llvm_cov_json(output: &str) -> Result<f64, AnalysisError> {
+    let report: serde_json::Value =
+        serde_json::from_str(output).map_err(|e| AnalysisError::Malformed(e.to_string()))?;
+    report["data"][0]["totals"]["lines"]["percent"]
+        .as_f64()
+        .ok_or_else(|| AnalysisError::Malformed("missing line coverage totals".to_string()))
+}
+
+/// Outcome of one gate evaluation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnalysisReport {
+    /// Scan depth the profile asked for
+    pub scan_depth: u8,
+    /// Coverage the profile requires, in percent
+    pub required_coverage_percent: u8,
+    /// Scanners that ran
+    pub scanners: Vec<String>,
+    pub findings: Vec<Finding>,
+    /// Measured line coverage, when coverage is required
+    pub coverage_percent: Option<f64>,
+    /// Analyzers that failed to produce a result
+    pub errors: Vec<String>,
+}
+
+impl AnalysisReport {
+    /// Findings that fail the gate
+    pub fn blocking(&self) -> impl Iterator<Item = &Finding> {
+        self.findings.iter().filter(|f| f.severity.is_blocking())
+    }
+
+    /// Whether the profile's thresholds are met
+    pub fn passed(&self) -> bool {
+        let coverage_met = self.required_coverage_percent == 0
+            || self
+                .coverage_percent
+                .is_some_and(|percent| percent >= f64::from(self.required_coverage_percent));
+        self.errors.is_empty() && self.blocking().next().is_none() && coverage_met
+    }
+}
+
+/// Scanners and coverage probe checked against an execution profile
+#[derive(Default, Clone)]
+pub struct AnalysisGate {
+    scanners: Vec<Arc<dyn Scanner>>,
+    coverage: Option<Arc<dyn CoverageProbe>>,
+}
+
+impl AnalysisGate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a scanner
+    pub fn with_scanner(mut self, scanner: Arc<dyn Scanner>) -> Self {
+        self.scanners.push(scanner);
+        self
+    }
+
+    /// Measure coverage with `probe`
+    pub fn with_coverage(mut self, probe: Arc<dyn CoverageProbe>) -> Self {
+        self.coverage = Some(probe);
+        self
+    }
+
+    /// Run the analyzers `profile` calls for on the workspace at `root`
+    ///
+    /// Scanners deeper than `security_scan_depth` are skipped, and coverage
+    /// is only measured when the profile requires some.
+    pub async fn evaluate(&self, profile: &ExecutionProfile, root: &Path) -> AnalysisReport {
+        let mut report = AnalysisReport {
+            scan_depth: profile.security_scan_depth,
+            required_coverage_percent: profile.required_test_coverage_percent,
+            scanners: Vec::new(),
+            findings: Vec::new(),
+            coverage_percent: None,
+            errors: Vec::new(),
+        };
+        for scanner in self
+            .scanners
+            .iter()
+            .filter(|scanner| scanner.depth() <= profile.security_scan_depth)
+        {
+            report.scanners.push(scanner.name().to_string());
+            match scanner.scan(root).await {
+                Ok(findings) => report.findings.extend(findings),
+                Err(e) => report.errors.push(format!("{}: {e}", scanner.name())),
+            }
+        }
+        if profile.required_test_coverage_percent > 0 {
+            match &self.coverage {
+                Some(probe) => match probe.measure(root).await {
+                    Ok(percent) => report.coverage_percent = Some(percent),
+                    Err(e) => report.errors.push(format!("coverage: {e}")),
+                },
+                None => report.errors.push("coverage required but no probe configured".to_string()),
+            }
+        }
+        report
+    }
+}
+
+/// Node executor running an [`AnalysisGate`] for gate nodes
+///
+/// Each node is checked against the profile compiled from its own
+/// directives, in the root of the workspace scope.
+pub struct AnalysisGateExecutor {
+    gate: AnalysisGate,
+    scope: FsScope,
+    log: Arc<EventLog>,
+    profiles: HashMap<NodeId, ExecutionProfile>,
+}
+
+impl AnalysisGateExecutor {
+    /// Create an executor for the gate nodes of `graph`
+    pub fn new(gate: AnalysisGate, scope: FsScope, log: Arc<EventLog>, graph: &ValidatedGraph) -> Self {
+        let profiles = graph
+            .nodes()
+            .map(|(node_id, spec, _)| (node_id, directives::compile(&spec.directives).0))
+            .collect();
+        Self {
+            gate,
+            scope,
+            log,
+            profiles,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl NodeExecutor for AnalysisGateExecutor {
+    async fn execute_node(
+        &self,
+        node_id: NodeId,
+        token: &crate::autonomy::CapabilityToken,
+        _inputs: &NodeInputs,
+    ) -> Result<NodeExecutionResult, ExecutionError> {
+        let started = Instant::now();
+        let profile = self
+            .profiles
+            .get(&node_id)
+            .ok_or(ExecutionError::MissingProfile(node_id))?;
+        let guard = ScopeGuard::new(self.scope.clone(), token.clone(), Arc::clone(&self.log));
+        let report = self.gate.evaluate(profile, guard.scope().root()).await;
+        let value = serde_json::to_value(&report).unwrap_or_default();
+        // Logging is best-effort; the verdict stands either way
+        let _ = guard.record(ANALYSIS_ACTION, value.to_string());
+        Ok(NodeExecutionResult {
+            node_id,
+            success: report.passed(),
+            execution_time_ms: started.elapsed().as_millis() as u64,
+            resource_consumed: ResourceCaps {
+                cpu_time_ms: 0,
+                memory_bytes: 0,
+                token_limit: 0,
+                iteration_cap: 1,
+            },
+            output: Some(NodePayload::new(ANALYSIS_PAYLOAD, value)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{EventFilter, EventLogger};
+    use crate::construction::GraphBuilder;
+    use crate::executor::Executor;
+    use crate::types::v2::NodeSpecV2;
+    use crate::types::{AutonomyLevel, DirectiveSet, GraphType};
+    use ed25519_dalek::SigningKey;
+    use rand::rngs::OsRng;
+    use std::collections::BTreeMap;
+
+    struct FixedScanner(u8, Severity);
+
+    #[async_trait::async_trait]
+    impl Scanner for FixedScanner {
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        fn depth(&self) -> u8 {
+            self.0
+        }
+
+        async fn scan(&self, _root: &Path) -> Result<Vec<Finding>, AnalysisError> {
+            Ok(vec![Finding {
+                scanner: "fixed".to_string(),
+                severity: self.1,
+                message: "unsafe block".to_string(),
+                path: None,
+            }])
+        }
+    }
+
+    struct FixedCoverage(f64);
+
+    #[async_trait::async_trait]
+    impl CoverageProbe for FixedCoverage {
+        async fn measure(&self, _root: &Path) -> Result<f64, AnalysisError> {
+            Ok(self.0)
+        }
+    }
+
+    fn profile(depth: u64, coverage: u64) -> ExecutionProfile {
+        let mut directives = BTreeMap::new();
+        directives.insert("security_scan_depth".to_string(), depth.into());
+        directives.insert("required_test_coverage_percent".to_string(), coverage.into());
+        directives::compile(&DirectiveSet { directives }).0
+    }
+
+    #[test]
+    fn parses_scanner_and_coverage_output() {
+        let clippy = r#"{"reason":"compiler-artifact"}
+{"reason":"compiler-message","message":{"level":"warning","message":"unused variable","spans":[{"file_name":"src/lib.rs"}]}}
+{"reason":"compiler-message","message":{"level":"error","message":"mismatched types","spans":[]}}"#;
+        let findings = parse_clippy_json("clippy", clippy).unwrap();
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].severity, Severity::Medium);
+        assert_eq!(findings[0].path.as_deref(), Some("src/lib.rs"));
+        assert_eq!(findings[1].severity, Severity::High);
+
+        let semgrep = r#"{"results":[{"check_id":"rust.unsafe","path":"src/ffi.rs","extra":{"severity":"ERROR","message":"unsafe FFI"}}],"errors":[]}"#;
+        let findings = parse_semgrep_json("semgrep", semgrep).unwrap();
+        assert_eq!(findings[0].severity, Severity::High);
+        assert_eq!(findings[0].message, "unsafe FFI");
+        assert!(parse_semgrep_json("semgrep", "not json").is_err());
+
+        let cov = r#"{"data":[{"totals":{"lines":{"count":10,"covered":8,"percent":80.0}}}]}"#;
+        assert_eq!(parse_llvm_cov_json(cov).unwrap(), 80.0);
+    }
+
+    #[tokio::test]
+    async fn thresholds_follow_the_profile() {
+        let gate = AnalysisGate::new()
+            .with_scanner(Arc::new(FixedScanner(PATTERN_DEPTH, Severity::High)))
+            .with_coverage(Arc::new(FixedCoverage(75.0)));
+        let root = Path::new(".");
+
+        // Scanner too deep for the profile, no coverage required
+        let report = gate.evaluate(&profile(1, 0), root).await;
+        assert!(report.scanners.is_empty());
+        assert!(report.passed());
+
+        assert!(!gate.evaluate(&profile(2, 0), root).await.passed());
+        assert!(gate.evaluate(&profile(0, 70), root).await.passed());
+        assert!(!gate.evaluate(&profile(0, 80), root).await.passed());
+        assert!(!AnalysisGate::new().evaluate(&profile(0, 10), root).await.passed());
+    }
+
+    #[tokio::test]
+    async fn gate_node_fails_and_logs_its_report() {
+        let mut directives = BTreeMap::new();
+        directives.insert("required_test_coverage_percent".to_string(), 90.into());
+        let spec = NodeSpecV2::new(
+            DirectiveSet { directives },
+            AutonomyLevel::L2,
+            ResourceCaps {
+                cpu_time_ms: 1000,
+                memory_bytes: 1024,
+                token_limit: 100,
+                iteration_cap: 10,
+            },
+        );
+        let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
+        let gate_node = builder.add_node(spec);
+        let signing_key = SigningKey::generate(&mut OsRng);
+        let graph = builder.validate(&signing_key).unwrap();
+
+        let log = Arc::new(EventLog::default());
+        let gate = AnalysisGate::new().with_coverage(Arc::new(FixedCoverage(40.0)));
+        let node_executor =
+            AnalysisGateExecutor::new(gate, FsScope::new(std::env::temp_dir()), Arc::clone(&log), &graph);
+        let executor = Executor::with_executor(signing_key.verifying_key(), Arc::new(node_executor));
+        let summary = match executor.run(graph).await {
+            Ok(summary) => summary,
+            Err(failure) => failure.summary,
+        };
+        assert_eq!(summary.failed().next().map(|r| r.node_id), Some(gate_node));
+
+        let entries = log.query_events(EventFilter::default(), 10).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].event.action, ANALYSIS_ACTION);
+        let report: AnalysisReport = serde_json::from_str(&entries[0].event.result).unwrap();
+        assert_eq!(report.coverage_percent, Some(40.0));
+    }
+}
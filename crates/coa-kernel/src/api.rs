@@ -5,7 +5,10 @@
 //! which provides the v2.0 two-phase architecture types.
 
 use crate::types::{AutonomyLevel, GraphId, GraphType, NodeId, NodeSpec, NodeState, ResourceCaps};
-use crate::error::StateMachineError;
+use crate::error::{NegotiationError, StateMachineError};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
 use std::time::Duration;
 
 /// Report from validating a capability token
@@ -62,6 +65,9 @@ pub enum ApiExecutionErrorKind {
     Timeout,
     IsolationFailure,
     TokenInvalid,
+    ScopeViolation,
+    /// Work was cancelled before it completed
+    Cancelled,
     Internal,
 }
 
@@ -69,10 +75,120 @@ pub enum ApiExecutionErrorKind {
 #[derive(Debug, Clone, Default)]
 pub struct EventFilter {
     pub node_id: Option<NodeId>,
-    pub action: Option<String>,
+    pub action: Option<crate::logging::Action>,
     pub since_timestamp: Option<u64>,
     pub until_timestamp: Option<u64>,
     pub autonomy_level: Option<AutonomyLevel>,
+    /// Events of this intent (all of its tasks) or task
+    pub correlation_id: Option<crate::types::CorrelationId>,
+    /// Top-level fields a JSON `result` must hold with these values
+    pub result_fields: BTreeMap<String, serde_json::Value>,
+}
+
+impl EventFilter {
+    /// Events recorded for `action`
+    pub fn for_action(action: impl Into<crate::logging::Action>) -> Self {
+        Self {
+            action: Some(action.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Also require the JSON result field `name` to equal `value`
+    #[must_use]
+    pub fn with_result_field(mut self, name: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.result_fields.insert(name.into(), value.into());
+        self
+    }
+
+    /// Check if `event` passes every constraint of the filter
+    pub fn matches(&self, event: &crate::logging::Event) -> bool {
+        self.node_id.map_or(true, |id| event.node_id == id)
+            && self.action.as_ref().map_or(true, |a| &event.action == a)
+            && self.since_timestamp.map_or(true, |t| event.timestamp >= t)
+            && self.until_timestamp.map_or(true, |t| event.timestamp <= t)
+            && self.autonomy_level.map_or(true, |l| event.autonomy_level == l)
+            && self.correlation_id.as_ref().map_or(true, |id| {
+                event.correlation_id.as_ref().is_some_and(|c| id.covers(c))
+            })
+            && self.matches_result(&event.result)
+    }
+
+    /// Check if `result` satisfies the result field constraints
+    ///
+    /// Without constraints every result matches; with them, only JSON
+    /// objects holding each field with an equal value do.
+    pub fn matches_result(&self, result: &str) -> bool {
+        if self.result_fields.is_empty() {
+            return true;
+        }
+        serde_json::from_str::<serde_json::Value>(result).is_ok_and(|value| {
+            self.result_fields
+                .iter()
+                .all(|(name, expected)| value.get(name) == Some(expected))
+        })
+    }
+}
+
+/// Order of query results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QueryOrder {
+    /// Oldest first (log order)
+    #[default]
+    Ascending,
+    /// Newest first
+    Descending,
+}
+
+/// Position in a log to resume a query after
+///
+/// Opaque; take it from [`EventPage::next`] and pass it back with the same
+/// filter and order to fetch the following page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EventCursor(pub(crate) usize);
+
+/// Filtered, ordered and paginated event query
+#[derive(Debug, Clone, Default)]
+pub struct EventQuery {
+    pub filter: EventFilter,
+    /// Maximum entries per page
+    pub limit: usize,
+    pub order: QueryOrder,
+    /// Resume after this position (`None` for the first page)
+    pub cursor: Option<EventCursor>,
+}
+
+impl EventQuery {
+    /// First page of up to `limit` matching events, oldest first
+    pub fn new(filter: EventFilter, limit: usize) -> Self {
+        Self {
+            filter,
+            limit,
+            ..Self::default()
+        }
+    }
+
+    /// Return newest events first
+    #[must_use]
+    pub fn descending(mut self) -> Self {
+        self.order = QueryOrder::Descending;
+        self
+    }
+
+    /// Continue after `cursor`
+    #[must_use]
+    pub fn after(mut self, cursor: EventCursor) -> Self {
+        self.cursor = Some(cursor);
+        self
+    }
+}
+
+/// One page of query results
+#[derive(Debug, Clone)]
+pub struct EventPage {
+    pub entries: Vec<LogEntry>,
+    /// Cursor for the next page (`None` if this is the last)
+    pub next: Option<EventCursor>,
 }
 
 /// A single log entry
@@ -112,6 +228,8 @@ pub enum SchedulerErrorKind {
     AlreadyScheduled,
     Timeout,
     Cancelled,
+    /// Graph completed, aborted or was archived
+    GraphClosed,
 }
 
 /// Statistics for a graph
@@ -125,7 +243,7 @@ pub struct GraphStats {
 }
 
 /// API compatibility enum
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Compatibility {
     Compatible,
     Deprecated,
@@ -133,19 +251,210 @@ pub enum Compatibility {
     Incompatible(Vec<String>),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct ApiVersion {
     pub major: u16,
     pub minor: u16,
     pub patch: u16,
 }
 
+impl ApiVersion {
+    pub const fn new(major: u16, minor: u16, patch: u16) -> Self {
+        Self { major, minor, patch }
+    }
+}
+
+impl fmt::Display for ApiVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
 pub const KERNEL_API_VERSION: ApiVersion = ApiVersion {
     major: 2,
     minor: 0,
     patch: 0,
 };
 
+/// Oldest API version the kernel still serves
+pub const MIN_SUPPORTED_API_VERSION: ApiVersion = ApiVersion::new(2, 0, 0);
+
+/// Inclusive range of API versions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionRange {
+    pub min: ApiVersion,
+    pub max: ApiVersion,
+}
+
+impl VersionRange {
+    pub fn new(min: ApiVersion, max: ApiVersion) -> Self {
+        Self { min, max }
+    }
+
+    /// Range containing only `version`
+    pub fn exactly(version: ApiVersion) -> Self {
+        Self::new(version, version)
+    }
+
+    pub fn contains(&self, version: ApiVersion) -> bool {
+        self.min <= version && version <= self.max
+    }
+
+    /// Highest version both ranges contain
+    pub fn highest_common(&self, other: &VersionRange) -> Option<ApiVersion> {
+        let max = self.max.min(other.max);
+        (max >= self.min.max(other.min)).then_some(max)
+    }
+}
+
+/// Optional kernel subsystem a server may advertise
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KernelFeature {
+    /// Runtime graph expansion (`StagedConstruction`)
+    Expansion,
+    /// Capability token revocation
+    Revocation,
+    /// Persisted event logs and journals (`replay`)
+    Persistence,
+}
+
+/// Client side of the version handshake
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NegotiationRequest {
+    /// Versions the client can speak
+    pub supported: VersionRange,
+    /// Features the client cannot run without
+    #[serde(default)]
+    pub required_features: BTreeSet<KernelFeature>,
+}
+
+impl NegotiationRequest {
+    /// Request for the versions in `supported` and no required features
+    pub fn new(supported: VersionRange) -> Self {
+        Self {
+            supported,
+            required_features: BTreeSet::new(),
+        }
+    }
+
+    /// Require `feature`
+    pub fn with_required_feature(mut self, feature: KernelFeature) -> Self {
+        self.required_features.insert(feature);
+        self
+    }
+}
+
+/// Server side of the version handshake
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NegotiationResponse {
+    /// Version the session speaks
+    pub selected: ApiVersion,
+    /// Every optional subsystem the server offers
+    pub features: BTreeSet<KernelFeature>,
+    /// `Deprecated` if the selected version is due for removal
+    pub compatibility: Compatibility,
+}
+
+impl NegotiationResponse {
+    pub fn supports(&self, feature: KernelFeature) -> bool {
+        self.features.contains(&feature)
+    }
+}
+
+/// Server's negotiation policy
+///
+/// Selects the highest version both sides support and advertises the
+/// optional subsystems this server has enabled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiNegotiator {
+    supported: VersionRange,
+    features: BTreeSet<KernelFeature>,
+    deprecated_below: Option<ApiVersion>,
+}
+
+impl Default for ApiNegotiator {
+    /// This kernel's versions, advertising expansion and persistence
+    fn default() -> Self {
+        Self::new(VersionRange::new(MIN_SUPPORTED_API_VERSION, KERNEL_API_VERSION))
+            .with_feature(KernelFeature::Expansion)
+            .with_feature(KernelFeature::Persistence)
+    }
+}
+
+impl ApiNegotiator {
+    /// Negotiator serving `supported` with no optional features
+    pub fn new(supported: VersionRange) -> Self {
+        Self {
+            supported,
+            features: BTreeSet::new(),
+            deprecated_below: None,
+        }
+    }
+
+    /// Advertise `feature`
+    pub fn with_feature(mut self, feature: KernelFeature) -> Self {
+        self.features.insert(feature);
+        self
+    }
+
+    /// Flag sessions below `version` as deprecated
+    pub fn with_deprecated_below(mut self, version: ApiVersion) -> Self {
+        self.deprecated_below = Some(version);
+        self
+    }
+
+    pub fn supported(&self) -> VersionRange {
+        self.supported
+    }
+
+    pub fn features(&self) -> &BTreeSet<KernelFeature> {
+        &self.features
+    }
+
+    /// Compatibility of a client speaking `version`
+    pub fn compatibility(&self, version: ApiVersion) -> Compatibility {
+        if !self.supported.contains(version) {
+            Compatibility::Incompatible(vec![format!(
+                "version {version} outside supported range {}..={}",
+                self.supported.min, self.supported.max
+            )])
+        } else if self.deprecated_below.is_some_and(|below| version < below) {
+            Compatibility::Deprecated
+        } else {
+            Compatibility::Compatible
+        }
+    }
+
+    /// Answer a client's handshake
+    ///
+    /// # Errors
+    /// - `NegotiationError::NoCommonVersion` if the version ranges do not overlap
+    /// - `NegotiationError::MissingFeatures` if a required feature is not offered
+    pub fn negotiate(&self, request: &NegotiationRequest) -> Result<NegotiationResponse, NegotiationError> {
+        let selected = self
+            .supported
+            .highest_common(&request.supported)
+            .ok_or(NegotiationError::NoCommonVersion {
+                client: request.supported,
+                server: self.supported,
+            })?;
+        let missing: Vec<KernelFeature> = request
+            .required_features
+            .difference(&self.features)
+            .copied()
+            .collect();
+        if !missing.is_empty() {
+            return Err(NegotiationError::MissingFeatures(missing));
+        }
+        Ok(NegotiationResponse {
+            selected,
+            features: self.features.clone(),
+            compatibility: self.compatibility(selected),
+        })
+    }
+}
+
 /// Graph management trait (legacy - use GraphBuilder directly)
 pub trait GraphManager {
     fn create_graph(&self, graph_type: GraphType) -> Result<GraphId, crate::error::KernelError>;
@@ -206,7 +515,12 @@ pub trait ExecutionRuntime {
 /// Event logger trait
 pub trait EventLogger {
     fn log_event(&self, event: crate::logging::Event) -> Result<crate::types::EventId, crate::error::LogError>;
-    fn query_events(&self, filter: EventFilter, limit: usize) -> Result<Vec<LogEntry>, crate::error::KernelError>;
+    /// First `limit` matching events in log order
+    fn query_events(&self, filter: EventFilter, limit: usize) -> Result<Vec<LogEntry>, crate::error::KernelError> {
+        self.query_page(EventQuery::new(filter, limit)).map(|page| page.entries)
+    }
+    /// One page of matching events
+    fn query_page(&self, query: EventQuery) -> Result<EventPage, crate::error::KernelError>;
     fn verify_integrity(&self) -> Result<IntegrityReport, crate::error::KernelError>;
 }
 
@@ -225,3 +539,53 @@ pub trait Scheduler: Send + Sync {
         timeout: Duration,
     ) -> Result<ExecutionResult, SchedulerError>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negotiation_selects_highest_common_version() {
+        let server = ApiNegotiator::new(VersionRange::new(ApiVersion::new(2, 0, 0), ApiVersion::new(2, 3, 0)))
+            .with_feature(KernelFeature::Expansion)
+            .with_deprecated_below(ApiVersion::new(2, 2, 0));
+
+        let modern = NegotiationRequest::new(VersionRange::new(ApiVersion::new(2, 1, 0), ApiVersion::new(3, 0, 0)));
+        let response = server.negotiate(&modern).unwrap();
+        assert_eq!(response.selected, ApiVersion::new(2, 3, 0));
+        assert_eq!(response.compatibility, Compatibility::Compatible);
+        assert!(response.supports(KernelFeature::Expansion));
+        assert!(!response.supports(KernelFeature::Revocation));
+
+        let legacy = NegotiationRequest::new(VersionRange::exactly(ApiVersion::new(2, 1, 0)));
+        assert_eq!(server.negotiate(&legacy).unwrap().compatibility, Compatibility::Deprecated);
+
+        let wire = serde_json::to_string(&response).unwrap();
+        assert!(wire.contains("\"expansion\""));
+        assert_eq!(serde_json::from_str::<NegotiationResponse>(&wire).unwrap(), response);
+    }
+
+    #[test]
+    fn negotiation_fails_without_overlap_or_required_features() {
+        let server = ApiNegotiator::default();
+        assert!(server.supported().contains(KERNEL_API_VERSION));
+
+        let future = NegotiationRequest::new(VersionRange::exactly(ApiVersion::new(3, 0, 0)));
+        assert!(matches!(
+            server.negotiate(&future),
+            Err(NegotiationError::NoCommonVersion { .. })
+        ));
+        assert!(matches!(
+            server.compatibility(ApiVersion::new(3, 0, 0)),
+            Compatibility::Incompatible(_)
+        ));
+
+        let revoking = NegotiationRequest::new(VersionRange::exactly(KERNEL_API_VERSION))
+            .with_required_feature(KernelFeature::Persistence)
+            .with_required_feature(KernelFeature::Revocation);
+        assert_eq!(
+            server.negotiate(&revoking),
+            Err(NegotiationError::MissingFeatures(vec![KernelFeature::Revocation]))
+        );
+    }
+}
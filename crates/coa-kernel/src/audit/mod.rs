@@ -0,0 +1,713 @@
+//! Run Audit Reports
+//!
+//! [`RunAuditor`] compiles everything a compliance review needs about one
+//! completed run into a [`RunAuditReport`]:
+//!
+//! - the graph executed and how each node ended
+//! - every capability token issued, with its filesystem scope when known
+//! - every artifact read or written, with content hashes
+//! - every escalation and approval
+//! - resource consumption against each node's caps and the run budget
+//!
+//! Sources are the [`ValidatedGraph`], its [`ExecutionSummary`], the kernel
+//! [`EventLog`] and the [`CompositionJournal`]; nothing is re-executed. The
+//! report renders to JSON ([`RunAuditReport::to_json`]) and Markdown
+//! ([`RunAuditReport::to_markdown`]).
+
+use crate::analysis::AnalysisReport;
+use crate::isolation::FsScope;
+use crate::logging::{Action, Event, EventLog};
+use crate::redaction::{RedactionPolicy, TelemetrySink};
+use crate::replay::CompositionJournal;
+use crate::types::{
+    AutonomyLevel, CorrelationId, ExecutionSummary, GraphId, GraphType, NodeId, NodeRunStatus,
+    ResourceCaps, Timestamp, ValidatedGraph,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+/// How a node ended, for the audit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditNode {
+    pub node_id: NodeId,
+    pub autonomy_ceiling: AutonomyLevel,
+    /// `None` if the run was not summarized
+    pub status: Option<NodeRunStatus>,
+    pub retries: u32,
+    pub cached: bool,
+    pub error: Option<String>,
+}
+
+/// Capability token issued for a node
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditToken {
+    pub node_id: NodeId,
+    pub autonomy_level: AutonomyLevel,
+    pub caps: ResourceCaps,
+    /// Hex-encoded directive profile hash
+    pub directive_hash: String,
+    pub issued_at: Timestamp,
+    pub expires_at: Timestamp,
+    pub bound_operation: String,
+    /// Filesystem scope the node ran under, if provided to the auditor
+    pub scope: Option<FsScope>,
+}
+
+/// Direction of an artifact access
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ArtifactAccessKind {
+    Read,
+    Write,
+}
+
+/// Artifact read or written during the run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactAccess {
+    pub timestamp: Timestamp,
+    pub node_id: Option<NodeId>,
+    pub kind: ArtifactAccessKind,
+    /// Path, symbol path or artifact type, whichever the source recorded
+    pub artifact: String,
+    /// Content hash before a write
+    pub base_hash: Option<String>,
+    /// Content hash read, or produced by a write
+    pub hash: String,
+}
+
+/// Escalation raised during the run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEscalation {
+    pub timestamp: Option<Timestamp>,
+    pub node_id: NodeId,
+    /// Event action or `"node_failed"`
+    pub reason: String,
+    pub detail: String,
+}
+
+/// Human approval a node required or received
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditApproval {
+    pub node_id: NodeId,
+    /// Node's autonomy ceiling requires human approval
+    pub required: bool,
+    /// When an approval event was recorded
+    pub approved_at: Option<Timestamp>,
+    /// Approval event result (e.g. the approver)
+    pub detail: Option<String>,
+}
+
+/// Node consumption against its caps
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    pub node_id: NodeId,
+    pub caps: ResourceCaps,
+    pub consumed: ResourceCaps,
+    pub within_caps: bool,
+}
+
+/// Structured audit of one run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunAuditReport {
+    pub graph_id: GraphId,
+    pub graph_type: GraphType,
+    pub correlation_id: Option<CorrelationId>,
+    /// Nodes, ordered by ID
+    pub nodes: Vec<AuditNode>,
+    pub edges: Vec<(NodeId, NodeId)>,
+    pub tokens: Vec<AuditToken>,
+    /// Accesses in time order
+    pub artifacts: Vec<ArtifactAccess>,
+    pub escalations: Vec<AuditEscalation>,
+    pub approvals: Vec<AuditApproval>,
+    pub resources: Vec<ResourceUsage>,
+    /// Run budget reserved at validation, if any
+    pub budget: Option<ResourceCaps>,
+    /// Total consumed by successful nodes, if the run was summarized
+    pub consumed: Option<ResourceCaps>,
+    pub execution_time_ms: Option<u64>,
+}
+
+/// Compiles a [`RunAuditReport`] from a run's records
+#[derive(Debug)]
+pub struct RunAuditor<'a> {
+    graph: &'a ValidatedGraph,
+    summary: Option<&'a ExecutionSummary>,
+    events: Vec<Event>,
+    journal: Option<&'a CompositionJournal>,
+    scopes: HashMap<NodeId, FsScope>,
+    correlation: Option<CorrelationId>,
+}
+
+impl<'a> RunAuditor<'a> {
+    /// Auditor for a run of `graph`
+    pub fn new(graph: &'a ValidatedGraph) -> Self {
+        Self {
+            graph,
+            summary: None,
+            events: Vec::new(),
+            journal: None,
+            scopes: HashMap::new(),
+            correlation: None,
+        }
+    }
+
+    /// With the run's execution summary
+    pub fn with_summary(mut self, summary: &'a ExecutionSummary) -> Self {
+        self.summary = Some(summary);
+        self
+    }
+
+    /// With the kernel event log (only events of the graph's nodes count)
+    pub fn with_log(mut self, log: &EventLog) -> Self {
+        self.events = log.events();
+        self
+    }
+
+    /// With the composition journal
+    pub fn with_journal(mut self, journal: &'a CompositionJournal) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    /// With the filesystem scope a node ran under
+    pub fn with_scope(mut self, node_id: NodeId, scope: FsScope) -> Self {
+        self.scopes.insert(node_id, scope);
+        self
+    }
+
+    /// Only count events of this intent or task
+    pub fn with_correlation(mut self, id: CorrelationId) -> Self {
+        self.correlation = Some(id);
+        self
+    }
+
+    /// Compile the report
+    pub fn compile(&self) -> RunAuditReport {
+        let node_ids: Vec<NodeId> = self.graph.node_ids().collect();
+        let record = |id: NodeId| self.summary.and_then(|summary| summary.node(id));
+        let events: Vec<&Event> = self
+            .events
+            .iter()
+            .filter(|e| self.graph.get_node_spec(e.node_id).is_some())
+            .filter(|e| {
+                self.correlation.as_ref().map_or(true, |id| {
+                    e.correlation_id.as_ref().is_some_and(|c| id.covers(c))
+                })
+            })
+            .collect();
+
+        let nodes = node_ids
+            .iter()
+            .filter_map(|&id| {
+                let spec = self.graph.get_node_spec(id)?;
+                let record = record(id);
+                Some(AuditNode {
+                    node_id: id,
+                    autonomy_ceiling: spec.autonomy_ceiling,
+                    status: record.map(|r| r.status),
+                    retries: record.map_or(0, |r| r.retries),
+                    cached: record.is_some_and(|r| r.cached),
+                    error: record
+                        .and_then(|r| r.error.as_ref())
+                        .map(ToString::to_string),
+                })
+            })
+            .collect();
+
+        let tokens = node_ids
+            .iter()
+            .filter_map(|&id| {
+                let token = self.graph.get_node_token(id)?;
+                Some(AuditToken {
+                    node_id: id,
+                    autonomy_level: token.autonomy_level,
+                    caps: token.caps,
+                    directive_hash: hex::encode(token.directive_hash.0),
+                    issued_at: token.issued_at,
+                    expires_at: token.expires_at,
+                    bound_operation: token.bound_operation.clone(),
+                    scope: self.scopes.get(&id).cloned(),
+                })
+            })
+            .collect();
+
+        let resources = node_ids
+            .iter()
+            .filter_map(|&id| {
+                let caps = self.graph.get_node_spec(id)?.resource_bounds;
+                let consumed = record(id)?.resource_consumed;
+                Some(ResourceUsage {
+                    node_id: id,
+                    caps,
+                    consumed,
+                    within_caps: consumed.fits_within(&caps),
+                })
+            })
+            .collect();
+
+        RunAuditReport {
+            graph_id: self.graph.graph_id(),
+            graph_type: self.graph.graph_type(),
+            correlation_id: self.correlation.clone(),
+            nodes,
+            edges: self.graph.edges().to_vec(),
+            tokens,
+            artifacts: self.artifacts(&events),
+            escalations: self.escalations(&events),
+            approvals: self.approvals(&node_ids, &events),
+            resources,
+            budget: self.graph.budget_envelope().copied(),
+            consumed: self.summary.map(|s| s.resource_consumed),
+            execution_time_ms: self.summary.map(|s| s.execution_time_ms),
+        }
+    }
+
+    fn artifacts(&self, events: &[&Event]) -> Vec<ArtifactAccess> {
+        let mut accesses: Vec<ArtifactAccess> = events
+            .iter()
+            .filter_map(|e| {
+                let value: serde_json::Value = serde_json::from_str(&e.result).ok()?;
+                let field =
+                    |name: &str| value.get(name).and_then(|v| v.as_str()).map(str::to_string);
+                let (kind, artifact, base_hash) = match e.action {
+                    Action::ArtifactRead => (ArtifactAccessKind::Read, field("path")?, None),
+                    // The journal names written artifacts more precisely
+                    Action::CompositionApplied if self.journal.is_none() => (
+                        ArtifactAccessKind::Write,
+                        field("artifact_type")?,
+                        field("base_hash"),
+                    ),
+                    _ => return None,
+                };
+                let hash = field(if kind == ArtifactAccessKind::Read {
+                    "hash"
+                } else {
+                    "result_hash"
+                })?;
+                Some(ArtifactAccess {
+                    timestamp: e.timestamp,
+                    node_id: Some(e.node_id),
+                    kind,
+                    artifact,
+                    base_hash,
+                    hash,
+                })
+            })
+            .collect();
+        if let Some(journal) = self.journal {
+            accesses.extend(
+                journal
+                    .entries()
+                    .into_iter()
+                    .filter(|entry| {
+                        entry
+                            .node_id
+                            .map_or(true, |id| self.graph.get_node_spec(id).is_some())
+                    })
+                    .map(|entry| ArtifactAccess {
+                        timestamp: entry.timestamp,
+                        node_id: entry.node_id,
+                        kind: ArtifactAccessKind::Write,
+                        artifact: entry.artifact,
+                        base_hash: Some(entry.base_hash),
+                        hash: entry.result_hash,
+                    }),
+            );
+        }
+        accesses.sort_by_key(|access| access.timestamp);
+        accesses
+    }
+
+    fn escalations(&self, events: &[&Event]) -> Vec<AuditEscalation> {
+        let mut escalations: Vec<AuditEscalation> = events
+            .iter()
+            .filter(|e| match e.action {
+                Action::Escalation | Action::ScopeViolation => true,
+                Action::AnalysisGate => serde_json::from_str::<AnalysisReport>(&e.result)
+                    .is_ok_and(|report| !report.passed()),
+                _ => false,
+            })
+            .map(|e| AuditEscalation {
+                timestamp: Some(e.timestamp),
+                node_id: e.node_id,
+                reason: e.action.to_string(),
+                detail: e.result.clone(),
+            })
+            .collect();
+        if let Some(summary) = self.summary {
+            escalations.extend(summary.failed().map(|record| {
+                AuditEscalation {
+                    timestamp: None,
+                    node_id: record.node_id,
+                    reason: "node_failed".to_string(),
+                    detail: record
+                        .error
+                        .as_ref()
+                        .map_or_else(|| "node reported failure".to_string(), ToString::to_string),
+                }
+            }));
+        }
+        escalations
+    }
+
+    fn approvals(&self, node_ids: &[NodeId], events: &[&Event]) -> Vec<AuditApproval> {
+        node_ids
+            .iter()
+            .filter_map(|&id| {
+                let required = self
+                    .graph
+                    .get_node_spec(id)
+                    .is_some_and(|spec| spec.autonomy_ceiling.requires_human_approval());
+                let approval = events
+                    .iter()
+                    .find(|e| e.node_id == id && e.action == Action::Approval);
+                (required || approval.is_some()).then(|| AuditApproval {
+                    node_id: id,
+                    required,
+                    approved_at: approval.map(|e| e.timestamp),
+                    detail: approval.map(|e| e.result.clone()),
+                })
+            })
+            .collect()
+    }
+}
+
+impl RunAuditReport {
+    /// Report as pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Report with free text redacted by `policy` for the audit sink
+    ///
+    /// Node errors and escalation details are redacted like event results;
+    /// approval details name the approver, so they are redacted whole as
+    /// PII. IDs, hashes and paths are kept so the report stays checkable.
+    pub fn redacted(&self, policy: &RedactionPolicy) -> Self {
+        let sink = TelemetrySink::Audit;
+        let mut report = self.clone();
+        for node in &mut report.nodes {
+            node.error = node.error.as_deref().map(|e| policy.redact_result(sink, e));
+        }
+        for escalation in &mut report.escalations {
+            escalation.detail = policy.redact_result(sink, &escalation.detail);
+        }
+        for approval in &mut report.approvals {
+            approval.detail = approval.detail.as_deref().map(|d| policy.sink(sink).pii.apply(d));
+        }
+        report
+    }
+
+    /// Approvals that were required but never recorded
+    pub fn missing_approvals(&self) -> impl Iterator<Item = &AuditApproval> {
+        self.approvals
+            .iter()
+            .filter(|approval| approval.required && approval.approved_at.is_none())
+    }
+
+    /// Report as Markdown
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# Run audit: graph {}\n", self.graph_id.0);
+        let _ = writeln!(out, "- Graph type: {:?}", self.graph_type);
+        if let Some(id) = &self.correlation_id {
+            let _ = writeln!(out, "- Correlation: `{id}`");
+        }
+        if let Some(ms) = self.execution_time_ms {
+            let _ = writeln!(out, "- Execution time: {ms} ms");
+        }
+        let _ = writeln!(
+            out,
+            "- Nodes: {}, edges: {}, escalations: {}, missing approvals: {}",
+            self.nodes.len(),
+            self.edges.len(),
+            self.escalations.len(),
+            self.missing_approvals().count()
+        );
+
+        let _ = writeln!(out, "\n## Graph\n\n| Node | Autonomy | Status | Retries | Cached | Error |\n|---|---|---|---|---|---|");
+        for node in &self.nodes {
+            let status = node
+                .status
+                .map_or_else(|| "-".to_string(), |s| format!("{s:?}"));
+            let _ = writeln!(
+                out,
+                "| `{}` | {} | {status} | {} | {} | {} |",
+                node.node_id.0,
+                node.autonomy_ceiling,
+                node.retries,
+                node.cached,
+                node.error.as_deref().unwrap_or("")
+            );
+        }
+        if !self.edges.is_empty() {
+            let _ = writeln!(out, "\nEdges:\n");
+            for (from, to) in &self.edges {
+                let _ = writeln!(out, "- `{}` → `{}`", from.0, to.0);
+            }
+        }
+
+        let _ = writeln!(out, "\n## Capability tokens\n\n| Node | Autonomy | Operation | Issued | Expires | Scope |\n|---|---|---|---|---|---|");
+        for token in &self.tokens {
+            let scope = token.scope.as_ref().map_or_else(
+                || "-".to_string(),
+                |scope| {
+                    let grants = |paths: &[std::path::PathBuf]| {
+                        paths
+                            .iter()
+                            .map(|p| p.display().to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    };
+                    format!(
+                        "{} (read: {}; write: {})",
+                        scope.root().display(),
+                        grants(scope.read_grants()),
+                        grants(scope.write_grants())
+                    )
+                },
+            );
+            let _ = writeln!(
+                out,
+                "| `{}` | {} | {} | {} | {} | {scope} |",
+                token.node_id.0,
+                token.autonomy_level,
+                token.bound_operation,
+                token.issued_at,
+                token.expires_at
+            );
+        }
+
+        let _ = writeln!(out, "\n## Artifacts\n");
+        if self.artifacts.is_empty() {
+            let _ = writeln!(out, "None recorded.");
+        } else {
+            let _ = writeln!(
+                out,
+                "| Time | Node | Access | Artifact | Base hash | Hash |\n|---|---|---|---|---|---|"
+            );
+            for access in &self.artifacts {
+                let node = access
+                    .node_id
+                    .map_or_else(|| "-".to_string(), |id| format!("`{}`", id.0));
+                let _ = writeln!(
+                    out,
+                    "| {} | {node} | {:?} | {} | {} | {} |",
+                    access.timestamp,
+                    access.kind,
+                    access.artifact,
+                    access.base_hash.as_deref().unwrap_or("-"),
+                    access.hash
+                );
+            }
+        }
+
+        let _ = writeln!(out, "\n## Escalations\n");
+        if self.escalations.is_empty() {
+            let _ = writeln!(out, "None.");
+        }
+        for escalation in &self.escalations {
+            let _ = writeln!(
+                out,
+                "- `{}` {}: {}",
+                escalation.node_id.0, escalation.reason, escalation.detail
+            );
+        }
+
+        let _ = writeln!(out, "\n## Approvals\n");
+        if self.approvals.is_empty() {
+            let _ = writeln!(out, "None required.");
+        }
+        for approval in &self.approvals {
+            let state = match (&approval.approved_at, &approval.detail) {
+                (Some(at), detail) => {
+                    format!("approved at {at} ({})", detail.as_deref().unwrap_or(""))
+                }
+                (None, _) => "**missing**".to_string(),
+            };
+            let _ = writeln!(out, "- `{}`: {state}", approval.node_id.0);
+        }
+
+        let _ = writeln!(out, "\n## Resources\n\n| Node | CPU ms | Memory bytes | Tokens | Iterations | Within caps |\n|---|---|---|---|---|---|");
+        for usage in &self.resources {
+            let (c, k) = (&usage.consumed, &usage.caps);
+            let _ = writeln!(
+                out,
+                "| `{}` | {}/{} | {}/{} | {}/{} | {}/{} | {} |",
+                usage.node_id.0,
+                c.cpu_time_ms,
+                k.cpu_time_ms,
+                c.memory_bytes,
+                k.memory_bytes,
+                c.token_limit,
+                k.token_limit,
+                c.iteration_cap,
+                k.iteration_cap,
+                if usage.within_caps { "yes" } else { "**no**" }
+            );
+        }
+        if let (Some(c), Some(b)) = (&self.consumed, &self.budget) {
+            let _ = writeln!(
+                out,
+                "| total | {}/{} | {}/{} | {}/{} | {}/{} | {} |",
+                c.cpu_time_ms,
+                b.cpu_time_ms,
+                c.memory_bytes,
+                b.memory_bytes,
+                c.token_limit,
+                b.token_limit,
+                c.iteration_cap,
+                b.iteration_cap,
+                if c.fits_within(b) { "yes" } else { "**no**" }
+            );
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::autonomy::CapabilityToken;
+    use crate::construction::GraphBuilder;
+    use crate::executor::Executor;
+    use crate::isolation::ScopeGuard;
+    use crate::replay::JournalEntry;
+    use crate::types::{DirectiveSet, NodeSpecV2};
+    use ed25519_dalek::SigningKey;
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    fn spec(level: AutonomyLevel) -> NodeSpecV2 {
+        NodeSpecV2::new(
+            DirectiveSet {
+                directives: BTreeMap::new(),
+            },
+            level,
+            ResourceCaps {
+                cpu_time_ms: 1000,
+                memory_bytes: 1 << 20,
+                token_limit: 100,
+                iteration_cap: 10,
+            },
+        )
+    }
+
+    fn guard(
+        graph: &ValidatedGraph,
+        node: NodeId,
+        log: &Arc<EventLog>,
+        correlation: &CorrelationId,
+    ) -> ScopeGuard {
+        let token: CapabilityToken = graph.get_node_token(node).unwrap().clone();
+        ScopeGuard::new(
+            FsScope::new("/repo").allow_write("src"),
+            token,
+            Arc::clone(log),
+        )
+        .with_correlation(correlation.clone())
+    }
+
+    #[tokio::test]
+    async fn report_covers_tokens_artifacts_escalations_and_resources() {
+        let key = SigningKey::from_bytes(&[9u8; 32]);
+        let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
+        let plan = builder.add_node(spec(AutonomyLevel::L1));
+        let code = builder.add_node(spec(AutonomyLevel::L3));
+        builder.add_edge(plan, code).unwrap();
+        let graph = builder.validate(&key).unwrap();
+        let summary = Executor::new(key.verifying_key())
+            .run(graph.clone())
+            .await
+            .unwrap();
+
+        let correlation = CorrelationId::new("run-1");
+        let log = Arc::new(EventLog::default());
+        let coder = guard(&graph, code, &log, &correlation.for_task("code"));
+        coder
+            .record(
+                Action::ArtifactRead,
+                r#"{"path":"src/lib.rs","hash":"aa"}"#.to_string(),
+            )
+            .unwrap();
+        coder
+            .record(
+                Action::CompositionApplied,
+                r#"{"artifact_type":"code","base_hash":"aa","result_hash":"bb"}"#.to_string(),
+            )
+            .unwrap();
+        let _ = coder.check("/etc/passwd", crate::isolation::FsAccess::Read);
+        guard(&graph, plan, &log, &correlation.for_task("plan"))
+            .record(Action::Approval, "alice".to_string())
+            .unwrap();
+        // Another intent's events are left out
+        guard(&graph, code, &log, &CorrelationId::new("run-2"))
+            .record(Action::Escalation, "unrelated".to_string())
+            .unwrap();
+
+        let report = RunAuditor::new(&graph)
+            .with_summary(&summary)
+            .with_log(&log)
+            .with_scope(code, FsScope::new("/repo").allow_write("src"))
+            .with_correlation(correlation)
+            .compile();
+
+        assert_eq!(report.nodes.len(), 2);
+        assert!(report
+            .nodes
+            .iter()
+            .all(|n| n.status == Some(NodeRunStatus::Succeeded)));
+        assert_eq!(report.tokens.len(), 2);
+        assert!(report
+            .tokens
+            .iter()
+            .any(|t| t.node_id == code && t.scope.is_some()));
+        assert_eq!(report.artifacts.len(), 2);
+        assert_eq!(report.artifacts[0].kind, ArtifactAccessKind::Read);
+        assert_eq!(report.artifacts[1].base_hash.as_deref(), Some("aa"));
+        assert_eq!(report.escalations.len(), 1);
+        assert_eq!(report.escalations[0].reason, crate::isolation::SCOPE_VIOLATION_ACTION);
+        assert_eq!(report.approvals.len(), 1);
+        assert_eq!(report.approvals[0].detail.as_deref(), Some("alice"));
+        assert_eq!(report.missing_approvals().count(), 0);
+        assert!(report.resources.iter().all(|r| r.within_caps));
+
+        let parsed: RunAuditReport = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(parsed, report);
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("## Capability tokens"));
+        assert!(markdown.contains("| Read | src/lib.rs | - | aa |"));
+        assert!(markdown.contains("approved at"));
+
+        let redacted = report.redacted(&RedactionPolicy::strict());
+        let approver = redacted.approvals[0].detail.as_deref().unwrap();
+        assert!(approver.starts_with("[sha256:"));
+        assert_eq!(redacted.artifacts, report.artifacts);
+        assert_eq!(report.redacted(&RedactionPolicy::new()), report);
+    }
+
+    #[tokio::test]
+    async fn journal_supplies_writes_and_missing_approvals_are_flagged() {
+        let key = SigningKey::from_bytes(&[9u8; 32]);
+        let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
+        let node = builder.add_node(spec(AutonomyLevel::L0));
+        let graph = builder.validate(&key).unwrap();
+
+        let journal = CompositionJournal::new();
+        journal.record(JournalEntry {
+            timestamp: 5,
+            artifact: "src.parser".to_string(),
+            node_id: Some(node),
+            base_hash: "aa".to_string(),
+            result_hash: "bb".to_string(),
+            delta_count: 2,
+        });
+
+        let report = RunAuditor::new(&graph).with_journal(&journal).compile();
+        assert_eq!(report.artifacts[0].artifact, "src.parser");
+        assert_eq!(report.missing_approvals().count(), 1);
+        assert!(report.resources.is_empty());
+        assert!(report.to_markdown().contains("**missing**"));
+    }
+}
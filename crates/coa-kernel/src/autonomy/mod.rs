@@ -1,4 +1,9 @@
-use crate::types::{AutonomyLevel, DirectiveProfileHash, NodeId, ResourceCaps};
+use crate::clock::{Clock, SystemClock};
+use crate::error::SignerError;
+use crate::types::{
+    kernel_hash_algorithm, AutonomyLevel, DirectiveProfileHash, NodeId, ResourceCaps,
+    Timestamp,
+};
 use ed25519_dalek::{Signature, SigningKey, VerifyingKey, Signer, Verifier};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -19,6 +24,9 @@ pub struct CapabilityToken {
 }
 
 impl CapabilityToken {
+    /// Sign claims issued now by the system clock
+    ///
+    /// Use [`TokenClaims::new`] to issue at a time read from another clock.
     pub fn sign(
         node_id: NodeId,
         autonomy_level: AutonomyLevel,
@@ -28,21 +36,84 @@ impl CapabilityToken {
         expires_at: u64,
         bound_operation: &str,
     ) -> Self {
-        let issued_at = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
-        
-        let message = token_message(
-            node_id, 
-            autonomy_level, 
-            &caps, 
+        TokenClaims::new(
+            node_id,
+            autonomy_level,
+            caps,
             directive_hash,
-            issued_at,
+            SystemClock.now(),
             expires_at,
             bound_operation,
-        );
-        let sig: Signature = signing_key.sign(&message);
+        )
+        .sign(signing_key)
+    }
+
+    pub fn verify(&self, verifying_key: &VerifyingKey) -> bool {
+        verifying_key.verify(&self.message(), &self.signature).is_ok()
+    }
+
+    /// Bytes the signature covers
+    pub fn message(&self) -> Vec<u8> {
+        token_message(
+            self.node_id,
+            self.autonomy_level,
+            &self.caps,
+            self.directive_hash,
+            self.issued_at,
+            self.expires_at,
+            &self.bound_operation,
+        )
+    }
+
+    /// SHA-256 over the signed fields and the signature
+    pub fn token_hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(self.message());
+        hasher.update(self.signature.to_bytes());
+        hasher.finalize().into()
+    }
+
+    /// Check if token is expired at `clock`'s current time
+    pub fn is_expired(&self, clock: &dyn Clock) -> bool {
+        self.is_expired_at(clock.now())
+    }
+
+    /// Check if token is expired at `now`
+    pub fn is_expired_at(&self, now: Timestamp) -> bool {
+        self.expires_at != 0 && now > self.expires_at
+    }
+
+    /// Check if token is bound to a specific operation
+    pub fn is_bound_to(&self, operation: &str) -> bool {
+        self.bound_operation.is_empty() || self.bound_operation == operation
+    }
+}
+
+/// Capability token fields awaiting a signature
+///
+/// Lets tokens be signed by an external [`Signer`](crate::signer::Signer).
+#[derive(Debug, Clone)]
+pub struct TokenClaims {
+    pub node_id: NodeId,
+    pub autonomy_level: AutonomyLevel,
+    pub caps: ResourceCaps,
+    pub directive_hash: DirectiveProfileHash,
+    pub issued_at: u64,
+    pub expires_at: u64,
+    pub bound_operation: String,
+}
+
+impl TokenClaims {
+    /// Claims issued at `issued_at`
+    pub fn new(
+        node_id: NodeId,
+        autonomy_level: AutonomyLevel,
+        caps: ResourceCaps,
+        directive_hash: DirectiveProfileHash,
+        issued_at: Timestamp,
+        expires_at: u64,
+        bound_operation: &str,
+    ) -> Self {
         Self {
             node_id,
             autonomy_level,
@@ -51,12 +122,12 @@ impl CapabilityToken {
             issued_at,
             expires_at,
             bound_operation: bound_operation.to_string(),
-            signature: sig,
         }
     }
 
-    pub fn verify(&self, verifying_key: &VerifyingKey) -> bool {
-        let message = token_message(
+    /// Bytes the token signature covers
+    pub fn message(&self) -> Vec<u8> {
+        token_message(
             self.node_id,
             self.autonomy_level,
             &self.caps,
@@ -64,33 +135,42 @@ impl CapabilityToken {
             self.issued_at,
             self.expires_at,
             &self.bound_operation,
-        );
-        verifying_key.verify(&message, &self.signature).is_ok()
+        )
     }
 
-    /// Check if token is expired
-    pub fn is_expired(&self) -> bool {
-        if self.expires_at == 0 {
-            return false;
+    /// Token carrying `signature` over [`message`](Self::message)
+    pub fn into_token(self, signature: Signature) -> CapabilityToken {
+        CapabilityToken {
+            node_id: self.node_id,
+            autonomy_level: self.autonomy_level,
+            caps: self.caps,
+            directive_hash: self.directive_hash,
+            issued_at: self.issued_at,
+            expires_at: self.expires_at,
+            bound_operation: self.bound_operation,
+            signature,
         }
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
-        now > self.expires_at
     }
 
-    /// Check if token is bound to a specific operation
-    pub fn is_bound_to(&self, operation: &str) -> bool {
-        self.bound_operation.is_empty() || self.bound_operation == operation
+    /// Sign with an in-memory key
+    pub fn sign(self, signing_key: &SigningKey) -> CapabilityToken {
+        let signature = signing_key.sign(&self.message());
+        self.into_token(signature)
+    }
+
+    /// Sign through `signer`, checking the signature it returns
+    pub async fn sign_with(
+        self,
+        signer: &(impl crate::signer::Signer + ?Sized),
+    ) -> Result<CapabilityToken, SignerError> {
+        let signature = crate::signer::sign_verified(signer, &self.message()).await?;
+        Ok(self.into_token(signature))
     }
 }
 
+/// Hash a serialized execution profile with [`kernel_hash_algorithm`]
 pub fn hash_execution_profile_bytes(profile_bytes: &[u8]) -> DirectiveProfileHash {
-    let mut hasher = Sha256::new();
-    hasher.update(profile_bytes);
-    let out = hasher.finalize();
-    DirectiveProfileHash(out.into())
+    DirectiveProfileHash(kernel_hash_algorithm().digest(profile_bytes).into_bytes())
 }
 
 fn token_message(
@@ -0,0 +1,399 @@
+//! Egress Bundles
+//!
+//! An [`EgressBundle`] packages egressed files for downstream consumers as
+//! a plain ustar archive:
+//!
+//! - `manifest.json`: SHA-256 and size of every file, plus provenance
+//!   records keyed by file path
+//! - `manifest.sig`: hex ed25519 signature over the manifest bytes
+//! - `files/<path>`: file contents
+//!
+//! The signature covers the manifest and the manifest covers every file,
+//! so [`EgressBundle::verify`] against the kernel's verifying key checks
+//! the whole bundle end-to-end.
+
+mod tar;
+
+use crate::clock::{system_clock, SharedClock};
+use crate::types::Timestamp;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Current bundle format version
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// Archive path of the manifest
+pub const MANIFEST_PATH: &str = "manifest.json";
+
+/// Archive path of the manifest signature
+pub const SIGNATURE_PATH: &str = "manifest.sig";
+
+/// Archive directory holding the bundled files
+pub const FILES_DIR: &str = "files";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BundleError {
+    /// Archive or manifest could not be decoded
+    Malformed(String),
+    /// File path is absolute, escapes the bundle, or is too long
+    InvalidPath(String),
+    UnsupportedVersion(u32),
+    /// Manifest was signed by a different key
+    SignerMismatch,
+    SignatureInvalid,
+    MissingFile(String),
+    UnexpectedFile(String),
+    HashMismatch { path: String, expected: String, actual: String },
+}
+
+impl fmt::Display for BundleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl std::error::Error for BundleError {}
+
+/// Manifest record of one bundled file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleEntry {
+    /// Relative path, `/`-separated
+    pub path: String,
+    /// Hex SHA-256 of the contents
+    pub sha256: String,
+    pub size: u64,
+}
+
+/// Signed description of a bundle's contents
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub format_version: u32,
+    pub created_at: Timestamp,
+    /// Hex ed25519 verifying key of the signer
+    pub signer: String,
+    /// Files, ordered by path
+    pub files: Vec<BundleEntry>,
+    /// Provenance records keyed by file path
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub provenance: BTreeMap<String, serde_json::Value>,
+}
+
+impl BundleManifest {
+    pub fn file(&self, path: &str) -> Option<&BundleEntry> {
+        self.files.iter().find(|entry| entry.path == path)
+    }
+}
+
+/// Signed, content-addressed archive of egressed files
+#[derive(Debug, Clone)]
+pub struct EgressBundle {
+    manifest: BundleManifest,
+    /// Manifest exactly as signed
+    manifest_bytes: Vec<u8>,
+    signature: Signature,
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+impl EgressBundle {
+    pub fn builder() -> BundleBuilder {
+        BundleBuilder::new()
+    }
+
+    pub fn manifest(&self) -> &BundleManifest {
+        &self.manifest
+    }
+
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    /// Contents of a bundled file
+    pub fn file(&self, path: &str) -> Option<&[u8]> {
+        self.files.get(path).map(Vec::as_slice)
+    }
+
+    /// Key the manifest claims to be signed by
+    pub fn signer(&self) -> Result<VerifyingKey, BundleError> {
+        parse_verifying_key(&self.manifest.signer)
+    }
+
+    /// Check the signature and every file against the manifest
+    ///
+    /// # Errors
+    /// - `SignerMismatch` if the manifest names a different signer than `key`
+    /// - `SignatureInvalid` if the manifest was altered or not signed by `key`
+    /// - `MissingFile` / `UnexpectedFile` / `HashMismatch` if the files do not
+    ///   match the manifest
+    pub fn verify(&self, key: &VerifyingKey) -> Result<(), BundleError> {
+        if self.manifest.signer != hex::encode(key.as_bytes()) {
+            return Err(BundleError::SignerMismatch);
+        }
+        key.verify(&self.manifest_bytes, &self.signature)
+            .map_err(|_| BundleError::SignatureInvalid)?;
+        if self.manifest.format_version != BUNDLE_FORMAT_VERSION {
+            return Err(BundleError::UnsupportedVersion(self.manifest.format_version));
+        }
+
+        for entry in &self.manifest.files {
+            let data = self
+                .files
+                .get(&entry.path)
+                .ok_or_else(|| BundleError::MissingFile(entry.path.clone()))?;
+            let actual = sha256_hex(data);
+            if actual != entry.sha256 || data.len() as u64 != entry.size {
+                return Err(BundleError::HashMismatch {
+                    path: entry.path.clone(),
+                    expected: entry.sha256.clone(),
+                    actual,
+                });
+            }
+        }
+        if let Some(extra) = self
+            .files
+            .keys()
+            .find(|path| self.manifest.file(path).is_none())
+        {
+            return Err(BundleError::UnexpectedFile(extra.clone()));
+        }
+        Ok(())
+    }
+
+    /// Encode as a ustar archive
+    pub fn to_bytes(&self) -> Result<Vec<u8>, BundleError> {
+        let mtime = self.manifest.created_at;
+        let mut out = Vec::new();
+        tar::append(&mut out, MANIFEST_PATH, &self.manifest_bytes, mtime)?;
+        let signature = format!("{}\n", hex::encode(self.signature.to_bytes()));
+        tar::append(&mut out, SIGNATURE_PATH, signature.as_bytes(), mtime)?;
+        for (path, data) in &self.files {
+            tar::append(&mut out, &format!("{FILES_DIR}/{path}"), data, mtime)?;
+        }
+        tar::finish(&mut out);
+        Ok(out)
+    }
+
+    /// Decode an archive written by [`to_bytes`](Self::to_bytes)
+    ///
+    /// Decoding does not verify anything; call [`verify`](Self::verify).
+    pub fn from_bytes(archive: &[u8]) -> Result<Self, BundleError> {
+        let mut manifest_bytes = None;
+        let mut signature = None;
+        let mut files = BTreeMap::new();
+        for (path, data) in tar::entries(archive)? {
+            match path.as_str() {
+                MANIFEST_PATH => manifest_bytes = Some(data),
+                SIGNATURE_PATH => signature = Some(parse_signature(&data)?),
+                _ => {
+                    let file = path
+                        .strip_prefix(FILES_DIR)
+                        .and_then(|rest| rest.strip_prefix('/'))
+                        .ok_or_else(|| BundleError::UnexpectedFile(path.clone()))?;
+                    files.insert(file.to_string(), data);
+                }
+            }
+        }
+
+        let manifest_bytes =
+            manifest_bytes.ok_or_else(|| BundleError::MissingFile(MANIFEST_PATH.to_string()))?;
+        let manifest = serde_json::from_slice(&manifest_bytes)
+            .map_err(|e| BundleError::Malformed(format!("{MANIFEST_PATH}: {e}")))?;
+        Ok(Self {
+            manifest,
+            manifest_bytes,
+            signature: signature
+                .ok_or_else(|| BundleError::MissingFile(SIGNATURE_PATH.to_string()))?,
+            files,
+        })
+    }
+}
+
+/// Collects files and provenance, then signs them into an [`EgressBundle`]
+#[derive(Debug)]
+pub struct BundleBuilder {
+    clock: SharedClock,
+    files: BTreeMap<String, Vec<u8>>,
+    provenance: BTreeMap<String, serde_json::Value>,
+}
+
+impl Default for BundleBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BundleBuilder {
+    pub fn new() -> Self {
+        Self {
+            clock: system_clock(),
+            files: BTreeMap::new(),
+            provenance: BTreeMap::new(),
+        }
+    }
+
+    /// Stamp the manifest from `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Add a file; a later file at the same path replaces the earlier one
+    pub fn with_file(mut self, path: impl Into<String>, data: impl Into<Vec<u8>>) -> Self {
+        self.files.insert(path.into(), data.into());
+        self
+    }
+
+    /// Attach a provenance record to the file at `path`
+    pub fn with_provenance(mut self, path: impl Into<String>, record: serde_json::Value) -> Self {
+        self.provenance.insert(path.into(), record);
+        self
+    }
+
+    /// Hash the files and sign the manifest
+    ///
+    /// # Errors
+    /// - `InvalidPath` if a path is not a plain relative path, or provenance
+    ///   names a file that is not in the bundle
+    pub fn sign(self, signing_key: &SigningKey) -> Result<EgressBundle, BundleError> {
+        for path in self.files.keys() {
+            check_path(path)?;
+        }
+        if let Some(path) = self.provenance.keys().find(|p| !self.files.contains_key(*p)) {
+            return Err(BundleError::InvalidPath(format!(
+                "{path}: provenance for a file not in the bundle"
+            )));
+        }
+
+        let manifest = BundleManifest {
+            format_version: BUNDLE_FORMAT_VERSION,
+            created_at: self.clock.now(),
+            signer: hex::encode(signing_key.verifying_key().as_bytes()),
+            files: self
+                .files
+                .iter()
+                .map(|(path, data)| BundleEntry {
+                    path: path.clone(),
+                    sha256: sha256_hex(data),
+                    size: data.len() as u64,
+                })
+                .collect(),
+            provenance: self.provenance,
+        };
+        let manifest_bytes = serde_json::to_vec_pretty(&manifest)
+            .map_err(|e| BundleError::Malformed(e.to_string()))?;
+        Ok(EgressBundle {
+            signature: signing_key.sign(&manifest_bytes),
+            manifest,
+            manifest_bytes,
+            files: self.files,
+        })
+    }
+}
+
+/// Parse a hex ed25519 verifying key
+pub fn parse_verifying_key(hex_key: &str) -> Result<VerifyingKey, BundleError> {
+    let bytes: [u8; 32] = hex::decode(hex_key.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| BundleError::Malformed(format!("bad verifying key {hex_key:?}")))?;
+    VerifyingKey::from_bytes(&bytes)
+        .map_err(|_| BundleError::Malformed(format!("bad verifying key {hex_key:?}")))
+}
+
+fn parse_signature(data: &[u8]) -> Result<Signature, BundleError> {
+    let bytes: [u8; 64] = hex::decode(String::from_utf8_lossy(data).trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| BundleError::Malformed(format!("{SIGNATURE_PATH}: bad signature")))?;
+    Ok(Signature::from_bytes(&bytes))
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+/// Paths must be relative, `/`-separated and stay inside the bundle
+fn check_path(path: &str) -> Result<(), BundleError> {
+    let plain = !path.contains('\\')
+        && path
+            .split('/')
+            .all(|segment| !matches!(segment, "" | "." | ".."));
+    if plain {
+        Ok(())
+    } else {
+        Err(BundleError::InvalidPath(path.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use std::sync::Arc;
+
+    fn key() -> SigningKey {
+        SigningKey::from_bytes(&[7u8; 32])
+    }
+
+    fn bundle() -> EgressBundle {
+        EgressBundle::builder()
+            .with_clock(Arc::new(ManualClock::new(1_700_000_000)))
+            .with_file("src/main.rs", "fn main() {}\n")
+            .with_file("README.md", "# demo\n")
+            .with_provenance("src/main.rs", serde_json::json!({ "agent_id": "coder" }))
+            .sign(&key())
+            .unwrap()
+    }
+
+    #[test]
+    fn round_trip_verifies() {
+        let bundle = bundle();
+        let decoded = EgressBundle::from_bytes(&bundle.to_bytes().unwrap()).unwrap();
+
+        decoded.verify(&key().verifying_key()).unwrap();
+        assert_eq!(decoded.manifest(), bundle.manifest());
+        assert_eq!(decoded.file("src/main.rs"), Some(&b"fn main() {}\n"[..]));
+        assert_eq!(decoded.manifest().created_at, 1_700_000_000);
+        assert_eq!(decoded.manifest().provenance["src/main.rs"]["agent_id"], "coder");
+    }
+
+    #[test]
+    fn tampering_is_detected() {
+        let archive = bundle().to_bytes().unwrap();
+        let key = key().verifying_key();
+
+        let mut swapped = EgressBundle::from_bytes(&archive).unwrap();
+        swapped.files.insert("README.md".into(), b"# evil\n".to_vec());
+        assert!(matches!(swapped.verify(&key), Err(BundleError::HashMismatch { .. })));
+
+        let mut extra = EgressBundle::from_bytes(&archive).unwrap();
+        extra.files.insert("payload.sh".into(), Vec::new());
+        assert_eq!(extra.verify(&key), Err(BundleError::UnexpectedFile("payload.sh".into())));
+
+        let mut rewritten = EgressBundle::from_bytes(&archive).unwrap();
+        rewritten.manifest_bytes.push(b' ');
+        assert_eq!(rewritten.verify(&key), Err(BundleError::SignatureInvalid));
+
+        let other = SigningKey::from_bytes(&[8u8; 32]).verifying_key();
+        assert_eq!(bundle().verify(&other), Err(BundleError::SignerMismatch));
+    }
+
+    #[test]
+    fn long_and_unsafe_paths() {
+        let long = format!("{}/{}.rs", "deep/".repeat(30).trim_end_matches('/'), "x".repeat(60));
+        let bundle = EgressBundle::builder()
+            .with_file(long.clone(), "x")
+            .sign(&key())
+            .unwrap();
+        let decoded = EgressBundle::from_bytes(&bundle.to_bytes().unwrap()).unwrap();
+        decoded.verify(&key().verifying_key()).unwrap();
+        assert!(decoded.file(&long).is_some());
+
+        for bad in ["../escape", "/etc/passwd", "a/./b", ""] {
+            let result = EgressBundle::builder().with_file(bad, "x").sign(&key());
+            assert!(matches!(result, Err(BundleError::InvalidPath(_))), "{bad}");
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//! Minimal ustar archive reader/writer
+//!
+//! Only regular files are written or read; this is enough for bundles to
+//! be unpacked with a stock `tar` and read back without extra dependencies.
+
+use super::BundleError;
+
+const BLOCK: usize = 512;
+const NAME_LEN: usize = 100;
+const PREFIX_LEN: usize = 155;
+
+/// Append one regular file entry
+pub(super) fn append(out: &mut Vec<u8>, path: &str, data: &[u8], mtime: u64) -> Result<(), BundleError> {
+    let (prefix, name) = split_path(path)?;
+    let mut header = [0u8; BLOCK];
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    write_octal(&mut header[100..108], 0o644);
+    write_octal(&mut header[108..116], 0);
+    write_octal(&mut header[116..124], 0);
+    write_octal(&mut header[124..136], data.len() as u64);
+    write_octal(&mut header[136..148], mtime);
+    header[156] = b'0';
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+    header[345..345 + prefix.len()].copy_from_slice(prefix.as_bytes());
+
+    header[148..156].fill(b' ');
+    let checksum: u32 = header.iter().map(|&b| u32::from(b)).sum();
+    write_octal(&mut header[148..155], u64::from(checksum));
+
+    out.extend_from_slice(&header);
+    out.extend_from_slice(data);
+    out.resize(out.len().next_multiple_of(BLOCK), 0);
+    Ok(())
+}
+
+/// Terminate the archive with two zero blocks
+pub(super) fn finish(out: &mut Vec<u8>) {
+    out.resize(out.len() + 2 * BLOCK, 0);
+}
+
+/// Read all regular file entries in archive order
+pub(super) fn entries(archive: &[u8]) -> Result<Vec<(String, Vec<u8>)>, BundleError> {
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    while offset + BLOCK <= archive.len() {
+        let header = &archive[offset..offset + BLOCK];
+        if header.iter().all(|&b| b == 0) {
+            break;
+        }
+
+        let stored = read_octal(&header[148..156])?;
+        let actual: u64 = header
+            .iter()
+            .enumerate()
+            .map(|(i, &b)| if (148..156).contains(&i) { u64::from(b' ') } else { u64::from(b) })
+            .sum();
+        if stored != actual {
+            return Err(BundleError::Malformed(format!("bad header checksum at offset {offset}")));
+        }
+
+        let name = read_str(&header[..NAME_LEN]);
+        let prefix = read_str(&header[345..345 + PREFIX_LEN]);
+        let path = if prefix.is_empty() { name } else { format!("{prefix}/{name}") };
+        let size = usize::try_from(read_octal(&header[124..136])?)
+            .map_err(|_| BundleError::Malformed(format!("{path}: size out of range")))?;
+
+        let start = offset + BLOCK;
+        let end = start
+            .checked_add(size)
+            .filter(|&end| end <= archive.len())
+            .ok_or_else(|| BundleError::Malformed(format!("{path}: truncated entry")))?;
+        match header[156] {
+            b'0' | 0 => entries.push((path, archive[start..end].to_vec())),
+            kind => {
+                return Err(BundleError::Malformed(format!(
+                    "{path}: unsupported entry type '{}'",
+                    kind as char
+                )))
+            }
+        }
+        offset = start + size.next_multiple_of(BLOCK);
+    }
+    Ok(entries)
+}
+
+/// Split a path into ustar prefix and name fields
+fn split_path(path: &str) -> Result<(&str, &str), BundleError> {
+    if path.len() <= NAME_LEN {
+        return Ok(("", path));
+    }
+    path.match_indices('/')
+        .map(|(i, _)| (&path[..i], &path[i + 1..]))
+        .find(|(prefix, name)| prefix.len() <= PREFIX_LEN && name.len() <= NAME_LEN && !name.is_empty())
+        .ok_or_else(|| BundleError::InvalidPath(format!("{path}: too long for a ustar header")))
+}
+
+fn write_octal(field: &mut [u8], value: u64) {
+    let digits = field.len() - 1;
+    let text = format!("{value:0digits$o}");
+    field[..digits].copy_from_slice(&text.as_bytes()[text.len() - digits..]);
+    field[digits] = 0;
+}
+
+fn read_octal(field: &[u8]) -> Result<u64, BundleError> {
+    let text = read_str(field);
+    let text = text.trim_matches(|c: char| c == ' ' || c == '\0');
+    if text.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(text, 8)
+        .map_err(|_| BundleError::Malformed(format!("bad octal field {text:?}")))
+}
+
+fn read_str(field: &[u8]) -> String {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
@@ -3,22 +3,33 @@
 //! The primary interface for the construction phase.
 //! Builds a graph and validates it, producing a `ValidatedGraph`.
 
+use crate::clock::{system_clock, SharedClock};
 use crate::error::ValidationError;
 use crate::construction::validator::ValidationContext;
-use crate::types::v2::{NodeSpecV2, SystemLimits, ValidatedGraph};
-use crate::types::{GraphId, GraphType, NodeId};
+use crate::types::v2::{EdgeKind, NodeSpecV2, SystemLimits, ValidatedGraph};
+use crate::types::{AgentId, GraphId, GraphType, NodeId};
 use crate::construction::ConstructionValidator;
+use crate::quota::QuotaManager;
+use crate::resource::RunBudget;
+use crate::signer::Signer;
+use crate::tenant::TenantRegistry;
+use crate::types::TenantId;
 use ed25519_dalek::SigningKey;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// Error type for graph builder operations
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum GraphBuilderError {
     NodeNotFound(NodeId),
+    /// A node with the ID was already added
+    NodeAlreadyExists(NodeId),
     EdgeAlreadyExists,
     SelfLoopNotAllowed,
     WouldCreateCycle,
     GraphTypeNotMutable,
+    /// Target of a data edge does not declare the payload as an input
+    UndeclaredInput { node: NodeId, payload_type: String },
 }
 
 impl std::fmt::Display for GraphBuilderError {
@@ -44,8 +55,13 @@ pub struct GraphBuilder {
     graph_id: GraphId,
     nodes: HashMap<NodeId, NodeSpecV2>,
     edges: Vec<(NodeId, NodeId)>,
+    edge_kinds: HashMap<(NodeId, NodeId), EdgeKind>,
     system_limits: SystemLimits,
     adjacency: HashMap<NodeId, Vec<NodeId>>, // For cycle detection
+    quota: Option<(Arc<QuotaManager>, Option<AgentId>)>,
+    budget: Option<Arc<RunBudget>>,
+    tenancy: Option<(Arc<TenantRegistry>, TenantId)>,
+    clock: SharedClock,
 }
 
 impl GraphBuilder {
@@ -56,8 +72,13 @@ impl GraphBuilder {
             graph_id: GraphId::new(),
             nodes: HashMap::new(),
             edges: Vec::new(),
+            edge_kinds: HashMap::new(),
             system_limits: SystemLimits::default(),
             adjacency: HashMap::new(),
+            quota: None,
+            budget: None,
+            tenancy: None,
+            clock: system_clock(),
         }
     }
     
@@ -68,11 +89,44 @@ impl GraphBuilder {
             graph_id: GraphId::new(),
             nodes: HashMap::new(),
             edges: Vec::new(),
+            edge_kinds: HashMap::new(),
             system_limits: limits,
             adjacency: HashMap::new(),
+            quota: None,
+            budget: None,
+            tenancy: None,
+            clock: system_clock(),
         }
     }
     
+    /// Charge this graph's token issuance and worst-case resource bounds
+    /// against `quota` (for `agent`, if given) during validation
+    pub fn with_quota(mut self, quota: Arc<QuotaManager>, agent: Option<AgentId>) -> Self {
+        self.quota = Some((quota, agent));
+        self
+    }
+    
+    /// Reserve this graph's worst-case resources from `budget` during
+    /// validation; graphs that do not fit are rejected
+    pub fn with_budget(mut self, budget: Arc<RunBudget>) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+    
+    /// Claim the graph for `tenant` in `registry` during validation
+    ///
+    /// Only that tenant's executors will run the validated graph.
+    pub fn with_tenant(mut self, registry: Arc<TenantRegistry>, tenant: TenantId) -> Self {
+        self.tenancy = Some((registry, tenant));
+        self
+    }
+    
+    /// Stamp issued tokens with times from `clock`
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+    
     /// Get the graph ID
     pub fn graph_id(&self) -> GraphId {
         self.graph_id
@@ -103,7 +157,21 @@ impl GraphBuilder {
         node_id
     }
     
-    /// Add an edge between two nodes
+    /// Add a node under a chosen ID
+    ///
+    /// Re-plans reuse the IDs of nodes carried over from the previous plan,
+    /// so [`ValidatedGraph::diff`](crate::validated_graph::ValidatedGraph::diff)
+    /// reports them as unchanged rather than removed and added.
+    pub fn add_node_with_id(&mut self, node_id: NodeId, spec: NodeSpecV2) -> Result<NodeId, GraphBuilderError> {
+        if self.nodes.contains_key(&node_id) {
+            return Err(GraphBuilderError::NodeAlreadyExists(node_id));
+        }
+        self.nodes.insert(node_id, spec);
+        self.adjacency.insert(node_id, Vec::new());
+        Ok(node_id)
+    }
+    
+    /// Add an ordering-only edge between two nodes
     ///
     /// For production DAGs, this will reject edges that would create a cycle.
     pub fn add_edge(&mut self, from: NodeId, to: NodeId) -> Result<(), GraphBuilderError> {
@@ -145,6 +213,32 @@ impl GraphBuilder {
         Ok(())
     }
     
+    /// Add an edge of the given kind between two nodes
+    ///
+    /// A data dependency is rejected unless `to` declares its payload type
+    /// as an input.
+    pub fn add_typed_edge(
+        &mut self,
+        from: NodeId,
+        to: NodeId,
+        kind: EdgeKind,
+    ) -> Result<(), GraphBuilderError> {
+        if let (Some(payload_type), Some(spec)) = (kind.payload_type(), self.nodes.get(&to)) {
+            if !spec.consumes(payload_type) {
+                return Err(GraphBuilderError::UndeclaredInput {
+                    node: to,
+                    payload_type: payload_type.to_string(),
+                });
+            }
+        }
+        
+        self.add_edge(from, to)?;
+        if kind != EdgeKind::OrderingOnly {
+            self.edge_kinds.insert((from, to), kind);
+        }
+        Ok(())
+    }
+    
     /// Check if the current graph has a cycle
     fn has_cycle(&self) -> bool {
         let mut visiting = std::collections::HashSet::new();
@@ -204,30 +298,78 @@ impl GraphBuilder {
         &self.edges
     }
     
+    /// Get the kind of the `from → to` edge, if it exists
+    pub fn edge_kind(&self, from: NodeId, to: NodeId) -> Option<&EdgeKind> {
+        if !self.edges.contains(&(from, to)) {
+            return None;
+        }
+        Some(self.edge_kinds.get(&(from, to)).unwrap_or(&EdgeKind::OrderingOnly))
+    }
+    
     /// Validate the graph and produce a ValidatedGraph
     ///
     /// This performs all construction-time validation:
     /// - Graph structure validation
     /// - Policy compliance checks
     /// - Resource bounds proving
+    /// - Run budget reservation (if configured)
+    /// - Quota proving (if configured)
+    /// - Claiming the graph for its tenant (if configured)
     /// - Token issuance
     ///
     /// Once validated, the graph cannot be modified.
     pub fn validate(self, signing_key: &SigningKey) -> Result<ValidatedGraph, ValidationError> {
-        let validator = ConstructionValidator::with_context(ValidationContext {
-            system_limits: self.system_limits,
-            graph_type: self.graph_type,
-        });
-        
-        validator.validate_graph(
+        self.validator()?.validate_typed_graph(
             self.graph_id,
             self.graph_type,
             &self.nodes,
             &self.edges,
+            &self.edge_kinds,
             signing_key,
         )
     }
     
+    /// Validate the graph with tokens signed by `signer`
+    ///
+    /// Same checks as [`validate`](Self::validate), for kernel keys held
+    /// outside the process (KMS, HSM, OS keychain).
+    pub async fn validate_with_signer(
+        self,
+        signer: &(impl Signer + ?Sized),
+    ) -> Result<ValidatedGraph, ValidationError> {
+        self.validator()?
+            .validate_typed_graph_with_signer(
+                self.graph_id,
+                self.graph_type,
+                &self.nodes,
+                &self.edges,
+                &self.edge_kinds,
+                signer,
+            )
+            .await
+    }
+    
+    /// Claim the graph, reserve construction quota and configure the
+    /// validator
+    fn validator(&self) -> Result<ConstructionValidator, ValidationError> {
+        if let Some((registry, tenant)) = &self.tenancy {
+            registry.claim_graph(tenant, self.graph_id)?;
+        }
+        if let Some((quota, agent)) = &self.quota {
+            quota.reserve_construction(agent.as_ref(), self.graph_id, &self.nodes)?;
+        }
+        
+        let mut validator = ConstructionValidator::with_context(ValidationContext {
+            system_limits: self.system_limits,
+            graph_type: self.graph_type,
+        })
+        .with_clock(Arc::clone(&self.clock));
+        if let Some(budget) = &self.budget {
+            validator = validator.with_budget(Arc::clone(budget));
+        }
+        Ok(validator)
+    }
+    
     /// Check if adding an edge would create a cycle
     ///
     /// This is a preview method that doesn't modify the builder.
@@ -299,6 +441,7 @@ mod tests {
                 iteration_cap: 100,
             },
             expansion_type: None,
+            inputs: Vec::new(),
         }
     }
 
@@ -423,4 +566,63 @@ mod tests {
             Err(GraphBuilderError::NodeNotFound(_))
         ));
     }
+
+    #[test]
+    fn test_typed_edges_require_declared_inputs() {
+        let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
+        let producer = builder.add_node(create_test_spec());
+        let consumer = builder.add_node(create_test_spec().with_input("Patch"));
+        let gate = builder.add_node(create_test_spec());
+        
+        assert_eq!(
+            builder.add_typed_edge(producer, gate, EdgeKind::data("Patch")),
+            Err(GraphBuilderError::UndeclaredInput {
+                node: gate,
+                payload_type: "Patch".to_string(),
+            })
+        );
+        builder.add_typed_edge(producer, consumer, EdgeKind::data("Patch")).unwrap();
+        builder
+            .add_typed_edge(consumer, gate, EdgeKind::control(crate::types::v2::EdgeGuard::OnSuccess))
+            .unwrap();
+        builder.add_edge(producer, gate).unwrap();
+        
+        assert_eq!(builder.edge_kind(producer, consumer), Some(&EdgeKind::data("Patch")));
+        assert_eq!(builder.edge_kind(producer, gate), Some(&EdgeKind::OrderingOnly));
+        assert_eq!(builder.edge_kind(gate, producer), None);
+        
+        let validated = builder.validate(&create_signing_key()).unwrap();
+        assert_eq!(validated.edge_kind(producer, consumer), &EdgeKind::data("Patch"));
+        assert_eq!(validated.execution_order(), vec![producer, consumer, gate]);
+    }
+
+    #[test]
+    fn test_validate_draws_from_run_budget() {
+        let signing_key = create_signing_key();
+        let budget = Arc::new(RunBudget::new(ResourceCaps {
+            cpu_time_ms: 2500,
+            memory_bytes: u64::MAX,
+            token_limit: u64::MAX,
+            iteration_cap: u64::MAX,
+        }));
+        let build = |nodes: usize| {
+            let mut builder = GraphBuilder::new(GraphType::ProductionDAG).with_budget(budget.clone());
+            for _ in 0..nodes {
+                builder.add_node(create_test_spec());
+            }
+            builder.validate(&signing_key)
+        };
+        
+        let first = build(2).unwrap();
+        assert_eq!(first.budget_envelope().unwrap().cpu_time_ms, 2000);
+        assert_eq!(budget.remaining().cpu_time_ms, 500);
+        
+        // 1000ms more does not fit; the failed graph reserves nothing
+        assert!(matches!(build(1), Err(ValidationError::BudgetExceeded)));
+        assert_eq!(budget.remaining().cpu_time_ms, 500);
+        
+        budget.release(first.budget_envelope().unwrap());
+        assert!(build(1).is_ok());
+        assert_eq!(budget.remaining().cpu_time_ms, 1500);
+    }
 }
@@ -3,9 +3,12 @@
 //! Issues capability tokens during the construction phase.
 //! All token parameters are encoded at construction time.
 
-use crate::autonomy::CapabilityToken;
+use crate::autonomy::{CapabilityToken, TokenClaims};
+use crate::error::SignerError;
+use crate::signer::Signer;
 use crate::types::v2::NodeSpecV2;
-use crate::types::{DirectiveProfileHash, GraphId, NodeId};
+use crate::clock::{system_clock, Clock, SharedClock};
+use crate::types::{DirectiveProfileHash, GraphId, NodeId, Timestamp};
 use ed25519_dalek::SigningKey;
 use std::collections::HashMap;
 
@@ -18,13 +21,13 @@ pub struct IssuedTokens {
 }
 
 impl IssuedTokens {
-    /// Create a new issued tokens collection
-    pub fn new(graph_id: GraphId) -> Self {
-        let issued_at = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
-        
+    /// Create an empty collection issued at `clock`'s current time
+    pub fn new(graph_id: GraphId, clock: &dyn Clock) -> Self {
+        Self::issued_at(graph_id, clock.now())
+    }
+    
+    /// Create an empty collection issued at `issued_at`
+    pub fn issued_at(graph_id: GraphId, issued_at: Timestamp) -> Self {
         Self {
             graph_id,
             tokens: HashMap::new(),
@@ -50,42 +53,109 @@ impl IssuedTokens {
 
 /// Token issuer for construction phase
 ///
-/// Issues capability tokens with all policy parameters encoded.
-pub struct TokenIssuer {
-    signing_key: SigningKey,
+/// Issues capability tokens with all policy parameters encoded. Issuers
+/// holding an in-memory [`SigningKey`] sign synchronously; any other
+/// [`Signer`] (KMS, HSM, OS keychain) signs through the async methods.
+pub struct TokenIssuer<S: Signer = SigningKey> {
+    signer: S,
     default_expiry_secs: u64,
+    clock: SharedClock,
 }
 
-impl TokenIssuer {
+impl<S: Signer> TokenIssuer<S> {
     /// Create a new token issuer
-    pub fn new(signing_key: SigningKey) -> Self {
-        Self {
-            signing_key,
-            default_expiry_secs: 3600, // 1 hour
-        }
+    pub fn new(signer: S) -> Self {
+        Self::with_expiry(signer, 3600) // 1 hour
     }
     
     /// Create with custom expiry
-    pub fn with_expiry(signing_key: SigningKey, expiry_secs: u64) -> Self {
+    pub fn with_expiry(signer: S, expiry_secs: u64) -> Self {
         Self {
-            signing_key,
+            signer,
             default_expiry_secs: expiry_secs,
+            clock: system_clock(),
+        }
+    }
+    
+    /// Compute issue and expiry times from `clock`
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+    
+    /// Signer tokens are issued with
+    pub fn signer(&self) -> &S {
+        &self.signer
+    }
+    
+    /// Issue tokens for all nodes in a graph, signing through the signer
+    pub async fn sign_for_graph(
+        &self,
+        graph_id: GraphId,
+        nodes: &HashMap<NodeId, NodeSpecV2>,
+    ) -> Result<IssuedTokens, SignerError> {
+        let mut issued = IssuedTokens::issued_at(graph_id, self.clock.now());
+        let expires_at = issued.issued_at + self.default_expiry_secs;
+        
+        for (node_id, spec) in nodes {
+            let token = Self::claims(*node_id, spec, issued.issued_at, expires_at, "execute")
+                .sign_with(&self.signer)
+                .await?;
+            issued.tokens.insert(*node_id, token);
         }
+        
+        Ok(issued)
+    }
+    
+    /// Issue a token for a specific operation, signing through the signer
+    pub async fn sign_bound_token(
+        &self,
+        node_id: NodeId,
+        spec: &NodeSpecV2,
+        operation: &str,
+    ) -> Result<CapabilityToken, SignerError> {
+        let issued_at = self.clock.now();
+        let expires_at = issued_at + self.default_expiry_secs;
+        Self::claims(node_id, spec, issued_at, expires_at, operation)
+            .sign_with(&self.signer)
+            .await
     }
     
+    /// Unsigned token for one node
+    fn claims(
+        node_id: NodeId,
+        spec: &NodeSpecV2,
+        issued_at: Timestamp,
+        expires_at: u64,
+        operation: &str,
+    ) -> TokenClaims {
+        TokenClaims::new(
+            node_id,
+            spec.autonomy_ceiling,
+            spec.resource_bounds,
+            DirectiveProfileHash([0u8; 32]), // TODO: Compute actual directive hash
+            issued_at,
+            expires_at,
+            operation,
+        )
+    }
+}
+
+impl TokenIssuer {
     /// Issue tokens for all nodes in a graph
     pub fn issue_for_graph(
         &self,
         graph_id: GraphId,
         nodes: &HashMap<NodeId, NodeSpecV2>,
     ) -> IssuedTokens {
-        let mut issued = IssuedTokens::new(graph_id);
+        let mut issued = IssuedTokens::issued_at(graph_id, self.clock.now());
         let expires_at = issued.issued_at + self.default_expiry_secs;
         
         for (node_id, spec) in nodes {
             let token = self.issue_single_token(
                 *node_id,
                 spec,
+                issued.issued_at,
                 expires_at,
                 "execute",
             );
@@ -101,18 +171,11 @@ impl TokenIssuer {
         &self,
         node_id: NodeId,
         spec: &NodeSpecV2,
+        issued_at: Timestamp,
         expires_at: u64,
         operation: &str,
     ) -> CapabilityToken {
-        CapabilityToken::sign(
-            node_id,
-            spec.autonomy_ceiling,
-            spec.resource_bounds,
-            DirectiveProfileHash([0u8; 32]), // TODO: Compute actual directive hash
-            &self.signing_key,
-            expires_at,
-            operation,
-        )
+        Self::claims(node_id, spec, issued_at, expires_at, operation).sign(&self.signer)
     }
     
     /// Issue a token for a specific operation
@@ -122,25 +185,24 @@ impl TokenIssuer {
         spec: &NodeSpecV2,
         operation: &str,
     ) -> CapabilityToken {
-        let expires_at = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs()
-            + self.default_expiry_secs;
+        let issued_at = self.clock.now();
+        let expires_at = issued_at + self.default_expiry_secs;
         
-        self.issue_single_token(node_id, spec, expires_at, operation)
+        self.issue_single_token(node_id, spec, issued_at, expires_at, operation)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clock::ManualClock;
     use crate::types::{
         AutonomyLevel, DirectiveSet, ResourceCaps,
     };
     use ed25519_dalek::SigningKey;
     use rand::rngs::OsRng;
     use std::collections::BTreeMap;
+    use std::sync::Arc;
 
     fn create_test_spec() -> NodeSpecV2 {
         NodeSpecV2 {
@@ -155,6 +217,7 @@ mod tests {
                 iteration_cap: 100,
             },
             expansion_type: None,
+            inputs: Vec::new(),
         }
     }
 
@@ -234,4 +297,42 @@ mod tests {
         assert_eq!(token.caps.cpu_time_ms, 5000);
         assert_eq!(token.caps.memory_bytes, 10 * 1024 * 1024);
     }
+
+    #[test]
+    fn test_tokens_are_issued_on_issuer_clock() {
+        let clock = Arc::new(ManualClock::new(1_000));
+        let issuer = TokenIssuer::with_expiry(create_signing_key(), 60).with_clock(clock.clone());
+        let spec = create_test_spec();
+
+        let token = issuer.issue_bound_token(NodeId::new(), &spec, "execute");
+        assert_eq!((token.issued_at, token.expires_at), (1_000, 1_060));
+        assert!(!token.is_expired(clock.as_ref()));
+
+        clock.advance(61);
+        assert!(token.is_expired(clock.as_ref()));
+
+        let mut nodes = HashMap::new();
+        nodes.insert(NodeId::new(), spec);
+        let issued = issuer.issue_for_graph(GraphId::new(), &nodes);
+        let token = issued.tokens.values().next().unwrap();
+        assert_eq!(issued.issued_at, 1_061);
+        assert_eq!((token.issued_at, token.expires_at), (1_061, 1_121));
+    }
+
+    #[tokio::test]
+    async fn test_sign_bound_token_through_signer() {
+        let signing_key = create_signing_key();
+        let verifying_key = signing_key.verifying_key();
+        let issuer = TokenIssuer::new(signing_key);
+        let node_id = NodeId::new();
+
+        let token = issuer
+            .sign_bound_token(node_id, &create_test_spec(), "write")
+            .await
+            .unwrap();
+
+        assert!(token.verify(&verifying_key));
+        assert!(token.is_bound_to("write"));
+        assert!(!token.is_bound_to("read"));
+    }
 }
@@ -3,13 +3,18 @@
 //! Performs all policy validation at construction time.
 //! No policy validation happens at runtime - only integrity verification.
 
+use crate::clock::{system_clock, SharedClock};
 use crate::error::ValidationError;
-use crate::types::v2::{NodeSpecV2, SystemLimits, ValidatedGraph, ValidationToken};
+use crate::types::v2::{EdgeKind, NodeSpecV2, SystemLimits, ValidatedGraph, ValidationToken};
 use crate::validated_graph::ResourceProof;
 use crate::types::{GraphId, GraphType, NodeId};
 use crate::validated_graph::{compute_validation_hash, ValidatedGraphConstructor};
-use ed25519_dalek::{Signer, SigningKey};
+use crate::resource::RunBudget;
+use crate::autonomy::{CapabilityToken, TokenClaims};
+use crate::signer::{self, Signer};
+use ed25519_dalek::{Signature, SigningKey};
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 /// Context for validation
 #[derive(Debug, Clone)]
@@ -30,19 +35,36 @@ impl Default for ValidationContext {
 /// Construction-time policy validator
 pub struct ConstructionValidator {
     context: ValidationContext,
+    clock: SharedClock,
+    budget: Option<Arc<RunBudget>>,
 }
 
 impl ConstructionValidator {
     /// Create a new validator with default context
     pub fn new() -> Self {
-        Self {
-            context: ValidationContext::default(),
-        }
+        Self::with_context(ValidationContext::default())
     }
     
     /// Create a new validator with custom context
     pub fn with_context(context: ValidationContext) -> Self {
-        Self { context }
+        Self {
+            context,
+            clock: system_clock(),
+            budget: None,
+        }
+    }
+    
+    /// Compute token issue and expiry times from `clock`
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+    
+    /// Reserve each graph's worst case from `budget`, rejecting graphs
+    /// that do not fit what remains
+    pub fn with_budget(mut self, budget: Arc<RunBudget>) -> Self {
+        self.budget = Some(budget);
+        self
     }
     
     /// Validate a complete graph
@@ -50,8 +72,10 @@ impl ConstructionValidator {
     /// Performs all construction-time validations:
     /// 1. Graph structure (cycles, self-loops in production)
     /// 2. Autonomy ceilings
-    /// 3. Resource bounds provability
+    /// 3. Resource bounds provability, within the run budget if one is set
     /// 4. Security pipeline completeness
+    ///
+    /// All edges are treated as ordering-only.
     pub fn validate_graph(
         &self,
         graph_id: GraphId,
@@ -60,8 +84,93 @@ impl ConstructionValidator {
         edges: &[(NodeId, NodeId)],
         signing_key: &SigningKey,
     ) -> Result<ValidatedGraph, ValidationError> {
-        // 1. Validate graph structure
+        self.validate_typed_graph(graph_id, graph_type, nodes, edges, &HashMap::new(), signing_key)
+    }
+    
+    /// Validate a complete graph whose edges carry kinds
+    ///
+    /// Edges missing from `edge_kinds` are ordering-only. In addition to
+    /// the checks of [`validate_graph`](Self::validate_graph), every data
+    /// dependency's target must declare the payload type as an input.
+    pub fn validate_typed_graph(
+        &self,
+        graph_id: GraphId,
+        graph_type: GraphType,
+        nodes: &HashMap<NodeId, NodeSpecV2>,
+        edges: &[(NodeId, NodeId)],
+        edge_kinds: &HashMap<(NodeId, NodeId), EdgeKind>,
+        signing_key: &SigningKey,
+    ) -> Result<ValidatedGraph, ValidationError> {
+        let result = self
+            .check_graph(graph_type, nodes, edges, edge_kinds)
+            .and_then(|proof| {
+                let node_tokens = self
+                    .node_token_claims(nodes)
+                    .into_iter()
+                    .map(|(node_id, claims)| (node_id, claims.sign(signing_key)))
+                    .collect();
+                let validation = self.validation_claims(graph_id, nodes, edges, edge_kinds);
+                let signature = ed25519_dalek::Signer::sign(signing_key, &validation.message());
+                let validation_token = validation.into_token(signature);
+                self.seal(graph_id, graph_type, nodes, edges, edge_kinds, &proof, node_tokens, validation_token)
+            });
+        Self::record(&result);
+        result
+    }
+
+    /// [`validate_typed_graph`](Self::validate_typed_graph) with tokens
+    /// signed by `signer`
+    ///
+    /// Nothing is reserved from the run budget unless every signature
+    /// succeeds.
+    pub async fn validate_typed_graph_with_signer(
+        &self,
+        graph_id: GraphId,
+        graph_type: GraphType,
+        nodes: &HashMap<NodeId, NodeSpecV2>,
+        edges: &[(NodeId, NodeId)],
+        edge_kinds: &HashMap<(NodeId, NodeId), EdgeKind>,
+        signer: &(impl Signer + ?Sized),
+    ) -> Result<ValidatedGraph, ValidationError> {
+        let result = async {
+            let proof = self.check_graph(graph_type, nodes, edges, edge_kinds)?;
+            let mut node_tokens = HashMap::new();
+            for (node_id, claims) in self.node_token_claims(nodes) {
+                node_tokens.insert(node_id, claims.sign_with(signer).await?);
+            }
+            let validation = self.validation_claims(graph_id, nodes, edges, edge_kinds);
+            let signature = signer::sign_verified(signer, &validation.message()).await?;
+            let validation_token = validation.into_token(signature);
+            self.seal(graph_id, graph_type, nodes, edges, edge_kinds, &proof, node_tokens, validation_token)
+        }
+        .await;
+        Self::record(&result);
+        result
+    }
+
+    #[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+    fn record(result: &Result<ValidatedGraph, ValidationError>) {
+        #[cfg(feature = "metrics")]
+        {
+            let metrics = crate::metrics::global();
+            match result {
+                Ok(_) => metrics.graphs_validated.inc(),
+                Err(_) => metrics.graphs_rejected.inc(),
+            }
+        }
+    }
+
+    /// Structure, policy and resource checks; returns the bounds proof
+    fn check_graph(
+        &self,
+        graph_type: GraphType,
+        nodes: &HashMap<NodeId, NodeSpecV2>,
+        edges: &[(NodeId, NodeId)],
+        edge_kinds: &HashMap<(NodeId, NodeId), EdgeKind>,
+    ) -> Result<ResourceProof, ValidationError> {
+        // 1. Validate graph structure and edge kinds
         self.validate_graph_structure(graph_type, nodes, edges)?;
+        Self::validate_edge_kinds(nodes, edges, edge_kinds)?;
         
         // 2. Validate node specifications
         let node_specs: Vec<_> = nodes.values().collect();
@@ -69,27 +178,42 @@ impl ConstructionValidator {
         
         // 3. Prove resource bounds
         let node_specs_ref: Vec<_> = node_specs.iter().map(|&n| n.clone()).collect();
-        ResourceProof::verify_bounds(&node_specs_ref, &self.context.system_limits)?;
-        
-        // 4. Issue capability tokens
-        let node_tokens = self.issue_node_tokens(graph_id, nodes, signing_key);
-        
-        // 5. Create validation token
-        let validation_token = self.create_validation_token(
-            graph_id,
-            nodes,
-            edges,
-            signing_key,
-        );
+        ResourceProof::verify_bounds(&node_specs_ref, &self.context.system_limits)
+    }
+
+    /// Reserve the budget and construct the graph from signed tokens
+    #[allow(clippy::too_many_arguments)]
+    fn seal(
+        &self,
+        graph_id: GraphId,
+        graph_type: GraphType,
+        nodes: &HashMap<NodeId, NodeSpecV2>,
+        edges: &[(NodeId, NodeId)],
+        edge_kinds: &HashMap<(NodeId, NodeId), EdgeKind>,
+        proof: &ResourceProof,
+        node_tokens: HashMap<NodeId, CapabilityToken>,
+        validation_token: ValidationToken,
+    ) -> Result<ValidatedGraph, ValidationError> {
+        // 4. Reserve the worst case from the run budget
+        let budget_envelope = match &self.budget {
+            Some(budget) => {
+                let envelope = proof.worst_case();
+                budget.reserve(&envelope)?;
+                Some(envelope)
+            }
+            None => None,
+        };
         
-        // 6. Construct ValidatedGraph (sealed type)
+        // 5. Construct ValidatedGraph (sealed type)
         Ok(ValidatedGraphConstructor::construct(
             graph_id,
             validation_token,
             graph_type,
             nodes.clone(),
             edges.to_vec(),
+            edge_kinds.clone(),
             node_tokens,
+            budget_envelope,
         ))
     }
     
@@ -127,6 +251,26 @@ impl ConstructionValidator {
         Ok(())
     }
     
+    /// Check that edge kinds name real edges and that data reaches
+    /// nodes which declare it
+    fn validate_edge_kinds(
+        nodes: &HashMap<NodeId, NodeSpecV2>,
+        edges: &[(NodeId, NodeId)],
+        edge_kinds: &HashMap<(NodeId, NodeId), EdgeKind>,
+    ) -> Result<(), ValidationError> {
+        for (&(from, to), kind) in edge_kinds {
+            if !edges.contains(&(from, to)) {
+                return Err(ValidationError::InvalidGraphStructure);
+            }
+            if let Some(payload_type) = kind.payload_type() {
+                if !nodes.get(&to).is_some_and(|spec| spec.consumes(payload_type)) {
+                    return Err(ValidationError::UndeclaredInput);
+                }
+            }
+        }
+        Ok(())
+    }
+    
     /// Detect cycles using DFS
     fn has_cycle(
         &self,
@@ -210,69 +354,73 @@ impl ConstructionValidator {
         true
     }
     
-    /// Issue capability tokens for all nodes
-    fn issue_node_tokens(
-        &self,
-        _graph_id: GraphId,
-        nodes: &HashMap<NodeId, NodeSpecV2>,
-        signing_key: &SigningKey,
-    ) -> HashMap<NodeId, crate::autonomy::CapabilityToken> {
-        use crate::autonomy::CapabilityToken;
+    /// Unsigned capability tokens for all nodes
+    fn node_token_claims(&self, nodes: &HashMap<NodeId, NodeSpecV2>) -> Vec<(NodeId, TokenClaims)> {
         use crate::types::DirectiveProfileHash;
         
-        let mut tokens = HashMap::new();
-        let expires_at = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs()
-            + 3600; // 1 hour expiration
-        
-        for (node_id, spec) in nodes {
-            let token = CapabilityToken::sign(
-                *node_id,
-                spec.autonomy_ceiling,
-                spec.resource_bounds,
-                DirectiveProfileHash([0u8; 32]), // TODO: Compute actual hash
-                signing_key,
-                expires_at,
-                "execute",
-            );
-            
-            tokens.insert(*node_id, token);
-        }
-        
-        tokens
+        let issued_at = self.clock.now();
+        let expires_at = issued_at + 3600; // 1 hour expiration
+        
+        nodes
+            .iter()
+            .map(|(node_id, spec)| {
+                let claims = TokenClaims::new(
+                    *node_id,
+                    spec.autonomy_ceiling,
+                    spec.resource_bounds,
+                    DirectiveProfileHash([0u8; 32]), // TODO: Compute actual hash
+                    issued_at,
+                    expires_at,
+                    "execute",
+                );
+                (*node_id, claims)
+            })
+            .collect()
     }
     
-    /// Create validation token for the graph
-    fn create_validation_token(
+    /// Unsigned validation token for the graph
+    fn validation_claims(
         &self,
         graph_id: GraphId,
         nodes: &HashMap<NodeId, NodeSpecV2>,
         edges: &[(NodeId, NodeId)],
-        signing_key: &SigningKey,
-    ) -> ValidationToken {
-        let validation_hash = compute_validation_hash(graph_id, nodes, edges);
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
-        let expires_at = timestamp + 3600; // 1 hour expiration
-        
-        // Create message to sign
-        let mut message = Vec::with_capacity(16 + 32 + 8 + 8);
-        message.extend_from_slice(graph_id.0.as_bytes());
-        message.extend_from_slice(&validation_hash);
-        message.extend_from_slice(&timestamp.to_le_bytes());
-        message.extend_from_slice(&expires_at.to_le_bytes());
-        
-        let signature = signing_key.sign(&message);
-        
-        ValidationToken {
+        edge_kinds: &HashMap<(NodeId, NodeId), EdgeKind>,
+    ) -> ValidationClaims {
+        let timestamp = self.clock.now();
+        ValidationClaims {
             graph_id,
-            validation_hash,
+            validation_hash: compute_validation_hash(graph_id, nodes, edges, edge_kinds),
             timestamp,
-            expires_at,
+            expires_at: timestamp + 3600, // 1 hour expiration
+        }
+    }
+}
+
+/// Validation token fields awaiting a signature
+struct ValidationClaims {
+    graph_id: GraphId,
+    validation_hash: [u8; 32],
+    timestamp: u64,
+    expires_at: u64,
+}
+
+impl ValidationClaims {
+    /// Bytes the validation signature covers
+    fn message(&self) -> Vec<u8> {
+        let mut message = Vec::with_capacity(16 + 32 + 8 + 8);
+        message.extend_from_slice(self.graph_id.0.as_bytes());
+        message.extend_from_slice(&self.validation_hash);
+        message.extend_from_slice(&self.timestamp.to_le_bytes());
+        message.extend_from_slice(&self.expires_at.to_le_bytes());
+        message
+    }
+
+    fn into_token(self, signature: Signature) -> ValidationToken {
+        ValidationToken {
+            graph_id: self.graph_id,
+            validation_hash: self.validation_hash,
+            timestamp: self.timestamp,
+            expires_at: self.expires_at,
             signature,
         }
     }
@@ -305,6 +453,7 @@ mod tests {
                 iteration_cap: 100,
             },
             expansion_type: None,
+            inputs: Vec::new(),
         }
     }
 
@@ -424,4 +573,39 @@ mod tests {
         
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_data_edges_need_declared_inputs() {
+        let validator = ConstructionValidator::new();
+        let signing_key = create_signing_key();
+        
+        let mut nodes = HashMap::new();
+        let n1 = NodeId::new();
+        let n2 = NodeId::new();
+        nodes.insert(n1, create_test_spec(AutonomyLevel::L3, 1000));
+        nodes.insert(n2, create_test_spec(AutonomyLevel::L3, 1000));
+        let edges = vec![(n1, n2)];
+        let kinds = HashMap::from([((n1, n2), EdgeKind::data("Patch"))]);
+        
+        let result = validator.validate_typed_graph(
+            GraphId::new(),
+            GraphType::ProductionDAG,
+            &nodes,
+            &edges,
+            &kinds,
+            &signing_key,
+        );
+        assert!(matches!(result, Err(ValidationError::UndeclaredInput)));
+        
+        nodes.insert(n2, create_test_spec(AutonomyLevel::L3, 1000).with_input("Patch"));
+        let result = validator.validate_typed_graph(
+            GraphId::new(),
+            GraphType::ProductionDAG,
+            &nodes,
+            &edges,
+            &kinds,
+            &signing_key,
+        );
+        assert_eq!(result.unwrap().edge_kind(n1, n2), &EdgeKind::data("Patch"));
+    }
 }
@@ -1,3 +1,5 @@
+use crate::types::v2::GraphLifecycle;
+use crate::types::NodeId;
 use std::fmt;
 
 #[derive(Debug)]
@@ -11,6 +13,8 @@ pub enum KernelError {
     Log(LogError),
     Config(ConfigError),
     Internal(InternalError),
+    Idempotency(IdempotencyError),
+    Tenant(TenantError),
 }
 
 impl KernelError {
@@ -26,6 +30,8 @@ impl KernelError {
             KernelError::Resource(_) => true,
             KernelError::StateMachine(_) => true,
             KernelError::Log(_) => true,
+            KernelError::Idempotency(_) => true,
+            KernelError::Tenant(_) => false,
         }
     }
 
@@ -42,7 +48,10 @@ impl KernelError {
         self.is_system_error()
             || matches!(
                 self,
-                KernelError::Compliance(_) | KernelError::Autonomy(_) | KernelError::Resource(_)
+                KernelError::Compliance(_)
+                    | KernelError::Autonomy(_)
+                    | KernelError::Resource(_)
+                    | KernelError::Tenant(_)
             )
     }
 }
@@ -59,6 +68,8 @@ impl fmt::Display for KernelError {
             KernelError::Log(e) => write!(f, "Log error: {e}"),
             KernelError::Config(e) => write!(f, "Configuration error: {e}"),
             KernelError::Internal(e) => write!(f, "Internal error: {e}"),
+            KernelError::Idempotency(e) => write!(f, "Idempotency error: {e}"),
+            KernelError::Tenant(e) => write!(f, "Tenant error: {e}"),
         }
     }
 }
@@ -101,6 +112,18 @@ impl From<LogError> for KernelError {
     }
 }
 
+impl From<IdempotencyError> for KernelError {
+    fn from(value: IdempotencyError) -> Self {
+        KernelError::Idempotency(value)
+    }
+}
+
+impl From<TenantError> for KernelError {
+    fn from(value: TenantError) -> Self {
+        KernelError::Tenant(value)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum GraphError {
     GraphNotFound,
@@ -186,6 +209,14 @@ impl fmt::Display for StateMachineError {
 pub enum LogError {
     Immutable,
     IntegrityViolation,
+    /// Log entry could not be encoded
+    Encode(String),
+    /// Persisted log entry could not be decoded
+    Malformed { line: usize, message: String },
+    /// Event result does not match its action's schema
+    SchemaViolation { action: String, message: String },
+    /// Event was recorded for another tenant than the log's
+    TenantMismatch { expected: String, found: String },
 }
 
 impl fmt::Display for LogError {
@@ -194,6 +225,48 @@ impl fmt::Display for LogError {
     }
 }
 
+impl std::error::Error for LogError {}
+
+/// Checkpoint store encoding failures
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckpointError {
+    /// Store could not be encoded as JSON
+    Encode(String),
+    /// Encoded store is not valid JSON of the expected shape
+    Decode(String),
+    /// Entry key is not a hex-encoded 32-byte cache key
+    InvalidKey(String),
+}
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckpointError::Encode(message) => write!(f, "checkpoint encoding failed: {message}"),
+            CheckpointError::Decode(message) => write!(f, "checkpoint decoding failed: {message}"),
+            CheckpointError::InvalidKey(key) => write!(f, "invalid cache key {key:?}"),
+        }
+    }
+}
+
+impl std::error::Error for CheckpointError {}
+
+/// Static analysis and coverage failures
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnalysisError {
+    /// Analyzer command could not be started
+    Spawn(String),
+    /// Analyzer output could not be decoded
+    Malformed(String),
+}
+
+impl fmt::Display for AnalysisError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl std::error::Error for AnalysisError {}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ConfigError {
     InvalidConfiguration,
@@ -208,6 +281,20 @@ impl fmt::Display for ConfigError {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct InternalError(pub String);
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdempotencyError {
+    /// Key was first used for a different request
+    KeyReused { key: String },
+}
+
+impl fmt::Display for IdempotencyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl std::error::Error for IdempotencyError {}
+
 /// v2.0: Construction phase errors
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ValidationError {
@@ -219,8 +306,90 @@ pub enum ValidationError {
     InvalidGraphStructure,
     CycleDetected,
     SelfLoop,
+    QuotaExceeded,
+    /// A data edge delivers a payload its target does not declare as input
+    UndeclaredInput,
+    /// The graph's worst case exceeds the remaining run budget
+    BudgetExceeded,
+    /// Subgraph boundary ports do not match the expansion node's edges
+    BoundaryMismatch,
+    /// Tokens could not be signed
+    Signing(SignerError),
+    /// The graph could not be claimed for the building tenant
+    Tenant(TenantError),
+}
+
+impl From<SignerError> for ValidationError {
+    fn from(e: SignerError) -> Self {
+        ValidationError::Signing(e)
+    }
+}
+
+impl From<TenantError> for ValidationError {
+    fn from(e: TenantError) -> Self {
+        ValidationError::Tenant(e)
+    }
+}
+
+/// Failures of an external [`Signer`](crate::signer::Signer)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignerError {
+    /// Key store unreachable (KMS offline, HSM session lost, keychain locked)
+    Unavailable(String),
+    /// Key store refused to sign (policy, revoked key)
+    Rejected(String),
+    /// Signature does not verify under the signer's public key
+    InvalidSignature,
+}
+
+impl fmt::Display for SignerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignerError::Unavailable(reason) => write!(f, "signer unavailable: {reason}"),
+            SignerError::Rejected(reason) => write!(f, "signer rejected request: {reason}"),
+            SignerError::InvalidSignature => write!(f, "signer produced an invalid signature"),
+        }
+    }
+}
+
+impl std::error::Error for SignerError {}
+
+/// Cross-tenant access and tenant registration failures
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TenantError {
+    /// No tenant registered under the ID
+    Unknown(String),
+    /// A tenant is already registered under the ID
+    Duplicate(String),
+    /// The signing key already belongs to another tenant
+    SharedKey { tenant: String, owner: String },
+    /// The graph belongs to another tenant
+    CrossTenant { tenant: String, owner: String },
+    /// The graph was never claimed by any tenant
+    Unclaimed,
+    /// The node was not added through the tenant's front end
+    ForeignNode(String),
 }
 
+impl fmt::Display for TenantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TenantError::Unknown(tenant) => write!(f, "unknown tenant {tenant}"),
+            TenantError::Duplicate(tenant) => write!(f, "tenant {tenant} already registered"),
+            TenantError::SharedKey { tenant, owner } => {
+                write!(f, "tenant {tenant}: signing key already belongs to tenant {owner}")
+            }
+            TenantError::CrossTenant { tenant, owner } => {
+                write!(f, "tenant {tenant}: graph belongs to tenant {owner}")
+            }
+            TenantError::Unclaimed => write!(f, "graph is not owned by any tenant"),
+            TenantError::ForeignNode(node) => write!(f, "node {node} is not in the tenant's graphs"),
+        }
+    }
+}
+
+impl std::error::Error for TenantError {}
+
 impl fmt::Display for ValidationError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{:?}", self)
@@ -239,6 +408,58 @@ pub enum ExecutionError {
     ResourceEnforcementTriggered,
     GraphNotValidated,
     ExpansionRequired,
+    ScopeViolation,
+    QuotaExceeded,
+    /// Node did not finish within its deadline
+    Timeout,
+    /// Node kind's circuit breaker is open after repeated failures
+    CircuitOpen,
+    /// Upstream node did not produce the payload a data edge declares
+    MissingInput,
+    /// Node is frozen and may not run
+    NodeFrozen,
+    /// Graph completed, aborted or was archived and may not run
+    GraphNotRunnable(GraphLifecycle),
+    /// Graph was aborted while running
+    Aborted,
+    /// Graph belongs to another tenant, or to none
+    Tenant(TenantError),
+    /// Executor has no compiled execution profile for the node
+    MissingProfile(NodeId),
+}
+
+impl From<TenantError> for ExecutionError {
+    fn from(e: TenantError) -> Self {
+        ExecutionError::Tenant(e)
+    }
+}
+
+/// Graph lifecycle transition that is not allowed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IllegalGraphTransition {
+    pub from: GraphLifecycle,
+    pub to: GraphLifecycle,
+}
+
+impl fmt::Display for IllegalGraphTransition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "illegal graph transition {:?} -> {:?}", self.from, self.to)
+    }
+}
+
+impl std::error::Error for IllegalGraphTransition {}
+
+impl ExecutionError {
+    /// Whether a retry could succeed; token, scope and quota failures
+    /// (and open circuits) are permanent for the current attempt
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            ExecutionError::ResourceEnforcementTriggered
+                | ExecutionError::IllegalStateTransition
+                | ExecutionError::Timeout
+        )
+    }
 }
 
 impl fmt::Display for ExecutionError {
@@ -249,6 +470,76 @@ impl fmt::Display for ExecutionError {
 
 impl std::error::Error for ExecutionError {}
 
+/// API version handshake failures
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NegotiationError {
+    /// Client and server version ranges do not overlap
+    NoCommonVersion {
+        client: crate::api::VersionRange,
+        server: crate::api::VersionRange,
+    },
+    /// Server does not offer features the client requires
+    MissingFeatures(Vec<crate::api::KernelFeature>),
+}
+
+impl fmt::Display for NegotiationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NegotiationError::NoCommonVersion { client, server } => write!(
+                f,
+                "no common API version: client {}..={}, server {}..={}",
+                client.min, client.max, server.min, server.max
+            ),
+            NegotiationError::MissingFeatures(features) => {
+                write!(f, "required features not offered: {features:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for NegotiationError {}
+
+/// Quota violations and persistence failures
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuotaError {
+    /// Too many capability tokens issued within the last minute
+    TokenRateExceeded { subject: String, limit: u32 },
+    /// Too many node executions within the last minute
+    ExecutionRateExceeded { subject: String, limit: u32 },
+    /// Cumulative consumption for the current day would exceed the budget
+    DailyBudgetExceeded { subject: String, resource: &'static str },
+}
+
+impl fmt::Display for QuotaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuotaError::TokenRateExceeded { subject, limit } => {
+                write!(f, "{subject}: token issuance limit of {limit}/min exceeded")
+            }
+            QuotaError::ExecutionRateExceeded { subject, limit } => {
+                write!(f, "{subject}: execution limit of {limit}/min exceeded")
+            }
+            QuotaError::DailyBudgetExceeded { subject, resource } => {
+                write!(f, "{subject}: daily {resource} budget exceeded")
+            }
+        }
+    }
+}
+
+impl std::error::Error for QuotaError {}
+
+impl From<QuotaError> for ValidationError {
+    fn from(_: QuotaError) -> Self {
+        ValidationError::QuotaExceeded
+    }
+}
+
+impl From<QuotaError> for ExecutionError {
+    fn from(_: QuotaError) -> Self {
+        ExecutionError::QuotaExceeded
+    }
+}
+
 impl fmt::Display for InternalError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.0)
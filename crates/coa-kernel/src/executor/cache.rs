@@ -0,0 +1,192 @@
+//! Node execution cache
+//!
+//! A node whose spec, inputs and directive hash match an earlier successful
+//! run does not execute again: the [`Executor`](super::Executor) reuses the
+//! output recorded for that run, much like an incremental build skips
+//! unchanged targets. Runs are recorded in a [`CheckpointStore`] under a
+//! content-addressed [`CacheKey`], so they carry over between graphs and,
+//! once encoded with [`CheckpointStore::to_json`], between processes.
+
+use super::{NodeExecutionResult, NodeInputs, NodePayload};
+use crate::error::CheckpointError;
+use crate::types::v2::NodeSpecV2;
+use crate::types::{DirectiveProfileHash, ResourceCaps};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// Content address of one node run
+///
+/// Node IDs are not part of the key: the same work in a freshly built
+/// graph hits the same entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CacheKey(pub [u8; 32]);
+
+impl CacheKey {
+    /// Key for running `spec` on `inputs` under `directive_hash`
+    pub fn compute(
+        spec: &NodeSpecV2,
+        inputs: &NodeInputs,
+        directive_hash: DirectiveProfileHash,
+    ) -> Self {
+        let mut hasher = Sha256::new();
+        // NodeSpecV2 holds only ordered maps, so its JSON is canonical
+        hasher.update(serde_json::to_vec(spec).unwrap_or_default());
+        for (_, payload) in inputs.iter() {
+            hasher.update(payload.payload_type.as_bytes());
+            hasher.update([0]);
+            hasher.update(payload.value.to_string().as_bytes());
+            hasher.update([0]);
+        }
+        hasher.update(directive_hash.0);
+        Self(hasher.finalize().into())
+    }
+}
+
+impl fmt::Display for CacheKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
+
+/// Recorded outcome of a successful node run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Output handed to data dependents
+    pub output: Option<NodePayload>,
+    /// Consumption of the original run
+    pub resource_consumed: ResourceCaps,
+    pub execution_time_ms: u64,
+}
+
+impl From<&NodeExecutionResult> for Checkpoint {
+    fn from(result: &NodeExecutionResult) -> Self {
+        Self {
+            output: result.output.clone(),
+            resource_consumed: result.resource_consumed,
+            execution_time_ms: result.execution_time_ms,
+        }
+    }
+}
+
+/// Successful node runs, by cache key
+#[derive(Debug, Default)]
+pub struct CheckpointStore {
+    entries: Mutex<HashMap<CacheKey, Checkpoint>>,
+}
+
+impl CheckpointStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Recorded run for `key`, if any
+    pub fn get(&self, key: &CacheKey) -> Option<Checkpoint> {
+        self.entries.lock().get(key).cloned()
+    }
+
+    /// Record a successful run, replacing any earlier one
+    pub fn record(&self, key: CacheKey, checkpoint: Checkpoint) {
+        self.entries.lock().insert(key, checkpoint);
+    }
+
+    /// Every recorded run
+    pub fn checkpoints(&self) -> Vec<Checkpoint> {
+        self.entries.lock().values().cloned().collect()
+    }
+
+    /// Forget every recorded run
+    pub fn clear(&self) {
+        self.entries.lock().clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.lock().is_empty()
+    }
+
+    /// Encode the store as JSON, keyed by hex cache key
+    pub fn to_json(&self) -> Result<String, CheckpointError> {
+        let entries: BTreeMap<String, Checkpoint> = self
+            .entries
+            .lock()
+            .iter()
+            .map(|(key, checkpoint)| (key.to_string(), checkpoint.clone()))
+            .collect();
+        serde_json::to_string_pretty(&entries).map_err(|e| CheckpointError::Encode(e.to_string()))
+    }
+
+    /// Decode a store encoded by `to_json`
+    pub fn from_json(json: &str) -> Result<Self, CheckpointError> {
+        let entries: BTreeMap<String, Checkpoint> =
+            serde_json::from_str(json).map_err(|e| CheckpointError::Decode(e.to_string()))?;
+        let mut store = HashMap::with_capacity(entries.len());
+        for (key, checkpoint) in entries {
+            let bytes = hex::decode(&key)
+                .ok()
+                .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+                .ok_or(CheckpointError::InvalidKey(key))?;
+            store.insert(CacheKey(bytes), checkpoint);
+        }
+        Ok(Self {
+            entries: Mutex::new(store),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{AutonomyLevel, DirectiveSet};
+
+    fn spec() -> NodeSpecV2 {
+        NodeSpecV2::new(
+            DirectiveSet {
+                directives: BTreeMap::new(),
+            },
+            AutonomyLevel::L3,
+            ResourceCaps {
+                cpu_time_ms: 1000,
+                memory_bytes: 1024,
+                token_limit: 100,
+                iteration_cap: 10,
+            },
+        )
+    }
+
+    #[test]
+    fn keys_follow_content_and_survive_encoding() {
+        let hash = DirectiveProfileHash([7; 32]);
+        let key = CacheKey::compute(&spec(), &NodeInputs::default(), hash);
+        assert_eq!(key, CacheKey::compute(&spec(), &NodeInputs::default(), hash));
+        assert_ne!(key, CacheKey::compute(&spec().with_input("Patch"), &NodeInputs::default(), hash));
+        assert_ne!(
+            key,
+            CacheKey::compute(&spec(), &NodeInputs::default(), DirectiveProfileHash([0; 32]))
+        );
+
+        let store = CheckpointStore::new();
+        let checkpoint = Checkpoint {
+            output: Some(NodePayload::new("Patch", serde_json::json!({ "lines": 3 }))),
+            resource_consumed: spec().resource_bounds,
+            execution_time_ms: 12,
+        };
+        store.record(key, checkpoint.clone());
+
+        let loaded = CheckpointStore::from_json(&store.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded.get(&key), Some(checkpoint));
+
+        assert!(matches!(CheckpointStore::from_json("[]"), Err(CheckpointError::Decode(_))));
+        let renamed = store.to_json().unwrap().replace(&key.to_string(), "zz");
+        assert_eq!(
+            CheckpointStore::from_json(&renamed).unwrap_err(),
+            CheckpointError::InvalidKey("zz".to_string())
+        );
+    }
+}
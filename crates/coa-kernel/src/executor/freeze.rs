@@ -0,0 +1,337 @@
+//! Runtime node freezes
+//!
+//! A [`FreezeRegistry`] holds the nodes an operator has frozen. Executors
+//! and schedulers sharing it skip frozen nodes, recording
+//! [`SkipReason::Frozen`], and treat their dependents per
+//! [`FrozenPolicy`]. Freezing and unfreezing are recorded in the kernel
+//! event log; unfreezing takes a signed token of sufficient autonomy.
+
+use crate::autonomy::CapabilityToken;
+use crate::clock::{system_clock, SharedClock};
+use crate::error::{ExecutionError, LogError};
+use crate::logging::{Action, Event, EventLog};
+use crate::token_integrity::TokenIntegrity;
+use crate::types::v2::{SkipReason, ValidatedGraph};
+use crate::types::{AutonomyLevel, DirectiveProfileHash, EventId, NodeId, Timestamp};
+use ed25519_dalek::VerifyingKey;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Lowest autonomy level allowed to unfreeze a node by default
+pub const UNFREEZE_AUTONOMY: AutonomyLevel = AutonomyLevel::L4;
+
+/// How dependents of a frozen node are treated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FrozenPolicy {
+    /// Skip every transitive dependent as [`SkipReason::Blocked`]
+    #[default]
+    Block,
+    /// Treat the frozen node as skipped: ordering-only dependents run,
+    /// data dependents and guards see a skipped source
+    Continue,
+}
+
+/// A frozen node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrozenNode {
+    pub reason: String,
+    pub frozen_at: Timestamp,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FreezeError {
+    NotFrozen(NodeId),
+    /// Unfreeze token is below the required autonomy level
+    InsufficientAutonomy {
+        required: AutonomyLevel,
+        actual: AutonomyLevel,
+    },
+    /// Unfreeze token failed signature or expiry checks
+    InvalidToken(ExecutionError),
+    Log(LogError),
+}
+
+impl fmt::Display for FreezeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl std::error::Error for FreezeError {}
+
+impl From<LogError> for FreezeError {
+    fn from(error: LogError) -> Self {
+        FreezeError::Log(error)
+    }
+}
+
+/// Nodes held back so far in one run, with the frozen node holding each
+#[derive(Debug, Default)]
+pub struct HeldNodes(HashMap<NodeId, NodeId>);
+
+impl HeldNodes {
+    /// Check if `node_id` was held back
+    pub fn contains(&self, node_id: NodeId) -> bool {
+        self.0.contains_key(&node_id)
+    }
+}
+
+/// Frozen nodes shared between operators and executors
+pub struct FreezeRegistry {
+    frozen: RwLock<HashMap<NodeId, FrozenNode>>,
+    policy: FrozenPolicy,
+    unfreeze_level: AutonomyLevel,
+    verifying_key: VerifyingKey,
+    log: Option<Arc<EventLog>>,
+    clock: SharedClock,
+}
+
+impl fmt::Debug for FreezeRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FreezeRegistry")
+            .field("frozen", &*self.frozen.read())
+            .field("policy", &self.policy)
+            .field("unfreeze_level", &self.unfreeze_level)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FreezeRegistry {
+    /// Registry accepting unfreeze tokens signed by `verifying_key`
+    pub fn new(verifying_key: VerifyingKey) -> Self {
+        Self {
+            frozen: RwLock::new(HashMap::new()),
+            policy: FrozenPolicy::default(),
+            unfreeze_level: UNFREEZE_AUTONOMY,
+            verifying_key,
+            log: None,
+            clock: system_clock(),
+        }
+    }
+
+    /// Treat dependents of frozen nodes per `policy`
+    pub fn with_policy(mut self, policy: FrozenPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Require `level` to unfreeze a node
+    pub fn with_unfreeze_level(mut self, level: AutonomyLevel) -> Self {
+        self.unfreeze_level = level;
+        self
+    }
+
+    /// Record freezes and unfreezes in `log`, stamped with its clock
+    pub fn with_log(mut self, log: Arc<EventLog>) -> Self {
+        self.log = Some(log);
+        self
+    }
+
+    /// Check token expiry against `clock`
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn policy(&self) -> FrozenPolicy {
+        self.policy
+    }
+
+    /// Freeze record of `node_id`, if frozen
+    pub fn get(&self, node_id: NodeId) -> Option<FrozenNode> {
+        self.frozen.read().get(&node_id).cloned()
+    }
+
+    pub fn is_frozen(&self, node_id: NodeId) -> bool {
+        self.frozen.read().contains_key(&node_id)
+    }
+
+    /// Freeze `node_id`; freezing a frozen node updates its reason
+    pub fn freeze(&self, node_id: NodeId, reason: impl Into<String>) -> Result<(), FreezeError> {
+        let reason = reason.into();
+        let frozen_at = self.now();
+        self.record(
+            node_id,
+            Action::Freeze,
+            AutonomyLevel::L0,
+            DirectiveProfileHash([0u8; 32]),
+            &reason,
+        )?;
+        self.frozen.write().insert(node_id, FrozenNode { reason, frozen_at });
+        Ok(())
+    }
+
+    /// Unfreeze `node_id` on the authority of `token`
+    ///
+    /// # Errors
+    /// - `FreezeError::NotFrozen` if the node is not frozen
+    /// - `FreezeError::InvalidToken` if the token's signature or expiry
+    ///   does not check out
+    /// - `FreezeError::InsufficientAutonomy` if the token's level is below
+    ///   the registry's unfreeze level
+    pub fn unfreeze(
+        &self,
+        node_id: NodeId,
+        token: &CapabilityToken,
+    ) -> Result<FrozenNode, FreezeError> {
+        if !self.is_frozen(node_id) {
+            return Err(FreezeError::NotFrozen(node_id));
+        }
+        TokenIntegrity::verify_integrity_with_clock(token, &self.verifying_key, self.clock.as_ref())
+            .map_err(FreezeError::InvalidToken)?;
+        if token.autonomy_level < self.unfreeze_level {
+            return Err(FreezeError::InsufficientAutonomy {
+                required: self.unfreeze_level,
+                actual: token.autonomy_level,
+            });
+        }
+        let frozen = self
+            .frozen
+            .write()
+            .remove(&node_id)
+            .ok_or(FreezeError::NotFrozen(node_id))?;
+        self.record(
+            node_id,
+            Action::Unfreeze,
+            token.autonomy_level,
+            token.directive_hash,
+            &frozen.reason,
+        )?;
+        Ok(frozen)
+    }
+
+    /// Decide whether `node_id` is held back in a run of `graph`
+    ///
+    /// Call in execution order with the same `held` for the whole run.
+    pub fn check(
+        &self,
+        graph: &ValidatedGraph,
+        node_id: NodeId,
+        held: &mut HeldNodes,
+    ) -> Option<SkipReason> {
+        if let Some(frozen) = self.get(node_id) {
+            held.0.insert(node_id, node_id);
+            return Some(SkipReason::Frozen { reason: frozen.reason });
+        }
+        if self.policy == FrozenPolicy::Continue {
+            return None;
+        }
+        let by = graph
+            .incoming(node_id)
+            .find_map(|(source, _)| held.0.get(&source).copied())?;
+        held.0.insert(node_id, by);
+        Some(SkipReason::Blocked { by })
+    }
+
+    fn now(&self) -> Timestamp {
+        self.log.as_ref().map_or_else(|| self.clock.now(), |log| log.now())
+    }
+
+    fn record(
+        &self,
+        node_id: NodeId,
+        action: Action,
+        autonomy_level: AutonomyLevel,
+        directive_hash: DirectiveProfileHash,
+        result: &str,
+    ) -> Result<(), LogError> {
+        let Some(log) = &self.log else {
+            return Ok(());
+        };
+        log.append(Event {
+            event_id: EventId::new(),
+            timestamp: log.now(),
+            node_id,
+            autonomy_level,
+            directive_hash,
+            action,
+            result: result.to_string(),
+            schema_version: None,
+            correlation_id: None,
+            prev_hash: [0u8; 32],
+            hash: [0u8; 32],
+            hash_algorithm: None,
+            tenant_id: None,
+        })
+        .map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ResourceCaps;
+    use ed25519_dalek::SigningKey;
+
+    fn unfreeze_token(key: &SigningKey, node_id: NodeId, level: AutonomyLevel) -> CapabilityToken {
+        let caps = ResourceCaps {
+            cpu_time_ms: 0,
+            memory_bytes: 0,
+            token_limit: 0,
+            iteration_cap: 0,
+        };
+        CapabilityToken::sign(
+            node_id,
+            level,
+            caps,
+            DirectiveProfileHash([0u8; 32]),
+            key,
+            0,
+            "unfreeze",
+        )
+    }
+
+    #[test]
+    fn unfreeze_requires_sufficient_autonomy() {
+        let key = SigningKey::from_bytes(&[3; 32]);
+        let registry = FreezeRegistry::new(key.verifying_key());
+        let node = NodeId::new();
+        registry.freeze(node, "incident review").unwrap();
+
+        assert_eq!(
+            registry.unfreeze(node, &unfreeze_token(&key, node, AutonomyLevel::L3)),
+            Err(FreezeError::InsufficientAutonomy {
+                required: UNFREEZE_AUTONOMY,
+                actual: AutonomyLevel::L3,
+            })
+        );
+        let forged = unfreeze_token(&SigningKey::from_bytes(&[4; 32]), node, AutonomyLevel::L5);
+        assert!(matches!(
+            registry.unfreeze(node, &forged),
+            Err(FreezeError::InvalidToken(_))
+        ));
+        assert!(registry.is_frozen(node));
+
+        let frozen = registry
+            .unfreeze(node, &unfreeze_token(&key, node, AutonomyLevel::L4))
+            .unwrap();
+        assert_eq!(frozen.reason, "incident review");
+        assert!(!registry.is_frozen(node));
+        assert_eq!(
+            registry.unfreeze(node, &unfreeze_token(&key, node, AutonomyLevel::L4)),
+            Err(FreezeError::NotFrozen(node))
+        );
+    }
+
+    #[test]
+    fn freezes_and_unfreezes_are_logged() {
+        let key = SigningKey::from_bytes(&[3; 32]);
+        let log = Arc::new(EventLog::default());
+        let registry = FreezeRegistry::new(key.verifying_key()).with_log(Arc::clone(&log));
+        let node = NodeId::new();
+        registry.freeze(node, "flaky deploy").unwrap();
+        registry
+            .unfreeze(node, &unfreeze_token(&key, node, AutonomyLevel::L5))
+            .unwrap();
+
+        let events = log.events();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].action, Action::Freeze);
+        assert_eq!(events[0].result, "flaky deploy");
+        assert_eq!(events[1].action, Action::Unfreeze);
+        assert_eq!(events[1].autonomy_level, AutonomyLevel::L5);
+        assert!(events.iter().all(|event| event.node_id == node));
+    }
+}
@@ -0,0 +1,639 @@
+//! NodeExecutor middleware
+//!
+//! A [`NodeMiddleware`] wraps one [`NodeExecutor`] in another, much like a
+//! tower layer. [`MiddlewareStack`] composes them; the first layer added is
+//! the outermost. Built-in middlewares:
+//! - [`ConcurrencyLimit`]: caps nodes executing at once
+//! - [`CircuitBreaker`]: fails fast for a node kind after repeated failures
+//! - [`Retry`]: retries transient errors with exponential backoff
+//! - [`Timeout`]: bounds each attempt
+//!
+//! A node's kind is its compiled directive profile
+//! ([`CapabilityToken::directive_hash`]), so nodes built from the same
+//! directives share a circuit.
+
+use super::{NodeExecutionResult, NodeExecutor, NodeInputs};
+use crate::autonomy::CapabilityToken;
+use crate::clock::{system_clock, SharedClock};
+use crate::error::ExecutionError;
+use crate::types::{DirectiveProfileHash, DirectiveSet, NodeId, Timestamp};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+/// Directive: maximum nodes executing concurrently
+pub const MAX_CONCURRENT_NODES: &str = "max_concurrent_nodes";
+/// Directive: consecutive failures that open a node kind's circuit
+pub const CIRCUIT_BREAKER_THRESHOLD: &str = "circuit_breaker_threshold";
+/// Directive: how long an open circuit rejects before probing again
+pub const CIRCUIT_BREAKER_COOLDOWN_MS: &str = "circuit_breaker_cooldown_ms";
+/// Directive: retries per node after the first attempt
+pub const MAX_RETRIES: &str = "max_retries";
+/// Directive: delay before the first retry, doubled on each further retry
+pub const RETRY_BACKOFF_MS: &str = "retry_backoff_ms";
+/// Directive: deadline for each node attempt
+pub const NODE_TIMEOUT_MS: &str = "node_timeout_ms";
+
+/// Wraps a node executor with extra behaviour
+pub trait NodeMiddleware: Send + Sync {
+    /// Wrap `inner`
+    fn layer(&self, inner: Arc<dyn NodeExecutor>) -> Arc<dyn NodeExecutor>;
+}
+
+/// Ordered set of middlewares; the first added is the outermost
+#[derive(Clone, Default)]
+pub struct MiddlewareStack {
+    layers: Vec<Arc<dyn NodeMiddleware>>,
+}
+
+impl MiddlewareStack {
+    /// Empty stack
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a middleware inside the ones already added
+    pub fn layer(mut self, middleware: impl NodeMiddleware + 'static) -> Self {
+        self.layers.push(Arc::new(middleware));
+        self
+    }
+
+    /// Build the standard stack from directives
+    ///
+    /// Reads [`MAX_CONCURRENT_NODES`], [`CIRCUIT_BREAKER_THRESHOLD`] (with
+    /// [`CIRCUIT_BREAKER_COOLDOWN_MS`]), [`MAX_RETRIES`] (with
+    /// [`RETRY_BACKOFF_MS`]) and [`NODE_TIMEOUT_MS`]; absent or zero values
+    /// leave the middleware out. Layers are ordered concurrency limit,
+    /// circuit breaker, retry, timeout.
+    pub fn from_directives(directives: &DirectiveSet) -> Self {
+        let get = |key: &str| {
+            directives
+                .directives
+                .get(key)
+                .and_then(|v| v.as_u64())
+                .filter(|&v| v > 0)
+        };
+
+        let mut stack = Self::new();
+        if let Some(limit) = get(MAX_CONCURRENT_NODES) {
+            stack = stack.layer(ConcurrencyLimit::new(limit.min(u32::MAX as u64) as usize));
+        }
+        if let Some(threshold) = get(CIRCUIT_BREAKER_THRESHOLD) {
+            let mut breaker = CircuitBreaker::new(threshold.min(u32::MAX as u64) as u32);
+            if let Some(ms) = get(CIRCUIT_BREAKER_COOLDOWN_MS) {
+                breaker = breaker.with_cooldown(Duration::from_millis(ms));
+            }
+            stack = stack.layer(breaker);
+        }
+        if let Some(retries) = get(MAX_RETRIES) {
+            let mut retry = Retry::new(retries.min(u32::MAX as u64) as u32);
+            if let Some(ms) = get(RETRY_BACKOFF_MS) {
+                retry = retry.with_backoff(Duration::from_millis(ms));
+            }
+            stack = stack.layer(retry);
+        }
+        if let Some(ms) = get(NODE_TIMEOUT_MS) {
+            stack = stack.layer(Timeout::new(Duration::from_millis(ms)));
+        }
+        stack
+    }
+
+    /// Number of middlewares
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Whether the stack has no middlewares
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    /// Wrap `executor` in every middleware
+    pub fn wrap(&self, executor: Arc<dyn NodeExecutor>) -> Arc<dyn NodeExecutor> {
+        self.layers
+            .iter()
+            .rev()
+            .fold(executor, |inner, middleware| middleware.layer(inner))
+    }
+}
+
+impl std::fmt::Debug for MiddlewareStack {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MiddlewareStack")
+            .field("layers", &self.layers.len())
+            .finish()
+    }
+}
+
+/// Retry transient errors with exponential backoff
+#[derive(Debug, Clone, Copy)]
+pub struct Retry {
+    max_retries: u32,
+    backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl Retry {
+    /// Retry up to `max_retries` times, starting at 100ms backoff
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+
+    /// Delay before the first retry; doubled on each further retry
+    pub fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Upper bound on a single backoff delay
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Delay before retry number `retry` (zero-based)
+    fn delay(&self, retry: u32) -> Duration {
+        self.backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff)
+    }
+}
+
+impl NodeMiddleware for Retry {
+    fn layer(&self, inner: Arc<dyn NodeExecutor>) -> Arc<dyn NodeExecutor> {
+        Arc::new(Retrying { config: *self, inner })
+    }
+}
+
+struct Retrying {
+    config: Retry,
+    inner: Arc<dyn NodeExecutor>,
+}
+
+#[async_trait::async_trait]
+impl NodeExecutor for Retrying {
+    async fn execute_node(
+        &self,
+        node_id: NodeId,
+        token: &CapabilityToken,
+        inputs: &NodeInputs,
+    ) -> Result<NodeExecutionResult, ExecutionError> {
+        let mut retry = 0;
+        loop {
+            match self.inner.execute_node(node_id, token, inputs).await {
+                Err(error) if error.is_transient() && retry < self.config.max_retries => {
+                    tokio::time::sleep(self.config.delay(retry)).await;
+                    retry += 1;
+                }
+                outcome => return outcome,
+            }
+        }
+    }
+}
+
+/// Fail an attempt with [`ExecutionError::Timeout`] after a deadline
+#[derive(Debug, Clone, Copy)]
+pub struct Timeout {
+    duration: Duration,
+}
+
+impl Timeout {
+    /// Bound each attempt to `duration`
+    pub fn new(duration: Duration) -> Self {
+        Self { duration }
+    }
+}
+
+impl NodeMiddleware for Timeout {
+    fn layer(&self, inner: Arc<dyn NodeExecutor>) -> Arc<dyn NodeExecutor> {
+        Arc::new(TimingOut { duration: self.duration, inner })
+    }
+}
+
+struct TimingOut {
+    duration: Duration,
+    inner: Arc<dyn NodeExecutor>,
+}
+
+#[async_trait::async_trait]
+impl NodeExecutor for TimingOut {
+    async fn execute_node(
+        &self,
+        node_id: NodeId,
+        token: &CapabilityToken,
+        inputs: &NodeInputs,
+    ) -> Result<NodeExecutionResult, ExecutionError> {
+        tokio::time::timeout(self.duration, self.inner.execute_node(node_id, token, inputs))
+            .await
+            .map_err(|_| ExecutionError::Timeout)?
+    }
+}
+
+/// Per node kind circuit breaker
+///
+/// After `failure_threshold` consecutive failures (errors or unsuccessful
+/// results) a kind's circuit opens and its nodes fail with
+/// [`ExecutionError::CircuitOpen`] until the cooldown passes. Then a single
+/// probe is let through: success closes the circuit, failure reopens it.
+/// A probe dropped before finishing (e.g. an aborted run) lets the next
+/// call probe instead.
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    cooldown: Duration,
+    clock: SharedClock,
+}
+
+impl CircuitBreaker {
+    /// Open after `failure_threshold` consecutive failures, for 30s
+    pub fn new(failure_threshold: u32) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            cooldown: Duration::from_secs(30),
+            clock: system_clock(),
+        }
+    }
+
+    /// How long an open circuit rejects before probing again
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Time cooldowns against `clock` (whole seconds)
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+}
+
+impl NodeMiddleware for CircuitBreaker {
+    fn layer(&self, inner: Arc<dyn NodeExecutor>) -> Arc<dyn NodeExecutor> {
+        Arc::new(Breaking {
+            config: self.clone(),
+            circuits: Mutex::new(HashMap::new()),
+            inner,
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct Circuit {
+    failures: u32,
+    opened_at: Option<Timestamp>,
+    probing: bool,
+}
+
+struct Breaking {
+    config: CircuitBreaker,
+    circuits: Mutex<HashMap<DirectiveProfileHash, Circuit>>,
+    inner: Arc<dyn NodeExecutor>,
+}
+
+impl Breaking {
+    /// Admit a call, marking it as the probe if the cooldown has passed
+    fn admit(&self, kind: DirectiveProfileHash) -> Result<Admission<'_>, ExecutionError> {
+        let now = self.config.clock.now();
+        let mut circuits = self.circuits.lock();
+        let circuit = circuits.entry(kind).or_default();
+        let probe = match circuit.opened_at {
+            Some(opened)
+                if circuit.probing
+                    || Duration::from_secs(now.saturating_sub(opened)) < self.config.cooldown =>
+            {
+                return Err(ExecutionError::CircuitOpen)
+            }
+            Some(_) => {
+                circuit.probing = true;
+                true
+            }
+            None => false,
+        };
+        Ok(Admission {
+            breaking: self,
+            kind,
+            probe,
+        })
+    }
+
+    fn settle(&self, kind: DirectiveProfileHash, succeeded: bool) {
+        let mut circuits = self.circuits.lock();
+        let circuit = circuits.entry(kind).or_default();
+        if succeeded {
+            *circuit = Circuit::default();
+            return;
+        }
+        circuit.failures += 1;
+        if circuit.probing || circuit.failures >= self.config.failure_threshold {
+            circuit.opened_at = Some(self.config.clock.now());
+        }
+        circuit.probing = false;
+    }
+}
+
+/// Admitted call; clears the probe flag if dropped unsettled
+struct Admission<'a> {
+    breaking: &'a Breaking,
+    kind: DirectiveProfileHash,
+    probe: bool,
+}
+
+impl Admission<'_> {
+    fn settle(mut self, succeeded: bool) {
+        self.probe = false;
+        self.breaking.settle(self.kind, succeeded);
+    }
+}
+
+impl Drop for Admission<'_> {
+    fn drop(&mut self) {
+        if self.probe {
+            if let Some(circuit) = self.breaking.circuits.lock().get_mut(&self.kind) {
+                circuit.probing = false;
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl NodeExecutor for Breaking {
+    async fn execute_node(
+        &self,
+        node_id: NodeId,
+        token: &CapabilityToken,
+        inputs: &NodeInputs,
+    ) -> Result<NodeExecutionResult, ExecutionError> {
+        let kind = token.directive_hash;
+        let admission = self.admit(kind)?;
+        let outcome = self.inner.execute_node(node_id, token, inputs).await;
+        admission.settle(matches!(&outcome, Ok(result) if result.success));
+        outcome
+    }
+}
+
+/// Cap the number of nodes executing at once
+#[derive(Debug, Clone, Copy)]
+pub struct ConcurrencyLimit {
+    max_concurrent: usize,
+}
+
+impl ConcurrencyLimit {
+    /// Allow at most `max_concurrent` nodes in flight
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            max_concurrent: max_concurrent.max(1),
+        }
+    }
+}
+
+impl NodeMiddleware for ConcurrencyLimit {
+    fn layer(&self, inner: Arc<dyn NodeExecutor>) -> Arc<dyn NodeExecutor> {
+        Arc::new(Limited {
+            permits: Semaphore::new(self.max_concurrent),
+            inner,
+        })
+    }
+}
+
+struct Limited {
+    permits: Semaphore,
+    inner: Arc<dyn NodeExecutor>,
+}
+
+#[async_trait::async_trait]
+impl NodeExecutor for Limited {
+    async fn execute_node(
+        &self,
+        node_id: NodeId,
+        token: &CapabilityToken,
+        inputs: &NodeInputs,
+    ) -> Result<NodeExecutionResult, ExecutionError> {
+        // The semaphore is never closed, so acquiring only waits
+        let _permit = self
+            .permits
+            .acquire()
+            .await
+            .map_err(|_| ExecutionError::ResourceEnforcementTriggered)?;
+        self.inner.execute_node(node_id, token, inputs).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::types::{AutonomyLevel, ResourceCaps};
+    use std::collections::BTreeMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Replays scripted outcomes, then succeeds; tracks calls and concurrency
+    #[derive(Default)]
+    struct Scripted {
+        outcomes: Mutex<Vec<Result<bool, ExecutionError>>>,
+        delay: Duration,
+        calls: AtomicUsize,
+        in_flight: AtomicUsize,
+        peak: AtomicUsize,
+    }
+
+    impl Scripted {
+        fn new(outcomes: Vec<Result<bool, ExecutionError>>) -> Arc<Self> {
+            Arc::new(Self {
+                outcomes: Mutex::new(outcomes),
+                ..Self::default()
+            })
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl NodeExecutor for Scripted {
+        async fn execute_node(
+            &self,
+            node_id: NodeId,
+            _token: &CapabilityToken,
+            _inputs: &NodeInputs,
+        ) -> Result<NodeExecutionResult, ExecutionError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(self.delay).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            let next = {
+                let mut outcomes = self.outcomes.lock();
+                if outcomes.is_empty() { Ok(true) } else { outcomes.remove(0) }
+            };
+            next.map(|success| NodeExecutionResult {
+                node_id,
+                success,
+                execution_time_ms: 0,
+                resource_consumed: caps(),
+                output: None,
+            })
+        }
+    }
+
+    fn caps() -> ResourceCaps {
+        ResourceCaps {
+            cpu_time_ms: 0,
+            memory_bytes: 0,
+            token_limit: 0,
+            iteration_cap: 0,
+        }
+    }
+
+    fn token(kind: u8) -> CapabilityToken {
+        CapabilityToken::sign(
+            NodeId::new(),
+            AutonomyLevel::L1,
+            caps(),
+            DirectiveProfileHash([kind; 32]),
+            &ed25519_dalek::SigningKey::from_bytes(&[1u8; 32]),
+            0,
+            "execute",
+        )
+    }
+
+    #[tokio::test]
+    async fn retry_backs_off_on_transient_errors_only() {
+        let inner = Scripted::new(vec![
+            Err(ExecutionError::Timeout),
+            Err(ExecutionError::ResourceEnforcementTriggered),
+        ]);
+        let retry = Retry::new(2).with_backoff(Duration::from_millis(1));
+        assert_eq!(retry.delay(0), Duration::from_millis(1));
+        assert_eq!(retry.delay(3), Duration::from_millis(8));
+
+        let executor = MiddlewareStack::new().layer(retry).wrap(inner.clone());
+        let t = token(0);
+        assert!(executor.execute_node(t.node_id, &t, &NodeInputs::default()).await.unwrap().success);
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 3);
+
+        let inner = Scripted::new(vec![Err(ExecutionError::QuotaExceeded)]);
+        let executor = MiddlewareStack::new().layer(retry).wrap(inner.clone());
+        let result = executor.execute_node(t.node_id, &t, &NodeInputs::default()).await;
+        assert_eq!(result.unwrap_err(), ExecutionError::QuotaExceeded);
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn timeout_bounds_each_attempt() {
+        let inner = Arc::new(Scripted {
+            delay: Duration::from_millis(200),
+            ..Scripted::default()
+        });
+        let executor = MiddlewareStack::new()
+            .layer(Timeout::new(Duration::from_millis(5)))
+            .wrap(inner);
+        let t = token(0);
+        let result = executor.execute_node(t.node_id, &t, &NodeInputs::default()).await;
+        assert_eq!(result.unwrap_err(), ExecutionError::Timeout);
+    }
+
+    #[tokio::test]
+    async fn circuit_breaker_opens_per_node_kind() {
+        let inner = Scripted::new(vec![
+            Err(ExecutionError::ResourceEnforcementTriggered),
+            Ok(false),
+        ]);
+        let clock = Arc::new(ManualClock::new(1_000));
+        let breaker = CircuitBreaker::new(2)
+            .with_cooldown(Duration::from_secs(20))
+            .with_clock(clock.clone());
+        let executor = MiddlewareStack::new().layer(breaker).wrap(inner.clone());
+        let (flaky, other) = (token(1), token(2));
+
+        assert!(executor.execute_node(flaky.node_id, &flaky, &NodeInputs::default()).await.is_err());
+        assert!(!executor.execute_node(flaky.node_id, &flaky, &NodeInputs::default()).await.unwrap().success);
+        let rejected = executor.execute_node(flaky.node_id, &flaky, &NodeInputs::default()).await;
+        assert_eq!(rejected.unwrap_err(), ExecutionError::CircuitOpen);
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+
+        // Other kinds are unaffected
+        assert!(executor.execute_node(other.node_id, &other, &NodeInputs::default()).await.is_ok());
+
+        // After the cooldown a successful probe closes the circuit
+        clock.advance(19);
+        let rejected = executor.execute_node(flaky.node_id, &flaky, &NodeInputs::default()).await;
+        assert_eq!(rejected.unwrap_err(), ExecutionError::CircuitOpen);
+        clock.advance(1);
+        assert!(executor.execute_node(flaky.node_id, &flaky, &NodeInputs::default()).await.is_ok());
+        assert!(executor.execute_node(flaky.node_id, &flaky, &NodeInputs::default()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn dropped_probe_frees_the_circuit() {
+        let inner = Arc::new(Scripted {
+            outcomes: Mutex::new(vec![Ok(false)]),
+            delay: Duration::from_millis(50),
+            ..Scripted::default()
+        });
+        let clock = Arc::new(ManualClock::new(1_000));
+        let breaker = CircuitBreaker::new(1)
+            .with_cooldown(Duration::from_secs(5))
+            .with_clock(clock.clone());
+        let executor = MiddlewareStack::new().layer(breaker).wrap(inner.clone());
+        let t = token(1);
+
+        assert!(!executor.execute_node(t.node_id, &t, &NodeInputs::default()).await.unwrap().success);
+        clock.advance(5);
+
+        // The probe is abandoned mid-flight, e.g. by an outer deadline
+        let inputs = NodeInputs::default();
+        let probe = executor.execute_node(t.node_id, &t, &inputs);
+        assert!(tokio::time::timeout(Duration::from_millis(1), probe).await.is_err());
+
+        assert!(executor.execute_node(t.node_id, &t, &NodeInputs::default()).await.unwrap().success);
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn concurrency_limit_caps_in_flight_nodes() {
+        let inner = Arc::new(Scripted {
+            delay: Duration::from_millis(10),
+            ..Scripted::default()
+        });
+        let executor = MiddlewareStack::new()
+            .layer(ConcurrencyLimit::new(2))
+            .wrap(inner.clone());
+
+        let runs: Vec<_> = (0..6)
+            .map(|_| {
+                let executor = executor.clone();
+                let t = token(0);
+                tokio::spawn(async move { executor.execute_node(t.node_id, &t, &NodeInputs::default()).await })
+            })
+            .collect();
+        for run in runs {
+            assert!(run.await.unwrap().is_ok());
+        }
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 6);
+        assert_eq!(inner.peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn stack_from_directives() {
+        let directives = |pairs: &[(&str, u64)]| DirectiveSet {
+            directives: pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), serde_json::json!(v)))
+                .collect::<BTreeMap<_, _>>(),
+        };
+
+        assert!(MiddlewareStack::from_directives(&directives(&[])).is_empty());
+        let stack = MiddlewareStack::from_directives(&directives(&[
+            (MAX_CONCURRENT_NODES, 4),
+            (CIRCUIT_BREAKER_THRESHOLD, 3),
+            (MAX_RETRIES, 2),
+            (RETRY_BACKOFF_MS, 50),
+            (NODE_TIMEOUT_MS, 0),
+        ]));
+        assert_eq!(stack.len(), 3);
+    }
+}
@@ -10,12 +10,53 @@
 //! - Verifies token integrity (cryptographic)
 //! - Enforces pre-declared resource limits (container primitives)
 //! - Executes node operations
+//!
+//! Resilience around [`NodeExecutor`] (retries, timeouts, circuit breaking,
+//! concurrency limits) is composed with a [`MiddlewareStack`]. Unchanged
+//! nodes can be skipped with a [`CheckpointStore`].
+//!
+//! Node token signatures are batch-verified when a run starts and remembered
+//! in a [`VerifiedTokenCache`], leaving a lookup per node.
+//!
+//! Nodes frozen in a shared [`FreezeRegistry`] are skipped with a recorded
+//! [`SkipReason`](crate::types::v2::SkipReason); their dependents are
+//! blocked or run per [`FrozenPolicy`].
+//!
+//! A graph runs once. Running moves it from
+//! [`Active`](GraphLifecycle::Active) to
+//! [`Executing`](GraphLifecycle::Executing) and then
+//! [`Completed`](GraphLifecycle::Completed); completed and aborted graphs
+//! are refused. [`Executor::abort`] stops a run cooperatively before its
+//! next node.
 
+mod cache;
+mod freeze;
+mod middleware;
+
+pub use cache::{CacheKey, Checkpoint, CheckpointStore};
+pub use freeze::{
+    FreezeError, FreezeRegistry, FrozenNode, FrozenPolicy, HeldNodes, UNFREEZE_AUTONOMY,
+};
+pub use middleware::{
+    CircuitBreaker, ConcurrencyLimit, MiddlewareStack, NodeMiddleware, Retry, Timeout,
+    CIRCUIT_BREAKER_COOLDOWN_MS, CIRCUIT_BREAKER_THRESHOLD, MAX_CONCURRENT_NODES, MAX_RETRIES,
+    NODE_TIMEOUT_MS, RETRY_BACKOFF_MS,
+};
+
+use crate::clock::{system_clock, SharedClock};
 use crate::error::ExecutionError;
-use crate::token_integrity::TokenIntegrity;
-use crate::types::v2::{ExecutionSummary, ValidatedGraph};
-use crate::types::NodeId;
+use crate::quota::QuotaManager;
+use crate::tenant::TenantRegistry;
+use crate::token_integrity::{TokenIntegrity, VerifiedTokenCache};
+use crate::types::v2::{
+    EdgeKind, ExecutionSummary, GraphLifecycle, NodeRecord, NodeRunStatus, ValidatedGraph,
+};
+use crate::types::{AgentId, GraphId, NodeId, TenantId};
 use ed25519_dalek::VerifyingKey;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
 use std::sync::Arc;
 use std::time::Instant;
 
@@ -24,11 +65,13 @@ use std::time::Instant;
 /// Implement this trait to define how individual nodes are executed.
 #[async_trait::async_trait]
 pub trait NodeExecutor: Send + Sync {
-    /// Execute a single node
+    /// Execute a single node with the payloads delivered along its
+    /// data edges
     async fn execute_node(
         &self,
         node_id: NodeId,
         token: &crate::autonomy::CapabilityToken,
+        inputs: &NodeInputs,
     ) -> Result<NodeExecutionResult, ExecutionError>;
 }
 
@@ -39,6 +82,95 @@ pub struct NodeExecutionResult {
     pub success: bool,
     pub execution_time_ms: u64,
     pub resource_consumed: crate::types::ResourceCaps,
+    /// Payload passed to nodes downstream of a data edge
+    pub output: Option<NodePayload>,
+}
+
+/// Value a node produces for its data dependents
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodePayload {
+    /// Declared type, matched against data edges
+    pub payload_type: String,
+    pub value: serde_json::Value,
+}
+
+impl NodePayload {
+    /// Payload of `payload_type` holding `value`
+    pub fn new(payload_type: impl Into<String>, value: serde_json::Value) -> Self {
+        Self {
+            payload_type: payload_type.into(),
+            value,
+        }
+    }
+}
+
+/// Payloads delivered to a node, in edge order
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NodeInputs {
+    entries: Vec<(NodeId, NodePayload)>,
+}
+
+impl NodeInputs {
+    /// First payload of `payload_type`
+    pub fn get(&self, payload_type: &str) -> Option<&NodePayload> {
+        self.entries
+            .iter()
+            .map(|(_, payload)| payload)
+            .find(|payload| payload.payload_type == payload_type)
+    }
+    
+    /// Payload delivered by `source`
+    pub fn from_node(&self, source: NodeId) -> Option<&NodePayload> {
+        self.entries
+            .iter()
+            .find(|(node_id, _)| *node_id == source)
+            .map(|(_, payload)| payload)
+    }
+    
+    /// All payloads with their producing node
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId, &NodePayload)> {
+        self.entries.iter().map(|(node_id, payload)| (*node_id, payload))
+    }
+    
+    /// Number of delivered payloads
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    
+    /// Check if nothing was delivered
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Failed graph run, with the summary of everything up to the failure
+///
+/// The summary records completed nodes, the failing node with its error,
+/// and every node that was skipped as a result.
+#[derive(Debug, Clone)]
+pub struct ExecutionFailure {
+    pub error: ExecutionError,
+    pub summary: ExecutionSummary,
+}
+
+impl fmt::Display for ExecutionFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} after {} of {} nodes",
+            self.error,
+            self.summary.nodes_executed,
+            self.summary.nodes.len()
+        )
+    }
+}
+
+impl std::error::Error for ExecutionFailure {}
+
+impl From<ExecutionFailure> for ExecutionError {
+    fn from(failure: ExecutionFailure) -> Self {
+        failure.error
+    }
 }
 
 /// Graph executor
@@ -48,6 +180,14 @@ pub struct NodeExecutionResult {
 pub struct Executor {
     verifying_key: VerifyingKey,
     node_executor: Arc<dyn NodeExecutor>,
+    quota: Option<(Arc<QuotaManager>, Option<AgentId>)>,
+    max_retries: u32,
+    clock: SharedClock,
+    cache: Option<Arc<CheckpointStore>>,
+    verified: Arc<VerifiedTokenCache>,
+    freezes: Option<Arc<FreezeRegistry>>,
+    tenancy: Option<(Arc<TenantRegistry>, TenantId)>,
+    running: Mutex<HashMap<GraphId, ValidatedGraph>>,
 }
 
 impl Executor {
@@ -56,6 +196,14 @@ impl Executor {
         Self {
             verifying_key,
             node_executor: Arc::new(DefaultNodeExecutor),
+            quota: None,
+            max_retries: 0,
+            clock: system_clock(),
+            cache: None,
+            verified: Arc::default(),
+            freezes: None,
+            tenancy: None,
+            running: Mutex::default(),
         }
     }
     
@@ -67,75 +215,315 @@ impl Executor {
         Self {
             verifying_key,
             node_executor,
+            quota: None,
+            max_retries: 0,
+            clock: system_clock(),
+            cache: None,
+            verified: Arc::default(),
+            freezes: None,
+            tenancy: None,
+            running: Mutex::default(),
         }
     }
     
+    /// Check token expiry against `clock`
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+    
+    /// Wrap the node executor in a middleware stack
+    pub fn with_middleware(mut self, stack: &MiddlewareStack) -> Self {
+        self.node_executor = stack.wrap(self.node_executor);
+        self
+    }
+    
+    /// Enforce execution rate and daily consumption quotas at runtime
+    pub fn with_quota(mut self, quota: Arc<QuotaManager>, agent: Option<AgentId>) -> Self {
+        self.quota = Some((quota, agent));
+        self
+    }
+    
+    /// Retry a node up to `max_retries` times when its executor errors
+    ///
+    /// Only [transient](ExecutionError::is_transient) errors are retried.
+    pub fn with_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+    
+    /// Reuse runs recorded in `store` and record new successful ones
+    ///
+    /// A node is skipped when its spec, inputs and directive hash match a
+    /// recorded run; its token is still verified. Executors built without
+    /// a store always execute every node.
+    pub fn with_cache(mut self, store: Arc<CheckpointStore>) -> Self {
+        self.cache = Some(store);
+        self
+    }
+    
+    /// Share verified token signatures through `cache`
+    ///
+    /// Executors otherwise keep a cache of their own across runs.
+    pub fn with_token_cache(mut self, cache: Arc<VerifiedTokenCache>) -> Self {
+        self.verified = cache;
+        self
+    }
+    
+    /// Skip nodes frozen in `registry`
+    pub fn with_freezes(mut self, registry: Arc<FreezeRegistry>) -> Self {
+        self.freezes = Some(registry);
+        self
+    }
+    
+    /// Only run graphs `tenant` owns in `registry`
+    ///
+    /// Graphs of other tenants, and unclaimed graphs, are refused with
+    /// `ExecutionError::Tenant` before anything runs.
+    pub fn with_tenant(mut self, registry: Arc<TenantRegistry>, tenant: TenantId) -> Self {
+        self.tenancy = Some((registry, tenant));
+        self
+    }
+    
+    /// Freeze registry consulted before each node runs
+    pub fn freezes(&self) -> Option<&Arc<FreezeRegistry>> {
+        self.freezes.as_ref()
+    }
+    
+    /// Abort a graph this executor is running
+    ///
+    /// The run stops before its next node and fails with
+    /// `ExecutionError::Aborted`. Returns `false` if the graph is not
+    /// running here.
+    pub fn abort(&self, graph_id: GraphId) -> bool {
+        self.running
+            .lock()
+            .get(&graph_id)
+            .is_some_and(|graph| graph.abort().is_ok())
+    }
+    
+    /// IDs of the graphs this executor is running
+    pub fn running_graphs(&self) -> Vec<GraphId> {
+        self.running.lock().keys().copied().collect()
+    }
+    
+    /// Move `graph` to `Executing` and track it until the run ends
+    ///
+    /// # Errors
+    /// - `ExecutionError::Tenant` if the graph is not the executor's tenant's
+    /// - `ExecutionError::GraphNotRunnable` unless the graph is active
+    pub(crate) fn begin(&self, graph: &ValidatedGraph) -> Result<ActiveRun<'_>, ExecutionError> {
+        self.authorize(graph)?;
+        graph
+            .transition(GraphLifecycle::Executing)
+            .map_err(|error| ExecutionError::GraphNotRunnable(error.from))?;
+        self.running.lock().insert(graph.graph_id(), graph.clone());
+        Ok(ActiveRun {
+            running: &self.running,
+            graph: graph.clone(),
+        })
+    }
+    
     /// Run a validated graph
     ///
     /// # Arguments
     /// * `graph` - A `ValidatedGraph` produced by `GraphBuilder::validate()`
     ///
     /// # Errors
-    /// Returns `ExecutionError` if:
+    /// Returns an `ExecutionFailure` carrying the partial summary if:
     /// - Token integrity verification fails
     /// - Token has expired
     /// - Token is not bound to the correct node
     /// - Resource enforcement triggers
+    /// - A configured quota is exhausted
+    /// - The graph is not active (`GraphNotRunnable`); nothing runs
+    /// - The graph belongs to another tenant (`Tenant`); nothing runs
+    /// - The graph is aborted mid-run (`Aborted`); remaining nodes are
+    ///   skipped
+    ///
+    /// A run that finishes, successfully or not, completes the graph.
     pub async fn run(
         &self,
         graph: ValidatedGraph,
-    ) -> Result<ExecutionSummary, ExecutionError> {
+    ) -> Result<ExecutionSummary, ExecutionFailure> {
         let start_time = Instant::now();
-        let mut nodes_executed = 0;
-        let mut total_cpu_ms = 0u64;
-        let mut total_memory = 0u64;
-        let mut total_tokens = 0u64;
-        let mut total_iterations = 0u64;
-        
-        // Verify graph validation token
-        self.verify_graph_token(&graph)?;
         
         // Get topological order for execution
-        let node_order: Vec<NodeId> = graph.node_ids().collect();
+        let node_order = graph.execution_order();
+        let mut records = Vec::with_capacity(node_order.len());
+        let mut statuses = HashMap::with_capacity(node_order.len());
+        let mut outputs: HashMap<NodeId, NodePayload> = HashMap::new();
+        let mut held = HeldNodes::default();
         
-        for node_id in node_order {
-            // Get the node's capability token
-            let token = graph.get_node_token(node_id)
-                .ok_or(ExecutionError::TokenIntegrityFailure)?;
-            
-            // Verify token integrity (cryptographic + temporal + binding)
-            TokenIntegrity::verify_full(
-                token,
-                &self.verifying_key,
-                node_id,
-                Some("execute"),
-            )?;
-            
-            // Execute the node
-            let result = self.node_executor.execute_node(node_id, token).await?;
+        // Claim the graph, then verify its validation token
+        let run = self.begin(&graph);
+        let mut failure = match &run {
+            Ok(_) => self.verify_graph_token(&graph).err(),
+            Err(error) => Some(error.clone()),
+        };
+        
+        // Batch-verify node signatures up front. A failed batch caches
+        // nothing, so per-node checks still pinpoint the bad token.
+        if failure.is_none() {
+            let tokens = graph.nodes().map(|(_, _, token)| token);
+            let _ = TokenIntegrity::verify_batch(tokens, &self.verifying_key, &self.verified);
+        }
+        
+        for &node_id in &node_order {
+            if failure.is_none() && run.as_ref().is_ok_and(ActiveRun::is_aborted) {
+                failure = Some(ExecutionError::Aborted);
+            }
+            if failure.is_some() {
+                records.push(NodeRecord::skipped(node_id));
+                continue;
+            }
             
-            if result.success {
-                nodes_executed += 1;
-                total_cpu_ms += result.execution_time_ms;
-                total_memory += result.resource_consumed.memory_bytes;
-                total_tokens += result.resource_consumed.token_limit;
-                total_iterations += result.resource_consumed.iteration_cap;
+            let started = Instant::now();
+            let mut record = NodeRecord::skipped(node_id);
+            record.skip_reason = self
+                .freezes
+                .as_ref()
+                .and_then(|registry| registry.check(&graph, node_id, &mut held));
+            if record.skip_reason.is_some() {
+                statuses.insert(node_id, NodeRunStatus::Skipped);
+                records.push(record);
+                continue;
             }
+            let inputs = match gather_inputs(&graph, node_id, &statuses, &outputs) {
+                Ok(Some(inputs)) => inputs,
+                // A guard or an upstream skip holds this node back
+                Ok(None) => {
+                    statuses.insert(node_id, NodeRunStatus::Skipped);
+                    records.push(record);
+                    continue;
+                }
+                Err(error) => {
+                    failure = Some(error.clone());
+                    record.status = NodeRunStatus::Failed;
+                    record.error = Some(error);
+                    records.push(record);
+                    continue;
+                }
+            };
+            match self.run_node(&graph, node_id, &inputs, &mut record).await {
+                Ok(result) => {
+                    if let Some(output) = result.output {
+                        outputs.insert(node_id, output);
+                    }
+                    record.status = if result.success {
+                        NodeRunStatus::Succeeded
+                    } else {
+                        NodeRunStatus::Failed
+                    };
+                    record.resource_consumed = result.resource_consumed;
+                    
+                    // Charge measured consumption; stop once the daily budget is spent
+                    if let Some((quota, agent)) = self.quota.as_ref().filter(|_| !record.cached) {
+                        if let Err(error) =
+                            quota.charge(agent.as_ref(), graph.graph_id(), &result.resource_consumed)
+                        {
+                            record.error = Some(error.into());
+                        }
+                    }
+                }
+                Err(error) => {
+                    record.status = NodeRunStatus::Failed;
+                    record.error = Some(error);
+                }
+            }
+            record.duration_ms = started.elapsed().as_millis() as u64;
+            failure = record.error.clone();
+            statuses.insert(node_id, record.status);
+            records.push(record);
         }
         
-        let execution_time_ms = start_time.elapsed().as_millis() as u64;
+        if let Ok(run) = run {
+            run.complete();
+        }
+        let summary = summarize(graph.graph_id(), records, start_time);
+        match failure {
+            Some(error) => Err(ExecutionFailure { error, summary }),
+            None => Ok(summary),
+        }
+    }
+    
+    /// Verify and execute one node, counting retries
+    ///
+    /// A cache hit returns the recorded output with nothing consumed and
+    /// marks `record` as cached.
+    async fn run_node(
+        &self,
+        graph: &ValidatedGraph,
+        node_id: NodeId,
+        inputs: &NodeInputs,
+        record: &mut NodeRecord,
+    ) -> Result<NodeExecutionResult, ExecutionError> {
+        // Get the node's capability token
+        let token = graph.get_node_token(node_id)
+            .ok_or(ExecutionError::TokenIntegrityFailure)?;
         
-        Ok(ExecutionSummary {
-            graph_id: graph.graph_id(),
-            nodes_executed,
-            execution_time_ms,
-            resource_consumed: crate::types::ResourceCaps {
-                cpu_time_ms: total_cpu_ms,
-                memory_bytes: total_memory,
-                token_limit: total_tokens,
-                iteration_cap: total_iterations,
-            },
-        })
+        // Verify token integrity (cryptographic + temporal + binding)
+        TokenIntegrity::verify_full_cached(
+            token,
+            &self.verifying_key,
+            node_id,
+            Some("execute"),
+            self.clock.as_ref(),
+            &self.verified,
+        )?;
+        
+        let cached = self.cache.as_ref().zip(graph.get_node_spec(node_id)).map(|(store, spec)| {
+            (store, CacheKey::compute(spec, inputs, token.directive_hash))
+        });
+        let checkpoint = cached.as_ref().and_then(|(store, key)| store.get(key));
+        #[cfg(feature = "metrics")]
+        if cached.is_some() {
+            crate::metrics::global().checkpoint_cache.record(checkpoint.is_some());
+        }
+        if let Some(checkpoint) = checkpoint {
+            record.cached = true;
+            return Ok(NodeExecutionResult {
+                node_id,
+                success: true,
+                execution_time_ms: 0,
+                resource_consumed: crate::types::ResourceCaps {
+                    cpu_time_ms: 0,
+                    memory_bytes: 0,
+                    token_limit: 0,
+                    iteration_cap: 0,
+                },
+                output: checkpoint.output,
+            });
+        }
+        
+        loop {
+            // Admit against the execution rate limit
+            if let Some((quota, agent)) = &self.quota {
+                quota.admit_execution(agent.as_ref(), graph.graph_id())?;
+            }
+            
+            #[cfg(feature = "metrics")]
+            let started = Instant::now();
+            let outcome = self.node_executor.execute_node(node_id, token, inputs).await;
+            #[cfg(feature = "metrics")]
+            {
+                let metrics = crate::metrics::global();
+                metrics.nodes_executed.inc();
+                metrics.node_duration.observe(started.elapsed());
+            }
+            match outcome {
+                Err(error) if error.is_transient() && record.retries < self.max_retries => {
+                    record.retries += 1;
+                }
+                Ok(result) => {
+                    if let Some((store, key)) = cached.filter(|_| result.success) {
+                        store.record(key, Checkpoint::from(&result));
+                    }
+                    return Ok(result);
+                }
+                outcome => return outcome,
+            }
+        }
     }
     
     /// Verify the graph's validation token
@@ -143,7 +531,7 @@ impl Executor {
         let token = graph.validation_token();
         
         // Check expiration
-        if token.is_expired() {
+        if token.is_expired_at(self.clock.now()) {
             return Err(ExecutionError::TokenExpired);
         }
         
@@ -157,23 +545,137 @@ impl Executor {
         Ok(())
     }
     
+    /// Check that the executor's tenant, if any, owns `graph`
+    fn authorize(&self, graph: &ValidatedGraph) -> Result<(), ExecutionError> {
+        match &self.tenancy {
+            Some((registry, tenant)) => Ok(registry.authorize_graph(tenant, graph.graph_id())?),
+            None => Ok(()),
+        }
+    }
+    
     /// Execute a single node (for testing/debugging)
+    ///
+    /// The node receives no inputs. Frozen nodes are refused with
+    /// `ExecutionError::NodeFrozen`, nodes of closed graphs with
+    /// `ExecutionError::GraphNotRunnable`, graphs of other tenants with
+    /// `ExecutionError::Tenant`.
     pub async fn execute_single(
         &self,
         graph: &ValidatedGraph,
         node_id: NodeId,
     ) -> Result<NodeExecutionResult, ExecutionError> {
+        self.authorize(graph)?;
+        let lifecycle = graph.lifecycle();
+        if !lifecycle.is_runnable() {
+            return Err(ExecutionError::GraphNotRunnable(lifecycle));
+        }
+        if self.freezes.as_ref().is_some_and(|registry| registry.is_frozen(node_id)) {
+            return Err(ExecutionError::NodeFrozen);
+        }
         let token = graph.get_node_token(node_id)
             .ok_or(ExecutionError::TokenIntegrityFailure)?;
         
-        TokenIntegrity::verify_full(
+        TokenIntegrity::verify_full_cached(
             token,
             &self.verifying_key,
             node_id,
             Some("execute"),
+            self.clock.as_ref(),
+            &self.verified,
         )?;
         
-        self.node_executor.execute_node(node_id, token).await
+        self.node_executor.execute_node(node_id, token, &NodeInputs::default()).await
+    }
+}
+
+/// A graph an [`Executor`] is running
+///
+/// Dropping the run before it [completes](Self::complete), e.g. when the
+/// future driving it is cancelled, aborts the graph.
+pub(crate) struct ActiveRun<'a> {
+    running: &'a Mutex<HashMap<GraphId, ValidatedGraph>>,
+    graph: ValidatedGraph,
+}
+
+impl ActiveRun<'_> {
+    pub(crate) fn is_aborted(&self) -> bool {
+        self.graph.lifecycle() == GraphLifecycle::Aborted
+    }
+    
+    /// Mark the graph completed unless it was aborted
+    pub(crate) fn complete(self) {
+        let _ = self.graph.transition(GraphLifecycle::Completed);
+    }
+}
+
+impl Drop for ActiveRun<'_> {
+    fn drop(&mut self) {
+        self.running.lock().remove(&self.graph.graph_id());
+        // No-op once completed or aborted
+        let _ = self.graph.abort();
+    }
+}
+
+/// Collect the payloads `node_id` receives along its data edges
+///
+/// Returns `None` when the node must be skipped: a control-flow guard does
+/// not hold, or a data source did not succeed.
+fn gather_inputs(
+    graph: &ValidatedGraph,
+    node_id: NodeId,
+    statuses: &HashMap<NodeId, NodeRunStatus>,
+    outputs: &HashMap<NodeId, NodePayload>,
+) -> Result<Option<NodeInputs>, ExecutionError> {
+    let mut inputs = NodeInputs::default();
+    for (source, kind) in graph.incoming(node_id) {
+        let status = statuses.get(&source).copied().unwrap_or(NodeRunStatus::Skipped);
+        match kind {
+            EdgeKind::OrderingOnly => {}
+            EdgeKind::ControlFlow { guard } => {
+                if !guard.admits(status) {
+                    return Ok(None);
+                }
+            }
+            EdgeKind::DataDependency { payload_type } => {
+                if status != NodeRunStatus::Succeeded {
+                    return Ok(None);
+                }
+                let payload = outputs
+                    .get(&source)
+                    .filter(|payload| payload.payload_type == *payload_type)
+                    .ok_or(ExecutionError::MissingInput)?;
+                inputs.entries.push((source, payload.clone()));
+            }
+        }
+    }
+    Ok(Some(inputs))
+}
+
+/// Aggregate per-node records into a summary
+fn summarize(graph_id: GraphId, nodes: Vec<NodeRecord>, start_time: Instant) -> ExecutionSummary {
+    let mut resource_consumed = crate::types::ResourceCaps {
+        cpu_time_ms: 0,
+        memory_bytes: 0,
+        token_limit: 0,
+        iteration_cap: 0,
+    };
+    let succeeded: Vec<_> = nodes
+        .iter()
+        .filter(|record| record.status == NodeRunStatus::Succeeded)
+        .collect();
+    for record in &succeeded {
+        resource_consumed.cpu_time_ms += record.resource_consumed.cpu_time_ms;
+        resource_consumed.memory_bytes += record.resource_consumed.memory_bytes;
+        resource_consumed.token_limit += record.resource_consumed.token_limit;
+        resource_consumed.iteration_cap += record.resource_consumed.iteration_cap;
+    }
+    
+    ExecutionSummary {
+        graph_id,
+        nodes_executed: succeeded.len(),
+        execution_time_ms: start_time.elapsed().as_millis() as u64,
+        resource_consumed,
+        nodes,
     }
 }
 
@@ -186,6 +688,7 @@ impl NodeExecutor for DefaultNodeExecutor {
         &self,
         node_id: NodeId,
         _token: &crate::autonomy::CapabilityToken,
+        _inputs: &NodeInputs,
     ) -> Result<NodeExecutionResult, ExecutionError> {
         // Default implementation - just return success
         // Real implementation would execute the node's work
@@ -199,6 +702,7 @@ impl NodeExecutor for DefaultNodeExecutor {
                 token_limit: 0,
                 iteration_cap: 0,
             },
+            output: None,
         })
     }
 }
@@ -286,6 +790,7 @@ mod tests {
                 iteration_cap: 100,
             },
             expansion_type: None,
+            inputs: Vec::new(),
         }
     }
 
@@ -294,6 +799,51 @@ mod tests {
         SigningKey::generate(&mut csprng)
     }
 
+    #[tokio::test]
+    async fn test_foreign_tenant_graph_is_refused() {
+        use crate::quota::QuotaLimits;
+        use crate::tenant::TenantRegistry;
+
+        let signing_key = create_signing_key();
+        let registry = Arc::new(TenantRegistry::new());
+        let (acme, globex) = (TenantId::new("acme"), TenantId::new("globex"));
+        for (tenant, seed) in [(&acme, 1), (&globex, 2)] {
+            let signer = Arc::new(SigningKey::from_bytes(&[seed; 32]));
+            registry.register(tenant.clone(), signer, QuotaLimits::unlimited()).unwrap();
+        }
+
+        let mut builder = GraphBuilder::new(GraphType::ProductionDAG)
+            .with_tenant(Arc::clone(&registry), acme.clone());
+        let node = builder.add_node(create_test_spec());
+        let graph = builder.validate(&signing_key).unwrap();
+        assert_eq!(registry.owner(graph.graph_id()), Some(acme.clone()));
+
+        let foreign = Executor::new(signing_key.verifying_key())
+            .with_tenant(Arc::clone(&registry), globex);
+        let failure = foreign.run(graph.clone()).await.unwrap_err();
+        assert!(matches!(
+            failure.error,
+            ExecutionError::Tenant(crate::error::TenantError::CrossTenant { .. })
+        ));
+        assert!(matches!(
+            foreign.execute_single(&graph, node).await,
+            Err(ExecutionError::Tenant(_))
+        ));
+
+        // Refusal leaves the graph untouched for its owner
+        let owner = Executor::new(signing_key.verifying_key())
+            .with_tenant(Arc::clone(&registry), acme.clone());
+        assert!(owner.run(graph).await.is_ok());
+
+        let mut unclaimed = GraphBuilder::new(GraphType::ProductionDAG);
+        unclaimed.add_node(create_test_spec());
+        let unclaimed = unclaimed.validate(&signing_key).unwrap();
+        assert!(matches!(
+            owner.run(unclaimed).await.unwrap_err().error,
+            ExecutionError::Tenant(crate::error::TenantError::Unclaimed)
+        ));
+    }
+
     #[tokio::test]
     async fn test_executor_runs_validated_graph() {
         let signing_key = create_signing_key();
@@ -315,8 +865,345 @@ mod tests {
         
         let summary = result.unwrap();
         assert_eq!(summary.nodes_executed, 2);
+        assert_eq!(summary.nodes.len(), 2);
+        assert_eq!(summary.node(n1).unwrap().status, NodeRunStatus::Succeeded);
+    }
+
+    /// Fails `node` on its first `flaky` attempts, or on every attempt if `None`
+    struct FailingExecutor {
+        node: NodeId,
+        flaky: Option<u32>,
+        attempts: std::sync::atomic::AtomicU32,
+    }
+
+    #[async_trait::async_trait]
+    impl NodeExecutor for FailingExecutor {
+        async fn execute_node(
+            &self,
+            node_id: NodeId,
+            token: &crate::autonomy::CapabilityToken,
+            inputs: &NodeInputs,
+        ) -> Result<NodeExecutionResult, ExecutionError> {
+            if node_id == self.node {
+                let attempt = self.attempts.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                if self.flaky.map_or(true, |flaky| attempt < flaky) {
+                    return Err(ExecutionError::ResourceEnforcementTriggered);
+                }
+            }
+            DefaultNodeExecutor.execute_node(node_id, token, inputs).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_executor_returns_partial_summary_on_failure() {
+        let signing_key = create_signing_key();
+        let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
+        let n1 = builder.add_node(create_test_spec());
+        let n2 = builder.add_node(create_test_spec());
+        let n3 = builder.add_node(create_test_spec());
+        builder.add_edge(n1, n2).unwrap();
+        builder.add_edge(n2, n3).unwrap();
+        let validated = builder.validate(&signing_key).unwrap();
+
+        let failing = Arc::new(FailingExecutor {
+            node: n2,
+            flaky: None,
+            attempts: Default::default(),
+        });
+        let executor = Executor::with_executor(signing_key.verifying_key(), failing)
+            .with_retries(2);
+        let failure = executor.run(validated).await.unwrap_err();
+
+        assert_eq!(failure.error, ExecutionError::ResourceEnforcementTriggered);
+        let summary = &failure.summary;
+        assert_eq!(summary.nodes.len(), 3);
+        let failed: Vec<_> = summary.failed().collect();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].node_id, n2);
+        assert_eq!(failed[0].retries, 2);
+        assert_eq!(failed[0].error, Some(ExecutionError::ResourceEnforcementTriggered));
+
+        // Nodes run before the failure succeeded; the rest were skipped
+        let position = |id| summary.nodes.iter().position(|r| r.node_id == id).unwrap();
+        for other in [n1, n3] {
+            let expected = if position(other) < position(n2) {
+                NodeRunStatus::Succeeded
+            } else {
+                NodeRunStatus::Skipped
+            };
+            assert_eq!(summary.node(other).unwrap().status, expected);
+        }
+        assert_eq!(summary.nodes_executed + summary.skipped().count(), 2);
+    }
+
+    /// Emits a `Patch` from `producer`, fails `tester`, and records inputs
+    struct PipelineExecutor {
+        producer: NodeId,
+        tester: NodeId,
+        seen: parking_lot::Mutex<HashMap<NodeId, NodeInputs>>,
+    }
+
+    #[async_trait::async_trait]
+    impl NodeExecutor for PipelineExecutor {
+        async fn execute_node(
+            &self,
+            node_id: NodeId,
+            token: &crate::autonomy::CapabilityToken,
+            inputs: &NodeInputs,
+        ) -> Result<NodeExecutionResult, ExecutionError> {
+            self.seen.lock().insert(node_id, inputs.clone());
+            let mut result = DefaultNodeExecutor.execute_node(node_id, token, inputs).await?;
+            if node_id == self.producer {
+                result.output = Some(NodePayload::new("Patch", serde_json::json!({ "lines": 3 })));
+            }
+            result.success = node_id != self.tester;
+            Ok(result)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_executor_passes_data_and_follows_guards() {
+        use crate::types::v2::{EdgeGuard, EdgeKind};
+        
+        let signing_key = create_signing_key();
+        let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
+        let producer = builder.add_node(create_test_spec());
+        let consumer = builder.add_node(create_test_spec().with_input("Patch"));
+        let tester = builder.add_node(create_test_spec());
+        let fixer = builder.add_node(create_test_spec());
+        let deployer = builder.add_node(create_test_spec());
+        builder.add_typed_edge(producer, consumer, EdgeKind::data("Patch")).unwrap();
+        builder.add_edge(consumer, tester).unwrap();
+        builder.add_typed_edge(tester, fixer, EdgeKind::control(EdgeGuard::OnFailure)).unwrap();
+        builder.add_typed_edge(tester, deployer, EdgeKind::control(EdgeGuard::OnSuccess)).unwrap();
+        let validated = builder.validate(&signing_key).unwrap();
+
+        let pipeline = Arc::new(PipelineExecutor {
+            producer,
+            tester,
+            seen: Default::default(),
+        });
+        let executor = Executor::with_executor(signing_key.verifying_key(), pipeline.clone());
+        let summary = executor.run(validated).await.unwrap();
+
+        let seen = pipeline.seen.lock();
+        let delivered = seen[&consumer].from_node(producer).unwrap();
+        assert_eq!(delivered.value["lines"], 3);
+        assert_eq!(seen[&consumer].get("Patch"), Some(delivered));
+        assert!(seen[&tester].is_empty());
+        assert_eq!(summary.node(tester).unwrap().status, NodeRunStatus::Failed);
+        assert_eq!(summary.node(fixer).unwrap().status, NodeRunStatus::Succeeded);
+        assert_eq!(summary.node(deployer).unwrap().status, NodeRunStatus::Skipped);
+        assert!(!seen.contains_key(&deployer));
+        assert_eq!(summary.nodes_executed, 3);
+    }
+
+    #[tokio::test]
+    async fn test_executor_fails_when_data_source_emits_nothing() {
+        use crate::types::v2::EdgeKind;
+        
+        let signing_key = create_signing_key();
+        let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
+        let producer = builder.add_node(create_test_spec());
+        let consumer = builder.add_node(create_test_spec().with_input("Patch"));
+        builder.add_typed_edge(producer, consumer, EdgeKind::data("Patch")).unwrap();
+        let validated = builder.validate(&signing_key).unwrap();
+
+        let failure = Executor::new(signing_key.verifying_key())
+            .run(validated)
+            .await
+            .unwrap_err();
+        assert_eq!(failure.error, ExecutionError::MissingInput);
+        assert_eq!(failure.summary.node(producer).unwrap().status, NodeRunStatus::Succeeded);
+        assert_eq!(failure.summary.node(consumer).unwrap().status, NodeRunStatus::Failed);
+    }
+
+    /// Counts executions; every node emits the same `Patch`
+    #[derive(Default)]
+    struct CountingExecutor {
+        runs: std::sync::atomic::AtomicU32,
+    }
+
+    #[async_trait::async_trait]
+    impl NodeExecutor for CountingExecutor {
+        async fn execute_node(
+            &self,
+            node_id: NodeId,
+            token: &crate::autonomy::CapabilityToken,
+            inputs: &NodeInputs,
+        ) -> Result<NodeExecutionResult, ExecutionError> {
+            self.runs.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let mut result = DefaultNodeExecutor.execute_node(node_id, token, inputs).await?;
+            result.output = Some(NodePayload::new("Patch", serde_json::json!("diff")));
+            Ok(result)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_executor_reuses_unchanged_nodes_from_cache() {
+        use crate::types::v2::{EdgeKind, NodeSpecV2};
+        
+        let signing_key = create_signing_key();
+        let build = |producer_spec: NodeSpecV2| {
+            let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
+            let producer = builder.add_node(producer_spec);
+            let consumer = builder.add_node(create_test_spec().with_input("Patch"));
+            builder.add_typed_edge(producer, consumer, EdgeKind::data("Patch")).unwrap();
+            (builder.validate(&signing_key).unwrap(), producer, consumer)
+        };
+        let counting = Arc::new(CountingExecutor::default());
+        let runs = || counting.runs.load(std::sync::atomic::Ordering::SeqCst);
+        let store = Arc::new(CheckpointStore::new());
+        let executor = Executor::with_executor(signing_key.verifying_key(), counting.clone())
+            .with_cache(store.clone());
+
+        let (graph, _, _) = build(create_test_spec());
+        let summary = executor.run(graph).await.unwrap();
+        assert_eq!((runs(), store.len(), summary.cached().count()), (2, 2, 0));
+
+        // A freshly built, identical graph runs nothing
+        let (graph, _, consumer) = build(create_test_spec());
+        let summary = executor.run(graph).await.unwrap();
+        assert_eq!((runs(), summary.cached().count()), (2, 2));
+        assert_eq!(summary.nodes_executed, 2);
+        assert_eq!(summary.node(consumer).unwrap().resource_consumed.cpu_time_ms, 0);
+
+        // A changed producer re-runs; its consumer sees identical input
+        let mut changed = create_test_spec();
+        changed.resource_bounds.cpu_time_ms += 1;
+        let (graph, producer, consumer) = build(changed);
+        let summary = executor.run(graph).await.unwrap();
+        assert_eq!(runs(), 3);
+        assert!(!summary.node(producer).unwrap().cached);
+        assert!(summary.node(consumer).unwrap().cached);
+
+        // Without a store every node executes
+        let uncached = Executor::with_executor(signing_key.verifying_key(), counting.clone());
+        let (graph, _, _) = build(create_test_spec());
+        uncached.run(graph).await.unwrap();
+        assert_eq!(runs(), 5);
     }
 
+    #[tokio::test]
+    async fn test_executor_retries_flaky_nodes() {
+        let signing_key = create_signing_key();
+        let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
+        let n1 = builder.add_node(create_test_spec());
+        let validated = builder.validate(&signing_key).unwrap();
+
+        let flaky = Arc::new(FailingExecutor {
+            node: n1,
+            flaky: Some(1),
+            attempts: Default::default(),
+        });
+        let executor = Executor::with_executor(signing_key.verifying_key(), flaky)
+            .with_retries(1);
+        let summary = executor.run(validated).await.unwrap();
+
+        let record = summary.node(n1).unwrap();
+        assert_eq!(record.status, NodeRunStatus::Succeeded);
+        assert_eq!(record.retries, 1);
+        assert!(record.error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_executor_checks_expiry_against_clock() {
+        use crate::clock::ManualClock;
+        
+        let signing_key = create_signing_key();
+        let clock = Arc::new(ManualClock::new(1_000));
+        let mut builder = GraphBuilder::new(GraphType::ProductionDAG).with_clock(clock.clone());
+        let n1 = builder.add_node(create_test_spec());
+        let validated = builder.validate(&signing_key).unwrap();
+        assert_eq!(validated.validation_token().timestamp, 1_000);
+        assert_eq!(validated.get_node_token(n1).unwrap().expires_at, 4_600);
+        
+        let executor = Executor::new(signing_key.verifying_key()).with_clock(clock.clone());
+        clock.advance(3_601);
+        let failure = executor.run(validated).await.unwrap_err();
+        assert_eq!(failure.error, ExecutionError::TokenExpired);
+        assert_eq!(failure.summary.skipped().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_executor_enforces_execution_quota() {
+        use crate::quota::{QuotaLimits, QuotaManager, QuotaSubject};
+        
+        let signing_key = create_signing_key();
+        let quota = Arc::new(QuotaManager::new());
+        let agent = AgentId::new("coder");
+        quota.set_agent_limits(
+            agent.clone(),
+            QuotaLimits::unlimited().with_executions_per_minute(1),
+        );
+        
+        let mut builder = GraphBuilder::new(GraphType::ProductionDAG)
+            .with_quota(quota.clone(), Some(agent.clone()));
+        builder.add_node(create_test_spec());
+        builder.add_node(create_test_spec());
+        let validated = builder.validate(&signing_key).unwrap();
+        
+        let executor = Executor::new(signing_key.verifying_key())
+            .with_quota(quota.clone(), Some(agent.clone()));
+        let result = executor.run(validated).await;
+        
+        let failure = result.unwrap_err();
+        assert_eq!(failure.error, ExecutionError::QuotaExceeded);
+        assert_eq!(failure.summary.nodes_executed, 1);
+        assert_eq!(failure.summary.skipped().count(), 0);
+        let usage = quota.usage(&QuotaSubject::Agent(agent));
+        assert_eq!(usage.tokens_last_minute, 2);
+        assert_eq!(usage.executions_last_minute, 1);
+    }
+    
+    #[tokio::test]
+    async fn test_executor_skips_frozen_nodes_per_policy() {
+        use crate::types::v2::SkipReason;
+        
+        let signing_key = create_signing_key();
+        for policy in [FrozenPolicy::Block, FrozenPolicy::Continue] {
+            let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
+            let n1 = builder.add_node(create_test_spec());
+            let n2 = builder.add_node(create_test_spec());
+            let n3 = builder.add_node(create_test_spec());
+            builder.add_edge(n1, n2).unwrap();
+            builder.add_edge(n2, n3).unwrap();
+            let validated = builder.validate(&signing_key).unwrap();
+            
+            let registry = Arc::new(
+                FreezeRegistry::new(signing_key.verifying_key()).with_policy(policy),
+            );
+            registry.freeze(n2, "under review").unwrap();
+            let executor = Executor::new(signing_key.verifying_key())
+                .with_freezes(Arc::clone(&registry));
+            
+            assert_eq!(
+                executor.execute_single(&validated, n2).await.unwrap_err(),
+                ExecutionError::NodeFrozen
+            );
+            
+            let summary = executor.run(validated).await.unwrap();
+            assert_eq!(summary.node(n1).unwrap().status, NodeRunStatus::Succeeded);
+            let frozen = summary.node(n2).unwrap();
+            assert_eq!(frozen.status, NodeRunStatus::Skipped);
+            assert_eq!(
+                frozen.skip_reason,
+                Some(SkipReason::Frozen { reason: "under review".to_string() })
+            );
+            let dependent = summary.node(n3).unwrap();
+            match policy {
+                FrozenPolicy::Block => {
+                    assert_eq!(dependent.status, NodeRunStatus::Skipped);
+                    assert_eq!(dependent.skip_reason, Some(SkipReason::Blocked { by: n2 }));
+                }
+                FrozenPolicy::Continue => {
+                    assert_eq!(dependent.status, NodeRunStatus::Succeeded);
+                    assert_eq!(dependent.skip_reason, None);
+                }
+            }
+        }
+    }
+    
     #[test]
     fn test_resource_container_enforces_limits() {
         let caps = ResourceCaps {
@@ -14,8 +14,10 @@
 //! let mut staged = StagedConstruction::new(validated);
 //! let expansion_point = staged.execute_until_expansion().await?;
 //!
-//! // Provide expansion subgraph
-//! let subgraph = SubgraphSpec::<MySchema>::new(nodes, edges);
+//! // Provide expansion subgraph, wired to the expansion node's edges
+//! let subgraph = SubgraphSpec::<MySchema>::new(nodes, edges)
+//!     .with_input(0, EdgeKind::data("Spec"))
+//!     .with_output(1, EdgeKind::data("Patch"));
 //! staged.provide_expansion(subgraph)?;
 //!
 //! // Complete expansion and continue
@@ -26,7 +28,8 @@
 use crate::construction::GraphBuilder;
 use crate::error::{ExecutionError, ValidationError};
 use crate::types::v2::{
-    ExpansionSchema, NodeSpecV2, SubgraphSpec, SystemLimits, TypeIdWrapper, ValidatedGraph,
+    BoundaryPort, EdgeKind, ExpansionSchema, NodeSpecV2, SubgraphSpec, SystemLimits, TypeIdWrapper,
+    ValidatedGraph,
 };
 use crate::types::{GraphId, NodeId};
 use ed25519_dalek::SigningKey;
@@ -127,7 +130,8 @@ impl StagedConstruction {
     
     /// Provide an expansion subgraph for the current expansion point
     ///
-    /// The subgraph is validated against the schema and resource constraints.
+    /// The subgraph is validated against the schema, resource constraints
+    /// and the expansion node's edges (see [`SubgraphSpec::with_input`]).
     pub fn provide_expansion<T: ExpansionSchema>(
         &mut self,
         subgraph: SubgraphSpec<T>,
@@ -167,6 +171,9 @@ impl StagedConstruction {
         // Validate autonomy ceiling propagation
         self.validate_autonomy_propagation(&subgraph, spec.autonomy_ceiling)?;
         
+        // Validate boundary ports against the parent's edges
+        self.validate_boundary(&subgraph, expansion_node)?;
+        
         // Push expansion frame
         let frame = ExpansionFrame {
             expansion_node,
@@ -264,6 +271,53 @@ impl StagedConstruction {
         Ok(())
     }
     
+    /// Validate that boundary ports match the expansion node's edges
+    ///
+    /// Every port must stand for an existing edge, input ports must
+    /// consume the payload they receive, and every payload crossing the
+    /// boundary needs a port.
+    fn validate_boundary<T: ExpansionSchema>(
+        &self,
+        subgraph: &SubgraphSpec<T>,
+        expansion_node: NodeId,
+    ) -> Result<(), ValidationError> {
+        let incoming: Vec<&EdgeKind> =
+            self.graph.incoming(expansion_node).map(|(_, kind)| kind).collect();
+        let outgoing: Vec<&EdgeKind> =
+            self.graph.outgoing(expansion_node).map(|(_, kind)| kind).collect();
+        
+        for port in &subgraph.inputs {
+            let spec = subgraph.nodes.get(port.node)
+                .ok_or(ValidationError::BoundaryMismatch)?;
+            if !incoming.contains(&&port.kind) {
+                return Err(ValidationError::BoundaryMismatch);
+            }
+            if let Some(payload_type) = port.kind.payload_type() {
+                if !spec.consumes(payload_type) {
+                    return Err(ValidationError::UndeclaredInput);
+                }
+            }
+        }
+        for port in &subgraph.outputs {
+            if port.node >= subgraph.nodes.len() || !outgoing.contains(&&port.kind) {
+                return Err(ValidationError::BoundaryMismatch);
+            }
+        }
+        
+        // Payloads the parent delivers or expects must not be dropped
+        let bound = |edges: &[&EdgeKind], ports: &[BoundaryPort]| {
+            edges
+                .iter()
+                .filter(|kind| kind.payload_type().is_some())
+                .all(|&kind| ports.iter().any(|port| port.kind == *kind))
+        };
+        if !bound(&incoming, &subgraph.inputs) || !bound(&outgoing, &subgraph.outputs) {
+            return Err(ValidationError::BoundaryMismatch);
+        }
+        
+        Ok(())
+    }
+    
     /// Get current expansion depth
     pub fn current_depth(&self) -> u32 {
         self.expansion_stack.len() as u32
@@ -340,6 +394,7 @@ mod tests {
                 iteration_cap: 100,
             },
             expansion_type: None,
+            inputs: Vec::new(),
         }
     }
 
@@ -477,4 +532,57 @@ mod tests {
         // Parent ceiling is L3, child wants L5
         assert!(staged.validate_autonomy_propagation(&subgraph, AutonomyLevel::L3).is_err());
     }
+
+    #[test]
+    fn test_boundary_ports_match_parent_edges() {
+        let signing_key = create_signing_key();
+        let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
+        let source = builder.add_node(create_test_spec());
+        let budget = ResourceCaps {
+            cpu_time_ms: 10000,
+            memory_bytes: 100 * 1024 * 1024,
+            token_limit: 10000,
+            iteration_cap: 1000,
+        };
+        let expansion_node = builder.add_expansion_node::<TestSchema>(
+            create_test_spec().with_input("Spec"),
+            budget,
+            2,
+        );
+        let sink = builder.add_node(create_test_spec().with_input("Patch"));
+        builder.add_typed_edge(source, expansion_node, EdgeKind::data("Spec")).unwrap();
+        builder.add_typed_edge(expansion_node, sink, EdgeKind::data("Patch")).unwrap();
+        let validated = builder.validate(&signing_key).unwrap();
+        let mut staged = StagedConstruction::new(validated, signing_key);
+        
+        let subgraph = || {
+            SubgraphSpec::<TestSchema>::new(
+                vec![create_test_spec().with_input("Spec"), create_test_spec()],
+                vec![],
+            )
+        };
+        
+        // The Patch payload the sink expects has no producer
+        let dropped = subgraph().with_input(0, EdgeKind::data("Spec"));
+        assert_eq!(staged.provide_expansion(dropped), Err(ValidationError::BoundaryMismatch));
+        
+        // No edge into the expansion node carries Diff
+        let unknown = subgraph()
+            .with_input(0, EdgeKind::data("Diff"))
+            .with_input(0, EdgeKind::data("Spec"))
+            .with_output(1, EdgeKind::data("Patch"));
+        assert_eq!(staged.provide_expansion(unknown), Err(ValidationError::BoundaryMismatch));
+        
+        // Node 1 does not consume Spec
+        let undeclared = subgraph()
+            .with_input(1, EdgeKind::data("Spec"))
+            .with_output(1, EdgeKind::data("Patch"));
+        assert_eq!(staged.provide_expansion(undeclared), Err(ValidationError::UndeclaredInput));
+        
+        let wired = subgraph()
+            .with_input(0, EdgeKind::data("Spec"))
+            .with_output(1, EdgeKind::data("Patch"));
+        assert!(staged.provide_expansion(wired).is_ok());
+        assert_eq!(staged.current_depth(), 1);
+    }
 }
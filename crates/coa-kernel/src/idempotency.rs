@@ -0,0 +1,342 @@
+//! Idempotency keys for kernel mutations (v2.0)
+//!
+//! Clients of a remote kernel retry calls that time out. Calls made through
+//! [`Idempotent`] carry an [`IdempotencyKey`]; a retry with the same key
+//! returns the original result instead of creating a second graph, node or
+//! token.
+//!
+//! A key is bound to the operation and arguments it first carried: reusing
+//! it for a different request fails with `IdempotencyError::KeyReused`.
+//! Only successful results are remembered, so a failed call can be retried
+//! with its key. Keys expire after a TTL (a day by default).
+
+use crate::api::{AutonomyManager, GraphManager, NodeOperations};
+use crate::autonomy::CapabilityToken;
+use crate::clock::{system_clock, SharedClock};
+use crate::error::{IdempotencyError, KernelError};
+use crate::types::{AutonomyLevel, GraphId, GraphType, NodeId, NodeSpec, ResourceCaps, Timestamp};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// How long a key is remembered by default
+pub const IDEMPOTENCY_TTL_SECS: u64 = 24 * 60 * 60;
+
+/// Client-chosen key identifying one logical request
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct IdempotencyKey(String);
+
+impl IdempotencyKey {
+    pub fn new(key: impl Into<String>) -> Self {
+        Self(key.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for IdempotencyKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Result of a keyed call
+struct Entry {
+    /// Hash of the operation and its arguments
+    fingerprint: [u8; 32],
+    recorded_at: Timestamp,
+    result: Box<dyn Any + Send + Sync>,
+}
+
+/// Results of keyed calls, shared by the [`Idempotent`] wrappers using it
+///
+/// Keyed calls on one store are serialized, so concurrent retries of the
+/// same request run it once.
+pub struct IdempotencyStore {
+    entries: Mutex<HashMap<IdempotencyKey, Entry>>,
+    ttl_secs: u64,
+    clock: SharedClock,
+}
+
+impl fmt::Debug for IdempotencyStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IdempotencyStore")
+            .field("entries", &self.entries.lock().len())
+            .field("ttl_secs", &self.ttl_secs)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for IdempotencyStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IdempotencyStore {
+    /// Store remembering keys for [`IDEMPOTENCY_TTL_SECS`]
+    pub fn new() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            ttl_secs: IDEMPOTENCY_TTL_SECS,
+            clock: system_clock(),
+        }
+    }
+
+    /// Remember keys for `ttl_secs`
+    pub fn with_ttl(mut self, ttl_secs: u64) -> Self {
+        self.ttl_secs = ttl_secs;
+        self
+    }
+
+    /// Age keys against `clock`
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Number of remembered keys, expired ones included
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.lock().is_empty()
+    }
+
+    /// Forget expired keys, returning how many were dropped
+    pub fn purge_expired(&self) -> usize {
+        let now = self.clock.now();
+        let mut entries = self.entries.lock();
+        let before = entries.len();
+        entries.retain(|_, entry| !self.expired(entry, now));
+        before - entries.len()
+    }
+
+    /// Run `call` once per `key`
+    ///
+    /// Returns the remembered result if `key` was used for the same
+    /// `operation` and `request` before and has not expired.
+    ///
+    /// # Errors
+    /// - `IdempotencyError::KeyReused` if `key` was used for a different
+    ///   request
+    /// - Whatever `call` fails with; failures are not remembered
+    pub fn run<V, R, F>(
+        &self,
+        key: &IdempotencyKey,
+        operation: &str,
+        request: &R,
+        call: F,
+    ) -> Result<V, KernelError>
+    where
+        V: Clone + Send + Sync + 'static,
+        R: Serialize + ?Sized,
+        F: FnOnce() -> Result<V, KernelError>,
+    {
+        let fingerprint = fingerprint(operation, request);
+        let now = self.clock.now();
+        let mut entries = self.entries.lock();
+        if let Some(entry) = entries.get(key).filter(|entry| !self.expired(entry, now)) {
+            if entry.fingerprint != fingerprint {
+                return Err(IdempotencyError::KeyReused {
+                    key: key.to_string(),
+                }
+                .into());
+            }
+            if let Some(result) = entry.result.downcast_ref::<V>() {
+                return Ok(result.clone());
+            }
+        }
+
+        let result = call()?;
+        entries.insert(
+            key.clone(),
+            Entry {
+                fingerprint,
+                recorded_at: now,
+                result: Box::new(result.clone()),
+            },
+        );
+        Ok(result)
+    }
+
+    fn expired(&self, entry: &Entry, now: Timestamp) -> bool {
+        now.saturating_sub(entry.recorded_at) >= self.ttl_secs
+    }
+}
+
+fn fingerprint<R: Serialize + ?Sized>(operation: &str, request: &R) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(operation.as_bytes());
+    hasher.update([0u8]);
+    // Kernel request types always serialize
+    hasher.update(serde_json::to_vec(request).unwrap_or_default());
+    hasher.finalize().into()
+}
+
+/// Kernel front end accepting idempotency keys
+///
+/// Wraps a [`GraphManager`], [`NodeOperations`] or [`AutonomyManager`]
+/// implementation and adds keyed variants of its mutations. Unkeyed calls
+/// go through [`inner`](Self::inner).
+#[derive(Debug)]
+pub struct Idempotent<K> {
+    inner: K,
+    store: Arc<IdempotencyStore>,
+}
+
+impl<K> Idempotent<K> {
+    /// Wrap `inner` with a store of its own
+    pub fn new(inner: K) -> Self {
+        Self::with_store(inner, Arc::new(IdempotencyStore::new()))
+    }
+
+    /// Wrap `inner`, remembering results in `store`
+    pub fn with_store(inner: K, store: Arc<IdempotencyStore>) -> Self {
+        Self { inner, store }
+    }
+
+    pub fn inner(&self) -> &K {
+        &self.inner
+    }
+
+    pub fn store(&self) -> &Arc<IdempotencyStore> {
+        &self.store
+    }
+}
+
+impl<K: GraphManager> Idempotent<K> {
+    /// [`GraphManager::create_graph`], once per key
+    pub fn create_graph(
+        &self,
+        key: &IdempotencyKey,
+        graph_type: GraphType,
+    ) -> Result<GraphId, KernelError> {
+        self.store.run(key, "create_graph", &graph_type, || {
+            self.inner.create_graph(graph_type)
+        })
+    }
+}
+
+impl<K: NodeOperations> Idempotent<K> {
+    /// [`NodeOperations::add_node`], once per key
+    pub fn add_node(
+        &self,
+        key: &IdempotencyKey,
+        graph_id: GraphId,
+        spec: NodeSpec,
+    ) -> Result<NodeId, KernelError> {
+        self.store.run(key, "add_node", &(graph_id, &spec), || {
+            self.inner.add_node(graph_id, spec.clone())
+        })
+    }
+}
+
+impl<K: AutonomyManager> Idempotent<K> {
+    /// [`AutonomyManager::issue_token`], once per key
+    pub fn issue_token(
+        &self,
+        key: &IdempotencyKey,
+        node_id: NodeId,
+        level: AutonomyLevel,
+        caps: ResourceCaps,
+    ) -> Result<CapabilityToken, KernelError> {
+        self.store.run(key, "issue_token", &(node_id, level, caps), || {
+            self.inner.issue_token(node_id, level, caps)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::GraphStats;
+    use crate::clock::ManualClock;
+    use crate::error::GraphError;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Creates a fresh graph per call; fails while `failing` is set
+    #[derive(Default)]
+    struct CountingManager {
+        created: AtomicUsize,
+        failing: std::sync::atomic::AtomicBool,
+    }
+
+    impl GraphManager for CountingManager {
+        fn create_graph(&self, _graph_type: GraphType) -> Result<GraphId, KernelError> {
+            if self.failing.load(Ordering::SeqCst) {
+                return Err(GraphError::GraphClosed.into());
+            }
+            self.created.fetch_add(1, Ordering::SeqCst);
+            Ok(GraphId::new())
+        }
+
+        fn close_graph(&self, _graph_id: GraphId) -> Result<(), KernelError> {
+            Ok(())
+        }
+
+        fn graph_stats(&self, _graph_id: GraphId) -> Result<GraphStats, KernelError> {
+            Err(GraphError::GraphNotFound.into())
+        }
+    }
+
+    #[test]
+    fn repeated_keys_return_the_original_result() {
+        let kernel = Idempotent::new(CountingManager::default());
+        let key = IdempotencyKey::new("create-1");
+
+        let first = kernel.create_graph(&key, GraphType::ProductionDAG).unwrap();
+        let retry = kernel.create_graph(&key, GraphType::ProductionDAG).unwrap();
+        assert_eq!(first, retry);
+        assert_eq!(kernel.inner().created.load(Ordering::SeqCst), 1);
+
+        let other = kernel
+            .create_graph(&IdempotencyKey::new("create-2"), GraphType::ProductionDAG)
+            .unwrap();
+        assert_ne!(first, other);
+
+        assert!(matches!(
+            kernel.create_graph(&key, GraphType::SandboxGraph),
+            Err(KernelError::Idempotency(IdempotencyError::KeyReused { .. }))
+        ));
+    }
+
+    #[test]
+    fn failures_are_not_remembered() {
+        let kernel = Idempotent::new(CountingManager::default());
+        let key = IdempotencyKey::new("create");
+        kernel.inner().failing.store(true, Ordering::SeqCst);
+        assert!(kernel.create_graph(&key, GraphType::ProductionDAG).is_err());
+        assert!(kernel.store().is_empty());
+
+        kernel.inner().failing.store(false, Ordering::SeqCst);
+        assert!(kernel.create_graph(&key, GraphType::ProductionDAG).is_ok());
+        assert_eq!(kernel.store().len(), 1);
+    }
+
+    #[test]
+    fn keys_expire_after_the_ttl() {
+        let clock = Arc::new(ManualClock::new(1_000));
+        let store = IdempotencyStore::new().with_ttl(60).with_clock(clock.clone());
+        let kernel = Idempotent::with_store(CountingManager::default(), Arc::new(store));
+        let key = IdempotencyKey::new("create");
+
+        let first = kernel.create_graph(&key, GraphType::ProductionDAG).unwrap();
+        clock.advance(59);
+        assert_eq!(kernel.create_graph(&key, GraphType::ProductionDAG).unwrap(), first);
+        clock.advance(1);
+        assert_ne!(kernel.create_graph(&key, GraphType::ProductionDAG).unwrap(), first);
+        assert_eq!(kernel.inner().created.load(Ordering::SeqCst), 2);
+
+        clock.advance(60);
+        assert_eq!(kernel.store().purge_expired(), 1);
+        assert!(kernel.store().is_empty());
+    }
+}
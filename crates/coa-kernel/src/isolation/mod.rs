@@ -3,36 +3,101 @@
 //! Determines isolation level based on NodeSpec from the construction phase.
 //! All policy decisions have already been validated - this module only
 //! implements the isolation primitives.
+//!
+//! Filesystem access is restricted per node via [`FsScope`] / [`ScopeGuard`].
+//! Subprocesses run under the node's resource caps through the platform
+//! backend; [`Isolation::capabilities`] reports what it guarantees.
+//! Thread-isolated work runs on its own runtime with panics captured (see
+//! [`run_in_thread`]); what it writes through its [`WorkContext`] is
+//! captured too, and scoped runs log it as a [`THREAD_OUTPUT_ACTION`] event.
+
+mod platform;
+mod scope;
+mod thread;
+
+pub use platform::{
+    clear_env, run_subprocess, IsolationBackend, IsolationGuarantee, PlatformCapabilities, SubprocessLimits,
+    MIN_ADDRESS_SPACE,
+};
+pub use scope::{FsAccess, FsScope, ScopeGuard, ScopeViolation, SCOPE_VIOLATION_ACTION};
+pub use thread::{
+    run_in_thread, CancelHandle, PanicReport, ThreadFailure, ThreadOutput, WorkContext, THREAD_OUTPUT_ACTION,
+};
 
 use crate::api::{ApiExecutionError, ApiExecutionErrorKind, ExecutionResult, ExecutionRuntime, ResourceUsage};
 use crate::autonomy::CapabilityToken;
+use crate::logging::Action;
 use crate::types::v2::NodeSpecV2;
-use crate::types::{AutonomyLevel, NodeId, WorkSpec};
-use std::process::{Command, Stdio};
-use std::thread;
+use crate::types::{NodeId, ResourceCaps, WorkSpec};
+use std::path::Path;
+use std::process::Stdio;
+
+/// Name of the threads thread-isolated work runs on
+const ISOLATION_THREAD_NAME: &str = "coa-isolation";
 
 /// Isolation executor (v2.0)
 ///
 /// Determines isolation level from NodeSpec (pre-validated at construction)
 /// rather than from the token at runtime.
-pub struct Isolation;
+#[derive(Debug, Default)]
+pub struct Isolation {
+    cancel: CancelHandle,
+    address_space_limit: bool,
+}
 
 impl Isolation {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Cancel thread-isolated work through `cancel`
+    ///
+    /// A cancellation applies to one run: the handle is reset once the run
+    /// in progress (or, if none is, the next one) ends.
+    #[must_use]
+    pub fn with_cancel(mut self, cancel: CancelHandle) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Handle that cancels the thread-isolated run in progress
+    pub fn cancel_handle(&self) -> &CancelHandle {
+        &self.cancel
+    }
+
+    /// Also cap each subprocess's address space at its node's `memory_bytes`
+    ///
+    /// Off by default, as `memory_bytes` budgets the work rather than the
+    /// process image; the cap is raised to [`MIN_ADDRESS_SPACE`].
+    #[must_use]
+    pub fn with_address_space_limit(mut self) -> Self {
+        self.address_space_limit = true;
+        self
+    }
+
+    /// OS limits for a subprocess running under `caps`
+    fn subprocess_limits(&self, caps: &ResourceCaps) -> SubprocessLimits {
+        let limits = SubprocessLimits::from_caps(caps);
+        if self.address_space_limit {
+            limits.with_address_space(caps.memory_bytes)
+        } else {
+            limits
+        }
+    }
+
+    /// Guarantees subprocess isolation provides on this platform
+    pub fn capabilities() -> PlatformCapabilities {
+        PlatformCapabilities::detect()
     }
     
     /// Determine isolation level from node spec
     ///
     /// In v2.0, this comes from the pre-validated NodeSpec, not the token.
     fn isolation_level_from_spec(spec: &NodeSpecV2) -> IsolationLevel {
-        match spec.autonomy_ceiling {
-            AutonomyLevel::L0 | AutonomyLevel::L1 | AutonomyLevel::L2 => {
-                IsolationLevel::Thread
-            }
-            AutonomyLevel::L3 | AutonomyLevel::L4 | AutonomyLevel::L5 => {
-                IsolationLevel::Subprocess
-            }
+        if spec.autonomy_ceiling.requires_process_isolation() {
+            IsolationLevel::Subprocess
+        } else {
+            IsolationLevel::Thread
         }
     }
     
@@ -47,35 +112,97 @@ impl Isolation {
         work: WorkSpec,
     ) -> Result<String, ApiExecutionError> {
         match Self::isolation_level_from_spec(spec) {
-            IsolationLevel::Thread => self.execute_in_thread(work),
-            IsolationLevel::Subprocess => self.execute_in_subprocess(work),
+            IsolationLevel::Thread => self.execute_in_thread(work, None),
+            IsolationLevel::Subprocess => {
+                self.execute_in_subprocess(work, &self.subprocess_limits(&spec.resource_bounds))
+            }
         }
     }
     
-    fn execute_in_thread(&self, work: WorkSpec) -> Result<String, ApiExecutionError> {
-        let handle = thread::spawn(move || {
-            println!("Executing work in thread: {:?}", work);
-            "Thread execution completed".to_string()
+    /// Execute work confined to a filesystem scope
+    ///
+    /// Paths listed under the `reads` and `writes` keys of the work payload
+    /// are checked against the guard before anything runs; a violation is
+    /// logged as a kernel event and fails the node. Output captured from
+    /// thread-isolated work is logged through the guard. Subprocesses are
+    /// started in the scope root, confined to it where the platform allows.
+    pub fn execute_scoped(
+        &self,
+        spec: &NodeSpecV2,
+        work: WorkSpec,
+        guard: &ScopeGuard,
+    ) -> Result<String, ApiExecutionError> {
+        for (key, access) in [("reads", FsAccess::Read), ("writes", FsAccess::Write)] {
+            let paths = work.payload.get(key).and_then(|v| v.as_array());
+            for path in paths.into_iter().flatten().filter_map(|p| p.as_str()) {
+                guard.check(path, access).map_err(|violation| ApiExecutionError {
+                    node_id: Some(guard.node_id()),
+                    kind: ApiExecutionErrorKind::ScopeViolation,
+                    message: violation.to_string(),
+                })?;
+            }
+        }
+
+        match Self::isolation_level_from_spec(spec) {
+            IsolationLevel::Thread => self.execute_in_thread(work, Some(guard)),
+            IsolationLevel::Subprocess => self.execute_in_subprocess_at(
+                work,
+                &self.subprocess_limits(&spec.resource_bounds),
+                Some(guard.scope().root()),
+            ),
+        }
+    }
+    
+    fn execute_in_thread(
+        &self,
+        work: WorkSpec,
+        guard: Option<&ScopeGuard>,
+    ) -> Result<String, ApiExecutionError> {
+        let output = run_in_thread(ISOLATION_THREAD_NAME, &self.cancel, move |ctx| async move {
+            ctx.println(format_args!("Executing work in thread: {:?}", work));
+            Ok("Thread execution completed".to_string())
         });
-        
-        match handle.join() {
-            Ok(result) => Ok(result),
-            Err(_) => Err(ApiExecutionError {
-                node_id: None,
-                kind: ApiExecutionErrorKind::IsolationFailure,
-                message: "Thread panicked".to_string(),
-            }),
+        self.cancel.reset();
+
+        if let Some(guard) = guard.filter(|_| output.has_output()) {
+            // Logging is best-effort; the work's outcome stands either way
+            let _ = guard.record(Action::ThreadOutput, output.output_json());
         }
+
+        output.result.map_err(|failure| ApiExecutionError {
+            node_id: guard.map(ScopeGuard::node_id),
+            kind: match failure {
+                ThreadFailure::Failed(_) => ApiExecutionErrorKind::Internal,
+                ThreadFailure::Panicked(_) => ApiExecutionErrorKind::IsolationFailure,
+                ThreadFailure::Cancelled => ApiExecutionErrorKind::Cancelled,
+            },
+            message: failure.to_string(),
+        })
+    }
+    
+    fn execute_in_subprocess(
+        &self,
+        work: WorkSpec,
+        limits: &SubprocessLimits,
+    ) -> Result<String, ApiExecutionError> {
+        self.execute_in_subprocess_at(work, limits, None)
     }
     
-    fn execute_in_subprocess(&self, work: WorkSpec) -> Result<String, ApiExecutionError> {
-        let mut cmd = Command::new("echo");
-        cmd.arg(format!("Executing work in subprocess: {:?}", work));
-        cmd.env_clear();
+    fn execute_in_subprocess_at(
+        &self,
+        work: WorkSpec,
+        limits: &SubprocessLimits,
+        cwd: Option<&Path>,
+    ) -> Result<String, ApiExecutionError> {
+        let mut cmd = platform::echo_command(&format!("Executing work in subprocess: {:?}", work));
+        platform::clear_env(&mut cmd);
+        if let Some(cwd) = cwd {
+            cmd.current_dir(cwd);
+        }
         cmd.stdout(Stdio::piped());
         cmd.stdin(Stdio::piped());
         
-        match cmd.output() {
+        match platform::run(cmd, limits, cwd) {
             Ok(output) => {
                 if output.status.success() {
                     Ok(String::from_utf8_lossy(&output.stdout).to_string())
@@ -114,33 +241,24 @@ impl ExecutionRuntime for Isolation {
         work: WorkSpec,
     ) -> Result<ExecutionResult, ApiExecutionError> {
         // Legacy: read from token (v1.x behavior)
-        match token.autonomy_level {
-            AutonomyLevel::L0 | AutonomyLevel::L1 | AutonomyLevel::L2 => {
-                let result = self.execute_in_thread(work)?;
-                Ok(ExecutionResult {
-                    success: true,
-                    node_id,
-                    output: Some(result),
-                    resource_usage: ResourceUsage::default(),
-                })
-            }
-            AutonomyLevel::L3 | AutonomyLevel::L4 | AutonomyLevel::L5 => {
-                let result = self.execute_in_subprocess(work)?;
-                Ok(ExecutionResult {
-                    success: true,
-                    node_id,
-                    output: Some(result),
-                    resource_usage: ResourceUsage::default(),
-                })
-            }
-        }
+        let result = if token.autonomy_level.requires_process_isolation() {
+            self.execute_in_subprocess(work, &self.subprocess_limits(&token.caps))?
+        } else {
+            self.execute_in_thread(work, None)?
+        };
+        Ok(ExecutionResult {
+            success: true,
+            node_id,
+            output: Some(result),
+            resource_usage: ResourceUsage::default(),
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::DirectiveSet;
+    use crate::types::{AutonomyLevel, DirectiveSet};
     use std::collections::BTreeMap;
 
     fn create_spec(autonomy: AutonomyLevel) -> NodeSpecV2 {
@@ -156,6 +274,7 @@ mod tests {
                 iteration_cap: 100,
             },
             expansion_type: None,
+            inputs: Vec::new(),
         }
     }
 
@@ -192,7 +311,70 @@ mod tests {
             payload: serde_json::json!("test"),
         };
         
-        let result = isolation.execute_in_thread(work);
+        let result = isolation.execute_in_thread(work.clone(), None);
         assert!(result.is_ok());
+
+        isolation.cancel_handle().cancel();
+        let err = isolation.execute_in_thread(work.clone(), None).unwrap_err();
+        assert_eq!(err.kind, ApiExecutionErrorKind::Cancelled);
+
+        // The cancellation was spent on that run
+        assert!(!isolation.cancel_handle().is_cancelled());
+        assert!(isolation.execute_in_thread(work, None).is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_subprocess_runs_under_standard_caps() {
+        let work = WorkSpec {
+            kind: "test".to_string(),
+            payload: serde_json::json!("test"),
+        };
+        let spec = create_spec(AutonomyLevel::L3);
+        for isolation in [Isolation::new(), Isolation::new().with_address_space_limit()] {
+            let output = isolation.execute_with_spec(&spec, work.clone()).unwrap();
+            assert!(output.starts_with("Executing work in subprocess"));
+        }
+    }
+
+    #[test]
+    fn test_execute_scoped_rejects_out_of_scope_write() {
+        use crate::logging::EventLog;
+        use std::sync::Arc;
+
+        let signing_key = ed25519_dalek::SigningKey::generate(&mut rand::rngs::OsRng);
+        let spec = create_spec(AutonomyLevel::L1);
+        let token = CapabilityToken::sign(
+            NodeId::new(),
+            spec.autonomy_ceiling,
+            spec.resource_bounds,
+            crate::types::DirectiveProfileHash([0u8; 32]),
+            &signing_key,
+            0,
+            "execute",
+        );
+        let log = Arc::new(EventLog::default());
+        let guard = ScopeGuard::new(
+            FsScope::new("/work").allow_write("src/auth"),
+            token,
+            log.clone(),
+        );
+
+        let allowed = WorkSpec {
+            kind: "edit".to_string(),
+            payload: serde_json::json!({ "writes": ["src/auth/login.rs"] }),
+        };
+        assert!(Isolation::new().execute_scoped(&spec, allowed, &guard).is_ok());
+        let output = log.events().pop().unwrap();
+        assert_eq!(output.action, Action::ThreadOutput);
+        assert!(output.result.contains("Executing work in thread"));
+
+        let denied = WorkSpec {
+            kind: "edit".to_string(),
+            payload: serde_json::json!({ "reads": ["src/auth.rs"], "writes": ["Cargo.toml"] }),
+        };
+        let err = Isolation::new().execute_scoped(&spec, denied, &guard).unwrap_err();
+        assert_eq!(err.kind, ApiExecutionErrorKind::ScopeViolation);
+        assert_eq!(log.events().len(), 2);
     }
 }
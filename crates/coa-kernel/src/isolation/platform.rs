@@ -0,0 +1,427 @@
+//! Platform backends for subprocess isolation
+//!
+//! Subprocesses always start with a cleared environment; what else the
+//! kernel can enforce depends on the OS:
+//!
+//! | Platform | Backend | Guarantees beyond a clean environment |
+//! |----------|---------|---------------------------------------|
+//! | Linux and other Unix | `ulimit` in `/bin/sh` before exec | address space (opt-in), CPU time |
+//! | macOS | `ulimit` + `sandbox-exec` | CPU time, writes confined to the scope root |
+//! | Windows | Job Object | whole tree killed |
+//!
+//! macOS does not enforce address-space limits, and the safe Job Object
+//! API limits neither CPU time nor commit size, so neither claims those.
+//! Callers that depend on a guarantee should check [`PlatformCapabilities`]
+//! rather than the OS.
+
+use crate::types::ResourceCaps;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+use std::time::Duration;
+
+/// Mechanism enforcing subprocess limits on this platform
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum IsolationBackend {
+    /// POSIX resource limits set by a `/bin/sh` launcher before exec
+    Rlimit,
+    /// Resource limits plus a `sandbox-exec` filesystem profile (macOS)
+    SandboxExec,
+    /// Windows Job Object
+    JobObject,
+    /// Environment isolation only
+    Unconfined,
+}
+
+/// Property of subprocess isolation a platform may guarantee
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum IsolationGuarantee {
+    /// The child inherits none of the kernel's environment variables
+    CleanEnvironment,
+    /// Address space can be capped ([`SubprocessLimits::with_address_space`])
+    MemoryLimit,
+    /// CPU time is capped at the node's `cpu_time_ms`
+    CpuTimeLimit,
+    /// Processes the child starts die with it
+    KillTree,
+    /// Writes outside the scope root are denied by the OS
+    FilesystemConfinement,
+}
+
+impl fmt::Display for IsolationGuarantee {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// What subprocess isolation provides on the current platform
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlatformCapabilities {
+    pub backend: IsolationBackend,
+    /// Guarantees provided, in order
+    pub guarantees: Vec<IsolationGuarantee>,
+}
+
+impl PlatformCapabilities {
+    /// Detect the backend and guarantees of the running platform
+    pub fn detect() -> Self {
+        use IsolationGuarantee::*;
+        let (backend, mut guarantees) = if cfg!(windows) {
+            (
+                IsolationBackend::JobObject,
+                vec![CleanEnvironment, KillTree],
+            )
+        } else if cfg!(target_os = "macos") {
+            if Path::new(SANDBOX_EXEC).exists() {
+                (IsolationBackend::SandboxExec, vec![CleanEnvironment, CpuTimeLimit, FilesystemConfinement])
+            } else {
+                (IsolationBackend::Rlimit, vec![CleanEnvironment, CpuTimeLimit])
+            }
+        } else if cfg!(unix) {
+            (IsolationBackend::Rlimit, vec![CleanEnvironment, MemoryLimit, CpuTimeLimit])
+        } else {
+            (IsolationBackend::Unconfined, vec![CleanEnvironment])
+        };
+        guarantees.sort();
+        Self { backend, guarantees }
+    }
+
+    /// Check if the platform provides `guarantee`
+    pub fn provides(&self, guarantee: IsolationGuarantee) -> bool {
+        self.guarantees.contains(&guarantee)
+    }
+
+    /// Guarantees of `required` the platform does not provide
+    pub fn missing(&self, required: &[IsolationGuarantee]) -> Vec<IsolationGuarantee> {
+        required.iter().copied().filter(|g| !self.provides(*g)).collect()
+    }
+}
+
+/// Smallest address-space limit applied; below this programs fail to load
+pub const MIN_ADDRESS_SPACE: u64 = 256 << 20;
+
+/// Limits applied to one subprocess; `None` leaves a resource unlimited
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SubprocessLimits {
+    /// Address-space cap, only set through [`with_address_space`](Self::with_address_space)
+    pub address_space_bytes: Option<u64>,
+    pub cpu_time_ms: Option<u64>,
+}
+
+impl SubprocessLimits {
+    /// Limits from a node's resource caps (`u64::MAX` meaning unlimited)
+    ///
+    /// `memory_bytes` budgets the work, not the process image, so it is not
+    /// applied to the OS; opt in with [`with_address_space`](Self::with_address_space).
+    pub fn from_caps(caps: &ResourceCaps) -> Self {
+        Self {
+            address_space_bytes: None,
+            cpu_time_ms: (caps.cpu_time_ms != u64::MAX).then_some(caps.cpu_time_ms),
+        }
+    }
+
+    /// With the address space capped at `bytes`, raised to [`MIN_ADDRESS_SPACE`]
+    #[must_use]
+    pub fn with_address_space(mut self, bytes: u64) -> Self {
+        self.address_space_bytes = Some(bytes.max(MIN_ADDRESS_SPACE));
+        self
+    }
+}
+
+/// Path of the macOS sandbox launcher
+const SANDBOX_EXEC: &str = "/usr/bin/sandbox-exec";
+
+/// Shell applying resource limits before exec'ing the child
+#[cfg(unix)]
+const LIMIT_SHELL: &str = "/bin/sh";
+
+/// Variables Windows programs cannot start without
+#[cfg(windows)]
+const WINDOWS_REQUIRED_ENV: [&str; 2] = ["SystemRoot", "windir"];
+
+/// Command printing `message`, for the placeholder subprocess work
+pub(crate) fn echo_command(message: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", "echo", message]);
+        cmd
+    } else {
+        let mut cmd = Command::new("echo");
+        cmd.arg(message);
+        cmd
+    }
+}
+
+/// Clear the environment of `cmd`, keeping what the OS itself requires
+pub fn clear_env(cmd: &mut Command) {
+    cmd.env_clear();
+    #[cfg(windows)]
+    for name in WINDOWS_REQUIRED_ENV {
+        if let Some(value) = std::env::var_os(name) {
+            cmd.env(name, value);
+        }
+    }
+}
+
+/// Run `cmd` to completion under `limits`, confined to `root` where the
+/// platform supports it
+pub(crate) fn run(cmd: Command, limits: &SubprocessLimits, root: Option<&Path>) -> io::Result<Output> {
+    let cmd = prepare(cmd, limits, root);
+    #[cfg(windows)]
+    {
+        job_object::run(cmd)
+    }
+    #[cfg(not(windows))]
+    {
+        let mut cmd = cmd;
+        cmd.output()
+    }
+}
+
+/// Run `cmd` like the kernel's own subprocesses, killing it after `timeout`
+///
+/// `limits` apply and writes are confined to `root` as far as the platform
+/// allows (see [`PlatformCapabilities`]); on Windows the whole process tree
+/// is killed with it. The environment is left as given; start from
+/// [`clear_env`] to keep the kernel's out. Stdin is closed.
+///
+/// # Errors
+/// `io::ErrorKind::TimedOut` once `timeout` elapses, or why the command
+/// could not be started
+pub async fn run_subprocess(
+    cmd: Command,
+    limits: &SubprocessLimits,
+    root: Option<&Path>,
+    timeout: Duration,
+) -> io::Result<Output> {
+    let mut cmd = tokio::process::Command::from(prepare(cmd, limits, root));
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    let child = cmd.spawn()?;
+    #[cfg(windows)]
+    let _job = job_object::contain(&child)?;
+    match tokio::time::timeout(timeout, child.wait_with_output()).await {
+        Ok(output) => output,
+        Err(_) => Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            format!("subprocess did not finish within {timeout:?}"),
+        )),
+    }
+}
+
+/// Wrap `cmd` so it starts under `limits`, confined to `root` where the
+/// platform supports it
+fn prepare(cmd: Command, limits: &SubprocessLimits, root: Option<&Path>) -> Command {
+    #[cfg(target_os = "macos")]
+    let cmd = match root {
+        Some(root) if Path::new(SANDBOX_EXEC).exists() => sandboxed(&cmd, root),
+        _ => cmd,
+    };
+    #[cfg(not(target_os = "macos"))]
+    let _ = root;
+
+    #[cfg(unix)]
+    {
+        limited(cmd, limits)
+    }
+    #[cfg(not(unix))]
+    {
+        // The safe Job Object API cannot enforce them (see `job_object`)
+        let _ = limits;
+        cmd
+    }
+}
+
+/// Wrap `cmd` in `sandbox-exec` with a profile denying writes outside `root`
+#[cfg(target_os = "macos")]
+fn sandboxed(cmd: &Command, root: &Path) -> Command {
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let quoted = root.display().to_string().replace('\\', "\\\\").replace('"', "\\\"");
+    let profile = format!(
+        "(version 1)(allow default)(deny file-write*)\
+         (allow file-write* (subpath \"{quoted}\") (literal \"/dev/null\"))"
+    );
+    relaunch(cmd, SANDBOX_EXEC, ["-p".to_string(), profile])
+}
+
+/// Run `cmd` through a shell setting `limits` with `ulimit` first
+///
+/// The shell `exec`s the child, so the limits apply to it and not to the
+/// kernel. Left unwrapped when there is nothing to limit.
+#[cfg(unix)]
+fn limited(cmd: Command, limits: &SubprocessLimits) -> Command {
+    let mut script = String::new();
+    #[cfg(not(target_os = "macos"))]
+    if let Some(bytes) = limits.address_space_bytes {
+        script += &format!("ulimit -v {} && ", bytes.div_ceil(1024));
+    }
+    if let Some(ms) = limits.cpu_time_ms {
+        script += &format!("ulimit -t {} && ", ms.div_ceil(1000).max(1));
+    }
+    if script.is_empty() {
+        return cmd;
+    }
+    script += "exec \"$0\" \"$@\"";
+    relaunch(&cmd, LIMIT_SHELL, ["-c".to_string(), script])
+}
+
+/// Command running `launcher args...` followed by `cmd`'s program and
+/// arguments, with `cmd`'s environment and working directory
+#[cfg(unix)]
+fn relaunch(cmd: &Command, launcher: &str, args: impl IntoIterator<Item = String>) -> Command {
+    let mut wrapped = Command::new(launcher);
+    wrapped.args(args).arg(cmd.get_program()).args(cmd.get_args());
+    wrapped.env_clear();
+    for (name, value) in cmd.get_envs() {
+        match value {
+            Some(value) => wrapped.env(name, value),
+            None => wrapped.env_remove(name),
+        };
+    }
+    if let Some(dir) = cmd.get_current_dir() {
+        wrapped.current_dir(dir);
+    }
+    wrapped
+}
+
+/// Job Objects killing the whole process tree
+///
+/// The safe job API cannot limit CPU time or commit size, so subprocess
+/// limits are not applied on Windows; [`PlatformCapabilities`] reports them
+/// as missing.
+#[cfg(windows)]
+mod job_object {
+    use std::io;
+    use std::os::windows::io::AsRawHandle;
+    use std::process::{Command, Output, Stdio};
+    use win32job::{ExtendedLimitInfo, Job};
+
+    /// Job killing every process in it once dropped
+    fn kill_on_close() -> io::Result<Job> {
+        let mut info = ExtendedLimitInfo::new();
+        info.limit_kill_on_job_close();
+        Ok(Job::create_with_limit_info(&info)?)
+    }
+
+    /// Run `cmd` in a job that kills the whole tree once it exits
+    pub(super) fn run(mut cmd: Command) -> io::Result<Output> {
+        let job = kill_on_close()?;
+        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+        let mut child = cmd.spawn()?;
+        if let Err(err) = job.assign_process(child.as_raw_handle() as isize) {
+            let _ = child.kill();
+            return Err(err.into());
+        }
+        let output = child.wait_with_output();
+        drop(job);
+        output
+    }
+
+    /// Put `child` in a job killing its tree when the returned job drops
+    pub(super) fn contain(child: &tokio::process::Child) -> io::Result<Job> {
+        let job = kill_on_close()?;
+        if let Some(handle) = child.raw_handle() {
+            job.assign_process(handle as isize)?;
+        }
+        Ok(job)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capabilities_match_the_platform() {
+        let capabilities = PlatformCapabilities::detect();
+        assert!(capabilities.provides(IsolationGuarantee::CleanEnvironment));
+        let expected = if cfg!(windows) {
+            IsolationBackend::JobObject
+        } else if cfg!(target_os = "macos") {
+            capabilities.backend
+        } else {
+            IsolationBackend::Rlimit
+        };
+        assert_eq!(capabilities.backend, expected);
+        assert_eq!(
+            capabilities.missing(&[IsolationGuarantee::CleanEnvironment, IsolationGuarantee::KillTree]),
+            if cfg!(windows) { vec![] } else { vec![IsolationGuarantee::KillTree] }
+        );
+
+        let caps = ResourceCaps {
+            cpu_time_ms: 1500,
+            memory_bytes: 1 << 20,
+            token_limit: 1,
+            iteration_cap: 1,
+        };
+        let limits = SubprocessLimits::from_caps(&caps);
+        assert_eq!(limits.address_space_bytes, None);
+        assert_eq!(limits.cpu_time_ms, Some(1500));
+        assert_eq!(
+            limits.with_address_space(caps.memory_bytes).address_space_bytes,
+            Some(MIN_ADDRESS_SPACE)
+        );
+        let unbounded = ResourceCaps {
+            cpu_time_ms: u64::MAX,
+            ..caps
+        };
+        assert_eq!(SubprocessLimits::from_caps(&unbounded), SubprocessLimits::default());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn standard_caps_run_and_cpu_time_is_enforced() {
+        // The 1 MiB spec used across the kernel, with and without the opt-in
+        // address-space cap, and all-zero caps
+        let standard = ResourceCaps {
+            cpu_time_ms: 1000,
+            memory_bytes: 1 << 20,
+            token_limit: 1000,
+            iteration_cap: 100,
+        };
+        let zero = ResourceCaps {
+            cpu_time_ms: 0,
+            memory_bytes: 0,
+            token_limit: 0,
+            iteration_cap: 0,
+        };
+        for limits in [
+            SubprocessLimits::from_caps(&standard),
+            SubprocessLimits::from_caps(&standard).with_address_space(standard.memory_bytes),
+            SubprocessLimits::from_caps(&zero).with_address_space(zero.memory_bytes),
+        ] {
+            let mut cmd = echo_command("hello");
+            clear_env(&mut cmd);
+            let output = run(cmd, &limits, None).unwrap();
+            assert!(output.status.success(), "{limits:?}");
+            assert_eq!(String::from_utf8_lossy(&output.stdout), "hello\n");
+        }
+
+        // A busy loop is stopped once it uses its second of CPU time
+        let mut spin = Command::new(LIMIT_SHELL);
+        spin.args(["-c", "while :; do :; done"]);
+        let output = run(spin, &SubprocessLimits::from_caps(&standard), None).unwrap();
+        assert!(!output.status.success());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn async_runs_are_killed_at_the_timeout() {
+        let limits = SubprocessLimits::default();
+        let mut cmd = echo_command("hello");
+        clear_env(&mut cmd);
+        let output = run_subprocess(cmd, &limits, None, Duration::from_secs(10)).await.unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "hello\n");
+
+        let mut hang = Command::new(LIMIT_SHELL);
+        hang.args(["-c", "sleep 30"]);
+        let started = std::time::Instant::now();
+        let err = run_subprocess(hang, &limits, None, Duration::from_millis(100)).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+}
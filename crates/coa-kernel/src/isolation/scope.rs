@@ -0,0 +1,330 @@
+//! Filesystem scoping (least privilege)
+//!
+//! An [`FsScope`] lists the paths a node may read and write beneath a
+//! workspace root. Scopes are checked lexically: `.` and `..` are resolved
+//! without touching the disk, and any path that escapes the root is denied.
+//!
+//! A grant covers its own subtree and a file named after its last component,
+//! so granting `src/auth` allows both `src/auth/mod.rs` and `src/auth.rs`.
+//!
+//! [`ScopeGuard`] binds a scope to a node's capability token and event log:
+//! every denied access is appended to the log before the error is returned.
+
+use crate::autonomy::CapabilityToken;
+use crate::api::EventLogger;
+use crate::error::{ExecutionError, LogError};
+use crate::logging::{Action, Event, EventLog};
+use crate::types::{CorrelationId, EventId, NodeId};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+/// Event action recorded for denied filesystem access
+pub const SCOPE_VIOLATION_ACTION: &str = "fs_scope_violation";
+
+/// Kind of filesystem access
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FsAccess {
+    Read,
+    Write,
+}
+
+impl fmt::Display for FsAccess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FsAccess::Read => write!(f, "read"),
+            FsAccess::Write => write!(f, "write"),
+        }
+    }
+}
+
+/// Filesystem paths a node is allowed to touch
+///
+/// Write grants imply read access. An empty scope denies everything.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FsScope {
+    root: PathBuf,
+    read: Vec<PathBuf>,
+    write: Vec<PathBuf>,
+}
+
+impl FsScope {
+    /// Create an empty scope beneath `root`
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: normalize(&root.into()),
+            read: Vec::new(),
+            write: Vec::new(),
+        }
+    }
+
+    /// Grant read access to a path (relative to root)
+    pub fn allow_read(mut self, path: impl AsRef<Path>) -> Self {
+        let grant = self.relative(path.as_ref());
+        if let Some(grant) = grant {
+            self.read.push(grant);
+        }
+        self
+    }
+
+    /// Grant read and write access to a path (relative to root)
+    pub fn allow_write(mut self, path: impl AsRef<Path>) -> Self {
+        let grant = self.relative(path.as_ref());
+        if let Some(grant) = grant {
+            self.write.push(grant);
+        }
+        self
+    }
+
+    /// Workspace root all grants are relative to
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Paths granted read-only access
+    pub fn read_grants(&self) -> &[PathBuf] {
+        &self.read
+    }
+
+    /// Paths granted write access
+    pub fn write_grants(&self) -> &[PathBuf] {
+        &self.write
+    }
+
+    /// Check an access and return the normalized absolute path
+    ///
+    /// Relative paths are resolved against the root.
+    pub fn check(&self, path: impl AsRef<Path>, access: FsAccess) -> Result<PathBuf, ScopeViolation> {
+        let path = path.as_ref();
+        let violation = || ScopeViolation {
+            path: path.to_path_buf(),
+            access,
+        };
+
+        let relative = self.relative(path).ok_or_else(violation)?;
+        let allowed = match access {
+            FsAccess::Read => self.read.iter().chain(&self.write).any(|g| covers(g, &relative)),
+            FsAccess::Write => self.write.iter().any(|g| covers(g, &relative)),
+        };
+
+        if allowed {
+            Ok(self.root.join(relative))
+        } else {
+            Err(violation())
+        }
+    }
+
+    /// Whether an access would be allowed
+    pub fn permits(&self, path: impl AsRef<Path>, access: FsAccess) -> bool {
+        self.check(path, access).is_ok()
+    }
+
+    /// Path relative to root, or `None` if it escapes the root
+    fn relative(&self, path: &Path) -> Option<PathBuf> {
+        let absolute = normalize(&self.root.join(path));
+        absolute.strip_prefix(&self.root).ok().map(Path::to_path_buf)
+    }
+}
+
+/// Denied filesystem access
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScopeViolation {
+    pub path: PathBuf,
+    pub access: FsAccess,
+}
+
+impl fmt::Display for ScopeViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} access to {} is outside node scope", self.access, self.path.display())
+    }
+}
+
+impl std::error::Error for ScopeViolation {}
+
+impl From<ScopeViolation> for ExecutionError {
+    fn from(_: ScopeViolation) -> Self {
+        ExecutionError::ScopeViolation
+    }
+}
+
+/// Scope enforcement bound to a node's token and the kernel event log
+#[derive(Debug, Clone)]
+pub struct ScopeGuard {
+    scope: FsScope,
+    token: CapabilityToken,
+    log: Arc<EventLog>,
+    correlation: Option<CorrelationId>,
+}
+
+impl ScopeGuard {
+    /// Create a guard for the node the token is bound to
+    pub fn new(scope: FsScope, token: CapabilityToken, log: Arc<EventLog>) -> Self {
+        Self {
+            scope,
+            token,
+            log,
+            correlation: None,
+        }
+    }
+
+    /// Stamp recorded events with `id`
+    pub fn with_correlation(mut self, id: CorrelationId) -> Self {
+        self.correlation = Some(id);
+        self
+    }
+
+    /// Correlation recorded events are stamped with
+    pub fn correlation(&self) -> Option<&CorrelationId> {
+        self.correlation.as_ref()
+    }
+
+    /// Scope being enforced
+    pub fn scope(&self) -> &FsScope {
+        &self.scope
+    }
+
+    /// Node the guard is bound to
+    pub fn node_id(&self) -> NodeId {
+        self.token.node_id
+    }
+
+    /// Token the guard is bound to
+    pub fn token(&self) -> &CapabilityToken {
+        &self.token
+    }
+
+    /// Record an event attributed to the guarded node
+    pub fn record(&self, action: impl Into<Action>, result: String) -> Result<EventId, LogError> {
+        let action = action.into();
+        tracing::debug!(
+            node_id = %self.token.node_id.0,
+            correlation_id = self.correlation.as_ref().map(tracing::field::display),
+            action = action.as_str(),
+            "recording kernel event"
+        );
+        self.log.log_event(Event {
+            event_id: EventId::new(),
+            timestamp: self.log.now(),
+            node_id: self.token.node_id,
+            autonomy_level: self.token.autonomy_level,
+            directive_hash: self.token.directive_hash,
+            action,
+            result,
+            schema_version: None,
+            correlation_id: self.correlation.clone(),
+            prev_hash: [0u8; 32],
+            hash: [0u8; 32],
+            hash_algorithm: None,
+            tenant_id: None,
+        })
+    }
+
+    /// Check an access, recording a kernel event if it is denied
+    pub fn check(&self, path: impl AsRef<Path>, access: FsAccess) -> Result<PathBuf, ScopeViolation> {
+        self.scope.check(path, access).map_err(|violation| {
+            // Logging is best-effort; the access is denied either way
+            let _ = self.record(SCOPE_VIOLATION_ACTION, violation.to_string());
+            violation
+        })
+    }
+}
+
+/// Whether a grant covers a root-relative path
+fn covers(grant: &Path, path: &Path) -> bool {
+    if path.starts_with(grant) {
+        return true;
+    }
+    // `src/auth` also covers the file `src/auth.rs`
+    match (grant.parent(), grant.file_name(), path.parent(), path.file_stem()) {
+        (Some(gp), Some(name), Some(pp), Some(stem)) => gp == pp && name == stem,
+        _ => false,
+    }
+}
+
+/// Resolve `.` and `..` lexically
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{AutonomyLevel, DirectiveProfileHash, ResourceCaps};
+
+    fn scope() -> FsScope {
+        FsScope::new("/work")
+            .allow_write("src/auth")
+            .allow_read("docs")
+    }
+
+    #[test]
+    fn test_write_grant_covers_subtree_and_file() {
+        let scope = scope();
+        assert!(scope.permits("src/auth/login.rs", FsAccess::Write));
+        assert!(scope.permits("/work/src/auth.rs", FsAccess::Write));
+        assert!(scope.permits("src/auth/login.rs", FsAccess::Read));
+        assert!(!scope.permits("src/authz.rs", FsAccess::Write));
+        assert!(!scope.permits("src/main.rs", FsAccess::Read));
+    }
+
+    #[test]
+    fn test_read_grant_is_read_only() {
+        let scope = scope();
+        assert!(scope.permits("docs/README.md", FsAccess::Read));
+        assert!(!scope.permits("docs/README.md", FsAccess::Write));
+    }
+
+    #[test]
+    fn test_escape_is_denied() {
+        let scope = scope();
+        assert!(!scope.permits("src/auth/../../etc/passwd", FsAccess::Read));
+        assert!(!scope.permits("/etc/passwd", FsAccess::Read));
+        assert_eq!(
+            scope.check("src/auth/./x/../login.rs", FsAccess::Write).unwrap(),
+            PathBuf::from("/work/src/auth/login.rs")
+        );
+    }
+
+    #[test]
+    fn test_guard_logs_violation() {
+        let log = Arc::new(EventLog::default());
+        let signing_key = ed25519_dalek::SigningKey::generate(&mut rand::rngs::OsRng);
+        let token = CapabilityToken::sign(
+            NodeId::new(),
+            AutonomyLevel::L3,
+            ResourceCaps {
+                cpu_time_ms: 100,
+                memory_bytes: 1024,
+                token_limit: 10,
+                iteration_cap: 1,
+            },
+            DirectiveProfileHash([0u8; 32]),
+            &signing_key,
+            0,
+            "execute",
+        );
+        let guard = ScopeGuard::new(scope(), token, log.clone());
+
+        assert!(guard.check("src/auth.rs", FsAccess::Write).is_ok());
+        assert!(log.events().is_empty());
+
+        let err = guard.check("Cargo.toml", FsAccess::Write).unwrap_err();
+        assert_eq!(ExecutionError::from(err), ExecutionError::ScopeViolation);
+
+        let events = log.events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].action, SCOPE_VIOLATION_ACTION);
+        assert!(log.verify_integrity().is_ok());
+    }
+}
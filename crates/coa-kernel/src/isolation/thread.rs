@@ -0,0 +1,363 @@
+//! Thread isolation
+//!
+//! Work runs on a dedicated, named thread driving its own single-threaded
+//! tokio runtime, so async work never blocks (or is blocked by) the
+//! caller's runtime. A panic is caught on that thread together with its
+//! message, location and backtrace, and comes back as a
+//! [`ThreadFailure::Panicked`] instead of taking the process down or
+//! printing to stderr.
+//!
+//! Work writes its output through the [`WorkContext`] it is handed; both
+//! streams are captured into the [`ThreadOutput`], including whatever was
+//! written before a panic or cancellation. Writes to the process's own
+//! stdout and stderr are not captured. A [`CancelHandle`] stops the
+//! work at its next await point; synchronous loops should check
+//! [`WorkContext::is_cancelled`].
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe, Location};
+use std::sync::{Arc, Once};
+use std::thread;
+use tokio::sync::watch;
+
+/// Event action recorded for output captured from thread-isolated work;
+/// `result` holds `{"stdout", "stderr"}` as JSON
+pub const THREAD_OUTPUT_ACTION: &str = "thread_output";
+
+thread_local! {
+    /// Whether panics on this thread are being captured
+    static CAPTURING: Cell<bool> = const { Cell::new(false) };
+    /// Last panic captured on this thread
+    static CAUGHT: RefCell<Option<PanicReport>> = const { RefCell::new(None) };
+}
+
+/// Cancels thread-isolated work
+///
+/// Clones share the same state; cancelling one cancels them all.
+#[derive(Debug, Clone)]
+pub struct CancelHandle {
+    sender: Arc<watch::Sender<bool>>,
+}
+
+impl Default for CancelHandle {
+    fn default() -> Self {
+        Self {
+            sender: Arc::new(watch::channel(false).0),
+        }
+    }
+}
+
+impl CancelHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation
+    pub fn cancel(&self) {
+        self.sender.send_replace(true);
+    }
+
+    /// Withdraw a cancellation request, so later work runs again
+    pub fn reset(&self) {
+        self.sender.send_replace(false);
+    }
+
+    /// Check if cancellation was requested
+    pub fn is_cancelled(&self) -> bool {
+        *self.sender.borrow()
+    }
+
+    /// Resolve once cancellation is requested
+    pub async fn cancelled(&self) {
+        // The sender lives as long as `self`, so waiting cannot fail
+        let _ = self
+            .sender
+            .subscribe()
+            .wait_for(|cancelled| *cancelled)
+            .await;
+    }
+}
+
+/// Captured output stream
+type OutputBuffer = Arc<Mutex<String>>;
+
+/// Handle given to thread-isolated work
+#[derive(Debug, Clone)]
+pub struct WorkContext {
+    stdout: OutputBuffer,
+    stderr: OutputBuffer,
+    cancel: CancelHandle,
+}
+
+impl WorkContext {
+    /// Append a line to the captured stdout
+    pub fn println(&self, line: impl fmt::Display) {
+        write_line(&self.stdout, line);
+    }
+
+    /// Append a line to the captured stderr
+    pub fn eprintln(&self, line: impl fmt::Display) {
+        write_line(&self.stderr, line);
+    }
+
+    /// Check if the work has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// Handle the work is cancelled through
+    pub fn cancel_handle(&self) -> &CancelHandle {
+        &self.cancel
+    }
+}
+
+fn write_line(buffer: &OutputBuffer, line: impl fmt::Display) {
+    use std::fmt::Write;
+    let _ = writeln!(buffer.lock(), "{line}");
+}
+
+/// Panic caught on an isolation thread
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PanicReport {
+    pub message: String,
+    /// `file:line:column` the panic was raised at, if known
+    pub location: Option<String>,
+    /// Backtrace captured at the panic (empty if unavailable)
+    pub backtrace: String,
+}
+
+impl PanicReport {
+    fn capture(payload: &(dyn Any + Send), location: Option<&Location<'_>>) -> Self {
+        Self {
+            message: panic_message(payload),
+            location: location.map(ToString::to_string),
+            backtrace: Backtrace::force_capture().to_string(),
+        }
+    }
+
+    fn from_payload(payload: &(dyn Any + Send)) -> Self {
+        Self {
+            message: panic_message(payload),
+            location: None,
+            backtrace: String::new(),
+        }
+    }
+}
+
+impl fmt::Display for PanicReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.location {
+            Some(location) => write!(f, "panicked at {location}: {}", self.message)?,
+            None => write!(f, "panicked: {}", self.message)?,
+        }
+        if !self.backtrace.is_empty() {
+            write!(f, "\nbacktrace:\n{}", self.backtrace)?;
+        }
+        Ok(())
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| (*s).to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "non-string panic payload".to_string())
+}
+
+/// Why thread-isolated work did not complete
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ThreadFailure {
+    /// Work returned an error
+    Failed(String),
+    /// Work panicked
+    Panicked(PanicReport),
+    /// Work was cancelled before it completed
+    Cancelled,
+}
+
+impl fmt::Display for ThreadFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThreadFailure::Failed(message) => f.write_str(message),
+            ThreadFailure::Panicked(report) => write!(f, "thread {report}"),
+            ThreadFailure::Cancelled => f.write_str("thread work cancelled"),
+        }
+    }
+}
+
+/// Result and captured output of thread-isolated work
+#[derive(Debug, Clone)]
+pub struct ThreadOutput {
+    pub result: Result<String, ThreadFailure>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl ThreadOutput {
+    /// Check if anything was written to either stream
+    pub fn has_output(&self) -> bool {
+        !self.stdout.is_empty() || !self.stderr.is_empty()
+    }
+
+    /// Captured streams as an event result (see [`THREAD_OUTPUT_ACTION`])
+    pub fn output_json(&self) -> String {
+        serde_json::json!({ "stdout": self.stdout, "stderr": self.stderr }).to_string()
+    }
+}
+
+/// Run `work` on a dedicated thread named `name` with its own runtime
+///
+/// Blocks until the work completes, fails, panics or is cancelled through
+/// `cancel`.
+pub fn run_in_thread<F, Fut>(
+    name: impl Into<String>,
+    cancel: &CancelHandle,
+    work: F,
+) -> ThreadOutput
+where
+    F: FnOnce(WorkContext) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<String, String>>,
+{
+    let context = WorkContext {
+        stdout: OutputBuffer::default(),
+        stderr: OutputBuffer::default(),
+        cancel: cancel.clone(),
+    };
+    let (stdout, stderr) = (context.stdout.clone(), context.stderr.clone());
+
+    let result = match thread::Builder::new()
+        .name(name.into())
+        .spawn(move || run_on_runtime(context, work))
+    {
+        Ok(handle) => handle.join().unwrap_or_else(|payload| {
+            Err(ThreadFailure::Panicked(PanicReport::from_payload(
+                payload.as_ref(),
+            )))
+        }),
+        Err(e) => Err(ThreadFailure::Failed(format!(
+            "failed to spawn thread: {e}"
+        ))),
+    };
+
+    let take = |buffer: &OutputBuffer| std::mem::take(&mut *buffer.lock());
+    ThreadOutput {
+        result,
+        stdout: take(&stdout),
+        stderr: take(&stderr),
+    }
+}
+
+fn run_on_runtime<F, Fut>(context: WorkContext, work: F) -> Result<String, ThreadFailure>
+where
+    F: FnOnce(WorkContext) -> Fut,
+    Fut: Future<Output = Result<String, String>>,
+{
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| ThreadFailure::Failed(format!("failed to start runtime: {e}")))?;
+    let cancel = context.cancel.clone();
+    let stderr = context.stderr.clone();
+
+    install_panic_hook();
+    CAPTURING.with(|capturing| capturing.set(true));
+    let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+        runtime.block_on(async {
+            tokio::select! {
+                biased;
+                () = cancel.cancelled() => Err(ThreadFailure::Cancelled),
+                result = work(context) => result.map_err(ThreadFailure::Failed),
+            }
+        })
+    }));
+    CAPTURING.with(|capturing| capturing.set(false));
+    let caught = CAUGHT.with(|caught| caught.borrow_mut().take());
+
+    match outcome {
+        Ok(result) => {
+            // A panic in a task the work spawned surfaces through its
+            // `JoinHandle`; keep its report rather than dropping it
+            if let Some(report) = caught {
+                write_line(&stderr, format_args!("task {report}"));
+            }
+            result
+        }
+        Err(payload) => {
+            Err(ThreadFailure::Panicked(caught.unwrap_or_else(|| {
+                PanicReport::from_payload(payload.as_ref())
+            })))
+        }
+    }
+}
+
+/// Chain a panic hook that captures panics on isolation threads and leaves
+/// every other thread to the previous hook
+fn install_panic_hook() {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if CAPTURING.with(Cell::get) {
+                let report = PanicReport::capture(info.payload(), info.location());
+                CAUGHT.with(|caught| *caught.borrow_mut() = Some(report));
+            } else {
+                previous(info);
+            }
+        }));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn captures_output_panics_and_cancellation() {
+        let done = run_in_thread("coa-test", &CancelHandle::new(), |ctx| async move {
+            ctx.println("working");
+            ctx.eprintln("warning");
+            tokio::task::yield_now().await;
+            Ok("done".to_string())
+        });
+        assert_eq!(done.result, Ok("done".to_string()));
+        assert_eq!(
+            (done.stdout.as_str(), done.stderr.as_str()),
+            ("working\n", "warning\n")
+        );
+
+        let panicked = run_in_thread("coa-test", &CancelHandle::new(), |ctx| async move {
+            ctx.println("before");
+            panic!("boom {}", 42);
+        });
+        let Err(ThreadFailure::Panicked(report)) = panicked.result else {
+            panic!("expected a panic, got {:?}", panicked.result);
+        };
+        assert_eq!(report.message, "boom 42");
+        assert!(report.location.is_some_and(|l| l.contains("thread.rs")));
+        assert!(!report.backtrace.is_empty());
+        assert_eq!(panicked.stdout, "before\n");
+
+        let cancel = CancelHandle::new();
+        let remote = cancel.clone();
+        let canceller = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            remote.cancel();
+        });
+        let cancelled = run_in_thread("coa-test", &cancel, |ctx| async move {
+            ctx.println("waiting");
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            Ok("finished".to_string())
+        });
+        canceller.join().unwrap();
+        assert_eq!(cancelled.result, Err(ThreadFailure::Cancelled));
+        assert_eq!(cancelled.stdout, "waiting\n");
+    }
+}
@@ -23,16 +23,28 @@
 //! ```
 
 // Core modules
+pub mod analysis;
 pub mod api;
+pub mod audit;
 pub mod autonomy;
+pub mod bundle;
+pub mod clock;
 pub mod dag;
 pub mod directives;
 pub mod error;
+pub mod idempotency;
 pub mod isolation;
 pub mod logging;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod quota;
+pub mod redaction;
+pub mod replay;
 pub mod resource;
 pub mod scheduler;
+pub mod signer;
 pub mod state_machine;
+pub mod tenant;
 pub mod types;
 
 // v2.0 modules
@@ -41,6 +53,7 @@ pub mod executor;
 pub mod expansion;
 pub mod token_integrity;
 pub mod validated_graph;
+pub mod visualize;
 
 // Test harness
 pub mod test_harness;
@@ -52,18 +65,35 @@ pub use types::*;
 
 /// Re-export v2.0 types for convenience
 pub mod prelude {
+    pub use crate::audit::{RunAuditReport, RunAuditor};
+    pub use crate::api::{ApiNegotiator, ApiVersion, KernelFeature, NegotiationRequest, NegotiationResponse, VersionRange};
+    pub use crate::clock::{Clock, ManualClock, MonotonicClock, SharedClock, SystemClock};
     pub use crate::construction::{GraphBuilder, GraphBuilderError, ConstructionValidator, TokenIssuer, ValidationContext};
-    pub use crate::executor::{Executor, NodeExecutor, NodeExecutionResult, ResourceContainer};
-    pub use crate::error::{ExecutionError, ValidationError};
+    pub use crate::executor::{
+        ExecutionFailure, Executor, MiddlewareStack, NodeExecutor, NodeExecutionResult,
+        ResourceContainer,
+    };
+    pub use crate::error::{ExecutionError, SignerError, ValidationError};
     pub use crate::expansion::{ExpansionBuilder, ExpansionPoint, StagedConstruction};
+    pub use crate::isolation::{CancelHandle, FsAccess, FsScope, ScopeGuard, ScopeViolation};
+    pub use crate::quota::{QuotaLimits, QuotaManager, QuotaSubject, QuotaUsage};
+    pub use crate::redaction::{Redaction, RedactionPolicy, SinkPolicy, TelemetrySink};
+    pub use crate::tenant::{Tenant, TenantRegistry, TenantScoped};
+    pub use crate::replay::{CompositionJournal, JournalEntry, RunDiff, RunReplayer, RunState};
+    pub use crate::resource::RunBudget;
+    pub use crate::signer::Signer;
     pub use crate::types::v2::ExpansionSchema;
-    pub use crate::token_integrity::TokenIntegrity;
+    pub use crate::token_integrity::{TokenIntegrity, VerifiedTokenCache};
     pub use crate::types::v2::{
-        ExecutionSummary, ExpansionType, IntegrityVerification, NodeSpecV2, SubgraphSpec,
+        BoundaryPort, ExecutionSummary, ExpansionType, GraphNode, IntegrityVerification, NodeRecord,
+        NodeRunStatus, NodeSpecV2, SubgraphSpec,
         SystemLimits, ValidatedGraph, ValidationToken,
     };
-    pub use crate::types::{AutonomyLevel, GraphType, ResourceCaps, NodeId, GraphId};
-    pub use crate::validated_graph::{ResourceProof, ValidationReport};
+    pub use crate::types::{AgentId, AutonomyLevel, GraphType, ResourceCaps, NodeId, GraphId};
+    pub use crate::validated_graph::{
+        CriticalPath, EdgeChange, GraphDiff, NodeChange, NodeField, ResourceProof, ValidationReport,
+    };
+    pub use crate::visualize::{GraphExporter, GraphFormat};
 }
 
 /// Version information
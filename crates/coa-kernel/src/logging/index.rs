@@ -0,0 +1,90 @@
+//! Secondary indexes over an event log
+//!
+//! Events are addressed by their position in the log. The index keeps, per
+//! node, the positions of its events, and all positions ordered by
+//! timestamp, so node and time-range queries touch only candidate events
+//! instead of scanning the whole log.
+
+use super::Event;
+use crate::api::EventFilter;
+use crate::types::NodeId;
+use std::collections::{BTreeSet, HashMap};
+use std::ops::Range;
+
+#[derive(Debug, Default)]
+pub(crate) struct EventIndex {
+    /// Positions of each node's events, ascending
+    by_node: HashMap<NodeId, Vec<usize>>,
+    /// `(timestamp, position)` of every event
+    by_time: BTreeSet<(u64, usize)>,
+}
+
+impl EventIndex {
+    pub(crate) fn build(events: &[Event]) -> Self {
+        let mut index = Self::default();
+        for (position, event) in events.iter().enumerate() {
+            index.insert(position, event);
+        }
+        index
+    }
+
+    /// Index the event at `position`, which must follow every indexed one
+    pub(crate) fn insert(&mut self, position: usize, event: &Event) {
+        self.by_node.entry(event.node_id).or_default().push(position);
+        self.by_time.insert((event.timestamp, position));
+    }
+
+    /// Positions within `window` that may match `filter`
+    ///
+    /// Uses the node index when the filter names a node, the time index
+    /// when it bounds timestamps, and the whole window otherwise. Callers
+    /// still apply the full filter to each candidate.
+    pub(crate) fn candidates(&self, filter: &EventFilter, window: Range<usize>) -> Candidates<'_> {
+        if let Some(node_id) = filter.node_id {
+            let Some(positions) = self.by_node.get(&node_id) else {
+                return Candidates::Positions(Vec::new());
+            };
+            let start = positions.partition_point(|&p| p < window.start);
+            let end = positions.partition_point(|&p| p < window.end);
+            return Candidates::Slice(&positions[start..end]);
+        }
+        if filter.since_timestamp.is_some() || filter.until_timestamp.is_some() {
+            let since = filter.since_timestamp.unwrap_or(0);
+            let until = filter.until_timestamp.unwrap_or(u64::MAX);
+            if since > until {
+                return Candidates::Positions(Vec::new());
+            }
+            let mut positions: Vec<usize> = self
+                .by_time
+                .range((since, 0)..=(until, usize::MAX))
+                .map(|&(_, position)| position)
+                .filter(|position| window.contains(position))
+                .collect();
+            positions.sort_unstable();
+            return Candidates::Positions(positions);
+        }
+        Candidates::Window(window)
+    }
+}
+
+/// Candidate positions, ascending
+#[derive(Debug)]
+pub(crate) enum Candidates<'a> {
+    Window(Range<usize>),
+    Slice(&'a [usize]),
+    Positions(Vec<usize>),
+}
+
+impl Candidates<'_> {
+    /// Iterate ascending, or descending if `reverse`
+    pub(crate) fn iter(&self, reverse: bool) -> Box<dyn Iterator<Item = usize> + '_> {
+        match (self, reverse) {
+            (Candidates::Window(range), false) => Box::new(range.clone()),
+            (Candidates::Window(range), true) => Box::new(range.clone().rev()),
+            (Candidates::Slice(positions), false) => Box::new(positions.iter().copied()),
+            (Candidates::Slice(positions), true) => Box::new(positions.iter().rev().copied()),
+            (Candidates::Positions(positions), false) => Box::new(positions.iter().copied()),
+            (Candidates::Positions(positions), true) => Box::new(positions.iter().rev().copied()),
+        }
+    }
+}
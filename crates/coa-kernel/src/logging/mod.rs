@@ -1,8 +1,22 @@
-use crate::error::LogError;
-use crate::types::{AutonomyLevel, DirectiveProfileHash, EventId, NodeId};
-use parking_lot::Mutex;
+use crate::api::{EventCursor, EventLogger, EventPage, EventQuery, IntegrityReport, LogEntry, QueryOrder};
+use crate::clock::{system_clock, SharedClock};
+use crate::error::{KernelError, LogError};
+use crate::redaction::{RedactionPolicy, TelemetrySink};
+use crate::types::{
+    kernel_hash_algorithm, AutonomyLevel, CorrelationId, DirectiveProfileHash, EventId, HashAlgorithm,
+    NodeId, TenantId, Timestamp,
+};
+use parking_lot::RwLock;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+mod index;
+mod schema;
+
+use index::EventIndex;
+
+pub use schema::{Action, EventSchema, EventSchemaRegistry, ResultFormat};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Event {
@@ -11,60 +25,345 @@ pub struct Event {
     pub node_id: NodeId,
     pub autonomy_level: AutonomyLevel,
     pub directive_hash: DirectiveProfileHash,
-    pub action: String,
+    pub action: Action,
     pub result: String,
+    /// Version of the action's result schema (`None` if unchecked)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<u32>,
+    /// Intent or task the event was recorded for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<CorrelationId>,
     pub prev_hash: [u8; 32],
     pub hash: [u8; 32],
+    /// Algorithm of `hash` (SHA-256 if `None`, as in logs predating the choice)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash_algorithm: Option<HashAlgorithm>,
+    /// Tenant the event was recorded for (`None` if single-tenant)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<TenantId>,
 }
 
-#[derive(Debug, Default)]
+/// Action recorded for node state transitions; `result` holds the new
+/// `NodeState` name (e.g. `"Executing"`)
+pub const TRANSITION_ACTION: &str = "transition";
+
+/// Action recorded when a validated composition is applied; `result` holds
+/// the validation metadata as JSON
+pub const COMPOSITION_ACTION: &str = "composition_applied";
+
+/// Action recorded when a node reads an artifact; `result` holds
+/// `{"path", "hash"}` as JSON
+pub const ARTIFACT_READ_ACTION: &str = "artifact_read";
+
+/// Action recorded when the secret scanner flags or blocks ingested
+/// content; `result` holds `{"path", "blocked", "findings"}` as JSON
+pub const SECRET_SCAN_ACTION: &str = "secret_scan";
+
+/// Action recorded when a node escalates to a human; `result` holds the reason
+pub const ESCALATION_ACTION: &str = "escalation";
+
+/// Action recorded when a human approves a node; `result` holds the approver
+pub const APPROVAL_ACTION: &str = "approval";
+
+/// Action recorded when a node is frozen; `result` holds the reason
+pub const FREEZE_ACTION: &str = "node_frozen";
+
+/// Action recorded when a node is unfrozen; `result` holds the reason it
+/// was frozen for
+pub const UNFREEZE_ACTION: &str = "node_unfrozen";
+
+#[derive(Debug)]
 pub struct EventLog {
-    inner: Mutex<Vec<Event>>,
+    inner: RwLock<LogStore>,
+    clock: SharedClock,
+    /// Algorithm new events are hashed with
+    algorithm: HashAlgorithm,
+    /// Schemas new events are checked against
+    schemas: Option<Arc<EventSchemaRegistry>>,
+    /// Tenant every event belongs to (`None` if single-tenant)
+    tenant: Option<TenantId>,
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self::with_clock(system_clock())
+    }
 }
 
 impl EventLog {
+    /// Empty log that timestamps events with `clock`
+    pub fn with_clock(clock: SharedClock) -> Self {
+        Self {
+            inner: RwLock::new(LogStore::default()),
+            clock,
+            algorithm: kernel_hash_algorithm(),
+            schemas: None,
+            tenant: None,
+        }
+    }
+
+    /// Hash events appended from now on with `algorithm`
+    ///
+    /// Existing events keep their algorithm; the chain verifies across the
+    /// switch since every event records its own.
+    #[must_use]
+    pub fn with_hash_algorithm(mut self, algorithm: HashAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Check events appended from now on against `schemas`
+    ///
+    /// Appending an event whose result does not match its action's schema
+    /// fails with [`LogError::SchemaViolation`]; accepted events record the
+    /// schema version.
+    #[must_use]
+    pub fn with_schemas(mut self, schemas: Arc<EventSchemaRegistry>) -> Self {
+        self.schemas = Some(schemas);
+        self
+    }
+
+    /// Record only `tenant`'s events
+    ///
+    /// Appended events are stamped with the tenant; events already stamped
+    /// for another tenant fail with [`LogError::TenantMismatch`].
+    #[must_use]
+    pub fn with_tenant(mut self, tenant: TenantId) -> Self {
+        self.tenant = Some(tenant);
+        self
+    }
+
+    /// Tenant the log belongs to, if any
+    pub fn tenant(&self) -> Option<&TenantId> {
+        self.tenant.as_ref()
+    }
+
+    /// Rebuild a log from previously appended events, checking the hash chain
+    pub fn from_events(events: Vec<Event>) -> Result<Self, LogError> {
+        let log = Self {
+            inner: RwLock::new(LogStore::new(events)),
+            ..Self::default()
+        };
+        log.verify_integrity()?;
+        Ok(log)
+    }
+
+    /// Current time on the log's clock, for stamping new events
+    pub fn now(&self) -> Timestamp {
+        self.clock.now()
+    }
+
+    /// Encode the log as JSON lines, one event per line
+    pub fn to_json_lines(&self) -> Result<String, LogError> {
+        encode_json_lines(&self.inner.read().events)
+    }
+
+    /// Encode the log as JSON lines with results redacted by `policy`
+    ///
+    /// Unlike [`to_json_lines`](Self::to_json_lines), the export is one-way:
+    /// redacted events keep their original hashes, so it does not decode
+    /// with [`from_json_lines`](Self::from_json_lines).
+    pub fn export(&self, policy: &RedactionPolicy) -> Result<String, LogError> {
+        let events: Vec<Event> = self
+            .inner
+            .read()
+            .events
+            .iter()
+            .map(|event| policy.redact_event(TelemetrySink::EventExport, event))
+            .collect();
+        encode_json_lines(&events)
+    }
+
+    /// Decode a log encoded by `to_json_lines`, checking the hash chain
+    pub fn from_json_lines(text: &str) -> Result<Self, LogError> {
+        Self::from_events(decode_json_lines(text)?)
+    }
+
     pub fn append(&self, mut event: Event) -> Result<EventId, LogError> {
-        let mut guard = self.inner.lock();
-        let prev_hash = guard.last().map(|e| e.hash).unwrap_or([0u8; 32]);
+        if let Some(tenant) = &self.tenant {
+            match &event.tenant_id {
+                Some(found) if found != tenant => {
+                    return Err(LogError::TenantMismatch {
+                        expected: tenant.to_string(),
+                        found: found.to_string(),
+                    });
+                }
+                _ => event.tenant_id = Some(tenant.clone()),
+            }
+        }
+        if let Some(schemas) = &self.schemas {
+            schemas.stamp(&mut event)?;
+        }
+        let mut store = self.inner.write();
+        let prev_hash = store.events.last().map(|e| e.hash).unwrap_or([0u8; 32]);
         event.prev_hash = prev_hash;
+        event.hash_algorithm = (self.algorithm != HashAlgorithm::Sha256).then_some(self.algorithm);
         event.hash = compute_hash(&event);
-        guard.push(event.clone());
-        Ok(event.event_id)
+        let event_id = event.event_id;
+        store.push(event);
+        Ok(event_id)
     }
 
     pub fn events(&self) -> Vec<Event> {
-        self.inner.lock().clone()
+        self.inner.read().events.clone()
+    }
+
+    /// Number of events in the log
+    pub fn len(&self) -> usize {
+        self.inner.read().events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
     }
 
     pub fn verify_integrity(&self) -> Result<(), LogError> {
-        let guard = self.inner.lock();
-        let mut prev = [0u8; 32];
-        for e in guard.iter() {
-            if e.prev_hash != prev {
-                return Err(LogError::IntegrityViolation);
-            }
-            let expected = compute_hash(e);
-            if e.hash != expected {
-                return Err(LogError::IntegrityViolation);
-            }
-            prev = e.hash;
+        match first_invalid_index(&self.inner.read().events) {
+            Some(_) => Err(LogError::IntegrityViolation),
+            None => Ok(()),
         }
-        Ok(())
     }
 }
 
+impl EventLogger for EventLog {
+    fn log_event(&self, event: Event) -> Result<EventId, LogError> {
+        self.append(event)
+    }
+
+    /// Matching events, marked verified up to the first broken link of
+    /// the hash chain
+    ///
+    /// Node and time-range constraints are answered from the log's
+    /// indexes; the remaining constraints are checked per candidate.
+    fn query_page(&self, query: EventQuery) -> Result<EventPage, KernelError> {
+        let store = self.inner.read();
+        let len = store.events.len();
+        let descending = query.order == QueryOrder::Descending;
+        let window = match (query.cursor, descending) {
+            (None, _) => 0..len,
+            (Some(EventCursor(after)), false) => (after + 1).min(len)..len,
+            (Some(EventCursor(before)), true) => 0..before.min(len),
+        };
+
+        let candidates = store.index.candidates(&query.filter, window);
+        let mut matches = candidates
+            .iter(descending)
+            .filter(|&position| query.filter.matches(&store.events[position]));
+        let mut entries = Vec::with_capacity(query.limit.min(len));
+        let mut last = None;
+        for position in matches.by_ref().take(query.limit) {
+            entries.push(LogEntry {
+                event: store.events[position].clone(),
+                verified: store.first_invalid.map_or(true, |bad| position < bad),
+            });
+            last = Some(position);
+        }
+        let next = match (last, matches.next()) {
+            (Some(position), Some(_)) => Some(EventCursor(position)),
+            _ => None,
+        };
+        Ok(EventPage { entries, next })
+    }
+
+    fn verify_integrity(&self) -> Result<IntegrityReport, KernelError> {
+        let mut store = self.inner.write();
+        let first_invalid_index = first_invalid_index(&store.events);
+        store.first_invalid = first_invalid_index;
+        Ok(IntegrityReport {
+            valid: first_invalid_index.is_none(),
+            events_checked: store.events.len(),
+            first_invalid_index,
+            tamper_detected: first_invalid_index.is_some(),
+        })
+    }
+}
+
+/// Events with their indexes
+#[derive(Debug, Default)]
+struct LogStore {
+    events: Vec<Event>,
+    index: EventIndex,
+    /// First broken link found by the last integrity check
+    first_invalid: Option<usize>,
+}
+
+impl LogStore {
+    fn new(events: Vec<Event>) -> Self {
+        Self {
+            index: EventIndex::build(&events),
+            first_invalid: first_invalid_index(&events),
+            events,
+        }
+    }
+
+    fn push(&mut self, event: Event) {
+        self.index.insert(self.events.len(), &event);
+        self.events.push(event);
+    }
+}
+
+/// Index of the first event whose hash or chain link is wrong
+fn first_invalid_index(events: &[Event]) -> Option<usize> {
+    let mut prev = [0u8; 32];
+    for (i, e) in events.iter().enumerate() {
+        if e.prev_hash != prev || e.hash != compute_hash(e) {
+            return Some(i);
+        }
+        prev = e.hash;
+    }
+    None
+}
+
+/// Encode `items` as JSON lines
+pub(crate) fn encode_json_lines<T: Serialize>(items: &[T]) -> Result<String, LogError> {
+    let mut out = String::new();
+    for item in items {
+        let line = serde_json::to_string(item).map_err(|e| LogError::Encode(e.to_string()))?;
+        out.push_str(&line);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Decode JSON lines, skipping blank lines
+pub(crate) fn decode_json_lines<T: DeserializeOwned>(text: &str) -> Result<Vec<T>, LogError> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).map_err(|e| LogError::Malformed {
+                line: index + 1,
+                message: e.to_string(),
+            })
+        })
+        .collect()
+}
+
 fn compute_hash(event: &Event) -> [u8; 32] {
-    let mut hasher = Sha256::new();
+    let mut hasher = event.hash_algorithm.unwrap_or(HashAlgorithm::Sha256).hasher();
     hasher.update(event.event_id.0.as_bytes());
     hasher.update(&event.timestamp.to_le_bytes());
     hasher.update(event.node_id.0.as_bytes());
-    hasher.update([event.autonomy_level.as_u8()]);
+    hasher.update(&[event.autonomy_level.as_u8()]);
     hasher.update(&event.directive_hash.0);
-    hasher.update(event.action.as_bytes());
-    hasher.update([0]);
+    hasher.update(event.action.as_str().as_bytes());
+    hasher.update(&[0]);
     hasher.update(event.result.as_bytes());
-    hasher.update([0]);
+    hasher.update(&[0]);
+    // Uncorrelated events hash as before correlation existed
+    if let Some(id) = &event.correlation_id {
+        hasher.update(id.to_string().as_bytes());
+        hasher.update(&[0]);
+    }
+    // Likewise for single-tenant events
+    if let Some(tenant) = &event.tenant_id {
+        hasher.update(tenant.as_str().as_bytes());
+        hasher.update(&[0]);
+    }
+    // Likewise for events written without a schema
+    if let Some(version) = event.schema_version {
+        hasher.update(&version.to_le_bytes());
+    }
     hasher.update(&event.prev_hash);
-    let out = hasher.finalize();
-    out.into()
+    hasher.finalize().into_bytes()
 }
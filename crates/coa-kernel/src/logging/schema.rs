@@ -0,0 +1,295 @@
+//! Typed event actions and versioned result schemas
+//!
+//! [`Action`] names what an [`Event`] records. Built-in actions have their
+//! own variants; anything else is an [`Action::Extension`]. Actions
+//! serialize, hash and display as the same strings the kernel has always
+//! written, so existing logs load and verify unchanged.
+//!
+//! An [`EventSchemaRegistry`] describes the `result` of each action: plain
+//! text, or a JSON object with required fields. Logs built with
+//! [`EventLog::with_schemas`](super::EventLog::with_schemas) check results
+//! against it and stamp each event with its schema version.
+
+use super::{
+    Event, APPROVAL_ACTION, ARTIFACT_READ_ACTION, COMPOSITION_ACTION, ESCALATION_ACTION,
+    FREEZE_ACTION, TRANSITION_ACTION, UNFREEZE_ACTION,
+};
+use crate::analysis::ANALYSIS_ACTION;
+use crate::error::LogError;
+use crate::isolation::{SCOPE_VIOLATION_ACTION, THREAD_OUTPUT_ACTION};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// What an event records
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum Action {
+    /// Node state transition ([`TRANSITION_ACTION`])
+    Transition,
+    /// Validated composition applied ([`COMPOSITION_ACTION`])
+    CompositionApplied,
+    /// Artifact read by a node ([`ARTIFACT_READ_ACTION`])
+    ArtifactRead,
+    /// Node escalated to a human ([`ESCALATION_ACTION`])
+    Escalation,
+    /// Human approved a node ([`APPROVAL_ACTION`])
+    Approval,
+    /// Filesystem access denied ([`SCOPE_VIOLATION_ACTION`])
+    ScopeViolation,
+    /// Analysis gate report ([`ANALYSIS_ACTION`])
+    AnalysisGate,
+    /// Node frozen ([`FREEZE_ACTION`])
+    Freeze,
+    /// Node unfrozen ([`UNFREEZE_ACTION`])
+    Unfreeze,
+    /// Output captured from thread-isolated work ([`THREAD_OUTPUT_ACTION`])
+    ThreadOutput,
+    /// Action defined outside the kernel
+    Extension(String),
+}
+
+impl Action {
+    /// Built-in actions
+    pub const BUILT_IN: [Action; 10] = [
+        Action::Transition,
+        Action::CompositionApplied,
+        Action::ArtifactRead,
+        Action::Escalation,
+        Action::Approval,
+        Action::ScopeViolation,
+        Action::AnalysisGate,
+        Action::Freeze,
+        Action::Unfreeze,
+        Action::ThreadOutput,
+    ];
+
+    /// Name written to the log
+    pub fn as_str(&self) -> &str {
+        match self {
+            Action::Transition => TRANSITION_ACTION,
+            Action::CompositionApplied => COMPOSITION_ACTION,
+            Action::ArtifactRead => ARTIFACT_READ_ACTION,
+            Action::Escalation => ESCALATION_ACTION,
+            Action::Approval => APPROVAL_ACTION,
+            Action::ScopeViolation => SCOPE_VIOLATION_ACTION,
+            Action::AnalysisGate => ANALYSIS_ACTION,
+            Action::Freeze => FREEZE_ACTION,
+            Action::Unfreeze => UNFREEZE_ACTION,
+            Action::ThreadOutput => THREAD_OUTPUT_ACTION,
+            Action::Extension(name) => name,
+        }
+    }
+
+    /// Check if the action is defined outside the kernel
+    pub fn is_extension(&self) -> bool {
+        matches!(self, Action::Extension(_))
+    }
+}
+
+impl From<&str> for Action {
+    fn from(name: &str) -> Self {
+        Action::BUILT_IN
+            .into_iter()
+            .find(|action| action.as_str() == name)
+            .unwrap_or_else(|| Action::Extension(name.to_string()))
+    }
+}
+
+impl From<String> for Action {
+    fn from(name: String) -> Self {
+        match Action::from(name.as_str()) {
+            Action::Extension(_) => Action::Extension(name),
+            action => action,
+        }
+    }
+}
+
+impl From<Action> for String {
+    fn from(action: Action) -> Self {
+        match action {
+            Action::Extension(name) => name,
+            action => action.as_str().to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl PartialEq<str> for Action {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Action {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+/// Shape of an event's `result`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResultFormat {
+    /// Free text
+    Text,
+    /// JSON object with at least these top-level fields
+    Json { required: Vec<String> },
+}
+
+impl ResultFormat {
+    /// JSON object requiring `fields`
+    pub fn json(fields: &[&str]) -> Self {
+        ResultFormat::Json {
+            required: fields.iter().map(|f| (*f).to_string()).collect(),
+        }
+    }
+}
+
+/// Version and result format of one action
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventSchema {
+    pub action: Action,
+    /// Bumped whenever the result format changes
+    pub version: u32,
+    pub result: ResultFormat,
+}
+
+impl EventSchema {
+    pub fn new(action: Action, version: u32, result: ResultFormat) -> Self {
+        Self {
+            action,
+            version,
+            result,
+        }
+    }
+
+    /// Check a `result` against the schema
+    pub fn validate(&self, result: &str) -> Result<(), LogError> {
+        let ResultFormat::Json { required } = &self.result else {
+            return Ok(());
+        };
+        let invalid = |message: String| LogError::SchemaViolation {
+            action: self.action.to_string(),
+            message,
+        };
+        let value: serde_json::Value =
+            serde_json::from_str(result).map_err(|e| invalid(e.to_string()))?;
+        let object = value
+            .as_object()
+            .ok_or_else(|| invalid("result is not a JSON object".to_string()))?;
+        match required.iter().find(|field| !object.contains_key(*field)) {
+            Some(field) => Err(invalid(format!("missing field `{field}`"))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Schemas of known actions
+///
+/// Actions without a schema are accepted as they are and left unversioned.
+#[derive(Debug, Clone)]
+pub struct EventSchemaRegistry {
+    schemas: HashMap<Action, EventSchema>,
+}
+
+impl Default for EventSchemaRegistry {
+    /// Version 1 schemas of the built-in actions
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        for (action, result) in [
+            (Action::Transition, ResultFormat::Text),
+            (
+                Action::CompositionApplied,
+                ResultFormat::json(&["strategy", "artifact_type", "base_hash", "result_hash"]),
+            ),
+            (Action::ArtifactRead, ResultFormat::json(&["path", "hash"])),
+            (Action::Escalation, ResultFormat::Text),
+            (Action::Approval, ResultFormat::Text),
+            (Action::ScopeViolation, ResultFormat::Text),
+            (Action::AnalysisGate, ResultFormat::json(&["scanners", "findings"])),
+            (Action::Freeze, ResultFormat::Text),
+            (Action::Unfreeze, ResultFormat::Text),
+            (Action::ThreadOutput, ResultFormat::json(&["stdout", "stderr"])),
+        ] {
+            registry.register(EventSchema::new(action, 1, result));
+        }
+        registry
+    }
+}
+
+impl EventSchemaRegistry {
+    /// Registry without schemas
+    pub fn empty() -> Self {
+        Self {
+            schemas: HashMap::new(),
+        }
+    }
+
+    /// Add or replace the schema of an action
+    pub fn register(&mut self, schema: EventSchema) -> &mut Self {
+        self.schemas.insert(schema.action.clone(), schema);
+        self
+    }
+
+    /// Schema of `action`
+    pub fn get(&self, action: &Action) -> Option<&EventSchema> {
+        self.schemas.get(action)
+    }
+
+    /// Check an event's result against its action's current schema
+    ///
+    /// Events stamped with an older schema version are accepted as they
+    /// were valid when written.
+    pub fn validate(&self, event: &Event) -> Result<(), LogError> {
+        match self.get(&event.action) {
+            Some(schema) if event.schema_version.map_or(true, |v| v == schema.version) => {
+                schema.validate(&event.result)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Stamp `event` with its action's schema version after validating it
+    pub fn stamp(&self, event: &mut Event) -> Result<(), LogError> {
+        event.schema_version = None;
+        self.validate(event)?;
+        event.schema_version = self.get(&event.action).map(|schema| schema.version);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn actions_render_as_their_legacy_strings() {
+        for action in Action::BUILT_IN {
+            let name = action.to_string();
+            assert_eq!(Action::from(name.as_str()), action);
+            assert_eq!(serde_json::to_string(&action).unwrap(), format!("\"{name}\""));
+        }
+        let custom = Action::from("deploy");
+        assert_eq!(custom, Action::Extension("deploy".to_string()));
+        assert_eq!(serde_json::from_str::<Action>("\"deploy\"").unwrap(), custom);
+        assert_eq!(Action::ArtifactRead, "artifact_read");
+    }
+
+    #[test]
+    fn json_schemas_require_their_fields() {
+        let registry = EventSchemaRegistry::default();
+        let schema = registry.get(&Action::ArtifactRead).unwrap();
+        assert!(schema.validate(r#"{"path": "a.rs", "hash": "00"}"#).is_ok());
+        assert!(matches!(
+            schema.validate(r#"{"path": "a.rs"}"#),
+            Err(LogError::SchemaViolation { .. })
+        ));
+        assert!(schema.validate("a.rs").is_err());
+        assert!(registry.get(&Action::Transition).unwrap().validate("Executing").is_ok());
+    }
+}
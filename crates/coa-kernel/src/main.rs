@@ -1,5 +1,10 @@
 use clap::{Arg, ArgAction, Command, value_parser};
-use coa_kernel::test_harness::{SimulatorConfig, run_simulator, TestHarness};
+use coa_kernel::bundle::{parse_verifying_key, EgressBundle};
+use coa_kernel::isolation::Isolation;
+use coa_kernel::replay::RunReplayer;
+use coa_kernel::test_harness::{run_simulator, run_stress, SimulatorConfig, StressConfig, TestHarness, Topology};
+use coa_symbol::{IndexEntry, SymbolRefIndex};
+use std::path::PathBuf;
 
 #[tokio::main]
 async fn main() {
@@ -42,6 +47,12 @@ async fn main() {
                         .long("verify-zero-policy")
                         .action(ArgAction::SetTrue)
                         .help("Verify zero runtime policy validation"),
+                )
+                .arg(
+                    Arg::new("no-cache")
+                        .long("no-cache")
+                        .action(ArgAction::SetTrue)
+                        .help("Re-execute every node instead of reusing unchanged results"),
                 ),
         )
         .subcommand(
@@ -57,9 +68,23 @@ async fn main() {
                 .arg(
                     Arg::new("iterations")
                         .long("iterations")
-                        .default_value("5000")
+                        .default_value("5")
                         .value_parser(value_parser!(usize))
-                        .help("Number of iterations"),
+                        .help("Number of graphs to build, validate and execute"),
+                )
+                .arg(
+                    Arg::new("topology")
+                        .long("topology")
+                        .default_value("mixed")
+                        .value_parser(["mixed", "layered", "fan-out", "disconnected"])
+                        .help("Shape of the generated graphs"),
+                )
+                .arg(
+                    Arg::new("seed")
+                        .long("seed")
+                        .default_value("42")
+                        .value_parser(value_parser!(u64))
+                        .help("Graph generator seed"),
                 ),
         )
         .subcommand(
@@ -75,6 +100,83 @@ async fn main() {
                         .action(ArgAction::SetTrue)
                         .help("Output as JSON"),
                 ),
+        )
+        .subcommand(
+            Command::new("replay")
+                .about("Reconstruct run state from a persisted event log")
+                .arg(
+                    Arg::new("log")
+                        .long("log")
+                        .required(true)
+                        .value_parser(value_parser!(PathBuf))
+                        .help("Event log (JSON lines)"),
+                )
+                .arg(
+                    Arg::new("journal")
+                        .long("journal")
+                        .value_parser(value_parser!(PathBuf))
+                        .help("Composition journal (JSON lines)"),
+                )
+                .arg(
+                    Arg::new("until")
+                        .long("until")
+                        .value_parser(value_parser!(u64))
+                        .help("Timestamp to reconstruct (default: end of run)"),
+                )
+                .arg(
+                    Arg::new("diff-from")
+                        .long("diff-from")
+                        .value_parser(value_parser!(u64))
+                        .help("Show changes between this timestamp and --until"),
+                )
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .action(ArgAction::SetTrue)
+                        .help("Output as JSON"),
+                ),
+        )
+        .subcommand(
+            Command::new("verify-bundle")
+                .about("Verify a signed egress bundle")
+                .arg(
+                    Arg::new("bundle")
+                        .required(true)
+                        .value_parser(value_parser!(PathBuf))
+                        .help("Bundle archive"),
+                )
+                .arg(
+                    Arg::new("key")
+                        .long("key")
+                        .help("Hex ed25519 verifying key (default: signer named in the manifest)"),
+                )
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .action(ArgAction::SetTrue)
+                        .help("Print the verified manifest as JSON"),
+                ),
+        )
+        .subcommand(
+            Command::new("query-symbols")
+                .about("Query an exported symbol index")
+                .arg(
+                    Arg::new("index")
+                        .required(true)
+                        .value_parser(value_parser!(PathBuf))
+                        .help("JSON array of index entries"),
+                )
+                .arg(
+                    Arg::new("query")
+                        .default_value("")
+                        .help("Query, e.g. \"kind:function visibility:public path:auth.**\""),
+                )
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .action(ArgAction::SetTrue)
+                        .help("Output matching entries as JSON"),
+                ),
         );
 
     let matches = cli.get_matches();
@@ -86,12 +188,14 @@ async fn main() {
             let seed = *args.get_one::<u64>("seed").unwrap();
             let stop_on_violation = args.get_flag("stop-on-violation");
             let verify_zero_policy = args.get_flag("verify-zero-policy");
+            let node_cache = !args.get_flag("no-cache");
 
             println!("Running COA Simulator v2.0...");
             println!("Constructions: {}", constructions);
             println!("Executions: {}", executions);
             println!("Seed: {}", seed);
             println!("Verify Zero Policy: {}", verify_zero_policy);
+            println!("Node Cache: {}", node_cache);
             println!();
 
             let config = SimulatorConfig {
@@ -100,6 +204,7 @@ async fn main() {
                 total_executions: executions,
                 stop_on_first_violation: stop_on_violation,
                 verify_zero_runtime_policy: verify_zero_policy,
+                node_cache,
             };
 
             let report: coa_kernel::test_harness::SimulatorReport = run_simulator(config).await;
@@ -111,18 +216,46 @@ async fn main() {
         Some(("stress", args)) => {
             let nodes = *args.get_one::<usize>("nodes").unwrap();
             let iterations = *args.get_one::<usize>("iterations").unwrap();
+            let seed = *args.get_one::<u64>("seed").unwrap();
+            let topology = match args.get_one::<String>("topology").unwrap().as_str() {
+                "layered" => Topology::Layered { width: (nodes as f64).sqrt() as usize },
+                "fan-out" => Topology::FanOut { degree: 8 },
+                "disconnected" => Topology::Disconnected,
+                _ => Topology::Mixed,
+            };
 
             println!("Running stress test...");
             println!("Nodes: {}", nodes);
             println!("Iterations: {}", iterations);
+            println!("Topology: {:?}", topology);
             println!();
 
-            let report = TestHarness::run_stress_test(nodes, iterations);
+            let config = StressConfig::new(nodes, iterations)
+                .with_topology(topology)
+                .with_seed(seed);
+            let report = run_stress(&config).await;
             
             println!("Stress Test Report:");
             println!("  Nodes: {}", report.nodes);
+            println!("  Edges: {}", report.edges);
+            println!("  Expansion Nodes: {}", report.expansion_nodes);
             println!("  Iterations: {}", report.iterations);
             println!("  Construction Time: {}ms", report.construction_time_ms);
+            println!("  Execution Time: {}ms", report.execution_time_ms);
+            for (name, latency) in [
+                ("Validate", report.validate_latency),
+                ("Execute", report.execute_latency),
+                ("Node", report.node_latency),
+            ] {
+                println!(
+                    "  {} Latency: p50 {}us, p90 {}us, p99 {}us, max {}us",
+                    name, latency.p50_us, latency.p90_us, latency.p99_us, latency.max_us
+                );
+            }
+            println!("  Peak Node Memory: {} bytes", report.peak_node_memory_bytes);
+            if let Some(rss) = report.peak_rss_bytes {
+                println!("  Peak RSS: {} bytes", rss);
+            }
             println!("  Violations: {}", report.violations);
             println!("  Success: {}", report.success);
             
@@ -149,6 +282,10 @@ async fn main() {
                 println!("  \"kernel_version\": \"2.0.0\",");
                 println!("  \"api_version\": \"2.0.0\",");
                 println!("  \"architecture\": \"safe-by-construction\",");
+                println!(
+                    "  \"isolation\": {},",
+                    serde_json::to_string(&Isolation::capabilities()).unwrap()
+                );
                 println!("  \"tests\": {{");
                 println!("    \"unit_tests\": \"PASS\",");
                 println!("    \"construction_tests\": \"PASS\",");
@@ -176,11 +313,144 @@ async fn main() {
                 println!("Resource Governance: PASS");
                 println!("Log Integrity: PASS");
                 println!();
+                let isolation = Isolation::capabilities();
+                println!("Subprocess Isolation: {:?}", isolation.backend);
+                for guarantee in &isolation.guarantees {
+                    println!("  ✓ {}", guarantee);
+                }
+                println!();
                 println!("Performance Summary:");
                 println!("  Binary size: ~850 KB");
                 println!("  10k nodes test: < 2s");
             }
         }
+        Some(("replay", args)) => {
+            let log = args.get_one::<PathBuf>("log").unwrap();
+            let journal = args.get_one::<PathBuf>("journal");
+            let json = args.get_flag("json");
+
+            let loaded = std::fs::read_to_string(log)
+                .and_then(|log| Ok((log, journal.map(std::fs::read_to_string).transpose()?)))
+                .map_err(anyhow::Error::from)
+                .and_then(|(log, journal)| Ok(RunReplayer::from_json_lines(&log, journal.as_deref())?));
+            let replayer = match loaded {
+                Ok(replayer) => replayer,
+                Err(e) => {
+                    eprintln!("Replay failed: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            let until = args
+                .get_one::<u64>("until")
+                .copied()
+                .or_else(|| replayer.last_timestamp())
+                .unwrap_or(0);
+
+            let output = match args.get_one::<u64>("diff-from") {
+                Some(from) => {
+                    let diff = replayer.diff(*from, until);
+                    if json {
+                        serde_json::to_string_pretty(&diff).unwrap()
+                    } else {
+                        diff.to_string()
+                    }
+                }
+                None => {
+                    let state = replayer.state_at(until);
+                    if json {
+                        serde_json::to_string_pretty(&state).unwrap()
+                    } else {
+                        state.to_string()
+                    }
+                }
+            };
+            println!("{}", output.trim_end());
+        }
+        Some(("verify-bundle", args)) => {
+            let path = args.get_one::<PathBuf>("bundle").unwrap();
+            let pinned = args.get_one::<String>("key");
+
+            let archive = match std::fs::read(path) {
+                Ok(archive) => archive,
+                Err(e) => {
+                    eprintln!("Cannot read bundle {}: {}", path.display(), e);
+                    std::process::exit(1);
+                }
+            };
+            let verified = EgressBundle::from_bytes(&archive).and_then(|bundle| {
+                let key = match pinned {
+                    Some(hex_key) => parse_verifying_key(hex_key)?,
+                    None => bundle.signer()?,
+                };
+                bundle.verify(&key).map(|()| bundle)
+            });
+            let bundle = match verified {
+                Ok(bundle) => bundle,
+                Err(e) => {
+                    eprintln!("Bundle verification FAILED: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            let manifest = bundle.manifest();
+            if args.get_flag("json") {
+                println!("{}", serde_json::to_string_pretty(manifest).unwrap());
+            } else {
+                println!("Bundle: {}", path.display());
+                println!("Signer: {}", manifest.signer);
+                if pinned.is_none() {
+                    println!("  (not pinned; pass --key to check the signer's identity)");
+                }
+                println!("Created: {}", manifest.created_at);
+                println!("Files: {}", manifest.files.len());
+                for entry in &manifest.files {
+                    let provenance = if manifest.provenance.contains_key(&entry.path) {
+                        " [provenance]"
+                    } else {
+                        ""
+                    };
+                    println!("  {} {} ({} bytes){}", entry.sha256, entry.path, entry.size, provenance);
+                }
+                println!("Status: VERIFIED");
+            }
+        }
+        Some(("query-symbols", args)) => {
+            let path = args.get_one::<PathBuf>("index").unwrap();
+            let query = args.get_one::<String>("query").unwrap();
+
+            let loaded = std::fs::read_to_string(path)
+                .map_err(anyhow::Error::from)
+                .and_then(|text| Ok(serde_json::from_str::<Vec<IndexEntry>>(&text)?))
+                .and_then(|entries| Ok(SymbolRefIndex::from_entries(entries)?));
+            let found = match loaded.and_then(|index| Ok(index.query(query)?)) {
+                Ok(found) => found,
+                Err(e) => {
+                    eprintln!("Symbol query failed: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            if args.get_flag("json") {
+                println!("{}", serde_json::to_string_pretty(&found).unwrap());
+            } else {
+                for entry in &found {
+                    let location = entry
+                        .metadata
+                        .source_location
+                        .as_ref()
+                        .map(|loc| format!("  {}:{}", loc.file, loc.line))
+                        .unwrap_or_default();
+                    println!(
+                        "{:<40} {:<8} {:<10}{}",
+                        entry.symbol.path().join("."),
+                        entry.metadata.kind.as_str(),
+                        entry.metadata.visibility.as_str(),
+                        location
+                    );
+                }
+                println!("{} symbol(s)", found.len());
+            }
+        }
         _ => {}
     }
 }
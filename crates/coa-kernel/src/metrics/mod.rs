@@ -0,0 +1,384 @@
+//! Runtime metrics (feature `metrics`)
+//!
+//! Process-wide counters and histograms for the kernel and the layers above
+//! it: graphs validated, nodes executed, token verifications, composition
+//! conflicts, cache hit rates and agent restarts. Instrumented code records
+//! into [`global`]; deployments read it back through the pull API
+//! ([`Metrics::snapshot`]) or scrape the Prometheus text format, either
+//! rendered directly ([`Metrics::render_prometheus`]) or served over HTTP
+//! ([`serve`]).
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::Semaphore;
+
+/// Upper bounds (seconds) of the node duration histogram buckets
+pub const DURATION_BUCKETS: [f64; 10] = [0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 30.0, 120.0];
+
+/// Monotonic counter
+#[derive(Debug, Default)]
+pub struct Counter(AtomicU64);
+
+impl Counter {
+    /// Add one
+    pub fn inc(&self) {
+        self.inc_by(1);
+    }
+
+    /// Add `n`
+    pub fn inc_by(&self, n: u64) {
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Current value
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Hit and miss counters of one cache
+#[derive(Debug, Default)]
+pub struct CacheCounters {
+    pub hits: Counter,
+    pub misses: Counter,
+}
+
+impl CacheCounters {
+    /// Record a lookup
+    pub fn record(&self, hit: bool) {
+        if hit {
+            self.hits.inc();
+        } else {
+            self.misses.inc();
+        }
+    }
+
+    fn snapshot(&self) -> CacheSnapshot {
+        CacheSnapshot {
+            hits: self.hits.get(),
+            misses: self.misses.get(),
+        }
+    }
+}
+
+/// Duration histogram over [`DURATION_BUCKETS`]
+#[derive(Debug, Default)]
+pub struct Histogram {
+    buckets: [AtomicU64; DURATION_BUCKETS.len()],
+    count: AtomicU64,
+    sum_micros: AtomicU64,
+}
+
+impl Histogram {
+    /// Record one observation
+    pub fn observe(&self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        for (bucket, bound) in self.buckets.iter().zip(DURATION_BUCKETS) {
+            if seconds <= bound {
+                bucket.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        let micros = u64::try_from(duration.as_micros()).unwrap_or(u64::MAX);
+        self.sum_micros.fetch_add(micros, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> HistogramSnapshot {
+        HistogramSnapshot {
+            buckets: self.buckets.iter().map(|b| b.load(Ordering::Relaxed)).collect(),
+            count: self.count.load(Ordering::Relaxed),
+            sum_seconds: self.sum_micros.load(Ordering::Relaxed) as f64 / 1e6,
+        }
+    }
+}
+
+/// Metrics of one process
+#[derive(Debug, Default)]
+pub struct Metrics {
+    /// Graphs that passed construction-time validation
+    pub graphs_validated: Counter,
+    /// Graphs rejected by validation
+    pub graphs_rejected: Counter,
+    /// Nodes run by the executor (cache hits excluded)
+    pub nodes_executed: Counter,
+    /// Wall-clock duration of node runs
+    pub node_duration: Histogram,
+    /// Capability token verifications
+    pub token_verifications: Counter,
+    /// Capability token verifications that failed
+    pub token_verification_failures: Counter,
+    /// Delta batches composed
+    pub compositions: Counter,
+    /// Overlapping edits reconciled during composition
+    pub composition_conflicts: Counter,
+    /// Executor checkpoint cache
+    pub checkpoint_cache: CacheCounters,
+    /// Constitutional artifact cache
+    pub artifact_cache: CacheCounters,
+    /// Agents restarted by the orchestrator's supervisor
+    pub agent_restarts: Counter,
+}
+
+impl Metrics {
+    /// Metrics with every value zero
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current values (pull API)
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            graphs_validated: self.graphs_validated.get(),
+            graphs_rejected: self.graphs_rejected.get(),
+            nodes_executed: self.nodes_executed.get(),
+            node_duration: self.node_duration.snapshot(),
+            token_verifications: self.token_verifications.get(),
+            token_verification_failures: self.token_verification_failures.get(),
+            compositions: self.compositions.get(),
+            composition_conflicts: self.composition_conflicts.get(),
+            checkpoint_cache: self.checkpoint_cache.snapshot(),
+            artifact_cache: self.artifact_cache.snapshot(),
+            agent_restarts: self.agent_restarts.get(),
+        }
+    }
+
+    /// Current values in the Prometheus text exposition format
+    pub fn render_prometheus(&self) -> String {
+        self.snapshot().render_prometheus()
+    }
+}
+
+/// Process-wide metrics that instrumented code records into
+pub fn global() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(Metrics::new)
+}
+
+/// Point-in-time copy of a cache's counters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct CacheSnapshot {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl CacheSnapshot {
+    /// Fraction of lookups that hit (0.0 with no lookups)
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+/// Point-in-time copy of a histogram
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct HistogramSnapshot {
+    /// Cumulative counts per bucket of [`DURATION_BUCKETS`]
+    pub buckets: Vec<u64>,
+    pub count: u64,
+    pub sum_seconds: f64,
+}
+
+/// Point-in-time copy of [`Metrics`]
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct MetricsSnapshot {
+    pub graphs_validated: u64,
+    pub graphs_rejected: u64,
+    pub nodes_executed: u64,
+    pub node_duration: HistogramSnapshot,
+    pub token_verifications: u64,
+    pub token_verification_failures: u64,
+    pub compositions: u64,
+    pub composition_conflicts: u64,
+    pub checkpoint_cache: CacheSnapshot,
+    pub artifact_cache: CacheSnapshot,
+    pub agent_restarts: u64,
+}
+
+impl MetricsSnapshot {
+    /// Values in the Prometheus text exposition format
+    pub fn render_prometheus(&self) -> String {
+        let mut out = String::new();
+        let counters = [
+            ("coa_graphs_validated_total", "Graphs that passed validation", self.graphs_validated),
+            ("coa_graphs_rejected_total", "Graphs rejected by validation", self.graphs_rejected),
+            ("coa_nodes_executed_total", "Nodes run by the executor", self.nodes_executed),
+            ("coa_token_verifications_total", "Capability token verifications", self.token_verifications),
+            (
+                "coa_token_verification_failures_total",
+                "Capability token verifications that failed",
+                self.token_verification_failures,
+            ),
+            ("coa_compositions_total", "Delta batches composed", self.compositions),
+            (
+                "coa_composition_conflicts_total",
+                "Overlapping edits reconciled during composition",
+                self.composition_conflicts,
+            ),
+            ("coa_agent_restarts_total", "Agents restarted by the supervisor", self.agent_restarts),
+        ];
+        for (name, help, value) in counters {
+            let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} counter\n{name} {value}");
+        }
+
+        let caches = [("checkpoint", self.checkpoint_cache), ("artifact", self.artifact_cache)];
+        for (name, help, hits) in [
+            ("coa_cache_hits_total", "Cache lookups that hit", true),
+            ("coa_cache_misses_total", "Cache lookups that missed", false),
+        ] {
+            let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} counter");
+            for (cache, counters) in caches {
+                let value = if hits { counters.hits } else { counters.misses };
+                let _ = writeln!(out, "{name}{{cache=\"{cache}\"}} {value}");
+            }
+        }
+
+        let name = "coa_node_duration_seconds";
+        let _ = writeln!(out, "# HELP {name} Wall-clock duration of node runs\n# TYPE {name} histogram");
+        for (bound, count) in DURATION_BUCKETS.iter().zip(&self.node_duration.buckets) {
+            let _ = writeln!(out, "{name}_bucket{{le=\"{bound}\"}} {count}");
+        }
+        let histogram = &self.node_duration;
+        let _ = writeln!(out, "{name}_bucket{{le=\"+Inf\"}} {}", histogram.count);
+        let _ = writeln!(out, "{name}_sum {}", histogram.sum_seconds);
+        let _ = writeln!(out, "{name}_count {}", histogram.count);
+        out
+    }
+}
+
+/// Connections [`serve`] handles at once; later clients wait to be accepted
+pub const MAX_CONNECTIONS: usize = 32;
+
+/// How long [`serve`] waits for a client to send its request or take the
+/// response
+pub const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Pause after a failed accept, so persistent errors do not spin
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Serve `metrics` in the Prometheus text format on `GET /metrics`
+///
+/// Answers every other request with 404. At most [`MAX_CONNECTIONS`] are
+/// served at once and clients slower than [`CLIENT_TIMEOUT`] are dropped.
+/// Failed accepts (e.g. out of file descriptors) are logged and retried, so
+/// the endpoint runs until its task is dropped.
+pub async fn serve(listener: TcpListener, metrics: &'static Metrics) {
+    serve_limited(listener, metrics, MAX_CONNECTIONS, CLIENT_TIMEOUT).await;
+}
+
+async fn serve_limited(
+    listener: TcpListener,
+    metrics: &'static Metrics,
+    max_connections: usize,
+    client_timeout: Duration,
+) {
+    let permits = Arc::new(Semaphore::new(max_connections));
+    loop {
+        // The semaphore is never closed
+        let Ok(permit) = Arc::clone(&permits).acquire_owned().await else {
+            return;
+        };
+        let mut stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                tracing::warn!("metrics endpoint failed to accept a connection: {e}");
+                tokio::time::sleep(ACCEPT_BACKOFF).await;
+                continue;
+            }
+        };
+        tokio::spawn(async move {
+            let _permit = permit;
+            let mut request = [0u8; 1024];
+            let Ok(Ok(read)) = tokio::time::timeout(client_timeout, stream.read(&mut request)).await
+            else {
+                return;
+            };
+            let request = String::from_utf8_lossy(&request[..read]);
+            let response = match request.split_whitespace().take(2).collect::<Vec<_>>()[..] {
+                ["GET", "/metrics"] => {
+                    let body = metrics.render_prometheus();
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    )
+                }
+                _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+            };
+            let _ = tokio::time::timeout(client_timeout, stream.write_all(response.as_bytes())).await;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_and_text_format_reflect_recorded_values() {
+        let metrics = Metrics::new();
+        metrics.graphs_validated.inc();
+        metrics.composition_conflicts.inc_by(3);
+        metrics.checkpoint_cache.record(true);
+        metrics.checkpoint_cache.record(false);
+        metrics.node_duration.observe(Duration::from_millis(20));
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.graphs_validated, 1);
+        assert_eq!(snapshot.checkpoint_cache.hit_rate(), 0.5);
+        assert_eq!(snapshot.node_duration.buckets[..4], [0, 0, 0, 1]);
+
+        let text = metrics.render_prometheus();
+        assert!(text.contains("coa_composition_conflicts_total 3\n"));
+        assert!(text.contains("coa_cache_hits_total{cache=\"checkpoint\"} 1\n"));
+        assert!(text.contains("coa_node_duration_seconds_bucket{le=\"+Inf\"} 1\n"));
+        assert!(text.contains("coa_node_duration_seconds_sum 0.02\n"));
+    }
+
+    #[tokio::test]
+    async fn endpoint_serves_metrics() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        global().agent_restarts.inc();
+        tokio::spawn(serve(listener, global()));
+
+        let fetch = |path: &'static str| async move {
+            let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+            stream
+                .write_all(format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").as_bytes())
+                .await
+                .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            response
+        };
+        let response = fetch("/metrics").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("# TYPE coa_agent_restarts_total counter"));
+        assert!(fetch("/other").await.starts_with("HTTP/1.1 404"));
+    }
+
+    #[tokio::test]
+    async fn idle_clients_time_out_and_free_their_slot() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve_limited(listener, global(), 1, Duration::from_millis(50)));
+
+        // Holds the only slot without sending a request
+        let mut idle = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let mut waiting = tokio::net::TcpStream::connect(addr).await.unwrap();
+        waiting.write_all(b"GET /metrics HTTP/1.1\r\n\r\n").await.unwrap();
+
+        let mut response = String::new();
+        waiting.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert_eq!(idle.read(&mut [0u8; 16]).await.unwrap(), 0);
+    }
+}
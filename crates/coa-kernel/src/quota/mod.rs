@@ -0,0 +1,576 @@
+//! Quotas and Rate Limits (v2.0)
+//!
+//! Per-agent, per-graph and per-tenant limits on:
+//! - capability token issuance per minute
+//! - node executions per minute
+//! - cumulative resource consumption per (UTC) day
+//!
+//! Enforcement follows the two-phase architecture:
+//! - **Construction time**: `GraphBuilder` proves the graph fits the
+//!   remaining token and daily budgets before any token is issued
+//! - **Runtime**: `Executor` admits each node execution and charges its
+//!   measured consumption, like a resource container
+//!
+//! Graphs assigned to a tenant (`QuotaManager::assign_tenant`) are also
+//! charged against the tenant's limits, so one tenant cannot exhaust
+//! capacity shared with others.
+//!
+//! State is queryable through `QuotaManager::usage`. `QuotaManager::snapshot`
+//! captures it as a serializable `QuotaSnapshot` and `QuotaManager::restore`
+//! reloads it; storing snapshots is left to the caller.
+
+use crate::clock::{system_clock, SharedClock};
+use crate::error::QuotaError;
+use crate::types::v2::NodeSpecV2;
+use crate::types::{AgentId, GraphId, ResourceCaps, TenantId};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// Length of the rate limit window
+pub const RATE_WINDOW_MS: u64 = 60_000;
+
+const DAY_MS: u64 = 86_400_000;
+
+/// Entity a quota applies to
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum QuotaSubject {
+    Agent(AgentId),
+    Graph(GraphId),
+    Tenant(TenantId),
+}
+
+impl std::fmt::Display for QuotaSubject {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuotaSubject::Agent(agent) => write!(f, "agent {agent}"),
+            QuotaSubject::Graph(graph) => write!(f, "graph {}", graph.0),
+            QuotaSubject::Tenant(tenant) => write!(f, "tenant {tenant}"),
+        }
+    }
+}
+
+/// Limits for one subject; `None` means unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaLimits {
+    pub tokens_per_minute: Option<u32>,
+    pub executions_per_minute: Option<u32>,
+    pub daily_resources: Option<ResourceCaps>,
+}
+
+impl QuotaLimits {
+    /// No limits
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// Limit token issuance per minute
+    pub fn with_tokens_per_minute(mut self, limit: u32) -> Self {
+        self.tokens_per_minute = Some(limit);
+        self
+    }
+
+    /// Limit node executions per minute
+    pub fn with_executions_per_minute(mut self, limit: u32) -> Self {
+        self.executions_per_minute = Some(limit);
+        self
+    }
+
+    /// Limit cumulative consumption per day
+    pub fn with_daily_resources(mut self, caps: ResourceCaps) -> Self {
+        self.daily_resources = Some(caps);
+        self
+    }
+}
+
+/// Current usage of one subject
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaUsage {
+    pub tokens_last_minute: u32,
+    pub executions_last_minute: u32,
+    /// Days since the unix epoch that `consumed_today` refers to
+    pub day: u64,
+    pub consumed_today: ResourceCaps,
+}
+
+/// Evidence that a graph fits its quotas, computed at construction time
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuotaProof {
+    pub subjects: Vec<QuotaSubject>,
+    pub tokens_reserved: u32,
+    pub worst_case: ResourceCaps,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct SubjectState {
+    token_issues: VecDeque<u64>,
+    executions: VecDeque<u64>,
+    day: u64,
+    consumed: Consumption,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+struct Consumption {
+    cpu_time_ms: u64,
+    memory_bytes: u64,
+    tokens: u64,
+    iterations: u64,
+}
+
+impl Consumption {
+    fn plus(self, caps: &ResourceCaps) -> Self {
+        Self {
+            cpu_time_ms: self.cpu_time_ms.saturating_add(caps.cpu_time_ms),
+            memory_bytes: self.memory_bytes.saturating_add(caps.memory_bytes),
+            tokens: self.tokens.saturating_add(caps.token_limit),
+            iterations: self.iterations.saturating_add(caps.iteration_cap),
+        }
+    }
+
+    /// First resource exceeding `caps`, if any
+    fn exceeded(&self, caps: &ResourceCaps) -> Option<&'static str> {
+        if self.cpu_time_ms > caps.cpu_time_ms {
+            Some("cpu_time_ms")
+        } else if self.memory_bytes > caps.memory_bytes {
+            Some("memory_bytes")
+        } else if self.tokens > caps.token_limit {
+            Some("tokens")
+        } else if self.iterations > caps.iteration_cap {
+            Some("iterations")
+        } else {
+            None
+        }
+    }
+
+    fn as_caps(&self) -> ResourceCaps {
+        ResourceCaps {
+            cpu_time_ms: self.cpu_time_ms,
+            memory_bytes: self.memory_bytes,
+            token_limit: self.tokens,
+            iteration_cap: self.iterations,
+        }
+    }
+}
+
+impl SubjectState {
+    /// Drop window entries older than a minute and reset on a new day
+    fn roll(&mut self, now_ms: u64) {
+        let cutoff = now_ms.saturating_sub(RATE_WINDOW_MS);
+        while self.token_issues.front().is_some_and(|t| *t <= cutoff) {
+            self.token_issues.pop_front();
+        }
+        while self.executions.front().is_some_and(|t| *t <= cutoff) {
+            self.executions.pop_front();
+        }
+        let day = now_ms / DAY_MS;
+        if day != self.day {
+            self.day = day;
+            self.consumed = Consumption::default();
+        }
+    }
+
+    fn usage(&self) -> QuotaUsage {
+        QuotaUsage {
+            tokens_last_minute: self.token_issues.len() as u32,
+            executions_last_minute: self.executions.len() as u32,
+            day: self.day,
+            consumed_today: self.consumed.as_caps(),
+        }
+    }
+}
+
+/// Serializable quota limits and state
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QuotaSnapshot {
+    default_graph_limits: QuotaLimits,
+    limits: Vec<(QuotaSubject, QuotaLimits)>,
+    state: Vec<(QuotaSubject, SubjectState)>,
+    #[serde(default)]
+    tenants: Vec<(GraphId, TenantId)>,
+}
+
+/// Quota bookkeeping shared by the construction and execution phases
+pub struct QuotaManager {
+    default_graph_limits: RwLock<QuotaLimits>,
+    limits: RwLock<HashMap<QuotaSubject, QuotaLimits>>,
+    state: Mutex<HashMap<QuotaSubject, SubjectState>>,
+    tenants: RwLock<HashMap<GraphId, TenantId>>,
+    clock: SharedClock,
+}
+
+impl std::fmt::Debug for QuotaManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QuotaManager")
+            .field("default_graph_limits", &*self.default_graph_limits.read())
+            .field("limits", &*self.limits.read())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for QuotaManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl QuotaManager {
+    /// Create a manager with no limits, using the system clock
+    pub fn new() -> Self {
+        Self {
+            default_graph_limits: RwLock::new(QuotaLimits::default()),
+            limits: RwLock::new(HashMap::new()),
+            state: Mutex::new(HashMap::new()),
+            tenants: RwLock::new(HashMap::new()),
+            clock: system_clock(),
+        }
+    }
+
+    /// Replace the clock; used for tests and replays
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Current time in unix milliseconds, at the clock's resolution
+    fn now_ms(&self) -> u64 {
+        self.clock.now().saturating_mul(1_000)
+    }
+
+    /// Set limits for an agent
+    pub fn set_agent_limits(&self, agent: AgentId, limits: QuotaLimits) {
+        self.limits.write().insert(QuotaSubject::Agent(agent), limits);
+    }
+
+    /// Set limits for a specific graph
+    pub fn set_graph_limits(&self, graph_id: GraphId, limits: QuotaLimits) {
+        self.limits.write().insert(QuotaSubject::Graph(graph_id), limits);
+    }
+
+    /// Set limits for a tenant, shared by all of its graphs
+    pub fn set_tenant_limits(&self, tenant: TenantId, limits: QuotaLimits) {
+        self.limits.write().insert(QuotaSubject::Tenant(tenant), limits);
+    }
+
+    /// Charge `graph_id` against `tenant`'s limits as well as its own
+    pub fn assign_tenant(&self, graph_id: GraphId, tenant: TenantId) {
+        self.tenants.write().insert(graph_id, tenant);
+    }
+
+    /// Tenant a graph is charged to, if assigned
+    pub fn tenant_of(&self, graph_id: GraphId) -> Option<TenantId> {
+        self.tenants.read().get(&graph_id).cloned()
+    }
+
+    /// Set limits applied to graphs without their own entry
+    pub fn set_default_graph_limits(&self, limits: QuotaLimits) {
+        *self.default_graph_limits.write() = limits;
+    }
+
+    /// Effective limits for a subject
+    pub fn limits(&self, subject: &QuotaSubject) -> QuotaLimits {
+        match self.limits.read().get(subject) {
+            Some(limits) => *limits,
+            None => match subject {
+                QuotaSubject::Agent(_) | QuotaSubject::Tenant(_) => QuotaLimits::default(),
+                QuotaSubject::Graph(_) => *self.default_graph_limits.read(),
+            },
+        }
+    }
+
+    /// Current usage of a subject
+    pub fn usage(&self, subject: &QuotaSubject) -> QuotaUsage {
+        let now = self.now_ms();
+        let mut state = self.state.lock();
+        let entry = state.entry(subject.clone()).or_default();
+        entry.roll(now);
+        entry.usage()
+    }
+
+    /// Prove a graph fits the remaining quotas and reserve its tokens
+    ///
+    /// Called at construction time before token issuance. Every node is
+    /// charged one token against the per-minute issuance limit, and the sum
+    /// of node resource bounds (worst case) must fit the remaining daily
+    /// budget. Nothing is reserved unless every subject passes.
+    pub fn reserve_construction(
+        &self,
+        agent: Option<&AgentId>,
+        graph_id: GraphId,
+        nodes: &HashMap<crate::types::NodeId, NodeSpecV2>,
+    ) -> Result<QuotaProof, QuotaError> {
+        let worst_case = nodes
+            .values()
+            .fold(Consumption::default(), |acc, spec| acc.plus(&spec.resource_bounds));
+        let tokens = nodes.len() as u32;
+        let subjects = subjects(agent, graph_id, self.tenant_of(graph_id));
+
+        let now = self.now_ms();
+        let mut state = self.state.lock();
+
+        for subject in &subjects {
+            let limits = self.limits(subject);
+            let entry = state.entry(subject.clone()).or_default();
+            entry.roll(now);
+
+            if let Some(limit) = limits.tokens_per_minute {
+                if entry.token_issues.len() as u64 + u64::from(tokens) > u64::from(limit) {
+                    return Err(QuotaError::TokenRateExceeded {
+                        subject: subject.to_string(),
+                        limit,
+                    });
+                }
+            }
+            if let Some(daily) = &limits.daily_resources {
+                let projected = entry.consumed.plus(&worst_case.as_caps());
+                if let Some(resource) = projected.exceeded(daily) {
+                    return Err(QuotaError::DailyBudgetExceeded {
+                        subject: subject.to_string(),
+                        resource,
+                    });
+                }
+            }
+        }
+
+        for subject in &subjects {
+            let entry = state.entry(subject.clone()).or_default();
+            entry.token_issues.extend(std::iter::repeat(now).take(tokens as usize));
+        }
+
+        Ok(QuotaProof {
+            subjects,
+            tokens_reserved: tokens,
+            worst_case: worst_case.as_caps(),
+        })
+    }
+
+    /// Admit one node execution
+    ///
+    /// Called by the executor before running a node.
+    pub fn admit_execution(
+        &self,
+        agent: Option<&AgentId>,
+        graph_id: GraphId,
+    ) -> Result<(), QuotaError> {
+        let subjects = subjects(agent, graph_id, self.tenant_of(graph_id));
+        let now = self.now_ms();
+        let mut state = self.state.lock();
+
+        for subject in &subjects {
+            let limits = self.limits(subject);
+            let entry = state.entry(subject.clone()).or_default();
+            entry.roll(now);
+
+            if let Some(limit) = limits.executions_per_minute {
+                if entry.executions.len() >= limit as usize {
+                    return Err(QuotaError::ExecutionRateExceeded {
+                        subject: subject.to_string(),
+                        limit,
+                    });
+                }
+            }
+        }
+
+        for subject in &subjects {
+            state.entry(subject.clone()).or_default().executions.push_back(now);
+        }
+        Ok(())
+    }
+
+    /// Charge measured consumption of a node
+    ///
+    /// Consumption is always recorded; the error reports that the daily
+    /// budget is now exhausted so the executor can stop.
+    pub fn charge(
+        &self,
+        agent: Option<&AgentId>,
+        graph_id: GraphId,
+        consumed: &ResourceCaps,
+    ) -> Result<(), QuotaError> {
+        let now = self.now_ms();
+        let mut state = self.state.lock();
+        let mut result = Ok(());
+
+        for subject in subjects(agent, graph_id, self.tenant_of(graph_id)) {
+            let limits = self.limits(&subject);
+            let entry = state.entry(subject.clone()).or_default();
+            entry.roll(now);
+            entry.consumed = entry.consumed.plus(consumed);
+
+            if let Some(resource) = limits
+                .daily_resources
+                .and_then(|daily| entry.consumed.exceeded(&daily))
+            {
+                if result.is_ok() {
+                    result = Err(QuotaError::DailyBudgetExceeded {
+                        subject: subject.to_string(),
+                        resource,
+                    });
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Capture limits and state
+    pub fn snapshot(&self) -> QuotaSnapshot {
+        let mut limits: Vec<_> = self
+            .limits
+            .read()
+            .iter()
+            .map(|(subject, limits)| (subject.clone(), *limits))
+            .collect();
+        limits.sort_by_key(|(subject, _)| subject.to_string());
+
+        let mut state: Vec<_> = self
+            .state
+            .lock()
+            .iter()
+            .map(|(subject, state)| (subject.clone(), state.clone()))
+            .collect();
+        state.sort_by_key(|(subject, _)| subject.to_string());
+
+        let mut tenants: Vec<_> = self
+            .tenants
+            .read()
+            .iter()
+            .map(|(graph_id, tenant)| (*graph_id, tenant.clone()))
+            .collect();
+        tenants.sort_by_key(|(graph_id, _)| graph_id.0);
+
+        QuotaSnapshot {
+            default_graph_limits: *self.default_graph_limits.read(),
+            limits,
+            state,
+            tenants,
+        }
+    }
+
+    /// Replace limits and state with a snapshot
+    pub fn restore(&self, snapshot: QuotaSnapshot) {
+        *self.default_graph_limits.write() = snapshot.default_graph_limits;
+        *self.limits.write() = snapshot.limits.into_iter().collect();
+        *self.state.lock() = snapshot.state.into_iter().collect();
+        *self.tenants.write() = snapshot.tenants.into_iter().collect();
+    }
+}
+
+fn subjects(agent: Option<&AgentId>, graph_id: GraphId, tenant: Option<TenantId>) -> Vec<QuotaSubject> {
+    agent
+        .map(|agent| QuotaSubject::Agent(agent.clone()))
+        .into_iter()
+        .chain(std::iter::once(QuotaSubject::Graph(graph_id)))
+        .chain(tenant.map(QuotaSubject::Tenant))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::types::{AutonomyLevel, DirectiveSet, NodeId};
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    fn caps(n: u64) -> ResourceCaps {
+        ResourceCaps {
+            cpu_time_ms: n,
+            memory_bytes: n,
+            token_limit: n,
+            iteration_cap: n,
+        }
+    }
+
+    fn nodes(count: usize, bound: u64) -> HashMap<NodeId, NodeSpecV2> {
+        (0..count)
+            .map(|_| {
+                let spec = NodeSpecV2::new(
+                    DirectiveSet {
+                        directives: BTreeMap::new(),
+                    },
+                    AutonomyLevel::L3,
+                    caps(bound),
+                );
+                (NodeId::new(), spec)
+            })
+            .collect()
+    }
+
+    fn manager() -> (QuotaManager, Arc<ManualClock>) {
+        let clock = Arc::new(ManualClock::new(DAY_MS / 1_000 * 100));
+        let manager = QuotaManager::new().with_clock(clock.clone());
+        (manager, clock)
+    }
+
+    #[test]
+    fn test_token_issuance_limit_per_agent() {
+        let (quota, now) = manager();
+        let agent = AgentId::new("coder-1");
+        quota.set_agent_limits(agent.clone(), QuotaLimits::unlimited().with_tokens_per_minute(3));
+
+        assert!(quota.reserve_construction(Some(&agent), GraphId::new(), &nodes(2, 1)).is_ok());
+        let err = quota
+            .reserve_construction(Some(&agent), GraphId::new(), &nodes(2, 1))
+            .unwrap_err();
+        assert!(matches!(err, QuotaError::TokenRateExceeded { limit: 3, .. }));
+        assert_eq!(quota.usage(&QuotaSubject::Agent(agent.clone())).tokens_last_minute, 2);
+
+        now.advance(RATE_WINDOW_MS / 1_000);
+        assert!(quota.reserve_construction(Some(&agent), GraphId::new(), &nodes(2, 1)).is_ok());
+    }
+
+    #[test]
+    fn test_construction_proves_daily_budget() {
+        let (quota, _) = manager();
+        quota.set_default_graph_limits(QuotaLimits::unlimited().with_daily_resources(caps(10)));
+
+        let proof = quota.reserve_construction(None, GraphId::new(), &nodes(2, 5)).unwrap();
+        assert_eq!(proof.worst_case, caps(10));
+        assert!(matches!(
+            quota.reserve_construction(None, GraphId::new(), &nodes(3, 5)),
+            Err(QuotaError::DailyBudgetExceeded { .. })
+        ));
+    }
+
+    #[test]
+    fn test_execution_rate_and_daily_consumption() {
+        let (quota, now) = manager();
+        let graph = GraphId::new();
+        quota.set_graph_limits(
+            graph,
+            QuotaLimits::unlimited()
+                .with_executions_per_minute(2)
+                .with_daily_resources(caps(10)),
+        );
+
+        assert!(quota.admit_execution(None, graph).is_ok());
+        assert!(quota.admit_execution(None, graph).is_ok());
+        assert!(quota.admit_execution(None, graph).is_err());
+
+        assert!(quota.charge(None, graph, &caps(6)).is_ok());
+        assert!(quota.charge(None, graph, &caps(6)).is_err());
+        assert_eq!(quota.usage(&QuotaSubject::Graph(graph)).consumed_today, caps(12));
+
+        now.advance(DAY_MS / 1_000);
+        let usage = quota.usage(&QuotaSubject::Graph(graph));
+        assert_eq!(usage.consumed_today, caps(0));
+        assert_eq!(usage.executions_last_minute, 0);
+    }
+
+    #[test]
+    fn test_state_survives_snapshot_and_restore() {
+        let (quota, _) = manager();
+        let agent = AgentId::new("planner");
+        let graph = GraphId::new();
+        quota.set_agent_limits(agent.clone(), QuotaLimits::unlimited().with_tokens_per_minute(5));
+        quota.charge(Some(&agent), graph, &caps(4)).unwrap();
+
+        let json = serde_json::to_string(&quota.snapshot()).unwrap();
+        let (restored, _) = manager();
+        restored.restore(serde_json::from_str(&json).unwrap());
+
+        let subject = QuotaSubject::Agent(agent);
+        assert_eq!(restored.limits(&subject).tokens_per_minute, Some(5));
+        assert_eq!(restored.usage(&subject).consumed_today, caps(4));
+    }
+}
@@ -0,0 +1,335 @@
+//! Telemetry Redaction
+//!
+//! Event results and audit details can carry proprietary source code and
+//! personal data. A [`RedactionPolicy`] says, per [`TelemetrySink`], what
+//! happens to two kinds of text before it leaves the kernel:
+//!
+//! - artifact contents: JSON result fields named in [`CONTENT_KEYS`]
+//! - PII-like strings: e-mail addresses, IPv4 addresses and long
+//!   credential-like tokens found in any other text
+//!
+//! Each is kept, hashed (SHA-256 prefix, so equal values still correlate)
+//! or truncated. Policies come from the node directives, under
+//! [`REDACTION_DIRECTIVE`], so the compliance profile that gates a graph
+//! also decides what its telemetry may reveal:
+//!
+//! ```json
+//! {"telemetry_redaction": {
+//!     "event_export": {"artifacts": "hash", "pii": "hash"},
+//!     "audit": {"artifacts": {"truncate": 80}, "pii": "hash"}
+//! }}
+//! ```
+//!
+//! Sinks: [`EventLog::export`](crate::logging::EventLog::export) and
+//! [`RunAuditReport::redacted`](crate::audit::RunAuditReport::redacted).
+//! Metrics carry counts only; label values an embedder attaches go through
+//! [`RedactionPolicy::redact_text`] with [`TelemetrySink::Metrics`].
+
+use crate::logging::Event;
+use crate::types::DirectiveSet;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Directive holding the telemetry redaction policy
+pub const REDACTION_DIRECTIVE: &str = "telemetry_redaction";
+
+/// JSON result fields holding artifact contents
+pub const CONTENT_KEYS: [&str; 10] = [
+    "body", "code", "content", "contents", "diff", "patch", "payload", "source", "stderr", "stdout",
+];
+
+/// Shortest token treated as a credential
+const MIN_SECRET_LEN: usize = 24;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RedactionError {
+    /// Redaction directive does not describe a policy
+    Malformed(String),
+}
+
+impl fmt::Display for RedactionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl std::error::Error for RedactionError {}
+
+/// Destination of telemetry leaving the kernel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TelemetrySink {
+    /// Event log exports
+    EventExport,
+    /// Metrics labels
+    Metrics,
+    /// Run audit reports
+    Audit,
+}
+
+/// What happens to a redacted string
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Redaction {
+    /// Leave as is
+    #[default]
+    Keep,
+    /// Replace with `[sha256:<16 hex>]`
+    Hash,
+    /// Keep the first `n` characters and note how many were cut
+    Truncate(usize),
+}
+
+impl Redaction {
+    /// Apply to `text`
+    pub fn apply(self, text: &str) -> String {
+        match self {
+            Self::Keep => text.to_string(),
+            Self::Hash => {
+                let digest = hex::encode(Sha256::digest(text.as_bytes()));
+                format!("[sha256:{}]", &digest[..16])
+            }
+            Self::Truncate(keep) => match text.char_indices().nth(keep) {
+                Some((cut, _)) => {
+                    let dropped = text[cut..].chars().count();
+                    format!("{}…[{dropped} chars redacted]", &text[..cut])
+                }
+                None => text.to_string(),
+            },
+        }
+    }
+}
+
+/// Redaction of one sink
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct SinkPolicy {
+    /// Artifact contents
+    #[serde(default)]
+    pub artifacts: Redaction,
+    /// PII-like strings
+    #[serde(default)]
+    pub pii: Redaction,
+}
+
+impl SinkPolicy {
+    /// Hash both artifact contents and PII
+    pub fn hashed() -> Self {
+        Self {
+            artifacts: Redaction::Hash,
+            pii: Redaction::Hash,
+        }
+    }
+
+    fn is_noop(&self) -> bool {
+        self.artifacts == Redaction::Keep && self.pii == Redaction::Keep
+    }
+}
+
+/// Redaction per sink; sinks without an entry are not redacted
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RedactionPolicy {
+    sinks: BTreeMap<TelemetrySink, SinkPolicy>,
+}
+
+impl RedactionPolicy {
+    /// Policy that redacts nothing
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Policy hashing artifact contents and PII in every sink
+    pub fn strict() -> Self {
+        [TelemetrySink::EventExport, TelemetrySink::Metrics, TelemetrySink::Audit]
+            .into_iter()
+            .fold(Self::new(), |policy, sink| policy.with_sink(sink, SinkPolicy::hashed()))
+    }
+
+    /// Policy from the [`REDACTION_DIRECTIVE`] of `directives`, empty if
+    /// the directive is absent
+    pub fn from_directives(directives: &DirectiveSet) -> Result<Self, RedactionError> {
+        directives
+            .directives
+            .get(REDACTION_DIRECTIVE)
+            .map_or(Ok(Self::new()), |value| {
+                serde_json::from_value(value.clone()).map_err(|e| RedactionError::Malformed(e.to_string()))
+            })
+    }
+
+    /// With the redaction of `sink`
+    pub fn with_sink(mut self, sink: TelemetrySink, policy: SinkPolicy) -> Self {
+        self.sinks.insert(sink, policy);
+        self
+    }
+
+    /// Redaction of `sink`
+    pub fn sink(&self, sink: TelemetrySink) -> SinkPolicy {
+        self.sinks.get(&sink).copied().unwrap_or_default()
+    }
+
+    /// `text` with its PII-like strings redacted for `sink`
+    pub fn redact_text(&self, sink: TelemetrySink, text: &str) -> String {
+        redact_pii(self.sink(sink).pii, text)
+    }
+
+    /// Event result redacted for `sink`
+    ///
+    /// JSON results have their content fields redacted as artifacts and
+    /// every other string scanned for PII; other results are scanned whole.
+    pub fn redact_result(&self, sink: TelemetrySink, result: &str) -> String {
+        let policy = self.sink(sink);
+        if policy.is_noop() {
+            return result.to_string();
+        }
+        match serde_json::from_str::<Value>(result) {
+            Ok(mut value @ (Value::Object(_) | Value::Array(_))) => {
+                redact_value(&policy, &mut value, false);
+                value.to_string()
+            }
+            _ => redact_pii(policy.pii, result),
+        }
+    }
+
+    /// `event` with its result redacted for `sink`
+    ///
+    /// The hash chain fields are left untouched, so a redacted event still
+    /// names the original it was derived from but no longer verifies.
+    pub fn redact_event(&self, sink: TelemetrySink, event: &Event) -> Event {
+        Event {
+            result: self.redact_result(sink, &event.result),
+            ..event.clone()
+        }
+    }
+}
+
+fn redact_value(policy: &SinkPolicy, value: &mut Value, content: bool) {
+    match value {
+        Value::String(text) if content => *text = policy.artifacts.apply(text),
+        Value::String(text) => *text = redact_pii(policy.pii, text),
+        Value::Array(items) => {
+            for item in items {
+                redact_value(policy, item, content);
+            }
+        }
+        Value::Object(fields) => {
+            for (key, field) in fields {
+                let content = content || CONTENT_KEYS.contains(&key.to_ascii_lowercase().as_str());
+                redact_value(policy, field, content);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replace each PII-like token of `text`
+fn redact_pii(redaction: Redaction, text: &str) -> String {
+    if redaction == Redaction::Keep {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(|c: char| !is_delimiter(c)) {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest.find(is_delimiter).unwrap_or(rest.len());
+        // Sentence punctuation after a token is not part of it
+        let span = &rest[..end];
+        let token = match span.trim_end_matches(['.', ':', '!', '?']) {
+            "" => span,
+            token => token,
+        };
+        if is_pii(token) {
+            out.push_str(&redaction.apply(token));
+        } else {
+            out.push_str(token);
+        }
+        rest = &rest[token.len()..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '"' | '\'' | '`' | ',' | ';' | '(' | ')' | '[' | ']' | '{' | '}' | '<' | '>')
+}
+
+fn is_pii(token: &str) -> bool {
+    is_email(token) || is_ipv4(token) || is_secret(token)
+}
+
+fn is_email(token: &str) -> bool {
+    let Some((local, domain)) = token.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && domain.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-'))
+}
+
+fn is_ipv4(token: &str) -> bool {
+    let octets: Vec<_> = token.split('.').collect();
+    octets.len() == 4 && octets.iter().all(|octet| !octet.is_empty() && octet.len() <= 3 && octet.parse::<u8>().is_ok())
+}
+
+/// Long run of key material: digits and both letter cases, which content
+/// hashes, UUIDs and ULIDs never mix
+fn is_secret(token: &str) -> bool {
+    token.len() >= MIN_SECRET_LEN
+        && token.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '+' | '=' | '.'))
+        && token.chars().any(|c| c.is_ascii_digit())
+        && token.chars().any(|c| c.is_ascii_lowercase())
+        && token.chars().any(|c| c.is_ascii_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn policies_redact_per_sink() {
+        let directives = DirectiveSet {
+            directives: BTreeMap::from([(
+                REDACTION_DIRECTIVE.to_string(),
+                serde_json::json!({
+                    "event_export": {"artifacts": "hash", "pii": "hash"},
+                    "audit": {"artifacts": {"truncate": 4}}
+                }),
+            )]),
+        };
+        let policy = RedactionPolicy::from_directives(&directives).unwrap();
+        assert_eq!(policy.sink(TelemetrySink::Metrics), SinkPolicy::default());
+
+        let result = r#"{"path":"src/auth.rs","content":"fn secret() {}","by":"dev@corp.example"}"#;
+        let exported: Value = serde_json::from_str(&policy.redact_result(TelemetrySink::EventExport, result)).unwrap();
+        assert_eq!(exported["path"], "src/auth.rs");
+        assert_eq!(exported["content"], Redaction::Hash.apply("fn secret() {}"));
+        assert!(exported["by"].as_str().unwrap().starts_with("[sha256:"));
+
+        let audited: Value = serde_json::from_str(&policy.redact_result(TelemetrySink::Audit, result)).unwrap();
+        assert_eq!(audited["content"], "fn s…[10 chars redacted]");
+        assert_eq!(audited["by"], "dev@corp.example");
+        assert_eq!(policy.redact_result(TelemetrySink::Metrics, result), result);
+
+        let text = "approved by ops@corp.example from 10.0.3.7, token ghp_a1B2c3D4e5F6g7H8i9J0k1L2m3.";
+        let redacted = policy.redact_text(TelemetrySink::EventExport, text);
+        assert!(redacted.starts_with("approved by [sha256:"));
+        assert!(!redacted.contains("10.0.3.7") && !redacted.contains("ghp_"));
+        assert!(redacted.ends_with("].") && redacted.contains(" from [sha256:"));
+        let benign = "node failed at src/executor/mod.rs: v1.2 timeout, hash 9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+        assert_eq!(policy.redact_text(TelemetrySink::EventExport, benign), benign);
+
+        let malformed = DirectiveSet {
+            directives: BTreeMap::from([(REDACTION_DIRECTIVE.to_string(), serde_json::json!({"logs": {}}))]),
+        };
+        assert!(matches!(RedactionPolicy::from_directives(&malformed), Err(RedactionError::Malformed(_))));
+        assert_eq!(
+            RedactionPolicy::from_directives(&DirectiveSet { directives: BTreeMap::new() }).unwrap(),
+            RedactionPolicy::new()
+        );
+    }
+}
@@ -0,0 +1,482 @@
+//! Run Replay
+//!
+//! Reconstructs what a run looked like at any point in time from two
+//! persisted records:
+//!
+//! - the kernel `EventLog` (node transitions and other kernel events)
+//! - the `CompositionJournal` (every artifact composition, with the hash it
+//!   produced)
+//!
+//! `RunReplayer::state_at` folds both records up to a timestamp (inclusive)
+//! and `RunReplayer::diff` compares two such points. Nothing is re-executed;
+//! replay is a pure read of what was recorded.
+
+use crate::error::LogError;
+use crate::logging::{decode_json_lines, encode_json_lines, Action, Event, EventLog};
+use crate::types::{AutonomyLevel, NodeId, NodeState, Timestamp};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// One recorded artifact composition
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub timestamp: Timestamp,
+    /// Artifact name (e.g. its symbol path)
+    pub artifact: String,
+    /// Node whose deltas were composed, if any
+    pub node_id: Option<NodeId>,
+    /// Content hash before composition
+    pub base_hash: String,
+    /// Content hash after composition
+    pub result_hash: String,
+    /// Number of deltas composed
+    pub delta_count: usize,
+}
+
+/// Append-only record of artifact compositions
+#[derive(Debug, Default)]
+pub struct CompositionJournal {
+    inner: Mutex<Vec<JournalEntry>>,
+}
+
+impl CompositionJournal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rebuild a journal from recorded entries
+    pub fn from_entries(entries: Vec<JournalEntry>) -> Self {
+        Self {
+            inner: Mutex::new(entries),
+        }
+    }
+
+    /// Record a composition
+    pub fn record(&self, entry: JournalEntry) {
+        self.inner.lock().push(entry);
+    }
+
+    pub fn entries(&self) -> Vec<JournalEntry> {
+        self.inner.lock().clone()
+    }
+
+    /// Encode the journal as JSON lines, one entry per line
+    pub fn to_json_lines(&self) -> Result<String, LogError> {
+        encode_json_lines(&self.inner.lock())
+    }
+
+    /// Decode a journal encoded by `to_json_lines`
+    pub fn from_json_lines(text: &str) -> Result<Self, LogError> {
+        Ok(Self::from_entries(decode_json_lines(text)?))
+    }
+}
+
+/// Node as of a point in time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeSnapshot {
+    pub node_id: NodeId,
+    /// Last recorded transition, if any
+    pub state: Option<NodeState>,
+    pub autonomy_level: AutonomyLevel,
+    pub last_action: String,
+    pub last_result: String,
+    /// Events recorded for the node so far
+    pub events: usize,
+    pub updated_at: Timestamp,
+}
+
+/// Artifact as of a point in time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactSnapshot {
+    pub artifact: String,
+    /// Content hash after the last composition
+    pub hash: String,
+    /// Compositions recorded so far
+    pub compositions: usize,
+    pub last_node: Option<NodeId>,
+    pub updated_at: Timestamp,
+}
+
+/// Reconstructed run state, ordered by node ID and artifact name
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunState {
+    pub timestamp: Timestamp,
+    pub nodes: Vec<NodeSnapshot>,
+    pub artifacts: Vec<ArtifactSnapshot>,
+}
+
+impl RunState {
+    pub fn node(&self, node_id: NodeId) -> Option<&NodeSnapshot> {
+        self.nodes.iter().find(|node| node.node_id == node_id)
+    }
+
+    pub fn artifact(&self, name: &str) -> Option<&ArtifactSnapshot> {
+        self.artifacts.iter().find(|artifact| artifact.artifact == name)
+    }
+}
+
+impl fmt::Display for RunState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Run state at {}", self.timestamp)?;
+        writeln!(f, "Nodes: {}", self.nodes.len())?;
+        for node in &self.nodes {
+            writeln!(
+                f,
+                "  {} {} L{} {} -> {} ({} events)",
+                node.node_id.0,
+                format_state(node.state),
+                node.autonomy_level.as_u8(),
+                node.last_action,
+                node.last_result,
+                node.events
+            )?;
+        }
+        writeln!(f, "Artifacts: {}", self.artifacts.len())?;
+        for artifact in &self.artifacts {
+            writeln!(
+                f,
+                "  {} {} ({} compositions)",
+                artifact.artifact, artifact.hash, artifact.compositions
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Node change between two points in time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeChange {
+    pub node_id: NodeId,
+    pub before: Option<NodeState>,
+    pub after: Option<NodeState>,
+    /// Events recorded between the two points
+    pub new_events: usize,
+}
+
+/// Artifact change between two points in time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactChange {
+    pub artifact: String,
+    /// Hash at the earlier point; `None` if not yet composed
+    pub before: Option<String>,
+    pub after: Option<String>,
+    /// Compositions recorded between the two points
+    pub new_compositions: usize,
+}
+
+/// Differences between two reconstructed states
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunDiff {
+    pub from: Timestamp,
+    pub to: Timestamp,
+    pub nodes: Vec<NodeChange>,
+    pub artifacts: Vec<ArtifactChange>,
+}
+
+impl RunDiff {
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty() && self.artifacts.is_empty()
+    }
+}
+
+impl fmt::Display for RunDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Run diff {} -> {}", self.from, self.to)?;
+        for node in &self.nodes {
+            writeln!(
+                f,
+                "  node {}: {} -> {} (+{} events)",
+                node.node_id.0,
+                format_state(node.before),
+                format_state(node.after),
+                node.new_events
+            )?;
+        }
+        for artifact in &self.artifacts {
+            writeln!(
+                f,
+                "  artifact {}: {} -> {} (+{} compositions)",
+                artifact.artifact,
+                artifact.before.as_deref().unwrap_or("-"),
+                artifact.after.as_deref().unwrap_or("-"),
+                artifact.new_compositions
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Reconstructs run state from the event log and composition journal
+#[derive(Debug, Clone)]
+pub struct RunReplayer {
+    events: Vec<Event>,
+    journal: Vec<JournalEntry>,
+}
+
+impl RunReplayer {
+    /// Create a replayer over a verified event log and a journal
+    pub fn new(log: &EventLog, journal: &CompositionJournal) -> Result<Self, LogError> {
+        log.verify_integrity()?;
+        Ok(Self::from_records(log.events(), journal.entries()))
+    }
+
+    /// Create a replayer over raw records
+    ///
+    /// Records are ordered by timestamp; records sharing a timestamp keep
+    /// their recorded order.
+    pub fn from_records(mut events: Vec<Event>, mut journal: Vec<JournalEntry>) -> Self {
+        events.sort_by_key(|event| event.timestamp);
+        journal.sort_by_key(|entry| entry.timestamp);
+        Self { events, journal }
+    }
+
+    /// Decode a persisted event log and, optionally, a journal (JSON lines)
+    pub fn from_json_lines(log: &str, journal: Option<&str>) -> Result<Self, LogError> {
+        let log = EventLog::from_json_lines(log)?;
+        let journal = match journal {
+            Some(text) => CompositionJournal::from_json_lines(text)?,
+            None => CompositionJournal::new(),
+        };
+        Self::new(&log, &journal)
+    }
+
+    /// Timestamp of the last recorded event or composition
+    pub fn last_timestamp(&self) -> Option<Timestamp> {
+        let event = self.events.last().map(|event| event.timestamp);
+        let entry = self.journal.last().map(|entry| entry.timestamp);
+        event.max(entry)
+    }
+
+    /// Reconstruct the run as of `until` (inclusive)
+    pub fn state_at(&self, until: Timestamp) -> RunState {
+        let mut nodes: BTreeMap<NodeId, NodeSnapshot> = BTreeMap::new();
+        for event in self.events.iter().take_while(|event| event.timestamp <= until) {
+            let node = nodes.entry(event.node_id).or_insert_with(|| NodeSnapshot {
+                node_id: event.node_id,
+                state: None,
+                autonomy_level: event.autonomy_level,
+                last_action: String::new(),
+                last_result: String::new(),
+                events: 0,
+                updated_at: event.timestamp,
+            });
+            if event.action == Action::Transition {
+                if let Some(state) = parse_state(&event.result) {
+                    node.state = Some(state);
+                }
+            }
+            node.autonomy_level = event.autonomy_level;
+            node.last_action = event.action.to_string();
+            node.last_result = event.result.clone();
+            node.events += 1;
+            node.updated_at = event.timestamp;
+        }
+
+        let mut artifacts: BTreeMap<String, ArtifactSnapshot> = BTreeMap::new();
+        for entry in self.journal.iter().take_while(|entry| entry.timestamp <= until) {
+            let artifact = artifacts
+                .entry(entry.artifact.clone())
+                .or_insert_with(|| ArtifactSnapshot {
+                    artifact: entry.artifact.clone(),
+                    hash: entry.base_hash.clone(),
+                    compositions: 0,
+                    last_node: None,
+                    updated_at: entry.timestamp,
+                });
+            artifact.hash = entry.result_hash.clone();
+            artifact.compositions += 1;
+            artifact.last_node = entry.node_id;
+            artifact.updated_at = entry.timestamp;
+        }
+
+        RunState {
+            timestamp: until,
+            nodes: nodes.into_values().collect(),
+            artifacts: artifacts.into_values().collect(),
+        }
+    }
+
+    /// Changes between two points in time
+    ///
+    /// The points may be given in either order; the result always runs from
+    /// the earlier to the later one.
+    pub fn diff(&self, a: Timestamp, b: Timestamp) -> RunDiff {
+        let (from, to) = if a <= b { (a, b) } else { (b, a) };
+        let before = self.state_at(from);
+        let after = self.state_at(to);
+
+        let nodes = after
+            .nodes
+            .iter()
+            .filter_map(|node| {
+                let previous = before.node(node.node_id);
+                let new_events = node.events - previous.map_or(0, |p| p.events);
+                if new_events == 0 {
+                    return None;
+                }
+                Some(NodeChange {
+                    node_id: node.node_id,
+                    before: previous.and_then(|p| p.state),
+                    after: node.state,
+                    new_events,
+                })
+            })
+            .collect();
+
+        let artifacts = after
+            .artifacts
+            .iter()
+            .filter_map(|artifact| {
+                let previous = before.artifact(&artifact.artifact);
+                let new_compositions =
+                    artifact.compositions - previous.map_or(0, |p| p.compositions);
+                if new_compositions == 0 {
+                    return None;
+                }
+                Some(ArtifactChange {
+                    artifact: artifact.artifact.clone(),
+                    before: previous.map(|p| p.hash.clone()),
+                    after: Some(artifact.hash.clone()),
+                    new_compositions,
+                })
+            })
+            .collect();
+
+        RunDiff {
+            from,
+            to,
+            nodes,
+            artifacts,
+        }
+    }
+}
+
+fn parse_state(name: &str) -> Option<NodeState> {
+    serde_json::from_value(serde_json::Value::String(name.to_string())).ok()
+}
+
+fn format_state(state: Option<NodeState>) -> String {
+    state.map_or_else(|| "-".to_string(), |state| format!("{:?}", state))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logging::TRANSITION_ACTION;
+    use crate::types::{DirectiveProfileHash, EventId};
+
+    fn event(node_id: NodeId, timestamp: Timestamp, action: &str, result: &str) -> Event {
+        Event {
+            event_id: EventId::new(),
+            timestamp,
+            node_id,
+            autonomy_level: AutonomyLevel::L3,
+            directive_hash: DirectiveProfileHash([0u8; 32]),
+            action: action.into(),
+            result: result.to_string(),
+            schema_version: None,
+            correlation_id: None,
+            prev_hash: [0u8; 32],
+            hash: [0u8; 32],
+            hash_algorithm: None,
+            tenant_id: None,
+        }
+    }
+
+    fn entry(timestamp: Timestamp, artifact: &str, node_id: NodeId, base: &str, result: &str) -> JournalEntry {
+        JournalEntry {
+            timestamp,
+            artifact: artifact.to_string(),
+            node_id: Some(node_id),
+            base_hash: base.to_string(),
+            result_hash: result.to_string(),
+            delta_count: 1,
+        }
+    }
+
+    fn run() -> (EventLog, CompositionJournal, NodeId, NodeId) {
+        let plan = NodeId::new();
+        let code = NodeId::new();
+        let log = EventLog::default();
+        log.append(event(plan, 10, TRANSITION_ACTION, "Executing")).unwrap();
+        log.append(event(plan, 20, TRANSITION_ACTION, "Merged")).unwrap();
+        log.append(event(code, 20, TRANSITION_ACTION, "Executing")).unwrap();
+        log.append(event(code, 30, "fs_scope_violation", "denied")).unwrap();
+        log.append(event(code, 40, TRANSITION_ACTION, "Merged")).unwrap();
+
+        let journal = CompositionJournal::new();
+        journal.record(entry(20, "docs.plan", plan, "h0", "h1"));
+        journal.record(entry(40, "src.lib", code, "h2", "h3"));
+        journal.record(entry(40, "docs.plan", code, "h1", "h4"));
+        (log, journal, plan, code)
+    }
+
+    #[test]
+    fn test_state_at_reconstructs_nodes_and_artifacts() {
+        let (log, journal, plan, code) = run();
+        let replayer = RunReplayer::new(&log, &journal).unwrap();
+
+        let state = replayer.state_at(30);
+        assert_eq!(state.node(plan).unwrap().state, Some(NodeState::Merged));
+        let node = state.node(code).unwrap();
+        assert_eq!(node.state, Some(NodeState::Executing));
+        assert_eq!(node.last_action, "fs_scope_violation");
+        assert_eq!(node.events, 2);
+        assert_eq!(state.artifact("docs.plan").unwrap().hash, "h1");
+        assert!(state.artifact("src.lib").is_none());
+
+        assert!(replayer.state_at(5).nodes.is_empty());
+        assert_eq!(replayer.last_timestamp(), Some(40));
+    }
+
+    #[test]
+    fn test_diff_between_two_points() {
+        let (log, journal, plan, code) = run();
+        let replayer = RunReplayer::new(&log, &journal).unwrap();
+
+        let diff = replayer.diff(40, 20);
+        assert_eq!((diff.from, diff.to), (20, 40));
+        assert_eq!(diff.nodes.len(), 1);
+        assert_eq!(diff.nodes[0].node_id, code);
+        assert_eq!(diff.nodes[0].before, Some(NodeState::Executing));
+        assert_eq!(diff.nodes[0].after, Some(NodeState::Merged));
+        assert_eq!(diff.nodes[0].new_events, 2);
+
+        let plan_doc = diff.artifacts.iter().find(|a| a.artifact == "docs.plan").unwrap();
+        assert_eq!(plan_doc.before.as_deref(), Some("h1"));
+        assert_eq!(plan_doc.after.as_deref(), Some("h4"));
+        let lib = diff.artifacts.iter().find(|a| a.artifact == "src.lib").unwrap();
+        assert_eq!(lib.before, None);
+
+        assert!(replayer.diff(40, 50).is_empty());
+        assert!(replayer.state_at(40).node(plan).is_some());
+    }
+
+    #[test]
+    fn test_persisted_records_round_trip() {
+        let (log, journal, _, code) = run();
+        let log_lines = log.to_json_lines().unwrap();
+        let journal_lines = journal.to_json_lines().unwrap();
+
+        let replayer = RunReplayer::from_json_lines(&log_lines, Some(&journal_lines)).unwrap();
+        assert_eq!(
+            replayer.state_at(40),
+            RunReplayer::new(&log, &journal).unwrap().state_at(40)
+        );
+        assert_eq!(replayer.state_at(40).node(code).unwrap().state, Some(NodeState::Merged));
+
+        // Tampering breaks the hash chain
+        let tampered = log_lines.replace("denied", "allowed");
+        assert!(matches!(
+            RunReplayer::from_json_lines(&tampered, None),
+            Err(LogError::IntegrityViolation)
+        ));
+
+        assert!(matches!(
+            EventLog::from_json_lines("not json\n"),
+            Err(LogError::Malformed { line: 1, .. })
+        ));
+    }
+}
@@ -1,12 +1,14 @@
 //! Resource Management (v2.0)
 //!
 //! This module handles resource management with the v2.0 architecture:
-//! - **Construction time**: Prove resource bounds are satisfiable
+//! - **Construction time**: Prove resource bounds are satisfiable, and
+//!   draw them from the session's [`RunBudget`]
 //! - **Runtime**: Enforce pre-declared limits (NOT validation)
 
 use crate::error::ValidationError;
 use crate::types::v2::{NodeSpecV2, SystemLimits};
 use crate::types::ResourceCaps;
+use parking_lot::Mutex;
 
 /// Resource proof - evidence that bounds are satisfiable
 ///
@@ -81,6 +83,74 @@ pub fn validate_caps(caps: &ResourceCaps, limits: &ResourceCaps) -> Result<(), c
     }
 }
 
+/// Resources a session may spend across all the graphs it constructs
+///
+/// Each validated graph reserves its worst case (the sum of its node
+/// bounds) as a budget envelope. A graph whose worst case exceeds what is
+/// left is rejected at construction rather than overrunning at runtime.
+#[derive(Debug)]
+pub struct RunBudget {
+    limit: ResourceCaps,
+    reserved: Mutex<ResourceCaps>,
+}
+
+impl RunBudget {
+    /// Budget of `limit` with nothing reserved
+    pub fn new(limit: ResourceCaps) -> Self {
+        Self {
+            limit,
+            reserved: Mutex::new(ResourceCaps {
+                cpu_time_ms: 0,
+                memory_bytes: 0,
+                token_limit: 0,
+                iteration_cap: 0,
+            }),
+        }
+    }
+    
+    /// Total budget for the session
+    pub fn limit(&self) -> ResourceCaps {
+        self.limit
+    }
+    
+    /// Budget not yet reserved by a graph
+    pub fn remaining(&self) -> ResourceCaps {
+        self.remaining_after(&self.reserved.lock())
+    }
+    
+    fn remaining_after(&self, reserved: &ResourceCaps) -> ResourceCaps {
+        ResourceCaps {
+            cpu_time_ms: self.limit.cpu_time_ms.saturating_sub(reserved.cpu_time_ms),
+            memory_bytes: self.limit.memory_bytes.saturating_sub(reserved.memory_bytes),
+            token_limit: self.limit.token_limit.saturating_sub(reserved.token_limit),
+            iteration_cap: self.limit.iteration_cap.saturating_sub(reserved.iteration_cap),
+        }
+    }
+    
+    /// Reserve `envelope` if it fits the remaining budget
+    ///
+    /// Nothing is reserved on failure.
+    pub fn reserve(&self, envelope: &ResourceCaps) -> Result<(), ValidationError> {
+        let mut reserved = self.reserved.lock();
+        validate_caps(envelope, &self.remaining_after(&reserved)).map_err(|_| ValidationError::BudgetExceeded)?;
+        
+        reserved.cpu_time_ms += envelope.cpu_time_ms;
+        reserved.memory_bytes += envelope.memory_bytes;
+        reserved.token_limit += envelope.token_limit;
+        reserved.iteration_cap += envelope.iteration_cap;
+        Ok(())
+    }
+    
+    /// Return an envelope that will not be used, e.g. a discarded graph's
+    pub fn release(&self, envelope: &ResourceCaps) {
+        let mut reserved = self.reserved.lock();
+        reserved.cpu_time_ms = reserved.cpu_time_ms.saturating_sub(envelope.cpu_time_ms);
+        reserved.memory_bytes = reserved.memory_bytes.saturating_sub(envelope.memory_bytes);
+        reserved.token_limit = reserved.token_limit.saturating_sub(envelope.token_limit);
+        reserved.iteration_cap = reserved.iteration_cap.saturating_sub(envelope.iteration_cap);
+    }
+}
+
 /// Container for runtime resource enforcement
 ///
 /// Enforces pre-declared resource limits. This is NOT validation -
@@ -200,6 +270,7 @@ mod tests {
                 iteration_cap: 100,
             },
             expansion_type: None,
+            inputs: Vec::new(),
         }
     }
 
@@ -3,13 +3,26 @@
 //! The scheduler works with pre-validated graphs from the construction phase.
 //! All policy decisions have already been made - the scheduler only handles
 //! execution ordering and timing.
+//!
+//! Nodes run in waves: every node in a wave has all of its constraining
+//! predecessors in earlier waves, so a wave's nodes run concurrently. Data
+//! and control-flow edges always constrain; ordering-only edges may be
+//! relaxed (see [`GraphScheduler::with_ordering_relaxation`]).
+//!
+//! Nodes frozen in the executor's [`FreezeRegistry`](crate::executor::FreezeRegistry)
+//! are not spawned; their results are unsuccessful and carry the skip reason.
+//!
+//! Graphs follow the executor's lifecycle rules: a graph runs once, and
+//! aborting it through [`Executor::abort`] stops the run between waves.
 
 use crate::api::{ExecutionResult, ResourceUsage, ScheduleToken, Scheduler, SchedulerError, SchedulerErrorKind};
 use crate::autonomy::CapabilityToken;
-use crate::executor::Executor;
-use crate::types::v2::ValidatedGraph;
+use crate::error::ExecutionError;
+use crate::executor::{Executor, HeldNodes, NodeExecutionResult};
+use crate::types::v2::{EdgeKind, ValidatedGraph};
 use crate::types::NodeId;
 use ed25519_dalek::VerifyingKey;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::sleep;
@@ -20,19 +33,68 @@ use tokio::time::sleep;
 /// execution ordering without any policy validation.
 pub struct GraphScheduler {
     executor: Arc<Executor>,
+    relax_ordering: bool,
 }
 
 impl GraphScheduler {
     /// Create a new graph scheduler
     pub fn new(verifying_key: VerifyingKey) -> Self {
-        Self {
-            executor: Arc::new(Executor::new(verifying_key)),
-        }
+        Self::with_executor(Arc::new(Executor::new(verifying_key)))
     }
     
     /// Create with custom executor
     pub fn with_executor(executor: Arc<Executor>) -> Self {
-        Self { executor }
+        Self {
+            executor,
+            relax_ordering: false,
+        }
+    }
+    
+    /// Drop ordering-only edges whose source cannot auto-merge
+    ///
+    /// Ordering edges serialize side effects. A source below the
+    /// auto-merge level publishes nothing until a human merges its work,
+    /// so its target cannot observe it and may run alongside.
+    pub fn with_ordering_relaxation(mut self, relax: bool) -> Self {
+        self.relax_ordering = relax;
+        self
+    }
+    
+    /// Group the graph's nodes into waves that may run concurrently
+    ///
+    /// Waves are listed in execution order; nodes within a wave are sorted
+    /// by ID.
+    pub fn plan(&self, graph: &ValidatedGraph) -> Vec<Vec<NodeId>> {
+        let mut waves: HashMap<NodeId, usize> = HashMap::new();
+        let mut plan: BTreeMap<usize, Vec<NodeId>> = BTreeMap::new();
+        
+        for node_id in graph.execution_order() {
+            // Predecessors without a wave yet sit on a sandbox cycle
+            let wave = graph
+                .incoming(node_id)
+                .filter(|&(source, kind)| !self.relaxes(graph, source, kind))
+                .filter_map(|(source, _)| waves.get(&source).map(|wave| wave + 1))
+                .max()
+                .unwrap_or(0);
+            waves.insert(node_id, wave);
+            plan.entry(wave).or_default().push(node_id);
+        }
+        
+        plan.into_values()
+            .map(|mut wave| {
+                wave.sort();
+                wave
+            })
+            .collect()
+    }
+    
+    /// Check if the edge from `source` can be ignored when planning
+    fn relaxes(&self, graph: &ValidatedGraph, source: NodeId, kind: &EdgeKind) -> bool {
+        self.relax_ordering
+            && *kind == EdgeKind::OrderingOnly
+            && graph
+                .get_node_spec(source)
+                .is_some_and(|spec| !spec.autonomy_ceiling.can_auto_merge())
     }
     
     /// Schedule a node from a validated graph for execution
@@ -87,44 +149,101 @@ impl GraphScheduler {
             })?;
         
         // Execute through executor (zero policy checks)
-        match self.executor.execute_single(graph, node_id).await {
-            Ok(result) => Ok(ExecutionResult {
-                success: result.success,
-                node_id: result.node_id,
-                output: Some(format!("Executed node {:?}", node_id)),
-                resource_usage: ResourceUsage {
-                    cpu_time_ms: result.execution_time_ms,
-                    memory_bytes: result.resource_consumed.memory_bytes,
-                    tokens_used: result.resource_consumed.token_limit,
-                    iterations: result.resource_consumed.iteration_cap,
-                },
-            }),
-            Err(e) => Err(SchedulerError {
-                kind: SchedulerErrorKind::Timeout,
-                message: format!("Execution failed: {:?}", e),
-            }),
-        }
+        to_execution_result(node_id, self.executor.execute_single(graph, node_id).await)
     }
     
-    /// Schedule and execute all nodes in topological order
+    /// Schedule and execute all nodes wave by wave
+    ///
+    /// Nodes within a wave run concurrently; results follow the
+    /// [`plan`](Self::plan) order.
+    ///
+    /// # Errors
+    /// - `GraphClosed` if the graph is not active
+    /// - `Cancelled` if the graph is aborted before a wave starts or a
+    ///   node task panics
     pub async fn execute_graph(
         &self,
         graph: ValidatedGraph,
     ) -> Result<Vec<ExecutionResult>, SchedulerError> {
-        let mut results = Vec::new();
-        
-        // Get all node IDs from the graph
-        let node_ids: Vec<NodeId> = graph.node_ids().collect();
+        let run = self.executor.begin(&graph).map_err(|e| SchedulerError {
+            kind: SchedulerErrorKind::GraphClosed,
+            message: format!("Graph {:?} cannot run: {:?}", graph.graph_id(), e),
+        })?;
+        let mut results = Vec::with_capacity(graph.node_count());
+        let plan = self.plan(&graph);
+        let graph = Arc::new(graph);
+        let mut held = HeldNodes::default();
         
-        for node_id in node_ids {
-            let result = self.execute_node(&graph, node_id).await?;
-            results.push(result);
+        for wave in plan {
+            if run.is_aborted() {
+                return Err(SchedulerError {
+                    kind: SchedulerErrorKind::Cancelled,
+                    message: format!("Graph {:?} was aborted", graph.graph_id()),
+                });
+            }
+            let running: Vec<_> = wave
+                .into_iter()
+                .map(|node_id| {
+                    let skip = self
+                        .executor
+                        .freezes()
+                        .and_then(|registry| registry.check(&graph, node_id, &mut held));
+                    let task = skip.is_none().then(|| {
+                        let executor = Arc::clone(&self.executor);
+                        let graph = Arc::clone(&graph);
+                        tokio::spawn(async move { executor.execute_single(&graph, node_id).await })
+                    });
+                    (node_id, skip, task)
+                })
+                .collect();
+            
+            for (node_id, skip, task) in running {
+                let Some(task) = task else {
+                    results.push(ExecutionResult {
+                        success: false,
+                        node_id,
+                        output: skip.map(|reason| reason.to_string()),
+                        resource_usage: ResourceUsage::default(),
+                    });
+                    continue;
+                };
+                let outcome = task.await.map_err(|e| SchedulerError {
+                    kind: SchedulerErrorKind::Cancelled,
+                    message: format!("Node {:?} task failed: {}", node_id, e),
+                })?;
+                results.push(to_execution_result(node_id, outcome)?);
+            }
         }
         
+        run.complete();
         Ok(results)
     }
 }
 
+/// Map an executor outcome onto the scheduler API
+fn to_execution_result(
+    node_id: NodeId,
+    outcome: Result<NodeExecutionResult, ExecutionError>,
+) -> Result<ExecutionResult, SchedulerError> {
+    match outcome {
+        Ok(result) => Ok(ExecutionResult {
+            success: result.success,
+            node_id: result.node_id,
+            output: Some(format!("Executed node {:?}", node_id)),
+            resource_usage: ResourceUsage {
+                cpu_time_ms: result.execution_time_ms,
+                memory_bytes: result.resource_consumed.memory_bytes,
+                tokens_used: result.resource_consumed.token_limit,
+                iterations: result.resource_consumed.iteration_cap,
+            },
+        }),
+        Err(e) => Err(SchedulerError {
+            kind: SchedulerErrorKind::Timeout,
+            message: format!("Execution failed: {:?}", e),
+        }),
+    }
+}
+
 /// Legacy scheduler implementation (for backward compatibility in tests)
 pub struct BasicScheduler;
 
@@ -191,6 +310,7 @@ mod tests {
                 iteration_cap: 100,
             },
             expansion_type: None,
+            inputs: Vec::new(),
         }
     }
 
@@ -236,4 +356,65 @@ mod tests {
         assert!(execution.success);
         assert_eq!(execution.node_id, n1);
     }
+
+    #[tokio::test]
+    async fn test_scheduler_relaxes_ordering_edges_when_safe() {
+        let signing_key = create_signing_key();
+        let proposer = NodeSpecV2 {
+            autonomy_ceiling: AutonomyLevel::L2,
+            ..create_test_spec()
+        };
+        
+        let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
+        let draft = builder.add_node(proposer.clone());
+        let review = builder.add_node(proposer);
+        let merge = builder.add_node(create_test_spec().with_input("Patch"));
+        let deploy = builder.add_node(create_test_spec());
+        builder.add_edge(draft, review).unwrap();
+        builder.add_typed_edge(review, merge, EdgeKind::data("Patch")).unwrap();
+        builder.add_edge(merge, deploy).unwrap();
+        let validated = builder.validate(&signing_key).unwrap();
+        
+        let strict = GraphScheduler::new(signing_key.verifying_key());
+        assert_eq!(
+            strict.plan(&validated),
+            vec![vec![draft], vec![review], vec![merge], vec![deploy]]
+        );
+        
+        // draft cannot auto-merge, so review need not wait for it; the data
+        // edge and the auto-merging node's ordering edge still hold
+        let relaxed = GraphScheduler::new(signing_key.verifying_key())
+            .with_ordering_relaxation(true);
+        let mut first = vec![draft, review];
+        first.sort();
+        assert_eq!(relaxed.plan(&validated), vec![first, vec![merge], vec![deploy]]);
+        
+        let results = relaxed.execute_graph(validated).await.unwrap();
+        assert_eq!(results.len(), 4);
+        let order: Vec<_> = results.iter().map(|result| result.node_id).collect();
+        assert_eq!(order[2..], [merge, deploy]);
+    }
+    
+    #[tokio::test]
+    async fn test_scheduler_holds_back_frozen_nodes() {
+        use crate::executor::FreezeRegistry;
+        
+        let signing_key = create_signing_key();
+        let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
+        let n1 = builder.add_node(create_test_spec());
+        let n2 = builder.add_node(create_test_spec());
+        builder.add_edge(n1, n2).unwrap();
+        let validated = builder.validate(&signing_key).unwrap();
+        
+        let registry = Arc::new(FreezeRegistry::new(signing_key.verifying_key()));
+        registry.freeze(n1, "audit").unwrap();
+        let executor = Executor::new(signing_key.verifying_key()).with_freezes(registry);
+        let scheduler = GraphScheduler::with_executor(Arc::new(executor));
+        
+        let results = scheduler.execute_graph(validated).await.unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|result| !result.success));
+        assert_eq!(results[0].output.as_deref(), Some("frozen: audit"));
+        assert!(results[1].output.as_deref().unwrap().starts_with("blocked by frozen node"));
+    }
 }
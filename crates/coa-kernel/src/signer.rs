@@ -0,0 +1,150 @@
+//! Kernel key signers
+//!
+//! Capability tokens and validation tokens are signed through [`Signer`],
+//! so the kernel's Ed25519 key can stay in an external KMS, HSM or OS
+//! keychain instead of process memory. An in-memory [`SigningKey`]
+//! implements it for tests and single-process setups.
+//!
+//! Signatures returned by external signers are verified against the
+//! signer's public key before use, so a misconfigured signer fails
+//! construction instead of producing tokens the executor later rejects.
+
+use crate::error::SignerError;
+use async_trait::async_trait;
+use ed25519_dalek::{Signature, SigningKey, Verifier, VerifyingKey};
+
+/// Source of Ed25519 signatures for kernel tokens
+#[async_trait]
+pub trait Signer: Send + Sync {
+    /// Public key signatures verify under (given to executors)
+    fn verifying_key(&self) -> VerifyingKey;
+
+    /// Sign `message`
+    ///
+    /// # Errors
+    /// Returns error if the backing key store is unreachable or refuses
+    async fn sign(&self, message: &[u8]) -> Result<Signature, SignerError>;
+}
+
+#[async_trait]
+impl Signer for SigningKey {
+    fn verifying_key(&self) -> VerifyingKey {
+        SigningKey::verifying_key(self)
+    }
+
+    async fn sign(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        Ok(ed25519_dalek::Signer::sign(self, message))
+    }
+}
+
+/// Sign `message` with `signer` and check the signature
+pub(crate) async fn sign_verified(
+    signer: &(impl Signer + ?Sized),
+    message: &[u8],
+) -> Result<Signature, SignerError> {
+    let signature = signer.sign(message).await?;
+    signer
+        .verifying_key()
+        .verify(message, &signature)
+        .map_err(|_| SignerError::InvalidSignature)?;
+    Ok(signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::construction::GraphBuilder;
+    use crate::error::ValidationError;
+    use crate::types::v2::NodeSpecV2;
+    use crate::types::{AutonomyLevel, DirectiveSet, GraphType, ResourceCaps};
+    use std::collections::BTreeMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Stand-in for a remote key store
+    struct RemoteSigner {
+        key: SigningKey,
+        calls: AtomicUsize,
+        online: bool,
+    }
+
+    #[async_trait]
+    impl Signer for RemoteSigner {
+        fn verifying_key(&self) -> VerifyingKey {
+            self.key.verifying_key()
+        }
+
+        async fn sign(&self, message: &[u8]) -> Result<Signature, SignerError> {
+            tokio::task::yield_now().await;
+            if !self.online {
+                return Err(SignerError::Unavailable("kms offline".to_string()));
+            }
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            Ok(ed25519_dalek::Signer::sign(&self.key, message))
+        }
+    }
+
+    /// Signer whose key store holds a different key than it advertises
+    struct MismatchedSigner(SigningKey);
+
+    #[async_trait]
+    impl Signer for MismatchedSigner {
+        fn verifying_key(&self) -> VerifyingKey {
+            SigningKey::from_bytes(&[2u8; 32]).verifying_key()
+        }
+
+        async fn sign(&self, message: &[u8]) -> Result<Signature, SignerError> {
+            Ok(ed25519_dalek::Signer::sign(&self.0, message))
+        }
+    }
+
+    fn builder() -> GraphBuilder {
+        let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
+        let spec = NodeSpecV2 {
+            directives: DirectiveSet {
+                directives: BTreeMap::new(),
+            },
+            autonomy_ceiling: AutonomyLevel::L2,
+            resource_bounds: ResourceCaps {
+                cpu_time_ms: 1000,
+                memory_bytes: 1024 * 1024,
+                token_limit: 1000,
+                iteration_cap: 10,
+            },
+            expansion_type: None,
+            inputs: Vec::new(),
+        };
+        let a = builder.add_node(spec.clone());
+        let b = builder.add_node(spec);
+        builder.add_edge(a, b).unwrap();
+        builder
+    }
+
+    #[tokio::test]
+    async fn graphs_validate_with_external_signer() {
+        let signer = RemoteSigner {
+            key: SigningKey::from_bytes(&[1u8; 32]),
+            calls: AtomicUsize::new(0),
+            online: true,
+        };
+        let graph = builder().validate_with_signer(&signer).await.unwrap();
+        // Two capability tokens and the validation token
+        assert_eq!(signer.calls.load(Ordering::Relaxed), 3);
+        let key = signer.verifying_key();
+        assert!(graph.node_ids().all(|id| graph.get_node_token(id).unwrap().verify(&key)));
+
+        let offline = RemoteSigner {
+            online: false,
+            ..signer
+        };
+        assert!(matches!(
+            builder().validate_with_signer(&offline).await,
+            Err(ValidationError::Signing(SignerError::Unavailable(_)))
+        ));
+        assert!(matches!(
+            builder()
+                .validate_with_signer(&MismatchedSigner(SigningKey::from_bytes(&[1u8; 32])))
+                .await,
+            Err(ValidationError::Signing(SignerError::InvalidSignature))
+        ));
+    }
+}
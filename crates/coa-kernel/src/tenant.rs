@@ -0,0 +1,511 @@
+//! Multi-tenant namespaces
+//!
+//! A [`Tenant`] owns everything done on its behalf: its graphs, its event
+//! log and the key its tokens are signed with. The [`TenantRegistry`]
+//! hands out tenants and answers which tenant a graph belongs to, so a
+//! request from one tenant can never reach another tenant's graphs.
+//!
+//! Isolation is by construction rather than by filtering:
+//! - each tenant has its own [`EventLog`], stamping every event with the
+//!   tenant and refusing events recorded for anyone else
+//! - signing keys are never shared; registering a key another tenant
+//!   already uses fails
+//! - graphs claimed by a tenant are charged against its [`QuotaManager`]
+//!   limits as well as their own
+//!
+//! Artifact caches and symbol indexes are kept per tenant in the same way,
+//! one per tenant (`coa_constitutional::ConstitutionalLayer::for_tenant`).
+//!
+//! The kernel entry points enforce ownership: `GraphBuilder::with_tenant`
+//! claims the graph it validates, `Executor::with_tenant` refuses graphs of
+//! other tenants, and [`TenantScoped`] does both for the [`GraphManager`]
+//! and [`NodeOperations`] front ends.
+
+use crate::api::{GraphManager, GraphStats, NodeOperations};
+use crate::autonomy::CapabilityToken;
+use crate::error::{KernelError, TenantError};
+use crate::isolation::{FsScope, ScopeGuard};
+use crate::logging::EventLog;
+use crate::quota::{QuotaLimits, QuotaManager};
+use crate::signer::Signer;
+use crate::types::{GraphId, GraphType, NodeId, NodeSpec, TenantId};
+use ed25519_dalek::VerifyingKey;
+use parking_lot::RwLock;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// One tenant's namespace: key material, event log and graphs
+pub struct Tenant {
+    id: TenantId,
+    signer: Arc<dyn Signer>,
+    log: Arc<EventLog>,
+    graphs: RwLock<HashSet<GraphId>>,
+}
+
+impl std::fmt::Debug for Tenant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tenant")
+            .field("id", &self.id)
+            .field("verifying_key", &self.signer.verifying_key())
+            .field("graphs", &self.graphs.read().len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Tenant {
+    /// Tenant identifier
+    pub fn id(&self) -> &TenantId {
+        &self.id
+    }
+
+    /// Signer for the tenant's capability and validation tokens
+    pub fn signer(&self) -> &Arc<dyn Signer> {
+        &self.signer
+    }
+
+    /// Public key the tenant's tokens verify under
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.signer.verifying_key()
+    }
+
+    /// The tenant's own event log
+    pub fn log(&self) -> &Arc<EventLog> {
+        &self.log
+    }
+
+    /// Guard confining a node to `scope`, recording into the tenant's log
+    pub fn scope_guard(&self, scope: FsScope, token: CapabilityToken) -> ScopeGuard {
+        ScopeGuard::new(scope, token, Arc::clone(&self.log))
+    }
+
+    /// Whether the tenant owns `graph_id`
+    pub fn owns(&self, graph_id: GraphId) -> bool {
+        self.graphs.read().contains(&graph_id)
+    }
+
+    /// Graphs the tenant owns
+    pub fn graphs(&self) -> Vec<GraphId> {
+        self.graphs.read().iter().copied().collect()
+    }
+}
+
+/// Registry of tenants and the graphs each one owns
+#[derive(Debug, Default)]
+pub struct TenantRegistry {
+    tenants: RwLock<HashMap<TenantId, Arc<Tenant>>>,
+    owners: RwLock<HashMap<GraphId, TenantId>>,
+    quota: Option<Arc<QuotaManager>>,
+}
+
+impl TenantRegistry {
+    /// Empty registry without quota enforcement
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Charge claimed graphs against per-tenant limits in `quota`
+    #[must_use]
+    pub fn with_quota(mut self, quota: Arc<QuotaManager>) -> Self {
+        self.quota = Some(quota);
+        self
+    }
+
+    /// Register a tenant signing with `signer` and limited by `limits`
+    ///
+    /// # Errors
+    /// - `TenantError::Duplicate` if `id` is already registered
+    /// - `TenantError::SharedKey` if another tenant signs with the same key
+    pub fn register(
+        &self,
+        id: TenantId,
+        signer: Arc<dyn Signer>,
+        limits: QuotaLimits,
+    ) -> Result<Arc<Tenant>, TenantError> {
+        let mut tenants = self.tenants.write();
+        if tenants.contains_key(&id) {
+            return Err(TenantError::Duplicate(id.to_string()));
+        }
+        let key = signer.verifying_key();
+        if let Some(owner) = tenants.values().find(|t| t.verifying_key() == key) {
+            return Err(TenantError::SharedKey {
+                tenant: id.to_string(),
+                owner: owner.id.to_string(),
+            });
+        }
+
+        if let Some(quota) = &self.quota {
+            quota.set_tenant_limits(id.clone(), limits);
+        }
+        let tenant = Arc::new(Tenant {
+            log: Arc::new(EventLog::default().with_tenant(id.clone())),
+            id: id.clone(),
+            signer,
+            graphs: RwLock::default(),
+        });
+        tenants.insert(id, Arc::clone(&tenant));
+        Ok(tenant)
+    }
+
+    /// Registered tenant
+    ///
+    /// # Errors
+    /// Returns `TenantError::Unknown` if `id` is not registered
+    pub fn tenant(&self, id: &TenantId) -> Result<Arc<Tenant>, TenantError> {
+        self.tenants
+            .read()
+            .get(id)
+            .cloned()
+            .ok_or_else(|| TenantError::Unknown(id.to_string()))
+    }
+
+    /// Tenant owning `graph_id`, if claimed
+    pub fn owner(&self, graph_id: GraphId) -> Option<TenantId> {
+        self.owners.read().get(&graph_id).cloned()
+    }
+
+    /// Record `graph_id` as owned by `id`
+    ///
+    /// Claiming a graph the tenant already owns is a no-op.
+    ///
+    /// # Errors
+    /// - `TenantError::Unknown` if `id` is not registered
+    /// - `TenantError::CrossTenant` if another tenant owns the graph
+    pub fn claim_graph(&self, id: &TenantId, graph_id: GraphId) -> Result<(), TenantError> {
+        let tenant = self.tenant(id)?;
+        let mut owners = self.owners.write();
+        match owners.get(&graph_id) {
+            Some(owner) if owner != id => {
+                return Err(TenantError::CrossTenant {
+                    tenant: id.to_string(),
+                    owner: owner.to_string(),
+                });
+            }
+            Some(_) => return Ok(()),
+            None => {}
+        }
+        owners.insert(graph_id, id.clone());
+        tenant.graphs.write().insert(graph_id);
+        if let Some(quota) = &self.quota {
+            quota.assign_tenant(graph_id, id.clone());
+        }
+        Ok(())
+    }
+
+    /// Check that `id` may build, run or inspect `graph_id`
+    ///
+    /// # Errors
+    /// - `TenantError::Unclaimed` if no tenant owns the graph
+    /// - `TenantError::CrossTenant` if another tenant owns it
+    pub fn authorize_graph(&self, id: &TenantId, graph_id: GraphId) -> Result<(), TenantError> {
+        match self.owners.read().get(&graph_id) {
+            None => Err(TenantError::Unclaimed),
+            Some(owner) if owner != id => Err(TenantError::CrossTenant {
+                tenant: id.to_string(),
+                owner: owner.to_string(),
+            }),
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Registered tenant IDs, sorted
+    pub fn tenant_ids(&self) -> Vec<TenantId> {
+        let mut ids: Vec<_> = self.tenants.read().keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+}
+
+/// Kernel front end serving one tenant
+///
+/// Wraps a [`GraphManager`] or [`NodeOperations`] implementation. Graphs
+/// created through it are claimed for the tenant; calls naming a graph, or
+/// a node added through it, fail with `KernelError::Tenant` unless the
+/// tenant owns that graph.
+#[derive(Debug)]
+pub struct TenantScoped<K> {
+    inner: K,
+    registry: Arc<TenantRegistry>,
+    tenant: TenantId,
+    nodes: RwLock<HashMap<NodeId, GraphId>>,
+}
+
+impl<K> TenantScoped<K> {
+    /// Serve `tenant` of `registry` through `inner`
+    pub fn new(inner: K, registry: Arc<TenantRegistry>, tenant: TenantId) -> Self {
+        Self {
+            inner,
+            registry,
+            tenant,
+            nodes: RwLock::default(),
+        }
+    }
+
+    pub fn inner(&self) -> &K {
+        &self.inner
+    }
+
+    pub fn tenant(&self) -> &TenantId {
+        &self.tenant
+    }
+
+    fn authorize(&self, graph_id: GraphId) -> Result<(), TenantError> {
+        self.registry.authorize_graph(&self.tenant, graph_id)
+    }
+
+    /// Graph of a node added through this front end, if the tenant owns it
+    fn node_graph(&self, node_id: NodeId) -> Result<GraphId, TenantError> {
+        let graph_id = self
+            .nodes
+            .read()
+            .get(&node_id)
+            .copied()
+            .ok_or_else(|| TenantError::ForeignNode(node_id.0.to_string()))?;
+        self.authorize(graph_id)?;
+        Ok(graph_id)
+    }
+}
+
+impl<K: GraphManager> GraphManager for TenantScoped<K> {
+    fn create_graph(&self, graph_type: GraphType) -> Result<GraphId, KernelError> {
+        self.registry.tenant(&self.tenant)?;
+        let graph_id = self.inner.create_graph(graph_type)?;
+        self.registry.claim_graph(&self.tenant, graph_id)?;
+        Ok(graph_id)
+    }
+
+    fn close_graph(&self, graph_id: GraphId) -> Result<(), KernelError> {
+        self.authorize(graph_id)?;
+        self.inner.close_graph(graph_id)
+    }
+
+    fn graph_stats(&self, graph_id: GraphId) -> Result<GraphStats, KernelError> {
+        self.authorize(graph_id)?;
+        self.inner.graph_stats(graph_id)
+    }
+}
+
+impl<K: NodeOperations> NodeOperations for TenantScoped<K> {
+    fn add_node(&self, graph_id: GraphId, spec: NodeSpec) -> Result<NodeId, KernelError> {
+        self.authorize(graph_id)?;
+        let node_id = self.inner.add_node(graph_id, spec)?;
+        self.nodes.write().insert(node_id, graph_id);
+        Ok(node_id)
+    }
+
+    fn add_edge(&self, graph_id: GraphId, from: NodeId, to: NodeId) -> Result<(), KernelError> {
+        self.authorize(graph_id)?;
+        for node in [from, to] {
+            if self.node_graph(node)? != graph_id {
+                return Err(TenantError::ForeignNode(node.0.to_string()).into());
+            }
+        }
+        self.inner.add_edge(graph_id, from, to)
+    }
+
+    fn deactivate_node(&self, node_id: NodeId) -> Result<(), KernelError> {
+        self.node_graph(node_id)?;
+        self.inner.deactivate_node(node_id)
+    }
+
+    fn freeze_node(&self, node_id: NodeId) -> Result<(), KernelError> {
+        self.node_graph(node_id)?;
+        self.inner.freeze_node(node_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::LogError;
+    use crate::logging::{Action, Event, FREEZE_ACTION};
+    use crate::quota::QuotaSubject;
+    use crate::types::{AutonomyLevel, DirectiveProfileHash, EventId, NodeId};
+    use ed25519_dalek::SigningKey;
+
+    fn signer(seed: u8) -> Arc<dyn Signer> {
+        Arc::new(SigningKey::from_bytes(&[seed; 32]))
+    }
+
+    fn event(tenant_id: Option<TenantId>) -> Event {
+        Event {
+            event_id: EventId::new(),
+            timestamp: 0,
+            node_id: NodeId::new(),
+            autonomy_level: AutonomyLevel::L3,
+            directive_hash: DirectiveProfileHash([0; 32]),
+            action: Action::from(FREEZE_ACTION),
+            result: "test".into(),
+            schema_version: None,
+            correlation_id: None,
+            prev_hash: [0; 32],
+            hash: [0; 32],
+            hash_algorithm: None,
+            tenant_id,
+        }
+    }
+
+    #[test]
+    fn graphs_are_isolated_between_tenants() {
+        let registry = TenantRegistry::new();
+        let acme = TenantId::new("acme");
+        let globex = TenantId::new("globex");
+        registry.register(acme.clone(), signer(1), QuotaLimits::unlimited()).unwrap();
+        registry.register(globex.clone(), signer(2), QuotaLimits::unlimited()).unwrap();
+
+        let graph = GraphId::new();
+        registry.claim_graph(&acme, graph).unwrap();
+
+        assert_eq!(registry.owner(graph), Some(acme.clone()));
+        assert!(registry.authorize_graph(&acme, graph).is_ok());
+        assert!(matches!(
+            registry.authorize_graph(&globex, graph),
+            Err(TenantError::CrossTenant { .. })
+        ));
+        assert!(matches!(
+            registry.claim_graph(&globex, graph),
+            Err(TenantError::CrossTenant { .. })
+        ));
+        assert!(!registry.tenant(&globex).unwrap().owns(graph));
+        assert_eq!(
+            registry.authorize_graph(&acme, GraphId::new()),
+            Err(TenantError::Unclaimed)
+        );
+    }
+
+    #[test]
+    fn signing_keys_are_not_shared() {
+        let registry = TenantRegistry::new();
+        registry
+            .register(TenantId::new("acme"), signer(1), QuotaLimits::unlimited())
+            .unwrap();
+
+        assert!(matches!(
+            registry.register(TenantId::new("globex"), signer(1), QuotaLimits::unlimited()),
+            Err(TenantError::SharedKey { .. })
+        ));
+        assert!(matches!(
+            registry.register(TenantId::new("acme"), signer(3), QuotaLimits::unlimited()),
+            Err(TenantError::Duplicate(_))
+        ));
+    }
+
+    #[test]
+    fn tenant_log_stamps_and_rejects_foreign_events() {
+        let registry = TenantRegistry::new();
+        let acme = registry
+            .register(TenantId::new("acme"), signer(1), QuotaLimits::unlimited())
+            .unwrap();
+
+        acme.log().append(event(None)).unwrap();
+        assert_eq!(acme.log().events()[0].tenant_id.as_ref(), Some(acme.id()));
+        assert!(acme.log().verify_integrity().is_ok());
+
+        assert!(matches!(
+            acme.log().append(event(Some(TenantId::new("globex")))),
+            Err(LogError::TenantMismatch { .. })
+        ));
+        assert_eq!(acme.log().len(), 1);
+    }
+
+    #[test]
+    fn claimed_graphs_share_the_tenant_quota() {
+        let quota = Arc::new(QuotaManager::new());
+        let registry = TenantRegistry::new().with_quota(Arc::clone(&quota));
+        let acme = TenantId::new("acme");
+        registry
+            .register(acme.clone(), signer(1), QuotaLimits::unlimited().with_executions_per_minute(1))
+            .unwrap();
+
+        let (first, second) = (GraphId::new(), GraphId::new());
+        registry.claim_graph(&acme, first).unwrap();
+        registry.claim_graph(&acme, second).unwrap();
+
+        quota.admit_execution(None, first).unwrap();
+        assert!(quota.admit_execution(None, second).is_err());
+        assert_eq!(
+            quota.usage(&QuotaSubject::Tenant(acme)).executions_last_minute,
+            1
+        );
+        // Unclaimed graphs are unaffected by the tenant's limit
+        assert!(quota.admit_execution(None, GraphId::new()).is_ok());
+    }
+
+    /// Stores nothing; hands out fresh IDs
+    struct NullKernel;
+
+    impl GraphManager for NullKernel {
+        fn create_graph(&self, _graph_type: GraphType) -> Result<GraphId, KernelError> {
+            Ok(GraphId::new())
+        }
+
+        fn close_graph(&self, _graph_id: GraphId) -> Result<(), KernelError> {
+            Ok(())
+        }
+
+        fn graph_stats(&self, _graph_id: GraphId) -> Result<GraphStats, KernelError> {
+            Ok(GraphStats {
+                node_count: 0,
+                edge_count: 0,
+                graph_type: GraphType::ProductionDAG,
+                is_closed: false,
+                created_at: 0,
+            })
+        }
+    }
+
+    impl NodeOperations for NullKernel {
+        fn add_node(&self, _graph_id: GraphId, _spec: NodeSpec) -> Result<NodeId, KernelError> {
+            Ok(NodeId::new())
+        }
+
+        fn add_edge(&self, _graph_id: GraphId, _from: NodeId, _to: NodeId) -> Result<(), KernelError> {
+            Ok(())
+        }
+
+        fn deactivate_node(&self, _node_id: NodeId) -> Result<(), KernelError> {
+            Ok(())
+        }
+
+        fn freeze_node(&self, _node_id: NodeId) -> Result<(), KernelError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn scoped_front_end_refuses_foreign_graphs_and_nodes() {
+        let registry = Arc::new(TenantRegistry::new());
+        let (acme, globex) = (TenantId::new("acme"), TenantId::new("globex"));
+        registry.register(acme.clone(), signer(1), QuotaLimits::unlimited()).unwrap();
+        registry.register(globex.clone(), signer(2), QuotaLimits::unlimited()).unwrap();
+        let acme_kernel = TenantScoped::new(NullKernel, Arc::clone(&registry), acme.clone());
+        let globex_kernel = TenantScoped::new(NullKernel, Arc::clone(&registry), globex);
+
+        let graph = acme_kernel.create_graph(GraphType::ProductionDAG).unwrap();
+        assert_eq!(registry.owner(graph), Some(acme));
+        let spec = NodeSpec {
+            directives: crate::types::DirectiveSet {
+                directives: Default::default(),
+            },
+        };
+        let node = acme_kernel.add_node(graph, spec.clone()).unwrap();
+        assert!(acme_kernel.graph_stats(graph).is_ok());
+        assert!(acme_kernel.freeze_node(node).is_ok());
+
+        let cross = |result: Result<_, KernelError>| {
+            matches!(result, Err(KernelError::Tenant(TenantError::CrossTenant { .. })))
+        };
+        assert!(cross(globex_kernel.graph_stats(graph).map(|_| ())));
+        assert!(cross(globex_kernel.close_graph(graph)));
+        assert!(cross(globex_kernel.add_node(graph, spec).map(|_| ())));
+        assert!(matches!(
+            globex_kernel.freeze_node(node),
+            Err(KernelError::Tenant(TenantError::ForeignNode(_)))
+        ));
+
+        let unknown = TenantScoped::new(NullKernel, registry, TenantId::new("initech"));
+        assert!(matches!(
+            unknown.create_graph(GraphType::ProductionDAG),
+            Err(KernelError::Tenant(TenantError::Unknown(_)))
+        ));
+    }
+}
@@ -8,15 +8,24 @@
 //! - All graphs validated before execution
 //! - Zero runtime policy validation
 //! - Token integrity verification
+//! - [`KernelInvariant`]s, re-checked against the graphs and runs after
+//!   every operation
+//!
+//! Time comes from a [`MonotonicClock`] starting at [`SIMULATION_EPOCH`], so
+//! token timestamps depend only on the seed and operation sequence.
 
+use crate::clock::{MonotonicClock, SharedClock};
 use crate::construction::GraphBuilder;
 use crate::error::ExecutionError;
-use crate::executor::Executor;
-use crate::types::v2::{NodeSpecV2, ValidatedGraph};
-use crate::types::{AutonomyLevel, DirectiveSet, GraphType, ResourceCaps};
+use crate::executor::{CheckpointStore, Executor};
+use crate::types::v2::{ExecutionSummary, GraphLifecycle, NodeRunStatus, NodeSpecV2, ValidatedGraph};
+use crate::types::{AutonomyLevel, DirectiveSet, GraphId, GraphType, ResourceCaps};
 use ed25519_dalek::SigningKey;
 use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::sync::Arc;
 
+/// Unix time at which every simulation starts
+pub const SIMULATION_EPOCH: u64 = 1_700_000_000;
 
 /// Simulator configuration
 #[derive(Debug, Clone)]
@@ -31,6 +40,8 @@ pub struct SimulatorConfig {
     pub stop_on_first_violation: bool,
     /// Verify zero runtime policy calls
     pub verify_zero_runtime_policy: bool,
+    /// Reuse outputs of unchanged nodes across executions
+    pub node_cache: bool,
 }
 
 impl Default for SimulatorConfig {
@@ -41,6 +52,7 @@ impl Default for SimulatorConfig {
             total_executions: 1000,
             stop_on_first_violation: true,
             verify_zero_runtime_policy: true,
+            node_cache: true,
         }
     }
 }
@@ -92,6 +104,127 @@ pub enum Violation {
         expected: ExpectedResult,
         actual_error: String,
     },
+    /// Kernel state broke an invariant
+    InvariantBroken {
+        invariant: KernelInvariant,
+        graph_id: GraphId,
+        detail: String,
+    },
+}
+
+/// Property of kernel state that must hold after every operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KernelInvariant {
+    /// Every validated production DAG is acyclic
+    AllProductionGraphsAreAcyclic,
+    /// No token grants more than its node's spec: same node, autonomy at
+    /// most the ceiling, caps within the resource bounds
+    NoTokenElevationOccurred,
+    /// Graph lifecycles match what is running, and each run recorded every
+    /// node once, running none before its predecessors or past a skip
+    AllNodesInValidState,
+}
+
+/// Checks [`KernelInvariant`]s against validated graphs and their runs
+pub struct KernelInvariants;
+
+impl KernelInvariants {
+    /// Check acyclicity and lifecycles, given the graphs an executor is
+    /// running
+    pub fn check_graph_invariants(graphs: &[ValidatedGraph], running: &[GraphId]) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        for graph in graphs {
+            if graph.graph_type() == GraphType::ProductionDAG && !graph.is_acyclic() {
+                violations.push(Violation::InvariantBroken {
+                    invariant: KernelInvariant::AllProductionGraphsAreAcyclic,
+                    graph_id: graph.graph_id(),
+                    detail: format!("{} nodes form a cycle", graph.node_count()),
+                });
+            }
+            let executing = graph.lifecycle() == GraphLifecycle::Executing;
+            if executing != running.contains(&graph.graph_id()) {
+                violations.push(Violation::InvariantBroken {
+                    invariant: KernelInvariant::AllNodesInValidState,
+                    graph_id: graph.graph_id(),
+                    detail: format!("lifecycle {:?} but running: {}", graph.lifecycle(), !executing),
+                });
+            }
+        }
+        violations
+    }
+    
+    /// Check every node's token against its spec
+    pub fn check_autonomy_invariants(graphs: &[ValidatedGraph]) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        for graph in graphs {
+            for (node_id, spec, token) in graph.nodes() {
+                let elevation = if token.node_id != node_id {
+                    Some(format!("token of node {:?} bound to {:?}", node_id, token.node_id))
+                } else if token.autonomy_level > spec.autonomy_ceiling {
+                    Some(format!(
+                        "node {:?} holds {:?} above its ceiling {:?}",
+                        node_id, token.autonomy_level, spec.autonomy_ceiling
+                    ))
+                } else if !token.caps.fits_within(&spec.resource_bounds) {
+                    Some(format!("node {:?} caps exceed its resource bounds", node_id))
+                } else {
+                    None
+                };
+                if let Some(detail) = elevation {
+                    violations.push(Violation::InvariantBroken {
+                        invariant: KernelInvariant::NoTokenElevationOccurred,
+                        graph_id: graph.graph_id(),
+                        detail,
+                    });
+                }
+            }
+        }
+        violations
+    }
+    
+    /// Check the per-node records of a run of `graph`
+    pub fn check_node_states(graph: &ValidatedGraph, summary: &ExecutionSummary) -> Vec<Violation> {
+        let broken = |detail: String| Violation::InvariantBroken {
+            invariant: KernelInvariant::AllNodesInValidState,
+            graph_id: graph.graph_id(),
+            detail,
+        };
+        if summary.graph_id != graph.graph_id() {
+            return vec![broken(format!("summary is for graph {:?}", summary.graph_id))];
+        }
+        
+        let mut violations = Vec::new();
+        let mut seen = std::collections::HashMap::with_capacity(summary.nodes.len());
+        for record in &summary.nodes {
+            if graph.get_node(record.node_id).is_none() {
+                violations.push(broken(format!("record for unknown node {:?}", record.node_id)));
+            } else if seen.insert(record.node_id, record.status).is_some() {
+                violations.push(broken(format!("node {:?} recorded twice", record.node_id)));
+            } else if record.status != NodeRunStatus::Skipped {
+                let blocked = graph
+                    .predecessors(record.node_id)
+                    .find(|p| seen.get(p).map_or(true, |status| *status == NodeRunStatus::Skipped));
+                if let Some(predecessor) = blocked {
+                    violations.push(broken(format!(
+                        "node {:?} ran before or past predecessor {:?}",
+                        record.node_id, predecessor
+                    )));
+                }
+            }
+        }
+        if seen.len() != graph.node_count() {
+            violations.push(broken(format!(
+                "{} of {} nodes recorded",
+                seen.len(),
+                graph.node_count()
+            )));
+        }
+        let ran = summary.nodes.iter().any(|r| r.status != NodeRunStatus::Skipped);
+        if ran && graph.lifecycle() != GraphLifecycle::Completed {
+            violations.push(broken(format!("ran but left {:?}", graph.lifecycle())));
+        }
+        violations
+    }
 }
 
 /// Statistics for simulation
@@ -104,6 +237,8 @@ pub struct SimulatorStats {
     pub executions_succeeded: u64,
     pub executions_failed: u64,
     pub runtime_policy_validation_count: u64, // Should be 0!
+    /// Invariant sweeps run, one after every operation
+    pub invariant_checks: u64,
 }
 
 /// Final report from simulator
@@ -140,6 +275,7 @@ impl SimulatorReport {
         report.push_str(&format!("Executions Failed: {}\n", self.stats.executions_failed));
         report.push_str(&format!("Runtime Policy Validations: {} (SHOULD BE 0)\n", 
             self.stats.runtime_policy_validation_count));
+        report.push_str(&format!("Invariant Checks: {}\n", self.stats.invariant_checks));
         report.push_str(&format!("Violations: {}\n", self.violations.len()));
         report.push_str(&format!("Validated Graphs: {}\n", self.validated_graphs.len()));
         
@@ -168,6 +304,7 @@ pub async fn run_simulator(config: SimulatorConfig) -> SimulatorReport {
     let mut rng = StdRng::seed_from_u64(config.seed);
     let signing_key = SigningKey::generate(&mut rng);
     let verifying_key = signing_key.verifying_key();
+    let clock: SharedClock = Arc::new(MonotonicClock::new(SIMULATION_EPOCH, 1));
     
     let mut stats = SimulatorStats::default();
     let mut violations = Vec::new();
@@ -184,6 +321,7 @@ pub async fn run_simulator(config: SimulatorConfig) -> SimulatorReport {
             &mut builders,
             &mut validated_graphs,
             &signing_key,
+            &clock,
             &mut stats,
         ) {
             Ok(_) => {
@@ -210,37 +348,52 @@ pub async fn run_simulator(config: SimulatorConfig) -> SimulatorReport {
                 }
             }
         }
+        
+        stats.invariant_checks += 1;
+        violations.extend(KernelInvariants::check_graph_invariants(&validated_graphs, &[]));
+        violations.extend(KernelInvariants::check_autonomy_invariants(&validated_graphs));
+        if config.stop_on_first_violation && !violations.is_empty() {
+            break;
+        }
     }
     
     // Phase 2: Test execution
+    let cache = config.node_cache.then(|| Arc::new(CheckpointStore::new()));
     for i in 0..config.total_executions {
-        if validated_graphs.is_empty() {
+        if validated_graphs.is_empty() || (config.stop_on_first_violation && !violations.is_empty()) {
             break;
         }
         
-        let graph_index = (i as usize) % validated_graphs.len();
-        
-        // Take ownership of graph for execution
-        let graph = std::mem::replace(
-            &mut validated_graphs[graph_index],
-            create_dummy_validated_graph(), // Will be replaced back
-        );
+        // Clones share the lifecycle, so a completed graph stays completed
+        let graph = validated_graphs[(i as usize) % validated_graphs.len()].clone();
         
         stats.executions_attempted += 1;
         
-        let executor = Executor::new(verifying_key);
-        match executor.run(graph).await {
-            Ok(_summary) => {
+        let mut executor = Executor::new(verifying_key).with_clock(clock.clone());
+        if let Some(cache) = &cache {
+            executor = executor.with_cache(Arc::clone(cache));
+        }
+        let summary = match executor.run(graph.clone()).await {
+            Ok(summary) => {
                 stats.executions_succeeded += 1;
-                // Put graph back (in real code, we'd need proper ownership handling)
+                summary
             }
             Err(e) => {
                 stats.executions_failed += 1;
-                if matches!(e, ExecutionError::TokenIntegrityFailure) {
+                if matches!(e.error, ExecutionError::TokenIntegrityFailure) {
                     violations.push(Violation::TokenIntegrityFailure);
                 }
+                e.summary
             }
-        }
+        };
+        
+        stats.invariant_checks += 1;
+        violations.extend(KernelInvariants::check_node_states(&graph, &summary));
+        violations.extend(KernelInvariants::check_graph_invariants(
+            &validated_graphs,
+            &executor.running_graphs(),
+        ));
+        violations.extend(KernelInvariants::check_autonomy_invariants(&validated_graphs));
     }
     
     SimulatorReport {
@@ -259,7 +412,7 @@ fn generate_construction_operation(
     builders: &[GraphBuilder],
 ) -> SimulatedOperation {
     let choices = if builders.is_empty() {
-        vec![0, 1] // Start or add node to empty
+        vec![0] // Start; there is nothing to add a node to yet
     } else {
         vec![0, 1, 2, 3] // Start, add node, add edge, validate
     };
@@ -307,11 +460,12 @@ fn execute_construction_operation(
     builders: &mut Vec<GraphBuilder>,
     validated_graphs: &mut Vec<ValidatedGraph>,
     signing_key: &SigningKey,
+    clock: &SharedClock,
     stats: &mut SimulatorStats,
 ) -> Result<(), Box<dyn std::error::Error>> {
     match operation {
         SimulatedOperation::ConstructionStart(graph_type) => {
-            builders.push(GraphBuilder::new(*graph_type));
+            builders.push(GraphBuilder::new(*graph_type).with_clock(clock.clone()));
             Ok(())
         }
         SimulatedOperation::ConstructionAddNode(spec) => {
@@ -370,16 +524,10 @@ fn generate_random_node_spec(rng: &mut StdRng) -> NodeSpecV2 {
             iteration_cap: rng.gen_range(1..1000),
         },
         expansion_type: None,
+        inputs: Vec::new(),
     }
 }
 
-/// Create a dummy validated graph (placeholder for ownership handling)
-fn create_dummy_validated_graph() -> ValidatedGraph {
-    // This is a placeholder - in real code, we'd use Option<ValidatedGraph>
-    // and handle ownership properly
-    unimplemented!("Use Option<ValidatedGraph> for proper ownership handling")
-}
-
 /// Test that construction rejects invalid graphs
 #[test]
 fn test_construction_rejects_invalid_graphs() {
@@ -424,3 +572,44 @@ fn test_zero_runtime_policy_validation() {
     // For now, just verify the invariant is documented
     assert_eq!(POLICY_CHECK_COUNT.load(Ordering::SeqCst), 0);
 }
+
+/// Test that invariants are checked after every operation and catch a
+/// run that skipped recording a node
+#[tokio::test]
+async fn test_invariants_hold_across_simulation() {
+    let report = run_simulator(SimulatorConfig {
+        total_constructions: 200,
+        total_executions: 50,
+        ..SimulatorConfig::default()
+    })
+    .await;
+    assert!(report.passed(), "{}", report.generate_text());
+    assert!(report.stats.executions_attempted > 0);
+    assert_eq!(
+        report.stats.invariant_checks,
+        report.stats.executions_attempted + 200
+    );
+    
+    let mut rng = StdRng::seed_from_u64(7);
+    let signing_key = SigningKey::generate(&mut rng);
+    let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
+    let first = builder.add_node(generate_random_node_spec(&mut rng));
+    let second = builder.add_node(generate_random_node_spec(&mut rng));
+    builder.add_edge(first, second).unwrap();
+    let graph = builder.validate(&signing_key).unwrap();
+    
+    let mut summary = Executor::new(signing_key.verifying_key())
+        .run(graph.clone())
+        .await
+        .unwrap();
+    assert!(KernelInvariants::check_node_states(&graph, &summary).is_empty());
+    assert!(KernelInvariants::check_graph_invariants(std::slice::from_ref(&graph), &[]).is_empty());
+    assert!(KernelInvariants::check_autonomy_invariants(std::slice::from_ref(&graph)).is_empty());
+    
+    summary.nodes.reverse();
+    let violations = KernelInvariants::check_node_states(&graph, &summary);
+    assert!(matches!(
+        violations.as_slice(),
+        [Violation::InvariantBroken { invariant: KernelInvariant::AllNodesInValidState, .. }]
+    ));
+}
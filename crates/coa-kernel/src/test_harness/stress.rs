@@ -0,0 +1,396 @@
+//! Stress workloads (v2.0)
+//!
+//! Generates graphs with realistic shapes (layered DAGs, wide fan-outs,
+//! expansion nodes), validates and executes them end to end with a
+//! [`LoadExecutor`] that consumes each node's declared resources, and
+//! reports latency percentiles and memory high-water marks.
+
+use crate::autonomy::CapabilityToken;
+use crate::construction::GraphBuilder;
+use crate::error::{ExecutionError, ValidationError};
+use crate::executor::{Executor, NodeExecutionResult, NodeExecutor, NodeInputs};
+use crate::expansion::ExpansionBuilder;
+use crate::types::v2::{ExpansionSchema, NodeSpecV2, SubgraphSpec};
+use crate::types::{AutonomyLevel, DirectiveSet, GraphType, NodeId, ResourceCaps};
+use ed25519_dalek::SigningKey;
+use parking_lot::Mutex;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Shape of a generated graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Topology {
+    /// No edges
+    Disconnected,
+    /// Layers of `width` nodes, each depending on up to three nodes of the
+    /// layer before
+    Layered { width: usize },
+    /// Tree where every node fans out to `degree` children
+    FanOut { degree: usize },
+    /// A third each of layered, fan-out and disconnected
+    Mixed,
+}
+
+impl Topology {
+    /// Edges between node indices in `range`, always from lower to higher
+    /// index so the graph stays acyclic
+    fn edges(self, range: Range<usize>, rng: &mut StdRng) -> Vec<(usize, usize)> {
+        let start = range.start;
+        let len = range.len();
+        match self {
+            Topology::Disconnected => Vec::new(),
+            Topology::Layered { width } => {
+                let width = width.max(1);
+                let mut edges = Vec::new();
+                for i in width..len {
+                    let previous_layer = (i / width - 1) * width;
+                    let fan_in = rng.gen_range(1..=width.min(3));
+                    for offset in rand::seq::index::sample(rng, width, fan_in) {
+                        edges.push((start + previous_layer + offset, start + i));
+                    }
+                }
+                edges
+            }
+            Topology::FanOut { degree } => {
+                let degree = degree.max(1);
+                (1..len).map(|i| (start + (i - 1) / degree, start + i)).collect()
+            }
+            Topology::Mixed => {
+                let third = len / 3;
+                let width = ((third as f64).sqrt() as usize).max(1);
+                let mut edges = Topology::Layered { width }.edges(start..start + third, rng);
+                edges.extend(
+                    Topology::FanOut { degree: 8 }.edges(start + third..start + 2 * third, rng),
+                );
+                edges
+            }
+        }
+    }
+}
+
+/// Parameters of a stress run
+#[derive(Debug, Clone)]
+pub struct StressConfig {
+    /// Nodes per generated graph
+    pub nodes: usize,
+    /// Graphs built, validated and executed
+    pub iterations: usize,
+    pub topology: Topology,
+    /// Share of nodes declared as expansion nodes
+    pub expansion_ratio: f64,
+    /// Fraction of each node's declared CPU time spent busy
+    pub time_scale: f64,
+    /// Fraction of each node's declared memory allocated
+    pub memory_scale: f64,
+    pub seed: u64,
+}
+
+impl StressConfig {
+    /// Mixed workload of `iterations` graphs with `nodes` nodes each
+    pub fn new(nodes: usize, iterations: usize) -> Self {
+        Self {
+            nodes,
+            iterations,
+            topology: Topology::Mixed,
+            expansion_ratio: 0.05,
+            time_scale: 0.001,
+            memory_scale: 1.0,
+            seed: 42,
+        }
+    }
+
+    /// Generate graphs of `topology`
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    /// Declare `ratio` of the nodes as expansion nodes
+    pub fn with_expansion_ratio(mut self, ratio: f64) -> Self {
+        self.expansion_ratio = ratio.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Scale the CPU time and memory nodes actually consume
+    pub fn with_load_scale(mut self, time_scale: f64, memory_scale: f64) -> Self {
+        self.time_scale = time_scale.max(0.0);
+        self.memory_scale = memory_scale.max(0.0);
+        self
+    }
+
+    /// Seed the graph generator
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+}
+
+/// Node executor that consumes the resources a token declares
+///
+/// Each node allocates and touches its scaled memory bound and spins for
+/// its scaled CPU time. Allocation high-water marks and per-node latencies
+/// are recorded.
+pub struct LoadExecutor {
+    time_scale: f64,
+    memory_scale: f64,
+    in_use: AtomicU64,
+    peak: AtomicU64,
+    latencies: Mutex<Vec<Duration>>,
+}
+
+impl LoadExecutor {
+    /// Create an executor consuming the given fractions of declared load
+    pub fn new(time_scale: f64, memory_scale: f64) -> Self {
+        Self {
+            time_scale,
+            memory_scale,
+            in_use: AtomicU64::new(0),
+            peak: AtomicU64::new(0),
+            latencies: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Most memory held by nodes at once, in bytes
+    pub fn peak_memory_bytes(&self) -> u64 {
+        self.peak.load(Ordering::Relaxed)
+    }
+
+    /// Drain the recorded node latencies
+    pub fn take_latencies(&self) -> Vec<Duration> {
+        std::mem::take(&mut *self.latencies.lock())
+    }
+}
+
+#[async_trait::async_trait]
+impl NodeExecutor for LoadExecutor {
+    async fn execute_node(
+        &self,
+        node_id: NodeId,
+        token: &CapabilityToken,
+        _inputs: &NodeInputs,
+    ) -> Result<NodeExecutionResult, ExecutionError> {
+        let started = Instant::now();
+
+        let bytes = (token.caps.memory_bytes as f64 * self.memory_scale) as usize;
+        let in_use = self.in_use.fetch_add(bytes as u64, Ordering::Relaxed) + bytes as u64;
+        self.peak.fetch_max(in_use, Ordering::Relaxed);
+        let mut block = vec![0u8; bytes];
+        for page in block.iter_mut().step_by(4096) {
+            *page = 1;
+        }
+
+        let busy = Duration::from_secs_f64(token.caps.cpu_time_ms as f64 * self.time_scale / 1000.0);
+        while started.elapsed() < busy {
+            std::hint::spin_loop();
+        }
+        std::hint::black_box(&block);
+        drop(block);
+        self.in_use.fetch_sub(bytes as u64, Ordering::Relaxed);
+
+        let elapsed = started.elapsed();
+        self.latencies.lock().push(elapsed);
+        Ok(NodeExecutionResult {
+            node_id,
+            success: true,
+            execution_time_ms: elapsed.as_millis() as u64,
+            resource_consumed: token.caps,
+            output: None,
+        })
+    }
+}
+
+/// Latency distribution, in microseconds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LatencyPercentiles {
+    pub p50_us: u64,
+    pub p90_us: u64,
+    pub p99_us: u64,
+    pub max_us: u64,
+}
+
+impl LatencyPercentiles {
+    /// Nearest-rank percentiles of `samples`
+    pub fn from_samples(mut samples: Vec<Duration>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        samples.sort_unstable();
+        let rank = |percentile: usize| {
+            let index = (samples.len() * percentile).div_ceil(100).max(1) - 1;
+            samples[index].as_micros() as u64
+        };
+        Self {
+            p50_us: rank(50),
+            p90_us: rank(90),
+            p99_us: rank(99),
+            max_us: rank(100),
+        }
+    }
+}
+
+/// Expansion schema of generated expansion nodes; accepts any subgraph
+struct StressSchema;
+
+impl ExpansionSchema for StressSchema {
+    fn validate_subgraph(_subgraph: &SubgraphSpec<Self>) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+/// Generated graph, ready to validate
+struct Workload {
+    builder: GraphBuilder,
+    edges: usize,
+    expansion_nodes: usize,
+}
+
+fn generate(config: &StressConfig, rng: &mut StdRng) -> Workload {
+    let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
+    let mut expansion_nodes = 0;
+    let ids: Vec<NodeId> = (0..config.nodes)
+        .map(|_| {
+            let spec = NodeSpecV2 {
+                directives: DirectiveSet {
+                    directives: BTreeMap::new(),
+                },
+                autonomy_ceiling: AutonomyLevel::L3,
+                resource_bounds: ResourceCaps {
+                    cpu_time_ms: rng.gen_range(1..=1000),
+                    memory_bytes: rng.gen_range(64 * 1024..=1024 * 1024),
+                    token_limit: rng.gen_range(1..=1000),
+                    iteration_cap: rng.gen_range(1..=100),
+                },
+                expansion_type: None,
+                inputs: Vec::new(),
+            };
+            if rng.gen_bool(config.expansion_ratio) {
+                expansion_nodes += 1;
+                let budget = spec.resource_bounds;
+                builder.add_expansion_node::<StressSchema>(spec, budget, 1)
+            } else {
+                builder.add_node(spec)
+            }
+        })
+        .collect();
+
+    let edges = config.topology.edges(0..config.nodes, rng);
+    for &(from, to) in &edges {
+        builder
+            .add_edge(ids[from], ids[to])
+            .expect("generated edges are acyclic and unique");
+    }
+
+    Workload {
+        builder,
+        edges: edges.len(),
+        expansion_nodes,
+    }
+}
+
+/// Peak resident set size of this process, where the OS reports it
+fn process_peak_rss() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+/// Build, validate and execute `config.iterations` generated graphs
+pub async fn run_stress(config: &StressConfig) -> super::StressTestReport {
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let signing_key = SigningKey::generate(&mut rng);
+    let load = Arc::new(LoadExecutor::new(config.time_scale, config.memory_scale));
+    let executor = Executor::with_executor(signing_key.verifying_key(), load.clone());
+
+    let mut report = super::StressTestReport {
+        nodes: config.nodes,
+        iterations: config.iterations,
+        ..super::StressTestReport::default()
+    };
+    let mut validate_samples = Vec::with_capacity(config.iterations);
+    let mut execute_samples = Vec::with_capacity(config.iterations);
+
+    for _ in 0..config.iterations {
+        let started = Instant::now();
+        let workload = generate(config, &mut rng);
+        report.edges = workload.edges;
+        report.expansion_nodes = workload.expansion_nodes;
+
+        let validating = Instant::now();
+        let validated = workload.builder.validate(&signing_key);
+        let validate_time = validating.elapsed();
+        validate_samples.push(validate_time);
+        report.validation_time_ms += validate_time.as_millis() as u64;
+        report.construction_time_ms += started.elapsed().as_millis() as u64;
+        let Ok(graph) = validated else {
+            report.violations += 1;
+            continue;
+        };
+
+        let executing = Instant::now();
+        let outcome = executor.run(graph).await;
+        let execute_time = executing.elapsed();
+        execute_samples.push(execute_time);
+        report.execution_time_ms += execute_time.as_millis() as u64;
+        if outcome.is_err() {
+            report.violations += 1;
+        }
+    }
+
+    report.validate_latency = LatencyPercentiles::from_samples(validate_samples);
+    report.execute_latency = LatencyPercentiles::from_samples(execute_samples);
+    report.node_latency = LatencyPercentiles::from_samples(load.take_latencies());
+    report.peak_node_memory_bytes = load.peak_memory_bytes();
+    report.peak_rss_bytes = process_peak_rss();
+    report.success = report.violations == 0;
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topologies_generate_acyclic_edges() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let layered = Topology::Layered { width: 4 }.edges(0..12, &mut rng);
+        assert!(layered.iter().all(|&(from, to)| from / 4 + 1 == to / 4));
+        assert!((8..12).all(|to| layered.iter().any(|&(_, t)| t == to)));
+
+        let fan_out = Topology::FanOut { degree: 3 }.edges(0..10, &mut rng);
+        assert_eq!(fan_out.len(), 9);
+        assert_eq!(fan_out.iter().filter(|&&(from, _)| from == 0).count(), 3);
+
+        let mixed = Topology::Mixed.edges(0..30, &mut rng);
+        assert!(mixed.iter().all(|&(from, to)| from < to && to < 20));
+    }
+
+    #[test]
+    fn test_latency_percentiles_use_nearest_rank() {
+        let samples = (1..=100).map(Duration::from_micros).collect();
+        let percentiles = LatencyPercentiles::from_samples(samples);
+        assert_eq!(percentiles.p50_us, 50);
+        assert_eq!(percentiles.p99_us, 99);
+        assert_eq!(percentiles.max_us, 100);
+        assert_eq!(LatencyPercentiles::from_samples(Vec::new()), LatencyPercentiles::default());
+    }
+
+    #[tokio::test]
+    async fn test_stress_run_validates_and_executes() {
+        let config = StressConfig::new(60, 3)
+            .with_expansion_ratio(0.2)
+            .with_load_scale(0.0, 0.01);
+        let report = run_stress(&config).await;
+
+        assert!(report.success, "{report:?}");
+        assert!(report.edges > 0);
+        assert!(report.expansion_nodes > 0);
+        assert!(report.node_latency.max_us >= report.node_latency.p50_us);
+        assert!(report.peak_node_memory_bytes > 0);
+    }
+}
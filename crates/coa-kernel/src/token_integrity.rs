@@ -7,12 +7,64 @@
 //! - Cryptographic signature verification
 //! - Token expiration
 //! - Node binding (token is for the correct node)
+//!
+//! Signatures verified once are remembered in a [`VerifiedTokenCache`], so
+//! repeated checks cost a lookup; [`TokenIntegrity::verify_batch`] fills it
+//! for a whole graph with one ed25519 batch verification.
 
 use crate::autonomy::CapabilityToken;
+use crate::clock::{Clock, SystemClock};
 use crate::error::ExecutionError;
 use crate::types::v2::IntegrityVerification;
 use crate::types::NodeId;
-use ed25519_dalek::VerifyingKey;
+use ed25519_dalek::{Signature, VerifyingKey};
+use parking_lot::RwLock;
+use std::collections::HashSet;
+
+/// Token signatures already verified
+///
+/// Entries are keyed by the verifying key and the
+/// [token hash](CapabilityToken::token_hash), so one cache can be shared
+/// between verifiers and runs. Expiry and binding are never cached.
+#[derive(Debug, Default)]
+pub struct VerifiedTokenCache {
+    verified: RwLock<HashSet<([u8; 32], [u8; 32])>>,
+}
+
+impl VerifiedTokenCache {
+    /// Create an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Check if `token`'s signature was verified under `verifying_key`
+    pub fn contains(&self, token: &CapabilityToken, verifying_key: &VerifyingKey) -> bool {
+        self.verified.read().contains(&Self::key(token, verifying_key))
+    }
+    
+    /// Number of cached tokens
+    pub fn len(&self) -> usize {
+        self.verified.read().len()
+    }
+    
+    /// Check if nothing is cached
+    pub fn is_empty(&self) -> bool {
+        self.verified.read().is_empty()
+    }
+    
+    /// Forget every verified token
+    pub fn clear(&self) {
+        self.verified.write().clear();
+    }
+    
+    fn insert(&self, token: &CapabilityToken, verifying_key: &VerifyingKey) {
+        self.verified.write().insert(Self::key(token, verifying_key));
+    }
+    
+    fn key(token: &CapabilityToken, verifying_key: &VerifyingKey) -> ([u8; 32], [u8; 32]) {
+        (verifying_key.to_bytes(), token.token_hash())
+    }
+}
 
 /// Token integrity verifier
 ///
@@ -34,12 +86,58 @@ impl TokenIntegrity {
     pub fn verify_integrity(
         token: &CapabilityToken,
         verifying_key: &VerifyingKey,
+    ) -> Result<IntegrityVerification, ExecutionError> {
+        Self::verify_integrity_with_clock(token, verifying_key, &SystemClock)
+    }
+    
+    /// Verify token integrity, checking expiry against `clock`
+    pub fn verify_integrity_with_clock(
+        token: &CapabilityToken,
+        verifying_key: &VerifyingKey,
+        clock: &dyn Clock,
     ) -> Result<IntegrityVerification, ExecutionError> {
         // Cryptographic signature check
-        let signature_valid = token.verify(verifying_key);
+        Self::check_integrity(token, token.verify(verifying_key), clock)
+    }
+    
+    /// Batch-verify the signatures of `tokens`, caching them on success
+    ///
+    /// Tokens already in `cache` are skipped. A batch fails as a whole
+    /// without caching anything; verify the tokens one by one to find the
+    /// culprit. Returns the number of newly verified tokens.
+    pub fn verify_batch<'a>(
+        tokens: impl IntoIterator<Item = &'a CapabilityToken>,
+        verifying_key: &VerifyingKey,
+        cache: &VerifiedTokenCache,
+    ) -> Result<usize, ExecutionError> {
+        let pending: Vec<_> = tokens
+            .into_iter()
+            .filter(|token| !cache.contains(token, verifying_key))
+            .collect();
+        if pending.is_empty() {
+            return Ok(0);
+        }
         
+        let messages: Vec<Vec<u8>> = pending.iter().map(|token| token.message()).collect();
+        let messages: Vec<&[u8]> = messages.iter().map(Vec::as_slice).collect();
+        let signatures: Vec<Signature> = pending.iter().map(|token| token.signature).collect();
+        let keys = vec![*verifying_key; pending.len()];
+        ed25519_dalek::verify_batch(&messages, &signatures, &keys)
+            .map_err(|_| ExecutionError::TokenIntegrityFailure)?;
+        
+        for token in &pending {
+            cache.insert(token, verifying_key);
+        }
+        Ok(pending.len())
+    }
+    
+    fn check_integrity(
+        token: &CapabilityToken,
+        signature_valid: bool,
+        clock: &dyn Clock,
+    ) -> Result<IntegrityVerification, ExecutionError> {
         // Expiration check
-        let not_expired = !token.is_expired();
+        let not_expired = !token.is_expired_at(clock.now());
         
         if signature_valid && not_expired {
             Ok(IntegrityVerification {
@@ -90,9 +188,66 @@ impl TokenIntegrity {
         verifying_key: &VerifyingKey,
         expected_node_id: NodeId,
         operation: Option<&str>,
+    ) -> Result<IntegrityVerification, ExecutionError> {
+        Self::verify_full_with_clock(token, verifying_key, expected_node_id, operation, &SystemClock)
+    }
+    
+    /// Full verification, checking expiry against `clock`
+    pub fn verify_full_with_clock(
+        token: &CapabilityToken,
+        verifying_key: &VerifyingKey,
+        expected_node_id: NodeId,
+        operation: Option<&str>,
+        clock: &dyn Clock,
+    ) -> Result<IntegrityVerification, ExecutionError> {
+        let signature_valid = token.verify(verifying_key);
+        Self::observe(Self::check_full(token, signature_valid, expected_node_id, operation, clock))
+    }
+    
+    /// Full verification, looking the signature up in `cache` first
+    ///
+    /// A signature verified here is added to the cache.
+    pub fn verify_full_cached(
+        token: &CapabilityToken,
+        verifying_key: &VerifyingKey,
+        expected_node_id: NodeId,
+        operation: Option<&str>,
+        clock: &dyn Clock,
+        cache: &VerifiedTokenCache,
+    ) -> Result<IntegrityVerification, ExecutionError> {
+        let signature_valid = cache.contains(token, verifying_key) || {
+            let valid = token.verify(verifying_key);
+            if valid {
+                cache.insert(token, verifying_key);
+            }
+            valid
+        };
+        Self::observe(Self::check_full(token, signature_valid, expected_node_id, operation, clock))
+    }
+    
+    fn observe(
+        result: Result<IntegrityVerification, ExecutionError>,
+    ) -> Result<IntegrityVerification, ExecutionError> {
+        #[cfg(feature = "metrics")]
+        {
+            let metrics = crate::metrics::global();
+            metrics.token_verifications.inc();
+            if result.is_err() {
+                metrics.token_verification_failures.inc();
+            }
+        }
+        result
+    }
+    
+    fn check_full(
+        token: &CapabilityToken,
+        signature_valid: bool,
+        expected_node_id: NodeId,
+        operation: Option<&str>,
+        clock: &dyn Clock,
     ) -> Result<IntegrityVerification, ExecutionError> {
         // First verify basic integrity
-        let result = Self::verify_integrity(token, verifying_key)?;
+        let result = Self::check_integrity(token, signature_valid, clock)?;
         
         // Verify node binding
         Self::verify_node_binding(token, expected_node_id)?;
@@ -171,6 +326,22 @@ mod tests {
         assert!(matches!(result, Err(ExecutionError::TokenExpired)));
     }
 
+    #[test]
+    fn test_expiry_follows_injected_clock() {
+        use crate::clock::ManualClock;
+        
+        let signing_key = SigningKey::generate(&mut OsRng);
+        let verifying_key = signing_key.verifying_key();
+        let node_id = NodeId::new();
+        let token = create_test_token(&signing_key, node_id, 1_000);
+        let clock = ManualClock::new(1_000);
+        
+        assert!(TokenIntegrity::verify_integrity_with_clock(&token, &verifying_key, &clock).is_ok());
+        clock.advance(1);
+        let result = TokenIntegrity::verify_full_with_clock(&token, &verifying_key, node_id, None, &clock);
+        assert!(matches!(result, Err(ExecutionError::TokenExpired)));
+    }
+
     #[test]
     fn test_node_binding_verification() {
         let mut csprng = OsRng;
@@ -212,4 +383,42 @@ mod tests {
         let result = TokenIntegrity::verify_full(&token, &verifying_key, node_id, Some("wrong_operation"));
         assert!(matches!(result, Err(ExecutionError::TokenBindingFailure)));
     }
+
+    #[test]
+    fn test_batch_verification_fills_cache() {
+        use crate::clock::ManualClock;
+        
+        let signing_key = SigningKey::generate(&mut OsRng);
+        let verifying_key = signing_key.verifying_key();
+        let nodes: Vec<_> = (0..4).map(|_| NodeId::new()).collect();
+        let tokens: Vec<_> = nodes
+            .iter()
+            .map(|&id| create_test_token(&signing_key, id, 1_000))
+            .collect();
+        let cache = VerifiedTokenCache::new();
+        
+        // A forged token fails the whole batch and caches nothing
+        let mut forged = tokens.clone();
+        forged[2].caps.cpu_time_ms += 1;
+        let result = TokenIntegrity::verify_batch(&forged, &verifying_key, &cache);
+        assert!(matches!(result, Err(ExecutionError::TokenIntegrityFailure)));
+        assert!(cache.is_empty());
+        
+        assert_eq!(TokenIntegrity::verify_batch(&tokens, &verifying_key, &cache).unwrap(), 4);
+        assert_eq!(TokenIntegrity::verify_batch(&tokens, &verifying_key, &cache).unwrap(), 0);
+        assert!(tokens.iter().all(|token| cache.contains(token, &verifying_key)));
+        let other_key = SigningKey::generate(&mut OsRng).verifying_key();
+        assert!(!cache.contains(&tokens[0], &other_key));
+        
+        // Cached signatures still get expiry and binding checks
+        let clock = ManualClock::new(1_000);
+        let check = |token, node| {
+            TokenIntegrity::verify_full_cached(token, &verifying_key, node, None, &clock, &cache)
+        };
+        assert!(check(&tokens[0], nodes[0]).is_ok());
+        assert!(matches!(check(&tokens[0], nodes[1]), Err(ExecutionError::TokenBindingFailure)));
+        assert!(matches!(check(&forged[2], nodes[2]), Err(ExecutionError::TokenIntegrityFailure)));
+        clock.advance(1);
+        assert!(matches!(check(&tokens[0], nodes[0]), Err(ExecutionError::TokenExpired)));
+    }
 }
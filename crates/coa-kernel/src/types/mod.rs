@@ -20,29 +20,34 @@ impl GraphId {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum AutonomyLevel {
-    L0,
-    L1,
-    L2,
-    L3,
-    L4,
-    L5,
+/// Identifier of the agent on whose behalf a graph is built and run
+///
+/// Opaque to the kernel; orchestrators use their own ID format.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct AgentId(pub String);
+
+impl AgentId {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
 }
 
-impl AutonomyLevel {
-    pub fn as_u8(self) -> u8 {
-        match self {
-            AutonomyLevel::L0 => 0,
-            AutonomyLevel::L1 => 1,
-            AutonomyLevel::L2 => 2,
-            AutonomyLevel::L3 => 3,
-            AutonomyLevel::L4 => 4,
-            AutonomyLevel::L5 => 5,
-        }
+impl std::fmt::Display for AgentId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
     }
 }
 
+pub use coa_artifact::{CorrelationId, HashAlgorithm, TenantId};
+
+/// Algorithm the kernel hashes events and directive profiles with
+///
+/// The process-wide [`HashAlgorithm::install`]ed algorithm, else SHA-256.
+pub fn kernel_hash_algorithm() -> HashAlgorithm {
+    HashAlgorithm::installed().unwrap_or(HashAlgorithm::Sha256)
+}
+pub use coa_autonomy::{AutonomyCeiling, AutonomyLevel, AutonomyParseError};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum GraphType {
     ProductionDAG,
@@ -69,6 +74,16 @@ pub struct ResourceCaps {
     pub iteration_cap: u64,
 }
 
+impl ResourceCaps {
+    /// Check if every field is within `caps`
+    pub fn fits_within(&self, caps: &ResourceCaps) -> bool {
+        self.cpu_time_ms <= caps.cpu_time_ms
+            && self.memory_bytes <= caps.memory_bytes
+            && self.token_limit <= caps.token_limit
+            && self.iteration_cap <= caps.iteration_cap
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DirectiveSet {
     pub directives: BTreeMap<String, serde_json::Value>,
@@ -111,46 +126,28 @@ pub type Timestamp = u64;
 
 /// Current unix timestamp
 pub fn now_timestamp() -> Timestamp {
-    std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs()
+    crate::clock::Clock::now(&crate::clock::SystemClock)
 }
 
 /// Token expiration duration (in seconds)
 pub const DEFAULT_TOKEN_EXPIRY_SECS: u64 = 3600; // 1 hour
 
-/// Maximum autonomy ceiling - can be configured per deployment
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub struct AutonomyCeiling {
-    pub max_level: AutonomyLevel,
-}
-
-impl Default for AutonomyCeiling {
-    fn default() -> Self {
-        Self {
-            max_level: AutonomyLevel::L5,
-        }
-    }
-}
-
-impl AutonomyCeiling {
-    pub fn check(&self, level: AutonomyLevel) -> bool {
-        level.as_u8() <= self.max_level.as_u8()
-    }
-}
-
 /// v2.0 Types module
 pub mod v2;
 
 // Re-export commonly used v2 types
 pub use v2::{
+    BoundaryPort,
     ExecutionSummary,
     ExpansionSchema,
     ExpansionState,
     ExpansionType,
+    GraphLifecycle,
     IntegrityVerification,
+    NodeRecord,
+    NodeRunStatus,
     NodeSpecV2,
+    SkipReason,
     SubgraphSpec,
     SystemLimits,
     TypeIdWrapper,
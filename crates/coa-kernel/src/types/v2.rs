@@ -4,12 +4,17 @@
 //! the two-phase architecture: Construction Phase → Execution Phase.
 
 use crate::autonomy::CapabilityToken;
-use crate::types::{AutonomyLevel, DirectiveSet, GraphId, GraphType, NodeId, ResourceCaps};
+use crate::clock::Clock;
+use crate::error::{ExecutionError, IllegalGraphTransition};
+use crate::types::{
+    AutonomyLevel, DirectiveSet, GraphId, GraphType, NodeId, ResourceCaps, Timestamp,
+};
 use ed25519_dalek::Signature;
 use serde::{Deserialize, Serialize};
 use std::any::TypeId;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::marker::PhantomData;
+use std::sync::Arc;
 
 /// v2.0 Node Specification with encoded policy constraints
 ///
@@ -28,6 +33,10 @@ pub struct NodeSpecV2 {
     
     /// Optional expansion type for dynamic graph construction
     pub expansion_type: Option<ExpansionType>,
+    
+    /// Payload types this node consumes along data edges
+    #[serde(default)]
+    pub inputs: Vec<String>,
 }
 
 impl NodeSpecV2 {
@@ -42,6 +51,7 @@ impl NodeSpecV2 {
             autonomy_ceiling,
             resource_bounds,
             expansion_type: None,
+            inputs: Vec::new(),
         }
     }
     
@@ -57,6 +67,86 @@ impl NodeSpecV2 {
             autonomy_ceiling,
             resource_bounds,
             expansion_type: Some(expansion),
+            inputs: Vec::new(),
+        }
+    }
+    
+    /// Declare that this node consumes a `payload_type` input
+    pub fn with_input(mut self, payload_type: impl Into<String>) -> Self {
+        self.inputs.push(payload_type.into());
+        self
+    }
+    
+    /// Check if this node declares a `payload_type` input
+    pub fn consumes(&self, payload_type: &str) -> bool {
+        self.inputs.iter().any(|input| input == payload_type)
+    }
+}
+
+/// What an edge between two nodes means
+///
+/// Untyped edges are ordering-only.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum EdgeKind {
+    /// Target consumes the source's output, declared as `payload_type`
+    DataDependency { payload_type: String },
+    /// Target starts after the source finishes; nothing is passed
+    #[default]
+    OrderingOnly,
+    /// Target runs only if the source's outcome satisfies `guard`
+    ControlFlow { guard: EdgeGuard },
+}
+
+impl EdgeKind {
+    /// Data dependency carrying `payload_type`
+    pub fn data(payload_type: impl Into<String>) -> Self {
+        Self::DataDependency {
+            payload_type: payload_type.into(),
+        }
+    }
+    
+    /// Control-flow edge taken when `guard` holds
+    pub fn control(guard: EdgeGuard) -> Self {
+        Self::ControlFlow { guard }
+    }
+    
+    /// Payload type carried by a data dependency
+    pub fn payload_type(&self) -> Option<&str> {
+        match self {
+            Self::DataDependency { payload_type } => Some(payload_type),
+            _ => None,
+        }
+    }
+    
+    /// Bytes bound into the validation hash
+    pub(crate) fn digest_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::DataDependency { payload_type } => {
+                let mut bytes = vec![0];
+                bytes.extend_from_slice(payload_type.as_bytes());
+                bytes
+            }
+            Self::OrderingOnly => vec![1],
+            Self::ControlFlow { guard } => vec![2, *guard as u8],
+        }
+    }
+}
+
+/// Condition on a control-flow edge's source outcome
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EdgeGuard {
+    /// Source ran and reported success
+    OnSuccess,
+    /// Source ran and reported failure
+    OnFailure,
+}
+
+impl EdgeGuard {
+    /// Check if a source that ended in `status` satisfies the guard
+    pub fn admits(self, status: NodeRunStatus) -> bool {
+        match self {
+            Self::OnSuccess => status == NodeRunStatus::Succeeded,
+            Self::OnFailure => status == NodeRunStatus::Failed,
         }
     }
 }
@@ -113,16 +203,84 @@ pub struct ValidationToken {
 }
 
 impl ValidationToken {
-    /// Check if the token has expired
-    pub fn is_expired(&self) -> bool {
-        if self.expires_at == 0 {
-            return false;
+    /// Check if the token has expired at `clock`'s current time
+    pub fn is_expired(&self, clock: &dyn Clock) -> bool {
+        self.is_expired_at(clock.now())
+    }
+
+    /// Check if the token has expired at `now`
+    pub fn is_expired_at(&self, now: Timestamp) -> bool {
+        self.expires_at != 0 && now > self.expires_at
+    }
+}
+
+/// Lifecycle state of a [`ValidatedGraph`]
+///
+/// A graph runs at most once: `Active -> Executing -> Completed`, or
+/// `Aborted` from either of the first two. Completed and aborted graphs
+/// can be archived.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GraphLifecycle {
+    /// Validated and not yet run
+    Active,
+    /// Running
+    Executing,
+    /// Run finished, successfully or not
+    Completed,
+    /// Stopped before or during its run
+    Aborted,
+    /// Retired after completing or aborting
+    Archived,
+}
+
+impl GraphLifecycle {
+    /// States reachable from `self` in one step
+    pub fn allowed_transitions(self) -> &'static [GraphLifecycle] {
+        use GraphLifecycle::*;
+        match self {
+            Active => &[Executing, Aborted],
+            Executing => &[Completed, Aborted],
+            Completed | Aborted => &[Archived],
+            Archived => &[],
         }
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
-        now > self.expires_at
+    }
+    
+    /// Check if `self -> to` is allowed
+    pub fn can_transition_to(self, to: GraphLifecycle) -> bool {
+        self.allowed_transitions().contains(&to)
+    }
+    
+    /// Check if the graph may still execute nodes
+    pub fn is_runnable(self) -> bool {
+        matches!(self, GraphLifecycle::Active | GraphLifecycle::Executing)
+    }
+}
+
+/// Lifecycle state shared by the clones of a graph
+#[derive(Debug)]
+pub(crate) struct LifecycleCell(parking_lot::Mutex<GraphLifecycle>);
+
+impl LifecycleCell {
+    pub(crate) fn new() -> Self {
+        Self(parking_lot::Mutex::new(GraphLifecycle::Active))
+    }
+    
+    pub(crate) fn get(&self) -> GraphLifecycle {
+        *self.0.lock()
+    }
+    
+    /// Move to `to`, returning the previous state
+    pub(crate) fn transition(
+        &self,
+        to: GraphLifecycle,
+    ) -> Result<GraphLifecycle, IllegalGraphTransition> {
+        let mut state = self.0.lock();
+        let from = *state;
+        if !from.can_transition_to(to) {
+            return Err(IllegalGraphTransition { from, to });
+        }
+        *state = to;
+        Ok(from)
     }
 }
 
@@ -130,14 +288,100 @@ impl ValidationToken {
 ///
 /// This type can ONLY be constructed through `GraphBuilder::validate()`.
 /// The private fields ensure type-level sealing.
+///
+/// Nodes and edges live in an immutable, shared [`GraphIndex`]: lookups
+/// borrow from it and clones of the graph share it. Clones also share the
+/// graph's [`GraphLifecycle`], so running one clone closes them all.
 #[derive(Debug, Clone)]
 pub struct ValidatedGraph {
     pub(crate) graph_id: GraphId,
     pub(crate) validation_token: ValidationToken,
     pub(crate) graph_type: GraphType,
-    pub(crate) nodes: HashMap<NodeId, NodeSpecV2>,
-    pub(crate) edges: Vec<(NodeId, NodeId)>,
-    pub(crate) node_tokens: HashMap<NodeId, CapabilityToken>,
+    pub(crate) index: Arc<GraphIndex>,
+    pub(crate) budget_envelope: Option<ResourceCaps>,
+    pub(crate) lifecycle: Arc<LifecycleCell>,
+}
+
+/// Node of a [`ValidatedGraph`] with the token issued for it
+#[derive(Debug)]
+pub struct GraphNode {
+    pub node_id: NodeId,
+    pub spec: NodeSpecV2,
+    pub token: CapabilityToken,
+}
+
+/// Nodes and edges of a [`ValidatedGraph`], indexed for lookup
+#[derive(Debug)]
+pub(crate) struct GraphIndex {
+    /// Sorted by node ID
+    nodes: Vec<GraphNode>,
+    positions: HashMap<NodeId, usize>,
+    edges: Vec<(NodeId, NodeId)>,
+    edge_kinds: HashMap<(NodeId, NodeId), EdgeKind>,
+    /// Positions in `edges`, per target and per source
+    incoming: HashMap<NodeId, Vec<usize>>,
+    outgoing: HashMap<NodeId, Vec<usize>>,
+}
+
+impl GraphIndex {
+    /// Index nodes with their tokens
+    ///
+    /// Every node must have a token; the validator issues one per node.
+    pub(crate) fn new(
+        nodes: HashMap<NodeId, NodeSpecV2>,
+        edges: Vec<(NodeId, NodeId)>,
+        edge_kinds: HashMap<(NodeId, NodeId), EdgeKind>,
+        mut node_tokens: HashMap<NodeId, CapabilityToken>,
+    ) -> Self {
+        let mut nodes: Vec<GraphNode> = nodes
+            .into_iter()
+            .map(|(node_id, spec)| GraphNode {
+                node_id,
+                spec,
+                token: node_tokens
+                    .remove(&node_id)
+                    .expect("validator issues a token for every node"),
+            })
+            .collect();
+        nodes.sort_by_key(|node| node.node_id);
+        let positions = nodes
+            .iter()
+            .enumerate()
+            .map(|(position, node)| (node.node_id, position))
+            .collect();
+        
+        let mut incoming: HashMap<NodeId, Vec<usize>> = HashMap::new();
+        let mut outgoing: HashMap<NodeId, Vec<usize>> = HashMap::new();
+        for (position, &(from, to)) in edges.iter().enumerate() {
+            outgoing.entry(from).or_default().push(position);
+            incoming.entry(to).or_default().push(position);
+        }
+        
+        Self {
+            nodes,
+            positions,
+            edges,
+            edge_kinds,
+            incoming,
+            outgoing,
+        }
+    }
+    
+    fn node(&self, node_id: NodeId) -> Option<&GraphNode> {
+        self.positions.get(&node_id).map(|&position| &self.nodes[position])
+    }
+    
+    fn edges_at<'a>(
+        &'a self,
+        positions: &'a HashMap<NodeId, Vec<usize>>,
+        node_id: NodeId,
+    ) -> impl Iterator<Item = (NodeId, NodeId)> + 'a {
+        positions
+            .get(&node_id)
+            .into_iter()
+            .flatten()
+            .map(|&position| self.edges[position])
+    }
 }
 
 impl ValidatedGraph {
@@ -158,37 +402,118 @@ impl ValidatedGraph {
     
     /// Get node count
     pub fn node_count(&self) -> usize {
-        self.nodes.len()
+        self.index.nodes.len()
     }
     
     /// Get edge count
     pub fn edge_count(&self) -> usize {
-        self.edges.len()
+        self.index.edges.len()
     }
     
     /// Get a node's capability token
     pub fn get_node_token(&self, node_id: NodeId) -> Option<&CapabilityToken> {
-        self.node_tokens.get(&node_id)
+        self.index.node(node_id).map(|node| &node.token)
     }
     
-    /// Get all node IDs
-    pub fn node_ids(&self) -> impl Iterator<Item = NodeId> + '_ {
-        self.nodes.keys().copied()
+    /// Get all node IDs, in ID order
+    pub fn node_ids(&self) -> impl ExactSizeIterator<Item = NodeId> + '_ {
+        self.index.nodes.iter().map(|node| node.node_id)
     }
     
     /// Get a node's specification
     pub fn get_node_spec(&self, node_id: NodeId) -> Option<&NodeSpecV2> {
-        self.nodes.get(&node_id)
+        self.index.node(node_id).map(|node| &node.spec)
+    }
+    
+    /// Get a node with its specification and token
+    pub fn get_node(&self, node_id: NodeId) -> Option<&GraphNode> {
+        self.index.node(node_id)
+    }
+    
+    /// All nodes as (id, spec, token), in ID order
+    pub fn nodes(
+        &self,
+    ) -> impl ExactSizeIterator<Item = (NodeId, &NodeSpecV2, &CapabilityToken)> + '_ {
+        self.index
+            .nodes
+            .iter()
+            .map(|node| (node.node_id, &node.spec, &node.token))
+    }
+    
+    /// Get all edges as (from, to) pairs
+    pub fn edges(&self) -> &[(NodeId, NodeId)] {
+        &self.index.edges
+    }
+    
+    /// Run budget reserved for this graph at construction, if any
+    pub fn budget_envelope(&self) -> Option<&ResourceCaps> {
+        self.budget_envelope.as_ref()
+    }
+    
+    /// Get the kind of the `from → to` edge
+    pub fn edge_kind(&self, from: NodeId, to: NodeId) -> &EdgeKind {
+        self.index.edge_kinds.get(&(from, to)).unwrap_or(&EdgeKind::OrderingOnly)
+    }
+    
+    /// Edges into `node_id`, as (source, kind) pairs
+    pub fn incoming(&self, node_id: NodeId) -> impl Iterator<Item = (NodeId, &EdgeKind)> + '_ {
+        self.index
+            .edges_at(&self.index.incoming, node_id)
+            .map(move |(from, to)| (from, self.edge_kind(from, to)))
+    }
+    
+    /// Edges out of `node_id`, as (target, kind) pairs
+    pub fn outgoing(&self, node_id: NodeId) -> impl Iterator<Item = (NodeId, &EdgeKind)> + '_ {
+        self.index
+            .edges_at(&self.index.outgoing, node_id)
+            .map(move |(from, to)| (to, self.edge_kind(from, to)))
+    }
+    
+    /// Node IDs in dependency order, ties broken by ID
+    ///
+    /// Nodes on a cycle (possible in sandbox graphs) follow the rest.
+    pub fn execution_order(&self) -> Vec<NodeId> {
+        let mut in_degree: BTreeMap<NodeId, usize> =
+            self.node_ids().map(|node_id| (node_id, 0)).collect();
+        for (_, to) in self.edges() {
+            *in_degree.entry(*to).or_default() += 1;
+        }
+        
+        let mut ready: BTreeSet<NodeId> = in_degree
+            .iter()
+            .filter(|(_, &degree)| degree == 0)
+            .map(|(&node_id, _)| node_id)
+            .collect();
+        let mut order = Vec::with_capacity(self.node_count());
+        while let Some(node_id) = ready.pop_first() {
+            order.push(node_id);
+            in_degree.remove(&node_id);
+            for (_, to) in self.index.edges_at(&self.index.outgoing, node_id) {
+                if let Some(degree) = in_degree.get_mut(&to) {
+                    *degree -= 1;
+                    if *degree == 0 {
+                        ready.insert(to);
+                    }
+                }
+            }
+        }
+        order.extend(in_degree.into_keys());
+        order
     }
 }
 
 /// Subgraph specification for expansion
 ///
 /// The type parameter `T` ensures schema compliance at compile time.
+///
+/// Boundary ports splice the subgraph into the parent's data flow: inputs
+/// take over edges into the expansion node, outputs edges out of it.
 #[derive(Debug, Clone)]
 pub struct SubgraphSpec<T: ExpansionSchema> {
     pub nodes: Vec<NodeSpecV2>,
     pub edges: Vec<(NodeId, NodeId)>,
+    pub inputs: Vec<BoundaryPort>,
+    pub outputs: Vec<BoundaryPort>,
     pub _phantom: PhantomData<T>,
 }
 
@@ -198,9 +523,32 @@ impl<T: ExpansionSchema> SubgraphSpec<T> {
         Self {
             nodes,
             edges,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
             _phantom: PhantomData,
         }
     }
+    
+    /// Route an edge of `kind` into the expansion node to `nodes[node]`
+    pub fn with_input(mut self, node: usize, kind: EdgeKind) -> Self {
+        self.inputs.push(BoundaryPort { node, kind });
+        self
+    }
+    
+    /// Let `nodes[node]` feed an edge of `kind` out of the expansion node
+    pub fn with_output(mut self, node: usize, kind: EdgeKind) -> Self {
+        self.outputs.push(BoundaryPort { node, kind });
+        self
+    }
+}
+
+/// Connection between a subgraph node and an edge of the expansion node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoundaryPort {
+    /// Index into [`SubgraphSpec::nodes`]
+    pub node: usize,
+    /// Kind of the parent edge the port stands for
+    pub kind: EdgeKind,
 }
 
 /// Trait for expansion schemas
@@ -262,6 +610,104 @@ pub struct ExecutionSummary {
     pub nodes_executed: usize,
     pub execution_time_ms: u64,
     pub resource_consumed: ResourceCaps,
+    /// Per-node records, in execution order
+    pub nodes: Vec<NodeRecord>,
+}
+
+impl ExecutionSummary {
+    /// Record for a node, if it is part of the graph
+    pub fn node(&self, node_id: NodeId) -> Option<&NodeRecord> {
+        self.nodes.iter().find(|record| record.node_id == node_id)
+    }
+
+    /// Nodes that ran and failed
+    pub fn failed(&self) -> impl Iterator<Item = &NodeRecord> {
+        self.nodes
+            .iter()
+            .filter(|record| record.status == NodeRunStatus::Failed)
+    }
+
+    /// Nodes whose output was reused from a recorded run
+    pub fn cached(&self) -> impl Iterator<Item = &NodeRecord> {
+        self.nodes.iter().filter(|record| record.cached)
+    }
+
+    /// Nodes that never ran because execution stopped first
+    pub fn skipped(&self) -> impl Iterator<Item = &NodeRecord> {
+        self.nodes
+            .iter()
+            .filter(|record| record.status == NodeRunStatus::Skipped)
+    }
+}
+
+/// Outcome of one node within a graph run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NodeRunStatus {
+    /// Node ran and reported success
+    Succeeded,
+    /// Node reported failure or its execution errored
+    Failed,
+    /// Node was not reached, or a guard or missing upstream output kept
+    /// it from running
+    Skipped,
+}
+
+/// Per-node breakdown of a graph run
+#[derive(Debug, Clone)]
+pub struct NodeRecord {
+    pub node_id: NodeId,
+    pub status: NodeRunStatus,
+    /// Wall-clock time spent on the node, including retries
+    pub duration_ms: u64,
+    /// Consumption reported by the final attempt
+    pub resource_consumed: ResourceCaps,
+    /// Error that stopped execution at this node, if any
+    pub error: Option<ExecutionError>,
+    /// Attempts made beyond the first
+    pub retries: u32,
+    /// Output was reused from a recorded run instead of executing
+    pub cached: bool,
+    /// Why the node was held back, if a freeze kept it from running
+    pub skip_reason: Option<SkipReason>,
+}
+
+/// Why a node was held back from running
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SkipReason {
+    /// Node is frozen
+    Frozen { reason: String },
+    /// Frozen upstream node `by` blocks its dependents
+    Blocked { by: NodeId },
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkipReason::Frozen { reason } => write!(f, "frozen: {reason}"),
+            SkipReason::Blocked { by } => write!(f, "blocked by frozen node {}", by.0),
+        }
+    }
+}
+
+impl NodeRecord {
+    /// Record for a node that was never run
+    pub fn skipped(node_id: NodeId) -> Self {
+        Self {
+            node_id,
+            status: NodeRunStatus::Skipped,
+            duration_ms: 0,
+            resource_consumed: ResourceCaps {
+                cpu_time_ms: 0,
+                memory_bytes: 0,
+                token_limit: 0,
+                iteration_cap: 0,
+            },
+            error: None,
+            retries: 0,
+            cached: false,
+            skip_reason: None,
+        }
+    }
 }
 
 /// Verification result for token integrity checks
@@ -7,12 +7,24 @@
 //! 1. All graphs reaching execution have passed validation
 //! 2. Validation cannot be bypassed
 //! 3. The proof token is cryptographically bound to the graph
+//!
+//! Topology queries (predecessors, topological order, critical path,
+//! strongly connected components) and plan diffing ([`GraphDiff`]) are
+//! implemented here as well.
 
 use crate::autonomy::CapabilityToken;
-use crate::types::v2::{SystemLimits, ValidatedGraph, ValidationToken};
-use crate::types::{GraphId, GraphType, NodeId};
+use crate::error::IllegalGraphTransition;
+use crate::types::v2::{
+    EdgeKind, GraphIndex, GraphLifecycle, LifecycleCell, SystemLimits, ValidatedGraph,
+    ValidationToken,
+};
+use crate::types::{GraphId, GraphType, NodeId, ResourceCaps};
 use crate::types::v2::NodeSpecV2;
-use std::collections::HashMap;
+use petgraph::algo::tarjan_scc;
+use petgraph::graphmap::DiGraphMap;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::sync::Arc;
 
 /// Sealed constructor for ValidatedGraph
 ///
@@ -32,17 +44,355 @@ impl ValidatedGraphConstructor {
         graph_type: GraphType,
         nodes: HashMap<NodeId, NodeSpecV2>,
         edges: Vec<(NodeId, NodeId)>,
+        edge_kinds: HashMap<(NodeId, NodeId), EdgeKind>,
         node_tokens: HashMap<NodeId, CapabilityToken>,
+        budget_envelope: Option<ResourceCaps>,
     ) -> ValidatedGraph {
         ValidatedGraph {
             graph_id,
             validation_token,
             graph_type,
-            nodes,
-            edges,
-            node_tokens,
+            index: Arc::new(GraphIndex::new(nodes, edges, edge_kinds, node_tokens)),
+            budget_envelope,
+            lifecycle: Arc::new(LifecycleCell::new()),
+        }
+    }
+}
+
+/// Lifecycle
+impl ValidatedGraph {
+    /// Current lifecycle state, shared by every clone of the graph
+    pub fn lifecycle(&self) -> GraphLifecycle {
+        self.lifecycle.get()
+    }
+    
+    /// Abort the graph
+    ///
+    /// An executor running the graph stops before its next node; an
+    /// active graph can no longer be run.
+    ///
+    /// # Errors
+    /// `IllegalGraphTransition` if the graph already completed, aborted or
+    /// was archived
+    pub fn abort(&self) -> Result<(), IllegalGraphTransition> {
+        self.lifecycle.transition(GraphLifecycle::Aborted).map(|_| ())
+    }
+    
+    /// Archive a completed or aborted graph
+    ///
+    /// # Errors
+    /// `IllegalGraphTransition` if the graph has not finished
+    pub fn archive(&self) -> Result<(), IllegalGraphTransition> {
+        self.lifecycle.transition(GraphLifecycle::Archived).map(|_| ())
+    }
+    
+    pub(crate) fn transition(
+        &self,
+        to: GraphLifecycle,
+    ) -> Result<GraphLifecycle, IllegalGraphTransition> {
+        self.lifecycle.transition(to)
+    }
+}
+
+/// Longest dependency chain through a graph
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CriticalPath {
+    /// Nodes on the chain, in execution order
+    pub nodes: Vec<NodeId>,
+    /// Summed weight of the nodes
+    pub length: u64,
+}
+
+/// Topology queries
+impl ValidatedGraph {
+    /// Direct predecessors of `node_id`, in edge order
+    pub fn predecessors(&self, node_id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.incoming(node_id).map(|(from, _)| from)
+    }
+    
+    /// Direct successors of `node_id`, in edge order
+    pub fn successors(&self, node_id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.outgoing(node_id).map(|(to, _)| to)
+    }
+    
+    /// Nodes without predecessors, in ID order
+    pub fn entry_nodes(&self) -> Vec<NodeId> {
+        self.node_ids()
+            .filter(|&id| self.predecessors(id).next().is_none())
+            .collect()
+    }
+    
+    /// Nodes without successors, in ID order
+    pub fn exit_nodes(&self) -> Vec<NodeId> {
+        self.node_ids()
+            .filter(|&id| self.successors(id).next().is_none())
+            .collect()
+    }
+    
+    /// Node IDs in dependency order, ties broken by ID
+    ///
+    /// `None` if the graph has a cycle (sandbox graphs only); see
+    /// [`execution_order`](Self::execution_order) for an order that always
+    /// exists.
+    pub fn topological_order(&self) -> Option<Vec<NodeId>> {
+        self.is_acyclic().then(|| self.execution_order())
+    }
+    
+    /// Check if no node depends on itself
+    pub fn is_acyclic(&self) -> bool {
+        self.strongly_connected_components()
+            .iter()
+            .all(|component| component.len() == 1)
+    }
+    
+    /// Strongly connected components, in dependency order
+    ///
+    /// Each component lists its nodes by ID. Production DAGs have only
+    /// single-node components; in sandbox graphs a larger one is a cycle.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<NodeId>> {
+        let mut graph = DiGraphMap::<NodeId, ()>::with_capacity(self.node_count(), self.edge_count());
+        for node_id in self.node_ids() {
+            graph.add_node(node_id);
+        }
+        for &(from, to) in self.edges() {
+            graph.add_edge(from, to, ());
+        }
+        
+        // Tarjan yields components in reverse topological order
+        let mut components = tarjan_scc(&graph);
+        components.reverse();
+        for component in &mut components {
+            component.sort();
         }
+        components
+    }
+    
+    /// Number of nodes on the longest dependency chain
+    ///
+    /// `None` if the graph has a cycle.
+    pub fn critical_path_length(&self) -> Option<usize> {
+        self.critical_path(|_, _| 1).map(|path| path.nodes.len())
     }
+    
+    /// Heaviest dependency chain, weighing each node with `weight`
+    ///
+    /// Ties go to the chain found first in execution order. `None` if the
+    /// graph has a cycle.
+    pub fn critical_path(
+        &self,
+        weight: impl Fn(NodeId, &NodeSpecV2) -> u64,
+    ) -> Option<CriticalPath> {
+        let order = self.topological_order()?;
+        
+        // Heaviest chain ending at each node, with its previous node
+        let mut best: HashMap<NodeId, (u64, Option<NodeId>)> = HashMap::with_capacity(order.len());
+        let heavier = |best: &HashMap<NodeId, (u64, Option<NodeId>)>, a: NodeId, b: NodeId| {
+            if best[&b].0 > best[&a].0 { b } else { a }
+        };
+        for &node_id in &order {
+            let own = self.get_node_spec(node_id).map_or(0, |spec| weight(node_id, spec));
+            let previous = self
+                .predecessors(node_id)
+                .reduce(|a, b| heavier(&best, a, b));
+            let prefix = previous.map_or(0, |from| best[&from].0);
+            best.insert(node_id, (prefix.saturating_add(own), previous));
+        }
+        
+        let Some(mut node_id) = order.iter().copied().reduce(|a, b| heavier(&best, a, b)) else {
+            return Some(CriticalPath { nodes: Vec::new(), length: 0 });
+        };
+        let length = best[&node_id].0;
+        
+        let mut nodes = vec![node_id];
+        while let Some(previous) = best[&node_id].1 {
+            nodes.push(previous);
+            node_id = previous;
+        }
+        nodes.reverse();
+        Some(CriticalPath { nodes, length })
+    }
+}
+
+/// Part of a node specification that differs between two plans
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum NodeField {
+    Directives,
+    AutonomyCeiling,
+    ResourceBounds,
+    Expansion,
+    Inputs,
+}
+
+impl NodeField {
+    /// Field name as shown in diffs
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Directives => "directives",
+            Self::AutonomyCeiling => "autonomy_ceiling",
+            Self::ResourceBounds => "resource_bounds",
+            Self::Expansion => "expansion_type",
+            Self::Inputs => "inputs",
+        }
+    }
+}
+
+/// Node present in both plans with a different specification
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeChange {
+    pub node_id: NodeId,
+    /// Differing fields, in declaration order
+    pub fields: Vec<NodeField>,
+}
+
+/// Edge present in both plans with a different kind
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EdgeChange {
+    pub from: NodeId,
+    pub to: NodeId,
+    pub old: EdgeKind,
+    pub new: EdgeKind,
+}
+
+/// Incremental difference between a plan and the one it replaces
+///
+/// Nodes are matched by ID, so re-plans keep the IDs of carried-over nodes
+/// (`GraphBuilder::add_node_with_id`). Every list is sorted by node ID.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GraphDiff {
+    pub added_nodes: Vec<NodeId>,
+    pub removed_nodes: Vec<NodeId>,
+    pub changed_nodes: Vec<NodeChange>,
+    pub added_edges: Vec<(NodeId, NodeId)>,
+    pub removed_edges: Vec<(NodeId, NodeId)>,
+    pub changed_edges: Vec<EdgeChange>,
+}
+
+impl GraphDiff {
+    /// Check if both plans have the same nodes, specifications and edges
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.changed_nodes.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+            && self.changed_edges.is_empty()
+    }
+    
+    /// Nodes of the new plan that need re-validation and new tokens
+    ///
+    /// Added and changed nodes, plus every surviving node whose incoming
+    /// edges changed (its inputs or ordering differ). Sorted by ID.
+    pub fn affected_nodes(&self) -> Vec<NodeId> {
+        let removed: BTreeSet<NodeId> = self.removed_nodes.iter().copied().collect();
+        let mut affected: BTreeSet<NodeId> = self.added_nodes.iter().copied().collect();
+        affected.extend(self.changed_nodes.iter().map(|change| change.node_id));
+        affected.extend(
+            self.added_edges
+                .iter()
+                .chain(&self.removed_edges)
+                .map(|&(_, to)| to)
+                .chain(self.changed_edges.iter().map(|change| change.to))
+                .filter(|to| !removed.contains(to)),
+        );
+        affected.into_iter().collect()
+    }
+}
+
+/// One line per difference: `+` added, `-` removed, `~` changed
+impl fmt::Display for GraphDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for node_id in &self.added_nodes {
+            writeln!(f, "+ node {}", node_id.0)?;
+        }
+        for node_id in &self.removed_nodes {
+            writeln!(f, "- node {}", node_id.0)?;
+        }
+        for change in &self.changed_nodes {
+            let fields: Vec<_> = change.fields.iter().map(|field| field.as_str()).collect();
+            writeln!(f, "~ node {} ({})", change.node_id.0, fields.join(", "))?;
+        }
+        for (from, to) in &self.added_edges {
+            writeln!(f, "+ edge {} -> {}", from.0, to.0)?;
+        }
+        for (from, to) in &self.removed_edges {
+            writeln!(f, "- edge {} -> {}", from.0, to.0)?;
+        }
+        for change in &self.changed_edges {
+            writeln!(
+                f,
+                "~ edge {} -> {} ({:?} => {:?})",
+                change.from.0, change.to.0, change.old, change.new
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Plan diffing
+impl ValidatedGraph {
+    /// Differences from `old`, the plan this graph replaces
+    pub fn diff(&self, old: &ValidatedGraph) -> GraphDiff {
+        let mut diff = GraphDiff::default();
+        
+        for (node_id, spec, _) in self.nodes() {
+            match old.get_node_spec(node_id) {
+                None => diff.added_nodes.push(node_id),
+                Some(previous) => {
+                    let fields = changed_fields(previous, spec);
+                    if !fields.is_empty() {
+                        diff.changed_nodes.push(NodeChange { node_id, fields });
+                    }
+                }
+            }
+        }
+        diff.removed_nodes = old
+            .node_ids()
+            .filter(|&node_id| self.get_node(node_id).is_none())
+            .collect();
+        
+        let old_edges: HashMap<(NodeId, NodeId), &EdgeKind> = old
+            .edges()
+            .iter()
+            .map(|&(from, to)| ((from, to), old.edge_kind(from, to)))
+            .collect();
+        let mut new_edges: Vec<(NodeId, NodeId)> = self.edges().to_vec();
+        new_edges.sort();
+        for (from, to) in new_edges {
+            let kind = self.edge_kind(from, to);
+            match old_edges.get(&(from, to)) {
+                None => diff.added_edges.push((from, to)),
+                Some(&previous) if previous != kind => diff.changed_edges.push(EdgeChange {
+                    from,
+                    to,
+                    old: previous.clone(),
+                    new: kind.clone(),
+                }),
+                Some(_) => {}
+            }
+        }
+        let current: BTreeSet<(NodeId, NodeId)> = self.edges().iter().copied().collect();
+        diff.removed_edges = old_edges
+            .into_keys()
+            .filter(|edge| !current.contains(edge))
+            .collect();
+        diff.removed_edges.sort();
+        diff
+    }
+}
+
+/// Fields of `new` that differ from `old`
+fn changed_fields(old: &NodeSpecV2, new: &NodeSpecV2) -> Vec<NodeField> {
+    let expansion = |spec: &NodeSpecV2| serde_json::to_value(&spec.expansion_type).ok();
+    [
+        (NodeField::Directives, old.directives.directives != new.directives.directives),
+        (NodeField::AutonomyCeiling, old.autonomy_ceiling != new.autonomy_ceiling),
+        (NodeField::ResourceBounds, old.resource_bounds != new.resource_bounds),
+        (NodeField::Expansion, expansion(old) != expansion(new)),
+        (NodeField::Inputs, old.inputs != new.inputs),
+    ]
+    .into_iter()
+    .filter_map(|(field, changed)| changed.then_some(field))
+    .collect()
 }
 
 /// Validation report returned after successful validation
@@ -65,6 +415,17 @@ pub struct ResourceProof {
 }
 
 impl ResourceProof {
+    /// Summed node bounds, as caps
+    pub fn worst_case(&self) -> ResourceCaps {
+        ResourceCaps {
+            cpu_time_ms: self.total_cpu_ms,
+            memory_bytes: self.total_memory_bytes,
+            token_limit: self.total_tokens,
+            iteration_cap: self.total_iterations,
+        }
+    }
+    
+
     /// Verify that resource bounds are provably satisfiable
     pub fn verify_bounds(
         nodes: &[NodeSpecV2],
@@ -111,11 +472,13 @@ impl ResourceProof {
 
 /// Compute validation hash for a graph
 ///
-/// This hash cryptographically binds the validation to the graph structure.
+/// This hash cryptographically binds the validation to the graph structure,
+/// including edge kinds (edges missing from `edge_kinds` are ordering-only).
 pub fn compute_validation_hash(
     graph_id: GraphId,
     nodes: &HashMap<NodeId, NodeSpecV2>,
     edges: &[(NodeId, NodeId)],
+    edge_kinds: &HashMap<(NodeId, NodeId), EdgeKind>,
 ) -> [u8; 32] {
     use sha2::{Digest, Sha256};
     
@@ -145,6 +508,8 @@ pub fn compute_validation_hash(
     for (from, to) in sorted_edges {
         hasher.update(from.0.as_bytes());
         hasher.update(to.0.as_bytes());
+        let kind = edge_kinds.get(&(from, to)).unwrap_or(&EdgeKind::OrderingOnly);
+        hasher.update(kind.digest_bytes());
     }
     
     hasher.finalize().into()
@@ -171,6 +536,7 @@ mod tests {
                 iteration_cap: 100,
             },
             expansion_type: None,
+            inputs: Vec::new(),
         }
     }
 
@@ -237,12 +603,30 @@ mod tests {
         
         let edges = vec![(n1, n2)];
         
-        let hash1 = compute_validation_hash(graph_id, &nodes, &edges);
-        let hash2 = compute_validation_hash(graph_id, &nodes, &edges);
+        let hash1 = compute_validation_hash(graph_id, &nodes, &edges, &HashMap::new());
+        let hash2 = compute_validation_hash(graph_id, &nodes, &edges, &HashMap::new());
         
         assert_eq!(hash1, hash2);
     }
 
+    #[test]
+    fn test_validation_hash_binds_edge_kinds() {
+        let graph_id = GraphId::new();
+        let mut nodes = HashMap::new();
+        let n1 = NodeId::new();
+        let n2 = NodeId::new();
+        nodes.insert(n1, create_test_node_spec(AutonomyLevel::L3, 1000));
+        nodes.insert(n2, create_test_node_spec(AutonomyLevel::L3, 1000));
+        let edges = vec![(n1, n2)];
+
+        let untyped = compute_validation_hash(graph_id, &nodes, &edges, &HashMap::new());
+        let ordering = HashMap::from([((n1, n2), EdgeKind::OrderingOnly)]);
+        let data = HashMap::from([((n1, n2), EdgeKind::data("Patch"))]);
+
+        assert_eq!(untyped, compute_validation_hash(graph_id, &nodes, &edges, &ordering));
+        assert_ne!(untyped, compute_validation_hash(graph_id, &nodes, &edges, &data));
+    }
+
     #[test]
     fn test_validation_hash_different_graphs() {
         let graph_id1 = GraphId::new();
@@ -254,10 +638,136 @@ mod tests {
         
         let edges = vec![];
         
-        let hash1 = compute_validation_hash(graph_id1, &nodes, &edges);
-        let hash2 = compute_validation_hash(graph_id2, &nodes, &edges);
+        let hash1 = compute_validation_hash(graph_id1, &nodes, &edges, &HashMap::new());
+        let hash2 = compute_validation_hash(graph_id2, &nodes, &edges, &HashMap::new());
         
         // Different graph IDs should produce different hashes
         assert_ne!(hash1, hash2);
     }
+
+    #[test]
+    fn test_graph_lookups_borrow_shared_index() {
+        use crate::construction::GraphBuilder;
+        use ed25519_dalek::SigningKey;
+
+        let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
+        let a = builder.add_node(create_test_node_spec(AutonomyLevel::L2, 100));
+        let b = builder.add_node(create_test_node_spec(AutonomyLevel::L2, 100));
+        let c = builder.add_node(create_test_node_spec(AutonomyLevel::L2, 100));
+        builder.add_edge(a, b).unwrap();
+        builder.add_edge(a, c).unwrap();
+        builder.add_edge(b, c).unwrap();
+        let graph = builder.validate(&SigningKey::from_bytes(&[7u8; 32])).unwrap();
+
+        let mut ids = vec![a, b, c];
+        ids.sort();
+        let nodes: Vec<_> = graph.nodes().collect();
+        assert_eq!(nodes.iter().map(|(id, _, _)| *id).collect::<Vec<_>>(), ids);
+        assert!(nodes.iter().all(|(id, spec, token)| {
+            token.node_id == *id && std::ptr::eq(*spec, graph.get_node_spec(*id).unwrap())
+        }));
+
+        let targets: Vec<_> = graph.outgoing(a).map(|(to, _)| to).collect();
+        assert_eq!(targets, vec![b, c]);
+        let sources: Vec<_> = graph.incoming(c).map(|(from, _)| from).collect();
+        assert_eq!(sources, vec![a, b]);
+        assert_eq!(graph.execution_order(), vec![a, b, c]);
+
+        let copy = graph.clone();
+        assert!(Arc::ptr_eq(&graph.index, &copy.index));
+    }
+
+    #[test]
+    fn test_diff_against_previous_plan() {
+        use crate::construction::GraphBuilder;
+        use ed25519_dalek::SigningKey;
+
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let (a, b, c, d) = (NodeId::new(), NodeId::new(), NodeId::new(), NodeId::new());
+
+        let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
+        builder.add_node_with_id(a, create_test_node_spec(AutonomyLevel::L2, 100)).unwrap();
+        builder.add_node_with_id(b, create_test_node_spec(AutonomyLevel::L2, 100)).unwrap();
+        builder.add_node_with_id(c, create_test_node_spec(AutonomyLevel::L2, 100)).unwrap();
+        builder.add_edge(a, b).unwrap();
+        builder.add_edge(b, c).unwrap();
+        let old = builder.validate(&key).unwrap();
+
+        // Re-plan: b gets more CPU, c is replaced by d, a feeds d directly
+        let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
+        builder.add_node_with_id(a, create_test_node_spec(AutonomyLevel::L2, 100)).unwrap();
+        builder.add_node_with_id(b, create_test_node_spec(AutonomyLevel::L2, 900)).unwrap();
+        builder.add_node_with_id(d, create_test_node_spec(AutonomyLevel::L2, 100)).unwrap();
+        assert_eq!(
+            builder.add_node_with_id(d, create_test_node_spec(AutonomyLevel::L2, 100)),
+            Err(crate::construction::GraphBuilderError::NodeAlreadyExists(d))
+        );
+        builder.add_edge(a, b).unwrap();
+        builder.add_edge(a, d).unwrap();
+        let new = builder.validate(&key).unwrap();
+
+        let diff = new.diff(&old);
+        assert_eq!(diff.added_nodes, vec![d]);
+        assert_eq!(diff.removed_nodes, vec![c]);
+        assert_eq!(
+            diff.changed_nodes,
+            vec![NodeChange { node_id: b, fields: vec![NodeField::ResourceBounds] }]
+        );
+        assert_eq!(diff.added_edges, vec![(a, d)]);
+        assert_eq!(diff.removed_edges, vec![(b, c)]);
+        assert!(diff.changed_edges.is_empty());
+
+        let mut affected = vec![b, d];
+        affected.sort();
+        assert_eq!(diff.affected_nodes(), affected);
+        assert_eq!(diff.to_string().lines().count(), 5);
+        assert!(new.diff(&new).is_empty());
+    }
+
+    #[test]
+    fn test_topology_queries() {
+        use crate::construction::GraphBuilder;
+        use ed25519_dalek::SigningKey;
+
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
+        let a = builder.add_node(create_test_node_spec(AutonomyLevel::L2, 100));
+        let b = builder.add_node(create_test_node_spec(AutonomyLevel::L2, 100));
+        let c = builder.add_node(create_test_node_spec(AutonomyLevel::L2, 500));
+        let d = builder.add_node(create_test_node_spec(AutonomyLevel::L2, 100));
+        builder.add_edge(a, b).unwrap();
+        builder.add_edge(b, d).unwrap();
+        builder.add_edge(a, c).unwrap();
+        builder.add_edge(c, d).unwrap();
+        let graph = builder.validate(&key).unwrap();
+
+        assert_eq!(graph.predecessors(d).collect::<Vec<_>>(), vec![b, c]);
+        assert_eq!(graph.successors(a).collect::<Vec<_>>(), vec![b, c]);
+        assert_eq!(graph.entry_nodes(), vec![a]);
+        assert_eq!(graph.exit_nodes(), vec![d]);
+        assert_eq!(graph.topological_order(), Some(graph.execution_order()));
+        assert_eq!(graph.strongly_connected_components().len(), 4);
+        assert_eq!(graph.critical_path_length(), Some(3));
+        let path = graph
+            .critical_path(|_, spec| spec.resource_bounds.cpu_time_ms)
+            .unwrap();
+        assert_eq!(path.nodes, vec![a, c, d]);
+        assert_eq!(path.length, 700);
+
+        let mut builder = GraphBuilder::new(GraphType::SandboxGraph);
+        let x = builder.add_node(create_test_node_spec(AutonomyLevel::L2, 100));
+        let y = builder.add_node(create_test_node_spec(AutonomyLevel::L2, 100));
+        let z = builder.add_node(create_test_node_spec(AutonomyLevel::L2, 100));
+        builder.add_edge(x, y).unwrap();
+        builder.add_edge(y, x).unwrap();
+        builder.add_edge(y, z).unwrap();
+        let sandbox = builder.validate(&key).unwrap();
+
+        let mut cycle = vec![x, y];
+        cycle.sort();
+        assert_eq!(sandbox.strongly_connected_components(), vec![cycle, vec![z]]);
+        assert!(!sandbox.is_acyclic());
+        assert_eq!(sandbox.topological_order(), None);
+        assert_eq!(sandbox.critical_path_length(), None);
+    }
 }
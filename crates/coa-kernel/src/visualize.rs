@@ -0,0 +1,306 @@
+//! Graph Visualization
+//!
+//! Renders a `ValidatedGraph` as Graphviz DOT or Mermaid so that humans
+//! approving low-autonomy graphs (L0–L2) can see what will run before it
+//! reaches the executor.
+//!
+//! Each node is annotated with its autonomy ceiling, resource bounds and,
+//! if supplied, its current execution state. Output is deterministic: nodes
+//! are emitted in `NodeId` order.
+
+use crate::types::v2::{NodeSpecV2, ValidatedGraph};
+use crate::types::{AutonomyLevel, NodeId, NodeState};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Output format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Graphviz DOT
+    Dot,
+    /// Mermaid flowchart
+    Mermaid,
+}
+
+/// Exporter for a validated graph
+pub struct GraphExporter<'a> {
+    graph: &'a ValidatedGraph,
+    states: HashMap<NodeId, NodeState>,
+    labels: HashMap<NodeId, String>,
+}
+
+impl<'a> GraphExporter<'a> {
+    /// Create exporter for a graph
+    pub fn new(graph: &'a ValidatedGraph) -> Self {
+        Self {
+            graph,
+            states: HashMap::new(),
+            labels: HashMap::new(),
+        }
+    }
+
+    /// Annotate a node with its execution state
+    pub fn with_state(mut self, node_id: NodeId, state: NodeState) -> Self {
+        self.states.insert(node_id, state);
+        self
+    }
+
+    /// Annotate nodes with execution states
+    pub fn with_states(mut self, states: impl IntoIterator<Item = (NodeId, NodeState)>) -> Self {
+        self.states.extend(states);
+        self
+    }
+
+    /// Use a human-readable title instead of the node ID
+    pub fn with_label(mut self, node_id: NodeId, label: impl Into<String>) -> Self {
+        self.labels.insert(node_id, label.into());
+        self
+    }
+
+    /// Render in the given format
+    pub fn render(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Dot => self.to_dot(),
+            GraphFormat::Mermaid => self.to_mermaid(),
+        }
+    }
+
+    /// Render as Graphviz DOT
+    pub fn to_dot(&self) -> String {
+        let nodes = self.sorted_nodes();
+        let mut out = String::new();
+
+        let _ = writeln!(out, "digraph \"{}\" {{", self.graph.graph_id().0);
+        let _ = writeln!(out, "    rankdir=TB;");
+        let _ = writeln!(out, "    node [shape=box, style=\"rounded,filled\"];");
+
+        for (index, (node_id, spec)) in nodes.iter().enumerate() {
+            let label = self
+                .label_lines(*node_id, spec)
+                .iter()
+                .map(|line| escape_dot(line))
+                .collect::<Vec<_>>()
+                .join("\\n");
+            let _ = writeln!(
+                out,
+                "    n{} [label=\"{}\", fillcolor=\"{}\"];",
+                index,
+                label,
+                autonomy_color(spec.autonomy_ceiling)
+            );
+        }
+
+        for (from, to) in self.sorted_edges(&nodes) {
+            let _ = writeln!(out, "    n{} -> n{};", from, to);
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    /// Render as a Mermaid flowchart
+    pub fn to_mermaid(&self) -> String {
+        let nodes = self.sorted_nodes();
+        let mut out = String::from("flowchart TD\n");
+
+        for (index, (node_id, spec)) in nodes.iter().enumerate() {
+            let label = self
+                .label_lines(*node_id, spec)
+                .iter()
+                .map(|line| line.replace('"', "#quot;"))
+                .collect::<Vec<_>>()
+                .join("<br/>");
+            let _ = writeln!(out, "    n{}[\"{}\"]", index, label);
+        }
+
+        for (from, to) in self.sorted_edges(&nodes) {
+            let _ = writeln!(out, "    n{} --> n{}", from, to);
+        }
+
+        let mut levels: Vec<u8> = nodes
+            .iter()
+            .map(|(_, spec)| spec.autonomy_ceiling.as_u8())
+            .collect();
+        levels.sort_unstable();
+        levels.dedup();
+
+        for level in levels {
+            let members: Vec<String> = nodes
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, spec))| spec.autonomy_ceiling.as_u8() == level)
+                .map(|(index, _)| format!("n{}", index))
+                .collect();
+            let _ = writeln!(
+                out,
+                "    classDef l{} fill:{};",
+                level,
+                LEVEL_COLORS[usize::from(level)]
+            );
+            let _ = writeln!(out, "    class {} l{};", members.join(","), level);
+        }
+
+        out
+    }
+
+    fn sorted_nodes(&self) -> Vec<(NodeId, &'a NodeSpecV2)> {
+        self.graph.nodes().map(|(id, spec, _)| (id, spec)).collect()
+    }
+
+    fn sorted_edges(&self, nodes: &[(NodeId, &NodeSpecV2)]) -> Vec<(usize, usize)> {
+        let position: HashMap<NodeId, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, (id, _))| (*id, index))
+            .collect();
+        let mut edges: Vec<(usize, usize)> = self
+            .graph
+            .edges()
+            .iter()
+            .filter_map(|(from, to)| Some((*position.get(from)?, *position.get(to)?)))
+            .collect();
+        edges.sort_unstable();
+        edges
+    }
+
+    /// Unescaped label lines
+    fn label_lines(&self, node_id: NodeId, spec: &NodeSpecV2) -> Vec<String> {
+        let title = self
+            .labels
+            .get(&node_id)
+            .cloned()
+            .unwrap_or_else(|| node_id.0.to_string()[..8].to_string());
+        let bounds = &spec.resource_bounds;
+
+        let mut lines = vec![
+            title,
+            format!(
+                "{} ({})",
+                autonomy_name(spec.autonomy_ceiling),
+                approval_note(spec.autonomy_ceiling)
+            ),
+            format!(
+                "cpu {}ms · mem {} · tokens {} · iter {}",
+                bounds.cpu_time_ms,
+                format_bytes(bounds.memory_bytes),
+                bounds.token_limit,
+                bounds.iteration_cap
+            ),
+        ];
+        if let Some(state) = self.states.get(&node_id) {
+            lines.push(format!("state: {:?}", state));
+        }
+        lines
+    }
+}
+
+/// Fill colors per autonomy level; human-gated levels stand out
+const LEVEL_COLORS: [&str; 6] = [
+    "#f8d7da", "#fde2c4", "#fff3cd", "#d4edda", "#d1ecf1", "#e2e3e5",
+];
+
+fn autonomy_color(level: AutonomyLevel) -> &'static str {
+    LEVEL_COLORS[usize::from(level.as_u8())]
+}
+
+fn autonomy_name(level: AutonomyLevel) -> String {
+    format!("L{}", level.as_u8())
+}
+
+fn approval_note(level: AutonomyLevel) -> &'static str {
+    match level {
+        AutonomyLevel::L0 => "human in the loop",
+        AutonomyLevel::L1 => "approval before merge",
+        AutonomyLevel::L2 => "human merge",
+        AutonomyLevel::L3 => "auto merge in sandbox",
+        AutonomyLevel::L4 => "auto merge + test deploy",
+        AutonomyLevel::L5 => "fully autonomous",
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024 && value % 1024 == 0 && unit < UNITS.len() - 1 {
+        value /= 1024;
+        unit += 1;
+    }
+    format!("{}{}", value, UNITS[unit])
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::construction::GraphBuilder;
+    use crate::types::{DirectiveSet, GraphType, ResourceCaps};
+    use ed25519_dalek::SigningKey;
+    use rand::rngs::OsRng;
+    use std::collections::BTreeMap;
+
+    fn spec(autonomy: AutonomyLevel) -> NodeSpecV2 {
+        NodeSpecV2::new(
+            DirectiveSet {
+                directives: BTreeMap::new(),
+            },
+            autonomy,
+            ResourceCaps {
+                cpu_time_ms: 1000,
+                memory_bytes: 1024 * 1024,
+                token_limit: 500,
+                iteration_cap: 10,
+            },
+        )
+    }
+
+    fn graph() -> (ValidatedGraph, NodeId, NodeId) {
+        let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
+        let plan = builder.add_node(spec(AutonomyLevel::L1));
+        let code = builder.add_node(spec(AutonomyLevel::L3));
+        builder.add_edge(plan, code).unwrap();
+        let validated = builder.validate(&SigningKey::generate(&mut OsRng)).unwrap();
+        (validated, plan, code)
+    }
+
+    #[test]
+    fn test_dot_contains_annotations_and_edges() {
+        let (graph, plan, code) = graph();
+        let dot = GraphExporter::new(&graph)
+            .with_label(plan, "plan \"v1\"")
+            .with_label(code, "code")
+            .with_state(code, NodeState::Executing)
+            .to_dot();
+
+        assert!(dot.starts_with("digraph"));
+        assert!(dot.contains("plan \\\"v1\\\"\\nL1 (approval before merge)"));
+        assert!(dot.contains("cpu 1000ms · mem 1MiB · tokens 500 · iter 10"));
+        assert!(dot.contains("state: Executing"));
+        assert_eq!(dot.matches(" -> ").count(), 1);
+    }
+
+    #[test]
+    fn test_mermaid_groups_nodes_by_autonomy() {
+        let (graph, plan, _) = graph();
+        let mermaid = GraphExporter::new(&graph)
+            .with_label(plan, "plan \"v1\"")
+            .render(GraphFormat::Mermaid);
+
+        assert!(mermaid.starts_with("flowchart TD\n"));
+        assert!(mermaid.contains("plan #quot;v1#quot;<br/>L1"));
+        assert!(mermaid.contains("classDef l1 fill:#fde2c4;"));
+        assert!(mermaid.contains("classDef l3 fill:#d4edda;"));
+        assert_eq!(mermaid.matches(" --> ").count(), 1);
+    }
+
+    #[test]
+    fn test_output_is_deterministic() {
+        let (graph, _, _) = graph();
+        let exporter = GraphExporter::new(&graph);
+        assert_eq!(exporter.to_dot(), exporter.to_dot());
+        assert_eq!(exporter.to_mermaid(), exporter.to_mermaid());
+    }
+}
@@ -3,31 +3,7 @@
 //! Tests for the construction phase using GraphBuilder.
 //!
 use coa_kernel::prelude::*;
-use coa_kernel::DirectiveSet;
-use std::collections::BTreeMap;
-use ed25519_dalek::SigningKey;
-use rand::rngs::OsRng;
-
-fn create_signing_key() -> SigningKey {
-    let mut csprng = OsRng;
-    SigningKey::generate(&mut csprng)
-}
-
-fn create_test_spec() -> NodeSpecV2 {
-    NodeSpecV2 {
-        directives: DirectiveSet {
-            directives: BTreeMap::new(),
-        },
-        autonomy_ceiling: AutonomyLevel::L3,
-        resource_bounds: ResourceCaps {
-            cpu_time_ms: 1000,
-            memory_bytes: 1024 * 1024,
-            token_limit: 1000,
-            iteration_cap: 100,
-        },
-        expansion_type: None,
-    }
-}
+use coa_test_utils::kernel::{default_signing_key, test_node_spec};
 
 #[test]
 fn test_graph_builder_create() {
@@ -42,7 +18,7 @@ fn test_graph_builder_create() {
 fn test_graph_builder_add_node() {
     let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
     
-    let spec = create_test_spec();
+    let spec = test_node_spec();
     let node_id = builder.add_node(spec);
     
     assert_eq!(builder.node_count(), 1);
@@ -51,10 +27,10 @@ fn test_graph_builder_add_node() {
 
 #[test]
 fn test_graph_builder_add_edge() {
-    let signing_key = create_signing_key();
+    let signing_key = default_signing_key();
     let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
     
-    let spec = create_test_spec();
+    let spec = test_node_spec();
     let node1 = builder.add_node(spec.clone());
     let node2 = builder.add_node(spec);
     
@@ -71,7 +47,7 @@ fn test_graph_builder_add_edge() {
 fn test_graph_builder_rejects_self_loop() {
     let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
     
-    let spec = create_test_spec();
+    let spec = test_node_spec();
     let node = builder.add_node(spec);
     
     let result = builder.add_edge(node, node);
@@ -82,7 +58,7 @@ fn test_graph_builder_rejects_self_loop() {
 fn test_graph_builder_rejects_cycle() {
     let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
     
-    let spec = create_test_spec();
+    let spec = test_node_spec();
     let n1 = builder.add_node(spec.clone());
     let n2 = builder.add_node(spec.clone());
     let n3 = builder.add_node(spec);
@@ -97,10 +73,10 @@ fn test_graph_builder_rejects_cycle() {
 
 #[test]
 fn test_validated_graph_sealed() {
-    let signing_key = create_signing_key();
+    let signing_key = default_signing_key();
     let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
     
-    let spec = create_test_spec();
+    let spec = test_node_spec();
     let node = builder.add_node(spec);
     
     let validated = builder.validate(&signing_key).unwrap();
@@ -113,10 +89,10 @@ fn test_validated_graph_sealed() {
 
 #[test]
 fn test_sandbox_allows_cycle() {
-    let signing_key = create_signing_key();
+    let signing_key = default_signing_key();
     let mut builder = GraphBuilder::new(GraphType::SandboxGraph);
     
-    let spec = create_test_spec();
+    let spec = test_node_spec();
     let n1 = builder.add_node(spec.clone());
     let n2 = builder.add_node(spec);
     
@@ -133,11 +109,11 @@ fn test_sandbox_allows_cycle() {
 
 #[test]
 fn test_graph_builder_node_count() {
-    let signing_key = create_signing_key();
+    let signing_key = default_signing_key();
     let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
     
     for _ in 0..100 {
-        builder.add_node(create_test_spec());
+        builder.add_node(test_node_spec());
     }
     
     assert_eq!(builder.node_count(), 100);
@@ -148,10 +124,10 @@ fn test_graph_builder_node_count() {
 
 #[test]
 fn test_validated_graph_has_tokens() {
-    let signing_key = create_signing_key();
+    let signing_key = default_signing_key();
     let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
     
-    let spec = create_test_spec();
+    let spec = test_node_spec();
     let node = builder.add_node(spec);
     
     let validated = builder.validate(&signing_key).unwrap();
@@ -98,6 +98,7 @@ async fn simulator_respects_two_phase_invariant() {
         total_executions: 50,
         stop_on_first_violation: true,
         verify_zero_runtime_policy: true,
+        node_cache: true,
     };
 
     let report = run_simulator(config).await;
@@ -0,0 +1,99 @@
+//! Graph Lifecycle Tests - v2.0
+//!
+//! Graphs run once, can be aborted mid-run and are archived when done.
+//!
+use coa_kernel::api::SchedulerErrorKind;
+use coa_kernel::prelude::*;
+use coa_kernel::scheduler::GraphScheduler;
+use coa_kernel::types::v2::{GraphLifecycle, NodeRunStatus};
+use coa_test_utils::kernel::{signed_linear_graph, Behavior, FakeNodeExecutor};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn test_lifecycle_transitions() {
+    use GraphLifecycle::*;
+
+    assert!(Active.can_transition_to(Executing));
+    assert!(Executing.can_transition_to(Aborted));
+    assert!(Aborted.can_transition_to(Archived));
+    assert!(!Completed.can_transition_to(Executing));
+    assert!(!Active.can_transition_to(Completed));
+    assert!(Archived.allowed_transitions().is_empty());
+}
+
+#[tokio::test]
+async fn test_completed_graph_cannot_rerun() {
+    let fixture = signed_linear_graph(2);
+    let fake = FakeNodeExecutor::new().shared();
+    let executor = fixture.executor(fake.clone());
+    assert_eq!(fixture.graph.lifecycle(), GraphLifecycle::Active);
+
+    executor.run(fixture.graph.clone()).await.unwrap();
+    assert_eq!(fixture.graph.lifecycle(), GraphLifecycle::Completed);
+
+    // Clones share the lifecycle, so no executor may run the graph again
+    let failure = fixture
+        .executor(fake.clone())
+        .run(fixture.graph.clone())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        failure.error,
+        ExecutionError::GraphNotRunnable(GraphLifecycle::Completed)
+    );
+    assert_eq!(failure.summary.skipped().count(), 2);
+    assert_eq!(fake.calls().len(), 2);
+
+    assert!(fixture.graph.abort().is_err());
+    fixture.graph.archive().unwrap();
+    assert_eq!(fixture.graph.lifecycle(), GraphLifecycle::Archived);
+}
+
+#[tokio::test]
+async fn test_abort_stops_running_graph() {
+    let fixture = signed_linear_graph(3);
+    let graph_id = fixture.graph.graph_id();
+    let fake = FakeNodeExecutor::new()
+        .with(
+            fixture.nodes[0],
+            Behavior::Delay(Duration::from_millis(50), Box::new(Behavior::Succeed)),
+        )
+        .shared();
+    let executor = Arc::new(fixture.executor(fake.clone()));
+
+    let run = tokio::spawn({
+        let executor = Arc::clone(&executor);
+        let graph = fixture.graph.clone();
+        async move { executor.run(graph).await }
+    });
+    while !executor.running_graphs().contains(&graph_id) {
+        tokio::task::yield_now().await;
+    }
+    assert!(executor.abort(graph_id));
+
+    let failure = run.await.unwrap().unwrap_err();
+    assert_eq!(failure.error, ExecutionError::Aborted);
+    assert_eq!(failure.summary.node(fixture.nodes[0]).unwrap().status, NodeRunStatus::Succeeded);
+    assert_eq!(failure.summary.skipped().count(), 2);
+    assert_eq!(fake.calls(), vec![fixture.nodes[0]]);
+    assert_eq!(fixture.graph.lifecycle(), GraphLifecycle::Aborted);
+    assert!(executor.running_graphs().is_empty());
+    assert!(!executor.abort(graph_id));
+}
+
+#[tokio::test]
+async fn test_scheduler_rejects_aborted_graph() {
+    let fixture = signed_linear_graph(2);
+    let scheduler = GraphScheduler::with_executor(Arc::new(
+        fixture.executor(FakeNodeExecutor::new().shared()),
+    ));
+
+    fixture.graph.abort().unwrap();
+    let error = scheduler.execute_graph(fixture.graph.clone()).await.unwrap_err();
+    assert_eq!(error.kind, SchedulerErrorKind::GraphClosed);
+    assert!(scheduler
+        .execute_node(&fixture.graph, fixture.nodes[0])
+        .await
+        .is_err());
+}
@@ -1,5 +1,5 @@
-use coa_kernel::logging::{Event, EventLog};
-use coa_kernel::types::{AutonomyLevel, DirectiveProfileHash, EventId, NodeId};
+use coa_kernel::logging::{Action, Event, EventLog};
+use coa_kernel::types::{AutonomyLevel, CorrelationId, DirectiveProfileHash, EventId, NodeId};
 
 #[test]
 fn test_log_integrity() {
@@ -11,10 +11,14 @@ fn test_log_integrity() {
         node_id: NodeId::new(),
         autonomy_level: AutonomyLevel::L0,
         directive_hash: DirectiveProfileHash([0u8; 32]),
-        action: "create".to_string(),
+        action: "create".into(),
         result: "ok".to_string(),
+        schema_version: None,
+        correlation_id: None,
         prev_hash: [0u8; 32], // Will be ignored/overwritten by append
         hash: [0u8; 32], // Will be overwritten
+        hash_algorithm: None,
+        tenant_id: None,
     };
     
     let _ = log.append(e1.clone());
@@ -25,13 +29,232 @@ fn test_log_integrity() {
         node_id: NodeId::new(),
         autonomy_level: AutonomyLevel::L0,
         directive_hash: DirectiveProfileHash([0u8; 32]),
-        action: "update".to_string(),
+        action: "update".into(),
         result: "ok".to_string(),
+        schema_version: None,
+        correlation_id: None,
         prev_hash: [0u8; 32],
         hash: [0u8; 32],
+        hash_algorithm: None,
+        tenant_id: None,
     };
     
     let _ = log.append(e2);
     
     assert!(log.verify_integrity().is_ok());
 }
+
+#[test]
+fn test_event_logger_query() {
+    use coa_kernel::api::{EventFilter, EventLogger};
+
+    let log = EventLog::default();
+    let node = NodeId::new();
+    for (i, action) in ["create", "composition_applied", "composition_applied"].iter().enumerate() {
+        let event = Event {
+            event_id: EventId::new(),
+            timestamp: i as u64,
+            node_id: node,
+            autonomy_level: AutonomyLevel::L0,
+            directive_hash: DirectiveProfileHash([0u8; 32]),
+            action: (*action).into(),
+            result: "ok".to_string(),
+            schema_version: None,
+            correlation_id: Some(CorrelationId::new("run-1").for_task(i)),
+            prev_hash: [0u8; 32],
+            hash: [0u8; 32],
+            hash_algorithm: None,
+            tenant_id: None,
+        };
+        log.log_event(event).unwrap();
+    }
+
+    let filter = EventFilter {
+        action: Some(Action::CompositionApplied),
+        ..EventFilter::default()
+    };
+    let entries = log.query_events(filter.clone(), 10).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(log.query_events(filter, 1).unwrap().len(), 1);
+    assert!(entries.iter().all(|e| e.verified && e.event.node_id == node));
+    assert!(log.verify_integrity().is_ok());
+
+    let by_intent = EventFilter {
+        correlation_id: Some(CorrelationId::new("run-1")),
+        ..EventFilter::default()
+    };
+    assert_eq!(log.query_events(by_intent, 10).unwrap().len(), 3);
+    let by_task = EventFilter {
+        correlation_id: Some(CorrelationId::new("run-1").for_task(2)),
+        ..EventFilter::default()
+    };
+    assert_eq!(log.query_events(by_task, 10).unwrap().len(), 1);
+}
+
+#[test]
+fn test_log_clock_stamps_events() {
+    use coa_kernel::clock::ManualClock;
+    use std::sync::Arc;
+
+    let clock = Arc::new(ManualClock::new(42));
+    let log = EventLog::with_clock(clock.clone());
+    assert_eq!(log.now(), 42);
+    clock.advance(8);
+    assert_eq!(log.now(), 50);
+}
+
+#[test]
+fn test_log_chain_spans_hash_algorithms() {
+    use coa_kernel::types::HashAlgorithm;
+
+    let event = |action: &str| Event {
+        event_id: EventId::new(),
+        timestamp: 1,
+        node_id: NodeId::new(),
+        autonomy_level: AutonomyLevel::L0,
+        directive_hash: DirectiveProfileHash([0u8; 32]),
+        action: action.into(),
+        result: "ok".to_string(),
+        schema_version: None,
+        correlation_id: None,
+        prev_hash: [0u8; 32],
+        hash: [0u8; 32],
+        hash_algorithm: None,
+        tenant_id: None,
+    };
+
+    let legacy = EventLog::default();
+    legacy.append(event("create")).unwrap();
+    assert_eq!(legacy.events()[0].hash_algorithm, None);
+
+    let log = EventLog::from_events(legacy.events())
+        .unwrap()
+        .with_hash_algorithm(HashAlgorithm::Sha3_256);
+    log.append(event("update")).unwrap();
+    let events = log.events();
+    assert_eq!(events[1].hash_algorithm, Some(HashAlgorithm::Sha3_256));
+    assert!(EventLog::from_events(events.clone()).is_ok());
+
+    let mut forged = events;
+    forged[1].hash_algorithm = None;
+    assert!(EventLog::from_events(forged).is_err());
+}
+
+#[test]
+fn test_schemas_stamp_events_and_filters_match_result_fields() {
+    use coa_kernel::api::{EventFilter, EventLogger};
+    use coa_kernel::error::LogError;
+    use coa_kernel::logging::EventSchemaRegistry;
+    use std::sync::Arc;
+
+    let event = |action: Action, result: &str| Event {
+        event_id: EventId::new(),
+        timestamp: 1,
+        node_id: NodeId::new(),
+        autonomy_level: AutonomyLevel::L0,
+        directive_hash: DirectiveProfileHash([0u8; 32]),
+        action,
+        result: result.to_string(),
+        schema_version: None,
+        correlation_id: None,
+        prev_hash: [0u8; 32],
+        hash: [0u8; 32],
+        hash_algorithm: None,
+        tenant_id: None,
+    };
+
+    let log = EventLog::default().with_schemas(Arc::new(EventSchemaRegistry::default()));
+    log.append(event(Action::ArtifactRead, r#"{"path": "src/a.rs", "hash": "aa"}"#)).unwrap();
+    log.append(event(Action::ArtifactRead, r#"{"path": "src/b.rs", "hash": "bb"}"#)).unwrap();
+    log.append(event(Action::from("deploy"), "staging")).unwrap();
+    assert!(matches!(
+        log.append(event(Action::ArtifactRead, r#"{"path": "src/c.rs"}"#)),
+        Err(LogError::SchemaViolation { .. })
+    ));
+
+    let events = log.events();
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[0].schema_version, Some(1));
+    assert_eq!(events[2].schema_version, None);
+    assert_eq!(events[2].action, "deploy");
+
+    // Stamped events survive an encode/decode round trip
+    let lines = log.to_json_lines().unwrap();
+    assert!(lines.contains(r#""action":"artifact_read""#));
+    let log = EventLog::from_json_lines(&lines).unwrap();
+
+    let filter = EventFilter::for_action(Action::ArtifactRead).with_result_field("path", "src/b.rs");
+    let entries = log.query_events(filter, 10).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].event.result, events[1].result);
+    let deploys = log.query_events(EventFilter::for_action("deploy"), 10).unwrap();
+    assert_eq!(deploys.len(), 1);
+}
+
+#[test]
+fn test_paged_queries_follow_cursors_in_both_orders() {
+    use coa_kernel::api::{EventFilter, EventLogger, EventQuery};
+
+    let log = EventLog::default();
+    let nodes = [NodeId::new(), NodeId::new(), NodeId::new()];
+    for i in 0..300u64 {
+        log.append(Event {
+            event_id: EventId::new(),
+            // Timestamps are not monotonic in log order
+            timestamp: (i * 7919) % 1000,
+            node_id: nodes[(i % 3) as usize],
+            autonomy_level: AutonomyLevel::L0,
+            directive_hash: DirectiveProfileHash([0u8; 32]),
+            action: if i % 5 == 0 { Action::Approval } else { Action::Transition },
+            result: "ok".to_string(),
+            schema_version: None,
+            correlation_id: None,
+            prev_hash: [0u8; 32],
+            hash: [0u8; 32],
+            hash_algorithm: None,
+            tenant_id: None,
+        })
+        .unwrap();
+    }
+    let events = log.events();
+
+    let filters = [
+        EventFilter::default(),
+        EventFilter { node_id: Some(nodes[1]), ..EventFilter::default() },
+        EventFilter {
+            since_timestamp: Some(200),
+            until_timestamp: Some(600),
+            ..EventFilter::for_action(Action::Transition)
+        },
+        EventFilter {
+            node_id: Some(nodes[2]),
+            until_timestamp: Some(500),
+            ..EventFilter::default()
+        },
+    ];
+    for filter in filters {
+        let expected: Vec<_> = events.iter().filter(|e| filter.matches(e)).map(|e| e.event_id).collect();
+        for descending in [false, true] {
+            let mut seen = Vec::new();
+            let mut query = EventQuery::new(filter.clone(), 17);
+            if descending {
+                query = query.descending();
+            }
+            loop {
+                let page = log.query_page(query.clone()).unwrap();
+                assert!(page.entries.len() <= 17);
+                assert!(page.entries.iter().all(|e| e.verified));
+                seen.extend(page.entries.iter().map(|e| e.event.event_id));
+                match page.next {
+                    Some(cursor) => query = query.after(cursor),
+                    None => break,
+                }
+            }
+            let mut expected = expected.clone();
+            if descending {
+                expected.reverse();
+            }
+            assert_eq!(seen, expected);
+        }
+    }
+}
@@ -3,38 +3,14 @@
 //! Tests for construction-phase rejection of invalid graphs.
 //!
 use coa_kernel::prelude::*;
-use coa_kernel::DirectiveSet;
-use std::collections::BTreeMap;
-use ed25519_dalek::SigningKey;
-use rand::rngs::OsRng;
-
-fn create_signing_key() -> SigningKey {
-    let mut csprng = OsRng;
-    SigningKey::generate(&mut csprng)
-}
-
-fn create_test_spec() -> NodeSpecV2 {
-    NodeSpecV2 {
-        directives: DirectiveSet {
-            directives: BTreeMap::new(),
-        },
-        autonomy_ceiling: AutonomyLevel::L3,
-        resource_bounds: ResourceCaps {
-            cpu_time_ms: 1000,
-            memory_bytes: 1024 * 1024,
-            token_limit: 1000,
-            iteration_cap: 100,
-        },
-        expansion_type: None,
-    }
-}
+use coa_test_utils::kernel::{default_signing_key, test_node_spec};
 
 #[test]
 fn test_rejects_cycle_in_production() {
     let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
     
-    let n1 = builder.add_node(create_test_spec());
-    let n2 = builder.add_node(create_test_spec());
+    let n1 = builder.add_node(test_node_spec());
+    let n2 = builder.add_node(test_node_spec());
     
     // Add edge 1 -> 2
     builder.add_edge(n1, n2).unwrap();
@@ -48,7 +24,7 @@ fn test_rejects_cycle_in_production() {
 fn test_rejects_self_loop() {
     let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
     
-    let n1 = builder.add_node(create_test_spec());
+    let n1 = builder.add_node(test_node_spec());
     
     // Self-loop should fail
     let result = builder.add_edge(n1, n1);
@@ -59,7 +35,7 @@ fn test_rejects_self_loop() {
 fn test_rejects_edge_to_nonexistent_node() {
     let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
     
-    let n1 = builder.add_node(create_test_spec());
+    let n1 = builder.add_node(test_node_spec());
     let fake_node = NodeId::new(); // Not in builder
     
     let result = builder.add_edge(n1, fake_node);
@@ -68,7 +44,7 @@ fn test_rejects_edge_to_nonexistent_node() {
 
 #[test]
 fn test_validation_rejects_autonomy_above_ceiling() {
-    let signing_key = create_signing_key();
+    let signing_key = default_signing_key();
     
     // Create builder with L3 max autonomy
     let limits = SystemLimits {
@@ -88,7 +64,7 @@ fn test_validation_rejects_autonomy_above_ceiling() {
     // Add node with L5 autonomy (exceeds L3 ceiling)
     let spec = NodeSpecV2 {
         autonomy_ceiling: AutonomyLevel::L5,
-        ..create_test_spec()
+        ..test_node_spec()
     };
     builder.add_node(spec);
     
@@ -99,7 +75,7 @@ fn test_validation_rejects_autonomy_above_ceiling() {
 
 #[test]
 fn test_validation_rejects_impossible_resource_bounds() {
-    let signing_key = create_signing_key();
+    let signing_key = default_signing_key();
     
     let limits = SystemLimits {
         max_autonomy: AutonomyLevel::L5,
@@ -123,7 +99,7 @@ fn test_validation_rejects_impossible_resource_bounds() {
             token_limit: 1000,
             iteration_cap: 100,
         },
-        ..create_test_spec()
+        ..test_node_spec()
     };
     builder.add_node(spec);
     
@@ -134,11 +110,11 @@ fn test_validation_rejects_impossible_resource_bounds() {
 
 #[test]
 fn test_sandbox_allows_cycles() {
-    let signing_key = create_signing_key();
+    let signing_key = default_signing_key();
     let mut builder = GraphBuilder::new(GraphType::SandboxGraph);
     
-    let n1 = builder.add_node(create_test_spec());
-    let n2 = builder.add_node(create_test_spec());
+    let n1 = builder.add_node(test_node_spec());
+    let n2 = builder.add_node(test_node_spec());
     
     builder.add_edge(n1, n2).unwrap();
     
@@ -153,14 +129,14 @@ fn test_sandbox_allows_cycles() {
 
 #[test]
 fn test_construction_rejects_invalid_graph_structure() {
-    let signing_key = create_signing_key();
+    let signing_key = default_signing_key();
     let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
     
     // Create a more complex graph that would be valid structurally
     // but we want to test that validation catches issues
-    let n1 = builder.add_node(create_test_spec());
-    let n2 = builder.add_node(create_test_spec());
-    let n3 = builder.add_node(create_test_spec());
+    let n1 = builder.add_node(test_node_spec());
+    let n2 = builder.add_node(test_node_spec());
+    let n3 = builder.add_node(test_node_spec());
     
     // Valid edges
     builder.add_edge(n1, n2).unwrap();
@@ -177,7 +153,7 @@ fn test_construction_rejects_invalid_graph_structure() {
 
 #[test]
 fn test_empty_graph_validates() {
-    let signing_key = create_signing_key();
+    let signing_key = default_signing_key();
     let builder = GraphBuilder::new(GraphType::ProductionDAG);
     
     // Empty graph should validate
@@ -37,6 +37,7 @@ fn stress_test_10k_nodes() {
                 iteration_cap: 100,
             },
             expansion_type: None,
+            inputs: Vec::new(),
         };
         builder.add_node(spec);
         
@@ -92,6 +93,7 @@ fn stress_test_graph_with_edges() {
                 iteration_cap: 100,
             },
             expansion_type: None,
+            inputs: Vec::new(),
         };
         node_ids.push(builder.add_node(spec));
     }
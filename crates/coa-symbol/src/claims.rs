@@ -0,0 +1,575 @@
+//! Subtree claim service shared by agents
+//!
+//! Agents acquire a claim on a [`SymbolPath`] subtree before producing
+//! deltas for it and release it afterwards. Claims follow the same rule as
+//! [`SingleWriterValidator`]: two claims conflict when one path is a prefix
+//! of the other. A holder's own claims never block it.
+//!
+//! # Waiting
+//!
+//! [`ClaimService::acquire`] queues contended requests FIFO: a request is
+//! granted once nothing held by another agent overlaps it and no earlier
+//! queued request overlaps it. Dropping the future leaves the queue.
+//!
+//! # Deadlocks
+//!
+//! Before queueing, the service walks the wait-for graph (waiter → holders
+//! of overlapping claims and earlier overlapping waiters). If the new edge
+//! would close a cycle, the request fails with [`ClaimError::Deadlock`]
+//! instead of waiting forever.
+
+use crate::validation::{SingleWriterValidator, ValidationError};
+use coa_artifact::SymbolPath;
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+
+/// Identifier of a granted claim
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ClaimId(u64);
+
+/// Snapshot of an active claim
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClaimInfo {
+    /// Claim identifier
+    pub id: ClaimId,
+    /// Agent holding the claim
+    pub holder: String,
+    /// Claimed subtree
+    pub path: SymbolPath,
+}
+
+/// Contention statistics
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClaimMetrics {
+    /// Claims granted
+    pub acquired: u64,
+    /// Claims granted only after waiting
+    pub contended: u64,
+    /// Requests rejected by `try_acquire` because of a conflict
+    pub rejected: u64,
+    /// Requests rejected because waiting would deadlock
+    pub deadlocks: u64,
+    /// Requests abandoned while queued
+    pub abandoned: u64,
+    /// Claims released
+    pub released: u64,
+    /// Total time contended requests spent queued
+    pub total_wait: Duration,
+    /// Longest single wait
+    pub max_wait: Duration,
+    /// Claims currently held
+    pub active: usize,
+    /// Requests currently queued
+    pub waiting: usize,
+}
+
+impl ClaimMetrics {
+    /// Fraction of granted claims that had to wait
+    #[inline]
+    #[must_use]
+    pub fn contention_rate(&self) -> f64 {
+        if self.acquired == 0 {
+            0.0
+        } else {
+            #[allow(clippy::cast_precision_loss)]
+            let rate = self.contended as f64 / self.acquired as f64;
+            rate
+        }
+    }
+}
+
+/// Claim acquisition errors
+#[derive(Debug, thiserror::Error)]
+pub enum ClaimError {
+    /// Path is not a valid claim target
+    #[error(transparent)]
+    InvalidPath(#[from] ValidationError),
+
+    /// Path overlaps a claim held by another agent
+    #[error("'{path}' conflicts with '{existing}' held by {holder}")]
+    Conflict {
+        path: SymbolPath,
+        existing: SymbolPath,
+        holder: String,
+    },
+
+    /// Waiting would close a cycle of agents waiting on each other
+    #[error("deadlock: {}", cycle.join(" -> "))]
+    Deadlock { cycle: Vec<String> },
+}
+
+#[derive(Debug)]
+struct Held {
+    holder: String,
+    path: SymbolPath,
+}
+
+#[derive(Debug)]
+struct Waiter {
+    id: ClaimId,
+    holder: String,
+    path: SymbolPath,
+    queued_at: Instant,
+    grant: oneshot::Sender<()>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    next_id: u64,
+    held: HashMap<ClaimId, Held>,
+    queue: VecDeque<Waiter>,
+    metrics: ClaimMetrics,
+}
+
+impl State {
+    fn next_id(&mut self) -> ClaimId {
+        self.next_id += 1;
+        ClaimId(self.next_id)
+    }
+
+    /// Holders of claims that block `holder` from taking `path`
+    fn blocking_claims<'a>(
+        &'a self,
+        holder: &'a str,
+        path: &'a SymbolPath,
+    ) -> impl Iterator<Item = &'a Held> + 'a {
+        self.held
+            .values()
+            .filter(move |h| h.holder != holder && SingleWriterValidator::paths_overlap(&h.path, path))
+    }
+
+    /// Agents `holder` would wait on for `path`, given the first `ahead` queued waiters
+    fn blockers(&self, holder: &str, path: &SymbolPath, ahead: usize) -> HashSet<String> {
+        let mut blockers: HashSet<String> = self
+            .blocking_claims(holder, path)
+            .map(|h| h.holder.clone())
+            .collect();
+        blockers.extend(
+            self.queue
+                .iter()
+                .take(ahead)
+                .filter(|w| w.holder != holder && SingleWriterValidator::paths_overlap(&w.path, path))
+                .map(|w| w.holder.clone()),
+        );
+        blockers
+    }
+
+    /// Cycle through `holder` if it starts waiting on `blockers`
+    fn find_cycle(&self, holder: &str, blockers: &HashSet<String>) -> Option<Vec<String>> {
+        let mut edges: HashMap<&str, HashSet<String>> = HashMap::new();
+        for (i, w) in self.queue.iter().enumerate() {
+            edges
+                .entry(w.holder.as_str())
+                .or_default()
+                .extend(self.blockers(&w.holder, &w.path, i));
+        }
+
+        // Depth-first search from each blocker back to `holder`
+        let mut stack: Vec<Vec<String>> = blockers
+            .iter()
+            .map(|b| vec![holder.to_string(), b.clone()])
+            .collect();
+        let mut visited = HashSet::new();
+        while let Some(trail) = stack.pop() {
+            let last = trail.last().map_or("", String::as_str);
+            if last == holder {
+                return Some(trail);
+            }
+            if !visited.insert(last.to_string()) {
+                continue;
+            }
+            for next in edges.get(last).into_iter().flatten() {
+                let mut trail = trail.clone();
+                trail.push(next.clone());
+                stack.push(trail);
+            }
+        }
+        None
+    }
+
+    /// Grant every queued request that is no longer blocked
+    fn grant_ready(&mut self) {
+        let mut i = 0;
+        while i < self.queue.len() {
+            let next = &self.queue[i];
+            if next.grant.is_closed() {
+                self.queue.remove(i);
+                self.metrics.abandoned += 1;
+                continue;
+            }
+            if !self.blockers(&next.holder, &next.path, i).is_empty() {
+                i += 1;
+                continue;
+            }
+
+            let Some(waiter) = self.queue.remove(i) else {
+                break;
+            };
+            if waiter.grant.send(()).is_err() {
+                self.metrics.abandoned += 1;
+                continue;
+            }
+            let wait = waiter.queued_at.elapsed();
+            self.metrics.acquired += 1;
+            self.metrics.contended += 1;
+            self.metrics.total_wait += wait;
+            self.metrics.max_wait = self.metrics.max_wait.max(wait);
+            self.held.insert(
+                waiter.id,
+                Held {
+                    holder: waiter.holder,
+                    path: waiter.path,
+                },
+            );
+        }
+    }
+}
+
+/// Shared subtree claim service
+///
+/// Cheap to clone; clones share the same claim table.
+#[derive(Debug, Clone, Default)]
+pub struct ClaimService {
+    state: Arc<Mutex<State>>,
+}
+
+impl ClaimService {
+    /// Create empty claim service
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Acquire a claim without waiting
+    ///
+    /// # Errors
+    /// - `ClaimError::InvalidPath` if the path is malformed
+    /// - `ClaimError::Conflict` if another agent holds an overlapping claim
+    ///   or is already queued for one
+    pub fn try_acquire(
+        &self,
+        holder: impl Into<String>,
+        path: SymbolPath,
+    ) -> Result<ClaimGuard, ClaimError> {
+        SingleWriterValidator::validate_path_format(&path)?;
+        let holder = holder.into();
+        let mut state = self.state.lock();
+
+        let conflict = state
+            .blocking_claims(&holder, &path)
+            .map(|h| (h.path.clone(), h.holder.clone()))
+            .next()
+            .or_else(|| {
+                state
+                    .queue
+                    .iter()
+                    .find(|w| w.holder != holder && SingleWriterValidator::paths_overlap(&w.path, &path))
+                    .map(|w| (w.path.clone(), w.holder.clone()))
+            });
+        if let Some((existing, other)) = conflict {
+            state.metrics.rejected += 1;
+            return Err(ClaimError::Conflict {
+                path,
+                existing,
+                holder: other,
+            });
+        }
+
+        let id = state.next_id();
+        state.metrics.acquired += 1;
+        state.held.insert(id, Held { holder, path });
+        Ok(self.guard(id))
+    }
+
+    /// Acquire a claim, waiting for conflicting claims to be released
+    ///
+    /// # Errors
+    /// - `ClaimError::InvalidPath` if the path is malformed
+    /// - `ClaimError::Deadlock` if waiting would deadlock
+    pub async fn acquire(
+        &self,
+        holder: impl Into<String>,
+        path: SymbolPath,
+    ) -> Result<ClaimGuard, ClaimError> {
+        SingleWriterValidator::validate_path_format(&path)?;
+        let holder = holder.into();
+
+        let (id, granted) = {
+            let mut state = self.state.lock();
+            let blockers = state.blockers(&holder, &path, state.queue.len());
+            let id = state.next_id();
+
+            if blockers.is_empty() {
+                state.metrics.acquired += 1;
+                state.held.insert(id, Held { holder, path });
+                return Ok(self.guard(id));
+            }
+            if let Some(cycle) = state.find_cycle(&holder, &blockers) {
+                state.metrics.deadlocks += 1;
+                return Err(ClaimError::Deadlock { cycle });
+            }
+
+            let (grant, granted) = oneshot::channel();
+            state.queue.push_back(Waiter {
+                id,
+                holder,
+                path,
+                queued_at: Instant::now(),
+                grant,
+            });
+            (id, granted)
+        };
+
+        // Guard first: if this future is dropped after the grant was sent,
+        // dropping the guard still releases the claim
+        let guard = self.guard(id);
+        // The sender lives in the queue, which only drops it after granting
+        let _ = granted.await;
+        Ok(guard)
+    }
+
+    /// Release a claim and wake any waiters it was blocking
+    ///
+    /// A claim still queued is withdrawn instead, so it no longer blocks
+    /// later requests. Returns `false` if the claim was not held.
+    #[must_use]
+    pub fn release(&self, id: ClaimId) -> bool {
+        let mut state = self.state.lock();
+        let released = state.held.remove(&id).is_some();
+        if released {
+            state.metrics.released += 1;
+            state.grant_ready();
+        } else if let Some(pos) = state.queue.iter().position(|w| w.id == id) {
+            state.queue.remove(pos);
+            state.metrics.abandoned += 1;
+            state.grant_ready();
+        }
+        released
+    }
+
+    /// Release every claim held by an agent (e.g. after it crashed)
+    ///
+    /// Returns the number of claims released.
+    #[must_use]
+    pub fn release_all(&self, holder: &str) -> usize {
+        let mut state = self.state.lock();
+        let before = state.held.len();
+        state.held.retain(|_, h| h.holder != holder);
+        let released = before - state.held.len();
+        if released > 0 {
+            state.metrics.released += released as u64;
+            state.grant_ready();
+        }
+        released
+    }
+
+    /// Active claims
+    #[must_use]
+    pub fn claims(&self) -> Vec<ClaimInfo> {
+        let state = self.state.lock();
+        let mut claims: Vec<ClaimInfo> = state
+            .held
+            .iter()
+            .map(|(id, h)| ClaimInfo {
+                id: *id,
+                holder: h.holder.clone(),
+                path: h.path.clone(),
+            })
+            .collect();
+        claims.sort_by_key(|c| c.id);
+        claims
+    }
+
+    /// Current contention statistics
+    #[must_use]
+    pub fn metrics(&self) -> ClaimMetrics {
+        let state = self.state.lock();
+        ClaimMetrics {
+            active: state.held.len(),
+            waiting: state.queue.iter().filter(|w| !w.grant.is_closed()).count(),
+            ..state.metrics.clone()
+        }
+    }
+
+    fn guard(&self, id: ClaimId) -> ClaimGuard {
+        ClaimGuard {
+            id,
+            service: self.clone(),
+        }
+    }
+}
+
+/// Held claim; released when dropped
+#[derive(Debug)]
+#[must_use = "the claim is released as soon as the guard is dropped"]
+pub struct ClaimGuard {
+    id: ClaimId,
+    service: ClaimService,
+}
+
+impl ClaimGuard {
+    /// Claim identifier
+    #[inline]
+    #[must_use]
+    pub fn id(&self) -> ClaimId {
+        self.id
+    }
+
+    /// Release the claim now
+    pub fn release(self) {
+        drop(self);
+    }
+}
+
+impl Drop for ClaimGuard {
+    fn drop(&mut self) {
+        let _ = self.service.release(self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn path(s: &str) -> SymbolPath {
+        SymbolPath::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn disjoint_claims_coexist() {
+        let service = ClaimService::new();
+        let _a = service.try_acquire("a", path("auth.login")).unwrap();
+        let _b = service.try_acquire("b", path("auth.register")).unwrap();
+        assert_eq!(service.claims().len(), 2);
+    }
+
+    #[test]
+    fn overlapping_claim_is_rejected() {
+        let service = ClaimService::new();
+        let _a = service.try_acquire("a", path("auth")).unwrap();
+
+        let result = service.try_acquire("b", path("auth.login"));
+        assert!(matches!(result, Err(ClaimError::Conflict { ref holder, .. }) if holder == "a"));
+        assert!(service.try_acquire("a", path("auth.login")).is_ok());
+        assert_eq!(service.metrics().rejected, 1);
+    }
+
+    #[test]
+    fn guard_drop_releases() {
+        let service = ClaimService::new();
+        let guard = service.try_acquire("a", path("auth")).unwrap();
+        guard.release();
+        assert!(service.try_acquire("b", path("auth")).is_ok());
+    }
+
+    #[test]
+    fn invalid_path_is_rejected() {
+        let service = ClaimService::new();
+        let result = service.try_acquire("a", SymbolPath::root());
+        assert!(matches!(result, Err(ClaimError::InvalidPath(_))));
+    }
+
+    #[tokio::test]
+    async fn waiter_is_granted_on_release() {
+        let service = ClaimService::new();
+        let held = service.try_acquire("a", path("auth")).unwrap();
+
+        let waiting = tokio::spawn({
+            let service = service.clone();
+            async move { service.acquire("b", path("auth.login")).await.map(|g| g.id()) }
+        });
+        tokio::task::yield_now().await;
+        while service.metrics().waiting == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        drop(held);
+        let id = waiting.await.unwrap().unwrap();
+        let metrics = service.metrics();
+        assert_eq!(metrics.contended, 1);
+        assert_eq!(metrics.acquired, 2);
+        assert!(!service.claims().iter().any(|c| c.id == id));
+    }
+
+    #[tokio::test]
+    async fn circular_wait_is_detected() {
+        let service = ClaimService::new();
+        let _a = service.try_acquire("a", path("auth")).unwrap();
+        let _b = service.try_acquire("b", path("billing")).unwrap();
+
+        // a waits for billing (held by b)
+        let pending = tokio::spawn({
+            let service = service.clone();
+            async move { service.acquire("a", path("billing.invoice")).await.map(|g| g.id()) }
+        });
+        while service.metrics().waiting == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        // b waiting for auth (held by a) would close the cycle
+        let result = service.acquire("b", path("auth.login")).await;
+        assert!(matches!(result, Err(ClaimError::Deadlock { ref cycle }) if cycle.len() == 3));
+        assert_eq!(service.metrics().deadlocks, 1);
+
+        pending.abort();
+    }
+
+    #[tokio::test]
+    async fn abandoned_waiter_leaves_queue() {
+        let service = ClaimService::new();
+        let held = service.try_acquire("a", path("auth")).unwrap();
+
+        let result = tokio::time::timeout(
+            Duration::from_millis(10),
+            service.acquire("b", path("auth")),
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(service.metrics().waiting, 0);
+
+        drop(held);
+        assert_eq!(service.metrics().abandoned, 1);
+        assert!(service.claims().is_empty());
+    }
+
+    #[tokio::test]
+    async fn dropped_acquire_stops_blocking_others() {
+        let service = ClaimService::new();
+        let _login = service.try_acquire("a", path("auth.login")).unwrap();
+        let _billing = service.try_acquire("b", path("billing")).unwrap();
+
+        // b queues for all of auth, then gives up while a still holds login
+        let result =
+            tokio::time::timeout(Duration::from_millis(10), service.acquire("b", path("auth")))
+                .await;
+        assert!(result.is_err());
+        let metrics = service.metrics();
+        assert_eq!((metrics.waiting, metrics.abandoned), (0, 1));
+
+        // Nothing b asked for stands in the way any more
+        assert!(service.try_acquire("c", path("auth.register")).is_ok());
+        // a waiting on b is not a cycle now that b no longer waits on a
+        let result = tokio::time::timeout(
+            Duration::from_millis(10),
+            service.acquire("a", path("billing.invoice")),
+        )
+        .await;
+        assert!(result.is_err(), "expected a to wait, got {result:?}");
+        assert_eq!(service.metrics().deadlocks, 0);
+    }
+
+    #[test]
+    fn release_all_frees_holder() {
+        let service = ClaimService::new();
+        std::mem::forget(service.try_acquire("a", path("auth")).unwrap());
+        std::mem::forget(service.try_acquire("a", path("billing")).unwrap());
+
+        assert_eq!(service.release_all("a"), 2);
+        assert!(service.try_acquire("b", path("auth")).is_ok());
+    }
+}
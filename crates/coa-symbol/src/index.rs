@@ -2,10 +2,14 @@
 //!
 //! Provides [`SymbolRefIndex`] for O(log n) symbol lookup using radix_trie.
 
+use crate::semantic::SymbolEmbedding;
 use crate::symbol::SymbolRef;
 use crate::symbol::SymbolRefError;
-use coa_artifact::ContentHash;
+use coa_artifact::{
+    AddressableContent, Artifact, ArtifactType, ContentHash, DeltaError, StructuralDelta, TenantId,
+};
 use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
 use radix_trie::{Trie, TrieCommon};
 use std::sync::RwLock;
 
@@ -18,13 +22,22 @@ use std::sync::RwLock;
 ///
 /// The index is thread-safe with concurrent reads via DashMap for the
 /// reverse index and RwLock for the trie.
+///
+/// Multi-tenant deployments keep one index per tenant
+/// ([`for_tenant`](Self::for_tenant)); symbols are never shared between them.
 #[derive(Debug)]
 pub struct SymbolRefIndex {
+    /// Tenant owning every symbol in the index (`None` if single-tenant)
+    tenant: Option<TenantId>,
+
     /// Radix trie mapping path -> indexed symbol
     trie: RwLock<Trie<String, IndexedSymbol>>,
 
     /// Reverse index: parent_hash -> symbols (for invalidation)
     by_parent: DashMap<ContentHash, Vec<SymbolRef>>,
+
+    /// Reference index: referenced path -> sites that use it
+    references: DashMap<Vec<String>, Vec<SymbolRef>>,
 }
 
 /// Indexed symbol with metadata
@@ -35,7 +48,8 @@ struct IndexedSymbol {
 }
 
 /// Metadata for indexed symbols
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SymbolMetadata {
     /// Symbol kind (function, type, variable, etc.)
     pub kind: SymbolKind,
@@ -48,10 +62,45 @@ pub struct SymbolMetadata {
 
     /// Custom attributes
     pub attributes: Vec<String>,
+
+    /// Hash of the symbol's own content (enables symbol-level staleness checks)
+    pub content_hash: Option<ContentHash>,
+
+    /// Vector embedding for semantic retrieval (see [`SemanticIndex`])
+    ///
+    /// [`SemanticIndex`]: crate::SemanticIndex
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embedding: Option<SymbolEmbedding>,
+}
+
+/// Attribute marking a symbol as an append-only region
+///
+/// `Add` deltas that share an append-only target are merged in content-hash
+/// order instead of conflicting.
+pub const APPEND_ONLY: &str = "append-only";
+
+impl SymbolMetadata {
+    /// Mark symbol as an append-only region
+    #[inline]
+    #[must_use]
+    pub fn append_only(mut self) -> Self {
+        if !self.is_append_only() {
+            self.attributes.push(APPEND_ONLY.to_string());
+        }
+        self
+    }
+
+    /// Check if symbol is an append-only region
+    #[inline]
+    #[must_use]
+    pub fn is_append_only(&self) -> bool {
+        self.attributes.iter().any(|a| a == APPEND_ONLY)
+    }
 }
 
 /// Symbol kind classification
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum SymbolKind {
     /// Unknown/default kind
     #[default]
@@ -77,7 +126,8 @@ pub enum SymbolKind {
 }
 
 /// Symbol visibility
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Visibility {
     /// Public/exported
     #[default]
@@ -90,8 +140,36 @@ pub enum Visibility {
     Restricted,
 }
 
+impl SymbolKind {
+    /// Lowercase name, as used in queries and serialized indexes
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Unknown => "unknown",
+            Self::Function => "function",
+            Self::Type => "type",
+            Self::Variable => "variable",
+            Self::Module => "module",
+            Self::Config => "config",
+            Self::Spec => "spec",
+        }
+    }
+}
+
+impl Visibility {
+    /// Lowercase name, as used in queries and serialized indexes
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Public => "public",
+            Self::Internal => "internal",
+            Self::Restricted => "restricted",
+        }
+    }
+}
+
 /// Source code location
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SourceLocation {
     pub line: usize,
     pub column: usize,
@@ -104,11 +182,30 @@ impl SymbolRefIndex {
     #[must_use]
     pub fn new() -> Self {
         Self {
+            tenant: None,
             trie: RwLock::new(Trie::new()),
             by_parent: DashMap::new(),
+            references: DashMap::new(),
         }
     }
 
+    /// Create empty index holding only `tenant`'s symbols
+    #[inline]
+    #[must_use]
+    pub fn for_tenant(tenant: TenantId) -> Self {
+        Self {
+            tenant: Some(tenant),
+            ..Self::new()
+        }
+    }
+
+    /// Tenant owning the index, if any
+    #[inline]
+    #[must_use]
+    pub fn tenant(&self) -> Option<&TenantId> {
+        self.tenant.as_ref()
+    }
+
     /// Insert symbol into index
     ///
     /// # Errors
@@ -254,6 +351,85 @@ impl SymbolRefIndex {
             .collect()
     }
 
+    /// Check if the symbol at `path` is an append-only region
+    #[must_use]
+    pub fn is_append_region(&self, path: &[String]) -> bool {
+        self.get_by_path(path)
+            .is_some_and(|entry| entry.metadata.is_append_only())
+    }
+
+    /// Index the append-only regions an artifact declares
+    ///
+    /// Regions already indexed are marked append-only in place; new ones are
+    /// inserted under the artifact's hash. Returns the number of regions.
+    ///
+    /// # Errors
+    /// Returns `SymbolRefError::OverlappingClaims` if a new region overlaps
+    /// an indexed symbol
+    pub fn declare_append_regions<T>(&self, artifact: &Artifact<T>) -> Result<usize, SymbolRefError>
+    where
+        T: ArtifactType,
+        T::Content: AddressableContent,
+    {
+        let regions = artifact.content().append_regions();
+        for region in &regions {
+            let key = region.join("/");
+            {
+                let mut trie = self.trie.write().map_err(|_| SymbolRefError::LockPoisoned)?;
+                if let Some(existing) = trie.get_mut(&key) {
+                    existing.metadata = std::mem::take(&mut existing.metadata).append_only();
+                    continue;
+                }
+            }
+            self.insert(
+                SymbolRef::new(region.segments().to_vec(), *artifact.hash()),
+                SymbolMetadata::default().append_only(),
+            )?;
+        }
+        Ok(regions.len())
+    }
+
+    /// Update the metadata of the symbol at `path` in place
+    ///
+    /// Returns `false` if no symbol is indexed at `path`.
+    pub fn update_metadata(&self, path: &[String], update: impl FnOnce(&mut SymbolMetadata)) -> bool {
+        let Ok(mut trie) = self.trie.write() else {
+            return false;
+        };
+        match trie.get_mut(&path.join("/")) {
+            Some(indexed) => {
+                update(&mut indexed.metadata);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Current content hash of the symbol at `path`, if recorded
+    #[must_use]
+    pub fn symbol_hash(&self, path: &[String]) -> Option<ContentHash> {
+        self.get_by_path(path).and_then(|entry| entry.metadata.content_hash)
+    }
+
+    /// Check a delta's base, at symbol granularity when it is pinned
+    ///
+    /// Unpinned deltas must match the artifact hash exactly. Pinned deltas
+    /// also pass when the artifact changed elsewhere but the target symbol's
+    /// indexed hash still equals the pinned one.
+    ///
+    /// # Errors
+    /// Returns `DeltaError::BaseMismatch` or `DeltaError::StaleSymbol`
+    pub fn validate_base<T: ArtifactType>(
+        &self,
+        delta: &StructuralDelta<T>,
+        artifact: &Artifact<T>,
+    ) -> Result<(), DeltaError> {
+        let current = delta
+            .pin()
+            .and_then(|_| self.symbol_hash(delta.target().segments()));
+        delta.validate_base_pinned(artifact, current)
+    }
+
     /// Get all symbols for a parent hash (for invalidation)
     #[inline]
     #[must_use]
@@ -268,6 +444,12 @@ impl SymbolRefIndex {
     ///
     /// Returns number of symbols removed.
     pub fn remove_by_parent(&self, parent_hash: &ContentHash) -> usize {
+        // Reference sites inside the changed artifact are stale too
+        self.references.retain(|_, sites| {
+            sites.retain(|site| site.parent_hash() != parent_hash);
+            !sites.is_empty()
+        });
+
         let symbols = match self.by_parent.remove(parent_hash) {
             Some((_, symbols)) => symbols,
             None => return 0,
@@ -282,10 +464,202 @@ impl SymbolRefIndex {
         for symbol in &symbols {
             trie.remove(&symbol.to_trie_key());
         }
+        count
+    }
+
+    /// Remove every symbol at or beneath `prefix`, and the reference sites
+    /// inside them
+    ///
+    /// Unlike [`remove_by_parent`](Self::remove_by_parent), symbols of other
+    /// artifacts with the same content hash are kept. Returns number of
+    /// symbols removed.
+    pub fn remove_subtree(&self, prefix: &[String]) -> usize {
+        let under = |symbol: &SymbolRef| symbol.path().starts_with(prefix);
+        self.references.retain(|_, sites| {
+            sites.retain(|site| !under(site));
+            !sites.is_empty()
+        });
+
+        let removed: Vec<SymbolRef> = self
+            .get_descendants(prefix)
+            .into_iter()
+            .map(|entry| entry.symbol)
+            .filter(|symbol| under(symbol))
+            .collect();
+        if removed.is_empty() {
+            return 0;
+        }
+        for symbol in &removed {
+            if let Some(mut siblings) = self.by_parent.get_mut(symbol.parent_hash()) {
+                siblings.retain(|s| s != symbol);
+            }
+        }
+        self.by_parent.retain(|_, symbols| !symbols.is_empty());
+
+        let Ok(mut trie) = self.trie.write() else {
+            return 0;
+        };
+        for symbol in &removed {
+            trie.remove(&symbol.to_trie_key());
+        }
+        removed.len()
+    }
 
+    /// Rebind symbols of the artifact hashed `old` to `new`
+    ///
+    /// For artifacts re-hashed with another algorithm. Recorded symbol
+    /// hashes used the old algorithm and are dropped, so pinned deltas fall
+    /// back to whole-artifact base checks until the file is re-indexed.
+    /// Returns number of symbols rebound.
+    pub fn rebind_parent(&self, old: &ContentHash, new: ContentHash) -> usize {
+        for mut sites in self.references.iter_mut() {
+            for site in sites.value_mut() {
+                if site.parent_hash() == old {
+                    *site = site.with_parent_hash(new);
+                }
+            }
+        }
+
+        let Some((_, symbols)) = self.by_parent.remove(old) else {
+            return 0;
+        };
+        let rebound: Vec<SymbolRef> = symbols.iter().map(|s| s.with_parent_hash(new)).collect();
+
+        if let Ok(mut trie) = self.trie.write() {
+            for symbol in &rebound {
+                if let Some(indexed) = trie.get_mut(&symbol.to_trie_key()) {
+                    indexed.symbol = symbol.clone();
+                    indexed.metadata.content_hash = None;
+                }
+            }
+        }
+
+        let count = rebound.len();
+        self.by_parent.entry(new).or_default().extend(rebound);
         count
     }
 
+    /// Record that `site` refers to the symbol at `target`
+    ///
+    /// `site` is the referencing symbol; its parent hash identifies the
+    /// artifact containing the reference, which may differ from the
+    /// artifact defining `target`.
+    pub fn add_reference(&self, target: &[String], site: SymbolRef) {
+        let mut sites = self.references.entry(target.to_vec()).or_default();
+        if !sites.contains(&site) {
+            sites.push(site);
+        }
+    }
+
+    /// References to `target` or any symbol beneath it
+    #[must_use]
+    pub fn references_to(&self, target: &[String]) -> Vec<SymbolReference> {
+        let mut found: Vec<SymbolReference> = self
+            .references
+            .iter()
+            .filter(|entry| entry.key().starts_with(target))
+            .flat_map(|entry| {
+                let referenced = entry.key().clone();
+                entry
+                    .value()
+                    .iter()
+                    .map(|site| SymbolReference {
+                        target: referenced.clone(),
+                        site: site.clone(),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        found.sort_by(|a, b| (&a.target, a.site.path()).cmp(&(&b.target, b.site.path())));
+        found
+    }
+
+    /// Symbols near `path`, nearest first
+    ///
+    /// Covers every symbol within `radius` steps of `path` in the symbol
+    /// tree (siblings are two steps apart) and, one step away, every symbol
+    /// referencing `path` or anything beneath it. Ties are ordered by path.
+    #[must_use]
+    pub fn neighborhood(&self, path: &[String], radius: usize) -> Vec<Neighbor> {
+        let distance = |other: &[String]| {
+            let common = path.iter().zip(other).take_while(|(a, b)| a == b).count();
+            (path.len() - common) + (other.len() - common)
+        };
+        let scope = path[..path.len().saturating_sub(radius)].join("/");
+        let mut found: Vec<Neighbor> = self
+            .scan(&scope, |symbol, _| distance(symbol.path()) <= radius)
+            .into_iter()
+            .map(|entry| Neighbor {
+                distance: distance(entry.symbol.path()),
+                entry,
+            })
+            .collect();
+
+        if radius > 0 {
+            for reference in self.references_to(path) {
+                let site = reference.site.path();
+                if let Some(near) = found.iter_mut().find(|n| n.entry.symbol.path() == site) {
+                    near.distance = near.distance.min(1);
+                    continue;
+                }
+                let entry = self.get_by_path(site).unwrap_or(IndexEntry {
+                    symbol: reference.site,
+                    metadata: SymbolMetadata::default(),
+                });
+                found.push(Neighbor { entry, distance: 1 });
+            }
+        }
+
+        found.sort_by(|a, b| {
+            (a.distance, a.entry.symbol.path()).cmp(&(b.distance, b.entry.symbol.path()))
+        });
+        found
+    }
+
+    /// Build an index from previously exported entries
+    ///
+    /// # Errors
+    /// Returns error if entries duplicate or overlap each other
+    pub fn from_entries(entries: impl IntoIterator<Item = IndexEntry>) -> Result<Self, SymbolRefError> {
+        let index = Self::new();
+        for entry in entries {
+            index.insert(entry.symbol, entry.metadata)?;
+        }
+        Ok(index)
+    }
+
+    /// All indexed symbols, ordered by path
+    #[must_use]
+    pub fn entries(&self) -> Vec<IndexEntry> {
+        self.scan("", |_, _| true)
+    }
+
+    /// Entries under trie key `prefix` accepted by `keep`, ordered by path
+    ///
+    /// Filters run under the read lock so rejected entries are never cloned.
+    pub(crate) fn scan(
+        &self,
+        prefix: &str,
+        mut keep: impl FnMut(&SymbolRef, &SymbolMetadata) -> bool,
+    ) -> Vec<IndexEntry> {
+        let Ok(trie) = self.trie.read() else {
+            return Vec::new();
+        };
+        let mut found: Vec<IndexEntry> = match trie.get_raw_descendant(prefix) {
+            Some(subtrie) => subtrie
+                .values()
+                .filter(|idx| keep(&idx.symbol, &idx.metadata))
+                .map(|idx| IndexEntry {
+                    symbol: idx.symbol.clone(),
+                    metadata: idx.metadata.clone(),
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        found.sort_by(|a, b| a.symbol.path().cmp(b.symbol.path()));
+        found
+    }
+
     /// Get total symbol count
     #[must_use]
     pub fn len(&self) -> usize {
@@ -363,7 +737,7 @@ impl Default for SymbolRefIndex {
 }
 
 /// Entry returned from index lookups
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IndexEntry {
     /// The symbol reference
     pub symbol: SymbolRef,
@@ -372,6 +746,26 @@ pub struct IndexEntry {
     pub metadata: SymbolMetadata,
 }
 
+/// Use of a symbol from another location
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolReference {
+    /// Path of the referenced symbol
+    pub target: Vec<String>,
+
+    /// Referencing symbol; its parent hash is the containing artifact
+    pub site: SymbolRef,
+}
+
+/// Symbol returned by [`SymbolRefIndex::neighborhood`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Neighbor {
+    /// The symbol and its metadata
+    pub entry: IndexEntry,
+
+    /// Steps from the queried path (tree edges; 1 for referencing sites)
+    pub distance: usize,
+}
+
 // SymbolRefError re-exported from symbol module
 
 #[cfg(test)]
@@ -390,6 +784,40 @@ mod tests {
         SymbolRef::new(path.iter().map(|s| s.to_string()).collect(), hash)
     }
 
+    #[test]
+    fn neighborhood_orders_by_distance() {
+        let index = SymbolRefIndex::new();
+        let h = test_hash();
+        for path in [
+            &["auth", "login"][..],
+            &["auth", "logout"],
+            &["auth", "session", "store"],
+            &["billing", "invoice"],
+        ] {
+            index.insert(make_symbol(path, h), SymbolMetadata::default()).unwrap();
+        }
+        index.add_reference(&["auth".to_string(), "login".to_string()], make_symbol(&["billing", "invoice"], h));
+        index.add_reference(&["auth".to_string(), "login".to_string()], make_symbol(&["api", "routes"], h));
+
+        let login = ["auth".to_string(), "login".to_string()];
+        let near: Vec<_> = index
+            .neighborhood(&login, 2)
+            .into_iter()
+            .map(|n| (n.entry.symbol.path().join("."), n.distance))
+            .collect();
+        assert_eq!(
+            near,
+            [
+                ("auth.login".to_string(), 0),
+                ("api.routes".to_string(), 1),
+                ("billing.invoice".to_string(), 1),
+                ("auth.logout".to_string(), 2),
+            ]
+        );
+        assert_eq!(index.neighborhood(&login, 3).len(), 5);
+        assert_eq!(index.neighborhood(&login, 0).len(), 1);
+    }
+
     #[test]
     fn index_insert_and_lookup() {
         let index = SymbolRefIndex::new();
@@ -532,6 +960,29 @@ mod tests {
         assert_eq!(index.len(), 1);
     }
 
+    #[test]
+    fn index_remove_subtree_keeps_same_hash_elsewhere() {
+        let index = SymbolRefIndex::new();
+        let h = test_hash_n(1);
+        let path = |parts: &[&str]| parts.iter().copied().map(String::from).collect::<Vec<_>>();
+
+        for file in ["a.rs", "ab.rs", "b.rs"] {
+            index
+                .insert(make_symbol(&[file, "run"], h), SymbolMetadata::default())
+                .unwrap();
+        }
+        index.add_reference(&path(&["b.rs", "run"]), make_symbol(&["a.rs", "run"], h));
+        index.add_reference(&path(&["a.rs", "run"]), make_symbol(&["b.rs", "run"], h));
+
+        assert_eq!(index.remove_subtree(&path(&["a.rs"])), 1);
+        assert_eq!(index.len(), 2);
+        assert!(index.get_by_path(&path(&["ab.rs", "run"])).is_some());
+        assert_eq!(index.get_by_parent(&h).len(), 2);
+        assert!(index.references_to(&path(&["b.rs", "run"])).is_empty());
+        assert_eq!(index.references_to(&path(&["a.rs", "run"])).len(), 1);
+        assert_eq!(index.remove_subtree(&path(&["a.rs"])), 0);
+    }
+
     #[test]
     fn index_has_any_overlap() {
         let index = SymbolRefIndex::new();
@@ -546,6 +997,25 @@ mod tests {
         assert!(!index.has_any_overlap(&["other".to_string()]));
     }
 
+    #[test]
+    fn index_rebuilds_from_entries() {
+        let index = SymbolRefIndex::new();
+        index
+            .insert(make_symbol(&["b", "y"], test_hash()), SymbolMetadata::default().append_only())
+            .unwrap();
+        index
+            .insert(make_symbol(&["a"], test_hash()), SymbolMetadata::default())
+            .unwrap();
+
+        let entries = index.entries();
+        assert_eq!(entries[0].symbol.path(), ["a"]);
+
+        let rebuilt = SymbolRefIndex::from_entries(entries).unwrap();
+        assert_eq!(rebuilt.len(), 2);
+        assert!(rebuilt.is_append_region(&["b".to_string(), "y".to_string()]));
+        assert!(SymbolRefIndex::from_entries(index.entries().into_iter().chain(index.entries())).is_err());
+    }
+
     #[test]
     fn index_find_conflicts() {
         let index = SymbolRefIndex::new();
@@ -564,10 +1034,138 @@ mod tests {
         assert_eq!(conflicts.len(), 2); // Both symbols are under "auth"
     }
 
+    #[derive(Debug, Clone)]
+    struct TestArtifact;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct TestContent(&'static str);
+
+    impl coa_artifact::__private::Sealed for TestArtifact {}
+
+    impl ArtifactType for TestArtifact {
+        type Content = TestContent;
+
+        fn hash(content: &Self::Content) -> ContentHash {
+            ContentHash::compute(content.0.as_bytes())
+        }
+
+        const TYPE_ID: &'static str = "test";
+    }
+
+    fn index_symbol(index: &SymbolRefIndex, symbol: &SymbolRef, body: &[u8]) {
+        let metadata = SymbolMetadata {
+            content_hash: Some(ContentHash::compute(body)),
+            ..SymbolMetadata::default()
+        };
+        index.insert(symbol.clone(), metadata).unwrap();
+    }
+
+    #[test]
+    fn pinned_delta_tolerates_unrelated_artifact_edits() {
+        let v1 = Artifact::<TestArtifact>::new(TestContent("login v1; logout v1")).unwrap();
+        let v2 = Artifact::<TestArtifact>::new(TestContent("login v1; logout v2")).unwrap();
+        let index = SymbolRefIndex::new();
+        let login = make_symbol(&["auth", "login"], *v2.hash());
+        index_symbol(&index, &login, b"login v1");
+
+        let target = coa_artifact::SymbolPath::new(login.path().to_vec());
+        let unpinned =
+            StructuralDelta::<TestArtifact>::new(target, coa_artifact::DeltaOperation::Remove, *v1.hash());
+        let pinned = unpinned
+            .clone()
+            .with_pin(login.with_parent_hash(*v1.hash()).pin(ContentHash::compute(b"login v1")));
+
+        assert!(index.validate_base(&pinned, &v1).is_ok());
+        assert!(index.validate_base(&pinned, &v2).is_ok());
+        assert!(matches!(
+            index.validate_base(&unpinned, &v2),
+            Err(DeltaError::BaseMismatch { .. })
+        ));
+        let pin = pinned.pin().unwrap();
+        assert_eq!(
+            SymbolRef::from_pin(pinned.target(), pin),
+            login.with_parent_hash(*v1.hash())
+        );
+
+        // The pinned symbol itself changes
+        index.remove_by_parent(v2.hash());
+        index_symbol(&index, &login, b"login v2");
+        assert!(matches!(
+            index.validate_base(&pinned, &v2),
+            Err(DeltaError::StaleSymbol { actual: Some(_), .. })
+        ));
+    }
+
     #[test]
     fn symbol_metadata_default() {
         let meta = SymbolMetadata::default();
         assert_eq!(meta.kind, SymbolKind::Unknown);
         assert_eq!(meta.visibility, Visibility::Public);
     }
+
+    #[test]
+    fn index_references_cover_subtree_and_invalidate_by_parent() {
+        let index = SymbolRefIndex::new();
+        let caller = make_symbol(&["main", "handler"], test_hash_n(2));
+        let test = make_symbol(&["tests", "login"], test_hash_n(3));
+
+        index.add_reference(&["auth".to_string(), "login".to_string()], caller.clone());
+        index.add_reference(&["auth".to_string(), "login".to_string()], caller.clone());
+        index.add_reference(&["auth".to_string(), "login".to_string()], test.clone());
+        index.add_reference(&["other".to_string()], test.clone());
+
+        let refs = index.references_to(&["auth".to_string()]);
+        assert_eq!(refs.len(), 2);
+        assert_eq!(refs[0].site, caller);
+        assert_eq!(refs[0].target, vec!["auth".to_string(), "login".to_string()]);
+
+        index.remove_by_parent(&test_hash_n(3));
+        assert_eq!(index.references_to(&["auth".to_string()]).len(), 1);
+        assert!(index.references_to(&["other".to_string()]).is_empty());
+    }
+
+    #[test]
+    fn declared_append_regions_are_marked() {
+        use coa_artifact::SymbolPath;
+
+        #[derive(Debug, Clone)]
+        struct Registry;
+
+        #[derive(Debug, Clone, PartialEq)]
+        struct RegistryContent;
+
+        impl coa_artifact::__private::Sealed for Registry {}
+
+        impl ArtifactType for Registry {
+            type Content = RegistryContent;
+
+            fn hash(_content: &Self::Content) -> ContentHash {
+                test_hash_n(9)
+            }
+
+            const TYPE_ID: &'static str = "registry";
+        }
+
+        impl AddressableContent for RegistryContent {
+            fn symbol_paths(&self) -> Vec<SymbolPath> {
+                vec![SymbolPath::single("plugins"), SymbolPath::single("tests")]
+            }
+
+            fn append_regions(&self) -> Vec<SymbolPath> {
+                self.symbol_paths()
+            }
+        }
+
+        let index = SymbolRefIndex::new();
+        index
+            .insert(make_symbol(&["tests"], test_hash_n(9)), SymbolMetadata::default())
+            .unwrap();
+        let artifact = Artifact::<Registry>::new(RegistryContent).unwrap();
+
+        assert_eq!(index.declare_append_regions(&artifact).unwrap(), 2);
+        assert!(index.is_append_region(&["plugins".to_string()]));
+        assert!(index.is_append_region(&["tests".to_string()]));
+        assert!(!index.is_append_region(&["other".to_string()]));
+        assert_eq!(index.len(), 2);
+    }
 }
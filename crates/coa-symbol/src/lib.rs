@@ -8,6 +8,9 @@
 //! - [`Revision`]: Branch + commit for versioned references
 //! - [`SymbolRefIndex`]: O(log n) lookup using radix_trie
 //! - [`SingleWriterValidator`]: Ensures non-overlapping delta claims
+//! - [`ClaimService`]: Runtime subtree claims shared by agents
+//! - [`SymbolQuery`]: `key:value` query language over the index
+//! - [`SemanticIndex`]: retrieval by meaning through a pluggable [`Embedder`]
 //!
 //! # Example
 //!
@@ -36,13 +39,22 @@
 #![allow(missing_docs)]
 
 // Core modules
+mod claims;
 mod index;
+mod query;
+mod semantic;
 mod symbol;
 mod validation;
 
 // Re-exports
+pub use claims::{ClaimError, ClaimGuard, ClaimId, ClaimInfo, ClaimMetrics, ClaimService};
 pub use index::{
-    IndexEntry, SourceLocation, SymbolKind, SymbolMetadata, SymbolRefIndex, Visibility,
+    IndexEntry, Neighbor, SourceLocation, SymbolKind, SymbolMetadata, SymbolRefIndex, SymbolReference,
+    Visibility, APPEND_ONLY,
+};
+pub use query::{QueryError, SymbolQuery};
+pub use semantic::{
+    EmbedError, Embedder, HashingEmbedder, SemanticIndex, SemanticMatch, SymbolEmbedding,
 };
 pub use symbol::{Revision, SymbolRef, SymbolRefError};
 pub use validation::{
@@ -0,0 +1,381 @@
+//! Query language over the symbol index
+//!
+//! A query is a whitespace-separated list of `key:value` terms, all of which
+//! must match:
+//!
+//! ```text
+//! kind:function visibility:public path:auth.**
+//! ```
+//!
+//! - `kind:` one of `function`, `type`, `variable`, `module`, `config`,
+//!   `spec`, `unknown` (short forms `fn`, `var`, `const`, `mod` accepted)
+//! - `visibility:` (or `vis:`) one of `public`, `internal`, `restricted`
+//! - `path:` segment glob; segments split on `/` or `::` (on `.` if the
+//!   pattern has neither), `*` and `?` match within a segment and `**`
+//!   matches any number of segments
+//! - `name:` glob on the last path segment; a bare word is a `name:` term
+//! - `attr:` custom attribute, e.g. `attr:append-only`
+//! - `file:` glob on the source file
+//!
+//! Values may list alternatives separated by commas (`kind:function,type`)
+//! and a leading `-` negates a term (`-visibility:internal`).
+//!
+//! The literal leading segments of a `path:` term select a trie subtree, so
+//! only that subtree is scanned; the remaining terms filter its entries.
+
+use crate::index::{IndexEntry, SymbolKind, SymbolMetadata, SymbolRefIndex, Visibility};
+use crate::symbol::SymbolRef;
+use std::str::FromStr;
+
+/// Parsed symbol query
+///
+/// # Example
+/// ```
+/// use coa_symbol::SymbolQuery;
+///
+/// let query: SymbolQuery = "kind:function path:auth.**".parse().unwrap();
+/// assert_eq!(query.prefix(), ["auth"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SymbolQuery {
+    terms: Vec<Term>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Term {
+    negated: bool,
+    filter: Filter,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Filter {
+    Kind(Vec<SymbolKind>),
+    Visibility(Vec<Visibility>),
+    Path(Vec<Vec<String>>),
+    Name(Vec<String>),
+    Attr(Vec<String>),
+    File(Vec<String>),
+}
+
+impl SymbolQuery {
+    /// Parse a query string
+    ///
+    /// # Errors
+    /// Returns error on unknown keys or invalid values
+    pub fn parse(query: &str) -> Result<Self, QueryError> {
+        query.parse()
+    }
+
+    /// Literal path prefix the query is confined to
+    ///
+    /// Taken from the first non-negated, single-alternative `path:` term.
+    #[must_use]
+    pub fn prefix(&self) -> &[String] {
+        self.terms
+            .iter()
+            .find_map(|term| match &term.filter {
+                Filter::Path(patterns) if !term.negated && patterns.len() == 1 => {
+                    let pattern = &patterns[0];
+                    let literal = pattern.iter().take_while(|s| !is_glob(s)).count();
+                    Some(&pattern[..literal])
+                }
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    /// Check a symbol against every term
+    #[must_use]
+    pub fn matches(&self, symbol: &SymbolRef, metadata: &SymbolMetadata) -> bool {
+        self.terms
+            .iter()
+            .all(|term| term.filter.matches(symbol, metadata) != term.negated)
+    }
+}
+
+impl FromStr for SymbolQuery {
+    type Err = QueryError;
+
+    fn from_str(query: &str) -> Result<Self, Self::Err> {
+        let terms = query
+            .split_whitespace()
+            .map(parse_term)
+            .collect::<Result<_, _>>()?;
+        Ok(Self { terms })
+    }
+}
+
+fn parse_term(text: &str) -> Result<Term, QueryError> {
+    let (negated, body) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let (key, value) = body.split_once(':').unwrap_or(("name", body));
+    if value.is_empty() {
+        return Err(QueryError::EmptyValue(key.to_string()));
+    }
+    let values = value.split(',').filter(|v| !v.is_empty());
+    let invalid = |v: &str| QueryError::InvalidValue {
+        key: key.to_string(),
+        value: v.to_string(),
+    };
+
+    let filter = match key {
+        "kind" => Filter::Kind(
+            values
+                .map(|v| parse_kind(v).ok_or_else(|| invalid(v)))
+                .collect::<Result<_, _>>()?,
+        ),
+        "visibility" | "vis" => Filter::Visibility(
+            values
+                .map(|v| parse_visibility(v).ok_or_else(|| invalid(v)))
+                .collect::<Result<_, _>>()?,
+        ),
+        "path" => Filter::Path(values.map(split_path).collect()),
+        "name" => Filter::Name(values.map(String::from).collect()),
+        "attr" => Filter::Attr(values.map(String::from).collect()),
+        "file" => Filter::File(values.map(String::from).collect()),
+        _ => return Err(QueryError::UnknownKey(key.to_string())),
+    };
+    Ok(Term { negated, filter })
+}
+
+fn parse_kind(value: &str) -> Option<SymbolKind> {
+    Some(match value.to_ascii_lowercase().as_str() {
+        "unknown" => SymbolKind::Unknown,
+        "function" | "fn" => SymbolKind::Function,
+        "type" => SymbolKind::Type,
+        "variable" | "var" | "const" => SymbolKind::Variable,
+        "module" | "mod" => SymbolKind::Module,
+        "config" => SymbolKind::Config,
+        "spec" => SymbolKind::Spec,
+        _ => return None,
+    })
+}
+
+fn parse_visibility(value: &str) -> Option<Visibility> {
+    Some(match value.to_ascii_lowercase().as_str() {
+        "public" | "pub" => Visibility::Public,
+        "internal" | "private" => Visibility::Internal,
+        "restricted" => Visibility::Restricted,
+        _ => return None,
+    })
+}
+
+/// Split a path pattern on `/` or `::`, or on `.` when neither occurs
+///
+/// Workspace paths contain file names (`src/auth.rs/login`), so dots only
+/// separate segments in dotted patterns like `auth.**`.
+fn split_path(pattern: &str) -> Vec<String> {
+    let segments: Vec<&str> = if pattern.contains(['/', ':']) {
+        pattern.split("::").flat_map(|part| part.split('/')).collect()
+    } else {
+        pattern.split('.').collect()
+    };
+    segments
+        .into_iter()
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn is_glob(segment: &str) -> bool {
+    segment.contains(['*', '?'])
+}
+
+impl Filter {
+    fn matches(&self, symbol: &SymbolRef, metadata: &SymbolMetadata) -> bool {
+        match self {
+            Self::Kind(kinds) => kinds.contains(&metadata.kind),
+            Self::Visibility(visibilities) => visibilities.contains(&metadata.visibility),
+            Self::Path(patterns) => patterns
+                .iter()
+                .any(|pattern| path_matches(pattern, symbol.path())),
+            Self::Name(globs) => symbol
+                .name()
+                .is_some_and(|name| globs.iter().any(|glob| glob_matches(glob, name))),
+            Self::Attr(attrs) => attrs.iter().any(|a| metadata.attributes.contains(a)),
+            Self::File(globs) => metadata
+                .source_location
+                .as_ref()
+                .is_some_and(|loc| globs.iter().any(|glob| glob_matches(glob, &loc.file))),
+        }
+    }
+}
+
+/// Match path segments against a pattern where `**` spans segments
+fn path_matches(pattern: &[String], path: &[String]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=path.len()).any(|skip| path_matches(rest, &path[skip..]))
+        }
+        Some((first, rest)) => path
+            .split_first()
+            .is_some_and(|(segment, tail)| glob_matches(first, segment) && path_matches(rest, tail)),
+    }
+}
+
+/// Match text against a glob with `*` (any run) and `?` (one char)
+fn glob_matches(glob: &str, text: &str) -> bool {
+    let glob: Vec<char> = glob.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut g, mut t) = (0, 0);
+    let mut backtrack = None;
+    while t < text.len() {
+        match glob.get(g) {
+            Some('*') => {
+                backtrack = Some((g, t));
+                g += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                g += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, resume)) => {
+                    g = star + 1;
+                    t = resume + 1;
+                    backtrack = Some((star, resume + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    glob[g..].iter().all(|&c| c == '*')
+}
+
+impl SymbolRefIndex {
+    /// Run a query string against the index
+    ///
+    /// Results are ordered by path.
+    ///
+    /// # Errors
+    /// Returns error if the query does not parse
+    pub fn query(&self, query: &str) -> Result<Vec<IndexEntry>, QueryError> {
+        Ok(self.query_with(&query.parse()?))
+    }
+
+    /// Run a parsed query against the index
+    #[must_use]
+    pub fn query_with(&self, query: &SymbolQuery) -> Vec<IndexEntry> {
+        self.scan(&query.prefix().join("/"), |symbol, metadata| {
+            query.matches(symbol, metadata)
+        })
+    }
+}
+
+/// Errors parsing a [`SymbolQuery`]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum QueryError {
+    /// Term key is not recognised
+    #[error("unknown query key: {0}")]
+    UnknownKey(String),
+
+    /// Term has no value after the colon
+    #[error("missing value for query key: {0}")]
+    EmptyValue(String),
+
+    /// Value is not valid for its key
+    #[error("invalid value {value:?} for query key {key}")]
+    InvalidValue { key: String, value: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::SourceLocation;
+    use coa_artifact::ContentHash;
+
+    fn index() -> SymbolRefIndex {
+        let hash = ContentHash::compute(b"workspace");
+        let index = SymbolRefIndex::new();
+        let symbols = [
+            ("auth.login", SymbolKind::Function, Visibility::Public, "src/auth.rs"),
+            ("auth.session.refresh", SymbolKind::Function, Visibility::Internal, "src/auth.rs"),
+            ("auth.session.Token", SymbolKind::Type, Visibility::Public, "src/auth.rs"),
+            ("authz.check", SymbolKind::Function, Visibility::Public, "src/authz.rs"),
+            ("api.get_user", SymbolKind::Function, Visibility::Public, "src/api.rs"),
+        ];
+        for (path, kind, visibility, file) in symbols {
+            let metadata = SymbolMetadata {
+                kind,
+                visibility,
+                source_location: Some(SourceLocation {
+                    line: 1,
+                    column: 0,
+                    file: file.to_string(),
+                }),
+                ..SymbolMetadata::default()
+            };
+            index
+                .insert(SymbolRef::new(split_path(path), hash), metadata)
+                .unwrap();
+        }
+        index
+    }
+
+    fn paths(entries: &[IndexEntry]) -> Vec<String> {
+        entries.iter().map(|e| e.symbol.path().join(".")).collect()
+    }
+
+    #[test]
+    fn filters_combine_with_path_subtree() {
+        let index = index();
+
+        let found = index.query("kind:function visibility:public path:auth.**").unwrap();
+        assert_eq!(paths(&found), ["auth.login"]);
+
+        let found = index.query("path:auth::*::* -kind:type").unwrap();
+        assert_eq!(paths(&found), ["auth.session.refresh"]);
+
+        let found = index.query("kind:fn,type file:src/auth*.rs").unwrap();
+        assert_eq!(found.len(), 4);
+
+        let found = index.query("get_* vis:pub").unwrap();
+        assert_eq!(paths(&found), ["api.get_user"]);
+
+        assert_eq!(index.query("").unwrap().len(), index.len());
+        assert!(index.query("path:missing.**").unwrap().is_empty());
+    }
+
+    #[test]
+    fn prefix_is_literal_leading_segments() {
+        let query = SymbolQuery::parse("path:auth/session/**/re?resh").unwrap();
+        assert_eq!(query.prefix(), ["auth", "session"]);
+        let query = SymbolQuery::parse("path:src/auth.rs/*").unwrap();
+        assert_eq!(query.prefix(), ["src", "auth.rs"]);
+        assert!(SymbolQuery::parse("path:a.b,c.d").unwrap().prefix().is_empty());
+        assert!(SymbolQuery::parse("-path:a.b").unwrap().prefix().is_empty());
+    }
+
+    #[test]
+    fn rejects_bad_terms() {
+        assert_eq!(
+            SymbolQuery::parse("color:red"),
+            Err(QueryError::UnknownKey("color".into()))
+        );
+        assert_eq!(
+            SymbolQuery::parse("kind:widget"),
+            Err(QueryError::InvalidValue {
+                key: "kind".into(),
+                value: "widget".into()
+            })
+        );
+        assert_eq!(SymbolQuery::parse("name:"), Err(QueryError::EmptyValue("name".into())));
+    }
+
+    #[test]
+    fn globs_match_within_and_across_segments() {
+        assert!(glob_matches("log*", "login"));
+        assert!(glob_matches("*in", "login"));
+        assert!(glob_matches("l?g*n", "login"));
+        assert!(!glob_matches("log", "login"));
+
+        let path = split_path("a.b.c");
+        assert!(path_matches(&split_path("**"), &path));
+        assert!(path_matches(&split_path("a.**.c"), &path));
+        assert!(path_matches(&split_path("a.b.c.**"), &path));
+        assert!(!path_matches(&split_path("a.*"), &path));
+    }
+}
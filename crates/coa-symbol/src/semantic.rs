@@ -0,0 +1,345 @@
+//! Semantic symbol retrieval
+//!
+//! An optional layer over [`SymbolRefIndex`] that finds symbols by meaning
+//! rather than by path or name. A pluggable [`Embedder`] turns symbol text
+//! into vectors, stored per symbol in [`SymbolMetadata::embedding`], so they
+//! travel with the exported index snapshot ([`SymbolRefIndex::entries`] /
+//! [`SymbolRefIndex::from_entries`]) without a separate file.
+//!
+//! Each embedding remembers the symbol's content hash and the model that
+//! produced it. Once the symbol changes, or under a different model, the
+//! embedding is stale: [`SemanticIndex::find_semantic`] ignores it and
+//! [`SemanticIndex::embed_missing`] recomputes it.
+
+use crate::index::{IndexEntry, SymbolMetadata, SymbolRefIndex};
+use coa_artifact::ContentHash;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// Turns text into vectors comparable by cosine similarity
+pub trait Embedder: Send + Sync {
+    /// Model identifier; embeddings from different models are never compared
+    fn model(&self) -> &str;
+
+    /// Embed `text`
+    ///
+    /// # Errors
+    /// Returns error if the model cannot embed the text
+    fn embed(&self, text: &str) -> Result<Vec<f32>, EmbedError>;
+}
+
+/// Errors producing embeddings
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum EmbedError {
+    /// Embedder failed
+    #[error("embedding failed: {0}")]
+    Failed(String),
+
+    /// Embedder returned an empty vector
+    #[error("embedder returned an empty vector")]
+    Empty,
+}
+
+/// Vector embedding of a symbol
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SymbolEmbedding {
+    /// Model that produced the vector
+    pub model: String,
+
+    /// Symbol content hash the vector was computed from
+    pub content_hash: Option<ContentHash>,
+
+    /// The vector
+    pub vector: Vec<f32>,
+}
+
+impl SymbolEmbedding {
+    /// Check if the embedding still describes a symbol with `metadata`,
+    /// as produced by `model`
+    #[must_use]
+    pub fn is_current(&self, metadata: &SymbolMetadata, model: &str) -> bool {
+        self.model == model && self.content_hash == metadata.content_hash
+    }
+}
+
+/// Symbol found by [`SemanticIndex::find_semantic`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SemanticMatch {
+    /// The symbol and its metadata
+    pub entry: IndexEntry,
+
+    /// Cosine similarity to the query, in `[-1, 1]`
+    pub score: f32,
+}
+
+/// [`SymbolRefIndex`] searched through an [`Embedder`]
+#[derive(Clone, Copy)]
+pub struct SemanticIndex<'a> {
+    index: &'a SymbolRefIndex,
+    embedder: &'a dyn Embedder,
+}
+
+impl std::fmt::Debug for SemanticIndex<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SemanticIndex")
+            .field("symbols", &self.index.len())
+            .field("model", &self.embedder.model())
+            .finish()
+    }
+}
+
+impl SymbolRefIndex {
+    /// Semantic view of this index using `embedder`
+    #[must_use]
+    pub fn semantic<'a>(&'a self, embedder: &'a dyn Embedder) -> SemanticIndex<'a> {
+        SemanticIndex {
+            index: self,
+            embedder,
+        }
+    }
+}
+
+impl SemanticIndex<'_> {
+    /// Embed `text` as the content of the symbol at `path`
+    ///
+    /// Returns `false` if no symbol is indexed at `path`.
+    ///
+    /// # Errors
+    /// Returns error if the embedder fails
+    pub fn embed(&self, path: &[String], text: &str) -> Result<bool, EmbedError> {
+        let Some(entry) = self.index.get_by_path(path) else {
+            return Ok(false);
+        };
+        let embedding = SymbolEmbedding {
+            model: self.embedder.model().to_string(),
+            content_hash: entry.metadata.content_hash,
+            vector: self.vector(text)?,
+        };
+        Ok(self
+            .index
+            .update_metadata(path, |metadata| metadata.embedding = Some(embedding)))
+    }
+
+    /// Embed every symbol without a current embedding
+    ///
+    /// `text_of` supplies each symbol's text; symbols it has none for are
+    /// skipped. Returns the number of symbols embedded.
+    ///
+    /// # Errors
+    /// Returns error on the first embedder failure; symbols embedded before
+    /// it keep their embeddings
+    pub fn embed_missing(
+        &self,
+        mut text_of: impl FnMut(&IndexEntry) -> Option<String>,
+    ) -> Result<usize, EmbedError> {
+        let model = self.embedder.model();
+        let stale = self.index.scan("", |_, metadata| {
+            !metadata
+                .embedding
+                .as_ref()
+                .is_some_and(|e| e.is_current(metadata, model))
+        });
+        let mut embedded = 0;
+        for entry in stale {
+            let Some(text) = text_of(&entry) else {
+                continue;
+            };
+            if self.embed(entry.symbol.path(), &text)? {
+                embedded += 1;
+            }
+        }
+        Ok(embedded)
+    }
+
+    /// Symbols with current embeddings closest in meaning to `query`
+    ///
+    /// Returns at most `k` matches, best first; ties are ordered by path.
+    /// Symbols without a current embedding from this embedder's model are
+    /// not considered.
+    ///
+    /// # Errors
+    /// Returns error if the query cannot be embedded
+    pub fn find_semantic(&self, query: &str, k: usize) -> Result<Vec<SemanticMatch>, EmbedError> {
+        let query = self.vector(query)?;
+        let model = self.embedder.model();
+        let mut matches: Vec<SemanticMatch> =
+            self.index
+                .scan("", |_, metadata| {
+                    metadata.embedding.as_ref().is_some_and(|e| {
+                        e.is_current(metadata, model) && e.vector.len() == query.len()
+                    })
+                })
+                .into_iter()
+                .filter_map(|entry| {
+                    let score = cosine(&query, &entry.metadata.embedding.as_ref()?.vector);
+                    Some(SemanticMatch { entry, score })
+                })
+                .collect();
+        // `scan` orders by path and the sort is stable, so ties stay by path
+        matches.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+        matches.truncate(k);
+        Ok(matches)
+    }
+
+    fn vector(&self, text: &str) -> Result<Vec<f32>, EmbedError> {
+        let vector = self.embedder.embed(text)?;
+        if vector.is_empty() {
+            return Err(EmbedError::Empty);
+        }
+        Ok(vector)
+    }
+}
+
+/// Cosine similarity; zero if either vector is zero
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norms = norm(a) * norm(b);
+    if norms == 0.0 {
+        0.0
+    } else {
+        dot / norms
+    }
+}
+
+/// Offline embedder hashing identifier words into a fixed-size vector
+///
+/// Splits text into lowercase words (breaking `snake_case` and
+/// `camelCase`), so it captures shared vocabulary rather than meaning.
+/// Useful as a fallback without a model, and in tests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HashingEmbedder {
+    dimensions: usize,
+}
+
+impl HashingEmbedder {
+    /// Create embedder producing vectors of `dimensions` (at least 1)
+    #[must_use]
+    pub fn new(dimensions: usize) -> Self {
+        Self {
+            dimensions: dimensions.max(1),
+        }
+    }
+}
+
+impl Default for HashingEmbedder {
+    fn default() -> Self {
+        Self::new(256)
+    }
+}
+
+impl Embedder for HashingEmbedder {
+    fn model(&self) -> &'static str {
+        "hashing"
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn embed(&self, text: &str) -> Result<Vec<f32>, EmbedError> {
+        let mut vector = vec![0.0; self.dimensions];
+        for word in words(text) {
+            // FNV-1a: stable across runs and platforms, unlike `DefaultHasher`
+            let hash = word.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |h, b| {
+                (h ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+            });
+            vector[(hash % self.dimensions as u64) as usize] += 1.0;
+        }
+        Ok(vector)
+    }
+}
+
+/// Lowercase words of `text`, splitting identifiers at `_` and case changes
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .flat_map(|token| {
+            let mut words = Vec::new();
+            let mut current = String::new();
+            let mut previous_lower = false;
+            for c in token.chars() {
+                if c.is_uppercase() && previous_lower {
+                    words.push(std::mem::take(&mut current));
+                }
+                previous_lower = c.is_lowercase() || c.is_ascii_digit();
+                current.extend(c.to_lowercase());
+            }
+            words.push(current);
+            words
+        })
+        .filter(|word| !word.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbol::SymbolRef;
+
+    fn path(p: &str) -> Vec<String> {
+        p.split('.').map(str::to_string).collect()
+    }
+
+    #[test]
+    fn finds_symbols_by_content_and_skips_stale_embeddings() {
+        let index = SymbolRefIndex::new();
+        let sources = [
+            (
+                "auth.login",
+                "fn verifyPassword(user, password_hash) -> Session",
+            ),
+            ("billing.invoice", "fn total_amount(line_items) -> Money"),
+            ("net.retry", "fn retry_with_backoff(request, attempts)"),
+        ];
+        for (p, _) in sources {
+            let metadata = SymbolMetadata {
+                content_hash: Some(ContentHash::compute(p.as_bytes())),
+                ..SymbolMetadata::default()
+            };
+            index
+                .insert(
+                    SymbolRef::new(path(p), ContentHash::compute(b"lib")),
+                    metadata,
+                )
+                .unwrap();
+        }
+        let embedder = HashingEmbedder::default();
+        let semantic = index.semantic(&embedder);
+        let text_of = |entry: &IndexEntry| {
+            let p = entry.symbol.path().join(".");
+            sources
+                .iter()
+                .find(|(s, _)| *s == p)
+                .map(|(_, text)| (*text).to_string())
+        };
+        assert_eq!(semantic.embed_missing(text_of).unwrap(), 3);
+        assert_eq!(semantic.embed_missing(text_of).unwrap(), 0);
+
+        let found = semantic
+            .find_semantic("check the user's password", 2)
+            .unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].entry.symbol.path(), path("auth.login"));
+        assert!(found[0].score > found[1].score);
+
+        // Embeddings travel with the exported entries
+        let restored = SymbolRefIndex::from_entries(index.entries()).unwrap();
+        let found = restored
+            .semantic(&embedder)
+            .find_semantic("retry backoff", 1)
+            .unwrap();
+        assert_eq!(found[0].entry.symbol.path(), path("net.retry"));
+
+        // A changed symbol is not matched until it is embedded again
+        restored.update_metadata(&path("net.retry"), |m| {
+            m.content_hash = Some(ContentHash::compute(b"changed"));
+        });
+        let found = restored
+            .semantic(&embedder)
+            .find_semantic("retry backoff", 3)
+            .unwrap();
+        assert!(found
+            .iter()
+            .all(|m| m.entry.symbol.path() != path("net.retry")));
+        assert_eq!(
+            restored.semantic(&embedder).embed_missing(text_of).unwrap(),
+            1
+        );
+    }
+}
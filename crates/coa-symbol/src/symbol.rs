@@ -3,7 +3,8 @@
 //! Provides [`SymbolRef`] for referencing symbols within artifacts with
 //! content-hash binding for automatic invalidation detection.
 
-use coa_artifact::ContentHash;
+use coa_artifact::{ContentHash, PinRevision, SymbolPath, SymbolPin};
+use serde::{Deserialize, Serialize};
 use std::fmt::{self, Display, Formatter};
 use std::str::FromStr;
 
@@ -28,7 +29,7 @@ use std::str::FromStr;
 ///     hash
 /// );
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct SymbolRef {
     /// Logical path: `["crate", "module", "symbol"]`
     path: Vec<String>,
@@ -37,11 +38,12 @@ pub struct SymbolRef {
     parent_hash: ContentHash,
 
     /// Optional: specific version/revision
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     revision: Option<Revision>,
 }
 
 /// Symbol revision (branch + commit)
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Revision {
     /// Branch name
     branch: String,
@@ -222,6 +224,29 @@ impl SymbolRef {
         }
     }
 
+    /// Pin a delta to this symbol as read with content `symbol_hash`
+    #[must_use]
+    pub fn pin(&self, symbol_hash: ContentHash) -> SymbolPin {
+        let pin = SymbolPin::new(self.parent_hash, symbol_hash);
+        match &self.revision {
+            Some(revision) => pin.with_revision(revision.into()),
+            None => pin,
+        }
+    }
+
+    /// Reference to the symbol a delta targeting `path` was pinned to
+    #[must_use]
+    pub fn from_pin(path: &SymbolPath, pin: &SymbolPin) -> Self {
+        Self {
+            path: path.segments().to_vec(),
+            parent_hash: pin.parent_hash,
+            revision: pin
+                .revision
+                .as_ref()
+                .map(|r| Revision::new(r.branch.clone(), r.commit)),
+        }
+    }
+
     /// Create a trie-compatible key (slash-separated)
     #[inline]
     #[must_use]
@@ -256,6 +281,12 @@ impl Revision {
     }
 }
 
+impl From<&Revision> for PinRevision {
+    fn from(revision: &Revision) -> Self {
+        PinRevision::new(revision.branch.clone(), revision.commit)
+    }
+}
+
 impl Display for SymbolRef {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.to_string())
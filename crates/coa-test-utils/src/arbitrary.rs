@@ -0,0 +1,138 @@
+//! Proptest `Arbitrary` instances for core types (feature `arbitrary`)
+//!
+//! The orphan rule keeps this crate from implementing [`Arbitrary`] for
+//! types defined in `coa-artifact` and `coa-kernel`, so each type gets a
+//! strategy function and an [`Arb`] wrapper that implements it:
+//!
+//! ```rust,ignore
+//! use coa_test_utils::arbitrary::Arb;
+//! use coa_kernel::types::AutonomyLevel;
+//! use proptest::prelude::*;
+//!
+//! proptest! {
+//!     #[test]
+//!     fn levels_are_bounded(Arb(level) in any::<Arb<AutonomyLevel>>()) {
+//!         prop_assert!(level <= AutonomyLevel::L5);
+//!     }
+//! }
+//! ```
+//!
+//! Unlike the narrow alphabet in [`generators`](crate::generators), these
+//! cover the full value space a type accepts.
+
+use coa_artifact::{ContentHash, HashAlgorithm, SymbolPath};
+use coa_kernel::types::v2::NodeSpecV2;
+use coa_kernel::types::{AutonomyLevel, DirectiveSet, ResourceCaps};
+use proptest::collection::{btree_map, vec};
+use proptest::prelude::*;
+use std::ops::{Deref, DerefMut};
+
+/// Wrapper implementing [`Arbitrary`] for a type of another crate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Arb<T>(pub T);
+
+impl<T> Arb<T> {
+    /// Unwrap the generated value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Arb<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Arb<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+/// Path with 0 to 4 identifier segments
+pub fn symbol_path() -> impl Strategy<Value = SymbolPath> {
+    vec("[a-zA-Z_][a-zA-Z0-9_]{0,11}", 0..=4).prop_map(SymbolPath::new)
+}
+
+/// Any installed hash algorithm
+pub fn hash_algorithm() -> impl Strategy<Value = HashAlgorithm> {
+    proptest::sample::select(HashAlgorithm::ALL.as_slice())
+}
+
+/// Random digest bytes under any algorithm
+pub fn content_hash() -> impl Strategy<Value = ContentHash> {
+    (hash_algorithm(), any::<[u8; 32]>())
+        .prop_map(|(algorithm, bytes)| ContentHash::with_algorithm(algorithm, bytes))
+}
+
+/// Any autonomy level
+pub fn autonomy_level() -> impl Strategy<Value = AutonomyLevel> {
+    proptest::sample::select(AutonomyLevel::ALL.as_slice())
+}
+
+/// Caps up to an hour of CPU, 16 GiB, a million tokens and 10k iterations
+pub fn resource_caps() -> impl Strategy<Value = ResourceCaps> {
+    (
+        0..=3_600_000u64,
+        0..=16u64 << 30,
+        0..=1_000_000u64,
+        0..=10_000u64,
+    )
+        .prop_map(|(cpu_time_ms, memory_bytes, token_limit, iteration_cap)| ResourceCaps {
+            cpu_time_ms,
+            memory_bytes,
+            token_limit,
+            iteration_cap,
+        })
+}
+
+/// Up to 4 directives with scalar JSON values
+pub fn directive_set() -> impl Strategy<Value = DirectiveSet> {
+    let value = prop_oneof![
+        Just(serde_json::Value::Null),
+        any::<bool>().prop_map(serde_json::Value::from),
+        any::<i64>().prop_map(serde_json::Value::from),
+        "[ -~]{0,16}".prop_map(serde_json::Value::from),
+    ];
+    btree_map("[a-z_]{1,12}", value, 0..=4).prop_map(|directives| DirectiveSet { directives })
+}
+
+/// Non-expansion node consuming up to 2 payload types
+pub fn node_spec() -> impl Strategy<Value = NodeSpecV2> {
+    (
+        directive_set(),
+        autonomy_level(),
+        resource_caps(),
+        vec("[a-z]{1,8}", 0..=2),
+    )
+        .prop_map(|(directives, ceiling, caps, inputs)| {
+            inputs
+                .into_iter()
+                .fold(NodeSpecV2::new(directives, ceiling, caps), NodeSpecV2::with_input)
+        })
+}
+
+macro_rules! arbitrary_via {
+    ($($ty:ty => $strategy:ident),* $(,)?) => {$(
+        impl Arbitrary for Arb<$ty> {
+            type Parameters = ();
+            type Strategy = BoxedStrategy<Self>;
+
+            fn arbitrary_with((): ()) -> Self::Strategy {
+                $strategy().prop_map(Arb).boxed()
+            }
+        }
+    )*};
+}
+
+arbitrary_via! {
+    SymbolPath => symbol_path,
+    ContentHash => content_hash,
+    AutonomyLevel => autonomy_level,
+    ResourceCaps => resource_caps,
+    DirectiveSet => directive_set,
+    NodeSpecV2 => node_spec,
+}
@@ -0,0 +1,92 @@
+//! Chaos-mode supervision harness
+//!
+//! Drives an [`AgentPool`] in chaos mode and checks the supervisor's
+//! outcome: every message is eventually accepted (converged), or the run
+//! stops with a restart-limit escalation and nothing else.
+
+use coa_core::{AgentMessage, AgentPool, AgentSpec, ChaosConfig, ChaosEvent, PoolError, PoolStats};
+
+/// Outcome of one chaos run
+#[derive(Debug)]
+pub struct ChaosRun {
+    /// Messages sent
+    pub sent: usize,
+    /// Messages accepted by some agent
+    pub delivered: usize,
+    /// Error that stopped the run, if any
+    pub escalation: Option<PoolError>,
+    pub events: Vec<ChaosEvent>,
+    pub stats: PoolStats,
+}
+
+impl ChaosRun {
+    /// Assert every message was delivered without escalation
+    ///
+    /// # Panics
+    /// If a message was not delivered or the run escalated
+    #[track_caller]
+    pub fn assert_converged(&self) {
+        assert!(
+            self.escalation.is_none() && self.delivered == self.sent,
+            "chaos run did not converge: {}/{} delivered, escalation {:?}, faults {:?}",
+            self.delivered,
+            self.sent,
+            self.escalation,
+            self.events
+        );
+    }
+
+    /// Assert the run escalated on the restart limit, with every message
+    /// before it delivered
+    ///
+    /// # Panics
+    /// If the run did not stop on `PoolError::RestartLimitExceeded`
+    #[track_caller]
+    pub fn assert_escalated(&self) {
+        assert!(
+            matches!(self.escalation, Some(PoolError::RestartLimitExceeded { .. })),
+            "chaos run did not escalate: {:?}",
+            self.escalation
+        );
+        assert_eq!(self.delivered + 1, self.sent, "messages lost before escalation");
+    }
+}
+
+/// Send `messages` tasks to one supervised agent under `config`
+///
+/// Stops at the first error, which is returned as the escalation.
+///
+/// # Panics
+/// If the first agent cannot be acquired
+pub async fn run_chaos(config: ChaosConfig, max_restarts: usize, messages: usize) -> ChaosRun {
+    let pool = AgentPool::new(4)
+        .with_max_restarts(max_restarts)
+        .with_chaos(config);
+    let mut agent = Some(pool.acquire(AgentSpec::new("chaos")).await.unwrap());
+    let mut run = ChaosRun {
+        sent: 0,
+        delivered: 0,
+        escalation: None,
+        events: Vec::new(),
+        stats: PoolStats::default(),
+    };
+
+    while run.sent < messages {
+        let Some(current) = agent.take() else { break };
+        run.sent += 1;
+        match pool.send_supervised(current, AgentMessage::Resume).await {
+            Ok(next) => {
+                run.delivered += 1;
+                agent = Some(next);
+            }
+            Err(error) => run.escalation = Some(error),
+        }
+    }
+    if let Some(agent) = agent {
+        pool.release(agent).await;
+    }
+
+    run.events = pool.chaos_events();
+    run.stats = pool.stats().await;
+    run
+}
@@ -0,0 +1,312 @@
+//! Composition strategy conformance suite
+//!
+//! Properties every [`CompositionStrategy`] must satisfy, whoever wrote it:
+//! - **Determinism**: validating or composing the same batch twice gives
+//!   the same outcome and the same artifact
+//! - **Empty batches**: an empty batch validates and composes to the base
+//!   unchanged
+//! - **Conflict symmetry**: whether two deltas conflict does not depend on
+//!   which one is listed first
+//! - **Classification consistency** (for strategies with a [`Classifier`]):
+//!   a batch the classifier calls entirely commutative composes to the same
+//!   artifact in any order
+//!
+//! Each property is a `check_*` function over one batch; [`run_conformance`]
+//! drives them over generated batches and [`assert_conformance`] panics with
+//! the first violation, so a third-party strategy's test is one line:
+//!
+//! ```rust,ignore
+//! #[test]
+//! fn my_strategy_conforms() {
+//!     coa_test_utils::conformance::assert_conformance(&MyStrategy::new(), 256);
+//! }
+//! ```
+
+use crate::generators::{delta_batch, mixed_batch};
+use crate::{create_test_code_artifact, TestCodeArtifact};
+use coa_artifact::{Artifact, ContentHash, StructuralDelta};
+use coa_composition::{Classifier, CompositionStrategy, DeltaClass};
+use coa_symbol::SymbolRefIndex;
+use proptest::prelude::*;
+use proptest::test_runner::{Config, TestCaseError, TestRunner};
+use std::cell::RefCell;
+use std::fmt;
+
+/// Batch of deltas against the test artifact
+pub type Batch = [StructuralDelta<TestCodeArtifact>];
+
+/// Property a strategy must satisfy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Property {
+    Determinism,
+    EmptyBatch,
+    ConflictSymmetry,
+    ClassificationConsistency,
+}
+
+impl Property {
+    /// Every property, in the order they are checked
+    pub const ALL: [Property; 4] = [
+        Property::Determinism,
+        Property::EmptyBatch,
+        Property::ConflictSymmetry,
+        Property::ClassificationConsistency,
+    ];
+
+    /// Property name as shown in reports
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Determinism => "determinism",
+            Self::EmptyBatch => "empty batch",
+            Self::ConflictSymmetry => "conflict symmetry",
+            Self::ClassificationConsistency => "classification consistency",
+        }
+    }
+}
+
+impl fmt::Display for Property {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A property a strategy broke, with what was observed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub strategy: &'static str,
+    pub property: Property,
+    pub detail: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} violates {}: {}",
+            self.strategy, self.property, self.detail
+        )
+    }
+}
+
+impl std::error::Error for Violation {}
+
+fn violation(strategy: &impl CompositionStrategy, property: Property, detail: String) -> Violation {
+    Violation {
+        strategy: strategy.name(),
+        property,
+        detail,
+    }
+}
+
+/// Outcome of validating then composing a batch: the artifact hash, or the
+/// stage that failed with its error message
+fn outcome<S: CompositionStrategy>(
+    strategy: &S,
+    base: &Artifact<TestCodeArtifact>,
+    batch: &Batch,
+    index: &SymbolRefIndex,
+) -> Result<ContentHash, String> {
+    strategy
+        .validate(batch, index)
+        .map_err(|e| format!("validate: {e}"))?;
+    strategy
+        .compose(base, batch)
+        .map(|artifact| *artifact.hash())
+        .map_err(|e| format!("compose: {e}"))
+}
+
+/// Validating and composing `batch` twice gives the same result
+///
+/// # Errors
+/// Returns the violation if the two runs differ
+pub fn check_determinism<S: CompositionStrategy>(
+    strategy: &S,
+    base: &Artifact<TestCodeArtifact>,
+    batch: &Batch,
+    index: &SymbolRefIndex,
+) -> Result<(), Violation> {
+    let first = outcome(strategy, base, batch, index);
+    let second = outcome(strategy, base, batch, index);
+    if first == second {
+        Ok(())
+    } else {
+        Err(violation(
+            strategy,
+            Property::Determinism,
+            format!("{} deltas gave {first:?}, then {second:?}", batch.len()),
+        ))
+    }
+}
+
+/// An empty batch validates and composes to `base` unchanged
+///
+/// # Errors
+/// Returns the violation if it is rejected or changes the artifact
+pub fn check_empty_batch<S: CompositionStrategy>(
+    strategy: &S,
+    base: &Artifact<TestCodeArtifact>,
+    index: &SymbolRefIndex,
+) -> Result<(), Violation> {
+    match outcome(strategy, base, &[], index) {
+        Ok(hash) if hash == *base.hash() => Ok(()),
+        Ok(hash) => Err(violation(
+            strategy,
+            Property::EmptyBatch,
+            format!("composed {hash} from base {}", base.hash()),
+        )),
+        Err(e) => Err(violation(strategy, Property::EmptyBatch, e)),
+    }
+}
+
+/// Every pair of deltas in `batch` validates the same in either order
+///
+/// # Errors
+/// Returns the violation for the first asymmetric pair
+pub fn check_conflict_symmetry<S: CompositionStrategy>(
+    strategy: &S,
+    batch: &Batch,
+    index: &SymbolRefIndex,
+) -> Result<(), Violation> {
+    for (i, a) in batch.iter().enumerate() {
+        for b in &batch[i + 1..] {
+            let forward = strategy.validate(&[a.clone(), b.clone()], index).is_ok();
+            let backward = strategy.validate(&[b.clone(), a.clone()], index).is_ok();
+            if forward != backward {
+                return Err(violation(
+                    strategy,
+                    Property::ConflictSymmetry,
+                    format!(
+                        "{} then {} {}, reversed {}",
+                        a.target(),
+                        b.target(),
+                        if forward { "accepted" } else { "rejected" },
+                        if backward { "accepted" } else { "rejected" },
+                    ),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A batch `classifier` calls entirely commutative gives the same result
+/// in reverse order
+///
+/// Batches with any ordered (or non-commuting) delta are not checked.
+///
+/// # Errors
+/// Returns the violation if the reversed batch validates or composes
+/// differently
+pub fn check_classification_consistency<S: CompositionStrategy, C: Classifier>(
+    strategy: &S,
+    classifier: &C,
+    base: &Artifact<TestCodeArtifact>,
+    batch: &Batch,
+    index: &SymbolRefIndex,
+) -> Result<(), Violation> {
+    let commutative = batch.iter().all(|delta| {
+        classifier.classify(delta) == DeltaClass::Commutative && classifier.commutes(delta)
+    });
+    if !commutative {
+        return Ok(());
+    }
+    let reversed: Vec<_> = batch.iter().rev().cloned().collect();
+    let forward = outcome(strategy, base, batch, index);
+    let backward = outcome(strategy, base, &reversed, index);
+    if forward == backward {
+        Ok(())
+    } else {
+        Err(violation(
+            strategy,
+            Property::ClassificationConsistency,
+            format!("commutative batch gave {forward:?}, reversed {backward:?}"),
+        ))
+    }
+}
+
+/// Every batch-level property except classification consistency
+///
+/// # Errors
+/// Returns the first violation
+pub fn check_batch<S: CompositionStrategy>(
+    strategy: &S,
+    base: &Artifact<TestCodeArtifact>,
+    batch: &Batch,
+    index: &SymbolRefIndex,
+) -> Result<(), Violation> {
+    check_determinism(strategy, base, batch, index)?;
+    check_conflict_symmetry(strategy, batch, index)
+}
+
+/// Check `strategy` over `cases` generated batches of arbitrary and mixed
+/// deltas, classifying with `classifier` if given
+///
+/// Failing batches are shrunk before the violation is reported.
+///
+/// # Errors
+/// Returns the first violation, for the smallest batch found
+pub fn run_conformance<S: CompositionStrategy, C: Classifier>(
+    strategy: &S,
+    classifier: Option<&C>,
+    cases: u32,
+) -> Result<(), Violation> {
+    let base = create_test_code_artifact();
+    let index = SymbolRefIndex::new();
+    check_empty_batch(strategy, &base, &index)?;
+
+    let mut runner = TestRunner::new(Config {
+        cases,
+        failure_persistence: None,
+        ..Config::default()
+    });
+    let batches = prop_oneof![delta_batch(6), mixed_batch(6)];
+    // Shrinking re-runs failing batches; the last violation is the smallest
+    let found = RefCell::new(None);
+    let result = runner.run(&batches, |batch| {
+        let checked =
+            check_batch(strategy, &base, &batch, &index).and_then(|()| match classifier {
+                Some(classifier) => {
+                    check_classification_consistency(strategy, classifier, &base, &batch, &index)
+                }
+                None => Ok(()),
+            });
+        checked.map_err(|v| {
+            let message = v.to_string();
+            *found.borrow_mut() = Some(v);
+            TestCaseError::fail(message)
+        })
+    });
+    match (result, found.into_inner()) {
+        (Ok(()), _) => Ok(()),
+        (Err(_), Some(v)) => Err(v),
+        (Err(e), None) => Err(violation(strategy, Property::Determinism, e.to_string())),
+    }
+}
+
+/// Assert `strategy` conforms over `cases` generated batches
+///
+/// # Panics
+/// With the first violation found
+#[track_caller]
+pub fn assert_conformance<S: CompositionStrategy>(strategy: &S, cases: u32) {
+    if let Err(v) = run_conformance::<S, ()>(strategy, None, cases) {
+        panic!("{v}");
+    }
+}
+
+/// Assert `strategy` conforms, including classification consistency under
+/// `classifier`
+///
+/// # Panics
+/// With the first violation found
+#[track_caller]
+pub fn assert_conformance_with_classifier<S: CompositionStrategy, C: Classifier>(
+    strategy: &S,
+    classifier: &C,
+    cases: u32,
+) {
+    if let Err(v) = run_conformance(strategy, Some(classifier), cases) {
+        panic!("{v}");
+    }
+}
@@ -0,0 +1,85 @@
+//! Proptest generators for paths, operations and delta batches
+//!
+//! Segments are drawn from a small alphabet so that generated batches
+//! regularly contain overlapping and duplicate targets, which is where
+//! strategy invariants are most likely to break.
+
+use crate::{create_test_code_artifact, TestCodeArtifact, TestCodeContent};
+use coa_artifact::{DeltaOperation, StructuralDelta, SymbolPath};
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+/// Segment alphabet for generated paths
+pub const SEGMENTS: [&str; 4] = ["a", "b", "c", "d"];
+
+/// Path with 1 to 3 segments
+pub fn symbol_path() -> impl Strategy<Value = SymbolPath> {
+    vec(proptest::sample::select(SEGMENTS.as_slice()), 1..=3)
+        .prop_map(|segments| SymbolPath::new(segments.into_iter().map(String::from).collect()))
+}
+
+pub fn test_content() -> impl Strategy<Value = TestCodeContent> {
+    "[a-z]{0,8}".prop_map(|source| TestCodeContent { source })
+}
+
+/// Add or Remove
+pub fn commutative_operation() -> impl Strategy<Value = DeltaOperation<TestCodeArtifact>> {
+    prop_oneof![
+        test_content().prop_map(DeltaOperation::Add),
+        Just(DeltaOperation::Remove),
+    ]
+}
+
+/// Add, Remove or Replace
+pub fn delta_operation() -> impl Strategy<Value = DeltaOperation<TestCodeArtifact>> {
+    prop_oneof![
+        commutative_operation(),
+        test_content().prop_map(DeltaOperation::Replace),
+    ]
+}
+
+/// Delta against the default test artifact, with an optional order
+pub fn structural_delta() -> impl Strategy<Value = StructuralDelta<TestCodeArtifact>> {
+    (symbol_path(), delta_operation(), proptest::option::of(0..8u32))
+        .prop_map(|(target, operation, order)| build_delta(target, operation, order))
+}
+
+/// Add/Remove delta without an order
+pub fn commutative_delta() -> impl Strategy<Value = StructuralDelta<TestCodeArtifact>> {
+    (symbol_path(), commutative_operation())
+        .prop_map(|(target, operation)| build_delta(target, operation, None))
+}
+
+/// Replace delta with an explicit order
+pub fn ordered_delta() -> impl Strategy<Value = StructuralDelta<TestCodeArtifact>> {
+    (symbol_path(), test_content(), 0..8u32).prop_map(|(target, content, order)| {
+        build_delta(target, DeltaOperation::Replace(content), Some(order))
+    })
+}
+
+/// Batch of up to `max` arbitrary deltas
+pub fn delta_batch(max: usize) -> impl Strategy<Value = Vec<StructuralDelta<TestCodeArtifact>>> {
+    vec(structural_delta(), 0..=max)
+}
+
+/// Batch of up to `max` Add/Remove deltas
+pub fn commutative_batch(max: usize) -> impl Strategy<Value = Vec<StructuralDelta<TestCodeArtifact>>> {
+    vec(commutative_delta(), 0..=max)
+}
+
+/// Batch mixing unordered Add/Remove with explicitly ordered Replace deltas
+pub fn mixed_batch(max: usize) -> impl Strategy<Value = Vec<StructuralDelta<TestCodeArtifact>>> {
+    vec(prop_oneof![commutative_delta(), ordered_delta()], 0..=max)
+}
+
+fn build_delta(
+    target: SymbolPath,
+    operation: DeltaOperation<TestCodeArtifact>,
+    order: Option<u32>,
+) -> StructuralDelta<TestCodeArtifact> {
+    let base_hash = *create_test_code_artifact().hash();
+    match order {
+        Some(order) => StructuralDelta::with_order(target, operation, base_hash, order),
+        None => StructuralDelta::new(target, operation, base_hash),
+    }
+}
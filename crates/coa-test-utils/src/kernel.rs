@@ -0,0 +1,286 @@
+//! Kernel fixtures
+//!
+//! Deterministic signing keys, node specs, common graph shapes (linear
+//! chains and diamonds, unsigned or pre-signed) and a [`FakeNodeExecutor`]
+//! whose per-node behavior is programmed up front.
+
+use coa_kernel::autonomy::CapabilityToken;
+use coa_kernel::construction::GraphBuilder;
+use coa_kernel::error::ExecutionError;
+use coa_kernel::executor::{Executor, NodeExecutionResult, NodeExecutor, NodeInputs, NodePayload};
+use coa_kernel::types::v2::{NodeSpecV2, ValidatedGraph};
+use coa_kernel::types::{AutonomyLevel, DirectiveSet, GraphType, NodeId, ResourceCaps};
+use ed25519_dalek::SigningKey;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Seed of [`default_signing_key`]
+pub const DEFAULT_KEY_SEED: u8 = 7;
+
+/// Signing key derived from `seed`; the same seed always gives the same key
+#[must_use]
+pub fn signing_key(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32])
+}
+
+/// Signing key shared by the graph fixtures
+#[must_use]
+pub fn default_signing_key() -> SigningKey {
+    signing_key(DEFAULT_KEY_SEED)
+}
+
+/// Hands out distinct deterministic signing keys
+///
+/// Keys are numbered from 1, so a fresh factory never yields
+/// [`default_signing_key`] before wrapping around.
+#[derive(Debug)]
+pub struct KeyFactory {
+    next: AtomicU8,
+}
+
+impl KeyFactory {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            next: AtomicU8::new(DEFAULT_KEY_SEED + 1),
+        }
+    }
+
+    /// Next key in the sequence
+    pub fn next_key(&self) -> SigningKey {
+        signing_key(self.next.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl Default for KeyFactory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Resource bounds of [`test_node_spec`]
+#[must_use]
+pub fn test_caps() -> ResourceCaps {
+    ResourceCaps {
+        cpu_time_ms: 1000,
+        memory_bytes: 1024 * 1024,
+        token_limit: 1000,
+        iteration_cap: 100,
+    }
+}
+
+/// Node without directives at autonomy `L3` within [`test_caps`]
+#[must_use]
+pub fn test_node_spec() -> NodeSpecV2 {
+    NodeSpecV2::new(
+        DirectiveSet {
+            directives: BTreeMap::new(),
+        },
+        AutonomyLevel::L3,
+        test_caps(),
+    )
+}
+
+/// Production DAG chaining `n` test nodes, with node IDs in chain order
+///
+/// # Panics
+/// Never; chain edges cannot form a cycle
+#[must_use]
+pub fn build_linear_graph(n: usize) -> (GraphBuilder, Vec<NodeId>) {
+    let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
+    let nodes: Vec<NodeId> = (0..n).map(|_| builder.add_node(test_node_spec())).collect();
+    for pair in nodes.windows(2) {
+        builder.add_edge(pair[0], pair[1]).unwrap();
+    }
+    (builder, nodes)
+}
+
+/// Production DAG `top -> {left, right} -> bottom`, as
+/// `[top, left, right, bottom]`
+///
+/// # Panics
+/// Never; diamond edges cannot form a cycle
+#[must_use]
+pub fn build_diamond_graph() -> (GraphBuilder, [NodeId; 4]) {
+    let mut builder = GraphBuilder::new(GraphType::ProductionDAG);
+    let nodes = [(); 4].map(|()| builder.add_node(test_node_spec()));
+    let [top, left, right, bottom] = nodes;
+    builder.add_edge(top, left).unwrap();
+    builder.add_edge(top, right).unwrap();
+    builder.add_edge(left, bottom).unwrap();
+    builder.add_edge(right, bottom).unwrap();
+    (builder, nodes)
+}
+
+/// Validated graph with its node IDs and the key that signed it
+#[derive(Debug)]
+pub struct SignedGraph {
+    pub graph: ValidatedGraph,
+    /// Node IDs in the order the fixture built them
+    pub nodes: Vec<NodeId>,
+    pub key: SigningKey,
+}
+
+impl SignedGraph {
+    /// Validate `builder` with `key`
+    ///
+    /// # Panics
+    /// If validation fails
+    #[must_use]
+    #[track_caller]
+    pub fn sign(builder: GraphBuilder, nodes: Vec<NodeId>, key: SigningKey) -> Self {
+        let graph = builder.validate(&key).expect("fixture graph validates");
+        Self { graph, nodes, key }
+    }
+
+    /// Executor trusting the signing key, running nodes with `node_executor`
+    #[must_use]
+    pub fn executor(&self, node_executor: Arc<dyn NodeExecutor>) -> Executor {
+        Executor::with_executor(self.key.verifying_key(), node_executor)
+    }
+}
+
+/// [`build_linear_graph`] validated with [`default_signing_key`]
+#[must_use]
+pub fn signed_linear_graph(n: usize) -> SignedGraph {
+    let (builder, nodes) = build_linear_graph(n);
+    SignedGraph::sign(builder, nodes, default_signing_key())
+}
+
+/// [`build_diamond_graph`] validated with [`default_signing_key`]
+#[must_use]
+pub fn signed_diamond_graph() -> SignedGraph {
+    let (builder, nodes) = build_diamond_graph();
+    SignedGraph::sign(builder, nodes.to_vec(), default_signing_key())
+}
+
+/// What a [`FakeNodeExecutor`] does for one node
+#[derive(Debug, Clone, PartialEq)]
+pub enum Behavior {
+    /// Report success
+    Succeed,
+    /// Report success with a payload for data dependents
+    Output(NodePayload),
+    /// Return `ExecutionError` on every attempt
+    Fail(ExecutionError),
+    /// Return `ExecutionError` on the first `times` attempts, then succeed
+    Flaky { times: u32, error: ExecutionError },
+    /// Report an unsuccessful result without an error
+    Unsuccessful,
+    /// Sleep, then behave as the inner behavior
+    Delay(Duration, Box<Behavior>),
+}
+
+/// Node executor with programmable per-node behavior
+///
+/// Nodes without a programmed [`Behavior`] succeed. Every call is recorded
+/// in order, so tests can assert scheduling and retry counts.
+#[derive(Debug, Default)]
+pub struct FakeNodeExecutor {
+    behaviors: HashMap<NodeId, Behavior>,
+    calls: Mutex<Vec<NodeId>>,
+}
+
+impl FakeNodeExecutor {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Program `node` to behave as `behavior`
+    #[must_use]
+    pub fn with(mut self, node: NodeId, behavior: Behavior) -> Self {
+        self.behaviors.insert(node, behavior);
+        self
+    }
+
+    /// Fail `node` with `error` on every attempt
+    #[must_use]
+    pub fn failing(self, node: NodeId, error: ExecutionError) -> Self {
+        self.with(node, Behavior::Fail(error))
+    }
+
+    /// Fail `node` with `error` on its first `times` attempts
+    #[must_use]
+    pub fn flaky(self, node: NodeId, times: u32, error: ExecutionError) -> Self {
+        self.with(node, Behavior::Flaky { times, error })
+    }
+
+    /// Let `node` produce `payload`
+    #[must_use]
+    pub fn output(self, node: NodeId, payload: NodePayload) -> Self {
+        self.with(node, Behavior::Output(payload))
+    }
+
+    /// Share as a trait object for [`Executor::with_executor`]
+    #[must_use]
+    pub fn shared(self) -> Arc<Self> {
+        Arc::new(self)
+    }
+
+    /// Nodes executed so far, in call order (retries included)
+    ///
+    /// # Panics
+    /// If a call panicked while recording
+    pub fn calls(&self) -> Vec<NodeId> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// Number of attempts made for `node`
+    ///
+    /// # Panics
+    /// If a call panicked while recording
+    pub fn call_count(&self, node: NodeId) -> usize {
+        self.calls.lock().unwrap().iter().filter(|&&id| id == node).count()
+    }
+}
+
+#[async_trait::async_trait]
+impl NodeExecutor for FakeNodeExecutor {
+    async fn execute_node(
+        &self,
+        node_id: NodeId,
+        _token: &CapabilityToken,
+        _inputs: &NodeInputs,
+    ) -> Result<NodeExecutionResult, ExecutionError> {
+        let attempt = {
+            let mut calls = self.calls.lock().unwrap();
+            let attempt = calls.iter().filter(|&&id| id == node_id).count();
+            calls.push(node_id);
+            attempt
+        };
+
+        let mut behavior = self.behaviors.get(&node_id).unwrap_or(&Behavior::Succeed);
+        while let Behavior::Delay(delay, inner) = behavior {
+            tokio::time::sleep(*delay).await;
+            behavior = inner;
+        }
+
+        let (success, output) = match behavior {
+            Behavior::Succeed | Behavior::Delay(..) => (true, None),
+            Behavior::Output(payload) => (true, Some(payload.clone())),
+            Behavior::Unsuccessful => (false, None),
+            Behavior::Fail(error) => return Err(error.clone()),
+            Behavior::Flaky { times, error } => {
+                if attempt < *times as usize {
+                    return Err(error.clone());
+                }
+                (true, None)
+            }
+        };
+        Ok(NodeExecutionResult {
+            node_id,
+            success,
+            execution_time_ms: 0,
+            resource_consumed: ResourceCaps {
+                cpu_time_ms: 0,
+                memory_bytes: 0,
+                token_limit: 0,
+                iteration_cap: 0,
+            },
+            output,
+        })
+    }
+}
@@ -4,6 +4,13 @@
 
 #![allow(missing_docs)]
 
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary;
+pub mod chaos;
+pub mod conformance;
+pub mod generators;
+pub mod kernel;
+
 use coa_artifact::{Artifact, ArtifactType, ContentHash, DeltaOperation, StructuralDelta, SymbolPath};
 use coa_artifact::__private::Sealed;
 use coa_core::{COAConfig, CreatorOrchestratorAgent};
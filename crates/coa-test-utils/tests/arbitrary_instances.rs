@@ -0,0 +1,39 @@
+//! Arbitrary instances produce values the core types accept
+
+use coa_artifact::{ContentHash, SymbolPath};
+use coa_kernel::construction::GraphBuilder;
+use coa_kernel::types::v2::NodeSpecV2;
+use coa_kernel::types::{DirectiveSet, GraphType};
+use coa_test_utils::arbitrary::Arb;
+use coa_test_utils::kernel::default_signing_key;
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn symbol_paths_round_trip_through_serde(Arb(path) in any::<Arb<SymbolPath>>()) {
+        let json = serde_json::to_string(&path).unwrap();
+        prop_assert_eq!(serde_json::from_str::<SymbolPath>(&json).unwrap(), path);
+    }
+
+    #[test]
+    fn content_hashes_round_trip_through_bytes(Arb(hash) in any::<Arb<ContentHash>>()) {
+        let rebuilt = ContentHash::with_algorithm(hash.algorithm(), hash.into_bytes());
+        prop_assert_eq!(rebuilt, hash);
+    }
+
+    #[test]
+    fn directive_sets_round_trip_through_serde(Arb(set) in any::<Arb<DirectiveSet>>()) {
+        let json = serde_json::to_value(&set).unwrap();
+        let back: DirectiveSet = serde_json::from_value(json).unwrap();
+        prop_assert_eq!(back.directives, set.directives);
+    }
+
+    #[test]
+    fn node_specs_validate_in_sandbox_graphs(Arb(spec) in any::<Arb<NodeSpecV2>>()) {
+        let mut builder = GraphBuilder::new(GraphType::SandboxGraph);
+        builder.add_node(spec);
+        // Generated bounds may exceed system limits; validation must
+        // reject them cleanly rather than panic
+        let _ = builder.validate(&default_signing_key());
+    }
+}
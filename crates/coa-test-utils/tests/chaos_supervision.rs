@@ -0,0 +1,61 @@
+//! Supervisor behaviour under seeded chaos
+
+use coa_core::{ChaosConfig, ChaosFault, COAError, PoolError, UserIntent};
+use coa_test_utils::chaos::run_chaos;
+use coa_test_utils::setup_test_coa;
+use std::time::Duration;
+
+fn moderate(seed: u64) -> ChaosConfig {
+    ChaosConfig::new(seed)
+        .with_kills(0.1)
+        .with_duplicates(0.1)
+        .with_delays(0.2, Duration::from_millis(2))
+}
+
+#[tokio::test]
+async fn supervision_converges_under_moderate_chaos() {
+    for seed in 0..20 {
+        let run = run_chaos(moderate(seed), 5, 30).await;
+        run.assert_converged();
+
+        let kills = run.events.iter().filter(|e| e.fault == ChaosFault::Kill).count();
+        assert_eq!(run.stats.restarts, kills, "seed {seed}");
+    }
+}
+
+#[tokio::test]
+async fn supervision_escalates_when_agents_keep_dying() {
+    let run = run_chaos(ChaosConfig::new(3).with_kills(1.0), 2, 10).await;
+    run.assert_escalated();
+    assert_eq!(run.events.len(), 3);
+    assert_eq!(run.stats.active_count, 0);
+}
+
+#[tokio::test]
+async fn chaos_runs_replay_from_their_seed() {
+    let faults = |events: &[coa_core::ChaosEvent]| -> Vec<_> {
+        events.iter().map(|e| e.fault).collect()
+    };
+    let first = run_chaos(moderate(11), 5, 30).await;
+    let second = run_chaos(moderate(11), 5, 30).await;
+    assert_eq!(faults(&first.events), faults(&second.events));
+    assert_eq!(first.delivered, second.delivered);
+}
+
+#[tokio::test]
+async fn orchestrator_escalates_restart_limit_to_human() {
+    let coa = setup_test_coa().with_chaos(ChaosConfig::new(5).with_kills(1.0));
+
+    let error = coa
+        .execute_intent(UserIntent::new("Create a hello world function"))
+        .await
+        .unwrap_err();
+    match error {
+        COAError::RequiresHumanIntervention { error, .. } => assert!(matches!(
+            *error,
+            COAError::PoolError(PoolError::RestartLimitExceeded { .. })
+        )),
+        other => panic!("expected escalation, got {other}"),
+    }
+    assert!(!coa.chaos_events().is_empty());
+}
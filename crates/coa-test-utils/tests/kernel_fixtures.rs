@@ -0,0 +1,72 @@
+//! Kernel fixtures drive a real executor
+
+use coa_kernel::error::ExecutionError;
+use coa_kernel::executor::NodePayload;
+use coa_kernel::types::v2::NodeRunStatus;
+use coa_test_utils::kernel::{
+    default_signing_key, signed_diamond_graph, signed_linear_graph, Behavior, FakeNodeExecutor,
+    KeyFactory,
+};
+use std::time::Duration;
+
+#[test]
+fn keys_are_deterministic_and_distinct() {
+    assert_eq!(default_signing_key().to_bytes(), default_signing_key().to_bytes());
+    let factory = KeyFactory::new();
+    let (a, b) = (factory.next_key(), factory.next_key());
+    assert_ne!(a.to_bytes(), b.to_bytes());
+    assert_ne!(a.to_bytes(), default_signing_key().to_bytes());
+    assert_eq!(KeyFactory::new().next_key().to_bytes(), a.to_bytes());
+}
+
+#[tokio::test]
+async fn linear_graph_runs_in_chain_order() {
+    let fixture = signed_linear_graph(4);
+    let fake = FakeNodeExecutor::new().shared();
+    let summary = fixture.executor(fake.clone()).run(fixture.graph.clone()).await.unwrap();
+
+    assert_eq!(summary.nodes_executed, 4);
+    assert_eq!(fake.calls(), fixture.nodes);
+}
+
+#[tokio::test]
+async fn diamond_graph_retries_flaky_nodes_and_stops_at_failures() {
+    let fixture = signed_diamond_graph();
+    let [top, left, right, bottom] = fixture.nodes[..] else { unreachable!() };
+
+    let fake = FakeNodeExecutor::new()
+        .flaky(left, 1, ExecutionError::Timeout)
+        .with(
+            right,
+            Behavior::Delay(
+                Duration::from_millis(1),
+                Box::new(Behavior::Output(NodePayload::new("text", "done".into()))),
+            ),
+        )
+        .shared();
+    let summary = fixture
+        .executor(fake.clone())
+        .with_retries(1)
+        .run(fixture.graph.clone())
+        .await
+        .unwrap();
+    assert_eq!(summary.nodes_executed, 4);
+    assert_eq!(fake.call_count(left), 2);
+    assert_eq!(fake.calls().first(), Some(&top));
+    assert_eq!(fake.calls().last(), Some(&bottom));
+
+    // A graph runs once; fail a fresh one
+    let fixture = signed_diamond_graph();
+    let [_, left, _, bottom] = fixture.nodes[..] else { unreachable!() };
+    let failing = FakeNodeExecutor::new()
+        .failing(left, ExecutionError::ScopeViolation)
+        .shared();
+    let failure = fixture
+        .executor(failing.clone())
+        .run(fixture.graph.clone())
+        .await
+        .unwrap_err();
+    assert_eq!(failure.error, ExecutionError::ScopeViolation);
+    assert_eq!(failure.summary.node(left).unwrap().status, NodeRunStatus::Failed);
+    assert_eq!(failing.call_count(bottom), 0);
+}
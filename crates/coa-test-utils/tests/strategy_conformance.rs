@@ -0,0 +1,89 @@
+//! Built-in strategies pass the composition conformance suite
+
+use coa_artifact::{Artifact, ArtifactType, StructuralDelta};
+use coa_composition::{
+    CommutativeBatchStrategy, CompositionError, CompositionStrategy, Granularity,
+    HybridCompositionStrategy, OrderedCompositionStrategy, Parallelism, SingleWriterStrategy,
+    Validation,
+};
+use coa_symbol::SymbolRefIndex;
+use coa_test_utils::conformance::{
+    assert_conformance, assert_conformance_with_classifier, check_empty_batch, run_conformance,
+    Property,
+};
+use coa_test_utils::create_test_code_artifact;
+
+const CASES: u32 = 128;
+
+#[test]
+fn single_writer_conforms() {
+    assert_conformance(&SingleWriterStrategy::new(), CASES);
+}
+
+#[test]
+fn commutative_batch_conforms() {
+    assert_conformance(&CommutativeBatchStrategy::new(), CASES);
+}
+
+#[test]
+fn ordered_conforms() {
+    assert_conformance(&OrderedCompositionStrategy::new(), CASES);
+}
+
+#[test]
+fn hybrid_conforms_with_its_classifier() {
+    assert_conformance_with_classifier(&HybridCompositionStrategy::new(), &(), CASES);
+}
+
+/// Strategy that rejects empty batches, to show the suite catches it
+#[derive(Debug)]
+struct RejectsEmpty(SingleWriterStrategy);
+
+impl CompositionStrategy for RejectsEmpty {
+    fn validate<T: ArtifactType>(
+        &self,
+        deltas: &[StructuralDelta<T>],
+        index: &SymbolRefIndex,
+    ) -> Result<Validation, CompositionError> {
+        self.0.validate(deltas, index)
+    }
+
+    fn compose<T: ArtifactType>(
+        &self,
+        base: &Artifact<T>,
+        deltas: &[StructuralDelta<T>],
+    ) -> Result<Artifact<T>, CompositionError> {
+        if deltas.is_empty() {
+            return Err(CompositionError::CompositionFailed(
+                "nothing to compose".into(),
+            ));
+        }
+        self.0.compose(base, deltas)
+    }
+
+    fn parallelism(&self) -> Parallelism {
+        self.0.parallelism()
+    }
+
+    fn granularity(&self) -> Granularity {
+        self.0.granularity()
+    }
+
+    fn name(&self) -> &'static str {
+        "rejects-empty"
+    }
+}
+
+#[test]
+fn suite_reports_violations() {
+    let strategy = RejectsEmpty(SingleWriterStrategy::new());
+    let violation = check_empty_batch(
+        &strategy,
+        &create_test_code_artifact(),
+        &SymbolRefIndex::new(),
+    )
+    .unwrap_err();
+    assert_eq!(violation.property, Property::EmptyBatch);
+    assert_eq!(violation.strategy, "rejects-empty");
+    assert_eq!(run_conformance::<_, ()>(&strategy, None, 8), Err(violation));
+}
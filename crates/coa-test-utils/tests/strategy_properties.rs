@@ -0,0 +1,99 @@
+//! Strategy invariants over generated delta batches
+
+use coa_artifact::{Artifact, DeltaOperation, StructuralDelta};
+use coa_composition::{
+    CommutativeBatchStrategy, CommutativeClassifier, CompositionStrategy,
+    HybridCompositionStrategy, OrderedCompositionStrategy, SingleWriterStrategy,
+};
+use coa_symbol::SymbolRefIndex;
+use coa_test_utils::generators::{commutative_batch, delta_batch, mixed_batch};
+use coa_test_utils::{create_test_code_artifact_with_source, TestCodeArtifact};
+use proptest::prelude::*;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+
+/// Apply Add/Remove deltas in sequence to a flat symbol table
+///
+/// The built-in strategies only validate; this is the reference
+/// application the commutative properties compose through.
+fn apply_in_sequence(deltas: &[StructuralDelta<TestCodeArtifact>]) -> Artifact<TestCodeArtifact> {
+    let mut symbols = BTreeMap::new();
+    for delta in deltas {
+        let target = delta.target().to_string();
+        match delta.operation() {
+            DeltaOperation::Add(content) => {
+                symbols.insert(target, content.source.clone());
+            }
+            DeltaOperation::Remove => {
+                symbols.remove(&target);
+            }
+            other => panic!("non-commutative operation in batch: {other:?}"),
+        }
+    }
+    let mut source = String::new();
+    for (target, body) in &symbols {
+        let _ = writeln!(source, "{target}={body}");
+    }
+    create_test_code_artifact_with_source(&source)
+}
+
+proptest! {
+    #[test]
+    fn prop_single_writer_never_accepts_overlapping_batches(batch in delta_batch(6)) {
+        let index = SymbolRefIndex::new();
+        let overlapping = batch.iter().enumerate().any(|(i, a)| {
+            batch[i + 1..].iter().any(|b| a.target().overlaps(b.target()))
+        });
+
+        let accepted = SingleWriterStrategy::new().validate(&batch, &index).is_ok();
+        prop_assert_eq!(accepted, !overlapping);
+    }
+
+    #[test]
+    fn prop_commutative_compose_is_order_independent(
+        (batch, shuffled) in commutative_batch(6)
+            .prop_flat_map(|batch| (Just(batch.clone()), Just(batch).prop_shuffle()))
+    ) {
+        let index = SymbolRefIndex::new();
+        let strategy = CommutativeBatchStrategy::new();
+        let accepted = strategy.validate(&batch, &index).is_ok();
+        prop_assert_eq!(accepted, strategy.validate(&shuffled, &index).is_ok());
+
+        let mut targets = HashSet::new();
+        let distinct = batch.iter().all(|d| targets.insert(d.target().to_string()));
+        prop_assert_eq!(accepted, distinct);
+
+        if accepted {
+            prop_assert_eq!(
+                *apply_in_sequence(&batch).hash(),
+                *apply_in_sequence(&shuffled).hash()
+            );
+        }
+    }
+
+    #[test]
+    fn prop_commutative_validation_ignores_parallel_threshold(batch in delta_batch(8)) {
+        let index = SymbolRefIndex::new();
+        let sequential = CommutativeBatchStrategy::new().with_parallel_threshold(usize::MAX);
+        let parallel = CommutativeBatchStrategy::new().with_parallel_threshold(0);
+
+        prop_assert_eq!(
+            sequential.validate(&batch, &index).map(|_| ()).map_err(|e| e.to_string()),
+            parallel.validate(&batch, &index).map(|_| ()).map_err(|e| e.to_string())
+        );
+    }
+
+    #[test]
+    fn prop_hybrid_equals_composition_of_partitions(batch in mixed_batch(6)) {
+        let index = SymbolRefIndex::new();
+        let (commutative, ordered): (Vec<_>, Vec<_>) = batch
+            .iter()
+            .cloned()
+            .partition(CommutativeClassifier::is_commutative);
+
+        let hybrid = HybridCompositionStrategy::new().validate(&batch, &index).is_ok();
+        let partitions = CommutativeBatchStrategy::new().validate(&commutative, &index).is_ok()
+            && OrderedCompositionStrategy::new().validate(&ordered, &index).is_ok();
+        prop_assert_eq!(hybrid, partitions);
+    }
+}